@@ -89,10 +89,50 @@ fn main() {
             panic!("Failed to assemble breakpoint entry");
         }
 
+        // Assemble debug exception entry code
+        let status = Command::new("nasm")
+            .args(&[
+                "-f",
+                "elf64",
+                "-o",
+                &format!("{}/debug_entry.o", out_dir),
+                kernel_dir
+                    .join("src/interrupts/debug_entry.asm")
+                    .to_str()
+                    .unwrap(),
+            ])
+            .status()
+            .expect("Failed to run nasm");
+
+        if !status.success() {
+            panic!("Failed to assemble debug entry");
+        }
+
+        // Assemble page fault entry code
+        let status = Command::new("nasm")
+            .args(&[
+                "-f",
+                "elf64",
+                "-o",
+                &format!("{}/page_fault_entry.o", out_dir),
+                kernel_dir
+                    .join("src/interrupts/page_fault_entry.asm")
+                    .to_str()
+                    .unwrap(),
+            ])
+            .status()
+            .expect("Failed to run nasm");
+
+        if !status.success() {
+            panic!("Failed to assemble page fault entry");
+        }
+
         // Tell cargo to link the assembled object files
         println!("cargo:rustc-link-arg={}/syscall_entry.o", out_dir);
         println!("cargo:rustc-link-arg={}/timer_entry.o", out_dir);
         println!("cargo:rustc-link-arg={}/breakpoint_entry.o", out_dir);
+        println!("cargo:rustc-link-arg={}/debug_entry.o", out_dir);
+        println!("cargo:rustc-link-arg={}/page_fault_entry.o", out_dir);
     }
 
     // Use our custom linker script for x86_64
@@ -146,6 +186,8 @@ fn main() {
     println!("cargo:rerun-if-changed=src/syscall/entry.asm");
     println!("cargo:rerun-if-changed=src/interrupts/timer_entry.asm");
     println!("cargo:rerun-if-changed=src/interrupts/breakpoint_entry.asm");
+    println!("cargo:rerun-if-changed=src/interrupts/debug_entry.asm");
+    println!("cargo:rerun-if-changed=src/interrupts/page_fault_entry.asm");
     println!("cargo:rerun-if-changed=linker.ld");
     println!("cargo:rerun-if-changed=src/arch_impl/aarch64/linker.ld");
 
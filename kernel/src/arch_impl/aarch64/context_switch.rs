@@ -3027,6 +3027,12 @@ fn restore_userspace_context_inline(
     frame.elr = thread.context.elr_el1;
     frame.spsr = dispatch_spsr(thread.context.spsr_el1) & !SPSR_MODE_MASK;
 
+    // A traced thread resumed with PTRACE_SINGLESTEP carries SPSR.SS; arm
+    // this CPU's software step to match
+    if crate::process::ptrace::active() {
+        super::exception::sync_software_step(frame.spsr);
+    }
+
     // Restore SP_EL0 (user stack pointer)
     unsafe {
         core::arch::asm!(
@@ -5375,8 +5381,11 @@ fn emit_el0_entry_marker() {
 ///
 /// NOTE: This function acquires its own locks (SCHEDULER for current_thread_id,
 /// PROCESS_MANAGER for signal delivery). It is called AFTER the consolidated
-/// context switch lock is released.
-fn check_and_deliver_signals_for_current_thread_arm64(frame: &mut Aarch64ExceptionFrame) {
+/// context switch lock is released. EL0 data aborts also call it to enter a
+/// SIGSEGV handler directly, since the sync exception return path does not.
+pub(crate) fn check_and_deliver_signals_for_current_thread_arm64(
+    frame: &mut Aarch64ExceptionFrame,
+) {
     // Get current thread ID
     let current_thread_id = match crate::task::scheduler::current_thread_id() {
         Some(id) => id,
@@ -5397,6 +5406,8 @@ fn check_and_deliver_signals_for_current_thread_arm64(frame: &mut Aarch64Excepti
     // Track if signal termination happened (for parent notification after borrow ends)
     let mut signal_termination_info: Option<crate::signal::delivery::ParentNotification> = None;
     let mut terminated_child_pid: Option<u64> = None;
+    // Same for a job-control stop
+    let mut job_notification: Option<crate::process::job_control::JobNotification> = None;
 
     if let Some(ref mut manager) = *manager_guard {
         // Find the process for this thread
@@ -5405,7 +5416,7 @@ fn check_and_deliver_signals_for_current_thread_arm64(frame: &mut Aarch64Excepti
             crate::signal::delivery::check_and_fire_alarm(process);
             crate::signal::delivery::check_and_fire_itimer_real(process, 5000);
 
-            if crate::signal::delivery::has_deliverable_signals(process) {
+            if crate::signal::delivery::has_return_work(process) {
                 // Read current SP_EL0 (user stack pointer)
                 let sp_el0: u64;
                 unsafe {
@@ -5469,6 +5480,30 @@ fn check_and_deliver_signals_for_current_thread_arm64(frame: &mut Aarch64Excepti
                             crate::task::scheduler::switch_to_idle();
                         }
                     }
+                    crate::signal::delivery::SignalDeliveryResult::Stopped => {
+                        // Stopped for the ptrace tracer: park the thread with its
+                        // user registers saved; ptrace::resume unblocks it
+                        crate::task::scheduler::with_thread_mut(current_thread_id, |thread| {
+                            save_userspace_context_inline(thread, frame);
+                            thread.set_blocked();
+                        });
+                        crate::task::scheduler::set_need_resched();
+                        setup_idle_return_arm64(frame);
+                        crate::task::scheduler::switch_to_idle();
+                    }
+                    crate::signal::delivery::SignalDeliveryResult::JobStopped(notification) => {
+                        // Stopped for job control: park the thread the same way;
+                        // job_control::finish_continue unblocks it on SIGCONT
+                        crate::process::job_control::park(process, current_thread_id);
+                        crate::task::scheduler::with_thread_mut(current_thread_id, |thread| {
+                            save_userspace_context_inline(thread, frame);
+                            thread.set_blocked();
+                        });
+                        crate::task::scheduler::set_need_resched();
+                        setup_idle_return_arm64(frame);
+                        crate::task::scheduler::switch_to_idle();
+                        job_notification = notification;
+                    }
                     crate::signal::delivery::SignalDeliveryResult::NoAction => {}
                 }
             }
@@ -5481,6 +5516,9 @@ fn check_and_deliver_signals_for_current_thread_arm64(frame: &mut Aarch64Excepti
         if let Some(notification) = signal_termination_info {
             crate::signal::delivery::notify_parent_of_termination_deferred(&notification);
         }
+        if let Some(notification) = job_notification {
+            crate::process::job_control::notify_parent_deferred(&notification);
+        }
 
         // Clean up window buffers so compositor stops reading freed pages
         if let Some(pid) = terminated_child_pid {
@@ -459,6 +459,32 @@ fn resolve_el0_fault_victim(page_table_phys: u64) -> Option<(crate::process::Pro
     }
 }
 
+/// Queue SIGSEGV for an EL0 data abort if the faulting process handles it.
+///
+/// Returns true when the fault was handed to a user handler, in which case
+/// the process keeps running instead of being terminated, or when delivery
+/// will dump core with the faulting registers.
+fn queue_el0_fault_signal(page_table_phys: u64, far: u64, dfsc: u16) -> bool {
+    use crate::signal::constants::{SEGV_ACCERR, SEGV_MAPERR, SIGSEGV};
+
+    // DFSC 0x00-0x07 are address size and translation faults (nothing
+    // mapped); access flag and permission faults mean the page exists.
+    let code = if dfsc < 0x08 {
+        SEGV_MAPERR
+    } else {
+        SEGV_ACCERR
+    };
+    let info = crate::signal::types::SigInfo::fault(SIGSEGV, code, far);
+    crate::process::with_process_manager(|pm| {
+        pm.find_process_by_cr3_mut(page_table_phys)
+            .is_some_and(|(_, process)| {
+                !process.is_terminated()
+                    && crate::signal::delivery::queue_fault_signal(process, info)
+            })
+    })
+    .unwrap_or(false)
+}
+
 #[cold]
 #[inline(never)]
 fn dump_fatal_postmortem_section<F>(cpu_id: usize, section: usize, heading: &str, dump: F)
@@ -592,6 +618,36 @@ mod exception_class {
     pub const BRK_AARCH64: u32 = 0b111100; // BRK instruction
 }
 
+/// SPSR_EL1 software step bit
+const SPSR_SS: u64 = 1 << 21;
+
+/// Arm or disarm EL0 software step on this CPU to match a user SPSR
+///
+/// Used only while some process is traced. MDSCR_EL1.SS is per CPU, so it is
+/// set from the SPSR of whichever thread is about to return to EL0; the step
+/// then traps after one instruction, raising a software step exception.
+pub(crate) fn sync_software_step(spsr: u64) {
+    let mut mdscr: u64;
+    unsafe {
+        core::arch::asm!("mrs {}, mdscr_el1", out(reg) mdscr, options(nomem, nostack));
+    }
+    let enable = (spsr & SPSR_SS) >> 21;
+    if mdscr & 1 == enable {
+        return;
+    }
+    mdscr = (mdscr & !1) | enable;
+    unsafe {
+        // Release the OS lock, which otherwise masks debug exceptions
+        core::arch::asm!(
+            "msr oslar_el1, xzr",
+            "msr mdscr_el1, {}",
+            "isb",
+            in(reg) mdscr,
+            options(nostack)
+        );
+    }
+}
+
 /// Handle synchronous exceptions (syscalls, page faults, etc.)
 ///
 /// Called from assembly with:
@@ -664,6 +720,7 @@ pub extern "C" fn handle_sync_exception(frame: *mut Aarch64ExceptionFrame, esr:
             // Try to handle as CoW fault first
             if handle_cow_fault_arm64(far, iss) {
                 // CoW fault handled successfully, return to userspace
+                crate::task::accounting::record_minor_fault();
                 return;
             }
 
@@ -792,6 +849,16 @@ pub extern "C" fn handle_sync_exception(frame: *mut Aarch64ExceptionFrame, esr:
             }
 
             if from_el0 {
+                // Hand the fault to the process's SIGSEGV handler if it has
+                // one. This frame holds the full user register state, so the
+                // handler frame can be pushed right away and entered by ERET.
+                if queue_el0_fault_signal(ttbr0 & !0xFFFF_0000_0000_0FFF, far, dfsc) {
+                    super::context_switch::check_and_deliver_signals_for_current_thread_arm64(
+                        frame_ref,
+                    );
+                    return;
+                }
+
                 crate::trace_count!(crate::tracing::providers::teardown::TEARDOWN_ENTRY_FAULT);
                 // Page table walk diagnostic: dump L0-L3 entries for the fault VA
                 // to understand why the mapping is missing or has wrong permissions.
@@ -1313,6 +1380,22 @@ pub extern "C" fn handle_sync_exception(frame: *mut Aarch64ExceptionFrame, esr:
             crate::task::scheduler::switch_to_idle_best_effort();
         }
 
+        exception_class::SOFTWARE_STEP_LOWER => {
+            // One EL0 instruction ran under PTRACE_SINGLESTEP. Disarm the step
+            // and stop the tracee with SIGTRAP; a step nobody asked for (a
+            // stale MDSCR_EL1.SS after a context switch) is dropped.
+            let frame = unsafe { &mut *frame };
+            sync_software_step(0);
+            frame.spsr &= !SPSR_SS;
+            if let Some(thread_id) = crate::task::scheduler::current_thread_id() {
+                if crate::process::ptrace::step_trap(thread_id, frame.elr) {
+                    super::context_switch::check_and_deliver_signals_for_current_thread_arm64(
+                        frame,
+                    );
+                }
+            }
+        }
+
         exception_class::BRK_AARCH64 => {
             let frame = unsafe { &mut *frame };
             let imm = iss & 0xFFFF;
@@ -97,7 +97,14 @@ pub extern "C" fn rust_syscall_handler_aarch64(frame: &mut Aarch64ExceptionFrame
         emit_el0_syscall_marker();
     }
 
-    let syscall_num = frame.syscall_number();
+    // Time from here until the return to userspace is system time
+    crate::task::accounting::syscall_entry();
+
+    let mut syscall_num = frame.syscall_number();
+    // A traced process may stop at syscall entry; its tracer can change the call
+    if crate::process::ptrace::active() {
+        syscall_num = crate::syscall::ptrace::syscall_entry_aarch64(frame);
+    }
     trace_entry(syscall_num);
 
     let arg1 = frame.arg1();
@@ -126,6 +133,7 @@ pub extern "C" fn rust_syscall_handler_aarch64(frame: &mut Aarch64ExceptionFrame
             match crate::syscall::signal::sys_sigreturn_with_frame_aarch64(frame) {
                 crate::syscall::SyscallResult::Ok(_) => {
                     check_and_deliver_signals_aarch64(frame);
+                    crate::task::accounting::syscall_exit();
                     Aarch64PerCpu::preempt_enable();
                     return;
                 }
@@ -171,12 +179,19 @@ pub extern "C" fn rust_syscall_handler_aarch64(frame: &mut Aarch64ExceptionFrame
     trace_exit(result as i64);
     frame.set_return_value(result);
 
+    // A traced process may stop at syscall exit and for pending signals first
+    if crate::process::ptrace::active() {
+        crate::syscall::ptrace::syscall_exit_aarch64(frame);
+    }
+
     // Check for pending signals before returning to userspace
     check_and_deliver_signals_aarch64(frame);
 
     // Trace: about to return from syscall handler to assembly (will ERET)
     super::trace::trace_exec(b'D');
 
+    crate::task::accounting::syscall_exit();
+
     // Decrement preempt count on syscall exit
     Aarch64PerCpu::preempt_enable();
 }
@@ -220,6 +235,9 @@ fn check_and_deliver_signals_aarch64(frame: &mut Aarch64ExceptionFrame) {
     // Track if signal termination happened (for parent notification after lock release)
     let mut signal_termination_info: Option<crate::signal::delivery::ParentNotification> = None;
     let mut terminated_child_pid: Option<u64> = None;
+    let mut ptrace_stopped = false;
+    let mut job_stopped = false;
+    let mut job_notification: Option<crate::process::job_control::JobNotification> = None;
 
     if let Some(ref mut manager) = *manager_guard {
         // Find the process for this thread
@@ -228,8 +246,8 @@ fn check_and_deliver_signals_aarch64(frame: &mut Aarch64ExceptionFrame) {
             crate::signal::delivery::check_and_fire_alarm(process);
             crate::signal::delivery::check_and_fire_itimer_real(process, 5000);
 
-            // Check if there are any deliverable signals
-            if !crate::signal::delivery::has_deliverable_signals(process) {
+            // Check if there are any deliverable signals or a job-control stop
+            if !crate::signal::delivery::has_return_work(process) {
                 return;
             }
 
@@ -273,15 +291,25 @@ fn check_and_deliver_signals_aarch64(frame: &mut Aarch64ExceptionFrame) {
                 }
             }
 
-            // Handle termination
-            if let crate::signal::delivery::SignalDeliveryResult::Terminated(notification) =
-                signal_result
-            {
-                // Process was terminated by signal - switch to idle
-                crate::task::scheduler::set_need_resched();
-                terminated_child_pid = Some(notification.child_pid.as_u64());
-                // Save notification to notify parent after releasing lock
-                signal_termination_info = Some(notification);
+            match signal_result {
+                crate::signal::delivery::SignalDeliveryResult::Terminated(notification) => {
+                    // Process was terminated by signal - switch to idle
+                    crate::task::scheduler::set_need_resched();
+                    terminated_child_pid = Some(notification.child_pid.as_u64());
+                    // Save notification to notify parent after releasing lock
+                    signal_termination_info = Some(notification);
+                }
+                crate::signal::delivery::SignalDeliveryResult::Stopped => {
+                    // Stopped for a tracer: wait inside the syscall instead of parking
+                    crate::process::ptrace::keep_stop_in_syscall(process);
+                    ptrace_stopped = true;
+                }
+                crate::signal::delivery::SignalDeliveryResult::JobStopped(notification) => {
+                    // Stopped for job control: wait for SIGCONT inside the syscall
+                    job_stopped = true;
+                    job_notification = notification;
+                }
+                _ => {}
             }
         }
     }
@@ -293,11 +321,21 @@ fn check_and_deliver_signals_aarch64(frame: &mut Aarch64ExceptionFrame) {
     if let Some(notification) = signal_termination_info {
         crate::signal::delivery::notify_parent_of_termination_deferred(&notification);
     }
+    if let Some(notification) = job_notification {
+        crate::process::job_control::notify_parent_deferred(&notification);
+    }
 
     // Clean up window buffers so compositor stops reading freed pages
     if let Some(pid) = terminated_child_pid {
         crate::syscall::graphics::cleanup_windows_for_pid(pid);
     }
+
+    if ptrace_stopped {
+        crate::syscall::ptrace::wait_signal_stop_aarch64(frame);
+    }
+    if job_stopped {
+        crate::syscall::signal::wait_while_stopped(current_thread_id);
+    }
 }
 
 // =============================================================================
@@ -489,6 +527,23 @@ fn dispatch_syscall_enum(
         SyscallNumber::Sigaltstack => {
             result_to_u64(crate::syscall::signal::sys_sigaltstack(arg1, arg2))
         }
+        SyscallNumber::Tkill => {
+            result_to_u64(crate::syscall::signal::sys_tkill(arg1 as i64, arg2 as i32))
+        }
+        SyscallNumber::Tgkill => result_to_u64(crate::syscall::signal::sys_tgkill(
+            arg1 as i64,
+            arg2 as i64,
+            arg3 as i32,
+        )),
+        SyscallNumber::RtSigqueueinfo => result_to_u64(
+            crate::syscall::signal::sys_rt_sigqueueinfo(arg1 as i64, arg2 as i32, arg3),
+        ),
+        SyscallNumber::RtSigtimedwait => result_to_u64(
+            crate::syscall::signal::sys_rt_sigtimedwait(arg1, arg2, arg3, arg4),
+        ),
+        SyscallNumber::Ptrace => {
+            result_to_u64(crate::syscall::ptrace::sys_ptrace(arg1, arg2, arg3, arg4))
+        }
         SyscallNumber::Alarm => result_to_u64(crate::syscall::signal::sys_alarm(arg1)),
         SyscallNumber::Getitimer => {
             result_to_u64(crate::syscall::signal::sys_getitimer(arg1 as i32, arg2))
@@ -508,6 +563,14 @@ fn dispatch_syscall_enum(
             arg1 as i64,
             arg2,
             arg3 as u32,
+            arg4,
+        )),
+        SyscallNumber::Waitid => result_to_u64(crate::syscall::waitid::sys_waitid(
+            arg1 as u32,
+            arg2,
+            arg3,
+            arg4 as u32,
+            arg5,
         )),
         SyscallNumber::Yield => {
             crate::task::scheduler::yield_current();
@@ -726,6 +789,44 @@ fn dispatch_syscall_enum(
         SyscallNumber::Prlimit64 => result_to_u64(crate::syscall::handlers::sys_prlimit64(
             arg1, arg2, arg3, arg4,
         )),
+        SyscallNumber::Getrusage => {
+            result_to_u64(crate::syscall::rusage::sys_getrusage(arg1 as i32, arg2))
+        }
+        SyscallNumber::Times => result_to_u64(crate::syscall::rusage::sys_times(arg1)),
+        // Scheduling priorities
+        SyscallNumber::Getpriority => {
+            result_to_u64(crate::syscall::sched::sys_getpriority(arg1 as i32, arg2))
+        }
+        SyscallNumber::Setpriority => result_to_u64(crate::syscall::sched::sys_setpriority(
+            arg1 as i32,
+            arg2,
+            arg3 as i32,
+        )),
+        SyscallNumber::SchedSetparam => {
+            result_to_u64(crate::syscall::sched::sys_sched_setparam(arg1 as i64, arg2))
+        }
+        SyscallNumber::SchedGetparam => {
+            result_to_u64(crate::syscall::sched::sys_sched_getparam(arg1 as i64, arg2))
+        }
+        SyscallNumber::SchedSetscheduler => result_to_u64(
+            crate::syscall::sched::sys_sched_setscheduler(arg1 as i64, arg2 as u32, arg3),
+        ),
+        SyscallNumber::SchedGetscheduler => {
+            result_to_u64(crate::syscall::sched::sys_sched_getscheduler(arg1 as i64))
+        }
+        SyscallNumber::SchedGetPriorityMax => result_to_u64(
+            crate::syscall::sched::sys_sched_get_priority_max(arg1 as u32),
+        ),
+        SyscallNumber::SchedGetPriorityMin => result_to_u64(
+            crate::syscall::sched::sys_sched_get_priority_min(arg1 as u32),
+        ),
+        SyscallNumber::SchedSetaffinity => result_to_u64(
+            crate::syscall::sched::sys_sched_setaffinity(arg1 as i64, arg2, arg3),
+        ),
+        SyscallNumber::SchedGetaffinity => result_to_u64(
+            crate::syscall::sched::sys_sched_getaffinity(arg1 as i64, arg2, arg3),
+        ),
+        SyscallNumber::Getcpu => result_to_u64(crate::syscall::sched::sys_getcpu(arg1, arg2)),
         SyscallNumber::Uname => result_to_u64(crate::syscall::handlers::sys_uname(arg1)),
         // epoll
         SyscallNumber::EpollCreate1 => {
@@ -782,6 +883,46 @@ fn dispatch_syscall_enum(
             arg3,
             arg4 as i64,
         )),
+        // File size and durability
+        SyscallNumber::Truncate => {
+            result_to_u64(crate::syscall::fs::sys_truncate(arg1, arg2 as i64))
+        }
+        SyscallNumber::Ftruncate => {
+            result_to_u64(crate::syscall::fs::sys_ftruncate(arg1 as i32, arg2 as i64))
+        }
+        SyscallNumber::Fallocate => result_to_u64(crate::syscall::fs::sys_fallocate(
+            arg1 as i32,
+            arg2 as i32,
+            arg3 as i64,
+            arg4 as i64,
+        )),
+        SyscallNumber::Fsync => result_to_u64(crate::syscall::fs::sys_fsync(arg1 as i32)),
+        SyscallNumber::Fdatasync => result_to_u64(crate::syscall::fs::sys_fdatasync(arg1 as i32)),
+        SyscallNumber::Statfs => result_to_u64(crate::syscall::fs::sys_statfs(arg1, arg2)),
+        SyscallNumber::Fstatfs => result_to_u64(crate::syscall::fs::sys_fstatfs(arg1 as i32, arg2)),
+        SyscallNumber::Flock => {
+            result_to_u64(crate::syscall::fs::sys_flock(arg1 as i32, arg2 as i32))
+        }
+        SyscallNumber::Sendfile => result_to_u64(crate::syscall::splice::sys_sendfile(
+            arg1 as i32,
+            arg2 as i32,
+            arg3,
+            arg4,
+        )),
+        SyscallNumber::Splice => result_to_u64(crate::syscall::splice::sys_splice(
+            arg1 as i32,
+            arg2,
+            arg3 as i32,
+            arg4,
+            arg5,
+            arg6 as u32,
+        )),
+        SyscallNumber::Tee => result_to_u64(crate::syscall::splice::sys_tee(
+            arg1 as i32,
+            arg2 as i32,
+            arg3,
+            arg4 as u32,
+        )),
         // Process spawning (no fork — avoids MAP_SHARED page corruption)
         SyscallNumber::Spawn => sys_spawn_aarch64(arg1, arg2),
     }
@@ -1394,15 +1535,11 @@ fn load_elf_from_ext2_inner(
     fs: &crate::fs::ext2::Ext2Fs,
     path: &str,
 ) -> Result<alloc::vec::Vec<u8>, i32> {
-    use crate::syscall::errno::{EACCES, EIO, ENOENT, ENOTDIR};
+    use crate::syscall::errno::{EACCES, EIO, ENOTDIR};
 
     let inode_num = fs.resolve_path(path).map_err(|e| {
         super::trace::trace_exec(b'!');
-        if e.contains("not found") {
-            ENOENT
-        } else {
-            EIO
-        }
+        e.to_errno()
     })?;
     super::trace::trace_exec(b'4');
 
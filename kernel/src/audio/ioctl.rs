@@ -0,0 +1,185 @@
+//! `/dev/dsp` ioctl request codes and handlers
+//!
+//! The request codes and argument layouts are the OSS ones, so programs
+//! written against `<sys/soundcard.h>` negotiate formats the same way here:
+//! - SNDCTL_DSP_SPEED/CHANNELS/STEREO/SETFMT: set a parameter, reading back
+//!   the value actually in effect
+//! - SNDCTL_DSP_GETOSPACE/GETODELAY/GETBLKSIZE: buffer space and latency
+//! - SNDCTL_DSP_GETISPACE: captured audio waiting to be read
+//! - SNDCTL_DSP_GETPLAYVOL/SETPLAYVOL: per-stream volume
+//! - SNDCTL_DSP_RESET/SYNC: discard queued and captured audio, or wait out
+//!   queued audio
+
+use super::stream::{AFMT_S16_LE, AFMT_U8, BUFFER_FRAMES, FRAGMENT_FRAMES};
+use super::SharedStream;
+use crate::syscall::errno::{EFAULT, EINVAL, ENOTTY};
+use crate::syscall::userptr::{copy_from_user, copy_to_user};
+
+/// Discard queued and captured audio
+pub const SNDCTL_DSP_RESET: u64 = 0x5000;
+/// Wait until queued audio has played
+pub const SNDCTL_DSP_SYNC: u64 = 0x5001;
+/// Set the sample rate (int, read back)
+pub const SNDCTL_DSP_SPEED: u64 = 0xC004_5002;
+/// Select mono (0) or stereo (1) (int, read back)
+pub const SNDCTL_DSP_STEREO: u64 = 0xC004_5003;
+/// Get the fragment size in bytes (int)
+pub const SNDCTL_DSP_GETBLKSIZE: u64 = 0xC004_5004;
+/// Set the sample format, or query it with AFMT_QUERY (int, read back)
+pub const SNDCTL_DSP_SETFMT: u64 = 0xC004_5005;
+/// Set the channel count (int, read back)
+pub const SNDCTL_DSP_CHANNELS: u64 = 0xC004_5006;
+/// Get the mask of supported formats (int)
+pub const SNDCTL_DSP_GETFMTS: u64 = 0x8004_500B;
+/// Get output buffer space (struct audio_buf_info)
+pub const SNDCTL_DSP_GETOSPACE: u64 = 0x8010_500C;
+/// Get captured audio waiting to be read (struct audio_buf_info)
+pub const SNDCTL_DSP_GETISPACE: u64 = 0x8010_500D;
+/// Get the bytes queued but not yet mixed (int)
+pub const SNDCTL_DSP_GETODELAY: u64 = 0x8004_5017;
+/// Get the volume, left | right << 8 (int)
+pub const SNDCTL_DSP_GETPLAYVOL: u64 = 0x8004_5018;
+/// Set the volume, left | right << 8, each 0-100 (int, read back)
+pub const SNDCTL_DSP_SETPLAYVOL: u64 = 0xC004_5018;
+
+/// SETFMT argument that reads the current format without changing it
+pub const AFMT_QUERY: u32 = 0;
+
+/// Buffer state reported by SNDCTL_DSP_GETOSPACE and SNDCTL_DSP_GETISPACE
+/// (OSS `audio_buf_info`)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AudioBufInfo {
+    /// Whole fragments that can be written (or read) without blocking
+    pub fragments: i32,
+    /// Fragments in the whole buffer
+    pub fragstotal: i32,
+    /// Fragment size in bytes
+    pub fragsize: i32,
+    /// Bytes that can be written (or read) without blocking
+    pub bytes: i32,
+}
+
+fn read_int(arg: u64) -> Result<u32, i32> {
+    copy_from_user(arg as *const u32).map_err(|_| EFAULT)
+}
+
+fn write_int(arg: u64, value: u32) -> Result<(), i32> {
+    copy_to_user(arg as *mut u32, &value).map_err(|_| EFAULT)
+}
+
+/// Dispatch an ioctl on a `/dev/dsp` stream
+///
+/// # Returns
+/// * `Ok(0)` on success
+/// * `Err(errno)` on failure
+pub fn dsp_ioctl(stream: &SharedStream, request: u64, arg: u64) -> Result<i32, i32> {
+    match request {
+        SNDCTL_DSP_RESET => {
+            stream.lock().reset();
+            Ok(0)
+        }
+        SNDCTL_DSP_SYNC => {
+            super::drain(stream)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_SPEED => {
+            let rate = read_int(arg)?;
+            let rate = stream.lock().set_rate(rate);
+            write_int(arg, rate)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_STEREO => {
+            let stereo = read_int(arg)? != 0;
+            let channels = stream.lock().set_channels(if stereo { 2 } else { 1 });
+            write_int(arg, (channels == 2) as u32)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_CHANNELS => {
+            let channels = read_int(arg)?;
+            if channels == 0 {
+                return Err(EINVAL);
+            }
+            let channels = stream.lock().set_channels(channels);
+            write_int(arg, channels)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_SETFMT => {
+            let afmt = read_int(arg)?;
+            let mut stream = stream.lock();
+            let afmt = if afmt == AFMT_QUERY {
+                stream.format().afmt()
+            } else {
+                stream.set_format(afmt)
+            };
+            drop(stream);
+            write_int(arg, afmt)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_GETFMTS => {
+            write_int(arg, AFMT_U8 | AFMT_S16_LE)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_GETBLKSIZE => {
+            let fragsize = stream.lock().device_frames_to_bytes(FRAGMENT_FRAMES);
+            write_int(arg, fragsize as u32)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_GETOSPACE => {
+            let info = {
+                let stream = stream.lock();
+                let free = stream.free_frames();
+                AudioBufInfo {
+                    fragments: (free / FRAGMENT_FRAMES) as i32,
+                    fragstotal: (BUFFER_FRAMES / FRAGMENT_FRAMES) as i32,
+                    fragsize: stream.device_frames_to_bytes(FRAGMENT_FRAMES) as i32,
+                    bytes: stream.device_frames_to_bytes(free) as i32,
+                }
+            };
+            copy_to_user(arg as *mut AudioBufInfo, &info).map_err(|_| EFAULT)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_GETISPACE => {
+            let info = {
+                let stream = stream.lock();
+                let fragsize = stream.device_frames_to_bytes(FRAGMENT_FRAMES);
+                let bytes = stream.captured_frames() * stream.frame_bytes();
+                AudioBufInfo {
+                    fragments: (bytes / fragsize.max(1)) as i32,
+                    fragstotal: (BUFFER_FRAMES / FRAGMENT_FRAMES) as i32,
+                    fragsize: fragsize as i32,
+                    bytes: bytes as i32,
+                }
+            };
+            copy_to_user(arg as *mut AudioBufInfo, &info).map_err(|_| EFAULT)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_GETODELAY => {
+            let delay = {
+                let stream = stream.lock();
+                stream.device_frames_to_bytes(stream.queued_frames())
+            };
+            write_int(arg, delay as u32)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_GETPLAYVOL => {
+            let [left, right] = stream.lock().volume();
+            write_int(arg, left as u32 | (right as u32) << 8)?;
+            Ok(0)
+        }
+        SNDCTL_DSP_SETPLAYVOL => {
+            let value = read_int(arg)?;
+            let volume = {
+                let mut stream = stream.lock();
+                stream.set_volume([value as u8, (value >> 8) as u8]);
+                stream.volume()
+            };
+            write_int(arg, volume[0] as u32 | (volume[1] as u32) << 8)?;
+            Ok(0)
+        }
+        _ => {
+            log::debug!("dsp: unknown ioctl request {:#x}", request);
+            Err(ENOTTY)
+        }
+    }
+}
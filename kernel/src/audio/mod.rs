@@ -0,0 +1,508 @@
+//! Audio mixer
+//!
+//! Every open of `/dev/dsp` gets its own [`AudioStream`] with its own sample
+//! rate, format, channel count and volume. Writers convert their samples to
+//! the device format (S16_LE, 44100 Hz, stereo) as they queue them, and the
+//! `kaudiod` kernel thread sums one period from every stream and hands it to
+//! the VirtIO sound driver, so any number of clients can play at once.
+//!
+//! Without a sound device the mixed periods are paced by the timer and
+//! discarded, so clients see the same buffering and blocking either way.
+//!
+//! The legacy `AudioInit`/`AudioWrite` syscalls queue into a per-process
+//! stream in the device format and mix with `/dev/dsp` clients.
+//!
+//! Streams opened for reading also record: the `kaudiocapd` kernel thread
+//! reads periods from the device's capture stream and hands a copy to every
+//! recording stream, which converts it to its own rate as it arrives. Without
+//! a capture stream it produces silence at the same pace.
+//!
+//! # Architecture
+//!
+//! ```text
+//! write(/dev/dsp) ──> AudioStream (convert, queue) ─┐
+//! write(/dev/dsp) ──> AudioStream (convert, queue) ─┼─> kaudiod ──> VirtIO sound
+//! AudioWrite      ──> AudioStream (per process)   ──┘    (mix)
+//!
+//! read(/dev/dsp)  <── AudioStream (convert) <─┬── kaudiocapd <── VirtIO sound
+//! read(/dev/dsp)  <── AudioStream (convert) <─┘    (copy)         (capture)
+//! ```
+
+pub mod ioctl;
+pub mod stream;
+
+pub use stream::AudioStream;
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use spin::Mutex;
+
+use crate::arch_without_interrupts as without_interrupts;
+use crate::syscall::errno::{EAGAIN, EINVAL, EIO};
+use crate::task::thread::ThreadState;
+use crate::task::waitqueue::WaitQueueHead;
+use crate::task::{kthread, scheduler};
+
+/// Sample rate of the device, and of every stream after conversion
+pub const DEVICE_RATE: u32 = 44100;
+
+/// Frames mixed and submitted to the device at a time (about 46ms)
+const PERIOD_FRAMES: usize = 2048;
+
+/// Frames captured from the device at a time (about 23ms)
+const CAPTURE_PERIOD_FRAMES: usize = 1024;
+
+/// A stream shared by the file descriptors that refer to it and the mixer
+pub type SharedStream = Arc<Mutex<AudioStream>>;
+
+struct Client {
+    stream: SharedStream,
+    /// Process whose AudioWrite calls feed this stream
+    legacy_pid: Option<u64>,
+    /// Whether captured audio is delivered to this stream
+    capture: bool,
+}
+
+impl Client {
+    /// Whether the stream is still open or has audio left to play
+    fn alive(&self) -> bool {
+        Arc::strong_count(&self.stream) > 1 || !self.stream.lock().is_empty()
+    }
+
+    fn recording(&self) -> bool {
+        self.capture && Arc::strong_count(&self.stream) > 1
+    }
+}
+
+/// A kernel thread driving one direction of the device
+struct Worker {
+    tid: AtomicU64,
+    starting: AtomicBool,
+    /// Whether the device stream is set up; otherwise the thread paces
+    /// itself with the timer
+    device_ready: AtomicBool,
+    /// Set by clients after giving the thread work, so it doesn't go idle
+    /// under them
+    pending: AtomicBool,
+}
+
+impl Worker {
+    const fn new() -> Self {
+        Self {
+            tid: AtomicU64::new(0),
+            starting: AtomicBool::new(false),
+            device_ready: AtomicBool::new(false),
+            pending: AtomicBool::new(false),
+        }
+    }
+
+    /// Set up the device stream and start the thread, once
+    fn start(
+        &self,
+        name: &'static str,
+        entry: fn(),
+        device_setup: fn() -> Result<(), &'static str>,
+    ) -> Result<(), i32> {
+        if self.tid.load(Ordering::Acquire) != 0
+            || self
+                .starting
+                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                .is_err()
+        {
+            return Ok(());
+        }
+
+        match device_setup() {
+            Ok(()) => self.device_ready.store(true, Ordering::Release),
+            Err(e) => log::info!(
+                "audio: {} has no device stream ({}), using the timer",
+                name,
+                e
+            ),
+        }
+
+        match kthread::kthread_run(entry, name) {
+            Ok(handle) => {
+                let _ =
+                    self.tid
+                        .compare_exchange(0, handle.tid(), Ordering::AcqRel, Ordering::Acquire);
+                Ok(())
+            }
+            Err(error) => {
+                log::error!("failed to start {}: {:?}", name, error);
+                self.starting.store(false, Ordering::Release);
+                Err(EIO)
+            }
+        }
+    }
+
+    /// Record the calling thread as the worker, returning its thread ID
+    fn register(&self) -> Option<u64> {
+        let my_tid = scheduler::current_thread_id()?;
+        let _ = self
+            .tid
+            .compare_exchange(0, my_tid, Ordering::AcqRel, Ordering::Acquire);
+        Some(my_tid)
+    }
+
+    fn device_ready(&self) -> bool {
+        self.device_ready.load(Ordering::Acquire)
+    }
+
+    /// Mark work pending and wake the thread if it is idle
+    fn kick(&self) {
+        self.pending.store(true, Ordering::Release);
+        let tid = self.tid.load(Ordering::Acquire);
+        if tid != 0 {
+            let _ = scheduler::wake_thread_any_context(tid);
+        }
+    }
+
+    /// Block until a client kicks the thread
+    fn idle(&self, my_tid: u64) {
+        without_interrupts(|| {
+            scheduler::with_scheduler(|sched| {
+                sched.block_current();
+            });
+            if self.pending.load(Ordering::Acquire) {
+                scheduler::with_scheduler(|sched| {
+                    sched.unblock(my_tid);
+                });
+            }
+        });
+        scheduler::yield_current();
+        crate::arch_halt_with_interrupts();
+    }
+}
+
+/// Open streams, and closed ones still playing out
+static CLIENTS: Mutex<Vec<Client>> = Mutex::new(Vec::new());
+
+static MIXER: Worker = Worker::new();
+static CAPTURE: Worker = Worker::new();
+
+/// Writers waiting for buffer space and callers waiting for a stream to drain
+static SPACE_WQ: WaitQueueHead = WaitQueueHead::new();
+/// Readers waiting for captured audio
+static DATA_WQ: WaitQueueHead = WaitQueueHead::new();
+
+/// Open a new stream for a `/dev/dsp` client, starting the mixer if needed.
+/// A stream opened with `capture` also records.
+pub fn open_stream(capture: bool) -> Result<SharedStream, i32> {
+    start_mixer()?;
+    if capture {
+        start_capture()?;
+    }
+    let stream = Arc::new(Mutex::new(AudioStream::new()));
+    CLIENTS.lock().push(Client {
+        stream: stream.clone(),
+        legacy_pid: None,
+        capture,
+    });
+    if capture {
+        CAPTURE.kick();
+    }
+    Ok(stream)
+}
+
+/// Whether a sound device is playing the mixer's output
+pub fn device_ready() -> bool {
+    MIXER.device_ready()
+}
+
+/// Whether a sound device is supplying captured audio
+pub fn capture_device_ready() -> bool {
+    CAPTURE.device_ready()
+}
+
+/// Queue `data` (in the stream's format) for playback.
+///
+/// Blocks while the stream's buffer is full unless `nonblocking`, in which
+/// case it returns the bytes that fit, or EAGAIN if none did.
+pub fn write(stream: &SharedStream, data: &[u8], nonblocking: bool) -> Result<usize, i32> {
+    let mut written = 0;
+    loop {
+        let queued = stream.lock().write(&data[written..]);
+        if queued > 0 {
+            written += queued;
+            MIXER.kick();
+        }
+        if written == data.len() {
+            return Ok(written);
+        }
+        if nonblocking {
+            return if written > 0 {
+                Ok(written)
+            } else {
+                Err(EAGAIN)
+            };
+        }
+        if let Err(e) = wait_for(&SPACE_WQ, || stream.lock().has_fragment_space()) {
+            return if written > 0 { Ok(written) } else { Err(e) };
+        }
+    }
+}
+
+/// Read captured audio (in the stream's format) into `buf`.
+///
+/// Blocks until `buf` is full of whole frames unless `nonblocking`, in which
+/// case it returns what has been captured, or EAGAIN if nothing has.
+pub fn read(stream: &SharedStream, buf: &mut [u8], nonblocking: bool) -> Result<usize, i32> {
+    let frame_bytes = stream.lock().frame_bytes();
+    if buf.len() < frame_bytes {
+        return Err(EINVAL);
+    }
+    let mut filled = 0;
+    loop {
+        filled += stream.lock().read(&mut buf[filled..]);
+        if buf.len() - filled < frame_bytes {
+            return Ok(filled);
+        }
+        if nonblocking {
+            return if filled > 0 { Ok(filled) } else { Err(EAGAIN) };
+        }
+        if let Err(e) = wait_for(&DATA_WQ, || stream.lock().captured_frames() > 0) {
+            return if filled > 0 { Ok(filled) } else { Err(e) };
+        }
+    }
+}
+
+/// Wait until everything queued on `stream` has been mixed
+pub fn drain(stream: &SharedStream) -> Result<(), i32> {
+    wait_for(&SPACE_WQ, || stream.lock().is_empty())
+}
+
+/// Set up the legacy AudioInit path. Fails without a sound device, as the
+/// syscall always has.
+pub fn legacy_init() -> Result<(), i32> {
+    start_mixer()?;
+    if device_ready() {
+        Ok(())
+    } else {
+        Err(EIO)
+    }
+}
+
+/// Queue device-format PCM for the calling process (legacy AudioWrite)
+pub fn legacy_write(data: &[u8]) -> Result<usize, i32> {
+    start_mixer()?;
+    let pid = crate::process::current_pid().map(|pid| pid.as_u64());
+    let stream = {
+        let mut clients = CLIENTS.lock();
+        match clients
+            .iter()
+            .find(|client| client.legacy_pid.is_some() && client.legacy_pid == pid)
+        {
+            Some(client) => client.stream.clone(),
+            None => {
+                let stream = Arc::new(Mutex::new(AudioStream::new()));
+                clients.push(Client {
+                    stream: stream.clone(),
+                    legacy_pid: pid,
+                    capture: false,
+                });
+                stream
+            }
+        }
+    };
+    write(&stream, data, false)
+}
+
+/// Block the calling thread on `wq` until `ready` holds. The worker threads
+/// wake their queue every period, so the condition is re-checked at least
+/// that often.
+fn wait_for(wq: &WaitQueueHead, ready: impl Fn() -> bool) -> Result<(), i32> {
+    loop {
+        if ready() {
+            return Ok(());
+        }
+        if let Some(e) = crate::syscall::check_signals_for_eintr() {
+            return Err(e);
+        }
+        if wq.prepare_to_wait(ThreadState::BlockedOnIO).is_none() {
+            return Err(EIO);
+        }
+        // The worker may have run between the check and queueing
+        if ready() {
+            wq.finish_wait();
+            return Ok(());
+        }
+        crate::task::waitqueue::schedule_current_wait();
+        wq.finish_wait();
+    }
+}
+
+fn start_mixer() -> Result<(), i32> {
+    MIXER.start("kaudiod", mixer_fn, device_setup)
+}
+
+fn start_capture() -> Result<(), i32> {
+    CAPTURE.start("kaudiocapd", capture_fn, capture_setup)
+}
+
+fn device_setup() -> Result<(), &'static str> {
+    #[cfg(target_arch = "aarch64")]
+    let result = crate::drivers::virtio::sound_mmio::setup_stream();
+
+    #[cfg(target_arch = "x86_64")]
+    let result = crate::drivers::virtio::sound::setup_stream();
+
+    result
+}
+
+fn device_write(data: &[u8]) -> Result<usize, &'static str> {
+    #[cfg(target_arch = "aarch64")]
+    let result = crate::drivers::virtio::sound_mmio::write_pcm(data);
+
+    #[cfg(target_arch = "x86_64")]
+    let result = crate::drivers::virtio::sound::write_pcm(data);
+
+    result
+}
+
+fn capture_setup() -> Result<(), &'static str> {
+    #[cfg(target_arch = "aarch64")]
+    let result = crate::drivers::virtio::sound_mmio::setup_capture();
+
+    #[cfg(target_arch = "x86_64")]
+    let result = crate::drivers::virtio::sound::setup_capture();
+
+    result
+}
+
+fn capture_read(buf: &mut [u8]) -> Result<usize, &'static str> {
+    #[cfg(target_arch = "aarch64")]
+    let result = crate::drivers::virtio::sound_mmio::read_pcm(buf);
+
+    #[cfg(target_arch = "x86_64")]
+    let result = crate::drivers::virtio::sound::read_pcm(buf);
+
+    result
+}
+
+/// Sum up to one period from every stream into `acc`, dropping streams that
+/// are closed and played out. Returns the frames produced.
+fn mix_period(acc: &mut [i32]) -> usize {
+    acc.fill(0);
+    let mut clients = CLIENTS.lock();
+    let mut frames = 0;
+    for client in clients.iter() {
+        frames = frames.max(client.stream.lock().mix_into(acc));
+    }
+    clients.retain(Client::alive);
+    frames
+}
+
+fn mixer_fn() {
+    let Some(my_tid) = MIXER.register() else {
+        return;
+    };
+
+    let mut acc = vec![0i32; PERIOD_FRAMES * 2];
+    let mut pcm = vec![0u8; PERIOD_FRAMES * 4];
+    let mut deadline_ns = 0;
+
+    loop {
+        MIXER.pending.store(false, Ordering::Release);
+        let frames = mix_period(&mut acc);
+        if frames == 0 {
+            MIXER.idle(my_tid);
+            continue;
+        }
+        SPACE_WQ.wake_up();
+
+        for (bytes, &sample) in pcm.chunks_exact_mut(2).zip(acc.iter()) {
+            let sample = sample.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+            bytes.copy_from_slice(&sample.to_le_bytes());
+        }
+        let len = frames * 4;
+        if MIXER.device_ready() && device_write(&pcm[..len]).is_ok() {
+            continue;
+        }
+
+        // Null sink: hold each period for as long as it would take to play
+        pace(my_tid, &mut deadline_ns, frames);
+    }
+}
+
+fn capture_fn() {
+    let Some(my_tid) = CAPTURE.register() else {
+        return;
+    };
+
+    let mut pcm = vec![0u8; CAPTURE_PERIOD_FRAMES * 4];
+    let mut frames = Vec::with_capacity(CAPTURE_PERIOD_FRAMES);
+    let mut deadline_ns = 0;
+
+    loop {
+        CAPTURE.pending.store(false, Ordering::Release);
+        let recording = {
+            let mut clients = CLIENTS.lock();
+            clients.retain(Client::alive);
+            clients.iter().any(Client::recording)
+        };
+        if !recording {
+            CAPTURE.idle(my_tid);
+            continue;
+        }
+
+        frames.clear();
+        let len = if CAPTURE.device_ready() {
+            capture_read(&mut pcm).unwrap_or(0)
+        } else {
+            0
+        };
+        if len > 0 {
+            frames.extend(pcm[..len].chunks_exact(4).map(|bytes| {
+                [
+                    i16::from_le_bytes([bytes[0], bytes[1]]),
+                    i16::from_le_bytes([bytes[2], bytes[3]]),
+                ]
+            }));
+        } else {
+            // Null source: a period of silence for each period of real time
+            pace(my_tid, &mut deadline_ns, CAPTURE_PERIOD_FRAMES);
+            frames.resize(CAPTURE_PERIOD_FRAMES, [0; 2]);
+        }
+
+        for client in CLIENTS.lock().iter().filter(|client| client.recording()) {
+            client.stream.lock().push_captured(&frames);
+        }
+        DATA_WQ.wake_up();
+    }
+}
+
+/// Sleep until `frames` device frames past the previous deadline, so the
+/// caller keeps the device rate without drifting
+fn pace(my_tid: u64, deadline_ns: &mut u64, frames: usize) {
+    let period_ns = frames as u64 * 1_000_000_000 / DEVICE_RATE as u64;
+    *deadline_ns = (*deadline_ns).max(monotonic_ns()) + period_ns;
+    sleep_until(my_tid, *deadline_ns);
+}
+
+fn sleep_until(my_tid: u64, wake_ns: u64) {
+    scheduler::with_scheduler(|sched| {
+        sched.block_current_for_timer(wake_ns);
+    });
+    scheduler::yield_current();
+    loop {
+        let blocked = scheduler::with_scheduler(|sched| {
+            sched.wake_expired_timers();
+            sched
+                .get_thread(my_tid)
+                .is_some_and(|thread| thread.state == ThreadState::BlockedOnTimer)
+        })
+        .unwrap_or(false);
+        if !blocked {
+            break;
+        }
+        crate::arch_halt_with_interrupts();
+    }
+}
+
+fn monotonic_ns() -> u64 {
+    let (secs, nanos) = crate::time::get_monotonic_time_ns();
+    secs * 1_000_000_000 + nanos
+}
@@ -0,0 +1,439 @@
+//! Per-client audio streams
+//!
+//! A stream remembers the format its client negotiated and converts each
+//! write to the mixer's format on the way in: samples become signed 16-bit,
+//! mono is copied to both channels, and other sample rates are linearly
+//! interpolated to the device rate. The mixer only ever sees device frames.
+//!
+//! Captured audio takes the opposite path: device frames are resampled to
+//! the client rate as they arrive and encoded in the client format as the
+//! client reads them.
+
+use alloc::collections::VecDeque;
+
+use super::DEVICE_RATE;
+
+/// OSS format code for unsigned 8-bit samples
+pub const AFMT_U8: u32 = 0x0000_0008;
+/// OSS format code for signed 16-bit little-endian samples
+pub const AFMT_S16_LE: u32 = 0x0000_0010;
+
+/// Lowest sample rate a stream accepts
+pub const MIN_RATE: u32 = 4000;
+/// Highest sample rate a stream accepts
+pub const MAX_RATE: u32 = 192_000;
+
+/// Device frames a stream can hold before writers wait (about 370ms)
+pub const BUFFER_FRAMES: usize = 16384;
+/// Device frames per fragment, the unit buffer space is reported in
+pub const FRAGMENT_FRAMES: usize = 2048;
+
+/// Full volume, and the default for new streams
+pub const MAX_VOLUME: u8 = 100;
+
+/// One in 16.16 fixed point
+const PHASE_ONE: u32 = 1 << 16;
+
+/// Sample formats a stream accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    U8,
+    S16Le,
+}
+
+impl SampleFormat {
+    /// The format for an OSS `AFMT_*` code, if supported
+    pub fn from_afmt(afmt: u32) -> Option<Self> {
+        match afmt {
+            AFMT_U8 => Some(SampleFormat::U8),
+            AFMT_S16_LE => Some(SampleFormat::S16Le),
+            _ => None,
+        }
+    }
+
+    /// The OSS `AFMT_*` code for this format
+    pub fn afmt(self) -> u32 {
+        match self {
+            SampleFormat::U8 => AFMT_U8,
+            SampleFormat::S16Le => AFMT_S16_LE,
+        }
+    }
+
+    /// Bytes per sample
+    pub fn bytes(self) -> usize {
+        match self {
+            SampleFormat::U8 => 1,
+            SampleFormat::S16Le => 2,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> i16 {
+        match self {
+            SampleFormat::U8 => ((bytes[0] as i16) - 128) << 8,
+            SampleFormat::S16Le => i16::from_le_bytes([bytes[0], bytes[1]]),
+        }
+    }
+
+    fn encode(self, sample: i16, bytes: &mut [u8]) {
+        match self {
+            SampleFormat::U8 => bytes[0] = ((sample >> 8) + 128) as u8,
+            SampleFormat::S16Le => bytes[..2].copy_from_slice(&sample.to_le_bytes()),
+        }
+    }
+}
+
+/// Linear-interpolating sample rate converter
+#[derive(Debug, Clone, Copy, Default)]
+struct Resampler {
+    /// Position between `prev` and the next input frame, 16.16 fixed point
+    phase: u32,
+    prev: [i16; 2],
+}
+
+impl Resampler {
+    /// Input frames per output frame converting `from` Hz to `to` Hz, 16.16
+    /// fixed point
+    fn step(from: u32, to: u32) -> u32 {
+        (((from as u64) << 16) / to as u64) as u32
+    }
+
+    /// Output frames one input frame can produce at `step`
+    fn max_outputs(step: u32) -> usize {
+        PHASE_ONE.div_ceil(step) as usize + 1
+    }
+
+    /// Convert one input frame, interpolating from the previous one, and
+    /// pass each output frame to `emit`
+    fn push(&mut self, frame: [i16; 2], step: u32, mut emit: impl FnMut([i16; 2])) {
+        while self.phase < PHASE_ONE {
+            let t = self.phase as i64;
+            let lerp = |a: i16, b: i16| (a as i64 + ((b as i64 - a as i64) * t >> 16)) as i16;
+            emit([lerp(self.prev[0], frame[0]), lerp(self.prev[1], frame[1])]);
+            self.phase += step;
+        }
+        self.phase -= PHASE_ONE;
+        self.prev = frame;
+    }
+}
+
+/// A client's queue of audio on its way to the mixer
+pub struct AudioStream {
+    rate: u32,
+    channels: u32,
+    format: SampleFormat,
+    /// Playback volume of the left and right channels, 0-100
+    volume: [u8; 2],
+    /// Converted frames waiting for the mixer
+    queue: VecDeque<[i16; 2]>,
+    /// Bytes of an incomplete input frame, completed by the next write
+    partial: [u8; 4],
+    partial_len: usize,
+    /// Converts written frames to the device rate
+    playback: Resampler,
+    /// Captured frames at the client rate, waiting to be read
+    captured: VecDeque<[i16; 2]>,
+    /// Converts captured device frames to the client rate
+    capture: Resampler,
+}
+
+impl AudioStream {
+    /// A stream in the device format (S16_LE, 44100 Hz, stereo) at full volume
+    pub fn new() -> Self {
+        Self {
+            rate: DEVICE_RATE,
+            channels: 2,
+            format: SampleFormat::S16Le,
+            volume: [MAX_VOLUME; 2],
+            queue: VecDeque::with_capacity(BUFFER_FRAMES),
+            partial: [0; 4],
+            partial_len: 0,
+            playback: Resampler::default(),
+            captured: VecDeque::new(),
+            capture: Resampler::default(),
+        }
+    }
+
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// Set the sample rate, clamped to what streams accept. Returns the rate
+    /// in effect.
+    pub fn set_rate(&mut self, rate: u32) -> u32 {
+        let rate = rate.clamp(MIN_RATE, MAX_RATE);
+        if rate != self.rate {
+            // Captured frames are already at the old rate
+            self.captured.clear();
+            self.capture = Resampler::default();
+        }
+        self.rate = rate;
+        self.rate
+    }
+
+    pub fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    /// Set mono (1) or stereo (2). Returns the channel count in effect.
+    pub fn set_channels(&mut self, channels: u32) -> u32 {
+        self.channels = channels.clamp(1, 2);
+        self.partial_len = 0;
+        self.channels
+    }
+
+    pub fn format(&self) -> SampleFormat {
+        self.format
+    }
+
+    /// Switch to the format for `afmt`, keeping the current format if it is
+    /// not supported. Returns the format code in effect.
+    pub fn set_format(&mut self, afmt: u32) -> u32 {
+        if let Some(format) = SampleFormat::from_afmt(afmt) {
+            self.format = format;
+            self.partial_len = 0;
+        }
+        self.format.afmt()
+    }
+
+    pub fn volume(&self) -> [u8; 2] {
+        self.volume
+    }
+
+    /// Set the left and right volume, each clamped to 0-100
+    pub fn set_volume(&mut self, volume: [u8; 2]) {
+        self.volume = [volume[0].min(MAX_VOLUME), volume[1].min(MAX_VOLUME)];
+    }
+
+    /// Bytes in one frame of the client format
+    pub fn frame_bytes(&self) -> usize {
+        self.channels as usize * self.format.bytes()
+    }
+
+    /// Device frames queued for the mixer
+    pub fn queued_frames(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Device frames that can still be queued
+    pub fn free_frames(&self) -> usize {
+        BUFFER_FRAMES.saturating_sub(self.queue.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Whether at least one fragment of space is free
+    pub fn has_fragment_space(&self) -> bool {
+        self.free_frames() >= FRAGMENT_FRAMES
+    }
+
+    /// `frames` device frames expressed as bytes of the client format
+    pub fn device_frames_to_bytes(&self, frames: usize) -> usize {
+        let client_frames = (frames as u64 * self.rate as u64 / DEVICE_RATE as u64) as usize;
+        client_frames * self.frame_bytes()
+    }
+
+    /// Captured frames waiting to be read
+    pub fn captured_frames(&self) -> usize {
+        self.captured.len()
+    }
+
+    /// Whether at least one fragment of captured audio is waiting
+    pub fn has_captured_fragment(&self) -> bool {
+        self.captured.len() * self.frame_bytes() >= self.device_frames_to_bytes(FRAGMENT_FRAMES)
+    }
+
+    /// Client frames the capture queue holds before the oldest are dropped
+    fn capture_capacity(&self) -> usize {
+        (BUFFER_FRAMES as u64 * self.rate as u64 / DEVICE_RATE as u64).max(1) as usize
+    }
+
+    /// Queue as much of `data` (client format) as fits, returning the bytes
+    /// consumed. A trailing partial frame is always consumed and held until
+    /// the rest of it arrives.
+    pub fn write(&mut self, data: &[u8]) -> usize {
+        let frame_bytes = self.frame_bytes();
+        let step = Resampler::step(self.rate, DEVICE_RATE);
+        let max_outputs = Resampler::max_outputs(step);
+
+        let mut used = 0;
+        while used < data.len() {
+            let need = frame_bytes - self.partial_len;
+            if data.len() - used < need {
+                let rest = &data[used..];
+                self.partial[self.partial_len..self.partial_len + rest.len()].copy_from_slice(rest);
+                self.partial_len += rest.len();
+                used = data.len();
+                break;
+            }
+            if self.free_frames() < max_outputs {
+                break;
+            }
+
+            let mut bytes = [0u8; 4];
+            bytes[..self.partial_len].copy_from_slice(&self.partial[..self.partial_len]);
+            bytes[self.partial_len..frame_bytes].copy_from_slice(&data[used..used + need]);
+            used += need;
+            self.partial_len = 0;
+
+            let size = self.format.bytes();
+            let left = self.format.decode(&bytes[..size]);
+            let right = if self.channels == 2 {
+                self.format.decode(&bytes[size..2 * size])
+            } else {
+                left
+            };
+            let queue = &mut self.queue;
+            self.playback
+                .push([left, right], step, |frame| queue.push_back(frame));
+        }
+        used
+    }
+
+    /// Read captured audio into `buf` in the client format, whole frames
+    /// only. Returns the bytes filled.
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        let frame_bytes = self.frame_bytes();
+        let size = self.format.bytes();
+        let frames = self.captured.len().min(buf.len() / frame_bytes);
+        for (out, [left, right]) in buf
+            .chunks_exact_mut(frame_bytes)
+            .zip(self.captured.drain(..frames))
+        {
+            if self.channels == 2 {
+                self.format.encode(left, &mut out[..size]);
+                self.format.encode(right, &mut out[size..]);
+            } else {
+                let mono = ((left as i32 + right as i32) / 2) as i16;
+                self.format.encode(mono, out);
+            }
+        }
+        frames * frame_bytes
+    }
+
+    /// Add captured device frames, dropping the oldest unread audio if the
+    /// client has fallen behind
+    pub fn push_captured(&mut self, frames: &[[i16; 2]]) {
+        let step = Resampler::step(DEVICE_RATE, self.rate);
+        let captured = &mut self.captured;
+        for &frame in frames {
+            self.capture
+                .push(frame, step, |frame| captured.push_back(frame));
+        }
+        let excess = self.captured.len().saturating_sub(self.capture_capacity());
+        self.captured.drain(..excess);
+    }
+
+    /// Add up to `acc.len() / 2` queued frames, scaled by the stream volume,
+    /// into the interleaved stereo accumulator. Returns the frames mixed.
+    pub fn mix_into(&mut self, acc: &mut [i32]) -> usize {
+        let frames = self.queue.len().min(acc.len() / 2);
+        let [left_volume, right_volume] = self.volume.map(|v| v as i32);
+        for (out, frame) in acc.chunks_exact_mut(2).zip(self.queue.drain(..frames)) {
+            out[0] += frame[0] as i32 * left_volume / MAX_VOLUME as i32;
+            out[1] += frame[1] as i32 * right_volume / MAX_VOLUME as i32;
+        }
+        frames
+    }
+
+    /// Drop everything queued and captured, keeping the negotiated format
+    /// and volume
+    pub fn reset(&mut self) {
+        self.queue.clear();
+        self.partial_len = 0;
+        self.playback = Resampler::default();
+        self.captured.clear();
+        self.capture = Resampler::default();
+    }
+}
+
+impl Default for AudioStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_format_passes_through() {
+        let mut stream = AudioStream::new();
+        let samples: [i16; 4] = [100, -100, 200, -200];
+        let bytes: alloc::vec::Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        assert_eq!(stream.write(&bytes), bytes.len());
+        assert_eq!(stream.queued_frames(), 2);
+
+        let mut acc = [0i32; 8];
+        assert_eq!(stream.mix_into(&mut acc), 2);
+        // One frame of latency from interpolation: silence, then the first frame
+        assert_eq!(&acc[..4], &[0, 0, 100, -100]);
+    }
+
+    #[test]
+    fn mono_u8_at_half_rate_doubles_frames() {
+        let mut stream = AudioStream::new();
+        stream.set_format(AFMT_U8);
+        stream.set_channels(1);
+        stream.set_rate(DEVICE_RATE / 2);
+        assert_eq!(stream.write(&[128; 100]), 100);
+        assert!((199..=201).contains(&stream.queued_frames()));
+    }
+
+    #[test]
+    fn partial_frames_wait_for_the_rest() {
+        let mut stream = AudioStream::new();
+        assert_eq!(stream.write(&[1, 0, 2]), 3);
+        assert_eq!(stream.queued_frames(), 0);
+        assert_eq!(stream.write(&[0]), 1);
+        assert_eq!(stream.queued_frames(), 1);
+    }
+
+    #[test]
+    fn volume_scales_and_full_buffer_stops_writes() {
+        let mut stream = AudioStream::new();
+        stream.set_volume([50, 200]);
+        assert_eq!(stream.volume(), [50, MAX_VOLUME]);
+
+        let data = alloc::vec![0x10u8; (BUFFER_FRAMES + 16) * 4];
+        let used = stream.write(&data);
+        assert!(used < data.len());
+        assert!(!stream.has_fragment_space());
+
+        let mut acc = [0i32; 4];
+        stream.mix_into(&mut acc);
+        stream.mix_into(&mut acc);
+        assert_eq!(acc[0], 0x1010 / 2);
+        assert_eq!(acc[1], 0x1010);
+    }
+
+    #[test]
+    fn capture_converts_to_client_format() {
+        let mut stream = AudioStream::new();
+        stream.set_format(AFMT_U8);
+        stream.set_channels(1);
+        stream.set_rate(DEVICE_RATE / 2);
+        stream.push_captured(&[[0x4000, 0x4000]; 100]);
+        assert!((49..=51).contains(&stream.captured_frames()));
+
+        let mut buf = [0u8; 8];
+        assert_eq!(stream.read(&mut buf), 8);
+        // Interpolation starts from silence, then settles on the input level
+        assert_eq!(buf[0], 128);
+        assert_eq!(buf[7], 128 + 0x40);
+    }
+
+    #[test]
+    fn capture_overrun_keeps_newest() {
+        let mut stream = AudioStream::new();
+        stream.push_captured(&alloc::vec![[1, 1]; BUFFER_FRAMES]);
+        stream.push_captured(&[[2, 2]; 4]);
+        assert_eq!(stream.captured_frames(), BUFFER_FRAMES);
+
+        let mut buf = alloc::vec![0u8; BUFFER_FRAMES * 4];
+        assert_eq!(stream.read(&mut buf), buf.len());
+        assert_eq!(&buf[buf.len() - 4..], &[2, 0, 2, 0]);
+    }
+}
@@ -30,6 +30,16 @@ pub const TEST_BINARIES: &[&str] = &[
     "signal_return_test",
     "signal_regs_test",
     "sigaltstack_test",
+    "siginfo_test",
+    "rt_signal_test",
+    "coredump_test",
+    "ptrace_test",
+    "rusage_test",
+    "priority_test",
+    "affinity_test",
+    "proc_pid_test",
+    "dsp_mixer_test",
+    "dsp_capture_test",
     "sigchld_test",
     "sigkill_teardown_test",
     "pause_test",
@@ -67,6 +77,10 @@ pub const TEST_BINARIES: &[&str] = &[
     "cwd_test",
     "exec_from_ext2_test",
     "fs_block_alloc_test",
+    "fs_truncate_test",
+    "fs_statfs_test",
+    "flock_test",
+    "splice_test",
     // Coreutils tests
     "true_test",
     "false_test",
@@ -0,0 +1,530 @@
+//! Hardware cursor plane shared by the VirtIO GPU transports (MMIO and PCI).
+//!
+//! virtio-gpu shows the pointer in its own plane: a 64x64 image resource
+//! selected with UPDATE_CURSOR and positioned with MOVE_CURSOR, both sent on
+//! the cursor queue (queue 1). A pointer move is then one small command,
+//! with no framebuffer damage and no recomposite.
+//!
+//! The compositor picks the shape (set_cursor_shape) from the built-in set
+//! below, or uploads its own image (set_cursor_image). The render thread
+//! calls [`update`] with the pointer position: it uploads the image when the
+//! shape changed and otherwise only moves the cursor. Positions are desktop
+//! coordinates, mapped to the output the pointer is on (see `gpu_display`).
+//!
+//! Until a transport brings the plane up, the pointer stays software-drawn
+//! (`graphics::cursor`) or a VirGL quad; both use the same shapes.
+
+use super::gpu_display::DisplayLayout;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use spin::Mutex;
+
+/// Width and height of the cursor image resource (fixed by virtio-gpu).
+pub const CURSOR_SIZE: u32 = 64;
+/// Pixels in a cursor image.
+pub const CURSOR_PIXELS: usize = (CURSOR_SIZE * CURSOR_SIZE) as usize;
+
+/// Built-in shape numbers, as passed to set_cursor_shape.
+pub const SHAPE_ARROW: u32 = 0;
+pub const SHAPE_RESIZE_NS: u32 = 1;
+pub const SHAPE_RESIZE_EW: u32 = 2;
+pub const SHAPE_RESIZE_NWSE: u32 = 3;
+pub const SHAPE_RESIZE_NESW: u32 = 4;
+pub const SHAPE_TEXT: u32 = 5;
+/// Number of built-in shapes.
+pub const NUM_SHAPES: u32 = 6;
+/// The image last uploaded with set_cursor_image.
+pub const SHAPE_CUSTOM: u32 = NUM_SHAPES;
+
+/// Size of a built-in shape bitmap.
+pub const SHAPE_W: u32 = 16;
+pub const SHAPE_H: u32 = 16;
+
+/// Built-in shapes. 0=transparent, 1=white, 2=black outline.
+pub const SHAPES: [[[u8; 16]; 16]; NUM_SHAPES as usize] = [
+    // Arrow
+    [
+        [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 1, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 2, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 2, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    ],
+    // NS resize (vertical double arrow ↕)
+    [
+        [0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 2, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 2, 2, 1, 2, 2, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 2, 2, 1, 2, 2, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 2, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    ],
+    // EW resize (horizontal double arrow ↔)
+    [
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0],
+        [0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0],
+        [0, 2, 1, 1, 1, 2, 2, 2, 2, 2, 2, 1, 1, 1, 2, 0],
+        [2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2],
+        [0, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 0],
+        [0, 0, 2, 1, 1, 2, 2, 2, 2, 2, 2, 1, 1, 2, 0, 0],
+        [0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0],
+        [0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    ],
+    // NWSE resize (diagonal ↘↗)
+    [
+        [2, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 2, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 1, 2],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 2, 2, 2, 2, 2],
+    ],
+    // NESW resize (diagonal ↙↗)
+    [
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 2, 2, 2, 2, 2],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 1, 2],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [2, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    ],
+    // Text (I-beam)
+    [
+        [0, 0, 0, 0, 2, 2, 2, 0, 2, 2, 2, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 2, 1, 1, 2, 1, 1, 2, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 2, 2, 1, 2, 2, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 2, 2, 1, 2, 2, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 2, 1, 1, 2, 1, 1, 2, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 2, 2, 2, 0, 2, 2, 2, 0, 0, 0, 0, 0],
+    ],
+];
+
+/// Hotspot of each built-in shape (pixels from the top-left of its bitmap).
+pub const HOTSPOTS: [(u32, u32); NUM_SHAPES as usize] = [
+    (0, 0), // Arrow: top-left
+    (7, 7), // NS resize: center
+    (7, 7), // EW resize: center
+    (7, 7), // NWSE resize: center
+    (7, 7), // NESW resize: center
+    (7, 8), // Text: middle of the stem
+];
+
+/// BGRA value of a built-in shape pixel.
+pub const fn shape_pixel(value: u8) -> u32 {
+    match value {
+        1 => 0xFF_FF_FF_FF, // white
+        2 => 0xFF_00_00_00, // black with alpha=FF
+        _ => 0x00_00_00_00, // transparent
+    }
+}
+
+/// Current shape (one of the SHAPE_* numbers).
+static SHAPE: AtomicU32 = AtomicU32::new(SHAPE_ARROW);
+/// Bumped whenever the image to show changes (new shape or new upload).
+static IMAGE_GEN: AtomicU32 = AtomicU32::new(0);
+/// A transport has created the cursor resource and its cursor queue is up.
+static PLANE_READY: AtomicBool = AtomicBool::new(false);
+
+/// An image uploaded with set_cursor_image, padded to 64x64.
+struct CursorImage {
+    pixels: [u32; CURSOR_PIXELS],
+    hot_x: u32,
+    hot_y: u32,
+}
+
+static CUSTOM: Mutex<CursorImage> = Mutex::new(CursorImage {
+    pixels: [0; CURSOR_PIXELS],
+    hot_x: 0,
+    hot_y: 0,
+});
+
+/// What the cursor plane shows. Only touched by the render thread.
+struct Plane {
+    /// IMAGE_GEN of the uploaded image (u32::MAX before the first upload)
+    image_gen: u32,
+    /// Output the cursor is on, and its position there
+    scanout: Option<usize>,
+    x: i32,
+    y: i32,
+    /// Staging copy of the image being uploaded
+    image: CursorImage,
+}
+
+static PLANE: Mutex<Plane> = Mutex::new(Plane {
+    image_gen: u32::MAX,
+    scanout: None,
+    x: 0,
+    y: 0,
+    image: CursorImage {
+        pixels: [0; CURSOR_PIXELS],
+        hot_x: 0,
+        hot_y: 0,
+    },
+});
+
+/// Called by a transport once its cursor resource exists.
+pub fn set_plane_ready() {
+    PLANE_READY.store(true, Ordering::Release);
+    crate::serial_println!("[virtio-gpu] Hardware cursor plane enabled");
+}
+
+/// Whether the pointer is shown in the hardware cursor plane. When false,
+/// it must be drawn into the frame.
+pub fn hardware_active() -> bool {
+    PLANE_READY.load(Ordering::Acquire)
+}
+
+/// Select a built-in shape, or SHAPE_CUSTOM for the last uploaded image.
+pub fn set_shape(shape: u32) -> Result<(), &'static str> {
+    if shape > SHAPE_CUSTOM {
+        return Err("no such cursor shape");
+    }
+    if SHAPE.swap(shape, Ordering::AcqRel) != shape {
+        IMAGE_GEN.fetch_add(1, Ordering::AcqRel);
+    }
+    Ok(())
+}
+
+/// The built-in shape to draw when the cursor is not in the hardware plane.
+/// A custom image has no software fallback and shows as the arrow.
+pub fn builtin_shape() -> u32 {
+    match SHAPE.load(Ordering::Acquire) {
+        s if s < NUM_SHAPES => s,
+        _ => SHAPE_ARROW,
+    }
+}
+
+/// Upload a `width` x `height` BGRA image (at most 64x64) with its hotspot,
+/// and show it.
+pub fn set_image(
+    pixels: &[u32],
+    width: u32,
+    height: u32,
+    hot_x: u32,
+    hot_y: u32,
+) -> Result<(), &'static str> {
+    copy_image(&mut CUSTOM.lock(), pixels, width, height, hot_x, hot_y)?;
+    SHAPE.store(SHAPE_CUSTOM, Ordering::Release);
+    IMAGE_GEN.fetch_add(1, Ordering::AcqRel);
+    Ok(())
+}
+
+/// Check a `width` x `height` image and its hotspot, and copy it into the
+/// top-left of `image`, the rest transparent. `image` is left as it was if
+/// the image is rejected.
+fn copy_image(
+    image: &mut CursorImage,
+    pixels: &[u32],
+    width: u32,
+    height: u32,
+    hot_x: u32,
+    hot_y: u32,
+) -> Result<(), &'static str> {
+    if width == 0 || height == 0 || width > CURSOR_SIZE || height > CURSOR_SIZE {
+        return Err("cursor image size");
+    }
+    if pixels.len() < (width * height) as usize || hot_x >= width || hot_y >= height {
+        return Err("invalid cursor image");
+    }
+    image.pixels.fill(0);
+    for (row, src) in pixels
+        .chunks_exact(width as usize)
+        .take(height as usize)
+        .enumerate()
+    {
+        let dst = row * CURSOR_SIZE as usize;
+        image.pixels[dst..dst + width as usize].copy_from_slice(src);
+    }
+    image.hot_x = hot_x;
+    image.hot_y = hot_y;
+    Ok(())
+}
+
+/// Render the current shape into `image`.
+fn render_current(image: &mut CursorImage) {
+    let shape = SHAPE.load(Ordering::Acquire);
+    if shape == SHAPE_CUSTOM {
+        let custom = CUSTOM.lock();
+        image.pixels.copy_from_slice(&custom.pixels);
+        image.hot_x = custom.hot_x;
+        image.hot_y = custom.hot_y;
+        return;
+    }
+    let shape = shape.min(NUM_SHAPES - 1) as usize;
+    image.pixels.fill(0);
+    for (row, bits) in SHAPES[shape].iter().enumerate() {
+        for (col, &bit) in bits.iter().enumerate() {
+            image.pixels[row * CURSOR_SIZE as usize + col] = shape_pixel(bit);
+        }
+    }
+    (image.hot_x, image.hot_y) = HOTSPOTS[shape];
+}
+
+/// The output showing desktop point (x, y), and the point's position on it.
+fn locate(layout: &DisplayLayout, x: u32, y: u32) -> Option<(usize, u32, u32)> {
+    layout.scanouts[..layout.count]
+        .iter()
+        .enumerate()
+        .find(|(_, s)| s.active && x >= s.x && x < s.x + s.width && y >= s.y && y < s.y + s.height)
+        .map(|(id, s)| (id, x - s.x, y - s.y))
+}
+
+/// Show the cursor at desktop position (x, y). Called from the render thread
+/// on every pass; sends nothing unless the position or image changed.
+pub fn update(x: u32, y: u32) {
+    if !hardware_active() {
+        return;
+    }
+    let Some(mut plane) = PLANE.try_lock() else {
+        return;
+    };
+    let Some(layout) = super::gpu_display::current() else {
+        return;
+    };
+    let Some((scanout, sx, sy)) = locate(&layout, x, y) else {
+        return;
+    };
+    let image_gen = IMAGE_GEN.load(Ordering::Acquire);
+
+    if image_gen != plane.image_gen {
+        render_current(&mut plane.image);
+        if let Err(e) = transport_upload(&plane.image.pixels) {
+            crate::serial_println!("[virtio-gpu] cursor upload failed: {}", e);
+            return;
+        }
+    }
+
+    // Like Linux, the position sent is the image's top-left corner
+    let (hot_x, hot_y) = (plane.image.hot_x, plane.image.hot_y);
+    let cx = sx as i32 - hot_x as i32;
+    let cy = sy as i32 - hot_y as i32;
+
+    let result = if image_gen != plane.image_gen || plane.scanout != Some(scanout) {
+        if let Some(old) = plane.scanout.filter(|&old| old != scanout) {
+            let _ = transport_update(old as u32, 0, 0, (0, 0), false);
+        }
+        transport_update(scanout as u32, cx, cy, (hot_x, hot_y), true)
+    } else if (cx, cy) != (plane.x, plane.y) {
+        transport_move(scanout as u32, cx, cy)
+    } else {
+        return;
+    };
+
+    // On failure (e.g. the GPU was busy) the next pass retries
+    if result.is_ok() {
+        plane.image_gen = image_gen;
+        plane.scanout = Some(scanout);
+        plane.x = cx;
+        plane.y = cy;
+    }
+}
+
+fn transport_upload(pixels: &[u32; CURSOR_PIXELS]) -> Result<(), &'static str> {
+    if super::gpu_pci::is_initialized() {
+        super::gpu_pci::upload_cursor(pixels)
+    } else {
+        super::gpu_mmio::upload_cursor(pixels)
+    }
+}
+
+fn transport_update(
+    scanout: u32,
+    x: i32,
+    y: i32,
+    hot: (u32, u32),
+    visible: bool,
+) -> Result<(), &'static str> {
+    if super::gpu_pci::is_initialized() {
+        super::gpu_pci::update_cursor(scanout, x, y, hot, visible)
+    } else {
+        super::gpu_mmio::update_cursor(scanout, x, y, hot, visible)
+    }
+}
+
+fn transport_move(scanout: u32, x: i32, y: i32) -> Result<(), &'static str> {
+    if super::gpu_pci::is_initialized() {
+        super::gpu_pci::move_cursor(scanout, x, y)
+    } else {
+        super::gpu_mmio::move_cursor(scanout, x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::gpu_display::HostMode;
+    use super::*;
+
+    fn blank_image() -> CursorImage {
+        CursorImage {
+            pixels: [0xDEAD_BEEF; CURSOR_PIXELS],
+            hot_x: 9,
+            hot_y: 9,
+        }
+    }
+
+    fn two_outputs() -> DisplayLayout {
+        let connected = |width, height| HostMode {
+            width,
+            height,
+            enabled: true,
+        };
+        let modes = [connected(1024, 768), connected(800, 600)];
+        DisplayLayout::for_boot(&modes, (1024, 768), 4096 * 2160)
+    }
+
+    #[test]
+    fn test_copy_image_pads_rows_to_cursor_size() {
+        let mut image = blank_image();
+        let pixels = [1, 2, 3, 4, 5, 6];
+        assert_eq!(copy_image(&mut image, &pixels, 3, 2, 2, 1), Ok(()));
+        assert_eq!(image.pixels[..4], [1, 2, 3, 0]);
+        let second_row = CURSOR_SIZE as usize;
+        assert_eq!(image.pixels[second_row..second_row + 4], [4, 5, 6, 0]);
+        assert!(
+            image.pixels[2 * second_row..].iter().all(|&p| p == 0),
+            "rows below the image should be transparent"
+        );
+        assert_eq!((image.hot_x, image.hot_y), (2, 1));
+    }
+
+    #[test]
+    fn test_copy_image_accepts_full_size_image() {
+        let mut image = blank_image();
+        let pixels = [0xFF00_00FF; CURSOR_PIXELS];
+        let max = CURSOR_SIZE - 1;
+        assert_eq!(
+            copy_image(&mut image, &pixels, CURSOR_SIZE, CURSOR_SIZE, max, max),
+            Ok(())
+        );
+        assert!(image.pixels.iter().all(|&p| p == 0xFF00_00FF));
+    }
+
+    #[test]
+    fn test_copy_image_rejects_bad_size() {
+        let mut image = blank_image();
+        let pixels = [0; CURSOR_PIXELS + 64];
+        assert_eq!(
+            copy_image(&mut image, &pixels, 0, 16, 0, 0),
+            Err("cursor image size")
+        );
+        assert_eq!(
+            copy_image(&mut image, &pixels, CURSOR_SIZE + 1, 16, 0, 0),
+            Err("cursor image size")
+        );
+        assert_eq!(
+            copy_image(&mut image, &pixels, 16, CURSOR_SIZE + 1, 0, 0),
+            Err("cursor image size")
+        );
+    }
+
+    #[test]
+    fn test_copy_image_rejects_bad_hotspot_and_short_buffer() {
+        let mut image = blank_image();
+        let pixels = [7; 16 * 16];
+        assert_eq!(
+            copy_image(&mut image, &pixels, 16, 16, 16, 0),
+            Err("invalid cursor image")
+        );
+        assert_eq!(
+            copy_image(&mut image, &pixels, 16, 16, 0, 16),
+            Err("invalid cursor image")
+        );
+        assert_eq!(
+            copy_image(&mut image, &pixels[..255], 16, 16, 0, 0),
+            Err("invalid cursor image")
+        );
+        assert!(
+            image.pixels.iter().all(|&p| p == 0xDEAD_BEEF),
+            "a rejected image should leave the current one alone"
+        );
+        assert_eq!((image.hot_x, image.hot_y), (9, 9));
+    }
+
+    #[test]
+    fn test_locate_maps_desktop_to_output() {
+        let layout = two_outputs();
+        assert_eq!(locate(&layout, 10, 20), Some((0, 10, 20)));
+        assert_eq!(locate(&layout, 1023, 767), Some((0, 1023, 767)));
+        assert_eq!(locate(&layout, 1024, 0), Some((1, 0, 0)));
+        assert_eq!(locate(&layout, 1030, 599), Some((1, 6, 599)));
+    }
+
+    #[test]
+    fn test_locate_outside_every_output() {
+        let layout = two_outputs();
+        // Below the shorter second output, and past the right of the desktop
+        assert_eq!(locate(&layout, 1030, 600), None);
+        assert_eq!(locate(&layout, 1824, 10), None);
+    }
+
+    #[test]
+    fn test_locate_skips_inactive_and_prefers_first_of_mirrors() {
+        let mut layout = two_outputs();
+        layout.set_mode(1, 0, 0).unwrap();
+        assert_eq!(locate(&layout, 1030, 10), None);
+
+        // Too wide to fit beside the first, the second output mirrors it
+        layout.set_mode(1, 1024, 768).unwrap();
+        assert_eq!(layout.scanouts[1].x, 0);
+        assert_eq!(locate(&layout, 10, 20), Some((0, 10, 20)));
+        assert_eq!(locate(&layout, 1030, 10), None);
+    }
+}
@@ -0,0 +1,393 @@
+//! Display layout shared by the VirtIO GPU transports (MMIO and PCI).
+//!
+//! Each driver keeps one desktop resource, sized at init to hold every output
+//! connected at boot side by side. A scanout shows its own rectangle of that
+//! resource — SET_SCANOUT takes a source rect — so outputs are placed left to
+//! right in scanout order and the compositor renders a single desktop.
+//!
+//! Runtime mode setting resizes a scanout's rectangle within the desktop. The
+//! desktop itself, and everything sized from it (FbInfo, the compositor
+//! texture, pointer scaling), never changes after init. An output that no
+//! longer fits beside the others (e.g. one hotplugged after boot) mirrors the
+//! start of the desktop instead.
+//!
+//! Display hotplug (VIRTIO_GPU_EVENT_DISPLAY) is noted here from the config
+//! interrupt or by polling; the display list is re-queried from the device
+//! the next time it is read in process context, since GPU commands sleep.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// VIRTIO_GPU_MAX_SCANOUTS
+pub const MAX_SCANOUTS: usize = 16;
+
+/// One display output.
+#[derive(Clone, Copy, Default)]
+pub struct Scanout {
+    /// The host reports a display attached to this output.
+    pub connected: bool,
+    /// The output is shown (SET_SCANOUT with the desktop resource).
+    pub active: bool,
+    /// The display's own size, from GET_DISPLAY_INFO.
+    pub preferred_width: u32,
+    pub preferred_height: u32,
+    /// Rectangle of the desktop the output shows; its size is the mode.
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Mode of each output as reported by GET_DISPLAY_INFO.
+#[derive(Clone, Copy, Default)]
+pub struct HostMode {
+    pub width: u32,
+    pub height: u32,
+    pub enabled: bool,
+}
+
+/// The outputs of one GPU and where each sits in the desktop.
+#[derive(Clone, Copy)]
+pub struct DisplayLayout {
+    pub scanouts: [Scanout; MAX_SCANOUTS],
+    /// Number of outputs the device has (num_scanouts).
+    pub count: usize,
+    pub desktop_width: u32,
+    pub desktop_height: u32,
+}
+
+impl DisplayLayout {
+    pub const fn empty() -> Self {
+        Self {
+            scanouts: [Scanout {
+                connected: false,
+                active: false,
+                preferred_width: 0,
+                preferred_height: 0,
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+            }; MAX_SCANOUTS],
+            count: 0,
+            desktop_width: 0,
+            desktop_height: 0,
+        }
+    }
+
+    /// Lay out the outputs connected at init. `primary` is the mode chosen
+    /// for the first connected output (it may differ from what the host
+    /// prefers); the others use their preferred modes. The desktop holds
+    /// the primary and as many of the others beside it as keep it within
+    /// `max_pixels` (the framebuffer backing); the rest mirror.
+    pub fn for_boot(modes: &[HostMode], primary: (u32, u32), max_pixels: usize) -> Self {
+        let mut layout = Self::empty();
+        layout.count = modes.len().min(MAX_SCANOUTS);
+        let (mut desk_w, mut desk_h) = (0u32, 0u32);
+        let mut have_primary = false;
+        for (i, mode) in modes.iter().take(MAX_SCANOUTS).enumerate() {
+            let s = &mut layout.scanouts[i];
+            s.connected = mode.enabled;
+            s.preferred_width = mode.width;
+            s.preferred_height = mode.height;
+            if !mode.enabled {
+                continue;
+            }
+            let (w, h) = if have_primary {
+                (mode.width, mode.height)
+            } else {
+                primary
+            };
+            if w == 0 || h == 0 {
+                continue;
+            }
+            s.width = w;
+            s.height = h;
+            let grown = (desk_w + w) as usize * desk_h.max(h) as usize;
+            if !have_primary || grown <= max_pixels {
+                desk_w += w;
+                desk_h = desk_h.max(h);
+            }
+            have_primary = true;
+        }
+        if !have_primary {
+            // The host reported no display: drive scanout 0 at `primary`
+            layout.count = layout.count.max(1);
+            let s = &mut layout.scanouts[0];
+            s.connected = true;
+            s.width = primary.0;
+            s.height = primary.1;
+            desk_w = primary.0;
+            desk_h = primary.1;
+        }
+        layout.desktop_width = desk_w;
+        layout.desktop_height = desk_h;
+        layout.arrange();
+        layout
+    }
+
+    /// Place the outputs that have a mode left to right in scanout order. An
+    /// output that does not fit in the remaining width mirrors the left of
+    /// the desktop.
+    pub fn arrange(&mut self) {
+        let (desk_w, desk_h) = (self.desktop_width, self.desktop_height);
+        let mut next_x = 0u32;
+        for s in self.scanouts.iter_mut().take(self.count) {
+            s.active = s.connected && s.width > 0 && s.height > 0;
+            if !s.active {
+                (s.x, s.y) = (0, 0);
+                continue;
+            }
+            s.width = s.width.min(desk_w);
+            s.height = s.height.min(desk_h);
+            if next_x + s.width <= desk_w {
+                s.x = next_x;
+                next_x += s.width;
+            } else {
+                s.x = 0;
+            }
+            s.y = 0;
+        }
+    }
+
+    /// Set the mode of output `id`; 0x0 turns it off. The mode must fit in
+    /// the desktop, and at least one output must stay on.
+    pub fn set_mode(&mut self, id: usize, width: u32, height: u32) -> Result<(), &'static str> {
+        if id >= self.count {
+            return Err("no such scanout");
+        }
+        if !self.scanouts[id].connected {
+            return Err("no display on scanout");
+        }
+        if (width == 0) != (height == 0) {
+            return Err("invalid mode");
+        }
+        if width > self.desktop_width || height > self.desktop_height {
+            return Err("mode larger than the desktop");
+        }
+        let others_on = self.scanouts[..self.count]
+            .iter()
+            .enumerate()
+            .any(|(i, s)| i != id && s.active);
+        if width == 0 && !others_on {
+            return Err("cannot turn off the last display");
+        }
+        self.scanouts[id].width = width;
+        self.scanouts[id].height = height;
+        self.arrange();
+        Ok(())
+    }
+
+    /// Take the modes reported after a display event. Newly connected
+    /// outputs come up at their preferred mode (clamped to the desktop);
+    /// outputs that stay connected keep the mode they have.
+    pub fn update_from_host(&mut self, modes: &[HostMode]) {
+        for (i, mode) in modes.iter().take(self.count).enumerate() {
+            let s = &mut self.scanouts[i];
+            if mode.enabled && !s.connected {
+                s.width = mode.width;
+                s.height = mode.height;
+            }
+            if !mode.enabled {
+                s.width = 0;
+                s.height = 0;
+            }
+            s.connected = mode.enabled;
+            s.preferred_width = mode.width;
+            s.preferred_height = mode.height;
+        }
+        self.arrange();
+    }
+}
+
+/// A display event arrived and the modes have not been re-read yet.
+static HOTPLUG_PENDING: AtomicBool = AtomicBool::new(false);
+/// The layout changed since the compositor last looked.
+static LAYOUT_CHANGED: AtomicBool = AtomicBool::new(false);
+
+/// Note a VIRTIO_GPU_EVENT_DISPLAY. Safe from interrupt context.
+pub fn note_hotplug() {
+    HOTPLUG_PENDING.store(true, Ordering::Release);
+    LAYOUT_CHANGED.store(true, Ordering::Release);
+}
+
+/// Note a layout change made by a mode set.
+pub fn note_layout_changed() {
+    LAYOUT_CHANGED.store(true, Ordering::Release);
+}
+
+/// Whether the layout changed since the last call. Used by compositor_wait.
+pub fn take_layout_changed() -> bool {
+    // The MMIO transport has no config interrupt wired; poll its event
+    // register here instead.
+    if super::gpu_mmio::display_event_pending() {
+        note_hotplug();
+    }
+    LAYOUT_CHANGED.swap(false, Ordering::AcqRel)
+}
+
+fn take_hotplug() -> bool {
+    HOTPLUG_PENDING.swap(false, Ordering::AcqRel)
+}
+
+/// The display layout of the active GPU, re-read from the device first if a
+/// display event is pending. Must be called from process context.
+pub fn layout() -> Option<DisplayLayout> {
+    if super::gpu_pci::is_initialized() {
+        if take_hotplug() {
+            if let Err(e) = super::gpu_pci::refresh_displays() {
+                crate::serial_println!("[virtio-gpu] display refresh failed: {}", e);
+            }
+        }
+        super::gpu_pci::display_layout()
+    } else {
+        if take_hotplug() {
+            if let Err(e) = super::gpu_mmio::refresh_displays() {
+                crate::serial_println!("[virtio-gpu] display refresh failed: {}", e);
+            }
+        }
+        super::gpu_mmio::display_layout()
+    }
+}
+
+/// The display layout of the active GPU as last read, without going to the
+/// device. Safe from any context.
+pub fn current() -> Option<DisplayLayout> {
+    if super::gpu_pci::is_initialized() {
+        super::gpu_pci::display_layout()
+    } else {
+        super::gpu_mmio::display_layout()
+    }
+}
+
+/// Set the mode of output `id` on the active GPU; 0x0 turns it off.
+pub fn set_mode(id: usize, width: u32, height: u32) -> Result<(), &'static str> {
+    if super::gpu_pci::is_initialized() {
+        super::gpu_pci::set_display_mode(id, width, height)?;
+    } else {
+        super::gpu_mmio::set_display_mode(id, width, height)?;
+    }
+    note_layout_changed();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connected(width: u32, height: u32) -> HostMode {
+        HostMode {
+            width,
+            height,
+            enabled: true,
+        }
+    }
+
+    fn unplugged() -> HostMode {
+        HostMode::default()
+    }
+
+    fn rect(s: &Scanout) -> (u32, u32, u32, u32) {
+        (s.x, s.y, s.width, s.height)
+    }
+
+    #[test]
+    fn test_for_boot_places_outputs_side_by_side() {
+        let modes = [connected(1024, 768), connected(800, 600)];
+        let layout = DisplayLayout::for_boot(&modes, (1024, 768), 4096 * 2160);
+        assert_eq!(layout.count, 2);
+        assert_eq!((layout.desktop_width, layout.desktop_height), (1824, 768));
+        assert!(layout.scanouts[0].active && layout.scanouts[1].active);
+        assert_eq!(rect(&layout.scanouts[0]), (0, 0, 1024, 768));
+        assert_eq!(rect(&layout.scanouts[1]), (1024, 0, 800, 600));
+    }
+
+    #[test]
+    fn test_for_boot_primary_mode_overrides_host_preference() {
+        let modes = [connected(1920, 1080)];
+        let layout = DisplayLayout::for_boot(&modes, (1280, 800), 4096 * 2160);
+        assert_eq!((layout.desktop_width, layout.desktop_height), (1280, 800));
+        assert_eq!(rect(&layout.scanouts[0]), (0, 0, 1280, 800));
+        assert_eq!(layout.scanouts[0].preferred_width, 1920);
+    }
+
+    #[test]
+    fn test_for_boot_mirrors_output_beyond_max_pixels() {
+        let modes = [connected(1024, 768), connected(800, 600)];
+        let layout = DisplayLayout::for_boot(&modes, (1024, 768), 1024 * 768);
+        assert_eq!((layout.desktop_width, layout.desktop_height), (1024, 768));
+        assert!(
+            layout.scanouts[1].active,
+            "the extra output should mirror, not go dark"
+        );
+        assert_eq!(rect(&layout.scanouts[1]), (0, 0, 800, 600));
+    }
+
+    #[test]
+    fn test_for_boot_without_displays_drives_scanout_zero() {
+        let layout = DisplayLayout::for_boot(&[], (1280, 800), 4096 * 2160);
+        assert_eq!(layout.count, 1);
+        assert!(layout.scanouts[0].active);
+        assert_eq!(rect(&layout.scanouts[0]), (0, 0, 1280, 800));
+    }
+
+    #[test]
+    fn test_set_mode_rearranges_outputs() {
+        let modes = [connected(1024, 768), connected(800, 600)];
+        let mut layout = DisplayLayout::for_boot(&modes, (1024, 768), 4096 * 2160);
+        assert_eq!(layout.set_mode(0, 640, 480), Ok(()));
+        assert_eq!(rect(&layout.scanouts[0]), (0, 0, 640, 480));
+        assert_eq!(rect(&layout.scanouts[1]), (640, 0, 800, 600));
+    }
+
+    #[test]
+    fn test_set_mode_rejects_mode_larger_than_desktop() {
+        let mut layout = DisplayLayout::for_boot(&[connected(1024, 768)], (1024, 768), 4096 * 2160);
+        assert_eq!(
+            layout.set_mode(0, 1280, 800),
+            Err("mode larger than the desktop")
+        );
+        assert_eq!(layout.set_mode(0, 1024, 0), Err("invalid mode"));
+        assert_eq!(layout.set_mode(1, 800, 600), Err("no such scanout"));
+        assert_eq!(rect(&layout.scanouts[0]), (0, 0, 1024, 768));
+    }
+
+    #[test]
+    fn test_set_mode_refuses_to_turn_off_last_display() {
+        let modes = [connected(1024, 768), connected(800, 600)];
+        let mut layout = DisplayLayout::for_boot(&modes, (1024, 768), 4096 * 2160);
+        assert_eq!(layout.set_mode(1, 0, 0), Ok(()));
+        assert!(!layout.scanouts[1].active);
+        assert_eq!(
+            layout.set_mode(0, 0, 0),
+            Err("cannot turn off the last display")
+        );
+        assert!(layout.scanouts[0].active);
+        assert_eq!(rect(&layout.scanouts[0]), (0, 0, 1024, 768));
+    }
+
+    #[test]
+    fn test_update_from_host_brings_up_hotplugged_output_at_preferred_mode() {
+        let boot = [connected(1024, 768), unplugged()];
+        let mut layout = DisplayLayout::for_boot(&boot, (1024, 768), 4096 * 2160);
+        assert_eq!(layout.set_mode(1, 800, 600), Err("no display on scanout"));
+
+        // The connected output keeps its mode even though the host now prefers another
+        layout.update_from_host(&[connected(1280, 800), connected(800, 600)]);
+        assert_eq!(rect(&layout.scanouts[0]), (0, 0, 1024, 768));
+        assert!(layout.scanouts[1].active);
+        // The desktop is sized at boot, so the new output mirrors its start
+        assert_eq!(rect(&layout.scanouts[1]), (0, 0, 800, 600));
+
+        layout.update_from_host(&[connected(1280, 800), unplugged()]);
+        assert!(!layout.scanouts[1].connected && !layout.scanouts[1].active);
+    }
+
+    #[test]
+    fn test_update_from_host_clamps_hotplugged_output_to_desktop() {
+        let boot = [connected(1024, 768), unplugged()];
+        let mut layout = DisplayLayout::for_boot(&boot, (1024, 768), 4096 * 2160);
+        layout.update_from_host(&[connected(1024, 768), connected(1920, 1080)]);
+        assert_eq!(rect(&layout.scanouts[1]), (0, 0, 1024, 768));
+        assert_eq!(layout.scanouts[1].preferred_width, 1920);
+    }
+}
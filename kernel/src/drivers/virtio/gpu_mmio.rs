@@ -3,6 +3,8 @@
 //! Implements a basic GPU/display driver using VirtIO MMIO transport.
 //! Provides framebuffer functionality for simple 2D graphics.
 
+use super::gpu_cursor::{CURSOR_PIXELS, CURSOR_SIZE};
+use super::gpu_display::{DisplayLayout, HostMode, MAX_SCANOUTS};
 use super::mmio::{
     device_id, VirtioMmioDevice, VIRTIO_MMIO_BASE, VIRTIO_MMIO_COUNT, VIRTIO_MMIO_SIZE,
 };
@@ -29,6 +31,10 @@ mod cmd {
     pub const RESOURCE_ATTACH_BACKING: u32 = 0x0106;
     pub const RESOURCE_DETACH_BACKING: u32 = 0x0107;
 
+    // Cursor commands (cursor queue)
+    pub const UPDATE_CURSOR: u32 = 0x0300;
+    pub const MOVE_CURSOR: u32 = 0x0301;
+
     // Response types
     pub const RESP_OK_NODATA: u32 = 0x1100;
     pub const RESP_OK_DISPLAY_INFO: u32 = 0x1101;
@@ -154,6 +160,28 @@ struct VirtioGpuResourceFlush {
     padding: u32,
 }
 
+/// Cursor position on one scanout
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct VirtioGpuCursorPos {
+    scanout_id: u32,
+    x: u32,
+    y: u32,
+    padding: u32,
+}
+
+/// Update/move cursor command (UPDATE_CURSOR and MOVE_CURSOR share it)
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct VirtioGpuUpdateCursor {
+    hdr: VirtioGpuCtrlHdr,
+    pos: VirtioGpuCursorPos,
+    resource_id: u32,
+    hot_x: u32,
+    hot_y: u32,
+    padding: u32,
+}
+
 /// Virtqueue descriptor
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
@@ -166,6 +194,8 @@ struct VirtqDesc {
 
 const DESC_F_NEXT: u16 = 1;
 const DESC_F_WRITE: u16 = 2;
+/// Avail ring flag: no used-buffer interrupts (the cursor queue is polled)
+const VIRTQ_AVAIL_F_NO_INTERRUPT: u16 = 1;
 
 /// Available ring
 #[repr(C)]
@@ -221,6 +251,27 @@ static mut CTRL_QUEUE: CtrlQueueMemory = CtrlQueueMemory {
     },
 };
 
+/// Cursor queue (queue 1), for UPDATE_CURSOR / MOVE_CURSOR
+static mut CURSOR_QUEUE: CtrlQueueMemory = CtrlQueueMemory {
+    desc: [VirtqDesc {
+        addr: 0,
+        len: 0,
+        flags: 0,
+        next: 0,
+    }; 16],
+    avail: VirtqAvail {
+        flags: 0,
+        idx: 0,
+        ring: [0; 16],
+    },
+    _padding: [0; 4096 - 256 - 36],
+    used: VirtqUsed {
+        flags: 0,
+        idx: 0,
+        ring: [VirtqUsedElem { id: 0, len: 0 }; 16],
+    },
+};
+
 // Command/response buffers
 #[repr(C, align(64))]
 struct CmdBuffer {
@@ -230,6 +281,38 @@ struct CmdBuffer {
 static mut CMD_BUF: CmdBuffer = CmdBuffer { data: [0; 512] };
 static mut RESP_BUF: CmdBuffer = CmdBuffer { data: [0; 512] };
 
+/// Cursor commands have no response; each descriptor slot keeps its own
+/// command until the device has consumed it.
+static mut CURSOR_CMDS: [VirtioGpuUpdateCursor; 16] = [VirtioGpuUpdateCursor {
+    hdr: VirtioGpuCtrlHdr {
+        type_: 0,
+        flags: 0,
+        fence_id: 0,
+        ctx_id: 0,
+        padding: 0,
+    },
+    pos: VirtioGpuCursorPos {
+        scanout_id: 0,
+        x: 0,
+        y: 0,
+        padding: 0,
+    },
+    resource_id: 0,
+    hot_x: 0,
+    hot_y: 0,
+    padding: 0,
+}; 16];
+
+/// Backing of the 64x64 cursor image resource
+#[repr(C, align(4096))]
+struct CursorImage {
+    pixels: [u32; CURSOR_PIXELS],
+}
+
+static mut CURSOR_IMAGE: CursorImage = CursorImage {
+    pixels: [0; CURSOR_PIXELS],
+};
+
 // Default framebuffer dimensions (used when fw_cfg doesn't specify)
 const DEFAULT_FB_WIDTH: u32 = 1280;
 const DEFAULT_FB_HEIGHT: u32 = 800;
@@ -239,6 +322,13 @@ const FB_MAX_HEIGHT: u32 = 1200;
 const FB_SIZE: usize = (FB_MAX_WIDTH * FB_MAX_HEIGHT * 4) as usize;
 const BYTES_PER_PIXEL: usize = 4;
 const RESOURCE_ID: u32 = 1;
+const CURSOR_RESOURCE_ID: u32 = 2;
+
+// Device config space (struct virtio_gpu_config)
+const GPU_CFG_EVENTS_READ: usize = 0;
+const GPU_CFG_EVENTS_CLEAR: usize = 4;
+const GPU_CFG_NUM_SCANOUTS: usize = 8;
+const VIRTIO_GPU_EVENT_DISPLAY: u32 = 1 << 0;
 
 // T39 bisect: dead statics intentionally added to measure whether even
 // pure BSS additions to gpu_mmio.rs perturb the Parallels CPU0 guard.
@@ -381,10 +471,14 @@ static mut GPU_DEVICE: Option<GpuDeviceState> = None;
 
 struct GpuDeviceState {
     base: u64,
+    /// Desktop size: the framebuffer resource spanning all outputs
     width: u32,
     height: u32,
     resource_id: u32,
     last_used_idx: u16,
+    layout: DisplayLayout,
+    /// The device has a cursor queue
+    cursor_queue: bool,
 }
 
 #[inline(always)]
@@ -465,6 +559,32 @@ fn init_device(device: &mut VirtioMmioDevice, base: u64, _slot: u32) -> Result<(
         device.set_queue_ready(true);
     }
 
+    // Set up cursor queue (queue 1). Cursor commands are fire-and-forget, so
+    // the queue runs without interrupts and is polled for free slots.
+    device.select_queue(1);
+    let cursor_queue_max = device.get_queue_num_max();
+    let cursor_queue = cursor_queue_max > 0;
+    if cursor_queue {
+        device.set_queue_num(core::cmp::min(cursor_queue_max, 16));
+        let cursor_queue_phys = virt_to_phys(&raw const CURSOR_QUEUE as u64);
+        unsafe {
+            let queue_ptr = &raw mut CURSOR_QUEUE;
+            (*queue_ptr).avail.flags = VIRTQ_AVAIL_F_NO_INTERRUPT;
+            (*queue_ptr).avail.idx = 0;
+            (*queue_ptr).used.flags = 0;
+            (*queue_ptr).used.idx = 0;
+        }
+        if version == 1 {
+            device.set_queue_align(4096);
+            device.set_queue_pfn((cursor_queue_phys / 4096) as u32);
+        } else {
+            device.set_queue_desc(cursor_queue_phys);
+            device.set_queue_avail(cursor_queue_phys + 256);
+            device.set_queue_used(cursor_queue_phys + 4096);
+            device.set_queue_ready(true);
+        }
+    }
+
     // Mark device ready
     device.driver_ok();
 
@@ -478,31 +598,64 @@ fn init_device(device: &mut VirtioMmioDevice, base: u64, _slot: u32) -> Result<(
             height: init_h,
             resource_id: RESOURCE_ID,
             last_used_idx: 0,
+            layout: DisplayLayout::empty(),
+            cursor_queue,
         });
     }
 
-    // Determine display dimensions:
+    // Clear any display event raised before we were listening
+    let events = device.read_config_u32(GPU_CFG_EVENTS_READ);
+    if events & VIRTIO_GPU_EVENT_DISPLAY != 0 {
+        device.write_config_u32(GPU_CFG_EVENTS_CLEAR, VIRTIO_GPU_EVENT_DISPLAY);
+    }
+    let num_scanouts = (device.read_config_u32(GPU_CFG_NUM_SCANOUTS) as usize).clamp(1, MAX_SCANOUTS);
+    let modes = query_modes().unwrap_or_else(|e| {
+        crate::serial_println!("[virtio-gpu] GET_DISPLAY_INFO failed: {}", e);
+        [HostMode::default(); MAX_SCANOUTS]
+    });
+
+    // Determine the primary display's mode:
     // fw_cfg resolution takes priority over GET_DISPLAY_INFO
-    let (width, height) = {
+    let primary = {
         let (req_w, req_h) = configured_resolution();
         if req_w != DEFAULT_FB_WIDTH || req_h != DEFAULT_FB_HEIGHT {
             // fw_cfg provided a custom resolution — use it
             crate::serial_println!("[virtio-gpu] Display: {}x{} (from fw_cfg)", req_w, req_h);
             (req_w, req_h)
         } else {
-            // No fw_cfg override — query the GPU
-            let (gpu_w, gpu_h) = get_display_info()?;
+            // No fw_cfg override — the first enabled display's mode
+            let (gpu_w, gpu_h) = modes[..num_scanouts]
+                .iter()
+                .find(|m| m.enabled && m.width > 0 && m.height > 0)
+                .map(|m| (m.width.min(FB_MAX_WIDTH), m.height.min(FB_MAX_HEIGHT)))
+                .unwrap_or_else(configured_resolution);
             crate::serial_println!("[virtio-gpu] Display: {}x{}", gpu_w, gpu_h);
             (gpu_w, gpu_h)
         }
     };
 
+    // The framebuffer resource spans every output, side by side
+    let layout = DisplayLayout::for_boot(&modes[..num_scanouts], primary, FB_SIZE / BYTES_PER_PIXEL);
+    for (i, s) in layout.scanouts[..layout.count].iter().enumerate() {
+        if s.active {
+            crate::serial_println!(
+                "[virtio-gpu] Scanout {}: {}x{} at {},{}",
+                i,
+                s.width,
+                s.height,
+                s.x,
+                s.y
+            );
+        }
+    }
+
     // Update state with final dimensions
     unsafe {
         let ptr = &raw mut GPU_DEVICE;
         if let Some(ref mut state) = *ptr {
-            state.width = width;
-            state.height = height;
+            state.width = layout.desktop_width;
+            state.height = layout.desktop_height;
+            state.layout = layout;
         }
     }
 
@@ -512,11 +665,18 @@ fn init_device(device: &mut VirtioMmioDevice, base: u64, _slot: u32) -> Result<(
     set_scanout()?;
     flush()?;
 
+    // The pointer falls back to software drawing without a cursor plane
+    match init_cursor() {
+        Ok(()) => super::gpu_cursor::set_plane_ready(),
+        Err(e) => crate::serial_println!("[virtio-gpu] No hardware cursor: {}", e),
+    }
+
     crate::serial_println!("[virtio-gpu] GPU device initialized successfully");
     Ok(())
 }
 
-fn get_display_info() -> Result<(u32, u32), &'static str> {
+/// Query the mode of every scanout (GET_DISPLAY_INFO).
+fn query_modes() -> Result<[HostMode; MAX_SCANOUTS], &'static str> {
     with_device_state(|device, state| {
         // Prepare GET_DISPLAY_INFO command
         let cmd_phys = virt_to_phys(&raw const CMD_BUF as u64);
@@ -553,15 +713,15 @@ fn get_display_info() -> Result<(u32, u32), &'static str> {
                 return Err("GET_DISPLAY_INFO failed");
             }
 
-            // Find first enabled display
-            for pmode in &resp.pmodes {
-                if pmode.enabled != 0 {
-                    return Ok((pmode.r_width, pmode.r_height));
-                }
+            let mut modes = [HostMode::default(); MAX_SCANOUTS];
+            for (mode, pmode) in modes.iter_mut().zip(resp.pmodes.iter()) {
+                *mode = HostMode {
+                    width: pmode.r_width,
+                    height: pmode.r_height,
+                    enabled: pmode.enabled != 0,
+                };
             }
-
-            // Default if no display found — use configured resolution
-            Ok(configured_resolution())
+            Ok(modes)
         }
     })
 }
@@ -747,8 +907,15 @@ fn attach_backing() -> Result<(), &'static str> {
     })
 }
 
+/// Point every output at its rectangle of the framebuffer resource, and
+/// turn off the outputs that are not shown.
 fn set_scanout() -> Result<(), &'static str> {
-    with_device_state(|device, state| {
+    with_device_state(|device, state| apply_layout(device, state))
+}
+
+fn apply_layout(device: &VirtioMmioDevice, state: &mut GpuDeviceState) -> Result<(), &'static str> {
+    let layout = state.layout;
+    for (id, scanout) in layout.scanouts[..layout.count].iter().enumerate() {
         unsafe {
             let cmd_ptr = &raw mut CMD_BUF;
             let cmd = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut VirtioGpuSetScanout);
@@ -760,20 +927,22 @@ fn set_scanout() -> Result<(), &'static str> {
                     ctx_id: 0,
                     padding: 0,
                 },
-                r_x: 0,
-                r_y: 0,
-                r_width: state.width,
-                r_height: state.height,
-                scanout_id: 0,
-                resource_id: state.resource_id,
+                r_x: scanout.x,
+                r_y: scanout.y,
+                r_width: scanout.width,
+                r_height: scanout.height,
+                scanout_id: id as u32,
+                // Resource 0 disables the scanout
+                resource_id: if scanout.active { state.resource_id } else { 0 },
             };
         }
         send_command_expect_ok(
             device,
             state,
             core::mem::size_of::<VirtioGpuSetScanout>() as u32,
-        )
-    })
+        )?;
+    }
+    Ok(())
 }
 
 fn transfer_to_host(
@@ -854,6 +1023,183 @@ fn resource_flush(
     )
 }
 
+/// Create the 64x64 cursor image resource and attach its backing.
+fn init_cursor() -> Result<(), &'static str> {
+    with_device_state(|device, state| {
+        if !state.cursor_queue {
+            return Err("no cursor queue");
+        }
+        unsafe {
+            let cmd_ptr = &raw mut CMD_BUF;
+            let cmd = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut VirtioGpuResourceCreate2d);
+            *cmd = VirtioGpuResourceCreate2d {
+                hdr: VirtioGpuCtrlHdr {
+                    type_: cmd::RESOURCE_CREATE_2D,
+                    flags: 0,
+                    fence_id: 0,
+                    ctx_id: 0,
+                    padding: 0,
+                },
+                resource_id: CURSOR_RESOURCE_ID,
+                format: format::B8G8R8A8_UNORM,
+                width: CURSOR_SIZE,
+                height: CURSOR_SIZE,
+            };
+        }
+        send_command_expect_ok(
+            device,
+            state,
+            core::mem::size_of::<VirtioGpuResourceCreate2d>() as u32,
+        )?;
+
+        unsafe {
+            let cmd_ptr = &raw mut CMD_BUF;
+            let cmd = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut AttachBackingCmd);
+            *cmd = AttachBackingCmd {
+                cmd: VirtioGpuResourceAttachBacking {
+                    hdr: VirtioGpuCtrlHdr {
+                        type_: cmd::RESOURCE_ATTACH_BACKING,
+                        flags: 0,
+                        fence_id: 0,
+                        ctx_id: 0,
+                        padding: 0,
+                    },
+                    resource_id: CURSOR_RESOURCE_ID,
+                    nr_entries: 1,
+                },
+                entry: VirtioGpuMemEntry {
+                    addr: virt_to_phys(&raw const CURSOR_IMAGE as u64),
+                    length: core::mem::size_of::<CursorImage>() as u32,
+                    padding: 0,
+                },
+            };
+        }
+        send_command_expect_ok(
+            device,
+            state,
+            core::mem::size_of::<AttachBackingCmd>() as u32,
+        )
+    })
+}
+
+/// Queue a command on the cursor queue. Returns without waiting: the device
+/// takes cursor commands in order and sends no response.
+fn send_cursor_command(
+    device: &VirtioMmioDevice,
+    command: VirtioGpuUpdateCursor,
+) -> Result<(), &'static str> {
+    unsafe {
+        let queue_ptr = &raw mut CURSOR_QUEUE;
+        let avail_idx = (*queue_ptr).avail.idx;
+        let used_idx = read_volatile(&(*queue_ptr).used.idx);
+        if avail_idx.wrapping_sub(used_idx) >= 16 {
+            return Err("cursor queue full");
+        }
+
+        let slot = (avail_idx % 16) as usize;
+        let cmds_ptr = &raw mut CURSOR_CMDS;
+        (*cmds_ptr)[slot] = command;
+        (*queue_ptr).desc[slot] = VirtqDesc {
+            addr: virt_to_phys(&raw const (*cmds_ptr)[slot] as u64),
+            len: core::mem::size_of::<VirtioGpuUpdateCursor>() as u32,
+            flags: 0,
+            next: 0,
+        };
+        (*queue_ptr).avail.ring[slot] = slot as u16;
+        fence(Ordering::SeqCst);
+        (*queue_ptr).avail.idx = avail_idx.wrapping_add(1);
+        fence(Ordering::SeqCst);
+    }
+
+    device.notify_queue(1);
+    Ok(())
+}
+
+fn cursor_command(type_: u32, scanout: u32, x: i32, y: i32) -> VirtioGpuUpdateCursor {
+    VirtioGpuUpdateCursor {
+        hdr: VirtioGpuCtrlHdr {
+            type_,
+            ..Default::default()
+        },
+        pos: VirtioGpuCursorPos {
+            scanout_id: scanout,
+            // Negative when the hotspot is near the left/top edge
+            x: x as u32,
+            y: y as u32,
+            padding: 0,
+        },
+        ..Default::default()
+    }
+}
+
+/// Copy a 64x64 BGRA image into the cursor resource. It shows on the next
+/// `update_cursor`.
+pub fn upload_cursor(pixels: &[u32; CURSOR_PIXELS]) -> Result<(), &'static str> {
+    with_device_state(|device, state| {
+        unsafe {
+            let image_ptr = &raw mut CURSOR_IMAGE;
+            (*image_ptr).pixels.copy_from_slice(pixels);
+        }
+        fence(Ordering::SeqCst);
+        unsafe {
+            let cmd_ptr = &raw mut CMD_BUF;
+            let cmd = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut VirtioGpuTransferToHost2d);
+            *cmd = VirtioGpuTransferToHost2d {
+                hdr: VirtioGpuCtrlHdr {
+                    type_: cmd::TRANSFER_TO_HOST_2D,
+                    flags: 0,
+                    fence_id: 0,
+                    ctx_id: 0,
+                    padding: 0,
+                },
+                r_x: 0,
+                r_y: 0,
+                r_width: CURSOR_SIZE,
+                r_height: CURSOR_SIZE,
+                offset: 0,
+                resource_id: CURSOR_RESOURCE_ID,
+                padding: 0,
+            };
+        }
+        send_command_expect_ok(
+            device,
+            state,
+            core::mem::size_of::<VirtioGpuTransferToHost2d>() as u32,
+        )
+    })
+}
+
+/// Show the cursor image on `scanout` with its top-left at (x, y), or hide
+/// the cursor there.
+pub fn update_cursor(
+    scanout: u32,
+    x: i32,
+    y: i32,
+    hot: (u32, u32),
+    visible: bool,
+) -> Result<(), &'static str> {
+    with_device_state(|device, _state| {
+        let mut command = cursor_command(cmd::UPDATE_CURSOR, scanout, x, y);
+        if visible {
+            command.resource_id = CURSOR_RESOURCE_ID;
+            (command.hot_x, command.hot_y) = hot;
+        }
+        send_cursor_command(device, command)
+    })
+}
+
+/// Move the cursor on `scanout`. Gives up rather than wait if another GPU
+/// command is in flight; the caller retries on its next pass.
+pub fn move_cursor(scanout: u32, x: i32, y: i32) -> Result<(), &'static str> {
+    let _guard = GPU_LOCK.try_lock().ok_or("GPU busy")?;
+    let base = unsafe {
+        let ptr = &raw const GPU_DEVICE;
+        (*ptr).as_ref().ok_or("GPU device not initialized")?.base
+    };
+    let device = VirtioMmioDevice::probe(base).ok_or("Device disappeared")?;
+    send_cursor_command(&device, cursor_command(cmd::MOVE_CURSOR, scanout, x, y))
+}
+
 /// Flush the entire framebuffer to the display.
 pub fn flush() -> Result<(), &'static str> {
     with_device_state(|device, state| {
@@ -880,6 +1226,56 @@ pub fn dimensions() -> Option<(u32, u32)> {
     }
 }
 
+/// The outputs and where each shows the desktop.
+pub fn display_layout() -> Option<DisplayLayout> {
+    unsafe {
+        let ptr = &raw const GPU_DEVICE;
+        (*ptr).as_ref().map(|s| s.layout)
+    }
+}
+
+/// Whether the device raised a display event (hotplug or a host-side mode
+/// change) that has not been handled. Only reads the event register, so it
+/// is cheap enough to poll.
+pub fn display_event_pending() -> bool {
+    unsafe {
+        let ptr = &raw const GPU_DEVICE;
+        (*ptr)
+            .as_ref()
+            .and_then(|state| VirtioMmioDevice::probe(state.base))
+            .is_some_and(|device| {
+                device.read_config_u32(GPU_CFG_EVENTS_READ) & VIRTIO_GPU_EVENT_DISPLAY != 0
+            })
+    }
+}
+
+/// Re-read the outputs after a display event and re-apply the layout.
+pub fn refresh_displays() -> Result<(), &'static str> {
+    let modes = query_modes()?;
+    with_device_state(|device, state| {
+        device.write_config_u32(GPU_CFG_EVENTS_CLEAR, VIRTIO_GPU_EVENT_DISPLAY);
+        state.layout.update_from_host(&modes);
+        apply_layout(device, state)
+    })?;
+    flush()
+}
+
+/// Set the mode of output `id` (0x0 turns it off) and re-apply the layout.
+pub fn set_display_mode(id: usize, width: u32, height: u32) -> Result<(), &'static str> {
+    with_device_state(|device, state| {
+        let previous = state.layout;
+        state.layout.set_mode(id, width, height)?;
+        if let Err(e) = apply_layout(device, state) {
+            // Put back what the outputs showed before
+            state.layout = previous;
+            let _ = apply_layout(device, state);
+            return Err(e);
+        }
+        Ok(())
+    })?;
+    flush()
+}
+
 /// Get a mutable reference to the framebuffer pixels
 #[allow(dead_code)]
 pub fn framebuffer() -> Option<&'static mut [u8]> {
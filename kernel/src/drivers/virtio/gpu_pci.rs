@@ -7,6 +7,8 @@
 //! communicates via the PCI transport layer (`VirtioPciDevice` from
 //! `pci_transport.rs`) instead of MMIO registers.
 
+use super::gpu_cursor::{self, CURSOR_PIXELS, CURSOR_SIZE};
+use super::gpu_display::{DisplayLayout, HostMode, Scanout, MAX_SCANOUTS};
 use super::pci_transport::VirtioPciDevice;
 use crate::tracing::providers::virtgpu;
 use core::ptr::read_volatile;
@@ -171,6 +173,10 @@ mod cmd {
     pub const TRANSFER_FROM_HOST_3D: u32 = 0x0206;
     pub const SUBMIT_3D: u32 = 0x0207;
 
+    // Cursor commands (cursor queue)
+    pub const UPDATE_CURSOR: u32 = 0x0300;
+    pub const MOVE_CURSOR: u32 = 0x0301;
+
     // Capability commands (sequential with 2D commands)
     pub const GET_CAPSET_INFO: u32 = 0x0108;
     pub const GET_CAPSET: u32 = 0x0109;
@@ -287,6 +293,28 @@ struct VirtioGpuTransferToHost2d {
     padding: u32,
 }
 
+/// Cursor position on one scanout
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct VirtioGpuCursorPos {
+    scanout_id: u32,
+    x: u32,
+    y: u32,
+    padding: u32,
+}
+
+/// Update/move cursor command (UPDATE_CURSOR and MOVE_CURSOR share it)
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct VirtioGpuUpdateCursor {
+    hdr: VirtioGpuCtrlHdr,
+    pos: VirtioGpuCursorPos,
+    resource_id: u32,
+    hot_x: u32,
+    hot_y: u32,
+    padding: u32,
+}
+
 /// Resource flush command
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
@@ -441,6 +469,8 @@ struct VirtqDesc {
 
 const DESC_F_NEXT: u16 = 1;
 const DESC_F_WRITE: u16 = 2;
+/// Avail ring flag: no used-buffer interrupts (the cursor queue is polled)
+const VIRTQ_AVAIL_F_NO_INTERRUPT: u16 = 1;
 
 /// Available ring
 #[repr(C)]
@@ -522,6 +552,38 @@ static mut PCI_CURSOR_QUEUE: PciCtrlQueueMemory = PciCtrlQueueMemory {
     },
 };
 
+/// Cursor commands have no response; each cursor queue descriptor slot keeps
+/// its own command until the device has consumed it.
+static mut PCI_CURSOR_CMDS: [VirtioGpuUpdateCursor; 16] = [VirtioGpuUpdateCursor {
+    hdr: VirtioGpuCtrlHdr {
+        type_: 0,
+        flags: 0,
+        fence_id: 0,
+        ctx_id: 0,
+        padding: 0,
+    },
+    pos: VirtioGpuCursorPos {
+        scanout_id: 0,
+        x: 0,
+        y: 0,
+        padding: 0,
+    },
+    resource_id: 0,
+    hot_x: 0,
+    hot_y: 0,
+    padding: 0,
+}; 16];
+
+/// Backing of the 64x64 hardware cursor image resource
+#[repr(C, align(4096))]
+struct PciCursorImage {
+    pixels: [u32; CURSOR_PIXELS],
+}
+
+static mut PCI_CURSOR_IMAGE: PciCursorImage = PciCursorImage {
+    pixels: [0; CURSOR_PIXELS],
+};
+
 /// Command/response buffers
 #[repr(C, align(64))]
 struct PciCmdBuffer {
@@ -669,16 +731,18 @@ const TEST_TEX_BYTES: usize = (TEST_TEX_DIM * TEST_TEX_DIM * 4) as usize;
 
 /// Resource ID for the compositor texture (BWM uploads pixel buffers here)
 const RESOURCE_COMPOSITE_TEX_ID: u32 = 5;
-/// Resource ID for the GPU cursor texture (16x80 atlas, uploaded once at init)
+/// Resource ID for the GPU cursor texture (16x96 atlas, uploaded once at init)
 const RESOURCE_CURSOR_TEX_ID: u32 = 6;
 /// Resource ID for the fullscreen dimmer overlay texture (4x4, B8G8R8A8, translucent black)
 const RESOURCE_DIMMER_TEX_ID: u32 = 7;
+/// Resource ID for the hardware cursor image (64x64 2D resource, cursor queue)
+const RESOURCE_HW_CURSOR_ID: u32 = 8;
 /// Individual cursor shape dimensions
-const CURSOR_SHAPE_W: u32 = 16;
-const CURSOR_SHAPE_H: u32 = 16;
-const NUM_CURSOR_SHAPES: u32 = 5;
+const CURSOR_SHAPE_W: u32 = gpu_cursor::SHAPE_W;
+const CURSOR_SHAPE_H: u32 = gpu_cursor::SHAPE_H;
+const NUM_CURSOR_SHAPES: u32 = gpu_cursor::NUM_SHAPES;
 const CURSOR_TEX_W: u32 = CURSOR_SHAPE_W;
-const CURSOR_TEX_H: u32 = CURSOR_SHAPE_H * NUM_CURSOR_SHAPES; // 80
+const CURSOR_TEX_H: u32 = CURSOR_SHAPE_H * NUM_CURSOR_SHAPES; // 96
 
 // VirtIO standard feature bits
 const VIRTIO_F_VERSION_1: u64 = 1 << 32;
@@ -828,18 +892,6 @@ static CURSOR_TEX_READY: AtomicBool = AtomicBool::new(false);
 /// Whether the dimmer overlay texture has been initialized.
 static DIMMER_TEX_READY: AtomicBool = AtomicBool::new(false);
 
-/// Current cursor shape index (0=arrow, 1=NS, 2=EW, 3=NWSE, 4=NESW).
-static CURSOR_SHAPE: AtomicU32 = AtomicU32::new(0);
-
-/// Per-shape hotspot offsets (pixels from top-left of shape bitmap).
-const CURSOR_HOTSPOT: [(i32, i32); 5] = [
-    (0, 0), // Arrow: top-left
-    (7, 7), // NS resize: center
-    (7, 7), // EW resize: center
-    (7, 7), // NWSE resize: center
-    (7, 7), // NESW resize: center
-];
-
 // =============================================================================
 // Per-Window GPU Textures
 // =============================================================================
@@ -1174,7 +1226,7 @@ fn init_composite_texture(width: u32, height: u32) -> Result<(), &'static str> {
         );
     }
 
-    // Initialize cursor GPU texture (12x18 arrow bitmap, uploaded once)
+    // Initialize cursor GPU texture (the built-in shapes, uploaded once)
     init_cursor_texture()?;
 
     // Initialize dimmer overlay texture (4x4 translucent black, for launcher dimming)
@@ -1183,131 +1235,21 @@ fn init_composite_texture(width: u32, height: u32) -> Result<(), &'static str> {
     Ok(())
 }
 
-/// Set the active cursor shape. Called from the set_cursor_shape syscall.
-/// 0=arrow, 1=NS resize, 2=EW resize, 3=NWSE resize, 4=NESW resize.
-pub fn set_cursor_shape(shape: u32) {
-    if shape < NUM_CURSOR_SHAPES {
-        CURSOR_SHAPE.store(shape, Ordering::Release);
-    }
-}
-
-/// Initialize a small GPU texture containing the cursor arrow bitmap.
+/// Initialize a small GPU texture containing the built-in cursor shapes
+/// (`gpu_cursor::SHAPES`), stacked vertically.
 ///
-/// The cursor is rendered as a GPU quad in `virgl_composite_single_quad()`,
-/// sampling from this texture. This avoids stamping the cursor into COMPOSITE_TEX
-/// (which caused ghost trails when the saved background was stale).
+/// Without a hardware cursor plane, the cursor is rendered as a GPU quad in
+/// `virgl_composite_single_quad()`, sampling from this texture. This avoids
+/// stamping the cursor into COMPOSITE_TEX (which caused ghost trails when the
+/// saved background was stale).
 fn init_cursor_texture() -> Result<(), &'static str> {
     use super::virgl::{format as vfmt, pipe};
 
-    // 5 cursor shapes, each 16x16. 0=transparent, 1=white, 2=black outline.
-    // Shape 0: Arrow pointer
-    // Shape 1: NS resize (vertical double arrow)
-    // Shape 2: EW resize (horizontal double arrow)
-    // Shape 3: NWSE resize (diagonal ↘↗)
-    // Shape 4: NESW resize (diagonal ↙↗)
-    const SHAPES: [[[u8; 16]; 16]; 5] = [
-        // Shape 0: Arrow
-        [
-            [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 1, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 2, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 2, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        ],
-        // Shape 1: NS resize (vertical double arrow ↕)
-        [
-            [0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 2, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 2, 2, 1, 2, 2, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 2, 2, 1, 2, 2, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 2, 1, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        ],
-        // Shape 2: EW resize (horizontal double arrow ↔)
-        [
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0],
-            [0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0],
-            [0, 2, 1, 1, 1, 2, 2, 2, 2, 2, 2, 1, 1, 1, 2, 0],
-            [2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2],
-            [0, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 0],
-            [0, 0, 2, 1, 1, 2, 2, 2, 2, 2, 2, 1, 1, 2, 0, 0],
-            [0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0],
-            [0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        ],
-        // Shape 3: NWSE resize (diagonal ↘↗)
-        [
-            [2, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 2, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 1, 2],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 2, 2, 2, 2, 2],
-        ],
-        // Shape 4: NESW resize (diagonal ↙↗)
-        [
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 2, 2, 2, 2, 2],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 1, 2],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 1, 2, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 2, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 1, 1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [2, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        ],
-    ];
-
     let w = CURSOR_TEX_W;
     let h = CURSOR_TEX_H;
     let size = (w as usize) * (h as usize) * 4;
 
-    // Allocate page-aligned backing (16*80*4=5120 bytes, needs 2 pages)
+    // Allocate page-aligned backing (16*96*4=6144 bytes, needs 2 pages)
     let alloc_size = 8192usize;
     let layout = alloc::alloc::Layout::from_size_align(alloc_size, 4096)
         .map_err(|_| "invalid cursor texture layout")?;
@@ -1324,11 +1266,8 @@ fn init_cursor_texture() -> Result<(), &'static str> {
                 for col in 0..CURSOR_SHAPE_W as usize {
                     let tex_row = shape * CURSOR_SHAPE_H as usize + row;
                     let idx = tex_row * w as usize + col;
-                    *pixels.add(idx) = match SHAPES[shape][row][col] {
-                        1 => 0xFF_FF_FF_FF, // white
-                        2 => 0xFF_00_00_00, // black with alpha=FF
-                        _ => 0x00_00_00_00, // transparent
-                    };
+                    *pixels.add(idx) =
+                        gpu_cursor::shape_pixel(gpu_cursor::SHAPES[shape][row][col]);
                 }
             }
         }
@@ -1472,6 +1411,7 @@ impl GpuMsiConfig {
 /// Combined GPU PCI device state (transport + GPU state)
 struct GpuPciDeviceState {
     device: VirtioPciDevice,
+    /// Desktop size: the scanout resources span all outputs
     width: u32,
     height: u32,
     resource_id: u32,
@@ -1481,6 +1421,10 @@ struct GpuPciDeviceState {
     /// by echoing this ID in the response. Required for TRANSFER_FROM_HOST_3D
     /// to ensure DMA writes complete before reading backing memory.
     next_fence_id: u64,
+    /// The outputs and where each shows the desktop.
+    layout: DisplayLayout,
+    /// The device has a cursor queue (queue 1)
+    cursor_queue: bool,
 }
 
 static mut GPU_PCI_STATE: Option<GpuPciDeviceState> = None;
@@ -1577,14 +1521,12 @@ fn dma_cache_clean(_ptr: *const u8, _len: usize) {
 
 /// Invalidate data cache lines covering a DMA buffer.
 ///
-/// After the host writes to guest memory via DMA (e.g., TRANSFER_FROM_HOST),
-/// the CPU cache may hold stale data. DC CIVAC cleans and invalidates each
-/// cache line so subsequent CPU reads see the DMA-written values.
-/// Currently unused but kept for future TRANSFER_FROM_HOST operations.
-#[allow(dead_code)]
+/// After the host writes to guest memory via DMA (e.g., the cursor queue's
+/// used ring), the CPU cache may hold stale data. DC CIVAC cleans and
+/// invalidates each cache line so subsequent CPU reads see the DMA-written
+/// values.
 #[cfg(target_arch = "aarch64")]
 #[inline]
-#[allow(dead_code)]
 fn dma_cache_invalidate(ptr: *const u8, len: usize) {
     const CACHE_LINE: usize = 64;
     let start = ptr as usize & !(CACHE_LINE - 1);
@@ -1599,7 +1541,6 @@ fn dma_cache_invalidate(ptr: *const u8, len: usize) {
     }
 }
 
-#[allow(dead_code)]
 #[cfg(not(target_arch = "aarch64"))]
 #[inline]
 fn dma_cache_invalidate(_ptr: *const u8, _len: usize) {
@@ -1781,8 +1722,9 @@ pub fn handle_interrupt() {
     }
 }
 
-/// Handle GPU config MSI-X interrupt. Display hot-plug/config changes are not
-/// used yet, so acknowledge the device condition and return.
+/// Handle GPU config MSI-X interrupt. A display event (hotplug, or the host
+/// resizing a display) is cleared and noted for the compositor; the display
+/// list is re-read later in process context, since GPU commands sleep.
 #[cfg(target_arch = "aarch64")]
 pub fn handle_config_interrupt() {
     let irq = GPU_CONFIG_IRQ.load(Ordering::Relaxed);
@@ -1791,11 +1733,25 @@ pub fn handle_config_interrupt() {
     }
 
     if GPU_PCI_INITIALIZED.load(Ordering::Acquire) {
-        unsafe {
+        let display_event = unsafe {
             let ptr = &raw const GPU_PCI_STATE;
-            if let Some(ref state) = *ptr {
-                state.device.read_interrupt_status();
+            match *ptr {
+                Some(ref state) => {
+                    state.device.read_interrupt_status();
+                    let events = state.device.read_config_u32(GPU_CFG_EVENTS_READ);
+                    if events & VIRTIO_GPU_EVENT_DISPLAY != 0 {
+                        state
+                            .device
+                            .write_config_u32(GPU_CFG_EVENTS_CLEAR, VIRTIO_GPU_EVENT_DISPLAY);
+                    }
+                    events & VIRTIO_GPU_EVENT_DISPLAY != 0
+                }
+                None => false,
             }
+        };
+        if display_event {
+            super::gpu_display::note_hotplug();
+            crate::syscall::graphics::wake_compositor_if_waiting();
         }
     }
 }
@@ -1975,7 +1931,9 @@ pub fn init() -> Result<(), &'static str> {
                 (*q).desc[i].next = (i + 1) as u16;
             }
             (*q).desc[15].next = 0;
-            (*q).avail.flags = 0;
+            // Cursor commands are fire-and-forget; the queue is polled for
+            // free slots instead of raising completion interrupts.
+            (*q).avail.flags = VIRTQ_AVAIL_F_NO_INTERRUPT;
             (*q).avail.idx = 0;
             (*q).used.flags = 0;
             (*q).used.idx = 0;
@@ -1998,6 +1956,7 @@ pub fn init() -> Result<(), &'static str> {
         }
 
         virtio.set_queue_ready(true);
+        virtio.cache_queue_notify_addr(1);
         crate::serial_println!(
             "[virtio-gpu-pci] Cursor queue (q1) set up: size={}",
             cursor_queue_size
@@ -2038,6 +1997,8 @@ pub fn init() -> Result<(), &'static str> {
             resource_id: RESOURCE_ID,
             last_used_idx: 0,
             next_fence_id: 1,
+            layout: DisplayLayout::empty(),
+            cursor_queue: cursor_queue_max > 0,
         });
     }
     // Don't set GPU_PCI_INITIALIZED yet — the GPU commands below can fail.
@@ -2103,7 +2064,15 @@ pub fn init() -> Result<(), &'static str> {
     }
 
     // Query display info to see what Parallels reports as native resolution.
-    let display_dims = get_display_info();
+    let num_scanouts = (num_scanouts as usize).clamp(1, MAX_SCANOUTS);
+    let modes = query_modes();
+    let display_dims = modes.map(|modes| {
+        // First enabled scanout, or default
+        modes[..num_scanouts]
+            .iter()
+            .find(|m| m.enabled)
+            .map_or((DEFAULT_FB_WIDTH, DEFAULT_FB_HEIGHT), |m| (m.width, m.height))
+    });
     match display_dims {
         Ok((dw, dh)) => crate::serial_println!("[virtio-gpu-pci] Display reports: {}x{}", dw, dh),
         Err(e) => crate::serial_println!("[virtio-gpu-pci] GET_DISPLAY_INFO failed: {}", e),
@@ -2144,12 +2113,35 @@ pub fn init() -> Result<(), &'static str> {
         MIN_FB_HEIGHT
     );
 
+    // The chosen resolution is the primary output's mode; any other enabled
+    // outputs sit beside it, and the scanout resources span them all.
+    let modes = modes.unwrap_or([HostMode::default(); MAX_SCANOUTS]);
+    let layout = DisplayLayout::for_boot(
+        &modes[..num_scanouts],
+        (use_width, use_height),
+        FB_SIZE / BYTES_PER_PIXEL,
+    );
+    let (use_width, use_height) = (layout.desktop_width, layout.desktop_height);
+    for (i, s) in layout.scanouts[..layout.count].iter().enumerate() {
+        if s.active {
+            crate::serial_println!(
+                "[virtio-gpu-pci] Scanout {}: {}x{} at {},{}",
+                i,
+                s.width,
+                s.height,
+                s.x,
+                s.y
+            );
+        }
+    }
+
     // Update state with actual dimensions
     unsafe {
         let ptr = &raw mut GPU_PCI_STATE;
         if let Some(ref mut state) = *ptr {
             state.width = use_width;
             state.height = use_height;
+            state.layout = layout;
         }
     }
 
@@ -2164,6 +2156,12 @@ pub fn init() -> Result<(), &'static str> {
     set_scanout()?;
     crate::serial_println!("[virtio-gpu-pci] 2D resource created and scanout set");
 
+    // The pointer falls back to a VirGL quad without a cursor plane
+    match init_cursor() {
+        Ok(()) => gpu_cursor::set_plane_ready(),
+        Err(e) => crate::serial_println!("[virtio-gpu-pci] No hardware cursor: {}", e),
+    }
+
     // All GPU setup commands succeeded — now mark as initialized.
     GPU_PCI_INITIALIZED.store(true, Ordering::Release);
 
@@ -2974,7 +2972,8 @@ fn send_owned_3desc_command(
 // GPU Commands
 // =============================================================================
 
-fn get_display_info() -> Result<(u32, u32), &'static str> {
+/// Query the mode of every scanout (GET_DISPLAY_INFO).
+fn query_modes() -> Result<[HostMode; MAX_SCANOUTS], &'static str> {
     with_device_state(|state| {
         let cmd_phys = virt_to_phys(&raw const PCI_CMD_BUF as u64);
         let resp_phys = virt_to_phys(&raw const PCI_RESP_BUF as u64);
@@ -3032,15 +3031,15 @@ fn get_display_info() -> Result<(u32, u32), &'static str> {
                 return Err("GET_DISPLAY_INFO failed");
             }
 
-            let mut first_enabled = None;
-            for (_i, pmode) in resp.pmodes.iter().enumerate() {
-                if pmode.enabled != 0 && first_enabled.is_none() {
-                    first_enabled = Some((pmode.r_width, pmode.r_height));
-                }
+            let mut modes = [HostMode::default(); MAX_SCANOUTS];
+            for (mode, pmode) in modes.iter_mut().zip(resp.pmodes.iter()) {
+                *mode = HostMode {
+                    width: core::ptr::read_volatile(&pmode.r_width),
+                    height: core::ptr::read_volatile(&pmode.r_height),
+                    enabled: core::ptr::read_volatile(&pmode.enabled) != 0,
+                };
             }
-
-            // Use first enabled scanout, or default
-            Ok(first_enabled.unwrap_or((DEFAULT_FB_WIDTH, DEFAULT_FB_HEIGHT)))
+            Ok(modes)
         }
     })
 }
@@ -3228,28 +3227,12 @@ fn attach_backing() -> Result<(), &'static str> {
     })
 }
 
+/// Point every output at its rectangle of the 2D resource, and turn off the
+/// outputs that are not shown.
 fn set_scanout() -> Result<(), &'static str> {
     with_device_state(|state| {
-        unsafe {
-            let cmd_ptr = &raw mut PCI_CMD_BUF;
-            let cmd = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut VirtioGpuSetScanout);
-            *cmd = VirtioGpuSetScanout {
-                hdr: VirtioGpuCtrlHdr {
-                    type_: cmd::SET_SCANOUT,
-                    flags: 0,
-                    fence_id: 0,
-                    ctx_id: 0,
-                    padding: 0,
-                },
-                r_x: 0,
-                r_y: 0,
-                r_width: state.width,
-                r_height: state.height,
-                scanout_id: 0,
-                resource_id: state.resource_id,
-            };
-        }
-        send_command_expect_ok(state, core::mem::size_of::<VirtioGpuSetScanout>() as u32)
+        let resource_id = state.resource_id;
+        apply_layout(state, resource_id)
     })
 }
 
@@ -4143,10 +4126,37 @@ fn virgl_fence_sync(
     }
 }
 
-/// Set scanout to a specific resource ID (used for 3D render targets).
+/// Point every shown output at its rectangle of `resource_id` (used for 3D
+/// render targets, every frame).
 fn set_scanout_resource(
     state: &mut GpuPciDeviceState,
     resource_id: u32,
+) -> Result<(), &'static str> {
+    let layout = state.layout;
+    for (id, scanout) in layout.scanouts[..layout.count].iter().enumerate() {
+        if scanout.active {
+            send_set_scanout(state, id as u32, scanout, resource_id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Apply the layout: shown outputs scan out `resource_id`, the others are
+/// turned off (resource 0).
+fn apply_layout(state: &mut GpuPciDeviceState, resource_id: u32) -> Result<(), &'static str> {
+    let layout = state.layout;
+    for (id, scanout) in layout.scanouts[..layout.count].iter().enumerate() {
+        let resource = if scanout.active { resource_id } else { 0 };
+        send_set_scanout(state, id as u32, scanout, resource)?;
+    }
+    Ok(())
+}
+
+fn send_set_scanout(
+    state: &mut GpuPciDeviceState,
+    scanout_id: u32,
+    scanout: &Scanout,
+    resource_id: u32,
 ) -> Result<(), &'static str> {
     let command = VirtioGpuSetScanout {
         hdr: VirtioGpuCtrlHdr {
@@ -4156,11 +4166,11 @@ fn set_scanout_resource(
             ctx_id: 0,
             padding: 0,
         },
-        r_x: 0,
-        r_y: 0,
-        r_width: state.width,
-        r_height: state.height,
-        scanout_id: 0,
+        r_x: scanout.x,
+        r_y: scanout.y,
+        r_width: scanout.width,
+        r_height: scanout.height,
+        scanout_id,
         resource_id,
     };
     hex_dump_cmd_buf("SET_SCANOUT", core::mem::size_of::<VirtioGpuSetScanout>());
@@ -4233,6 +4243,227 @@ pub fn dimensions() -> Option<(u32, u32)> {
     }
 }
 
+/// The outputs and where each shows the desktop.
+pub fn display_layout() -> Option<DisplayLayout> {
+    unsafe {
+        let ptr = &raw const GPU_PCI_STATE;
+        (*ptr).as_ref().map(|s| s.layout)
+    }
+}
+
+/// The resource the outputs currently scan out.
+fn scanout_resource_id(state: &GpuPciDeviceState) -> u32 {
+    if VIRGL_SCANOUT_ACTIVE.load(Ordering::Acquire) {
+        RESOURCE_3D_ID
+    } else {
+        state.resource_id
+    }
+}
+
+/// Re-read the outputs after a display event and re-apply the layout.
+pub fn refresh_displays() -> Result<(), &'static str> {
+    let modes = query_modes()?;
+    with_device_state(|state| {
+        state.layout.update_from_host(&modes);
+        let resource_id = scanout_resource_id(state);
+        apply_layout(state, resource_id)
+    })?;
+    if VIRGL_SCANOUT_ACTIVE.load(Ordering::Acquire) {
+        Ok(()) // The compositor's next frame flushes
+    } else {
+        flush()
+    }
+}
+
+/// Set the mode of output `id` (0x0 turns it off) and re-apply the layout.
+pub fn set_display_mode(id: usize, width: u32, height: u32) -> Result<(), &'static str> {
+    with_device_state(|state| {
+        let previous = state.layout;
+        state.layout.set_mode(id, width, height)?;
+        let resource_id = scanout_resource_id(state);
+        if let Err(e) = apply_layout(state, resource_id) {
+            // Put back what the outputs showed before
+            state.layout = previous;
+            let _ = apply_layout(state, resource_id);
+            return Err(e);
+        }
+        Ok(())
+    })?;
+    if VIRGL_SCANOUT_ACTIVE.load(Ordering::Acquire) {
+        Ok(())
+    } else {
+        flush()
+    }
+}
+
+// =============================================================================
+// Hardware Cursor (cursor queue)
+// =============================================================================
+
+/// Create the 64x64 hardware cursor image resource and attach its backing.
+///
+/// A plain 2D resource: the host reads cursor images itself (with VirGL on,
+/// via the renderer's cursor readback), so it is never attached to the VirGL
+/// context.
+fn init_cursor() -> Result<(), &'static str> {
+    with_device_state(|state| {
+        if !state.cursor_queue {
+            return Err("no cursor queue");
+        }
+        unsafe {
+            let cmd_ptr = &raw mut PCI_CMD_BUF;
+            let cmd = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut VirtioGpuResourceCreate2d);
+            *cmd = VirtioGpuResourceCreate2d {
+                hdr: VirtioGpuCtrlHdr {
+                    type_: cmd::RESOURCE_CREATE_2D,
+                    flags: 0,
+                    fence_id: 0,
+                    ctx_id: 0,
+                    padding: 0,
+                },
+                resource_id: RESOURCE_HW_CURSOR_ID,
+                format: format::B8G8R8A8_UNORM,
+                width: CURSOR_SIZE,
+                height: CURSOR_SIZE,
+            };
+        }
+        send_command_expect_ok(
+            state,
+            core::mem::size_of::<VirtioGpuResourceCreate2d>() as u32,
+        )?;
+        virgl_attach_backing_paged(
+            state,
+            RESOURCE_HW_CURSOR_ID,
+            &raw const PCI_CURSOR_IMAGE as *const u8,
+            core::mem::size_of::<PciCursorImage>(),
+        )
+    })
+}
+
+/// Queue a command on the cursor queue. Returns without waiting: the device
+/// takes cursor commands in order and sends no response.
+fn send_cursor_command(
+    state: &GpuPciDeviceState,
+    command: VirtioGpuUpdateCursor,
+) -> Result<(), &'static str> {
+    unsafe {
+        let queue_ptr = &raw mut PCI_CURSOR_QUEUE;
+        let avail_idx = (*queue_ptr).avail.idx;
+        // The device writes the used ring; drop any stale line first
+        dma_cache_invalidate(&(*queue_ptr).used.idx as *const u16 as *const u8, 64);
+        let used_idx = read_volatile(&(*queue_ptr).used.idx);
+        if avail_idx.wrapping_sub(used_idx) >= 16 {
+            return Err("cursor queue full");
+        }
+
+        let slot = (avail_idx % 16) as usize;
+        let cmds_ptr = &raw mut PCI_CURSOR_CMDS;
+        (*cmds_ptr)[slot] = command;
+        dma_cache_clean(
+            &raw const (*cmds_ptr)[slot] as *const u8,
+            core::mem::size_of::<VirtioGpuUpdateCursor>(),
+        );
+        (*queue_ptr).desc[slot] = VirtqDesc {
+            addr: virt_to_phys(&raw const (*cmds_ptr)[slot] as u64),
+            len: core::mem::size_of::<VirtioGpuUpdateCursor>() as u32,
+            flags: 0,
+            next: 0,
+        };
+        (*queue_ptr).avail.ring[slot] = slot as u16;
+        // Descriptors (256 bytes) + avail ring (36 bytes)
+        dma_cache_clean(queue_ptr as *const u8, 512);
+        fence(Ordering::SeqCst);
+        (*queue_ptr).avail.idx = avail_idx.wrapping_add(1);
+        dma_cache_clean(&(*queue_ptr).avail.idx as *const u16 as *const u8, 64);
+        fence(Ordering::SeqCst);
+    }
+
+    state.device.notify_queue_fast(1);
+    Ok(())
+}
+
+fn cursor_command(type_: u32, scanout: u32, x: i32, y: i32) -> VirtioGpuUpdateCursor {
+    VirtioGpuUpdateCursor {
+        hdr: VirtioGpuCtrlHdr {
+            type_,
+            ..Default::default()
+        },
+        pos: VirtioGpuCursorPos {
+            scanout_id: scanout,
+            // Negative when the hotspot is near the left/top edge
+            x: x as u32,
+            y: y as u32,
+            padding: 0,
+        },
+        ..Default::default()
+    }
+}
+
+/// Copy a 64x64 BGRA image into the hardware cursor resource. It shows on
+/// the next `update_cursor`.
+pub fn upload_cursor(pixels: &[u32; CURSOR_PIXELS]) -> Result<(), &'static str> {
+    with_device_state(|state| {
+        unsafe {
+            let image = &raw mut PCI_CURSOR_IMAGE;
+            (*image).pixels.copy_from_slice(pixels);
+            dma_cache_clean(image as *const u8, core::mem::size_of::<PciCursorImage>());
+            let cmd_ptr = &raw mut PCI_CMD_BUF;
+            let cmd = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut VirtioGpuTransferToHost2d);
+            *cmd = VirtioGpuTransferToHost2d {
+                hdr: VirtioGpuCtrlHdr {
+                    type_: cmd::TRANSFER_TO_HOST_2D,
+                    flags: 0,
+                    fence_id: 0,
+                    ctx_id: 0,
+                    padding: 0,
+                },
+                r_x: 0,
+                r_y: 0,
+                r_width: CURSOR_SIZE,
+                r_height: CURSOR_SIZE,
+                offset: 0,
+                resource_id: RESOURCE_HW_CURSOR_ID,
+                padding: 0,
+            };
+        }
+        send_command_expect_ok(
+            state,
+            core::mem::size_of::<VirtioGpuTransferToHost2d>() as u32,
+        )
+    })
+}
+
+/// Show the cursor image on `scanout` with its top-left at (x, y), or hide
+/// the cursor there.
+pub fn update_cursor(
+    scanout: u32,
+    x: i32,
+    y: i32,
+    hot: (u32, u32),
+    visible: bool,
+) -> Result<(), &'static str> {
+    with_device_state(|state| {
+        let mut command = cursor_command(cmd::UPDATE_CURSOR, scanout, x, y);
+        if visible {
+            command.resource_id = RESOURCE_HW_CURSOR_ID;
+            (command.hot_x, command.hot_y) = hot;
+        }
+        send_cursor_command(state, command)
+    })
+}
+
+/// Move the cursor on `scanout`. Gives up rather than wait if a control
+/// command is in flight (e.g. a SUBMIT_3D); the caller retries on its next
+/// pass.
+pub fn move_cursor(scanout: u32, x: i32, y: i32) -> Result<(), &'static str> {
+    let _guard = GPU_PCI_LOCK.try_lock().ok_or("GPU busy")?;
+    let state = unsafe {
+        let ptr = &raw const GPU_PCI_STATE;
+        (*ptr).as_ref().ok_or("GPU PCI not initialized")?
+    };
+    send_cursor_command(state, cursor_command(cmd::MOVE_CURSOR, scanout, x, y))
+}
+
 /// Get a mutable reference to the heap-backed 2D framebuffer pixels.
 #[allow(dead_code)]
 pub fn framebuffer() -> Option<&'static mut [u8]> {
@@ -5293,18 +5524,17 @@ fn virgl_composite_single_quad(
 
     // ── Draw cursor as GPU quad (rendered LAST, on top of everything) ──
     // The cursor lives in a dedicated GPU texture atlas (RESOURCE_CURSOR_TEX_ID,
-    // 16x80, 5 shapes stacked vertically). UV coordinates select the active shape.
-    if CURSOR_TEX_READY.load(Ordering::Acquire) {
+    // 16x96, 6 shapes stacked vertically). UV coordinates select the active shape.
+    // Skipped when the cursor is in the hardware cursor plane.
+    if CURSOR_TEX_READY.load(Ordering::Acquire) && !gpu_cursor::hardware_active() {
         let (mouse_x, mouse_y) = if crate::drivers::virtio::input_mmio::is_tablet_initialized() {
             crate::drivers::virtio::input_mmio::mouse_position()
         } else {
             crate::drivers::usb::hid::mouse_position()
         };
 
-        let shape = CURSOR_SHAPE
-            .load(Ordering::Acquire)
-            .min(NUM_CURSOR_SHAPES - 1);
-        let (hx, hy) = CURSOR_HOTSPOT[shape as usize];
+        let shape = gpu_cursor::builtin_shape();
+        let (hx, hy) = gpu_cursor::HOTSPOTS[shape as usize];
         let mx = mouse_x as f32 - hx as f32;
         let my = mouse_y as f32 - hy as f32;
         let sw = CURSOR_SHAPE_W as f32;
@@ -5507,7 +5737,8 @@ pub fn virgl_composite_windows(
     let cur_y = mouse_y as i32;
     let prev_cx = CURSOR_PREV_X.load(Ordering::Relaxed);
     let prev_cy = CURSOR_PREV_Y.load(Ordering::Relaxed);
-    let cursor_moved = cur_x != prev_cx || cur_y != prev_cy;
+    // With a hardware cursor plane, pointer motion needs no new frame
+    let cursor_moved = !gpu_cursor::hardware_active() && (cur_x != prev_cx || cur_y != prev_cy);
     if cursor_moved {
         CURSOR_PREV_X.store(cur_x, Ordering::Relaxed);
         CURSOR_PREV_Y.store(cur_y, Ordering::Relaxed);
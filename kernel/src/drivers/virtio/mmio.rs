@@ -325,6 +325,13 @@ impl VirtioMmioDevice {
         unsafe { read_volatile((self.base + regs::CONFIG as u64 + offset as u64) as *const u32) }
     }
 
+    /// Write a u32 to device config space
+    pub fn write_config_u32(&self, offset: usize, value: u32) {
+        unsafe {
+            write_volatile((self.base + regs::CONFIG as u64 + offset as u64) as *mut u32, value)
+        }
+    }
+
     /// Read a u64 from device config space
     pub fn read_config_u64(&self, offset: usize) -> u64 {
         let low = self.read_config_u32(offset) as u64;
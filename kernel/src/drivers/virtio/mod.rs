@@ -26,6 +26,10 @@ pub mod queue;
 #[cfg(target_arch = "aarch64")]
 pub mod block_mmio;
 #[cfg(target_arch = "aarch64")]
+pub mod gpu_cursor;
+#[cfg(target_arch = "aarch64")]
+pub mod gpu_display;
+#[cfg(target_arch = "aarch64")]
 pub mod gpu_mmio;
 #[cfg(target_arch = "aarch64")]
 pub mod gpu_pci;
@@ -198,7 +202,7 @@ impl VirtioDevice {
     }
 
     /// Read a u32 from device-specific configuration
-    fn read_config_u32(&self, offset: u16) -> u32 {
+    pub fn read_config_u32(&self, offset: u16) -> u32 {
         self.read_u32(regs::DEVICE_CONFIG + offset)
     }
 
@@ -1,7 +1,8 @@
 //! VirtIO Sound Device Driver for x86_64 (PCI Transport)
 //!
-//! Implements audio playback using VirtIO PCI legacy transport.
-//! Provides PCM audio output at 44100 Hz, S16_LE, stereo.
+//! Implements audio playback and capture using VirtIO PCI legacy transport.
+//! Provides PCM audio output at 44100 Hz, S16_LE, stereo, and PCM input in
+//! the same format when the device has a capture stream.
 
 use super::queue::Virtqueue;
 use super::VirtioDevice;
@@ -16,6 +17,10 @@ const SOUND_COMPLETION_TIMEOUT_NS: u64 = 5_000_000_000;
 const SOUND_EARLY_COMPLETION_TIMEOUT_NS: u64 = 100_000_000_000;
 const NO_COMPLETED_DESC: u32 = u32::MAX;
 const SOUND_TEST_SILENCE: [u8; 16_384] = [0; 16_384];
+/// Bytes of captured PCM returned per RX request
+const CAPTURE_PERIOD_BYTES: u32 = 4096;
+/// `capture_stream` value before discovery, or when there is no capture stream
+const NO_CAPTURE_STREAM: u32 = u32::MAX;
 
 struct SoundRequestGate {
     locked: AtomicBool,
@@ -125,6 +130,8 @@ struct SoundQueueCompletion {
     next_token: AtomicU32,
     pending_token: AtomicU32,
     completed_desc: AtomicU32,
+    /// Bytes the device wrote into the completed chain
+    completed_len: AtomicU32,
 }
 
 impl SoundQueueCompletion {
@@ -134,6 +141,7 @@ impl SoundQueueCompletion {
             next_token: AtomicU32::new(0),
             pending_token: AtomicU32::new(0),
             completed_desc: AtomicU32::new(NO_COMPLETED_DESC),
+            completed_len: AtomicU32::new(0),
         }
     }
 
@@ -199,16 +207,21 @@ impl SoundQueueCompletion {
         Ok(desc as u16)
     }
 
+    fn completed_len(&self) -> u32 {
+        self.completed_len.load(Ordering::Acquire)
+    }
+
     fn has_pending(&self) -> bool {
         self.pending_token.load(Ordering::Acquire) != 0
     }
 
-    fn complete_desc(&self, completed_desc: u16) -> bool {
+    fn complete_desc(&self, completed_desc: u16, len: u32) -> bool {
         let token = self.pending_token.load(Ordering::Acquire);
         if token == 0 {
             return false;
         }
 
+        self.completed_len.store(len, Ordering::Release);
         self.completed_desc
             .store(completed_desc as u32, Ordering::Release);
         self.completion.complete(token);
@@ -218,6 +231,7 @@ impl SoundQueueCompletion {
 
 /// VirtIO Sound command codes
 mod cmd {
+    pub const PCM_INFO: u32 = 0x0100;
     pub const SET_PARAMS: u32 = 0x0101;
     pub const PREPARE: u32 = 0x0102;
     pub const START: u32 = 0x0104;
@@ -238,6 +252,11 @@ mod pcm_rate {
     pub const RATE_44100: u8 = 6; // VIRTIO_SND_PCM_RATE_44100
 }
 
+/// VirtIO Sound stream directions (from virtio_snd.h)
+mod direction {
+    pub const INPUT: u8 = 1; // VIRTIO_SND_D_INPUT
+}
+
 /// Control header for sound commands
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -245,6 +264,30 @@ struct VirtioSndHdr {
     code: u32,
 }
 
+/// Item information request (PCM_INFO)
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct VirtioSndQueryInfo {
+    hdr: VirtioSndHdr,
+    start_id: u32,
+    count: u32,
+    size: u32,
+}
+
+/// PCM stream information (device writes back, one per stream)
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct VirtioSndPcmInfo {
+    hda_fn_nid: u32,
+    features: u32,
+    formats: u64,
+    rates: u64,
+    direction: u8,
+    channels_min: u8,
+    channels_max: u8,
+    _padding: [u8; 5],
+}
+
 /// PCM set params request
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -268,7 +311,7 @@ struct VirtioSndPcmCtrl {
     stream_id: u32,
 }
 
-/// PCM xfer header (for TX queue)
+/// PCM xfer header (for TX and RX queues)
 #[repr(C)]
 #[derive(Clone, Copy)]
 struct VirtioSndPcmXfer {
@@ -295,6 +338,12 @@ struct DmaBuffers {
     tx_pcm: (u64, u64),
     /// TX status (phys, virt)
     tx_status: (u64, u64),
+    /// RX xfer header (phys, virt)
+    rx_xfer: (u64, u64),
+    /// RX PCM data buffer (phys, virt) - one capture period
+    rx_pcm: (u64, u64),
+    /// RX status (phys, virt)
+    rx_status: (u64, u64),
 }
 
 /// VirtIO sound device driver
@@ -302,12 +351,21 @@ struct VirtioSoundDevice {
     device: VirtioDevice,
     ctrl_queue: Mutex<Virtqueue>,
     tx_queue: Mutex<Virtqueue>,
+    /// RX queue, if the device exposes one
+    rx_queue: Option<Mutex<Virtqueue>>,
     ctrl_gate: SoundRequestGate,
     tx_gate: SoundRequestGate,
+    rx_gate: SoundRequestGate,
     ctrl_completion: SoundQueueCompletion,
     tx_completion: SoundQueueCompletion,
+    rx_completion: SoundQueueCompletion,
     dma: DmaBuffers,
     stream_started: AtomicBool,
+    /// Number of PCM streams the device reports
+    streams: u32,
+    /// Stream ID of the capture stream, once discovered
+    capture_stream: AtomicU32,
+    capture_started: AtomicBool,
 }
 
 impl VirtioSoundDevice {
@@ -352,6 +410,21 @@ impl VirtioSoundDevice {
         device.select_queue(2);
         device.set_queue_address(tx_queue.phys_addr());
 
+        // Set up RX queue (queue 3); capture is optional
+        device.select_queue(3);
+        let rx_size = device.get_queue_size();
+        let rx_queue = if rx_size == 0 {
+            None
+        } else {
+            let rx_queue = Virtqueue::new(rx_size)?;
+            device.select_queue(3);
+            device.set_queue_address(rx_queue.phys_addr());
+            Some(Mutex::new(rx_queue))
+        };
+
+        // virtio_snd_config: jacks, streams, chmaps
+        let streams = device.read_config_u32(4);
+
         device.driver_ok();
 
         // Allocate DMA buffers
@@ -360,6 +433,9 @@ impl VirtioSoundDevice {
         let tx_xfer_buf = Self::alloc_dma(4096)?;
         let tx_pcm_buf = Self::alloc_dma(16384)?;
         let tx_status_buf = Self::alloc_dma(4096)?;
+        let rx_xfer_buf = Self::alloc_dma(4096)?;
+        let rx_pcm_buf = Self::alloc_dma(CAPTURE_PERIOD_BYTES as usize)?;
+        let rx_status_buf = Self::alloc_dma(4096)?;
 
         let dma = DmaBuffers {
             cmd: cmd_buf,
@@ -367,20 +443,32 @@ impl VirtioSoundDevice {
             tx_xfer: tx_xfer_buf,
             tx_pcm: tx_pcm_buf,
             tx_status: tx_status_buf,
+            rx_xfer: rx_xfer_buf,
+            rx_pcm: rx_pcm_buf,
+            rx_status: rx_status_buf,
         };
 
-        log::info!("VirtIO sound: Device initialization complete");
+        log::info!(
+            "VirtIO sound: Device initialization complete ({} PCM streams)",
+            streams
+        );
 
         Ok(VirtioSoundDevice {
             device,
             ctrl_queue: Mutex::new(ctrl_queue),
             tx_queue: Mutex::new(tx_queue),
+            rx_queue,
             ctrl_gate: SoundRequestGate::new(),
             tx_gate: SoundRequestGate::new(),
+            rx_gate: SoundRequestGate::new(),
             ctrl_completion: SoundQueueCompletion::new(),
             tx_completion: SoundQueueCompletion::new(),
+            rx_completion: SoundQueueCompletion::new(),
             dma,
             stream_started: AtomicBool::new(false),
+            streams,
+            capture_stream: AtomicU32::new(NO_CAPTURE_STREAM),
+            capture_started: AtomicBool::new(false),
         })
     }
 
@@ -501,6 +589,21 @@ impl VirtioSoundDevice {
             return Ok(());
         }
 
+        self.start_pcm_locked(&mut request_guard, 0, 32768, 16384)?;
+
+        self.stream_started.store(true, Ordering::Release);
+        log::info!("VirtIO sound: Stream started (S16_LE, 44100 Hz, stereo)");
+        Ok(())
+    }
+
+    /// Configure, prepare and start a PCM stream in S16_LE, 44100 Hz, stereo.
+    fn start_pcm_locked(
+        &self,
+        request_guard: &mut SoundRequestGuard<'_>,
+        stream_id: u32,
+        buffer_bytes: u32,
+        period_bytes: u32,
+    ) -> Result<(), &'static str> {
         let (_, cmd_virt) = self.dma.cmd;
         let hdr_size = core::mem::size_of::<VirtioSndHdr>() as u32;
 
@@ -508,9 +611,9 @@ impl VirtioSoundDevice {
         unsafe {
             let params = cmd_virt as *mut VirtioSndPcmSetParams;
             (*params).hdr.code = cmd::SET_PARAMS;
-            (*params).stream_id = 0;
-            (*params).buffer_bytes = 32768;
-            (*params).period_bytes = 16384;
+            (*params).stream_id = stream_id;
+            (*params).buffer_bytes = buffer_bytes;
+            (*params).period_bytes = period_bytes;
             (*params).features = 0;
             (*params).channels = 2;
             (*params).format = pcm_format::S16;
@@ -518,40 +621,195 @@ impl VirtioSoundDevice {
             (*params)._padding = 0;
         }
         self.send_ctrl_locked(
-            &mut request_guard,
+            request_guard,
             core::mem::size_of::<VirtioSndPcmSetParams>() as u32,
             hdr_size,
         )?;
 
-        // 2. PREPARE
+        // 2. PREPARE, 3. START
+        for code in [cmd::PREPARE, cmd::START] {
+            unsafe {
+                let ctrl = cmd_virt as *mut VirtioSndPcmCtrl;
+                (*ctrl).hdr.code = code;
+                (*ctrl).stream_id = stream_id;
+            }
+            self.send_ctrl_locked(
+                request_guard,
+                core::mem::size_of::<VirtioSndPcmCtrl>() as u32,
+                hdr_size,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Find a capture stream that supports S16_LE, 44100 Hz, stereo.
+    fn find_capture_stream_locked(
+        &self,
+        request_guard: &mut SoundRequestGuard<'_>,
+    ) -> Result<u32, &'static str> {
+        let info_size = core::mem::size_of::<VirtioSndPcmInfo>() as u32;
+        let hdr_size = core::mem::size_of::<VirtioSndHdr>() as u32;
+        let count = self.streams.min((4096 - hdr_size) / info_size);
+        if count == 0 {
+            return Err("Sound device has no PCM streams");
+        }
+
+        let (_, cmd_virt) = self.dma.cmd;
         unsafe {
-            let ctrl = cmd_virt as *mut VirtioSndPcmCtrl;
-            (*ctrl).hdr.code = cmd::PREPARE;
-            (*ctrl).stream_id = 0;
+            let query = cmd_virt as *mut VirtioSndQueryInfo;
+            (*query).hdr.code = cmd::PCM_INFO;
+            (*query).start_id = 0;
+            (*query).count = count;
+            (*query).size = info_size;
         }
         self.send_ctrl_locked(
-            &mut request_guard,
-            core::mem::size_of::<VirtioSndPcmCtrl>() as u32,
-            hdr_size,
+            request_guard,
+            core::mem::size_of::<VirtioSndQueryInfo>() as u32,
+            hdr_size + count * info_size,
         )?;
 
-        // 3. START
-        unsafe {
-            let ctrl = cmd_virt as *mut VirtioSndPcmCtrl;
-            (*ctrl).hdr.code = cmd::START;
-            (*ctrl).stream_id = 0;
+        let (_, resp_virt) = self.dma.resp;
+        (0..count)
+            .find(|&id| {
+                let info = unsafe {
+                    core::ptr::read_unaligned(
+                        (resp_virt + (hdr_size + id * info_size) as u64) as *const VirtioSndPcmInfo,
+                    )
+                };
+                info.direction == direction::INPUT
+                    && info.formats & (1 << pcm_format::S16) != 0
+                    && info.rates & (1 << pcm_rate::RATE_44100) != 0
+                    && (info.channels_min..=info.channels_max).contains(&2)
+            })
+            .ok_or("Sound device has no usable capture stream")
+    }
+
+    fn do_setup_capture(&self) -> Result<(), &'static str> {
+        if self.capture_started.load(Ordering::Acquire) {
+            return Ok(());
         }
-        self.send_ctrl_locked(
+        if self.rx_queue.is_none() {
+            return Err("Sound device has no RX queue");
+        }
+        if !self.irq_completion_available() {
+            return Err("Sound IRQ completion unavailable before interrupts are enabled");
+        }
+
+        let mut request_guard = self.ctrl_gate.lock()?;
+
+        if self.capture_started.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        let stream_id = self.find_capture_stream_locked(&mut request_guard)?;
+        self.start_pcm_locked(
             &mut request_guard,
-            core::mem::size_of::<VirtioSndPcmCtrl>() as u32,
-            hdr_size,
+            stream_id,
+            CAPTURE_PERIOD_BYTES * 4,
+            CAPTURE_PERIOD_BYTES,
         )?;
 
-        self.stream_started.store(true, Ordering::Release);
-        log::info!("VirtIO sound: Stream started (S16_LE, 44100 Hz, stereo)");
+        self.capture_stream.store(stream_id, Ordering::Release);
+        self.capture_started.store(true, Ordering::Release);
+        log::info!(
+            "VirtIO sound: Capture stream {} started (S16_LE, 44100 Hz, stereo)",
+            stream_id
+        );
         Ok(())
     }
 
+    fn do_read_pcm(&self, buf: &mut [u8]) -> Result<usize, &'static str> {
+        if !self.capture_started.load(Ordering::Acquire) {
+            return Err("Capture stream not started");
+        }
+        let rx_queue = self.rx_queue.as_ref().ok_or("Sound device has no RX queue")?;
+        if !self.irq_completion_available() {
+            return Err("Sound IRQ completion unavailable before interrupts are enabled");
+        }
+
+        let len = core::cmp::min(buf.len(), CAPTURE_PERIOD_BYTES as usize);
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let mut request_guard = self.rx_gate.lock()?;
+        let completion_token = self.rx_completion.prepare_wait();
+        let (xfer_phys, xfer_virt) = self.dma.rx_xfer;
+        let (pcm_phys, pcm_virt) = self.dma.rx_pcm;
+        let (status_phys, status_virt) = self.dma.rx_status;
+        let status_size = core::mem::size_of::<VirtioSndPcmStatus>();
+
+        unsafe {
+            let xfer = xfer_virt as *mut VirtioSndPcmXfer;
+            (*xfer).stream_id = self.capture_stream.load(Ordering::Acquire);
+            core::ptr::write_bytes(status_virt as *mut u8, 0, status_size);
+        }
+
+        // Device reads the header, then writes PCM data and the status
+        let buffers = [
+            (
+                xfer_phys,
+                core::mem::size_of::<VirtioSndPcmXfer>() as u32,
+                false,
+            ),
+            (pcm_phys, len as u32, true),
+            (status_phys, status_size as u32, true),
+        ];
+
+        {
+            let mut queue = rx_queue.lock();
+            if queue.add_chain(&buffers).is_none() {
+                self.rx_completion.clear();
+                return Err("RX queue full");
+            }
+        }
+
+        fence(Ordering::SeqCst);
+        self.device.notify_queue(3);
+
+        if let Err(e) = self.rx_completion.wait_for_completion(
+            completion_token,
+            "Sound RX timeout",
+        ) {
+            request_guard.wedge();
+            return Err(e);
+        }
+
+        let completed_desc = match self
+            .rx_completion
+            .take_completed_desc("Sound RX woke without completion")
+        {
+            Ok(desc) => desc,
+            Err(e) => {
+                self.rx_completion.clear();
+                return Err(e);
+            }
+        };
+
+        fence(Ordering::SeqCst);
+        let status = unsafe { core::ptr::read_volatile(status_virt as *const u32) };
+        // The used length covers the PCM data plus the trailing status
+        let captured = (self.rx_completion.completed_len() as usize)
+            .saturating_sub(status_size)
+            .min(len);
+
+        {
+            let mut queue = rx_queue.lock();
+            queue.free_chain(completed_desc);
+        }
+
+        self.rx_completion.clear();
+        if status != resp::OK {
+            log::warn!("VirtIO sound: RX failed with code {:#x}", status);
+            return Err("Sound RX failed");
+        }
+
+        unsafe {
+            core::ptr::copy_nonoverlapping(pcm_virt as *const u8, buf.as_mut_ptr(), captured);
+        }
+        Ok(captured)
+    }
+
     fn do_write_pcm(&self, data: &[u8]) -> Result<usize, &'static str> {
         if !self.stream_started.load(Ordering::Acquire) {
             return Err("Stream not started");
@@ -664,6 +922,7 @@ impl VirtioSoundDevice {
 
         self.drain_ctrl_completion();
         self.drain_tx_completion();
+        self.drain_rx_completion();
         true
     }
 
@@ -676,8 +935,8 @@ impl VirtioSoundDevice {
             return;
         };
 
-        if let Some((completed_desc, _bytes)) = queue.get_used() {
-            self.ctrl_completion.complete_desc(completed_desc);
+        if let Some((completed_desc, bytes)) = queue.get_used() {
+            self.ctrl_completion.complete_desc(completed_desc, bytes);
         }
     }
 
@@ -690,8 +949,25 @@ impl VirtioSoundDevice {
             return;
         };
 
-        if let Some((completed_desc, _bytes)) = queue.get_used() {
-            self.tx_completion.complete_desc(completed_desc);
+        if let Some((completed_desc, bytes)) = queue.get_used() {
+            self.tx_completion.complete_desc(completed_desc, bytes);
+        }
+    }
+
+    fn drain_rx_completion(&self) {
+        if !self.rx_completion.has_pending() {
+            return;
+        }
+
+        let Some(rx_queue) = self.rx_queue.as_ref() else {
+            return;
+        };
+        let Some(mut queue) = rx_queue.try_lock() else {
+            return;
+        };
+
+        if let Some((completed_desc, bytes)) = queue.get_used() {
+            self.rx_completion.complete_desc(completed_desc, bytes);
         }
     }
 }
@@ -755,3 +1031,16 @@ pub fn write_pcm(data: &[u8]) -> Result<usize, &'static str> {
     let dev = get_device().ok_or("Sound device not initialized")?;
     dev.do_write_pcm(data)
 }
+
+/// Find and start the device's capture stream (S16_LE, 44100 Hz, stereo)
+pub fn setup_capture() -> Result<(), &'static str> {
+    let dev = get_device().ok_or("Sound device not initialized")?;
+    dev.do_setup_capture()
+}
+
+/// Read one period of captured PCM data (at most 4KB) from the sound device.
+/// Blocks until the device has filled it.
+pub fn read_pcm(buf: &mut [u8]) -> Result<usize, &'static str> {
+    let dev = get_device().ok_or("Sound device not initialized")?;
+    dev.do_read_pcm(buf)
+}
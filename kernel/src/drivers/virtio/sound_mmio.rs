@@ -1,7 +1,8 @@
 //! VirtIO Sound Device Driver for ARM64 (MMIO Transport)
 //!
-//! Implements a basic audio playback driver using VirtIO MMIO transport.
-//! Provides PCM audio output at 44100 Hz, S16_LE, stereo.
+//! Implements a basic audio playback and capture driver using VirtIO MMIO
+//! transport. Provides PCM audio output at 44100 Hz, S16_LE, stereo, and PCM
+//! input in the same format when the device has a capture stream.
 
 use super::mmio::{
     device_id, VirtioMmioDevice, VIRTIO_MMIO_BASE, VIRTIO_MMIO_COUNT, VIRTIO_MMIO_SIZE,
@@ -117,6 +118,8 @@ struct SoundMmioQueueCompletion {
     next_token: AtomicU32,
     pending_token: AtomicU32,
     completed_desc: AtomicU32,
+    /// Bytes the device wrote into the completed chain
+    completed_len: AtomicU32,
     last_used_idx: AtomicU32,
 }
 
@@ -127,6 +130,7 @@ impl SoundMmioQueueCompletion {
             next_token: AtomicU32::new(0),
             pending_token: AtomicU32::new(0),
             completed_desc: AtomicU32::new(NO_COMPLETED_DESC),
+            completed_len: AtomicU32::new(0),
             last_used_idx: AtomicU32::new(0),
         }
     }
@@ -186,16 +190,21 @@ impl SoundMmioQueueCompletion {
         Ok(desc as u16)
     }
 
+    fn completed_len(&self) -> u32 {
+        self.completed_len.load(Ordering::Acquire)
+    }
+
     fn has_pending(&self) -> bool {
         self.pending_token.load(Ordering::Acquire) != 0
     }
 
-    fn complete_desc(&self, completed_desc: u16) -> bool {
+    fn complete_desc(&self, completed_desc: u16, len: u32) -> bool {
         let token = self.pending_token.load(Ordering::Acquire);
         if token == 0 {
             return false;
         }
 
+        self.completed_len.store(len, Ordering::Release);
         self.completed_desc
             .store(completed_desc as u32, Ordering::Release);
         self.completion.complete(token);
@@ -205,11 +214,14 @@ impl SoundMmioQueueCompletion {
 
 static CTRL_GATE: SoundMmioRequestGate = SoundMmioRequestGate::new();
 static TX_GATE: SoundMmioRequestGate = SoundMmioRequestGate::new();
+static RX_GATE: SoundMmioRequestGate = SoundMmioRequestGate::new();
 static CTRL_COMPLETION: SoundMmioQueueCompletion = SoundMmioQueueCompletion::new();
 static TX_COMPLETION: SoundMmioQueueCompletion = SoundMmioQueueCompletion::new();
+static RX_COMPLETION: SoundMmioQueueCompletion = SoundMmioQueueCompletion::new();
 
 /// VirtIO Sound command codes
 mod cmd {
+    pub const PCM_INFO: u32 = 0x0100;
     pub const SET_PARAMS: u32 = 0x0101;
     pub const PREPARE: u32 = 0x0102;
     pub const START: u32 = 0x0104;
@@ -230,6 +242,11 @@ mod pcm_rate {
     pub const RATE_44100: u8 = 6; // VIRTIO_SND_PCM_RATE_44100
 }
 
+/// VirtIO Sound stream directions (from virtio_snd.h)
+mod direction {
+    pub const INPUT: u8 = 1; // VIRTIO_SND_D_INPUT
+}
+
 /// Control header for sound commands
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
@@ -237,6 +254,30 @@ struct VirtioSndHdr {
     code: u32,
 }
 
+/// Item information request (PCM_INFO)
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct VirtioSndQueryInfo {
+    hdr: VirtioSndHdr,
+    start_id: u32,
+    count: u32,
+    size: u32,
+}
+
+/// PCM stream information (device writes back, one per stream)
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct VirtioSndPcmInfo {
+    hda_fn_nid: u32,
+    features: u32,
+    formats: u64,
+    rates: u64,
+    direction: u8,
+    channels_min: u8,
+    channels_max: u8,
+    _padding: [u8; 5],
+}
+
 /// PCM set params request
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
@@ -260,7 +301,7 @@ struct VirtioSndPcmCtrl {
     stream_id: u32,
 }
 
-/// PCM xfer header (for TX queue)
+/// PCM xfer header (for TX and RX queues)
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
 struct VirtioSndPcmXfer {
@@ -362,6 +403,27 @@ static mut TX_QUEUE: QueueMemory = QueueMemory {
     },
 };
 
+// RX queue (queue 3)
+static mut RX_QUEUE: QueueMemory = QueueMemory {
+    desc: [VirtqDesc {
+        addr: 0,
+        len: 0,
+        flags: 0,
+        next: 0,
+    }; 16],
+    avail: VirtqAvail {
+        flags: 0,
+        idx: 0,
+        ring: [0; 16],
+    },
+    _padding: [0; 4096 - 256 - 36],
+    used: VirtqUsed {
+        flags: 0,
+        idx: 0,
+        ring: [VirtqUsedElem { id: 0, len: 0 }; 16],
+    },
+};
+
 // Command/response buffers
 #[repr(C, align(64))]
 struct CmdBuffer {
@@ -388,6 +450,23 @@ static mut PCM_BUF: PcmBuffer = PcmBuffer {
     data: [0; PCM_BUF_SIZE],
 };
 
+// RX path buffers
+static mut RX_XFER: VirtioSndPcmXfer = VirtioSndPcmXfer { stream_id: 0 };
+static mut RX_STATUS: VirtioSndPcmStatus = VirtioSndPcmStatus {
+    status: 0,
+    latency_bytes: 0,
+};
+
+// Captured PCM buffer (one period)
+const CAPTURE_PERIOD_BYTES: usize = 4096;
+#[repr(C, align(64))]
+struct CaptureBuffer {
+    data: [u8; CAPTURE_PERIOD_BYTES],
+}
+static mut RX_PCM_BUF: CaptureBuffer = CaptureBuffer {
+    data: [0; CAPTURE_PERIOD_BYTES],
+};
+
 /// Sound device state
 static mut SOUND_DEVICE: Option<SoundDeviceState> = None;
 
@@ -395,6 +474,12 @@ struct SoundDeviceState {
     base: u64,
     slot: usize,
     stream_started: bool,
+    /// Whether the device exposes an RX queue
+    has_rx: bool,
+    /// Number of PCM streams the device reports
+    streams: u32,
+    /// Stream ID of the capture stream, once started
+    capture_stream: Option<u32>,
 }
 
 #[inline(always)]
@@ -442,11 +527,18 @@ fn init_device(device: &mut VirtioMmioDevice, base: u64, slot: usize) -> Result<
     // Set up TX queue (queue 2)
     setup_queue(device, 2, version, &raw mut TX_QUEUE)?;
 
+    // Set up RX queue (queue 3); capture is optional
+    let has_rx = setup_queue(device, 3, version, &raw mut RX_QUEUE).is_ok();
+
+    // virtio_snd_config: jacks, streams, chmaps
+    let streams = device.read_config_u32(4);
+
     // Mark device ready
     device.driver_ok();
 
     CTRL_COMPLETION.last_used_idx.store(0, Ordering::Release);
     TX_COMPLETION.last_used_idx.store(0, Ordering::Release);
+    RX_COMPLETION.last_used_idx.store(0, Ordering::Release);
 
     unsafe {
         let ptr = &raw mut SOUND_DEVICE;
@@ -454,6 +546,9 @@ fn init_device(device: &mut VirtioMmioDevice, base: u64, slot: usize) -> Result<
             base,
             slot,
             stream_started: false,
+            has_rx,
+            streams,
+            capture_stream: None,
         });
     }
 
@@ -461,7 +556,10 @@ fn init_device(device: &mut VirtioMmioDevice, base: u64, slot: usize) -> Result<
     gic::Gicv2::enable_irq(irq as u8);
     crate::serial_println!("[virtio-sound] Sound MMIO IRQ {} enabled", irq);
 
-    crate::serial_println!("[virtio-sound] Sound device initialized");
+    crate::serial_println!(
+        "[virtio-sound] Sound device initialized ({} PCM streams)",
+        streams
+    );
     Ok(())
 }
 
@@ -614,6 +712,25 @@ pub fn setup_stream() -> Result<(), &'static str> {
     };
     let device = VirtioMmioDevice::probe(base).ok_or("Device disappeared")?;
 
+    start_pcm(&device, &mut request_guard, 0, 32768, 16384)?;
+
+    {
+        let state = sound_device_state_mut()?;
+        state.stream_started = true;
+    }
+
+    crate::serial_println!("[virtio-sound] Stream started (S16_LE, 44100 Hz, stereo)");
+    Ok(())
+}
+
+/// Configure, prepare and start a PCM stream in S16_LE, 44100 Hz, stereo
+fn start_pcm(
+    device: &VirtioMmioDevice,
+    request_guard: &mut SoundMmioRequestGuard<'_>,
+    stream_id: u32,
+    buffer_bytes: u32,
+    period_bytes: u32,
+) -> Result<(), &'static str> {
     let cmd_phys = virt_to_phys(&raw const CMD_BUF as u64);
     let resp_phys = virt_to_phys(&raw const RESP_BUF as u64);
 
@@ -625,9 +742,9 @@ pub fn setup_stream() -> Result<(), &'static str> {
             hdr: VirtioSndHdr {
                 code: cmd::SET_PARAMS,
             },
-            stream_id: 0,
-            buffer_bytes: 32768,
-            period_bytes: 16384,
+            stream_id,
+            buffer_bytes,
+            period_bytes,
             features: 0,
             channels: 2,
             format: pcm_format::S16,
@@ -636,8 +753,8 @@ pub fn setup_stream() -> Result<(), &'static str> {
         };
     }
     send_ctrl_command(
-        &device,
-        &mut request_guard,
+        device,
+        request_guard,
         cmd_phys,
         core::mem::size_of::<VirtioSndPcmSetParams>() as u32,
         resp_phys,
@@ -645,50 +762,120 @@ pub fn setup_stream() -> Result<(), &'static str> {
     )?;
     check_response("SET_PARAMS")?;
 
-    // 2. PREPARE
+    // 2. PREPARE, 3. START
+    for (code, name) in [(cmd::PREPARE, "PREPARE"), (cmd::START, "START")] {
+        unsafe {
+            let cmd_ptr = &raw mut CMD_BUF;
+            let ctrl = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut VirtioSndPcmCtrl);
+            *ctrl = VirtioSndPcmCtrl {
+                hdr: VirtioSndHdr { code },
+                stream_id,
+            };
+        }
+        send_ctrl_command(
+            device,
+            request_guard,
+            cmd_phys,
+            core::mem::size_of::<VirtioSndPcmCtrl>() as u32,
+            resp_phys,
+            core::mem::size_of::<VirtioSndHdr>() as u32,
+        )?;
+        check_response(name)?;
+    }
+    Ok(())
+}
+
+/// Find a capture stream that supports S16_LE, 44100 Hz, stereo
+fn find_capture_stream(
+    device: &VirtioMmioDevice,
+    request_guard: &mut SoundMmioRequestGuard<'_>,
+    streams: u32,
+) -> Result<u32, &'static str> {
+    let info_size = core::mem::size_of::<VirtioSndPcmInfo>() as u32;
+    let hdr_size = core::mem::size_of::<VirtioSndHdr>() as u32;
+    // The response has to fit in RESP_BUF
+    let count = streams.min((256 - hdr_size) / info_size);
+    if count == 0 {
+        return Err("Sound device has no PCM streams");
+    }
+
     unsafe {
         let cmd_ptr = &raw mut CMD_BUF;
-        let ctrl = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut VirtioSndPcmCtrl);
-        *ctrl = VirtioSndPcmCtrl {
-            hdr: VirtioSndHdr { code: cmd::PREPARE },
-            stream_id: 0,
+        let query = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut VirtioSndQueryInfo);
+        *query = VirtioSndQueryInfo {
+            hdr: VirtioSndHdr {
+                code: cmd::PCM_INFO,
+            },
+            start_id: 0,
+            count,
+            size: info_size,
         };
     }
     send_ctrl_command(
-        &device,
-        &mut request_guard,
-        cmd_phys,
-        core::mem::size_of::<VirtioSndPcmCtrl>() as u32,
-        resp_phys,
-        core::mem::size_of::<VirtioSndHdr>() as u32,
+        device,
+        request_guard,
+        virt_to_phys(&raw const CMD_BUF as u64),
+        core::mem::size_of::<VirtioSndQueryInfo>() as u32,
+        virt_to_phys(&raw const RESP_BUF as u64),
+        hdr_size + count * info_size,
     )?;
-    check_response("PREPARE")?;
+    check_response("PCM_INFO")?;
+
+    (0..count)
+        .find(|&id| {
+            let info = unsafe {
+                let resp_ptr = &raw const RESP_BUF;
+                let offset = (hdr_size + id * info_size) as usize;
+                core::ptr::read_unaligned(
+                    (*resp_ptr).data.as_ptr().add(offset) as *const VirtioSndPcmInfo
+                )
+            };
+            info.direction == direction::INPUT
+                && info.formats & (1 << pcm_format::S16) != 0
+                && info.rates & (1 << pcm_rate::RATE_44100) != 0
+                && (info.channels_min..=info.channels_max).contains(&2)
+        })
+        .ok_or("Sound device has no usable capture stream")
+}
 
-    // 3. START
-    unsafe {
-        let cmd_ptr = &raw mut CMD_BUF;
-        let ctrl = &mut *((*cmd_ptr).data.as_mut_ptr() as *mut VirtioSndPcmCtrl);
-        *ctrl = VirtioSndPcmCtrl {
-            hdr: VirtioSndHdr { code: cmd::START },
-            stream_id: 0,
-        };
+/// Find and start the device's capture stream (S16_LE, 44100 Hz, stereo)
+pub fn setup_capture() -> Result<(), &'static str> {
+    if !irq_completion_available() {
+        return Err("Sound MMIO IRQ completion unavailable before interrupts are enabled");
     }
-    send_ctrl_command(
+
+    let mut request_guard = CTRL_GATE.lock()?;
+
+    let (base, streams) = {
+        let state = sound_device_state()?;
+        if state.capture_stream.is_some() {
+            return Ok(());
+        }
+        if !state.has_rx {
+            return Err("Sound device has no RX queue");
+        }
+        (state.base, state.streams)
+    };
+    let device = VirtioMmioDevice::probe(base).ok_or("Device disappeared")?;
+
+    let stream_id = find_capture_stream(&device, &mut request_guard, streams)?;
+    start_pcm(
         &device,
         &mut request_guard,
-        cmd_phys,
-        core::mem::size_of::<VirtioSndPcmCtrl>() as u32,
-        resp_phys,
-        core::mem::size_of::<VirtioSndHdr>() as u32,
+        stream_id,
+        CAPTURE_PERIOD_BYTES as u32 * 4,
+        CAPTURE_PERIOD_BYTES as u32,
     )?;
-    check_response("START")?;
 
     {
         let state = sound_device_state_mut()?;
-        state.stream_started = true;
+        state.capture_stream = Some(stream_id);
     }
 
-    crate::serial_println!("[virtio-sound] Stream started (S16_LE, 44100 Hz, stereo)");
+    crate::serial_println!(
+        "[virtio-sound] Capture stream {} started (S16_LE, 44100 Hz, stereo)",
+        stream_id
+    );
     Ok(())
 }
 
@@ -826,6 +1013,126 @@ pub fn write_pcm(data: &[u8]) -> Result<usize, &'static str> {
     Ok(len)
 }
 
+/// Read one period of captured PCM data from the sound device
+///
+/// Data is S16_LE stereo at 44100 Hz. At most 4KB per call; blocks until the
+/// device has filled the buffer.
+pub fn read_pcm(buf: &mut [u8]) -> Result<usize, &'static str> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+    if !irq_completion_available() {
+        return Err("Sound MMIO IRQ completion unavailable before interrupts are enabled");
+    }
+
+    let len = core::cmp::min(buf.len(), CAPTURE_PERIOD_BYTES);
+
+    let (base, stream_id) = {
+        let state = sound_device_state()?;
+        let stream_id = state.capture_stream.ok_or("Capture stream not started")?;
+        (state.base, stream_id)
+    };
+    let device = VirtioMmioDevice::probe(base).ok_or("Device disappeared")?;
+    let mut request_guard = RX_GATE.lock()?;
+    let completion_token = RX_COMPLETION.prepare_wait();
+
+    let xfer_phys = virt_to_phys(&raw const RX_XFER as u64);
+    let pcm_phys = virt_to_phys(&raw const RX_PCM_BUF as u64);
+    let status_phys = virt_to_phys(&raw const RX_STATUS as u64);
+    let status_size = core::mem::size_of::<VirtioSndPcmStatus>();
+
+    unsafe {
+        let xfer_ptr = &raw mut RX_XFER;
+        (*xfer_ptr).stream_id = stream_id;
+
+        let status_ptr = &raw mut RX_STATUS;
+        (*status_ptr).status = 0;
+        (*status_ptr).latency_bytes = 0;
+
+        let queue_ptr = &raw mut RX_QUEUE;
+
+        // Descriptor 0: xfer header (device reads)
+        (*queue_ptr).desc[0] = VirtqDesc {
+            addr: xfer_phys,
+            len: core::mem::size_of::<VirtioSndPcmXfer>() as u32,
+            flags: DESC_F_NEXT,
+            next: 1,
+        };
+
+        // Descriptor 1: PCM data (device writes)
+        (*queue_ptr).desc[1] = VirtqDesc {
+            addr: pcm_phys,
+            len: len as u32,
+            flags: DESC_F_NEXT | DESC_F_WRITE,
+            next: 2,
+        };
+
+        // Descriptor 2: status (device writes)
+        (*queue_ptr).desc[2] = VirtqDesc {
+            addr: status_phys,
+            len: status_size as u32,
+            flags: DESC_F_WRITE,
+            next: 0,
+        };
+
+        // Add to available ring
+        let avail_idx = (*queue_ptr).avail.idx;
+        (*queue_ptr).avail.ring[(avail_idx % 16) as usize] = 0;
+        fence(Ordering::SeqCst);
+        (*queue_ptr).avail.idx = avail_idx.wrapping_add(1);
+        fence(Ordering::SeqCst);
+    }
+
+    // Notify device (queue 3 = rxq)
+    dsb_sy();
+    device.notify_queue(3);
+
+    if let Err(e) = RX_COMPLETION.wait_for_completion(
+        completion_token,
+        "Sound MMIO RX timeout",
+    ) {
+        request_guard.wedge();
+        return Err(e);
+    }
+
+    let completed_desc =
+        match RX_COMPLETION.take_completed_desc("Sound MMIO RX woke without completion") {
+            Ok(desc) => desc,
+            Err(e) => {
+                RX_COMPLETION.clear();
+                return Err(e);
+            }
+        };
+
+    if completed_desc != 0 {
+        RX_COMPLETION.clear();
+        return Err("Sound MMIO RX completed unexpected descriptor");
+    }
+
+    dsb_sy();
+    fence(Ordering::SeqCst);
+    let rx_status = unsafe {
+        let status_ptr = &raw const RX_STATUS;
+        read_volatile(&(*status_ptr).status)
+    };
+    // The used length covers the PCM data plus the trailing status
+    let captured = (RX_COMPLETION.completed_len() as usize)
+        .saturating_sub(status_size)
+        .min(len);
+
+    RX_COMPLETION.clear();
+    if rx_status != resp::OK {
+        crate::serial_println!("[virtio-sound] RX failed with code {:#x}", rx_status);
+        return Err("Sound MMIO RX failed");
+    }
+
+    unsafe {
+        let buf_ptr = &raw const RX_PCM_BUF;
+        buf[..captured].copy_from_slice(&(&(*buf_ptr).data)[..captured]);
+    }
+    Ok(captured)
+}
+
 /// Return the GIC SPI assigned to the VirtIO MMIO sound device.
 pub fn get_irq() -> Option<u32> {
     unsafe {
@@ -862,6 +1169,7 @@ pub fn handle_interrupt() {
 
     drain_queue_completion(&CTRL_COMPLETION, &raw const CTRL_QUEUE);
     drain_queue_completion(&TX_COMPLETION, &raw const TX_QUEUE);
+    drain_queue_completion(&RX_COMPLETION, &raw const RX_QUEUE);
 }
 
 fn drain_queue_completion(completion: &SoundMmioQueueCompletion, queue: *const QueueMemory) {
@@ -882,5 +1190,5 @@ fn drain_queue_completion(completion: &SoundMmioQueueCompletion, queue: *const Q
     completion
         .last_used_idx
         .store(used_idx as u32, Ordering::Release);
-    completion.complete_desc(used_elem.id as u16);
+    completion.complete_desc(used_elem.id as u16, used_elem.len);
 }
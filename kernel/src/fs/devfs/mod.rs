@@ -9,6 +9,7 @@
 //! - `/dev/zero` - Discards all writes, reads return zero bytes
 //! - `/dev/console` - System console (serial output)
 //! - `/dev/tty` - Current process's controlling terminal
+//! - `/dev/dsp` - Audio playback and capture; each open is a separate mixer stream
 //!
 //! # Architecture
 //!
@@ -29,6 +30,9 @@ use alloc::string::String;
 use alloc::vec::Vec;
 use spin::Mutex;
 
+/// Filesystem type magic reported by statfs (Linux DEVFS_SUPER_MAGIC)
+pub const DEVFS_SUPER_MAGIC: u32 = 0x1373;
+
 /// Device types supported by devfs
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DeviceType {
@@ -40,6 +44,8 @@ pub enum DeviceType {
     Console,
     /// /dev/tty - controlling terminal
     Tty,
+    /// /dev/dsp - audio output and input (opens become mixer streams)
+    Dsp,
 }
 
 impl DeviceType {
@@ -50,6 +56,7 @@ impl DeviceType {
             DeviceType::Zero => "zero",
             DeviceType::Console => "console",
             DeviceType::Tty => "tty",
+            DeviceType::Dsp => "dsp",
         }
     }
 
@@ -61,6 +68,7 @@ impl DeviceType {
             DeviceType::Zero => 2,
             DeviceType::Console => 3,
             DeviceType::Tty => 4,
+            DeviceType::Dsp => 5,
         }
     }
 
@@ -140,6 +148,9 @@ pub fn init() {
         .push(DeviceNode::new(DeviceType::Console, 5, 1)); // /dev/console
     devfs.devices.push(DeviceNode::new(DeviceType::Tty, 5, 0)); // /dev/tty
 
+    // Major 14 = OSS sound devices
+    devfs.devices.push(DeviceNode::new(DeviceType::Dsp, 14, 3)); // /dev/dsp
+
     devfs.initialized = true;
     log::info!("devfs: initialized with {} devices", devfs.devices.len());
 
@@ -203,6 +214,10 @@ pub fn device_read(device_type: DeviceType, buf: &mut [u8]) -> Result<usize, i32
             // In the future, this would read from keyboard buffer
             Err(-11) // EAGAIN
         }
+        DeviceType::Dsp => {
+            // Opens of /dev/dsp become mixer streams; the bare device has no data
+            Err(-22) // EINVAL
+        }
     }
 }
 
@@ -220,5 +235,6 @@ pub fn device_write(device_type: DeviceType, buf: &[u8]) -> Result<usize, i32> {
             }
             Ok(buf.len())
         }
+        DeviceType::Dsp => Err(-22), // EINVAL
     }
 }
@@ -31,6 +31,9 @@ use spin::Mutex;
 
 use crate::tty::pty;
 
+/// Filesystem type magic reported by statfs (Linux DEVPTS_SUPER_MAGIC)
+pub const DEVPTS_SUPER_MAGIC: u32 = 0x1cd1;
+
 /// Directory entry for /dev/pts listing
 #[derive(Debug, Clone)]
 pub struct PtsEntry {
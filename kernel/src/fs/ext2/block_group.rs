@@ -5,7 +5,7 @@
 
 use crate::block::{BlockDevice, BlockError};
 use crate::fs::ext2::file::{read_ext2_block, write_ext2_block};
-use crate::fs::ext2::Ext2Superblock;
+use crate::fs::ext2::{Ext2Error, Ext2Superblock};
 use alloc::vec::Vec;
 use core::mem;
 
@@ -179,12 +179,12 @@ impl Ext2BlockGroupDesc {
 ///
 /// # Returns
 /// * `Ok(block_num)` - The allocated block number
-/// * `Err(msg)` - Error if no free blocks available or I/O error
+/// * `Err(e)` - `NoSpace` if no free blocks are available, `Io` on I/O error
 pub fn allocate_block<B: BlockDevice + ?Sized>(
     device: &B,
     superblock: &Ext2Superblock,
     block_groups: &mut [Ext2BlockGroupDesc],
-) -> Result<u32, &'static str> {
+) -> Result<u32, Ext2Error> {
     let block_size = superblock.block_size();
     let blocks_per_group = superblock.s_blocks_per_group;
 
@@ -209,7 +209,7 @@ pub fn allocate_block<B: BlockDevice + ?Sized>(
             block_size,
             &mut bitmap_buf[..block_size],
         )
-        .map_err(|_| "Failed to read block bitmap")?;
+        .map_err(|_| Ext2Error::Io)?;
 
         // Search for a free block in this group
         // s_first_data_block is the first data block in the filesystem (usually 1 for 1KB blocks)
@@ -235,7 +235,7 @@ pub fn allocate_block<B: BlockDevice + ?Sized>(
                 if let Err(_) =
                     write_ext2_block(device, bitmap_block, block_size, &bitmap_buf[..block_size])
                 {
-                    return Err("Failed to write block bitmap");
+                    return Err(Ext2Error::Io);
                 }
 
                 // Update the free block count in the block group descriptor
@@ -252,7 +252,7 @@ pub fn allocate_block<B: BlockDevice + ?Sized>(
                 if let Err(_) =
                     write_ext2_block(device, global_block, block_size, &zero_buf[..block_size])
                 {
-                    return Err("Failed to zero allocated block");
+                    return Err(Ext2Error::Io);
                 }
 
                 return Ok(global_block);
@@ -260,7 +260,7 @@ pub fn allocate_block<B: BlockDevice + ?Sized>(
         }
     }
 
-    Err("No free blocks available")
+    Err(Ext2Error::NoSpace)
 }
 
 /// Free a data block in the block bitmap
@@ -276,20 +276,20 @@ pub fn allocate_block<B: BlockDevice + ?Sized>(
 ///
 /// # Returns
 /// * `Ok(())` - Block was successfully freed
-/// * `Err(msg)` - Error message if operation failed
+/// * `Err(e)` - Why the operation failed
 pub fn free_block<B: BlockDevice + ?Sized>(
     device: &B,
     block_num: u32,
     superblock: &Ext2Superblock,
     block_groups: &mut [Ext2BlockGroupDesc],
-) -> Result<(), &'static str> {
+) -> Result<(), Ext2Error> {
     let block_size = superblock.block_size();
     let blocks_per_group = superblock.s_blocks_per_group;
     let first_data_block = superblock.s_first_data_block;
 
     // Block number must be >= s_first_data_block
     if block_num < first_data_block {
-        return Err("Invalid block number (below first data block)");
+        return Err(Ext2Error::InvalidArgument);
     }
 
     // Calculate which block group contains this block
@@ -299,7 +299,7 @@ pub fn free_block<B: BlockDevice + ?Sized>(
     let local_block = adjusted_block % blocks_per_group;
 
     if bg_index >= block_groups.len() {
-        return Err("Block number out of range");
+        return Err(Ext2Error::Io);
     }
 
     let bg = &mut block_groups[bg_index];
@@ -315,7 +315,7 @@ pub fn free_block<B: BlockDevice + ?Sized>(
         block_size,
         &mut bitmap_buf[..block_size],
     )
-    .map_err(|_| "Failed to read block bitmap")?;
+    .map_err(|_| Ext2Error::Io)?;
 
     // Clear the bit for this block
     let byte_index = (local_block / 8) as usize;
@@ -327,7 +327,7 @@ pub fn free_block<B: BlockDevice + ?Sized>(
 
     // Write the updated bitmap back
     write_ext2_block(device, bitmap_block, block_size, &bitmap_buf[..block_size])
-        .map_err(|_| "Failed to write block bitmap")?;
+        .map_err(|_| Ext2Error::Io)?;
 
     // Update the free block count
     let free_blocks =
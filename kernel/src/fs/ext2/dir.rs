@@ -1,3 +1,4 @@
+use crate::fs::ext2::Ext2Error;
 use alloc::string::String;
 use alloc::vec::Vec;
 use core::mem::size_of;
@@ -12,6 +13,9 @@ pub const EXT2_FT_FIFO: u8 = 5;
 pub const EXT2_FT_SOCK: u8 = 6;
 pub const EXT2_FT_SYMLINK: u8 = 7;
 
+/// Maximum length of a directory entry name (name_len is a u8)
+pub const EXT2_NAME_LEN: usize = 255;
+
 /// ext2 directory entry structure (variable size)
 /// Layout on disk:
 /// - inode: u32 (4 bytes)
@@ -264,15 +268,15 @@ pub fn find_entry_location(data: &[u8], name: &str) -> Option<DirEntryLocation>
 ///
 /// # Returns
 /// * `Ok(u32)` - Inode number of the removed entry
-/// * `Err(&'static str)` - Error message
-pub fn remove_entry(data: &mut [u8], name: &str) -> Result<u32, &'static str> {
+/// * `Err(Ext2Error)` - Why the removal failed
+pub fn remove_entry(data: &mut [u8], name: &str) -> Result<u32, Ext2Error> {
     // Cannot remove . or ..
     if name == "." || name == ".." {
-        return Err("Cannot remove . or ..");
+        return Err(Ext2Error::InvalidArgument);
     }
 
     // Find the entry location
-    let location = find_entry_location(data, name).ok_or("Entry not found")?;
+    let location = find_entry_location(data, name).ok_or(Ext2Error::NotFound)?;
     let removed_inode = location.entry.inode;
 
     // If there's a previous entry, extend its rec_len to include this entry
@@ -321,16 +325,19 @@ fn required_entry_size(name_len: usize) -> usize {
 ///
 /// # Returns
 /// * `Ok(())` - Entry was added successfully
-/// * `Err(msg)` - No space available or other error
+/// * `Err(e)` - `NoSpace` if the directory is full, or another error
 pub fn add_directory_entry(
     dir_data: &mut Vec<u8>,
     new_inode: u32,
     name: &str,
     file_type: u8,
-) -> Result<(), &'static str> {
+) -> Result<(), Ext2Error> {
     let name_bytes = name.as_bytes();
-    if name_bytes.is_empty() || name_bytes.len() > 255 {
-        return Err("Invalid name length");
+    if name_bytes.is_empty() {
+        return Err(Ext2Error::InvalidArgument);
+    }
+    if name_bytes.len() > EXT2_NAME_LEN {
+        return Err(Ext2Error::NameTooLong);
     }
 
     let new_entry_size = required_entry_size(name_bytes.len());
@@ -354,7 +361,7 @@ pub fn add_directory_entry(
         let entry_name_len = dir_data[offset + 6] as usize;
 
         if rec_len == 0 || rec_len < MIN_DIR_ENTRY_SIZE {
-            return Err("Corrupt directory entry");
+            return Err(Ext2Error::Io);
         }
 
         if entry_inode == 0 {
@@ -404,7 +411,7 @@ pub fn add_directory_entry(
 
     // No space found in existing entries - need to extend the directory
     // This would require allocating a new data block, which is complex
-    Err("No space in directory")
+    Err(Ext2Error::NoSpace)
 }
 
 /// Write a directory entry at the given offset
@@ -466,12 +473,12 @@ pub fn is_directory_empty(dir_data: &[u8]) -> bool {
 ///
 /// # Returns
 /// * `Ok(())` - Entry was updated
-/// * `Err(msg)` - Entry not found or other error
+/// * `Err(e)` - `NotFound` if the entry is missing, or another error
 pub fn update_directory_entry(
     dir_data: &mut [u8],
     name: &str,
     new_inode: u32,
-) -> Result<(), &'static str> {
+) -> Result<(), Ext2Error> {
     let mut offset = 0usize;
 
     while offset < dir_data.len() {
@@ -491,7 +498,7 @@ pub fn update_directory_entry(
         let entry_name_len = dir_data[offset + 6] as usize;
 
         if rec_len == 0 || rec_len < MIN_DIR_ENTRY_SIZE {
-            return Err("Corrupt directory entry");
+            return Err(Ext2Error::Io);
         }
 
         // Check if this is the entry we're looking for
@@ -510,7 +517,7 @@ pub fn update_directory_entry(
         offset += rec_len;
     }
 
-    Err("Entry not found")
+    Err(Ext2Error::NotFound)
 }
 
 #[cfg(test)]
@@ -0,0 +1,125 @@
+//! ext2 error types
+//!
+//! Every `Ext2Fs` operation reports failures through `Ext2Error`, which maps
+//! one-to-one onto a POSIX errno. Syscalls convert it (via `VfsError`) instead
+//! of inspecting error strings, so userspace always sees the precise errno.
+
+use crate::block::BlockError;
+use crate::syscall::errno;
+use core::fmt;
+
+/// Errors returned by ext2 filesystem operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ext2Error {
+    /// A path component or directory entry does not exist (ENOENT)
+    NotFound,
+    /// The target name already exists (EEXIST)
+    AlreadyExists,
+    /// No free blocks, inodes, or directory space (ENOSPC)
+    NoSpace,
+    /// Directory contains entries other than "." and ".." (ENOTEMPTY)
+    NotEmpty,
+    /// A directory was given where a non-directory was required (EISDIR)
+    IsDirectory,
+    /// A non-directory was given where a directory was required (ENOTDIR)
+    NotDirectory,
+    /// A path component exceeds EXT2_NAME_LEN bytes (ENAMETOOLONG)
+    NameTooLong,
+    /// The filesystem is mounted read-only (EROFS)
+    ReadOnly,
+    /// Block device failure or on-disk corruption (EIO)
+    Io,
+    /// The operation would cross a filesystem boundary (EXDEV)
+    CrossDevice,
+    /// Malformed path, name, or argument (EINVAL)
+    InvalidArgument,
+    /// Symlink resolution exceeded the nesting limit (ELOOP)
+    TooManySymlinks,
+    /// The operation is not allowed on this object, e.g. hard-linking a directory (EPERM)
+    NotPermitted,
+    /// The object is in use, e.g. removing the root directory (EBUSY)
+    Busy,
+    /// The requested size exceeds what the block map can address (EFBIG)
+    FileTooLarge,
+}
+
+impl Ext2Error {
+    /// The POSIX errno corresponding to this error
+    pub fn to_errno(self) -> i32 {
+        match self {
+            Ext2Error::NotFound => errno::ENOENT,
+            Ext2Error::AlreadyExists => errno::EEXIST,
+            Ext2Error::NoSpace => errno::ENOSPC,
+            Ext2Error::NotEmpty => errno::ENOTEMPTY,
+            Ext2Error::IsDirectory => errno::EISDIR,
+            Ext2Error::NotDirectory => errno::ENOTDIR,
+            Ext2Error::NameTooLong => errno::ENAMETOOLONG,
+            Ext2Error::ReadOnly => errno::EROFS,
+            Ext2Error::Io => errno::EIO,
+            Ext2Error::CrossDevice => errno::EXDEV,
+            Ext2Error::InvalidArgument => errno::EINVAL,
+            Ext2Error::TooManySymlinks => errno::ELOOP,
+            Ext2Error::NotPermitted => errno::EPERM,
+            Ext2Error::Busy => errno::EBUSY,
+            Ext2Error::FileTooLarge => errno::EFBIG,
+        }
+    }
+
+    /// Short human-readable description, for logs and `&'static str` callers
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Ext2Error::NotFound => "No such file or directory",
+            Ext2Error::AlreadyExists => "File exists",
+            Ext2Error::NoSpace => "No space left on device",
+            Ext2Error::NotEmpty => "Directory not empty",
+            Ext2Error::IsDirectory => "Is a directory",
+            Ext2Error::NotDirectory => "Not a directory",
+            Ext2Error::NameTooLong => "File name too long",
+            Ext2Error::ReadOnly => "Read-only file system",
+            Ext2Error::Io => "I/O error",
+            Ext2Error::CrossDevice => "Invalid cross-device link",
+            Ext2Error::InvalidArgument => "Invalid argument",
+            Ext2Error::TooManySymlinks => "Too many levels of symbolic links",
+            Ext2Error::NotPermitted => "Operation not permitted",
+            Ext2Error::Busy => "Device or resource busy",
+            Ext2Error::FileTooLarge => "File too large",
+        }
+    }
+}
+
+impl fmt::Display for Ext2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<BlockError> for Ext2Error {
+    fn from(_: BlockError) -> Self {
+        Ext2Error::Io
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errno_mapping() {
+        assert_eq!(Ext2Error::NotFound.to_errno(), 2);
+        assert_eq!(Ext2Error::AlreadyExists.to_errno(), 17);
+        assert_eq!(Ext2Error::NoSpace.to_errno(), 28);
+        assert_eq!(Ext2Error::NotEmpty.to_errno(), 39);
+        assert_eq!(Ext2Error::IsDirectory.to_errno(), 21);
+        assert_eq!(Ext2Error::NotDirectory.to_errno(), 20);
+        assert_eq!(Ext2Error::NameTooLong.to_errno(), 36);
+        assert_eq!(Ext2Error::ReadOnly.to_errno(), 30);
+        assert_eq!(Ext2Error::Io.to_errno(), 5);
+        assert_eq!(Ext2Error::CrossDevice.to_errno(), 18);
+    }
+
+    #[test]
+    fn test_block_error_is_io() {
+        assert_eq!(Ext2Error::from(BlockError::Timeout), Ext2Error::Io);
+        assert_eq!(Ext2Error::from(BlockError::OutOfBounds), Ext2Error::Io);
+    }
+}
@@ -4,7 +4,7 @@
 //! in the inode (direct, single/double/triple indirect blocks).
 
 use crate::block::{BlockDevice, BlockError};
-use crate::fs::ext2::{Ext2Inode, Ext2Superblock};
+use crate::fs::ext2::{Ext2Error, Ext2Inode, Ext2Superblock};
 use alloc::vec::Vec;
 
 /// Read an ext2 block using device block numbers
@@ -440,7 +440,7 @@ fn read_indirect_block<B: BlockDevice + ?Sized>(
 ///
 /// # Returns
 /// * `Ok(())` - Block pointer set successfully
-/// * `Err(BlockError)` - I/O error or allocation failure
+/// * `Err(Ext2Error)` - I/O error or allocation failure (`NoSpace`)
 pub fn set_block_num<B: BlockDevice + ?Sized>(
     device: &B,
     inode: &mut Ext2Inode,
@@ -448,7 +448,7 @@ pub fn set_block_num<B: BlockDevice + ?Sized>(
     block_groups: &mut [super::Ext2BlockGroupDesc],
     logical_block: u32,
     physical_block: u32,
-) -> Result<(), BlockError> {
+) -> Result<(), Ext2Error> {
     let block_size = superblock.block_size();
     let ptrs_per_block = (block_size / 4) as u32;
 
@@ -475,8 +475,7 @@ pub fn set_block_num<B: BlockDevice + ?Sized>(
         if single_indirect_ptr == 0 {
             // Allocate a new indirect block
             single_indirect_ptr =
-                super::block_group::allocate_block(device, superblock, block_groups)
-                    .map_err(|_| BlockError::IoError)?;
+                super::block_group::allocate_block(device, superblock, block_groups)?;
 
             // Update the inode's indirect block pointer
             unsafe {
@@ -507,8 +506,7 @@ pub fn set_block_num<B: BlockDevice + ?Sized>(
         if double_indirect_ptr == 0 {
             // Allocate a new double indirect block
             double_indirect_ptr =
-                super::block_group::allocate_block(device, superblock, block_groups)
-                    .map_err(|_| BlockError::IoError)?;
+                super::block_group::allocate_block(device, superblock, block_groups)?;
 
             // Update the inode's double indirect block pointer
             unsafe {
@@ -528,8 +526,8 @@ pub fn set_block_num<B: BlockDevice + ?Sized>(
         let mut second_level_ptr = first_level_blocks[first_level_index];
         if second_level_ptr == 0 {
             // Allocate a new second-level indirect block
-            second_level_ptr = super::block_group::allocate_block(device, superblock, block_groups)
-                .map_err(|_| BlockError::IoError)?;
+            second_level_ptr =
+                super::block_group::allocate_block(device, superblock, block_groups)?;
 
             // Update the first-level block with the new pointer
             first_level_blocks[first_level_index] = second_level_ptr;
@@ -551,8 +549,7 @@ pub fn set_block_num<B: BlockDevice + ?Sized>(
 
     if triple_indirect_ptr == 0 {
         // Allocate a new triple indirect block
-        triple_indirect_ptr = super::block_group::allocate_block(device, superblock, block_groups)
-            .map_err(|_| BlockError::IoError)?;
+        triple_indirect_ptr = super::block_group::allocate_block(device, superblock, block_groups)?;
 
         // Update the inode's triple indirect block pointer
         unsafe {
@@ -573,8 +570,7 @@ pub fn set_block_num<B: BlockDevice + ?Sized>(
     // Get or allocate second-level indirect block
     let mut second_level_ptr = first_level_blocks[first_level_index];
     if second_level_ptr == 0 {
-        second_level_ptr = super::block_group::allocate_block(device, superblock, block_groups)
-            .map_err(|_| BlockError::IoError)?;
+        second_level_ptr = super::block_group::allocate_block(device, superblock, block_groups)?;
 
         first_level_blocks[first_level_index] = second_level_ptr;
         write_indirect_block(device, triple_indirect_ptr, block_size, &first_level_blocks)?;
@@ -586,8 +582,7 @@ pub fn set_block_num<B: BlockDevice + ?Sized>(
     // Get or allocate third-level indirect block
     let mut third_level_ptr = second_level_blocks[second_level_index];
     if third_level_ptr == 0 {
-        third_level_ptr = super::block_group::allocate_block(device, superblock, block_groups)
-            .map_err(|_| BlockError::IoError)?;
+        third_level_ptr = super::block_group::allocate_block(device, superblock, block_groups)?;
 
         second_level_blocks[second_level_index] = third_level_ptr;
         write_indirect_block(device, second_level_ptr, block_size, &second_level_blocks)?;
@@ -612,14 +607,14 @@ pub fn set_block_num<B: BlockDevice + ?Sized>(
 ///
 /// # Returns
 /// * `Ok(())` - Write successful
-/// * `Err(BlockError)` - I/O error
+/// * `Err(Ext2Error)` - I/O error or no free blocks
 pub fn write_file<B: BlockDevice + ?Sized>(
     device: &B,
     inode: &mut Ext2Inode,
     superblock: &Ext2Superblock,
     block_groups: &mut [super::Ext2BlockGroupDesc],
     data: &[u8],
-) -> Result<(), BlockError> {
+) -> Result<(), Ext2Error> {
     write_file_range(device, inode, superblock, block_groups, 0, data)
 }
 
@@ -635,7 +630,7 @@ pub fn write_file<B: BlockDevice + ?Sized>(
 ///
 /// # Returns
 /// * `Ok(())` - Write successful
-/// * `Err(BlockError)` - I/O error
+/// * `Err(Ext2Error)` - I/O error or no free blocks
 pub fn write_file_range<B: BlockDevice + ?Sized>(
     device: &B,
     inode: &mut Ext2Inode,
@@ -643,7 +638,7 @@ pub fn write_file_range<B: BlockDevice + ?Sized>(
     block_groups: &mut [super::Ext2BlockGroupDesc],
     offset: u64,
     data: &[u8],
-) -> Result<(), BlockError> {
+) -> Result<(), Ext2Error> {
     if data.is_empty() {
         return Ok(());
     }
@@ -665,12 +660,7 @@ pub fn write_file_range<B: BlockDevice + ?Sized>(
             Ok(None) => {
                 // Sparse hole or no block allocated - allocate a new block
                 let new_block =
-                    match super::block_group::allocate_block(device, superblock, block_groups) {
-                        Ok(b) => b,
-                        Err(_) => {
-                            return Err(BlockError::IoError);
-                        }
-                    };
+                    super::block_group::allocate_block(device, superblock, block_groups)?;
 
                 // Set the block pointer in the inode
                 if let Err(e) = set_block_num(
@@ -695,7 +685,7 @@ pub fn write_file_range<B: BlockDevice + ?Sized>(
                 new_block
             }
             Err(e) => {
-                return Err(e);
+                return Err(e.into());
             }
         };
 
@@ -721,7 +711,7 @@ pub fn write_file_range<B: BlockDevice + ?Sized>(
                 block_size,
                 &mut block_buf[..block_size],
             ) {
-                return Err(e);
+                return Err(e.into());
             }
         }
 
@@ -734,7 +724,7 @@ pub fn write_file_range<B: BlockDevice + ?Sized>(
         if let Err(e) =
             write_ext2_block(device, physical_block, block_size, &block_buf[..block_size])
         {
-            return Err(e);
+            return Err(e.into());
         }
     }
 
@@ -756,6 +746,174 @@ pub fn write_file_range<B: BlockDevice + ?Sized>(
     Ok(())
 }
 
+/// Number of logical blocks addressable through the inode's block map
+///
+/// Direct blocks plus the single, double, and triple indirect trees.
+pub fn max_logical_blocks(superblock: &Ext2Superblock) -> u64 {
+    let ptrs_per_block = (superblock.block_size() / 4) as u64;
+    DIRECT_BLOCKS as u64
+        + ptrs_per_block
+        + ptrs_per_block * ptrs_per_block
+        + ptrs_per_block * ptrs_per_block * ptrs_per_block
+}
+
+/// Free every block of a file at or beyond a logical block index
+///
+/// Direct pointers past the cut are cleared, and the indirect trees are
+/// walked so that data blocks past the cut are freed together with any
+/// indirect block left without entries. The caller updates i_size, i_blocks,
+/// and the superblock free-block count.
+///
+/// # Arguments
+/// * `device` - The block device to read/write
+/// * `inode` - The inode to shrink (block pointers are modified in place)
+/// * `superblock` - The superblock (for block size calculation)
+/// * `block_groups` - Mutable reference to block group descriptors (for block freeing)
+/// * `first_block` - First logical block to free; earlier blocks are kept
+///
+/// # Returns
+/// * `Ok(blocks_freed)` - Number of blocks freed, including indirect blocks
+/// * `Err(Ext2Error)` - I/O error
+pub fn free_blocks_from<B: BlockDevice + ?Sized>(
+    device: &B,
+    inode: &mut Ext2Inode,
+    superblock: &Ext2Superblock,
+    block_groups: &mut [super::Ext2BlockGroupDesc],
+    first_block: u64,
+) -> Result<u32, Ext2Error> {
+    let ptrs_per_block = (superblock.block_size() / 4) as u64;
+    let mut i_block = unsafe { core::ptr::read_unaligned(core::ptr::addr_of!(inode.i_block)) };
+    let mut blocks_freed = 0u32;
+
+    // Direct blocks (0-11)
+    let first_direct = core::cmp::min(first_block, DIRECT_BLOCKS as u64) as usize;
+    for block_ptr in i_block[first_direct..DIRECT_BLOCKS as usize].iter_mut() {
+        if *block_ptr != 0 {
+            super::block_group::free_block(device, *block_ptr, superblock, block_groups)?;
+            *block_ptr = 0;
+            blocks_freed += 1;
+        }
+    }
+
+    // Single, double, and triple indirect trees, each covering `span` blocks
+    let mut tree_start = DIRECT_BLOCKS as u64;
+    let mut span = ptrs_per_block;
+    for (index, depth) in [
+        (SINGLE_INDIRECT, 1),
+        (DOUBLE_INDIRECT, 2),
+        (TRIPLE_INDIRECT, 3),
+    ] {
+        let tree_ptr = i_block[index];
+        if tree_ptr != 0 && first_block < tree_start + span {
+            let (freed, empty) = truncate_indirect_block(
+                device,
+                superblock,
+                block_groups,
+                tree_ptr,
+                depth,
+                first_block.saturating_sub(tree_start),
+            )?;
+            blocks_freed += freed;
+
+            if empty {
+                super::block_group::free_block(device, tree_ptr, superblock, block_groups)?;
+                i_block[index] = 0;
+                blocks_freed += 1;
+            }
+        }
+        tree_start += span;
+        span *= ptrs_per_block;
+    }
+
+    unsafe {
+        core::ptr::write_unaligned(core::ptr::addr_of_mut!(inode.i_block), i_block);
+    }
+
+    Ok(blocks_freed)
+}
+
+/// Free the entries of an indirect block that map logical blocks past a cut
+///
+/// # Arguments
+/// * `depth` - 1 for a block of data pointers, 2 for double, 3 for triple indirect
+/// * `first` - First logical block to free, relative to the start of this subtree
+///
+/// # Returns
+/// * `Ok((blocks_freed, empty))` - Blocks freed beneath this one, and whether
+///   every entry is now zero (the caller then frees this block too)
+/// * `Err(Ext2Error)` - I/O error
+fn truncate_indirect_block<B: BlockDevice + ?Sized>(
+    device: &B,
+    superblock: &Ext2Superblock,
+    block_groups: &mut [super::Ext2BlockGroupDesc],
+    block_num: u32,
+    depth: u32,
+    first: u64,
+) -> Result<(u32, bool), Ext2Error> {
+    let block_size = superblock.block_size();
+    let ptrs_per_block = block_size / 4;
+    let entry_span = (ptrs_per_block as u64).pow(depth - 1);
+
+    // Use stack-based buffer to avoid heap allocation (bump allocator doesn't reclaim)
+    let mut buf = [0u8; 4096]; // Max block size
+    read_ext2_block(device, block_num, block_size, &mut buf[..block_size])?;
+
+    let mut blocks_freed = 0u32;
+    let mut modified = false;
+    let mut empty = true;
+
+    for i in 0..ptrs_per_block {
+        let offset = i * 4;
+        let entry = u32::from_le_bytes([
+            buf[offset],
+            buf[offset + 1],
+            buf[offset + 2],
+            buf[offset + 3],
+        ]);
+        if entry == 0 {
+            continue;
+        }
+
+        // Entries wholly before the cut are kept
+        let entry_start = i as u64 * entry_span;
+        if entry_start + entry_span <= first {
+            empty = false;
+            continue;
+        }
+
+        let free_entry = if depth == 1 {
+            true
+        } else {
+            let (freed, child_empty) = truncate_indirect_block(
+                device,
+                superblock,
+                block_groups,
+                entry,
+                depth - 1,
+                first.saturating_sub(entry_start),
+            )?;
+            blocks_freed += freed;
+            child_empty
+        };
+
+        if free_entry {
+            super::block_group::free_block(device, entry, superblock, block_groups)?;
+            buf[offset..offset + 4].fill(0);
+            blocks_freed += 1;
+            modified = true;
+        } else {
+            empty = false;
+        }
+    }
+
+    // An emptied block is about to be freed by the caller; no need to write it
+    if modified && !empty {
+        write_ext2_block(device, block_num, block_size, &buf[..block_size])?;
+    }
+
+    Ok((blocks_freed, empty))
+}
+
 /// Helper to write block pointers to an indirect block
 ///
 /// Writes an array of u32 block pointers (little-endian) to a block.
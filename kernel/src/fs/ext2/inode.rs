@@ -6,6 +6,7 @@
 use crate::block::{BlockDevice, BlockError};
 use crate::fs::ext2::block_group::free_block;
 use crate::fs::ext2::file::{read_ext2_block, write_ext2_block};
+use crate::fs::ext2::Ext2Error;
 
 /// File type constants (from i_mode upper bits)
 pub const EXT2_S_IFSOCK: u16 = 0xC000; // Socket
@@ -244,6 +245,24 @@ impl Ext2Inode {
         }
     }
 
+    /// Set file size (splits across i_size and i_dir_acl for regular files)
+    ///
+    /// Counterpart to `size()`: the high 32 bits are only stored for regular
+    /// files, since i_dir_acl holds the ACL block for directories.
+    pub fn set_size(&mut self, size: u64) {
+        let is_file = self.is_file();
+        // Safety: Writing to packed struct requires unaligned access
+        unsafe {
+            core::ptr::write_unaligned(core::ptr::addr_of_mut!(self.i_size), size as u32);
+            if is_file {
+                core::ptr::write_unaligned(
+                    core::ptr::addr_of_mut!(self.i_dir_acl),
+                    (size >> 32) as u32,
+                );
+            }
+        }
+    }
+
     /// Get permissions (lower 12 bits of mode)
     pub fn permissions(&self) -> u16 {
         // Safety: Reading from packed struct requires unaligned access
@@ -314,16 +333,16 @@ pub const EXT2_FIRST_INO: u32 = 11;
 ///
 /// # Returns
 /// * `Ok(new_link_count)` - The new link count after decrement
-/// * `Err(msg)` - Error message
+/// * `Err(e)` - Why the operation failed
 pub fn decrement_inode_links<B: BlockDevice + ?Sized>(
     device: &B,
     inode_num: u32,
     superblock: &super::Ext2Superblock,
     block_groups: &mut [super::Ext2BlockGroupDesc],
-) -> Result<u16, &'static str> {
+) -> Result<u16, Ext2Error> {
     // Read the inode
     let mut inode = Ext2Inode::read_from(device, inode_num, superblock, block_groups)
-        .map_err(|_| "Failed to read inode")?;
+        .map_err(|_| Ext2Error::Io)?;
 
     // Decrement the link count
     let current_links =
@@ -354,7 +373,7 @@ pub fn decrement_inode_links<B: BlockDevice + ?Sized>(
     // Write the updated inode back
     inode
         .write_to(device, inode_num, superblock, block_groups)
-        .map_err(|_| "Failed to write inode")?;
+        .map_err(|_| Ext2Error::Io)?;
 
     Ok(new_links)
 }
@@ -375,16 +394,16 @@ pub fn decrement_inode_links<B: BlockDevice + ?Sized>(
 ///
 /// # Returns
 /// * `Ok(new_link_count)` - The new link count after increment
-/// * `Err(msg)` - Error message
+/// * `Err(e)` - Why the operation failed
 pub fn increment_inode_links<B: BlockDevice + ?Sized>(
     device: &B,
     inode_num: u32,
     superblock: &super::Ext2Superblock,
     block_groups: &[super::Ext2BlockGroupDesc],
-) -> Result<u16, &'static str> {
+) -> Result<u16, Ext2Error> {
     // Read the inode
     let mut inode = Ext2Inode::read_from(device, inode_num, superblock, block_groups)
-        .map_err(|_| "Failed to read inode")?;
+        .map_err(|_| Ext2Error::Io)?;
 
     // Increment the link count (saturating to prevent overflow)
     let current_links =
@@ -398,7 +417,7 @@ pub fn increment_inode_links<B: BlockDevice + ?Sized>(
     // Write the updated inode back
     inode
         .write_to(device, inode_num, superblock, block_groups)
-        .map_err(|_| "Failed to write inode")?;
+        .map_err(|_| Ext2Error::Io)?;
 
     Ok(new_links)
 }
@@ -412,7 +431,7 @@ fn free_inode_bitmap<B: BlockDevice + ?Sized>(
     inode_num: u32,
     superblock: &super::Ext2Superblock,
     block_groups: &mut [super::Ext2BlockGroupDesc],
-) -> Result<(), &'static str> {
+) -> Result<(), Ext2Error> {
     let block_size = superblock.block_size();
     let inodes_per_group = superblock.s_inodes_per_group;
 
@@ -434,7 +453,7 @@ fn free_inode_bitmap<B: BlockDevice + ?Sized>(
         block_size,
         &mut bitmap_buf[..block_size],
     )
-    .map_err(|_| "Failed to read inode bitmap")?;
+    .map_err(|_| Ext2Error::Io)?;
 
     // Clear the bit for this inode
     let byte_index = (local_index / 8) as usize;
@@ -446,7 +465,7 @@ fn free_inode_bitmap<B: BlockDevice + ?Sized>(
 
     // Write the updated bitmap back
     write_ext2_block(device, bitmap_block, block_size, &bitmap_buf[..block_size])
-        .map_err(|_| "Failed to write inode bitmap")?;
+        .map_err(|_| Ext2Error::Io)?;
 
     // Update the free inode count
     let free_inodes =
@@ -474,13 +493,13 @@ fn free_inode_bitmap<B: BlockDevice + ?Sized>(
 ///
 /// # Returns
 /// * `Ok(blocks_freed)` - Number of blocks freed
-/// * `Err(msg)` - Error message if operation failed
+/// * `Err(e)` - Why the operation failed
 fn free_inode_blocks<B: BlockDevice + ?Sized>(
     device: &B,
     superblock: &super::Ext2Superblock,
     block_groups: &mut [super::Ext2BlockGroupDesc],
     inode: &Ext2Inode,
-) -> Result<u32, &'static str> {
+) -> Result<u32, Ext2Error> {
     let block_size = superblock.block_size();
     let _ptrs_per_block = block_size / 4; // Reserved for future full deallocation
     let mut blocks_freed = 0u32;
@@ -532,14 +551,14 @@ fn free_indirect_block<B: BlockDevice + ?Sized>(
     block_groups: &mut [super::Ext2BlockGroupDesc],
     indirect_block: u32,
     block_size: usize,
-) -> Result<u32, &'static str> {
+) -> Result<u32, Ext2Error> {
     let mut blocks_freed = 0u32;
 
     // Read the indirect block
     // Use stack-based buffer to avoid heap allocation (bump allocator doesn't reclaim)
     let mut buf = [0u8; 4096]; // Max block size
     read_ext2_block(device, indirect_block, block_size, &mut buf[..block_size])
-        .map_err(|_| "Failed to read indirect block")?;
+        .map_err(|_| Ext2Error::Io)?;
 
     // Parse block pointers and free each non-zero block
     let num_pointers = block_size / 4;
@@ -569,7 +588,7 @@ fn free_double_indirect_block<B: BlockDevice + ?Sized>(
     double_indirect_block: u32,
     block_size: usize,
     ptrs_per_block: usize,
-) -> Result<u32, &'static str> {
+) -> Result<u32, Ext2Error> {
     let mut blocks_freed = 0u32;
 
     // Read the double indirect block (contains pointers to single indirect blocks)
@@ -581,7 +600,7 @@ fn free_double_indirect_block<B: BlockDevice + ?Sized>(
         block_size,
         &mut buf[..block_size],
     )
-    .map_err(|_| "Failed to read double indirect block")?;
+    .map_err(|_| Ext2Error::Io)?;
 
     // For each first-level pointer
     for i in 0..ptrs_per_block {
@@ -619,7 +638,7 @@ fn free_triple_indirect_block<B: BlockDevice + ?Sized>(
     triple_indirect_block: u32,
     block_size: usize,
     ptrs_per_block: usize,
-) -> Result<u32, &'static str> {
+) -> Result<u32, Ext2Error> {
     let mut blocks_freed = 0u32;
 
     // Read the triple indirect block (contains pointers to double indirect blocks)
@@ -631,7 +650,7 @@ fn free_triple_indirect_block<B: BlockDevice + ?Sized>(
         block_size,
         &mut buf[..block_size],
     )
-    .map_err(|_| "Failed to read triple indirect block")?;
+    .map_err(|_| Ext2Error::Io)?;
 
     // For each first-level pointer
     for i in 0..ptrs_per_block {
@@ -881,12 +900,12 @@ impl Ext2Inode {
 ///
 /// # Returns
 /// * `Ok(inode_num)` - The allocated inode number (1-indexed)
-/// * `Err(msg)` - Error if no free inodes available or I/O error
+/// * `Err(e)` - `NoSpace` if no free inodes are available, `Io` on I/O error
 pub fn allocate_inode<B: BlockDevice + ?Sized>(
     device: &B,
     superblock: &super::Ext2Superblock,
     block_groups: &mut [super::Ext2BlockGroupDesc],
-) -> Result<u32, &'static str> {
+) -> Result<u32, Ext2Error> {
     let block_size = superblock.block_size();
     let inodes_per_group = superblock.s_inodes_per_group;
 
@@ -918,7 +937,7 @@ pub fn allocate_inode<B: BlockDevice + ?Sized>(
             block_size,
             &mut bitmap_buf[..block_size],
         )
-        .map_err(|_| "Failed to read inode bitmap")?;
+        .map_err(|_| Ext2Error::Io)?;
 
         // Search for a free inode in this group
         for local_inode in 0..inodes_per_group {
@@ -944,7 +963,7 @@ pub fn allocate_inode<B: BlockDevice + ?Sized>(
 
                 // Write the updated bitmap back to disk
                 write_ext2_block(device, bitmap_block, block_size, &bitmap_buf[..block_size])
-                    .map_err(|_| "Failed to write inode bitmap")?;
+                    .map_err(|_| Ext2Error::Io)?;
 
                 // Update the free inode count in the block group descriptor
                 // Safety: Writing to packed struct
@@ -960,7 +979,7 @@ pub fn allocate_inode<B: BlockDevice + ?Sized>(
         }
     }
 
-    Err("No free inodes available")
+    Err(Ext2Error::NoSpace)
 }
 
 #[cfg(test)]
@@ -7,7 +7,7 @@ use crate::block::{BlockDevice, BlockError};
 use core::mem;
 
 /// ext2 magic number - identifies an ext2 filesystem
-const EXT2_SUPER_MAGIC: u16 = 0xEF53;
+pub const EXT2_SUPER_MAGIC: u16 = 0xEF53;
 
 /// Superblock offset from start of device (always 1024 bytes)
 const SUPERBLOCK_OFFSET: usize = 1024;
@@ -0,0 +1,546 @@
+//! Advisory file locking
+//!
+//! Two independent lock families share one table, keyed by (mount, inode):
+//!
+//! - **flock** locks cover the whole file and belong to an open file
+//!   description, so they are shared across dup() and fork() and released
+//!   when the last descriptor referring to the description is closed.
+//! - **Record locks** (fcntl F_SETLK and friends) cover byte ranges. POSIX
+//!   locks belong to a thread group and are released when that process
+//!   closes *any* descriptor for the file or exits; OFD locks
+//!   (F_OFD_SETLK) belong to an open file description like flock locks.
+//!
+//! The two families never conflict with each other, matching Linux. Blocked
+//! lockers sleep on a per-file waitqueue and retry whenever a lock on the
+//! file is released or downgraded. Before a POSIX locker sleeps, the chain of
+//! owners it would wait on is walked to detect EDEADLK.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use spin::Mutex;
+
+use crate::task::thread::ThreadState;
+use crate::task::waitqueue::{PrepareOutcome, WaitQueueHead};
+
+/// Lock table key: (mount ID, inode number)
+pub type FileKey = (usize, u64);
+
+/// Record lock end offset meaning "through end of file, however far it grows"
+pub const LOCK_TO_EOF: u64 = u64::MAX;
+
+/// Maximum length of a wait-for chain walked by deadlock detection
+///
+/// Same bound as Linux's MAX_DEADLK_ITERATIONS; longer chains are not
+/// reported as deadlocks.
+const MAX_DEADLOCK_DEPTH: usize = 10;
+
+/// Who a lock belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LockOwner {
+    /// POSIX record lock owned by a thread group
+    Process(u64),
+    /// flock or OFD lock owned by an open file description
+    OpenFile(u64),
+}
+
+/// Shared (read) or exclusive (write) lock
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockKind {
+    Shared,
+    Exclusive,
+}
+
+/// A byte-range lock covering `[start, end)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecordLock {
+    pub owner: LockOwner,
+    pub kind: LockKind,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl RecordLock {
+    fn overlaps(&self, start: u64, end: u64) -> bool {
+        self.start < end && start < self.end
+    }
+}
+
+/// A lock operation on one file
+#[derive(Clone, Copy, Debug)]
+pub enum LockRequest {
+    /// flock(): `kind` of None unlocks
+    Flock {
+        open_file: u64,
+        kind: Option<LockKind>,
+    },
+    /// fcntl record lock on `[start, end)`: `kind` of None unlocks
+    Record {
+        owner: LockOwner,
+        kind: Option<LockKind>,
+        start: u64,
+        end: u64,
+    },
+}
+
+impl LockRequest {
+    fn owner(&self) -> LockOwner {
+        match *self {
+            LockRequest::Flock { open_file, .. } => LockOwner::OpenFile(open_file),
+            LockRequest::Record { owner, .. } => owner,
+        }
+    }
+}
+
+/// Result of a single locking attempt
+#[derive(Debug, PartialEq, Eq)]
+pub enum LockAttempt {
+    /// The lock was set (or released)
+    Granted,
+    /// A conflicting lock is held and the caller did not ask to wait
+    WouldBlock,
+    /// Waiting would deadlock against the holder of the conflicting lock
+    Deadlock,
+    /// The current thread is queued on the file's waitqueue; sleep, then
+    /// call [`finish_wait`] and retry
+    Queued,
+    /// The scheduler refused to block the current thread
+    WaitFailed,
+}
+
+/// All locks held on one file
+struct FileLocks {
+    /// flock locks by open file description
+    flocks: Vec<(u64, LockKind)>,
+    /// POSIX and OFD record locks; a given owner's ranges never overlap
+    records: Vec<RecordLock>,
+    /// Threads waiting for a conflicting lock on this file to go away
+    waiters: WaitQueueHead,
+}
+
+impl FileLocks {
+    const fn new() -> Self {
+        Self {
+            flocks: Vec::new(),
+            records: Vec::new(),
+            waiters: WaitQueueHead::new(),
+        }
+    }
+
+    fn is_unused(&self) -> bool {
+        self.flocks.is_empty() && self.records.is_empty() && !self.waiters.has_waiters()
+    }
+
+    /// Owner of a lock that prevents `request` from being granted
+    fn conflict(&self, request: &LockRequest) -> Option<LockOwner> {
+        match *request {
+            LockRequest::Flock { kind: None, .. } | LockRequest::Record { kind: None, .. } => None,
+            LockRequest::Flock {
+                open_file,
+                kind: Some(kind),
+            } => self
+                .flocks
+                .iter()
+                .find(|&&(holder, held)| {
+                    holder != open_file
+                        && (kind == LockKind::Exclusive || held == LockKind::Exclusive)
+                })
+                .map(|&(holder, _)| LockOwner::OpenFile(holder)),
+            LockRequest::Record {
+                owner,
+                kind: Some(kind),
+                start,
+                end,
+            } => self
+                .conflicting_record(owner, kind, start, end)
+                .map(|lock| lock.owner),
+        }
+    }
+
+    /// First record lock held by another owner that conflicts with a `kind`
+    /// lock on `[start, end)` (the F_GETLK answer)
+    fn conflicting_record(
+        &self,
+        owner: LockOwner,
+        kind: LockKind,
+        start: u64,
+        end: u64,
+    ) -> Option<RecordLock> {
+        self.records
+            .iter()
+            .find(|lock| {
+                lock.owner != owner
+                    && lock.overlaps(start, end)
+                    && (kind == LockKind::Exclusive || lock.kind == LockKind::Exclusive)
+            })
+            .copied()
+    }
+
+    /// Apply a request that is known not to conflict
+    ///
+    /// Returns true if any lock was removed or weakened, meaning waiters
+    /// may now be able to proceed.
+    fn apply(&mut self, request: &LockRequest) -> bool {
+        match *request {
+            LockRequest::Flock { open_file, kind } => {
+                let previous = self
+                    .flocks
+                    .iter()
+                    .position(|&(holder, _)| holder == open_file);
+                let released = match previous {
+                    Some(index) => {
+                        let (_, held) = self.flocks.remove(index);
+                        kind != Some(held)
+                    }
+                    None => false,
+                };
+                if let Some(kind) = kind {
+                    self.flocks.push((open_file, kind));
+                }
+                released
+            }
+            LockRequest::Record {
+                owner,
+                kind,
+                start,
+                end,
+            } => self.set_records(owner, kind, start, end),
+        }
+    }
+
+    /// Replace `owner`'s locks on `[start, end)` with a `kind` lock (or with
+    /// nothing), splitting any lock that straddles the range and merging
+    /// adjacent locks of the same kind, as POSIX requires
+    fn set_records(
+        &mut self,
+        owner: LockOwner,
+        kind: Option<LockKind>,
+        mut start: u64,
+        mut end: u64,
+    ) -> bool {
+        let mut released = false;
+        let mut kept = Vec::with_capacity(self.records.len() + 1);
+
+        for lock in self.records.drain(..) {
+            if lock.owner != owner || !lock.overlaps(start, end) {
+                kept.push(lock);
+                continue;
+            }
+            if kind != Some(lock.kind) {
+                released = true;
+            }
+            if lock.start < start {
+                kept.push(RecordLock { end: start, ..lock });
+            }
+            if lock.end > end {
+                kept.push(RecordLock { start: end, ..lock });
+            }
+        }
+
+        if let Some(kind) = kind {
+            // Absorb same-kind neighbours that touch the new range
+            kept.retain(|lock| {
+                let touches = lock.owner == owner
+                    && lock.kind == kind
+                    && lock.start <= end
+                    && start <= lock.end;
+                if touches {
+                    start = start.min(lock.start);
+                    end = end.max(lock.end);
+                }
+                !touches
+            });
+            kept.push(RecordLock {
+                owner,
+                kind,
+                start,
+                end,
+            });
+        }
+
+        self.records = kept;
+        released
+    }
+
+    /// Drop every lock `owner` holds on this file; returns true if any existed
+    fn remove_owner(&mut self, owner: LockOwner) -> bool {
+        let before = self.flocks.len() + self.records.len();
+        if let LockOwner::OpenFile(id) = owner {
+            self.flocks.retain(|&(holder, _)| holder != id);
+        }
+        self.records.retain(|lock| lock.owner != owner);
+        self.flocks.len() + self.records.len() != before
+    }
+}
+
+/// Global lock state
+struct LockTable {
+    files: BTreeMap<FileKey, FileLocks>,
+    /// Wait-for graph for deadlock detection: sleeping POSIX owner -> the
+    /// owner of the lock it is waiting on
+    blocked_on: BTreeMap<LockOwner, LockOwner>,
+}
+
+impl LockTable {
+    /// Would `owner` waiting on `holder` close a cycle in the wait-for graph?
+    fn would_deadlock(&self, owner: LockOwner, holder: LockOwner) -> bool {
+        let mut current = holder;
+        for _ in 0..MAX_DEADLOCK_DEPTH {
+            if current == owner {
+                return true;
+            }
+            match self.blocked_on.get(&current) {
+                Some(&next) => current = next,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Remove `owner`'s locks from every file, waking waiters where needed
+    fn release_everywhere(&mut self, owner: LockOwner) {
+        self.blocked_on.remove(&owner);
+        self.files.retain(|_, file| {
+            if file.remove_owner(owner) {
+                file.waiters.wake_up();
+            }
+            !file.is_unused()
+        });
+    }
+}
+
+/// Lock table shared by flock and fcntl locks
+///
+/// Lock order: process manager -> LOCKS -> waitqueue -> scheduler. Callers
+/// must resolve the file descriptor and drop the process manager before
+/// calling into this module from syscall context.
+static LOCKS: Mutex<LockTable> = Mutex::new(LockTable {
+    files: BTreeMap::new(),
+    blocked_on: BTreeMap::new(),
+});
+
+/// Source of open file description IDs used as flock/OFD lock owners
+static NEXT_OPEN_FILE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Allocate a unique ID for a newly opened file description
+pub fn alloc_open_file_id() -> u64 {
+    NEXT_OPEN_FILE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Try to apply `request` to the file at `key`
+///
+/// When a conflicting lock is held and `wait` is set, the current thread is
+/// queued on the file's waitqueue and published as blocked before the table
+/// lock is dropped, so a release between this call and the caller's sleep
+/// cannot be missed.
+pub fn try_lock(key: FileKey, request: &LockRequest, wait: bool) -> LockAttempt {
+    let mut table = LOCKS.lock();
+    let owner = request.owner();
+
+    let file = table.files.entry(key).or_insert_with(FileLocks::new);
+    let Some(holder) = file.conflict(request) else {
+        if file.apply(request) {
+            file.waiters.wake_up();
+        }
+        if file.is_unused() {
+            table.files.remove(&key);
+        }
+        table.blocked_on.remove(&owner);
+        return LockAttempt::Granted;
+    };
+
+    if !wait {
+        if file.is_unused() {
+            table.files.remove(&key);
+        }
+        return LockAttempt::WouldBlock;
+    }
+
+    if matches!(owner, LockOwner::Process(_)) && table.would_deadlock(owner, holder) {
+        return LockAttempt::Deadlock;
+    }
+
+    let file = table.files.get(&key).expect("entry inserted above");
+    match file
+        .waiters
+        .prepare_to_wait_checked(ThreadState::BlockedOnIO, None, || true)
+    {
+        PrepareOutcome::Queued => {
+            table.blocked_on.insert(owner, holder);
+            LockAttempt::Queued
+        }
+        _ => LockAttempt::WaitFailed,
+    }
+}
+
+/// Leave the file's waitqueue after sleeping on a [`LockAttempt::Queued`]
+pub fn finish_wait(key: FileKey, owner: LockOwner) {
+    let mut table = LOCKS.lock();
+    table.blocked_on.remove(&owner);
+    if let Some(file) = table.files.get(&key) {
+        file.waiters.finish_wait();
+        if file.is_unused() {
+            table.files.remove(&key);
+        }
+    }
+}
+
+/// Find the record lock that would block a `kind` lock on `[start, end)`
+pub fn test_record(
+    key: FileKey,
+    owner: LockOwner,
+    kind: LockKind,
+    start: u64,
+    end: u64,
+) -> Option<RecordLock> {
+    let table = LOCKS.lock();
+    table
+        .files
+        .get(&key)
+        .and_then(|file| file.conflicting_record(owner, kind, start, end))
+}
+
+/// Release the flock and OFD locks of a closed open file description
+pub fn release_open_file(open_file: u64) {
+    LOCKS
+        .lock()
+        .release_everywhere(LockOwner::OpenFile(open_file));
+}
+
+/// Release a process's POSIX locks on one file (called when it closes any
+/// descriptor for the file, including at exit)
+pub fn release_process_file(tgid: u64, key: FileKey) {
+    let mut table = LOCKS.lock();
+    let owner = LockOwner::Process(tgid);
+    if let Some(file) = table.files.get_mut(&key) {
+        if file.remove_owner(owner) {
+            file.waiters.wake_up();
+        }
+        if file.is_unused() {
+            table.files.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: LockOwner = LockOwner::Process(1);
+    const B: LockOwner = LockOwner::Process(2);
+
+    fn record(owner: LockOwner, kind: Option<LockKind>, start: u64, end: u64) -> LockRequest {
+        LockRequest::Record {
+            owner,
+            kind,
+            start,
+            end,
+        }
+    }
+
+    fn ranges(file: &FileLocks, owner: LockOwner) -> Vec<(LockKind, u64, u64)> {
+        let mut out: Vec<_> = file
+            .records
+            .iter()
+            .filter(|lock| lock.owner == owner)
+            .map(|lock| (lock.kind, lock.start, lock.end))
+            .collect();
+        out.sort_by_key(|&(_, start, _)| start);
+        out
+    }
+
+    #[test]
+    fn test_unlock_splits_range() {
+        let mut file = FileLocks::new();
+        file.apply(&record(A, Some(LockKind::Exclusive), 0, 100));
+        assert!(file.apply(&record(A, None, 40, 60)));
+        assert_eq!(
+            ranges(&file, A),
+            [(LockKind::Exclusive, 0, 40), (LockKind::Exclusive, 60, 100)]
+        );
+    }
+
+    #[test]
+    fn test_adjacent_same_kind_merges() {
+        let mut file = FileLocks::new();
+        file.apply(&record(A, Some(LockKind::Shared), 0, 10));
+        file.apply(&record(A, Some(LockKind::Shared), 20, 30));
+        file.apply(&record(A, Some(LockKind::Shared), 10, 20));
+        assert_eq!(ranges(&file, A), [(LockKind::Shared, 0, 30)]);
+    }
+
+    #[test]
+    fn test_downgrade_middle_of_range() {
+        let mut file = FileLocks::new();
+        file.apply(&record(A, Some(LockKind::Exclusive), 0, LOCK_TO_EOF));
+        assert!(file.apply(&record(A, Some(LockKind::Shared), 10, 20)));
+        assert_eq!(
+            ranges(&file, A),
+            [
+                (LockKind::Exclusive, 0, 10),
+                (LockKind::Shared, 10, 20),
+                (LockKind::Exclusive, 20, LOCK_TO_EOF)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_conflicts() {
+        let mut file = FileLocks::new();
+        file.apply(&record(A, Some(LockKind::Shared), 0, 10));
+        assert_eq!(
+            file.conflict(&record(B, Some(LockKind::Shared), 5, 15)),
+            None
+        );
+        assert_eq!(
+            file.conflict(&record(B, Some(LockKind::Exclusive), 5, 15)),
+            Some(A)
+        );
+        assert_eq!(
+            file.conflict(&record(B, Some(LockKind::Exclusive), 10, 20)),
+            None
+        );
+        assert_eq!(
+            file.conflict(&record(A, Some(LockKind::Exclusive), 0, 10)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_flock_conversion_and_conflict() {
+        let mut file = FileLocks::new();
+        let shared = |id| LockRequest::Flock {
+            open_file: id,
+            kind: Some(LockKind::Shared),
+        };
+        let exclusive = |id| LockRequest::Flock {
+            open_file: id,
+            kind: Some(LockKind::Exclusive),
+        };
+        file.apply(&shared(1));
+        file.apply(&shared(2));
+        assert_eq!(file.conflict(&exclusive(1)), Some(LockOwner::OpenFile(2)));
+        assert!(file.remove_owner(LockOwner::OpenFile(2)));
+        assert_eq!(file.conflict(&exclusive(1)), None);
+        file.apply(&exclusive(1));
+        assert_eq!(file.flocks, [(1, LockKind::Exclusive)]);
+        // flock and record locks are independent
+        assert_eq!(
+            file.conflict(&record(B, Some(LockKind::Exclusive), 0, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_deadlock_detection_follows_chain() {
+        let mut table = LockTable {
+            files: BTreeMap::new(),
+            blocked_on: BTreeMap::new(),
+        };
+        let c = LockOwner::Process(3);
+        table.blocked_on.insert(B, c);
+        table.blocked_on.insert(c, A);
+        assert!(table.would_deadlock(A, B));
+        assert!(!table.would_deadlock(c, LockOwner::Process(4)));
+    }
+}
@@ -12,5 +12,6 @@
 pub mod devfs;
 pub mod devptsfs;
 pub mod ext2;
+pub mod lock;
 pub mod procfs;
 pub mod vfs;
@@ -12,8 +12,16 @@
 //! - `/proc/meminfo` - Memory statistics
 //! - `/proc/cpuinfo` - CPU information
 //!
+//! - `/proc/self` - Symlink to the calling process's /proc/[pid]
+//!
 //! ## Per-process entries (/proc/[pid]/)
 //! - `/proc/[pid]/status` - Process name, state, parent, children, memory usage
+//! - `/proc/[pid]/stat`, `/proc/[pid]/statm` - Linux-format statistics
+//! - `/proc/[pid]/maps` - Memory regions
+//! - `/proc/[pid]/cmdline`, `/proc/[pid]/environ` - Program arguments and environment
+//! - `/proc/[pid]/exe`, `/proc/[pid]/cwd` - Symlinks to the program and working directory
+//! - `/proc/[pid]/fd/[n]` - Symlinks to the open files
+//! - `/proc/[pid]/task/[tid]/` - Per-thread directories with the same entries
 //!
 //! ## Tracing entries (/proc/trace/)
 //! - `/proc/trace/enable` - Tracing enable state (0/1)
@@ -41,10 +49,14 @@ use alloc::string::String;
 use alloc::vec::Vec;
 use spin::Mutex;
 
+mod pid;
 mod trace;
 #[cfg(target_arch = "aarch64")]
 mod xhci;
 
+/// Filesystem type magic reported by statfs (Linux PROC_SUPER_MAGIC)
+pub const PROC_SUPER_MAGIC: u32 = 0x9fa0;
+
 /// Procfs entry types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProcEntryType {
@@ -98,6 +110,28 @@ pub enum ProcEntryType {
     PidDir(u64),
     /// /proc/[pid]/status - per-process status (dynamic, not registered)
     PidStatus(u64),
+    /// /proc/self - symlink to the caller's /proc/[pid]
+    SelfLink,
+    /// /proc/[pid]/maps - memory regions (dynamic)
+    PidMaps(u64),
+    /// /proc/[pid]/cmdline - program arguments (dynamic)
+    PidCmdline(u64),
+    /// /proc/[pid]/environ - initial environment (dynamic)
+    PidEnviron(u64),
+    /// /proc/[pid]/stat - Linux-format process statistics (dynamic)
+    PidStat(u64),
+    /// /proc/[pid]/statm - memory sizes in pages (dynamic)
+    PidStatm(u64),
+    /// /proc/[pid]/exe - symlink to the executable (dynamic)
+    PidExe(u64),
+    /// /proc/[pid]/cwd - symlink to the working directory (dynamic)
+    PidCwd(u64),
+    /// /proc/[pid]/fd - open file descriptor directory (dynamic)
+    PidFdDir(u64),
+    /// /proc/[pid]/fd/[fd] - symlink to an open file (dynamic)
+    PidFd(u64, u32),
+    /// /proc/[pid]/task - thread directory (dynamic)
+    PidTaskDir(u64),
 }
 
 impl ProcEntryType {
@@ -132,6 +166,17 @@ impl ProcEntryType {
             ProcEntryType::XhciCounters => "counters",
             ProcEntryType::PidDir(_) => "pid",
             ProcEntryType::PidStatus(_) => "status",
+            ProcEntryType::SelfLink => "self",
+            ProcEntryType::PidMaps(_) => "maps",
+            ProcEntryType::PidCmdline(_) => "cmdline",
+            ProcEntryType::PidEnviron(_) => "environ",
+            ProcEntryType::PidStat(_) => "stat",
+            ProcEntryType::PidStatm(_) => "statm",
+            ProcEntryType::PidExe(_) => "exe",
+            ProcEntryType::PidCwd(_) => "cwd",
+            ProcEntryType::PidFdDir(_) => "fd",
+            ProcEntryType::PidFd(_, _) => "fd",
+            ProcEntryType::PidTaskDir(_) => "task",
         }
     }
 
@@ -167,6 +212,17 @@ impl ProcEntryType {
             // Dynamic entries don't have static paths
             ProcEntryType::PidDir(_) => "/proc/<pid>",
             ProcEntryType::PidStatus(_) => "/proc/<pid>/status",
+            ProcEntryType::SelfLink => "/proc/self",
+            ProcEntryType::PidMaps(_) => "/proc/<pid>/maps",
+            ProcEntryType::PidCmdline(_) => "/proc/<pid>/cmdline",
+            ProcEntryType::PidEnviron(_) => "/proc/<pid>/environ",
+            ProcEntryType::PidStat(_) => "/proc/<pid>/stat",
+            ProcEntryType::PidStatm(_) => "/proc/<pid>/statm",
+            ProcEntryType::PidExe(_) => "/proc/<pid>/exe",
+            ProcEntryType::PidCwd(_) => "/proc/<pid>/cwd",
+            ProcEntryType::PidFdDir(_) => "/proc/<pid>/fd",
+            ProcEntryType::PidFd(_, _) => "/proc/<pid>/fd/<fd>",
+            ProcEntryType::PidTaskDir(_) => "/proc/<pid>/task",
         }
     }
 
@@ -175,6 +231,7 @@ impl ProcEntryType {
     /// Dynamic PID entries use computed inodes:
     /// - PidDir(pid) -> 10000 + pid
     /// - PidStatus(pid) -> 20000 + pid
+    /// - Other per-PID entries -> `pid_inode(pid, slot)`
     pub fn inode(&self) -> u64 {
         match self {
             ProcEntryType::Uptime => 1,
@@ -202,6 +259,17 @@ impl ProcEntryType {
             ProcEntryType::XhciCounters => 302,
             ProcEntryType::PidDir(pid) => 10000 + pid,
             ProcEntryType::PidStatus(pid) => 20000 + pid,
+            ProcEntryType::SelfLink => 11,
+            ProcEntryType::PidMaps(pid) => pid_inode(pid, 1),
+            ProcEntryType::PidCmdline(pid) => pid_inode(pid, 2),
+            ProcEntryType::PidEnviron(pid) => pid_inode(pid, 3),
+            ProcEntryType::PidStat(pid) => pid_inode(pid, 4),
+            ProcEntryType::PidStatm(pid) => pid_inode(pid, 5),
+            ProcEntryType::PidExe(pid) => pid_inode(pid, 6),
+            ProcEntryType::PidCwd(pid) => pid_inode(pid, 7),
+            ProcEntryType::PidFdDir(pid) => pid_inode(pid, 8),
+            ProcEntryType::PidTaskDir(pid) => pid_inode(pid, 9),
+            ProcEntryType::PidFd(pid, fd) => pid_inode(pid, 0x100 + fd as u64),
         }
     }
 
@@ -214,10 +282,43 @@ impl ProcEntryType {
                 | ProcEntryType::BreenixDir
                 | ProcEntryType::XhciDir
                 | ProcEntryType::PidDir(_)
+                | ProcEntryType::PidFdDir(_)
+                | ProcEntryType::PidTaskDir(_)
+        )
+    }
+
+    /// Process whose private state this entry exposes: its environment,
+    /// memory map, descriptors and links, readable only by its owner and root
+    pub fn private_to(&self) -> Option<u64> {
+        match *self {
+            ProcEntryType::PidEnviron(pid)
+            | ProcEntryType::PidMaps(pid)
+            | ProcEntryType::PidFdDir(pid)
+            | ProcEntryType::PidFd(pid, _)
+            | ProcEntryType::PidExe(pid)
+            | ProcEntryType::PidCwd(pid) => Some(pid),
+            _ => None,
+        }
+    }
+
+    /// Check if this is a symbolic link
+    pub fn is_symlink(&self) -> bool {
+        matches!(
+            self,
+            ProcEntryType::SelfLink
+                | ProcEntryType::PidExe(_)
+                | ProcEntryType::PidCwd(_)
+                | ProcEntryType::PidFd(_, _)
         )
     }
 }
 
+/// Inode of a per-PID entry other than the directory and status: 4096 slots
+/// per PID above 2^32, clear of the fixed 10000/20000/30000 + pid ranges
+fn pid_inode(pid: u64, slot: u64) -> u64 {
+    (1 << 32) + (pid << 12) + slot
+}
+
 /// A procfs entry node
 #[derive(Debug, Clone)]
 pub struct ProcEntry {
@@ -279,6 +380,7 @@ pub fn init() {
 
     procfs.entries.push(ProcEntry::new(ProcEntryType::Pids));
     procfs.entries.push(ProcEntry::new(ProcEntryType::Kmsg));
+    procfs.entries.push(ProcEntry::new(ProcEntryType::SelfLink));
 
     // Register /proc/xhci directory and entries
     procfs.entries.push(ProcEntry::new(ProcEntryType::XhciDir));
@@ -480,6 +582,7 @@ pub fn is_initialized() -> bool {
 /// # Returns
 /// The content as a String, or an error code
 pub fn read_entry(entry_type: ProcEntryType) -> Result<String, i32> {
+    check_access(entry_type)?;
     match entry_type {
         ProcEntryType::Uptime => Ok(generate_uptime()),
         ProcEntryType::Version => Ok(generate_version()),
@@ -531,6 +634,88 @@ pub fn read_entry(entry_type: ProcEntryType) -> Result<String, i32> {
         }
         ProcEntryType::PidDir(pid) => Ok(generate_pid_dir(pid)),
         ProcEntryType::PidStatus(pid) => Ok(generate_pid_status(pid)),
+        ProcEntryType::PidMaps(pid) => Ok(pid::generate_maps(pid)),
+        ProcEntryType::PidCmdline(pid) => Ok(pid::generate_cmdline(pid)),
+        ProcEntryType::PidEnviron(pid) => Ok(pid::generate_environ(pid)),
+        ProcEntryType::PidStat(pid) => Ok(pid::generate_stat(pid)),
+        ProcEntryType::PidStatm(pid) => Ok(pid::generate_statm(pid)),
+        ProcEntryType::PidFdDir(pid) => Ok(pid::list_fds(pid).join("\n") + "\n"),
+        ProcEntryType::PidTaskDir(pid) => Ok(pid::list_tasks(pid).join("\n") + "\n"),
+        // Links are followed before they are read (see `follow_link`); the
+        // ones left point at pipes and sockets, which cannot be reopened
+        ProcEntryType::SelfLink
+        | ProcEntryType::PidExe(_)
+        | ProcEntryType::PidCwd(_)
+        | ProcEntryType::PidFd(_, _) => Err(-6), // ENXIO
+    }
+}
+
+/// Refuse an entry private to another user's process with -EACCES
+///
+/// Takes the scheduler and process manager locks, so callers must hold
+/// neither.
+pub fn check_access(entry_type: ProcEntryType) -> Result<(), i32> {
+    match entry_type.private_to() {
+        Some(pid) if !pid::may_inspect(pid) => Err(-13), // EACCES
+        _ => Ok(()),
+    }
+}
+
+/// Read the target of a procfs symlink by full path
+///
+/// # Returns
+/// The target, -ENOENT if the path does not exist, or -EINVAL if it is not
+/// a symlink
+pub fn read_link(path: &str) -> Result<String, i32> {
+    use alloc::format;
+
+    let entry = lookup_by_path(path.trim_end_matches('/')).ok_or(-2)?; // ENOENT
+    check_access(entry.entry_type)?;
+    let target = match entry.entry_type {
+        ProcEntryType::SelfLink => pid::current_tgid().map(|pid| format!("{}", pid)),
+        ProcEntryType::PidExe(pid) => pid::exe_link(pid),
+        ProcEntryType::PidCwd(pid) => pid::cwd_link(pid),
+        ProcEntryType::PidFd(pid, fd) => pid::fd_link(pid, fd),
+        _ => return Err(-22), // EINVAL
+    };
+    target.ok_or(-2)
+}
+
+/// Follow the procfs symlink at `path`, if it is one, to the absolute path
+/// it names
+///
+/// /proc/self resolves to /proc/[pid]. Links to pipes and sockets name no
+/// path and are not followed.
+pub fn follow_link(path: &str) -> Option<String> {
+    use alloc::format;
+
+    let entry = lookup_by_path(path.trim_end_matches('/'))?;
+    if !entry.entry_type.is_symlink() {
+        return None;
+    }
+    let target = read_link(path).ok()?;
+    if entry.entry_type == ProcEntryType::SelfLink {
+        Some(format!("/proc/{}", target))
+    } else if target.starts_with('/') {
+        Some(target)
+    } else {
+        None
+    }
+}
+
+/// List a per-process directory: /proc/[pid], its fd/ and task/
+/// subdirectories, and /proc/[pid]/task/[tid]
+///
+/// Returns an empty list for any other path.
+pub fn list_pid_entries(dir_path: &str) -> Vec<String> {
+    match lookup_pid_path(dir_path.trim_end_matches('/')).map(|entry| entry.entry_type) {
+        Some(ProcEntryType::PidDir(_)) => pid::PID_DIR_ENTRIES
+            .iter()
+            .map(|&name| String::from(name))
+            .collect(),
+        Some(ProcEntryType::PidFdDir(pid)) => pid::list_fds(pid),
+        Some(ProcEntryType::PidTaskDir(pid)) => pid::list_tasks(pid),
+        _ => Vec::new(),
     }
 }
 
@@ -966,7 +1151,10 @@ fn pid_exists(pid: u64) -> bool {
 ///
 /// Handles:
 /// - `/proc/123` -> PidDir(123)
-/// - `/proc/123/status` -> PidStatus(123)
+/// - `/proc/123/status` -> PidStatus(123), and likewise the other entries
+/// - `/proc/123/fd/4` -> PidFd(123, 4)
+/// - `/proc/123/task/124/...` -> the entries of thread 124 of 123's group
+/// - `/proc/self/...` -> the entries of the caller's thread group
 ///
 /// Returns None if the path doesn't match a PID pattern or the PID doesn't exist.
 /// This function acquires the process manager lock, so callers must NOT hold the
@@ -987,22 +1175,65 @@ fn lookup_pid_path(path: &str) -> Option<ProcEntry> {
         (relative, None)
     };
 
-    // Parse PID: must be purely numeric, no leading zeros (except "0" itself)
-    if pid_str.is_empty() || (pid_str.len() > 1 && pid_str.starts_with('0')) {
-        return None;
-    }
-    let pid: u64 = pid_str.parse().ok()?;
+    // /proc/self itself is a registered link; paths below it name the
+    // caller's own entries
+    let pid: u64 = if pid_str == "self" {
+        sub_path?;
+        pid::current_tgid()?
+    } else {
+        parse_pid(pid_str)?
+    };
 
     // Verify the PID exists (acquires process manager lock)
     if !pid_exists(pid) {
         return None;
     }
 
-    match sub_path {
-        None => Some(ProcEntry::new(ProcEntryType::PidDir(pid))),
-        Some("status") => Some(ProcEntry::new(ProcEntryType::PidStatus(pid))),
-        Some(_) => None, // Unknown sub-path
+    let entry_type = match sub_path {
+        None => ProcEntryType::PidDir(pid),
+        Some("status") => ProcEntryType::PidStatus(pid),
+        Some("maps") => ProcEntryType::PidMaps(pid),
+        Some("cmdline") => ProcEntryType::PidCmdline(pid),
+        Some("environ") => ProcEntryType::PidEnviron(pid),
+        Some("stat") => ProcEntryType::PidStat(pid),
+        Some("statm") => ProcEntryType::PidStatm(pid),
+        Some("exe") => ProcEntryType::PidExe(pid),
+        Some("cwd") => ProcEntryType::PidCwd(pid),
+        Some("fd") => ProcEntryType::PidFdDir(pid),
+        Some("task") => ProcEntryType::PidTaskDir(pid),
+        Some(sub) => {
+            if let Some(fd) = sub.strip_prefix("fd/") {
+                let fd = u32::try_from(parse_pid(fd)?).ok()?;
+                if !pid::fd_exists(pid, fd) {
+                    return None;
+                }
+                ProcEntryType::PidFd(pid, fd)
+            } else if let Some(task) = sub.strip_prefix("task/") {
+                // A thread's directory holds the same entries as /proc/[tid]
+                let (tid_str, rest) = match task.find('/') {
+                    Some(slash_pos) => (&task[..slash_pos], &task[slash_pos..]),
+                    None => (task, ""),
+                };
+                let tid = parse_pid(tid_str)?;
+                if !pid::task_exists(pid, tid) {
+                    return None;
+                }
+                return lookup_pid_path(&alloc::format!("/proc/{}{}", tid, rest));
+            } else {
+                return None; // Unknown sub-path
+            }
+        }
+    };
+    Some(ProcEntry::new(entry_type))
+}
+
+/// Parse a PID path component: purely numeric, no leading zeros (except "0"
+/// itself)
+fn parse_pid(text: &str) -> Option<u64> {
+    if text.is_empty() || (text.len() > 1 && text.starts_with('0')) {
+        return None;
     }
+    text.parse().ok()
 }
 
 fn lookup_trace_teardown_path(path: &str) -> Option<ProcEntry> {
@@ -1028,7 +1259,7 @@ fn generate_pid_dir(pid: u64) -> String {
         return format!("Process {} not found\n", pid);
     }
 
-    String::from("status\n")
+    pid::PID_DIR_ENTRIES.join("\n") + "\n"
 }
 
 /// Generate /proc/[pid]/status content
@@ -1044,6 +1275,8 @@ fn generate_pid_dir(pid: u64) -> String {
 /// VmCode: 8 kB
 /// VmHeap: 64 kB
 /// VmStack:    16 kB
+/// Cpus_allowed:   f
+/// Cpus_allowed_list:  0-3
 /// ```
 fn generate_pid_status(pid: u64) -> String {
     use crate::process::ProcessId;
@@ -1065,6 +1298,11 @@ fn generate_pid_status(pid: u64) -> String {
     };
     let (cpu_online, cpu_sample_ticks, cpu_capacity_ticks) = procfs_cpu_accounting_ticks();
     let scheduler_state = crate::task::scheduler::get_process_display_state(pid);
+    // Affinity also comes from the scheduler, so read it before the lock too
+    let cpus_allowed = crate::task::scheduler::threads_sched(&[pid])
+        .first()
+        .map_or(0, |sched| sched.cpus_allowed)
+        & crate::task::scheduler::online_cpu_mask();
 
     let manager_guard = crate::process::manager();
     let manager = match manager_guard.as_ref() {
@@ -1082,6 +1320,7 @@ fn generate_pid_status(pid: u64) -> String {
         ProcessState::Ready => "Ready",
         ProcessState::Running => "Running",
         ProcessState::Blocked => "Blocked",
+        ProcessState::Stopped(_) => "Stopped",
         ProcessState::Terminated(_) => "Terminated",
     });
 
@@ -1123,7 +1362,9 @@ fn generate_pid_status(pid: u64) -> String {
          CpuTicks:\t{}\n\
          CpuSampleTicks:\t{}\n\
          CpuCapacityTicks:\t{}\n\
-         CpuOnline:\t{}\n",
+         CpuOnline:\t{}\n\
+         Cpus_allowed:\t{:x}\n\
+         Cpus_allowed_list:\t{}\n",
         process.name,
         pid,
         ppid,
@@ -1137,5 +1378,32 @@ fn generate_pid_status(pid: u64) -> String {
         cpu_sample_ticks,
         cpu_capacity_ticks,
         cpu_online,
+        cpus_allowed,
+        cpu_list(cpus_allowed),
     )
 }
+
+/// Format a CPU mask as a Linux CPU list, e.g. `0-2,5`
+fn cpu_list(mask: u64) -> String {
+    use alloc::format;
+
+    let mut ranges: Vec<String> = Vec::new();
+    let mut cpu = 0;
+    while cpu < 64 {
+        if mask & (1 << cpu) == 0 {
+            cpu += 1;
+            continue;
+        }
+        let first = cpu;
+        while cpu + 1 < 64 && mask & (1 << (cpu + 1)) != 0 {
+            cpu += 1;
+        }
+        ranges.push(if first == cpu {
+            format!("{}", first)
+        } else {
+            format!("{}-{}", first, cpu)
+        });
+        cpu += 1;
+    }
+    ranges.join(",")
+}
@@ -0,0 +1,439 @@
+//! Per-process procfs entries
+//!
+//! Content generators for the /proc/[pid]/ entries beyond `status`, built
+//! from the process manager's `Process` rows:
+//!
+//! - `maps` - memory regions: the VMAs plus the mapped program image, heap
+//!   and stack
+//! - `cmdline`, `environ` - the NUL-separated argv and envp the current
+//!   program was started with
+//! - `stat`, `statm` - Linux-format process statistics
+//! - `exe`, `cwd`, `fd/[n]` - symlinks to the program, the working directory
+//!   and each open file
+//! - `task/[tid]/` - one directory per thread of the thread group
+//!
+//! `environ`, `maps`, `fd/`, `exe` and `cwd` are private: only root and the
+//! owner of the process may read them (see `may_inspect`).
+//!
+//! Each clone thread is a process row of its own, so the thread group of a
+//! pid is the rows whose `thread_group_id` names it. Fields Breenix does not
+//! track (file offsets of mappings, major faults, tty numbers and the like)
+//! read as 0.
+//!
+//! Scheduler state is collected before the process manager lock is taken,
+//! since SCHEDULER must never be acquired under PROCESS_MANAGER.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::ipc::fd::{FdKind, MAX_FDS};
+use crate::memory::regions::{memory_regions, PF_R, PF_W, PF_X};
+use crate::memory::vma::MmapFlags;
+use crate::process::rusage::thread_group;
+use crate::process::{Process, ProcessId, ProcessManager, ProcessState};
+use crate::syscall::rusage::to_clock_ticks;
+
+/// Entries of every /proc/[pid] directory
+pub const PID_DIR_ENTRIES: [&str; 10] = [
+    "cmdline", "cwd", "environ", "exe", "fd", "maps", "stat", "statm", "status", "task",
+];
+
+/// Width Linux pads a maps line to before the region name
+const MAPS_NAME_COLUMN: usize = 72;
+
+const PAGE_SIZE: u64 = 4096;
+
+/// Pid of the caller's thread group, which /proc/self names
+///
+/// Takes the scheduler lock and then the process manager lock, so callers
+/// must hold neither.
+pub fn current_tgid() -> Option<u64> {
+    let thread_id = crate::task::scheduler::current_thread_id()?;
+    let manager_guard = crate::process::manager();
+    let manager = manager_guard.as_ref()?;
+    let (_, process) = manager.find_process_by_thread(thread_id)?;
+    Some(thread_group(process).as_u64())
+}
+
+/// Whether the caller may read the private entries of process `pid`
+///
+/// Root may read any process; other callers only processes whose real uid
+/// is their effective uid. Kernel threads are not checked, and a missing
+/// process is left for the entry itself to report.
+///
+/// Takes the scheduler lock and then the process manager lock, so callers
+/// must hold neither.
+pub fn may_inspect(pid: u64) -> bool {
+    let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+        return true;
+    };
+    let manager_guard = crate::process::manager();
+    let Some(manager) = manager_guard.as_ref() else {
+        return true;
+    };
+    let (Some((_, caller)), Some(target)) = (
+        manager.find_process_by_thread(thread_id),
+        manager.get_process(ProcessId::new(pid)),
+    ) else {
+        return true;
+    };
+    caller.euid == 0 || caller.euid == target.uid
+}
+
+/// Run `f` on process `pid` under the process manager lock
+fn with_process<R>(pid: u64, f: impl FnOnce(&ProcessManager, &Process) -> R) -> Option<R> {
+    let manager_guard = crate::process::manager();
+    let manager = manager_guard.as_ref()?;
+    let process = manager.get_process(ProcessId::new(pid))?;
+    Some(f(manager, process))
+}
+
+/// Live threads of the thread group `process` belongs to, leader first
+fn group_members(manager: &ProcessManager, process: &Process) -> Vec<u64> {
+    let leader = thread_group(process);
+    let mut members: Vec<u64> = manager
+        .iter_processes()
+        .filter(|(_, p)| thread_group(p) == leader && !p.is_terminated())
+        .map(|(pid, _)| pid.as_u64())
+        .collect();
+    members.sort_unstable();
+    members
+}
+
+/// The row that owns the address space `process` runs in: itself, or the
+/// group leader for a CLONE_VM thread
+fn address_space_owner<'a>(manager: &'a ProcessManager, process: &'a Process) -> &'a Process {
+    if process.page_table.is_some() {
+        return process;
+    }
+    manager
+        .get_process(thread_group(process))
+        .unwrap_or(process)
+}
+
+/// Whether `tid` is a live thread in the thread group of `pid`
+pub fn task_exists(pid: u64, tid: u64) -> bool {
+    with_process(pid, |manager, process| {
+        group_members(manager, process).contains(&tid)
+    })
+    .unwrap_or(false)
+}
+
+/// Whether `fd` is open in process `pid`
+pub fn fd_exists(pid: u64, fd: u32) -> bool {
+    with_process(pid, |_, process| process.fd_table.get(fd as i32).is_some()).unwrap_or(false)
+}
+
+/// Entries of /proc/[pid]/fd: the open descriptors
+pub fn list_fds(pid: u64) -> Vec<String> {
+    with_process(pid, |_, process| {
+        (0..MAX_FDS as i32)
+            .filter(|&fd| process.fd_table.get(fd).is_some())
+            .map(|fd| format!("{}", fd))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Entries of /proc/[pid]/task: the thread ids of the thread group
+pub fn list_tasks(pid: u64) -> Vec<String> {
+    with_process(pid, |manager, process| {
+        group_members(manager, process)
+            .into_iter()
+            .map(|tid| format!("{}", tid))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Target of /proc/[pid]/exe
+pub fn exe_link(pid: u64) -> Option<String> {
+    with_process(pid, |_, process| process.exe.clone())
+}
+
+/// Target of /proc/[pid]/cwd
+pub fn cwd_link(pid: u64) -> Option<String> {
+    with_process(pid, |_, process| process.cwd.clone())
+}
+
+/// Target of /proc/[pid]/fd/[fd]
+pub fn fd_link(pid: u64, fd: u32) -> Option<String> {
+    with_process(pid, |_, process| {
+        process
+            .fd_table
+            .get(fd as i32)
+            .map(|entry| fd_target(&entry.kind))
+    })
+    .flatten()
+}
+
+/// What an open descriptor refers to, in the form Linux shows in /proc/[pid]/fd
+///
+/// Files name their path; pipes and sockets, which have no path, show their
+/// kind and inode number. Sockets carry no inode number here and show 0.
+fn fd_target(kind: &FdKind) -> String {
+    match kind {
+        FdKind::StdIo(_) => String::from("/dev/console"),
+        FdKind::PipeRead(pipe) | FdKind::PipeWrite(pipe) => {
+            format!("pipe:[{}]", pipe.lock().ino())
+        }
+        FdKind::UdpSocket(_)
+        | FdKind::TcpSocket(_)
+        | FdKind::TcpListener(_)
+        | FdKind::TcpConnection(_)
+        | FdKind::UnixStream(_)
+        | FdKind::UnixSocket(_)
+        | FdKind::UnixListener(_) => String::from("socket:[0]"),
+        FdKind::RegularFile(file) => file.lock().path.clone(),
+        FdKind::Directory(dir) => dir.lock().path.clone(),
+        FdKind::Device(device) => format!("/dev/{}", device.name()),
+        FdKind::Dsp(_) => String::from("/dev/dsp"),
+        FdKind::DevfsDirectory { .. } => String::from("/dev"),
+        FdKind::DevptsDirectory { .. } => String::from("/dev/pts"),
+        FdKind::PtyMaster(_) => String::from("/dev/ptmx"),
+        FdKind::PtySlave(n) => format!("/dev/pts/{}", n),
+        FdKind::FifoRead(path, _) | FdKind::FifoWrite(path, _) => path.clone(),
+        FdKind::ProcfsFile { path, .. } | FdKind::ProcfsDirectory { path, .. } => path.clone(),
+        FdKind::Epoll(_) => String::from("anon_inode:[eventpoll]"),
+    }
+}
+
+/// Generate /proc/[pid]/cmdline: argv, each string NUL-terminated
+pub fn generate_cmdline(pid: u64) -> String {
+    with_process(pid, |_, process| {
+        String::from_utf8_lossy(&process.cmdline).into_owned()
+    })
+    .unwrap_or_default()
+}
+
+/// Generate /proc/[pid]/environ: the initial environment, each string
+/// NUL-terminated
+pub fn generate_environ(pid: u64) -> String {
+    with_process(pid, |_, process| {
+        String::from_utf8_lossy(&process.environ).into_owned()
+    })
+    .unwrap_or_default()
+}
+
+/// Generate /proc/[pid]/maps
+///
+/// One line per region, as Linux prints them:
+/// ```text
+/// 00400000-00412000 r-xp 00000000 00:00 0                                  /bin/init_shell
+/// 00412000-00414000 rw-p 00000000 00:00 0                                  [heap]
+/// 7fffff000000-7fffff010000 rw-p 00000000 00:00 0                          [stack]
+/// ```
+/// Regions below the heap that no VMA covers are the program image and are
+/// named after the executable.
+pub fn generate_maps(pid: u64) -> String {
+    with_process(pid, |manager, process| {
+        let owner = address_space_owner(manager, process);
+        let Some(page_table) = owner.page_table.as_deref() else {
+            return String::new();
+        };
+
+        let mut out = String::new();
+        for (start, end, flags) in split_at_heap(memory_regions(page_table, &owner.vmas), owner) {
+            let vma = owner.vmas.iter().find(|vma| vma.start.as_u64() == start);
+            let shared = vma.is_some_and(|vma| vma.flags.contains(MmapFlags::SHARED));
+            let mut line = format!(
+                "{:08x}-{:08x} {}{}{}{} 00000000 00:00 0",
+                start,
+                end,
+                if flags & PF_R != 0 { 'r' } else { '-' },
+                if flags & PF_W != 0 { 'w' } else { '-' },
+                if flags & PF_X != 0 { 'x' } else { '-' },
+                if shared { 's' } else { 'p' },
+            );
+            let name = if start < owner.user_stack_top && end > owner.user_stack_bottom {
+                Some("[stack]")
+            } else if start >= owner.heap_start && end <= owner.heap_end {
+                Some("[heap]")
+            } else if vma.is_none() && end <= owner.heap_start {
+                Some(owner.exe.as_str())
+            } else {
+                None
+            };
+            if let Some(name) = name {
+                while line.len() < MAPS_NAME_COLUMN {
+                    line.push(' ');
+                }
+                line.push(' ');
+                line.push_str(name);
+            }
+            line.push('\n');
+            out.push_str(&line);
+        }
+        out
+    })
+    .unwrap_or_default()
+}
+
+/// Split regions that run from the program image into the heap, so that the
+/// heap gets a line of its own
+fn split_at_heap(regions: Vec<(u64, u64, u32)>, process: &Process) -> Vec<(u64, u64, u32)> {
+    let heap = process.heap_start;
+    let mut split = Vec::with_capacity(regions.len() + 1);
+    for (start, end, flags) in regions {
+        if start < heap && heap < end {
+            split.push((start, heap, flags));
+            split.push((heap, end, flags));
+        } else {
+            split.push((start, end, flags));
+        }
+    }
+    split
+}
+
+/// Generate /proc/[pid]/statm: sizes in pages
+///
+/// Format: `size resident shared text lib data dt`. Pages are mapped
+/// eagerly, so the resident size is the mapped size.
+pub fn generate_statm(pid: u64) -> String {
+    with_process(pid, |manager, process| {
+        let owner = address_space_owner(manager, process);
+        let size = crate::process::rusage::resident_kb(owner) * 1024 / PAGE_SIZE;
+        let shared: u64 = owner
+            .vmas
+            .iter()
+            .filter(|vma| vma.flags.contains(MmapFlags::SHARED))
+            .map(|vma| (vma.end.as_u64() - vma.start.as_u64()) / PAGE_SIZE)
+            .sum();
+        let text = owner.memory_usage.code_size as u64 / PAGE_SIZE;
+        let data = size.saturating_sub(text + shared);
+        format!("{} {} {} {} 0 {} 0\n", size, size, shared, text, data)
+    })
+    .unwrap_or_default()
+}
+
+/// Generate /proc/[pid]/stat in the Linux format
+///
+/// The 52 space-separated fields of proc(5), starting
+/// `pid (comm) state ppid pgrp session ...`. CPU times are in clock ticks
+/// (USER_HZ) and count the whole thread group for a group leader, only the
+/// thread itself otherwise.
+pub fn generate_stat(pid: u64) -> String {
+    // Group members and their scheduler state, before the process manager lock
+    let Some((members, is_leader)) = with_process(pid, |manager, process| {
+        (
+            group_members(manager, process),
+            thread_group(process).as_u64() == pid,
+        )
+    }) else {
+        return String::new();
+    };
+    let counted: Vec<u64> = if is_leader {
+        members.clone()
+    } else {
+        alloc::vec![pid]
+    };
+    let usage = crate::task::scheduler::threads_usage(&counted);
+    let sched = crate::task::scheduler::threads_sched(&[pid])
+        .first()
+        .copied()
+        .unwrap_or_default();
+    let scheduler_state = crate::task::scheduler::get_process_display_state(pid);
+    let processor = crate::task::scheduler::get_process_running_cpu(pid).unwrap_or(0);
+
+    with_process(pid, |manager, process| {
+        let owner = address_space_owner(manager, process);
+        let mut cpu = usage;
+        let mut children = Default::default();
+        if is_leader {
+            cpu.add(&process.usage.dead_threads);
+            children = process.usage.children.cpu;
+        }
+
+        let state = match process.state {
+            ProcessState::Terminated(_) => 'Z',
+            ProcessState::Stopped(_) => 'T',
+            _ => match scheduler_state {
+                Some("Blocked") => 'S',
+                Some("Terminated") => 'Z',
+                _ => 'R',
+            },
+        };
+        // Linux's priority field: 20 + nice for normal threads, -1 - the
+        // real-time priority for real-time ones
+        let priority = if sched.is_realtime() {
+            -1 - sched.rt_priority as i64
+        } else {
+            20 + sched.nice as i64
+        };
+        let vsize = crate::process::rusage::resident_kb(owner) * 1024;
+        let start_code = owner
+            .auxv
+            .iter()
+            .find(|&&(key, _)| key == 3) // AT_PHDR
+            .map_or(0, |&(_, phdr)| phdr & !(PAGE_SIZE - 1));
+        let (ignored, caught) =
+            (1..=crate::signal::constants::NSIG).fold((0u64, 0u64), |(ignored, caught), sig| {
+                let action = process.signals.get_handler(sig);
+                let bit = crate::signal::constants::sig_mask(sig);
+                (
+                    ignored | if action.is_ignore() { bit } else { 0 },
+                    caught | if action.is_handler() { bit } else { 0 },
+                )
+            });
+        let exit_status = match process.exit_code {
+            Some(code) if code < 0 => -code & 0xff,
+            Some(code) => (code & 0xff) << 8,
+            None => 0,
+        };
+
+        let mut out = String::with_capacity(256);
+        let _ = write!(
+            out,
+            "{} ({}) {} {} {} {} 0 -1 0 {} {} 0 0 {} {} {} {} {} {} {} 0 {} {} {} {} ",
+            pid,
+            comm(&process.name),
+            state,
+            process.parent.map_or(0, |parent| parent.as_u64()),
+            process.pgid.as_u64(),
+            process.sid.as_u64(),
+            cpu.minor_faults,
+            children.minor_faults,
+            to_clock_ticks(cpu.user_ns),
+            to_clock_ticks(cpu.system_ns),
+            to_clock_ticks(children.user_ns),
+            to_clock_ticks(children.system_ns),
+            priority,
+            sched.nice,
+            members.len(),
+            to_clock_ticks(process.start_time_ns),
+            vsize,
+            vsize / PAGE_SIZE,
+            u64::MAX,
+        );
+        let _ = writeln!(
+            out,
+            "{} {} {} 0 0 {} {} {} {} 0 0 0 {} {} {} {} 0 0 0 0 0 {} 0 0 0 0 {}",
+            start_code,
+            owner.heap_start,
+            owner.user_stack_top,
+            process.signals.pending,
+            process.signals.blocked,
+            ignored,
+            caught,
+            if is_leader { 17 } else { -1 }, // SIGCHLD for processes
+            processor,
+            sched.rt_priority,
+            sched.policy.raw(),
+            owner.heap_start,
+            exit_status,
+        );
+        out
+    })
+    .unwrap_or_default()
+}
+
+/// The `comm` of a process: its program's file name, at most 15 bytes
+fn comm(name: &str) -> &str {
+    let base = name.rsplit('/').next().unwrap_or(name);
+    match base.char_indices().nth(15) {
+        Some((end, _)) => &base[..end],
+        None => base,
+    }
+}
@@ -2,6 +2,9 @@
 //!
 //! Defines error conditions that can occur during VFS operations.
 
+use crate::fs::ext2::Ext2Error;
+use crate::syscall::errno;
+
 /// VFS error types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VfsError {
@@ -27,4 +30,66 @@ pub enum VfsError {
     ReadOnly,
     /// Too many open files
     TooManyOpenFiles,
+    /// Directory not empty
+    NotEmpty,
+    /// Path component name too long
+    NameTooLong,
+    /// Operation would cross a filesystem boundary
+    CrossDevice,
+    /// Too many levels of symbolic links
+    TooManySymlinks,
+    /// Operation not permitted on this object
+    NotPermitted,
+    /// Object is busy (e.g. the root directory)
+    Busy,
+    /// File would exceed the maximum size
+    FileTooLarge,
+}
+
+impl VfsError {
+    /// The POSIX errno that userspace should see for this error
+    pub fn to_errno(self) -> i32 {
+        match self {
+            VfsError::NotFound => errno::ENOENT,
+            VfsError::PermissionDenied => errno::EACCES,
+            VfsError::IsDirectory => errno::EISDIR,
+            VfsError::NotDirectory => errno::ENOTDIR,
+            VfsError::AlreadyExists => errno::EEXIST,
+            VfsError::NoSpace => errno::ENOSPC,
+            VfsError::IoError => errno::EIO,
+            VfsError::InvalidPath => errno::EINVAL,
+            VfsError::NotMounted => errno::ENOENT,
+            VfsError::ReadOnly => errno::EROFS,
+            VfsError::TooManyOpenFiles => errno::EMFILE,
+            VfsError::NotEmpty => errno::ENOTEMPTY,
+            VfsError::NameTooLong => errno::ENAMETOOLONG,
+            VfsError::CrossDevice => errno::EXDEV,
+            VfsError::TooManySymlinks => errno::ELOOP,
+            VfsError::NotPermitted => errno::EPERM,
+            VfsError::Busy => errno::EBUSY,
+            VfsError::FileTooLarge => errno::EFBIG,
+        }
+    }
+}
+
+impl From<Ext2Error> for VfsError {
+    fn from(e: Ext2Error) -> Self {
+        match e {
+            Ext2Error::NotFound => VfsError::NotFound,
+            Ext2Error::AlreadyExists => VfsError::AlreadyExists,
+            Ext2Error::NoSpace => VfsError::NoSpace,
+            Ext2Error::NotEmpty => VfsError::NotEmpty,
+            Ext2Error::IsDirectory => VfsError::IsDirectory,
+            Ext2Error::NotDirectory => VfsError::NotDirectory,
+            Ext2Error::NameTooLong => VfsError::NameTooLong,
+            Ext2Error::ReadOnly => VfsError::ReadOnly,
+            Ext2Error::Io => VfsError::IoError,
+            Ext2Error::CrossDevice => VfsError::CrossDevice,
+            Ext2Error::InvalidArgument => VfsError::InvalidPath,
+            Ext2Error::TooManySymlinks => VfsError::TooManySymlinks,
+            Ext2Error::NotPermitted => VfsError::NotPermitted,
+            Ext2Error::Busy => VfsError::Busy,
+            Ext2Error::FileTooLarge => VfsError::FileTooLarge,
+        }
+    }
 }
@@ -165,8 +165,9 @@ pub fn is_display_taken() -> bool {
 ///
 /// Reads the current mouse position from whichever input driver is available:
 /// VirtIO tablet (QEMU/Parallels) or XHCI USB HID mouse (VMware/Parallels).
-/// Redraws the cursor sprite if the position has changed. This runs on
-/// the render thread's stack, not in interrupt context.
+/// Moves the virtio-gpu hardware cursor when the device has a cursor plane;
+/// otherwise redraws the cursor sprite if the position has changed. This
+/// runs on the render thread's stack, not in interrupt context.
 #[cfg(target_arch = "aarch64")]
 fn update_mouse_cursor() {
     let (mx, my) = if crate::drivers::virtio::input_mmio::is_tablet_initialized() {
@@ -181,6 +182,11 @@ fn update_mouse_cursor() {
         return;
     }
 
+    if crate::drivers::virtio::gpu_cursor::hardware_active() {
+        crate::drivers::virtio::gpu_cursor::update(mx, my);
+        return;
+    }
+
     if let Some(fb) = crate::graphics::arm64_fb::SHELL_FRAMEBUFFER.get() {
         if let Some(mut fb_guard) = fb.try_lock() {
             if super::cursor::update_cursor(&mut *fb_guard, mx as usize, my as usize) {
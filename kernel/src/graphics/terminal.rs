@@ -1,19 +1,22 @@
 //! Terminal pane component for split-screen rendering.
 //!
 //! Provides a bounded text rendering area that can be used for terminal emulation.
-//! Supports character-based positioning, scrolling, and basic ANSI escape sequences.
+//! Escape sequences are interpreted by `libvt`, the VT/xterm emulator shared with
+//! bterm; the pane paints the cells it reports as damaged.
 
 // Public API module - methods are intentionally available for future use
 #![allow(dead_code)]
 
+use libvt::{Attrs, Rgb, Terminal};
+
 use super::font::{Font, FontMetrics};
 use super::primitives::{draw_char, fill_rect, Canvas, Color, Rect, TextStyle};
 
 /// A terminal pane that renders text within a bounded framebuffer region.
 ///
-/// The pane manages a character grid and renders text using the graphics primitives.
-/// It supports basic terminal operations like newlines, carriage returns, backspace,
-/// and scrolling.
+/// The pane owns a `libvt::Terminal` sized to its character grid. Writes feed
+/// the emulator and then repaint only the damaged cells, moving the existing
+/// pixels up when the whole screen scrolls.
 pub struct TerminalPane {
     // Region bounds (pixels)
     x: usize,
@@ -25,11 +28,7 @@ pub struct TerminalPane {
     cols: usize,
     rows: usize,
 
-    // Cursor position (character coordinates)
-    cursor_col: usize,
-    cursor_row: usize,
-
-    // Colors
+    // Colors for cells with the default rendition
     fg_color: Color,
     bg_color: Color,
 
@@ -37,18 +36,11 @@ pub struct TerminalPane {
     font: Font,
     metrics: FontMetrics,
 
-    // ANSI escape sequence parsing state
-    ansi_state: AnsiState,
-    ansi_params: [u8; 16],
-    ansi_param_idx: usize,
-}
+    // Screen contents and escape sequence state
+    term: Terminal,
 
-/// ANSI escape sequence parser state
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum AnsiState {
-    Normal,
-    Escape, // Saw ESC (0x1B)
-    Csi,    // Saw ESC[
+    // Cell the cursor is currently drawn in
+    cursor_drawn: Option<(usize, usize)>,
 }
 
 impl TerminalPane {
@@ -67,6 +59,11 @@ impl TerminalPane {
         let cols = width / metrics.char_advance();
         let rows = height / metrics.line_height();
 
+        // Kernel log output has not been through a tty, so LF must also return
+        // the carriage
+        let mut term = Terminal::new(cols, rows);
+        term.set_newline_mode(true);
+
         Self {
             x,
             y,
@@ -74,15 +71,12 @@ impl TerminalPane {
             height,
             cols,
             rows,
-            cursor_col: 0,
-            cursor_row: 0,
             fg_color: Color::WHITE,
             bg_color: Color::rgb(20, 30, 50), // Dark blue background
             font,
             metrics,
-            ansi_state: AnsiState::Normal,
-            ansi_params: [0; 16],
-            ansi_param_idx: 0,
+            term,
+            cursor_drawn: None,
         }
     }
 
@@ -96,14 +90,26 @@ impl TerminalPane {
         self.rows
     }
 
-    /// Set the foreground (text) color.
+    /// Set the foreground (text) color used for the default rendition.
     pub fn set_fg_color(&mut self, color: Color) {
         self.fg_color = color;
+        self.term.damage_all();
     }
 
-    /// Set the background color.
+    /// Set the background color used for the default rendition.
     pub fn set_bg_color(&mut self, color: Color) {
         self.bg_color = color;
+        self.term.damage_all();
+    }
+
+    /// Get the emulator behind the pane.
+    pub fn terminal(&self) -> &Terminal {
+        &self.term
+    }
+
+    /// Get mutable access to the emulator behind the pane.
+    pub fn terminal_mut(&mut self) -> &mut Terminal {
+        &mut self.term
     }
 
     /// Clear the terminal pane with the background color.
@@ -118,8 +124,9 @@ impl TerminalPane {
             },
             self.bg_color,
         );
-        self.cursor_col = 0;
-        self.cursor_row = 0;
+        self.cursor_drawn = None;
+        self.term.feed(b"\x1b[2J\x1b[H");
+        self.render(canvas);
     }
 
     /// Convert character coordinates to pixel coordinates.
@@ -129,75 +136,79 @@ impl TerminalPane {
         (px as i32, py as i32)
     }
 
-    /// Write a single character at the cursor position.
-    fn write_char_at_cursor(&mut self, canvas: &mut impl Canvas, c: char) {
-        let (px, py) = self.char_to_pixel(self.cursor_col, self.cursor_row);
-
-        let style = TextStyle::new()
-            .with_color(self.fg_color)
-            .with_background(self.bg_color)
-            .with_font(self.font);
+    fn to_color(rgb: Rgb) -> Color {
+        Color::rgb(rgb.r, rgb.g, rgb.b)
+    }
 
-        draw_char(canvas, px, py, c, &style);
+    fn to_rgb(color: Color) -> Rgb {
+        Rgb::new(color.r, color.g, color.b)
     }
 
-    /// Clear the character at the cursor position.
-    fn clear_char_at_cursor(&mut self, canvas: &mut impl Canvas) {
-        let (px, py) = self.char_to_pixel(self.cursor_col, self.cursor_row);
+    /// Paint one cell of the display from the emulator's contents.
+    fn draw_cell(&self, canvas: &mut impl Canvas, col: usize, row: usize) {
+        let cell = self
+            .term
+            .display_line(row)
+            .get(col)
+            .copied()
+            .unwrap_or_default();
+        let (fg, bg) = cell.colors(Self::to_rgb(self.fg_color), Self::to_rgb(self.bg_color));
+        let (fg, bg) = (Self::to_color(fg), Self::to_color(bg));
+        let (px, py) = self.char_to_pixel(col, row);
+        let cell_width = self.metrics.char_advance() as u32;
 
         fill_rect(
             canvas,
             Rect {
                 x: px,
                 y: py,
-                width: self.metrics.char_advance() as u32,
+                width: cell_width,
                 height: self.metrics.line_height() as u32,
             },
-            self.bg_color,
+            bg,
         );
-    }
-
-    /// Move cursor to the next line.
-    fn newline(&mut self, canvas: &mut impl Canvas) {
-        self.cursor_col = 0;
-        self.cursor_row += 1;
-
-        // Check if we need to scroll
-        if self.cursor_row >= self.rows {
-            self.scroll_up(canvas);
-            self.cursor_row = self.rows.saturating_sub(1);
+        if cell.ch != ' ' && !cell.attrs.contains(Attrs::HIDDEN) {
+            let style = TextStyle::new().with_color(fg).with_font(self.font);
+            draw_char(canvas, px, py, cell.ch, &style);
         }
-    }
-
-    /// Move cursor to the beginning of the current line.
-    fn carriage_return(&mut self) {
-        self.cursor_col = 0;
-    }
-
-    /// Handle backspace - move cursor back and clear the character.
-    fn backspace(&mut self, canvas: &mut impl Canvas) {
-        if self.cursor_col > 0 {
-            self.cursor_col -= 1;
-            self.clear_char_at_cursor(canvas);
+        if cell.attrs.contains(Attrs::UNDERLINE) {
+            let underline_y = py + self.metrics.char_height as i32 - 1;
+            fill_rect(
+                canvas,
+                Rect {
+                    x: px,
+                    y: underline_y,
+                    width: cell_width,
+                    height: 1,
+                },
+                fg,
+            );
+        }
+        if cell.attrs.contains(Attrs::STRIKETHROUGH) {
+            let strike_y = py + self.metrics.char_height as i32 / 2;
+            fill_rect(
+                canvas,
+                Rect {
+                    x: px,
+                    y: strike_y,
+                    width: cell_width,
+                    height: 1,
+                },
+                fg,
+            );
         }
     }
 
-    /// Scroll the terminal up by one line.
-    pub fn scroll_up(&mut self, canvas: &mut impl Canvas) {
+    /// Move the pixels of the pane up by `lines` text rows.
+    fn blit_up(&mut self, canvas: &mut impl Canvas, lines: usize) {
         let line_height = self.metrics.line_height();
         let bytes_per_pixel = canvas.bytes_per_pixel();
         let stride = canvas.stride();
 
         // Calculate source and destination regions
-        let src_y = self.y + line_height;
+        let src_y = self.y + lines * line_height;
         let dst_y = self.y;
-        let copy_height = self.height.saturating_sub(line_height);
-
-        if copy_height == 0 {
-            // Terminal is only one line tall, just clear it
-            self.clear(canvas);
-            return;
-        }
+        let copy_height = (self.rows * line_height).saturating_sub(lines * line_height);
 
         // Copy each row up using the canvas buffer directly
         let buffer = canvas.buffer_mut();
@@ -217,339 +228,93 @@ impl TerminalPane {
         }
 
         // Mark the ENTIRE terminal region as dirty so it gets flushed to the framebuffer
-        // (both the copied region and the cleared line)
         canvas.mark_dirty_region(self.x, self.y, self.width, self.height);
-
-        // Clear the last line
-        let clear_y = self.y + copy_height;
-        fill_rect(
-            canvas,
-            Rect {
-                x: self.x as i32,
-                y: clear_y as i32,
-                width: self.width as u32,
-                height: line_height as u32,
-            },
-            self.bg_color,
-        );
     }
 
-    /// Write a single character to the terminal, handling control characters.
-    pub fn write_char(&mut self, canvas: &mut impl Canvas, c: char) {
-        let byte = c as u8;
-
-        match self.ansi_state {
-            AnsiState::Normal => {
-                if byte == 0x1B {
-                    // ESC character - start escape sequence
-                    self.ansi_state = AnsiState::Escape;
-                    return;
-                }
-                self.write_char_normal(canvas, byte);
-            }
-            AnsiState::Escape => {
-                if byte == b'[' {
-                    // CSI sequence (ESC[)
-                    self.ansi_state = AnsiState::Csi;
-                    self.ansi_param_idx = 0;
-                    self.ansi_params = [0; 16];
-                    return;
-                }
-                // Not a CSI sequence, output ESC and character, return to normal
-                self.ansi_state = AnsiState::Normal;
-                // Skip outputting raw ESC
-                self.write_char_normal(canvas, byte);
-            }
-            AnsiState::Csi => {
-                if byte.is_ascii_digit() {
-                    // Accumulate parameter digit
-                    if self.ansi_param_idx < 16 {
-                        self.ansi_params[self.ansi_param_idx] = self.ansi_params
-                            [self.ansi_param_idx]
-                            .saturating_mul(10)
-                            .saturating_add(byte - b'0');
-                    }
-                    return;
-                }
-                if byte == b';' {
-                    // Next parameter
-                    self.ansi_param_idx = (self.ansi_param_idx + 1).min(15);
-                    return;
-                }
-                // Command byte - execute and return to normal
-                self.ansi_state = AnsiState::Normal;
-                self.execute_csi(canvas, byte);
-            }
+    /// Repaint what changed since the last render.
+    ///
+    /// When the whole screen scrolled, the existing image is moved up first so
+    /// only the new lines and the cells written since need painting.
+    fn render(&mut self, canvas: &mut impl Canvas) {
+        if !self.term.is_damaged() {
+            return;
         }
-    }
-
-    /// Write a normal character (not part of escape sequence).
-    fn write_char_normal(&mut self, canvas: &mut impl Canvas, byte: u8) {
-        match byte {
-            b'\n' => self.newline(canvas),
-            b'\r' => self.carriage_return(),
-            0x08 | 0x7F => self.backspace(canvas), // Backspace or DEL
-            b'\t' => {
-                // Tab - advance to next tab stop (every 8 columns)
-                let next_tab = ((self.cursor_col / 8) + 1) * 8;
-                self.cursor_col = next_tab.min(self.cols.saturating_sub(1));
-            }
-            c => {
-                // Check if we need to wrap
-                if self.cursor_col >= self.cols {
-                    self.newline(canvas);
+        let scrolled = self.term.scrolled();
+        if scrolled > 0 && scrolled < self.rows {
+            self.blit_up(canvas, scrolled);
+        }
+        for row in 0..self.rows {
+            let columns = if scrolled >= self.rows {
+                Some(0..self.cols)
+            } else {
+                self.term.damage(row)
+            };
+            if let Some(columns) = columns {
+                for col in columns {
+                    self.draw_cell(canvas, col, row);
                 }
-
-                // Write the character
-                self.write_char_at_cursor(canvas, c as char);
-                self.cursor_col += 1;
             }
         }
+        self.term.clear_damage();
     }
 
-    /// Execute a CSI (Control Sequence Introducer) command.
-    fn execute_csi(&mut self, canvas: &mut impl Canvas, cmd: u8) {
-        let param1 = self.ansi_params[0] as usize;
-        let param2 = self.ansi_params[1] as usize;
-
-        match cmd {
-            b'J' => {
-                // Erase in Display
-                match param1 {
-                    0 => self.clear_to_end_of_screen(canvas),
-                    1 => self.clear_to_start_of_screen(canvas),
-                    2 => self.clear(canvas),
-                    _ => {}
-                }
-            }
-            b'K' => {
-                // Erase in Line
-                match param1 {
-                    0 => self.clear_to_eol(canvas),
-                    1 => self.clear_to_sol(canvas),
-                    2 => self.clear_line(canvas),
-                    _ => {}
-                }
-            }
-            b'H' | b'f' => {
-                // Cursor Position
-                // Parameters are 1-based, default to 1
-                let row = if param1 == 0 {
-                    0
-                } else {
-                    param1.saturating_sub(1)
-                };
-                let col = if param2 == 0 {
-                    0
-                } else {
-                    param2.saturating_sub(1)
-                };
-                self.set_cursor(
-                    col.min(self.cols.saturating_sub(1)),
-                    row.min(self.rows.saturating_sub(1)),
-                );
-            }
-            b'A' => {
-                // Cursor Up
-                let n = if param1 == 0 { 1 } else { param1 };
-                self.cursor_row = self.cursor_row.saturating_sub(n);
-            }
-            b'B' => {
-                // Cursor Down
-                let n = if param1 == 0 { 1 } else { param1 };
-                self.cursor_row = (self.cursor_row + n).min(self.rows.saturating_sub(1));
-            }
-            b'C' => {
-                // Cursor Forward
-                let n = if param1 == 0 { 1 } else { param1 };
-                self.cursor_col = (self.cursor_col + n).min(self.cols.saturating_sub(1));
-            }
-            b'D' => {
-                // Cursor Back
-                let n = if param1 == 0 { 1 } else { param1 };
-                self.cursor_col = self.cursor_col.saturating_sub(n);
-            }
-            b'm' => {
-                // SGR (Select Graphic Rendition)
-                self.process_sgr();
-            }
-            _ => {
-                // Unknown command - ignore
-            }
-        }
+    /// Scroll the terminal up by one line.
+    pub fn scroll_up(&mut self, canvas: &mut impl Canvas) {
+        self.write_bytes(canvas, b"\x1b[S");
     }
 
-    /// Process SGR (color/style) escape sequence.
-    fn process_sgr(&mut self) {
-        // Process all parameters up to ansi_param_idx
-        for i in 0..=self.ansi_param_idx {
-            match self.ansi_params[i] {
-                0 => {
-                    // Reset
-                    self.fg_color = Color::WHITE;
-                    self.bg_color = Color::rgb(20, 30, 50);
-                }
-                1 => {
-                    // Bold (brighten foreground)
-                    self.fg_color = Color::rgb(
-                        self.fg_color.r.saturating_add(40),
-                        self.fg_color.g.saturating_add(40),
-                        self.fg_color.b.saturating_add(40),
-                    );
-                }
-                30 => self.fg_color = Color::BLACK,
-                31 => self.fg_color = Color::RED,
-                32 => self.fg_color = Color::GREEN,
-                33 => self.fg_color = Color::rgb(255, 255, 0), // Yellow
-                34 => self.fg_color = Color::BLUE,
-                35 => self.fg_color = Color::rgb(255, 0, 255), // Magenta
-                36 => self.fg_color = Color::rgb(0, 255, 255), // Cyan
-                37 => self.fg_color = Color::WHITE,
-                40 => self.bg_color = Color::BLACK,
-                41 => self.bg_color = Color::RED,
-                42 => self.bg_color = Color::GREEN,
-                43 => self.bg_color = Color::rgb(255, 255, 0),
-                44 => self.bg_color = Color::BLUE,
-                45 => self.bg_color = Color::rgb(255, 0, 255),
-                46 => self.bg_color = Color::rgb(0, 255, 255),
-                47 => self.bg_color = Color::WHITE,
-                _ => {} // Ignore unknown SGR codes
-            }
-        }
+    /// Write a single character to the terminal, handling control characters.
+    pub fn write_char(&mut self, canvas: &mut impl Canvas, c: char) {
+        let mut utf8 = [0u8; 4];
+        self.write_bytes(canvas, c.encode_utf8(&mut utf8).as_bytes());
     }
 
     /// Set cursor position (in character coordinates).
     pub fn set_cursor(&mut self, col: usize, row: usize) {
-        self.cursor_col = col.min(self.cols.saturating_sub(1));
-        self.cursor_row = row.min(self.rows.saturating_sub(1));
+        self.term.set_cursor(col, row);
     }
 
     /// Get current cursor position.
     pub fn cursor(&self) -> (usize, usize) {
-        (self.cursor_col, self.cursor_row)
-    }
-
-    /// Clear from cursor to end of screen.
-    fn clear_to_end_of_screen(&mut self, canvas: &mut impl Canvas) {
-        // Clear rest of current line
-        self.clear_to_eol(canvas);
-
-        // Clear all lines below
-        let start_row = self.cursor_row + 1;
-        for row in start_row..self.rows {
-            let (px, py) = self.char_to_pixel(0, row);
-            fill_rect(
-                canvas,
-                Rect {
-                    x: px,
-                    y: py,
-                    width: self.width as u32,
-                    height: self.metrics.line_height() as u32,
-                },
-                self.bg_color,
-            );
-        }
-    }
-
-    /// Clear from start of screen to cursor.
-    fn clear_to_start_of_screen(&mut self, canvas: &mut impl Canvas) {
-        // Clear all lines above
-        for row in 0..self.cursor_row {
-            let (px, py) = self.char_to_pixel(0, row);
-            fill_rect(
-                canvas,
-                Rect {
-                    x: px,
-                    y: py,
-                    width: self.width as u32,
-                    height: self.metrics.line_height() as u32,
-                },
-                self.bg_color,
-            );
-        }
-
-        // Clear current line up to cursor
-        self.clear_to_sol(canvas);
-    }
-
-    /// Clear from cursor to end of line.
-    fn clear_to_eol(&mut self, canvas: &mut impl Canvas) {
-        let (px, py) = self.char_to_pixel(self.cursor_col, self.cursor_row);
-        let clear_width = self.x + self.width - px as usize;
-
-        fill_rect(
-            canvas,
-            Rect {
-                x: px,
-                y: py,
-                width: clear_width as u32,
-                height: self.metrics.line_height() as u32,
-            },
-            self.bg_color,
-        );
-    }
-
-    /// Clear from start of line to cursor.
-    fn clear_to_sol(&mut self, canvas: &mut impl Canvas) {
-        let (_, py) = self.char_to_pixel(0, self.cursor_row);
-        let clear_width = (self.cursor_col + 1) * self.metrics.char_advance();
-
-        fill_rect(
-            canvas,
-            Rect {
-                x: self.x as i32,
-                y: py,
-                width: clear_width as u32,
-                height: self.metrics.line_height() as u32,
-            },
-            self.bg_color,
-        );
-    }
-
-    /// Clear entire current line.
-    fn clear_line(&mut self, canvas: &mut impl Canvas) {
-        let (_, py) = self.char_to_pixel(0, self.cursor_row);
-
-        fill_rect(
-            canvas,
-            Rect {
-                x: self.x as i32,
-                y: py,
-                width: self.width as u32,
-                height: self.metrics.line_height() as u32,
-            },
-            self.bg_color,
-        );
+        self.term.cursor()
     }
 
     /// Write a string to the terminal.
     pub fn write_str(&mut self, canvas: &mut impl Canvas, s: &str) {
-        for c in s.chars() {
-            self.write_char(canvas, c);
-        }
+        self.write_bytes(canvas, s.as_bytes());
     }
 
     /// Write bytes to the terminal (processes ANSI escape sequences).
     pub fn write_bytes(&mut self, canvas: &mut impl Canvas, bytes: &[u8]) {
-        for &byte in bytes {
-            self.write_char(canvas, byte as char);
+        // The cell under a drawn cursor is repainted with whatever moves there,
+        // and the cursor redrawn where it ends up
+        let cursor_was_drawn = self.cursor_drawn.take();
+        if let Some((col, row)) = cursor_was_drawn {
+            self.term.damage_cell(col, row);
+        }
+        self.term.feed(bytes);
+        self.render(canvas);
+        if cursor_was_drawn.is_some() {
+            self.draw_cursor(canvas, true);
         }
     }
 
     /// Draw a cursor at the current position.
     pub fn draw_cursor(&mut self, canvas: &mut impl Canvas, visible: bool) {
-        let (px, py) = self.char_to_pixel(self.cursor_col, self.cursor_row);
+        if let Some((col, row)) = self.cursor_drawn.take() {
+            self.draw_cell(canvas, col, row);
+        }
+        if !visible || !self.term.cursor_visible() {
+            return;
+        }
+
+        let (col, row) = self.term.cursor();
+        let (px, py) = self.char_to_pixel(col, row);
 
         // Draw underscore-style cursor at bottom of character cell
         let cursor_height = 2;
         let cursor_y = py + self.metrics.line_height() as i32 - cursor_height;
 
-        let color = if visible {
-            self.fg_color
-        } else {
-            self.bg_color
-        };
-
         fill_rect(
             canvas,
             Rect {
@@ -558,8 +323,9 @@ impl TerminalPane {
                 width: self.metrics.char_advance() as u32,
                 height: cursor_height as u32,
             },
-            color,
+            self.fg_color,
         );
+        self.cursor_drawn = Some((col, row));
     }
 }
 
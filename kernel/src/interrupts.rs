@@ -77,8 +77,15 @@ pub fn init_idt() {
         idt.divide_error.set_handler_fn(divide_by_zero_handler);
 
         // Debug exception handler (#DB) - IDT[1]
-        // Triggered by TF (Trap Flag) for single-stepping
-        idt.debug.set_handler_fn(debug_handler);
+        // Triggered by TF (Trap Flag) for single-stepping. The assembly entry
+        // runs the signal delivery return path so a traced process stops
+        // right after the stepped instruction.
+        extern "C" {
+            fn debug_entry();
+        }
+        unsafe {
+            idt.debug.set_handler_addr(VirtAddr::new(debug_entry as u64));
+        }
 
         // Breakpoint handler - must be callable from userspace
         // Set DPL=3 to allow INT3 from Ring 3
@@ -103,9 +110,15 @@ pub fn init_idt() {
                 .set_handler_fn(double_fault_handler)
                 .set_stack_index(gdt::DOUBLE_FAULT_IST_INDEX);
         }
+        // Page fault handler (#PF) - IDT[14]
+        // The assembly entry saves the user registers so a SIGSEGV handler
+        // can be entered directly from the fault.
+        extern "C" {
+            fn page_fault_entry();
+        }
         unsafe {
             idt.page_fault
-                .set_handler_fn(page_fault_handler)
+                .set_handler_addr(VirtAddr::new(page_fault_entry as u64))
                 .set_stack_index(gdt::PAGE_FAULT_IST_INDEX);
         }
 
@@ -305,27 +318,44 @@ pub fn enable_virtio_irq() {
     enable_irq11();
 }
 
-extern "x86-interrupt" fn debug_handler(stack_frame: InterruptStackFrame) {
+/// Debug exception (#DB) handler, called from the `debug_entry` assembly stub
+///
+/// From userspace this is a single-step trap armed by PTRACE_SINGLESTEP: the
+/// trap flag is cleared and SIGTRAP queued, and `debug_entry` delivers it on
+/// the way back out so the tracee stops before its next instruction.
+#[no_mangle]
+pub extern "C" fn rust_debug_handler(
+    _saved_regs: &mut crate::task::process_context::SavedRegisters,
+    stack_frame: &mut InterruptStackFrame,
+) {
     // Enter exception context - use preempt_disable for exceptions (not IRQs)
     crate::per_cpu::preempt_disable();
 
+    // DR6 is sticky: read the cause and reset it for the next trap
+    let dr6: u64;
+    unsafe {
+        core::arch::asm!("mov {}, dr6", out(reg) dr6, options(nomem, nostack));
+        core::arch::asm!("mov dr6, {}", in(reg) 0xFFFF_0FF0u64, options(nomem, nostack));
+    }
+
     // Check if we came from userspace
     let from_userspace = (stack_frame.code_segment.0 & 3) == 3;
 
     if from_userspace {
-        log::info!("🎯 #DB (DEBUG EXCEPTION) from USERSPACE - IRETQ SUCCEEDED!");
-        log::info!(
-            "  RIP: {:#x} (first user instruction after IRETQ)",
-            stack_frame.instruction_pointer.as_u64()
-        );
-        log::info!(
-            "  RSP: {:#x}, CS: {:#x} (RPL={}), SS: {:#x}",
-            stack_frame.stack_pointer.as_u64(),
-            stack_frame.code_segment.0,
-            stack_frame.code_segment.0 & 3,
-            stack_frame.stack_segment.0
-        );
-        // TODO: Clear TF flag to stop single-stepping after proving IRETQ works
+        let rip = stack_frame.instruction_pointer.as_u64();
+        unsafe {
+            stack_frame.as_mut().update(|frame| {
+                frame
+                    .cpu_flags
+                    .remove(x86_64::registers::rflags::RFlags::TRAP_FLAG)
+            });
+        }
+        // DR6.BS (bit 14): single step
+        if dr6 & (1 << 14) != 0 {
+            if let Some(thread_id) = crate::task::scheduler::current_thread_id() {
+                crate::process::ptrace::step_trap(thread_id, rip);
+            }
+        }
     } else {
         log::info!(
             "#DB (Debug Exception) from kernel at {:#x}",
@@ -1054,12 +1084,20 @@ fn handle_stack_growth(faulting_addr: VirtAddr, cr3: u64) -> bool {
     true
 }
 
-extern "x86-interrupt" fn page_fault_handler(
-    mut stack_frame: InterruptStackFrame,
-    error_code: PageFaultErrorCode,
-) {
+/// Page fault (#PF) handler, called from the `page_fault_entry` assembly stub
+///
+/// Returns true when SIGSEGV was queued for a user handler; `page_fault_entry`
+/// then delivers it on the way back out, so the handler runs in place of the
+/// faulting instruction. Every other outcome returns false.
+#[no_mangle]
+pub extern "C" fn rust_page_fault_handler(
+    stack_frame: &mut InterruptStackFrame,
+    error_code: u64,
+) -> bool {
     use x86_64::registers::control::Cr2;
 
+    let error_code = PageFaultErrorCode::from_bits_truncate(error_code);
+
     // Read CR2 and CR3 first
     let cr2 = Cr2::read().unwrap_or(x86_64::VirtAddr::zero()).as_u64();
     let cr3 = {
@@ -1249,8 +1287,9 @@ extern "x86-interrupt" fn page_fault_handler(
     let is_user_address = accessed_addr.as_u64() < crate::memory::layout::USER_STACK_REGION_END;
     if is_user_address && handle_cow_fault(accessed_addr, error_code, cr3) {
         // CoW fault handled successfully - resume execution
+        crate::task::accounting::record_minor_fault();
         crate::per_cpu::preempt_enable();
-        return;
+        return false;
     }
 
     // Try to handle as demand-paged stack growth
@@ -1261,8 +1300,31 @@ extern "x86-interrupt" fn page_fault_handler(
         && !error_code.contains(PageFaultErrorCode::INSTRUCTION_FETCH)
         && handle_stack_growth(accessed_addr, cr3)
     {
+        crate::task::accounting::record_minor_fault();
         crate::per_cpu::preempt_enable();
-        return;
+        return false;
+    }
+
+    // Hand the fault to the process's SIGSEGV handler if it has one (or to a
+    // core dump of the faulting state). page_fault_entry delivers the signal
+    // before returning to userspace.
+    if from_userspace {
+        use crate::signal::constants::{SEGV_ACCERR, SEGV_MAPERR, SIGSEGV};
+        use crate::signal::delivery::queue_fault_signal;
+        let code = if error_code.contains(PageFaultErrorCode::PROTECTION_VIOLATION) {
+            SEGV_ACCERR
+        } else {
+            SEGV_MAPERR
+        };
+        let info = crate::signal::types::SigInfo::fault(SIGSEGV, code, accessed_addr.as_u64());
+        let queued = crate::process::with_process_manager(|pm| {
+            pm.find_process_by_cr3_mut(cr3)
+                .is_some_and(|(_, process)| queue_fault_signal(process, info))
+        });
+        if queued == Some(true) {
+            crate::per_cpu::preempt_enable();
+            return true;
+        }
     }
 
     crate::serial_println!("EXCEPTION: PAGE FAULT - Now using IST stack for reliable diagnostics");
@@ -1481,12 +1543,12 @@ extern "x86-interrupt" fn page_fault_handler(
 
             // CR3 is already the kernel table. Rewrite the frame last, using the
             // scheduler-owned idle thread stack rather than the dying thread's stack.
-            context_switch::setup_idle_return(&mut stack_frame);
+            context_switch::setup_idle_return(stack_frame);
 
             log::info!("Page fault handler: Modified exception frame to return to idle loop");
 
             // Return from handler - IRET will jump to idle_loop
-            return;
+            return false;
         }
 
         // Kernel page fault - this is a bug, panic
@@ -891,6 +891,39 @@ fn switch_to_thread(
                             crate::signal::delivery::SignalDeliveryResult::Delivered => {
                                 log::info!("Signal delivered to thread {}", thread_id);
                             }
+                            crate::signal::delivery::SignalDeliveryResult::Stopped => {
+                                park_stopped_thread(
+                                    process,
+                                    thread_id,
+                                    saved_regs,
+                                    interrupt_frame,
+                                );
+                                unsafe {
+                                    crate::memory::process_memory::switch_to_kernel_page_table();
+                                }
+                                return;
+                            }
+                            crate::signal::delivery::SignalDeliveryResult::JobStopped(
+                                notification,
+                            ) => {
+                                crate::process::job_control::park(process, thread_id);
+                                park_stopped_thread(
+                                    process,
+                                    thread_id,
+                                    saved_regs,
+                                    interrupt_frame,
+                                );
+                                drop(manager_guard);
+                                if let Some(notification) = notification {
+                                    crate::process::job_control::notify_parent_deferred(
+                                        &notification,
+                                    );
+                                }
+                                unsafe {
+                                    crate::memory::process_memory::switch_to_kernel_page_table();
+                                }
+                                return;
+                            }
                             crate::signal::delivery::SignalDeliveryResult::NoAction => {}
                         }
                     } else {
@@ -1171,6 +1204,8 @@ fn restore_userspace_thread_context(
 
     // Track if signal termination happened (for parent notification after borrow ends)
     let mut signal_termination_info: Option<crate::signal::delivery::ParentNotification> = None;
+    // Same for a job-control stop
+    let mut job_notification: Option<crate::process::job_control::JobNotification> = None;
 
     if let Some(mut manager_guard) = guard_option {
         if let Some(ref mut manager) = *manager_guard {
@@ -1335,6 +1370,26 @@ fn restore_userspace_thread_context(
                                             crate::task::scheduler::switch_to_idle();
                                         }
                                     }
+                                    crate::signal::delivery::SignalDeliveryResult::Stopped => {
+                                        park_stopped_thread(
+                                            process,
+                                            thread_id,
+                                            saved_regs,
+                                            interrupt_frame,
+                                        );
+                                    }
+                                    crate::signal::delivery::SignalDeliveryResult::JobStopped(
+                                        notification,
+                                    ) => {
+                                        crate::process::job_control::park(process, thread_id);
+                                        park_stopped_thread(
+                                            process,
+                                            thread_id,
+                                            saved_regs,
+                                            interrupt_frame,
+                                        );
+                                        job_notification = notification;
+                                    }
                                     crate::signal::delivery::SignalDeliveryResult::NoAction => {}
                                 }
                             }
@@ -1349,6 +1404,9 @@ fn restore_userspace_thread_context(
             if let Some(notification) = signal_termination_info {
                 crate::signal::delivery::notify_parent_of_termination_deferred(&notification);
             }
+            if let Some(notification) = job_notification {
+                crate::process::job_control::notify_parent_deferred(&notification);
+            }
         }
     } else {
         log::error!(
@@ -1491,6 +1549,8 @@ fn check_and_deliver_signals_for_current_thread(
 
     // Track if signal termination happened (for parent notification after borrow ends)
     let mut signal_termination_info: Option<crate::signal::delivery::ParentNotification> = None;
+    // Same for a job-control stop
+    let mut job_notification: Option<crate::process::job_control::JobNotification> = None;
 
     if let Some(ref mut manager) = *manager_guard {
         // Find the process for this thread
@@ -1499,7 +1559,7 @@ fn check_and_deliver_signals_for_current_thread(
             crate::signal::delivery::check_and_fire_alarm(process);
             crate::signal::delivery::check_and_fire_itimer_real(process, 5000);
 
-            if crate::signal::delivery::has_deliverable_signals(process) {
+            if crate::signal::delivery::has_return_work(process) {
                 // Switch to process's page table for signal delivery
                 if let Some(cr3_val) = process.cr3_value() {
                     unsafe {
@@ -1536,6 +1596,24 @@ fn check_and_deliver_signals_for_current_thread(
                             crate::task::scheduler::switch_to_idle();
                         }
                     }
+                    crate::signal::delivery::SignalDeliveryResult::Stopped => {
+                        park_stopped_thread(
+                            process,
+                            current_thread_id,
+                            saved_regs,
+                            interrupt_frame,
+                        );
+                    }
+                    crate::signal::delivery::SignalDeliveryResult::JobStopped(notification) => {
+                        crate::process::job_control::park(process, current_thread_id);
+                        park_stopped_thread(
+                            process,
+                            current_thread_id,
+                            saved_regs,
+                            interrupt_frame,
+                        );
+                        job_notification = notification;
+                    }
                     crate::signal::delivery::SignalDeliveryResult::NoAction => {}
                 }
             }
@@ -1549,7 +1627,32 @@ fn check_and_deliver_signals_for_current_thread(
         if let Some(notification) = signal_termination_info {
             crate::signal::delivery::notify_parent_of_termination_deferred(&notification);
         }
+        if let Some(notification) = job_notification {
+            crate::process::job_control::notify_parent_deferred(&notification);
+        }
+    }
+}
+
+/// Park a thread that a signal stopped, for its ptrace tracer or for job control
+///
+/// Saves the user registers where the thread is resumed from, marks it
+/// Blocked and leaves for idle. Runs under the process manager lock, so the
+/// thread can only be resumed once it is parked: by the tracer
+/// (`ptrace::resume` writes any register changes into the saved context and
+/// unblocks it) or by SIGCONT (`job_control::finish_continue` unblocks it).
+fn park_stopped_thread(
+    process: &mut crate::process::Process,
+    thread_id: u64,
+    saved_regs: &SavedRegisters,
+    interrupt_frame: &mut InterruptStackFrame,
+) {
+    if let Some(ref mut thread) = process.main_thread {
+        save_userspace_context(thread, interrupt_frame, saved_regs);
     }
+    scheduler::with_thread_mut(thread_id, |thread| thread.set_blocked());
+    scheduler::set_need_resched();
+    setup_idle_return(interrupt_frame);
+    scheduler::switch_to_idle();
 }
 
 /// Simple idle loop - made pub for exception handlers that need to jump to idle
@@ -0,0 +1,139 @@
+; Debug exception (#DB) entry for single-step traps
+;
+; PTRACE_SINGLESTEP sets the trap flag in the tracee's RFLAGS, so #DB fires
+; after one user instruction. The Rust handler queues SIGTRAP; this stub then
+; takes the same reschedule and signal delivery path as the timer interrupt,
+; so the tracee stops for its tracer before running another instruction.
+
+global debug_entry
+extern rust_debug_handler
+extern check_need_resched_and_switch
+
+; CRITICAL: Place exception entry code in dedicated section that stays mapped
+; This ensures the code is accessible after CR3 switches to process page tables
+section .text.entry
+bits 64
+
+; Define constant for saved register count to avoid magic numbers
+%define SAVED_REGS_COUNT 15
+%define SAVED_REGS_SIZE (SAVED_REGS_COUNT * 8)
+
+debug_entry:
+    ; CRITICAL: Disable interrupts BEFORE saving any registers
+    cli
+
+    ; #DB pushes no error code; the frame matches the timer interrupt's
+
+    ; Save all general purpose registers
+    push rax
+    push rcx
+    push rdx
+    push rbx
+    push rbp
+    push rsi
+    push rdi
+    push r8
+    push r9
+    push r10
+    push r11
+    push r12
+    push r13
+    push r14
+    push r15
+
+    ; Frame layout after pushes: [r15...rax][RIP][CS][RFLAGS][RSP][SS]
+    ; CS is at RSP + 15*8 + 8 (15 saved regs + RIP)
+    mov rax, [rsp + SAVED_REGS_SIZE + 8]  ; Get CS
+    and rax, 3                            ; Check privilege level (RPL bits)
+    cmp rax, 3                            ; Ring 3?
+    jne .from_kernel
+
+    ; We came from userspace, swap to kernel GS
+    swapgs
+
+    ; Save the process CR3 to per-CPU data at gs:[80] (SAVED_PROCESS_CR3_OFFSET)
+    ; so the exit path can restore it if no context switch happens
+    mov rax, cr3
+    mov qword [gs:80], rax
+
+    cld
+    mov rdi, rsp                          ; Pass pointer to saved registers
+    lea rsi, [rsp + SAVED_REGS_SIZE]      ; Pass pointer to interrupt frame
+    call rust_debug_handler
+
+    ; Deliver the queued SIGTRAP (or switch away from a stopped tracee)
+    mov rdi, rsp
+    lea rsi, [rsp + SAVED_REGS_SIZE]
+    call check_need_resched_and_switch
+
+    cli
+
+    ; Restore all general purpose registers
+    ; Note: If we switched contexts, these will be different registers!
+    pop r15
+    pop r14
+    pop r13
+    pop r12
+    pop r11
+    pop r10
+    pop r9
+    pop r8
+    pop rdi
+    pop rsi
+    pop rbp
+    pop rbx
+    pop rdx
+    pop rcx
+    pop rax
+
+    ; The context switcher stores the target CR3 in gs:[64] (NEXT_CR3_OFFSET).
+    ; If it is set, switch to it; otherwise restore the CR3 saved on entry.
+    push rax
+    mov rax, qword [gs:64]
+    test rax, rax
+    jz .restore_saved_cr3
+
+    ; Clear next_cr3 BEFORE switching CR3, while per-CPU data is surely mapped
+    mov qword [gs:64], 0
+    mov cr3, rax
+    jmp .cr3_done
+
+.restore_saved_cr3:
+    mov rax, qword [gs:80]
+    test rax, rax
+    jz .cr3_done
+    mov cr3, rax
+
+.cr3_done:
+    pop rax
+
+    ; Returning to userspace, swap back to user GS
+    swapgs
+    iretq
+
+.from_kernel:
+    ; Kernel #DB: log it and return; no scheduling from here
+    cld
+    mov rdi, rsp
+    lea rsi, [rsp + SAVED_REGS_SIZE]
+    call rust_debug_handler
+
+    cli
+
+    pop r15
+    pop r14
+    pop r13
+    pop r12
+    pop r11
+    pop r10
+    pop r9
+    pop r8
+    pop rdi
+    pop rsi
+    pop rbp
+    pop rbx
+    pop rdx
+    pop rcx
+    pop rax
+
+    iretq
@@ -0,0 +1,176 @@
+; Page fault (#PF) entry
+;
+; The Rust handler resolves CoW and stack-growth faults, kills a faulting
+; process that has no SIGSEGV handler, and panics on kernel faults. When it
+; queues SIGSEGV for a user handler instead, this stub takes the same signal
+; delivery path as the timer interrupt on the way out, so the handler runs
+; straight away rather than the faulting instruction re-faulting until the
+; next tick.
+
+global page_fault_entry
+extern rust_page_fault_handler
+extern check_need_resched_and_switch
+
+; CRITICAL: Place exception entry code in dedicated section that stays mapped
+; This ensures the code is accessible after CR3 switches to process page tables
+section .text.entry
+bits 64
+
+; Define constant for saved register count to avoid magic numbers
+%define SAVED_REGS_COUNT 15
+%define SAVED_REGS_SIZE (SAVED_REGS_COUNT * 8)
+; Saved registers + error code + RIP, CS, RFLAGS, RSP, SS
+%define FRAME_QWORDS (SAVED_REGS_COUNT + 6)
+; Per-CPU kernel_stack_top (PERCPU_KERNEL_STACK_TOP_OFFSET), kept equal to TSS.RSP0
+%define KERNEL_STACK_TOP_OFFSET 16
+
+page_fault_entry:
+    ; CRITICAL: Disable interrupts BEFORE saving any registers
+    cli
+
+    ; Save all general purpose registers
+    push rax
+    push rcx
+    push rdx
+    push rbx
+    push rbp
+    push rsi
+    push rdi
+    push r8
+    push r9
+    push r10
+    push r11
+    push r12
+    push r13
+    push r14
+    push r15
+
+    ; Frame layout after pushes: [r15...rax][error code][RIP][CS][RFLAGS][RSP][SS]
+    ; CS is at RSP + 15*8 + 16 (15 saved regs + error code + RIP)
+    mov rax, [rsp + SAVED_REGS_SIZE + 16] ; Get CS
+    and rax, 3                            ; Check privilege level (RPL bits)
+    cmp rax, 3                            ; Ring 3?
+    jne .from_kernel
+
+    ; We came from userspace, swap to kernel GS
+    swapgs
+
+    ; Save the process CR3 to per-CPU data at gs:[80] (SAVED_PROCESS_CR3_OFFSET)
+    ; so the exit path can restore it if no context switch happens
+    mov rax, cr3
+    mov qword [gs:80], rax
+
+    cld
+    lea rdi, [rsp + SAVED_REGS_SIZE + 8]  ; Pass pointer to interrupt frame
+    mov rsi, [rsp + SAVED_REGS_SIZE]      ; Pass error code
+    sub rsp, 8                            ; The error code leaves the stack 8 off 16-byte alignment
+    call rust_page_fault_handler
+    add rsp, 8
+
+    ; false: the fault was resolved (or the process killed); just return
+    test al, al
+    jz .exit
+
+    ; true: SIGSEGV is queued for a user handler. Delivering it writes the
+    ; signal frame to the user stack, which can itself fault (e.g. CoW) and
+    ; re-enter this vector at the top of the same IST stack, so first move
+    ; the saved state to the thread's kernel stack.
+    mov rsi, rsp
+    mov rdi, qword [gs:KERNEL_STACK_TOP_OFFSET]
+    sub rdi, FRAME_QWORDS * 8
+    mov rsp, rdi
+    mov rcx, FRAME_QWORDS
+    rep movsq
+
+    ; Deliver the queued SIGSEGV (or switch away if another thread is due)
+    mov rdi, rsp
+    lea rsi, [rsp + SAVED_REGS_SIZE + 8]
+    sub rsp, 8
+    call check_need_resched_and_switch
+    add rsp, 8
+
+.exit:
+    cli
+
+    ; Restore all general purpose registers
+    ; Note: If we switched contexts, these will be different registers!
+    pop r15
+    pop r14
+    pop r13
+    pop r12
+    pop r11
+    pop r10
+    pop r9
+    pop r8
+    pop rdi
+    pop rsi
+    pop rbp
+    pop rbx
+    pop rdx
+    pop rcx
+    pop rax
+
+    ; Drop the error code
+    add rsp, 8
+
+    ; Killing the process rewrites the frame to return to the kernel idle
+    ; loop, with the kernel CR3 already loaded; stay on kernel GS for it.
+    test qword [rsp + 8], 3               ; CS
+    jz .iret
+
+    ; The context switcher stores the target CR3 in gs:[64] (NEXT_CR3_OFFSET).
+    ; If it is set, switch to it; otherwise restore the CR3 saved on entry.
+    push rax
+    mov rax, qword [gs:64]
+    test rax, rax
+    jz .restore_saved_cr3
+
+    ; Clear next_cr3 BEFORE switching CR3, while per-CPU data is surely mapped
+    mov qword [gs:64], 0
+    mov cr3, rax
+    jmp .cr3_done
+
+.restore_saved_cr3:
+    mov rax, qword [gs:80]
+    test rax, rax
+    jz .cr3_done
+    mov cr3, rax
+
+.cr3_done:
+    pop rax
+
+    ; Returning to userspace, swap back to user GS
+    swapgs
+.iret:
+    iretq
+
+.from_kernel:
+    ; Kernel #PF: CoW on a user page (e.g. copy_to_user) or a kernel bug
+    cld
+    lea rdi, [rsp + SAVED_REGS_SIZE + 8]
+    mov rsi, [rsp + SAVED_REGS_SIZE]
+    sub rsp, 8
+    call rust_page_fault_handler
+    add rsp, 8
+
+    cli
+
+    pop r15
+    pop r14
+    pop r13
+    pop r12
+    pop r11
+    pop r10
+    pop r9
+    pop r8
+    pop rdi
+    pop rsi
+    pop rbp
+    pop rbx
+    pop rdx
+    pop rcx
+    pop rax
+
+    ; Drop the error code
+    add rsp, 8
+    iretq
@@ -44,18 +44,44 @@ pub mod fcntl_cmd {
     pub const F_GETFL: i32 = 3;
     /// Set file status flags
     pub const F_SETFL: i32 = 4;
+    /// Get the first record lock that would block a lock (POSIX)
+    pub const F_GETLK: i32 = 5;
+    /// Set or clear a record lock without waiting (POSIX)
+    pub const F_SETLK: i32 = 6;
+    /// Set a record lock, waiting for conflicting locks (POSIX)
+    pub const F_SETLKW: i32 = 7;
+    /// F_GETLK for open file description locks
+    pub const F_OFD_GETLK: i32 = 36;
+    /// F_SETLK for open file description locks
+    pub const F_OFD_SETLK: i32 = 37;
+    /// F_SETLKW for open file description locks
+    pub const F_OFD_SETLKW: i32 = 38;
     /// Duplicate fd with close-on-exec set
     pub const F_DUPFD_CLOEXEC: i32 = 1030;
 }
 
 /// Regular file descriptor
-#[derive(Clone, Debug)]
+///
+/// One of these is an open file description: dup() and fork() share it
+/// through the Arc in `FdKind::RegularFile`, so it is dropped when the last
+/// descriptor referring to it is closed.
+#[derive(Debug)]
 #[allow(dead_code)] // Fields will be used when open/read/write are fully implemented
 pub struct RegularFile {
     pub inode_num: u64,
     pub mount_id: usize,
     pub position: u64,
     pub flags: u32,
+    /// Owner ID for flock and OFD locks taken through this description
+    pub open_file_id: u64,
+    /// Absolute path the file was opened by (for /proc/<pid>/fd links)
+    pub path: alloc::string::String,
+}
+
+impl Drop for RegularFile {
+    fn drop(&mut self) {
+        crate::fs::lock::release_open_file(self.open_file_id);
+    }
 }
 
 /// Directory file descriptor (for getdents)
@@ -64,6 +90,8 @@ pub struct DirectoryFile {
     pub inode_num: u64,
     pub mount_id: usize,
     pub position: u64, // Current offset in directory entries
+    /// Absolute path the directory was opened by (for /proc/<pid>/fd links)
+    pub path: alloc::string::String,
 }
 
 /// Types of file descriptors
@@ -133,6 +161,8 @@ pub enum FdKind {
     ProcfsFile {
         content: alloc::string::String,
         position: usize,
+        /// Path the file was opened by
+        path: alloc::string::String,
     },
     /// Procfs directory listing (for /proc and /proc/[pid])
     ProcfsDirectory {
@@ -141,6 +171,8 @@ pub enum FdKind {
     },
     /// Epoll instance file descriptor
     Epoll(u64),
+    /// Audio stream from an open of /dev/dsp (shared with the mixer)
+    Dsp(crate::audio::SharedStream),
 }
 
 impl core::fmt::Debug for FdKind {
@@ -174,13 +206,16 @@ impl core::fmt::Debug for FdKind {
             }
             FdKind::FifoRead(path, _) => write!(f, "FifoRead({})", path),
             FdKind::FifoWrite(path, _) => write!(f, "FifoWrite({})", path),
-            FdKind::ProcfsFile { content, position } => {
+            FdKind::ProcfsFile {
+                content, position, ..
+            } => {
                 write!(f, "ProcfsFile(len={}, pos={})", content.len(), position)
             }
             FdKind::ProcfsDirectory { path, position } => {
                 write!(f, "ProcfsDirectory(path={}, pos={})", path, position)
             }
             FdKind::Epoll(id) => write!(f, "Epoll({})", id),
+            FdKind::Dsp(_) => write!(f, "Dsp"),
         }
     }
 }
@@ -780,6 +815,10 @@ impl Drop for FdTable {
                         // Clean up the epoll instance
                         crate::syscall::epoll::remove_instance(id);
                     }
+                    FdKind::Dsp(_) => {
+                        // The mixer drops the stream once it has played out
+                        log::debug!("FdTable::drop() - releasing dsp fd {}", i);
+                    }
                 }
             }
         }
@@ -5,10 +5,14 @@
 //! read and write ends of a pipe.
 
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
 
 /// Default pipe buffer size (matches Linux)
 pub const PIPE_BUF_SIZE: usize = 65536;
 
+/// Next pipe inode number (pipes have no filesystem, so these are synthetic)
+static NEXT_PIPE_INO: AtomicU64 = AtomicU64::new(1000);
+
 /// Pipe buffer - a circular buffer with reader/writer tracking
 pub struct PipeBuffer {
     /// The buffer storage
@@ -25,6 +29,8 @@ pub struct PipeBuffer {
     writers: usize,
     /// Threads waiting to read from this pipe
     read_waiters: Vec<u64>,
+    /// Inode number shared by both ends, reported by fstat and /proc/<pid>/fd
+    ino: u64,
 }
 
 impl PipeBuffer {
@@ -40,9 +46,15 @@ impl PipeBuffer {
             readers: 1,
             writers: 1,
             read_waiters: Vec::new(),
+            ino: NEXT_PIPE_INO.fetch_add(1, Ordering::Relaxed),
         }
     }
 
+    /// Inode number of this pipe
+    pub fn ino(&self) -> u64 {
+        self.ino
+    }
+
     /// Read from the pipe buffer
     ///
     /// Returns:
@@ -75,6 +87,50 @@ impl PipeBuffer {
         Ok(read)
     }
 
+    /// Copy buffered bytes into `buf` without consuming them
+    ///
+    /// Used by tee() and splice(), which duplicate or forward pipe contents
+    /// and only consume what the destination accepted. Returns the number of
+    /// bytes copied (0 if the buffer is empty).
+    pub fn peek(&self, buf: &mut [u8]) -> usize {
+        let to_copy = buf.len().min(self.len);
+        let mut pos = self.read_pos;
+        for byte in buf.iter_mut().take(to_copy) {
+            *byte = self.buffer[pos];
+            pos = (pos + 1) % PIPE_BUF_SIZE;
+        }
+        to_copy
+    }
+
+    /// Append up to `max` buffered bytes to `dst` without consuming them
+    ///
+    /// Used by tee() and pipe-to-pipe splice(): the caller holds both locks,
+    /// so bytes move straight from this ring into `dst` with no intermediate
+    /// buffer. Returns the number of bytes copied, or `dst.write()`'s error
+    /// if nothing could be copied.
+    pub fn copy_to(&self, dst: &mut PipeBuffer, max: usize) -> Result<usize, i32> {
+        let count = max.min(self.len);
+        let first_len = count.min(PIPE_BUF_SIZE - self.read_pos);
+        let first = &self.buffer[self.read_pos..self.read_pos + first_len];
+        let second = &self.buffer[..count - first_len];
+
+        let written = dst.write(first)?;
+        if written < first.len() || second.is_empty() {
+            return Ok(written);
+        }
+        match dst.write(second) {
+            Ok(n) => Ok(written + n),
+            Err(_) => Ok(written),
+        }
+    }
+
+    /// Discard up to `count` buffered bytes, as if they had been read
+    pub fn consume(&mut self, count: usize) {
+        let count = count.min(self.len);
+        self.read_pos = (self.read_pos + count) % PIPE_BUF_SIZE;
+        self.len -= count;
+    }
+
     /// Write to the pipe buffer
     ///
     /// Returns:
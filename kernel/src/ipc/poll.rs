@@ -159,6 +159,9 @@ pub fn poll_fd(fd_entry: &FileDescriptor, events: i16) -> i16 {
                     }
                     // TODO: Check input buffer for POLLIN when implemented
                 }
+                DeviceType::Dsp => {
+                    // Opens of /dev/dsp are Dsp streams, never bare devices
+                }
             }
         }
         FdKind::DevfsDirectory { .. } => {
@@ -291,7 +294,9 @@ pub fn poll_fd(fd_entry: &FileDescriptor, events: i16) -> i16 {
                 }
             }
         }
-        FdKind::ProcfsFile { content, position } => {
+        FdKind::ProcfsFile {
+            content, position, ..
+        } => {
             // Procfs file is readable if there's remaining content
             if (events & events::POLLIN) != 0 && *position < content.len() {
                 revents |= events::POLLIN;
@@ -306,6 +311,17 @@ pub fn poll_fd(fd_entry: &FileDescriptor, events: i16) -> i16 {
         FdKind::Epoll(_) => {
             // Epoll fds are not directly pollable
         }
+        FdKind::Dsp(stream) => {
+            let stream = stream.lock();
+            // Readable once a whole fragment has been captured
+            if (events & events::POLLIN) != 0 && stream.has_captured_fragment() {
+                revents |= events::POLLIN;
+            }
+            // Writable once a whole fragment of buffer space is free
+            if (events & events::POLLOUT) != 0 && stream.has_fragment_space() {
+                revents |= events::POLLOUT;
+            }
+        }
     }
 
     revents
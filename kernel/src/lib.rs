@@ -15,6 +15,7 @@ pub mod serial_aarch64;
 #[cfg(target_arch = "aarch64")]
 pub use serial_aarch64 as serial;
 pub mod arch_impl;
+pub mod audio;
 pub mod drivers;
 #[cfg(target_arch = "x86_64")]
 pub mod gdt;
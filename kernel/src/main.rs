@@ -1420,6 +1420,38 @@ fn kernel_main_continue() -> ! {
         log::info!("=== SIGNAL TEST: sigaltstack() syscall functionality ===");
         test_exec::test_sigaltstack();
 
+        // Test SA_SIGINFO siginfo and ucontext
+        log::info!("=== SIGNAL TEST: SA_SIGINFO siginfo and ucontext ===");
+        test_exec::test_siginfo();
+
+        // Test queued real-time signals, sigtimedwait and tgkill
+        log::info!("=== SIGNAL TEST: queued real-time signals ===");
+        test_exec::test_rt_signal();
+
+        log::info!("=== SIGNAL TEST: core dumps ===");
+        test_exec::test_coredump();
+
+        log::info!("=== SIGNAL TEST: ptrace ===");
+        test_exec::test_ptrace();
+
+        log::info!("=== PROCESS TEST: resource usage and waitid ===");
+        test_exec::test_rusage();
+
+        log::info!("=== PROCESS TEST: scheduling priorities ===");
+        test_exec::test_priority();
+
+        log::info!("=== PROCESS TEST: CPU affinity ===");
+        test_exec::test_affinity();
+
+        log::info!("=== PROCESS TEST: /proc/<pid> hierarchy ===");
+        test_exec::test_proc_pid();
+
+        log::info!("=== AUDIO TEST: /dev/dsp mixer ===");
+        test_exec::test_dsp_mixer();
+
+        log::info!("=== AUDIO TEST: /dev/dsp capture ===");
+        test_exec::test_dsp_capture();
+
         // Test dup() syscall
         log::info!("=== IPC TEST: dup() syscall functionality ===");
         test_exec::test_dup();
@@ -1552,6 +1584,22 @@ fn kernel_main_continue() -> ! {
         log::info!("=== FS TEST: block allocation regression test ===");
         test_exec::test_fs_block_alloc();
 
+        // Test ftruncate/truncate, fallocate, and fsync
+        log::info!("=== FS TEST: truncate, fallocate, fsync ===");
+        test_exec::test_fs_truncate();
+
+        // Test statfs/fstatfs
+        log::info!("=== FS TEST: statfs, fstatfs ===");
+        test_exec::test_fs_statfs();
+
+        // Test flock and fcntl record locks
+        log::info!("=== FS TEST: flock, fcntl locks ===");
+        test_exec::test_flock();
+
+        // Test sendfile, splice, and tee
+        log::info!("=== FS TEST: sendfile, splice, tee ===");
+        test_exec::test_splice();
+
         // Coreutil tests
         log::info!("=== COREUTIL TEST: true (exit code 0) ===");
         test_exec::test_true_coreutil();
@@ -10,6 +10,7 @@ pub mod layout;
 pub mod paging;
 pub mod per_cpu_stack;
 pub mod process_memory;
+pub mod regions;
 pub mod slab;
 pub mod stack;
 pub mod tlb;
@@ -0,0 +1,92 @@
+//! User address space enumeration
+//!
+//! Lists the regions of a process's address space with ELF segment
+//! permissions, as written to core files and shown in /proc/<pid>/maps: every
+//! VMA, plus the runs of mapped user pages no VMA covers (program image, heap
+//! and stack).
+
+use crate::memory::process_memory::ProcessPageTable;
+use crate::memory::vma::{Protection, Vma};
+use alloc::vec::Vec;
+
+#[cfg(not(target_arch = "x86_64"))]
+use crate::memory::arch_stub::{PageTableFlags, VirtAddr};
+#[cfg(target_arch = "x86_64")]
+use x86_64::{structures::paging::PageTableFlags, VirtAddr};
+
+const PAGE_SIZE: u64 = 4096;
+
+/// Segment permission flags (Elf64_Phdr p_flags)
+pub const PF_X: u32 = 1;
+pub const PF_W: u32 = 2;
+pub const PF_R: u32 = 4;
+
+/// The memory regions of an address space as (start, end, PF_* flags): every
+/// VMA, plus the runs of mapped pages no VMA covers, in address order
+pub fn memory_regions(page_table: &ProcessPageTable, vmas: &[Vma]) -> Vec<(u64, u64, u32)> {
+    regions_of(&user_pages(page_table), vmas)
+}
+
+/// Mapped user pages as (virt, phys, flags), in address order
+pub fn user_pages(page_table: &ProcessPageTable) -> Vec<(u64, u64, PageTableFlags)> {
+    let mut pages: Vec<(u64, u64, PageTableFlags)> = Vec::new();
+    let _ = page_table.walk_mapped_pages(|virt, phys, flags| {
+        if flags.contains(PageTableFlags::USER_ACCESSIBLE)
+            && !flags.contains(PageTableFlags::HUGE_PAGE)
+        {
+            pages.push((virt.as_u64(), phys.as_u64(), flags));
+        }
+    });
+    pages.sort_unstable_by_key(|&(virt, _, _)| virt);
+    pages
+}
+
+/// Regions: every VMA, plus runs of mapped pages no VMA covers
+pub fn regions_of(pages: &[(u64, u64, PageTableFlags)], vmas: &[Vma]) -> Vec<(u64, u64, u32)> {
+    let mut regions: Vec<(u64, u64, u32)> = vmas
+        .iter()
+        .map(|vma| (vma.start.as_u64(), vma.end.as_u64(), vma_flags(vma.prot)))
+        .collect();
+    let in_vma = |addr: u64| vmas.iter().any(|vma| vma.contains(VirtAddr::new(addr)));
+    let mut run: Option<(u64, u64, u32)> = None;
+    for &(virt, _, flags) in pages.iter().filter(|&&(virt, _, _)| !in_vma(virt)) {
+        let flags = page_flags(flags);
+        run = match run {
+            Some((start, end, run_flags)) if end == virt && run_flags == flags => {
+                Some((start, end + PAGE_SIZE, flags))
+            }
+            previous => {
+                regions.extend(previous);
+                Some((virt, virt + PAGE_SIZE, flags))
+            }
+        };
+    }
+    regions.extend(run);
+    regions.sort_unstable_by_key(|&(start, _, _)| start);
+    regions
+}
+
+fn vma_flags(prot: Protection) -> u32 {
+    let mut flags = 0;
+    if prot.contains(Protection::READ) {
+        flags |= PF_R;
+    }
+    if prot.contains(Protection::WRITE) {
+        flags |= PF_W;
+    }
+    if prot.contains(Protection::EXEC) {
+        flags |= PF_X;
+    }
+    flags
+}
+
+fn page_flags(flags: PageTableFlags) -> u32 {
+    let mut pf = PF_R;
+    if flags.contains(PageTableFlags::WRITABLE) {
+        pf |= PF_W;
+    }
+    if !flags.contains(PageTableFlags::NO_EXECUTE) {
+        pf |= PF_X;
+    }
+    pf
+}
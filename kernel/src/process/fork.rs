@@ -467,6 +467,10 @@ pub fn copy_stack_contents(
 ///
 /// - **umask**: Not yet tracked per-process (uses global default). TODO when implemented.
 ///
+/// - **Resource limits and program image**: RLIMIT_CORE, the saved auxiliary vector
+///   and the exe path, argv and environ strings, which still describe the program
+///   image the child runs until it execs.
+///
 /// - **Current working directory**: Inherited from parent in fork_internal().
 ///
 /// Note: Memory (pages, heap bounds) and stack are copied separately by copy_user_pages()
@@ -498,6 +502,13 @@ pub fn copy_process_state(
     child_process.egid = parent_process.egid;
     child_process.umask = parent_process.umask;
 
+    // 5. Copy resource limits and the description of the (shared) program image
+    child_process.core_limit = parent_process.core_limit;
+    child_process.auxv = parent_process.auxv.clone();
+    child_process.exe = parent_process.exe.clone();
+    child_process.cmdline = parent_process.cmdline.clone();
+    child_process.environ = parent_process.environ.clone();
+
     Ok(())
 }
 
@@ -0,0 +1,291 @@
+//! Job control: stopping and continuing processes
+//!
+//! A stop signal (SIGSTOP, SIGTSTP, SIGTTIN, SIGTTOU) acted on with its
+//! default action moves the process to `ProcessState::Stopped`. Each of its
+//! threads then leaves the scheduler the next time it would return to
+//! userspace:
+//! - On the return from an interrupt or exception the thread is parked as
+//!   Blocked with its user registers saved, and recorded here.
+//! - On the ARM64 syscall return path it sleeps on `CONT_QUEUE` inside the
+//!   syscall.
+//!
+//! SIGCONT continues the process the moment it is sent, whatever its
+//! disposition: the parked threads are unblocked and `CONT_QUEUE` is woken
+//! once the sender has dropped the process manager lock.
+//! Sending a stop signal discards a pending SIGCONT and vice versa.
+//!
+//! Each stop and continue is reported to the parent once through waitpid
+//! (WUNTRACED, WCONTINUED), and with SIGCHLD (CLD_STOPPED, CLD_CONTINUED)
+//! unless the parent set SA_NOCLDSTOP.
+//!
+//! Everything here except `finish_continue` and `notify_parent_deferred`
+//! runs under the process manager lock.
+
+use super::{Process, ProcessId, ProcessManager, ProcessState};
+use crate::signal::constants::*;
+use crate::signal::types::SigInfo;
+use crate::task::waitqueue::WaitQueueHead;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Signals whose default action stops the process
+const STOP_SIGNALS: u64 =
+    sig_mask(SIGSTOP) | sig_mask(SIGTSTP) | sig_mask(SIGTTIN) | sig_mask(SIGTTOU);
+
+/// Bumped every time a stopped process is continued
+static CONTINUES: AtomicU64 = AtomicU64::new(0);
+
+/// Threads that stopped inside a syscall sleep here until continued
+static CONT_QUEUE: WaitQueueHead = WaitQueueHead::new();
+
+/// A change of state the parent has not collected yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitReport {
+    /// Stopped by this signal
+    Stopped(u32),
+    /// Continued by SIGCONT
+    Continued,
+}
+
+impl WaitReport {
+    /// waitpid status: WIFSTOPPED with the signal, or WIFCONTINUED
+    pub fn status(self) -> i32 {
+        match self {
+            WaitReport::Stopped(sig) => ((sig as i32) << 8) | 0x7f,
+            WaitReport::Continued => 0xffff,
+        }
+    }
+}
+
+/// Job-control state of a process
+#[derive(Debug, Default)]
+pub struct JobState {
+    /// Stop or continue not yet collected by the parent's waitpid
+    report: Option<WaitReport>,
+    /// Threads parked on the return to userspace while stopped
+    parked: Vec<u64>,
+}
+
+/// SIGCHLD owed to a parent, sent once the process manager lock is dropped
+#[derive(Debug)]
+pub struct JobNotification {
+    pub parent_pid: ProcessId,
+    pub info: SigInfo,
+}
+
+/// Whether the process is stopped
+#[inline]
+pub fn is_stopped(process: &Process) -> bool {
+    matches!(process.state, ProcessState::Stopped(_))
+}
+
+/// Record a change for the parent and build its SIGCHLD
+fn report(process: &mut Process, report: WaitReport) -> Option<JobNotification> {
+    process.job.report = Some(report);
+    let (code, sig) = match report {
+        WaitReport::Stopped(sig) => (CLD_STOPPED, sig),
+        WaitReport::Continued => (CLD_CONTINUED, SIGCONT),
+    };
+    Some(JobNotification {
+        parent_pid: process.parent?,
+        info: SigInfo::child_job(process.id.as_u64(), process.uid, code, sig),
+    })
+}
+
+/// Stop a process for `sig`
+///
+/// Returns the SIGCHLD for the parent; the caller sends it with
+/// [`notify_parent_deferred`] after releasing the process manager lock.
+pub fn stop(process: &mut Process, sig: u32) -> Option<JobNotification> {
+    if process.is_terminated() || is_stopped(process) {
+        return None;
+    }
+    process.state = ProcessState::Stopped(sig);
+    report(process, WaitReport::Stopped(sig))
+}
+
+/// Record a thread the caller is parking until the process is continued
+pub fn park(process: &mut Process, thread_id: u64) {
+    if !process.job.parked.contains(&thread_id) {
+        process.job.parked.push(thread_id);
+    }
+}
+
+/// A continue staged under the process manager lock
+///
+/// The parked threads are woken and the parent notified by
+/// [`finish_continue`] once the lock is dropped.
+#[derive(Debug)]
+#[must_use = "a continued process stays parked until finish_continue runs"]
+pub struct Continued {
+    parked: Vec<u64>,
+    notification: Option<JobNotification>,
+}
+
+/// Continue a stopped process
+pub fn cont(process: &mut Process) -> Option<Continued> {
+    if !is_stopped(process) {
+        return None;
+    }
+    process.state = ProcessState::Ready;
+    Some(Continued {
+        parked: core::mem::take(&mut process.job.parked),
+        notification: report(process, WaitReport::Continued),
+    })
+}
+
+/// Wake the threads of a continued process and send its parent SIGCHLD
+///
+/// Must be called without the process manager lock held.
+pub fn finish_continue(continued: Continued) {
+    crate::task::scheduler::with_scheduler(|sched| {
+        for thread_id in continued.parked {
+            sched.unblock(thread_id);
+        }
+    });
+    CONTINUES.fetch_add(1, Ordering::Release);
+    CONT_QUEUE.wake_up();
+    if let Some(notification) = continued.notification {
+        notify_parent_deferred(&notification);
+    }
+}
+
+/// Apply the rules for sending `sig`: a stop signal discards a pending
+/// SIGCONT, and SIGCONT discards pending stop signals
+pub fn on_send(process: &mut Process, sig: u32) {
+    if sig == SIGCONT {
+        for stop_sig in [SIGSTOP, SIGTSTP, SIGTTIN, SIGTTOU] {
+            process.signals.clear_pending(stop_sig);
+        }
+    } else if sig_mask(sig) & STOP_SIGNALS != 0 {
+        process.signals.clear_pending(SIGCONT);
+    }
+}
+
+/// Queue threads stopped inside a syscall wait on
+pub(crate) fn cont_queue() -> &'static WaitQueueHead {
+    &CONT_QUEUE
+}
+
+/// Current continue generation, for waits on `cont_queue`
+pub(crate) fn continue_generation() -> u64 {
+    CONTINUES.load(Ordering::Acquire)
+}
+
+/// Take a report of a child of `parent` matching `pid` (-1 for any)
+///
+/// Stops are taken only with `untraced`, continues only with `continued`.
+/// Returns the child's pid and its waitpid status; each report is taken once.
+pub fn take_wait_report(
+    manager: &mut ProcessManager,
+    parent: ProcessId,
+    pid: i64,
+    untraced: bool,
+    continued: bool,
+) -> Option<(ProcessId, i32)> {
+    let wanted = |report: WaitReport| match report {
+        WaitReport::Stopped(_) => untraced,
+        WaitReport::Continued => continued,
+    };
+    let child = manager
+        .get_process(parent)?
+        .children
+        .iter()
+        .copied()
+        .find(|&child| {
+            (pid == -1 || child.as_u64() == pid as u64)
+                && manager
+                    .get_process(child)
+                    .and_then(|process| process.job.report)
+                    .is_some_and(wanted)
+        })?;
+    let report = manager.get_process_mut(child)?.job.report.take()?;
+    Some((child, report.status()))
+}
+
+/// The uncollected stop or continue of `process`, for waitid()
+///
+/// With `consume` the report is taken as waitpid takes it; WNOWAIT leaves it
+/// for the next wait.
+pub fn child_report(process: &mut Process, consume: bool) -> Option<WaitReport> {
+    if consume {
+        process.job.report.take()
+    } else {
+        process.job.report
+    }
+}
+
+/// Whether process group `pgid` is orphaned: no member has a parent in
+/// another group of the same session
+pub fn is_orphaned_pgrp(manager: &ProcessManager, pgid: ProcessId) -> bool {
+    !manager.iter_processes().any(|(_, process)| {
+        process.pgid == pgid
+            && !process.is_terminated()
+            && process
+                .parent
+                .and_then(|parent| manager.get_process(parent))
+                .is_some_and(|parent| parent.pgid != pgid && parent.sid == process.sid)
+    })
+}
+
+/// What a process touching a terminal must do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TtyAccess {
+    /// Go ahead
+    Allowed,
+    /// Send the signal to the caller's process group and fail with EINTR
+    Signal(ProcessId),
+    /// Fail with EIO
+    Denied,
+}
+
+/// Check a read (`SIGTTIN`) or a write or attribute change (`SIGTTOU`) of
+/// a terminal by `process`
+///
+/// `session` is the session the terminal controls, `foreground` its
+/// foreground process group. Only a process of that session outside the
+/// foreground group is held back. A blocked or ignored SIGTTOU lets the
+/// access through; a blocked or ignored SIGTTIN, or an orphaned group that
+/// nobody could continue, fails it with EIO.
+pub fn tty_access(
+    manager: &ProcessManager,
+    process: &Process,
+    session: Option<u64>,
+    foreground: Option<u64>,
+    sig: u32,
+) -> TtyAccess {
+    let Some(foreground) = foreground else {
+        return TtyAccess::Allowed;
+    };
+    if session != Some(process.sid.as_u64()) || process.pgid.as_u64() == foreground {
+        return TtyAccess::Allowed;
+    }
+    let refused =
+        process.signals.is_blocked(sig) || process.signals.get_handler(sig).handler == SIG_IGN;
+    match sig {
+        SIGTTOU if refused => TtyAccess::Allowed,
+        _ if refused || is_orphaned_pgrp(manager, process.pgid) => TtyAccess::Denied,
+        _ => TtyAccess::Signal(process.pgid),
+    }
+}
+
+/// Send a stop or continue SIGCHLD to the parent and wake it for waitpid
+///
+/// Must be called without the process manager lock held.
+pub fn notify_parent_deferred(notification: &JobNotification) {
+    let parent_tid = crate::process::with_process_manager(|manager| {
+        let parent = manager.get_process_mut(notification.parent_pid)?;
+        if parent.signals.get_handler(SIGCHLD).flags & SA_NOCLDSTOP == 0 {
+            parent.signals.queue_signal(notification.info);
+        }
+        parent.main_thread.as_ref().map(|thread| thread.id)
+    })
+    .flatten();
+
+    if let Some(parent_tid) = parent_tid {
+        crate::task::scheduler::with_scheduler(|sched| {
+            sched.unblock_for_child_exit(parent_tid);
+            sched.unblock_for_signal(parent_tid);
+        });
+    }
+}
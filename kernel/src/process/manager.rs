@@ -898,7 +898,7 @@ impl ProcessManager {
             b"USER=root\0",
             b"SHELL=/bin/bsh\0",
         ];
-        let initial_sp = if let Some(ref page_table) = process.page_table {
+        let (initial_sp, auxv) = if let Some(ref page_table) = process.page_table {
             self.setup_argv_on_stack(
                 page_table,
                 user_stack_top,
@@ -917,6 +917,9 @@ impl ProcessManager {
             "manager.create_process_with_argv [ARM64]: argc/argv set up on stack, SP={:#x}",
             initial_sp
         );
+        process.auxv = auxv;
+        let program = process.name.clone();
+        process.set_program(&program, argv, &default_env);
 
         // Create the main thread with the adjusted stack pointer (pointing to argc)
         let thread = self.create_main_thread_with_sp(
@@ -1101,6 +1104,8 @@ impl ProcessManager {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: Some(process.id.as_u64()),
             cached_ttbr0: 0,
         };
@@ -1177,6 +1182,8 @@ impl ProcessManager {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: Some(process.id.as_u64()),
             cached_ttbr0: 0,
         };
@@ -1259,6 +1266,8 @@ impl ProcessManager {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: Some(process.id.as_u64()),
             cached_ttbr0: 0,
         };
@@ -1431,20 +1440,30 @@ impl ProcessManager {
                 exiting.children.clear();
             }
         }
+        crate::process::ptrace::release_tracees(self, pid);
 
         // Class-A SIGCHLD obligation: perform the PM-owned effect and mark it
         // completed in this same acquisition. Repeat exit paths do neither.
-        let sigchld_pending = self.processes.get(&pid).is_some_and(|process| {
-            matches!(
-                process.exit_notifications.sigchld,
-                super::process::ExitObligationState::Pending
-            )
-        });
-        if sigchld_pending {
+        let sigchld_info = self
+            .processes
+            .get(&pid)
+            .filter(|process| {
+                matches!(
+                    process.exit_notifications.sigchld,
+                    super::process::ExitObligationState::Pending
+                )
+            })
+            .map(|process| {
+                crate::signal::types::SigInfo::child(
+                    pid.as_u64(),
+                    process.uid,
+                    process.exit_code.unwrap_or(exit_code),
+                )
+            });
+        if let Some(sigchld_info) = sigchld_info {
             if let Some(parent_pid) = parent_pid {
                 if let Some(parent_process) = self.processes.get_mut(&parent_pid) {
-                    use crate::signal::constants::SIGCHLD;
-                    parent_process.signals.set_pending(SIGCHLD);
+                    parent_process.signals.queue_signal(sigchld_info);
                 }
             }
             if let Some(process) = self.processes.get_mut(&pid) {
@@ -2600,6 +2619,13 @@ impl ProcessManager {
                 wake_time_ns: None,
                 run_start_ticks: 0,
                 cpu_ticks_total: 0,
+                usage: Default::default(),
+                // The scheduler's copy of the forking thread is authoritative;
+                // the process table's may predate a setpriority()
+                sched: crate::per_cpu::current_thread()
+                    .filter(|current| current.id == parent_thread.id)
+                    .map_or(parent_thread.sched, |current| current.sched)
+                    .inherited(),
                 owner_pid: Some(child_pid.as_u64()),
                 cached_ttbr0: parent_thread.cached_ttbr0,
             };
@@ -2856,9 +2882,13 @@ impl ProcessManager {
         // Reset signal handlers per POSIX: user-defined handlers become SIG_DFL,
         // SIG_IGN handlers are preserved
         process.signals.exec_reset();
+        crate::process::ptrace::exec_trap(process);
         // Reset mmap state for the new address space
         process.mmap_hint = crate::memory::vma::MMAP_REGION_END;
         process.vmas.clear();
+        process.auxv.clear();
+        let program = process.name.clone();
+        process.set_program(&program, &[program.as_bytes()], &[]);
         // Close FD_CLOEXEC file descriptors per POSIX
         process.fd_table.close_cloexec();
         log::debug!(
@@ -3148,7 +3178,7 @@ impl ProcessManager {
             b"USER=root\0",
             b"SHELL=/bin/bsh\0",
         ];
-        let initial_rsp = self.setup_argv_on_stack(
+        let (initial_rsp, auxv) = self.setup_argv_on_stack(
             &new_page_table,
             USER_STACK_TOP,
             argv,
@@ -3193,8 +3223,12 @@ impl ProcessManager {
 
         // Reset signal handlers and mmap state per POSIX
         process.signals.exec_reset();
+        crate::process::ptrace::exec_trap(process);
         process.mmap_hint = crate::memory::vma::MMAP_REGION_END;
         process.vmas.clear();
+        process.auxv = auxv;
+        let program = process.name.clone();
+        process.set_program(&program, argv, &default_env);
 
         // Close FD_CLOEXEC file descriptors per POSIX
         process.fd_table.close_cloexec();
@@ -3435,7 +3469,7 @@ impl ProcessManager {
             b"USER=root\0",
             b"SHELL=/bin/bsh\0",
         ];
-        let initial_rsp = self.setup_argv_on_stack(
+        let (initial_rsp, auxv) = self.setup_argv_on_stack(
             &new_page_table,
             user_stack_top,
             argv,
@@ -3479,8 +3513,12 @@ impl ProcessManager {
         process.heap_end = heap_base;
 
         process.signals.exec_reset();
+        crate::process::ptrace::exec_trap(process);
         process.mmap_hint = crate::memory::vma::MMAP_REGION_END;
         process.vmas.clear();
+        process.auxv = auxv;
+        let program = process.name.clone();
+        process.set_program(&program, argv, &default_env);
 
         // Close FD_CLOEXEC file descriptors per POSIX
         process.fd_table.close_cloexec();
@@ -3792,8 +3830,12 @@ impl ProcessManager {
 
         // Reset signal handlers and mmap state per POSIX
         process.signals.exec_reset();
+        crate::process::ptrace::exec_trap(process);
         process.mmap_hint = crate::memory::vma::MMAP_REGION_END;
         process.vmas.clear();
+        process.auxv.clear();
+        let program = process.name.clone();
+        process.set_program(&program, &[program.as_bytes()], &[]);
         // Close FD_CLOEXEC file descriptors per POSIX
         process.fd_table.close_cloexec();
         log::debug!(
@@ -3970,7 +4012,7 @@ impl ProcessManager {
     /// - phentsize: Size of each program header entry
     /// - entry_point: Program entry point address
     ///
-    /// Returns: The initial RSP value (pointing to argc)
+    /// Returns: The initial RSP value (pointing to argc) and the auxv entries written
     #[allow(dead_code)]
     fn setup_argv_on_stack(
         &self,
@@ -3982,7 +4024,7 @@ impl ProcessManager {
         phnum: u16,
         phentsize: u16,
         entry_point: u64,
-    ) -> Result<u64, &'static str> {
+    ) -> Result<(u64, Vec<(u64, u64)>), &'static str> {
         let argc = argv.len();
 
         // We need to access the stack memory directly via physical addresses
@@ -4091,9 +4133,16 @@ impl ProcessManager {
         // --- Phase 2: Build the pointer/value section below the strings ---
 
         // Auxiliary vector entries (each is two u64 values: type, value)
-        // AT_ENTRY, AT_PHDR, AT_PHNUM, AT_PHENT, AT_PAGESZ, AT_RANDOM, AT_NULL = 7 entries = 14 u64s
-        let auxv_count = 7;
-        let auxv_space = auxv_count * 2 * 8; // 7 entries * 2 u64s * 8 bytes
+        let auxv: Vec<(u64, u64)> = alloc::vec![
+            (9, entry_point),      // AT_ENTRY - program entry point
+            (3, phdr_vaddr),       // AT_PHDR - address of program headers
+            (5, phnum as u64),     // AT_PHNUM - number of program headers
+            (4, phentsize as u64), // AT_PHENT - size of each program header entry
+            (6, 4096),             // AT_PAGESZ - page size
+            (25, random_addr),     // AT_RANDOM - pointer to 16 random bytes
+            (0, 0),                // AT_NULL - terminator
+        ];
+        let auxv_space = auxv.len() * 2 * 8;
 
         // envp: envp.len() pointers + NULL terminator
         let envp_space = (envp.len() + 1) * 8;
@@ -4141,46 +4190,12 @@ impl ProcessManager {
         write_pos += 8;
 
         // 6. Write auxiliary vector entries
-        // AT_ENTRY (9) - program entry point
-        self.write_u64_to_stack(page_table, write_pos, 9)?; // AT_ENTRY
-        write_pos += 8;
-        self.write_u64_to_stack(page_table, write_pos, entry_point)?;
-        write_pos += 8;
-
-        // AT_PHDR (3) - address of program headers
-        self.write_u64_to_stack(page_table, write_pos, 3)?; // AT_PHDR
-        write_pos += 8;
-        self.write_u64_to_stack(page_table, write_pos, phdr_vaddr)?;
-        write_pos += 8;
-
-        // AT_PHNUM (5) - number of program headers
-        self.write_u64_to_stack(page_table, write_pos, 5)?; // AT_PHNUM
-        write_pos += 8;
-        self.write_u64_to_stack(page_table, write_pos, phnum as u64)?;
-        write_pos += 8;
-
-        // AT_PHENT (4) - size of each program header entry
-        self.write_u64_to_stack(page_table, write_pos, 4)?; // AT_PHENT
-        write_pos += 8;
-        self.write_u64_to_stack(page_table, write_pos, phentsize as u64)?;
-        write_pos += 8;
-
-        // AT_PAGESZ (6) - page size
-        self.write_u64_to_stack(page_table, write_pos, 6)?; // AT_PAGESZ
-        write_pos += 8;
-        self.write_u64_to_stack(page_table, write_pos, 4096)?;
-        write_pos += 8;
-
-        // AT_RANDOM (25) - pointer to 16 random bytes
-        self.write_u64_to_stack(page_table, write_pos, 25)?; // AT_RANDOM
-        write_pos += 8;
-        self.write_u64_to_stack(page_table, write_pos, random_addr)?;
-        write_pos += 8;
-
-        // AT_NULL (0) - terminator
-        self.write_u64_to_stack(page_table, write_pos, 0)?; // AT_NULL
-        write_pos += 8;
-        self.write_u64_to_stack(page_table, write_pos, 0)?;
+        for &(key, value) in auxv.iter() {
+            self.write_u64_to_stack(page_table, write_pos, key)?;
+            write_pos += 8;
+            self.write_u64_to_stack(page_table, write_pos, value)?;
+            write_pos += 8;
+        }
 
         log::debug!(
             "setup_argv_on_stack: argc={}, RSP={:#x}, argv[0] at {:#x}, auxv with phdr={:#x} phnum={} entry={:#x}",
@@ -4192,7 +4207,7 @@ impl ProcessManager {
             entry_point,
         );
 
-        Ok(rsp)
+        Ok((rsp, auxv))
     }
 
     /// Write a single byte to the stack via physical address translation
@@ -8,12 +8,15 @@ use spin::Mutex;
 
 pub mod creation;
 pub mod fork;
+pub mod job_control;
 pub mod manager;
 pub mod process;
+pub mod ptrace;
+pub mod rusage;
 
 pub use manager::ProcessManager;
 pub use manager::{InitDesignationTicket, InitPublication, FIRST_ORDINARY_PID, RESERVED_INIT_PID};
-pub use process::{Process, ProcessId, ProcessState};
+pub use process::{Process, ProcessId, ProcessState, Rlimit, RLIM_INFINITY};
 
 /// Result of entering process teardown through the receipt-custody wrapper.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -359,6 +362,7 @@ pub fn try_dump_state() -> Option<ProcessDumpInfo> {
                 blocked_count += 1;
                 "blocked"
             }
+            ProcessState::Stopped(_) => "stopped",
             ProcessState::Terminated(_) => "terminated",
         };
         entries.push(ProcessDumpEntry {
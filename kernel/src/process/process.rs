@@ -47,6 +47,17 @@ impl ProcessId {
     }
 }
 
+/// Value of an unlimited resource limit (RLIM_INFINITY)
+pub const RLIM_INFINITY: u64 = u64::MAX;
+
+/// Soft and hard limit for one resource (matches Linux struct rlimit)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rlimit {
+    pub rlim_cur: u64,
+    pub rlim_max: u64,
+}
+
 /// Process state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProcessState {
@@ -58,6 +69,8 @@ pub enum ProcessState {
     Running,
     /// Process is blocked waiting for something
     Blocked,
+    /// Process is stopped by a job-control signal until SIGCONT
+    Stopped(u32), // stop signal
     /// Process has terminated
     Terminated(i32), // exit code
 }
@@ -282,6 +295,36 @@ pub struct Process {
 
     /// Accumulated CPU ticks for this process (for btop display)
     pub cpu_ticks: u64,
+
+    /// Monotonic time the process was created, in nanoseconds
+    pub start_time_ns: u64,
+
+    /// RLIMIT_CORE: largest core file written when a signal dumps core
+    pub core_limit: Rlimit,
+
+    /// Auxiliary vector handed to the current program at exec, as (type, value)
+    /// pairs ending with AT_NULL. Kept for the NT_AUXV note of core dumps.
+    pub auxv: Vec<(u64, u64)>,
+
+    /// Path of the current program's executable (target of /proc/<pid>/exe)
+    pub exe: String,
+
+    /// The current program's argv strings, each NUL-terminated
+    /// (/proc/<pid>/cmdline)
+    pub cmdline: Vec<u8>,
+
+    /// The current program's initial environment strings, each NUL-terminated
+    /// (/proc/<pid>/environ)
+    pub environ: Vec<u8>,
+
+    /// Tracing state while another process traces this one with ptrace
+    pub ptrace: Option<crate::process::ptrace::PtraceState>,
+
+    /// Job-control stop/continue state
+    pub job: crate::process::job_control::JobState,
+
+    /// Resource usage totals for getrusage() and wait4()
+    pub usage: crate::process::rusage::ProcessUsage,
 }
 
 /// Memory usage tracking
@@ -299,6 +342,9 @@ pub struct MemoryUsage {
 impl Process {
     /// Create a new process
     pub fn new(id: ProcessId, name: String, entry_point: VirtAddr) -> Self {
+        // Until an exec records its argv, the name stands in for argv[0]
+        let exe = program_path(&name);
+        let cmdline = nul_joined(&[name.as_bytes()]);
         Process {
             id,
             // By default, a process's pgid equals its pid (process is its own group leader)
@@ -343,9 +389,33 @@ impl Process {
             fb_mmap: None,
             has_display_ownership: false,
             cpu_ticks: 0,
+            start_time_ns: {
+                let (secs, nanos) = crate::time::get_monotonic_time_ns();
+                secs * 1_000_000_000 + nanos
+            },
+            // No core dumps unless the process raises its soft limit
+            core_limit: Rlimit {
+                rlim_cur: 0,
+                rlim_max: RLIM_INFINITY,
+            },
+            auxv: Vec::new(),
+            exe,
+            cmdline,
+            environ: Vec::new(),
+            ptrace: None,
+            job: crate::process::job_control::JobState::default(),
+            usage: crate::process::rusage::ProcessUsage::default(),
         }
     }
 
+    /// Record the program an exec or spawn loaded, with the argv and envp
+    /// strings it was given, for /proc/<pid>/exe, cmdline and environ.
+    pub fn set_program(&mut self, path: &str, argv: &[&[u8]], envp: &[&[u8]]) {
+        self.exe = program_path(path);
+        self.cmdline = nul_joined(argv);
+        self.environ = nul_joined(envp);
+    }
+
     /// Set the main thread for this process
     pub fn set_main_thread(&mut self, thread: Thread) {
         self.main_thread = Some(thread);
@@ -366,7 +436,10 @@ impl Process {
     pub fn admits_clone(&self) -> bool {
         match self.state {
             ProcessState::Creating => false,
-            ProcessState::Ready | ProcessState::Running | ProcessState::Blocked => true,
+            ProcessState::Ready
+            | ProcessState::Running
+            | ProcessState::Blocked
+            | ProcessState::Stopped(_) => true,
             ProcessState::Terminated(_) => false,
         }
     }
@@ -380,6 +453,7 @@ impl Process {
             ProcessState::Ready => false,
             ProcessState::Running => false,
             ProcessState::Blocked => false,
+            ProcessState::Stopped(_) => false,
             ProcessState::Terminated(_) => false,
         }
     }
@@ -440,6 +514,7 @@ impl Process {
         // is safe under PROCESS_MANAGER: record_exit allocates/logs nothing and
         // takes only its leaf spin mutex; that mutex never nests PROCESS_MANAGER.
         crate::task::exit_tally::record_exit(&self.name, exit_code);
+        crate::process::ptrace::on_terminate(self);
 
         // CRITICAL FIX: Mark the main thread as terminated so the scheduler
         // doesn't keep putting it back in the ready queue. The scheduler checks
@@ -469,6 +544,7 @@ impl Process {
         // is safe under PROCESS_MANAGER: record_exit allocates/logs nothing and
         // takes only its leaf spin mutex; that mutex never nests PROCESS_MANAGER.
         crate::task::exit_tally::record_exit(&self.name, exit_code);
+        crate::process::ptrace::on_terminate(self);
         if let Some(ref mut thread) = self.main_thread {
             thread.set_terminated();
         }
@@ -497,6 +573,7 @@ impl Process {
     fn close_all_fds(&mut self) {
         use crate::ipc::FdKind;
 
+        let tgid = self.thread_group_id.unwrap_or(self.id.as_u64());
         for fd in 0..crate::ipc::MAX_FDS {
             if let Ok(fd_entry) = self.fd_table.close(fd as i32) {
                 if crate::process::process_manager_held_on_current_cpu() {
@@ -541,7 +618,14 @@ impl Process {
                         crate::ipc::fifo::close_fifo_write(&path);
                         buffer.lock().close_write();
                     }
-                    _ => {} // StdIo, RegularFile, Directory, Device, etc. — no action needed
+                    FdKind::RegularFile(file) => {
+                        let key = {
+                            let file = file.lock();
+                            (file.mount_id, file.inode_num)
+                        };
+                        crate::fs::lock::release_process_file(tgid, key);
+                    }
+                    _ => {} // StdIo, Directory, Device, etc. — no action needed
                 }
             }
         }
@@ -555,6 +639,7 @@ impl Process {
     fn close_all_fds(&mut self) {
         use crate::ipc::FdKind;
 
+        let tgid = self.thread_group_id.unwrap_or(self.id.as_u64());
         for fd in 0..crate::ipc::MAX_FDS {
             if let Ok(fd_entry) = self.fd_table.close(fd as i32) {
                 if crate::process::process_manager_held_on_current_cpu() {
@@ -599,7 +684,14 @@ impl Process {
                         crate::ipc::fifo::close_fifo_write(&path);
                         buffer.lock().close_write();
                     }
-                    _ => {} // StdIo, RegularFile, Directory, Device, etc. — no action needed
+                    FdKind::RegularFile(file) => {
+                        let key = {
+                            let file = file.lock();
+                            (file.mount_id, file.inode_num)
+                        };
+                        crate::fs::lock::release_process_file(tgid, key);
+                    }
+                    _ => {} // StdIo, Directory, Device, etc. — no action needed
                 }
             }
         }
@@ -696,3 +788,24 @@ impl Process {
         &mut self.vmas
     }
 }
+
+/// Absolute path of a program given by path or, for bare names, installed under /bin
+fn program_path(path: &str) -> String {
+    if path.starts_with('/') {
+        String::from(path)
+    } else {
+        alloc::format!("/bin/{}", path)
+    }
+}
+
+/// Concatenate strings, terminating each with a NUL unless it already ends in one
+fn nul_joined(strings: &[&[u8]]) -> Vec<u8> {
+    let mut joined = Vec::new();
+    for s in strings {
+        joined.extend_from_slice(s);
+        if s.last() != Some(&0) {
+            joined.push(0);
+        }
+    }
+    joined
+}
@@ -0,0 +1,767 @@
+//! Process tracing (ptrace)
+//!
+//! A tracer attaches to a tracee with PTRACE_TRACEME or PTRACE_ATTACH. From
+//! then on every signal the tracee would act on, except SIGKILL, first stops
+//! it, and the tracer collects the stop through waitpid. While the tracee is
+//! stopped the tracer reads and writes its memory and registers, then resumes
+//! it with PTRACE_CONT, PTRACE_SYSCALL or PTRACE_SINGLESTEP, optionally
+//! passing a signal on. PTRACE_SYSCALL also stops the tracee at every syscall
+//! entry and exit; PTRACE_SINGLESTEP sets the trap flag (x86_64) or software
+//! step (ARM64) so the next instruction raises SIGTRAP.
+//!
+//! A tracee stops in one of two places:
+//! - On the return to userspace from an interrupt or exception, signal
+//!   delivery parks its thread as Blocked with the user registers saved in the
+//!   thread context. Resuming writes the registers back into that context and
+//!   unblocks the thread.
+//! - Inside a syscall (syscall stops, and signals pending when a syscall
+//!   returns), the tracee sleeps on `STOP_QUEUE` in `syscall::ptrace` and
+//!   copies the registers the tracer left back into its syscall frame itself.
+//!
+//! Everything here runs under the process manager lock.
+
+use super::{Process, ProcessId, ProcessManager};
+use crate::memory::process_memory::{is_cow_page, make_private_flags, ProcessPageTable};
+use crate::signal::constants::{SIGKILL, SIGTRAP, TRAP_TRACE};
+use crate::signal::types::SigInfo;
+use crate::task::thread::CpuContext;
+use crate::task::waitqueue::WaitQueueHead;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+#[cfg(not(target_arch = "x86_64"))]
+use crate::memory::arch_stub::{Page, PageTableFlags, Size4KiB, VirtAddr};
+#[cfg(target_arch = "x86_64")]
+use x86_64::{
+    structures::paging::{Page, PageTableFlags, Size4KiB},
+    VirtAddr,
+};
+
+/// PTRACE_O_TRACESYSGOOD: report syscall stops as SIGTRAP | 0x80
+pub const PTRACE_O_TRACESYSGOOD: u64 = 1;
+
+/// First non-canonical user address
+#[cfg(target_arch = "x86_64")]
+const USER_ADDR_END: u64 = 1 << 47;
+/// First user address outside the 48-bit TTBR0 range
+#[cfg(target_arch = "aarch64")]
+const USER_ADDR_END: u64 = 1 << 48;
+
+/// Number of traced processes; while zero the syscall path skips ptrace
+static TRACEES: AtomicUsize = AtomicUsize::new(0);
+
+/// Bumped on every resume of a tracee stopped inside a syscall
+static RESUMES: AtomicU64 = AtomicU64::new(0);
+
+/// Tracees stopped inside a syscall sleep here until resumed
+static STOP_QUEUE: WaitQueueHead = WaitQueueHead::new();
+
+/// How the tracer last resumed the tracee
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resume {
+    /// Run until the next signal
+    Continue,
+    /// Also stop at syscall entry and exit
+    Syscall,
+    /// Stop after one instruction
+    SingleStep,
+}
+
+/// Why a tracee is stopped
+#[derive(Debug, Clone, Copy)]
+pub enum StopKind {
+    /// About to act on this signal
+    Signal(SigInfo),
+    /// Syscall entry or exit under PTRACE_SYSCALL
+    Syscall,
+}
+
+/// A tracee waiting for its tracer
+#[derive(Debug)]
+pub struct Stop {
+    pub kind: StopKind,
+    /// User registers at the stop; PTRACE_GETREGS and PTRACE_SETREGS work on this copy
+    pub regs: ElfGregs,
+    /// Stopped inside a syscall rather than parked on the return to userspace
+    in_syscall: bool,
+    /// Already reported to the tracer's waitpid
+    reported: bool,
+    /// Resumed by the tracer; the tracee has not picked the registers up yet
+    resumed: bool,
+}
+
+/// Tracing state of a tracee
+#[derive(Debug)]
+pub struct PtraceState {
+    /// The tracing process
+    pub tracer: ProcessId,
+    /// Tracer thread woken when the tracee stops
+    tracer_tid: u64,
+    /// PTRACE_O_* options
+    pub options: u64,
+    resume: Resume,
+    stop: Option<Stop>,
+    /// Signal the tracer passed on when resuming; acted on without stopping again
+    pass_signal: Option<u32>,
+    /// Between a syscall-entry stop and the matching exit
+    in_syscall: bool,
+}
+
+impl PtraceState {
+    /// The stop the tracer may inspect, if the tracee is stopped
+    pub fn stop(&self) -> Option<&Stop> {
+        self.stop.as_ref().filter(|stop| !stop.resumed)
+    }
+
+    /// Mutable access to the stop the tracer may inspect
+    pub fn stop_mut(&mut self) -> Option<&mut Stop> {
+        self.stop.as_mut().filter(|stop| !stop.resumed)
+    }
+
+    /// waitpid status of the current stop
+    fn status(&self, stop: &Stop) -> i32 {
+        let sig = match stop.kind {
+            StopKind::Signal(info) => info.signo() as i32,
+            StopKind::Syscall if self.options & PTRACE_O_TRACESYSGOOD != 0 => SIGTRAP as i32 | 0x80,
+            StopKind::Syscall => SIGTRAP as i32,
+        };
+        (sig << 8) | 0x7f
+    }
+}
+
+/// Whether any process is traced
+#[inline]
+pub fn active() -> bool {
+    TRACEES.load(Ordering::Relaxed) != 0
+}
+
+/// Make `tracer` trace `tracee`
+pub fn attach(tracee: &mut Process, tracer: ProcessId, tracer_tid: u64) {
+    tracee.ptrace = Some(PtraceState {
+        tracer,
+        tracer_tid,
+        options: 0,
+        resume: Resume::Continue,
+        stop: None,
+        pass_signal: None,
+        in_syscall: false,
+    });
+    TRACEES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Stop tracing `tracee`, resuming it with `sig` if it is stopped
+pub fn detach(tracee: &mut Process, sig: u32) {
+    resume(tracee, Resume::Continue, sig);
+    if tracee.ptrace.take().is_some() {
+        TRACEES.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Drop the tracing state of a terminating process
+///
+/// Called from `Process::terminate` under the process manager lock; no logging.
+pub fn on_terminate(process: &mut Process) {
+    if process.ptrace.take().is_some() {
+        TRACEES.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Detach every tracee of an exiting tracer
+pub fn release_tracees(manager: &mut ProcessManager, tracer: ProcessId) {
+    if !active() {
+        return;
+    }
+    let tracees: Vec<ProcessId> = manager
+        .iter_processes()
+        .filter(|(_, process)| process.ptrace.as_ref().is_some_and(|s| s.tracer == tracer))
+        .map(|(pid, _)| pid)
+        .collect();
+    for pid in tracees {
+        if let Some(process) = manager.get_process_mut(pid) {
+            detach(process, 0);
+        }
+    }
+}
+
+/// Queue the SIGTRAP a traced process gets after a successful exec
+pub fn exec_trap(process: &mut Process) {
+    if process.ptrace.is_some() {
+        process.signals.queue_signal(SigInfo::kernel(SIGTRAP));
+    }
+}
+
+/// Queue the SIGTRAP for a completed single step at `pc`
+///
+/// Called from the debug exception handler. Returns false for a step trap no
+/// tracer asked for.
+pub fn step_trap(thread_id: u64, pc: u64) -> bool {
+    if !active() {
+        return false;
+    }
+    super::with_process_manager(|manager| {
+        let Some((_, process)) = manager.find_process_by_thread_mut(thread_id) else {
+            return false;
+        };
+        if !process
+            .ptrace
+            .as_ref()
+            .is_some_and(|s| s.resume == Resume::SingleStep)
+        {
+            return false;
+        }
+        process
+            .signals
+            .queue_signal(SigInfo::fault(SIGTRAP, TRAP_TRACE, pc))
+    })
+    .unwrap_or(false)
+}
+
+/// Whether `sig`, about to take effect in `process`, stops it for its tracer
+///
+/// A signal the tracer passed on when resuming is let through once.
+pub fn intercept_signal(process: &mut Process, sig: u32) -> bool {
+    let Some(state) = process.ptrace.as_mut() else {
+        return false;
+    };
+    if sig == SIGKILL {
+        return false;
+    }
+    if state.pass_signal == Some(sig) {
+        state.pass_signal = None;
+        return false;
+    }
+    true
+}
+
+/// Next pending signal that would stop `process` for its tracer
+pub fn next_stopping_signal(process: &Process) -> Option<u32> {
+    let state = process.ptrace.as_ref()?;
+    let sig = process.signals.next_deliverable_signal()?;
+    (sig != SIGKILL && state.pass_signal != Some(sig)).then_some(sig)
+}
+
+/// Record a stop and wake the tracer
+///
+/// `in_syscall` stops are waited out by the tracee itself; for the others the
+/// caller parks the thread on its way back to userspace.
+pub fn enter_stop(process: &mut Process, kind: StopKind, regs: ElfGregs, in_syscall: bool) {
+    let Some(state) = process.ptrace.as_mut() else {
+        return;
+    };
+    state.stop = Some(Stop {
+        kind,
+        regs,
+        in_syscall,
+        reported: false,
+        resumed: false,
+    });
+    let tracer_tid = state.tracer_tid;
+    crate::task::scheduler::with_scheduler(|sched| sched.unblock_for_child_exit(tracer_tid));
+}
+
+/// Make a signal stop found on a syscall's return path one the tracee waits
+/// out inside the syscall, instead of being parked
+pub fn keep_stop_in_syscall(process: &mut Process) {
+    if let Some(stop) = process.ptrace.as_mut().and_then(|s| s.stop.as_mut()) {
+        stop.in_syscall = true;
+    }
+}
+
+/// Record a signal-delivery stop found on the return to userspace
+pub fn signal_stop(process: &mut Process, info: SigInfo, regs: ElfGregs) {
+    enter_stop(process, StopKind::Signal(info), regs, false);
+}
+
+/// Decide whether the syscall being entered stops the tracee
+///
+/// Entry always marks the tracee as inside a syscall so the exit stop pairs
+/// with it even if the tracer switches to PTRACE_CONT in between.
+pub fn syscall_entry_stops(process: &mut Process) -> bool {
+    let Some(state) = process.ptrace.as_mut() else {
+        return false;
+    };
+    state.in_syscall = state.resume == Resume::Syscall;
+    state.in_syscall
+}
+
+/// Decide whether the syscall being left stops the tracee
+pub fn syscall_exit_stops(process: &mut Process) -> bool {
+    let Some(state) = process.ptrace.as_mut() else {
+        return false;
+    };
+    let stops = state.in_syscall && state.resume == Resume::Syscall;
+    state.in_syscall = false;
+    stops
+}
+
+/// Resume a stopped tracee
+///
+/// `sig`, if non-zero, is re-queued and acted on without stopping again. The
+/// original siginfo is kept when the tracer passes the stopping signal on.
+pub fn resume(process: &mut Process, how: Resume, sig: u32) {
+    let Some(state) = process.ptrace.as_mut() else {
+        return;
+    };
+    state.resume = how;
+    let Some(mut stop) = state.stop.take() else {
+        return;
+    };
+    stop.regs.set_single_step(how == Resume::SingleStep);
+
+    if sig != 0 {
+        let info = match stop.kind {
+            StopKind::Signal(info) if info.signo() == sig => info,
+            _ => SigInfo::kernel(sig),
+        };
+        state.pass_signal = Some(sig);
+        process.signals.queue_signal(info);
+    }
+
+    if stop.in_syscall {
+        // The tracee copies the registers into its frame when it wakes
+        stop.resumed = true;
+        state.stop = Some(stop);
+        RESUMES.fetch_add(1, Ordering::Release);
+        STOP_QUEUE.wake_up();
+        return;
+    }
+
+    let Some(thread) = process.main_thread.as_mut() else {
+        return;
+    };
+    let tid = thread.id;
+    #[cfg(target_arch = "x86_64")]
+    stop.regs.apply_to_context(&mut thread.context);
+    #[cfg(target_arch = "aarch64")]
+    crate::task::scheduler::with_thread_mut(tid, |thread| {
+        stop.regs.apply_to_context(&mut thread.context)
+    });
+    crate::task::scheduler::with_scheduler(|sched| sched.unblock(tid));
+}
+
+/// Queue a tracee stopped inside a syscall waits on
+pub(crate) fn stop_queue() -> &'static WaitQueueHead {
+    &STOP_QUEUE
+}
+
+/// Current resume generation, for waits on `stop_queue`
+pub(crate) fn resume_generation() -> u64 {
+    RESUMES.load(Ordering::Acquire)
+}
+
+/// Where a tracee stopped inside a syscall stands
+pub enum SyscallStop {
+    /// Still stopped
+    Waiting,
+    /// Resumed with these registers
+    Resumed(ElfGregs),
+    /// No longer traced; keep the registers it had
+    Released,
+}
+
+/// Check on a stop inside a syscall, taking the registers once resumed
+pub fn poll_syscall_stop(process: &mut Process) -> SyscallStop {
+    let Some(state) = process.ptrace.as_mut() else {
+        return SyscallStop::Released;
+    };
+    match state.stop.as_ref() {
+        Some(stop) if stop.in_syscall && !stop.resumed => SyscallStop::Waiting,
+        Some(stop) if stop.in_syscall => {
+            let regs = stop.regs;
+            state.stop = None;
+            SyscallStop::Resumed(regs)
+        }
+        _ => SyscallStop::Released,
+    }
+}
+
+/// Whether `pid` (or any process, for -1) is traced by `tracer`
+pub fn traces(manager: &ProcessManager, tracer: ProcessId, pid: i64) -> bool {
+    active()
+        && manager.iter_processes().any(|(id, process)| {
+            (pid == -1 || id.as_u64() == pid as u64)
+                && process.ptrace.as_ref().is_some_and(|s| s.tracer == tracer)
+        })
+}
+
+/// Take an unreported stop of a tracee of `tracer` matching `pid` (-1 for any)
+///
+/// Returns the tracee pid and its waitpid status. Each stop is reported once.
+pub fn take_stop_report(
+    manager: &mut ProcessManager,
+    tracer: ProcessId,
+    pid: i64,
+) -> Option<(ProcessId, i32)> {
+    if !active() {
+        return None;
+    }
+    let tracee = manager
+        .iter_processes()
+        .find(|(id, process)| {
+            (pid == -1 || id.as_u64() == pid as u64)
+                && process.ptrace.as_ref().is_some_and(|s| {
+                    s.tracer == tracer && s.stop().is_some_and(|stop| !stop.reported)
+                })
+        })
+        .map(|(id, _)| id)?;
+    let state = manager.get_process_mut(tracee)?.ptrace.as_mut()?;
+    let status = state.status(state.stop()?);
+    state.stop_mut()?.reported = true;
+    Some((tracee, status))
+}
+
+/// Number of registers in elf_gregset_t (struct user_regs_struct)
+#[cfg(target_arch = "x86_64")]
+pub const NGREG: usize = 27;
+/// Number of registers in elf_gregset_t (struct user_pt_regs)
+#[cfg(target_arch = "aarch64")]
+pub const NGREG: usize = 34;
+
+/// General registers of one thread, in elf_gregset_t order
+///
+/// The layout matches user_regs_struct (x86_64) and user_pt_regs (ARM64), so
+/// ptrace copies it to and from userspace as is.
+#[derive(Debug, Clone, Copy)]
+#[repr(transparent)]
+pub struct ElfGregs([u64; NGREG]);
+
+impl ElfGregs {
+    /// The registers in elf_gregset_t order
+    pub fn as_slice(&self) -> &[u64] {
+        &self.0
+    }
+}
+
+/// RFLAGS bits a tracer may change: CF, PF, AF, ZF, SF, TF, DF, OF and AC
+#[cfg(target_arch = "x86_64")]
+const USER_RFLAGS: u64 = 0x40DD5;
+/// RFLAGS trap flag (single-step)
+#[cfg(target_arch = "x86_64")]
+const RFLAGS_TF: u64 = 1 << 8;
+
+/// SPSR bits a tracer may change: the NZCV condition flags
+#[cfg(target_arch = "aarch64")]
+const USER_SPSR: u64 = 0xF000_0000;
+/// SPSR software step bit
+#[cfg(target_arch = "aarch64")]
+const SPSR_SS: u64 = 1 << 21;
+
+#[cfg(target_arch = "x86_64")]
+impl ElfGregs {
+    /// Registers of a thread interrupted in userspace
+    pub fn from_frame(
+        frame: &x86_64::structures::idt::InterruptStackFrame,
+        saved_regs: &crate::task::process_context::SavedRegisters,
+    ) -> Self {
+        let context =
+            crate::task::process_context::ProcessContext::from_interrupt_frame(frame, saved_regs);
+        Self::from_context(&context.cpu_context)
+    }
+
+    /// Registers saved in a thread's context
+    pub fn from_context(ctx: &CpuContext) -> Self {
+        // user_regs_struct; orig_rax is -1 (not in a syscall), fs/gs and
+        // their bases are not tracked per thread
+        Self([
+            ctx.r15,
+            ctx.r14,
+            ctx.r13,
+            ctx.r12,
+            ctx.rbp,
+            ctx.rbx,
+            ctx.r11,
+            ctx.r10,
+            ctx.r9,
+            ctx.r8,
+            ctx.rax,
+            ctx.rcx,
+            ctx.rdx,
+            ctx.rsi,
+            ctx.rdi,
+            u64::MAX,
+            ctx.rip,
+            ctx.cs,
+            ctx.rflags,
+            ctx.rsp,
+            ctx.ss,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+        ])
+    }
+
+    /// Registers of a thread stopped in a syscall; `orig_rax` is the syscall number
+    pub fn from_syscall_frame(
+        frame: &crate::syscall::handler::SyscallFrame,
+        orig_rax: u64,
+    ) -> Self {
+        let mut regs = Self::from_context(&CpuContext::from_syscall_frame(frame));
+        regs.0[15] = orig_rax;
+        regs
+    }
+
+    /// Syscall number the tracer left in orig_rax
+    pub fn orig_syscall(&self) -> u64 {
+        self.0[15]
+    }
+
+    /// Whether the instruction and stack pointers are user addresses
+    pub fn is_valid(&self) -> bool {
+        self.0[16] < USER_ADDR_END && self.0[19] < USER_ADDR_END
+    }
+
+    /// Arm or disarm the trap flag
+    pub fn set_single_step(&mut self, on: bool) {
+        if on {
+            self.0[18] |= RFLAGS_TF;
+        } else {
+            self.0[18] &= !RFLAGS_TF;
+        }
+    }
+
+    /// Write the registers into a syscall frame; segments stay as they are
+    pub fn apply_to_syscall_frame(&self, frame: &mut crate::syscall::handler::SyscallFrame) {
+        let r = &self.0;
+        (
+            frame.r15, frame.r14, frame.r13, frame.r12, frame.rbp, frame.rbx,
+        ) = (r[0], r[1], r[2], r[3], r[4], r[5]);
+        (frame.r11, frame.r10, frame.r9, frame.r8) = (r[6], r[7], r[8], r[9]);
+        (frame.rax, frame.rcx, frame.rdx, frame.rsi, frame.rdi) =
+            (r[10], r[11], r[12], r[13], r[14]);
+        frame.rip = r[16];
+        frame.rflags = (frame.rflags & !USER_RFLAGS) | (r[18] & USER_RFLAGS);
+        frame.rsp = r[19];
+    }
+
+    /// Write the registers into a saved thread context; segments stay as they are
+    pub fn apply_to_context(&self, ctx: &mut CpuContext) {
+        let r = &self.0;
+        (ctx.r15, ctx.r14, ctx.r13, ctx.r12, ctx.rbp, ctx.rbx) =
+            (r[0], r[1], r[2], r[3], r[4], r[5]);
+        (ctx.r11, ctx.r10, ctx.r9, ctx.r8) = (r[6], r[7], r[8], r[9]);
+        (ctx.rax, ctx.rcx, ctx.rdx, ctx.rsi, ctx.rdi) = (r[10], r[11], r[12], r[13], r[14]);
+        ctx.rip = r[16];
+        ctx.rflags = (ctx.rflags & !USER_RFLAGS) | (r[18] & USER_RFLAGS);
+        ctx.rsp = r[19];
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+impl ElfGregs {
+    /// Registers of a thread interrupted in userspace
+    pub fn from_saved(saved: &crate::task::process_context::SavedRegisters) -> Self {
+        Self([
+            saved.x0, saved.x1, saved.x2, saved.x3, saved.x4, saved.x5, saved.x6, saved.x7,
+            saved.x8, saved.x9, saved.x10, saved.x11, saved.x12, saved.x13, saved.x14, saved.x15,
+            saved.x16, saved.x17, saved.x18, saved.x19, saved.x20, saved.x21, saved.x22, saved.x23,
+            saved.x24, saved.x25, saved.x26, saved.x27, saved.x28, saved.x29, saved.x30, saved.sp,
+            saved.elr, saved.spsr,
+        ])
+    }
+
+    /// Registers saved in a thread's context
+    pub fn from_context(ctx: &CpuContext) -> Self {
+        Self([
+            ctx.x0,
+            ctx.x1,
+            ctx.x2,
+            ctx.x3,
+            ctx.x4,
+            ctx.x5,
+            ctx.x6,
+            ctx.x7,
+            ctx.x8,
+            ctx.x9,
+            ctx.x10,
+            ctx.x11,
+            ctx.x12,
+            ctx.x13,
+            ctx.x14,
+            ctx.x15,
+            ctx.x16,
+            ctx.x17,
+            ctx.x18,
+            ctx.x19,
+            ctx.x20,
+            ctx.x21,
+            ctx.x22,
+            ctx.x23,
+            ctx.x24,
+            ctx.x25,
+            ctx.x26,
+            ctx.x27,
+            ctx.x28,
+            ctx.x29,
+            ctx.x30,
+            ctx.sp_el0,
+            ctx.elr_el1,
+            ctx.spsr_el1,
+        ])
+    }
+
+    /// Syscall number the tracer left in x8
+    pub fn orig_syscall(&self) -> u64 {
+        self.0[8]
+    }
+
+    /// Whether the program counter and stack pointer are user addresses
+    pub fn is_valid(&self) -> bool {
+        self.0[32] < USER_ADDR_END && self.0[31] < USER_ADDR_END
+    }
+
+    /// Arm or disarm software step
+    pub fn set_single_step(&mut self, on: bool) {
+        if on {
+            self.0[33] |= SPSR_SS;
+        } else {
+            self.0[33] &= !SPSR_SS;
+        }
+    }
+
+    /// Merge the tracer-controlled SPSR bits into `spsr`
+    fn merge_spsr(&self, spsr: u64) -> u64 {
+        let user = USER_SPSR | SPSR_SS;
+        (spsr & !user) | (self.0[33] & user)
+    }
+
+    /// Write the registers into a saved register set
+    pub fn apply_to_saved(&self, saved: &mut crate::task::process_context::SavedRegisters) {
+        let r = &self.0;
+        (
+            saved.x0, saved.x1, saved.x2, saved.x3, saved.x4, saved.x5, saved.x6, saved.x7,
+        ) = (r[0], r[1], r[2], r[3], r[4], r[5], r[6], r[7]);
+        (
+            saved.x8, saved.x9, saved.x10, saved.x11, saved.x12, saved.x13, saved.x14,
+        ) = (r[8], r[9], r[10], r[11], r[12], r[13], r[14]);
+        (
+            saved.x15, saved.x16, saved.x17, saved.x18, saved.x19, saved.x20, saved.x21,
+        ) = (r[15], r[16], r[17], r[18], r[19], r[20], r[21]);
+        (
+            saved.x22, saved.x23, saved.x24, saved.x25, saved.x26, saved.x27, saved.x28,
+        ) = (r[22], r[23], r[24], r[25], r[26], r[27], r[28]);
+        (saved.x29, saved.x30, saved.sp, saved.elr) = (r[29], r[30], r[31], r[32]);
+        saved.spsr = self.merge_spsr(saved.spsr);
+    }
+
+    /// Write the registers into a saved thread context
+    pub fn apply_to_context(&self, ctx: &mut CpuContext) {
+        let r = &self.0;
+        (
+            ctx.x0, ctx.x1, ctx.x2, ctx.x3, ctx.x4, ctx.x5, ctx.x6, ctx.x7,
+        ) = (r[0], r[1], r[2], r[3], r[4], r[5], r[6], r[7]);
+        (ctx.x8, ctx.x9, ctx.x10, ctx.x11, ctx.x12, ctx.x13, ctx.x14) =
+            (r[8], r[9], r[10], r[11], r[12], r[13], r[14]);
+        (
+            ctx.x15, ctx.x16, ctx.x17, ctx.x18, ctx.x19, ctx.x20, ctx.x21,
+        ) = (r[15], r[16], r[17], r[18], r[19], r[20], r[21]);
+        (
+            ctx.x22, ctx.x23, ctx.x24, ctx.x25, ctx.x26, ctx.x27, ctx.x28,
+        ) = (r[22], r[23], r[24], r[25], r[26], r[27], r[28]);
+        (ctx.x29, ctx.x30, ctx.sp_el0, ctx.elr_el1) = (r[29], r[30], r[31], r[32]);
+        ctx.spsr_el1 = self.merge_spsr(ctx.spsr_el1);
+    }
+}
+
+/// Physical address backing a user address, with its page flags
+fn user_phys(page_table: &ProcessPageTable, addr: u64) -> Option<(u64, PageTableFlags)> {
+    if addr >= USER_ADDR_END {
+        return None;
+    }
+    let page: Page<Size4KiB> = Page::containing_address(VirtAddr::new(addr));
+    let (frame, flags) = page_table.get_page_info(page)?;
+    // Device memory is never touched on a tracer's behalf
+    if !flags.contains(PageTableFlags::USER_ACCESSIBLE) || flags.contains(PageTableFlags::NO_CACHE)
+    {
+        return None;
+    }
+    Some((frame.start_address().as_u64() + (addr & 0xFFF), flags))
+}
+
+/// Read a tracee's memory; false if any byte is not mapped
+pub fn read_memory(process: &Process, addr: u64, buf: &mut [u8]) -> bool {
+    let Some(page_table) = process.page_table.as_deref() else {
+        return false;
+    };
+    let phys_offset = crate::memory::physical_memory_offset().as_u64();
+    for (i, byte) in buf.iter_mut().enumerate() {
+        let Some((phys, _)) = user_phys(page_table, addr.wrapping_add(i as u64)) else {
+            return false;
+        };
+        *byte = unsafe { core::ptr::read_volatile((phys_offset + phys) as *const u8) };
+    }
+    true
+}
+
+/// Write a tracee's memory; false if any byte is not mapped
+///
+/// Read-only and copy-on-write pages are written too, the way a debugger
+/// plants breakpoints in program text. A page still shared with another
+/// address space is first replaced by a private copy.
+pub fn write_memory(process: &mut Process, addr: u64, data: &[u8]) -> bool {
+    let Some(page_table) = process.page_table.as_deref_mut() else {
+        return false;
+    };
+    let phys_offset = crate::memory::physical_memory_offset().as_u64();
+    for (i, &byte) in data.iter().enumerate() {
+        let virt = addr.wrapping_add(i as u64);
+        if user_phys(page_table, virt).is_none() || !make_page_private(page_table, virt) {
+            return false;
+        }
+        let Some((phys, _)) = user_phys(page_table, virt) else {
+            return false;
+        };
+        unsafe { core::ptr::write_volatile((phys_offset + phys) as *mut u8, byte) };
+    }
+    true
+}
+
+/// Give the page at `addr` its own frame if it shares one with another address space
+fn make_page_private(page_table: &mut ProcessPageTable, addr: u64) -> bool {
+    use crate::arch_impl::PageTableOps;
+    use crate::memory::frame_allocator::{allocate_frame, deallocate_leaf_frame};
+    use crate::memory::frame_metadata::frame_is_shared;
+
+    let page: Page<Size4KiB> = Page::containing_address(VirtAddr::new(addr));
+    let Some((old_frame, old_flags)) = page_table.get_page_info(page) else {
+        return false;
+    };
+    if !frame_is_shared(old_frame) {
+        return true;
+    }
+    let Some(new_frame) = allocate_frame() else {
+        return false;
+    };
+    let phys_offset = crate::memory::physical_memory_offset().as_u64();
+    unsafe {
+        core::ptr::copy_nonoverlapping(
+            (phys_offset + old_frame.start_address().as_u64()) as *const u8,
+            (phys_offset + new_frame.start_address().as_u64()) as *mut u8,
+            4096,
+        );
+    }
+    // The copy belongs to this address space alone; a COW page becomes
+    // writable, a read-only one stays read-only
+    let new_flags = if is_cow_page(old_flags) {
+        make_private_flags(old_flags)
+    } else {
+        old_flags
+    };
+    if page_table.unmap_page(page).is_err() {
+        let _ = deallocate_leaf_frame(new_frame);
+        return false;
+    }
+    if page_table.map_page(page, new_frame, new_flags).is_err() {
+        let _ = page_table.map_page(page, old_frame, old_flags);
+        let _ = deallocate_leaf_frame(new_frame);
+        return false;
+    }
+    let flush_addr = page.start_address().as_u64();
+    #[cfg(target_arch = "x86_64")]
+    crate::arch_impl::x86_64::paging::X86PageTableOps::flush_tlb_page(flush_addr);
+    #[cfg(target_arch = "aarch64")]
+    crate::arch_impl::aarch64::paging::Aarch64PageTableOps::flush_tlb_page(flush_addr);
+    true
+}
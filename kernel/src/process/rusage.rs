@@ -0,0 +1,159 @@
+//! Process resource usage for getrusage(), wait4(), waitid() and times()
+//!
+//! CPU time, page faults and context switches are counted per thread (see
+//! `task::accounting`) and summed over a process's threads when asked for.
+//! A process's totals are frozen when it exits:
+//! - An exiting clone thread's totals are folded into its group leader, so
+//!   RUSAGE_SELF keeps counting threads that are gone.
+//! - A reaped child's totals, together with those of the children it reaped
+//!   itself, are added to its parent's children totals (RUSAGE_CHILDREN).
+//!
+//! The maximum resident set size is a high-water mark of the process's
+//! mapped memory, sampled when it grows. Pages are mapped eagerly, so mapped
+//! memory is resident memory. No fault waits for I/O, so every fault is a
+//! minor fault.
+//!
+//! Everything here runs under the process manager lock; the per-thread
+//! counters are collected beforehand with
+//! `task::scheduler::threads_usage`, since the scheduler lock must not be
+//! taken under the process manager lock.
+
+use super::{Process, ProcessId, ProcessManager};
+use crate::task::accounting::UsageSnapshot;
+
+/// Resource usage of a process, or the sum over several
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rusage {
+    pub cpu: UsageSnapshot,
+    /// Largest resident set size, in kilobytes
+    pub max_rss_kb: u64,
+}
+
+impl Rusage {
+    /// Add another process's usage to this sum; max RSS is the larger one
+    pub fn add(&mut self, other: &Rusage) {
+        self.cpu.add(&other.cpu);
+        self.max_rss_kb = self.max_rss_kb.max(other.max_rss_kb);
+    }
+}
+
+/// Usage bookkeeping of one process
+#[derive(Debug, Clone, Default)]
+pub struct ProcessUsage {
+    /// Largest resident set size seen so far, in kilobytes
+    pub max_rss_kb: u64,
+    /// Clone threads of this thread group that have exited
+    pub dead_threads: UsageSnapshot,
+    /// The process's own totals, frozen when it exits
+    pub exited: Option<Rusage>,
+    /// Reaped children and the children they reaped
+    pub children: Rusage,
+}
+
+/// Resident set size of a process, in kilobytes
+pub fn resident_kb(process: &Process) -> u64 {
+    let mapped: u64 = process
+        .vmas
+        .iter()
+        .map(|vma| vma.end.as_u64() - vma.start.as_u64())
+        .sum();
+    let stack = process
+        .user_stack_top
+        .saturating_sub(process.user_stack_bottom);
+    let image = (process.memory_usage.code_size + process.memory_usage.heap_size) as u64;
+    (image + stack + mapped) / 1024
+}
+
+/// Raise the max RSS high-water mark after the process's memory grew
+pub fn note_rss(process: &mut Process) {
+    let resident = resident_kb(process);
+    if resident > process.usage.max_rss_kb {
+        process.usage.max_rss_kb = resident;
+    }
+}
+
+/// The thread group `process` belongs to: its leader's pid
+pub fn thread_group(process: &Process) -> ProcessId {
+    ProcessId::new(process.thread_group_id.unwrap_or(process.id.as_u64()))
+}
+
+/// Live members of the thread group led by `leader`, the leader included
+///
+/// These are the pids whose scheduler threads RUSAGE_SELF sums; members that
+/// exited are already counted in the leader's `dead_threads`.
+pub fn live_members(manager: &ProcessManager, leader: ProcessId) -> alloc::vec::Vec<u64> {
+    manager
+        .iter_processes()
+        .filter(|(_, process)| thread_group(process) == leader && !process.is_terminated())
+        .map(|(pid, _)| pid.as_u64())
+        .collect()
+}
+
+/// Usage of the thread group led by `leader` (RUSAGE_SELF), given the summed
+/// counters of its live members' threads
+pub fn self_usage(manager: &mut ProcessManager, leader: ProcessId, live: UsageSnapshot) -> Rusage {
+    let Some(process) = manager.get_process_mut(leader) else {
+        return Rusage {
+            cpu: live,
+            max_rss_kb: 0,
+        };
+    };
+    note_rss(process);
+    let mut cpu = live;
+    cpu.add(&process.usage.dead_threads);
+    Rusage {
+        cpu,
+        max_rss_kb: process.usage.max_rss_kb,
+    }
+}
+
+/// Freeze the usage of exiting process `pid`, given its threads' counters
+///
+/// A clone thread's counters go to its group leader; a group leader's frozen
+/// totals include the clone threads that exited before it.
+pub fn record_exit(manager: &mut ProcessManager, pid: ProcessId, threads: UsageSnapshot) {
+    let Some(process) = manager.get_process_mut(pid) else {
+        return;
+    };
+    let leader = thread_group(process);
+    if leader != pid {
+        process.usage.exited = Some(Rusage {
+            cpu: threads,
+            max_rss_kb: 0,
+        });
+        if let Some(leader) = manager.get_process_mut(leader) {
+            leader.usage.dead_threads.add(&threads);
+        }
+        return;
+    }
+    note_rss(process);
+    let mut cpu = threads;
+    cpu.add(&process.usage.dead_threads);
+    process.usage.exited = Some(Rusage {
+        cpu,
+        max_rss_kb: process.usage.max_rss_kb,
+    });
+}
+
+/// Totals of a terminated child: its own and those of the children it reaped
+pub fn reaped_usage(child: &Process) -> Rusage {
+    let mut usage = child.usage.exited.unwrap_or_default();
+    usage.add(&child.usage.children);
+    usage
+}
+
+/// Charge the totals of `child`, which `parent` is reaping, to the children
+/// totals of the parent's thread group
+///
+/// Returns the child's totals for wait4()'s rusage.
+pub fn reap(manager: &mut ProcessManager, parent: ProcessId, child: ProcessId) -> Rusage {
+    let usage = manager
+        .get_process(child)
+        .map(reaped_usage)
+        .unwrap_or_default();
+    let leader = manager.get_process(parent).map(thread_group);
+    if let Some(leader) = leader.and_then(|leader| manager.get_process_mut(leader)) {
+        leader.usage.children.add(&usage);
+    }
+    usage
+}
@@ -33,10 +33,13 @@ pub const SIGIO: u32 = 29;
 pub const SIGPWR: u32 = 30;
 pub const SIGSYS: u32 = 31;
 
-// Real-time signals (32-64) - for future use
+// Real-time signals (32-64) - queued, delivered lowest number first
 pub const SIGRTMIN: u32 = 32;
 pub const SIGRTMAX: u32 = 64;
 
+/// Maximum number of queued real-time signals per process (POSIX SIGQUEUE_MAX)
+pub const SIGQUEUE_MAX: usize = 32;
+
 /// Maximum signal number supported
 pub const NSIG: u32 = 64;
 
@@ -66,6 +69,8 @@ pub const MINSIGSTKSZ: usize = 2048;
 pub const SIGSTKSZ: usize = 8192;
 
 // sigaction flags
+/// SIGCHLD: don't report children that stop or continue
+pub const SA_NOCLDSTOP: u64 = 0x00000001;
 /// Restart interrupted syscalls
 #[allow(dead_code)] // Part of POSIX sigaction API, used by userspace
 pub const SA_RESTART: u64 = 0x10000000;
@@ -81,6 +86,34 @@ pub const SA_ONSTACK: u64 = 0x08000000;
 #[allow(dead_code)] // Part of POSIX sigaction API, used by userspace
 pub const SA_RESTORER: u64 = 0x04000000;
 
+// siginfo_t si_code values
+/// Sent by kill()
+pub const SI_USER: i32 = 0;
+/// Sent by the kernel
+pub const SI_KERNEL: i32 = 0x80;
+/// Sent by sigqueue()
+pub const SI_QUEUE: i32 = -1;
+/// Sent by tkill() or tgkill()
+pub const SI_TKILL: i32 = -6;
+/// SIGSEGV: address not mapped
+pub const SEGV_MAPERR: i32 = 1;
+/// SIGSEGV: invalid permissions for mapped object
+pub const SEGV_ACCERR: i32 = 2;
+/// SIGTRAP: process breakpoint
+pub const TRAP_BRKPT: i32 = 1;
+/// SIGTRAP: process trace trap (single-step)
+pub const TRAP_TRACE: i32 = 2;
+/// SIGCHLD: child exited
+pub const CLD_EXITED: i32 = 1;
+/// SIGCHLD: child killed by a signal
+pub const CLD_KILLED: i32 = 2;
+/// SIGCHLD: child killed by a signal and dumped core
+pub const CLD_DUMPED: i32 = 3;
+/// SIGCHLD: child stopped
+pub const CLD_STOPPED: i32 = 5;
+/// SIGCHLD: child continued by SIGCONT
+pub const CLD_CONTINUED: i32 = 6;
+
 /// Convert signal number to bit mask
 ///
 /// Returns 0 for invalid signal numbers (0 or > NSIG)
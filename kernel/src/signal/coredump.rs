@@ -0,0 +1,596 @@
+//! ELF core dumps for signals whose default action is CoreDump
+//!
+//! A dump is taken in two steps. `capture` runs from signal delivery under the
+//! process manager lock, before `Process::terminate` releases the address
+//! space: it records the dying thread's registers and copies the process
+//! memory while its frames are still mapped. The rest runs on the system
+//! workqueue, which may sleep: it adds the saved register state of the other
+//! threads in the group, lays out the ELF file and writes it through ext2.
+//!
+//! The file is named by `/etc/core_pattern` when that file exists, otherwise
+//! `core.%p`. `%p` expands to the pid, `%e` to the executable name and `%%` to
+//! a literal percent sign; relative names are resolved against the process's
+//! working directory. The file never exceeds the process's RLIMIT_CORE:
+//! segments past the limit are kept in the program headers with no file data.
+//!
+//! The layout matches what Linux writes, so host gdb can open the file:
+//! one PT_NOTE segment (NT_PRSTATUS for the dumping thread, NT_PRPSINFO,
+//! NT_AUXV, NT_FILE, then NT_PRSTATUS for each other thread) followed by one
+//! PT_LOAD per memory region. Regions are the process's VMAs plus the runs of
+//! mapped pages outside them (program image, heap and stack).
+
+use super::types::{default_action, SignalDefaultAction};
+use crate::fs::ext2::Ext2Error;
+use crate::memory::process_memory::ProcessPageTable;
+use crate::memory::regions::{regions_of, user_pages};
+use crate::memory::vma::Vma;
+use crate::process::ptrace::{ElfGregs, NGREG};
+use crate::process::{Process, ProcessManager};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+#[cfg(not(target_arch = "x86_64"))]
+use crate::memory::arch_stub::PageTableFlags;
+#[cfg(target_arch = "x86_64")]
+use x86_64::structures::paging::PageTableFlags;
+
+const PAGE_SIZE: u64 = 4096;
+
+/// Core images are buffered on the kernel heap before they are written, so
+/// RLIMIT_CORE is clamped to this size
+const MAX_CORE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// File holding the core file name pattern
+const PATTERN_FILE: &str = "/etc/core_pattern";
+
+/// Pattern used when `PATTERN_FILE` is missing or empty
+const DEFAULT_PATTERN: &str = "core.%p";
+
+// ELF constants
+const ET_CORE: u16 = 4;
+#[cfg(target_arch = "x86_64")]
+const EM_MACHINE: u16 = 62; // EM_X86_64
+#[cfg(target_arch = "aarch64")]
+const EM_MACHINE: u16 = 183; // EM_AARCH64
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const PT_PHDR: u32 = 6;
+const EHDR_SIZE: usize = 64;
+const PHDR_SIZE: usize = 56;
+
+// Note types
+const NT_PRSTATUS: u32 = 1;
+const NT_PRPSINFO: u32 = 3;
+const NT_AUXV: u32 = 6;
+const NT_FILE: u32 = 0x4649_4c45;
+
+// Auxiliary vector keys used to find the program headers
+const AT_PHDR: u64 = 3;
+const AT_PHENT: u64 = 4;
+const AT_PHNUM: u64 = 5;
+
+/// Offset of pr_reg in struct elf_prstatus (identical on x86_64 and ARM64)
+const PRSTATUS_REG_OFFSET: usize = 112;
+
+/// Size of struct elf_prstatus: the registers, then an int pr_fpvalid
+const PRSTATUS_SIZE: usize = (PRSTATUS_REG_OFFSET + NGREG * 8 + 4 + 7) & !7;
+
+/// Size of struct elf_prpsinfo
+const PRPSINFO_SIZE: usize = 136;
+
+/// Per-thread state for an NT_PRSTATUS note
+struct ThreadStatus {
+    tid: u64,
+    sigpend: u64,
+    sighold: u64,
+    regs: ElfGregs,
+}
+
+/// One PT_LOAD segment; `data` holds the first `data.len()` bytes
+struct Segment {
+    start: u64,
+    size: u64,
+    flags: u32,
+    data: Vec<u8>,
+}
+
+/// Everything needed to write a core file once the process is gone
+pub struct CoreDump {
+    sig: u32,
+    pid: u64,
+    tgid: u64,
+    ppid: u64,
+    pgid: u64,
+    sid: u64,
+    uid: u32,
+    gid: u32,
+    name: String,
+    cwd: String,
+    limit: u64,
+    /// Dumping thread first
+    threads: Vec<ThreadStatus>,
+    auxv: Vec<(u64, u64)>,
+    segments: Vec<Segment>,
+}
+
+/// Whether `sig` would make `process` write a core file
+pub fn wants_core(process: &Process, sig: u32) -> bool {
+    default_action(sig) == SignalDefaultAction::CoreDump && process.core_limit.rlim_cur > 0
+}
+
+/// Record the dying thread and copy the process memory
+///
+/// Called with the process manager lock held, before the process is
+/// terminated. Returns None when RLIMIT_CORE is zero. CLONE_VM threads have no
+/// page table of their own; their memory is copied from the group leader when
+/// the dump is written.
+pub fn capture(process: &Process, sig: u32, regs: ElfGregs) -> Option<CoreDump> {
+    if !wants_core(process, sig) {
+        return None;
+    }
+    let limit = process.core_limit.rlim_cur.min(MAX_CORE_SIZE);
+    let pid = process.id.as_u64();
+    let thread = ThreadStatus {
+        tid: process.main_thread.as_ref().map_or(pid, |t| t.id()),
+        sigpend: process.signals.pending,
+        sighold: process.signals.blocked,
+        regs,
+    };
+    let segments = process
+        .page_table
+        .as_deref()
+        .map(|page_table| capture_memory(page_table, &process.vmas, limit))
+        .unwrap_or_default();
+
+    Some(CoreDump {
+        sig,
+        pid,
+        tgid: process.thread_group_id.unwrap_or(pid),
+        ppid: process.parent.map_or(0, |p| p.as_u64()),
+        pgid: process.pgid.as_u64(),
+        sid: process.sid.as_u64(),
+        uid: process.uid,
+        gid: process.gid,
+        name: process.name.clone(),
+        cwd: process.cwd.clone(),
+        limit,
+        threads: alloc::vec![thread],
+        auxv: process.auxv.clone(),
+        segments,
+    })
+}
+
+/// Finish and write `dump` from the system workqueue
+pub fn schedule(dump: CoreDump) {
+    crate::task::workqueue::schedule_work_fn(move || dump.finish(), "coredump");
+}
+
+impl CoreDump {
+    fn finish(mut self) {
+        crate::process::with_process_manager(|pm| self.collect_group(pm));
+        let path = self.path(&core_pattern());
+        match self.write(&path) {
+            Ok(size) => log::info!(
+                "coredump: pid {} signal {} -> {} ({} bytes)",
+                self.pid,
+                self.sig,
+                path,
+                size
+            ),
+            Err(e) => log::warn!("coredump: pid {} could not write {}: {}", self.pid, path, e),
+        }
+    }
+
+    /// Add the other live threads of the group, and the leader's memory when
+    /// the dumping thread had no page table of its own
+    fn collect_group(&mut self, pm: &mut ProcessManager) {
+        for (pid, process) in pm.iter_processes() {
+            let pid = pid.as_u64();
+            if process.thread_group_id.unwrap_or(pid) != self.tgid || process.is_terminated() {
+                continue;
+            }
+            if pid == self.tgid && self.segments.is_empty() {
+                if let Some(page_table) = process.page_table.as_deref() {
+                    self.segments = capture_memory(page_table, &process.vmas, self.limit);
+                }
+            }
+            if pid == self.pid {
+                continue;
+            }
+            let Some(tid) = process.main_thread.as_ref().map(|t| t.id()) else {
+                continue;
+            };
+            // A thread blocked in a syscall keeps its user registers aside
+            let regs = crate::task::scheduler::with_thread_mut(tid, |thread| {
+                let context = thread
+                    .saved_userspace_context
+                    .as_ref()
+                    .unwrap_or(&thread.context);
+                ElfGregs::from_context(context)
+            });
+            if let Some(regs) = regs {
+                self.threads.push(ThreadStatus {
+                    tid,
+                    sigpend: process.signals.pending,
+                    sighold: process.signals.blocked,
+                    regs,
+                });
+            }
+        }
+    }
+
+    /// Executable name as reported in NT_PRPSINFO and `%e` (at most 15 bytes)
+    fn comm(&self) -> &str {
+        let base = self.name.rsplit('/').next().unwrap_or(&self.name);
+        let mut end = base.len().min(15);
+        while !base.is_char_boundary(end) {
+            end -= 1;
+        }
+        &base[..end]
+    }
+
+    /// Expand `pattern` into an absolute path
+    fn path(&self, pattern: &str) -> String {
+        let mut name = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                name.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('p') => {
+                    let _ = write!(name, "{}", self.pid);
+                }
+                Some('e') => name.push_str(self.comm()),
+                Some('%') => name.push('%'),
+                // Unknown specifiers expand to nothing, as on Linux
+                _ => {}
+            }
+        }
+        if name.starts_with('/') {
+            return name;
+        }
+        let mut path = self.cwd.clone();
+        if !path.ends_with('/') {
+            path.push('/');
+        }
+        path.push_str(&name);
+        path
+    }
+
+    /// Lay out the file and write it; returns the file size
+    fn write(&mut self, path: &str) -> Result<u64, Ext2Error> {
+        let notes = self.notes();
+        let phnum = 1 + self.segments.len();
+        let notes_offset = (EHDR_SIZE + phnum * PHDR_SIZE) as u64;
+        let data_offset = page_align_up(notes_offset + notes.len() as u64);
+        if data_offset > self.limit {
+            return Err(Ext2Error::FileTooLarge);
+        }
+
+        // Give each segment as much file data as the limit leaves room for
+        let mut budget = (self.limit - data_offset) & !(PAGE_SIZE - 1);
+        for segment in self.segments.iter_mut() {
+            let len = (segment.data.len() as u64).min(budget);
+            segment.data.truncate(len as usize);
+            budget -= len;
+        }
+
+        let mut header = Vec::with_capacity(data_offset as usize);
+        put_ehdr(&mut header, phnum as u16);
+        put_phdr(
+            &mut header,
+            PT_NOTE,
+            0,
+            notes_offset,
+            0,
+            notes.len() as u64,
+            0,
+            4,
+        );
+        let mut offset = data_offset;
+        for segment in self.segments.iter() {
+            put_phdr(
+                &mut header,
+                PT_LOAD,
+                segment.flags,
+                offset,
+                segment.start,
+                segment.data.len() as u64,
+                segment.size,
+                PAGE_SIZE,
+            );
+            offset += segment.data.len() as u64;
+        }
+        header.extend_from_slice(&notes);
+        header.resize(data_offset as usize, 0);
+
+        let mut chunks: Vec<(u64, &[u8])> = alloc::vec![(0, header.as_slice())];
+        let mut offset = data_offset;
+        for segment in self.segments.iter() {
+            chunks.push((offset, segment.data.as_slice()));
+            offset += segment.data.len() as u64;
+        }
+        write_core_file(path, &chunks)?;
+        Ok(offset)
+    }
+
+    /// Build the contents of the PT_NOTE segment
+    fn notes(&self) -> Vec<u8> {
+        let mut notes = Vec::new();
+        let mut threads = self.threads.iter();
+        if let Some(dumping) = threads.next() {
+            put_note(&mut notes, NT_PRSTATUS, &self.prstatus(dumping, true));
+        }
+        put_note(&mut notes, NT_PRPSINFO, &self.prpsinfo());
+        let mut auxv = Vec::with_capacity(self.auxv.len() * 16);
+        for &(key, value) in self.auxv.iter() {
+            auxv.extend_from_slice(&key.to_le_bytes());
+            auxv.extend_from_slice(&value.to_le_bytes());
+        }
+        put_note(&mut notes, NT_AUXV, &auxv);
+        put_note(&mut notes, NT_FILE, &self.file_note());
+        for thread in threads {
+            put_note(&mut notes, NT_PRSTATUS, &self.prstatus(thread, false));
+        }
+        notes
+    }
+
+    /// struct elf_prstatus
+    fn prstatus(&self, thread: &ThreadStatus, dumping: bool) -> Vec<u8> {
+        let mut desc = alloc::vec![0u8; PRSTATUS_SIZE];
+        let sig = if dumping { self.sig } else { 0 };
+        put_at(&mut desc, 0, &sig.to_le_bytes()); // pr_info.si_signo
+        put_at(&mut desc, 12, &(sig as u16).to_le_bytes()); // pr_cursig
+        put_at(&mut desc, 16, &thread.sigpend.to_le_bytes());
+        put_at(&mut desc, 24, &thread.sighold.to_le_bytes());
+        put_at(&mut desc, 32, &(thread.tid as u32).to_le_bytes());
+        put_at(&mut desc, 36, &(self.ppid as u32).to_le_bytes());
+        put_at(&mut desc, 40, &(self.pgid as u32).to_le_bytes());
+        put_at(&mut desc, 44, &(self.sid as u32).to_le_bytes());
+        for (i, reg) in thread.regs.as_slice().iter().enumerate() {
+            put_at(&mut desc, PRSTATUS_REG_OFFSET + i * 8, &reg.to_le_bytes());
+        }
+        desc
+    }
+
+    /// struct elf_prpsinfo
+    fn prpsinfo(&self) -> Vec<u8> {
+        let mut desc = alloc::vec![0u8; PRPSINFO_SIZE];
+        desc[1] = b'R'; // pr_sname
+        put_at(&mut desc, 16, &self.uid.to_le_bytes());
+        put_at(&mut desc, 20, &self.gid.to_le_bytes());
+        put_at(&mut desc, 24, &(self.pid as u32).to_le_bytes());
+        put_at(&mut desc, 28, &(self.ppid as u32).to_le_bytes());
+        put_at(&mut desc, 32, &(self.pgid as u32).to_le_bytes());
+        put_at(&mut desc, 36, &(self.sid as u32).to_le_bytes());
+        put_at(&mut desc, 40, self.comm().as_bytes()); // pr_fname[16]
+        let args = &self.name.as_bytes()[..self.name.len().min(79)];
+        put_at(&mut desc, 56, args); // pr_psargs[80]
+        desc
+    }
+
+    /// NT_FILE: the file-backed PT_LOAD ranges of the executable
+    ///
+    /// Anonymous mappings have no backing file, so only the program image is
+    /// listed. Its program headers are found through AT_PHDR in the dump.
+    fn file_note(&self) -> Vec<u8> {
+        let mut ranges: Vec<(u64, u64, u64)> = Vec::new();
+        let aux = |key: u64| self.auxv.iter().find(|&&(k, _)| k == key).map(|&(_, v)| v);
+        if let (Some(phdr), Some(phent), Some(phnum)) = (aux(AT_PHDR), aux(AT_PHENT), aux(AT_PHNUM))
+        {
+            let mut phdrs = Vec::new();
+            for i in 0..phnum {
+                let mut ph = [0u8; PHDR_SIZE];
+                if phent < PHDR_SIZE as u64 || !self.read(phdr + i * phent, &mut ph) {
+                    break;
+                }
+                phdrs.push(ph);
+            }
+            let field = |ph: &[u8; PHDR_SIZE], at: usize| {
+                u64::from_le_bytes(ph[at..at + 8].try_into().unwrap_or_default())
+            };
+            let kind = |ph: &[u8; PHDR_SIZE]| u32::from_le_bytes([ph[0], ph[1], ph[2], ph[3]]);
+            // Load bias of a position-independent executable
+            let bias = phdrs
+                .iter()
+                .find(|ph| kind(ph) == PT_PHDR)
+                .map_or(0, |ph| phdr.wrapping_sub(field(ph, 16)));
+            for ph in phdrs.iter().filter(|ph| kind(ph) == PT_LOAD) {
+                let (offset, vaddr, filesz) = (field(ph, 8), field(ph, 16), field(ph, 32));
+                if filesz == 0 {
+                    continue;
+                }
+                let start = (vaddr + bias) & !(PAGE_SIZE - 1);
+                let end = page_align_up(vaddr + bias + filesz);
+                ranges.push((start, end, offset / PAGE_SIZE));
+            }
+        }
+
+        let mut desc = Vec::new();
+        desc.extend_from_slice(&(ranges.len() as u64).to_le_bytes());
+        desc.extend_from_slice(&PAGE_SIZE.to_le_bytes());
+        for &(start, end, page_offset) in ranges.iter() {
+            desc.extend_from_slice(&start.to_le_bytes());
+            desc.extend_from_slice(&end.to_le_bytes());
+            desc.extend_from_slice(&page_offset.to_le_bytes());
+        }
+        for _ in ranges.iter() {
+            desc.extend_from_slice(self.name.as_bytes());
+            desc.push(0);
+        }
+        desc
+    }
+
+    /// Read dumped memory at `addr`; false if it was not captured
+    fn read(&self, addr: u64, buf: &mut [u8]) -> bool {
+        self.segments.iter().any(|segment| {
+            let Some(offset) = addr.checked_sub(segment.start) else {
+                return false;
+            };
+            let offset = offset as usize;
+            match segment.data.get(offset..offset + buf.len()) {
+                Some(bytes) => {
+                    buf.copy_from_slice(bytes);
+                    true
+                }
+                None => false,
+            }
+        })
+    }
+}
+
+/// Copy the memory of an address space, at most `limit` bytes
+///
+/// Pages that are not mapped (untouched mmap or stack pages) read as zeros.
+/// Uncached pages are device memory and are never read.
+fn capture_memory(page_table: &ProcessPageTable, vmas: &[Vma], limit: u64) -> Vec<Segment> {
+    let pages = user_pages(page_table);
+    let regions = regions_of(&pages, vmas);
+
+    let phys_offset = crate::memory::physical_memory_offset().as_u64();
+    let mut budget = limit & !(PAGE_SIZE - 1);
+    let mut segments = Vec::with_capacity(regions.len());
+    for (start, end, flags) in regions {
+        let mut data = Vec::new();
+        let len = (end - start).min(budget);
+        if data.try_reserve_exact(len as usize).is_ok() {
+            budget -= len;
+            for page in (start..start + len).step_by(PAGE_SIZE as usize) {
+                let mapped = pages
+                    .binary_search_by_key(&page, |&(virt, _, _)| virt)
+                    .ok()
+                    .map(|i| pages[i])
+                    .filter(|&(_, _, flags)| !flags.contains(PageTableFlags::NO_CACHE));
+                match mapped {
+                    Some((_, phys, _)) => {
+                        // SAFETY: the frame is mapped in this address space, and the
+                        // kernel maps all physical memory at phys_offset
+                        let bytes = unsafe {
+                            core::slice::from_raw_parts(
+                                (phys_offset + phys) as *const u8,
+                                PAGE_SIZE as usize,
+                            )
+                        };
+                        data.extend_from_slice(bytes);
+                    }
+                    None => data.resize(data.len() + PAGE_SIZE as usize, 0),
+                }
+            }
+        }
+        segments.push(Segment {
+            start,
+            size: end - start,
+            flags,
+            data,
+        });
+    }
+    segments
+}
+
+fn page_align_up(addr: u64) -> u64 {
+    (addr + PAGE_SIZE - 1) & !(PAGE_SIZE - 1)
+}
+
+fn put_at(buf: &mut [u8], offset: usize, bytes: &[u8]) {
+    buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+/// Append an ELF note named "CORE"
+fn put_note(out: &mut Vec<u8>, kind: u32, desc: &[u8]) {
+    out.extend_from_slice(&5u32.to_le_bytes());
+    out.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+    out.extend_from_slice(&kind.to_le_bytes());
+    out.extend_from_slice(b"CORE\0\0\0\0");
+    out.extend_from_slice(desc);
+    out.resize((out.len() + 3) & !3, 0);
+}
+
+/// Append the ELF header of a core file with `phnum` program headers
+fn put_ehdr(out: &mut Vec<u8>, phnum: u16) {
+    // e_ident: ELFCLASS64, ELFDATA2LSB, EV_CURRENT, ELFOSABI_NONE
+    out.extend_from_slice(&[0x7f, b'E', b'L', b'F', 2, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    out.extend_from_slice(&ET_CORE.to_le_bytes());
+    out.extend_from_slice(&EM_MACHINE.to_le_bytes());
+    out.extend_from_slice(&1u32.to_le_bytes()); // e_version
+    out.extend_from_slice(&0u64.to_le_bytes()); // e_entry
+    out.extend_from_slice(&(EHDR_SIZE as u64).to_le_bytes()); // e_phoff
+    out.extend_from_slice(&0u64.to_le_bytes()); // e_shoff
+    out.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+    out.extend_from_slice(&(EHDR_SIZE as u16).to_le_bytes());
+    out.extend_from_slice(&(PHDR_SIZE as u16).to_le_bytes());
+    out.extend_from_slice(&phnum.to_le_bytes());
+    out.extend_from_slice(&64u16.to_le_bytes()); // e_shentsize
+    out.extend_from_slice(&0u16.to_le_bytes()); // e_shnum
+    out.extend_from_slice(&0u16.to_le_bytes()); // e_shstrndx
+}
+
+/// Append an Elf64_Phdr
+#[allow(clippy::too_many_arguments)]
+fn put_phdr(
+    out: &mut Vec<u8>,
+    kind: u32,
+    flags: u32,
+    offset: u64,
+    vaddr: u64,
+    filesz: u64,
+    memsz: u64,
+    align: u64,
+) {
+    out.extend_from_slice(&kind.to_le_bytes());
+    out.extend_from_slice(&flags.to_le_bytes());
+    out.extend_from_slice(&offset.to_le_bytes());
+    out.extend_from_slice(&vaddr.to_le_bytes());
+    out.extend_from_slice(&0u64.to_le_bytes()); // p_paddr
+    out.extend_from_slice(&filesz.to_le_bytes());
+    out.extend_from_slice(&memsz.to_le_bytes());
+    out.extend_from_slice(&align.to_le_bytes());
+}
+
+/// The first line of `PATTERN_FILE`, or `DEFAULT_PATTERN`
+fn core_pattern() -> String {
+    let fs_guard = crate::fs::ext2::root_fs_read();
+    let pattern = fs_guard.as_ref().and_then(|fs| {
+        let inode_num = fs.resolve_path(PATTERN_FILE).ok()?;
+        let inode = fs.read_inode(inode_num).ok()?;
+        let content = fs.read_file_content(&inode).ok()?;
+        let line = core::str::from_utf8(&content).ok()?.lines().next()?.trim();
+        (!line.is_empty()).then(|| String::from(line))
+    });
+    pattern.unwrap_or_else(|| String::from(DEFAULT_PATTERN))
+}
+
+/// Create or truncate `path` and write each (offset, bytes) chunk into it
+///
+/// The filesystem stays write-locked throughout, so the file only becomes
+/// visible to other processes once it is complete.
+fn write_core_file(path: &str, chunks: &[(u64, &[u8])]) -> Result<(), Ext2Error> {
+    use crate::fs::ext2;
+
+    let home = ext2::is_home_path(path);
+    let (mut fs_guard, fs_path) = if home {
+        (ext2::home_fs_write(), ext2::strip_home_prefix(path))
+    } else {
+        (ext2::root_fs_write(), path)
+    };
+    let fs = fs_guard.as_mut().ok_or(Ext2Error::Io)?;
+
+    let inode_num = match fs.resolve_path(fs_path) {
+        Ok(inode_num) => {
+            fs.truncate_file(inode_num, 0)?;
+            inode_num
+        }
+        Err(Ext2Error::NotFound) => {
+            let (parent, name) = fs_path.rsplit_once('/').ok_or(Ext2Error::InvalidArgument)?;
+            let parent_inode = fs.resolve_path(if parent.is_empty() { "/" } else { parent })?;
+            fs.create_file(parent_inode, name, 0o600)?
+        }
+        Err(e) => return Err(e),
+    };
+    for &(offset, bytes) in chunks {
+        fs.write_file_range(inode_num, offset, bytes)?;
+    }
+    Ok(())
+}
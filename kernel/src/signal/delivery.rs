@@ -9,7 +9,8 @@
 
 use super::constants::*;
 use super::types::*;
-use crate::process::{Process, ProcessState};
+use crate::process::ptrace::ElfGregs;
+use crate::process::Process;
 
 /// Check whether there is pending signal work for the delivery path to process.
 ///
@@ -23,6 +24,13 @@ pub fn has_deliverable_signals(process: &Process) -> bool {
     process.signals.has_deliverable_signals()
 }
 
+/// Check whether the return to userspace has signal work: deliverable signals,
+/// or a job-control stop that parks the thread.
+#[inline]
+pub fn has_return_work(process: &Process) -> bool {
+    process.signals.has_deliverable_signals() || crate::process::job_control::is_stopped(process)
+}
+
 /// Check whether a pending signal will actually be seen by userspace, so a blocking syscall
 /// must abort with EINTR.
 ///
@@ -33,6 +41,28 @@ pub fn has_interrupting_signals(process: &Process) -> bool {
     process.signals.has_interrupting_signals()
 }
 
+/// Queue a synchronous fault signal (SIGSEGV, SIGBUS, ...) for a user handler
+///
+/// Returns false when the signal is blocked or has no user handler. The fault
+/// handler then terminates the process itself: a blocked synchronous fault
+/// would only re-fault, and the default action is termination anyway. A
+/// default disposition that dumps core is still queued, so delivery records
+/// the faulting registers in the core file, and a traced process always
+/// queues it so its tracer sees the fault.
+pub fn queue_fault_signal(process: &mut Process, info: SigInfo) -> bool {
+    let sig = info.signo();
+    let handler = process.signals.get_handler(sig).handler;
+    if process.signals.is_blocked(sig) || handler == SIG_IGN {
+        return false;
+    }
+    if handler == SIG_DFL && process.ptrace.is_none() && !super::coredump::wants_core(process, sig)
+    {
+        return false;
+    }
+    process.signals.queue_signal(info);
+    true
+}
+
 /// Result of signal delivery
 pub enum SignalDeliveryResult {
     /// No signals were delivered
@@ -41,6 +71,12 @@ pub enum SignalDeliveryResult {
     Delivered,
     /// Process was terminated - caller should notify parent after releasing lock
     Terminated(ParentNotification),
+    /// Signal stopped a traced process - caller must park the thread with its
+    /// user registers saved until the tracer resumes it
+    Stopped,
+    /// Process is stopped for job control - caller must park the thread until
+    /// SIGCONT, then send the parent's SIGCHLD (if any) after releasing the lock
+    JobStopped(Option<crate::process::job_control::JobNotification>),
 }
 
 // =============================================================================
@@ -67,6 +103,11 @@ pub fn deliver_pending_signals(
     interrupt_frame: &mut x86_64::structures::idt::InterruptStackFrame,
     saved_regs: &mut crate::task::process_context::SavedRegisters,
 ) -> SignalDeliveryResult {
+    // Threads of a stopped process leave the scheduler until SIGCONT
+    if crate::process::job_control::is_stopped(process) {
+        return SignalDeliveryResult::JobStopped(None);
+    }
+
     // Process all deliverable signals in a loop (avoids unbounded recursion)
     loop {
         // Get next deliverable signal
@@ -75,8 +116,15 @@ pub fn deliver_pending_signals(
             None => return SignalDeliveryResult::NoAction,
         };
 
-        // Clear pending flag for this signal
-        process.signals.clear_pending(sig);
+        // Clear pending flag for this signal and take its siginfo
+        let info = process.signals.dequeue_signal(sig);
+
+        // A traced process stops for its tracer before the signal takes effect
+        if crate::process::ptrace::intercept_signal(process, sig) {
+            let regs = ElfGregs::from_frame(interrupt_frame, saved_regs);
+            crate::process::ptrace::signal_stop(process, info, regs);
+            return SignalDeliveryResult::Stopped;
+        }
 
         // Get the handler for this signal
         let action = *process.signals.get_handler(sig);
@@ -92,11 +140,15 @@ pub fn deliver_pending_signals(
         match action.handler {
             SIG_DFL => {
                 // Default action may terminate/stop the process
-                match deliver_default_action(process, sig) {
+                let regs = ElfGregs::from_frame(interrupt_frame, saved_regs);
+                match deliver_default_action(process, sig, regs) {
                     DeliverResult::Delivered => return SignalDeliveryResult::Delivered,
                     DeliverResult::Terminated(notification) => {
                         return SignalDeliveryResult::Terminated(notification)
                     }
+                    DeliverResult::Stopped(notification) => {
+                        return SignalDeliveryResult::JobStopped(notification)
+                    }
                     DeliverResult::Ignored => {
                         // Continue loop to check for more signals
                     }
@@ -113,7 +165,7 @@ pub fn deliver_pending_signals(
                     process,
                     interrupt_frame,
                     saved_regs,
-                    sig,
+                    &info,
                     handler_addr,
                     &action,
                 ) {
@@ -149,6 +201,11 @@ pub fn deliver_pending_signals(
     exception_frame: &mut crate::arch_impl::aarch64::exception_frame::Aarch64ExceptionFrame,
     saved_regs: &mut crate::task::process_context::SavedRegisters,
 ) -> SignalDeliveryResult {
+    // Threads of a stopped process leave the scheduler until SIGCONT
+    if crate::process::job_control::is_stopped(process) {
+        return SignalDeliveryResult::JobStopped(None);
+    }
+
     // Process all deliverable signals in a loop (avoids unbounded recursion)
     loop {
         // Get next deliverable signal
@@ -157,8 +214,15 @@ pub fn deliver_pending_signals(
             None => return SignalDeliveryResult::NoAction,
         };
 
-        // Clear pending flag for this signal
-        process.signals.clear_pending(sig);
+        // Clear pending flag for this signal and take its siginfo
+        let info = process.signals.dequeue_signal(sig);
+
+        // A traced process stops for its tracer before the signal takes effect
+        if crate::process::ptrace::intercept_signal(process, sig) {
+            let regs = ElfGregs::from_saved(saved_regs);
+            crate::process::ptrace::signal_stop(process, info, regs);
+            return SignalDeliveryResult::Stopped;
+        }
 
         // Get the handler for this signal
         let action = *process.signals.get_handler(sig);
@@ -174,11 +238,15 @@ pub fn deliver_pending_signals(
         match action.handler {
             SIG_DFL => {
                 // Default action may terminate/stop the process
-                match deliver_default_action(process, sig) {
+                let regs = ElfGregs::from_saved(saved_regs);
+                match deliver_default_action(process, sig, regs) {
                     DeliverResult::Delivered => return SignalDeliveryResult::Delivered,
                     DeliverResult::Terminated(notification) => {
                         return SignalDeliveryResult::Terminated(notification)
                     }
+                    DeliverResult::Stopped(notification) => {
+                        return SignalDeliveryResult::JobStopped(notification)
+                    }
                     DeliverResult::Ignored => {
                         // Continue loop to check for more signals
                     }
@@ -195,7 +263,7 @@ pub fn deliver_pending_signals(
                     process,
                     exception_frame,
                     saved_regs,
-                    sig,
+                    &info,
                     handler_addr,
                     &action,
                 ) {
@@ -219,11 +287,15 @@ pub enum DeliverResult {
     Ignored,
     /// Process was terminated - caller should notify parent after releasing lock
     Terminated(ParentNotification),
+    /// Process was stopped - caller should notify parent after releasing lock
+    Stopped(Option<crate::process::job_control::JobNotification>),
 }
 
 /// Deliver a signal's default action
 /// Returns DeliverResult indicating what action was taken
-fn deliver_default_action(process: &mut Process, sig: u32) -> DeliverResult {
+///
+/// `regs` are the interrupted user registers, recorded if the signal dumps core.
+fn deliver_default_action(process: &mut Process, sig: u32, regs: ElfGregs) -> DeliverResult {
     match default_action(sig) {
         SignalDefaultAction::Terminate => {
             crate::serial_println!(
@@ -261,17 +333,23 @@ fn deliver_default_action(process: &mut Process, sig: u32) -> DeliverResult {
             }
         }
         SignalDefaultAction::CoreDump => {
+            // Memory must be copied before terminate() releases the address space
+            let dump = super::coredump::capture(process, sig, regs);
             crate::serial_println!(
-                "[signal] Process {} ({}) killed (core dump) by signal {} ({})",
+                "[signal] Process {} ({}) killed by signal {} ({}){}",
                 process.id.as_u64(),
                 process.name,
                 sig,
-                signal_name(sig)
+                signal_name(sig),
+                if dump.is_some() { " (core dumped)" } else { "" }
             );
-            // Core dump not implemented, just terminate
-            // The 0x80 flag indicates core dump
+            // The 0x80 flag tells wait() that a core file was written
+            let core_flag = if dump.is_some() { 0x80 } else { 0 };
             crate::trace_count!(crate::tracing::providers::teardown::TEARDOWN_ENTRY_SIGNAL);
-            process.terminate(-((sig as i32) | 0x80));
+            process.terminate(-((sig as i32) | core_flag));
+            if let Some(dump) = dump {
+                super::coredump::schedule(dump);
+            }
 
             // CRITICAL: Also mark the scheduler's copy of the thread as terminated.
             if let Some(ref thread) = process.main_thread {
@@ -299,23 +377,17 @@ fn deliver_default_action(process: &mut Process, sig: u32) -> DeliverResult {
                 sig,
                 signal_name(sig)
             );
-            process.set_blocked();
-            DeliverResult::Delivered
+            DeliverResult::Stopped(crate::process::job_control::stop(process, sig))
         }
         SignalDefaultAction::Continue => {
-            log::info!(
-                "Process {} continued by signal {} ({})",
-                process.id.as_u64(),
+            // SIGCONT continued the process when it was sent
+            log::debug!(
+                "Signal {} ({}) needs no action by process {}",
                 sig,
-                signal_name(sig)
+                signal_name(sig),
+                process.id.as_u64()
             );
-            // Only change state if process was stopped
-            if matches!(process.state, ProcessState::Blocked) {
-                process.set_ready();
-                DeliverResult::Delivered
-            } else {
-                DeliverResult::Ignored
-            }
+            DeliverResult::Ignored
         }
         SignalDefaultAction::Ignore => {
             log::debug!(
@@ -342,10 +414,11 @@ fn deliver_to_user_handler_x86_64(
     process: &mut Process,
     interrupt_frame: &mut x86_64::structures::idt::InterruptStackFrame,
     saved_regs: &mut crate::task::process_context::SavedRegisters,
-    sig: u32,
+    info: &SigInfo,
     handler_addr: u64,
     action: &SignalAction,
 ) -> bool {
+    let sig = info.signo();
     // Get current user stack pointer from interrupt frame
     let current_rsp = interrupt_frame.stack_pointer.as_u64();
     let original_rsp = current_rsp;
@@ -357,6 +430,7 @@ fn deliver_to_user_handler_x86_64(
         && process.signals.alt_stack.size > 0
         && !process.signals.alt_stack.on_stack; // Don't nest on alt stack
 
+    let uc_stack = process.signals.alt_stack.to_stack_t();
     let user_rsp = if use_alt_stack {
         // Use alternate stack - stack grows down, so start at top (base + size)
         let alt_top = process.signals.alt_stack.base + process.signals.alt_stack.size as u64;
@@ -373,41 +447,25 @@ fn deliver_to_user_handler_x86_64(
         current_rsp
     };
 
-    // Calculate space needed for signal frame (and optionally trampoline)
-    let frame_size = SignalFrame::SIZE as u64;
-
     // Check if the handler provides a restorer function (SA_RESTORER flag)
     // If so, use it instead of writing trampoline to the stack.
     // This is essential for signals delivered on alternate stacks where the
     // stack may not be executable (NX bit set).
     let use_restorer = (action.flags & super::constants::SA_RESTORER) != 0 && action.restorer != 0;
 
-    let (frame_rsp, return_addr) = if use_restorer {
+    // Lay out signal frame, siginfo, ucontext (and optionally trampoline)
+    let layout = FrameLayout::below(user_rsp, !use_restorer);
+    let frame_rsp = layout.frame;
+
+    let return_addr = if use_restorer {
         // Use the restorer function provided by the application/libc
-        // Only allocate space for the signal frame (no trampoline needed)
-        let frame_rsp = (user_rsp - frame_size) & !0xF; // 16-byte align
         log::debug!("Using SA_RESTORER: restorer={:#x}", action.restorer);
-        (frame_rsp, action.restorer)
+        action.restorer
     } else {
         // Fall back to writing trampoline on the stack
         // This works when the stack is executable (main stack without NX)
-        let trampoline_size = super::trampoline::SIGNAL_TRAMPOLINE_SIZE as u64;
-        let total_size = frame_size + trampoline_size;
-        let frame_rsp = (user_rsp - total_size) & !0xF; // 16-byte align
-        let trampoline_rsp = frame_rsp + frame_size;
-
-        // Write trampoline code to user stack
-        // SAFETY: We're writing to user memory that should be valid stack space
-        unsafe {
-            let trampoline_ptr = trampoline_rsp as *mut u8;
-            core::ptr::copy_nonoverlapping(
-                super::trampoline::SIGNAL_TRAMPOLINE.as_ptr(),
-                trampoline_ptr,
-                super::trampoline::SIGNAL_TRAMPOLINE_SIZE,
-            );
-        }
-
-        (frame_rsp, trampoline_rsp)
+        layout.write_trampoline();
+        layout.trampoline
     };
 
     // Build signal frame with saved context
@@ -422,8 +480,8 @@ fn deliver_to_user_handler_x86_64(
 
         // Signal info
         signal: sig as u64,
-        siginfo_ptr: 0,  // Not implemented yet
-        ucontext_ptr: 0, // Not implemented yet
+        siginfo_ptr: layout.siginfo,
+        ucontext_ptr: layout.ucontext,
 
         // Save current execution state
         saved_rip: interrupt_frame.instruction_pointer.as_u64(),
@@ -451,12 +509,13 @@ fn deliver_to_user_handler_x86_64(
         saved_blocked: process.signals.blocked,
     };
 
-    // Write signal frame to user stack
+    // Write signal frame, siginfo and ucontext to user stack
     // SAFETY: We're writing to user memory that should be valid stack space
     unsafe {
         let frame_ptr = frame_rsp as *mut SignalFrame;
         core::ptr::write_volatile(frame_ptr, signal_frame);
     }
+    layout.write_handler_context(&signal_frame, info, uc_stack);
 
     // Block signals during handler execution
     if (action.flags & SA_NODEFER) == 0 {
@@ -504,8 +563,8 @@ fn deliver_to_user_handler_x86_64(
     // Set up arguments for signal handler
     // void handler(int signum, siginfo_t *info, void *ucontext)
     saved_regs.rdi = sig as u64; // First argument: signal number
-    saved_regs.rsi = 0; // Second argument: siginfo_t* (not implemented)
-    saved_regs.rdx = 0; // Third argument: ucontext_t* (not implemented)
+    saved_regs.rsi = layout.siginfo; // Second argument: siginfo_t*
+    saved_regs.rdx = layout.ucontext; // Third argument: ucontext_t*
 
     if use_alt_stack {
         log::info!(
@@ -549,10 +608,11 @@ fn deliver_to_user_handler_aarch64(
     process: &mut Process,
     exception_frame: &mut crate::arch_impl::aarch64::exception_frame::Aarch64ExceptionFrame,
     saved_regs: &mut crate::task::process_context::SavedRegisters,
-    sig: u32,
+    info: &SigInfo,
     handler_addr: u64,
     action: &SignalAction,
 ) -> bool {
+    let sig = info.signo();
     // Get current user stack pointer from saved registers
     // On ARM64, user SP is in SP_EL0, which we save in saved_regs.sp
     let current_sp = saved_regs.sp;
@@ -565,6 +625,7 @@ fn deliver_to_user_handler_aarch64(
         && process.signals.alt_stack.size > 0
         && !process.signals.alt_stack.on_stack; // Don't nest on alt stack
 
+    let uc_stack = process.signals.alt_stack.to_stack_t();
     let user_sp = if use_alt_stack {
         // Use alternate stack - stack grows down, so start at top (base + size)
         let alt_top = process.signals.alt_stack.base + process.signals.alt_stack.size as u64;
@@ -581,39 +642,23 @@ fn deliver_to_user_handler_aarch64(
         current_sp
     };
 
-    // Calculate space needed for signal frame (and optionally trampoline)
-    let frame_size = SignalFrame::SIZE as u64;
-
     // Check if the handler provides a restorer function (SA_RESTORER flag)
     // If so, use it instead of writing trampoline to the stack.
     let use_restorer = (action.flags & super::constants::SA_RESTORER) != 0 && action.restorer != 0;
 
-    let (frame_sp, return_addr) = if use_restorer {
+    // Lay out signal frame, siginfo, ucontext (and optionally trampoline)
+    let layout = FrameLayout::below(user_sp, !use_restorer);
+    let frame_sp = layout.frame;
+
+    let return_addr = if use_restorer {
         // Use the restorer function provided by the application/libc
-        // Only allocate space for the signal frame (no trampoline needed)
-        let frame_sp = (user_sp - frame_size) & !0xF; // 16-byte align
         log::debug!("Using SA_RESTORER: restorer={:#x}", action.restorer);
-        (frame_sp, action.restorer)
+        action.restorer
     } else {
         // Fall back to writing trampoline on the stack
         // This works when the stack is executable (main stack without NX)
-        let trampoline_size = super::trampoline::SIGNAL_TRAMPOLINE_SIZE as u64;
-        let total_size = frame_size + trampoline_size;
-        let frame_sp = (user_sp - total_size) & !0xF; // 16-byte align
-        let trampoline_sp = frame_sp + frame_size;
-
-        // Write trampoline code to user stack
-        // SAFETY: We're writing to user memory that should be valid stack space
-        unsafe {
-            let trampoline_ptr = trampoline_sp as *mut u8;
-            core::ptr::copy_nonoverlapping(
-                super::trampoline::SIGNAL_TRAMPOLINE.as_ptr(),
-                trampoline_ptr,
-                super::trampoline::SIGNAL_TRAMPOLINE_SIZE,
-            );
-        }
-
-        (frame_sp, trampoline_sp)
+        layout.write_trampoline();
+        layout.trampoline
     };
 
     // Build signal frame with saved context
@@ -661,8 +706,8 @@ fn deliver_to_user_handler_aarch64(
 
         // Signal info
         signal: sig as u64,
-        siginfo_ptr: 0,  // Not implemented yet
-        ucontext_ptr: 0, // Not implemented yet
+        siginfo_ptr: layout.siginfo,
+        ucontext_ptr: layout.ucontext,
 
         // Save current execution state (ARM64 specific)
         saved_pc: saved_regs.elr,      // Program counter (ELR_EL1)
@@ -676,12 +721,13 @@ fn deliver_to_user_handler_aarch64(
         saved_blocked: process.signals.blocked,
     };
 
-    // Write signal frame to user stack
+    // Write signal frame, siginfo and ucontext to user stack
     // SAFETY: We're writing to user memory that should be valid stack space
     unsafe {
         let frame_ptr = frame_sp as *mut SignalFrame;
         core::ptr::write_volatile(frame_ptr, signal_frame);
     }
+    layout.write_handler_context(&signal_frame, info, uc_stack);
 
     // Block signals during handler execution
     if (action.flags & SA_NODEFER) == 0 {
@@ -708,11 +754,11 @@ fn deliver_to_user_handler_aarch64(
     // Set up arguments for signal handler (ARM64 ABI: X0-X2)
     // void handler(int signum, siginfo_t *info, void *ucontext)
     exception_frame.x0 = sig as u64; // First argument: signal number
-    exception_frame.x1 = 0; // Second argument: siginfo_t* (not implemented)
-    exception_frame.x2 = 0; // Third argument: ucontext_t* (not implemented)
+    exception_frame.x1 = layout.siginfo; // Second argument: siginfo_t*
+    exception_frame.x2 = layout.ucontext; // Third argument: ucontext_t*
     saved_regs.x0 = sig as u64;
-    saved_regs.x1 = 0;
-    saved_regs.x2 = 0;
+    saved_regs.x1 = layout.siginfo;
+    saved_regs.x2 = layout.ucontext;
 
     if use_alt_stack {
         log::info!(
@@ -737,6 +783,79 @@ fn deliver_to_user_handler_aarch64(
     true
 }
 
+// =============================================================================
+// Signal Frame Layout (Architecture-Independent)
+// =============================================================================
+
+/// User stack addresses used by one handler invocation
+///
+/// From the new stack pointer upwards: the SignalFrame, the siginfo_t and
+/// ucontext_t passed to the handler, then the sigreturn trampoline when the
+/// handler has no SA_RESTORER. Every part starts 16-byte aligned.
+pub(crate) struct FrameLayout {
+    pub frame: u64,
+    pub siginfo: u64,
+    pub ucontext: u64,
+    pub trampoline: u64,
+}
+
+impl FrameLayout {
+    /// Reserve space below `stack_top`
+    pub(crate) fn below(stack_top: u64, with_trampoline: bool) -> Self {
+        const fn align16(size: usize) -> u64 {
+            ((size + 15) & !15) as u64
+        }
+        let frame_size = align16(SignalFrame::SIZE);
+        let siginfo_size = align16(core::mem::size_of::<SigInfo>());
+        let ucontext_size = align16(core::mem::size_of::<UContext>());
+        let trampoline_size = if with_trampoline {
+            super::trampoline::SIGNAL_TRAMPOLINE_SIZE as u64
+        } else {
+            0
+        };
+
+        let total = frame_size + siginfo_size + ucontext_size + trampoline_size;
+        let frame = (stack_top - total) & !0xF; // 16-byte align
+        FrameLayout {
+            frame,
+            siginfo: frame + frame_size,
+            ucontext: frame + frame_size + siginfo_size,
+            trampoline: frame + frame_size + siginfo_size + ucontext_size,
+        }
+    }
+
+    /// Copy the sigreturn trampoline to the user stack
+    pub(crate) fn write_trampoline(&self) {
+        // SAFETY: We're writing to user memory that should be valid stack space
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                super::trampoline::SIGNAL_TRAMPOLINE.as_ptr(),
+                self.trampoline as *mut u8,
+                super::trampoline::SIGNAL_TRAMPOLINE_SIZE,
+            );
+        }
+    }
+
+    /// Write the handler's siginfo_t and ucontext_t to the user stack
+    ///
+    /// The ucontext mirrors the registers saved in `frame`; sigreturn reads
+    /// it back so handlers can change the context they return to.
+    pub(crate) fn write_handler_context(
+        &self,
+        frame: &SignalFrame,
+        info: &SigInfo,
+        uc_stack: StackT,
+    ) {
+        let fault_addr = if info.is_fault() { info.addr() } else { 0 };
+        let ucontext = UContext::from_signal_frame(frame, uc_stack, fault_addr);
+        // SAFETY: We're writing to user memory that should be valid stack space
+        unsafe {
+            core::ptr::write_volatile(self.siginfo as *mut SigInfo, *info);
+            core::ptr::write_volatile(self.ucontext as *mut UContext, ucontext);
+        }
+    }
+}
+
 // =============================================================================
 // Parent Notification (Architecture-Independent)
 // =============================================================================
@@ -780,10 +899,16 @@ pub fn notify_parent_of_termination_deferred(notification: &ParentNotification)
             return;
         };
 
+        // Describe how the child ended for the parent's SIGCHLD siginfo
+        let child_info = manager.get_process(child_pid).map_or_else(
+            || SigInfo::new(SIGCHLD, CLD_KILLED),
+            |child| SigInfo::child(child_pid.as_u64(), child.uid, child.exit_code.unwrap_or(0)),
+        );
+
         // Find parent process and send SIGCHLD
         if let Some(parent_process) = manager.get_process_mut(parent_pid) {
             // Send SIGCHLD to parent
-            parent_process.signals.set_pending(SIGCHLD);
+            parent_process.signals.queue_signal(child_info);
             log::debug!(
                 "notify_parent_of_termination_deferred: sent SIGCHLD to parent {} for child {} termination",
                 parent_pid.as_u64(),
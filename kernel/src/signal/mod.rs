@@ -5,11 +5,13 @@
 //! - Per-process signal state (pending, blocked, handlers)
 //! - Signal delivery to userspace handlers
 //! - Signal trampoline for returning from handlers
+//! - ELF core dumps for signals whose default action is CoreDump
 //!
 //! Signal delivery occurs at the return-to-userspace boundary in
 //! `interrupts/context_switch.rs`.
 
 pub mod constants;
+pub mod coredump;
 pub mod delivery;
 pub mod trampoline;
 pub mod types;
@@ -2,6 +2,7 @@
 
 use super::constants::*;
 use crate::memory::slab::{SlabBox, SIGNAL_HANDLERS_SLAB};
+use alloc::vec::Vec;
 
 /// Alternate signal stack configuration (matches Linux stack_t)
 ///
@@ -48,6 +49,163 @@ pub struct AltStack {
     pub on_stack: bool,
 }
 
+impl AltStack {
+    /// Describe this configuration as a stack_t (sigaltstack, uc_stack)
+    pub fn to_stack_t(&self) -> StackT {
+        StackT {
+            ss_sp: self.base,
+            ss_flags: if self.on_stack {
+                SS_ONSTACK as i32
+            } else if self.flags & SS_DISABLE != 0 {
+                SS_DISABLE as i32
+            } else {
+                0
+            },
+            _pad: 0,
+            ss_size: self.size,
+        }
+    }
+}
+
+/// Signal information (matches Linux siginfo_t, 128 bytes)
+///
+/// Recorded when a signal is generated and copied to the user stack for the
+/// handler. The `_sifields` union is kept as raw words; the constructors and
+/// accessors below read and write the members Breenix fills in:
+/// - kill, tkill: si_pid, si_uid
+/// - sigqueue: si_pid, si_uid, si_value
+/// - SIGCHLD: si_pid, si_uid, si_status
+/// - faults: si_addr
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct SigInfo {
+    pub si_signo: i32,
+    pub si_errno: i32,
+    pub si_code: i32,
+    _pad: i32,
+    /// The `_sifields` union (offset 16)
+    fields: [u64; 14],
+}
+
+impl SigInfo {
+    /// Create a record with only si_signo and si_code set
+    pub const fn new(sig: u32, code: i32) -> Self {
+        SigInfo {
+            si_signo: sig as i32,
+            si_errno: 0,
+            si_code: code,
+            _pad: 0,
+            fields: [0; 14],
+        }
+    }
+
+    /// Signal generated by the kernel itself (timers, tty, pipes)
+    pub const fn kernel(sig: u32) -> Self {
+        Self::new(sig, SI_KERNEL)
+    }
+
+    /// Signal sent by kill() from process `pid` running as `uid`
+    pub fn user(sig: u32, pid: u64, uid: u32) -> Self {
+        let mut info = Self::new(sig, SI_USER);
+        info.set_sender(pid, uid);
+        info
+    }
+
+    /// Signal sent by sigqueue() carrying `value` (the sigval union)
+    pub fn queue(sig: u32, pid: u64, uid: u32, value: u64) -> Self {
+        let mut info = Self::new(sig, SI_QUEUE);
+        info.set_sender(pid, uid);
+        info.fields[1] = value;
+        info
+    }
+
+    /// Signal sent to one thread by tkill() or tgkill()
+    pub fn tkill(sig: u32, pid: u64, uid: u32) -> Self {
+        let mut info = Self::new(sig, SI_TKILL);
+        info.set_sender(pid, uid);
+        info
+    }
+
+    /// Synchronous fault at `addr` (SIGSEGV, SIGBUS, ...)
+    pub fn fault(sig: u32, code: i32, addr: u64) -> Self {
+        let mut info = Self::new(sig, code);
+        info.fields[0] = addr;
+        info
+    }
+
+    /// SIGCHLD for child `pid` that finished with `exit_code`
+    ///
+    /// Uses the process exit code convention: non-negative values are exit
+    /// statuses, `-sig` means killed by `sig`, and bit 0x80 marks a core dump.
+    pub fn child(pid: u64, uid: u32, exit_code: i32) -> Self {
+        let (code, status) = if exit_code >= 0 {
+            (CLD_EXITED, exit_code & 0xff)
+        } else if (-exit_code) & 0x80 != 0 {
+            (CLD_DUMPED, (-exit_code) & 0x7f)
+        } else {
+            (CLD_KILLED, -exit_code)
+        };
+        let mut info = Self::new(SIGCHLD, code);
+        info.set_sender(pid, uid);
+        info.fields[1] = status as u32 as u64;
+        info
+    }
+
+    /// SIGCHLD for child `pid` stopped by `sig` (CLD_STOPPED) or continued
+    /// (CLD_CONTINUED, with SIGCONT as the status)
+    pub fn child_job(pid: u64, uid: u32, code: i32, sig: u32) -> Self {
+        let mut info = Self::new(SIGCHLD, code);
+        info.set_sender(pid, uid);
+        info.fields[1] = sig as u64;
+        info
+    }
+
+    /// Set si_pid and si_uid (kill, sigqueue and SIGCHLD layouts)
+    pub fn set_sender(&mut self, pid: u64, uid: u32) {
+        self.fields[0] = (pid as u32 as u64) | ((uid as u64) << 32);
+    }
+
+    /// Signal number this record describes
+    #[inline]
+    pub fn signo(&self) -> u32 {
+        self.si_signo as u32
+    }
+
+    /// si_pid (kill, sigqueue and SIGCHLD layouts)
+    #[allow(dead_code)] // Part of SigInfo API, used by tests and diagnostics
+    pub fn pid(&self) -> i32 {
+        self.fields[0] as u32 as i32
+    }
+
+    /// si_uid (kill, sigqueue and SIGCHLD layouts)
+    #[allow(dead_code)] // Part of SigInfo API, used by tests and diagnostics
+    pub fn uid(&self) -> u32 {
+        (self.fields[0] >> 32) as u32
+    }
+
+    /// si_addr (fault layout)
+    pub fn addr(&self) -> u64 {
+        self.fields[0]
+    }
+
+    /// si_value (sigqueue layout)
+    #[allow(dead_code)] // Part of SigInfo API, used by tests and diagnostics
+    pub fn value(&self) -> u64 {
+        self.fields[1]
+    }
+
+    /// si_status (SIGCHLD layout)
+    #[allow(dead_code)] // Part of SigInfo API, used by tests and diagnostics
+    pub fn status(&self) -> i32 {
+        self.fields[1] as u32 as i32
+    }
+
+    /// Whether this is a synchronous fault, so si_addr is meaningful
+    pub fn is_fault(&self) -> bool {
+        matches!(self.signo(), SIGSEGV | SIGBUS | SIGILL | SIGFPE | SIGTRAP) && self.si_code > 0
+    }
+}
+
 /// Default action for a signal
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SignalDefaultAction {
@@ -153,6 +311,11 @@ pub struct SignalState {
     /// This is set when sigsuspend temporarily changes the mask and a signal is delivered.
     /// The sigreturn syscall checks this and restores the original mask.
     pub sigsuspend_saved_mask: Option<u64>,
+    /// siginfo records for pending signals, in arrival order
+    /// Standard signals have at most one record; real-time signals have one
+    /// per queued instance. Signals made pending with set_pending() have no
+    /// record and are delivered with SI_KERNEL.
+    pending_info: Vec<SigInfo>,
 }
 
 impl Default for SignalState {
@@ -172,6 +335,7 @@ impl Default for SignalState {
             handlers,
             alt_stack: AltStack::default(),
             sigsuspend_saved_mask: None,
+            pending_info: Vec::new(),
         }
     }
 }
@@ -237,17 +401,80 @@ impl SignalState {
         }
     }
 
+    /// Mark a signal as pending with its siginfo
+    ///
+    /// Standard signals do not queue: if the signal is already pending, the
+    /// new record is dropped and the first sender's information is delivered.
+    /// Real-time signals queue every instance, up to SIGQUEUE_MAX records.
+    ///
+    /// Returns false if a real-time signal was refused because the queue is full.
+    pub fn queue_signal(&mut self, info: SigInfo) -> bool {
+        let sig = info.signo();
+        if !is_valid_signal(sig) {
+            return true;
+        }
+        if sig >= SIGRTMIN {
+            let queued = self
+                .pending_info
+                .iter()
+                .filter(|queued| queued.signo() >= SIGRTMIN)
+                .count();
+            if queued >= SIGQUEUE_MAX {
+                return false;
+            }
+            self.pending_info.push(info);
+        } else if !self.is_pending(sig) {
+            self.pending_info.retain(|queued| queued.signo() != sig);
+            self.pending_info.push(info);
+        }
+        self.pending |= sig_mask(sig);
+        true
+    }
+
+    /// Take a pending signal for delivery, returning its siginfo
+    ///
+    /// Takes the oldest record for the signal. The pending bit is cleared
+    /// once no queued instances remain. Signals raised without a record get
+    /// an SI_KERNEL siginfo.
+    pub fn dequeue_signal(&mut self, sig: u32) -> SigInfo {
+        let info = match self
+            .pending_info
+            .iter()
+            .position(|queued| queued.signo() == sig)
+        {
+            Some(index) => self.pending_info.remove(index),
+            None => SigInfo::kernel(sig),
+        };
+        if !self.pending_info.iter().any(|queued| queued.signo() == sig) {
+            self.clear_pending(sig);
+        }
+        info
+    }
+
+    /// Take the lowest-numbered pending signal in `set`, ignoring the mask
+    ///
+    /// Used by sigtimedwait(), which accepts signals that are blocked.
+    pub fn dequeue_signal_in(&mut self, set: u64) -> Option<SigInfo> {
+        let waiting = self.pending & set;
+        if waiting == 0 {
+            return None;
+        }
+        Some(self.dequeue_signal(waiting.trailing_zeros() + 1))
+    }
+
     /// Clear a pending signal
     #[inline]
     pub fn clear_pending(&mut self, sig: u32) {
         if is_valid_signal(sig) {
             self.pending &= !sig_mask(sig);
+            if !self.pending_info.is_empty() {
+                self.pending_info.retain(|queued| queued.signo() != sig);
+            }
         }
     }
 
     /// Check if a signal is pending
     #[inline]
-    #[allow(dead_code)] // Part of complete signal API, will be used for debugging/diagnostics
     pub fn is_pending(&self, sig: u32) -> bool {
         (self.pending & sig_mask(sig)) != 0
     }
@@ -317,6 +544,7 @@ impl SignalState {
             handlers: self.handlers.clone(),
             alt_stack: self.alt_stack,   // Alt stack is inherited per POSIX
             sigsuspend_saved_mask: None, // Child doesn't inherit sigsuspend state
+            pending_info: Vec::new(),
         }
     }
 
@@ -326,6 +554,7 @@ impl SignalState {
     #[allow(dead_code)] // Will be used when exec() implementation is complete
     pub fn exec_reset(&mut self) {
         self.pending = 0;
+        self.pending_info.clear();
         for handler in self.handlers.iter_mut() {
             if handler.is_handler() {
                 *handler = SignalAction::default();
@@ -356,8 +585,8 @@ pub struct SignalFrame {
 
     // Arguments for signal handler (in registers, but saved here too)
     pub signal: u64,       // Signal number (also in RDI)
-    pub siginfo_ptr: u64,  // Pointer to siginfo_t (also in RSI) - future
-    pub ucontext_ptr: u64, // Pointer to ucontext_t (also in RDX) - future
+    pub siginfo_ptr: u64,  // Pointer to siginfo_t (also in RSI)
+    pub ucontext_ptr: u64, // Pointer to ucontext_t (also in RDX)
 
     // Saved CPU state to restore after handler
     pub saved_rip: u64,
@@ -393,6 +622,120 @@ impl SignalFrame {
     /// Magic number for frame integrity validation
     /// This prevents privilege escalation via forged signal frames
     pub const MAGIC: u64 = 0xDEAD_BEEF_CAFE_BABE;
+
+    /// Take the registers and signal mask from a ucontext_t
+    ///
+    /// Used by sigreturn so that changes a handler makes to its ucontext
+    /// (for example advancing the PC past a faulting instruction) take effect.
+    pub fn apply_ucontext(&mut self, uc: &UContext) {
+        let mc = &uc.uc_mcontext;
+        self.saved_rip = mc.rip;
+        self.saved_rsp = mc.rsp;
+        self.saved_rflags = mc.eflags;
+        self.saved_rax = mc.rax;
+        self.saved_rbx = mc.rbx;
+        self.saved_rcx = mc.rcx;
+        self.saved_rdx = mc.rdx;
+        self.saved_rdi = mc.rdi;
+        self.saved_rsi = mc.rsi;
+        self.saved_rbp = mc.rbp;
+        self.saved_r8 = mc.r8;
+        self.saved_r9 = mc.r9;
+        self.saved_r10 = mc.r10;
+        self.saved_r11 = mc.r11;
+        self.saved_r12 = mc.r12;
+        self.saved_r13 = mc.r13;
+        self.saved_r14 = mc.r14;
+        self.saved_r15 = mc.r15;
+        self.saved_blocked = uc.uc_sigmask;
+    }
+}
+
+/// Machine context (matches Linux struct sigcontext on x86_64)
+///
+/// Register order follows the glibc `gregs[]` indices (REG_R8 .. REG_CR2).
+/// No FPU state is saved, so `fpstate` is always null.
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MContext {
+    pub r8: u64,
+    pub r9: u64,
+    pub r10: u64,
+    pub r11: u64,
+    pub r12: u64,
+    pub r13: u64,
+    pub r14: u64,
+    pub r15: u64,
+    pub rdi: u64,
+    pub rsi: u64,
+    pub rbp: u64,
+    pub rbx: u64,
+    pub rdx: u64,
+    pub rax: u64,
+    pub rcx: u64,
+    pub rsp: u64,
+    pub rip: u64,
+    pub eflags: u64,
+    pub cs: u16,
+    pub gs: u16,
+    pub fs: u16,
+    pub ss: u16,
+    pub err: u64,
+    pub trapno: u64,
+    pub oldmask: u64,
+    /// Faulting address for SIGSEGV/SIGBUS
+    pub cr2: u64,
+    pub fpstate: u64,
+    pub reserved: [u64; 8],
+}
+
+/// User context passed as the third handler argument (matches Linux struct ucontext)
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct UContext {
+    pub uc_flags: u64,
+    pub uc_link: u64,
+    pub uc_stack: StackT,
+    pub uc_mcontext: MContext,
+    pub uc_sigmask: u64,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl UContext {
+    /// Build the context for a handler from the state saved in its signal frame
+    pub fn from_signal_frame(frame: &SignalFrame, uc_stack: StackT, fault_addr: u64) -> Self {
+        UContext {
+            uc_flags: 0,
+            uc_link: 0,
+            uc_stack,
+            uc_mcontext: MContext {
+                r8: frame.saved_r8,
+                r9: frame.saved_r9,
+                r10: frame.saved_r10,
+                r11: frame.saved_r11,
+                r12: frame.saved_r12,
+                r13: frame.saved_r13,
+                r14: frame.saved_r14,
+                r15: frame.saved_r15,
+                rdi: frame.saved_rdi,
+                rsi: frame.saved_rsi,
+                rbp: frame.saved_rbp,
+                rbx: frame.saved_rbx,
+                rdx: frame.saved_rdx,
+                rax: frame.saved_rax,
+                rcx: frame.saved_rcx,
+                rsp: frame.saved_rsp,
+                rip: frame.saved_rip,
+                eflags: frame.saved_rflags,
+                oldmask: frame.saved_blocked,
+                cr2: fault_addr,
+                ..MContext::default()
+            },
+            uc_sigmask: frame.saved_blocked,
+        }
+    }
 }
 
 /// Signal frame structure pushed to user stack when delivering a signal (ARM64)
@@ -414,8 +757,8 @@ pub struct SignalFrame {
 
     // Arguments for signal handler
     pub signal: u64,       // Signal number (also in x0)
-    pub siginfo_ptr: u64,  // Pointer to siginfo_t (also in x1) - future
-    pub ucontext_ptr: u64, // Pointer to ucontext_t (also in x2) - future
+    pub siginfo_ptr: u64,  // Pointer to siginfo_t (also in x1)
+    pub ucontext_ptr: u64, // Pointer to ucontext_t (also in x2)
 
     // Saved CPU state to restore after handler
     pub saved_pc: u64,     // Program counter (ELR_EL1)
@@ -437,6 +780,76 @@ impl SignalFrame {
     /// Magic number for frame integrity validation
     /// This prevents privilege escalation via forged signal frames
     pub const MAGIC: u64 = 0xDEAD_BEEF_CAFE_BABE;
+
+    /// Take the registers and signal mask from a ucontext_t
+    ///
+    /// Used by sigreturn so that changes a handler makes to its ucontext
+    /// (for example advancing the PC past a faulting instruction) take effect.
+    pub fn apply_ucontext(&mut self, uc: &UContext) {
+        let mc = &uc.uc_mcontext;
+        self.saved_pc = mc.pc;
+        self.saved_sp = mc.sp;
+        self.saved_pstate = mc.pstate;
+        self.saved_x = mc.regs;
+        self.saved_blocked = uc.uc_sigmask;
+    }
+}
+
+/// Machine context (matches Linux struct sigcontext on ARM64)
+///
+/// Linux reserves 4096 bytes after `pstate` for a list of extension records
+/// (FP/SIMD state and others). No extension state is saved, so only the
+/// terminating null record is pushed.
+#[cfg(target_arch = "aarch64")]
+#[repr(C, align(16))]
+#[derive(Debug, Clone, Copy)]
+pub struct MContext {
+    /// Faulting address for SIGSEGV/SIGBUS
+    pub fault_address: u64,
+    pub regs: [u64; 31],
+    pub sp: u64,
+    pub pc: u64,
+    pub pstate: u64,
+    _pad: u64,
+    /// Null _aarch64_ctx header terminating the (empty) record list
+    pub reserved: [u64; 2],
+}
+
+/// User context passed as the third handler argument (matches Linux struct ucontext)
+#[cfg(target_arch = "aarch64")]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct UContext {
+    pub uc_flags: u64,
+    pub uc_link: u64,
+    pub uc_stack: StackT,
+    pub uc_sigmask: u64,
+    /// Room for a 1024-bit sigset_t, as in Linux
+    _unused: [u8; 120],
+    pub uc_mcontext: MContext,
+}
+
+#[cfg(target_arch = "aarch64")]
+impl UContext {
+    /// Build the context for a handler from the state saved in its signal frame
+    pub fn from_signal_frame(frame: &SignalFrame, uc_stack: StackT, fault_addr: u64) -> Self {
+        UContext {
+            uc_flags: 0,
+            uc_link: 0,
+            uc_stack,
+            uc_sigmask: frame.saved_blocked,
+            _unused: [0; 120],
+            uc_mcontext: MContext {
+                fault_address: fault_addr,
+                regs: frame.saved_x,
+                sp: frame.saved_sp,
+                pc: frame.saved_pc,
+                pstate: frame.saved_pstate,
+                _pad: 0,
+                reserved: [0; 2],
+            },
+        }
+    }
 }
 
 // ============================================================================
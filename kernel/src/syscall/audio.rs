@@ -1,6 +1,8 @@
 //! Audio-related system calls.
 //!
 //! Provides syscalls for initializing audio playback and writing PCM data.
+//! Both go through the kernel mixer, so audio written here plays alongside
+//! `/dev/dsp` clients and other processes using these syscalls.
 
 use super::SyscallResult;
 
@@ -9,25 +11,23 @@ const USER_SPACE_MAX: u64 = crate::memory::layout::USER_STACK_REGION_END;
 
 /// sys_audio_init - Initialize the audio stream for playback
 ///
-/// Sets up the VirtIO sound device for S16_LE, 44100 Hz, stereo output.
+/// Starts the mixer, which sets up the VirtIO sound device for S16_LE,
+/// 44100 Hz, stereo output.
 ///
 /// # Returns
 /// * 0 on success
-/// * -EIO on failure
+/// * -EIO if there is no sound device
 pub fn sys_audio_init() -> SyscallResult {
-    #[cfg(target_arch = "aarch64")]
-    let result = crate::drivers::virtio::sound_mmio::setup_stream();
-
-    #[cfg(target_arch = "x86_64")]
-    let result = crate::drivers::virtio::sound::setup_stream();
-
-    match result {
+    match crate::audio::legacy_init() {
         Ok(()) => SyscallResult::Ok(0),
         Err(_) => SyscallResult::Err(super::ErrorCode::IoError as u64),
     }
 }
 
-/// sys_audio_write - Write PCM data to the audio device
+/// sys_audio_write - Queue PCM data for playback
+///
+/// Data is S16_LE, 44100 Hz, stereo and goes to a mixer stream owned by the
+/// calling process. Blocks while that stream's buffer is full.
 ///
 /// # Arguments
 /// * `buf_ptr` - Pointer to PCM data buffer in userspace
@@ -36,7 +36,7 @@ pub fn sys_audio_init() -> SyscallResult {
 /// # Returns
 /// * Number of bytes written on success
 /// * -EFAULT if pointer is invalid
-/// * -EIO on device error
+/// * -EINTR if interrupted by a signal before anything was queued
 pub fn sys_audio_write(buf_ptr: u64, buf_len: u64) -> SyscallResult {
     if buf_ptr == 0 {
         return SyscallResult::Err(super::ErrorCode::Fault as u64);
@@ -52,16 +52,11 @@ pub fn sys_audio_write(buf_ptr: u64, buf_len: u64) -> SyscallResult {
     }
 
     let len = buf_len as usize;
-    let data = unsafe { core::slice::from_raw_parts(buf_ptr as *const u8, len) };
+    // Copied up front: the write may sleep waiting for buffer space
+    let data = unsafe { core::slice::from_raw_parts(buf_ptr as *const u8, len) }.to_vec();
 
-    #[cfg(target_arch = "aarch64")]
-    let result = crate::drivers::virtio::sound_mmio::write_pcm(data);
-
-    #[cfg(target_arch = "x86_64")]
-    let result = crate::drivers::virtio::sound::write_pcm(data);
-
-    match result {
+    match crate::audio::legacy_write(&data) {
         Ok(written) => SyscallResult::Ok(written as u64),
-        Err(_) => SyscallResult::Err(super::ErrorCode::IoError as u64),
+        Err(e) => SyscallResult::Err(e as u64),
     }
 }
@@ -93,7 +93,15 @@ pub fn sys_clone(
     if !manager.admit_clone_into(parent_pid) {
         return SyscallResult::Err(super::errno::EAGAIN as u64);
     }
-    let (parent_cr3, parent_tg_id, parent_cwd, parent_fd_table) = {
+    let (
+        parent_cr3,
+        parent_tg_id,
+        parent_cwd,
+        parent_fd_table,
+        parent_core_limit,
+        parent_auxv,
+        parent_program,
+    ) = {
         let process = manager
             .get_process(parent_pid)
             .expect("admitted clone parent remains present under process-manager guard");
@@ -114,7 +122,19 @@ pub fn sys_clone(
         // Thread group ID: inherit from parent or use parent's pid
         let tg_id = process.thread_group_id.unwrap_or(parent_pid.as_u64());
 
-        (cr3, tg_id, process.cwd.clone(), process.fd_table.clone())
+        (
+            cr3,
+            tg_id,
+            process.cwd.clone(),
+            process.fd_table.clone(),
+            process.core_limit,
+            process.auxv.clone(),
+            (
+                process.exe.clone(),
+                process.cmdline.clone(),
+                process.environ.clone(),
+            ),
+        )
     };
 
     // P5b: refuse a CLONE_VM join into the designated init's thread group. This returns
@@ -223,6 +243,11 @@ pub fn sys_clone(
         wake_time_ns: None,
         run_start_ticks: 0,
         cpu_ticks_total: 0,
+        usage: Default::default(),
+        // Threads inherit the creator's policy and priorities
+        sched: crate::per_cpu::current_thread()
+            .map(|parent| parent.sched.inherited())
+            .unwrap_or_default(),
         owner_pid: Some(child_pid.as_u64()),
         cached_ttbr0: 0,
     };
@@ -247,6 +272,13 @@ pub fn sys_clone(
     child_process.inherited_cr3 = Some(parent_cr3);
     child_process.thread_group_id = Some(parent_tg_id);
     child_process.cwd = parent_cwd;
+    child_process.core_limit = parent_core_limit;
+    child_process.auxv = parent_auxv;
+    (
+        child_process.exe,
+        child_process.cmdline,
+        child_process.environ,
+    ) = parent_program;
 
     // Share file descriptors if CLONE_FILES
     if flags & CLONE_FILES != 0 {
@@ -64,6 +64,13 @@ pub fn dispatch_syscall(
             SyscallResult::Err(38) // ENOSYS
         }
         SyscallNumber::Sigaltstack => super::signal::sys_sigaltstack(arg1, arg2),
+        SyscallNumber::Tkill => super::signal::sys_tkill(arg1 as i64, arg2 as i32),
+        SyscallNumber::Tgkill => super::signal::sys_tgkill(arg1 as i64, arg2 as i64, arg3 as i32),
+        SyscallNumber::RtSigqueueinfo => {
+            super::signal::sys_rt_sigqueueinfo(arg1 as i64, arg2 as i32, arg3)
+        }
+        SyscallNumber::RtSigtimedwait => super::signal::sys_rt_sigtimedwait(arg1, arg2, arg3, arg4),
+        SyscallNumber::Ptrace => super::ptrace::sys_ptrace(arg1, arg2, arg3, arg4),
         SyscallNumber::Sigreturn => super::signal::sys_sigreturn(),
         SyscallNumber::Ioctl => super::ioctl::sys_ioctl(arg1, arg2, arg3),
         SyscallNumber::Socket => super::socket::sys_socket(arg1, arg2, arg3),
@@ -92,7 +99,10 @@ pub fn dispatch_syscall(
         SyscallNumber::Getitimer => super::signal::sys_getitimer(arg1 as i32, arg2),
         SyscallNumber::Alarm => super::signal::sys_alarm(arg1),
         SyscallNumber::Setitimer => super::signal::sys_setitimer(arg1 as i32, arg2, arg3),
-        SyscallNumber::Wait4 => handlers::sys_waitpid(arg1 as i64, arg2, arg3 as u32),
+        SyscallNumber::Wait4 => handlers::sys_waitpid(arg1 as i64, arg2, arg3 as u32, arg4),
+        SyscallNumber::Waitid => {
+            super::waitid::sys_waitid(arg1 as u32, arg2, arg3, arg4 as u32, arg5)
+        }
         SyscallNumber::SetPgid => super::session::sys_setpgid(arg1 as i32, arg2 as i32),
         SyscallNumber::SetSid => super::session::sys_setsid(),
         SyscallNumber::GetPgid => super::session::sys_getpgid(arg1 as i32),
@@ -189,6 +199,26 @@ pub fn dispatch_syscall(
         // Resource limits and system info
         SyscallNumber::Getrlimit => handlers::sys_getrlimit(arg1, arg2),
         SyscallNumber::Prlimit64 => handlers::sys_prlimit64(arg1, arg2, arg3, arg4),
+        SyscallNumber::Getrusage => super::rusage::sys_getrusage(arg1 as i32, arg2),
+        SyscallNumber::Times => super::rusage::sys_times(arg1),
+        // Scheduling priorities
+        SyscallNumber::Getpriority => super::sched::sys_getpriority(arg1 as i32, arg2),
+        SyscallNumber::Setpriority => super::sched::sys_setpriority(arg1 as i32, arg2, arg3 as i32),
+        SyscallNumber::SchedSetparam => super::sched::sys_sched_setparam(arg1 as i64, arg2),
+        SyscallNumber::SchedGetparam => super::sched::sys_sched_getparam(arg1 as i64, arg2),
+        SyscallNumber::SchedSetscheduler => {
+            super::sched::sys_sched_setscheduler(arg1 as i64, arg2 as u32, arg3)
+        }
+        SyscallNumber::SchedGetscheduler => super::sched::sys_sched_getscheduler(arg1 as i64),
+        SyscallNumber::SchedGetPriorityMax => super::sched::sys_sched_get_priority_max(arg1 as u32),
+        SyscallNumber::SchedGetPriorityMin => super::sched::sys_sched_get_priority_min(arg1 as u32),
+        SyscallNumber::SchedSetaffinity => {
+            super::sched::sys_sched_setaffinity(arg1 as i64, arg2, arg3)
+        }
+        SyscallNumber::SchedGetaffinity => {
+            super::sched::sys_sched_getaffinity(arg1 as i64, arg2, arg3)
+        }
+        SyscallNumber::Getcpu => super::sched::sys_getcpu(arg1, arg2),
         SyscallNumber::Uname => handlers::sys_uname(arg1),
         // epoll
         SyscallNumber::EpollCreate1 => super::epoll::sys_epoll_create1(arg1 as u32),
@@ -215,6 +245,29 @@ pub fn dispatch_syscall(
         // Positional I/O
         SyscallNumber::Pread64 => handlers::sys_pread64(arg1 as i32, arg2, arg3, arg4 as i64),
         SyscallNumber::Pwrite64 => handlers::sys_pwrite64(arg1 as i32, arg2, arg3, arg4 as i64),
+        // File size and durability
+        SyscallNumber::Truncate => super::fs::sys_truncate(arg1, arg2 as i64),
+        SyscallNumber::Ftruncate => super::fs::sys_ftruncate(arg1 as i32, arg2 as i64),
+        SyscallNumber::Fallocate => {
+            super::fs::sys_fallocate(arg1 as i32, arg2 as i32, arg3 as i64, arg4 as i64)
+        }
+        SyscallNumber::Fsync => super::fs::sys_fsync(arg1 as i32),
+        SyscallNumber::Fdatasync => super::fs::sys_fdatasync(arg1 as i32),
+        SyscallNumber::Statfs => super::fs::sys_statfs(arg1, arg2),
+        SyscallNumber::Fstatfs => super::fs::sys_fstatfs(arg1 as i32, arg2),
+        SyscallNumber::Flock => super::fs::sys_flock(arg1 as i32, arg2 as i32),
+        SyscallNumber::Sendfile => {
+            super::splice::sys_sendfile(arg1 as i32, arg2 as i32, arg3, arg4)
+        }
+        SyscallNumber::Splice => super::splice::sys_splice(
+            arg1 as i32,
+            arg2,
+            arg3 as i32,
+            arg4,
+            arg5,
+            arg6 as u32,
+        ),
+        SyscallNumber::Tee => super::splice::sys_tee(arg1 as i32, arg2 as i32, arg3, arg4 as u32),
         SyscallNumber::Spawn => SyscallResult::Err(38),
     }
 }
@@ -43,6 +43,12 @@ pub const EBUSY: i32 = 16;
 /// File exists
 pub const EEXIST: i32 = 17;
 
+/// Invalid cross-device link
+pub const EXDEV: i32 = 18;
+
+/// No such device (operation not supported by this kind of file)
+pub const ENODEV: i32 = 19;
+
 /// Not a directory
 pub const ENOTDIR: i32 = 20;
 
@@ -58,18 +64,30 @@ pub const EMFILE: i32 = 24;
 /// Not a typewriter (inappropriate ioctl for device)
 pub const ENOTTY: i32 = 25;
 
+/// File too large
+pub const EFBIG: i32 = 27;
+
 /// No space left on device
 pub const ENOSPC: i32 = 28;
 
 /// Illegal seek (not a seekable fd)
 pub const ESPIPE: i32 = 29;
 
+/// Read-only file system
+pub const EROFS: i32 = 30;
+
 /// Broken pipe
 pub const EPIPE: i32 = 32;
 
 /// Result too large / buffer too small
 pub const ERANGE: i32 = 34;
 
+/// Resource deadlock would occur
+pub const EDEADLK: i32 = 35;
+
+/// File name too long
+pub const ENAMETOOLONG: i32 = 36;
+
 /// Function not implemented (used by syscall dispatcher)
 #[allow(dead_code)]
 pub const ENOSYS: i32 = 38;
@@ -77,6 +95,9 @@ pub const ENOSYS: i32 = 38;
 /// Directory not empty
 pub const ENOTEMPTY: i32 = 39;
 
+/// Too many levels of symbolic links
+pub const ELOOP: i32 = 40;
+
 /// Not a socket
 pub const ENOTSOCK: i32 = 88;
 
@@ -51,6 +51,9 @@ pub struct LinuxDirent64 {
 /// Size of the fixed part of LinuxDirent64 (before d_name)
 const DIRENT64_HEADER_SIZE: usize = 19; // 8 + 8 + 2 + 1 = 19 bytes
 
+/// Procfs links open() follows in a row before giving up (Linux's MAXSYMLINKS)
+const MAX_PROC_LINK_DEPTH: usize = 40;
+
 // File type constants for d_type field (Linux values)
 /// Unknown file type
 pub const DT_UNKNOWN: u8 = 0;
@@ -213,6 +216,11 @@ impl Stat {
     }
 }
 
+/// Map an ext2 error to the errno userspace should see, routed through `VfsError`
+pub(crate) fn ext2_errno(e: crate::fs::ext2::Ext2Error) -> i32 {
+    crate::fs::vfs::VfsError::from(e).to_errno()
+}
+
 /// sys_open - Open a file or directory
 ///
 /// Helper: sys_open write path (O_CREAT/O_TRUNC) — works on any Ext2Fs instance.
@@ -226,8 +234,8 @@ fn sys_open_write_path(
     want_trunc: bool,
     mode: u32,
 ) -> Result<(u32, crate::fs::ext2::FileType, bool, bool, usize), SyscallResult> {
-    use super::errno::{EEXIST, ENOENT, ENOSPC, ENOTDIR};
-    use crate::fs::ext2::FileType as Ext2FileType;
+    use super::errno::{EEXIST, ENOENT, ENOTDIR};
+    use crate::fs::ext2::{Ext2Error, FileType as Ext2FileType};
 
     let resolve_result = fs.resolve_path(fs_path);
 
@@ -240,7 +248,7 @@ fn sys_open_write_path(
             (ino, false)
         }
         Err(e) => {
-            if e.contains("not found") && want_creat {
+            if e == Ext2Error::NotFound && want_creat {
                 log::debug!("sys_open: creating new file {}", display_path);
 
                 let (parent_path, filename) = match fs_path.rfind('/') {
@@ -259,9 +267,9 @@ fn sys_open_write_path(
 
                 let parent_inode = match fs.resolve_path(parent_path) {
                     Ok(ino) => ino,
-                    Err(_) => {
+                    Err(e) => {
                         log::error!("sys_open: parent directory not found: {}", parent_path);
-                        return Err(SyscallResult::Err(ENOENT as u64));
+                        return Err(SyscallResult::Err(ext2_errno(e) as u64));
                     }
                 };
 
@@ -292,21 +300,12 @@ fn sys_open_write_path(
                     }
                     Err(e) => {
                         log::error!("sys_open: failed to create file: {}", e);
-                        if e.contains("No free inodes") || e.contains("No space") {
-                            return Err(SyscallResult::Err(ENOSPC as u64));
-                        }
-                        return Err(SyscallResult::Err(5)); // EIO
+                        return Err(SyscallResult::Err(ext2_errno(e) as u64));
                     }
                 }
             } else {
                 log::debug!("sys_open: path resolution failed: {}", e);
-                if e.contains("not found") {
-                    return Err(SyscallResult::Err(ENOENT as u64));
-                } else if e.contains("Not a directory") {
-                    return Err(SyscallResult::Err(ENOTDIR as u64));
-                } else {
-                    return Err(SyscallResult::Err(5)); // EIO
-                }
+                return Err(SyscallResult::Err(ext2_errno(e) as u64));
             }
         }
     };
@@ -325,9 +324,9 @@ fn sys_open_write_path(
 
     if want_trunc && is_reg && !file_created {
         log::debug!("sys_open: truncating file inode {}", ino);
-        if let Err(e) = fs.truncate_file(ino) {
+        if let Err(e) = fs.truncate_file(ino, 0) {
             log::error!("sys_open: failed to truncate file: {}", e);
-            return Err(SyscallResult::Err(5)); // EIO
+            return Err(SyscallResult::Err(ext2_errno(e) as u64));
         }
     }
 
@@ -340,20 +339,13 @@ fn sys_open_read_path(
     fs: &crate::fs::ext2::Ext2Fs,
     fs_path: &str,
 ) -> Result<(u32, crate::fs::ext2::FileType, bool, bool, usize), SyscallResult> {
-    use super::errno::{ENOENT, ENOTDIR};
     use crate::fs::ext2::FileType as Ext2FileType;
 
     let ino = match fs.resolve_path(fs_path) {
         Ok(ino) => ino,
         Err(e) => {
             log::debug!("sys_open: path resolution failed: {}", e);
-            if e.contains("not found") {
-                return Err(SyscallResult::Err(ENOENT as u64));
-            } else if e.contains("Not a directory") {
-                return Err(SyscallResult::Err(ENOTDIR as u64));
-            } else {
-                return Err(SyscallResult::Err(5)); // EIO
-            }
+            return Err(SyscallResult::Err(ext2_errno(e) as u64));
         }
     };
 
@@ -416,6 +408,19 @@ pub fn sys_open(pathname: u64, flags: u32, mode: u32) -> SyscallResult {
 
     log::debug!("sys_open: resolved path={:?}", path);
 
+    // Follow procfs symlinks (/proc/self, /proc/[pid]/exe, cwd and fd/[n])
+    // to the paths they name, which may be on any filesystem
+    let mut path = path;
+    for _ in 0..MAX_PROC_LINK_DEPTH {
+        if !path.starts_with("/proc/") {
+            break;
+        }
+        match crate::fs::procfs::follow_link(&path) {
+            Some(target) => path = target,
+            None => break,
+        }
+    }
+
     // Check for /dev directory itself
     if path == "/dev" || path == "/dev/" {
         return handle_devfs_directory_open(flags);
@@ -524,6 +529,7 @@ pub fn sys_open(pathname: u64, flags: u32, mode: u32) -> SyscallResult {
                 inode_num: inode_num as u64,
                 mount_id,
                 position: 0,
+                path: path.clone(),
             };
 
             // Get current process and allocate fd
@@ -595,6 +601,8 @@ pub fn sys_open(pathname: u64, flags: u32, mode: u32) -> SyscallResult {
             mount_id,
             position: 0,
             flags,
+            open_file_id: crate::fs::lock::alloc_open_file_id(),
+            path: path.clone(),
         };
 
         // Get current process and allocate fd
@@ -803,7 +811,7 @@ pub fn sys_fstat(fd: i32, statbuf: u64) -> SyscallResult {
     // as interrupts — holding the lock during disk I/O deadlocks the system.
     enum FstatKind {
         StdIo(i32),
-        Pipe,
+        Pipe(u64),
         UdpSocket,
         RegularFile { inode_num: u64, mount_id: usize },
         Directory { inode_num: u64, mount_id: usize },
@@ -842,7 +850,7 @@ pub fn sys_fstat(fd: i32, statbuf: u64) -> SyscallResult {
 
         match &fd_entry.kind {
             FdKind::StdIo(io_fd) => FstatKind::StdIo(*io_fd),
-            FdKind::PipeRead(_) | FdKind::PipeWrite(_) => FstatKind::Pipe,
+            FdKind::PipeRead(pipe) | FdKind::PipeWrite(pipe) => FstatKind::Pipe(pipe.lock().ino()),
             FdKind::UdpSocket(_) => FstatKind::UdpSocket,
             FdKind::RegularFile(file) => {
                 let file_guard = file.lock();
@@ -866,6 +874,14 @@ pub fn sys_fstat(fd: i32, statbuf: u64) -> SyscallResult {
                     rdev: device_node.map(|d| d.rdev()).unwrap_or(0),
                 }
             }
+            FdKind::Dsp(_) => {
+                use crate::fs::devfs::{self, DeviceType};
+                let device_node = devfs::lookup_by_inode(DeviceType::Dsp.inode());
+                FstatKind::Device {
+                    inode: DeviceType::Dsp.inode(),
+                    rdev: device_node.map(|d| d.rdev()).unwrap_or(0),
+                }
+            }
             FdKind::DevfsDirectory { .. } => FstatKind::DevfsDirectory,
             FdKind::DevptsDirectory { .. } => FstatKind::DevptsDirectory,
             FdKind::TcpSocket(_) | FdKind::TcpListener(_) | FdKind::TcpConnection(_) => {
@@ -901,11 +917,9 @@ pub fn sys_fstat(fd: i32, statbuf: u64) -> SyscallResult {
             stat.st_nlink = 1;
             stat.st_rdev = make_dev(5, io_fd as u64); // Major 5 (TTY), minor = fd number
         }
-        FstatKind::Pipe => {
-            static PIPE_INODE_COUNTER: core::sync::atomic::AtomicU64 =
-                core::sync::atomic::AtomicU64::new(1000);
+        FstatKind::Pipe(ino) => {
             stat.st_dev = 0;
-            stat.st_ino = PIPE_INODE_COUNTER.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+            stat.st_ino = ino;
             stat.st_mode = S_IFIFO | 0o600; // FIFO with rw-------
             stat.st_nlink = 1;
             stat.st_size = 0;
@@ -1409,7 +1423,7 @@ pub fn sys_getdents64(fd: i32, dirp: u64, count: u64) -> SyscallResult {
 /// * EACCES - Permission denied
 /// * EIO - I/O error
 pub fn sys_unlink(pathname: u64) -> SyscallResult {
-    use super::errno::{EACCES, EIO, EISDIR, ENOENT};
+    use super::errno::EIO;
     use super::userptr::copy_cstr_from_user;
     use crate::fs::ext2;
 
@@ -1488,15 +1502,7 @@ pub fn sys_unlink(pathname: u64) -> SyscallResult {
         Err(e) => {
             log::debug!("sys_unlink: failed: {}", e);
             // Map error to appropriate errno
-            let errno = if e.contains("not found") || e.contains("not exist") {
-                ENOENT
-            } else if e.contains("directory") {
-                EISDIR
-            } else if e.contains("permission") || e.contains("Cannot") {
-                EACCES
-            } else {
-                EIO
-            };
+            let errno = ext2_errno(e);
             SyscallResult::Err(errno as u64)
         }
     }
@@ -1519,9 +1525,10 @@ pub fn sys_unlink(pathname: u64) -> SyscallResult {
 /// * EISDIR - newpath is a directory but oldpath is not
 /// * ENOTDIR - Component in path is not a directory
 /// * EEXIST/ENOTEMPTY - newpath is a non-empty directory
+/// * EXDEV - oldpath and newpath are on different filesystems
 /// * EIO - I/O error
 pub fn sys_rename(oldpath: u64, newpath: u64) -> SyscallResult {
-    use super::errno::{EACCES, EIO, EISDIR, ENOENT};
+    use super::errno::EIO;
     use super::userptr::copy_cstr_from_user;
     use crate::fs::ext2;
 
@@ -1538,12 +1545,12 @@ pub fn sys_rename(oldpath: u64, newpath: u64) -> SyscallResult {
     log::debug!("sys_rename: old={:?}, new={:?}", old, new);
 
     // Both paths must be on the same filesystem
+    if let Err(e) = ext2::check_same_filesystem(&old, &new) {
+        log::debug!("sys_rename: {} -> {}: {}", old, new, e);
+        return SyscallResult::Err(ext2_errno(e) as u64);
+    }
     let old_is_home = ext2::is_home_path(&old);
     let new_is_home = ext2::is_home_path(&new);
-    if old_is_home != new_is_home {
-        log::error!("sys_rename: cross-filesystem rename not supported");
-        return SyscallResult::Err(18); // EXDEV
-    }
 
     let fs_old = if old_is_home {
         alloc::string::String::from(ext2::strip_home_prefix(&old))
@@ -1585,17 +1592,7 @@ pub fn sys_rename(oldpath: u64, newpath: u64) -> SyscallResult {
         Err(e) => {
             log::debug!("sys_rename: failed: {}", e);
             // Map error to appropriate errno
-            let errno = if e.contains("not found") || e.contains("not exist") {
-                ENOENT
-            } else if e.contains("is a directory") {
-                EISDIR
-            } else if e.contains("Not a directory") {
-                super::errno::ENOTDIR
-            } else if e.contains("permission") || e.contains("Cannot") {
-                EACCES
-            } else {
-                EIO
-            };
+            let errno = ext2_errno(e);
             SyscallResult::Err(errno as u64)
         }
     }
@@ -1620,7 +1617,7 @@ pub fn sys_rename(oldpath: u64, newpath: u64) -> SyscallResult {
 /// * EINVAL - pathname is "." or ends with "/."
 /// * EIO - I/O error
 pub fn sys_rmdir(pathname: u64) -> SyscallResult {
-    use super::errno::{EACCES, EINVAL, EIO, ENOENT, ENOTDIR, ENOTEMPTY};
+    use super::errno::{EINVAL, EIO};
     use super::userptr::copy_cstr_from_user;
     use crate::fs::ext2;
 
@@ -1674,22 +1671,7 @@ pub fn sys_rmdir(pathname: u64) -> SyscallResult {
         Err(e) => {
             log::debug!("sys_rmdir: failed: {}", e);
             // Map error to appropriate errno
-            let errno = if e.contains("not found") || e.contains("not exist") {
-                ENOENT
-            } else if e.contains("Not a directory") || e.contains("not a directory") {
-                ENOTDIR
-            } else if e.contains("not empty") || e.contains("Directory not empty") {
-                ENOTEMPTY
-            } else if e.contains("root directory") {
-                // Cannot remove root directory - treat as busy
-                super::errno::EBUSY
-            } else if e.contains("permission") || e.contains("Cannot") {
-                EACCES
-            } else if e.contains("Invalid") {
-                EINVAL
-            } else {
-                EIO
-            };
+            let errno = ext2_errno(e);
             SyscallResult::Err(errno as u64)
         }
     }
@@ -1713,9 +1695,10 @@ pub fn sys_rmdir(pathname: u64) -> SyscallResult {
 /// * EPERM - oldpath is a directory
 /// * ENOTDIR - A component in path is not a directory
 /// * ENOSPC - No space in target directory
+/// * EXDEV - oldpath and newpath are on different filesystems
 /// * EIO - I/O error
 pub fn sys_link(oldpath: u64, newpath: u64) -> SyscallResult {
-    use super::errno::{EACCES, EEXIST, EIO, ENOENT, ENOTDIR, EPERM};
+    use super::errno::EIO;
     use super::userptr::copy_cstr_from_user;
     use crate::fs::ext2;
 
@@ -1732,12 +1715,12 @@ pub fn sys_link(oldpath: u64, newpath: u64) -> SyscallResult {
     log::debug!("sys_link: oldpath={:?}, newpath={:?}", old, new);
 
     // Both paths must be on the same filesystem
+    if let Err(e) = ext2::check_same_filesystem(&old, &new) {
+        log::debug!("sys_link: {} -> {}: {}", old, new, e);
+        return SyscallResult::Err(ext2_errno(e) as u64);
+    }
     let old_is_home = ext2::is_home_path(&old);
     let new_is_home = ext2::is_home_path(&new);
-    if old_is_home != new_is_home {
-        log::error!("sys_link: cross-filesystem link not supported");
-        return SyscallResult::Err(18); // EXDEV
-    }
 
     let fs_old = if old_is_home {
         alloc::string::String::from(ext2::strip_home_prefix(&old))
@@ -1783,21 +1766,7 @@ pub fn sys_link(oldpath: u64, newpath: u64) -> SyscallResult {
         Err(e) => {
             log::debug!("sys_link: failed: {}", e);
             // Map error to appropriate errno
-            let errno = if e.contains("not found") || e.contains("not exist") {
-                ENOENT
-            } else if e.contains("already exists") || e.contains("Destination already exists") {
-                EEXIST
-            } else if e.contains("directory") && e.contains("hard link") {
-                EPERM // Cannot create hard link to directory
-            } else if e.contains("Not a directory") {
-                ENOTDIR
-            } else if e.contains("permission") || e.contains("Cannot") {
-                EACCES
-            } else if e.contains("No space") {
-                super::errno::ENOSPC
-            } else {
-                EIO
-            };
+            let errno = ext2_errno(e);
             SyscallResult::Err(errno as u64)
         }
     }
@@ -1821,7 +1790,7 @@ pub fn sys_link(oldpath: u64, newpath: u64) -> SyscallResult {
 /// * ENOSPC - No space for new directory
 /// * EIO - I/O error
 pub fn sys_mkdir(pathname: u64, mode: u32) -> SyscallResult {
-    use super::errno::{EACCES, EEXIST, EIO, ENOENT, ENOSPC, ENOTDIR};
+    use super::errno::EIO;
     use super::userptr::copy_cstr_from_user;
     use crate::fs::ext2;
 
@@ -1879,22 +1848,7 @@ pub fn sys_mkdir(pathname: u64, mode: u32) -> SyscallResult {
         Err(e) => {
             log::debug!("sys_mkdir: failed: {}", e);
             // Map error to appropriate errno
-            let errno = if e.contains("not found")
-                || e.contains("not exist")
-                || e.contains("Path component not found")
-            {
-                ENOENT
-            } else if e.contains("already exists") || e.contains("Directory already exists") {
-                EEXIST
-            } else if e.contains("Not a directory") || e.contains("not a directory") {
-                ENOTDIR
-            } else if e.contains("permission") || e.contains("Cannot") {
-                EACCES
-            } else if e.contains("No space") || e.contains("No free") {
-                ENOSPC
-            } else {
-                EIO
-            };
+            let errno = ext2_errno(e);
             SyscallResult::Err(errno as u64)
         }
     }
@@ -1920,7 +1874,7 @@ pub fn sys_mkdir(pathname: u64, mode: u32) -> SyscallResult {
 /// * ENOSPC - No space to create the symlink
 /// * EIO - I/O error
 pub fn sys_symlink(target: u64, linkpath: u64) -> SyscallResult {
-    use super::errno::{EACCES, EEXIST, EINVAL, EIO, ENOENT, ENOSPC, ENOTDIR};
+    use super::errno::{EINVAL, EIO};
     use super::userptr::copy_cstr_from_user;
     use crate::fs::ext2;
 
@@ -1987,24 +1941,7 @@ pub fn sys_symlink(target: u64, linkpath: u64) -> SyscallResult {
         Err(e) => {
             log::debug!("sys_symlink: failed: {}", e);
             // Map error to appropriate errno
-            let errno = if e.contains("not found")
-                || e.contains("not exist")
-                || e.contains("Path component not found")
-            {
-                ENOENT
-            } else if e.contains("already exists") || e.contains("File already exists") {
-                EEXIST
-            } else if e.contains("Not a directory") || e.contains("not a directory") {
-                ENOTDIR
-            } else if e.contains("permission") || e.contains("Cannot") {
-                EACCES
-            } else if e.contains("No space") || e.contains("No free") {
-                ENOSPC
-            } else if e.contains("empty") || e.contains("Invalid") {
-                EINVAL
-            } else {
-                EIO
-            };
+            let errno = ext2_errno(e);
             SyscallResult::Err(errno as u64)
         }
     }
@@ -2029,7 +1966,7 @@ pub fn sys_symlink(target: u64, linkpath: u64) -> SyscallResult {
 /// * EFAULT - Invalid buffer pointer
 /// * EIO - I/O error
 pub fn sys_readlink(pathname: u64, buf: u64, bufsize: u64) -> SyscallResult {
-    use super::errno::{EFAULT, EINVAL, EIO, ENOENT};
+    use super::errno::{EFAULT, EIO};
     use super::userptr::copy_cstr_from_user;
     use crate::fs::ext2;
 
@@ -2055,7 +1992,12 @@ pub fn sys_readlink(pathname: u64, buf: u64, bufsize: u64) -> SyscallResult {
     };
 
     // Resolve path and read symlink from the correct filesystem
-    let target = if is_home {
+    let target = if path.starts_with("/proc/") {
+        match crate::fs::procfs::read_link(&path) {
+            Ok(t) => t,
+            Err(e) => return SyscallResult::Err((-e) as u64),
+        }
+    } else if is_home {
         let fs_guard = ext2::home_fs_read();
         let fs = match fs_guard.as_ref() {
             Some(fs) => fs,
@@ -2068,20 +2010,14 @@ pub fn sys_readlink(pathname: u64, buf: u64, bufsize: u64) -> SyscallResult {
             Ok(ino) => ino,
             Err(e) => {
                 log::debug!("sys_readlink: path resolution failed: {}", e);
-                return SyscallResult::Err(ENOENT as u64);
+                return SyscallResult::Err(ext2_errno(e) as u64);
             }
         };
         match fs.read_symlink(inode_num) {
             Ok(t) => t,
             Err(e) => {
                 log::debug!("sys_readlink: failed to read symlink: {}", e);
-                let errno = if e.contains("Not a symbolic link") {
-                    EINVAL
-                } else if e.contains("not found") {
-                    ENOENT
-                } else {
-                    EIO
-                };
+                let errno = ext2_errno(e);
                 return SyscallResult::Err(errno as u64);
             }
         }
@@ -2098,20 +2034,14 @@ pub fn sys_readlink(pathname: u64, buf: u64, bufsize: u64) -> SyscallResult {
             Ok(ino) => ino,
             Err(e) => {
                 log::debug!("sys_readlink: path resolution failed: {}", e);
-                return SyscallResult::Err(ENOENT as u64);
+                return SyscallResult::Err(ext2_errno(e) as u64);
             }
         };
         match fs.read_symlink(inode_num) {
             Ok(t) => t,
             Err(e) => {
                 log::debug!("sys_readlink: failed to read symlink: {}", e);
-                let errno = if e.contains("Not a symbolic link") {
-                    EINVAL
-                } else if e.contains("not found") {
-                    ENOENT
-                } else {
-                    EIO
-                };
+                let errno = ext2_errno(e);
                 return SyscallResult::Err(errno as u64);
             }
         }
@@ -2149,7 +2079,7 @@ pub fn sys_readlink(pathname: u64, buf: u64, bufsize: u64) -> SyscallResult {
 /// * EACCES - Access would be denied
 /// * ENOTDIR - A component of path is not a directory
 pub fn sys_access(pathname: u64, mode: u32) -> SyscallResult {
-    use super::errno::{EACCES, ENOENT, ENOTDIR};
+    use super::errno::{EACCES, ENOENT};
     use super::userptr::copy_cstr_from_user;
     use crate::fs::ext2;
 
@@ -2215,13 +2145,7 @@ pub fn sys_access(pathname: u64, mode: u32) -> SyscallResult {
             Ok(ino) => ino,
             Err(e) => {
                 log::debug!("sys_access: path resolution failed: {}", e);
-                let errno = if e.contains("not found") {
-                    ENOENT
-                } else if e.contains("Not a directory") {
-                    ENOTDIR
-                } else {
-                    5
-                };
+                let errno = ext2_errno(e);
                 return SyscallResult::Err(errno as u64);
             }
         };
@@ -2246,13 +2170,7 @@ pub fn sys_access(pathname: u64, mode: u32) -> SyscallResult {
             Ok(ino) => ino,
             Err(e) => {
                 log::debug!("sys_access: path resolution failed: {}", e);
-                let errno = if e.contains("not found") {
-                    ENOENT
-                } else if e.contains("Not a directory") {
-                    ENOTDIR
-                } else {
-                    5
-                };
+                let errno = ext2_errno(e);
                 return SyscallResult::Err(errno as u64);
             }
         };
@@ -2299,6 +2217,7 @@ pub fn sys_access(pathname: u64, mode: u32) -> SyscallResult {
 /// For directories (/proc, /proc/trace, /proc/[pid]), returns a ProcfsDirectory fd.
 /// For files, generates the content at open time and stores it in a ProcfsFile fd.
 fn handle_procfs_open(path: &str, _flags: u32) -> SyscallResult {
+    use super::errno::EACCES;
     use crate::ipc::fd::{FdKind, FileDescriptor};
 
     let normalized = path.trim_end_matches('/');
@@ -2314,6 +2233,11 @@ fn handle_procfs_open(path: &str, _flags: u32) -> SyscallResult {
     };
 
     if is_directory {
+        if let Some(entry) = crate::fs::procfs::lookup_by_path(normalized) {
+            if let Err(e) = crate::fs::procfs::check_access(entry.entry_type) {
+                return SyscallResult::Err((-e) as u64);
+            }
+        }
         let dir_path = alloc::string::String::from(normalized);
         let fd_kind = FdKind::ProcfsDirectory {
             path: dir_path,
@@ -2350,12 +2274,14 @@ fn handle_procfs_open(path: &str, _flags: u32) -> SyscallResult {
     // Regular file open
     let content = match crate::fs::procfs::read_file(path) {
         Ok(c) => c,
+        Err(e) if e == -EACCES => return SyscallResult::Err(EACCES as u64),
         Err(_) => return SyscallResult::Err(super::errno::ENOENT as u64),
     };
 
     let fd_kind = FdKind::ProcfsFile {
         content,
         position: 0,
+        path: alloc::string::String::from(path),
     };
     let fd_entry = FileDescriptor::new(fd_kind);
 
@@ -2382,11 +2308,11 @@ fn handle_procfs_open(path: &str, _flags: u32) -> SyscallResult {
     }
 }
 
-/// * `_flags` - Open flags (currently unused for devices)
+/// * `flags` - Open flags (only O_NONBLOCK is used, by /dev/dsp)
 ///
 /// # Returns
 /// File descriptor on success, negative errno on failure
-fn handle_devfs_open(device_name: &str, _flags: u32) -> SyscallResult {
+fn handle_devfs_open(device_name: &str, flags: u32) -> SyscallResult {
     use super::errno::{EMFILE, ENOENT};
     use crate::fs::devfs;
 
@@ -2412,6 +2338,11 @@ fn handle_devfs_open(device_name: &str, _flags: u32) -> SyscallResult {
         }
     };
 
+    // Each open of /dev/dsp is its own stream in the audio mixer
+    if device.device_type == devfs::DeviceType::Dsp {
+        return handle_dsp_open(flags);
+    }
+
     // Get current process and allocate fd
     let thread_id = match crate::task::scheduler::current_thread_id() {
         Some(id) => id,
@@ -2515,6 +2446,47 @@ fn handle_devfs_open(device_name: &str, _flags: u32) -> SyscallResult {
     }
 }
 
+/// Open a new audio mixer stream for /dev/dsp
+///
+/// Streams opened for reading (O_RDONLY or O_RDWR) also record. The stream
+/// is created before the process manager lock is taken, since the first
+/// open starts the mixer thread.
+fn handle_dsp_open(flags: u32) -> SyscallResult {
+    use super::errno::EMFILE;
+    use crate::ipc::fd::{status_flags, FdKind, FileDescriptor};
+
+    let capture = flags & 3 != O_WRONLY;
+    let stream = match crate::audio::open_stream(capture) {
+        Ok(stream) => stream,
+        Err(e) => return SyscallResult::Err(e as u64),
+    };
+    let mut fd_entry = FileDescriptor::new(FdKind::Dsp(stream));
+    if (flags & status_flags::O_NONBLOCK) != 0 {
+        fd_entry.status_flags |= status_flags::O_NONBLOCK;
+    }
+
+    let thread_id = match crate::task::scheduler::current_thread_id() {
+        Some(id) => id,
+        None => return SyscallResult::Err(3), // ESRCH
+    };
+    let mut manager_guard = crate::process::manager();
+    let process = match &mut *manager_guard {
+        Some(manager) => match manager.find_process_by_thread_mut(thread_id) {
+            Some((_, p)) => p,
+            None => return SyscallResult::Err(3), // ESRCH
+        },
+        None => return SyscallResult::Err(3), // ESRCH
+    };
+
+    match process.fd_table.alloc_with_entry(fd_entry) {
+        Ok(fd) => {
+            log::info!("handle_devfs_open: opened /dev/dsp stream as fd {}", fd);
+            SyscallResult::Ok(fd as u64)
+        }
+        Err(_) => SyscallResult::Err(EMFILE as u64),
+    }
+}
+
 /// Handle opening a PTY slave device from /dev/pts/*
 ///
 /// # Arguments
@@ -2991,13 +2963,8 @@ fn handle_procfs_getdents64(
     } else if dir_path == "/proc/trace" {
         crate::fs::procfs::list_trace_entries()
     } else if dir_path.starts_with("/proc/") {
-        // Per-PID directory - only contains "status"
-        let relative = dir_path.strip_prefix("/proc/").unwrap_or("");
-        if !relative.is_empty() && relative.chars().all(|c| c.is_ascii_digit()) {
-            alloc::vec![String::from("status")]
-        } else {
-            alloc::vec![]
-        }
+        // Per-PID directory, or its fd/ and task/ subdirectories
+        crate::fs::procfs::list_pid_entries(dir_path)
     } else {
         alloc::vec![]
     };
@@ -3084,6 +3051,8 @@ fn handle_procfs_getdents64(
             let ino = entry.entry_type.inode();
             let dt = if entry.entry_type.is_directory() {
                 DT_DIR
+            } else if entry.entry_type.is_symlink() {
+                DT_LNK
             } else {
                 DT_REG
             };
@@ -3256,7 +3225,7 @@ pub fn sys_getcwd(buf: u64, size: u64) -> SyscallResult {
 /// * EACCES - Permission denied
 /// * EIO - I/O error
 pub fn sys_chdir(pathname: u64) -> SyscallResult {
-    use super::errno::{EACCES, EIO, ENOENT, ENOTDIR};
+    use super::errno::{EIO, ENOENT, ENOTDIR};
     use super::userptr::copy_cstr_from_user;
     use crate::fs::ext2::{self, FileType as Ext2FileType};
     use alloc::string::String;
@@ -3363,15 +3332,7 @@ pub fn sys_chdir(pathname: u64) -> SyscallResult {
             Ok(ino) => ino,
             Err(e) => {
                 log::debug!("sys_chdir: path resolution failed: {}", e);
-                let errno = if e.contains("not found") {
-                    ENOENT
-                } else if e.contains("Not a directory") {
-                    ENOTDIR
-                } else if e.contains("permission") {
-                    EACCES
-                } else {
-                    EIO
-                };
+                let errno = ext2_errno(e);
                 return SyscallResult::Err(errno as u64);
             }
         };
@@ -3393,15 +3354,7 @@ pub fn sys_chdir(pathname: u64) -> SyscallResult {
             Ok(ino) => ino,
             Err(e) => {
                 log::debug!("sys_chdir: path resolution failed: {}", e);
-                let errno = if e.contains("not found") {
-                    ENOENT
-                } else if e.contains("Not a directory") {
-                    ENOTDIR
-                } else if e.contains("permission") {
-                    EACCES
-                } else {
-                    EIO
-                };
+                let errno = ext2_errno(e);
                 return SyscallResult::Err(errno as u64);
             }
         };
@@ -3801,7 +3754,7 @@ pub fn sys_newfstatat(dirfd: i32, pathname: u64, statbuf: u64, _flags: u32) -> S
         let mid = fs.mount_id;
         match fs.resolve_path(fs_path) {
             Ok(inum) => (inum as u64, mid),
-            Err(_) => return SyscallResult::Err(ENOENT as u64),
+            Err(e) => return SyscallResult::Err(ext2_errno(e) as u64),
         }
     } else {
         let fs_guard = ext2::root_fs_read();
@@ -3812,7 +3765,7 @@ pub fn sys_newfstatat(dirfd: i32, pathname: u64, statbuf: u64, _flags: u32) -> S
         let mid = fs.mount_id;
         match fs.resolve_path(fs_path) {
             Ok(inum) => (inum as u64, mid),
-            Err(_) => return SyscallResult::Err(ENOENT as u64),
+            Err(e) => return SyscallResult::Err(ext2_errno(e) as u64),
         }
     };
 
@@ -4088,7 +4041,7 @@ pub fn sys_utimensat(dirfd: i32, path_ptr: u64, times_ptr: u64, flags: u32) -> S
         };
         match ino {
             Ok(n) => (n, fs.mount_id),
-            Err(_) => return SyscallResult::Err(super::errno::ENOENT as u64),
+            Err(e) => return SyscallResult::Err(ext2_errno(e) as u64),
         }
     } else {
         let fs_guard = ext2::root_fs_read();
@@ -4103,7 +4056,7 @@ pub fn sys_utimensat(dirfd: i32, path_ptr: u64, times_ptr: u64, flags: u32) -> S
         };
         match ino {
             Ok(n) => (n, fs.mount_id),
-            Err(_) => return SyscallResult::Err(super::errno::ENOENT as u64),
+            Err(e) => return SyscallResult::Err(ext2_errno(e) as u64),
         }
     };
 
@@ -4138,7 +4091,7 @@ fn update_inode_timestamps(
 
         match fs.write_inode(inode_num, &inode) {
             Ok(()) => SyscallResult::Ok(0),
-            Err(_) => SyscallResult::Err(super::errno::EIO as u64),
+            Err(e) => SyscallResult::Err(ext2_errno(e) as u64),
         }
     };
 
@@ -4156,3 +4109,938 @@ fn update_inode_timestamps(
         }
     }
 }
+
+// =============================================================================
+// truncate / ftruncate / fallocate / fsync / fdatasync
+// =============================================================================
+
+/// fallocate mode: allocate blocks but leave the file size unchanged
+const FALLOC_FL_KEEP_SIZE: i32 = 0x01;
+
+/// Helper: look up the ext2 inode, mount, and open flags behind a regular-file fd
+///
+/// # Returns
+/// * `Ok((inode_num, mount_id, flags))` - fd refers to a regular file
+/// * `Err(errno)` - EBADF if fd is not open, ESPIPE for pipes and FIFOs,
+///   EINVAL for any other kind of fd
+fn regular_file_for_fd(fd: i32) -> Result<(u32, usize, u32), u64> {
+    let thread_id = match crate::task::scheduler::current_thread_id() {
+        Some(id) => id,
+        None => return Err(super::errno::EBADF as u64),
+    };
+
+    crate::arch_without_interrupts(|| {
+        let manager_guard = crate::process::manager();
+        let manager = match *manager_guard {
+            Some(ref m) => m,
+            None => return Err(super::errno::EBADF as u64),
+        };
+        let (_pid, process) = match manager.find_process_by_thread(thread_id) {
+            Some(p) => p,
+            None => return Err(super::errno::EBADF as u64),
+        };
+        let fd_entry = match process.fd_table.get(fd) {
+            Some(entry) => entry,
+            None => return Err(super::errno::EBADF as u64),
+        };
+        match &fd_entry.kind {
+            FdKind::RegularFile(file_ref) => {
+                let file = file_ref.lock();
+                Ok((file.inode_num as u32, file.mount_id, file.flags))
+            }
+            FdKind::PipeRead(_) | FdKind::PipeWrite(_) => Err(super::errno::ESPIPE as u64),
+            _ => Err(super::errno::EINVAL as u64),
+        }
+    })
+}
+
+/// Helper: run a mutating operation on the ext2 filesystem with the given mount ID
+fn with_ext2_mount_mut<F>(mount_id: usize, f: F) -> SyscallResult
+where
+    F: FnOnce(&mut crate::fs::ext2::Ext2Fs) -> Result<(), crate::fs::ext2::Ext2Error>,
+{
+    use crate::fs::ext2;
+
+    let is_home = ext2::home_mount_id().map_or(false, |id| id == mount_id);
+    let result = if is_home {
+        let mut fs_guard = ext2::home_fs_write();
+        match fs_guard.as_mut() {
+            Some(fs) => f(fs),
+            None => return SyscallResult::Err(super::errno::EIO as u64),
+        }
+    } else {
+        let mut fs_guard = ext2::root_fs_write();
+        match fs_guard.as_mut() {
+            Some(fs) => f(fs),
+            None => return SyscallResult::Err(super::errno::EIO as u64),
+        }
+    };
+
+    match result {
+        Ok(()) => SyscallResult::Ok(0),
+        Err(e) => SyscallResult::Err(ext2_errno(e) as u64),
+    }
+}
+
+/// sys_ftruncate - Truncate or extend an open file to a given length
+///
+/// Shrinking frees the blocks past the new end of file; growing leaves a
+/// hole that reads back as zeros. The file offset is not changed.
+///
+/// # Arguments
+/// * `fd` - File descriptor open for writing
+/// * `length` - New file size in bytes
+///
+/// # Returns
+/// 0 on success, negative errno on failure
+///
+/// # Errors
+/// * EBADF - fd is not a valid file descriptor
+/// * EINVAL - length is negative, fd is not a regular file, or fd is not open for writing
+/// * EFBIG - length exceeds the maximum file size
+/// * EROFS - The filesystem is read-only
+/// * EIO - I/O error
+pub fn sys_ftruncate(fd: i32, length: i64) -> SyscallResult {
+    use super::errno::{EINVAL, ESPIPE};
+
+    if length < 0 {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+
+    let (inode_num, mount_id, flags) = match regular_file_for_fd(fd) {
+        Ok(info) => info,
+        Err(errno) if errno == ESPIPE as u64 => return SyscallResult::Err(EINVAL as u64),
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+
+    if flags & 3 == O_RDONLY {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+
+    log::debug!(
+        "sys_ftruncate: fd={} inode={} length={}",
+        fd,
+        inode_num,
+        length
+    );
+    with_ext2_mount_mut(mount_id, |fs| fs.truncate_file(inode_num, length as u64))
+}
+
+/// sys_truncate - Truncate or extend a file, named by path, to a given length
+///
+/// # Arguments
+/// * `pathname` - Path to the file (userspace pointer to null-terminated string)
+/// * `length` - New file size in bytes
+///
+/// # Returns
+/// 0 on success, negative errno on failure
+///
+/// # Errors
+/// * ENOENT - File does not exist
+/// * EISDIR - pathname refers to a directory
+/// * EINVAL - length is negative or pathname is not a regular file
+/// * EFBIG - length exceeds the maximum file size
+/// * EROFS - The filesystem is read-only
+/// * EIO - I/O error
+pub fn sys_truncate(pathname: u64, length: i64) -> SyscallResult {
+    use super::errno::{EINVAL, EIO};
+    use super::userptr::copy_cstr_from_user;
+    use crate::fs::ext2;
+
+    if length < 0 {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+
+    // Copy path from userspace
+    let raw_path = match copy_cstr_from_user(pathname) {
+        Ok(p) => p,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+
+    // Normalize path
+    let path = if raw_path.starts_with('/') {
+        raw_path
+    } else {
+        let cwd = get_current_cwd().unwrap_or_else(|| alloc::string::String::from("/"));
+        let absolute = if cwd.ends_with('/') {
+            alloc::format!("{}{}", cwd, raw_path)
+        } else {
+            alloc::format!("{}/{}", cwd, raw_path)
+        };
+        normalize_path(&absolute)
+    };
+
+    log::debug!("sys_truncate: path={:?} length={}", path, length);
+
+    // Determine which filesystem to use
+    let is_home = ext2::is_home_path(&path);
+    let fs_path = if is_home {
+        ext2::strip_home_prefix(&path)
+    } else {
+        &path
+    };
+
+    let truncate = |fs: &mut ext2::Ext2Fs| -> Result<(), ext2::Ext2Error> {
+        let inode_num = fs.resolve_path(fs_path)?;
+        fs.truncate_file(inode_num, length as u64)
+    };
+
+    let result = if is_home {
+        let mut fs_guard = ext2::home_fs_write();
+        match fs_guard.as_mut() {
+            Some(fs) => truncate(fs),
+            None => {
+                log::error!("sys_truncate: ext2 home filesystem not mounted");
+                return SyscallResult::Err(EIO as u64);
+            }
+        }
+    } else {
+        let mut fs_guard = ext2::root_fs_write();
+        match fs_guard.as_mut() {
+            Some(fs) => truncate(fs),
+            None => {
+                log::error!("sys_truncate: ext2 root filesystem not mounted");
+                return SyscallResult::Err(EIO as u64);
+            }
+        }
+    };
+
+    match result {
+        Ok(()) => SyscallResult::Ok(0),
+        Err(e) => {
+            log::debug!("sys_truncate: failed: {}", e);
+            SyscallResult::Err(ext2_errno(e) as u64)
+        }
+    }
+}
+
+/// sys_fallocate - Preallocate blocks for a range of an open file
+///
+/// Holes in `[offset, offset + len)` are backed by zeroed blocks so later
+/// writes into the range cannot fail with ENOSPC. Unless FALLOC_FL_KEEP_SIZE
+/// is given, the file grows to cover the range.
+///
+/// # Arguments
+/// * `fd` - File descriptor open for writing
+/// * `mode` - 0 or FALLOC_FL_KEEP_SIZE
+/// * `offset` - Starting byte offset of the range
+/// * `len` - Length of the range in bytes
+///
+/// # Returns
+/// 0 on success, negative errno on failure
+///
+/// # Errors
+/// * EBADF - fd is not a valid file descriptor or not open for writing
+/// * EINVAL - offset is negative or len is not positive
+/// * EOPNOTSUPP - mode contains flags other than FALLOC_FL_KEEP_SIZE
+/// * ESPIPE - fd refers to a pipe
+/// * ENODEV - fd does not refer to a regular file
+/// * ENOSPC - Not enough free blocks for the range
+/// * EFBIG - The range exceeds the maximum file size
+pub fn sys_fallocate(fd: i32, mode: i32, offset: i64, len: i64) -> SyscallResult {
+    use super::errno::{EBADF, EINVAL, ENODEV, EOPNOTSUPP};
+
+    if offset < 0 || len <= 0 {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+    if mode & !FALLOC_FL_KEEP_SIZE != 0 {
+        return SyscallResult::Err(EOPNOTSUPP as u64);
+    }
+
+    let (inode_num, mount_id, flags) = match regular_file_for_fd(fd) {
+        Ok(info) => info,
+        Err(errno) if errno == EINVAL as u64 => return SyscallResult::Err(ENODEV as u64),
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+
+    if flags & 3 == O_RDONLY {
+        return SyscallResult::Err(EBADF as u64);
+    }
+
+    let keep_size = mode & FALLOC_FL_KEEP_SIZE != 0;
+    with_ext2_mount_mut(mount_id, |fs| {
+        fs.allocate_range(inode_num, offset as u64, len as u64, keep_size)
+    })
+}
+
+/// sys_fsync - Flush a file's data and metadata to stable storage
+///
+/// ext2 writes go straight to the block device, so this flushes the
+/// device backing the file's filesystem. Directories may be synced too.
+///
+/// # Arguments
+/// * `fd` - File descriptor of a regular file or directory
+///
+/// # Returns
+/// 0 on success, negative errno on failure
+///
+/// # Errors
+/// * EBADF - fd is not a valid file descriptor
+/// * EINVAL - fd refers to an object that does not support synchronization
+/// * EIO - The device failed to flush
+pub fn sys_fsync(fd: i32) -> SyscallResult {
+    use super::errno::{EBADF, EINVAL, EIO};
+    use crate::fs::ext2;
+
+    let thread_id = match crate::task::scheduler::current_thread_id() {
+        Some(id) => id,
+        None => return SyscallResult::Err(EBADF as u64),
+    };
+
+    let mount_id: Result<usize, u64> = crate::arch_without_interrupts(|| {
+        let manager_guard = crate::process::manager();
+        if let Some(ref manager) = *manager_guard {
+            if let Some((_pid, process)) = manager.find_process_by_thread(thread_id) {
+                if let Some(fd_entry) = process.fd_table.get(fd) {
+                    return match &fd_entry.kind {
+                        FdKind::RegularFile(file_ref) => Ok(file_ref.lock().mount_id),
+                        FdKind::Directory(dir_ref) => Ok(dir_ref.lock().mount_id),
+                        _ => Err(EINVAL as u64),
+                    };
+                }
+            }
+        }
+        Err(EBADF as u64)
+    });
+
+    let mount_id = match mount_id {
+        Ok(id) => id,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+
+    let is_home = ext2::home_mount_id().map_or(false, |id| id == mount_id);
+    let result = if is_home {
+        match ext2::home_fs_read().as_ref() {
+            Some(fs) => fs.sync(),
+            None => return SyscallResult::Err(EIO as u64),
+        }
+    } else {
+        match ext2::root_fs_read().as_ref() {
+            Some(fs) => fs.sync(),
+            None => return SyscallResult::Err(EIO as u64),
+        }
+    };
+
+    match result {
+        Ok(()) => SyscallResult::Ok(0),
+        Err(e) => SyscallResult::Err(ext2_errno(e) as u64),
+    }
+}
+
+/// sys_fdatasync - Flush a file's data to stable storage
+///
+/// Identical to fsync: ext2 metadata is written synchronously with the data,
+/// so there is no cheaper data-only path.
+pub fn sys_fdatasync(fd: i32) -> SyscallResult {
+    sys_fsync(fd)
+}
+
+// =============================================================================
+// statfs / fstatfs
+// =============================================================================
+
+/// Pipe filesystem magic (Linux PIPEFS_MAGIC)
+const PIPEFS_MAGIC: u32 = 0x5049_5045;
+/// Socket filesystem magic (Linux SOCKFS_MAGIC)
+const SOCKFS_MAGIC: u32 = 0x534F_434B;
+/// Anonymous inode filesystem magic (Linux ANON_INODE_FS_MAGIC), used for epoll
+const ANON_INODE_FS_MAGIC: u32 = 0x0904_1934;
+
+/// statfs f_flags: filesystem is mounted read-only
+const ST_RDONLY: i64 = 0x0001;
+
+/// Maximum filename length reported for every filesystem
+const STATFS_NAME_MAX: i64 = 255;
+
+/// statfs structure (Linux compatible - 120 bytes)
+///
+/// x86_64 and the asm-generic layout used by aarch64 agree on this struct
+/// for 64-bit targets, so a single definition serves both.
+#[derive(Clone, Copy, Default)]
+#[repr(C)]
+pub struct StatFs {
+    pub f_type: i64,
+    pub f_bsize: i64,
+    pub f_blocks: u64,
+    pub f_bfree: u64,
+    pub f_bavail: u64,
+    pub f_files: u64,
+    pub f_ffree: u64,
+    pub f_fsid: [i32; 2],
+    pub f_namelen: i64,
+    pub f_frsize: i64,
+    pub f_flags: i64,
+    pub f_spare: [i64; 4],
+}
+
+const _: () = assert!(core::mem::size_of::<StatFs>() == 120);
+
+impl StatFs {
+    /// statfs for a virtual filesystem: no blocks or inodes, just the type
+    fn for_virtual(magic: u32) -> Self {
+        Self {
+            f_type: magic as i64,
+            f_bsize: 4096,
+            f_namelen: STATFS_NAME_MAX,
+            f_frsize: 4096,
+            ..Self::default()
+        }
+    }
+
+    /// statfs for a mounted ext2 filesystem
+    fn for_ext2(fs: &crate::fs::ext2::Ext2Fs) -> Self {
+        let usage = fs.usage();
+        Self {
+            f_type: crate::fs::ext2::EXT2_SUPER_MAGIC as i64,
+            f_bsize: usage.block_size as i64,
+            f_blocks: usage.blocks,
+            f_bfree: usage.free_blocks,
+            f_bavail: usage.free_blocks.saturating_sub(usage.reserved_blocks),
+            f_files: usage.inodes,
+            f_ffree: usage.free_inodes,
+            f_fsid: [fs.mount_id as i32, 0],
+            f_namelen: STATFS_NAME_MAX,
+            f_frsize: usage.block_size as i64,
+            f_flags: if fs.read_only { ST_RDONLY } else { 0 },
+            f_spare: [0; 4],
+        }
+    }
+}
+
+/// Helper: copy a filled-in StatFs out to userspace
+fn copy_statfs_to_user(buf: u64, statfs: &StatFs) -> SyscallResult {
+    use super::errno::EFAULT;
+    use super::userptr::copy_to_user;
+
+    if buf == 0 {
+        return SyscallResult::Err(EFAULT as u64);
+    }
+    match copy_to_user(buf as *mut StatFs, statfs) {
+        Ok(()) => SyscallResult::Ok(0),
+        Err(errno) => SyscallResult::Err(errno),
+    }
+}
+
+/// sys_statfs - Get filesystem statistics for the filesystem containing a path
+///
+/// Paths under /proc, /dev, and /dev/pts report their virtual filesystem's
+/// type magic with zero capacity; everything else reports the ext2 mount
+/// (root or /home) that holds it.
+///
+/// # Arguments
+/// * `pathname` - Path to any file on the filesystem (userspace pointer)
+/// * `buf` - Userspace pointer to a `struct statfs`
+///
+/// # Returns
+/// 0 on success, negative errno on failure
+///
+/// # Errors
+/// * ENOENT - The path does not exist
+/// * EFAULT - buf is not a valid pointer
+pub fn sys_statfs(pathname: u64, buf: u64) -> SyscallResult {
+    use super::errno::ENOENT;
+    use super::userptr::copy_cstr_from_user;
+    use crate::fs::{devfs, devptsfs, ext2, procfs};
+
+    // Copy path from userspace
+    let raw_path = match copy_cstr_from_user(pathname) {
+        Ok(p) => p,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+
+    // Normalize path
+    let path = if raw_path.starts_with('/') {
+        normalize_path(&raw_path)
+    } else {
+        let cwd = get_current_cwd().unwrap_or_else(|| alloc::string::String::from("/"));
+        let absolute = if cwd.ends_with('/') {
+            alloc::format!("{}{}", cwd, raw_path)
+        } else {
+            alloc::format!("{}/{}", cwd, raw_path)
+        };
+        normalize_path(&absolute)
+    };
+
+    log::debug!("sys_statfs: path={:?}", path);
+
+    // Virtual filesystems
+    if path == "/dev/pts" {
+        return copy_statfs_to_user(buf, &StatFs::for_virtual(devptsfs::DEVPTS_SUPER_MAGIC));
+    }
+    if let Some(pty_name) = path.strip_prefix("/dev/pts/") {
+        if devptsfs::lookup(pty_name).is_none() {
+            return SyscallResult::Err(ENOENT as u64);
+        }
+        return copy_statfs_to_user(buf, &StatFs::for_virtual(devptsfs::DEVPTS_SUPER_MAGIC));
+    }
+    if path == "/dev" {
+        return copy_statfs_to_user(buf, &StatFs::for_virtual(devfs::DEVFS_SUPER_MAGIC));
+    }
+    if let Some(device_name) = path.strip_prefix("/dev/") {
+        if devfs::lookup(device_name).is_none() {
+            return SyscallResult::Err(ENOENT as u64);
+        }
+        return copy_statfs_to_user(buf, &StatFs::for_virtual(devfs::DEVFS_SUPER_MAGIC));
+    }
+    if path == "/proc" || path.starts_with("/proc/") {
+        if path != "/proc" && procfs::lookup_by_path(&path).is_none() {
+            return SyscallResult::Err(ENOENT as u64);
+        }
+        return copy_statfs_to_user(buf, &StatFs::for_virtual(procfs::PROC_SUPER_MAGIC));
+    }
+
+    // Determine which ext2 filesystem to use
+    let is_home = ext2::is_home_path(&path);
+    let fs_path = if is_home {
+        ext2::strip_home_prefix(&path)
+    } else {
+        &path
+    };
+
+    let stat = |fs: &ext2::Ext2Fs| -> Result<StatFs, ext2::Ext2Error> {
+        fs.resolve_path(fs_path)?;
+        Ok(StatFs::for_ext2(fs))
+    };
+
+    let result = if is_home {
+        match ext2::home_fs_read().as_ref() {
+            Some(fs) => stat(fs),
+            None => return SyscallResult::Err(ENOENT as u64),
+        }
+    } else {
+        match ext2::root_fs_read().as_ref() {
+            Some(fs) => stat(fs),
+            None => return SyscallResult::Err(ENOENT as u64),
+        }
+    };
+
+    match result {
+        Ok(statfs) => copy_statfs_to_user(buf, &statfs),
+        Err(e) => SyscallResult::Err(ext2_errno(e) as u64),
+    }
+}
+
+/// sys_fstatfs - Get filesystem statistics for the filesystem behind an fd
+///
+/// Pipes, sockets, and epoll instances report the Linux pseudo-filesystem
+/// magic for their kind, so callers can identify them the usual way.
+///
+/// # Arguments
+/// * `fd` - Any open file descriptor
+/// * `buf` - Userspace pointer to a `struct statfs`
+///
+/// # Returns
+/// 0 on success, negative errno on failure
+///
+/// # Errors
+/// * EBADF - fd is not a valid file descriptor
+/// * EFAULT - buf is not a valid pointer
+/// * EIO - The ext2 filesystem behind fd is no longer mounted
+pub fn sys_fstatfs(fd: i32, buf: u64) -> SyscallResult {
+    use super::errno::{EBADF, EIO};
+    use crate::fs::{devfs, devptsfs, ext2, procfs};
+
+    /// Where the fd's filesystem statistics come from
+    enum StatfsSource {
+        Ext2 { mount_id: usize },
+        Virtual(u32),
+    }
+
+    let thread_id = match crate::task::scheduler::current_thread_id() {
+        Some(id) => id,
+        None => return SyscallResult::Err(EBADF as u64),
+    };
+
+    // Classify the fd under the PM lock; ext2 is only touched after it drops
+    let source: Result<StatfsSource, u64> = crate::arch_without_interrupts(|| {
+        let manager_guard = crate::process::manager();
+        let manager = match *manager_guard {
+            Some(ref m) => m,
+            None => return Err(EBADF as u64),
+        };
+        let (_pid, process) = match manager.find_process_by_thread(thread_id) {
+            Some(p) => p,
+            None => return Err(EBADF as u64),
+        };
+        let fd_entry = match process.fd_table.get(fd) {
+            Some(entry) => entry,
+            None => return Err(EBADF as u64),
+        };
+        Ok(match &fd_entry.kind {
+            FdKind::RegularFile(file_ref) => StatfsSource::Ext2 {
+                mount_id: file_ref.lock().mount_id,
+            },
+            FdKind::Directory(dir_ref) => StatfsSource::Ext2 {
+                mount_id: dir_ref.lock().mount_id,
+            },
+            FdKind::StdIo(_)
+            | FdKind::Device(_)
+            | FdKind::Dsp(_)
+            | FdKind::DevfsDirectory { .. }
+            | FdKind::PtyMaster(_) => StatfsSource::Virtual(devfs::DEVFS_SUPER_MAGIC),
+            FdKind::DevptsDirectory { .. } | FdKind::PtySlave(_) => {
+                StatfsSource::Virtual(devptsfs::DEVPTS_SUPER_MAGIC)
+            }
+            FdKind::ProcfsFile { .. } | FdKind::ProcfsDirectory { .. } => {
+                StatfsSource::Virtual(procfs::PROC_SUPER_MAGIC)
+            }
+            FdKind::PipeRead(_)
+            | FdKind::PipeWrite(_)
+            | FdKind::FifoRead(_, _)
+            | FdKind::FifoWrite(_, _) => StatfsSource::Virtual(PIPEFS_MAGIC),
+            FdKind::UdpSocket(_)
+            | FdKind::TcpSocket(_)
+            | FdKind::TcpListener(_)
+            | FdKind::TcpConnection(_)
+            | FdKind::UnixStream(_)
+            | FdKind::UnixSocket(_)
+            | FdKind::UnixListener(_) => StatfsSource::Virtual(SOCKFS_MAGIC),
+            FdKind::Epoll(_) => StatfsSource::Virtual(ANON_INODE_FS_MAGIC),
+        })
+    });
+
+    let statfs = match source {
+        Ok(StatfsSource::Virtual(magic)) => StatFs::for_virtual(magic),
+        Ok(StatfsSource::Ext2 { mount_id }) => {
+            let is_home = ext2::home_mount_id().map_or(false, |id| id == mount_id);
+            let fs_guard = if is_home {
+                ext2::home_fs_read()
+            } else {
+                ext2::root_fs_read()
+            };
+            match fs_guard.as_ref() {
+                Some(fs) => StatFs::for_ext2(fs),
+                None => return SyscallResult::Err(EIO as u64),
+            }
+        }
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+
+    copy_statfs_to_user(buf, &statfs)
+}
+
+// =============================================================================
+// Advisory locking: flock / fcntl record locks
+// =============================================================================
+
+/// flock() operations
+pub const LOCK_SH: i32 = 1;
+pub const LOCK_EX: i32 = 2;
+pub const LOCK_NB: i32 = 4;
+pub const LOCK_UN: i32 = 8;
+
+/// Record lock types (struct flock l_type)
+pub const F_RDLCK: i16 = 0;
+pub const F_WRLCK: i16 = 1;
+pub const F_UNLCK: i16 = 2;
+
+/// Linux struct flock (same layout on x86_64 and aarch64 - 32 bytes)
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct Flock {
+    pub l_type: i16,
+    pub l_whence: i16,
+    pub l_start: i64,
+    pub l_len: i64,
+    pub l_pid: i32,
+}
+
+const _: () = assert!(core::mem::size_of::<Flock>() == 32);
+
+/// What a lock syscall needs to know about its file descriptor
+struct LockTarget {
+    key: crate::fs::lock::FileKey,
+    open_file_id: u64,
+    flags: u32,
+    position: u64,
+    tgid: u64,
+}
+
+/// Helper: resolve a file descriptor to the lock table entry it refers to
+fn lock_target_for_fd(fd: i32) -> Result<LockTarget, u64> {
+    let thread_id = match crate::task::scheduler::current_thread_id() {
+        Some(id) => id,
+        None => return Err(super::errno::EBADF as u64),
+    };
+
+    crate::arch_without_interrupts(|| {
+        let manager_guard = crate::process::manager();
+        let manager = match *manager_guard {
+            Some(ref m) => m,
+            None => return Err(super::errno::EBADF as u64),
+        };
+        let (pid, process) = match manager.find_process_by_thread(thread_id) {
+            Some(p) => p,
+            None => return Err(super::errno::EBADF as u64),
+        };
+        let fd_entry = match process.fd_table.get(fd) {
+            Some(entry) => entry,
+            None => return Err(super::errno::EBADF as u64),
+        };
+        match &fd_entry.kind {
+            FdKind::RegularFile(file_ref) => {
+                let file = file_ref.lock();
+                Ok(LockTarget {
+                    key: (file.mount_id, file.inode_num),
+                    open_file_id: file.open_file_id,
+                    flags: file.flags,
+                    position: file.position,
+                    tgid: process.thread_group_id.unwrap_or(pid.as_u64()),
+                })
+            }
+            _ => Err(super::errno::EINVAL as u64),
+        }
+    })
+}
+
+/// Apply a lock request, sleeping on the file's waitqueue while a
+/// conflicting lock is held if `wait` is set
+///
+/// Follows the futex wait pattern: the thread is queued and marked blocked
+/// under the lock table lock, then halts until a release wakes it or a
+/// signal arrives, and retries from the top.
+fn apply_file_lock(
+    key: crate::fs::lock::FileKey,
+    request: crate::fs::lock::LockRequest,
+    owner: crate::fs::lock::LockOwner,
+    wait: bool,
+) -> SyscallResult {
+    use super::errno::{EAGAIN, EDEADLK, EINTR, ESRCH};
+    use crate::fs::lock::{self, LockAttempt};
+    use crate::task::thread::ThreadState;
+
+    loop {
+        match lock::try_lock(key, &request, wait) {
+            LockAttempt::Granted => return SyscallResult::Ok(0),
+            LockAttempt::WouldBlock => return SyscallResult::Err(EAGAIN as u64),
+            LockAttempt::Deadlock => return SyscallResult::Err(EDEADLK as u64),
+            LockAttempt::WaitFailed => return SyscallResult::Err(ESRCH as u64),
+            LockAttempt::Queued => {}
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        crate::per_cpu_aarch64::preempt_enable();
+        #[cfg(target_arch = "x86_64")]
+        crate::per_cpu::preempt_enable();
+
+        let mut signal_pending = false;
+        loop {
+            if crate::syscall::check_signals_for_eintr().is_some() {
+                signal_pending = true;
+                break;
+            }
+
+            let still_waiting = crate::task::scheduler::with_scheduler(|sched| {
+                sched
+                    .current_thread_mut()
+                    .map(|thread| thread.state == ThreadState::BlockedOnIO)
+                    .unwrap_or(false)
+            })
+            .unwrap_or(false);
+
+            if !still_waiting {
+                break;
+            }
+
+            crate::task::scheduler::yield_current();
+            Cpu::halt_with_interrupts();
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        crate::per_cpu_aarch64::preempt_disable();
+        #[cfg(target_arch = "x86_64")]
+        crate::per_cpu::preempt_disable();
+
+        lock::finish_wait(key, owner);
+
+        #[cfg(target_arch = "aarch64")]
+        super::futex::ensure_current_address_space();
+
+        if signal_pending {
+            return SyscallResult::Err(EINTR as u64);
+        }
+    }
+}
+
+/// sys_flock - Apply or remove an advisory lock on a whole file
+///
+/// flock locks belong to the open file description, so they are shared by
+/// descriptors created with dup() or inherited across fork(), and released
+/// when the last of them is closed. Converting between shared and exclusive
+/// is allowed; flock and fcntl record locks do not interact.
+///
+/// # Arguments
+/// * `fd` - Open file descriptor
+/// * `operation` - LOCK_SH, LOCK_EX, or LOCK_UN, optionally ORed with LOCK_NB
+///
+/// # Returns
+/// 0 on success, negative errno on failure
+///
+/// # Errors
+/// * EBADF - fd is not a valid file descriptor
+/// * EINVAL - operation is invalid, or fd is not a regular file
+/// * EWOULDBLOCK - LOCK_NB was given and a conflicting lock is held
+/// * EINTR - Interrupted by a signal while waiting
+pub fn sys_flock(fd: i32, operation: i32) -> SyscallResult {
+    use super::errno::EINVAL;
+    use crate::fs::lock::{LockKind, LockOwner, LockRequest};
+
+    let kind = match operation & !LOCK_NB {
+        LOCK_SH => Some(LockKind::Shared),
+        LOCK_EX => Some(LockKind::Exclusive),
+        LOCK_UN => None,
+        _ => return SyscallResult::Err(EINVAL as u64),
+    };
+
+    let target = match lock_target_for_fd(fd) {
+        Ok(target) => target,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+
+    let request = LockRequest::Flock {
+        open_file: target.open_file_id,
+        kind,
+    };
+    apply_file_lock(
+        target.key,
+        request,
+        LockOwner::OpenFile(target.open_file_id),
+        operation & LOCK_NB == 0,
+    )
+}
+
+/// Helper: convert a struct flock's whence/start/len to a `[start, end)`
+/// byte range, with `LOCK_TO_EOF` for a lock that extends to end of file
+fn flock_range(flock: &Flock, target: &LockTarget) -> Result<(u64, u64), u64> {
+    use super::errno::{EINVAL, EIO};
+    use crate::fs::lock::LOCK_TO_EOF;
+
+    let base = match flock.l_whence as i32 {
+        SEEK_SET => 0,
+        SEEK_CUR => target.position as i64,
+        SEEK_END => match get_ext2_file_size_for_mount(target.key.1, target.key.0) {
+            Some(size) => size as i64,
+            None => return Err(EIO as u64),
+        },
+        _ => return Err(EINVAL as u64),
+    };
+
+    let start = base.checked_add(flock.l_start).ok_or(EINVAL as u64)?;
+    let (start, end) = match flock.l_len {
+        0 => (start, None),
+        len if len > 0 => (start, Some(start.checked_add(len).ok_or(EINVAL as u64)?)),
+        // A negative length covers the bytes before l_start
+        len => (start.checked_add(len).ok_or(EINVAL as u64)?, Some(start)),
+    };
+    if start < 0 {
+        return Err(EINVAL as u64);
+    }
+
+    Ok((start as u64, end.map_or(LOCK_TO_EOF, |end| end as u64)))
+}
+
+/// fcntl record-lock commands: F_GETLK, F_SETLK, F_SETLKW and their
+/// F_OFD_* counterparts
+///
+/// POSIX locks are owned by the calling process (thread group), released
+/// when it closes any descriptor for the file, and not inherited by fork.
+/// OFD locks are owned by the open file description, like flock locks, but
+/// cover byte ranges. Both kinds of lock share one range space per file.
+///
+/// # Arguments
+/// * `fd` - Open file descriptor
+/// * `cmd` - One of the record lock commands
+/// * `arg` - Userspace pointer to a `struct flock`
+///
+/// # Returns
+/// 0 on success, negative errno on failure
+///
+/// # Errors
+/// * EBADF - fd is not valid, or not open for reading (F_RDLCK) or writing (F_WRLCK)
+/// * EINVAL - Bad lock type, whence, or range; l_pid not 0 for an OFD lock
+/// * EFAULT - arg is not a valid userspace pointer
+/// * EAGAIN - F_SETLK and a conflicting lock is held
+/// * EDEADLK - F_SETLKW would deadlock
+/// * EINTR - Interrupted by a signal while waiting
+pub fn sys_fcntl_lock(fd: i32, cmd: i32, arg: u64) -> SyscallResult {
+    use super::errno::{EBADF, EINVAL};
+    use super::userptr::{copy_from_user, copy_to_user};
+    use crate::fs::lock::{self, LockKind, LockOwner, LockRequest, LOCK_TO_EOF};
+    use crate::ipc::fd::fcntl_cmd::*;
+
+    let ofd = matches!(cmd, F_OFD_GETLK | F_OFD_SETLK | F_OFD_SETLKW);
+
+    let mut flock = match copy_from_user(arg as *const Flock) {
+        Ok(flock) => flock,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+    if ofd && flock.l_pid != 0 {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+
+    let target = match lock_target_for_fd(fd) {
+        Ok(target) => target,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+    let (start, end) = match flock_range(&flock, &target) {
+        Ok(range) => range,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+
+    let owner = if ofd {
+        LockOwner::OpenFile(target.open_file_id)
+    } else {
+        LockOwner::Process(target.tgid)
+    };
+    let access_mode = target.flags & 3;
+    let kind = match flock.l_type {
+        F_RDLCK if access_mode == O_WRONLY => return SyscallResult::Err(EBADF as u64),
+        F_WRLCK if access_mode == O_RDONLY => return SyscallResult::Err(EBADF as u64),
+        F_RDLCK => Some(LockKind::Shared),
+        F_WRLCK => Some(LockKind::Exclusive),
+        F_UNLCK => None,
+        _ => return SyscallResult::Err(EINVAL as u64),
+    };
+
+    match cmd {
+        F_GETLK | F_OFD_GETLK => {
+            let Some(kind) = kind else {
+                return SyscallResult::Err(EINVAL as u64);
+            };
+            match lock::test_record(target.key, owner, kind, start, end) {
+                Some(held) => {
+                    flock.l_type = match held.kind {
+                        LockKind::Shared => F_RDLCK,
+                        LockKind::Exclusive => F_WRLCK,
+                    };
+                    flock.l_whence = SEEK_SET as i16;
+                    flock.l_start = held.start as i64;
+                    flock.l_len = if held.end == LOCK_TO_EOF {
+                        0
+                    } else {
+                        (held.end - held.start) as i64
+                    };
+                    flock.l_pid = match held.owner {
+                        LockOwner::Process(tgid) => tgid as i32,
+                        LockOwner::OpenFile(_) => -1,
+                    };
+                }
+                None => flock.l_type = F_UNLCK,
+            }
+            match copy_to_user(arg as *mut Flock, &flock) {
+                Ok(()) => SyscallResult::Ok(0),
+                Err(errno) => SyscallResult::Err(errno),
+            }
+        }
+        _ => {
+            let request = LockRequest::Record {
+                owner,
+                kind,
+                start,
+                end,
+            };
+            let wait = matches!(cmd, F_SETLKW | F_OFD_SETLKW);
+            apply_file_lock(target.key, request, owner, wait)
+        }
+    }
+}
@@ -392,8 +392,8 @@ pub fn futex_wake_for_thread_group(tg_id: u64, uaddr: u64, max_wake: u32) -> u32
 
 #[cfg(target_arch = "aarch64")]
 /// Duplicate of time.rs's private helper because time.rs is prohibited from
-/// modification.
-fn ensure_current_address_space() {
+/// modification. Also used by the file lock wait in fs.rs.
+pub(super) fn ensure_current_address_space() {
     let thread_id = match crate::task::scheduler::current_thread_id() {
         Some(id) => id,
         None => return,
@@ -2,6 +2,7 @@
 
 #![cfg(feature = "boot_tests")]
 
+use crate::time::monotonic_ns;
 use core::sync::atomic::{AtomicU64, Ordering};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -69,14 +70,6 @@ static STAGE3_ELAPSED_NS: AtomicU64 = AtomicU64::new(0);
 static DRIVEN: AtomicU64 = AtomicU64::new(0);
 static RESCUES: AtomicU64 = AtomicU64::new(0);
 
-#[inline]
-fn now_ns() -> u64 {
-    let (seconds, nanos) = crate::time::get_monotonic_time_ns();
-    seconds
-        .saturating_mul(1_000_000_000)
-        .saturating_add(nanos)
-}
-
 pub fn arm_from_val3(val3: u32) -> Option<Stage> {
     match val3 {
         STAGE1_SENTINEL => Some(Stage::S1),
@@ -95,7 +88,7 @@ pub fn is_report(val3: u32) -> bool {
 }
 
 pub fn record_arm(stage: Stage, tg_id: u64, uaddr: u64) -> u64 {
-    let started_at = now_ns();
+    let started_at = monotonic_ns();
     match stage {
         Stage::S1 => {
             STAGE1_TG_ID.store(tg_id, Ordering::Release);
@@ -131,11 +124,11 @@ pub fn stage2_drive(tg_id: u64, uaddr: u64) {
 }
 
 pub fn deadline_passed(deadline_ns: u64) -> bool {
-    now_ns() >= deadline_ns
+    monotonic_ns() >= deadline_ns
 }
 
 pub fn elapsed_since_arm(stage: Stage) -> u64 {
-    now_ns().saturating_sub(match stage {
+    monotonic_ns().saturating_sub(match stage {
         Stage::S1 => STAGE1_ARM_NS.load(Ordering::Acquire),
         Stage::S2 => STAGE2_ARM_NS.load(Ordering::Acquire),
         Stage::S3 => STAGE3_ARM_NS.load(Ordering::Acquire),
@@ -84,6 +84,18 @@ static COMPOSITOR_LAST_MOUSE: core::sync::atomic::AtomicU64 = core::sync::atomic
 static COMPOSITOR_DIRTY_WAKE: core::sync::atomic::AtomicBool =
     core::sync::atomic::AtomicBool::new(false);
 
+/// Set when a client starts a drag (op=32 on the drag selection).
+/// compositor_wait reports it so BWM starts routing drag events.
+#[cfg(target_arch = "aarch64")]
+static COMPOSITOR_DRAG_WAKE: core::sync::atomic::AtomicBool =
+    core::sync::atomic::AtomicBool::new(false);
+
+/// Set when a window changes its cursor shape (op=40).
+/// compositor_wait reports it so BWM re-picks the cursor under the pointer.
+#[cfg(target_arch = "aarch64")]
+static COMPOSITOR_CURSOR_WAKE: core::sync::atomic::AtomicBool =
+    core::sync::atomic::AtomicBool::new(false);
+
 /// Wake the compositor thread if it's blocked in compositor_wait (op=23).
 /// Called from input interrupt handlers (mouse, keyboard) to provide low-latency
 /// input response without polling.
@@ -94,9 +106,10 @@ pub fn wake_compositor_if_waiting() {
 
 /// Clean up all window buffers owned by a terminated process.
 /// Removes entries from the registry and wakes the compositor so it
-/// discovers the removal and repaints.
+/// discovers the removal and repaints, and cancels any drag it started.
 #[cfg(target_arch = "aarch64")]
 pub fn cleanup_windows_for_pid(pid: u64) {
+    SELECTIONS.lock().cancel_drag_for_pid(pid);
     let mut reg = WINDOW_REGISTRY.lock();
     if reg.remove_for_pid(pid) {
         REGISTRY_GENERATION.fetch_add(1, core::sync::atomic::Ordering::Release);
@@ -246,6 +259,18 @@ fn compositor_ready_bits(last_registry_gen: u64, prev_mouse: u64) -> (u64, u64,
     if crate::drivers::usb::hid::has_pending_super_tap() {
         ready |= 8;
     }
+    // A client started a drag: BWM routes drag events until the release
+    if COMPOSITOR_DRAG_WAKE.swap(false, Ordering::Relaxed) {
+        ready |= 16;
+    }
+    // Outputs were hotplugged or changed mode: BWM re-reads the layout
+    if crate::drivers::virtio::gpu_display::take_layout_changed() {
+        ready |= 32;
+    }
+    // A window changed its cursor shape: BWM re-picks the cursor
+    if COMPOSITOR_CURSOR_WAKE.swap(false, Ordering::Relaxed) {
+        ready |= 64;
+    }
 
     (ready, cur_reg_gen, mouse_packed)
 }
@@ -397,6 +422,8 @@ struct WindowBuffer {
     last_uploaded_gen: u64,
     /// Last generation read via read_window_buffer (op=14)
     last_read_gen: u64,
+    /// Cursor shape the client wants over its content (op=40)
+    cursor_shape: u32,
     /// Physical addresses of all backing pages (for VirGL scatter-gather)
     page_phys_addrs: alloc::vec::Vec<u64>,
     /// Thread ID waiting for compositor to consume this frame (frame pacing)
@@ -492,6 +519,7 @@ impl WindowRegistry {
             generation: 1,
             last_uploaded_gen: 0,
             last_read_gen: 0,
+            cursor_shape: 0,
             page_phys_addrs,
             waiting_thread_id: None,
             input_ring: [WindowInputEvent::default(); INPUT_RING_SIZE],
@@ -555,6 +583,109 @@ impl WindowRegistry {
     }
 }
 
+// =============================================================================
+// Selections — clipboard and drag-and-drop data shared between windows.
+// The kernel keeps its own copy of the data, so the clipboard outlives the
+// window that set it. Only compiled for ARM64, next to the window registry.
+// =============================================================================
+
+/// Selection slot holding the clipboard
+#[cfg(target_arch = "aarch64")]
+const SELECTION_CLIPBOARD: usize = 0;
+
+/// Selection slot holding the data of the current (or last) drag
+#[cfg(target_arch = "aarch64")]
+const SELECTION_DRAG: usize = 1;
+
+/// Maximum number of MIME types one selection can be offered in
+#[cfg(target_arch = "aarch64")]
+const MAX_SELECTION_OFFERS: usize = 8;
+
+/// Maximum MIME type length in bytes
+#[cfg(target_arch = "aarch64")]
+const MAX_MIME_LEN: usize = 64;
+
+/// Maximum data size of a selection, summed over its offers
+#[cfg(target_arch = "aarch64")]
+const MAX_SELECTION_BYTES: usize = 1024 * 1024;
+
+/// One MIME type a selection is offered in, as passed to set_selection (op=32).
+/// Must match `SelectionOfferDesc` in libbreenix.
+#[cfg(target_arch = "aarch64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SelectionOfferDesc {
+    mime_ptr: u64,
+    mime_len: u64,
+    data_ptr: u64,
+    data_len: u64,
+}
+
+/// Request to read one MIME type of a selection (op=34).
+/// Must match `SelectionReadDesc` in libbreenix.
+#[cfg(target_arch = "aarch64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SelectionReadDesc {
+    mime_ptr: u64,
+    mime_len: u64,
+    out_ptr: u64,
+    out_len: u64,
+}
+
+#[cfg(target_arch = "aarch64")]
+struct SelectionOffer {
+    mime: alloc::vec::Vec<u8>,
+    data: alloc::vec::Vec<u8>,
+}
+
+#[cfg(target_arch = "aarch64")]
+/// Data set on a selection slot, in one or more MIME types.
+struct Selection {
+    /// Window buffer the data came from
+    source_id: u32,
+    /// Process that set the data
+    owner_pid: u64,
+    offers: alloc::vec::Vec<SelectionOffer>,
+}
+
+#[cfg(target_arch = "aarch64")]
+impl Selection {
+    fn find(&self, mime: &[u8]) -> Option<&SelectionOffer> {
+        self.offers.iter().find(|o| o.mime == mime)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+struct SelectionState {
+    slots: [Option<Selection>; 2],
+    /// Whether the drag slot belongs to a drag still in progress. BWM ends
+    /// the drag (op=36) when the button is released.
+    drag_active: bool,
+}
+
+#[cfg(target_arch = "aarch64")]
+impl SelectionState {
+    const fn new() -> Self {
+        Self {
+            slots: [None, None],
+            drag_active: false,
+        }
+    }
+
+    /// Drop the drag of an exiting process. Its clipboard data stays.
+    fn cancel_drag_for_pid(&mut self, pid: u64) {
+        if matches!(self.slots[SELECTION_DRAG], Some(ref sel) if sel.owner_pid == pid) {
+            self.slots[SELECTION_DRAG] = None;
+            self.drag_active = false;
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+/// Global clipboard and drag selections. Protected by a spinlock.
+static SELECTIONS: Mutex<SelectionState> = Mutex::new(SelectionState::new());
+
 /// Framebuffer info structure returned by sys_fbinfo.
 /// This matches the userspace FbInfo struct in libbreenix.
 #[cfg(any(target_arch = "aarch64", feature = "interactive"))]
@@ -1299,12 +1430,15 @@ fn handle_virgl_op(cmd: &FbDrawCmd) -> SyscallResult {
         }
         25 => {
             // SetCursorShape: change the active cursor shape.
-            // p1=shape (0=arrow, 1=NS, 2=EW, 3=NWSE, 4=NESW)
+            // p1=shape (0=arrow, 1=NS, 2=EW, 3=NWSE, 4=NESW, 5=text,
+            // 6=the image last set with op=39)
             #[cfg(target_arch = "aarch64")]
             {
                 let shape = cmd.p1 as u32;
-                crate::drivers::virtio::gpu_pci::set_cursor_shape(shape);
-                SyscallResult::Ok(0)
+                match crate::drivers::virtio::gpu_cursor::set_shape(shape) {
+                    Ok(()) => SyscallResult::Ok(0),
+                    Err(_) => SyscallResult::Err(super::ErrorCode::InvalidArgument as u64),
+                }
             }
             #[cfg(not(target_arch = "aarch64"))]
             {
@@ -1343,6 +1477,86 @@ fn handle_virgl_op(cmd: &FbDrawCmd) -> SyscallResult {
             let count = crate::drivers::usb::hid::take_super_tap_count();
             SyscallResult::Ok(count as u64)
         }
+        32 => {
+            // SetSelection: offer data on the clipboard or start a drag.
+            // p1=selection (0=clipboard, 1=drag), p2=source buffer_id,
+            // p3/p4=pointer to SelectionOfferDesc array, color=offer count.
+            // A count of 0 clears the selection.
+            handle_set_selection(cmd)
+        }
+        33 => {
+            // ListSelection: the MIME types a selection is offered in.
+            // p1=selection, p2/p3=out_ptr, p4=out_len
+            // Writes the types separated by '\n'; returns the full length.
+            handle_list_selection(cmd)
+        }
+        34 => {
+            // ReadSelection: copy a selection's data in one MIME type.
+            // p1=selection, p2/p3=pointer to SelectionReadDesc
+            // Returns the full data length (may exceed out_len).
+            handle_read_selection(cmd)
+        }
+        35 => {
+            // DragSource: buffer_id of the window that started the drag in
+            // progress, or 0 if there is none. Polled by BWM.
+            let selections = SELECTIONS.lock();
+            match selections.slots[SELECTION_DRAG] {
+                Some(ref sel) if selections.drag_active => SyscallResult::Ok(sel.source_id as u64),
+                _ => SyscallResult::Ok(0),
+            }
+        }
+        36 => {
+            // EndDrag: the drag in progress was dropped or cancelled. Its data
+            // stays readable so the drop target can fetch it.
+            SELECTIONS.lock().drag_active = false;
+            SyscallResult::Ok(0)
+        }
+        37 => {
+            // ListDisplays: the GPU's outputs and where each shows the desktop.
+            // p1/p2=pointer to DisplayDesc array, p3=max entries
+            // Returns the number of outputs (may exceed max entries).
+            handle_list_displays(cmd)
+        }
+        38 => {
+            // SetDisplayMode: p1=scanout, p2=width, p3=height (0x0 turns the
+            // output off). The mode must fit in the desktop sized at boot.
+            let (id, w, h) = (cmd.p1 as u32 as usize, cmd.p2 as u32, cmd.p3 as u32);
+            match crate::drivers::virtio::gpu_display::set_mode(id, w, h) {
+                Ok(()) => {
+                    wake_compositor_if_waiting();
+                    SyscallResult::Ok(0)
+                }
+                Err(e) => {
+                    log::warn!("set_display_mode({}, {}x{}): {}", id, w, h, e);
+                    SyscallResult::Err(super::ErrorCode::InvalidArgument as u64)
+                }
+            }
+        }
+        39 => {
+            // SetCursorImage: upload a BGRA cursor image and show it.
+            // p1/p2=pixels ptr, p3=width | height<<16 (at most 64x64),
+            // p4=hot_x | hot_y<<16
+            handle_set_cursor_image(cmd)
+        }
+        40 => {
+            // SetWindowCursor: the shape a window wants while the pointer is
+            // over its content. p1=buffer_id, p2=shape. BWM applies it.
+            handle_set_window_cursor(cmd)
+        }
+        41 => {
+            // WindowCursor: p1=buffer_id. Returns the window's cursor shape
+            // (0=arrow if it never set one). Polled by BWM.
+            match WINDOW_REGISTRY.lock().find(cmd.p1 as u32) {
+                Some(buf) => SyscallResult::Ok(buf.cursor_shape as u64),
+                None => SyscallResult::Err(super::ErrorCode::InvalidArgument as u64),
+            }
+        }
+        42 => {
+            // HardwareCursor: 1 if the pointer is in the GPU's cursor plane,
+            // so moving it needs no recomposite.
+            let active = crate::drivers::virtio::gpu_cursor::hardware_active();
+            SyscallResult::Ok(active as u64)
+        }
         _ => {
             crate::serial_println!("[virgl-op] UNKNOWN op={}", cmd.op);
             SyscallResult::Err(super::ErrorCode::InvalidArgument as u64)
@@ -1350,10 +1564,293 @@ fn handle_virgl_op(cmd: &FbDrawCmd) -> SyscallResult {
     }
 }
 
+/// Process ID of the calling thread's process.
+#[cfg(target_arch = "aarch64")]
+fn caller_pid() -> Option<u64> {
+    let thread_id = crate::syscall::memory_common::get_current_thread_id()?;
+    let manager_guard = crate::process::manager();
+    let manager = manager_guard.as_ref()?;
+    manager
+        .find_process_by_thread(thread_id)
+        .map(|(pid, _)| pid.as_u64())
+}
+
+/// Validate a selection number from userspace.
+#[cfg(target_arch = "aarch64")]
+fn selection_slot(selection: i32) -> Result<usize, u64> {
+    match selection as u32 as usize {
+        slot @ (SELECTION_CLIPBOARD | SELECTION_DRAG) => Ok(slot),
+        _ => Err(super::ErrorCode::InvalidArgument as u64),
+    }
+}
+
+/// Borrow `len` bytes of user memory at `ptr`, after a range check.
+#[cfg(target_arch = "aarch64")]
+fn user_bytes<'a>(ptr: u64, len: u64) -> Result<&'a [u8], u64> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if ptr == 0 || ptr.checked_add(len).map_or(true, |end| end > USER_SPACE_MAX) {
+        return Err(super::ErrorCode::Fault as u64);
+    }
+    Ok(unsafe { core::slice::from_raw_parts(ptr as *const u8, len as usize) })
+}
+
+/// Handle set_cursor_image (op=39): copy the image from userspace and make
+/// it the active cursor.
+#[cfg(target_arch = "aarch64")]
+fn handle_set_cursor_image(cmd: &FbDrawCmd) -> SyscallResult {
+    let ptr = (cmd.p1 as u32 as u64) | ((cmd.p2 as u32 as u64) << 32);
+    let (w, h) = (cmd.p3 as u32 & 0xFFFF, cmd.p3 as u32 >> 16);
+    let (hot_x, hot_y) = (cmd.p4 as u32 & 0xFFFF, cmd.p4 as u32 >> 16);
+    let size = crate::drivers::virtio::gpu_cursor::CURSOR_SIZE;
+    if w == 0 || h == 0 || w > size || h > size || ptr % 4 != 0 {
+        return SyscallResult::Err(super::ErrorCode::InvalidArgument as u64);
+    }
+    let count = (w * h) as usize;
+    if let Err(e) = user_bytes(ptr, (count * 4) as u64) {
+        return SyscallResult::Err(e);
+    }
+    let pixels = unsafe { core::slice::from_raw_parts(ptr as *const u32, count) };
+    match crate::drivers::virtio::gpu_cursor::set_image(pixels, w, h, hot_x, hot_y) {
+        Ok(()) => SyscallResult::Ok(0),
+        Err(_) => SyscallResult::Err(super::ErrorCode::InvalidArgument as u64),
+    }
+}
+
+/// Handle set_window_cursor (op=40): record the shape a window wants. The
+/// caller must own the window; BWM is woken to re-pick the cursor.
+#[cfg(target_arch = "aarch64")]
+fn handle_set_window_cursor(cmd: &FbDrawCmd) -> SyscallResult {
+    let (buffer_id, shape) = (cmd.p1 as u32, cmd.p2 as u32);
+    if shape > crate::drivers::virtio::gpu_cursor::SHAPE_CUSTOM {
+        return SyscallResult::Err(super::ErrorCode::InvalidArgument as u64);
+    }
+    let pid = match caller_pid() {
+        Some(pid) => pid,
+        None => return SyscallResult::Err(super::ErrorCode::NoSuchProcess as u64),
+    };
+    {
+        let mut registry = WINDOW_REGISTRY.lock();
+        let buf = match registry.find_mut(buffer_id) {
+            Some(buf) => buf,
+            None => return SyscallResult::Err(super::ErrorCode::InvalidArgument as u64),
+        };
+        if buf.owner_pid != pid {
+            return SyscallResult::Err(super::ErrorCode::PermissionDenied as u64);
+        }
+        if buf.cursor_shape == shape {
+            return SyscallResult::Ok(0);
+        }
+        buf.cursor_shape = shape;
+    }
+    COMPOSITOR_CURSOR_WAKE.store(true, core::sync::atomic::Ordering::Relaxed);
+    wake_compositor_if_waiting();
+    SyscallResult::Ok(0)
+}
+
+/// Handle set_selection (op=32): copy the offered data into a selection slot.
+///
+/// The caller must own the source window. Setting the drag selection starts
+/// a drag and wakes BWM to route it.
+#[cfg(target_arch = "aarch64")]
+fn handle_set_selection(cmd: &FbDrawCmd) -> SyscallResult {
+    let slot = match selection_slot(cmd.p1) {
+        Ok(slot) => slot,
+        Err(e) => return SyscallResult::Err(e),
+    };
+    let source_id = cmd.p2 as u32;
+    let descs_ptr = (cmd.p3 as u32 as u64) | ((cmd.p4 as u32 as u64) << 32);
+    let count = cmd.color as usize;
+    if count > MAX_SELECTION_OFFERS {
+        return SyscallResult::Err(super::ErrorCode::InvalidArgument as u64);
+    }
+
+    let pid = match caller_pid() {
+        Some(pid) => pid,
+        None => return SyscallResult::Err(super::ErrorCode::NoSuchProcess as u64),
+    };
+    let owns_source = WINDOW_REGISTRY
+        .lock()
+        .find(source_id)
+        .map_or(false, |buf| buf.owner_pid == pid);
+    if !owns_source {
+        return SyscallResult::Err(super::ErrorCode::PermissionDenied as u64);
+    }
+
+    let desc_bytes = count * core::mem::size_of::<SelectionOfferDesc>();
+    let descs = match user_bytes(descs_ptr, desc_bytes as u64) {
+        Ok(_) if count == 0 => &[][..],
+        Ok(_) => unsafe {
+            core::slice::from_raw_parts(descs_ptr as *const SelectionOfferDesc, count)
+        },
+        Err(e) => return SyscallResult::Err(e),
+    };
+
+    let mut offers = alloc::vec::Vec::with_capacity(count);
+    let mut total = 0usize;
+    for desc in descs {
+        if desc.mime_len == 0 || desc.mime_len as usize > MAX_MIME_LEN {
+            return SyscallResult::Err(super::ErrorCode::InvalidArgument as u64);
+        }
+        total = total.saturating_add(desc.data_len as usize);
+        if total > MAX_SELECTION_BYTES {
+            return SyscallResult::Err(super::ErrorCode::InvalidArgument as u64);
+        }
+        let (mime, data) = match (
+            user_bytes(desc.mime_ptr, desc.mime_len),
+            user_bytes(desc.data_ptr, desc.data_len),
+        ) {
+            (Ok(mime), Ok(data)) => (mime, data),
+            (Err(e), _) | (_, Err(e)) => return SyscallResult::Err(e),
+        };
+        offers.push(SelectionOffer {
+            mime: mime.to_vec(),
+            data: data.to_vec(),
+        });
+    }
+
+    let mut selections = SELECTIONS.lock();
+    if offers.is_empty() {
+        selections.slots[slot] = None;
+        if slot == SELECTION_DRAG {
+            selections.drag_active = false;
+        }
+        return SyscallResult::Ok(0);
+    }
+    selections.slots[slot] = Some(Selection {
+        source_id,
+        owner_pid: pid,
+        offers,
+    });
+    if slot == SELECTION_DRAG {
+        selections.drag_active = true;
+        drop(selections);
+        COMPOSITOR_DRAG_WAKE.store(true, core::sync::atomic::Ordering::Relaxed);
+        COMPOSITOR_FRAME_WQ.wake_up();
+    }
+    SyscallResult::Ok(0)
+}
+
+/// Handle list_selection (op=33): write the selection's MIME types, one per
+/// line, and return the length of the full list.
+#[cfg(target_arch = "aarch64")]
+fn handle_list_selection(cmd: &FbDrawCmd) -> SyscallResult {
+    let slot = match selection_slot(cmd.p1) {
+        Ok(slot) => slot,
+        Err(e) => return SyscallResult::Err(e),
+    };
+    let out_ptr = (cmd.p2 as u32 as u64) | ((cmd.p3 as u32 as u64) << 32);
+    let out_len = cmd.p4 as u32 as u64;
+    if let Err(e) = user_bytes(out_ptr, out_len) {
+        return SyscallResult::Err(e);
+    }
+
+    let mut list = alloc::vec::Vec::new();
+    if let Some(ref sel) = SELECTIONS.lock().slots[slot] {
+        for (i, offer) in sel.offers.iter().enumerate() {
+            if i > 0 {
+                list.push(b'\n');
+            }
+            list.extend_from_slice(&offer.mime);
+        }
+    }
+    let n = list.len().min(out_len as usize);
+    if n > 0 {
+        unsafe { core::ptr::copy_nonoverlapping(list.as_ptr(), out_ptr as *mut u8, n) };
+    }
+    SyscallResult::Ok(list.len() as u64)
+}
+
+/// Handle read_selection (op=34): copy as much of the data offered in the
+/// requested MIME type as fits, and return its full length.
+#[cfg(target_arch = "aarch64")]
+fn handle_read_selection(cmd: &FbDrawCmd) -> SyscallResult {
+    let slot = match selection_slot(cmd.p1) {
+        Ok(slot) => slot,
+        Err(e) => return SyscallResult::Err(e),
+    };
+    let desc_ptr = (cmd.p2 as u32 as u64) | ((cmd.p3 as u32 as u64) << 32);
+    if let Err(e) = user_bytes(desc_ptr, core::mem::size_of::<SelectionReadDesc>() as u64) {
+        return SyscallResult::Err(e);
+    }
+    let desc: SelectionReadDesc = unsafe { core::ptr::read(desc_ptr as *const SelectionReadDesc) };
+    if desc.mime_len as usize > MAX_MIME_LEN {
+        return SyscallResult::Err(super::ErrorCode::InvalidArgument as u64);
+    }
+    let mime = match user_bytes(desc.mime_ptr, desc.mime_len) {
+        Ok(mime) => mime,
+        Err(e) => return SyscallResult::Err(e),
+    };
+    if let Err(e) = user_bytes(desc.out_ptr, desc.out_len) {
+        return SyscallResult::Err(e);
+    }
+
+    let selections = SELECTIONS.lock();
+    let offer = match selections.slots[slot].as_ref().and_then(|sel| sel.find(mime)) {
+        Some(offer) => offer,
+        None => return SyscallResult::Err(super::errno::ENOENT as u64),
+    };
+    let n = offer.data.len().min(desc.out_len as usize);
+    if n > 0 {
+        unsafe { core::ptr::copy_nonoverlapping(offer.data.as_ptr(), desc.out_ptr as *mut u8, n) };
+    }
+    SyscallResult::Ok(offer.data.len() as u64)
+}
+
+/// One display output, as returned by list_displays (op=37).
+/// Must match `DisplayInfo` in libbreenix.
+#[cfg(target_arch = "aarch64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DisplayDesc {
+    id: u32,
+    /// bit0 = a display is connected, bit1 = the output is on
+    flags: u32,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    preferred_width: u32,
+    preferred_height: u32,
+}
+
+/// Handle list_displays (op=37): describe each output of the GPU. Re-reads
+/// the outputs from the device first if a display event is pending.
+#[cfg(target_arch = "aarch64")]
+fn handle_list_displays(cmd: &FbDrawCmd) -> SyscallResult {
+    let out_ptr = (cmd.p1 as u32 as u64) | ((cmd.p2 as u32 as u64) << 32);
+    let max = cmd.p3 as u32 as usize;
+    let out_len = (max * core::mem::size_of::<DisplayDesc>()) as u64;
+    if let Err(e) = user_bytes(out_ptr, out_len) {
+        return SyscallResult::Err(e);
+    }
+    let layout = match crate::drivers::virtio::gpu_display::layout() {
+        Some(layout) => layout,
+        None => return SyscallResult::Err(super::ErrorCode::InvalidArgument as u64),
+    };
+
+    for (i, s) in layout.scanouts[..layout.count].iter().enumerate().take(max) {
+        let desc = DisplayDesc {
+            id: i as u32,
+            flags: (s.connected as u32) | ((s.active as u32) << 1),
+            x: s.x,
+            y: s.y,
+            width: s.width,
+            height: s.height,
+            preferred_width: s.preferred_width,
+            preferred_height: s.preferred_height,
+        };
+        unsafe { core::ptr::write_unaligned((out_ptr as *mut DisplayDesc).add(i), desc) };
+    }
+    SyscallResult::Ok(layout.count as u64)
+}
+
 /// Handle compositor_wait (op=23): block until the compositor has work to do.
 ///
 /// Returns packed value: (registry_generation << 8) | ready_bitmask
-///   bits 0-7: ready bitmask (bit0=dirty, bit1=mouse, bit2=registry)
+///   bits 0-7: ready bitmask (bit0=dirty, bit1=mouse, bit2=registry,
+///             bit3=super tap, bit4=drag, bit5=displays)
 ///   bits 8-31: current registry generation (for next call's last_registry_gen)
 ///
 /// If nothing is ready, blocks the compositor thread on `COMPOSITOR_FRAME_WQ`.
@@ -205,7 +205,14 @@ pub extern "C" fn rust_syscall_handler(frame: &mut SyscallFrame) {
         emit_ring3_syscall_marker();
     }
 
-    let syscall_num = frame.syscall_number();
+    // Time from here until the return to userspace is system time
+    crate::task::accounting::syscall_entry();
+
+    let mut syscall_num = frame.syscall_number();
+    // A traced process may stop at syscall entry; its tracer can change the call
+    if crate::process::ptrace::active() {
+        syscall_num = super::ptrace::syscall_entry_x86_64(frame);
+    }
     let args = frame.args();
 
     // Trace syscall entry - compiles to ~5 instructions when tracing disabled
@@ -273,6 +280,17 @@ pub extern "C" fn rust_syscall_handler(frame: &mut SyscallFrame) {
             super::signal::sys_sigsuspend_with_frame(args.0, args.1, frame)
         }
         Some(SyscallNumber::Sigaltstack) => super::signal::sys_sigaltstack(args.0, args.1),
+        Some(SyscallNumber::Tkill) => super::signal::sys_tkill(args.0 as i64, args.1 as i32),
+        Some(SyscallNumber::Tgkill) => {
+            super::signal::sys_tgkill(args.0 as i64, args.1 as i64, args.2 as i32)
+        }
+        Some(SyscallNumber::RtSigqueueinfo) => {
+            super::signal::sys_rt_sigqueueinfo(args.0 as i64, args.1 as i32, args.2)
+        }
+        Some(SyscallNumber::RtSigtimedwait) => {
+            super::signal::sys_rt_sigtimedwait(args.0, args.1, args.2, args.3)
+        }
+        Some(SyscallNumber::Ptrace) => super::ptrace::sys_ptrace(args.0, args.1, args.2, args.3),
         Some(SyscallNumber::Sigreturn) => {
             // CRITICAL: sigreturn restores ALL registers including RAX from the signal frame.
             // We must NOT overwrite RAX with the syscall return value after this call!
@@ -288,6 +306,7 @@ pub extern "C" fn rust_syscall_handler(frame: &mut SyscallFrame) {
                 crate::gdt::set_tss_rsp0(VirtAddr::new(kernel_stack_top));
             }
             crate::irq_log::flush_local_try();
+            crate::task::accounting::syscall_exit();
             crate::per_cpu::preempt_enable();
             return;
         }
@@ -333,7 +352,10 @@ pub extern "C" fn rust_syscall_handler(frame: &mut SyscallFrame) {
             super::signal::sys_setitimer(args.0 as i32, args.1, args.2)
         }
         Some(SyscallNumber::Wait4) => {
-            super::handlers::sys_waitpid(args.0 as i64, args.1, args.2 as u32)
+            super::handlers::sys_waitpid(args.0 as i64, args.1, args.2 as u32, args.3)
+        }
+        Some(SyscallNumber::Waitid) => {
+            super::waitid::sys_waitid(args.0 as u32, args.1, args.2, args.3 as u32, args.4)
         }
         Some(SyscallNumber::SetPgid) => super::session::sys_setpgid(args.0 as i32, args.1 as i32),
         Some(SyscallNumber::SetSid) => super::session::sys_setsid(),
@@ -460,6 +482,38 @@ pub extern "C" fn rust_syscall_handler(frame: &mut SyscallFrame) {
         Some(SyscallNumber::Prlimit64) => {
             super::handlers::sys_prlimit64(args.0, args.1, args.2, args.3)
         }
+        Some(SyscallNumber::Getrusage) => super::rusage::sys_getrusage(args.0 as i32, args.1),
+        Some(SyscallNumber::Times) => super::rusage::sys_times(args.0),
+        // Scheduling priorities
+        Some(SyscallNumber::Getpriority) => super::sched::sys_getpriority(args.0 as i32, args.1),
+        Some(SyscallNumber::Setpriority) => {
+            super::sched::sys_setpriority(args.0 as i32, args.1, args.2 as i32)
+        }
+        Some(SyscallNumber::SchedSetparam) => {
+            super::sched::sys_sched_setparam(args.0 as i64, args.1)
+        }
+        Some(SyscallNumber::SchedGetparam) => {
+            super::sched::sys_sched_getparam(args.0 as i64, args.1)
+        }
+        Some(SyscallNumber::SchedSetscheduler) => {
+            super::sched::sys_sched_setscheduler(args.0 as i64, args.1 as u32, args.2)
+        }
+        Some(SyscallNumber::SchedGetscheduler) => {
+            super::sched::sys_sched_getscheduler(args.0 as i64)
+        }
+        Some(SyscallNumber::SchedGetPriorityMax) => {
+            super::sched::sys_sched_get_priority_max(args.0 as u32)
+        }
+        Some(SyscallNumber::SchedGetPriorityMin) => {
+            super::sched::sys_sched_get_priority_min(args.0 as u32)
+        }
+        Some(SyscallNumber::SchedSetaffinity) => {
+            super::sched::sys_sched_setaffinity(args.0 as i64, args.1, args.2)
+        }
+        Some(SyscallNumber::SchedGetaffinity) => {
+            super::sched::sys_sched_getaffinity(args.0 as i64, args.1, args.2)
+        }
+        Some(SyscallNumber::Getcpu) => super::sched::sys_getcpu(args.0, args.1),
         Some(SyscallNumber::Uname) => super::handlers::sys_uname(args.0),
         // epoll
         Some(SyscallNumber::EpollCreate1) => super::epoll::sys_epoll_create1(args.0 as u32),
@@ -497,6 +551,31 @@ pub extern "C" fn rust_syscall_handler(frame: &mut SyscallFrame) {
         Some(SyscallNumber::Pwrite64) => {
             super::handlers::sys_pwrite64(args.0 as i32, args.1, args.2, args.3 as i64)
         }
+        // File size and durability
+        Some(SyscallNumber::Truncate) => super::fs::sys_truncate(args.0, args.1 as i64),
+        Some(SyscallNumber::Ftruncate) => super::fs::sys_ftruncate(args.0 as i32, args.1 as i64),
+        Some(SyscallNumber::Fallocate) => {
+            super::fs::sys_fallocate(args.0 as i32, args.1 as i32, args.2 as i64, args.3 as i64)
+        }
+        Some(SyscallNumber::Fsync) => super::fs::sys_fsync(args.0 as i32),
+        Some(SyscallNumber::Fdatasync) => super::fs::sys_fdatasync(args.0 as i32),
+        Some(SyscallNumber::Statfs) => super::fs::sys_statfs(args.0, args.1),
+        Some(SyscallNumber::Fstatfs) => super::fs::sys_fstatfs(args.0 as i32, args.1),
+        Some(SyscallNumber::Flock) => super::fs::sys_flock(args.0 as i32, args.1 as i32),
+        Some(SyscallNumber::Sendfile) => {
+            super::splice::sys_sendfile(args.0 as i32, args.1 as i32, args.2, args.3)
+        }
+        Some(SyscallNumber::Splice) => super::splice::sys_splice(
+            args.0 as i32,
+            args.1,
+            args.2 as i32,
+            args.3,
+            args.4,
+            args.5 as u32,
+        ),
+        Some(SyscallNumber::Tee) => {
+            super::splice::sys_tee(args.0 as i32, args.1 as i32, args.2, args.3 as u32)
+        }
         Some(SyscallNumber::Spawn) => SyscallResult::Err(super::ErrorCode::NoSys as u64),
         None => {
             log::warn!("Unknown syscall number: {} - returning ENOSYS", syscall_num);
@@ -524,6 +603,10 @@ pub extern "C" fn rust_syscall_handler(frame: &mut SyscallFrame) {
     // Without this, a process that sends a signal to itself and then loops calling
     // yield() would never receive the signal (it would only get delivered on timer
     // interrupt, which might not fire for several milliseconds).
+    // A traced process may stop at syscall exit and for pending signals first
+    if crate::process::ptrace::active() {
+        super::ptrace::syscall_exit_x86_64(frame, syscall_num);
+    }
     check_and_deliver_signals_on_syscall_return(frame);
 
     // CRITICAL FIX: Update TSS.RSP0 before returning to userspace
@@ -539,6 +622,8 @@ pub extern "C" fn rust_syscall_handler(frame: &mut SyscallFrame) {
     // Flush any pending IRQ logs before returning to userspace
     crate::irq_log::flush_local_try();
 
+    crate::task::accounting::syscall_exit();
+
     // Decrement preempt count on syscall exit
     crate::per_cpu::preempt_enable();
 }
@@ -721,18 +806,27 @@ fn deliver_pending_signals_syscall(
             None => return crate::signal::delivery::SignalDeliveryResult::NoAction,
         };
 
-        // Clear pending flag for this signal
-        process.signals.clear_pending(sig);
+        // Clear pending flag for this signal and take its siginfo
+        let info = process.signals.dequeue_signal(sig);
 
         // Get the handler for this signal
         let action = *process.signals.get_handler(sig);
 
+        // A traced process stops for its tracer before a handler runs; leave
+        // the signal for the interrupt return path, which parks the thread
+        if !matches!(action.handler, SIG_DFL | SIG_IGN)
+            && crate::process::ptrace::intercept_signal(process, sig)
+        {
+            process.signals.queue_signal(info);
+            return crate::signal::delivery::SignalDeliveryResult::NoAction;
+        }
+
         match action.handler {
             SIG_DFL => {
                 // Default action - delegate to main delivery code
                 // For simplicity, return NoAction and let timer interrupt handle it
                 // This avoids duplicating termination logic here
-                process.signals.set_pending(sig); // Re-queue for timer interrupt
+                process.signals.queue_signal(info); // Re-queue for timer interrupt
                 return crate::signal::delivery::SignalDeliveryResult::NoAction;
             }
             SIG_IGN => {
@@ -744,7 +838,7 @@ fn deliver_pending_signals_syscall(
                     process,
                     frame,
                     saved_regs,
-                    sig,
+                    &info,
                     handler_addr,
                     &action,
                 );
@@ -759,13 +853,16 @@ fn deliver_to_user_handler_syscall(
     process: &mut crate::process::Process,
     frame: &mut SyscallInterruptFrameWrapper,
     saved_regs: &mut crate::task::process_context::SavedRegisters,
-    sig: u32,
+    info: &crate::signal::types::SigInfo,
     handler_addr: u64,
     action: &crate::signal::types::SignalAction,
 ) {
     use crate::signal::constants::*;
+    use crate::signal::delivery::FrameLayout;
     use crate::signal::types::*;
 
+    let sig = info.signo();
+
     // Get current user stack pointer
     let current_rsp = frame.rsp;
     let original_rsp = current_rsp;
@@ -776,6 +873,7 @@ fn deliver_to_user_handler_syscall(
         && process.signals.alt_stack.size > 0
         && !process.signals.alt_stack.on_stack;
 
+    let uc_stack = process.signals.alt_stack.to_stack_t();
     let user_rsp = if use_alt_stack {
         // Use alternate stack - stack grows down, so start at top (base + size)
         let alt_top = process.signals.alt_stack.base + process.signals.alt_stack.size as u64;
@@ -786,37 +884,23 @@ fn deliver_to_user_handler_syscall(
         current_rsp
     };
 
-    // Calculate space needed for signal frame (and optionally trampoline)
-    let frame_size = SignalFrame::SIZE as u64;
-
     // Check if the handler provides a restorer function (SA_RESTORER flag)
     // If so, use it instead of writing trampoline to the stack.
     // This is essential for signals delivered on alternate stacks where the
     // stack may not be executable (NX bit set).
     let use_restorer = (action.flags & SA_RESTORER) != 0 && action.restorer != 0;
 
-    let (frame_rsp, return_addr) = if use_restorer {
+    // Lay out signal frame, siginfo, ucontext (and optionally trampoline)
+    let layout = FrameLayout::below(user_rsp, !use_restorer);
+    let frame_rsp = layout.frame;
+
+    let return_addr = if use_restorer {
         // Use the restorer function provided by the application/libc
-        let frame_rsp = (user_rsp - frame_size) & !0xF;
-        (frame_rsp, action.restorer)
+        action.restorer
     } else {
         // Fall back to writing trampoline on the stack
-        let trampoline_size = crate::signal::trampoline::SIGNAL_TRAMPOLINE_SIZE as u64;
-        let total_size = frame_size + trampoline_size;
-        let frame_rsp = (user_rsp - total_size) & !0xF;
-        let trampoline_rsp = frame_rsp + frame_size;
-
-        // Write trampoline code to user stack
-        unsafe {
-            let trampoline_ptr = trampoline_rsp as *mut u8;
-            core::ptr::copy_nonoverlapping(
-                crate::signal::trampoline::SIGNAL_TRAMPOLINE.as_ptr(),
-                trampoline_ptr,
-                crate::signal::trampoline::SIGNAL_TRAMPOLINE_SIZE,
-            );
-        }
-
-        (frame_rsp, trampoline_rsp)
+        layout.write_trampoline();
+        layout.trampoline
     };
 
     // Build signal frame with saved context
@@ -824,8 +908,8 @@ fn deliver_to_user_handler_syscall(
         trampoline_addr: return_addr,
         magic: SignalFrame::MAGIC,
         signal: sig as u64,
-        siginfo_ptr: 0,
-        ucontext_ptr: 0,
+        siginfo_ptr: layout.siginfo,
+        ucontext_ptr: layout.ucontext,
         saved_rip: frame.rip,
         saved_rsp: original_rsp,
         saved_rflags: frame.rflags,
@@ -847,11 +931,12 @@ fn deliver_to_user_handler_syscall(
         saved_blocked: process.signals.blocked,
     };
 
-    // Write signal frame to user stack
+    // Write signal frame, siginfo and ucontext to user stack
     unsafe {
         let frame_ptr = frame_rsp as *mut SignalFrame;
         core::ptr::write_volatile(frame_ptr, signal_frame);
     }
+    layout.write_handler_context(&signal_frame, info, uc_stack);
 
     // Block signals during handler execution
     if (action.flags & SA_NODEFER) == 0 {
@@ -863,10 +948,11 @@ fn deliver_to_user_handler_syscall(
     frame.rip = handler_addr;
     frame.rsp = frame_rsp;
 
-    // Set up arguments for signal handler: void handler(int signum)
+    // Set up arguments for signal handler:
+    // void handler(int signum, siginfo_t *info, void *ucontext)
     saved_regs.rdi = sig as u64;
-    saved_regs.rsi = 0;
-    saved_regs.rdx = 0;
+    saved_regs.rsi = layout.siginfo;
+    saved_regs.rdx = layout.ucontext;
 }
 
 #[cfg(test)]
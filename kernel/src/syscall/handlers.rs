@@ -3,6 +3,7 @@
 //! This module contains the actual implementation of each system call.
 
 use super::SyscallResult;
+use crate::process::{Rlimit, RLIM_INFINITY};
 #[cfg(target_arch = "x86_64")]
 use alloc::boxed::Box;
 use alloc::vec::Vec;
@@ -33,6 +34,9 @@ const FD_STDOUT: u64 = 1;
 #[allow(dead_code)]
 const FD_STDERR: u64 = 2;
 
+/// Largest read() served from /dev/dsp in one call
+const DSP_READ_MAX: usize = 64 * 1024;
+
 /// Copy data from userspace memory
 ///
 /// CRITICAL: This function works WITHOUT switching page tables.
@@ -254,8 +258,6 @@ pub fn sys_exit(exit_code: i32) -> SyscallResult {
 ///
 /// Supports stdout/stderr (serial port) and pipe write ends.
 pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
-    use crate::ipc::FdKind;
-
     // Note: Logging removed from hot path to prevent stack overflow.
     // Each log call in interactive mode writes to the Logs terminal,
     // which adds significant stack depth during syscall handling.
@@ -273,12 +275,23 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
         }
     };
 
+    write_buffer_to_fd(fd, &buffer)
+}
+
+/// Write a kernel buffer to a file descriptor
+///
+/// The body of sys_write after the user copy. Also used by sendfile and
+/// splice to push file or pipe data into any writable descriptor without
+/// bouncing it through userspace.
+pub(super) fn write_buffer_to_fd(fd: u64, buffer: &[u8]) -> SyscallResult {
+    use crate::ipc::FdKind;
+
     // Get current process to look up fd
     let thread_id = match crate::task::scheduler::current_thread_id() {
         Some(id) => id,
         None => {
             // Fall back to stdio behavior for kernel threads
-            return write_to_stdio(fd, &buffer);
+            return write_to_stdio(fd, buffer);
         }
     };
 
@@ -307,6 +320,10 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
         Device {
             device_type: crate::fs::devfs::DeviceType,
         },
+        Dsp {
+            stream: crate::audio::SharedStream,
+            is_nonblocking: bool,
+        },
         PtyMaster(u32),
         PtySlave(u32),
         Ebadf,
@@ -322,12 +339,12 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
                 Some((_pid, p)) => p,
                 None => {
                     // Fall back to stdio behavior for kernel threads
-                    return write_to_stdio(fd, &buffer);
+                    return write_to_stdio(fd, buffer);
                 }
             },
             None => {
                 // Fall back to stdio behavior for kernel threads
-                return write_to_stdio(fd, &buffer);
+                return write_to_stdio(fd, buffer);
             }
         };
 
@@ -375,20 +392,25 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
             FdKind::ProcfsFile { .. } => WriteOperation::Ebadf,
             FdKind::ProcfsDirectory { .. } => WriteOperation::Eisdir,
             FdKind::Epoll(_) => WriteOperation::Ebadf,
+            FdKind::Dsp(stream) => WriteOperation::Dsp {
+                stream: stream.clone(),
+                is_nonblocking: (fd_entry.status_flags & crate::ipc::fd::status_flags::O_NONBLOCK)
+                    != 0,
+            },
         }
         // manager_guard dropped here, releasing the lock before I/O
     };
 
     // Now perform the actual I/O operation without holding the manager lock
     match write_op {
-        WriteOperation::StdIo => write_to_stdio(fd, &buffer),
+        WriteOperation::StdIo => write_to_stdio(fd, buffer),
         WriteOperation::Ebadf => SyscallResult::Err(9), // EBADF
         WriteOperation::Enotconn => SyscallResult::Err(super::errno::ENOTCONN as u64),
         WriteOperation::Eisdir => SyscallResult::Err(super::errno::EISDIR as u64),
         WriteOperation::Eopnotsupp => SyscallResult::Err(95), // EOPNOTSUPP
         WriteOperation::PtyMaster(pty_num) => {
             if let Some(pair) = crate::tty::pty::get(pty_num) {
-                match pair.master_write(&buffer) {
+                match pair.master_write(buffer) {
                     Ok(n) => SyscallResult::Ok(n as u64),
                     Err(e) => SyscallResult::Err(e as u64),
                 }
@@ -398,7 +420,17 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
         }
         WriteOperation::PtySlave(pty_num) => {
             if let Some(pair) = crate::tty::pty::get(pty_num) {
-                match pair.slave_write(&buffer) {
+                if pair.stops_background_writes() {
+                    let (session, foreground) = pair.job_control();
+                    if let Err(e) = super::signal::check_tty_access(
+                        session,
+                        foreground,
+                        crate::signal::constants::SIGTTOU,
+                    ) {
+                        return SyscallResult::Err(e);
+                    }
+                }
+                match pair.slave_write(buffer) {
                     Ok(n) => SyscallResult::Ok(n as u64),
                     Err(e) => SyscallResult::Err(e as u64),
                 }
@@ -411,7 +443,7 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
             is_nonblocking,
         } => {
             let mut pipe = pipe_buffer.lock();
-            match pipe.write(&buffer) {
+            match pipe.write(buffer) {
                 Ok(n) => {
                     log::debug!("sys_write: Wrote {} bytes to pipe", n);
                     SyscallResult::Ok(n as u64)
@@ -431,7 +463,7 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
             is_nonblocking,
         } => {
             let mut pipe = pipe_buffer.lock();
-            match pipe.write(&buffer) {
+            match pipe.write(buffer) {
                 Ok(n) => {
                     log::debug!("sys_write: Wrote {} bytes to FIFO", n);
                     SyscallResult::Ok(n as u64)
@@ -447,7 +479,7 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
         }
         WriteOperation::UnixStream { socket } => {
             let sock = socket.lock();
-            match sock.write(&buffer) {
+            match sock.write(buffer) {
                 Ok(n) => {
                     log::debug!("sys_write: Wrote {} bytes to Unix socket", n);
                     SyscallResult::Ok(n as u64)
@@ -457,7 +489,7 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
         }
         WriteOperation::TcpConnection { conn_id } => {
             // Write to established TCP connection
-            match crate::net::tcp::tcp_send(&conn_id, &buffer) {
+            match crate::net::tcp::tcp_send(&conn_id, buffer) {
                 Ok(n) => {
                     crate::net::drain_loopback_queue();
                     log::debug!("sys_write: Wrote {} bytes to TCP connection", n);
@@ -489,10 +521,18 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
                 }
                 DeviceType::Console | DeviceType::Tty => {
                     // Write to console/tty
-                    write_to_stdio(fd, &buffer)
+                    write_to_stdio(fd, buffer)
                 }
+                DeviceType::Dsp => SyscallResult::Err(super::errno::EINVAL as u64),
             }
         }
+        WriteOperation::Dsp {
+            stream,
+            is_nonblocking,
+        } => match crate::audio::write(&stream, buffer, is_nonblocking) {
+            Ok(n) => SyscallResult::Ok(n as u64),
+            Err(e) => SyscallResult::Err(e as u64),
+        },
         WriteOperation::RegularFile { file } => {
             // Write to ext2 regular file
             let (inode_num, position, flags, file_mount_id) = {
@@ -522,9 +562,9 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
                 } else {
                     position
                 };
-                let bw = match fs.write_file_range(inode_num as u32, wo, &buffer) {
+                let bw = match fs.write_file_range(inode_num as u32, wo, buffer) {
                     Ok(n) => n,
-                    Err(_) => return SyscallResult::Err(super::errno::EIO as u64),
+                    Err(e) => return SyscallResult::Err(super::fs::ext2_errno(e) as u64),
                 };
                 (wo, bw)
             } else {
@@ -541,9 +581,9 @@ pub fn sys_write(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
                 } else {
                     position
                 };
-                let bw = match fs.write_file_range(inode_num as u32, wo, &buffer) {
+                let bw = match fs.write_file_range(inode_num as u32, wo, buffer) {
                     Ok(n) => n,
-                    Err(_) => return SyscallResult::Err(super::errno::EIO as u64),
+                    Err(e) => return SyscallResult::Err(super::fs::ext2_errno(e) as u64),
                 };
                 (wo, bw)
             };
@@ -585,6 +625,95 @@ fn write_to_stdio(fd: u64, buffer: &[u8]) -> SyscallResult {
     SyscallResult::Ok(bytes_written as u64)
 }
 
+/// Block until `pipe` has data or every writer has closed it.
+///
+/// Shared by blocking reads of pipes and FIFOs and by splice()/tee(). The
+/// caller retries its read afterwards; a wakeup does not promise data (another
+/// reader may have taken it). Returns EINTR if a signal arrives while waiting.
+pub(crate) fn wait_for_pipe_data(
+    pipe: &alloc::sync::Arc<spin::Mutex<crate::ipc::pipe::PipeBuffer>>,
+) -> Result<(), u64> {
+    let thread_id = match crate::task::scheduler::current_thread_id() {
+        Some(tid) => tid,
+        None => return Err(3), // ESRCH
+    };
+
+    log::debug!("pipe read: thread {} entering blocking path", thread_id);
+
+    // Register as waiter BEFORE setting blocked state (race condition fix)
+    pipe.lock().add_read_waiter(thread_id);
+
+    // Block the thread
+    crate::task::scheduler::with_scheduler(|sched| {
+        sched.block_current();
+        if let Some(thread) = sched.current_thread_mut() {
+            thread.blocked_in_syscall = true;
+        }
+    });
+
+    // Check if data arrived during setup (race condition fix)
+    if pipe.lock().has_data_or_eof() {
+        // Data arrived during setup - unblock and let the caller retry
+        pipe.lock().remove_read_waiter(thread_id);
+        crate::task::scheduler::with_scheduler(|sched| {
+            if let Some(thread) = sched.current_thread_mut() {
+                thread.blocked_in_syscall = false;
+                thread.set_ready();
+            }
+        });
+        return Ok(());
+    }
+
+    // Enable preemption for HLT loop
+    crate::per_cpu::preempt_enable();
+
+    // HLT loop - wait for data or EOF
+    loop {
+        // Check for pending signals that should interrupt this syscall
+        if let Some(e) = crate::syscall::check_signals_for_eintr() {
+            // Signal pending - clean up and return EINTR
+            pipe.lock().remove_read_waiter(thread_id);
+            crate::task::scheduler::with_scheduler(|sched| {
+                if let Some(thread) = sched.current_thread_mut() {
+                    thread.blocked_in_syscall = false;
+                    thread.set_ready();
+                }
+            });
+            crate::per_cpu::preempt_disable();
+            log::debug!("pipe read: thread {} interrupted by signal (EINTR)", thread_id);
+            return Err(e as u64);
+        }
+
+        crate::task::scheduler::yield_current();
+        crate::arch_halt_with_interrupts();
+
+        let still_blocked = crate::task::scheduler::with_scheduler(|sched| {
+            if let Some(thread) = sched.current_thread_mut() {
+                thread.state == crate::task::thread::ThreadState::Blocked
+            } else {
+                false
+            }
+        })
+        .unwrap_or(false);
+
+        if !still_blocked {
+            crate::per_cpu::preempt_disable();
+            log::debug!("pipe read: thread {} woken from blocking", thread_id);
+            break;
+        }
+    }
+
+    // Clear blocked state
+    crate::task::scheduler::with_scheduler(|sched| {
+        if let Some(thread) = sched.current_thread_mut() {
+            thread.blocked_in_syscall = false;
+        }
+    });
+    reset_quantum();
+    crate::task::scheduler::check_and_clear_need_resched();
+    Ok(())
+}
+
 /// sys_read - Read from a file descriptor
 ///
 /// Supports stdin (with blocking), stdout/stderr (error), and pipe read ends.
@@ -815,104 +944,10 @@ pub fn sys_read(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
                         }
 
                         // === BLOCKING PATH ===
-                        let thread_id = match crate::task::scheduler::current_thread_id() {
-                            Some(tid) => tid,
-                            None => return SyscallResult::Err(3), // ESRCH
-                        };
-
-                        log::debug!(
-                            "sys_read: Pipe empty, thread {} entering blocking path",
-                            thread_id
-                        );
-
-                        // Register as waiter BEFORE setting blocked state (race condition fix)
-                        {
-                            let mut pipe = pipe_buffer_clone.lock();
-                            pipe.add_read_waiter(thread_id);
+                        if let Err(e) = wait_for_pipe_data(&pipe_buffer_clone) {
+                            return SyscallResult::Err(e);
                         }
 
-                        // Block the thread
-                        crate::task::scheduler::with_scheduler(|sched| {
-                            sched.block_current();
-                            if let Some(thread) = sched.current_thread_mut() {
-                                thread.blocked_in_syscall = true;
-                            }
-                        });
-
-                        // Check if data arrived during setup (race condition fix)
-                        let data_ready = {
-                            let pipe = pipe_buffer_clone.lock();
-                            pipe.has_data_or_eof()
-                        };
-
-                        if data_ready {
-                            // Data arrived during setup - unblock and retry immediately
-                            crate::task::scheduler::with_scheduler(|sched| {
-                                if let Some(thread) = sched.current_thread_mut() {
-                                    thread.blocked_in_syscall = false;
-                                    thread.set_ready();
-                                }
-                            });
-                            continue; // Retry read
-                        }
-
-                        // Enable preemption for HLT loop
-                        crate::per_cpu::preempt_enable();
-
-                        // HLT loop - wait for data or EOF
-                        loop {
-                            // Check for pending signals that should interrupt this syscall
-                            if let Some(e) = crate::syscall::check_signals_for_eintr() {
-                                // Signal pending - clean up and return EINTR
-                                {
-                                    let mut pipe = pipe_buffer_clone.lock();
-                                    pipe.remove_read_waiter(thread_id);
-                                }
-                                crate::task::scheduler::with_scheduler(|sched| {
-                                    if let Some(thread) = sched.current_thread_mut() {
-                                        thread.blocked_in_syscall = false;
-                                        thread.set_ready();
-                                    }
-                                });
-                                crate::per_cpu::preempt_disable();
-                                log::debug!(
-                                    "sys_read: Pipe thread {} interrupted by signal (EINTR)",
-                                    thread_id
-                                );
-                                return SyscallResult::Err(e as u64);
-                            }
-
-                            crate::task::scheduler::yield_current();
-                            crate::arch_halt_with_interrupts();
-
-                            let still_blocked = crate::task::scheduler::with_scheduler(|sched| {
-                                if let Some(thread) = sched.current_thread_mut() {
-                                    thread.state == crate::task::thread::ThreadState::Blocked
-                                } else {
-                                    false
-                                }
-                            })
-                            .unwrap_or(false);
-
-                            if !still_blocked {
-                                crate::per_cpu::preempt_disable();
-                                log::debug!(
-                                    "sys_read: Pipe thread {} woken from blocking",
-                                    thread_id
-                                );
-                                break;
-                            }
-                        }
-
-                        // Clear blocked state
-                        crate::task::scheduler::with_scheduler(|sched| {
-                            if let Some(thread) = sched.current_thread_mut() {
-                                thread.blocked_in_syscall = false;
-                            }
-                        });
-                        reset_quantum();
-                        crate::task::scheduler::check_and_clear_need_resched();
-
                         // Continue loop to retry read
                         continue;
                     }
@@ -966,104 +1001,10 @@ pub fn sys_read(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
                         }
 
                         // === BLOCKING PATH ===
-                        let thread_id = match crate::task::scheduler::current_thread_id() {
-                            Some(tid) => tid,
-                            None => return SyscallResult::Err(3), // ESRCH
-                        };
-
-                        log::debug!(
-                            "sys_read: FIFO empty, thread {} entering blocking path",
-                            thread_id
-                        );
-
-                        // Register as waiter BEFORE setting blocked state (race condition fix)
-                        {
-                            let mut pipe = pipe_buffer_clone.lock();
-                            pipe.add_read_waiter(thread_id);
+                        if let Err(e) = wait_for_pipe_data(&pipe_buffer_clone) {
+                            return SyscallResult::Err(e);
                         }
 
-                        // Block the thread
-                        crate::task::scheduler::with_scheduler(|sched| {
-                            sched.block_current();
-                            if let Some(thread) = sched.current_thread_mut() {
-                                thread.blocked_in_syscall = true;
-                            }
-                        });
-
-                        // Check if data arrived during setup (race condition fix)
-                        let data_ready = {
-                            let pipe = pipe_buffer_clone.lock();
-                            pipe.has_data_or_eof()
-                        };
-
-                        if data_ready {
-                            // Data arrived during setup - unblock and retry immediately
-                            crate::task::scheduler::with_scheduler(|sched| {
-                                if let Some(thread) = sched.current_thread_mut() {
-                                    thread.blocked_in_syscall = false;
-                                    thread.set_ready();
-                                }
-                            });
-                            continue; // Retry read
-                        }
-
-                        // Enable preemption for HLT loop
-                        crate::per_cpu::preempt_enable();
-
-                        // HLT loop - wait for data or EOF
-                        loop {
-                            // Check for pending signals that should interrupt this syscall
-                            if let Some(e) = crate::syscall::check_signals_for_eintr() {
-                                // Signal pending - clean up and return EINTR
-                                {
-                                    let mut pipe = pipe_buffer_clone.lock();
-                                    pipe.remove_read_waiter(thread_id);
-                                }
-                                crate::task::scheduler::with_scheduler(|sched| {
-                                    if let Some(thread) = sched.current_thread_mut() {
-                                        thread.blocked_in_syscall = false;
-                                        thread.set_ready();
-                                    }
-                                });
-                                crate::per_cpu::preempt_disable();
-                                log::debug!(
-                                    "sys_read: FIFO thread {} interrupted by signal (EINTR)",
-                                    thread_id
-                                );
-                                return SyscallResult::Err(e as u64);
-                            }
-
-                            crate::task::scheduler::yield_current();
-                            crate::arch_halt_with_interrupts();
-
-                            let still_blocked = crate::task::scheduler::with_scheduler(|sched| {
-                                if let Some(thread) = sched.current_thread_mut() {
-                                    thread.state == crate::task::thread::ThreadState::Blocked
-                                } else {
-                                    false
-                                }
-                            })
-                            .unwrap_or(false);
-
-                            if !still_blocked {
-                                crate::per_cpu::preempt_disable();
-                                log::debug!(
-                                    "sys_read: FIFO thread {} woken from blocking",
-                                    thread_id
-                                );
-                                break;
-                            }
-                        }
-
-                        // Clear blocked state
-                        crate::task::scheduler::with_scheduler(|sched| {
-                            if let Some(thread) = sched.current_thread_mut() {
-                                thread.blocked_in_syscall = false;
-                            }
-                        });
-                        reset_quantum();
-                        crate::task::scheduler::check_and_clear_need_resched();
-
                         // Continue loop to retry read
                         continue;
                     }
@@ -1455,6 +1396,15 @@ pub fn sys_read(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
             };
             drop(manager_guard);
 
+            let (session, foreground) = pair.job_control();
+            if let Err(e) = super::signal::check_tty_access(
+                session,
+                foreground,
+                crate::signal::constants::SIGTTIN,
+            ) {
+                return SyscallResult::Err(e);
+            }
+
             let mut user_buf = alloc::vec![0u8; count as usize];
 
             loop {
@@ -1690,6 +1640,7 @@ pub fn sys_read(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
         FdKind::ProcfsFile {
             ref content,
             position,
+            ..
         } => {
             // Read from procfs virtual file
             let content = content.clone();
@@ -1728,6 +1679,26 @@ pub fn sys_read(fd: u64, buf_ptr: u64, count: u64) -> SyscallResult {
             // Cannot read from epoll fd directly
             SyscallResult::Err(super::errno::EINVAL as u64)
         }
+        FdKind::Dsp(stream) => {
+            let is_nonblocking =
+                (fd_entry.status_flags & crate::ipc::fd::status_flags::O_NONBLOCK) != 0;
+            let stream = stream.clone();
+
+            // Release process manager lock before blocking for captured audio
+            drop(manager_guard);
+
+            // Bound the kernel buffer; read() may return less than asked
+            let mut user_buf = alloc::vec![0u8; (count as usize).min(DSP_READ_MAX)];
+            match crate::audio::read(&stream, &mut user_buf, is_nonblocking) {
+                Ok(n) => {
+                    if n > 0 && copy_to_user(buf_ptr, user_buf.as_ptr() as u64, n).is_err() {
+                        return SyscallResult::Err(super::errno::EFAULT as u64);
+                    }
+                    SyscallResult::Ok(n as u64)
+                }
+                Err(e) => SyscallResult::Err(e as u64),
+            }
+        }
     }
 }
 
@@ -2179,13 +2150,7 @@ fn load_elf_from_ext2(path: &str) -> Result<Vec<u8>, i32> {
 fn load_elf_from_ext2_fs(fs: &crate::fs::ext2::Ext2Fs, path: &str) -> Result<Vec<u8>, i32> {
     use super::errno::{EACCES, EIO, ENOTDIR};
 
-    let inode_num = fs.resolve_path(path).map_err(|e| {
-        if e.contains("not found") {
-            super::errno::ENOENT
-        } else {
-            EIO
-        }
-    })?;
+    let inode_num = fs.resolve_path(path).map_err(|e| e.to_errno())?;
 
     let inode = fs.read_inode(inode_num).map_err(|_| EIO)?;
 
@@ -2748,6 +2713,8 @@ pub fn sys_set_tid_address(_tidptr: u64) -> SyscallResult {
 pub const WNOHANG: u32 = 1;
 #[allow(dead_code)]
 pub const WUNTRACED: u32 = 2;
+#[allow(dead_code)]
+pub const WCONTINUED: u32 = 8;
 
 /// sys_waitpid - Wait for a child process to change state
 ///
@@ -2766,7 +2733,7 @@ pub const WUNTRACED: u32 = 2;
 /// - On success: PID of terminated child
 /// - If WNOHANG and no child terminated: 0
 /// - On error: negative errno (ECHILD, EINVAL, EFAULT)
-pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
+pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32, rusage_ptr: u64) -> SyscallResult {
     log::debug!(
         "sys_waitpid: pid={}, status_ptr={:#x}, options={}",
         pid,
@@ -2783,6 +2750,13 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
         }
     };
 
+    // A tracer also collects its tracees' stops here
+    if crate::process::ptrace::active() {
+        if let Some(result) = super::ptrace::wait_for_tracee(thread_id, pid, status_ptr, options) {
+            return result;
+        }
+    }
+
     // Find current process
     let mut manager_guard = crate::process::manager();
     let (current_pid, current_process) = match &mut *manager_guard {
@@ -2851,7 +2825,12 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
             };
 
             if let Some((child_pid, exit_code)) = child_terminated {
-                return complete_wait(child_pid, exit_code, status_ptr, &children_copy);
+                return complete_wait(child_pid, exit_code, status_ptr, &children_copy, rusage_ptr);
+            }
+
+            // Report a child that stopped or continued
+            if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                return super::ptrace::complete_stop_wait(child, status, status_ptr);
             }
 
             // Child exists but not terminated
@@ -2887,12 +2866,18 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                                 exit_code,
                                 status_ptr,
                                 &children_copy,
+                                rusage_ptr,
                             );
                         }
                     }
                 }
             }
 
+            // A child or tracee may have stopped in the race window too
+            if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                return super::ptrace::complete_stop_wait(child, status, status_ptr);
+            }
+
             crate::per_cpu::preempt_enable();
 
             loop {
@@ -2918,6 +2903,12 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                 crate::task::scheduler::yield_current();
                 crate::arch_halt_with_interrupts();
 
+                // A child or tracee stopping wakes the waiter like a child exiting
+                if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                    crate::per_cpu::preempt_disable();
+                    return super::ptrace::complete_stop_wait(child, status, status_ptr);
+                }
+
                 // After being rescheduled, check if child terminated
                 let manager_guard = crate::process::manager();
                 if let Some(ref manager) = *manager_guard {
@@ -2930,6 +2921,7 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                                 exit_code,
                                 status_ptr,
                                 &children_copy,
+                                rusage_ptr,
                             );
                         }
                     }
@@ -2963,7 +2955,12 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
             };
 
             if let Some((child_pid, exit_code)) = terminated_child {
-                return complete_wait(child_pid, exit_code, status_ptr, &children_copy);
+                return complete_wait(child_pid, exit_code, status_ptr, &children_copy, rusage_ptr);
+            }
+
+            // Report a child that stopped or continued
+            if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                return super::ptrace::complete_stop_wait(child, status, status_ptr);
             }
 
             // No terminated children yet
@@ -2997,6 +2994,7 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                                     exit_code,
                                     status_ptr,
                                     &children_copy,
+                                    rusage_ptr,
                                 );
                             }
                         }
@@ -3004,6 +3002,11 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                 }
             }
 
+            // A child or tracee may have stopped in the race window too
+            if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                return super::ptrace::complete_stop_wait(child, status, status_ptr);
+            }
+
             crate::per_cpu::preempt_enable();
 
             loop {
@@ -3029,6 +3032,12 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                 crate::task::scheduler::yield_current();
                 crate::arch_halt_with_interrupts();
 
+                // A child or tracee stopping wakes the waiter like a child exiting
+                if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                    crate::per_cpu::preempt_disable();
+                    return super::ptrace::complete_stop_wait(child, status, status_ptr);
+                }
+
                 // After being rescheduled, check if any child terminated
                 let manager_guard = crate::process::manager();
                 if let Some(ref manager) = *manager_guard {
@@ -3043,6 +3052,7 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                                     exit_code,
                                     status_ptr,
                                     &children_copy,
+                                    rusage_ptr,
                                 );
                             }
                         }
@@ -3066,6 +3076,7 @@ fn complete_wait(
     exit_code: i32,
     status_ptr: u64,
     _children: &[crate::process::ProcessId],
+    rusage_ptr: u64,
 ) -> SyscallResult {
     // Encode exit status in wstatus format.
     // The wstatus encoding distinguishes between:
@@ -3114,16 +3125,19 @@ fn complete_wait(
         }
     }
 
-    // Remove child from parent's children list and reap from process table
+    // Remove child from parent's children list and reap from process table,
+    // charging its resource usage to the parent
+    let mut usage = crate::process::rusage::Rusage::default();
     if let Some(thread_id) = crate::task::scheduler::current_thread_id() {
         let mut manager_guard = crate::process::manager();
         if let Some(ref mut manager) = *manager_guard {
-            if let Some((_parent_pid, parent)) = manager.find_process_by_thread_mut(thread_id) {
+            if let Some((parent_pid, parent)) = manager.find_process_by_thread_mut(thread_id) {
                 parent.children.retain(|&id| id != child_pid);
                 log::debug!(
                     "complete_wait: Removed child {} from parent's children list",
                     child_pid.as_u64()
                 );
+                usage = crate::process::rusage::reap(manager, parent_pid, child_pid);
             }
             manager.remove_process(child_pid);
             log::debug!(
@@ -3147,6 +3161,12 @@ fn complete_wait(
         }
     });
 
+    if rusage_ptr != 0 {
+        if let Err(e) = super::rusage::write_rusage(rusage_ptr, &usage) {
+            return SyscallResult::Err(e);
+        }
+    }
+
     SyscallResult::Ok(child_pid.as_u64())
 }
 
@@ -3263,6 +3283,16 @@ pub fn sys_fcntl(fd: u64, cmd: u64, arg: u64) -> SyscallResult {
 
     let fd = fd as i32;
     let cmd = cmd as i32;
+
+    // Record locks take a struct flock pointer and may block, so they are
+    // handled before the process manager lock is taken below.
+    if matches!(
+        cmd,
+        F_GETLK | F_SETLK | F_SETLKW | F_OFD_GETLK | F_OFD_SETLK | F_OFD_SETLKW
+    ) {
+        return super::fs::sys_fcntl_lock(fd, cmd, arg);
+    }
+
     let arg = arg as i32;
 
     log::debug!("sys_fcntl: fd={}, cmd={}, arg={}", fd, cmd, arg);
@@ -3995,7 +4025,7 @@ pub fn sys_cow_stats(stats_ptr: u64) -> SyscallResult {
 /// 2. Child writes to shared page (triggers CoW fault)
 /// 3. CoW fault handler tries to allocate frame, fails
 /// 4. handle_cow_fault() returns false
-/// 5. page_fault_handler() kills the process with exit code -11 (SIGSEGV)
+/// 5. rust_page_fault_handler() kills the process with exit code -11 (SIGSEGV)
 /// 6. Parent receives SIGCHLD and can waitpid() for the child
 pub fn sys_simulate_oom(enable: u64) -> SyscallResult {
     #[cfg(feature = "testing")]
@@ -4022,26 +4052,13 @@ pub fn sys_simulate_oom(enable: u64) -> SyscallResult {
 // Resource Limits and System Information
 // =============================================================================
 
-/// Linux rlimit structure
-#[repr(C)]
-#[derive(Copy, Clone)]
-struct Rlimit {
-    rlim_cur: u64,
-    rlim_max: u64,
-}
-
 const RLIMIT_STACK: u64 = 3;
 const RLIMIT_NOFILE: u64 = 7;
 const RLIMIT_CORE: u64 = 4;
-const RLIM_INFINITY: u64 = u64::MAX;
-
-/// getrlimit - Get resource limits
-pub fn sys_getrlimit(resource: u64, rlim_ptr: u64) -> SyscallResult {
-    if rlim_ptr == 0 {
-        return SyscallResult::Err(super::errno::EFAULT as u64);
-    }
 
-    let rlim = match resource {
+/// Current limit for `resource`; only RLIMIT_CORE is tracked per process
+fn resource_limit(process: &crate::process::Process, resource: u64) -> Rlimit {
+    match resource {
         RLIMIT_STACK => Rlimit {
             rlim_cur: 8 * 1024 * 1024,
             rlim_max: RLIM_INFINITY,
@@ -4050,31 +4067,67 @@ pub fn sys_getrlimit(resource: u64, rlim_ptr: u64) -> SyscallResult {
             rlim_cur: 1024,
             rlim_max: 4096,
         },
-        RLIMIT_CORE => Rlimit {
-            rlim_cur: 0,
-            rlim_max: RLIM_INFINITY,
-        },
+        RLIMIT_CORE => process.core_limit,
         _ => Rlimit {
             rlim_cur: RLIM_INFINITY,
             rlim_max: RLIM_INFINITY,
         },
-    };
+    }
+}
 
-    if super::userptr::copy_to_user(rlim_ptr as *mut Rlimit, &rlim).is_err() {
+/// getrlimit - Get resource limits
+pub fn sys_getrlimit(resource: u64, rlim_ptr: u64) -> SyscallResult {
+    if rlim_ptr == 0 {
         return SyscallResult::Err(super::errno::EFAULT as u64);
     }
-    SyscallResult::Ok(0)
+    sys_prlimit64(0, resource, 0, rlim_ptr)
 }
 
 /// prlimit64 - Get/set resource limits
+///
+/// `pid` 0 names the calling process. New limits are validated (soft <= hard)
+/// and stored for RLIMIT_CORE; other resources keep their fixed values.
 pub fn sys_prlimit64(
-    _pid: u64,
+    pid: u64,
     resource: u64,
-    _new_rlim_ptr: u64,
+    new_rlim_ptr: u64,
     old_rlim_ptr: u64,
 ) -> SyscallResult {
-    if old_rlim_ptr != 0 {
-        return sys_getrlimit(resource, old_rlim_ptr);
+    let new_rlim = if new_rlim_ptr != 0 {
+        match super::userptr::copy_from_user(new_rlim_ptr as *const Rlimit) {
+            Ok(rlim) if rlim.rlim_cur > rlim.rlim_max => {
+                return SyscallResult::Err(super::errno::EINVAL as u64)
+            }
+            Ok(rlim) => Some(rlim),
+            Err(_) => return SyscallResult::Err(super::errno::EFAULT as u64),
+        }
+    } else {
+        None
+    };
+
+    let old_rlim = crate::arch_without_interrupts(|| {
+        let thread_id = crate::task::scheduler::current_thread_id()?;
+        let mut manager_guard = crate::process::manager();
+        let manager = manager_guard.as_mut()?;
+        let process = if pid == 0 {
+            manager.find_process_by_thread_mut(thread_id)?.1
+        } else {
+            manager.get_process_mut(crate::process::ProcessId::new(pid))?
+        };
+        let old = resource_limit(process, resource);
+        if let (Some(rlim), RLIMIT_CORE) = (new_rlim, resource) {
+            process.core_limit = rlim;
+        }
+        Some(old)
+    });
+    let Some(old_rlim) = old_rlim else {
+        return SyscallResult::Err(super::errno::ESRCH as u64);
+    };
+
+    if old_rlim_ptr != 0
+        && super::userptr::copy_to_user(old_rlim_ptr as *mut Rlimit, &old_rlim).is_err()
+    {
+        return SyscallResult::Err(super::errno::EFAULT as u64);
     }
     SyscallResult::Ok(0)
 }
@@ -4412,7 +4465,7 @@ pub fn sys_pwrite64(fd: i32, buf_ptr: u64, count: u64, offset: i64) -> SyscallRe
     let write_fn = |fs: &mut ext2::Ext2Fs| -> SyscallResult {
         match fs.write_file_range(inode_num as u32, file_offset, &data) {
             Ok(written) => SyscallResult::Ok(written as u64),
-            Err(_) => SyscallResult::Err(super::errno::EIO as u64),
+            Err(e) => SyscallResult::Err(super::fs::ext2_errno(e) as u64),
         }
     };
 
@@ -6,6 +6,7 @@
 //! Supports:
 //! - TTY-related ioctls for terminal control
 //! - PTY-specific ioctls for pseudo-terminal devices
+//! - OSS-style format, volume and buffer ioctls for /dev/dsp streams
 
 use super::SyscallResult;
 use crate::ipc::fd::FdKind;
@@ -43,6 +44,8 @@ pub(crate) const ENOTTY: u64 = 25; // Inappropriate ioctl for device
 /// - TIOCGPTN (0x80045430): Get PTY number
 /// - TIOCSPTLCK (0x40045431): Lock/unlock PTY slave
 /// - TIOCGPTLCK (0x80045439): Get PTY lock status
+///
+/// For /dev/dsp streams: the SNDCTL_DSP_* requests in `audio::ioctl`
 pub fn sys_ioctl(fd: u64, request: u64, arg: u64) -> SyscallResult {
     log::debug!(
         "sys_ioctl: fd={}, request={:#x}, arg={:#x}",
@@ -65,11 +68,38 @@ pub fn sys_ioctl(fd: u64, request: u64, arg: u64) -> SyscallResult {
                     }
                 };
 
+                // Changing the attributes or foreground group of a controlling
+                // terminal from a background group raises SIGTTOU
+                if matches!(fd_kind, FdKind::PtySlave(_))
+                    && matches!(
+                        request,
+                        crate::tty::ioctl::TCSETS
+                            | crate::tty::ioctl::TCSETSW
+                            | crate::tty::ioctl::TCSETSF
+                            | crate::tty::ioctl::TIOCSPGRP
+                    )
+                {
+                    let (session, foreground) = pair.job_control();
+                    if let Err(e) = super::signal::check_tty_access(
+                        session,
+                        foreground,
+                        crate::signal::constants::SIGTTOU,
+                    ) {
+                        return SyscallResult::Err(e);
+                    }
+                }
+
                 match crate::tty::ioctl::pty_ioctl(&pair, request, arg, pid) {
                     Ok(ret) => return SyscallResult::Ok(ret as u64),
                     Err(errno) => return SyscallResult::Err(errno as u64),
                 }
             }
+            FdKind::Dsp(stream) => {
+                return match crate::audio::ioctl::dsp_ioctl(&stream, request, arg) {
+                    Ok(ret) => SyscallResult::Ok(ret as u64),
+                    Err(errno) => SyscallResult::Err(errno as u64),
+                };
+            }
             FdKind::StdIo(_) => {
                 // Fall through to console TTY handling
             }
@@ -178,6 +178,7 @@ pub fn sys_brk(addr: u64) -> SyscallResult {
         // Update the heap end
         process.heap_end = new_break;
         process.memory_usage.heap_size = (new_break - heap_start) as usize;
+        crate::process::rusage::note_rss(process);
 
         SyscallResult::Ok(new_break)
     }
@@ -257,6 +257,7 @@ pub fn sys_mmap(
                     flags,
                 );
                 process.vmas.push(vma);
+                crate::process::rusage::note_rss(process);
             }
         }
     }
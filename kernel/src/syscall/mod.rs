@@ -41,13 +41,18 @@ pub mod handlers;
 pub mod ioctl;
 pub mod iovec;
 pub mod pipe;
+pub mod ptrace;
 pub mod pty;
 pub mod random;
+pub mod rusage;
+pub mod sched;
 pub mod session;
 pub mod signal;
 pub mod socket;
+pub mod splice;
 #[cfg(target_arch = "aarch64")]
 pub mod wait;
+pub mod waitid;
 
 /// System call numbers - semantic names only.
 ///
@@ -100,6 +105,7 @@ pub enum SyscallNumber {
     Socketpair,
     Exec,
     Wait4,
+    Waitid,
     Kill,
     Getsockname,
     Getpeername,
@@ -114,6 +120,13 @@ pub enum SyscallNumber {
     Sigpending,
     Sigsuspend,
     Sigaltstack,
+    // Queued and thread-directed signals
+    Tkill,
+    Tgkill,
+    RtSigqueueinfo,
+    RtSigtimedwait,
+    // Process tracing
+    Ptrace,
     ArchPrctl, // x86_64 TLS setup (FS/GS base)
     GetTid,
     Futex,
@@ -176,7 +189,21 @@ pub enum SyscallNumber {
     // Resource limits and system info
     Getrlimit,
     Prlimit64,
+    Getrusage,
+    Times,
     Uname,
+    // Scheduling priorities
+    Getpriority,
+    Setpriority,
+    SchedSetparam,
+    SchedGetparam,
+    SchedSetscheduler,
+    SchedGetscheduler,
+    SchedGetPriorityMax,
+    SchedGetPriorityMin,
+    SchedSetaffinity,
+    SchedGetaffinity,
+    Getcpu,
     // epoll
     EpollCreate1,
     EpollCtl,
@@ -196,6 +223,21 @@ pub enum SyscallNumber {
     // Positional I/O
     Pread64,
     Pwrite64,
+    // File size and durability
+    Truncate,
+    Ftruncate,
+    Fallocate,
+    Fsync,
+    Fdatasync,
+    // Filesystem statistics
+    Statfs,
+    Fstatfs,
+    // Advisory locking
+    Flock,
+    // In-kernel data transfer
+    Sendfile,
+    Splice,
+    Tee,
     // Process spawning (Breenix-specific) — avoids fork+exec overhead
     Spawn,
 }
@@ -272,23 +314,42 @@ impl SyscallNumber {
             88 => Some(Self::Symlink),
             89 => Some(Self::Readlink),
             97 => Some(Self::Getrlimit),
+            98 => Some(Self::Getrusage),
+            100 => Some(Self::Times),
+            101 => Some(Self::Ptrace),
             109 => Some(Self::SetPgid),
             110 => Some(Self::Getppid),
             112 => Some(Self::SetSid),
             121 => Some(Self::GetPgid),
             124 => Some(Self::GetSid),
             127 => Some(Self::Sigpending),
+            128 => Some(Self::RtSigtimedwait),
+            129 => Some(Self::RtSigqueueinfo),
             130 => Some(Self::Sigsuspend),
             131 => Some(Self::Sigaltstack),
             133 => Some(Self::Mknod),
+            140 => Some(Self::Getpriority),
+            141 => Some(Self::Setpriority),
+            142 => Some(Self::SchedSetparam),
+            143 => Some(Self::SchedGetparam),
+            144 => Some(Self::SchedSetscheduler),
+            145 => Some(Self::SchedGetscheduler),
+            146 => Some(Self::SchedGetPriorityMax),
+            147 => Some(Self::SchedGetPriorityMin),
+            203 => Some(Self::SchedSetaffinity),
+            204 => Some(Self::SchedGetaffinity),
+            309 => Some(Self::Getcpu),
             158 => Some(Self::ArchPrctl), // NEW
             186 => Some(Self::GetTid),
+            200 => Some(Self::Tkill),
             202 => Some(Self::Futex),
             217 => Some(Self::Getdents64), // was Breenix 260
             218 => Some(Self::SetTidAddress),
             227 => Some(Self::ClockSetTime),
             228 => Some(Self::ClockGetTime),
             231 => Some(Self::ExitGroup),
+            234 => Some(Self::Tgkill),
+            247 => Some(Self::Waitid),
             257 => Some(Self::Openat), // Linux x86_64 openat (was Breenix Open)
             258 => Some(Self::Mkdirat),
             259 => Some(Self::Mknodat),
@@ -318,6 +379,17 @@ impl SyscallNumber {
             108 => Some(Self::Getegid),
             17 => Some(Self::Pread64),
             18 => Some(Self::Pwrite64),
+            74 => Some(Self::Fsync),
+            75 => Some(Self::Fdatasync),
+            76 => Some(Self::Truncate),
+            77 => Some(Self::Ftruncate),
+            285 => Some(Self::Fallocate),
+            137 => Some(Self::Statfs),
+            138 => Some(Self::Fstatfs),
+            73 => Some(Self::Flock),
+            40 => Some(Self::Sendfile),
+            275 => Some(Self::Splice),
+            276 => Some(Self::Tee),
             280 => Some(Self::Utimensat),
             318 => Some(Self::GetRandom),
             // PTY syscalls (Breenix-specific, same on both archs)
@@ -394,15 +466,31 @@ impl SyscallNumber {
             103 => Some(Self::Setitimer),
             112 => Some(Self::ClockSetTime),
             113 => Some(Self::ClockGetTime),
+            117 => Some(Self::Ptrace),
             // Scheduling
+            118 => Some(Self::SchedSetparam),
+            119 => Some(Self::SchedSetscheduler),
+            120 => Some(Self::SchedGetscheduler),
+            121 => Some(Self::SchedGetparam),
+            122 => Some(Self::SchedSetaffinity),
+            123 => Some(Self::SchedGetaffinity),
             124 => Some(Self::Yield),
+            125 => Some(Self::SchedGetPriorityMax),
+            126 => Some(Self::SchedGetPriorityMin),
+            140 => Some(Self::Setpriority),
+            141 => Some(Self::Getpriority),
+            168 => Some(Self::Getcpu),
             // Signals
             129 => Some(Self::Kill),
             132 => Some(Self::Sigaltstack),
             133 => Some(Self::Sigsuspend),
             134 => Some(Self::Sigaction),
             135 => Some(Self::Sigprocmask),
+            130 => Some(Self::Tkill),
+            131 => Some(Self::Tgkill),
             136 => Some(Self::Sigpending),
+            137 => Some(Self::RtSigtimedwait),
+            138 => Some(Self::RtSigqueueinfo),
             139 => Some(Self::Sigreturn),
             // Session/process group
             154 => Some(Self::SetPgid),
@@ -438,11 +526,29 @@ impl SyscallNumber {
             226 => Some(Self::Mprotect),
             233 => Some(Self::Madvise),
             // Wait
+            95 => Some(Self::Waitid),
             260 => Some(Self::Wait4),
             261 => Some(Self::Prlimit64),
+            // Resource usage
+            153 => Some(Self::Times),
+            165 => Some(Self::Getrusage),
             // Positional I/O
             67 => Some(Self::Pread64),
             68 => Some(Self::Pwrite64),
+            // File size and durability
+            45 => Some(Self::Truncate),
+            46 => Some(Self::Ftruncate),
+            47 => Some(Self::Fallocate),
+            82 => Some(Self::Fsync),
+            83 => Some(Self::Fdatasync),
+            // Filesystem statistics
+            43 => Some(Self::Statfs),
+            44 => Some(Self::Fstatfs),
+            32 => Some(Self::Flock),
+            // In-kernel data transfer
+            71 => Some(Self::Sendfile),
+            76 => Some(Self::Splice),
+            77 => Some(Self::Tee),
             // Timestamps
             88 => Some(Self::Utimensat),
             // Process identity
@@ -174,8 +174,16 @@ pub fn sys_close(fd: i32) -> SyscallResult {
                     // to remain bound until all references are closed.
                     log::debug!("sys_close: Closed UDP socket fd={}", fd);
                 }
-                FdKind::RegularFile(_) => {
-                    // Regular file cleanup handled by Arc refcount
+                FdKind::RegularFile(file) => {
+                    // Closing any descriptor for a file drops the process's POSIX
+                    // record locks on it. flock/OFD locks are released by
+                    // RegularFile::drop when the last Arc reference goes away.
+                    let tgid = process.thread_group_id.unwrap_or(process_pid.as_u64());
+                    let key = {
+                        let file = file.lock();
+                        (file.mount_id, file.inode_num)
+                    };
+                    crate::fs::lock::release_process_file(tgid, key);
                     log::debug!("sys_close: Closed regular file fd={}", fd);
                 }
                 FdKind::Directory(_) => {
@@ -267,6 +275,10 @@ pub fn sys_close(fd: i32) -> SyscallResult {
                     crate::syscall::epoll::remove_instance(id);
                     log::debug!("sys_close: Closed epoll fd={}", fd);
                 }
+                FdKind::Dsp(_) => {
+                    // The mixer drops the stream once it has played out
+                    log::debug!("sys_close: Closed dsp fd={}", fd);
+                }
             }
             log::debug!("sys_close: returning to userspace fd={}", fd);
             SyscallResult::Ok(0)
@@ -0,0 +1,578 @@
+//! ptrace syscall
+//!
+//! The tracing state and stop bookkeeping live in `process::ptrace`; this
+//! module decodes requests, copies data to and from the tracer, and runs the
+//! tracee side of stops taken inside a syscall:
+//!
+//! - Syscall-entry and syscall-exit stops under PTRACE_SYSCALL. The tracer
+//!   may rewrite the syscall number and arguments at entry, and the return
+//!   value at exit.
+//! - Signals pending when a syscall returns. They stop the tracee before the
+//!   signal frame is built, so the tracer can suppress or replace them.
+//!
+//! While stopped the tracee sleeps on the ptrace stop queue and copies the
+//! registers the tracer left back into its syscall frame when resumed.
+//!
+//! waitpid() reports tracee stops through [`stop_report`] and, for a tracer
+//! that attached to a process that is not its child, [`wait_for_tracee`].
+
+use super::errno::{ECHILD, EFAULT, EINTR, EINVAL, EIO, EPERM, ESRCH};
+use super::userptr::{copy_from_user, copy_to_user};
+use super::SyscallResult;
+use crate::arch_impl::traits::CpuOps;
+use crate::process::ptrace::{self, ElfGregs, Resume, StopKind, SyscallStop};
+use crate::process::{Process, ProcessId};
+use crate::signal::constants::SIGSTOP;
+use crate::signal::types::SigInfo;
+use crate::task::thread::ThreadState;
+use crate::task::waitqueue::PrepareOutcome;
+
+#[cfg(target_arch = "aarch64")]
+type Cpu = crate::arch_impl::aarch64::Aarch64Cpu;
+
+#[cfg(target_arch = "x86_64")]
+type Cpu = crate::arch_impl::x86_64::cpu::X86Cpu;
+
+/// ptrace requests (Linux values)
+pub const PTRACE_TRACEME: u64 = 0;
+pub const PTRACE_PEEKTEXT: u64 = 1;
+pub const PTRACE_PEEKDATA: u64 = 2;
+pub const PTRACE_POKETEXT: u64 = 4;
+pub const PTRACE_POKEDATA: u64 = 5;
+pub const PTRACE_CONT: u64 = 7;
+pub const PTRACE_SINGLESTEP: u64 = 9;
+pub const PTRACE_GETREGS: u64 = 12;
+pub const PTRACE_SETREGS: u64 = 13;
+pub const PTRACE_ATTACH: u64 = 16;
+pub const PTRACE_DETACH: u64 = 17;
+pub const PTRACE_SYSCALL: u64 = 24;
+pub const PTRACE_SETOPTIONS: u64 = 0x4200;
+
+/// PTRACE_O_* options this kernel implements
+const PTRACE_O_ALL: u64 = ptrace::PTRACE_O_TRACESYSGOOD;
+
+/// waitpid() WNOHANG flag
+const WNOHANG: u32 = 1;
+
+/// Highest signal number a tracer may pass on when resuming
+const MAX_SIGNAL: u64 = 64;
+
+/// sys_ptrace - Trace another process
+///
+/// Arguments follow the raw Linux syscall: PTRACE_PEEKTEXT and
+/// PTRACE_PEEKDATA store the word read at `data` rather than returning it.
+pub fn sys_ptrace(request: u64, pid: u64, addr: u64, data: u64) -> SyscallResult {
+    let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+        return SyscallResult::Err(ESRCH as u64);
+    };
+
+    let result = match request {
+        PTRACE_TRACEME => trace_me(thread_id),
+        PTRACE_ATTACH => attach(thread_id, pid),
+        PTRACE_PEEKTEXT | PTRACE_PEEKDATA => peek(thread_id, pid, addr, data),
+        PTRACE_POKETEXT | PTRACE_POKEDATA => with_stopped_tracee(thread_id, pid, |tracee| {
+            if ptrace::write_memory(tracee, addr, &data.to_ne_bytes()) {
+                Ok(0)
+            } else {
+                Err(EIO as u64)
+            }
+        }),
+        PTRACE_GETREGS => get_regs(thread_id, pid, data),
+        PTRACE_SETREGS => set_regs(thread_id, pid, data),
+        PTRACE_CONT => resume(thread_id, pid, Resume::Continue, data),
+        PTRACE_SYSCALL => resume(thread_id, pid, Resume::Syscall, data),
+        PTRACE_SINGLESTEP => resume(thread_id, pid, Resume::SingleStep, data),
+        PTRACE_DETACH => {
+            if data > MAX_SIGNAL {
+                return SyscallResult::Err(EIO as u64);
+            }
+            with_stopped_tracee(thread_id, pid, |tracee| {
+                ptrace::detach(tracee, data as u32);
+                Ok(0)
+            })
+        }
+        PTRACE_SETOPTIONS => {
+            if data & !PTRACE_O_ALL != 0 {
+                return SyscallResult::Err(EINVAL as u64);
+            }
+            with_stopped_tracee(thread_id, pid, |tracee| {
+                if let Some(state) = tracee.ptrace.as_mut() {
+                    state.options = data;
+                }
+                Ok(0)
+            })
+        }
+        _ => Err(EIO as u64),
+    };
+
+    match result {
+        Ok(value) => SyscallResult::Ok(value),
+        Err(errno) => SyscallResult::Err(errno),
+    }
+}
+
+/// PTRACE_TRACEME: let the parent trace the caller
+fn trace_me(thread_id: u64) -> Result<u64, u64> {
+    crate::process::with_process_manager(|manager| {
+        let (_, process) = manager
+            .find_process_by_thread(thread_id)
+            .ok_or(ESRCH as u64)?;
+        if process.ptrace.is_some() {
+            return Err(EPERM as u64);
+        }
+        let parent = process.parent.ok_or(EPERM as u64)?;
+        let parent_tid = manager
+            .get_process(parent)
+            .and_then(|parent| parent.main_thread.as_ref())
+            .map(|thread| thread.id)
+            .ok_or(EPERM as u64)?;
+        let (_, process) = manager
+            .find_process_by_thread_mut(thread_id)
+            .ok_or(ESRCH as u64)?;
+        ptrace::attach(process, parent, parent_tid);
+        Ok(0)
+    })
+    .unwrap_or(Err(ESRCH as u64))
+}
+
+/// PTRACE_ATTACH: trace `pid` and stop it with SIGSTOP
+fn attach(thread_id: u64, pid: u64) -> Result<u64, u64> {
+    let tracee_tid = crate::process::with_process_manager(|manager| {
+        let (tracer, caller) = manager
+            .find_process_by_thread(thread_id)
+            .ok_or(ESRCH as u64)?;
+        let (uid, euid) = (caller.uid, caller.euid);
+        if pid == tracer.as_u64() {
+            return Err(EPERM as u64);
+        }
+        let tracee = manager
+            .get_process_mut(ProcessId::new(pid))
+            .ok_or(ESRCH as u64)?;
+        if tracee.is_terminated() {
+            return Err(ESRCH as u64);
+        }
+        if tracee.ptrace.is_some() || (euid != 0 && tracee.uid != uid) {
+            return Err(EPERM as u64);
+        }
+        ptrace::attach(tracee, tracer, thread_id);
+        tracee
+            .signals
+            .queue_signal(SigInfo::user(SIGSTOP, tracer.as_u64(), uid));
+        Ok(tracee.main_thread.as_ref().map(|thread| thread.id))
+    })
+    .unwrap_or(Err(ESRCH as u64))?;
+
+    // Wake the tracee out of any interruptible wait so it takes the stop
+    if let Some(tid) = tracee_tid {
+        crate::task::scheduler::with_scheduler(|sched| {
+            sched.unblock_for_signal(tid);
+            sched.unblock_for_child_exit(tid);
+        });
+    }
+    Ok(0)
+}
+
+/// Run `f` on `pid` if it is a stopped tracee of the caller
+fn with_stopped_tracee<R>(
+    thread_id: u64,
+    pid: u64,
+    f: impl FnOnce(&mut Process) -> Result<R, u64>,
+) -> Result<R, u64> {
+    crate::process::with_process_manager(|manager| {
+        let (tracer, _) = manager
+            .find_process_by_thread(thread_id)
+            .ok_or(ESRCH as u64)?;
+        let tracee = manager
+            .get_process_mut(ProcessId::new(pid))
+            .ok_or(ESRCH as u64)?;
+        let stopped = tracee
+            .ptrace
+            .as_ref()
+            .is_some_and(|state| state.tracer == tracer && state.stop().is_some());
+        if !stopped {
+            return Err(ESRCH as u64);
+        }
+        f(tracee)
+    })
+    .unwrap_or(Err(ESRCH as u64))
+}
+
+/// PTRACE_PEEKTEXT/PTRACE_PEEKDATA: read one word into the tracer's `data`
+fn peek(thread_id: u64, pid: u64, addr: u64, data: u64) -> Result<u64, u64> {
+    let word = with_stopped_tracee(thread_id, pid, |tracee| {
+        let mut bytes = [0u8; 8];
+        if ptrace::read_memory(tracee, addr, &mut bytes) {
+            Ok(u64::from_ne_bytes(bytes))
+        } else {
+            Err(EIO as u64)
+        }
+    })?;
+    copy_to_user(data as *mut u64, &word).map_err(|_| EFAULT as u64)?;
+    Ok(0)
+}
+
+/// PTRACE_GETREGS: copy the tracee's registers to the tracer's `data`
+fn get_regs(thread_id: u64, pid: u64, data: u64) -> Result<u64, u64> {
+    let regs = with_stopped_tracee(thread_id, pid, |tracee| {
+        tracee
+            .ptrace
+            .as_ref()
+            .and_then(|state| state.stop())
+            .map(|stop| stop.regs)
+            .ok_or(ESRCH as u64)
+    })?;
+    copy_to_user(data as *mut ElfGregs, &regs).map_err(|_| EFAULT as u64)?;
+    Ok(0)
+}
+
+/// PTRACE_SETREGS: replace the tracee's registers from the tracer's `data`
+fn set_regs(thread_id: u64, pid: u64, data: u64) -> Result<u64, u64> {
+    let regs: ElfGregs = copy_from_user(data as *const ElfGregs).map_err(|_| EFAULT as u64)?;
+    if !regs.is_valid() {
+        return Err(EIO as u64);
+    }
+    with_stopped_tracee(thread_id, pid, |tracee| {
+        let stop = tracee
+            .ptrace
+            .as_mut()
+            .and_then(|state| state.stop_mut())
+            .ok_or(ESRCH as u64)?;
+        stop.regs = regs;
+        Ok(0)
+    })
+}
+
+/// PTRACE_CONT/PTRACE_SYSCALL/PTRACE_SINGLESTEP: resume with signal `data`
+fn resume(thread_id: u64, pid: u64, how: Resume, data: u64) -> Result<u64, u64> {
+    if data > MAX_SIGNAL {
+        return Err(EIO as u64);
+    }
+    with_stopped_tracee(thread_id, pid, |tracee| {
+        ptrace::resume(tracee, how, data as u32);
+        Ok(0)
+    })
+}
+
+// =============================================================================
+// Tracee side: stops inside a syscall
+// =============================================================================
+
+/// Sleep until the tracer resumes the current thread's stop inside a syscall
+///
+/// Returns the registers to continue with, or `regs` unchanged if tracing
+/// ended while stopped.
+fn wait_for_resume(thread_id: u64, regs: ElfGregs) -> ElfGregs {
+    let resumed = loop {
+        let generation = ptrace::resume_generation();
+        let stop = crate::process::with_process_manager(|manager| {
+            manager
+                .find_process_by_thread_mut(thread_id)
+                .map_or(SyscallStop::Released, |(_, process)| {
+                    ptrace::poll_syscall_stop(process)
+                })
+        })
+        .unwrap_or(SyscallStop::Released);
+        match stop {
+            SyscallStop::Resumed(regs) => break regs,
+            SyscallStop::Released => break regs,
+            SyscallStop::Waiting => {}
+        }
+
+        let outcome =
+            ptrace::stop_queue().prepare_to_wait_checked(ThreadState::BlockedOnIO, None, || {
+                ptrace::resume_generation() == generation
+            });
+        if outcome == PrepareOutcome::Queued {
+            crate::per_cpu::preempt_enable();
+            loop {
+                let still_waiting = crate::task::scheduler::with_scheduler(|sched| {
+                    sched
+                        .current_thread_mut()
+                        .map(|thread| thread.state == ThreadState::BlockedOnIO)
+                        .unwrap_or(false)
+                })
+                .unwrap_or(false);
+                if !still_waiting {
+                    break;
+                }
+                crate::task::scheduler::yield_current();
+                Cpu::halt_with_interrupts();
+            }
+            crate::per_cpu::preempt_disable();
+        }
+        ptrace::stop_queue().finish_wait();
+    };
+
+    #[cfg(target_arch = "aarch64")]
+    super::futex::ensure_current_address_space();
+
+    resumed
+}
+
+/// Take a syscall-entry (`entry`) or syscall-exit stop if the tracer asked
+/// for one, returning the registers it left
+fn syscall_stop(thread_id: u64, regs: ElfGregs, entry: bool) -> Option<ElfGregs> {
+    let stopped = crate::process::with_process_manager(|manager| {
+        let (_, process) = manager.find_process_by_thread_mut(thread_id)?;
+        let stops = if entry {
+            ptrace::syscall_entry_stops(process)
+        } else {
+            ptrace::syscall_exit_stops(process)
+        };
+        if stops {
+            ptrace::enter_stop(process, StopKind::Syscall, regs, true);
+        }
+        Some(stops)
+    })
+    .flatten()
+    .unwrap_or(false);
+    stopped.then(|| wait_for_resume(thread_id, regs))
+}
+
+/// Stop for each pending signal the tracer has not yet seen
+///
+/// Returns the registers left by the tracer if the tracee stopped at all.
+fn signal_stops(thread_id: u64, mut regs: ElfGregs) -> Option<ElfGregs> {
+    let mut stopped = false;
+    loop {
+        let stops = crate::process::with_process_manager(|manager| {
+            let (_, process) = manager.find_process_by_thread_mut(thread_id)?;
+            let sig = ptrace::next_stopping_signal(process)?;
+            let info = process.signals.dequeue_signal(sig);
+            ptrace::enter_stop(process, StopKind::Signal(info), regs, true);
+            Some(())
+        })
+        .flatten()
+        .is_some();
+        if !stops {
+            return stopped.then_some(regs);
+        }
+        regs = wait_for_resume(thread_id, regs);
+        stopped = true;
+    }
+}
+
+/// Syscall-entry stop (x86_64); returns the syscall number to run
+#[cfg(target_arch = "x86_64")]
+pub fn syscall_entry_x86_64(frame: &mut super::handler::SyscallFrame) -> u64 {
+    let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+        return frame.rax;
+    };
+    let regs = ElfGregs::from_syscall_frame(frame, frame.rax);
+    match syscall_stop(thread_id, regs, true) {
+        Some(regs) => {
+            regs.apply_to_syscall_frame(frame);
+            // The tracer chooses the syscall through orig_rax
+            frame.rax = regs.orig_syscall();
+            regs.orig_syscall()
+        }
+        None => frame.rax,
+    }
+}
+
+/// Syscall-exit stop and pending-signal stops (x86_64)
+///
+/// Runs after the return value is in the frame and before signal delivery.
+#[cfg(target_arch = "x86_64")]
+pub fn syscall_exit_x86_64(frame: &mut super::handler::SyscallFrame, syscall_num: u64) {
+    let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+        return;
+    };
+    let mut regs = ElfGregs::from_syscall_frame(frame, syscall_num);
+    let mut changed = false;
+    if let Some(new_regs) = syscall_stop(thread_id, regs, false) {
+        regs = new_regs;
+        changed = true;
+    }
+    if let Some(new_regs) = signal_stops(thread_id, regs) {
+        regs = new_regs;
+        changed = true;
+    }
+    if changed {
+        regs.apply_to_syscall_frame(frame);
+    }
+}
+
+/// User registers of a thread in an ARM64 syscall
+#[cfg(target_arch = "aarch64")]
+fn frame_regs(
+    frame: &crate::arch_impl::aarch64::exception_frame::Aarch64ExceptionFrame,
+) -> ElfGregs {
+    let sp = crate::arch_impl::aarch64::context::read_sp_el0();
+    let saved =
+        crate::task::process_context::SavedRegisters::from_exception_frame_with_sp(frame, sp);
+    ElfGregs::from_saved(&saved)
+}
+
+/// Write registers left by the tracer into an ARM64 syscall frame
+#[cfg(target_arch = "aarch64")]
+fn apply_frame_regs(
+    frame: &mut crate::arch_impl::aarch64::exception_frame::Aarch64ExceptionFrame,
+    regs: &ElfGregs,
+) {
+    let sp = crate::arch_impl::aarch64::context::read_sp_el0();
+    let mut saved =
+        crate::task::process_context::SavedRegisters::from_exception_frame_with_sp(frame, sp);
+    regs.apply_to_saved(&mut saved);
+    saved.apply_to_frame(frame);
+    unsafe {
+        crate::arch_impl::aarch64::context::write_sp_el0(saved.sp);
+    }
+}
+
+/// Syscall-entry stop (ARM64); returns the syscall number to run
+#[cfg(target_arch = "aarch64")]
+pub fn syscall_entry_aarch64(
+    frame: &mut crate::arch_impl::aarch64::exception_frame::Aarch64ExceptionFrame,
+) -> u64 {
+    let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+        return frame.syscall_number();
+    };
+    if let Some(regs) = syscall_stop(thread_id, frame_regs(frame), true) {
+        apply_frame_regs(frame, &regs);
+    }
+    frame.syscall_number()
+}
+
+/// Syscall-exit stop and pending-signal stops (ARM64)
+///
+/// Runs after the return value is in the frame and before signal delivery.
+#[cfg(target_arch = "aarch64")]
+pub fn syscall_exit_aarch64(
+    frame: &mut crate::arch_impl::aarch64::exception_frame::Aarch64ExceptionFrame,
+) {
+    let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+        return;
+    };
+    let mut regs = frame_regs(frame);
+    let mut changed = false;
+    if let Some(new_regs) = syscall_stop(thread_id, regs, false) {
+        regs = new_regs;
+        changed = true;
+    }
+    if let Some(new_regs) = signal_stops(thread_id, regs) {
+        regs = new_regs;
+        changed = true;
+    }
+    if changed {
+        apply_frame_regs(frame, &regs);
+    }
+    crate::arch_impl::aarch64::exception::sync_software_step(frame.spsr);
+}
+
+/// Wait out a signal stop that signal delivery recorded on a syscall's
+/// return path (ARM64), after `ptrace::keep_stop_in_syscall`
+#[cfg(target_arch = "aarch64")]
+pub fn wait_signal_stop_aarch64(
+    frame: &mut crate::arch_impl::aarch64::exception_frame::Aarch64ExceptionFrame,
+) {
+    let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+        return;
+    };
+    let regs = frame_regs(frame);
+    let regs = wait_for_resume(thread_id, regs);
+    apply_frame_regs(frame, &regs);
+    crate::arch_impl::aarch64::exception::sync_software_step(frame.spsr);
+}
+
+// =============================================================================
+// Tracer side: waitpid()
+// =============================================================================
+
+/// Take an unreported stop of a tracee of the caller matching `pid`
+///
+/// Returns the tracee's pid and its waitpid status.
+pub fn stop_report(thread_id: u64, pid: i64) -> Option<(u64, i32)> {
+    if !ptrace::active() {
+        return None;
+    }
+    crate::process::with_process_manager(|manager| {
+        let (tracer, _) = manager.find_process_by_thread(thread_id)?;
+        ptrace::take_stop_report(manager, tracer, pid)
+    })
+    .flatten()
+    .map(|(tracee, status)| (tracee.as_u64(), status))
+}
+
+/// Finish a waitpid() that collected a tracee or job-control stop report
+pub fn complete_stop_wait(tracee: u64, status: i32, status_ptr: u64) -> SyscallResult {
+    crate::task::scheduler::with_scheduler(|sched| {
+        if let Some(thread) = sched.current_thread_mut() {
+            thread.blocked_in_syscall = false;
+            if thread.state == ThreadState::BlockedOnChildExit {
+                thread.set_ready();
+            }
+        }
+    });
+
+    #[cfg(target_arch = "aarch64")]
+    super::futex::ensure_current_address_space();
+
+    if status_ptr != 0 && copy_to_user(status_ptr as *mut i32, &status).is_err() {
+        return SyscallResult::Err(EFAULT as u64);
+    }
+    SyscallResult::Ok(tracee)
+}
+
+/// waitpid() for a tracer
+///
+/// Reports a pending tracee stop at once. Otherwise, when `pid` matches only
+/// attached tracees and no children, waits for one of them to stop; children
+/// are left to waitpid itself, which checks [`stop_report`] as it waits.
+/// Returns None when waitpid should carry on as usual.
+///
+/// A tracee that is not a child does not wake the tracer when it exits, so
+/// this wait polls once per timer tick instead of blocking.
+pub fn wait_for_tracee(
+    thread_id: u64,
+    pid: i64,
+    status_ptr: u64,
+    options: u32,
+) -> Option<SyscallResult> {
+    if let Some((tracee, status)) = stop_report(thread_id, pid) {
+        return Some(complete_stop_wait(tracee, status, status_ptr));
+    }
+
+    let tracer_only = crate::process::with_process_manager(|manager| {
+        let (tracer, process) = manager.find_process_by_thread(thread_id)?;
+        let child = match pid {
+            -1 => !process.children.is_empty(),
+            p if p > 0 => process.children.contains(&ProcessId::new(p as u64)),
+            _ => true,
+        };
+        Some(!child && ptrace::traces(manager, tracer, pid))
+    })
+    .flatten()
+    .unwrap_or(false);
+    if !tracer_only {
+        return None;
+    }
+    if options & WNOHANG != 0 {
+        return Some(SyscallResult::Ok(0));
+    }
+
+    crate::per_cpu::preempt_enable();
+    let result = loop {
+        if crate::syscall::check_signals_for_eintr().is_some() {
+            break SyscallResult::Err(EINTR as u64);
+        }
+
+        crate::task::scheduler::yield_current();
+        Cpu::halt_with_interrupts();
+
+        if let Some((tracee, status)) = stop_report(thread_id, pid) {
+            break complete_stop_wait(tracee, status, status_ptr);
+        }
+        let traced = crate::process::with_process_manager(|manager| {
+            manager
+                .find_process_by_thread(thread_id)
+                .is_some_and(|(tracer, _)| ptrace::traces(manager, tracer, pid))
+        })
+        .unwrap_or(false);
+        if !traced {
+            break SyscallResult::Err(ECHILD as u64);
+        }
+    };
+    crate::per_cpu::preempt_disable();
+    Some(result)
+}
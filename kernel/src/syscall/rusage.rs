@@ -0,0 +1,164 @@
+//! getrusage and times syscalls, and the rusage of wait4() and waitid()
+//!
+//! The totals come from `process::rusage`; this module collects the thread
+//! counters, picks the totals `who` asks for and converts them to the Linux
+//! `struct rusage` and `struct tms` layouts.
+
+use super::errno::{EFAULT, EINVAL, ESRCH};
+use super::userptr::copy_to_user;
+use super::SyscallResult;
+use crate::process::rusage::{self, Rusage};
+use crate::signal::types::Timeval;
+use crate::task::accounting::UsageSnapshot;
+
+/// getrusage() targets (Linux values)
+pub const RUSAGE_SELF: i32 = 0;
+pub const RUSAGE_CHILDREN: i32 = -1;
+pub const RUSAGE_THREAD: i32 = 1;
+
+/// Clock ticks per second reported by times() (USER_HZ)
+const CLOCK_TICKS_PER_SEC: u64 = 100;
+
+/// Linux `struct rusage`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinuxRusage {
+    pub ru_utime: Timeval,
+    pub ru_stime: Timeval,
+    pub ru_maxrss: i64,
+    pub ru_ixrss: i64,
+    pub ru_idrss: i64,
+    pub ru_isrss: i64,
+    pub ru_minflt: i64,
+    pub ru_majflt: i64,
+    pub ru_nswap: i64,
+    pub ru_inblock: i64,
+    pub ru_oublock: i64,
+    pub ru_msgsnd: i64,
+    pub ru_msgrcv: i64,
+    pub ru_nsignals: i64,
+    pub ru_nvcsw: i64,
+    pub ru_nivcsw: i64,
+}
+
+impl From<&Rusage> for LinuxRusage {
+    fn from(usage: &Rusage) -> Self {
+        LinuxRusage {
+            ru_utime: Timeval::from_micros(usage.cpu.user_ns / 1000),
+            ru_stime: Timeval::from_micros(usage.cpu.system_ns / 1000),
+            ru_maxrss: usage.max_rss_kb as i64,
+            ru_minflt: usage.cpu.minor_faults as i64,
+            ru_nvcsw: usage.cpu.voluntary_switches as i64,
+            ru_nivcsw: usage.cpu.involuntary_switches as i64,
+            ..Default::default()
+        }
+    }
+}
+
+/// Linux `struct tms`, in clock ticks
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct Tms {
+    tms_utime: i64,
+    tms_stime: i64,
+    tms_cutime: i64,
+    tms_cstime: i64,
+}
+
+/// Nanoseconds to clock ticks
+pub fn to_clock_ticks(ns: u64) -> i64 {
+    (ns / (1_000_000_000 / CLOCK_TICKS_PER_SEC)) as i64
+}
+
+/// Copy `usage` to a user `struct rusage` at `ptr`
+pub fn write_rusage(ptr: u64, usage: &Rusage) -> Result<(), u64> {
+    copy_to_user(ptr as *mut LinuxRusage, &LinuxRusage::from(usage)).map_err(|_| EFAULT as u64)
+}
+
+/// Usage of the calling thread's thread group (RUSAGE_SELF)
+fn self_usage(thread_id: u64) -> Option<Rusage> {
+    let (leader, live) = crate::process::with_process_manager(|manager| {
+        let (_, process) = manager.find_process_by_thread(thread_id)?;
+        let leader = rusage::thread_group(process);
+        Some((leader, rusage::live_members(manager, leader)))
+    })
+    .flatten()?;
+    // Scheduler before PM (lock order)
+    let threads = crate::task::scheduler::threads_usage(&live);
+    crate::process::with_process_manager(|manager| rusage::self_usage(manager, leader, threads))
+}
+
+/// Usage of the calling thread's reaped children (RUSAGE_CHILDREN)
+fn children_usage(thread_id: u64) -> Option<Rusage> {
+    crate::process::with_process_manager(|manager| {
+        let (_, process) = manager.find_process_by_thread(thread_id)?;
+        let leader = rusage::thread_group(process);
+        manager
+            .get_process(leader)
+            .map(|leader| leader.usage.children)
+    })
+    .flatten()
+}
+
+/// Usage of the calling thread alone (RUSAGE_THREAD)
+///
+/// The counters are the thread's own; max RSS is its process's.
+fn thread_usage(thread_id: u64) -> Option<Rusage> {
+    let cpu = crate::per_cpu::current_thread()
+        .map(|thread| thread.usage.snapshot())
+        .unwrap_or_default();
+    crate::process::with_process_manager(|manager| {
+        let (_, process) = manager.find_process_by_thread_mut(thread_id)?;
+        rusage::note_rss(process);
+        Some(Rusage {
+            cpu,
+            max_rss_kb: process.usage.max_rss_kb,
+        })
+    })
+    .flatten()
+}
+
+/// getrusage(who, usage) - Resource usage of the caller or its children
+pub fn sys_getrusage(who: i32, usage_ptr: u64) -> SyscallResult {
+    let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+        return SyscallResult::Err(ESRCH as u64);
+    };
+    let usage = match who {
+        RUSAGE_SELF => self_usage(thread_id),
+        RUSAGE_CHILDREN => children_usage(thread_id),
+        RUSAGE_THREAD => thread_usage(thread_id),
+        _ => return SyscallResult::Err(EINVAL as u64),
+    };
+    let Some(usage) = usage else {
+        return SyscallResult::Err(ESRCH as u64);
+    };
+    match write_rusage(usage_ptr, &usage) {
+        Ok(()) => SyscallResult::Ok(0),
+        Err(e) => SyscallResult::Err(e),
+    }
+}
+
+/// times(buf) - CPU times of the caller and its reaped children
+///
+/// Returns the clock ticks elapsed since boot.
+pub fn sys_times(buf_ptr: u64) -> SyscallResult {
+    if buf_ptr != 0 {
+        let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+            return SyscallResult::Err(ESRCH as u64);
+        };
+        let own = self_usage(thread_id).map_or(UsageSnapshot::default(), |usage| usage.cpu);
+        let children =
+            children_usage(thread_id).map_or(UsageSnapshot::default(), |usage| usage.cpu);
+        let tms = Tms {
+            tms_utime: to_clock_ticks(own.user_ns),
+            tms_stime: to_clock_ticks(own.system_ns),
+            tms_cutime: to_clock_ticks(children.user_ns),
+            tms_cstime: to_clock_ticks(children.system_ns),
+        };
+        if copy_to_user(buf_ptr as *mut Tms, &tms).is_err() {
+            return SyscallResult::Err(EFAULT as u64);
+        }
+    }
+    let (secs, nanos) = crate::time::get_monotonic_time_ns();
+    SyscallResult::Ok(to_clock_ticks(secs * 1_000_000_000 + nanos) as u64)
+}
@@ -0,0 +1,340 @@
+//! Scheduling priority and CPU affinity syscalls
+//!
+//! getpriority/setpriority read and set nice levels; the sched_* calls set
+//! the SCHED_OTHER, SCHED_FIFO and SCHED_RR policies, real-time priorities
+//! and CPU affinity masks described in `task::priority`.
+//!
+//! As on Linux, a pid names one task: each thread is its own process entry
+//! here, so these act on the threads owned by that pid, and pid 0 is the
+//! caller. Only root may lower a nice level or choose a real-time policy.
+
+use super::errno::{EACCES, EFAULT, EINVAL, EPERM, ESRCH};
+use super::userptr::{copy_from_user, copy_to_user};
+use super::SyscallResult;
+use crate::process::ProcessId;
+use crate::task::priority::{
+    Policy, SchedEntity, NICE_MAX, NICE_MIN, RT_PRIORITY_MAX, RT_PRIORITY_MIN,
+};
+use crate::task::scheduler;
+use alloc::vec::Vec;
+
+/// getpriority()/setpriority() targets (Linux values)
+pub const PRIO_PROCESS: i32 = 0;
+pub const PRIO_PGRP: i32 = 1;
+pub const PRIO_USER: i32 = 2;
+
+/// Bytes of an affinity mask the kernel reads and writes (64 CPUs)
+const CPU_MASK_BYTES: u64 = core::mem::size_of::<u64>() as u64;
+
+/// Linux `struct sched_param`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct SchedParam {
+    sched_priority: i32,
+}
+
+/// The caller's pid, uid, effective uid and process group
+struct Caller {
+    pid: ProcessId,
+    uid: u32,
+    euid: u32,
+    pgid: ProcessId,
+}
+
+impl Caller {
+    fn is_root(&self) -> bool {
+        self.euid == 0
+    }
+}
+
+fn caller() -> Result<Caller, u64> {
+    let thread_id = scheduler::current_thread_id().ok_or(ESRCH as u64)?;
+    crate::process::with_process_manager(|manager| {
+        manager
+            .find_process_by_thread(thread_id)
+            .map(|(pid, process)| Caller {
+                pid,
+                uid: process.uid,
+                euid: process.euid,
+                pgid: process.pgid,
+            })
+    })
+    .flatten()
+    .ok_or(ESRCH as u64)
+}
+
+/// Live processes selected by `which` and `who`, checking that a caller
+/// who is not root owns them
+fn select(caller: &Caller, which: i32, who: u64) -> Result<Vec<u64>, u64> {
+    if !matches!(which, PRIO_PROCESS | PRIO_PGRP | PRIO_USER) {
+        return Err(EINVAL as u64);
+    }
+    let pids = crate::process::with_process_manager(|manager| {
+        let mut pids = Vec::new();
+        for (pid, process) in manager.iter_processes() {
+            if process.is_terminated() {
+                continue;
+            }
+            let selected = match which {
+                PRIO_PROCESS if who == 0 => pid == caller.pid,
+                PRIO_PROCESS => pid.as_u64() == who,
+                PRIO_PGRP if who == 0 => process.pgid == caller.pgid,
+                PRIO_PGRP => process.pgid.as_u64() == who,
+                PRIO_USER if who == 0 => process.uid == caller.uid,
+                _ => process.uid as u64 == who,
+            };
+            if !selected {
+                continue;
+            }
+            if !caller.is_root() && process.uid != caller.euid && process.euid != caller.euid {
+                return Err(EPERM as u64);
+            }
+            pids.push(pid.as_u64());
+        }
+        Ok(pids)
+    })
+    .unwrap_or(Err(ESRCH as u64))?;
+    if pids.is_empty() {
+        return Err(ESRCH as u64);
+    }
+    Ok(pids)
+}
+
+/// The one task a sched_* call names
+fn select_task(caller: &Caller, pid: i64) -> Result<Vec<u64>, u64> {
+    if pid < 0 {
+        return Err(EINVAL as u64);
+    }
+    select(caller, PRIO_PROCESS, pid as u64)
+}
+
+/// Scheduling attributes of the first thread of `pids`
+fn sched_of(pids: &[u64]) -> Result<SchedEntity, u64> {
+    scheduler::threads_sched(pids)
+        .first()
+        .copied()
+        .ok_or(ESRCH as u64)
+}
+
+/// getpriority(which, who) - Highest priority (lowest nice) of the selected
+/// processes
+///
+/// Returns `20 - nice` (1 to 40) as the Linux syscall does, so the result is
+/// never negative; libc converts it back.
+pub fn sys_getpriority(which: i32, who: u64) -> SyscallResult {
+    let result = caller().and_then(|caller| select(&caller, which, who));
+    let pids = match result {
+        Ok(pids) => pids,
+        Err(e) => return SyscallResult::Err(e),
+    };
+    match scheduler::threads_sched(&pids)
+        .iter()
+        .map(|sched| sched.nice as i32)
+        .min()
+    {
+        Some(nice) => SyscallResult::Ok((20 - nice) as u64),
+        None => SyscallResult::Err(ESRCH as u64),
+    }
+}
+
+/// setpriority(which, who, prio) - Set the nice level of the selected
+/// processes, clamped to -20..19
+pub fn sys_setpriority(which: i32, who: u64, prio: i32) -> SyscallResult {
+    let caller = match caller() {
+        Ok(caller) => caller,
+        Err(e) => return SyscallResult::Err(e),
+    };
+    let pids = match select(&caller, which, who) {
+        Ok(pids) => pids,
+        Err(e) => return SyscallResult::Err(e),
+    };
+    let nice = prio.clamp(NICE_MIN, NICE_MAX);
+    if !caller.is_root()
+        && scheduler::threads_sched(&pids)
+            .iter()
+            .any(|sched| nice < sched.nice as i32)
+    {
+        return SyscallResult::Err(EACCES as u64);
+    }
+    if scheduler::update_threads_sched(&pids, |sched| sched.nice = nice as i8) == 0 {
+        return SyscallResult::Err(ESRCH as u64);
+    }
+    SyscallResult::Ok(0)
+}
+
+/// Read a `struct sched_param` and check its priority against `policy`
+fn read_priority(param_ptr: u64, policy: Policy) -> Result<u8, u64> {
+    if param_ptr == 0 {
+        return Err(EINVAL as u64);
+    }
+    let param = copy_from_user(param_ptr as *const SchedParam).map_err(|_| EFAULT as u64)?;
+    let priority = param.sched_priority;
+    let valid = if policy.is_realtime() {
+        (RT_PRIORITY_MIN as i32..=RT_PRIORITY_MAX as i32).contains(&priority)
+    } else {
+        priority == 0
+    };
+    if !valid {
+        return Err(EINVAL as u64);
+    }
+    Ok(priority as u8)
+}
+
+/// Set the policy and priority of task `pid`
+fn set_scheduler(pid: i64, policy: Option<Policy>, param_ptr: u64) -> Result<(), u64> {
+    let caller = caller()?;
+    let pids = select_task(&caller, pid)?;
+    let policy = match policy {
+        Some(policy) => policy,
+        None => sched_of(&pids)?.policy,
+    };
+    let priority = read_priority(param_ptr, policy)?;
+    if policy.is_realtime() && !caller.is_root() {
+        return Err(EPERM as u64);
+    }
+    if scheduler::update_threads_sched(&pids, |sched| sched.set_policy(policy, priority)) == 0 {
+        return Err(ESRCH as u64);
+    }
+    Ok(())
+}
+
+fn to_result(result: Result<(), u64>) -> SyscallResult {
+    match result {
+        Ok(()) => SyscallResult::Ok(0),
+        Err(e) => SyscallResult::Err(e),
+    }
+}
+
+/// sched_setscheduler(pid, policy, param) - Set the scheduling policy and
+/// real-time priority of a task
+pub fn sys_sched_setscheduler(pid: i64, policy: u32, param_ptr: u64) -> SyscallResult {
+    match Policy::from_raw(policy) {
+        Some(policy) => to_result(set_scheduler(pid, Some(policy), param_ptr)),
+        None => SyscallResult::Err(EINVAL as u64),
+    }
+}
+
+/// sched_setparam(pid, param) - Set the real-time priority of a task
+/// without changing its policy
+pub fn sys_sched_setparam(pid: i64, param_ptr: u64) -> SyscallResult {
+    to_result(set_scheduler(pid, None, param_ptr))
+}
+
+/// sched_getscheduler(pid) - Scheduling policy of a task
+pub fn sys_sched_getscheduler(pid: i64) -> SyscallResult {
+    let result = caller()
+        .and_then(|caller| select_task(&caller, pid))
+        .and_then(|pids| sched_of(&pids));
+    match result {
+        Ok(sched) => SyscallResult::Ok(sched.policy.raw() as u64),
+        Err(e) => SyscallResult::Err(e),
+    }
+}
+
+/// sched_getparam(pid, param) - Real-time priority of a task (0 for
+/// SCHED_OTHER)
+pub fn sys_sched_getparam(pid: i64, param_ptr: u64) -> SyscallResult {
+    if param_ptr == 0 {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+    let result = caller()
+        .and_then(|caller| select_task(&caller, pid))
+        .and_then(|pids| sched_of(&pids));
+    let sched = match result {
+        Ok(sched) => sched,
+        Err(e) => return SyscallResult::Err(e),
+    };
+    let param = SchedParam {
+        sched_priority: sched.rt_priority as i32,
+    };
+    if copy_to_user(param_ptr as *mut SchedParam, &param).is_err() {
+        return SyscallResult::Err(EFAULT as u64);
+    }
+    SyscallResult::Ok(0)
+}
+
+/// sched_get_priority_max(policy) - Highest priority of a policy
+pub fn sys_sched_get_priority_max(policy: u32) -> SyscallResult {
+    match Policy::from_raw(policy) {
+        Some(policy) if policy.is_realtime() => SyscallResult::Ok(RT_PRIORITY_MAX as u64),
+        Some(_) => SyscallResult::Ok(0),
+        None => SyscallResult::Err(EINVAL as u64),
+    }
+}
+
+/// sched_get_priority_min(policy) - Lowest priority of a policy
+pub fn sys_sched_get_priority_min(policy: u32) -> SyscallResult {
+    match Policy::from_raw(policy) {
+        Some(policy) if policy.is_realtime() => SyscallResult::Ok(RT_PRIORITY_MIN as u64),
+        Some(_) => SyscallResult::Ok(0),
+        None => SyscallResult::Err(EINVAL as u64),
+    }
+}
+
+/// Read the first `len` bytes (at most 64 CPUs) of a user affinity mask
+fn read_cpu_mask(len: u64, mask_ptr: u64) -> Result<u64, u64> {
+    if mask_ptr == 0 {
+        return Err(EFAULT as u64);
+    }
+    let mut mask = 0u64;
+    for byte in 0..len.min(CPU_MASK_BYTES) {
+        let bits = copy_from_user((mask_ptr + byte) as *const u8).map_err(|_| EFAULT as u64)?;
+        mask |= (bits as u64) << (byte * 8);
+    }
+    Ok(mask)
+}
+
+/// sched_setaffinity(pid, len, mask) - Set the CPUs a task may run on
+///
+/// CPUs beyond those the kernel supports are ignored; a mask without any
+/// online CPU is EINVAL. A running task that is no longer allowed on its CPU
+/// moves at its next scheduling decision.
+pub fn sys_sched_setaffinity(pid: i64, len: u64, mask_ptr: u64) -> SyscallResult {
+    let result = read_cpu_mask(len, mask_ptr).and_then(|mask| {
+        let possible = (1u64 << scheduler::MAX_CPUS) - 1;
+        if mask & scheduler::online_cpu_mask() == 0 {
+            return Err(EINVAL as u64);
+        }
+        let pids = caller().and_then(|caller| select_task(&caller, pid))?;
+        let mask = mask & possible;
+        if scheduler::update_threads_sched(&pids, |sched| sched.cpus_allowed = mask) == 0 {
+            return Err(ESRCH as u64);
+        }
+        Ok(())
+    });
+    to_result(result)
+}
+
+/// sched_getaffinity(pid, len, mask) - The online CPUs a task may run on
+///
+/// As the Linux syscall does, returns the size of the mask written, which
+/// `len` must hold.
+pub fn sys_sched_getaffinity(pid: i64, len: u64, mask_ptr: u64) -> SyscallResult {
+    if len < CPU_MASK_BYTES || len % CPU_MASK_BYTES != 0 {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+    let result = caller()
+        .and_then(|caller| select_task(&caller, pid))
+        .and_then(|pids| sched_of(&pids));
+    let mask = match result {
+        Ok(sched) => sched.cpus_allowed & scheduler::online_cpu_mask(),
+        Err(e) => return SyscallResult::Err(e),
+    };
+    if copy_to_user(mask_ptr as *mut u64, &mask).is_err() {
+        return SyscallResult::Err(EFAULT as u64);
+    }
+    SyscallResult::Ok(CPU_MASK_BYTES)
+}
+
+/// getcpu(cpu, node, cache) - The CPU and NUMA node the caller is running
+/// on; there is a single node 0
+pub fn sys_getcpu(cpu_ptr: u64, node_ptr: u64) -> SyscallResult {
+    let cpu = scheduler::current_cpu() as u32;
+    if cpu_ptr != 0 && copy_to_user(cpu_ptr as *mut u32, &cpu).is_err() {
+        return SyscallResult::Err(EFAULT as u64);
+    }
+    if node_ptr != 0 && copy_to_user(node_ptr as *mut u32, &0u32).is_err() {
+        return SyscallResult::Err(EFAULT as u64);
+    }
+    SyscallResult::Ok(0)
+}
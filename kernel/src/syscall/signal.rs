@@ -6,12 +6,15 @@
 //! - sigprocmask(how, set, oldset, sigsetsize) - Block/unblock signals
 //! - sigreturn() - Return from signal handler
 //! - sigaltstack(ss, old_ss) - Set/get alternate signal stack
+//! - tkill/tgkill(..., sig) - Send a signal to one thread
+//! - rt_sigqueueinfo(tgid, sig, info) - Queue a signal with a payload
+//! - rt_sigtimedwait(set, info, timeout, sigsetsize) - Accept a signal synchronously
 
 use super::userptr::{copy_from_user, copy_to_user};
 use super::SyscallResult;
 use crate::process::{manager, ProcessId};
 use crate::signal::constants::*;
-use crate::signal::types::{SignalAction, StackT};
+use crate::signal::types::{SigInfo, SignalAction, StackT};
 
 // Architecture-specific imports
 use crate::arch_impl::traits::CpuOps;
@@ -55,23 +58,44 @@ pub fn sys_kill(pid: i64, sig: i32) -> SyscallResult {
         return SyscallResult::Err(22); // EINVAL
     }
 
+    let info = sender_siginfo(sig);
     if pid > 0 {
         // Send to specific process
-        send_signal_to_process(ProcessId::new(pid as u64), sig)
+        send_signal_to_process(ProcessId::new(pid as u64), info)
     } else if pid == 0 {
         // Send to all processes in caller's process group
-        send_signal_to_caller_process_group(sig)
+        send_signal_to_caller_process_group(info)
     } else if pid == -1 {
         // Send to all processes the caller can signal. The designated init is excluded when one
         // exists; with no designated init, no process is excluded by identity.
-        send_signal_to_all_processes(sig)
+        send_signal_to_all_processes(info)
     } else {
         // pid < -1: Send to process group abs(pid)
         let pgid = ProcessId::new((-pid) as u64);
-        send_signal_to_process_group(pgid, sig)
+        send_signal_to_process_group(pgid, info)
     }
 }
 
+/// Build the SI_USER siginfo for a signal sent by the calling process
+fn sender_siginfo(sig: u32) -> SigInfo {
+    let (pid, uid) = current_sender();
+    SigInfo::user(sig, pid, uid)
+}
+
+/// The calling process's pid and uid, as reported in si_pid/si_uid
+///
+/// Kernel threads have no process; their signals report pid 0 and uid 0.
+fn current_sender() -> (u64, u32) {
+    let sender = crate::task::scheduler::current_thread_id().and_then(|thread_id| {
+        let manager_guard = manager();
+        manager_guard
+            .as_ref()
+            .and_then(|manager| manager.find_process_by_thread(thread_id))
+            .map(|(pid, process)| (pid.as_u64(), process.uid))
+    });
+    sender.unwrap_or((0, 0))
+}
+
 /// Check if a target exists (kill with sig=0)
 ///
 /// This handles all pid cases per POSIX:
@@ -143,7 +167,11 @@ fn check_target_exists(pid: i64) -> SyscallResult {
 }
 
 /// Send a signal to a specific process
-fn send_signal_to_process(target_pid: ProcessId, sig: u32) -> SyscallResult {
+///
+/// `info` carries the signal number and the siginfo the handler will see.
+/// Returns EAGAIN if a real-time signal cannot be queued.
+fn send_signal_to_process(target_pid: ProcessId, info: SigInfo) -> SyscallResult {
+    let sig = info.signo();
     let mut manager_guard = manager();
 
     if let Some(ref mut manager) = *manager_guard {
@@ -153,7 +181,7 @@ fn send_signal_to_process(target_pid: ProcessId, sig: u32) -> SyscallResult {
                 return SyscallResult::Err(3); // ESRCH
             }
 
-            // SIGKILL and SIGSTOP are special - cannot be caught or blocked
+            // SIGKILL is special - it cannot be caught or blocked and acts at once
             if sig == SIGKILL {
                 let victim_pid = process.id;
                 drop(manager_guard);
@@ -174,30 +202,36 @@ fn send_signal_to_process(target_pid: ProcessId, sig: u32) -> SyscallResult {
                 return SyscallResult::Ok(0);
             }
 
-            if sig == SIGSTOP {
-                log::info!("SIGSTOP sent to process {} - stopping", target_pid.as_u64());
-                process.set_blocked();
-                return SyscallResult::Ok(0);
-            }
+            crate::process::job_control::on_send(process, sig);
 
+            // SIGCONT continues a stopped process whatever its disposition
             if sig == SIGCONT {
                 log::info!(
                     "SIGCONT sent to process {} - continuing",
                     target_pid.as_u64()
                 );
-                if matches!(process.state, crate::process::ProcessState::Blocked) {
-                    process.set_ready();
-                    crate::task::scheduler::set_need_resched();
-                }
+                let continued = crate::process::job_control::cont(process);
                 // SIGCONT also gets queued if there's a handler
                 if !process.signals.get_handler(sig).is_default() {
-                    process.signals.set_pending(sig);
+                    process.signals.queue_signal(info);
+                }
+                let thread_id = process.main_thread.as_ref().map(|thread| thread.id);
+                drop(manager_guard);
+                if let Some(thread_id) = thread_id {
+                    crate::task::scheduler::with_scheduler(|sched| {
+                        sched.unblock_for_signal(thread_id);
+                    });
+                }
+                if let Some(continued) = continued {
+                    crate::process::job_control::finish_continue(continued);
                 }
                 return SyscallResult::Ok(0);
             }
 
             // For other signals, queue them for delivery
-            process.signals.set_pending(sig);
+            if !process.signals.queue_signal(info) {
+                return SyscallResult::Err(11); // EAGAIN - real-time queue full
+            }
             log::debug!(
                 "Signal {} ({}) queued for process {}",
                 sig,
@@ -256,7 +290,7 @@ fn send_signal_to_process(target_pid: ProcessId, sig: u32) -> SyscallResult {
 /// # Returns
 /// * 0 on success (signal sent to at least one process)
 /// * -ESRCH (3) if no processes found in the caller's process group
-fn send_signal_to_caller_process_group(sig: u32) -> SyscallResult {
+fn send_signal_to_caller_process_group(info: SigInfo) -> SyscallResult {
     // Get the caller's thread ID to find their process group
     let current_thread_id = match crate::task::scheduler::current_thread_id() {
         Some(id) => id,
@@ -287,11 +321,11 @@ fn send_signal_to_caller_process_group(sig: u32) -> SyscallResult {
 
     log::debug!(
         "send_signal_to_caller_process_group: sending signal {} to pgid {}",
-        sig,
+        info.signo(),
         caller_pgid.as_u64()
     );
 
-    send_signal_to_process_group(caller_pgid, sig)
+    send_signal_to_process_group(caller_pgid, info)
 }
 
 /// Send a signal to all processes in a specific process group
@@ -301,12 +335,13 @@ fn send_signal_to_caller_process_group(sig: u32) -> SyscallResult {
 ///
 /// # Arguments
 /// * `pgid` - The target process group ID
-/// * `sig` - The signal to send
+/// * `info` - The signal to send and its siginfo
 ///
 /// # Returns
 /// * 0 on success (signal sent to at least one process)
 /// * -ESRCH (3) if no processes found in the specified process group
-fn send_signal_to_process_group(pgid: ProcessId, sig: u32) -> SyscallResult {
+fn send_signal_to_process_group(pgid: ProcessId, info: SigInfo) -> SyscallResult {
+    let sig = info.signo();
     log::info!(
         "send_signal_to_process_group: sending signal {} ({}) to process group {}",
         sig,
@@ -348,7 +383,7 @@ fn send_signal_to_process_group(pgid: ProcessId, sig: u32) -> SyscallResult {
     // Send signal to each process in the group
     let mut sent_count = 0;
     for pid in target_pids {
-        match send_signal_to_process(pid, sig) {
+        match send_signal_to_process(pid, info) {
             SyscallResult::Ok(_) => sent_count += 1,
             SyscallResult::Err(e) => {
                 log::debug!(
@@ -383,7 +418,8 @@ fn send_signal_to_process_group(pgid: ProcessId, sig: u32) -> SyscallResult {
 /// # Returns
 /// * 0 on success (signal sent to at least one process)
 /// * -ESRCH (3) if no signalable processes exist
-fn send_signal_to_all_processes(sig: u32) -> SyscallResult {
+fn send_signal_to_all_processes(info: SigInfo) -> SyscallResult {
+    let sig = info.signo();
     log::info!(
         "send_signal_to_all_processes: sending signal {} ({}) to all processes",
         sig,
@@ -425,7 +461,7 @@ fn send_signal_to_all_processes(sig: u32) -> SyscallResult {
     // can send to at least one process
     let mut sent_count = 0;
     for pid in target_pids {
-        match send_signal_to_process(pid, sig) {
+        match send_signal_to_process(pid, info) {
             SyscallResult::Ok(_) => sent_count += 1,
             SyscallResult::Err(e) => {
                 log::debug!(
@@ -450,6 +486,211 @@ fn send_signal_to_all_processes(sig: u32) -> SyscallResult {
     }
 }
 
+/// Sleep until the caller's process is continued after a job-control stop
+///
+/// Used on the ARM64 syscall return path, where the thread cannot be parked
+/// with its user registers saved. Returns at once if the process is not
+/// stopped, and when it is killed while stopped.
+pub fn wait_while_stopped(thread_id: u64) {
+    use crate::process::job_control;
+    use crate::task::thread::ThreadState;
+    use crate::task::waitqueue::PrepareOutcome;
+
+    loop {
+        let generation = job_control::continue_generation();
+        let stopped = crate::process::with_process_manager(|manager| {
+            manager
+                .find_process_by_thread(thread_id)
+                .is_some_and(|(_, process)| job_control::is_stopped(process))
+        })
+        .unwrap_or(false);
+        if !stopped {
+            break;
+        }
+
+        let outcome = job_control::cont_queue().prepare_to_wait_checked(
+            ThreadState::BlockedOnIO,
+            None,
+            || job_control::continue_generation() == generation,
+        );
+        if outcome == PrepareOutcome::Queued {
+            crate::per_cpu::preempt_enable();
+            loop {
+                let still_waiting = crate::task::scheduler::with_scheduler(|sched| {
+                    sched
+                        .current_thread_mut()
+                        .map(|thread| thread.state == ThreadState::BlockedOnIO)
+                        .unwrap_or(false)
+                })
+                .unwrap_or(false);
+                if !still_waiting {
+                    break;
+                }
+                crate::task::scheduler::yield_current();
+                Cpu::halt_with_interrupts();
+            }
+            crate::per_cpu::preempt_disable();
+        }
+        job_control::cont_queue().finish_wait();
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    super::futex::ensure_current_address_space();
+}
+
+/// waitpid() WUNTRACED flag
+const WUNTRACED: u32 = 2;
+
+/// waitpid() WCONTINUED flag
+const WCONTINUED: u32 = 8;
+
+/// Take an unreported stop for waitpid(): a tracee stop, or a job-control
+/// stop (WUNTRACED) or continue (WCONTINUED) of a child matching `pid`
+///
+/// Returns the pid and its waitpid status.
+pub fn wait_report(thread_id: u64, pid: i64, options: u32) -> Option<(u64, i32)> {
+    super::ptrace::stop_report(thread_id, pid).or_else(|| {
+        let untraced = options & WUNTRACED != 0;
+        let continued = options & WCONTINUED != 0;
+        if !untraced && !continued {
+            return None;
+        }
+        crate::process::with_process_manager(|manager| {
+            let (parent, _) = manager.find_process_by_thread(thread_id)?;
+            crate::process::job_control::take_wait_report(manager, parent, pid, untraced, continued)
+        })
+        .flatten()
+        .map(|(child, status)| (child.as_u64(), status))
+    })
+}
+
+/// Job-control check before the caller reads (`SIGTTIN`) or writes or
+/// reconfigures (`SIGTTOU`) a terminal
+///
+/// `session` is the session the terminal controls and `foreground` its
+/// foreground process group. A caller in a background group gets `sig` sent
+/// to its group and fails with EINTR, or fails with EIO when the signal
+/// cannot stop it.
+pub(crate) fn check_tty_access(
+    session: Option<u64>,
+    foreground: Option<u64>,
+    sig: u32,
+) -> Result<(), u64> {
+    use crate::process::job_control::{self, TtyAccess};
+
+    let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+        return Ok(());
+    };
+    let access = crate::process::with_process_manager(|manager| {
+        manager
+            .find_process_by_thread(thread_id)
+            .map(|(_, process)| job_control::tty_access(manager, process, session, foreground, sig))
+    })
+    .flatten()
+    .unwrap_or(TtyAccess::Allowed);
+
+    match access {
+        TtyAccess::Allowed => Ok(()),
+        TtyAccess::Signal(pgid) => {
+            let _ = send_signal_to_process_group(pgid, SigInfo::kernel(sig));
+            Err(super::errno::EINTR as u64)
+        }
+        TtyAccess::Denied => Err(super::errno::EIO as u64),
+    }
+}
+
+/// tkill(tid, sig) - Send a signal to a single thread
+///
+/// Every thread has its own signal state, so the signal is queued on the
+/// thread's process entry and only that thread can take it.
+///
+/// # Returns
+/// * 0 on success
+/// * -EINVAL (22) for an invalid thread ID or signal number
+/// * -ESRCH (3) if no such thread
+/// * -EAGAIN (11) if the real-time signal queue is full
+pub fn sys_tkill(tid: i64, sig: i32) -> SyscallResult {
+    send_signal_to_thread(None, tid, sig)
+}
+
+/// tgkill(tgid, tid, sig) - Send a signal to a thread in a thread group
+///
+/// Like tkill(), but fails with ESRCH unless `tid` belongs to `tgid`, so a
+/// recycled thread ID is not signalled by mistake. `tgid` may be the group
+/// leader's PID or the PID getpid() returns on the target thread.
+pub fn sys_tgkill(tgid: i64, tid: i64, sig: i32) -> SyscallResult {
+    if tgid <= 0 {
+        return SyscallResult::Err(22); // EINVAL
+    }
+    send_signal_to_thread(Some(tgid as u64), tid, sig)
+}
+
+/// Shared implementation of tkill() and tgkill()
+fn send_signal_to_thread(tgid: Option<u64>, tid: i64, sig: i32) -> SyscallResult {
+    let sig = sig as u32;
+    if tid <= 0 || (sig != 0 && !is_valid_signal(sig)) {
+        return SyscallResult::Err(22); // EINVAL
+    }
+
+    let target = {
+        let manager_guard = manager();
+        manager_guard.as_ref().and_then(|manager| {
+            manager
+                .find_process_by_thread(tid as u64)
+                .filter(|(_, process)| !process.is_terminated())
+                .filter(|(pid, process)| match tgid {
+                    Some(tgid) => pid.as_u64() == tgid || process.thread_group_id == Some(tgid),
+                    None => true,
+                })
+                .map(|(pid, _)| pid)
+        })
+    };
+    let Some(target_pid) = target else {
+        return SyscallResult::Err(3); // ESRCH
+    };
+
+    if sig == 0 {
+        return SyscallResult::Ok(0);
+    }
+    let (pid, uid) = current_sender();
+    send_signal_to_process(target_pid, SigInfo::tkill(sig, pid, uid))
+}
+
+/// rt_sigqueueinfo(tgid, sig, uinfo) - Queue a signal with caller-supplied siginfo
+///
+/// Backs sigqueue(): libc fills in SI_QUEUE, its pid/uid and the sigval. A
+/// process may only forge kill()/tkill() codes (si_code >= 0 or SI_TKILL) when
+/// signalling itself.
+///
+/// # Returns
+/// * 0 on success
+/// * -EINVAL (22) for an invalid signal number
+/// * -EFAULT (14) if uinfo is not readable
+/// * -EPERM (1) if si_code is reserved for kill()/tkill()
+/// * -ESRCH (3) if no such process
+/// * -EAGAIN (11) if the real-time signal queue is full
+pub fn sys_rt_sigqueueinfo(tgid: i64, sig: i32, uinfo: u64) -> SyscallResult {
+    let sig = sig as u32;
+    if !is_valid_signal(sig) {
+        return SyscallResult::Err(22); // EINVAL
+    }
+    if tgid <= 0 {
+        return SyscallResult::Err(3); // ESRCH
+    }
+    let mut info: SigInfo = match copy_from_user(uinfo as *const SigInfo) {
+        Ok(info) => info,
+        Err(e) => return SyscallResult::Err(e),
+    };
+
+    let (caller, _) = current_sender();
+    if (info.si_code >= 0 || info.si_code == SI_TKILL) && caller != tgid as u64 {
+        return SyscallResult::Err(1); // EPERM
+    }
+
+    info.si_signo = sig as i32;
+    send_signal_to_process(ProcessId::new(tgid as u64), info)
+}
+
 /// rt_sigaction(sig, act, oldact, sigsetsize) - Set signal handler
 ///
 /// # Arguments
@@ -760,6 +1001,138 @@ pub fn sys_sigpending(set: u64, sigsetsize: u64) -> SyscallResult {
     SyscallResult::Ok(0)
 }
 
+/// rt_sigtimedwait(set, info, timeout, sigsetsize) - Wait for a signal in a set
+///
+/// Synchronously accepts the lowest-numbered pending signal in `set`, which
+/// is normally blocked so no handler runs for it. The signal is removed from
+/// the pending set and its siginfo copied to `info` if non-NULL. Real-time
+/// signals are taken one queued instance at a time. A NULL `timeout` waits
+/// indefinitely; a zero timeout polls.
+///
+/// # Returns
+/// * The accepted signal number on success
+/// * -EAGAIN (11) if the timeout expired with no signal from `set`
+/// * -EINTR (4) if interrupted by a signal outside `set` that has a handler
+/// * -EINVAL (22) for a bad sigsetsize or timeout
+/// * -EFAULT (14) if a pointer is invalid
+pub fn sys_rt_sigtimedwait(set: u64, info: u64, timeout: u64, sigsetsize: u64) -> SyscallResult {
+    if sigsetsize != 8 {
+        return SyscallResult::Err(22); // EINVAL
+    }
+    let wanted: u64 = match copy_from_user(set as *const u64) {
+        Ok(set) => set & !UNCATCHABLE_SIGNALS,
+        Err(e) => return SyscallResult::Err(e),
+    };
+
+    let (cur_secs, cur_nanos) = crate::time::get_monotonic_time_ns();
+    let now_ns = cur_secs as u64 * 1_000_000_000 + cur_nanos as u64;
+    let deadline_ns = if timeout != 0 {
+        let ts: crate::syscall::time::Timespec =
+            match copy_from_user(timeout as *const crate::syscall::time::Timespec) {
+                Ok(ts) => ts,
+                Err(e) => return SyscallResult::Err(e),
+            };
+        if ts.tv_sec < 0 || ts.tv_nsec < 0 || ts.tv_nsec >= 1_000_000_000 {
+            return SyscallResult::Err(22); // EINVAL
+        }
+        let wait_ns = (ts.tv_sec as u64)
+            .saturating_mul(1_000_000_000)
+            .saturating_add(ts.tv_nsec as u64);
+        Some(now_ns.saturating_add(wait_ns))
+    } else {
+        None
+    };
+
+    let thread_id = match crate::task::scheduler::current_thread_id() {
+        Some(tid) => tid,
+        None => return SyscallResult::Err(3), // ESRCH
+    };
+    let take_waited_signal = || {
+        let mut manager_guard = manager();
+        manager_guard
+            .as_mut()
+            .and_then(|manager| manager.find_process_by_thread_mut(thread_id))
+            .and_then(|(_, process)| process.signals.dequeue_signal_in(wanted))
+    };
+
+    // Fast path: a signal is already pending, or this is a poll
+    let mut accepted = take_waited_signal();
+    if accepted.is_none() && deadline_ns.is_some_and(|deadline| deadline <= now_ns) {
+        return SyscallResult::Err(11); // EAGAIN
+    }
+
+    if accepted.is_none() {
+        // Sleep as an I/O wait: unblock_for_signal() wakes BlockedOnIO threads
+        // when a signal is queued, and the timer heap wakes us at the deadline.
+        #[cfg(target_arch = "aarch64")]
+        crate::per_cpu_aarch64::preempt_enable();
+        #[cfg(target_arch = "x86_64")]
+        crate::per_cpu::preempt_enable();
+
+        let result = loop {
+            // Publish the sleep before checking for signals so a signal queued
+            // after the check still finds us blocked and wakes us.
+            crate::task::scheduler::with_scheduler(|sched| {
+                let blocked = sched
+                    .current_thread_mut()
+                    .map(|t| t.state == crate::task::thread::ThreadState::BlockedOnIO)
+                    .unwrap_or(false);
+                if !blocked {
+                    sched.block_current_for_io_with_timeout(deadline_ns);
+                }
+            });
+
+            if let Some(waited) = take_waited_signal() {
+                break Ok(waited);
+            }
+            if let Some(e) = crate::syscall::check_signals_for_eintr() {
+                break Err(e as u64);
+            }
+            if let Some(deadline) = deadline_ns {
+                let (secs, nanos) = crate::time::get_monotonic_time_ns();
+                if secs as u64 * 1_000_000_000 + nanos as u64 >= deadline {
+                    break Err(11); // EAGAIN
+                }
+            }
+
+            crate::task::scheduler::yield_current();
+            Cpu::halt_with_interrupts();
+        };
+
+        crate::task::scheduler::with_scheduler(|sched| {
+            if let Some(thread) = sched.current_thread_mut() {
+                if thread.state == crate::task::thread::ThreadState::BlockedOnIO {
+                    thread.set_ready();
+                }
+                thread.wake_time_ns = None;
+                thread.blocked_in_syscall = false;
+            }
+        });
+
+        #[cfg(target_arch = "aarch64")]
+        crate::per_cpu_aarch64::preempt_disable();
+        #[cfg(target_arch = "x86_64")]
+        crate::per_cpu::preempt_disable();
+        #[cfg(target_arch = "aarch64")]
+        crate::syscall::time::ensure_current_address_space();
+
+        match result {
+            Ok(waited) => accepted = Some(waited),
+            Err(e) => return SyscallResult::Err(e),
+        }
+    }
+
+    let Some(accepted) = accepted else {
+        return SyscallResult::Err(11); // EAGAIN
+    };
+    if info != 0 {
+        if let Err(e) = copy_to_user(info as *mut SigInfo, &accepted) {
+            return SyscallResult::Err(e);
+        }
+    }
+    SyscallResult::Ok(accepted.signo() as u64)
+}
+
 /// rt_sigreturn() - Return from signal handler (legacy - use sys_sigreturn_with_frame)
 #[allow(dead_code)]
 pub fn sys_sigreturn() -> SyscallResult {
@@ -963,7 +1336,7 @@ const REQUIRED_RFLAGS: u64 = 0x0000_0200;
 /// - Sanitizes saved_rflags (prevents disabling interrupts, changing IOPL)
 #[cfg(target_arch = "x86_64")]
 pub fn sys_sigreturn_with_frame(frame: &mut super::handler::SyscallFrame) -> SyscallResult {
-    use crate::signal::types::SignalFrame;
+    use crate::signal::types::{SignalFrame, UContext};
 
     // The signal frame is at RSP - 8
     // When we delivered the signal, we set RSP to point to the signal frame.
@@ -973,7 +1346,7 @@ pub fn sys_sigreturn_with_frame(frame: &mut super::handler::SyscallFrame) -> Sys
     let signal_frame_ptr = (frame.rsp - 8) as *const SignalFrame;
 
     // Read the signal frame from userspace (with validation)
-    let signal_frame = match copy_from_user(signal_frame_ptr) {
+    let mut signal_frame = match copy_from_user(signal_frame_ptr) {
         Ok(frame) => frame,
         Err(errno) => {
             log::error!(
@@ -995,6 +1368,22 @@ pub fn sys_sigreturn_with_frame(frame: &mut super::handler::SyscallFrame) -> Sys
         return SyscallResult::Err(14); // EFAULT
     }
 
+    // Handlers may edit the ucontext to change where they return to, so it
+    // takes precedence over the frame copy. The checks below apply to both.
+    if signal_frame.ucontext_ptr != 0 {
+        let ucontext_ptr = signal_frame.ucontext_ptr as *const UContext;
+        match copy_from_user(ucontext_ptr) {
+            Ok(uc) => signal_frame.apply_ucontext(&uc),
+            Err(errno) => {
+                log::error!(
+                    "sys_sigreturn: invalid ucontext pointer {:#x}",
+                    signal_frame.ucontext_ptr
+                );
+                return SyscallResult::Err(errno);
+            }
+        }
+    }
+
     // SECURITY: Validate saved_rip is in userspace
     // Prevents returning to kernel code for privilege escalation
     if signal_frame.saved_rip >= USER_SPACE_END {
@@ -1169,21 +1558,7 @@ pub fn sys_sigaltstack(ss: u64, old_ss: u64) -> SyscallResult {
         };
 
         let alt = &process.signals.alt_stack;
-        (
-            StackT {
-                ss_sp: alt.base,
-                ss_flags: if alt.on_stack {
-                    SS_ONSTACK as i32
-                } else if alt.flags & SS_DISABLE != 0 {
-                    SS_DISABLE as i32
-                } else {
-                    0
-                },
-                _pad: 0,
-                ss_size: alt.size,
-            },
-            alt.on_stack,
-        )
+        (alt.to_stack_t(), alt.on_stack)
     };
 
     if old_ss != 0 {
@@ -1952,7 +2327,7 @@ pub fn sys_pause_with_frame_aarch64(
 pub fn sys_sigreturn_with_frame_aarch64(
     frame: &mut crate::arch_impl::aarch64::exception_frame::Aarch64ExceptionFrame,
 ) -> SyscallResult {
-    use crate::signal::types::SignalFrame;
+    use crate::signal::types::{SignalFrame, UContext};
 
     // On ARM64, signal frame is at current SP_EL0
     // The signal handler returns via BLR to the trampoline, which calls sigreturn.
@@ -1961,7 +2336,7 @@ pub fn sys_sigreturn_with_frame_aarch64(
     let signal_frame_ptr = sp as *const SignalFrame;
 
     // Read the signal frame from userspace (with validation)
-    let signal_frame = match copy_from_user(signal_frame_ptr) {
+    let mut signal_frame = match copy_from_user(signal_frame_ptr) {
         Ok(f) => f,
         Err(errno) => {
             log::error!(
@@ -1982,6 +2357,22 @@ pub fn sys_sigreturn_with_frame_aarch64(
         return SyscallResult::Err(14); // EFAULT
     }
 
+    // Handlers may edit the ucontext to change where they return to, so it
+    // takes precedence over the frame copy. The checks below apply to both.
+    if signal_frame.ucontext_ptr != 0 {
+        let ucontext_ptr = signal_frame.ucontext_ptr as *const UContext;
+        match copy_from_user(ucontext_ptr) {
+            Ok(uc) => signal_frame.apply_ucontext(&uc),
+            Err(errno) => {
+                log::error!(
+                    "sys_sigreturn_aarch64: invalid ucontext pointer {:#x}",
+                    signal_frame.ucontext_ptr
+                );
+                return SyscallResult::Err(errno);
+            }
+        }
+    }
+
     // Validate saved_pc is in userspace
     if signal_frame.saved_pc >= USER_SPACE_END {
         log::error!(
@@ -0,0 +1,568 @@
+//! In-kernel data transfer syscalls: sendfile, splice, and tee
+//!
+//! These move data between descriptors without copying it through a
+//! userspace buffer:
+//!
+//! - `sendfile` reads a regular file and writes to any descriptor (a TCP
+//!   connection, pipe, tty, or another file) through the same path as write().
+//! - `splice` moves data between a pipe and a regular file or any writable
+//!   descriptor, or between two pipes.
+//! - `tee` duplicates the contents of one pipe into another without consuming
+//!   them.
+//!
+//! Pipe-to-pipe splice and tee hold both pipe locks and copy bytes straight
+//! from one ring buffer into the other, with no intermediate buffer or heap
+//! allocation.
+//!
+//! Transfers that involve a file or another kind of descriptor still stage
+//! each chunk (at most PIPE_BUF_SIZE bytes) in one kernel buffer. ext2 has
+//! no page cache to lend pages from, and read_file_range() returns an owned
+//! buffer. A pipe's spinlock also cannot be held across a socket or file
+//! write that may block. Avoiding that copy for sendfile would need a page
+//! cache and a TCP send path that can take borrowed pages, and neither
+//! exists yet.
+//!
+//! Pipe contents are peeked and only consumed once the destination has
+//! accepted them, so a short write leaves the remainder in the pipe. As with
+//! write(), a full destination pipe returns EAGAIN rather than blocking; an
+//! empty source pipe blocks unless SPLICE_F_NONBLOCK or O_NONBLOCK is set.
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+use super::errno::{EAGAIN, EBADF, EINVAL, EIO, EPIPE, ESPIPE};
+use super::handlers;
+use super::userptr::{copy_from_user, copy_to_user};
+use super::SyscallResult;
+use crate::ipc::fd::{status_flags, RegularFile};
+use crate::ipc::pipe::{PipeBuffer, PIPE_BUF_SIZE};
+use crate::ipc::FdKind;
+
+/// splice()/tee() flags (Linux values)
+pub const SPLICE_F_MOVE: u32 = 1;
+pub const SPLICE_F_NONBLOCK: u32 = 2;
+pub const SPLICE_F_MORE: u32 = 4;
+pub const SPLICE_F_GIFT: u32 = 8;
+
+const SPLICE_F_ALL: u32 = SPLICE_F_MOVE | SPLICE_F_NONBLOCK | SPLICE_F_MORE | SPLICE_F_GIFT;
+
+/// Largest count a single call transfers (matches Linux MAX_RW_COUNT)
+const MAX_RW_COUNT: u64 = 0x7fff_f000;
+
+/// Bytes read from a file per step
+const CHUNK_SIZE: usize = PIPE_BUF_SIZE;
+
+/// What a descriptor taking part in a transfer refers to
+enum Endpoint {
+    File(Arc<Mutex<RegularFile>>),
+    PipeRead {
+        buffer: Arc<Mutex<PipeBuffer>>,
+        nonblocking: bool,
+    },
+    PipeWrite {
+        buffer: Arc<Mutex<PipeBuffer>>,
+        nonblocking: bool,
+    },
+    /// Any other descriptor; written through write_buffer_to_fd
+    Other,
+}
+
+/// Look up a descriptor in the current process's fd table
+fn endpoint_for_fd(fd: i32) -> Result<Endpoint, u64> {
+    let thread_id = match crate::task::scheduler::current_thread_id() {
+        Some(id) => id,
+        None => return Err(EBADF as u64),
+    };
+
+    crate::arch_without_interrupts(|| {
+        let manager_guard = crate::process::manager();
+        let manager = match *manager_guard {
+            Some(ref m) => m,
+            None => return Err(EBADF as u64),
+        };
+        let (_pid, process) = match manager.find_process_by_thread(thread_id) {
+            Some(p) => p,
+            None => return Err(EBADF as u64),
+        };
+        let fd_entry = match process.fd_table.get(fd) {
+            Some(entry) => entry,
+            None => return Err(EBADF as u64),
+        };
+        let nonblocking = (fd_entry.status_flags & status_flags::O_NONBLOCK) != 0;
+        Ok(match &fd_entry.kind {
+            FdKind::RegularFile(file) => Endpoint::File(file.clone()),
+            FdKind::PipeRead(buffer) | FdKind::FifoRead(_, buffer) => Endpoint::PipeRead {
+                buffer: buffer.clone(),
+                nonblocking,
+            },
+            FdKind::PipeWrite(buffer) | FdKind::FifoWrite(_, buffer) => Endpoint::PipeWrite {
+                buffer: buffer.clone(),
+                nonblocking,
+            },
+            _ => Endpoint::Other,
+        })
+    })
+}
+
+/// Helper: run `f` against the ext2 filesystem holding `mount_id`
+fn with_ext2_read<T>(
+    mount_id: usize,
+    f: impl FnOnce(&crate::fs::ext2::Ext2Fs) -> Result<T, u64>,
+) -> Result<T, u64> {
+    use crate::fs::ext2;
+
+    let is_home = ext2::home_mount_id().map_or(false, |id| id == mount_id);
+    if is_home {
+        let fs_guard = ext2::home_fs_read();
+        fs_guard.as_ref().map_or(Err(EIO as u64), f)
+    } else {
+        let fs_guard = ext2::root_fs_read();
+        fs_guard.as_ref().map_or(Err(EIO as u64), f)
+    }
+}
+
+/// Read up to `max` bytes of a regular file starting at `offset`
+///
+/// Returns an empty buffer at or past end of file.
+fn read_file_at(file: &Arc<Mutex<RegularFile>>, offset: u64, max: usize) -> Result<Vec<u8>, u64> {
+    let (inode_num, mount_id, flags) = {
+        let file = file.lock();
+        (file.inode_num, file.mount_id, file.flags)
+    };
+    if flags & 3 == super::fs::O_WRONLY {
+        return Err(EBADF as u64);
+    }
+
+    with_ext2_read(mount_id, |fs| {
+        let inode = fs.read_inode(inode_num as u32).map_err(|_| EIO as u64)?;
+        let size = inode.size();
+        if offset >= size {
+            return Ok(Vec::new());
+        }
+        let to_read = core::cmp::min(max as u64, size - offset) as usize;
+        let mut data = fs
+            .read_file_range(&inode, offset, to_read)
+            .map_err(|_| EIO as u64)?;
+        data.truncate(to_read);
+        Ok(data)
+    })
+}
+
+/// Write `data` to a regular file at `offset` without touching its position
+fn write_file_at(file: &Arc<Mutex<RegularFile>>, offset: u64, data: &[u8]) -> Result<usize, u64> {
+    use crate::fs::ext2;
+
+    let (inode_num, mount_id, flags) = {
+        let file = file.lock();
+        (file.inode_num, file.mount_id, file.flags)
+    };
+    if flags & 3 == super::fs::O_RDONLY {
+        return Err(EBADF as u64);
+    }
+
+    let is_home = ext2::home_mount_id().map_or(false, |id| id == mount_id);
+    let result = if is_home {
+        let mut fs_guard = ext2::home_fs_write();
+        match fs_guard.as_mut() {
+            Some(fs) => fs.write_file_range(inode_num as u32, offset, data),
+            None => return Err(EIO as u64),
+        }
+    } else {
+        let mut fs_guard = ext2::root_fs_write();
+        match fs_guard.as_mut() {
+            Some(fs) => fs.write_file_range(inode_num as u32, offset, data),
+            None => return Err(EIO as u64),
+        }
+    };
+    result.map_err(|e| super::fs::ext2_errno(e) as u64)
+}
+
+/// Read a user-supplied `loff_t *` offset
+fn read_user_offset(ptr: u64) -> Result<u64, u64> {
+    let offset = copy_from_user(ptr as *const i64)?;
+    if offset < 0 {
+        return Err(EINVAL as u64);
+    }
+    Ok(offset as u64)
+}
+
+/// Peek up to `max` bytes from a pipe, waiting for data if allowed
+///
+/// Returns an empty buffer at EOF (no data and no writers).
+fn peek_pipe(pipe: &Arc<Mutex<PipeBuffer>>, max: usize, nonblocking: bool) -> Result<Vec<u8>, u64> {
+    loop {
+        {
+            let buffer = pipe.lock();
+            if buffer.available() > 0 {
+                let mut data = vec![0u8; max.min(buffer.available())];
+                let n = buffer.peek(&mut data);
+                data.truncate(n);
+                return Ok(data);
+            }
+            if !buffer.has_writers() {
+                return Ok(Vec::new());
+            }
+        }
+        if nonblocking {
+            return Err(EAGAIN as u64);
+        }
+        handlers::wait_for_pipe_data(pipe)?;
+    }
+}
+
+/// Move (or, for tee, copy) up to `len` bytes from one pipe to another
+///
+/// Both pipe locks are held while the bytes are copied ring to ring, so
+/// nothing is staged in a temporary buffer. The locks are always taken in
+/// address order so two opposite-direction splices cannot deadlock.
+fn pipe_to_pipe(
+    input: &Arc<Mutex<PipeBuffer>>,
+    output: &Arc<Mutex<PipeBuffer>>,
+    len: usize,
+    nonblocking: bool,
+    consume: bool,
+) -> SyscallResult {
+    if Arc::ptr_eq(input, output) {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+
+    loop {
+        {
+            let (mut in_buf, mut out_buf) = if Arc::as_ptr(input) < Arc::as_ptr(output) {
+                let in_buf = input.lock();
+                (in_buf, output.lock())
+            } else {
+                let out_buf = output.lock();
+                (input.lock(), out_buf)
+            };
+            if !out_buf.has_readers() {
+                return SyscallResult::Err(EPIPE as u64);
+            }
+            if out_buf.space() == 0 {
+                return SyscallResult::Err(EAGAIN as u64);
+            }
+            if in_buf.available() > 0 {
+                let copied = match in_buf.copy_to(&mut out_buf, len) {
+                    Ok(n) => n,
+                    Err(e) => return SyscallResult::Err(e as u64),
+                };
+                if consume {
+                    in_buf.consume(copied);
+                }
+                return SyscallResult::Ok(copied as u64);
+            }
+            if !in_buf.has_writers() {
+                return SyscallResult::Ok(0);
+            }
+        }
+        if nonblocking {
+            return SyscallResult::Err(EAGAIN as u64);
+        }
+        if let Err(errno) = handlers::wait_for_pipe_data(input) {
+            return SyscallResult::Err(errno);
+        }
+    }
+}
+
+/// sys_sendfile - Copy data from a file to another descriptor in the kernel
+///
+/// # Arguments
+/// * `out_fd` - Destination: any descriptor open for writing (socket, pipe, file, tty)
+/// * `in_fd` - Source: a regular file open for reading
+/// * `offset_ptr` - If non-null, a `loff_t *` giving the read offset; it is
+///   updated and the file position of `in_fd` is left unchanged. If null,
+///   reading starts at and advances the file position.
+/// * `count` - Maximum number of bytes to copy
+///
+/// # Returns
+/// Number of bytes written to `out_fd`, or negative errno
+///
+/// # Errors
+/// * EBADF - A descriptor is invalid or has the wrong access mode
+/// * EINVAL - in_fd is not a regular file, or the offset is negative
+/// * EFAULT - offset_ptr is not a valid userspace pointer
+/// * EAGAIN - out_fd would block and nothing was sent
+/// * EPIPE - out_fd is a pipe or socket with no reader
+pub fn sys_sendfile(out_fd: i32, in_fd: i32, offset_ptr: u64, count: u64) -> SyscallResult {
+    let file = match endpoint_for_fd(in_fd) {
+        Ok(Endpoint::File(file)) => file,
+        Ok(_) => return SyscallResult::Err(EINVAL as u64),
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+    if let Err(errno) = endpoint_for_fd(out_fd) {
+        return SyscallResult::Err(errno);
+    }
+
+    let mut position = if offset_ptr != 0 {
+        match read_user_offset(offset_ptr) {
+            Ok(offset) => offset,
+            Err(errno) => return SyscallResult::Err(errno),
+        }
+    } else {
+        file.lock().position
+    };
+
+    let count = count.min(MAX_RW_COUNT);
+    let mut total: u64 = 0;
+    let mut error = None;
+    while total < count {
+        let chunk = core::cmp::min(CHUNK_SIZE as u64, count - total) as usize;
+        let data = match read_file_at(&file, position, chunk) {
+            Ok(data) => data,
+            Err(errno) => {
+                error = Some(errno);
+                break;
+            }
+        };
+        if data.is_empty() {
+            break;
+        }
+
+        match handlers::write_buffer_to_fd(out_fd as u64, &data) {
+            SyscallResult::Ok(n) => {
+                position += n;
+                total += n;
+                // Short write: the destination is full for now
+                if (n as usize) < data.len() {
+                    break;
+                }
+            }
+            SyscallResult::Err(errno) => {
+                error = Some(errno);
+                break;
+            }
+        }
+    }
+
+    if offset_ptr != 0 {
+        if let Err(errno) = copy_to_user(offset_ptr as *mut i64, &(position as i64)) {
+            return SyscallResult::Err(errno);
+        }
+    } else {
+        file.lock().position = position;
+    }
+
+    match error {
+        Some(errno) if total == 0 => SyscallResult::Err(errno),
+        _ => SyscallResult::Ok(total),
+    }
+}
+
+/// sys_splice - Move data between a pipe and another descriptor
+///
+/// One of the descriptors must be a pipe (or FIFO). Supported pairs:
+/// pipe to pipe, pipe to any writable descriptor, and regular file to pipe.
+/// At most one pipe buffer's worth of data moves per call.
+///
+/// # Arguments
+/// * `fd_in` - Source descriptor
+/// * `off_in` - `loff_t *` offset for a file source, or null to use (and
+///   advance) its file position; must be null for a pipe
+/// * `fd_out` - Destination descriptor
+/// * `off_out` - `loff_t *` offset for a file destination, or null; must be
+///   null for a pipe
+/// * `len` - Maximum number of bytes to move
+/// * `flags` - SPLICE_F_* flags; SPLICE_F_NONBLOCK makes pipe operations
+///   non-blocking, the others are accepted as hints
+///
+/// # Returns
+/// Number of bytes moved (0 at end of input), or negative errno
+///
+/// # Errors
+/// * EBADF - A descriptor is invalid or has the wrong access mode
+/// * EINVAL - Neither descriptor is a pipe, the source cannot be spliced
+///   from, both ends are the same pipe, or unknown flags
+/// * ESPIPE - An offset was given for a pipe
+/// * EAGAIN - Non-blocking and the source pipe is empty, or the destination
+///   pipe is full
+/// * EPIPE - The destination pipe has no readers
+pub fn sys_splice(
+    fd_in: i32,
+    off_in: u64,
+    fd_out: i32,
+    off_out: u64,
+    len: u64,
+    flags: u32,
+) -> SyscallResult {
+    if flags & !SPLICE_F_ALL != 0 {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+    if len == 0 {
+        return SyscallResult::Ok(0);
+    }
+    let len = len.min(MAX_RW_COUNT) as usize;
+    let flag_nonblocking = flags & SPLICE_F_NONBLOCK != 0;
+
+    let input = match endpoint_for_fd(fd_in) {
+        Ok(endpoint) => endpoint,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+    let output = match endpoint_for_fd(fd_out) {
+        Ok(endpoint) => endpoint,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+
+    match (input, output) {
+        (
+            Endpoint::PipeRead {
+                buffer: input,
+                nonblocking,
+            },
+            Endpoint::PipeWrite { buffer: output, .. },
+        ) => {
+            if off_in != 0 || off_out != 0 {
+                return SyscallResult::Err(ESPIPE as u64);
+            }
+            pipe_to_pipe(&input, &output, len, nonblocking || flag_nonblocking, true)
+        }
+        (
+            Endpoint::PipeRead {
+                buffer: input,
+                nonblocking,
+            },
+            output,
+        ) => {
+            if off_in != 0 {
+                return SyscallResult::Err(ESPIPE as u64);
+            }
+            let out_offset = match (&output, off_out) {
+                (_, 0) => None,
+                (Endpoint::File(_), ptr) => match read_user_offset(ptr) {
+                    Ok(offset) => Some(offset),
+                    Err(errno) => return SyscallResult::Err(errno),
+                },
+                _ => return SyscallResult::Err(ESPIPE as u64),
+            };
+
+            let data = match peek_pipe(&input, len, nonblocking || flag_nonblocking) {
+                Ok(data) => data,
+                Err(errno) => return SyscallResult::Err(errno),
+            };
+            if data.is_empty() {
+                return SyscallResult::Ok(0);
+            }
+
+            let written = match (&output, out_offset) {
+                (Endpoint::File(file), Some(offset)) => match write_file_at(file, offset, &data) {
+                    Ok(n) => {
+                        let new_offset = (offset + n as u64) as i64;
+                        if let Err(errno) = copy_to_user(off_out as *mut i64, &new_offset) {
+                            return SyscallResult::Err(errno);
+                        }
+                        n
+                    }
+                    Err(errno) => return SyscallResult::Err(errno),
+                },
+                _ => match handlers::write_buffer_to_fd(fd_out as u64, &data) {
+                    SyscallResult::Ok(n) => n as usize,
+                    SyscallResult::Err(errno) => return SyscallResult::Err(errno),
+                },
+            };
+            input.lock().consume(written);
+            SyscallResult::Ok(written as u64)
+        }
+        (Endpoint::File(file), Endpoint::PipeWrite { buffer: output, .. }) => {
+            if off_out != 0 {
+                return SyscallResult::Err(ESPIPE as u64);
+            }
+            let mut position = if off_in != 0 {
+                match read_user_offset(off_in) {
+                    Ok(offset) => offset,
+                    Err(errno) => return SyscallResult::Err(errno),
+                }
+            } else {
+                file.lock().position
+            };
+
+            let space = {
+                let out = output.lock();
+                if !out.has_readers() {
+                    return SyscallResult::Err(EPIPE as u64);
+                }
+                out.space()
+            };
+            if space == 0 {
+                return SyscallResult::Err(EAGAIN as u64);
+            }
+
+            let data = match read_file_at(&file, position, len.min(space).min(CHUNK_SIZE)) {
+                Ok(data) => data,
+                Err(errno) => return SyscallResult::Err(errno),
+            };
+            let written = if data.is_empty() {
+                0
+            } else {
+                match output.lock().write(&data) {
+                    Ok(n) => n,
+                    Err(e) => return SyscallResult::Err(e as u64),
+                }
+            };
+            position += written as u64;
+
+            if off_in != 0 {
+                if let Err(errno) = copy_to_user(off_in as *mut i64, &(position as i64)) {
+                    return SyscallResult::Err(errno);
+                }
+            } else {
+                file.lock().position = position;
+            }
+            SyscallResult::Ok(written as u64)
+        }
+        _ => SyscallResult::Err(EINVAL as u64),
+    }
+}
+
+/// sys_tee - Duplicate pipe contents into another pipe without consuming them
+///
+/// # Arguments
+/// * `fd_in` - Read end of the source pipe
+/// * `fd_out` - Write end of the destination pipe
+/// * `len` - Maximum number of bytes to duplicate
+/// * `flags` - SPLICE_F_* flags; SPLICE_F_NONBLOCK makes the call non-blocking
+///
+/// # Returns
+/// Number of bytes duplicated (0 if the source is empty with no writers),
+/// or negative errno
+///
+/// # Errors
+/// * EBADF - A descriptor is invalid
+/// * EINVAL - Either descriptor is not a pipe end of the right direction,
+///   both refer to the same pipe, or unknown flags
+/// * EAGAIN - Non-blocking and the source is empty, or the destination is full
+/// * EPIPE - The destination pipe has no readers
+pub fn sys_tee(fd_in: i32, fd_out: i32, len: u64, flags: u32) -> SyscallResult {
+    if flags & !SPLICE_F_ALL != 0 {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+    if len == 0 {
+        return SyscallResult::Ok(0);
+    }
+
+    let input = match endpoint_for_fd(fd_in) {
+        Ok(endpoint) => endpoint,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+    let output = match endpoint_for_fd(fd_out) {
+        Ok(endpoint) => endpoint,
+        Err(errno) => return SyscallResult::Err(errno),
+    };
+
+    match (input, output) {
+        (
+            Endpoint::PipeRead {
+                buffer: input,
+                nonblocking,
+            },
+            Endpoint::PipeWrite { buffer: output, .. },
+        ) => pipe_to_pipe(
+            &input,
+            &output,
+            len.min(MAX_RW_COUNT) as usize,
+            nonblocking || flags & SPLICE_F_NONBLOCK != 0,
+            false,
+        ),
+        _ => SyscallResult::Err(EINVAL as u64),
+    }
+}
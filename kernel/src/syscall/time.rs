@@ -128,7 +128,7 @@ pub fn sys_clock_settime(clock_id: u32, user_ptr: *const Timespec) -> SyscallRes
 /// manager() to reliably restore the correct page tables before returning
 /// to userspace.
 #[cfg(target_arch = "aarch64")]
-fn ensure_current_address_space() {
+pub(crate) fn ensure_current_address_space() {
     let thread_id = match crate::task::scheduler::current_thread_id() {
         Some(id) => id,
         None => return,
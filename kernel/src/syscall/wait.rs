@@ -51,11 +51,13 @@ fn ensure_current_address_space() {
 pub const WNOHANG: u32 = 1;
 #[allow(dead_code)]
 pub const WUNTRACED: u32 = 2;
+#[allow(dead_code)]
+pub const WCONTINUED: u32 = 8;
 
 /// sys_waitpid - Wait for a child process to change state
 ///
 /// This implements the wait4/waitpid system call.
-pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
+pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32, rusage_ptr: u64) -> SyscallResult {
     log::debug!(
         "sys_waitpid: pid={}, status_ptr={:#x}, options={}",
         pid,
@@ -72,6 +74,13 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
         }
     };
 
+    // A tracer also collects its tracees' stops here
+    if crate::process::ptrace::active() {
+        if let Some(result) = super::ptrace::wait_for_tracee(thread_id, pid, status_ptr, options) {
+            return result;
+        }
+    }
+
     // Find current process.
     let is_wnohang = (options & WNOHANG) != 0;
     let mut manager_guard = crate::process::manager();
@@ -136,7 +145,12 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
             };
 
             if let Some((child_pid, exit_code)) = child_terminated {
-                return complete_wait(child_pid, exit_code, status_ptr);
+                return complete_wait(child_pid, exit_code, status_ptr, rusage_ptr);
+            }
+
+            // Report a child that stopped or continued
+            if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                return super::ptrace::complete_stop_wait(child, status, status_ptr);
             }
 
             if options & WNOHANG != 0 {
@@ -176,12 +190,17 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                                     thread.set_ready();
                                 }
                             });
-                            return complete_wait(target_pid, exit_code, status_ptr);
+                            return complete_wait(target_pid, exit_code, status_ptr, rusage_ptr);
                         }
                     }
                 }
             }
 
+            // A child or tracee may have stopped in the race window too
+            if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                return super::ptrace::complete_stop_wait(child, status, status_ptr);
+            }
+
             crate::per_cpu::preempt_enable();
 
             loop {
@@ -205,13 +224,19 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                 crate::task::scheduler::yield_current();
                 Cpu::halt_with_interrupts();
 
+                // A child or tracee stopping wakes the waiter like a child exiting
+                if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                    crate::per_cpu::preempt_disable();
+                    return super::ptrace::complete_stop_wait(child, status, status_ptr);
+                }
+
                 let manager_guard = crate::process::manager();
                 if let Some(ref manager) = *manager_guard {
                     if let Some(child) = manager.get_process(target_pid) {
                         if let crate::process::ProcessState::Terminated(exit_code) = child.state {
                             drop(manager_guard);
                             crate::per_cpu::preempt_disable();
-                            return complete_wait(target_pid, exit_code, status_ptr);
+                            return complete_wait(target_pid, exit_code, status_ptr, rusage_ptr);
                         }
                     }
                 }
@@ -243,7 +268,12 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
             };
 
             if let Some((child_pid, exit_code)) = terminated_child {
-                return complete_wait(child_pid, exit_code, status_ptr);
+                return complete_wait(child_pid, exit_code, status_ptr, rusage_ptr);
+            }
+
+            // Report a child that stopped or continued
+            if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                return super::ptrace::complete_stop_wait(child, status, status_ptr);
             }
 
             if is_wnohang {
@@ -277,13 +307,18 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                                         thread.set_ready();
                                     }
                                 });
-                                return complete_wait(child_pid, exit_code, status_ptr);
+                                return complete_wait(child_pid, exit_code, status_ptr, rusage_ptr);
                             }
                         }
                     }
                 }
             }
 
+            // A child or tracee may have stopped in the race window too
+            if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                return super::ptrace::complete_stop_wait(child, status, status_ptr);
+            }
+
             crate::per_cpu::preempt_enable();
 
             loop {
@@ -307,6 +342,12 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                 crate::task::scheduler::yield_current();
                 Cpu::halt_with_interrupts();
 
+                // A child or tracee stopping wakes the waiter like a child exiting
+                if let Some((child, status)) = super::signal::wait_report(thread_id, pid, options) {
+                    crate::per_cpu::preempt_disable();
+                    return super::ptrace::complete_stop_wait(child, status, status_ptr);
+                }
+
                 let manager_guard = crate::process::manager();
                 if let Some(ref manager) = *manager_guard {
                     for &child_pid in &children_copy {
@@ -315,7 +356,7 @@ pub fn sys_waitpid(pid: i64, status_ptr: u64, options: u32) -> SyscallResult {
                             {
                                 drop(manager_guard);
                                 crate::per_cpu::preempt_disable();
-                                return complete_wait(child_pid, exit_code, status_ptr);
+                                return complete_wait(child_pid, exit_code, status_ptr, rusage_ptr);
                             }
                         }
                     }
@@ -336,6 +377,7 @@ fn complete_wait(
     child_pid: crate::process::ProcessId,
     exit_code: i32,
     status_ptr: u64,
+    rusage_ptr: u64,
 ) -> SyscallResult {
     let wstatus: i32 = if exit_code < 0 {
         let signal_number = (-exit_code) as i32;
@@ -372,16 +414,19 @@ fn complete_wait(
         }
     }
 
-    // Remove child from parent's children list and reap from process table
+    // Remove child from parent's children list and reap from process table,
+    // charging its resource usage to the parent
+    let mut usage = crate::process::rusage::Rusage::default();
     if let Some(thread_id) = crate::task::scheduler::current_thread_id() {
         let mut manager_guard = crate::process::manager();
         if let Some(ref mut manager) = *manager_guard {
-            if let Some((_parent_pid, parent)) = manager.find_process_by_thread_mut(thread_id) {
+            if let Some((parent_pid, parent)) = manager.find_process_by_thread_mut(thread_id) {
                 parent.children.retain(|&id| id != child_pid);
                 log::debug!(
                     "complete_wait: Removed child {} from parent's children list",
                     child_pid.as_u64()
                 );
+                usage = crate::process::rusage::reap(manager, parent_pid, child_pid);
             }
             manager.remove_process(child_pid);
             log::debug!(
@@ -404,5 +449,11 @@ fn complete_wait(
         }
     });
 
+    if rusage_ptr != 0 {
+        if let Err(e) = super::rusage::write_rusage(rusage_ptr, &usage) {
+            return SyscallResult::Err(e);
+        }
+    }
+
     SyscallResult::Ok(child_pid.as_u64())
 }
@@ -0,0 +1,230 @@
+//! waitid syscall
+//!
+//! Waits for a child selected by pid, process group or any child, and reports
+//! it in a SIGCHLD `siginfo_t` instead of a wait status. WEXITED, WSTOPPED
+//! and WCONTINUED choose the changes of state to wait for; WNOWAIT reports
+//! one without consuming it, leaving an exited child unreaped.
+//!
+//! Unlike waitpid() this is shared by both architectures.
+
+use super::errno::{ECHILD, EFAULT, EINVAL};
+use super::userptr::copy_to_user;
+use super::SyscallResult;
+use crate::arch_impl::traits::CpuOps;
+use crate::process::job_control::{self, WaitReport};
+use crate::process::{rusage, ProcessId, ProcessManager, ProcessState};
+use crate::signal::constants::{CLD_CONTINUED, CLD_STOPPED, SIGCONT};
+use crate::signal::types::SigInfo;
+use crate::task::thread::ThreadState;
+
+#[cfg(target_arch = "aarch64")]
+type Cpu = crate::arch_impl::aarch64::Aarch64Cpu;
+
+#[cfg(target_arch = "x86_64")]
+type Cpu = crate::arch_impl::x86_64::cpu::X86Cpu;
+
+/// waitid() id types (Linux values)
+pub const P_ALL: u32 = 0;
+pub const P_PID: u32 = 1;
+pub const P_PGID: u32 = 2;
+
+/// waitid() options (Linux values)
+pub const WNOHANG: u32 = 1;
+pub const WSTOPPED: u32 = 2;
+pub const WEXITED: u32 = 4;
+pub const WCONTINUED: u32 = 8;
+pub const WNOWAIT: u32 = 0x0100_0000;
+
+/// Children a waitid() call waits for
+#[derive(Debug, Clone, Copy)]
+enum Selector {
+    Any,
+    Pid(ProcessId),
+    Pgid(ProcessId),
+}
+
+/// A change of state collected from a child
+struct Event {
+    info: SigInfo,
+    /// Usage of an exited child, for the rusage argument
+    usage: rusage::Rusage,
+}
+
+/// Find a child matching `selector` with a change of state `options` asks
+/// for, consuming it unless WNOWAIT is set
+///
+/// Returns ECHILD when no child matches at all.
+fn collect(
+    manager: &mut ProcessManager,
+    thread_id: u64,
+    selector: Selector,
+    options: u32,
+) -> Result<Option<Event>, u64> {
+    let Some((parent, process)) = manager.find_process_by_thread(thread_id) else {
+        return Err(ECHILD as u64);
+    };
+    let candidates: alloc::vec::Vec<ProcessId> = process
+        .children
+        .iter()
+        .copied()
+        .filter(|&child| match selector {
+            Selector::Any => true,
+            Selector::Pid(pid) => child == pid,
+            Selector::Pgid(pgid) => manager
+                .get_process(child)
+                .is_some_and(|child| child.pgid == pgid),
+        })
+        .collect();
+    if candidates.is_empty() {
+        return Err(ECHILD as u64);
+    }
+
+    let consume = options & WNOWAIT == 0;
+    for child_pid in candidates {
+        let Some(child) = manager.get_process_mut(child_pid) else {
+            continue;
+        };
+        let pid = child_pid.as_u64();
+        if let ProcessState::Terminated(exit_code) = child.state {
+            if options & WEXITED == 0 {
+                continue;
+            }
+            let info = SigInfo::child(pid, child.uid, exit_code);
+            let usage = if consume {
+                let usage = rusage::reap(manager, parent, child_pid);
+                if let Some(parent) = manager.get_process_mut(parent) {
+                    parent.children.retain(|&id| id != child_pid);
+                }
+                manager.remove_process(child_pid);
+                usage
+            } else {
+                rusage::reaped_usage(child)
+            };
+            return Ok(Some(Event { info, usage }));
+        }
+
+        let wanted = match job_control::child_report(child, false) {
+            Some(WaitReport::Stopped(_)) => options & WSTOPPED != 0,
+            Some(WaitReport::Continued) => options & WCONTINUED != 0,
+            None => false,
+        };
+        if !wanted {
+            continue;
+        }
+        let uid = child.uid;
+        let info = match job_control::child_report(child, consume) {
+            Some(WaitReport::Stopped(sig)) => SigInfo::child_job(pid, uid, CLD_STOPPED, sig),
+            _ => SigInfo::child_job(pid, uid, CLD_CONTINUED, SIGCONT),
+        };
+        return Ok(Some(Event {
+            info,
+            usage: rusage::Rusage::default(),
+        }));
+    }
+    Ok(None)
+}
+
+/// Copy the collected event, or an all-zero siginfo_t when there is none, to
+/// the caller
+fn finish(event: Option<Event>, infop: u64, rusage_ptr: u64) -> SyscallResult {
+    #[cfg(target_arch = "aarch64")]
+    super::futex::ensure_current_address_space();
+
+    let (info, usage) = match event {
+        Some(event) => (event.info, event.usage),
+        None => (SigInfo::new(0, 0), rusage::Rusage::default()),
+    };
+    if infop != 0 && copy_to_user(infop as *mut SigInfo, &info).is_err() {
+        return SyscallResult::Err(EFAULT as u64);
+    }
+    if rusage_ptr != 0 {
+        if let Err(e) = super::rusage::write_rusage(rusage_ptr, &usage) {
+            return SyscallResult::Err(e);
+        }
+    }
+    SyscallResult::Ok(0)
+}
+
+/// waitid(idtype, id, infop, options, rusage) - Wait for a child to change
+/// state
+pub fn sys_waitid(
+    idtype: u32,
+    id: u64,
+    infop: u64,
+    options: u32,
+    rusage_ptr: u64,
+) -> SyscallResult {
+    let known = WNOHANG | WSTOPPED | WEXITED | WCONTINUED | WNOWAIT;
+    if options & !known != 0 || options & (WSTOPPED | WEXITED | WCONTINUED) == 0 {
+        return SyscallResult::Err(EINVAL as u64);
+    }
+    let Some(thread_id) = crate::task::scheduler::current_thread_id() else {
+        return SyscallResult::Err(EINVAL as u64);
+    };
+    let selector = match idtype {
+        P_ALL => Selector::Any,
+        P_PID if id > 0 => Selector::Pid(ProcessId::new(id)),
+        // pgid 0 names the caller's own process group
+        P_PGID if id == 0 => {
+            let own = crate::process::with_process_manager(|manager| {
+                manager
+                    .find_process_by_thread(thread_id)
+                    .map(|(_, process)| process.pgid)
+            })
+            .flatten();
+            match own {
+                Some(pgid) => Selector::Pgid(pgid),
+                None => return SyscallResult::Err(ECHILD as u64),
+            }
+        }
+        P_PGID => Selector::Pgid(ProcessId::new(id)),
+        _ => return SyscallResult::Err(EINVAL as u64),
+    };
+
+    let scan = || {
+        crate::process::with_process_manager(|manager| {
+            collect(manager, thread_id, selector, options)
+        })
+        .unwrap_or(Err(ECHILD as u64))
+    };
+    match scan() {
+        Err(e) => return SyscallResult::Err(e),
+        Ok(Some(event)) => return finish(Some(event), infop, rusage_ptr),
+        Ok(None) if options & WNOHANG != 0 => return finish(None, infop, 0),
+        Ok(None) => {}
+    }
+
+    // Block before each re-check so a child changing state in between still
+    // finds this thread waiting, as in waitpid()
+    crate::per_cpu::preempt_enable();
+    let result = loop {
+        crate::task::scheduler::with_scheduler(|sched| {
+            sched.block_current_for_child_exit();
+        });
+        match scan() {
+            Err(e) => break Err(e),
+            Ok(Some(event)) => break Ok(event),
+            Ok(None) => {}
+        }
+        if let Some(e) = crate::syscall::check_signals_for_eintr() {
+            break Err(e as u64);
+        }
+        crate::task::scheduler::yield_current();
+        Cpu::halt_with_interrupts();
+    };
+    crate::per_cpu::preempt_disable();
+
+    crate::task::scheduler::with_scheduler(|sched| {
+        if let Some(thread) = sched.current_thread_mut() {
+            thread.blocked_in_syscall = false;
+            if thread.state == ThreadState::BlockedOnChildExit {
+                thread.set_ready();
+            }
+        }
+    });
+
+    match result {
+        Ok(event) => finish(Some(event), infop, rusage_ptr),
+        Err(e) => SyscallResult::Err(e),
+    }
+}
@@ -0,0 +1,152 @@
+//! Per-thread CPU time and resource usage accounting
+//!
+//! Each thread's running time is split into user and system time by the
+//! syscall entry and exit hooks: a thread's clock runs from dispatch until it
+//! blocks or is preempted, and time inside a syscall is system time. Time
+//! spent handling interrupts and faults is charged to the mode the thread was
+//! in. The counters are atomics because the hooks update the current thread
+//! through the per-CPU pointer, without the scheduler lock, while readers
+//! such as getrusage() hold it.
+
+use crate::time::monotonic_ns;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// CPU time and usage counters of one thread
+#[derive(Debug, Default)]
+pub struct ThreadUsage {
+    user_ns: AtomicU64,
+    system_ns: AtomicU64,
+    minor_faults: AtomicU64,
+    voluntary_switches: AtomicU64,
+    involuntary_switches: AtomicU64,
+    /// When the open interval started, or 0 while the clock is stopped
+    mark_ns: AtomicU64,
+    in_syscall: AtomicBool,
+}
+
+/// A snapshot of a thread's counters, or a sum of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsageSnapshot {
+    pub user_ns: u64,
+    pub system_ns: u64,
+    pub minor_faults: u64,
+    pub voluntary_switches: u64,
+    pub involuntary_switches: u64,
+}
+
+impl UsageSnapshot {
+    /// Add another snapshot's counters to this one
+    pub fn add(&mut self, other: &UsageSnapshot) {
+        self.user_ns += other.user_ns;
+        self.system_ns += other.system_ns;
+        self.minor_faults += other.minor_faults;
+        self.voluntary_switches += other.voluntary_switches;
+        self.involuntary_switches += other.involuntary_switches;
+    }
+}
+
+impl Clone for ThreadUsage {
+    fn clone(&self) -> Self {
+        let copy = |value: &AtomicU64| AtomicU64::new(value.load(Ordering::Relaxed));
+        ThreadUsage {
+            user_ns: copy(&self.user_ns),
+            system_ns: copy(&self.system_ns),
+            minor_faults: copy(&self.minor_faults),
+            voluntary_switches: copy(&self.voluntary_switches),
+            involuntary_switches: copy(&self.involuntary_switches),
+            mark_ns: copy(&self.mark_ns),
+            in_syscall: AtomicBool::new(self.in_syscall.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl ThreadUsage {
+    /// Charge the open interval to user or system time and start a new one
+    /// at `now` (0 stops the clock)
+    fn charge(&self, now: u64, next_mark: u64) {
+        let mark = self.mark_ns.swap(next_mark, Ordering::Relaxed);
+        if mark == 0 || now <= mark {
+            return;
+        }
+        let bucket = if self.in_syscall.load(Ordering::Relaxed) {
+            &self.system_ns
+        } else {
+            &self.user_ns
+        };
+        bucket.fetch_add(now - mark, Ordering::Relaxed);
+    }
+
+    /// Start the clock as the thread is dispatched
+    pub fn start_clock(&self) {
+        self.mark_ns.store(monotonic_ns(), Ordering::Relaxed);
+    }
+
+    /// Stop the clock as the thread blocks or is preempted
+    pub fn stop_clock(&self) {
+        self.charge(monotonic_ns(), 0);
+    }
+
+    /// Count a context switch away from the thread
+    pub fn count_switch(&self, voluntary: bool) {
+        let counter = if voluntary {
+            &self.voluntary_switches
+        } else {
+            &self.involuntary_switches
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Switch between user and system time
+    fn set_in_syscall(&self, in_syscall: bool) {
+        let now = monotonic_ns();
+        if self.mark_ns.load(Ordering::Relaxed) != 0 {
+            self.charge(now, now);
+        }
+        self.in_syscall.store(in_syscall, Ordering::Relaxed);
+    }
+
+    /// Current counters, including the open interval
+    pub fn snapshot(&self) -> UsageSnapshot {
+        let mut snapshot = UsageSnapshot {
+            user_ns: self.user_ns.load(Ordering::Relaxed),
+            system_ns: self.system_ns.load(Ordering::Relaxed),
+            minor_faults: self.minor_faults.load(Ordering::Relaxed),
+            voluntary_switches: self.voluntary_switches.load(Ordering::Relaxed),
+            involuntary_switches: self.involuntary_switches.load(Ordering::Relaxed),
+        };
+        let mark = self.mark_ns.load(Ordering::Relaxed);
+        let now = monotonic_ns();
+        if mark != 0 && now > mark {
+            if self.in_syscall.load(Ordering::Relaxed) {
+                snapshot.system_ns += now - mark;
+            } else {
+                snapshot.user_ns += now - mark;
+            }
+        }
+        snapshot
+    }
+}
+
+/// Syscall entry hook: the current thread's time is system time from here
+#[inline]
+pub fn syscall_entry() {
+    if let Some(thread) = crate::per_cpu::current_thread() {
+        thread.usage.set_in_syscall(true);
+    }
+}
+
+/// Syscall exit hook: the current thread's time is user time again
+#[inline]
+pub fn syscall_exit() {
+    if let Some(thread) = crate::per_cpu::current_thread() {
+        thread.usage.set_in_syscall(false);
+    }
+}
+
+/// Count a page fault the kernel resolved for the current thread
+#[inline]
+pub fn record_minor_fault() {
+    if let Some(thread) = crate::per_cpu::current_thread() {
+        thread.usage.minor_faults.fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -7,9 +7,11 @@ use core::{
 };
 
 // Core task/thread modules - shared across architectures
+pub mod accounting;
 pub mod completion;
 pub mod executor;
 pub mod exit_tally;
+pub mod priority;
 pub mod thread;
 pub mod waitqueue;
 
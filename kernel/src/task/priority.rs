@@ -0,0 +1,223 @@
+//! Scheduling policies, nice levels and real-time priorities
+//!
+//! Every thread has a policy:
+//! - `SCHED_OTHER` threads share the CPU by weighted fair scheduling. Each
+//!   one accumulates a virtual run time, the time it has run scaled by the
+//!   weight of its nice level (the Linux table: each nice step is worth about
+//!   10% of CPU). The ready thread that has run least in virtual time goes
+//!   next, so a thread at nice -5 gets about three times the CPU of one at
+//!   nice 0.
+//! - `SCHED_FIFO` and `SCHED_RR` threads have a real-time priority from 1 to
+//!   99 and always run before normal threads. A FIFO thread keeps the CPU
+//!   until it blocks or a higher-priority thread wakes; an RR thread also
+//!   gives the CPU to equal-priority peers when its quantum expires.
+//!
+//! The scheduler keeps each per-CPU ready queue in this order (see
+//! `Scheduler::order_ready_queue`), so the pick and work-stealing code still
+//! takes the front of a queue.
+//!
+//! Each thread also has a CPU affinity mask. It is only ever queued on, and
+//! only stolen by, a CPU in its mask.
+
+use crate::time::monotonic_ns;
+use core::cmp::Reverse;
+
+/// Scheduling policies (Linux values)
+pub const SCHED_OTHER: u32 = 0;
+pub const SCHED_FIFO: u32 = 1;
+pub const SCHED_RR: u32 = 2;
+
+/// Nice range
+pub const NICE_MIN: i32 = -20;
+pub const NICE_MAX: i32 = 19;
+
+/// Real-time priority range
+pub const RT_PRIORITY_MIN: u32 = 1;
+pub const RT_PRIORITY_MAX: u32 = 99;
+
+/// Affinity mask of a thread that may run on any CPU
+pub const ALL_CPUS: u64 = u64::MAX;
+
+/// Weight of a nice 0 thread
+const NICE_0_WEIGHT: u64 = 1024;
+
+/// Weight of each nice level from -20 to 19 (Linux `sched_prio_to_weight`)
+const NICE_TO_WEIGHT: [u32; 40] = [
+    88761, 71755, 56483, 46273, 36291, 29154, 23254, 18705, 14949, 11916, 9548, 7620, 6100, 4904,
+    3906, 3121, 2501, 1991, 1586, 1277, 1024, 820, 655, 526, 423, 335, 272, 215, 172, 137, 110, 87,
+    70, 56, 45, 36, 29, 23, 18, 15,
+];
+
+/// Virtual run time a waking thread may be ahead of the queue by, so a
+/// thread that slept does not then monopolise the CPU (one quantum)
+const SLEEPER_CREDIT_NS: u64 = 50_000_000;
+
+/// Virtual run time the running normal thread must be ahead of the next one
+/// by to keep the CPU when it is rescheduled
+const PREEMPT_GRANULARITY_NS: u64 = 50_000_000;
+
+/// A thread's scheduling policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Weighted fair share by nice level
+    Normal,
+    /// Real-time, runs until it blocks or is preempted
+    Fifo,
+    /// Real-time with a quantum among equal priorities
+    RoundRobin,
+}
+
+impl Policy {
+    /// Policy for a Linux policy number
+    pub fn from_raw(policy: u32) -> Option<Policy> {
+        match policy {
+            SCHED_OTHER => Some(Policy::Normal),
+            SCHED_FIFO => Some(Policy::Fifo),
+            SCHED_RR => Some(Policy::RoundRobin),
+            _ => None,
+        }
+    }
+
+    /// Linux policy number
+    pub fn raw(self) -> u32 {
+        match self {
+            Policy::Normal => SCHED_OTHER,
+            Policy::Fifo => SCHED_FIFO,
+            Policy::RoundRobin => SCHED_RR,
+        }
+    }
+
+    /// Whether this is a real-time policy
+    pub fn is_realtime(self) -> bool {
+        self != Policy::Normal
+    }
+}
+
+/// Position of a ready thread in dispatch order; lower runs first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rank {
+    /// Real-time threads, highest priority first
+    RealTime(Reverse<u8>),
+    /// Normal threads, least virtual run time first
+    Normal(u64),
+}
+
+/// Scheduling attributes and fair-share state of one thread
+#[derive(Debug, Clone, Copy)]
+pub struct SchedEntity {
+    pub policy: Policy,
+    /// Nice level, -20 to 19 (normal threads)
+    pub nice: i8,
+    /// Real-time priority, 1 to 99 (0 for normal threads)
+    pub rt_priority: u8,
+    /// Run time scaled by weight, in nanoseconds
+    pub vruntime: u64,
+    /// CPUs the thread may run on, one bit per CPU
+    pub cpus_allowed: u64,
+    /// When the current run started, or 0 while not running
+    run_start_ns: u64,
+}
+
+impl Default for SchedEntity {
+    fn default() -> Self {
+        SchedEntity {
+            policy: Policy::Normal,
+            nice: 0,
+            rt_priority: 0,
+            vruntime: 0,
+            cpus_allowed: ALL_CPUS,
+            run_start_ns: 0,
+        }
+    }
+}
+
+impl SchedEntity {
+    /// Attributes for a thread created by this one; fork and clone inherit
+    /// the policy, priorities, affinity and virtual run time
+    pub fn inherited(&self) -> Self {
+        SchedEntity {
+            run_start_ns: 0,
+            ..*self
+        }
+    }
+
+    /// Whether the thread has a real-time policy
+    #[inline]
+    pub fn is_realtime(&self) -> bool {
+        self.policy.is_realtime()
+    }
+
+    /// Whether the affinity mask lets the thread run on `cpu`
+    #[inline]
+    pub fn allows_cpu(&self, cpu: usize) -> bool {
+        cpu < 64 && self.cpus_allowed & (1 << cpu) != 0
+    }
+
+    /// Load weight of the thread's nice level
+    pub fn weight(&self) -> u64 {
+        let index = (self.nice as i32).clamp(NICE_MIN, NICE_MAX) - NICE_MIN;
+        NICE_TO_WEIGHT[index as usize] as u64
+    }
+
+    /// Set the policy, resetting the nice level or real-time priority the
+    /// new policy does not use
+    pub fn set_policy(&mut self, policy: Policy, rt_priority: u8) {
+        self.policy = policy;
+        self.rt_priority = if policy.is_realtime() { rt_priority } else { 0 };
+    }
+
+    /// Start the run clock when the thread is dispatched
+    pub fn start_run(&mut self) {
+        self.run_start_ns = monotonic_ns();
+    }
+
+    /// Charge the time since dispatch to the virtual run time
+    pub fn stop_run(&mut self) {
+        let start = core::mem::replace(&mut self.run_start_ns, 0);
+        if start == 0 {
+            return;
+        }
+        let ran = monotonic_ns().saturating_sub(start);
+        self.vruntime += ran * NICE_0_WEIGHT / self.weight();
+    }
+
+    /// Bring a thread that fell behind back to within the sleeper credit of
+    /// `min_vruntime`
+    pub fn place(&mut self, min_vruntime: u64) {
+        self.vruntime = self
+            .vruntime
+            .max(min_vruntime.saturating_sub(SLEEPER_CREDIT_NS));
+    }
+
+    /// Position in dispatch order
+    pub fn rank(&self) -> Rank {
+        if self.is_realtime() {
+            Rank::RealTime(Reverse(self.rt_priority))
+        } else {
+            Rank::Normal(self.vruntime)
+        }
+    }
+
+    /// Whether this running thread keeps its CPU over `next`, the front of
+    /// its ready queue (`None` when the queue is empty)
+    pub fn keeps_cpu_over(&self, next: Option<&SchedEntity>) -> bool {
+        match (self.policy, next) {
+            (Policy::Normal, None) => false,
+            (_, None) => true,
+            (Policy::Normal, Some(next)) => {
+                !next.is_realtime() && self.vruntime + PREEMPT_GRANULARITY_NS < next.vruntime
+            }
+            (Policy::Fifo, Some(next)) => {
+                !next.is_realtime() || next.rt_priority <= self.rt_priority
+            }
+            (Policy::RoundRobin, Some(next)) => {
+                !next.is_realtime() || next.rt_priority < self.rt_priority
+            }
+        }
+    }
+
+    /// Whether this thread, on waking, should preempt `running`
+    pub fn preempts(&self, running: &SchedEntity) -> bool {
+        self.is_realtime() && self.rank() < running.rank()
+    }
+}
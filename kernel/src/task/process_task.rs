@@ -570,8 +570,11 @@ pub(crate) fn enqueue_process_reclaim(reclaim: PendingProcessReclaim) {
 /// Process::take_fd_entries(). This avoids holding PM lock during pipe wakeups,
 /// PTY refcounting, TCP close, etc.
 ///
+/// `tgid` is the exiting process's thread group, whose POSIX record locks on
+/// each closed regular file are released.
+///
 /// CRITICAL: No PM lock is held when this runs.
-fn close_extracted_fds(entries: alloc::vec::Vec<(usize, FileDescriptor)>) {
+fn close_extracted_fds(entries: alloc::vec::Vec<(usize, FileDescriptor)>, tgid: u64) {
     use crate::ipc::FdKind;
 
     for (_fd, fd_entry) in entries {
@@ -614,7 +617,14 @@ fn close_extracted_fds(entries: alloc::vec::Vec<(usize, FileDescriptor)>) {
                 crate::ipc::fifo::close_fifo_write(&path);
                 buffer.lock().close_write();
             }
-            _ => {} // StdIo, RegularFile, Directory, Device, etc. — no action needed
+            FdKind::RegularFile(file) => {
+                let key = {
+                    let file = file.lock();
+                    (file.mount_id, file.inode_num)
+                };
+                crate::fs::lock::release_process_file(tgid, key);
+            }
+            _ => {} // StdIo, Directory, Device, etc. — no action needed
         }
     }
 }
@@ -642,10 +652,19 @@ impl ProcessScheduler {
         // Capture the claimer before taking PM. This is a separate scheduler-only
         // acquisition; no scheduler state is consulted while PM is live.
         let report_claimer = scheduler::current_thread_id().unwrap_or(thread_id);
+        // The thread's usage counters are frozen into its process for
+        // getrusage() and wait4(); read them before PM as well.
+        let thread_usage = scheduler::with_thread_mut(thread_id, |thread| thread.usage.snapshot())
+            .unwrap_or_default();
         // Phase 1: Under PM lock — minimal work only
         let phase1_result = {
             if let Some(ref mut manager) = *crate::process::manager() {
                 let designated_init = manager.designated_init();
+                if let Some((pid, process)) = manager.find_process_by_thread(thread_id) {
+                    if process.usage.exited.is_none() {
+                        crate::process::rusage::record_exit(manager, pid, thread_usage);
+                    }
+                }
                 if let Some((pid, process)) = manager.find_process_by_thread_mut(thread_id) {
                     let already_terminated = process.is_terminated();
                     crate::tracing::providers::teardown::record_exit_request(already_terminated);
@@ -662,6 +681,7 @@ impl ProcessScheduler {
 
                     // Extract FDs without closing them under the PM lock.
                     let fd_entries = process.take_fd_entries();
+                    let fd_owner_tgid = process.thread_group_id.unwrap_or(pid.as_u64());
                     let retirement_receipt: Option<crate::process::RetirementReceipt> =
                         if already_terminated {
                             // Preserve the single-CoW-decref invariant: external
@@ -704,13 +724,17 @@ impl ProcessScheduler {
                         process.exit_notifications.sigchld,
                         crate::process::process::ExitObligationState::Pending
                     );
+                    let sigchld_info = crate::signal::types::SigInfo::child(
+                        pid.as_u64(),
+                        process.uid,
+                        reported_exit_code,
+                    );
 
                     // Set SIGCHLD on parent and get parent thread ID for wakeup
                     let parent_tid = if let Some(parent_pid) = parent_pid {
                         if let Some(parent_process) = manager.get_process_mut(parent_pid) {
                             if sigchld_pending {
-                                use crate::signal::constants::SIGCHLD;
-                                parent_process.signals.set_pending(SIGCHLD);
+                                parent_process.signals.queue_signal(sigchld_info);
                             }
                             parent_process.main_thread.as_ref().map(|t| t.id)
                         } else {
@@ -726,11 +750,13 @@ impl ProcessScheduler {
                     }
 
                     manager.reparent_children_to_init(pid, &children);
+                    crate::process::ptrace::release_tracees(manager, pid);
 
                     Some((
                         pid,
                         process_name,
                         fd_entries,
+                        fd_owner_tgid,
                         parent_tid,
                         retirement_receipt,
                         report_claimed,
@@ -749,6 +775,7 @@ impl ProcessScheduler {
             pid,
             process_name,
             fd_entries,
+            fd_owner_tgid,
             parent_tid,
             retirement_receipt,
             report_claimed,
@@ -762,7 +789,7 @@ impl ProcessScheduler {
             }
 
             // Close FDs outside PM lock (pipe close_write wakes readers, etc.)
-            close_extracted_fds(fd_entries);
+            close_extracted_fds(fd_entries, fd_owner_tgid);
 
             // Clean up window buffers so the compositor stops reading freed pages
             #[cfg(target_arch = "aarch64")]
@@ -1,6 +1,8 @@
 //! Preemptive scheduler implementation
 //!
-//! This module implements a round-robin scheduler for kernel threads.
+//! This module implements a preemptive scheduler for kernel and user threads.
+//! Ready queues are kept in priority order: real-time threads first, then
+//! normal threads by weighted virtual run time (see `task::priority`).
 //!
 //! # Lock Ordering Discipline
 //!
@@ -48,6 +50,7 @@
 
 #[cfg(target_arch = "aarch64")]
 use super::thread::{CpuContext, VirtAddr};
+use super::priority::Policy;
 use super::thread::{Thread, ThreadState};
 #[cfg(feature = "boot_tests")]
 use super::thread::ThreadPrivilege;
@@ -1259,6 +1262,10 @@ pub struct Scheduler {
 
     /// Per-thread all-CPU grace targets for kernel-stack reclamation.
     retirement_grace: alloc::vec::Vec<RetirementGrace>,
+
+    /// Per-CPU floor of the virtual run time of runnable normal threads;
+    /// waking threads are placed relative to it (see `task::priority`)
+    min_vruntime: [u64; MAX_CPUS],
 }
 
 impl Scheduler {
@@ -1306,6 +1313,7 @@ impl Scheduler {
             cpu_state,
             timer_heap: BinaryHeap::new(),
             retirement_grace: alloc::vec::Vec::new(),
+            min_vruntime: [0; MAX_CPUS],
         };
 
         scheduler
@@ -1397,7 +1405,17 @@ impl Scheduler {
                 ) {
                     continue;
                 }
-                self.per_cpu_queues[current_cpu].push_back(thread_id);
+                // A stalled CPU keeps threads pinned to it; an offline one
+                // hands them to a CPU in their mask if one is online.
+                let target = if self.cpu_allowed(thread_id, current_cpu) {
+                    current_cpu
+                } else if offline {
+                    self.least_loaded_cpu(thread_id)
+                } else {
+                    self.per_cpu_queues[cpu].push_back(thread_id);
+                    continue;
+                };
+                self.per_cpu_queues[target].push_back(thread_id);
                 reclaimed += 1;
             }
 
@@ -1453,11 +1471,12 @@ impl Scheduler {
         let is_user = thread.privilege == super::thread::ThreadPrivilege::User;
         self.threads.push(thread);
         // Route to least-loaded CPU queue (or current CPU if tied).
-        let target = self.least_loaded_cpu();
+        let target = self.least_loaded_cpu(thread_id);
         if front {
             self.per_cpu_queues[target].push_front(thread_id);
         } else {
             self.per_cpu_queues[target].push_back(thread_id);
+            self.preempt_for_wakeup(thread_id, target);
         }
         // CRITICAL: Only log on x86_64. On ARM64, log_serial_println! uses the same
         // SERIAL1 lock as serial_println!, causing deadlock if timer fires while
@@ -1680,6 +1699,9 @@ impl Scheduler {
                             let now = crate::time::get_ticks();
                             current.cpu_ticks_total += now.wrapping_sub(current.run_start_ticks);
                             current.run_start_ticks = now;
+                            current.usage.stop_clock();
+                            current.sched.stop_run();
+                            current.usage.count_switch(false);
                             current.set_ready();
                             WAKE_SITE_SCHEDULE.fetch_add(1, Ordering::Relaxed);
                             record_ready_site(current_id, READY_SITE_SCHEDULE);
@@ -1688,6 +1710,9 @@ impl Scheduler {
                             // Reset run_start_ticks so the next dispatch doesn't
                             // charge stale time from the blocked period.
                             current.run_start_ticks = crate::time::get_ticks();
+                            if was_blocked {
+                                current.usage.count_switch(true);
+                            }
                             false
                         };
 
@@ -1704,8 +1729,16 @@ impl Scheduler {
                 let will_add = !is_terminated && !is_blocked && !in_queue;
 
                 if will_add {
-                    let cpu = Self::current_cpu_id();
-                    self.per_cpu_queues[cpu].push_back(current_id);
+                    let cpu = self.requeue_cpu(current_id, Self::current_cpu_id());
+                    // A preempted FIFO thread stays at the head of its priority.
+                    let fifo = self
+                        .get_thread(current_id)
+                        .is_some_and(|thread| thread.sched.policy == Policy::Fifo);
+                    if fifo {
+                        self.per_cpu_queues[cpu].push_front(current_id);
+                    } else {
+                        self.per_cpu_queues[cpu].push_back(current_id);
+                    }
                     if published_ready {
                         ENQUEUE_SAME_LOCK_OK.fetch_add(1, Ordering::Relaxed);
                     }
@@ -1720,6 +1753,7 @@ impl Scheduler {
 
         // Get next thread from ready queue (local first, then steal), skipping terminated.
         let current_cpu = Self::current_cpu_id();
+        self.order_ready_queue(current_cpu, None);
         let mut next_thread_id = 'outer: loop {
             // Try local queue first
             let local_candidates = self.per_cpu_queues[current_cpu].len();
@@ -1749,6 +1783,9 @@ impl Scheduler {
                     let Some(n) = self.per_cpu_queues[steal_cpu].pop_front() else {
                         break;
                     };
+                    if self.retain_affine_thread(steal_cpu, n, current_cpu) {
+                        continue;
+                    }
                     #[cfg(all(target_arch = "aarch64", feature = "boot_tests"))]
                     if retain_cpu_affine_test_thread(
                         &mut self.per_cpu_queues[steal_cpu],
@@ -1792,7 +1829,18 @@ impl Scheduler {
         // Important: Don't skip if it's the same thread when there are other threads waiting
         // This was causing the issue where yielding wouldn't switch to other ready threads
         let any_queued = self.per_cpu_queues.iter().any(|q| !q.is_empty());
-        if Some(next_thread_id) == self.cpu_state[current_cpu].current_thread && any_queued {
+        let is_current = Some(next_thread_id) == self.cpu_state[current_cpu].current_thread;
+        if is_current && any_queued && self.keeps_cpu(next_thread_id, current_cpu) {
+            // A real-time thread, or a normal thread far behind its peers in
+            // virtual run time, keeps the CPU.
+            if let Some(t) = self.get_thread_mut(next_thread_id) {
+                t.set_running();
+                t.usage.start_clock();
+                t.sched.start_run();
+            }
+            return None;
+        }
+        if is_current && any_queued {
             // Put current thread back in its CPU queue and get the next one
             self.per_cpu_queues[current_cpu].push_back(next_thread_id);
             // Pop from local queue first; fall back to any CPU
@@ -1808,6 +1856,9 @@ impl Scheduler {
                             continue;
                         }
                         if let Some(n) = self.per_cpu_queues[steal_cpu].pop_front() {
+                            if self.retain_affine_thread(steal_cpu, n, current_cpu) {
+                                continue;
+                            }
                             #[cfg(all(target_arch = "aarch64", feature = "boot_tests"))]
                             if retain_cpu_affine_test_thread(
                                 &mut self.per_cpu_queues[steal_cpu],
@@ -1868,6 +1919,9 @@ impl Scheduler {
                         // Restore Running state (was set to Ready above).
                         if let Some(t) = self.get_thread_mut(next_thread_id) {
                             t.set_running();
+                            // Keep charging its CPU time; the clock stopped above
+                            t.usage.start_clock();
+                            t.sched.start_run();
                         }
                         // Remove from per-CPU queue (was pushed above).
                         for q in self.per_cpu_queues.iter_mut() {
@@ -1938,6 +1992,8 @@ impl Scheduler {
         if let Some(next) = self.get_thread_mut(next_thread_id) {
             next.set_running();
             next.run_start_ticks = crate::time::get_ticks();
+            next.usage.start_clock();
+            next.sched.start_run();
         }
 
         // Get mutable reference to old thread and immutable to new
@@ -1995,7 +2051,8 @@ impl Scheduler {
         };
         thread.set_ready();
         let _ = self.cpu_state[cpu].pending_next.take();
-        self.per_cpu_queues[cpu].push_back(tid);
+        let target = self.requeue_cpu(tid, cpu);
+        self.per_cpu_queues[target].push_back(tid);
         crate::per_cpu_aarch64::set_need_resched(true);
         self.send_resched_ipi();
         #[cfg(feature = "boot_tests")]
@@ -2087,6 +2144,12 @@ impl Scheduler {
                     observe_exit_kick(owner_pid);
                 }
 
+                if is_blocked {
+                    if let Some(current) = self.get_thread(current_id) {
+                        current.usage.count_switch(true);
+                    }
+                }
+
                 let published_ready = !is_terminated && !is_blocked;
                 let in_queue = self.per_cpu_queues.iter().any(|q| q.contains(&current_id));
                 // Instead of adding to a queue, just record whether we SHOULD
@@ -2099,6 +2162,9 @@ impl Scheduler {
                         let now = crate::time::get_ticks();
                         current.cpu_ticks_total += now.wrapping_sub(current.run_start_ticks);
                         current.run_start_ticks = now;
+                        current.usage.stop_clock();
+                        current.sched.stop_run();
+                        current.usage.count_switch(false);
                         current.set_ready();
                     }
                     WAKE_SITE_SCHEDULE.fetch_add(1, Ordering::Relaxed);
@@ -2128,6 +2194,32 @@ impl Scheduler {
 
         // Get next thread: local queue first, then work-steal, then idle.
         let current_cpu = Self::current_cpu_id();
+        let runnable_current = self.cpu_state[current_cpu]
+            .current_thread
+            .filter(|_| should_requeue_old);
+        self.order_ready_queue(current_cpu, runnable_current);
+
+        // The runnable current thread is not queued here, so check whether it
+        // outranks the queue before picking: a real-time thread, or a normal
+        // thread far behind in virtual run time, keeps the CPU.
+        if let Some(current_id) = runnable_current {
+            if self.keeps_cpu(current_id, current_cpu) {
+                self.cpu_state[current_cpu].previous_thread = None;
+                if let Some(t) = self.get_thread_mut(current_id) {
+                    t.set_running();
+                    t.usage.start_clock();
+                    t.sched.start_run();
+                }
+                trace_sched_diag(
+                    TRACE_SCHED_DIAG_RETURN_NONE,
+                    current_id,
+                    current_id,
+                    current_id,
+                    self.ready_queue_length() as u32,
+                );
+                return None;
+            }
+        }
         let mut next_thread_id = 'sched_outer: loop {
             // Try local queue
             let local_candidates = self.per_cpu_queues[current_cpu].len();
@@ -2157,6 +2249,9 @@ impl Scheduler {
                     let Some(n) = self.per_cpu_queues[steal_cpu].pop_front() else {
                         break;
                     };
+                    if self.retain_affine_thread(steal_cpu, n, current_cpu) {
+                        continue;
+                    }
                     #[cfg(all(target_arch = "aarch64", feature = "boot_tests"))]
                     if retain_cpu_affine_test_thread(
                         &mut self.per_cpu_queues[steal_cpu],
@@ -2215,6 +2310,9 @@ impl Scheduler {
                             continue;
                         }
                         if let Some(n) = self.per_cpu_queues[steal_cpu].pop_front() {
+                            if self.retain_affine_thread(steal_cpu, n, current_cpu) {
+                                continue;
+                            }
                             #[cfg(all(target_arch = "aarch64", feature = "boot_tests"))]
                             if retain_cpu_affine_test_thread(
                                 &mut self.per_cpu_queues[steal_cpu],
@@ -2258,6 +2356,9 @@ impl Scheduler {
                     }
                     if let Some(t) = self.get_thread_mut(next_thread_id) {
                         t.set_running();
+                        // Keep charging its CPU time; the clock stopped above
+                        t.usage.start_clock();
+                        t.sched.start_run();
                     }
                     trace_sched_diag(
                         TRACE_SCHED_DIAG_RETURN_NONE,
@@ -2310,6 +2411,8 @@ impl Scheduler {
         if let Some(next) = self.get_thread_mut(next_thread_id) {
             next.set_running();
             next.run_start_ticks = crate::time::get_ticks();
+            next.usage.start_clock();
+            next.sched.start_run();
         }
         self.cpu_state[current_cpu].pending_next = Some(next_thread_id);
 
@@ -2409,7 +2512,7 @@ impl Scheduler {
                     thread.set_ready();
                 }
             }
-            let cpu = Self::current_cpu_id();
+            let cpu = self.requeue_cpu(thread_id, Self::current_cpu_id());
             self.per_cpu_queues[cpu].push_back(thread_id);
             ENQUEUE_DEFERRED_DRAINED_OK.fetch_add(1, Ordering::Relaxed);
             // Send IPI to wake an idle CPU to pick up the requeued thread
@@ -2427,6 +2530,8 @@ impl Scheduler {
             let now = crate::time::get_ticks();
             current.cpu_ticks_total += now.wrapping_sub(current.run_start_ticks);
             current.run_start_ticks = now;
+            current.usage.stop_clock();
+            current.sched.stop_run();
 
             current.set_blocked();
         }
@@ -2493,6 +2598,7 @@ impl Scheduler {
                 {
                     let target = self.find_target_cpu_for_wakeup(thread_id);
                     self.per_cpu_queues[target].push_back(thread_id);
+                    self.preempt_for_wakeup(thread_id, target);
                     ENQUEUE_SAME_LOCK_OK.fetch_add(1, Ordering::Relaxed);
                     // CRITICAL: Only log on x86_64 to avoid deadlock on ARM64
                     #[cfg(target_arch = "x86_64")]
@@ -2672,6 +2778,8 @@ impl Scheduler {
                 let now = crate::time::get_ticks();
                 thread.cpu_ticks_total += now.wrapping_sub(thread.run_start_ticks);
                 thread.run_start_ticks = now;
+                thread.usage.stop_clock();
+                thread.sched.stop_run();
 
                 // CRITICAL: Save userspace context FIRST, THEN set state.
                 // This ensures that when unblock_for_signal() is called,
@@ -2764,6 +2872,7 @@ impl Scheduler {
                 {
                     let target = self.find_target_cpu_for_wakeup(thread_id);
                     self.per_cpu_queues[target].push_back(thread_id);
+                    self.preempt_for_wakeup(thread_id, target);
                     ENQUEUE_SAME_LOCK_OK.fetch_add(1, Ordering::Relaxed);
                     #[cfg(target_arch = "x86_64")]
                     log_serial_println!(
@@ -2818,6 +2927,8 @@ impl Scheduler {
                 let now = crate::time::get_ticks();
                 thread.cpu_ticks_total += now.wrapping_sub(thread.run_start_ticks);
                 thread.run_start_ticks = now;
+                thread.usage.stop_clock();
+                thread.sched.stop_run();
 
                 thread.state = ThreadState::BlockedOnChildExit;
                 // CRITICAL: Mark that this thread is blocked inside a syscall.
@@ -2873,6 +2984,7 @@ impl Scheduler {
                 {
                     let target = self.find_target_cpu_for_wakeup(thread_id);
                     self.per_cpu_queues[target].push_back(thread_id);
+                    self.preempt_for_wakeup(thread_id, target);
                     ENQUEUE_SAME_LOCK_OK.fetch_add(1, Ordering::Relaxed);
                     // CRITICAL: Only log on x86_64 to avoid deadlock on ARM64
                     #[cfg(target_arch = "x86_64")]
@@ -2906,6 +3018,8 @@ impl Scheduler {
                 let now = crate::time::get_ticks();
                 thread.cpu_ticks_total += now.wrapping_sub(thread.run_start_ticks);
                 thread.run_start_ticks = now;
+                thread.usage.stop_clock();
+                thread.sched.stop_run();
 
                 thread.state = ThreadState::BlockedOnTimer;
                 thread.wake_time_ns = Some(wake_time_ns);
@@ -2928,6 +3042,8 @@ impl Scheduler {
                 let now = crate::time::get_ticks();
                 thread.cpu_ticks_total += now.wrapping_sub(thread.run_start_ticks);
                 thread.run_start_ticks = now;
+                thread.usage.stop_clock();
+                thread.sched.stop_run();
 
                 thread.state = ThreadState::BlockedOnIO;
                 thread.wake_time_ns = wake_time_ns;
@@ -3112,6 +3228,7 @@ impl Scheduler {
                 {
                     let target = self.find_target_cpu_for_wakeup(tid);
                     self.per_cpu_queues[target].push_back(tid);
+                    self.preempt_for_wakeup(tid, target);
                     wake.enqueued_target = Some(target);
                     if from_isr_buffer {
                         ENQUEUE_ISR_BUFFER_DRAINED_OK.fetch_add(1, Ordering::Relaxed);
@@ -3144,6 +3261,8 @@ impl Scheduler {
                 let now = crate::time::get_ticks();
                 thread.cpu_ticks_total += now.wrapping_sub(thread.run_start_ticks);
                 thread.run_start_ticks = now;
+                thread.usage.stop_clock();
+                thread.sched.stop_run();
 
                 thread.state = ThreadState::BlockedOnTimer;
                 thread.wake_time_ns = Some(timeout_ns);
@@ -3272,6 +3391,7 @@ impl Scheduler {
                             | self.ready_queue_length() as u32,
                     );
                     self.per_cpu_queues[target].push_back(tid);
+                    self.preempt_for_wakeup(tid, target);
                     ENQUEUE_SAME_LOCK_OK.fetch_add(1, Ordering::Relaxed);
                 } else if in_deferred_requeue {
                     trace_sched_diag(
@@ -3384,6 +3504,7 @@ impl Scheduler {
                     .min_by_key(|&cpu| self.per_cpu_queues[cpu].len())
                 {
                     self.per_cpu_queues[target].push_back(thread_id);
+                    self.preempt_for_wakeup(thread_id, target);
                 }
             }
         }
@@ -3401,33 +3522,188 @@ impl Scheduler {
         self.per_cpu_queues.iter().map(|q| q.len()).sum()
     }
 
+    /// Put `cpu`'s ready queue in dispatch order: real-time threads by
+    /// priority, then normal threads by virtual run time.
+    ///
+    /// The sort is stable, so threads of equal rank keep their FIFO order.
+    /// Normal threads that fell behind the CPU's `min_vruntime` while asleep
+    /// are placed back near it first. `running` is a runnable thread that is
+    /// not queued but still counts towards the floor.
+    ///
+    /// SCHEDULER is held, so the queue is insertion-sorted in place rather
+    /// than through a scratch buffer; it is nearly in order already.
+    fn order_ready_queue(&mut self, cpu: usize, running: Option<u64>) {
+        let min_vruntime = self.min_vruntime[cpu];
+        let mut floor = running
+            .and_then(|id| self.get_thread(id))
+            .filter(|thread| !thread.sched.is_realtime())
+            .map_or(u64::MAX, |thread| thread.sched.vruntime);
+        for index in 0..self.per_cpu_queues[cpu].len() {
+            let id = self.per_cpu_queues[cpu][index];
+            if let Some(thread) = self.get_thread_mut(id) {
+                if !thread.sched.is_realtime() {
+                    thread.sched.place(min_vruntime);
+                    floor = floor.min(thread.sched.vruntime);
+                }
+            }
+        }
+        if floor != u64::MAX {
+            self.min_vruntime[cpu] = min_vruntime.max(floor);
+        }
+
+        let threads = &self.threads;
+        let rank_of = |id: u64| {
+            threads
+                .iter()
+                .find(|t| t.id() == id)
+                .map_or(super::priority::Rank::Normal(u64::MAX), |t| t.sched.rank())
+        };
+        let queue = self.per_cpu_queues[cpu].make_contiguous();
+        for index in 1..queue.len() {
+            let rank = rank_of(queue[index]);
+            let mut slot = index;
+            while slot > 0 && rank_of(queue[slot - 1]) > rank {
+                queue.swap(slot - 1, slot);
+                slot -= 1;
+            }
+        }
+    }
+
+    /// Whether running thread `thread_id` keeps `cpu` over the front of its
+    /// ordered ready queue when it is rescheduled.
+    fn keeps_cpu(&self, thread_id: u64, cpu: usize) -> bool {
+        let Some(thread) = self.get_thread(thread_id) else {
+            return false;
+        };
+        if !thread.sched.allows_cpu(cpu) {
+            return false;
+        }
+        let next = self.per_cpu_queues[cpu]
+            .front()
+            .and_then(|&id| self.get_thread(id))
+            .map(|next| &next.sched);
+        thread.sched.keeps_cpu_over(next)
+    }
+
+    /// Ask `cpu` to reschedule if woken thread `thread_id` outranks the
+    /// thread running there, so real-time threads preempt normal ones.
+    fn preempt_for_wakeup(&self, thread_id: u64, cpu: usize) {
+        let Some(woken) = self.get_thread(thread_id) else {
+            return;
+        };
+        let running = self.cpu_state[cpu]
+            .current_thread
+            .filter(|&id| id != self.cpu_state[cpu].idle_thread)
+            .and_then(|id| self.get_thread(id));
+        if !running.is_some_and(|running| woken.sched.preempts(&running.sched)) {
+            return;
+        }
+        if cpu == Self::current_cpu_id() {
+            set_need_resched();
+        } else {
+            #[cfg(target_arch = "aarch64")]
+            self.send_resched_ipi_to_cpu(cpu);
+        }
+    }
+
+    /// Set the scheduling policy, nice level or affinity of thread
+    /// `thread_id`.
+    ///
+    /// Takes effect at the next scheduling decision: the running thread is
+    /// rescheduled, a thread running on a CPU its affinity no longer allows
+    /// is sent away, a queued thread moves to a CPU its affinity allows, and
+    /// a queued thread that now outranks the thread on its CPU preempts it.
+    pub fn set_thread_sched(
+        &mut self,
+        thread_id: u64,
+        update: impl FnOnce(&mut super::priority::SchedEntity),
+    ) -> bool {
+        let Some(thread) = self.get_thread_mut(thread_id) else {
+            return false;
+        };
+        update(&mut thread.sched);
+        let current_cpu = Self::current_cpu_id();
+        let running_on =
+            (0..MAX_CPUS).find(|&cpu| self.cpu_state[cpu].current_thread == Some(thread_id));
+        let queued_on = self
+            .per_cpu_queues
+            .iter()
+            .position(|queue| queue.contains(&thread_id));
+        if running_on == Some(current_cpu) {
+            set_need_resched();
+        } else if let Some(cpu) = queued_on {
+            let target = self.requeue_cpu(thread_id, cpu);
+            if target != cpu {
+                self.per_cpu_queues[cpu].retain(|&id| id != thread_id);
+                self.per_cpu_queues[target].push_back(thread_id);
+            }
+            self.preempt_for_wakeup(thread_id, target);
+        } else if let Some(cpu) = running_on {
+            if !self.cpu_allowed(thread_id, cpu) {
+                #[cfg(target_arch = "aarch64")]
+                self.send_resched_ipi_to_cpu(cpu);
+            }
+        }
+        true
+    }
+
     /// Find which CPU this thread last ran on, or the least-loaded CPU if unknown.
     /// Used by wakeup paths for cache-affinity routing.
     fn find_target_cpu_for_wakeup(&self, tid: u64) -> usize {
-        let current_cpu = Self::current_cpu_id();
         // If the thread is still "current" on a CPU, use that CPU (affinity).
         for cpu in 0..MAX_CPUS {
-            if self.cpu_state[cpu].current_thread == Some(tid) {
+            if self.cpu_state[cpu].current_thread == Some(tid) && self.cpu_allowed(tid, cpu) {
                 return cpu;
             }
         }
         // Otherwise pick the least-loaded CPU.
-        (0..self.online_cpu_count())
-            .filter(|&cpu| self.cpu_accepts_wakeups(cpu))
-            .min_by_key(|&cpu| self.per_cpu_queues[cpu].len())
-            .unwrap_or(current_cpu)
+        self.least_loaded_cpu(tid)
     }
 
-    /// Find the CPU with the fewest threads in its queue.
-    /// Used when spawning new threads.
-    fn least_loaded_cpu(&self) -> usize {
+    /// Find the CPU in `tid`'s affinity mask with the fewest threads in its
+    /// queue. Used when spawning new threads.
+    ///
+    /// Falls back to an allowed CPU that is not accepting wakeups, and only
+    /// then to the current CPU, so affinity is broken only when every CPU in
+    /// the mask is offline.
+    fn least_loaded_cpu(&self, tid: u64) -> usize {
         let current_cpu = Self::current_cpu_id();
-        (0..self.online_cpu_count())
-            .filter(|&cpu| self.cpu_accepts_wakeups(cpu))
+        let online_cpus = self.online_cpu_count();
+        (0..online_cpus)
+            .filter(|&cpu| self.cpu_accepts_wakeups(cpu) && self.cpu_allowed(tid, cpu))
             .min_by_key(|&cpu| self.per_cpu_queues[cpu].len())
+            .or_else(|| (0..online_cpus).find(|&cpu| self.cpu_allowed(tid, cpu)))
             .unwrap_or(current_cpu)
     }
 
+    /// Whether thread `tid`'s affinity mask lets it run on `cpu`. Threads the
+    /// scheduler does not know may run anywhere.
+    #[inline]
+    fn cpu_allowed(&self, tid: u64, cpu: usize) -> bool {
+        self.get_thread(tid)
+            .is_none_or(|thread| thread.sched.allows_cpu(cpu))
+    }
+
+    /// The queue for thread `tid` when it stops running on `cpu`: `cpu`'s own
+    /// queue unless the thread's affinity has moved it away.
+    fn requeue_cpu(&self, tid: u64, cpu: usize) -> usize {
+        if self.cpu_allowed(tid, cpu) {
+            cpu
+        } else {
+            self.least_loaded_cpu(tid)
+        }
+    }
+
+    /// Put `tid`, popped from `queue_cpu`'s queue by a steal from `cpu`, back
+    /// on that queue if its affinity does not allow `cpu`.
+    fn retain_affine_thread(&mut self, queue_cpu: usize, tid: u64, cpu: usize) -> bool {
+        if self.cpu_allowed(tid, cpu) {
+            return false;
+        }
+        self.per_cpu_queues[queue_cpu].push_back(tid);
+        true
+    }
+
     /// Get a thread by ID (public for timer.rs)
     pub fn get_thread(&self, id: u64) -> Option<&Thread> {
         self.threads
@@ -3689,7 +3965,8 @@ impl Scheduler {
             (0..MAX_CPUS).any(|c| c != cpu && self.cpu_state[c].previous_thread == Some(previous));
 
         if is_ready && !is_idle && !is_queued && !is_current && !is_other_deferred {
-            self.per_cpu_queues[cpu].push_back(previous);
+            let target = self.requeue_cpu(previous, cpu);
+            self.per_cpu_queues[target].push_back(previous);
             ENQUEUE_DEFERRED_DRAINED_OK.fetch_add(1, Ordering::Relaxed);
             set_need_resched();
         }
@@ -4320,6 +4597,70 @@ pub fn get_process_cpu_ticks() -> alloc::vec::Vec<(u64, u64)> {
     })
 }
 
+/// CPUs that can schedule, one bit per CPU
+pub fn online_cpu_mask() -> u64 {
+    with_scheduler(|sched| (1u64 << sched.online_cpu_count()) - 1).unwrap_or(1)
+}
+
+/// CPU the caller is running on
+pub fn current_cpu() -> usize {
+    Scheduler::current_cpu_id()
+}
+
+/// Scheduling attributes of every thread owned by one of `pids`
+pub fn threads_sched(pids: &[u64]) -> alloc::vec::Vec<super::priority::SchedEntity> {
+    with_scheduler(|sched| {
+        sched
+            .threads
+            .iter()
+            .filter(|thread| thread.owner_pid.is_some_and(|pid| pids.contains(&pid)))
+            .map(|thread| thread.sched)
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Apply `update` to every thread owned by one of `pids` (see
+/// `Scheduler::set_thread_sched`); returns how many threads matched
+pub fn update_threads_sched(
+    pids: &[u64],
+    update: impl Fn(&mut super::priority::SchedEntity),
+) -> usize {
+    with_scheduler(|sched| {
+        let ids: alloc::vec::Vec<u64> = sched
+            .threads
+            .iter()
+            .filter(|thread| thread.owner_pid.is_some_and(|pid| pids.contains(&pid)))
+            .map(|thread| thread.id())
+            .collect();
+        for &id in &ids {
+            sched.set_thread_sched(id, &update);
+        }
+        ids.len()
+    })
+    .unwrap_or(0)
+}
+
+/// Sum the usage counters of the threads owned by any of `pids`
+///
+/// Used by getrusage() and at process exit. Unlike the btop collectors this
+/// waits for the scheduler lock, so it must not be called with the process
+/// manager lock held.
+pub fn threads_usage(pids: &[u64]) -> super::accounting::UsageSnapshot {
+    with_scheduler(|sched| {
+        let mut total = super::accounting::UsageSnapshot::default();
+        for thread in sched
+            .threads
+            .iter()
+            .filter(|thread| thread.owner_pid.is_some_and(|pid| pids.contains(&pid)))
+        {
+            total.add(&thread.usage.snapshot());
+        }
+        total
+    })
+    .unwrap_or_default()
+}
+
 /// Get a process display state from its scheduler-owned threads.
 ///
 /// The process manager's coarse state can remain Ready while all of the
@@ -4363,6 +4704,23 @@ pub fn get_process_display_state(owner_pid: u64) -> Option<&'static str> {
     })
 }
 
+/// CPU currently running one of `owner_pid`'s threads, if any.
+///
+/// Like `get_process_display_state`, this never waits for the scheduler lock
+/// and reports None while it is contended.
+pub fn get_process_running_cpu(owner_pid: u64) -> Option<usize> {
+    without_interrupts(|| {
+        let scheduler_lock = try_lock_scheduler()?;
+        let scheduler = scheduler_lock.as_ref()?;
+        (0..MAX_CPUS).find(|&cpu| {
+            scheduler.cpu_state[cpu]
+                .current_thread
+                .and_then(|tid| scheduler.get_thread(tid))
+                .is_some_and(|thread| thread.owner_pid == Some(owner_pid))
+        })
+    })
+}
+
 /// Get the current thread ID
 /// This function disables interrupts to prevent deadlock with timer interrupt
 pub fn current_thread_id() -> Option<u64> {
@@ -495,6 +495,12 @@ pub struct Thread {
     /// Updated in schedule() when the thread is switched out.
     pub cpu_ticks_total: u64,
 
+    /// User/system time, page faults and context switches, for getrusage()
+    pub usage: super::accounting::ThreadUsage,
+
+    /// Scheduling policy, nice level, CPU affinity and fair-share state
+    pub sched: super::priority::SchedEntity,
+
     /// Owner process PID (for mapping thread CPU time to process in btop).
     /// None for idle threads and kernel-internal threads not associated with a process.
     pub owner_pid: Option<u64>,
@@ -534,6 +540,8 @@ impl Clone for Thread {
             wake_time_ns: self.wake_time_ns,
             run_start_ticks: self.run_start_ticks,
             cpu_ticks_total: self.cpu_ticks_total,
+            usage: self.usage.clone(),
+            sched: self.sched,
             owner_pid: self.owner_pid,
             cached_ttbr0: self.cached_ttbr0,
         }
@@ -636,6 +644,8 @@ impl Thread {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: None,
             cached_ttbr0: 0,
         })
@@ -699,6 +709,8 @@ impl Thread {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: None,
             cached_ttbr0: 0,
         })
@@ -749,6 +761,8 @@ impl Thread {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: None,
             cached_ttbr0: 0,
         }
@@ -798,6 +812,8 @@ impl Thread {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: None,
             cached_ttbr0: 0,
         }
@@ -860,6 +876,8 @@ impl Thread {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: None,
             cached_ttbr0: 0,
         }
@@ -917,6 +935,8 @@ impl Thread {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: None,
             cached_ttbr0: 0,
         }
@@ -999,6 +1019,8 @@ impl Thread {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: None,
             cached_ttbr0: 0,
         }
@@ -1044,6 +1066,8 @@ impl Thread {
             wake_time_ns: None,
             run_start_ticks: 0,
             cpu_ticks_total: 0,
+            usage: Default::default(),
+            sched: Default::default(),
             owner_pid: None,
             cached_ttbr0: 0,
         }
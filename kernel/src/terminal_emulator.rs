@@ -44,8 +44,10 @@ impl TerminalEmulator {
         // Unlock the PTY for use
         pty.unlock();
 
-        // Create terminal pane
-        let pane = TerminalPane::new(x, y, width, height);
+        // Create terminal pane. The PTY already turns NL into CR-NL, so LF is
+        // a plain line feed here, as programs in raw mode expect
+        let mut pane = TerminalPane::new(x, y, width, height);
+        pane.terminal_mut().set_newline_mode(false);
 
         Ok(Self {
             pty,
@@ -96,6 +98,12 @@ impl TerminalEmulator {
 
                     // Show cursor after writing
                     self.pane.draw_cursor(canvas, self.cursor_visible);
+
+                    // Answer queries such as cursor position reports
+                    let responses = self.pane.terminal_mut().take_responses();
+                    if !responses.is_empty() {
+                        let _ = self.pty.master_write(&responses);
+                    }
                 }
                 _ => break,
             }
@@ -1820,6 +1820,474 @@ pub fn test_sigaltstack() {
     }
 }
 
+/// Test SA_SIGINFO siginfo_t and ucontext_t delivery
+///
+/// TWO-STAGE VALIDATION PATTERN:
+/// - Stage 1 (Checkpoint): Process creation
+///   - Marker: "Siginfo test: process scheduled for execution"
+///   - This is a CHECKPOINT confirming process creation succeeded
+/// - Stage 2 (Boot stage): Validates siginfo contents
+///   - Marker: "SIGINFO_TEST_PASSED"
+///   - This PROVES handlers see si_pid/si_uid, si_status and si_addr, and ucontext edits apply
+pub fn test_siginfo() {
+    log::info!("Testing SA_SIGINFO siginfo and ucontext delivery");
+
+    #[cfg(feature = "testing")]
+    let siginfo_test_elf_buf = crate::userspace_test::get_test_binary("siginfo_test");
+    #[cfg(feature = "testing")]
+    let siginfo_test_elf: &[u8] = &siginfo_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let siginfo_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("siginfo_test"),
+        siginfo_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created siginfo_test process with PID {:?}", pid);
+            log::info!("Siginfo test: process scheduled for execution.");
+            log::info!("    -> Userspace will emit SIGINFO_TEST marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_SIGINFO,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create siginfo_test process: {}", e);
+            log::error!("Siginfo test cannot run without valid userspace process");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_SIGINFO,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test queued real-time signals, sigtimedwait() and thread-directed kill
+///
+/// TWO-STAGE VALIDATION PATTERN:
+/// - Stage 1 (Checkpoint): Process creation
+///   - Marker: "RT signal test: process scheduled for execution"
+///   - This is a CHECKPOINT confirming process creation succeeded
+/// - Stage 2 (Boot stage): Validates queueing and thread-directed delivery
+///   - Marker: "RT_SIGNAL_TEST_PASSED"
+///   - This PROVES real-time signals queue with their values and pthread_kill reaches one thread
+pub fn test_rt_signal() {
+    log::info!("Testing queued real-time signals");
+
+    #[cfg(feature = "testing")]
+    let rt_signal_test_elf_buf = crate::userspace_test::get_test_binary("rt_signal_test");
+    #[cfg(feature = "testing")]
+    let rt_signal_test_elf: &[u8] = &rt_signal_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let rt_signal_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("rt_signal_test"),
+        rt_signal_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created rt_signal_test process with PID {:?}", pid);
+            log::info!("RT signal test: process scheduled for execution.");
+            log::info!("    -> Userspace will emit RT_SIGNAL_TEST marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_RT_SIGNAL,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create rt_signal_test process: {}", e);
+            log::error!("RT signal test cannot run without valid userspace process");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_RT_SIGNAL,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test ELF core dumps for core-dumping signals
+///
+/// TWO-STAGE VALIDATION PATTERN:
+/// - Stage 1 (Checkpoint): Process creation
+///   - Marker: "Coredump test: process scheduled for execution"
+///   - This is a CHECKPOINT confirming process creation succeeded
+/// - Stage 2 (Boot stage): Validates core files and RLIMIT_CORE
+///   - Marker: "COREDUMP_TEST_PASSED"
+///   - This PROVES a SIGSEGV fault writes a readable ELF core file and RLIMIT_CORE gates it
+pub fn test_coredump() {
+    log::info!("Testing core dumps");
+
+    #[cfg(feature = "testing")]
+    let coredump_test_elf_buf = crate::userspace_test::get_test_binary("coredump_test");
+    #[cfg(feature = "testing")]
+    let coredump_test_elf: &[u8] = &coredump_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let coredump_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("coredump_test"),
+        coredump_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created coredump_test process with PID {:?}", pid);
+            log::info!("Coredump test: process scheduled for execution.");
+            log::info!("    -> Userspace will emit COREDUMP_TEST marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_COREDUMP,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create coredump_test process: {}", e);
+            log::error!("Coredump test cannot run without valid userspace process");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_COREDUMP,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test ptrace: stops, peek/poke, registers, single-step and syscall stops
+///
+/// TWO-STAGE VALIDATION PATTERN:
+/// - Stage 1 (Checkpoint): Process creation
+///   - Marker: "Ptrace test: process scheduled for execution"
+///   - This is a CHECKPOINT confirming process creation succeeded
+/// - Stage 2 (Boot stage): Validates tracing a child and an attached process
+///   - Marker: "PTRACE_TEST_PASSED"
+///   - This PROVES a tracer can stop, inspect, step and resume a tracee
+pub fn test_ptrace() {
+    log::info!("Testing ptrace");
+
+    #[cfg(feature = "testing")]
+    let ptrace_test_elf_buf = crate::userspace_test::get_test_binary("ptrace_test");
+    #[cfg(feature = "testing")]
+    let ptrace_test_elf: &[u8] = &ptrace_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let ptrace_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("ptrace_test"),
+        ptrace_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created ptrace_test process with PID {:?}", pid);
+            log::info!("Ptrace test: process scheduled for execution.");
+            log::info!("    -> Userspace will emit PTRACE_TEST marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_PTRACE,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create ptrace_test process: {}", e);
+            log::error!("Ptrace test cannot run without valid userspace process");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_PTRACE,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test getrusage, wait4 rusage, times and waitid
+///
+/// TWO-STAGE VALIDATION PATTERN:
+/// - Stage 1 (Checkpoint): Process creation
+///   - Marker: "Rusage test: process scheduled for execution"
+///   - This is a CHECKPOINT confirming process creation succeeded
+/// - Stage 2 (Boot stage): Validates accounting and waitid selection
+///   - Marker: "RUSAGE_TEST_PASSED"
+///   - This PROVES CPU time, faults and switches are accounted and waitid
+///     reports children by pid, process group and any
+pub fn test_rusage() {
+    log::info!("Testing resource usage and waitid");
+
+    #[cfg(feature = "testing")]
+    let rusage_test_elf_buf = crate::userspace_test::get_test_binary("rusage_test");
+    #[cfg(feature = "testing")]
+    let rusage_test_elf: &[u8] = &rusage_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let rusage_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("rusage_test"),
+        rusage_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created rusage_test process with PID {:?}", pid);
+            log::info!("Rusage test: process scheduled for execution.");
+            log::info!("    -> Userspace will emit RUSAGE_TEST marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_RUSAGE,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create rusage_test process: {}", e);
+            log::error!("Rusage test cannot run without valid userspace process");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_RUSAGE,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test nice levels, scheduling policies and their effect on CPU share
+///
+/// TWO-STAGE VALIDATION PATTERN:
+/// - Stage 1 (Checkpoint): Process creation
+///   - Marker: "Priority test: process scheduled for execution"
+///   - This is a CHECKPOINT confirming process creation succeeded
+/// - Stage 2 (Boot stage): Validates priorities and the scheduler's use of them
+///   - Marker: "PRIORITY_TEST_PASSED"
+///   - This PROVES nice levels and SCHED_FIFO/SCHED_RR are validated,
+///     inherited and honoured by the scheduler
+pub fn test_priority() {
+    log::info!("Testing scheduling priorities");
+
+    #[cfg(feature = "testing")]
+    let priority_test_elf_buf = crate::userspace_test::get_test_binary("priority_test");
+    #[cfg(feature = "testing")]
+    let priority_test_elf: &[u8] = &priority_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let priority_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("priority_test"),
+        priority_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created priority_test process with PID {:?}", pid);
+            log::info!("Priority test: process scheduled for execution.");
+            log::info!("    -> Userspace will emit PRIORITY_TEST marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_PRIORITY,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create priority_test process: {}", e);
+            log::error!("Priority test cannot run without valid userspace process");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_PRIORITY,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test CPU affinity masks and their effect on thread placement
+///
+/// TWO-STAGE VALIDATION PATTERN:
+/// - Stage 1 (Checkpoint): Process creation
+///   - Marker: "Affinity test: process scheduled for execution"
+///   - This is a CHECKPOINT confirming process creation succeeded
+/// - Stage 2 (Boot stage): Validates affinity masks and pinned placement
+///   - Marker: "AFFINITY_TEST_PASSED"
+///   - This PROVES sched_setaffinity pins a thread to its CPUs, the mask is
+///     inherited by fork and shown in /proc/<pid>/status
+pub fn test_affinity() {
+    log::info!("Testing CPU affinity");
+
+    #[cfg(feature = "testing")]
+    let affinity_test_elf_buf = crate::userspace_test::get_test_binary("affinity_test");
+    #[cfg(feature = "testing")]
+    let affinity_test_elf: &[u8] = &affinity_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let affinity_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("affinity_test"),
+        affinity_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created affinity_test process with PID {:?}", pid);
+            log::info!("Affinity test: process scheduled for execution.");
+            log::info!("    -> Userspace will emit AFFINITY_TEST marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_AFFINITY,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create affinity_test process: {}", e);
+            log::error!("Affinity test cannot run without valid userspace process");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_AFFINITY,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test the /proc/<pid> hierarchy and /proc/self
+///
+/// TWO-STAGE VALIDATION PATTERN:
+/// - Stage 1 (Checkpoint): Process creation
+///   - Marker: "Proc pid test: process scheduled for execution"
+///   - This is a CHECKPOINT confirming process creation succeeded
+/// - Stage 2 (Boot stage): Validates the per-process procfs entries
+///   - Marker: "PROC_PID_TEST_PASSED"
+///   - This PROVES /proc/self resolves to the caller, and that maps, fd/,
+///     cmdline, environ, stat, statm, exe, cwd and task/ describe the process
+pub fn test_proc_pid() {
+    log::info!("Testing /proc/<pid> hierarchy");
+
+    #[cfg(feature = "testing")]
+    let proc_pid_test_elf_buf = crate::userspace_test::get_test_binary("proc_pid_test");
+    #[cfg(feature = "testing")]
+    let proc_pid_test_elf: &[u8] = &proc_pid_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let proc_pid_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("proc_pid_test"),
+        proc_pid_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created proc_pid_test process with PID {:?}", pid);
+            log::info!("Proc pid test: process scheduled for execution.");
+            log::info!("    -> Userspace will emit PROC_PID_TEST marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_PROC_PID,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create proc_pid_test process: {}", e);
+            log::error!("Proc pid test cannot run without valid userspace process");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_PROC_PID,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test the /dev/dsp audio mixer
+///
+/// TWO-STAGE VALIDATION PATTERN:
+/// - Stage 1 (Checkpoint): Process creation
+///   - Marker: "Dsp mixer test: process scheduled for execution"
+///   - This is a CHECKPOINT confirming process creation succeeded
+/// - Stage 2 (Boot stage): Validates per-client audio streams
+///   - Marker: "DSP_MIXER_TEST_PASSED"
+///   - This PROVES each open of /dev/dsp negotiates its own rate, format,
+///     channels and volume, and that writes, EAGAIN, POLLOUT and
+///     SNDCTL_DSP_SYNC follow the mixer's progress
+pub fn test_dsp_mixer() {
+    log::info!("Testing /dev/dsp audio mixer");
+
+    #[cfg(feature = "testing")]
+    let dsp_mixer_test_elf_buf = crate::userspace_test::get_test_binary("dsp_mixer_test");
+    #[cfg(feature = "testing")]
+    let dsp_mixer_test_elf: &[u8] = &dsp_mixer_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let dsp_mixer_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("dsp_mixer_test"),
+        dsp_mixer_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created dsp_mixer_test process with PID {:?}", pid);
+            log::info!("Dsp mixer test: process scheduled for execution.");
+            log::info!("    -> Userspace will emit DSP_MIXER_TEST marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_DSP_MIXER,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create dsp_mixer_test process: {}", e);
+            log::error!("Dsp mixer test cannot run without valid userspace process");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_DSP_MIXER,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test /dev/dsp audio capture
+///
+/// TWO-STAGE VALIDATION PATTERN:
+/// - Stage 1 (Checkpoint): Process creation
+///   - Marker: "Dsp capture test: process scheduled for execution"
+///   - This is a CHECKPOINT confirming process creation succeeded
+/// - Stage 2 (Boot stage): Validates recording streams
+///   - Marker: "DSP_CAPTURE_TEST_PASSED"
+///   - This PROVES streams opened for reading receive captured audio in
+///     their own format, and that GETISPACE, POLLIN, RESET and nonblocking
+///     reads follow the capture thread's progress
+pub fn test_dsp_capture() {
+    log::info!("Testing /dev/dsp audio capture");
+
+    #[cfg(feature = "testing")]
+    let dsp_capture_test_elf_buf = crate::userspace_test::get_test_binary("dsp_capture_test");
+    #[cfg(feature = "testing")]
+    let dsp_capture_test_elf: &[u8] = &dsp_capture_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let dsp_capture_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("dsp_capture_test"),
+        dsp_capture_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created dsp_capture_test process with PID {:?}", pid);
+            log::info!("Dsp capture test: process scheduled for execution.");
+            log::info!("    -> Userspace will emit DSP_CAPTURE_TEST marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_DSP_CAPTURE,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create dsp_capture_test process: {}", e);
+            log::error!("Dsp capture test cannot run without valid userspace process");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_DSP_CAPTURE,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
 /// Test dup() syscall functionality
 ///
 /// TWO-STAGE VALIDATION PATTERN:
@@ -2769,6 +3237,166 @@ pub fn test_fs_block_alloc() {
     }
 }
 
+/// Test file size and durability syscalls
+/// This tests:
+/// - ftruncate/truncate shrink (including indirect blocks) and grow with zero fill
+/// - fallocate with and without FALLOC_FL_KEEP_SIZE
+/// - fsync/fdatasync on a regular file
+pub fn test_fs_truncate() {
+    log::info!("Testing ftruncate/truncate, fallocate, and fsync");
+
+    #[cfg(feature = "testing")]
+    let fs_truncate_test_elf_buf = crate::userspace_test::get_test_binary("fs_truncate_test");
+    #[cfg(feature = "testing")]
+    let fs_truncate_test_elf: &[u8] = &fs_truncate_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let fs_truncate_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("fs_truncate_test"),
+        fs_truncate_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created fs_truncate_test process with PID {:?}", pid);
+            log::info!("    -> Userspace will emit FS_TRUNCATE_TEST_PASSED marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_FS_TRUNCATE,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create fs_truncate_test process: {}", e);
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_FS_TRUNCATE,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test filesystem statistics syscalls
+/// This tests:
+/// - statfs on ext2 reports capacity, and free counts drop after a write
+/// - procfs, devfs, and devpts report their type magic
+/// - fstatfs on files and pipes, ENOENT for missing paths
+pub fn test_fs_statfs() {
+    log::info!("Testing statfs/fstatfs");
+
+    #[cfg(feature = "testing")]
+    let fs_statfs_test_elf_buf = crate::userspace_test::get_test_binary("fs_statfs_test");
+    #[cfg(feature = "testing")]
+    let fs_statfs_test_elf: &[u8] = &fs_statfs_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let fs_statfs_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("fs_statfs_test"),
+        fs_statfs_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created fs_statfs_test process with PID {:?}", pid);
+            log::info!("    -> Userspace will emit FS_STATFS_TEST_PASSED marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_FS_STATFS,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create fs_statfs_test process: {}", e);
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_FS_STATFS,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test advisory file locking
+/// This tests:
+/// - flock conflicts, LOCK_NB, and blocked waiters woken by LOCK_UN
+/// - fcntl F_SETLK/F_GETLK byte ranges and POSIX release on close
+/// - F_SETLKW deadlock detection (EDEADLK) and OFD locks
+pub fn test_flock() {
+    log::info!("Testing flock and fcntl record locks");
+
+    #[cfg(feature = "testing")]
+    let flock_test_elf_buf = crate::userspace_test::get_test_binary("flock_test");
+    #[cfg(feature = "testing")]
+    let flock_test_elf: &[u8] = &flock_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let flock_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("flock_test"),
+        flock_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created flock_test process with PID {:?}", pid);
+            log::info!("    -> Userspace will emit FLOCK_TEST_PASSED marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_FLOCK,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create flock_test process: {}", e);
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_FLOCK,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
+/// Test in-kernel data transfer
+/// This tests:
+/// - sendfile from a file into a pipe, with and without an offset pointer
+/// - splice file -> pipe -> file and tee between two pipes
+/// - ESPIPE, EINVAL, and EAGAIN error cases
+pub fn test_splice() {
+    log::info!("Testing sendfile, splice, and tee");
+
+    #[cfg(feature = "testing")]
+    let splice_test_elf_buf = crate::userspace_test::get_test_binary("splice_test");
+    #[cfg(feature = "testing")]
+    let splice_test_elf: &[u8] = &splice_test_elf_buf;
+    #[cfg(not(feature = "testing"))]
+    let splice_test_elf = &create_hello_world_elf();
+
+    match crate::process::creation::create_user_process(
+        String::from("splice_test"),
+        splice_test_elf,
+    ) {
+        Ok(pid) => {
+            log::info!("Created splice_test process with PID {:?}", pid);
+            log::info!("    -> Userspace will emit SPLICE_TEST_PASSED marker if successful");
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::register_pid(
+                pid.as_u64(),
+                crate::test_framework::catalog::UTEST_SPLICE,
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create splice_test process: {}", e);
+            #[cfg(feature = "btrt")]
+            crate::test_framework::btrt::fail(
+                crate::test_framework::catalog::UTEST_SPLICE,
+                crate::test_framework::btrt::BtrtErrorCode::NoExec,
+                0,
+            );
+        }
+    }
+}
+
 /// Test Rust std library support via hello_std_real
 pub fn test_hello_std_real() {
     log::info!("Testing Rust std library support (hello_std_real)");
@@ -3841,10 +3841,13 @@ fn test_process_list_populated() -> TestResult {
 /// only verifies the infrastructure is in place and accessible.
 fn test_signal_delivery_infrastructure() -> TestResult {
     use crate::signal::constants::{
-        is_catchable, is_valid_signal, sig_mask, signal_name, NSIG, SIGCHLD, SIGCONT, SIGINT,
-        SIGKILL, SIGSTOP, SIGTERM, SIG_DFL, SIG_IGN, UNCATCHABLE_SIGNALS,
+        is_catchable, is_valid_signal, sig_mask, signal_name, CLD_EXITED, CLD_KILLED, NSIG,
+        SIGCHLD, SIGCONT, SIGINT, SIGKILL, SIGQUEUE_MAX, SIGRTMIN, SIGSTOP, SIGTERM, SIGUSR1,
+        SIG_DFL, SIG_IGN, SI_KERNEL, SI_QUEUE, SI_USER, UNCATCHABLE_SIGNALS,
+    };
+    use crate::signal::types::{
+        default_action, SigInfo, SignalAction, SignalDefaultAction, SignalState,
     };
-    use crate::signal::types::{default_action, SignalAction, SignalDefaultAction, SignalState};
 
     // Test 1: Verify signal constants are properly defined
     // These are fundamental signals that must exist per POSIX
@@ -3998,7 +4001,57 @@ fn test_signal_delivery_infrastructure() -> TestResult {
         return TestResult::Fail("set/get handler mismatch");
     }
 
-    // Test 11: Verify process manager is accessible (used by signal delivery)
+    // Test 11: Verify siginfo records travel with pending signals
+    if core::mem::size_of::<SigInfo>() != 128 {
+        return TestResult::Fail("SigInfo should match the 128-byte siginfo_t");
+    }
+    state.queue_signal(SigInfo::user(SIGUSR1, 42, 1000));
+    state.queue_signal(SigInfo::user(SIGUSR1, 43, 0));
+    let info = state.dequeue_signal(SIGUSR1);
+    if info.si_code != SI_USER || info.pid() != 42 || info.uid() != 1000 {
+        return TestResult::Fail("dequeue_signal should return the first kill() siginfo");
+    }
+    if state.is_pending(SIGUSR1) {
+        return TestResult::Fail("dequeue_signal should clear the pending bit");
+    }
+    state.set_pending(SIGUSR1);
+    if state.dequeue_signal(SIGUSR1).si_code != SI_KERNEL {
+        return TestResult::Fail("signal without a record should report SI_KERNEL");
+    }
+    let exited = SigInfo::child(7, 0, 3);
+    let killed = SigInfo::child(7, 0, -(SIGKILL as i32));
+    if exited.si_code != CLD_EXITED || exited.status() != 3 || exited.pid() != 7 {
+        return TestResult::Fail("SIGCHLD siginfo should report CLD_EXITED and the exit status");
+    }
+    if killed.si_code != CLD_KILLED || killed.status() != SIGKILL as i32 {
+        return TestResult::Fail("SIGCHLD siginfo should report CLD_KILLED and the signal");
+    }
+
+    // Test 12: Verify real-time signals queue every instance in order
+    for value in 0..3u64 {
+        state.queue_signal(SigInfo::queue(SIGRTMIN, 42, 0, value));
+    }
+    for value in 0..3u64 {
+        let info = state.dequeue_signal(SIGRTMIN);
+        if info.si_code != SI_QUEUE || info.value() != value {
+            return TestResult::Fail("real-time signals should dequeue in arrival order");
+        }
+        if state.is_pending(SIGRTMIN) != (value < 2) {
+            return TestResult::Fail("real-time signal should stay pending while instances remain");
+        }
+    }
+    for value in 0..SIGQUEUE_MAX as u64 {
+        state.queue_signal(SigInfo::queue(SIGRTMIN + 1, 42, 0, value));
+    }
+    if state.queue_signal(SigInfo::queue(SIGRTMIN + 1, 42, 0, 0)) {
+        return TestResult::Fail("real-time queue should refuse signals beyond SIGQUEUE_MAX");
+    }
+    state.clear_pending(SIGRTMIN + 1);
+    if state.dequeue_signal_in(sig_mask(SIGRTMIN + 1)).is_some() {
+        return TestResult::Fail("clear_pending should drop every queued instance");
+    }
+
+    // Test 13: Verify process manager is accessible (used by signal delivery)
     // This doesn't require a full process - just that the infrastructure exists
     let manager_available = crate::process::try_manager().is_some();
     // Note: manager may or may not be available depending on boot stage,
@@ -4693,8 +4746,12 @@ fn test_filesystem_syscalls_aarch64() -> TestResult {
         Ok(_) => {
             return TestResult::Fail("resolve_path succeeded for non-existent file");
         }
-        Err(_) => {
-            // Expected - file should not exist
+        Err(crate::fs::ext2::Ext2Error::NotFound) => {
+            // Expected - file should not exist, and must map to ENOENT
+        }
+        Err(e) => {
+            log::error!("resolve_path({}) returned {:?}", NONEXISTENT_PATH, e);
+            return TestResult::Fail("resolve_path for missing file did not return NotFound");
         }
     }
 
@@ -5572,6 +5629,44 @@ fn test_pipe_broken() -> TestResult {
     TestResult::Pass
 }
 
+/// Test copying between pipe buffers across the ring wrap point.
+///
+/// Verifies that copy_to() moves wrapped contents into another pipe in order
+/// and leaves the source untouched, as tee() and splice() rely on.
+fn test_pipe_copy_to() -> TestResult {
+    use crate::ipc::pipe::{PipeBuffer, PIPE_BUF_SIZE};
+
+    let mut src = PipeBuffer::new();
+    let mut dst = PipeBuffer::new();
+
+    // Advance the ring so the next write wraps around the end
+    let filler = alloc::vec![0u8; PIPE_BUF_SIZE - 4];
+    if src.write(&filler) != Ok(filler.len()) {
+        return TestResult::Fail("filler write failed");
+    }
+    src.consume(filler.len());
+    if src.write(b"0123456789") != Ok(10) {
+        return TestResult::Fail("wrapping write failed");
+    }
+
+    match src.copy_to(&mut dst, 16) {
+        Ok(10) => {}
+        Ok(_) => return TestResult::Fail("copy_to returned wrong count"),
+        Err(_) => return TestResult::Fail("copy_to failed"),
+    }
+    if src.available() != 10 {
+        return TestResult::Fail("copy_to should not consume the source");
+    }
+
+    let mut buf = [0u8; 16];
+    match dst.read(&mut buf) {
+        Ok(10) if &buf[..10] == b"0123456789" => {}
+        _ => return TestResult::Fail("copied data mismatch"),
+    }
+
+    TestResult::Pass
+}
+
 /// Test file descriptor table creation and allocation.
 ///
 /// Creates a new FdTable and verifies stdin/stdout/stderr are pre-allocated.
@@ -6252,6 +6347,7 @@ static NETWORK_TESTS: &[TestDef] = &[
 /// - pipe_buffer_basic: Basic pipe read/write operations
 /// - pipe_eof: EOF semantics when write end is closed
 /// - pipe_broken: Broken pipe detection when read end is closed
+/// - pipe_copy_to: Pipe-to-pipe copy across the ring wrap point
 /// - pipe_wake_mechanism: Verify pipe wake mechanism works on all architectures
 /// - fd_table_creation: File descriptor table initialization (stdin/stdout/stderr)
 /// - fd_alloc_close: File descriptor allocation and closing
@@ -6280,6 +6376,13 @@ static IPC_TESTS: &[TestDef] = &[
         timeout_ms: 2000,
         stage: TestStage::EarlyBoot,
     },
+    TestDef {
+        name: "pipe_copy_to",
+        func: test_pipe_copy_to,
+        arch: Arch::Any,
+        timeout_ms: 2000,
+        stage: TestStage::EarlyBoot,
+    },
     TestDef {
         name: "pipe_wake_mechanism",
         func: test_pipe_wake_mechanism,
@@ -36,6 +36,13 @@ pub fn init() {
     timer::init();
 }
 
+/// Monotonic time since boot in nanoseconds
+#[inline]
+pub(crate) fn monotonic_ns() -> u64 {
+    let (secs, nanos) = get_monotonic_time_ns();
+    secs.saturating_mul(1_000_000_000).saturating_add(nanos)
+}
+
 /// Get the current real (wall clock) time
 /// This is calculated as boot_wall_time + monotonic_time_since_boot
 pub fn get_real_time() -> DateTime {
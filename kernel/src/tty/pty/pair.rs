@@ -389,6 +389,20 @@ impl PtyPair {
         crate::task::scheduler::set_need_resched();
     }
 
+    /// Session this terminal controls and its foreground process group,
+    /// for job-control checks on slave reads and writes
+    pub fn job_control(&self) -> (Option<u64>, Option<u64>) {
+        (
+            self.controlling_pid.lock().map(u64::from),
+            self.foreground_pgid.lock().map(u64::from),
+        )
+    }
+
+    /// Whether background writes to the slave raise SIGTTOU (TOSTOP)
+    pub fn stops_background_writes(&self) -> bool {
+        self.termios.lock().c_lflag & crate::tty::termios::TOSTOP != 0
+    }
+
     /// Read data from master (slave's output)
     ///
     /// Returns Ok(n) if data was read, Err(EAGAIN) if no data available but
@@ -0,0 +1,141 @@
+//! Clipboard and drag-and-drop between Breengel windows.
+//!
+//! Both go through kernel-held selections next to the window registry. A
+//! window offers data in one or more MIME types; the kernel keeps a copy, so
+//! the clipboard outlives the window that set it. A reader asks for the
+//! first of the types it accepts that the selection is offered in.
+//!
+//! Drags start with [`Window::start_drag`](crate::Window::start_drag) while
+//! the mouse button is held. BWM then sends `DragEnter`/`DragMotion`/
+//! `DragLeave` to the window under the pointer, and `Drop` to the one it is
+//! released over, which fetches the data with
+//! [`Window::request_drop`](crate::Window::request_drop).
+
+use libbreenix::errno::Errno;
+use libbreenix::error::Error;
+use libbreenix::graphics::{self, SelectionOffer};
+
+/// Common MIME types.
+pub mod mime {
+    /// UTF-8 text
+    pub const TEXT: &str = "text/plain;charset=utf-8";
+    /// A list of `file://` URIs, one per line (RFC 2483)
+    pub const URI_LIST: &str = "text/uri-list";
+}
+
+/// Data read from the clipboard or a drop, in the MIME type it was read as.
+#[derive(Clone, Debug)]
+pub struct ClipboardData {
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+impl ClipboardData {
+    /// The data as text, if it is UTF-8 text.
+    pub fn text(&self) -> Option<&str> {
+        if self.mime.starts_with("text/") {
+            core::str::from_utf8(&self.data).ok()
+        } else {
+            None
+        }
+    }
+
+    /// The local file paths of a `text/uri-list`. Non-file URIs and comment
+    /// lines are skipped.
+    pub fn file_paths(&self) -> Vec<String> {
+        if self.mime != mime::URI_LIST {
+            return Vec::new();
+        }
+        let text = String::from_utf8_lossy(&self.data);
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.strip_prefix("file://"))
+            // Only local files: an empty host or localhost
+            .map(|rest| rest.strip_prefix("localhost").unwrap_or(rest))
+            .filter(|path| path.starts_with('/'))
+            .map(percent_decode)
+            .collect()
+    }
+}
+
+/// Build a `text/uri-list` naming the files at `paths`.
+pub fn file_uri_list(paths: &[&str]) -> String {
+    let mut list = String::new();
+    for path in paths {
+        list.push_str("file://");
+        for &b in path.as_bytes() {
+            if b.is_ascii_alphanumeric() || b"/-._~".contains(&b) {
+                list.push(b as char);
+            } else {
+                list.push_str(&format!("%{b:02X}"));
+            }
+        }
+        list.push_str("\r\n");
+    }
+    list
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| core::str::from_utf8(h).ok());
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(b) if bytes[i] == b'%' => {
+                out.push(b);
+                i += 3;
+            }
+            _ => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Offer `offers` (MIME type, data) on `selection` from window `window_id`.
+pub(crate) fn set(selection: u32, window_id: u32, offers: &[(&str, &[u8])]) -> Result<(), Error> {
+    if offers.len() > graphics::MAX_SELECTION_OFFERS {
+        return Err(Error::Os(Errno::EINVAL));
+    }
+    let mut raw = [SelectionOffer { mime: &[], data: &[] }; graphics::MAX_SELECTION_OFFERS];
+    for (slot, &(mime, data)) in raw.iter_mut().zip(offers) {
+        *slot = SelectionOffer { mime: mime.as_bytes(), data };
+    }
+    graphics::set_selection(selection, window_id, &raw[..offers.len()])
+}
+
+/// The MIME types `selection` is offered in.
+pub(crate) fn types(selection: u32) -> Vec<String> {
+    let mut buf = vec![0u8; 256];
+    loop {
+        let len = match graphics::list_selection(selection, &mut buf) {
+            Ok(len) => len,
+            Err(_) => return Vec::new(),
+        };
+        if len <= buf.len() {
+            let list = String::from_utf8_lossy(&buf[..len]);
+            return list.split('\n').filter(|t| !t.is_empty()).map(String::from).collect();
+        }
+        buf.resize(len, 0);
+    }
+}
+
+/// Read `selection` in the first type of `accept` it is offered in.
+pub(crate) fn request(selection: u32, accept: &[&str]) -> Option<ClipboardData> {
+    let offered = types(selection);
+    let mime = accept.iter().find(|&&m| offered.iter().any(|t| t == m))?;
+    let mut data = vec![0u8; 4096];
+    loop {
+        let len = graphics::read_selection(selection, mime.as_bytes(), &mut data).ok()?;
+        if len <= data.len() {
+            data.truncate(len);
+            return Some(ClipboardData { mime: String::from(*mime), data });
+        }
+        // Grew since the type list was read, or larger than the first guess
+        data.resize(len, 0);
+    }
+}
@@ -46,6 +46,16 @@ pub enum Event {
     /// The window was resized by the window manager. The buffer has already
     /// been reallocated — update application layout state as needed.
     Resized { width: u32, height: u32 },
+    /// A drag entered the window at window-local coordinates. Use
+    /// `win.drag_types()` to see what it carries.
+    DragEnter { x: i32, y: i32 },
+    /// A drag moved within the window.
+    DragMotion { x: i32, y: i32 },
+    /// A drag left the window without being dropped.
+    DragLeave,
+    /// A drag was dropped on the window. Take the data with
+    /// `win.request_drop(..)`.
+    Drop { x: i32, y: i32 },
     /// The system font configuration changed. The new font is already loaded
     /// internally — call `win.take_mono_font()` to get it. Recalculate text
     /// metrics, grid dimensions, etc. as needed.
@@ -88,6 +98,19 @@ impl Event {
                 width: raw.keycode as u32,
                 height: raw.mouse_x as u16 as u32,
             },
+            input_event_type::DRAG_ENTER => Event::DragEnter {
+                x: raw.mouse_x as i32,
+                y: raw.mouse_y as i32,
+            },
+            input_event_type::DRAG_MOTION => Event::DragMotion {
+                x: raw.mouse_x as i32,
+                y: raw.mouse_y as i32,
+            },
+            input_event_type::DRAG_LEAVE => Event::DragLeave,
+            input_event_type::DROP => Event::Drop {
+                x: raw.mouse_x as i32,
+                y: raw.mouse_y as i32,
+            },
             _ => Event::KeyPress {
                 ascii: 0,
                 keycode: raw.keycode,
@@ -5,15 +5,22 @@
 //!
 //! Font management is handled automatically by [`Window`](crate::Window).
 //! Apps receive [`Event::FontChanged`](crate::Event::FontChanged) when the
-//! system font config changes, and access fonts via `win.take_mono_font()`.
+//! system font config changes, and access fonts via `win.take_mono_font()`,
+//! or `win.take_mono_chain()` for the mono font with its fallbacks.
 
 use libfont::{Font, CachedFont};
+use libgfx::text::FontChain;
 
 const CONFIG_PATH: &str = "/etc/fonts.conf";
 const DEFAULT_MONO_FONT: &str = "/usr/share/fonts/DejaVuSansMono.ttf";
 const DEFAULT_MONO_SIZE: f32 = 10.0;
 const DEFAULT_DISPLAY_FONT: &str = "/usr/share/fonts/DejaVuSans.ttf";
 const DEFAULT_DISPLAY_SIZE: f32 = 14.0;
+/// Fonts with the scripts the default mono and display fonts lack.
+const DEFAULT_FALLBACK_FONTS: &[&str] = &[
+    "/usr/share/fonts/DejaVuSans.ttf",
+    "/usr/share/fonts/NotoSans-Regular.ttf",
+];
 
 /// Parsed system font configuration.
 pub struct FontConfig {
@@ -21,6 +28,9 @@ pub struct FontConfig {
     pub mono_size: f32,
     pub display_path: String,
     pub display_size: f32,
+    /// Fonts tried in order for characters the configured font lacks
+    /// (`fallback.fonts`, comma-separated).
+    pub fallback_paths: Vec<String>,
 }
 
 impl FontConfig {
@@ -31,6 +41,7 @@ impl FontConfig {
             mono_size: DEFAULT_MONO_SIZE,
             display_path: String::from(DEFAULT_DISPLAY_FONT),
             display_size: DEFAULT_DISPLAY_SIZE,
+            fallback_paths: DEFAULT_FALLBACK_FONTS.iter().map(|&p| String::from(p)).collect(),
         };
 
         if let Ok(contents) = std::fs::read_to_string(CONFIG_PATH) {
@@ -59,6 +70,13 @@ impl FontConfig {
                                 }
                             }
                         }
+                        "fallback.fonts" => {
+                            config.fallback_paths = value.split(',')
+                                .map(str::trim)
+                                .filter(|p| !p.is_empty())
+                                .map(String::from)
+                                .collect();
+                        }
                         _ => {}
                     }
                 }
@@ -80,6 +98,11 @@ impl FontConfig {
     pub fn load_display(&self) -> Option<Vec<u8>> {
         std::fs::read(&self.display_path).ok()
     }
+
+    /// Load the bytes of each fallback font that can be read.
+    pub fn load_fallbacks(&self) -> Vec<Vec<u8>> {
+        self.fallback_paths.iter().filter_map(|p| std::fs::read(p).ok()).collect()
+    }
 }
 
 /// Internal font watcher — polls `/etc/fonts.conf` for changes and
@@ -89,8 +112,10 @@ pub(crate) struct FontWatcher {
     mono_size: f32,
     display_path: String,
     display_size: f32,
+    fallback_paths: Vec<String>,
     mono_font: Option<CachedFont>,
     display_font: Option<CachedFont>,
+    fallback_fonts: Vec<CachedFont>,
     poll_counter: u32,
     poll_interval: u32,
 }
@@ -101,18 +126,25 @@ fn load_cached_font(path: &str) -> Option<CachedFont> {
     Some(CachedFont::new(font, 256))
 }
 
+fn load_cached_fonts(paths: &[String]) -> Vec<CachedFont> {
+    paths.iter().filter_map(|p| load_cached_font(p)).collect()
+}
+
 impl FontWatcher {
     pub(crate) fn new() -> Self {
         let config = FontConfig::load();
         let mono_font = load_cached_font(&config.mono_path);
         let display_font = load_cached_font(&config.display_path);
+        let fallback_fonts = load_cached_fonts(&config.fallback_paths);
         Self {
             mono_path: config.mono_path,
             mono_size: config.mono_size,
             display_path: config.display_path,
             display_size: config.display_size,
+            fallback_paths: config.fallback_paths,
             mono_font,
             display_font,
+            fallback_fonts,
             poll_counter: 0,
             poll_interval: 20,
         }
@@ -124,8 +156,10 @@ impl FontWatcher {
             mono_size: DEFAULT_MONO_SIZE,
             display_path: String::from(DEFAULT_DISPLAY_FONT),
             display_size: DEFAULT_DISPLAY_SIZE,
+            fallback_paths: Vec::new(),
             mono_font: None,
             display_font: None,
+            fallback_fonts: Vec::new(),
             poll_counter: 0,
             poll_interval: 0,
         }
@@ -165,6 +199,28 @@ impl FontWatcher {
         self.mono_font = font;
     }
 
+    /// Take the mono font and the fallback fonts out of the watcher as one
+    /// chain. None if there is no mono font.
+    pub(crate) fn take_mono_chain(&mut self) -> Option<FontChain> {
+        let mut chain = FontChain::new(self.mono_font.take()?);
+        for font in self.fallback_fonts.drain(..) {
+            chain.push(font);
+        }
+        Some(chain)
+    }
+
+    /// Return a mono font chain to the watcher.
+    pub(crate) fn put_mono_chain(&mut self, chain: Option<FontChain>) {
+        match chain {
+            Some(chain) => {
+                let (primary, fallbacks) = chain.into_parts();
+                self.mono_font = Some(primary);
+                self.fallback_fonts = fallbacks;
+            }
+            None => self.mono_font = None,
+        }
+    }
+
     /// Take the display font out of the watcher, leaving None.
     pub(crate) fn take_display_font(&mut self) -> Option<CachedFont> {
         self.display_font.take()
@@ -194,7 +250,9 @@ impl FontWatcher {
         let display_changed = config.display_path != self.display_path
             || config.display_size != self.display_size;
 
-        if !mono_changed && !display_changed {
+        let fallbacks_changed = config.fallback_paths != self.fallback_paths;
+
+        if !mono_changed && !display_changed && !fallbacks_changed {
             return false;
         }
 
@@ -208,6 +266,10 @@ impl FontWatcher {
             self.display_size = config.display_size;
             self.display_font = load_cached_font(&self.display_path);
         }
+        if fallbacks_changed {
+            self.fallback_paths = config.fallback_paths;
+            self.fallback_fonts = load_cached_fonts(&self.fallback_paths);
+        }
         true
     }
 }
@@ -48,13 +48,16 @@
 
 mod window;
 mod event;
+pub mod clipboard;
 pub mod font;
 
 pub use window::Window;
 pub use event::{Event, Modifiers};
 pub use font::FontConfig;
+pub use clipboard::{mime, ClipboardData};
 pub use libfont::CachedFont;
-pub use libbreenix::graphics::{WindowInputEvent, input_event_type};
+pub use libgfx::text::FontChain;
+pub use libbreenix::graphics::{WindowInputEvent, cursor_shape, input_event_type};
 pub use libgfx::framebuf::FrameBuf;
 pub use libgfx::color::Color;
 pub use libbui::widget::tab_bar::TabBar;
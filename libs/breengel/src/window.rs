@@ -4,10 +4,12 @@
 //! Includes automatic font management via an internal FontWatcher.
 
 use libfont::CachedFont;
+use libgfx::text::FontChain;
 use libbreenix::error::Error;
 use libbreenix::graphics::{self, WindowInputEvent};
 use libgfx::framebuf::FrameBuf;
 
+use crate::clipboard::{self, ClipboardData};
 use crate::event::Event;
 use crate::font::FontWatcher;
 
@@ -29,6 +31,8 @@ pub struct Window {
     width: u32,
     height: u32,
     font_watcher: FontWatcher,
+    /// Last shape passed to `set_cursor`, to skip repeated syscalls
+    cursor: u32,
 }
 
 impl Window {
@@ -76,6 +80,7 @@ impl Window {
             width,
             height,
             font_watcher,
+            cursor: graphics::cursor_shape::ARROW,
         })
     }
 
@@ -164,6 +169,19 @@ impl Window {
         self.font_watcher.put_mono_font(font);
     }
 
+    /// Take the monospace font and the configured fallback fonts out of the
+    /// window as a [`FontChain`], for text that may need glyphs the mono font
+    /// lacks. Like [`take_mono_font`](Window::take_mono_font), take it again
+    /// after `Event::FontChanged`.
+    pub fn take_mono_chain(&mut self) -> Option<FontChain> {
+        self.font_watcher.take_mono_chain()
+    }
+
+    /// Return a previously taken mono font chain.
+    pub fn put_mono_chain(&mut self, chain: Option<FontChain>) {
+        self.font_watcher.put_mono_chain(chain);
+    }
+
     /// Take the display font out of the window for rendering.
     pub fn take_display_font(&mut self) -> Option<CachedFont> {
         self.font_watcher.take_display_font()
@@ -205,6 +223,64 @@ impl Window {
         self.font_watcher.disable_polling();
     }
 
+    // ── Clipboard and drag-and-drop ─────────────────────────────────────
+
+    /// Put data on the clipboard, offered in each (MIME type, data) pair,
+    /// e.g. `&[(mime::TEXT, text.as_bytes())]`. Replaces what was there.
+    pub fn set_clipboard(&self, offers: &[(&str, &[u8])]) -> Result<(), Error> {
+        clipboard::set(graphics::selection::CLIPBOARD, self.buffer_id, offers)
+    }
+
+    /// Read the clipboard in the first of the `accept`ed MIME types it is
+    /// offered in. `None` if the clipboard is empty or has none of them.
+    pub fn request_clipboard(&self, accept: &[&str]) -> Option<ClipboardData> {
+        clipboard::request(graphics::selection::CLIPBOARD, accept)
+    }
+
+    /// The MIME types the clipboard is offered in.
+    pub fn clipboard_types(&self) -> Vec<String> {
+        clipboard::types(graphics::selection::CLIPBOARD)
+    }
+
+    /// Start dragging data offered in each (MIME type, data) pair. Call
+    /// while the mouse button is held; the drag ends when it is released,
+    /// with an `Event::Drop` to the window under the pointer.
+    pub fn start_drag(&self, offers: &[(&str, &[u8])]) -> Result<(), Error> {
+        clipboard::set(graphics::selection::DRAG, self.buffer_id, offers)
+    }
+
+    /// Read the data of a drag in the first of the `accept`ed MIME types it
+    /// is offered in. Call on `Event::DragEnter` to decide whether to accept
+    /// it, or on `Event::Drop` to take it.
+    pub fn request_drop(&self, accept: &[&str]) -> Option<ClipboardData> {
+        clipboard::request(graphics::selection::DRAG, accept)
+    }
+
+    /// The MIME types the current drag is offered in.
+    pub fn drag_types(&self) -> Vec<String> {
+        clipboard::types(graphics::selection::DRAG)
+    }
+
+    // ── Cursor ──────────────────────────────────────────────────────────
+
+    /// Set the cursor shown while the pointer is over this window's content,
+    /// one of [`cursor_shape`](crate::cursor_shape). BWM still shows resize
+    /// arrows on the borders. Cheap to call on every mouse move: unchanged
+    /// shapes are not sent to the kernel.
+    pub fn set_cursor(&mut self, shape: u32) -> Result<(), Error> {
+        if shape == self.cursor {
+            return Ok(());
+        }
+        graphics::set_window_cursor(self.buffer_id, shape)?;
+        self.cursor = shape;
+        Ok(())
+    }
+
+    /// The cursor last set with [`set_cursor`](Window::set_cursor).
+    pub fn cursor(&self) -> u32 {
+        self.cursor
+    }
+
     // ── Window metadata ─────────────────────────────────────────────────
 
     /// The kernel-assigned buffer ID for this window.
@@ -390,6 +390,50 @@ pub unsafe extern "C" fn stat64(path: *const u8, buf: *mut u8) -> i32 {
     stat(path, buf)
 }
 
+/// statfs - get filesystem statistics by path
+#[no_mangle]
+pub unsafe extern "C" fn statfs(path: *const u8, buf: *mut u8) -> i32 {
+    if path.is_null() || buf.is_null() {
+        ERRNO = EFAULT;
+        return -1;
+    }
+
+    let result = libbreenix::raw::syscall2(
+        libbreenix::syscall::nr::STATFS,
+        path as u64,
+        buf as u64,
+    ) as i64;
+    syscall_result_to_c_int(result)
+}
+
+/// fstatfs - get filesystem statistics by fd
+#[no_mangle]
+pub unsafe extern "C" fn fstatfs(fd: i32, buf: *mut u8) -> i32 {
+    if buf.is_null() {
+        ERRNO = EFAULT;
+        return -1;
+    }
+
+    let result = libbreenix::raw::syscall2(
+        libbreenix::syscall::nr::FSTATFS,
+        fd as u64,
+        buf as u64,
+    ) as i64;
+    syscall_result_to_c_int(result)
+}
+
+/// statfs64 - same as statfs (64-bit is native)
+#[no_mangle]
+pub unsafe extern "C" fn statfs64(path: *const u8, buf: *mut u8) -> i32 {
+    statfs(path, buf)
+}
+
+/// fstatfs64 - same as fstatfs (64-bit is native)
+#[no_mangle]
+pub unsafe extern "C" fn fstatfs64(fd: i32, buf: *mut u8) -> i32 {
+    fstatfs(fd, buf)
+}
+
 /// lstat64 - same as lstat (64-bit is native on x86_64)
 #[no_mangle]
 pub unsafe extern "C" fn lstat64(path: *const u8, buf: *mut u8) -> i32 {
@@ -783,27 +827,148 @@ pub unsafe extern "C" fn getdents64(fd: i32, buf: *mut u8, count: usize) -> isiz
 
 /// ftruncate - truncate a file to a specified length
 #[no_mangle]
-pub unsafe extern "C" fn ftruncate(_fd: i32, _length: i64) -> i32 {
-    ERRNO = ENOSYS;
-    -1
+pub extern "C" fn ftruncate(fd: i32, length: i64) -> i32 {
+    let fd_val = Fd::from_raw(fd as u64);
+    // Negative lengths pass through unchanged; the kernel rejects them with EINVAL
+    let result = libbreenix::fs::ftruncate(fd_val, length as u64);
+    result_unit_to_c_int(result)
 }
 
 /// ftruncate64 - same as ftruncate on 64-bit
 #[no_mangle]
-pub unsafe extern "C" fn ftruncate64(fd: i32, length: i64) -> i32 {
+pub extern "C" fn ftruncate64(fd: i32, length: i64) -> i32 {
     ftruncate(fd, length)
 }
 
+/// truncate - truncate a file, named by path, to a specified length
+#[no_mangle]
+pub unsafe extern "C" fn truncate(path: *const u8, length: i64) -> i32 {
+    if path.is_null() {
+        ERRNO = EFAULT;
+        return -1;
+    }
+
+    let result = libbreenix::raw::syscall2(
+        libbreenix::syscall::nr::TRUNCATE,
+        path as u64,
+        length as u64,
+    ) as i64;
+
+    if result < 0 {
+        set_errno_from_result(result);
+        -1
+    } else {
+        0
+    }
+}
+
+/// truncate64 - same as truncate on 64-bit
+#[no_mangle]
+pub unsafe extern "C" fn truncate64(path: *const u8, length: i64) -> i32 {
+    truncate(path, length)
+}
+
+/// fallocate - preallocate blocks for a range of a file
+#[no_mangle]
+pub extern "C" fn fallocate(fd: i32, mode: i32, offset: i64, len: i64) -> i32 {
+    let fd_val = Fd::from_raw(fd as u64);
+    let result = libbreenix::fs::fallocate(fd_val, mode, offset as u64, len as u64);
+    result_unit_to_c_int(result)
+}
+
+/// posix_fallocate - preallocate blocks, returning the error number directly
+#[no_mangle]
+pub extern "C" fn posix_fallocate(fd: i32, offset: i64, len: i64) -> i32 {
+    let fd_val = Fd::from_raw(fd as u64);
+    match libbreenix::fs::fallocate(fd_val, 0, offset as u64, len as u64) {
+        Ok(()) => 0,
+        Err(e) => error_to_errno(&e),
+    }
+}
+
 /// fsync - synchronize file state with storage
 #[no_mangle]
-pub extern "C" fn fsync(_fd: i32) -> i32 {
-    0 // No-op for now
+pub extern "C" fn fsync(fd: i32) -> i32 {
+    let fd_val = Fd::from_raw(fd as u64);
+    result_unit_to_c_int(libbreenix::fs::fsync(fd_val))
 }
 
 /// fdatasync - synchronize file data with storage
 #[no_mangle]
-pub extern "C" fn fdatasync(_fd: i32) -> i32 {
-    0 // No-op for now
+pub extern "C" fn fdatasync(fd: i32) -> i32 {
+    let fd_val = Fd::from_raw(fd as u64);
+    result_unit_to_c_int(libbreenix::fs::fdatasync(fd_val))
+}
+
+/// flock - apply or remove an advisory lock on an open file
+#[no_mangle]
+pub extern "C" fn flock(fd: i32, operation: i32) -> i32 {
+    let fd_val = Fd::from_raw(fd as u64);
+    result_unit_to_c_int(libbreenix::fs::flock(fd_val, operation))
+}
+
+/// sendfile - copy data from a file to another descriptor in the kernel
+#[no_mangle]
+pub unsafe extern "C" fn sendfile(
+    out_fd: i32,
+    in_fd: i32,
+    offset: *mut i64,
+    count: usize,
+) -> isize {
+    let result = libbreenix::raw::syscall4(
+        libbreenix::syscall::nr::SENDFILE,
+        out_fd as u64,
+        in_fd as u64,
+        offset as u64,
+        count as u64,
+    ) as i64;
+    syscall_result_to_c_ssize(result)
+}
+
+/// sendfile64 - alias of sendfile (off_t is always 64-bit)
+#[no_mangle]
+pub unsafe extern "C" fn sendfile64(
+    out_fd: i32,
+    in_fd: i32,
+    offset: *mut i64,
+    count: usize,
+) -> isize {
+    sendfile(out_fd, in_fd, offset, count)
+}
+
+/// splice - move data between a pipe and another descriptor
+#[no_mangle]
+pub unsafe extern "C" fn splice(
+    fd_in: i32,
+    off_in: *mut i64,
+    fd_out: i32,
+    off_out: *mut i64,
+    len: usize,
+    flags: u32,
+) -> isize {
+    let result = libbreenix::raw::syscall6(
+        libbreenix::syscall::nr::SPLICE,
+        fd_in as u64,
+        off_in as u64,
+        fd_out as u64,
+        off_out as u64,
+        len as u64,
+        flags as u64,
+    ) as i64;
+    syscall_result_to_c_ssize(result)
+}
+
+/// tee - duplicate pipe contents without consuming them
+#[no_mangle]
+pub unsafe extern "C" fn tee(fd_in: i32, fd_out: i32, len: usize, flags: u32) -> isize {
+    let result = libbreenix::raw::syscall4(
+        libbreenix::syscall::nr::TEE,
+        fd_in as u64,
+        fd_out as u64,
+        len as u64,
+        flags as u64,
+    ) as i64;
+    syscall_result_to_c_ssize(result)
 }
 
 /// fchmod - change file mode bits (by fd)
@@ -991,6 +1156,42 @@ pub extern "C" fn raise(sig: i32) -> i32 {
     kill(getpid(), sig)
 }
 
+/// sigqueue - queue a signal and a value to a process
+///
+/// `value` is the `union sigval` passed by value (sival_int or sival_ptr).
+#[no_mangle]
+pub extern "C" fn sigqueue(pid: i32, sig: i32, value: usize) -> i32 {
+    result_unit_to_c_int(libbreenix::signal::sigqueue(pid, sig, value as u64))
+}
+
+/// ptrace - trace another process
+///
+/// Declared variadic in C; the four arguments are always passed. As in glibc,
+/// PTRACE_PEEKTEXT/PTRACE_PEEKDATA return the word read (check errno).
+#[no_mangle]
+pub unsafe extern "C" fn ptrace(request: i32, pid: i32, addr: u64, data: u64) -> i64 {
+    use libbreenix::ptrace::{PTRACE_PEEKDATA, PTRACE_PEEKTEXT};
+
+    let request = request as u64;
+    if request == PTRACE_PEEKTEXT || request == PTRACE_PEEKDATA {
+        return match libbreenix::ptrace::peek_data(pid, addr) {
+            Ok(word) => {
+                ERRNO = 0;
+                word as i64
+            }
+            Err(e) => set_errno_from_error(e) as i64,
+        };
+    }
+    let ret = libbreenix::syscall::raw::syscall4(
+        libbreenix::syscall::nr::PTRACE,
+        request,
+        pid as u64,
+        addr,
+        data,
+    ) as i64;
+    if ret < 0 { set_errno_from_result(ret); -1 } else { ret }
+}
+
 // =============================================================================
 // Memory Management
 // =============================================================================
@@ -1123,6 +1324,7 @@ pub const ESPIPE: i32 = 29;
 pub const EROFS: i32 = 30;
 pub const EMLINK: i32 = 31;
 pub const EPIPE: i32 = 32;
+pub const EDEADLK: i32 = 35;
 pub const ENOSYS: i32 = 38;
 
 // =============================================================================
@@ -1592,6 +1794,19 @@ pub unsafe extern "C" fn getrlimit(resource: i32, rlim: *mut rlimit) -> i32 {
     if ret < 0 { set_errno_from_result(ret); -1 } else { 0 }
 }
 
+/// setrlimit - set resource limits
+#[no_mangle]
+pub unsafe extern "C" fn setrlimit(resource: i32, rlim: *const rlimit) -> i32 {
+    let ret = libbreenix::syscall::raw::syscall4(
+        libbreenix::syscall::nr::PRLIMIT64,
+        0u64,  // pid=0 means current process
+        resource as u64,
+        rlim as u64,
+        0u64,  // old_rlim = NULL
+    ) as i64;
+    if ret < 0 { set_errno_from_result(ret); -1 } else { 0 }
+}
+
 /// uname - get system identification
 #[no_mangle]
 pub unsafe extern "C" fn uname(buf: *mut utsname) -> i32 {
@@ -2423,6 +2638,29 @@ pub extern "C" fn pthread_join(thread: usize, _retval: *mut *mut u8) -> i32 {
     }
 }
 
+/// pthread_kill - send a signal to a thread
+///
+/// Accepts either pthread_self() or a handle from pthread_create, whose tid
+/// word holds the thread ID until the thread exits.
+#[no_mangle]
+pub extern "C" fn pthread_kill(thread: usize, sig: i32) -> i32 {
+    if thread == 0 {
+        return ESRCH;
+    }
+    let tid = if thread == pthread_self() {
+        thread as u32
+    } else {
+        unsafe { core::ptr::read_volatile(thread as *const u32) }
+    };
+    if tid == 0 {
+        return ESRCH;
+    }
+    match libbreenix::signal::tkill(tid as i32, sig) {
+        Ok(()) => 0,
+        Err(e) => error_to_errno(&e),
+    }
+}
+
 /// pthread_detach - detach a thread (stub - returns 0)
 #[no_mangle]
 pub extern "C" fn pthread_detach(_thread: usize) -> i32 {
@@ -3163,6 +3401,34 @@ pub unsafe extern "C" fn sigsuspend(mask: *const u64) -> i32 {
     -1
 }
 
+/// sigtimedwait - wait for a signal in a set, with a timeout
+///
+/// Returns the accepted signal number, or -1 with errno EAGAIN on timeout.
+#[no_mangle]
+pub unsafe extern "C" fn sigtimedwait(
+    set: *const u64,
+    info: *mut libbreenix::signal::Siginfo,
+    timeout: *const libbreenix::types::Timespec,
+) -> i32 {
+    if set.is_null() {
+        ERRNO = EFAULT;
+        return -1;
+    }
+    match libbreenix::signal::sigtimedwait(&*set, info.as_mut(), timeout.as_ref()) {
+        Ok(sig) => sig,
+        Err(e) => set_errno_from_error(e),
+    }
+}
+
+/// sigwaitinfo - wait indefinitely for a signal in a set
+#[no_mangle]
+pub unsafe extern "C" fn sigwaitinfo(
+    set: *const u64,
+    info: *mut libbreenix::signal::Siginfo,
+) -> i32 {
+    sigtimedwait(set, info, core::ptr::null())
+}
+
 // =============================================================================
 // PTY Functions (posix_openpt, grantpt, unlockpt, ptsname_r)
 // =============================================================================
@@ -1,9 +1,22 @@
-//! Audio playback API
+//! Audio playback and capture API
 //!
-//! Provides userspace access to the VirtIO sound device for PCM audio output.
+//! Provides userspace access to the kernel audio mixer for PCM audio output
+//! and input.
+//!
+//! [`Dsp`] opens `/dev/dsp`, which gives each caller its own stream with its
+//! own sample rate, format, channel count and volume; the kernel converts
+//! and mixes every open stream. The older `init()`/`write_pcm()` calls play
+//! device-format audio through a per-process stream of the same mixer.
+//!
+//! A stream opened for reading ([`Dsp::open_capture`]) records: `read()`
+//! returns captured audio converted to the stream's rate, format and channel
+//! count. Without a capture device the kernel supplies silence at the same
+//! pace.
 
 use crate::error::Error;
+use crate::fs;
 use crate::syscall::{nr, raw};
+use crate::types::{Fd, OwnedFd};
 
 /// Audio sample rate (Hz)
 pub const SAMPLE_RATE: u32 = 44100;
@@ -23,7 +36,7 @@ pub fn init() -> Result<(), Error> {
 /// Write raw PCM data to the audio device.
 ///
 /// Data must be S16_LE stereo at 44100 Hz.
-/// Maximum 16384 bytes per call.
+/// Maximum 16384 bytes per call. Blocks while the process's stream is full.
 pub fn write_pcm(data: &[u8]) -> Result<usize, Error> {
     let ret = unsafe {
         raw::syscall2(nr::AUDIO_WRITE, data.as_ptr() as u64, data.len() as u64) as i64
@@ -40,3 +53,185 @@ pub fn write_samples(samples: &[i16]) -> Result<usize, Error> {
     let data = unsafe { core::slice::from_raw_parts(ptr, byte_len) };
     write_pcm(data)
 }
+
+/// `/dev/dsp` ioctl request codes (OSS)
+pub mod request {
+    pub const SNDCTL_DSP_RESET: u64 = 0x5000;
+    pub const SNDCTL_DSP_SYNC: u64 = 0x5001;
+    pub const SNDCTL_DSP_SPEED: u64 = 0xC004_5002;
+    pub const SNDCTL_DSP_STEREO: u64 = 0xC004_5003;
+    pub const SNDCTL_DSP_GETBLKSIZE: u64 = 0xC004_5004;
+    pub const SNDCTL_DSP_SETFMT: u64 = 0xC004_5005;
+    pub const SNDCTL_DSP_CHANNELS: u64 = 0xC004_5006;
+    pub const SNDCTL_DSP_GETFMTS: u64 = 0x8004_500B;
+    pub const SNDCTL_DSP_GETOSPACE: u64 = 0x8010_500C;
+    pub const SNDCTL_DSP_GETISPACE: u64 = 0x8010_500D;
+    pub const SNDCTL_DSP_GETODELAY: u64 = 0x8004_5017;
+    pub const SNDCTL_DSP_GETPLAYVOL: u64 = 0x8004_5018;
+    pub const SNDCTL_DSP_SETPLAYVOL: u64 = 0xC004_5018;
+}
+
+/// SETFMT argument that reads the current format without changing it
+pub const AFMT_QUERY: u32 = 0x0000_0000;
+/// Unsigned 8-bit samples
+pub const AFMT_U8: u32 = 0x0000_0008;
+/// Signed 16-bit little-endian samples
+pub const AFMT_S16_LE: u32 = 0x0000_0010;
+
+/// Full volume, and the default for new streams
+pub const MAX_VOLUME: u8 = 100;
+
+/// Buffer state (matches kernel AudioBufInfo / OSS `audio_buf_info`)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AudioBufInfo {
+    /// Whole fragments that can be written (or read) without blocking
+    pub fragments: i32,
+    /// Fragments in the whole buffer
+    pub fragstotal: i32,
+    /// Fragment size in bytes
+    pub fragsize: i32,
+    /// Bytes that can be written (or read) without blocking
+    pub bytes: i32,
+}
+
+/// A stream on `/dev/dsp`. Closes the stream on drop; audio still queued
+/// plays out.
+///
+/// New streams are S16_LE stereo at 44100 Hz and full volume. Each setter
+/// returns the value the kernel actually put in effect.
+pub struct Dsp(OwnedFd);
+
+impl Dsp {
+    /// Open a new stream on `/dev/dsp`.
+    pub fn open() -> Result<Dsp, Error> {
+        Self::open_with_flags(fs::O_WRONLY)
+    }
+
+    /// Open a new stream that records as well as plays.
+    pub fn open_capture() -> Result<Dsp, Error> {
+        Self::open_with_flags(fs::O_RDWR)
+    }
+
+    /// Open a new stream with extra open flags (e.g. `O_NONBLOCK`).
+    pub fn open_with_flags(flags: u32) -> Result<Dsp, Error> {
+        let fd = fs::open("/dev/dsp", flags)?;
+        Ok(Dsp(OwnedFd::new(fd)))
+    }
+
+    /// Get the underlying file descriptor (borrowed, not owned).
+    pub fn fd(&self) -> Fd {
+        self.0.fd()
+    }
+
+    fn ioctl(&self, request: u64, arg: u64) -> Result<(), Error> {
+        let ret = unsafe { raw::syscall3(nr::IOCTL, self.0.fd().raw(), request, arg) };
+        Error::from_syscall(ret as i64).map(|_| ())
+    }
+
+    fn ioctl_int(&self, request: u64, value: u32) -> Result<u32, Error> {
+        let mut value = value;
+        self.ioctl(request, &mut value as *mut u32 as u64)?;
+        Ok(value)
+    }
+
+    /// Set the sample rate in Hz.
+    pub fn set_rate(&self, rate: u32) -> Result<u32, Error> {
+        self.ioctl_int(request::SNDCTL_DSP_SPEED, rate)
+    }
+
+    /// Set the channel count (1 or 2).
+    pub fn set_channels(&self, channels: u32) -> Result<u32, Error> {
+        self.ioctl_int(request::SNDCTL_DSP_CHANNELS, channels)
+    }
+
+    /// Set the sample format (`AFMT_U8` or `AFMT_S16_LE`).
+    pub fn set_format(&self, format: u32) -> Result<u32, Error> {
+        self.ioctl_int(request::SNDCTL_DSP_SETFMT, format)
+    }
+
+    /// The current sample format.
+    pub fn format(&self) -> Result<u32, Error> {
+        self.ioctl_int(request::SNDCTL_DSP_SETFMT, AFMT_QUERY)
+    }
+
+    /// Mask of the supported sample formats.
+    pub fn formats(&self) -> Result<u32, Error> {
+        self.ioctl_int(request::SNDCTL_DSP_GETFMTS, 0)
+    }
+
+    /// Set the left and right volume (0-100 each).
+    pub fn set_volume(&self, left: u8, right: u8) -> Result<(u8, u8), Error> {
+        let value = self.ioctl_int(
+            request::SNDCTL_DSP_SETPLAYVOL,
+            left as u32 | (right as u32) << 8,
+        )?;
+        Ok((value as u8, (value >> 8) as u8))
+    }
+
+    /// The left and right volume.
+    pub fn volume(&self) -> Result<(u8, u8), Error> {
+        let value = self.ioctl_int(request::SNDCTL_DSP_GETPLAYVOL, 0)?;
+        Ok((value as u8, (value >> 8) as u8))
+    }
+
+    /// Fragment size in bytes of the current format.
+    pub fn block_size(&self) -> Result<u32, Error> {
+        self.ioctl_int(request::SNDCTL_DSP_GETBLKSIZE, 0)
+    }
+
+    /// Space left in the stream's buffer.
+    pub fn space(&self) -> Result<AudioBufInfo, Error> {
+        let mut info = AudioBufInfo::default();
+        self.ioctl(request::SNDCTL_DSP_GETOSPACE, &mut info as *mut _ as u64)?;
+        Ok(info)
+    }
+
+    /// Captured audio waiting to be read.
+    pub fn input_space(&self) -> Result<AudioBufInfo, Error> {
+        let mut info = AudioBufInfo::default();
+        self.ioctl(request::SNDCTL_DSP_GETISPACE, &mut info as *mut _ as u64)?;
+        Ok(info)
+    }
+
+    /// Bytes written but not yet mixed.
+    pub fn delay(&self) -> Result<u32, Error> {
+        self.ioctl_int(request::SNDCTL_DSP_GETODELAY, 0)
+    }
+
+    /// Discard queued and captured audio.
+    pub fn reset(&self) -> Result<(), Error> {
+        self.ioctl(request::SNDCTL_DSP_RESET, 0)
+    }
+
+    /// Wait until queued audio has been mixed.
+    pub fn drain(&self) -> Result<(), Error> {
+        self.ioctl(request::SNDCTL_DSP_SYNC, 0)
+    }
+
+    /// Queue samples in the stream's format. Returns bytes accepted.
+    pub fn write(&self, data: &[u8]) -> Result<usize, Error> {
+        fs::write(self.0.fd(), data)
+    }
+
+    /// Queue interleaved 16-bit samples (for `AFMT_S16_LE` streams).
+    pub fn write_samples(&self, samples: &[i16]) -> Result<usize, Error> {
+        let ptr = samples.as_ptr() as *const u8;
+        let data = unsafe { core::slice::from_raw_parts(ptr, samples.len() * 2) };
+        self.write(data)
+    }
+
+    /// Read captured audio in the stream's format, in whole frames. Blocks
+    /// until some is available unless the stream was opened `O_NONBLOCK`.
+    pub fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        fs::read(self.0.fd(), buf)
+    }
+
+    /// Read interleaved 16-bit samples (for `AFMT_S16_LE` streams). Returns
+    /// bytes read.
+    pub fn read_samples(&self, samples: &mut [i16]) -> Result<usize, Error> {
+        let ptr = samples.as_mut_ptr() as *mut u8;
+        let data = unsafe { core::slice::from_raw_parts_mut(ptr, samples.len() * 2) };
+        self.read(data)
+    }
+}
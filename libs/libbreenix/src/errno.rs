@@ -70,6 +70,8 @@ pub enum Errno {
     EMLINK = 31,
     /// Broken pipe
     EPIPE = 32,
+    /// Resource deadlock would occur
+    EDEADLK = 35,
     /// Function not implemented
     ENOSYS = 38,
     /// Directory not empty
@@ -147,6 +149,7 @@ impl Errno {
             30 => Errno::EROFS,
             31 => Errno::EMLINK,
             32 => Errno::EPIPE,
+            35 => Errno::EDEADLK,
             38 => Errno::ENOSYS,
             39 => Errno::ENOTEMPTY,
             95 => Errno::EOPNOTSUPP,
@@ -5,6 +5,10 @@
 //! - read: Read data from a file descriptor (file-specific variant)
 //! - fstat: Get file metadata
 //! - lseek: Reposition file offset
+//! - truncate/ftruncate: Change a file's length
+//! - fallocate: Preallocate file blocks
+//! - fsync/fdatasync: Flush a file to stable storage
+//! - statfs/fstatfs: Get filesystem capacity and type
 //! - close: Close a file descriptor (re-exported from io)
 //!
 //! Also provides the [`File`] RAII wrapper for automatic close-on-drop.
@@ -82,6 +86,9 @@ pub const R_OK: u32 = 4;  // Test for read permission
 pub const W_OK: u32 = 2;  // Test for write permission
 pub const X_OK: u32 = 1;  // Test for execute permission
 
+/// fallocate mode: allocate blocks without changing the file size
+pub const FALLOC_FL_KEEP_SIZE: i32 = 0x01;
+
 /// Seek whence values
 pub const SEEK_SET: i32 = 0;
 pub const SEEK_CUR: i32 = 1;
@@ -162,6 +169,71 @@ impl Stat {
     }
 }
 
+// Filesystem type magic numbers reported in `StatFs::f_type`
+pub const EXT2_SUPER_MAGIC: i64 = 0xEF53;   // ext2 (root and /home)
+pub const PROC_SUPER_MAGIC: i64 = 0x9fa0;   // /proc
+pub const DEVFS_SUPER_MAGIC: i64 = 0x1373;  // /dev
+pub const DEVPTS_SUPER_MAGIC: i64 = 0x1cd1; // /dev/pts
+
+/// statfs structure (Linux compatible, same layout on x86_64 and aarch64)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StatFs {
+    pub f_type: i64,
+    pub f_bsize: i64,
+    pub f_blocks: u64,
+    pub f_bfree: u64,
+    pub f_bavail: u64,
+    pub f_files: u64,
+    pub f_ffree: u64,
+    pub f_fsid: [i32; 2],
+    pub f_namelen: i64,
+    pub f_frsize: i64,
+    pub f_flags: i64,
+    pub f_spare: [i64; 4],
+}
+
+/// flock() operations
+pub const LOCK_SH: i32 = 1; // Shared lock
+pub const LOCK_EX: i32 = 2; // Exclusive lock
+pub const LOCK_NB: i32 = 4; // Don't block (OR with LOCK_SH/LOCK_EX)
+pub const LOCK_UN: i32 = 8; // Unlock
+
+/// Record lock types (`Flock::l_type`)
+pub const F_RDLCK: i16 = 0;
+pub const F_WRLCK: i16 = 1;
+pub const F_UNLCK: i16 = 2;
+
+/// Record lock description for fcntl F_GETLK/F_SETLK/F_SETLKW
+/// (Linux compatible, same layout on x86_64 and aarch64)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Flock {
+    /// F_RDLCK, F_WRLCK, or F_UNLCK
+    pub l_type: i16,
+    /// SEEK_SET, SEEK_CUR, or SEEK_END
+    pub l_whence: i16,
+    /// Starting offset, relative to `l_whence`
+    pub l_start: i64,
+    /// Length in bytes; 0 means through end of file
+    pub l_len: i64,
+    /// Holder reported by F_GETLK (-1 for an OFD lock); 0 for OFD requests
+    pub l_pid: i32,
+}
+
+impl Flock {
+    /// A lock of `l_type` on `len` bytes from absolute offset `start`
+    pub fn range(l_type: i16, start: i64, len: i64) -> Self {
+        Flock {
+            l_type,
+            l_whence: SEEK_SET as i16,
+            l_start: start,
+            l_len: len,
+            l_pid: 0,
+        }
+    }
+}
+
 /// Open a file and return a file descriptor.
 ///
 /// # Arguments
@@ -790,6 +862,141 @@ pub fn mkfifo(pathname: &str, mode: u32) -> Result<(), Error> {
     Error::from_syscall(ret).map(|_| ())
 }
 
+/// Truncate or extend a file to a given length.
+///
+/// Shrinking discards data past `length`; growing adds a hole that reads as zeros.
+///
+/// # Arguments
+/// * `path` - Path to the file (null-terminated string)
+/// * `length` - New file size in bytes
+///
+/// # Errors
+/// * `ENOENT` - File does not exist
+/// * `EISDIR` - Path refers to a directory
+/// * `EFBIG` - Length exceeds the maximum file size
+/// * `EROFS` - Filesystem is read-only
+#[inline]
+pub fn truncate(path: &str, length: u64) -> Result<(), Error> {
+    let cpath = CPath::new(path)?;
+    let ret = unsafe { raw::syscall2(nr::TRUNCATE, cpath.as_u64(), length) as i64 };
+    Error::from_syscall(ret).map(|_| ())
+}
+
+/// Truncate or extend an open file to a given length.
+///
+/// The file must be open for writing. The file offset is not changed.
+///
+/// # Errors
+/// * `EBADF` - Invalid file descriptor
+/// * `EINVAL` - Not a regular file, or not open for writing
+/// * `EFBIG` - Length exceeds the maximum file size
+#[inline]
+pub fn ftruncate(fd: Fd, length: u64) -> Result<(), Error> {
+    let ret = unsafe { raw::syscall2(nr::FTRUNCATE, fd.raw(), length) as i64 };
+    Error::from_syscall(ret).map(|_| ())
+}
+
+/// Preallocate blocks for a byte range of an open file.
+///
+/// After success, writes inside `[offset, offset + len)` will not fail
+/// with `ENOSPC`.
+///
+/// # Arguments
+/// * `fd` - File descriptor open for writing
+/// * `mode` - 0, or `FALLOC_FL_KEEP_SIZE` to leave the file size unchanged
+/// * `offset` - Starting byte offset
+/// * `len` - Length of the range in bytes (must be non-zero)
+///
+/// # Errors
+/// * `ENOSPC` - Not enough free space for the range
+/// * `EOPNOTSUPP` - Unsupported mode flags
+/// * `ENODEV` - Not a regular file
+#[inline]
+pub fn fallocate(fd: Fd, mode: i32, offset: u64, len: u64) -> Result<(), Error> {
+    let ret = unsafe {
+        raw::syscall4(nr::FALLOCATE, fd.raw(), mode as u64, offset, len) as i64
+    };
+    Error::from_syscall(ret).map(|_| ())
+}
+
+/// Flush a file's data and metadata to stable storage.
+#[inline]
+pub fn fsync(fd: Fd) -> Result<(), Error> {
+    let ret = unsafe { raw::syscall1(nr::FSYNC, fd.raw()) as i64 };
+    Error::from_syscall(ret).map(|_| ())
+}
+
+/// Flush a file's data to stable storage.
+#[inline]
+pub fn fdatasync(fd: Fd) -> Result<(), Error> {
+    let ret = unsafe { raw::syscall1(nr::FDATASYNC, fd.raw()) as i64 };
+    Error::from_syscall(ret).map(|_| ())
+}
+
+/// Get statistics for the filesystem containing `path`.
+///
+/// # Errors
+/// * `ENOENT` - Path does not exist
+#[inline]
+pub fn statfs(path: &str) -> Result<StatFs, Error> {
+    let cpath = CPath::new(path)?;
+    let mut buf = StatFs::default();
+    let ret = unsafe {
+        raw::syscall2(nr::STATFS, cpath.as_u64(), &mut buf as *mut StatFs as u64) as i64
+    };
+    Error::from_syscall(ret)?;
+    Ok(buf)
+}
+
+/// Get statistics for the filesystem behind an open file descriptor.
+///
+/// # Errors
+/// * `EBADF` - Invalid file descriptor
+#[inline]
+pub fn fstatfs(fd: Fd) -> Result<StatFs, Error> {
+    let mut buf = StatFs::default();
+    let ret = unsafe {
+        raw::syscall2(nr::FSTATFS, fd.raw(), &mut buf as *mut StatFs as u64) as i64
+    };
+    Error::from_syscall(ret)?;
+    Ok(buf)
+}
+
+/// Apply or remove an advisory lock on a whole open file.
+///
+/// # Arguments
+/// * `fd` - File descriptor
+/// * `operation` - `LOCK_SH`, `LOCK_EX`, or `LOCK_UN`, optionally ORed with `LOCK_NB`
+///
+/// # Errors
+/// * `EAGAIN` - `LOCK_NB` was given and the file is locked
+/// * `EINTR` - Interrupted by a signal while waiting
+#[inline]
+pub fn flock(fd: Fd, operation: i32) -> Result<(), Error> {
+    let ret = unsafe { raw::syscall2(nr::FLOCK, fd.raw(), operation as u64) as i64 };
+    Error::from_syscall(ret).map(|_| ())
+}
+
+/// Find the record lock that would block `lock`.
+///
+/// On return `lock.l_type` is `F_UNLCK` if nothing conflicts; otherwise
+/// `lock` describes the conflicting lock and its holder.
+///
+/// `cmd` is `F_GETLK` or `F_OFD_GETLK`.
+#[inline]
+pub fn fcntl_getlk(fd: Fd, cmd: i32, lock: &mut Flock) -> Result<(), Error> {
+    crate::io::fcntl(fd, cmd, lock as *mut Flock as i64).map(|_| ())
+}
+
+/// Set or clear a record lock.
+///
+/// `cmd` is `F_SETLK`/`F_OFD_SETLK` (fail with `EAGAIN` on conflict) or
+/// `F_SETLKW`/`F_OFD_SETLKW` (wait; `EDEADLK` if waiting would deadlock).
+#[inline]
+pub fn fcntl_setlk(fd: Fd, cmd: i32, lock: &Flock) -> Result<(), Error> {
+    crate::io::fcntl(fd, cmd, lock as *const Flock as i64).map(|_| ())
+}
+
 // ============================================================================
 // RAII File Wrapper
 // ============================================================================
@@ -829,6 +1036,21 @@ impl File {
         lseek(self.0.fd(), offset, whence)
     }
 
+    /// Truncate or extend the file to `size` bytes.
+    pub fn set_len(&self, size: u64) -> Result<(), Error> {
+        ftruncate(self.0.fd(), size)
+    }
+
+    /// Flush data and metadata to stable storage.
+    pub fn sync_all(&self) -> Result<(), Error> {
+        fsync(self.0.fd())
+    }
+
+    /// Flush data to stable storage.
+    pub fn sync_data(&self) -> Result<(), Error> {
+        fdatasync(self.0.fd())
+    }
+
     /// Release the fd without closing it.
     pub fn into_raw_fd(self) -> Fd {
         self.0.into_raw()
@@ -152,6 +152,28 @@ pub mod draw_op {
     pub const WAIT_STRESS_WAKE: u32 = 29;
     /// F32c waitqueue stress stats.
     pub const WAIT_STRESS_STATS: u32 = 30;
+    /// Offer data on a selection (clipboard or drag)
+    pub const SET_SELECTION: u32 = 32;
+    /// List the MIME types a selection is offered in
+    pub const LIST_SELECTION: u32 = 33;
+    /// Read a selection's data in one MIME type
+    pub const READ_SELECTION: u32 = 34;
+    /// Query the source window of the drag in progress
+    pub const DRAG_SOURCE: u32 = 35;
+    /// End the drag in progress (dropped or cancelled)
+    pub const END_DRAG: u32 = 36;
+    /// List the GPU's display outputs
+    pub const LIST_DISPLAYS: u32 = 37;
+    /// Set the mode of a display output
+    pub const SET_DISPLAY_MODE: u32 = 38;
+    /// Upload a cursor image and show it
+    pub const SET_CURSOR_IMAGE: u32 = 39;
+    /// Set the cursor shape a window wants over its content
+    pub const SET_WINDOW_CURSOR: u32 = 40;
+    /// Query the cursor shape a window wants
+    pub const WINDOW_CURSOR: u32 = 41;
+    /// Query whether the pointer is in the GPU's hardware cursor plane
+    pub const HARDWARE_CURSOR: u32 = 42;
 }
 
 /// Ball descriptor for VirGL GPU rendering.
@@ -714,8 +736,15 @@ pub mod cursor_shape {
     pub const RESIZE_NWSE: u32 = 3;
     /// Northeast-southwest (diagonal) resize cursor
     pub const RESIZE_NESW: u32 = 4;
+    /// Text (I-beam) cursor
+    pub const TEXT: u32 = 5;
+    /// The image last uploaded with [`set_cursor_image`](super::set_cursor_image)
+    pub const CUSTOM: u32 = 6;
 }
 
+/// Largest cursor image [`set_cursor_image`] accepts, in each dimension.
+pub const MAX_CURSOR_SIZE: u32 = 64;
+
 /// Set the active cursor shape.
 ///
 /// Changes the cursor displayed by the GPU compositor.
@@ -732,6 +761,84 @@ pub fn set_cursor_shape(shape: u32) -> Result<(), Error> {
     fbdraw(&cmd)
 }
 
+/// Upload a `width` x `height` BGRA cursor image (at most
+/// [`MAX_CURSOR_SIZE`] square) with its hotspot, and make it the active
+/// cursor ([`cursor_shape::CUSTOM`]).
+///
+/// Only shown when [`hardware_cursor`] is true; otherwise the pointer falls
+/// back to the arrow.
+pub fn set_cursor_image(
+    pixels: &[u32],
+    width: u32,
+    height: u32,
+    hot_x: u32,
+    hot_y: u32,
+) -> Result<(), Error> {
+    if pixels.len() < (width as usize) * (height as usize) {
+        return Err(Error::Os(Errno::EINVAL));
+    }
+    let ptr = pixels.as_ptr() as u64;
+    let cmd = FbDrawCmd {
+        op: draw_op::SET_CURSOR_IMAGE,
+        p1: ptr as i32,
+        p2: (ptr >> 32) as i32,
+        p3: ((width & 0xFFFF) | (height << 16)) as i32,
+        p4: ((hot_x & 0xFFFF) | (hot_y << 16)) as i32,
+        color: 0,
+    };
+    fbdraw(&cmd)
+}
+
+/// Set the cursor shape window `buffer_id` wants while the pointer is over
+/// its content. The caller must own the window; BWM shows the shape.
+pub fn set_window_cursor(buffer_id: u32, shape: u32) -> Result<(), Error> {
+    let cmd = FbDrawCmd {
+        op: draw_op::SET_WINDOW_CURSOR,
+        p1: buffer_id as i32,
+        p2: shape as i32,
+        p3: 0,
+        p4: 0,
+        color: 0,
+    };
+    fbdraw(&cmd)
+}
+
+/// The cursor shape window `buffer_id` wants over its content
+/// ([`cursor_shape::ARROW`] if it never set one).
+///
+/// Called by BWM as the pointer moves between windows.
+pub fn window_cursor(buffer_id: u32) -> Result<u32, Error> {
+    let cmd = FbDrawCmd {
+        op: draw_op::WINDOW_CURSOR,
+        p1: buffer_id as i32,
+        p2: 0,
+        p3: 0,
+        p4: 0,
+        color: 0,
+    };
+    let ret = unsafe { raw::syscall1(nr::FBDRAW, &cmd as *const FbDrawCmd as u64) as i64 };
+    if ret < 0 {
+        return Err(Error::Os(Errno::from_raw(-ret)));
+    }
+    Ok(ret as u32)
+}
+
+/// Whether the GPU shows the pointer in its hardware cursor plane.
+///
+/// When true, moving the pointer or changing its shape needs no recomposite.
+pub fn hardware_cursor() -> bool {
+    let cmd = FbDrawCmd {
+        op: draw_op::HARDWARE_CURSOR,
+        p1: 0,
+        p2: 0,
+        p3: 0,
+        p4: 0,
+        color: 0,
+    };
+    let ret = unsafe { raw::syscall1(nr::FBDRAW, &cmd as *const FbDrawCmd as u64) as i64 };
+    ret == 1
+}
+
 /// Poll current modifier key state from the kernel HID driver.
 ///
 /// Returns a bitmask:
@@ -764,6 +871,241 @@ pub mod input_event_type {
     pub const WINDOW_RESIZED: u16 = 8;
     /// Scroll wheel event. `scroll_y` > 0 = scroll up, < 0 = scroll down.
     pub const MOUSE_SCROLL: u16 = 9;
+    /// A drag entered the window at (`mouse_x`, `mouse_y`).
+    pub const DRAG_ENTER: u16 = 10;
+    /// A drag moved within the window.
+    pub const DRAG_MOTION: u16 = 11;
+    /// A drag left the window without dropping.
+    pub const DRAG_LEAVE: u16 = 12;
+    /// A drag was dropped on the window; read it with
+    /// `read_selection(selection::DRAG, ..)`.
+    pub const DROP: u16 = 13;
+}
+
+// =============================================================================
+// Selections (clipboard and drag-and-drop)
+// =============================================================================
+
+/// Selection numbers for the selection calls.
+pub mod selection {
+    /// The clipboard: data copied by the user, kept until replaced
+    pub const CLIPBOARD: u32 = 0;
+    /// The data of the current (or last) drag
+    pub const DRAG: u32 = 1;
+}
+
+/// Data offered on a selection in one MIME type.
+#[derive(Clone, Copy, Debug)]
+pub struct SelectionOffer<'a> {
+    /// MIME type, e.g. `text/plain;charset=utf-8` (at most 64 bytes)
+    pub mime: &'a [u8],
+    pub data: &'a [u8],
+}
+
+/// Maximum number of offers one selection can carry.
+pub const MAX_SELECTION_OFFERS: usize = 8;
+
+/// One offer as passed to the kernel. Must match the kernel's
+/// `SelectionOfferDesc`.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct SelectionOfferDesc {
+    mime_ptr: u64,
+    mime_len: u64,
+    data_ptr: u64,
+    data_len: u64,
+}
+
+/// A read request as passed to the kernel. Must match the kernel's
+/// `SelectionReadDesc`.
+#[repr(C)]
+struct SelectionReadDesc {
+    mime_ptr: u64,
+    mime_len: u64,
+    out_ptr: u64,
+    out_len: u64,
+}
+
+/// Offer data on a selection, replacing what was there.
+///
+/// The kernel copies the data, so it stays available after the window
+/// closes. `window_id` must be a window owned by the calling process.
+/// Setting [`selection::DRAG`] starts a drag: BWM routes it to the window
+/// under the pointer until the button is released. No offers clears the
+/// selection. At most [`MAX_SELECTION_OFFERS`] offers and 1 MiB of data in
+/// total.
+pub fn set_selection(selection: u32, window_id: u32, offers: &[SelectionOffer]) -> Result<(), Error> {
+    if offers.len() > MAX_SELECTION_OFFERS {
+        return Err(Error::Os(Errno::EINVAL));
+    }
+    let mut descs = [SelectionOfferDesc::default(); MAX_SELECTION_OFFERS];
+    for (desc, offer) in descs.iter_mut().zip(offers) {
+        *desc = SelectionOfferDesc {
+            mime_ptr: offer.mime.as_ptr() as u64,
+            mime_len: offer.mime.len() as u64,
+            data_ptr: offer.data.as_ptr() as u64,
+            data_len: offer.data.len() as u64,
+        };
+    }
+    let descs_ptr = descs.as_ptr() as u64;
+    let cmd = FbDrawCmd {
+        op: draw_op::SET_SELECTION,
+        p1: selection as i32,
+        p2: window_id as i32,
+        p3: descs_ptr as i32,
+        p4: (descs_ptr >> 32) as i32,
+        color: offers.len() as u32,
+    };
+    fbdraw(&cmd)
+}
+
+/// List the MIME types a selection is offered in, separated by `\n`.
+///
+/// Returns the length of the full list, which is truncated to `out` if it
+/// does not fit. An empty selection has no types.
+pub fn list_selection(selection: u32, out: &mut [u8]) -> Result<usize, Error> {
+    let out_ptr = out.as_mut_ptr() as u64;
+    let cmd = FbDrawCmd {
+        op: draw_op::LIST_SELECTION,
+        p1: selection as i32,
+        p2: out_ptr as i32,
+        p3: (out_ptr >> 32) as i32,
+        p4: out.len() as i32,
+        color: 0,
+    };
+    let ret = unsafe { raw::syscall1(nr::FBDRAW, &cmd as *const FbDrawCmd as u64) as i64 };
+    if ret < 0 {
+        return Err(Error::Os(Errno::from_raw(-ret)));
+    }
+    Ok(ret as usize)
+}
+
+/// Read a selection's data in MIME type `mime`.
+///
+/// Returns the full data length; only as much as fits is copied into `out`.
+/// Fails with `ENOENT` if the selection is not offered in that type.
+pub fn read_selection(selection: u32, mime: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+    let desc = SelectionReadDesc {
+        mime_ptr: mime.as_ptr() as u64,
+        mime_len: mime.len() as u64,
+        out_ptr: out.as_mut_ptr() as u64,
+        out_len: out.len() as u64,
+    };
+    let desc_ptr = &desc as *const SelectionReadDesc as u64;
+    let cmd = FbDrawCmd {
+        op: draw_op::READ_SELECTION,
+        p1: selection as i32,
+        p2: desc_ptr as i32,
+        p3: (desc_ptr >> 32) as i32,
+        p4: 0,
+        color: 0,
+    };
+    let ret = unsafe { raw::syscall1(nr::FBDRAW, &cmd as *const FbDrawCmd as u64) as i64 };
+    if ret < 0 {
+        return Err(Error::Os(Errno::from_raw(-ret)));
+    }
+    Ok(ret as usize)
+}
+
+/// The window that started the drag in progress, if any.
+///
+/// Called by BWM when `compositor_wait` reports [`COMPOSITOR_READY_DRAG`].
+pub fn drag_source() -> Option<u32> {
+    let cmd = FbDrawCmd {
+        op: draw_op::DRAG_SOURCE,
+        p1: 0,
+        p2: 0,
+        p3: 0,
+        p4: 0,
+        color: 0,
+    };
+    let ret = unsafe { raw::syscall1(nr::FBDRAW, &cmd as *const FbDrawCmd as u64) as i64 };
+    if ret > 0 { Some(ret as u32) } else { None }
+}
+
+/// End the drag in progress. Called by BWM when the button is released.
+///
+/// The drag data stays readable so the window it was dropped on can fetch it.
+pub fn end_drag() -> Result<(), Error> {
+    let cmd = FbDrawCmd {
+        op: draw_op::END_DRAG,
+        p1: 0,
+        p2: 0,
+        p3: 0,
+        p4: 0,
+        color: 0,
+    };
+    fbdraw(&cmd)
+}
+
+/// One display output. Must match the kernel's `DisplayDesc`.
+///
+/// All outputs show part of one desktop (the framebuffer in [`FbInfo`]):
+/// `x`, `y`, `width` and `height` are the rectangle this one shows, and its
+/// size is the output's mode.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DisplayInfo {
+    /// Scanout number, for [`set_display_mode`]
+    pub id: u32,
+    /// bit0 = a display is connected, bit1 = the output is on
+    pub flags: u32,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// The display's own size
+    pub preferred_width: u32,
+    pub preferred_height: u32,
+}
+
+impl DisplayInfo {
+    /// A display is connected to this output.
+    pub fn connected(&self) -> bool {
+        self.flags & 1 != 0
+    }
+
+    /// The output is on, showing its rectangle of the desktop.
+    pub fn active(&self) -> bool {
+        self.flags & 2 != 0
+    }
+}
+
+/// Describe the GPU's display outputs into `out`.
+///
+/// Returns the number of outputs, which may exceed `out.len()`; only as many
+/// as fit are written.
+pub fn list_displays(out: &mut [DisplayInfo]) -> Result<usize, Error> {
+    let out_ptr = out.as_mut_ptr() as u64;
+    let cmd = FbDrawCmd {
+        op: draw_op::LIST_DISPLAYS,
+        p1: out_ptr as i32,
+        p2: (out_ptr >> 32) as i32,
+        p3: out.len() as i32,
+        p4: 0,
+        color: 0,
+    };
+    let ret = unsafe { raw::syscall1(nr::FBDRAW, &cmd as *const FbDrawCmd as u64) as i64 };
+    if ret < 0 {
+        return Err(Error::Os(Errno::from_raw(-ret)));
+    }
+    Ok(ret as usize)
+}
+
+/// Set the mode of display output `id`; 0x0 turns it off.
+///
+/// The mode must fit in the desktop, whose size is fixed at boot, and at
+/// least one output must stay on. Fails with `EINVAL` otherwise.
+pub fn set_display_mode(id: u32, width: u32, height: u32) -> Result<(), Error> {
+    let cmd = FbDrawCmd {
+        op: draw_op::SET_DISPLAY_MODE,
+        p1: id as i32,
+        p2: width as i32,
+        p3: height as i32,
+        p4: 0,
+        color: 0,
+    };
+    fbdraw(&cmd)
 }
 
 /// Write an input event to a window's kernel ring buffer.
@@ -957,6 +1299,12 @@ pub const COMPOSITOR_READY_DIRTY: u32 = 1;
 pub const COMPOSITOR_READY_MOUSE: u32 = 2;
 /// Bitmask: window registry changed (new/removed windows)
 pub const COMPOSITOR_READY_REGISTRY: u32 = 4;
+/// Bitmask: a client started a drag (see [`drag_source`])
+pub const COMPOSITOR_READY_DRAG: u32 = 16;
+/// Bitmask: displays were hotplugged or changed mode (see [`list_displays`])
+pub const COMPOSITOR_READY_DISPLAYS: u32 = 32;
+/// Bitmask: a window changed its cursor shape (see [`window_cursor`])
+pub const COMPOSITOR_READY_CURSOR: u32 = 64;
 
 /// Block until the compositor has work to do.
 ///
@@ -203,6 +203,18 @@ pub mod fcntl_cmd {
     pub const F_GETFL: i32 = 3;
     /// Set file status flags
     pub const F_SETFL: i32 = 4;
+    /// Find the first record lock that would block a lock
+    pub const F_GETLK: i32 = 5;
+    /// Set or clear a record lock, failing with EAGAIN on conflict
+    pub const F_SETLK: i32 = 6;
+    /// Set or clear a record lock, waiting for conflicting locks
+    pub const F_SETLKW: i32 = 7;
+    /// F_GETLK for open file description locks
+    pub const F_OFD_GETLK: i32 = 36;
+    /// F_SETLK for open file description locks
+    pub const F_OFD_SETLK: i32 = 37;
+    /// F_SETLKW for open file description locks
+    pub const F_OFD_SETLKW: i32 = 38;
     /// Duplicate fd with close-on-exec flag set
     pub const F_DUPFD_CLOEXEC: i32 = 1030;
 }
@@ -471,3 +483,89 @@ pub fn select(
     };
     Error::from_syscall(ret as i64).map(|v| v as usize)
 }
+
+/// splice() and tee() flags
+pub mod splice_flags {
+    /// Hint: move pages instead of copying
+    pub const SPLICE_F_MOVE: u32 = 1;
+    /// Don't block on pipe I/O
+    pub const SPLICE_F_NONBLOCK: u32 = 2;
+    /// Hint: more data will follow
+    pub const SPLICE_F_MORE: u32 = 4;
+    /// Hint: pages are gifted to the kernel (vmsplice only)
+    pub const SPLICE_F_GIFT: u32 = 8;
+}
+
+/// Copy data from a file to another descriptor without a userspace buffer.
+///
+/// # Arguments
+/// * `out_fd` - Destination (socket, pipe, file, or tty)
+/// * `in_fd` - Source regular file
+/// * `offset` - If `Some`, read from this offset and update it, leaving the
+///   file position of `in_fd` unchanged; if `None`, use and advance the file position
+/// * `count` - Maximum number of bytes to copy
+///
+/// # Returns
+/// Number of bytes copied (0 at end of file).
+#[inline]
+pub fn sendfile(
+    out_fd: Fd,
+    in_fd: Fd,
+    offset: Option<&mut i64>,
+    count: usize,
+) -> Result<usize, Error> {
+    let offset_ptr = offset.map(|p| p as *mut i64 as u64).unwrap_or(0);
+    let ret = unsafe {
+        raw::syscall4(
+            nr::SENDFILE,
+            out_fd.raw(),
+            in_fd.raw(),
+            offset_ptr,
+            count as u64,
+        )
+    };
+    Error::from_syscall(ret as i64).map(|v| v as usize)
+}
+
+/// Move data between a pipe and another descriptor.
+///
+/// One of `fd_in` or `fd_out` must be a pipe. Offsets may only be given for
+/// regular files; they are updated and the file position is left unchanged.
+///
+/// # Returns
+/// Number of bytes moved (0 at end of input).
+#[inline]
+pub fn splice(
+    fd_in: Fd,
+    off_in: Option<&mut i64>,
+    fd_out: Fd,
+    off_out: Option<&mut i64>,
+    len: usize,
+    flags: u32,
+) -> Result<usize, Error> {
+    let off_in_ptr = off_in.map(|p| p as *mut i64 as u64).unwrap_or(0);
+    let off_out_ptr = off_out.map(|p| p as *mut i64 as u64).unwrap_or(0);
+    let ret = unsafe {
+        raw::syscall6(
+            nr::SPLICE,
+            fd_in.raw(),
+            off_in_ptr,
+            fd_out.raw(),
+            off_out_ptr,
+            len as u64,
+            flags as u64,
+        )
+    };
+    Error::from_syscall(ret as i64).map(|v| v as usize)
+}
+
+/// Duplicate up to `len` bytes from one pipe into another without consuming them.
+///
+/// # Returns
+/// Number of bytes duplicated.
+#[inline]
+pub fn tee(fd_in: Fd, fd_out: Fd, len: usize, flags: u32) -> Result<usize, Error> {
+    let ret =
+        unsafe { raw::syscall4(nr::TEE, fd_in.raw(), fd_out.raw(), len as u64, flags as u64) };
+    Error::from_syscall(ret as i64).map(|v| v as usize)
+}
@@ -50,6 +50,7 @@ pub mod http;
 pub mod io;
 pub mod memory;
 pub mod process;
+pub mod ptrace;
 pub mod pty;
 #[cfg(feature = "runtime")]
 pub mod runtime;
@@ -69,6 +70,6 @@ pub mod x509;
 
 // Re-export commonly used signal functions
 pub use signal::{
-    alarm, getitimer, kill, setitimer, sigaction, sigaltstack, sigpending, sigprocmask,
-    sigsuspend, Itimerval, Sigaction, StackT, Timeval,
+    alarm, getitimer, kill, setitimer, sigaction, sigaltstack, sigpending, sigprocmask, sigqueue,
+    sigsuspend, sigtimedwait, sigwaitinfo, tgkill, tkill, Itimerval, Sigaction, StackT, Timeval,
 };
@@ -154,6 +154,12 @@ pub fn getpid() -> Result<Pid, Error> {
     Error::from_syscall(ret as i64).map(Pid::from_raw)
 }
 
+/// Get the real user ID of the current process.
+#[inline]
+pub fn getuid() -> u32 {
+    unsafe { raw::syscall0(nr::GETUID) as u32 }
+}
+
 /// Get the current thread ID.
 #[inline]
 pub fn gettid() -> Result<Tid, Error> {
@@ -173,8 +179,8 @@ pub fn yield_now() -> Result<(), Error> {
 
 /// waitpid options
 pub const WNOHANG: i32 = 1;
-#[allow(dead_code)]
 pub const WUNTRACED: i32 = 2;
+pub const WCONTINUED: i32 = 8;
 
 /// Wait for a child process to change state.
 ///
@@ -198,6 +204,59 @@ pub fn waitpid(pid: i32, status: *mut i32, options: i32) -> Result<Pid, Error> {
     Error::from_syscall(ret as i64).map(Pid::from_raw)
 }
 
+/// Wait for a child process like [`waitpid`], also returning the resource
+/// usage of a child that exited, including the children it reaped.
+#[inline]
+pub fn wait4(
+    pid: i32,
+    status: *mut i32,
+    options: i32,
+    rusage: Option<&mut Rusage>,
+) -> Result<Pid, Error> {
+    let rusage_ptr = rusage.map_or(0, |rusage| rusage as *mut Rusage as u64);
+    let ret = unsafe {
+        raw::syscall4(nr::WAIT4, pid as u64, status as u64, options as u64, rusage_ptr)
+    };
+    Error::from_syscall(ret as i64).map(Pid::from_raw)
+}
+
+/// waitid() id types
+pub const P_ALL: i32 = 0;
+pub const P_PID: i32 = 1;
+pub const P_PGID: i32 = 2;
+
+/// waitid() options (WNOHANG and WCONTINUED as for waitpid)
+pub const WSTOPPED: i32 = 2;
+pub const WEXITED: i32 = 4;
+pub const WNOWAIT: i32 = 0x0100_0000;
+
+/// Wait for a child to change state, selected by `idtype` and `id`.
+///
+/// The child is reported in `info` as a SIGCHLD siginfo (si_pid, si_code
+/// CLD_*, si_status). With WNOHANG and no child ready, `info.si_pid()` is 0.
+/// With WNOWAIT the child is left in a waitable state.
+#[inline]
+pub fn waitid(
+    idtype: i32,
+    id: i32,
+    info: &mut crate::signal::Siginfo,
+    options: i32,
+    rusage: Option<&mut Rusage>,
+) -> Result<(), Error> {
+    let rusage_ptr = rusage.map_or(0, |rusage| rusage as *mut Rusage as u64);
+    let ret = unsafe {
+        raw::syscall5(
+            nr::WAITID,
+            idtype as u64,
+            id as u64,
+            info as *mut crate::signal::Siginfo as u64,
+            options as u64,
+            rusage_ptr,
+        )
+    };
+    Error::from_syscall(ret as i64).map(|_| ())
+}
+
 /// Macros for extracting information from waitpid status
 ///
 /// Check if child exited normally (via exit() or return from main)
@@ -227,6 +286,12 @@ pub fn wtermsig(status: i32) -> i32 {
     status & 0x7f
 }
 
+/// Check if the signal that terminated the child wrote a core file
+#[inline]
+pub fn wcoredump(status: i32) -> bool {
+    (status & 0x80) != 0
+}
+
 /// Check if child was stopped by a signal (job control)
 #[inline]
 pub fn wifstopped(status: i32) -> bool {
@@ -240,6 +305,12 @@ pub fn wstopsig(status: i32) -> i32 {
     (status >> 8) & 0xff
 }
 
+/// Check if child was continued by SIGCONT (only reported with WCONTINUED)
+#[inline]
+pub fn wifcontinued(status: i32) -> bool {
+    status == 0xffff
+}
+
 /// Set the process group ID for a process.
 ///
 /// # Arguments
@@ -376,3 +447,280 @@ pub fn chdir(path: &[u8]) -> Result<(), Error> {
     let ret = unsafe { raw::syscall1(nr::CHDIR, path.as_ptr() as u64) };
     Error::from_syscall(ret as i64).map(|_| ())
 }
+
+/// Resource limit: largest core file written when a signal dumps core
+pub const RLIMIT_CORE: i32 = 4;
+/// Resource limit: maximum stack size
+pub const RLIMIT_STACK: i32 = 3;
+/// Resource limit: maximum number of open file descriptors
+pub const RLIMIT_NOFILE: i32 = 7;
+/// No limit
+pub const RLIM_INFINITY: u64 = u64::MAX;
+
+/// Soft and hard limit for one resource (matches Linux struct rlimit)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rlimit {
+    pub rlim_cur: u64,
+    pub rlim_max: u64,
+}
+
+/// Get and/or set a resource limit of a process.
+///
+/// # Arguments
+/// * `pid` - Target process, 0 for the calling process
+/// * `resource` - One of the `RLIMIT_*` constants
+/// * `new` - New limit to install, or None to only query
+///
+/// # Returns
+/// * The limit in effect before the call
+///
+/// # Errors
+/// * EINVAL - The new soft limit exceeds the new hard limit
+/// * ESRCH - No process with that pid
+#[inline]
+pub fn prlimit(pid: i32, resource: i32, new: Option<&Rlimit>) -> Result<Rlimit, Error> {
+    let mut old = Rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    let new_ptr = new.map_or(0, |rlim| rlim as *const Rlimit as u64);
+    let ret = unsafe {
+        raw::syscall4(
+            nr::PRLIMIT64,
+            pid as u64,
+            resource as u64,
+            new_ptr,
+            &mut old as *mut Rlimit as u64,
+        )
+    };
+    Error::from_syscall(ret as i64).map(|_| old)
+}
+
+/// Get a resource limit of the calling process.
+#[inline]
+pub fn getrlimit(resource: i32) -> Result<Rlimit, Error> {
+    prlimit(0, resource, None)
+}
+
+/// Set a resource limit of the calling process.
+#[inline]
+pub fn setrlimit(resource: i32, rlim: &Rlimit) -> Result<(), Error> {
+    prlimit(0, resource, Some(rlim)).map(|_| ())
+}
+
+/// getrusage() targets
+pub const RUSAGE_SELF: i32 = 0;
+pub const RUSAGE_CHILDREN: i32 = -1;
+pub const RUSAGE_THREAD: i32 = 1;
+
+/// Resource usage (matches Linux struct rusage)
+///
+/// Breenix fills in the CPU times, max RSS, page faults and context
+/// switches; the other fields are always 0.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct Rusage {
+    /// User CPU time
+    pub ru_utime: crate::signal::Timeval,
+    /// System CPU time
+    pub ru_stime: crate::signal::Timeval,
+    /// Maximum resident set size, in kilobytes
+    pub ru_maxrss: i64,
+    pub ru_ixrss: i64,
+    pub ru_idrss: i64,
+    pub ru_isrss: i64,
+    /// Page faults serviced without I/O
+    pub ru_minflt: i64,
+    /// Page faults that needed I/O
+    pub ru_majflt: i64,
+    pub ru_nswap: i64,
+    pub ru_inblock: i64,
+    pub ru_oublock: i64,
+    pub ru_msgsnd: i64,
+    pub ru_msgrcv: i64,
+    pub ru_nsignals: i64,
+    /// Voluntary context switches
+    pub ru_nvcsw: i64,
+    /// Involuntary context switches
+    pub ru_nivcsw: i64,
+}
+
+/// Get the resource usage of the calling process, its reaped children or
+/// the calling thread (`RUSAGE_*`).
+#[inline]
+pub fn getrusage(who: i32) -> Result<Rusage, Error> {
+    let mut usage = Rusage::default();
+    let ret = unsafe {
+        raw::syscall2(nr::GETRUSAGE, who as i64 as u64, &mut usage as *mut Rusage as u64)
+    };
+    Error::from_syscall(ret as i64).map(|_| usage)
+}
+
+/// Clock ticks per second of [`times`]
+pub const CLOCK_TICKS_PER_SEC: u64 = 100;
+
+/// CPU times in clock ticks (matches Linux struct tms)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tms {
+    pub tms_utime: i64,
+    pub tms_stime: i64,
+    /// User time of reaped children
+    pub tms_cutime: i64,
+    /// System time of reaped children
+    pub tms_cstime: i64,
+}
+
+/// Get the CPU times of the calling process and its reaped children.
+///
+/// Returns the times and the clock ticks elapsed since boot.
+#[inline]
+pub fn times() -> Result<(Tms, u64), Error> {
+    let mut tms = Tms::default();
+    let ret = unsafe { raw::syscall1(nr::TIMES, &mut tms as *mut Tms as u64) };
+    Error::from_syscall(ret as i64).map(|ticks| (tms, ticks))
+}
+
+// =============================================================================
+// Scheduling priorities and CPU affinity
+// =============================================================================
+
+/// getpriority()/setpriority() targets
+pub const PRIO_PROCESS: i32 = 0;
+pub const PRIO_PGRP: i32 = 1;
+pub const PRIO_USER: i32 = 2;
+
+/// Scheduling policies
+pub const SCHED_OTHER: i32 = 0;
+pub const SCHED_FIFO: i32 = 1;
+pub const SCHED_RR: i32 = 2;
+
+/// Scheduling parameters (matches Linux struct sched_param)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SchedParam {
+    /// Real-time priority, 1 to 99 (0 for SCHED_OTHER)
+    pub sched_priority: i32,
+}
+
+/// Get the lowest nice level of the processes selected by `which` and `who`
+/// (0 for the caller).
+#[inline]
+pub fn getpriority(which: i32, who: u32) -> Result<i32, Error> {
+    let ret = unsafe { raw::syscall2(nr::GETPRIORITY, which as u64, who as u64) };
+    // The kernel returns 20 - nice so that success is never negative
+    Error::from_syscall(ret as i64).map(|prio| 20 - prio as i32)
+}
+
+/// Set the nice level (-20 to 19) of the processes selected by `which` and
+/// `who` (0 for the caller).
+#[inline]
+pub fn setpriority(which: i32, who: u32, nice: i32) -> Result<(), Error> {
+    let ret = unsafe {
+        raw::syscall3(nr::SETPRIORITY, which as u64, who as u64, nice as i64 as u64)
+    };
+    Error::from_syscall(ret as i64).map(|_| ())
+}
+
+/// Add `inc` to the caller's nice level and return the new level.
+#[inline]
+pub fn nice(inc: i32) -> Result<i32, Error> {
+    let current = getpriority(PRIO_PROCESS, 0)?;
+    setpriority(PRIO_PROCESS, 0, current + inc)?;
+    getpriority(PRIO_PROCESS, 0)
+}
+
+/// Set the scheduling policy (`SCHED_*`) and priority of `pid` (0 for the
+/// caller).
+#[inline]
+pub fn sched_setscheduler(pid: i32, policy: i32, param: &SchedParam) -> Result<(), Error> {
+    let ret = unsafe {
+        raw::syscall3(
+            nr::SCHED_SETSCHEDULER,
+            pid as u64,
+            policy as u64,
+            param as *const SchedParam as u64,
+        )
+    };
+    Error::from_syscall(ret as i64).map(|_| ())
+}
+
+/// Get the scheduling policy of `pid` (0 for the caller).
+#[inline]
+pub fn sched_getscheduler(pid: i32) -> Result<i32, Error> {
+    let ret = unsafe { raw::syscall1(nr::SCHED_GETSCHEDULER, pid as u64) };
+    Error::from_syscall(ret as i64).map(|policy| policy as i32)
+}
+
+/// Set the real-time priority of `pid` (0 for the caller) without changing
+/// its policy.
+#[inline]
+pub fn sched_setparam(pid: i32, param: &SchedParam) -> Result<(), Error> {
+    let ret = unsafe {
+        raw::syscall2(nr::SCHED_SETPARAM, pid as u64, param as *const SchedParam as u64)
+    };
+    Error::from_syscall(ret as i64).map(|_| ())
+}
+
+/// Get the scheduling parameters of `pid` (0 for the caller).
+#[inline]
+pub fn sched_getparam(pid: i32) -> Result<SchedParam, Error> {
+    let mut param = SchedParam::default();
+    let ret = unsafe {
+        raw::syscall2(nr::SCHED_GETPARAM, pid as u64, &mut param as *mut SchedParam as u64)
+    };
+    Error::from_syscall(ret as i64).map(|_| param)
+}
+
+/// Highest priority of a scheduling policy.
+#[inline]
+pub fn sched_get_priority_max(policy: i32) -> Result<i32, Error> {
+    let ret = unsafe { raw::syscall1(nr::SCHED_GET_PRIORITY_MAX, policy as u64) };
+    Error::from_syscall(ret as i64).map(|prio| prio as i32)
+}
+
+/// Lowest priority of a scheduling policy.
+#[inline]
+pub fn sched_get_priority_min(policy: i32) -> Result<i32, Error> {
+    let ret = unsafe { raw::syscall1(nr::SCHED_GET_PRIORITY_MIN, policy as u64) };
+    Error::from_syscall(ret as i64).map(|prio| prio as i32)
+}
+
+/// Set the CPUs task `pid` (0 for the caller) may run on, one bit per CPU.
+#[inline]
+pub fn sched_setaffinity(pid: i32, mask: u64) -> Result<(), Error> {
+    let ret = unsafe {
+        raw::syscall3(
+            nr::SCHED_SETAFFINITY,
+            pid as u64,
+            core::mem::size_of::<u64>() as u64,
+            &mask as *const u64 as u64,
+        )
+    };
+    Error::from_syscall(ret as i64).map(|_| ())
+}
+
+/// Get the online CPUs task `pid` (0 for the caller) may run on, one bit per
+/// CPU.
+#[inline]
+pub fn sched_getaffinity(pid: i32) -> Result<u64, Error> {
+    let mut mask: u64 = 0;
+    let ret = unsafe {
+        raw::syscall3(
+            nr::SCHED_GETAFFINITY,
+            pid as u64,
+            core::mem::size_of::<u64>() as u64,
+            &mut mask as *mut u64 as u64,
+        )
+    };
+    Error::from_syscall(ret as i64).map(|_| mask)
+}
+
+/// Get the CPU the caller is running on.
+#[inline]
+pub fn sched_getcpu() -> Result<u32, Error> {
+    let mut cpu: u32 = 0;
+    let ret = unsafe { raw::syscall3(nr::GETCPU, &mut cpu as *mut u32 as u64, 0, 0) };
+    Error::from_syscall(ret as i64).map(|_| cpu)
+}
@@ -0,0 +1,202 @@
+//! Process tracing (ptrace) syscall wrappers
+//!
+//! A tracer forks a child that calls [`traceme`], or attaches to a running
+//! process with [`attach`]. The tracee then stops before acting on each
+//! signal; the tracer collects the stop with waitpid() (`wifstopped`), reads
+//! and writes the tracee's memory and registers, and resumes it with [`cont`],
+//! [`syscall`] or [`single_step`].
+
+use crate::error::Error;
+use crate::syscall::{nr, raw};
+
+/// ptrace requests (Linux values)
+pub const PTRACE_TRACEME: u64 = 0;
+pub const PTRACE_PEEKTEXT: u64 = 1;
+pub const PTRACE_PEEKDATA: u64 = 2;
+pub const PTRACE_POKETEXT: u64 = 4;
+pub const PTRACE_POKEDATA: u64 = 5;
+pub const PTRACE_CONT: u64 = 7;
+pub const PTRACE_SINGLESTEP: u64 = 9;
+pub const PTRACE_GETREGS: u64 = 12;
+pub const PTRACE_SETREGS: u64 = 13;
+pub const PTRACE_ATTACH: u64 = 16;
+pub const PTRACE_DETACH: u64 = 17;
+pub const PTRACE_SYSCALL: u64 = 24;
+pub const PTRACE_SETOPTIONS: u64 = 0x4200;
+
+/// Report syscall stops as `SIGTRAP | 0x80` so they can be told from real SIGTRAPs
+pub const PTRACE_O_TRACESYSGOOD: u64 = 1;
+
+/// General registers of a stopped tracee (matches Linux struct user_regs_struct)
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserRegs {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub rbp: u64,
+    pub rbx: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rax: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    /// Syscall number at a syscall stop
+    pub orig_rax: u64,
+    pub rip: u64,
+    pub cs: u64,
+    pub eflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+    pub fs_base: u64,
+    pub gs_base: u64,
+    pub ds: u64,
+    pub es: u64,
+    pub fs: u64,
+    pub gs: u64,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl UserRegs {
+    /// Instruction pointer
+    pub fn pc(&self) -> u64 {
+        self.rip
+    }
+
+    /// Syscall number at a syscall stop
+    pub fn syscall_number(&self) -> u64 {
+        self.orig_rax
+    }
+
+    /// Syscall return value at a syscall-exit stop
+    pub fn return_value(&self) -> u64 {
+        self.rax
+    }
+}
+
+/// General registers of a stopped tracee (matches Linux struct user_pt_regs)
+#[cfg(target_arch = "aarch64")]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserRegs {
+    pub regs: [u64; 31],
+    pub sp: u64,
+    pub pc: u64,
+    pub pstate: u64,
+}
+
+#[cfg(target_arch = "aarch64")]
+impl UserRegs {
+    /// Program counter
+    pub fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    /// Syscall number at a syscall stop
+    pub fn syscall_number(&self) -> u64 {
+        self.regs[8]
+    }
+
+    /// Syscall return value at a syscall-exit stop
+    pub fn return_value(&self) -> u64 {
+        self.regs[0]
+    }
+}
+
+#[inline]
+fn ptrace(request: u64, pid: i32, addr: u64, data: u64) -> Result<u64, Error> {
+    let ret = unsafe { raw::syscall4(nr::PTRACE, request, pid as u64, addr, data) };
+    Error::from_syscall(ret as i64)
+}
+
+/// Let the parent trace the calling process.
+///
+/// # Errors
+/// * EPERM - Already traced, or no parent
+#[inline]
+pub fn traceme() -> Result<(), Error> {
+    ptrace(PTRACE_TRACEME, 0, 0, 0).map(|_| ())
+}
+
+/// Trace process `pid`, which is sent SIGSTOP.
+///
+/// # Errors
+/// * EPERM - `pid` is the caller, is already traced, or belongs to another user
+/// * ESRCH - No process with that pid
+#[inline]
+pub fn attach(pid: i32) -> Result<(), Error> {
+    ptrace(PTRACE_ATTACH, pid, 0, 0).map(|_| ())
+}
+
+/// Stop tracing `pid`, resuming it with signal `sig` (0 for none).
+#[inline]
+pub fn detach(pid: i32, sig: i32) -> Result<(), Error> {
+    ptrace(PTRACE_DETACH, pid, 0, sig as u64).map(|_| ())
+}
+
+/// Read one word of the stopped tracee's memory.
+///
+/// # Errors
+/// * EIO - `addr` is not mapped in the tracee
+/// * ESRCH - `pid` is not a stopped tracee of the caller
+#[inline]
+pub fn peek_data(pid: i32, addr: u64) -> Result<u64, Error> {
+    let mut word: u64 = 0;
+    ptrace(PTRACE_PEEKDATA, pid, addr, &mut word as *mut u64 as u64).map(|_| word)
+}
+
+/// Write one word of the stopped tracee's memory, read-only text included.
+///
+/// # Errors
+/// * EIO - `addr` is not mapped in the tracee
+/// * ESRCH - `pid` is not a stopped tracee of the caller
+#[inline]
+pub fn poke_data(pid: i32, addr: u64, word: u64) -> Result<(), Error> {
+    ptrace(PTRACE_POKEDATA, pid, addr, word).map(|_| ())
+}
+
+/// Read the stopped tracee's general registers.
+#[inline]
+pub fn get_regs(pid: i32) -> Result<UserRegs, Error> {
+    let mut regs = UserRegs::default();
+    ptrace(PTRACE_GETREGS, pid, 0, &mut regs as *mut UserRegs as u64).map(|_| regs)
+}
+
+/// Replace the stopped tracee's general registers.
+///
+/// # Errors
+/// * EIO - The program counter or stack pointer is not a user address
+#[inline]
+pub fn set_regs(pid: i32, regs: &UserRegs) -> Result<(), Error> {
+    ptrace(PTRACE_SETREGS, pid, 0, regs as *const UserRegs as u64).map(|_| ())
+}
+
+/// Resume the stopped tracee, delivering signal `sig` (0 for none).
+#[inline]
+pub fn cont(pid: i32, sig: i32) -> Result<(), Error> {
+    ptrace(PTRACE_CONT, pid, 0, sig as u64).map(|_| ())
+}
+
+/// Resume the stopped tracee until its next syscall entry or exit.
+#[inline]
+pub fn syscall(pid: i32, sig: i32) -> Result<(), Error> {
+    ptrace(PTRACE_SYSCALL, pid, 0, sig as u64).map(|_| ())
+}
+
+/// Resume the stopped tracee for a single instruction.
+#[inline]
+pub fn single_step(pid: i32, sig: i32) -> Result<(), Error> {
+    ptrace(PTRACE_SINGLESTEP, pid, 0, sig as u64).map(|_| ())
+}
+
+/// Set `PTRACE_O_*` options on the stopped tracee.
+#[inline]
+pub fn set_options(pid: i32, options: u64) -> Result<(), Error> {
+    ptrace(PTRACE_SETOPTIONS, pid, 0, options).map(|_| ())
+}
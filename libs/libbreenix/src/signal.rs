@@ -6,6 +6,7 @@
 
 use crate::error::Error;
 use crate::syscall::raw;
+use crate::types::Timespec;
 
 use crate::syscall::nr;
 
@@ -42,6 +43,10 @@ pub const SIGIO: i32 = 29;
 pub const SIGPWR: i32 = 30;
 pub const SIGSYS: i32 = 31;
 
+// Real-time signals: queued with a value, delivered lowest number first
+pub const SIGRTMIN: i32 = 32;
+pub const SIGRTMAX: i32 = 64;
+
 // Signal handler special values
 pub const SIG_DFL: u64 = 0;
 pub const SIG_IGN: u64 = 1;
@@ -52,12 +57,26 @@ pub const SIG_UNBLOCK: i32 = 1;
 pub const SIG_SETMASK: i32 = 2;
 
 // sigaction flags
+pub const SA_NOCLDSTOP: u64 = 0x00000001;
 pub const SA_RESTART: u64 = 0x10000000;
 pub const SA_NODEFER: u64 = 0x40000000;
 pub const SA_SIGINFO: u64 = 0x00000004;
 pub const SA_ONSTACK: u64 = 0x08000000;
 pub const SA_RESTORER: u64 = 0x04000000;
 
+// siginfo si_code values
+pub const SI_USER: i32 = 0;
+pub const SI_KERNEL: i32 = 0x80;
+pub const SI_QUEUE: i32 = -1;
+pub const SI_TKILL: i32 = -6;
+pub const SEGV_MAPERR: i32 = 1;
+pub const SEGV_ACCERR: i32 = 2;
+pub const CLD_EXITED: i32 = 1;
+pub const CLD_KILLED: i32 = 2;
+pub const CLD_DUMPED: i32 = 3;
+pub const CLD_STOPPED: i32 = 5;
+pub const CLD_CONTINUED: i32 = 6;
+
 // sigaltstack flags
 pub const SS_ONSTACK: i32 = 1;
 pub const SS_DISABLE: i32 = 2;
@@ -143,6 +162,19 @@ impl Sigaction {
         }
     }
 
+    /// Create a signal action for an SA_SIGINFO handler
+    ///
+    /// The handler receives the signal number, the signal's `Siginfo` and the
+    /// interrupted `Ucontext`. Like `new()`, this uses the libbreenix restorer.
+    pub fn with_siginfo(handler: extern "C" fn(i32, *mut Siginfo, *mut Ucontext)) -> Self {
+        Sigaction {
+            handler: handler as u64,
+            mask: 0,
+            flags: SA_SIGINFO | SA_RESTORER,
+            restorer: __restore_rt as u64,
+        }
+    }
+
     /// Create a new signal action without a restorer
     ///
     /// This should only be used when signals will only be delivered on the
@@ -218,6 +250,150 @@ impl Default for StackT {
     }
 }
 
+/// Signal information passed to SA_SIGINFO handlers
+/// Note: This must match the kernel's siginfo_t layout exactly (128 bytes)
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct Siginfo {
+    /// Signal number
+    pub si_signo: i32,
+    /// Error number (always 0)
+    pub si_errno: i32,
+    /// Signal origin (SI_USER, SI_QUEUE, SI_TKILL, SI_KERNEL, SEGV_*, CLD_*)
+    pub si_code: i32,
+    _pad: i32,
+    /// The `_sifields` union; use the accessors below
+    pub fields: [u64; 14],
+}
+
+impl Siginfo {
+    /// Sending process (kill, sigqueue, tkill) or child process (SIGCHLD)
+    pub fn si_pid(&self) -> i32 {
+        self.fields[0] as u32 as i32
+    }
+
+    /// Real user ID of the sending or child process
+    pub fn si_uid(&self) -> u32 {
+        (self.fields[0] >> 32) as u32
+    }
+
+    /// Faulting address (SIGSEGV, SIGBUS)
+    pub fn si_addr(&self) -> u64 {
+        self.fields[0]
+    }
+
+    /// Exit status or terminating signal (SIGCHLD)
+    pub fn si_status(&self) -> i32 {
+        self.fields[1] as u32 as i32
+    }
+
+    /// Value passed to sigqueue() (the sigval union)
+    pub fn si_value(&self) -> u64 {
+        self.fields[1]
+    }
+}
+
+/// Machine context saved at signal delivery (Linux struct sigcontext layout)
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Mcontext {
+    /// r8..r15, rdi, rsi, rbp, rbx, rdx, rax, rcx, rsp, rip, eflags, in glibc REG_* order
+    pub gregs: [u64; 18],
+    /// cs, gs, fs, ss
+    pub segments: [u16; 4],
+    pub err: u64,
+    pub trapno: u64,
+    pub oldmask: u64,
+    pub cr2: u64,
+    pub fpstate: u64,
+    pub reserved: [u64; 8],
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Mcontext {
+    const REG_RSP: usize = 15;
+    const REG_RIP: usize = 16;
+
+    /// Program counter to resume at
+    pub fn pc(&self) -> u64 {
+        self.gregs[Self::REG_RIP]
+    }
+
+    /// Change where the interrupted code resumes after the handler returns
+    pub fn set_pc(&mut self, pc: u64) {
+        self.gregs[Self::REG_RIP] = pc;
+    }
+
+    /// Interrupted stack pointer
+    pub fn sp(&self) -> u64 {
+        self.gregs[Self::REG_RSP]
+    }
+}
+
+/// Machine context saved at signal delivery (Linux struct sigcontext layout)
+#[cfg(target_arch = "aarch64")]
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+pub struct Mcontext {
+    pub fault_address: u64,
+    pub regs: [u64; 31],
+    pub sp: u64,
+    pub pc: u64,
+    pub pstate: u64,
+    _pad: u64,
+    pub reserved: [u64; 2],
+}
+
+#[cfg(target_arch = "aarch64")]
+impl Mcontext {
+    /// Program counter to resume at
+    pub fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    /// Change where the interrupted code resumes after the handler returns
+    pub fn set_pc(&mut self, pc: u64) {
+        self.pc = pc;
+    }
+
+    /// Interrupted stack pointer
+    pub fn sp(&self) -> u64 {
+        self.sp
+    }
+}
+
+/// User context passed to SA_SIGINFO handlers
+///
+/// Changes made to `uc_mcontext` and `uc_sigmask` take effect when the
+/// handler returns.
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Ucontext {
+    pub uc_flags: u64,
+    pub uc_link: u64,
+    pub uc_stack: StackT,
+    pub uc_mcontext: Mcontext,
+    pub uc_sigmask: u64,
+}
+
+/// User context passed to SA_SIGINFO handlers
+///
+/// Changes made to `uc_mcontext` and `uc_sigmask` take effect when the
+/// handler returns.
+#[cfg(target_arch = "aarch64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Ucontext {
+    pub uc_flags: u64,
+    pub uc_link: u64,
+    pub uc_stack: StackT,
+    pub uc_sigmask: u64,
+    _unused: [u8; 120],
+    pub uc_mcontext: Mcontext,
+}
+
 /// Send a signal to a process
 ///
 /// # Arguments
@@ -243,6 +419,52 @@ pub fn kill(pid: i32, sig: i32) -> Result<(), Error> {
     Error::from_syscall(ret as i64).map(|_| ())
 }
 
+/// Queue a signal with a value
+///
+/// Real-time signals (SIGRTMIN..=SIGRTMAX) queue every instance, each with
+/// its own value, up to the kernel's per-process limit. Standard signals
+/// still collapse into one pending instance.
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(Error)` with EAGAIN if the real-time signal queue is full
+pub fn sigqueue(pid: i32, sig: i32, value: u64) -> Result<(), Error> {
+    let mut info = Siginfo {
+        si_signo: sig,
+        si_code: SI_QUEUE,
+        ..Siginfo::default()
+    };
+    let sender = crate::process::getpid().map_or(0, |p| p.raw());
+    info.fields[0] = (sender as u32 as u64) | ((crate::process::getuid() as u64) << 32);
+    info.fields[1] = value;
+
+    let ret = unsafe {
+        raw::syscall3(
+            nr::RT_SIGQUEUEINFO,
+            pid as u64,
+            sig as u64,
+            &info as *const _ as u64,
+        )
+    };
+    Error::from_syscall(ret as i64).map(|_| ())
+}
+
+/// Send a signal to a single thread
+///
+/// # Arguments
+/// * `tid` - Thread ID, as returned by gettid()
+/// * `sig` - Signal number, or 0 to check that the thread exists
+pub fn tkill(tid: i32, sig: i32) -> Result<(), Error> {
+    let ret = unsafe { raw::syscall2(nr::TKILL, tid as u64, sig as u64) };
+    Error::from_syscall(ret as i64).map(|_| ())
+}
+
+/// Send a signal to a thread, checking that it belongs to thread group `tgid`
+pub fn tgkill(tgid: i32, tid: i32, sig: i32) -> Result<(), Error> {
+    let ret = unsafe { raw::syscall3(nr::TGKILL, tgid as u64, tid as u64, sig as u64) };
+    Error::from_syscall(ret as i64).map(|_| ())
+}
+
 /// Set signal handler
 ///
 /// # Arguments
@@ -448,6 +670,41 @@ pub fn sigsuspend(mask: &u64) -> Result<(), Error> {
     Error::from_syscall(ret as i64).map(|_| ())
 }
 
+/// Wait for a signal in `set`, with an optional timeout
+///
+/// Accepts the lowest-numbered pending signal in `set` without running its
+/// handler; the signals should normally be blocked with sigprocmask() first.
+/// A `timeout` of None waits indefinitely; a zero timeout polls.
+///
+/// # Returns
+/// * `Ok(sig)` - the accepted signal, with its siginfo stored in `info`
+/// * `Err(Error)` with EAGAIN on timeout, or EINTR if another signal's
+///   handler ran
+pub fn sigtimedwait(
+    set: &u64,
+    info: Option<&mut Siginfo>,
+    timeout: Option<&Timespec>,
+) -> Result<i32, Error> {
+    let info_ptr = info.map_or(0, |i| i as *mut _ as u64);
+    let timeout_ptr = timeout.map_or(0, |t| t as *const _ as u64);
+
+    let ret = unsafe {
+        raw::syscall4(
+            nr::RT_SIGTIMEDWAIT,
+            set as *const u64 as u64,
+            info_ptr,
+            timeout_ptr,
+            8,
+        )
+    };
+    Error::from_syscall(ret as i64).map(|sig| sig as i32)
+}
+
+/// Wait indefinitely for a signal in `set`
+pub fn sigwaitinfo(set: &u64, info: Option<&mut Siginfo>) -> Result<i32, Error> {
+    sigtimedwait(set, info, None)
+}
+
 /// Set or get the alternate signal stack
 ///
 /// # Arguments
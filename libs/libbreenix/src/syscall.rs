@@ -80,23 +80,29 @@ pub mod nr {
     pub const UNLINK: u64 = 87;
     pub const SYMLINK: u64 = 88;
     pub const READLINK: u64 = 89;
+    pub const PTRACE: u64 = 101;
+    pub const GETUID: u64 = 102;
     pub const SETPGID: u64 = 109;
     pub const GETPPID: u64 = 110;
     pub const SETSID: u64 = 112;
     pub const GETPGID: u64 = 121;
     pub const GETSID: u64 = 124;
     pub const SIGPENDING: u64 = 127;
+    pub const RT_SIGTIMEDWAIT: u64 = 128;
+    pub const RT_SIGQUEUEINFO: u64 = 129;
     pub const SIGSUSPEND: u64 = 130;
     pub const SIGALTSTACK: u64 = 131;
     pub const MKNOD: u64 = 133;
     pub const ARCH_PRCTL: u64 = 158;
     pub const GETTID: u64 = 186;
+    pub const TKILL: u64 = 200;
     pub const FUTEX: u64 = 202;
     pub const GETDENTS64: u64 = 217;
     pub const SET_TID_ADDRESS: u64 = 218;
     pub const CLOCK_SETTIME: u64 = 227;
     pub const CLOCK_GETTIME: u64 = 228;
     pub const EXIT_GROUP: u64 = 231;
+    pub const TGKILL: u64 = 234;
     pub const OPEN: u64 = 2;           // Linux x86_64 open
     pub const NEWFSTATAT: u64 = 262;
     pub const OPENAT: u64 = 257;
@@ -114,6 +120,20 @@ pub mod nr {
     pub const DUP3: u64 = 292;
     pub const PIPE2: u64 = 293;
     pub const GETRANDOM: u64 = 318;
+    // File size and durability
+    pub const FSYNC: u64 = 74;
+    pub const FDATASYNC: u64 = 75;
+    pub const TRUNCATE: u64 = 76;
+    pub const FTRUNCATE: u64 = 77;
+    pub const FALLOCATE: u64 = 285;
+    // Filesystem statistics
+    pub const STATFS: u64 = 137;
+    pub const FSTATFS: u64 = 138;
+    pub const FLOCK: u64 = 73;
+    // In-kernel data transfer
+    pub const SENDFILE: u64 = 40;
+    pub const SPLICE: u64 = 275;
+    pub const TEE: u64 = 276;
     // PTY syscalls (Breenix-specific, same on both architectures)
     pub const POSIX_OPENPT: u64 = 400;
     pub const GRANTPT: u64 = 401;
@@ -134,6 +154,22 @@ pub mod nr {
     pub const GETRLIMIT: u64 = 97;
     pub const PRLIMIT64: u64 = 302;
     pub const UNAME: u64 = 63;
+    // Resource usage
+    pub const GETRUSAGE: u64 = 98;
+    pub const TIMES: u64 = 100;
+    pub const WAITID: u64 = 247;
+    // Scheduling priorities
+    pub const GETPRIORITY: u64 = 140;
+    pub const SETPRIORITY: u64 = 141;
+    pub const SCHED_SETPARAM: u64 = 142;
+    pub const SCHED_GETPARAM: u64 = 143;
+    pub const SCHED_SETSCHEDULER: u64 = 144;
+    pub const SCHED_GETSCHEDULER: u64 = 145;
+    pub const SCHED_GET_PRIORITY_MAX: u64 = 146;
+    pub const SCHED_GET_PRIORITY_MIN: u64 = 147;
+    pub const SCHED_SETAFFINITY: u64 = 203;
+    pub const SCHED_GETAFFINITY: u64 = 204;
+    pub const GETCPU: u64 = 309;
     // epoll
     pub const EPOLL_WAIT: u64 = 232;
     pub const EPOLL_CTL: u64 = 233;
@@ -186,6 +222,23 @@ pub mod nr {
     pub const NEWFSTATAT: u64 = 79;
     pub const FSTAT: u64 = 80;
 
+    // File size and durability
+    pub const TRUNCATE: u64 = 45;
+    pub const FTRUNCATE: u64 = 46;
+    pub const FALLOCATE: u64 = 47;
+    pub const FSYNC: u64 = 82;
+    pub const FDATASYNC: u64 = 83;
+
+    // Filesystem statistics
+    pub const STATFS: u64 = 43;
+    pub const FSTATFS: u64 = 44;
+    pub const FLOCK: u64 = 32;
+
+    // In-kernel data transfer
+    pub const SENDFILE: u64 = 71;
+    pub const SPLICE: u64 = 76;
+    pub const TEE: u64 = 77;
+
     // Process management
     pub const EXIT: u64 = 93;
     pub const EXIT_GROUP: u64 = 94;
@@ -200,16 +253,23 @@ pub mod nr {
     pub const CLOCK_SETTIME: u64 = 112;
     pub const CLOCK_GETTIME: u64 = 113;
 
+    // Process tracing
+    pub const PTRACE: u64 = 117;
+
     // Scheduling
     pub const YIELD: u64 = 124;
 
     // Signals
     pub const KILL: u64 = 129;
+    pub const TKILL: u64 = 130;
+    pub const TGKILL: u64 = 131;
     pub const SIGALTSTACK: u64 = 132;
     pub const SIGSUSPEND: u64 = 133;
     pub const SIGACTION: u64 = 134;
     pub const SIGPROCMASK: u64 = 135;
     pub const SIGPENDING: u64 = 136;
+    pub const RT_SIGTIMEDWAIT: u64 = 137;
+    pub const RT_SIGQUEUEINFO: u64 = 138;
     pub const SIGRETURN: u64 = 139;
 
     // Session/process group
@@ -221,6 +281,7 @@ pub mod nr {
     // Process info
     pub const GETPID: u64 = 172;
     pub const GETPPID: u64 = 173;
+    pub const GETUID: u64 = 174;
     pub const GETTID: u64 = 178;
 
     // Socket
@@ -250,6 +311,24 @@ pub mod nr {
 
     // Wait
     pub const WAIT4: u64 = 260;
+    pub const WAITID: u64 = 95;
+
+    // Resource usage
+    pub const TIMES: u64 = 153;
+    pub const GETRUSAGE: u64 = 165;
+
+    // Scheduling priorities
+    pub const SCHED_SETPARAM: u64 = 118;
+    pub const SCHED_SETSCHEDULER: u64 = 119;
+    pub const SCHED_GETSCHEDULER: u64 = 120;
+    pub const SCHED_GETPARAM: u64 = 121;
+    pub const SCHED_SETAFFINITY: u64 = 122;
+    pub const SCHED_GETAFFINITY: u64 = 123;
+    pub const SCHED_GET_PRIORITY_MAX: u64 = 125;
+    pub const SCHED_GET_PRIORITY_MIN: u64 = 126;
+    pub const SETPRIORITY: u64 = 140;
+    pub const GETPRIORITY: u64 = 141;
+    pub const GETCPU: u64 = 168;
 
     // Random
     pub const GETRANDOM: u64 = 278;
@@ -3,6 +3,7 @@
 //! Lightweight retained-mode widget library for Breenix graphical applications.
 //! Widgets are standalone structs with `update()` and `draw()` methods.
 //! No libbreenix dependency — pure drawing logic on `FrameBuf`.
+//! `wm` holds bwm's window placement, focus and Alt+Tab decisions.
 
 #![no_std]
 
@@ -15,6 +16,7 @@ pub mod shortcut;
 pub mod text;
 pub mod theme;
 pub mod widget;
+pub mod wm;
 
 pub use input::{InputState, WidgetEvent};
 pub use rect::Rect;
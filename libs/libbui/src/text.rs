@@ -7,9 +7,15 @@ use crate::rect::Rect;
 use crate::theme::Theme;
 
 /// Measure the pixel width of a text string using the theme's font.
+///
+/// Text is UTF-8; the bitmap font lays it out as Unicode (see
+/// [`bitmap_font::draw_str`]), the 5x7 font only draws ASCII.
 pub fn text_width(text: &[u8], theme: &Theme) -> i32 {
     if theme.use_bitmap_font {
-        bitmap_font::text_width(text) as i32
+        match core::str::from_utf8(text) {
+            Ok(text) => bitmap_font::str_width(text) as i32,
+            Err(_) => bitmap_font::text_width(text) as i32,
+        }
     } else {
         font::text_width(text, 1) as i32
     }
@@ -30,7 +36,12 @@ pub fn draw_text(fb: &mut FrameBuf, text: &[u8], x: i32, y: i32, color: Color, t
         return;
     }
     if theme.use_bitmap_font {
-        bitmap_font::draw_text(fb, text, x as usize, y as usize, color);
+        match core::str::from_utf8(text) {
+            Ok(text) => {
+                bitmap_font::draw_str(fb, text, x as usize, y as usize, color);
+            }
+            Err(_) => bitmap_font::draw_text(fb, text, x as usize, y as usize, color),
+        }
     } else {
         font::draw_text(fb, text, x as usize, y as usize, color, 1);
     }
@@ -14,6 +14,8 @@ const BUTTON_W: i32 = 60;
 const BUTTON_H: i32 = 22;
 const BUTTON_GAP: i32 = 8;
 const ROW_PAD_LEFT: i32 = 4;
+/// How far the mouse must move with the button held on an entry to drag it.
+const DRAG_THRESHOLD: i32 = 4;
 
 /// A file or directory entry for the picker.
 pub struct FileEntry {
@@ -32,6 +34,8 @@ pub enum FilePickerResult {
     NavigateDir(usize),
     /// User cancelled the dialog.
     Cancelled,
+    /// User started dragging the entry at this index out of the list.
+    DragStarted(usize),
 }
 
 /// Modal file picker dialog with scrollable file list.
@@ -50,6 +54,9 @@ pub struct FilePicker {
     open_pressed: bool,
     cancel_hovered: bool,
     cancel_pressed: bool,
+    /// Entry the mouse button went down on, and where, until it is
+    /// released or the drag starts.
+    drag_from: Option<(usize, i32, i32)>,
 }
 
 impl FilePicker {
@@ -85,6 +92,7 @@ impl FilePicker {
             open_pressed: false,
             cancel_hovered: false,
             cancel_pressed: false,
+            drag_from: None,
         }
     }
 
@@ -94,6 +102,7 @@ impl FilePicker {
         self.entries = entries;
         self.selected = None;
         self.scroll_offset = 0;
+        self.drag_from = None;
     }
 
     /// Get the currently selected entry, if any.
@@ -106,6 +115,17 @@ impl FilePicker {
         self.open_hovered = self.open_btn.contains(input.mouse_x, input.mouse_y);
         self.cancel_hovered = self.cancel_btn.contains(input.mouse_x, input.mouse_y);
 
+        if let Some((idx, x, y)) = self.drag_from {
+            if !input.mouse_down {
+                self.drag_from = None;
+            } else if (input.mouse_x - x).abs() > DRAG_THRESHOLD
+                || (input.mouse_y - y).abs() > DRAG_THRESHOLD
+            {
+                self.drag_from = None;
+                return FilePickerResult::DragStarted(idx);
+            }
+        }
+
         if self.open_hovered && input.mouse_pressed {
             self.open_pressed = true;
         }
@@ -157,6 +177,7 @@ impl FilePicker {
                     return self.activate(entry_idx);
                 }
                 self.selected = Some(entry_idx);
+                self.drag_from = Some((entry_idx, input.mouse_x, input.mouse_y));
             }
         }
 
@@ -63,6 +63,13 @@ impl TabBar {
         self.labels.len() - 1
     }
 
+    /// Replace the label of the tab at `index`. Out-of-range indices are ignored.
+    pub fn set_label(&mut self, index: usize, label: &'static [u8]) {
+        if let Some(slot) = self.labels.get_mut(index) {
+            *slot = label;
+        }
+    }
+
     /// Remove a tab by index. Adjusts selected index if needed.
     pub fn remove_tab(&mut self, index: usize) {
         if index >= self.labels.len() { return; }
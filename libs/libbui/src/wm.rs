@@ -0,0 +1,296 @@
+//! Window management decisions for bwm.
+//!
+//! Where a maximized, tiled or snapped window goes, which window gets focus
+//! after one is minimized, and the Alt+Tab switcher's stacking order. These
+//! are pure functions of window state; bwm applies the results through the
+//! kernel window API.
+
+use alloc::vec::Vec;
+
+/// Top taskbar height
+pub const TASKBAR_HEIGHT: usize = 28;
+
+/// Bottom app bar height
+pub const APPBAR_HEIGHT: usize = 36;
+
+/// Dropping a dragged window this close to the left/right screen edge tiles
+/// it to that half of the screen
+pub const SNAP_EDGE: i32 = 4;
+
+/// A window frame: x, y, width, height.
+pub type Frame = (i32, i32, usize, usize);
+
+/// How a window's frame is sized: where the user left it, or filling the
+/// work area between the taskbar and app bar, or one half of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placement {
+    Floating,
+    Maximized,
+    LeftHalf,
+    RightHalf,
+}
+
+/// A display output's part of the screen. The screen (the framebuffer bwm
+/// composites) spans every output; each shows one rectangle of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Display {
+    pub x: i32,
+    pub y: i32,
+    pub w: usize,
+    pub h: usize,
+}
+
+impl Display {
+    /// Returns true if the point (px, py) is on this display.
+    pub fn contains(&self, px: i32, py: i32) -> bool {
+        px >= self.x && px < self.x + self.w as i32 && py >= self.y && py < self.y + self.h as i32
+    }
+}
+
+/// What focus and switching decisions need from a managed window.
+pub trait ManagedWindow {
+    fn window_id(&self) -> u32;
+    fn is_minimized(&self) -> bool;
+    fn set_minimized(&mut self, minimized: bool);
+    /// Chromeless windows (the launcher, panels) are never Alt+Tab targets.
+    fn is_chromeless(&self) -> bool;
+}
+
+/// The frame `placement` gives a window on `display`: `restore_rect` when
+/// floating, otherwise all or half of the work area, which is the display
+/// between the taskbar and the app bar along the screen's bottom.
+pub fn placement_rect(placement: Placement, restore_rect: Frame, display: Display, screen_h: usize) -> Frame {
+    let top = display.y + TASKBAR_HEIGHT as i32;
+    let bottom = (display.y + display.h as i32).min(screen_h.saturating_sub(APPBAR_HEIGHT) as i32);
+    let work_h = (bottom - top).max(0) as usize;
+    let half = display.w / 2;
+    match placement {
+        Placement::Floating => restore_rect,
+        Placement::Maximized => (display.x, top, display.w, work_h),
+        Placement::LeftHalf => (display.x, top, half, work_h),
+        Placement::RightHalf => (display.x + half as i32, top, display.w - half, work_h),
+    }
+}
+
+/// Give a window currently at `frame` with `current` placement the new
+/// `placement` on `display`, returning its new frame and the floating frame
+/// to restore later. Leaving floating saves `frame` as that restore frame.
+pub fn place(
+    current: Placement,
+    frame: Frame,
+    restore_rect: Frame,
+    placement: Placement,
+    display: Display,
+    screen_h: usize,
+) -> (Frame, Frame) {
+    let restore_rect = if current == Placement::Floating { frame } else { restore_rect };
+    (placement_rect(placement, restore_rect, display, screen_h), restore_rect)
+}
+
+/// The placement a maximize/tile shortcut gives a window placed `current`:
+/// `requested`, or floating again if it is already placed that way.
+pub fn toggled(current: Placement, requested: Placement) -> Placement {
+    if current == requested { Placement::Floating } else { requested }
+}
+
+/// Where a window dragged to (`x`, `y`) on `display` snaps when dropped:
+/// the top edge maximizes it, a side edge tiles it.
+pub fn snap_placement(display: Display, x: i32, y: i32) -> Option<Placement> {
+    if y < display.y + TASKBAR_HEIGHT as i32 {
+        Some(Placement::Maximized)
+    } else if x < display.x + SNAP_EDGE {
+        Some(Placement::LeftHalf)
+    } else if x >= display.x + display.w as i32 - SNAP_EDGE {
+        Some(Placement::RightHalf)
+    } else {
+        None
+    }
+}
+
+/// Index of the topmost window that is not minimized, or `current` if all are.
+pub fn next_visible_window<W: ManagedWindow>(windows: &[W], current: usize) -> usize {
+    windows.iter().rposition(|w| !w.is_minimized()).unwrap_or(current)
+}
+
+/// The window focused once window `idx`, already marked minimized, is gone
+/// from the screen: the topmost window left if `idx` had focus.
+pub fn focus_after_minimize<W: ManagedWindow>(windows: &[W], idx: usize, focused_win: usize) -> usize {
+    if focused_win == idx { next_visible_window(windows, focused_win) } else { focused_win }
+}
+
+/// Alt+Tab state: the stacking order when the switch began, and how far
+/// down the switchable windows the selection is.
+pub struct Switcher {
+    /// Every window id, bottom first, chromeless windows included
+    stack: Vec<u32>,
+    /// Ids of the windows that can be selected, topmost first
+    order: Vec<u32>,
+    depth: usize,
+}
+
+impl Switcher {
+    /// Begin a switch from the current stacking order (index 0 = bottom).
+    pub fn new<W: ManagedWindow>(windows: &[W]) -> Self {
+        Self {
+            stack: windows.iter().map(|w| w.window_id()).collect(),
+            order: windows.iter().rev().filter(|w| !w.is_chromeless()).map(|w| w.window_id()).collect(),
+            depth: 0,
+        }
+    }
+
+    /// Ids of the windows the switcher cycles through, topmost first.
+    pub fn order(&self) -> &[u32] {
+        &self.order
+    }
+
+    /// Move the selection one window down (or up, wrapping), returning the id
+    /// of the window selected; None with fewer than two windows to pick from.
+    pub fn step(&mut self, forward: bool) -> Option<u32> {
+        let n = self.order.len();
+        if n < 2 {
+            return None;
+        }
+        self.depth = if forward { (self.depth + 1) % n } else { (self.depth + n - 1) % n };
+        Some(self.order[self.depth])
+    }
+
+    /// Put `windows` back in the order the switch began with, then raise and
+    /// restore window `target`. Windows opened since the switch began stay
+    /// above the others.
+    pub fn restack<W: ManagedWindow>(&self, windows: &mut Vec<W>, target: u32) {
+        let stack = &self.stack;
+        windows.sort_by_key(|w| stack.iter().position(|&id| id == w.window_id()).unwrap_or(usize::MAX));
+        if let Some(idx) = windows.iter().position(|w| w.window_id() == target) {
+            let mut win = windows.remove(idx);
+            win.set_minimized(false);
+            windows.push(win);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const PRIMARY: Display = Display { x: 0, y: 0, w: 1024, h: 768 };
+    const SECONDARY: Display = Display { x: 1024, y: 0, w: 800, h: 600 };
+    const SCREEN_H: usize = 768;
+
+    struct Win {
+        id: u32,
+        minimized: bool,
+        chromeless: bool,
+    }
+
+    impl ManagedWindow for Win {
+        fn window_id(&self) -> u32 {
+            self.id
+        }
+        fn is_minimized(&self) -> bool {
+            self.minimized
+        }
+        fn set_minimized(&mut self, minimized: bool) {
+            self.minimized = minimized;
+        }
+        fn is_chromeless(&self) -> bool {
+            self.chromeless
+        }
+    }
+
+    /// Windows with the given ids, bottom first
+    fn stack(ids: &[u32]) -> Vec<Win> {
+        ids.iter().map(|&id| Win { id, minimized: false, chromeless: false }).collect()
+    }
+
+    fn ids(windows: &[Win]) -> Vec<u32> {
+        windows.iter().map(|w| w.id).collect()
+    }
+
+    #[test]
+    fn placement_fills_the_work_area() {
+        let floating = (100, 120, 400, 300);
+        assert_eq!(placement_rect(Placement::Floating, floating, PRIMARY, SCREEN_H), floating);
+        // Between the taskbar and the app bar
+        assert_eq!(placement_rect(Placement::Maximized, floating, PRIMARY, SCREEN_H), (0, 28, 1024, 704));
+        let odd = Display { w: 1023, ..PRIMARY };
+        assert_eq!(placement_rect(Placement::LeftHalf, floating, odd, SCREEN_H), (0, 28, 511, 704));
+        assert_eq!(placement_rect(Placement::RightHalf, floating, odd, SCREEN_H), (511, 28, 512, 704));
+        // A display that stops short of the app bar keeps its own bottom edge
+        assert_eq!(placement_rect(Placement::Maximized, floating, SECONDARY, SCREEN_H), (1024, 28, 800, 572));
+    }
+
+    #[test]
+    fn drops_snap_at_display_edges() {
+        assert_eq!(snap_placement(PRIMARY, 500, 10), Some(Placement::Maximized));
+        assert_eq!(snap_placement(PRIMARY, SNAP_EDGE - 1, 300), Some(Placement::LeftHalf));
+        assert_eq!(snap_placement(PRIMARY, SNAP_EDGE, 300), None);
+        assert_eq!(snap_placement(PRIMARY, 1024 - SNAP_EDGE, 300), Some(Placement::RightHalf));
+        assert_eq!(snap_placement(PRIMARY, 500, 300), None);
+        // Edges are relative to the display dropped on
+        assert_eq!(snap_placement(SECONDARY, 1024 + 1, 300), Some(Placement::LeftHalf));
+        assert_eq!(snap_placement(SECONDARY, 1024 + 799, 300), Some(Placement::RightHalf));
+    }
+
+    #[test]
+    fn restore_returns_to_the_floating_frame() {
+        let floating = (100, 120, 400, 300);
+        let (frame, restore) = place(Placement::Floating, floating, (0, 0, 0, 0), Placement::LeftHalf, PRIMARY, SCREEN_H);
+        assert_eq!((frame, restore), ((0, 28, 512, 704), floating));
+        // Going from one placement to another keeps the frame saved on leaving floating
+        let (frame, restore) = place(Placement::LeftHalf, frame, restore, Placement::Maximized, PRIMARY, SCREEN_H);
+        assert_eq!(restore, floating);
+        let back = toggled(Placement::Maximized, Placement::Maximized);
+        assert_eq!(back, Placement::Floating);
+        assert_eq!(place(Placement::Maximized, frame, restore, back, PRIMARY, SCREEN_H).0, floating);
+        assert_eq!(toggled(Placement::LeftHalf, Placement::Maximized), Placement::Maximized);
+    }
+
+    #[test]
+    fn minimizing_the_focused_window_focuses_the_topmost_left() {
+        let mut windows = stack(&[1, 2, 3]);
+        windows[2].minimized = true;
+        assert_eq!(focus_after_minimize(&windows, 2, 2), 1);
+        // Minimizing another window leaves focus alone
+        assert_eq!(focus_after_minimize(&windows, 2, 0), 0);
+        windows[1].minimized = true;
+        assert_eq!(focus_after_minimize(&windows, 1, 1), 0);
+        // Nothing left to focus
+        windows[0].minimized = true;
+        assert_eq!(focus_after_minimize(&windows, 0, 0), 0);
+    }
+
+    #[test]
+    fn switcher_walks_the_stacking_order() {
+        let mut windows = stack(&[9, 1, 2, 3]);
+        windows[0].chromeless = true;
+        let mut sw = Switcher::new(&windows);
+        assert_eq!(sw.order(), &[3, 2, 1]);
+
+        // One step flips the top two windows; the chromeless window stays put
+        assert_eq!(sw.step(true), Some(2));
+        sw.restack(&mut windows, 2);
+        assert_eq!(ids(&windows), vec![9, 1, 3, 2]);
+
+        // The next step starts again from the original order, with a window
+        // opened mid-switch kept above the rest
+        windows[1].minimized = true;
+        windows.push(Win { id: 4, minimized: false, chromeless: false });
+        assert_eq!(sw.step(true), Some(1));
+        sw.restack(&mut windows, 1);
+        assert_eq!(ids(&windows), vec![9, 2, 3, 4, 1]);
+        assert!(!windows[4].minimized);
+
+        // Backwards, and wrapping past the top
+        assert_eq!(sw.step(false), Some(2));
+        assert_eq!(sw.step(false), Some(3));
+        assert_eq!(sw.step(false), Some(1));
+    }
+
+    #[test]
+    fn switcher_needs_two_windows() {
+        let mut windows = stack(&[9, 1]);
+        windows[0].chromeless = true;
+        assert_eq!(Switcher::new(&windows).step(true), None);
+    }
+}
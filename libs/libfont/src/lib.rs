@@ -1,9 +1,10 @@
-//! TrueType font parser and anti-aliased rasterizer.
+//! TrueType/OpenType font parser, shaper and anti-aliased rasterizer.
 //!
 //! `#![no_std]` + `extern crate alloc`. Zero external dependencies.
 //!
-//! Parses .ttf files, extracts glyph outlines, and rasterizes them into
-//! coverage bitmaps suitable for alpha-blended text rendering.
+//! Parses .ttf and .otf files (glyf or CFF/CFF2 outlines), extracts glyph
+//! outlines, and rasterizes them into coverage bitmaps suitable for
+//! alpha-blended text rendering.
 
 #![no_std]
 extern crate alloc;
@@ -17,6 +18,7 @@ mod float;
 
 use alloc::boxed::Box;
 use alloc::string::String;
+use alloc::vec::Vec;
 use core::ops::Range;
 use crate::tables::TableDirectory;
 use crate::tables::head::HeadTable;
@@ -27,7 +29,11 @@ use crate::tables::cmap::CmapTable;
 use crate::tables::loca::LocaTable;
 use crate::tables::kern::KernTable;
 use crate::tables::glyf;
-use crate::outline::flatten_glyph;
+use crate::tables::cff::CffTable;
+use crate::tables::layout::{Gdef, GlyphInfo, LayoutTable};
+use crate::tables::gsub::{self, Gsub};
+use crate::tables::gpos::{self, Adjustment, Gpos};
+use crate::outline::GlyphOutline;
 use crate::rasterizer::{rasterize, rasterize_subpixel, GlyphBitmap};
 use crate::cache::GlyphCache;
 use crate::float::{floor, ceil};
@@ -44,22 +50,49 @@ pub struct ScaledMetrics {
     pub line_height: f32,
 }
 
-/// Parsed TrueType font. Owns its data — no lifetime parameter.
+/// A glyph positioned by [`Font::shape`], in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedGlyph {
+    pub glyph_index: u16,
+    /// Index (in chars) of the first char of the input this glyph came from.
+    /// Ligatures carry the cluster of their first component.
+    pub cluster: usize,
+    /// Distance to advance the pen after this glyph.
+    pub x_advance: f32,
+    /// Offset to draw the glyph at from the pen position (y up).
+    pub x_offset: f32,
+    pub y_offset: f32,
+}
+
+/// Where a font's glyph outlines live.
+enum Outlines {
+    /// TrueType quadratic outlines in `glyf`, indexed by `loca`.
+    Glyf {
+        loca_range: Range<usize>,
+        glyf_range: Range<usize>,
+        index_to_loc_format: i16,
+    },
+    /// PostScript cubic outlines in `CFF ` or `CFF2`.
+    Cff(Range<usize>, CffTable),
+}
+
+/// Parsed TrueType/OpenType font. Owns its data — no lifetime parameter.
 pub struct Font {
     data: Box<[u8]>,
     head: HeadTable,
     hhea: HheaTable,
     cmap: CmapTable,
     hmtx_range: Range<usize>,
-    loca_range: Range<usize>,
-    glyf_range: Range<usize>,
+    outlines: Outlines,
     kern: Option<KernTable>,
+    gsub: Option<(Range<usize>, LayoutTable)>,
+    gpos: Option<(Range<usize>, LayoutTable)>,
+    gdef: Option<(Range<usize>, Gdef)>,
     num_h_metrics: u16,
-    index_to_loc_format: i16,
 }
 
 impl Font {
-    /// Parse a TrueType font from raw .ttf data. Takes ownership via copy.
+    /// Parse a font from raw .ttf/.otf data. Takes ownership via copy.
     pub fn parse(data: &[u8]) -> Result<Self, String> {
         let dir = TableDirectory::parse(data)?;
 
@@ -82,31 +115,53 @@ impl Font {
         let hmtx_range = dir.table_range(data.len(), b"hmtx")
             .ok_or_else(|| String::from("missing hmtx table"))?;
 
-        let loca_range = dir.table_range(data.len(), b"loca")
-            .ok_or_else(|| String::from("missing loca table"))?;
-
-        let glyf_range = dir.table_range(data.len(), b"glyf")
-            .ok_or_else(|| String::from("missing glyf table"))?;
+        let outlines = if let Some(range) = dir.table_range(data.len(), b"CFF ") {
+            let cff = CffTable::parse(&data[range.clone()])?;
+            Outlines::Cff(range, cff)
+        } else if let Some(range) = dir.table_range(data.len(), b"CFF2") {
+            let cff = CffTable::parse_cff2(&data[range.clone()])?;
+            Outlines::Cff(range, cff)
+        } else {
+            let loca_range = dir.table_range(data.len(), b"loca")
+                .ok_or_else(|| String::from("missing loca table"))?;
+            let glyf_range = dir.table_range(data.len(), b"glyf")
+                .ok_or_else(|| String::from("missing glyf table"))?;
+            Outlines::Glyf {
+                loca_range,
+                glyf_range,
+                index_to_loc_format: head.index_to_loc_format,
+            }
+        };
 
         let kern = dir.table_data(data, b"kern").and_then(KernTable::parse);
 
+        // Layout tables are optional; a malformed one is ignored
+        let layout = |tag: &[u8; 4], features: &[&[u8; 4]], extension: u16| {
+            let range = dir.table_range(data.len(), tag)?;
+            let table = LayoutTable::parse(&data[range.clone()], features, extension)?;
+            Some((range, table))
+        };
+        let gsub = layout(b"GSUB", &gsub::DEFAULT_FEATURES, gsub::EXTENSION);
+        let gpos = layout(b"GPOS", &gpos::DEFAULT_FEATURES, gpos::EXTENSION);
+        let gdef = dir.table_range(data.len(), b"GDEF")
+            .and_then(|range| Some((range.clone(), Gdef::parse(&data[range])?)));
+
         Ok(Self {
             data: Box::from(data),
             head,
             hhea,
             cmap,
             hmtx_range,
-            loca_range,
-            glyf_range,
+            outlines,
             kern,
+            gsub,
+            gpos,
+            gdef,
             num_h_metrics: hhea.num_h_metrics,
-            index_to_loc_format: head.index_to_loc_format,
         })
     }
 
     fn hmtx_data(&self) -> &[u8] { &self.data[self.hmtx_range.clone()] }
-    fn loca_data(&self) -> &[u8] { &self.data[self.loca_range.clone()] }
-    fn glyf_data(&self) -> &[u8] { &self.data[self.glyf_range.clone()] }
 
     /// Get scaled metrics for a given pixel size.
     pub fn metrics(&self, pixel_size: f32) -> ScaledMetrics {
@@ -134,15 +189,71 @@ impl Font {
         hmtx.advance_width(glyph_index) as f32 * scale
     }
 
-    /// Get the kerning value between two glyphs in pixels.
+    /// Get the kerning value between two glyphs in pixels, from `GPOS` pair
+    /// adjustments or else the `kern` table.
     pub fn kern(&self, left: u16, right: u16, pixel_size: f32) -> f32 {
-        match &self.kern {
-            Some(kern) => {
-                let scale = pixel_size / self.head.units_per_em as f32;
-                kern.kern_value(left, right) as f32 * scale
+        let scale = pixel_size / self.head.units_per_em as f32;
+        match (self.gpos(), &self.kern) {
+            (Some(gpos), _) => gpos.pair_kern(left, right) as f32 * scale,
+            (None, Some(kern)) => kern.kern_value(left, right) as f32 * scale,
+            (None, None) => 0.0,
+        }
+    }
+
+    fn gdef(&self) -> Option<(&[u8], Gdef)> {
+        self.gdef.as_ref().map(|(range, gdef)| (&self.data[range.clone()], *gdef))
+    }
+
+    fn gsub(&self) -> Option<Gsub<'_>> {
+        self.gsub.as_ref().map(|(range, table)| Gsub {
+            data: &self.data[range.clone()],
+            table,
+            gdef: self.gdef(),
+        })
+    }
+
+    fn gpos(&self) -> Option<Gpos<'_>> {
+        self.gpos.as_ref().map(|(range, table)| Gpos {
+            data: &self.data[range.clone()],
+            table,
+            gdef: self.gdef(),
+        }).filter(|gpos| gpos.has_lookups())
+    }
+
+    /// Shape a run of text: map chars to glyphs, apply the font's ligature
+    /// and contextual substitutions (`GSUB` ccmp/locl/rlig/liga/clig/calt),
+    /// then position the glyphs with `GPOS` kerning (or the `kern` table).
+    /// Text is laid out left to right; no script-specific shaping is done.
+    pub fn shape(&self, text: &str, pixel_size: f32) -> Vec<ShapedGlyph> {
+        let mut buf: Vec<GlyphInfo> = text.chars().enumerate()
+            .map(|(cluster, ch)| GlyphInfo { glyph: self.glyph_index(ch), cluster })
+            .collect();
+        if let Some(gsub) = self.gsub() {
+            gsub.apply(&mut buf);
+        }
+
+        let hmtx = HmtxTable::new(self.hmtx_data(), self.num_h_metrics);
+        let mut adjustments: Vec<Adjustment> = buf.iter()
+            .map(|g| Adjustment { x_advance: hmtx.advance_width(g.glyph) as i32, ..Adjustment::default() })
+            .collect();
+        match (self.gpos(), &self.kern) {
+            (Some(gpos), _) => gpos.apply(&buf, &mut adjustments),
+            (None, Some(kern)) => {
+                for i in 1..buf.len() {
+                    adjustments[i - 1].x_advance += kern.kern_value(buf[i - 1].glyph, buf[i].glyph) as i32;
+                }
             }
-            None => 0.0,
+            (None, None) => {}
         }
+
+        let scale = pixel_size / self.head.units_per_em as f32;
+        buf.iter().zip(&adjustments).map(|(g, adj)| ShapedGlyph {
+            glyph_index: g.glyph,
+            cluster: g.cluster,
+            x_advance: adj.x_advance as f32 * scale,
+            x_offset: adj.x_offset as f32 * scale,
+            y_offset: adj.y_offset as f32 * scale,
+        }).collect()
     }
 
     /// Rasterize a single glyph at the given pixel size.
@@ -152,12 +263,11 @@ impl Font {
         pixel_size: f32,
     ) -> Result<GlyphBitmap, String> {
         let scale = pixel_size / self.head.units_per_em as f32;
-        let loca = LocaTable::new(self.loca_data(), self.index_to_loc_format);
         let hmtx = HmtxTable::new(self.hmtx_data(), self.num_h_metrics);
 
-        // Get glyph offset — None means empty glyph (e.g., space)
-        let glyph_offset = match loca.glyph_offset(glyph_index) {
-            Some(off) => off,
+        // None means empty glyph (e.g., space)
+        let outline = match self.glyph_outline(glyph_index)? {
+            Some(outline) => outline,
             None => {
                 // Empty glyph — return a zero-size bitmap
                 let advance = hmtx.advance_width(glyph_index) as f32 * scale;
@@ -171,7 +281,7 @@ impl Font {
             }
         };
 
-        let simple_glyph = self.resolve_glyph(glyph_offset)?;
+        let (gx_min, gy_min, gx_max, gy_max) = outline.bounds();
 
         let ascender = self.hhea.ascender as f32 * scale;
         // Fixed baseline position in cell — same for ALL glyphs at this size.
@@ -179,10 +289,10 @@ impl Font {
         let baseline = (ascender + 0.5) as i32;
 
         // Calculate bitmap bounds from glyph bounds
-        let x_min = simple_glyph.x_min as f32 * scale;
-        let y_min = simple_glyph.y_min as f32 * scale;
-        let x_max = simple_glyph.x_max as f32 * scale;
-        let y_max = simple_glyph.y_max as f32 * scale;
+        let x_min = gx_min * scale;
+        let y_min = gy_min * scale;
+        let x_max = gx_max * scale;
+        let y_max = gy_max * scale;
 
         let bmp_x_offset = floor(x_min) as i32;
         // Position bitmap so its internal baseline (at row ceil(y_max))
@@ -206,7 +316,7 @@ impl Font {
         // Flatten the glyph outline into line segments
         let x_off = -floor(x_min);
         let y_off = ceil(y_max); // top of bitmap in font coords (y-flipped)
-        let segments = flatten_glyph(&simple_glyph, scale, x_off, y_off);
+        let segments = outline.flatten(scale, x_off, y_off);
 
         Ok(rasterize(&segments, bmp_width, bmp_height, bmp_x_offset, bmp_y_offset))
     }
@@ -218,11 +328,10 @@ impl Font {
         pixel_size: f32,
     ) -> Result<SubpixelBitmap, String> {
         let scale = pixel_size / self.head.units_per_em as f32;
-        let loca = LocaTable::new(self.loca_data(), self.index_to_loc_format);
         let hmtx = HmtxTable::new(self.hmtx_data(), self.num_h_metrics);
 
-        let glyph_offset = match loca.glyph_offset(glyph_index) {
-            Some(off) => off,
+        let outline = match self.glyph_outline(glyph_index)? {
+            Some(outline) => outline,
             None => {
                 let advance = hmtx.advance_width(glyph_index) as f32 * scale;
                 return Ok(SubpixelBitmap {
@@ -235,15 +344,15 @@ impl Font {
             }
         };
 
-        let simple_glyph = self.resolve_glyph(glyph_offset)?;
+        let (gx_min, gy_min, gx_max, gy_max) = outline.bounds();
 
         let ascender = self.hhea.ascender as f32 * scale;
         let baseline = (ascender + 0.5) as i32;
 
-        let x_min = simple_glyph.x_min as f32 * scale;
-        let y_min = simple_glyph.y_min as f32 * scale;
-        let x_max = simple_glyph.x_max as f32 * scale;
-        let y_max = simple_glyph.y_max as f32 * scale;
+        let x_min = gx_min * scale;
+        let y_min = gy_min * scale;
+        let x_max = gx_max * scale;
+        let y_max = gy_max * scale;
 
         let bmp_x_offset = floor(x_min) as i32;
         let bmp_y_offset = baseline - ceil(y_max) as i32;
@@ -263,37 +372,67 @@ impl Font {
 
         let x_off = -floor(x_min);
         let y_off = ceil(y_max);
-        let segments = flatten_glyph(&simple_glyph, scale, x_off, y_off);
+        let segments = outline.flatten(scale, x_off, y_off);
 
         Ok(rasterize_subpixel(&segments, bmp_width, bmp_height, bmp_x_offset, bmp_y_offset))
     }
 
+    /// Load a glyph's outline in font units. `None` means the glyph is
+    /// empty (e.g., space).
+    pub fn glyph_outline(&self, glyph_index: u16) -> Result<Option<GlyphOutline>, String> {
+        match &self.outlines {
+            Outlines::Glyf { .. } => match self.loca_offset(glyph_index) {
+                Some(offset) => self.resolve_glyph(offset).map(|g| Some(GlyphOutline::TrueType(g))),
+                None => Ok(None),
+            },
+            Outlines::Cff(range, cff) => {
+                let glyph = cff.glyph(&self.data[range.clone()], glyph_index)?;
+                if glyph.commands.is_empty() {
+                    Ok(None)
+                } else {
+                    Ok(Some(GlyphOutline::Cff(glyph)))
+                }
+            }
+        }
+    }
+
+    /// Bounding box of a glyph's outline in pixels from the pen position,
+    /// as (x_min, y_min, x_max, y_max) with y up. `None` for empty glyphs.
+    pub fn glyph_bounds(&self, glyph_index: u16, pixel_size: f32) -> Option<(f32, f32, f32, f32)> {
+        let (x_min, y_min, x_max, y_max) = self.glyph_outline(glyph_index).ok()??.bounds();
+        let scale = pixel_size / self.head.units_per_em as f32;
+        Some((x_min * scale, y_min * scale, x_max * scale, y_max * scale))
+    }
+
+    /// `glyf` offset of a glyph; `None` for empty glyphs and CFF fonts.
+    fn loca_offset(&self, glyph_index: u16) -> Option<u32> {
+        match &self.outlines {
+            Outlines::Glyf { loca_range, index_to_loc_format, .. } => {
+                LocaTable::new(&self.data[loca_range.clone()], *index_to_loc_format)
+                    .glyph_offset(glyph_index)
+            }
+            Outlines::Cff(..) => None,
+        }
+    }
+
     fn resolve_glyph(
         &self,
         offset: u32,
     ) -> Result<glyf::SimpleGlyph, String> {
-        let glyf_data = self.glyf_data();
-        // First try simple parse
-        if let Some(glyph) = glyf::parse_glyph(glyf_data, offset)? {
-            if !glyph.contours.is_empty() {
-                return Ok(glyph);
-            }
-            // Might be compound — try compound resolution
-            let loca_data = self.loca_data();
-            let index_to_loc_format = self.index_to_loc_format;
-
-            let result = glyf::resolve_compound(glyf_data, offset, &|comp_idx| {
-                let comp_loca = LocaTable::new(loca_data, index_to_loc_format);
-                let comp_off = comp_loca.glyph_offset(comp_idx)?;
-                glyf::parse_glyph(glyf_data, comp_off).ok().flatten()
-            })?;
-            match result {
-                Some(g) if !g.contours.is_empty() => Ok(g),
-                _ => Ok(glyph),
-            }
-        } else {
-            Err(String::from("failed to parse glyph"))
-        }
+        let (loca_data, glyf_data, index_to_loc_format) = match &self.outlines {
+            Outlines::Glyf { loca_range, glyf_range, index_to_loc_format } => (
+                &self.data[loca_range.clone()],
+                &self.data[glyf_range.clone()],
+                *index_to_loc_format,
+            ),
+            Outlines::Cff(..) => return Err(String::from("font has no glyf table")),
+        };
+        // Simple glyphs parse directly; compound glyphs recurse into their
+        // components through loca.
+        glyf::resolve_compound(glyf_data, offset, &|comp_idx| {
+            LocaTable::new(loca_data, index_to_loc_format).glyph_offset(comp_idx)
+        })?
+        .ok_or_else(|| String::from("failed to parse glyph"))
     }
 }
 
@@ -337,20 +476,18 @@ impl Font {
     /// Full rasterization diagnostic — returns all intermediate values.
     pub fn debug_rasterize(&self, glyph_index: u16, pixel_size: f32) -> Result<RasterDebugInfo, String> {
         let scale = pixel_size / self.head.units_per_em as f32;
-        let loca = LocaTable::new(self.loca_data(), self.index_to_loc_format);
 
-        let glyph_offset = loca.glyph_offset(glyph_index)
+        let outline = self.glyph_outline(glyph_index)?
             .ok_or_else(|| String::from("no glyph offset"))?;
-
-        let simple_glyph = self.resolve_glyph(glyph_offset)?;
+        let (gx_min, gy_min, gx_max, gy_max) = outline.bounds();
 
         let ascender = self.hhea.ascender as f32 * scale;
         let baseline = (ascender + 0.5) as i32;
 
-        let x_min_s = simple_glyph.x_min as f32 * scale;
-        let y_min_s = simple_glyph.y_min as f32 * scale;
-        let x_max_s = simple_glyph.x_max as f32 * scale;
-        let y_max_s = simple_glyph.y_max as f32 * scale;
+        let x_min_s = gx_min * scale;
+        let y_min_s = gy_min * scale;
+        let x_max_s = gx_max * scale;
+        let y_max_s = gy_max * scale;
 
         let bmp_x_offset = floor(x_min_s) as i32;
         let bmp_y_offset = baseline - ceil(y_max_s) as i32;
@@ -359,10 +496,10 @@ impl Font {
 
         let x_off = -floor(x_min_s);
         let y_off = ceil(y_max_s);
-        let segments = flatten_glyph(&simple_glyph, scale, x_off, y_off);
+        let segments = outline.flatten(scale, x_off, y_off);
 
-        let num_contours = simple_glyph.contours.len();
-        let num_points: usize = simple_glyph.contours.iter().map(|c| c.len()).sum();
+        let num_contours = outline.num_contours();
+        let num_points = outline.num_points();
 
         let bitmap = if bmp_width > 0 && bmp_height > 0 && !segments.is_empty() {
             rasterize(&segments, bmp_width, bmp_height, bmp_x_offset, bmp_y_offset)
@@ -379,10 +516,10 @@ impl Font {
             pixel_size,
             units_per_em: self.head.units_per_em,
             scale,
-            glyph_x_min: simple_glyph.x_min,
-            glyph_y_min: simple_glyph.y_min,
-            glyph_x_max: simple_glyph.x_max,
-            glyph_y_max: simple_glyph.y_max,
+            glyph_x_min: floor(gx_min) as i16,
+            glyph_y_min: floor(gy_min) as i16,
+            glyph_x_max: ceil(gx_max) as i16,
+            glyph_y_max: ceil(gy_max) as i16,
             x_min_scaled: x_min_s,
             y_min_scaled: y_min_s,
             x_max_scaled: x_max_s,
@@ -401,31 +538,43 @@ impl Font {
 
     /// Get raw glyph diagnostic info without rasterizing.
     pub fn debug_glyph(&self, glyph_index: u16) -> GlyphDebugInfo {
-        let loca = LocaTable::new(self.loca_data(), self.index_to_loc_format);
-        let loca_offset = loca.glyph_offset(glyph_index);
-
-        let glyf_data = self.glyf_data();
-        let (num_contours, x_min, y_min, x_max, y_max, total_points) = match loca_offset {
-            Some(off) => {
-                let off = off as usize;
-                if off + 10 <= glyf_data.len() {
-                    let mut r = reader::Reader::at(glyf_data, off);
-                    let nc = r.read_i16().unwrap_or(0);
-                    let xn = r.read_i16().unwrap_or(0);
-                    let yn = r.read_i16().unwrap_or(0);
-                    let xx = r.read_i16().unwrap_or(0);
-                    let yx = r.read_i16().unwrap_or(0);
-                    // Try to count points by parsing
-                    let pts = match self.resolve_glyph(off as u32) {
-                        Ok(g) => g.contours.iter().map(|c| c.len()).sum(),
-                        Err(_) => 0,
-                    };
-                    (nc, xn, yn, xx, yx, pts)
-                } else {
-                    (0, 0, 0, 0, 0, 0)
+        let loca_offset = self.loca_offset(glyph_index);
+
+        let (num_contours, x_min, y_min, x_max, y_max, total_points) = match &self.outlines {
+            Outlines::Glyf { glyf_range, .. } => {
+                let glyf_data = &self.data[glyf_range.clone()];
+                match loca_offset {
+                    Some(off) => {
+                        let off = off as usize;
+                        if off + 10 <= glyf_data.len() {
+                            let mut r = reader::Reader::at(glyf_data, off);
+                            let nc = r.read_i16().unwrap_or(0);
+                            let xn = r.read_i16().unwrap_or(0);
+                            let yn = r.read_i16().unwrap_or(0);
+                            let xx = r.read_i16().unwrap_or(0);
+                            let yx = r.read_i16().unwrap_or(0);
+                            // Try to count points by parsing
+                            let pts = match self.resolve_glyph(off as u32) {
+                                Ok(g) => g.contours.iter().map(|c| c.len()).sum(),
+                                Err(_) => 0,
+                            };
+                            (nc, xn, yn, xx, yx, pts)
+                        } else {
+                            (0, 0, 0, 0, 0, 0)
+                        }
+                    }
+                    None => (0, 0, 0, 0, 0, 0),
                 }
             }
-            None => (0, 0, 0, 0, 0, 0),
+            Outlines::Cff(..) => match self.glyph_outline(glyph_index) {
+                Ok(Some(outline)) => {
+                    let (xn, yn, xx, yx) = outline.bounds();
+                    (outline.num_contours() as i16,
+                     floor(xn) as i16, floor(yn) as i16, ceil(xx) as i16, ceil(yx) as i16,
+                     outline.num_points())
+                }
+                _ => (0, 0, 0, 0, 0, 0),
+            },
         };
 
         GlyphDebugInfo {
@@ -471,6 +620,10 @@ impl CachedFont {
         self.font.kern(left, right, pixel_size)
     }
 
+    pub fn shape(&self, text: &str, pixel_size: f32) -> Vec<ShapedGlyph> {
+        self.font.shape(text, pixel_size)
+    }
+
     pub fn rasterize_glyph(
         &mut self,
         glyph_index: u16,
@@ -616,4 +769,55 @@ mod tests {
                 ch, bmp.width, bmp.height);
         }
     }
+
+    static SANS_DATA: &[u8] = include_bytes!("../../../fonts/DejaVuSans.ttf");
+
+    #[test]
+    fn compound_glyphs_resolve() {
+        let font = Font::parse(FONT_DATA).unwrap();
+        let contours = |ch| match font.glyph_outline(font.glyph_index(ch)).unwrap() {
+            Some(outline) => outline.num_contours(),
+            None => 0,
+        };
+        // Accented letters are compounds of the base letter and an accent
+        assert_eq!(contours('\u{e9}'), contours('e') + contours('\u{b4}'));
+        assert!(contours('\u{c5}') > contours('A'));
+    }
+
+    #[test]
+    fn shape_applies_ligatures() {
+        let font = Font::parse(SANS_DATA).unwrap();
+        let shaped = font.shape("office", 16.0);
+        // "ffi" becomes one glyph carrying the cluster of its first char
+        assert_eq!(shaped.len(), 4);
+        let clusters: alloc::vec::Vec<usize> = shaped.iter().map(|g| g.cluster).collect();
+        assert_eq!(clusters, [0, 1, 4, 5]);
+        assert_ne!(shaped[1].glyph_index, font.glyph_index('f'));
+    }
+
+    #[test]
+    fn shape_applies_gpos_kerning() {
+        let font = Font::parse(SANS_DATA).unwrap();
+        let (a, v) = (font.glyph_index('A'), font.glyph_index('V'));
+        let shaped = font.shape("AV", 16.0);
+        assert!(shaped[0].x_advance < font.advance_width(a, 16.0));
+        assert_eq!(shaped[1].x_advance, font.advance_width(v, 16.0));
+        assert!(font.kern(a, v, 16.0) < 0.0);
+    }
+
+    #[test]
+    fn shape_applies_contextual_alternates() {
+        // JetBrains Mono draws "->" as a spacer plus a wide arrow glyph,
+        // keeping one glyph per char so monospace grids stay aligned
+        let font = Font::parse(JBM_DATA).unwrap();
+        let shaped = font.shape("a->b", 14.0);
+        assert_eq!(shaped.len(), 4);
+        assert_ne!(shaped[1].glyph_index, font.glyph_index('-'));
+        assert_ne!(shaped[2].glyph_index, font.glyph_index('>'));
+        let advance = font.advance_width(font.glyph_index('a'), 14.0);
+        assert!(shaped.iter().all(|g| g.x_advance == advance));
+        // Unaffected text maps straight through
+        let plain = font.shape("ab", 14.0);
+        assert_eq!(plain[0].glyph_index, font.glyph_index('a'));
+    }
 }
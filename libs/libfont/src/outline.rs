@@ -2,9 +2,11 @@
 //!
 //! Takes raw glyph contours from the `glyf` table and scales them to pixel
 //! coordinates, resolving TrueType's implicit on-curve midpoints between
-//! consecutive off-curve points.
+//! consecutive off-curve points. CFF outlines arrive as cubic path commands
+//! and are flattened the same way.
 
 use alloc::vec::Vec;
+use crate::tables::cff::CffGlyph;
 use crate::tables::glyf::{SimpleGlyph, GlyphPoint};
 
 #[inline]
@@ -199,3 +201,143 @@ fn flatten_quad_recursive(
     flatten_quad_recursive(p0, p01, p012, segments, depth + 1);
     flatten_quad_recursive(p012, p12, p2, segments, depth + 1);
 }
+
+/// A path command in font units (y up), as produced by CFF charstrings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    /// Cubic bezier: two control points, then the end point.
+    CurveTo(f32, f32, f32, f32, f32, f32),
+}
+
+/// A glyph outline from either outline format.
+#[derive(Debug, Clone)]
+pub enum GlyphOutline {
+    TrueType(SimpleGlyph),
+    Cff(CffGlyph),
+}
+
+impl GlyphOutline {
+    /// Bounding box in font units: (x_min, y_min, x_max, y_max).
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        match self {
+            GlyphOutline::TrueType(g) => {
+                (g.x_min as f32, g.y_min as f32, g.x_max as f32, g.y_max as f32)
+            }
+            GlyphOutline::Cff(g) => (g.x_min, g.y_min, g.x_max, g.y_max),
+        }
+    }
+
+    pub fn num_contours(&self) -> usize {
+        match self {
+            GlyphOutline::TrueType(g) => g.contours.len(),
+            GlyphOutline::Cff(g) => g.commands.iter()
+                .filter(|c| matches!(c, PathCommand::MoveTo(..)))
+                .count(),
+        }
+    }
+
+    pub fn num_points(&self) -> usize {
+        match self {
+            GlyphOutline::TrueType(g) => g.contours.iter().map(|c| c.len()).sum(),
+            GlyphOutline::Cff(g) => g.commands.iter()
+                .map(|c| if let PathCommand::CurveTo(..) = c { 3 } else { 1 })
+                .sum(),
+        }
+    }
+
+    /// Flatten to line segments in pixel coordinates; see [`flatten_glyph`].
+    pub fn flatten(&self, scale: f32, x_offset: f32, y_offset: f32) -> Vec<LineSegment> {
+        match self {
+            GlyphOutline::TrueType(g) => flatten_glyph(g, scale, x_offset, y_offset),
+            GlyphOutline::Cff(g) => flatten_commands(&g.commands, scale, x_offset, y_offset),
+        }
+    }
+}
+
+/// Flatten cubic path commands into line segments, scaled to pixel
+/// coordinates with the y-axis flipped. Every contour is closed.
+pub fn flatten_commands(
+    commands: &[PathCommand],
+    scale: f32,
+    x_off: f32,
+    y_off: f32,
+) -> Vec<LineSegment> {
+    let map = |x: f32, y: f32| ScaledPoint { x: x * scale + x_off, y: y_off - y * scale };
+    let mut segments = Vec::new();
+    let mut start = ScaledPoint { x: 0.0, y: 0.0 };
+    let mut cursor = start;
+
+    let close = |cursor: ScaledPoint, start: ScaledPoint, segments: &mut Vec<LineSegment>| {
+        if fabs(cursor.x - start.x) > 0.01 || fabs(cursor.y - start.y) > 0.01 {
+            segments.push(LineSegment {
+                x0: cursor.x, y0: cursor.y,
+                x1: start.x, y1: start.y,
+            });
+        }
+    };
+
+    for cmd in commands {
+        match *cmd {
+            PathCommand::MoveTo(x, y) => {
+                close(cursor, start, &mut segments);
+                start = map(x, y);
+                cursor = start;
+            }
+            PathCommand::LineTo(x, y) => {
+                let p = map(x, y);
+                segments.push(LineSegment {
+                    x0: cursor.x, y0: cursor.y,
+                    x1: p.x, y1: p.y,
+                });
+                cursor = p;
+            }
+            PathCommand::CurveTo(x1, y1, x2, y2, x, y) => {
+                let end = map(x, y);
+                flatten_cubic_recursive(cursor, map(x1, y1), map(x2, y2), end, &mut segments, 0);
+                cursor = end;
+            }
+        }
+    }
+    close(cursor, start, &mut segments);
+    segments
+}
+
+/// Adaptively flatten a cubic bezier: emit a line once both control points
+/// are within 0.35px of the chord's third points, otherwise split at t=0.5.
+fn flatten_cubic_recursive(
+    p0: ScaledPoint,
+    p1: ScaledPoint,
+    p2: ScaledPoint,
+    p3: ScaledPoint,
+    segments: &mut Vec<LineSegment>,
+    depth: u32,
+) {
+    let near = |p: ScaledPoint, t: f32| {
+        let dx = p.x - (p0.x + (p3.x - p0.x) * t);
+        let dy = p.y - (p0.y + (p3.y - p0.y) * t);
+        dx * dx + dy * dy <= 0.35 * 0.35
+    };
+    if depth > 8 || (near(p1, 1.0 / 3.0) && near(p2, 2.0 / 3.0)) {
+        segments.push(LineSegment {
+            x0: p0.x, y0: p0.y,
+            x1: p3.x, y1: p3.y,
+        });
+        return;
+    }
+
+    let mid = |a: ScaledPoint, b: ScaledPoint| ScaledPoint {
+        x: (a.x + b.x) * 0.5,
+        y: (a.y + b.y) * 0.5,
+    };
+    let p01 = mid(p0, p1);
+    let p12 = mid(p1, p2);
+    let p23 = mid(p2, p3);
+    let p012 = mid(p01, p12);
+    let p123 = mid(p12, p23);
+    let p0123 = mid(p012, p123);
+
+    flatten_cubic_recursive(p0, p01, p012, p0123, segments, depth + 1);
+    flatten_cubic_recursive(p0123, p123, p23, p3, segments, depth + 1);
+}
@@ -0,0 +1,1026 @@
+//! `CFF ` and `CFF2` tables: PostScript glyph outlines (Type 2 charstrings).
+//!
+//! Supports name-keyed and CID-keyed CFF fonts and CFF2 fonts at their
+//! default instance (variation deltas are parsed and dropped). Outlines are
+//! cubic, so they come out as `PathCommand`s rather than glyf-style points.
+//! Hints are skipped; `seac` accented glyphs are not composed.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+use crate::outline::PathCommand;
+use crate::reader::{read_u16_at, read_u32_at};
+
+/// Maximum subroutine nesting (the spec limit).
+const MAX_SUBR_DEPTH: usize = 10;
+
+/// Maximum operand stack depth (CFF2's limit; CFF's is 48).
+const MAX_STACK: usize = 513;
+
+/// A parsed CFF glyph in font units.
+#[derive(Debug, Clone)]
+pub struct CffGlyph {
+    pub x_min: f32,
+    pub y_min: f32,
+    pub x_max: f32,
+    pub y_max: f32,
+    pub commands: Vec<PathCommand>,
+}
+
+/// An INDEX: a counted array of variable-length objects.
+#[derive(Debug, Clone, Copy, Default)]
+struct Index {
+    count: u32,
+    off_size: u8,
+    /// Position of the offset array
+    offsets: usize,
+}
+
+impl Index {
+    /// Parse the INDEX at `pos`, returning it and the position just past it.
+    fn parse(data: &[u8], pos: usize, cff2: bool) -> Result<(Index, usize), String> {
+        let (count, header) = if cff2 {
+            (checked(data, pos, 4).map(|_| read_u32_at(data, pos))?, 4)
+        } else {
+            (checked(data, pos, 2).map(|_| read_u16_at(data, pos) as u32)?, 2)
+        };
+        if count == 0 {
+            return Ok((Index::default(), pos + header));
+        }
+        let off_size = *data.get(pos + header).ok_or_else(|| String::from("truncated CFF INDEX"))?;
+        if !(1..=4).contains(&off_size) {
+            return Err(String::from("bad CFF INDEX offset size"));
+        }
+        let index = Index { count, off_size, offsets: pos + header + 1 };
+        let end = index.data_base() + index.offset(data, count)?;
+        if end > data.len() {
+            return Err(String::from("CFF INDEX extends past table"));
+        }
+        Ok((index, end))
+    }
+
+    fn offset(&self, data: &[u8], i: u32) -> Result<usize, String> {
+        let at = self.offsets + i as usize * self.off_size as usize;
+        let bytes = data.get(at..at + self.off_size as usize)
+            .ok_or_else(|| String::from("truncated CFF INDEX"))?;
+        Ok(bytes.iter().fold(0usize, |v, &b| v << 8 | b as usize))
+    }
+
+    /// Offsets are 1-based from the byte before the object data.
+    fn data_base(&self) -> usize {
+        self.offsets + (self.count as usize + 1) * self.off_size as usize - 1
+    }
+
+    fn get(&self, data: &[u8], i: u32) -> Option<Range<usize>> {
+        if i >= self.count {
+            return None;
+        }
+        let start = self.data_base() + self.offset(data, i).ok()?;
+        let end = self.data_base() + self.offset(data, i + 1).ok()?;
+        if start > end || end > data.len() {
+            return None;
+        }
+        Some(start..end)
+    }
+}
+
+fn checked(data: &[u8], pos: usize, len: usize) -> Result<(), String> {
+    if pos + len <= data.len() {
+        Ok(())
+    } else {
+        Err(String::from("unexpected end of CFF data"))
+    }
+}
+
+/// Two-byte operators are `0x0C00 | second byte`.
+const OP_ESCAPE: u16 = 0x0C00;
+
+// DICT operators
+const OP_CHARSTRINGS: u16 = 17;
+const OP_PRIVATE: u16 = 18;
+const OP_SUBRS: u16 = 19;
+const OP_VSINDEX: u16 = 22;
+const OP_VSTORE: u16 = 24;
+const OP_CHARSTRING_TYPE: u16 = OP_ESCAPE | 6;
+const OP_FDARRAY: u16 = OP_ESCAPE | 36;
+const OP_FDSELECT: u16 = OP_ESCAPE | 37;
+
+/// Call `f(operator, operands)` for each entry of a DICT.
+fn parse_dict(
+    data: &[u8],
+    mut f: impl FnMut(u16, &[f32]) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut operands: Vec<f32> = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let b0 = data[i];
+        i += 1;
+        match b0 {
+            0..=21 => {
+                let op = if b0 == 12 {
+                    let b1 = *data.get(i).ok_or_else(|| String::from("truncated CFF DICT"))?;
+                    i += 1;
+                    OP_ESCAPE | b1 as u16
+                } else {
+                    b0 as u16
+                };
+                f(op, &operands)?;
+                operands.clear();
+            }
+            22..=24 => {
+                // CFF2 vsindex / blend / vstore. Blended values only occur
+                // in operands this parser ignores, so blend just discards.
+                f(b0 as u16, &operands)?;
+                operands.clear();
+            }
+            28 => {
+                checked(data, i, 2)?;
+                operands.push(read_u16_at(data, i) as i16 as f32);
+                i += 2;
+            }
+            29 => {
+                checked(data, i, 4)?;
+                operands.push(read_u32_at(data, i) as i32 as f32);
+                i += 4;
+            }
+            30 => {
+                let (value, len) = parse_real(&data[i..])?;
+                operands.push(value);
+                i += len;
+            }
+            32..=246 => operands.push(b0 as f32 - 139.0),
+            247..=250 => {
+                let b1 = *data.get(i).ok_or_else(|| String::from("truncated CFF DICT"))?;
+                operands.push((b0 as f32 - 247.0) * 256.0 + b1 as f32 + 108.0);
+                i += 1;
+            }
+            251..=254 => {
+                let b1 = *data.get(i).ok_or_else(|| String::from("truncated CFF DICT"))?;
+                operands.push(-(b0 as f32 - 251.0) * 256.0 - b1 as f32 - 108.0);
+                i += 1;
+            }
+            _ => return Err(String::from("bad CFF DICT operand")),
+        }
+        if operands.len() > MAX_STACK {
+            return Err(String::from("CFF DICT operand overflow"));
+        }
+    }
+    Ok(())
+}
+
+/// Parse a DICT real number (BCD nibbles), returning it and its byte length.
+fn parse_real(data: &[u8]) -> Result<(f32, usize), String> {
+    let mut mantissa = 0f64;
+    let mut scale = 1f64;
+    let mut in_fraction = false;
+    let mut negative = false;
+    let mut exponent = 0i32;
+    let mut exp_sign = 0i32; // 0 = no exponent yet
+    for (i, &byte) in data.iter().enumerate() {
+        for nibble in [byte >> 4, byte & 0x0F] {
+            match nibble {
+                0..=9 if exp_sign != 0 => exponent = exponent * 10 + nibble as i32,
+                0..=9 => {
+                    if in_fraction {
+                        scale /= 10.0;
+                        mantissa += nibble as f64 * scale;
+                    } else {
+                        mantissa = mantissa * 10.0 + nibble as f64;
+                    }
+                }
+                0xA => in_fraction = true,
+                0xB => exp_sign = 1,
+                0xC => exp_sign = -1,
+                0xE => negative = true,
+                0xF => {
+                    let mut value = if negative { -mantissa } else { mantissa };
+                    for _ in 0..exponent.min(38) {
+                        if exp_sign > 0 { value *= 10.0 } else { value /= 10.0 }
+                    }
+                    return Ok((value as f32, i + 1));
+                }
+                _ => return Err(String::from("bad CFF real number")),
+            }
+        }
+    }
+    Err(String::from("unterminated CFF real number"))
+}
+
+/// Subroutines and variation defaults from one Private DICT.
+#[derive(Debug, Clone, Copy, Default)]
+struct PrivateDict {
+    subrs: Index,
+    vsindex: u16,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FdSelect {
+    /// Single font DICT
+    None,
+    /// One byte per glyph
+    Format0(usize),
+    /// u16 range count, (u16 first, u8 fd) ranges
+    Format3(usize, u16),
+    /// u32 range count, (u32 first, u16 fd) ranges (CFF2)
+    Format4(usize, u32),
+}
+
+/// Parsed `CFF ` or `CFF2` table. Holds offsets only; glyph lookups take
+/// the table data again.
+#[derive(Debug, Clone)]
+pub struct CffTable {
+    cff2: bool,
+    global_subrs: Index,
+    char_strings: Index,
+    privates: Vec<PrivateDict>,
+    fd_select: FdSelect,
+    /// Region count of each ItemVariationData, for CFF2 `blend`
+    region_counts: Vec<u16>,
+}
+
+impl CffTable {
+    /// Parse a `CFF ` table.
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        Self::parse_inner(data, false)
+    }
+
+    /// Parse a `CFF2` table.
+    pub fn parse_cff2(data: &[u8]) -> Result<Self, String> {
+        Self::parse_inner(data, true)
+    }
+
+    fn parse_inner(data: &[u8], cff2: bool) -> Result<Self, String> {
+        checked(data, 0, 5)?;
+        let major = data[0];
+        let header_size = data[2] as usize;
+        if major != if cff2 { 2 } else { 1 } {
+            return Err(String::from("unsupported CFF version"));
+        }
+
+        let (top_dict, global_subrs_pos) = if cff2 {
+            let len = read_u16_at(data, 3) as usize;
+            checked(data, header_size, len)?;
+            (header_size..header_size + len, header_size + len)
+        } else {
+            let (_names, pos) = Index::parse(data, header_size, false)?;
+            let (top_dicts, pos) = Index::parse(data, pos, false)?;
+            let (_strings, pos) = Index::parse(data, pos, false)?;
+            let top = top_dicts.get(data, 0).ok_or_else(|| String::from("missing CFF Top DICT"))?;
+            (top, pos)
+        };
+        let (global_subrs, _) = Index::parse(data, global_subrs_pos, cff2)?;
+
+        let mut char_strings_pos = None;
+        let mut private = None;
+        let mut fd_array_pos = None;
+        let mut fd_select_pos = None;
+        let mut vstore_pos = None;
+        parse_dict(&data[top_dict], |op, args| {
+            match op {
+                OP_CHARSTRINGS => char_strings_pos = args.first().map(|&v| v as usize),
+                OP_PRIVATE if args.len() >= 2 => private = Some((args[0] as usize, args[1] as usize)),
+                OP_FDARRAY => fd_array_pos = args.first().map(|&v| v as usize),
+                OP_FDSELECT => fd_select_pos = args.first().map(|&v| v as usize),
+                OP_VSTORE => vstore_pos = args.first().map(|&v| v as usize),
+                OP_CHARSTRING_TYPE if args.first() != Some(&2.0) => {
+                    return Err(String::from("unsupported charstring type"));
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+
+        let char_strings_pos = char_strings_pos.ok_or_else(|| String::from("missing CFF CharStrings"))?;
+        let (char_strings, _) = Index::parse(data, char_strings_pos, cff2)?;
+
+        let mut privates = Vec::new();
+        if let Some(pos) = fd_array_pos {
+            let (fd_array, _) = Index::parse(data, pos, cff2)?;
+            for i in 0..fd_array.count {
+                let range = fd_array.get(data, i).ok_or_else(|| String::from("bad CFF FDArray"))?;
+                let mut font_private = None;
+                parse_dict(&data[range], |op, args| {
+                    if op == OP_PRIVATE && args.len() >= 2 {
+                        font_private = Some((args[0] as usize, args[1] as usize));
+                    }
+                    Ok(())
+                })?;
+                privates.push(match font_private {
+                    Some((size, offset)) => parse_private(data, size, offset, cff2)?,
+                    None => PrivateDict::default(),
+                });
+            }
+        } else if let Some((size, offset)) = private {
+            privates.push(parse_private(data, size, offset, cff2)?);
+        } else {
+            privates.push(PrivateDict::default());
+        }
+
+        let fd_select = match fd_select_pos {
+            Some(pos) if privates.len() > 1 => {
+                checked(data, pos, 1)?;
+                match data[pos] {
+                    0 => FdSelect::Format0(pos + 1),
+                    3 => {
+                        checked(data, pos + 1, 2)?;
+                        FdSelect::Format3(pos + 3, read_u16_at(data, pos + 1))
+                    }
+                    4 => {
+                        checked(data, pos + 1, 4)?;
+                        FdSelect::Format4(pos + 5, read_u32_at(data, pos + 1))
+                    }
+                    _ => return Err(String::from("unsupported CFF FDSelect format")),
+                }
+            }
+            _ => FdSelect::None,
+        };
+
+        let region_counts = match vstore_pos {
+            Some(pos) => parse_region_counts(data, pos).unwrap_or_default(),
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            cff2,
+            global_subrs,
+            char_strings,
+            privates,
+            fd_select,
+            region_counts,
+        })
+    }
+
+    /// Number of glyphs in the CharStrings INDEX.
+    pub fn num_glyphs(&self) -> u32 {
+        self.char_strings.count
+    }
+
+    /// Index of the font DICT used by `glyph_index`.
+    fn fd_index(&self, data: &[u8], glyph_index: u16) -> usize {
+        let gid = glyph_index as usize;
+        let fd = match self.fd_select {
+            FdSelect::None => 0,
+            FdSelect::Format0(pos) => data.get(pos + gid).copied().unwrap_or(0) as usize,
+            FdSelect::Format3(pos, n) => {
+                let mut fd = 0;
+                for i in 0..n as usize {
+                    let at = pos + i * 3;
+                    if at + 5 > data.len() || gid < read_u16_at(data, at) as usize {
+                        break;
+                    }
+                    fd = data[at + 2] as usize;
+                }
+                fd
+            }
+            FdSelect::Format4(pos, n) => {
+                let mut fd = 0;
+                for i in 0..n as usize {
+                    let at = pos + i * 6;
+                    if at + 10 > data.len() || gid < read_u32_at(data, at) as usize {
+                        break;
+                    }
+                    fd = read_u16_at(data, at + 4) as usize;
+                }
+                fd
+            }
+        };
+        fd.min(self.privates.len() - 1)
+    }
+
+    /// Run the glyph's charstring, producing its outline in font units.
+    pub fn glyph(&self, data: &[u8], glyph_index: u16) -> Result<CffGlyph, String> {
+        let range = self.char_strings.get(data, glyph_index as u32)
+            .ok_or_else(|| String::from("glyph index out of range"))?;
+        let private = self.privates[self.fd_index(data, glyph_index)];
+        let mut interp = Interpreter {
+            cff: self,
+            data,
+            local_subrs: private.subrs,
+            vsindex: private.vsindex,
+            stack: Vec::new(),
+            x: 0.0,
+            y: 0.0,
+            stems: 0,
+            seen_width: self.cff2,
+            open: false,
+            glyph: CffGlyph {
+                x_min: f32::MAX,
+                y_min: f32::MAX,
+                x_max: f32::MIN,
+                y_max: f32::MIN,
+                commands: Vec::new(),
+            },
+        };
+        interp.run(range, 0)?;
+        let mut glyph = interp.glyph;
+        if glyph.commands.is_empty() {
+            glyph.x_min = 0.0;
+            glyph.y_min = 0.0;
+            glyph.x_max = 0.0;
+            glyph.y_max = 0.0;
+        }
+        Ok(glyph)
+    }
+}
+
+fn parse_private(data: &[u8], size: usize, offset: usize, cff2: bool) -> Result<PrivateDict, String> {
+    checked(data, offset, size)?;
+    let mut subrs_offset = None;
+    let mut vsindex = 0;
+    parse_dict(&data[offset..offset + size], |op, args| {
+        match op {
+            OP_SUBRS => subrs_offset = args.first().map(|&v| v as usize),
+            OP_VSINDEX => vsindex = args.first().map_or(0, |&v| v as u16),
+            _ => {}
+        }
+        Ok(())
+    })?;
+    let subrs = match subrs_offset {
+        Some(rel) => Index::parse(data, offset + rel, cff2)?.0,
+        None => Index::default(),
+    };
+    Ok(PrivateDict { subrs, vsindex })
+}
+
+/// Region count per ItemVariationData of the CFF2 VariationStore at `pos`.
+fn parse_region_counts(data: &[u8], pos: usize) -> Option<Vec<u16>> {
+    // u16 length, then an ItemVariationStore
+    let store = pos + 2;
+    if store + 8 > data.len() {
+        return None;
+    }
+    let count = read_u16_at(data, store + 6) as usize;
+    let mut counts = Vec::with_capacity(count);
+    for i in 0..count {
+        let at = store + 8 + i * 4;
+        if at + 4 > data.len() {
+            return None;
+        }
+        let item_data = store + read_u32_at(data, at) as usize;
+        if item_data + 6 > data.len() {
+            return None;
+        }
+        counts.push(read_u16_at(data, item_data + 4));
+    }
+    Some(counts)
+}
+
+/// Subroutine index bias, by subroutine count.
+fn subr_bias(count: u32) -> i32 {
+    if count < 1240 {
+        107
+    } else if count < 33900 {
+        1131
+    } else {
+        32768
+    }
+}
+
+/// Type 2 charstring interpreter state.
+struct Interpreter<'a> {
+    cff: &'a CffTable,
+    data: &'a [u8],
+    local_subrs: Index,
+    vsindex: u16,
+    stack: Vec<f32>,
+    x: f32,
+    y: f32,
+    /// Stem hints declared so far (sizes the hintmask operand)
+    stems: usize,
+    /// Whether the optional leading advance width has been consumed
+    seen_width: bool,
+    open: bool,
+    glyph: CffGlyph,
+}
+
+impl Interpreter<'_> {
+    /// Drop the advance width, which the first stack-clearing operator
+    /// carries as an extra leading argument when `has_width` is set.
+    fn take_width(&mut self, has_width: bool) {
+        if !self.seen_width {
+            self.seen_width = true;
+            if has_width && !self.stack.is_empty() {
+                self.stack.remove(0);
+            }
+        }
+    }
+
+    fn extend_bounds(&mut self, x: f32, y: f32) {
+        let g = &mut self.glyph;
+        g.x_min = g.x_min.min(x);
+        g.y_min = g.y_min.min(y);
+        g.x_max = g.x_max.max(x);
+        g.y_max = g.y_max.max(y);
+    }
+
+    fn move_to(&mut self, dx: f32, dy: f32) {
+        self.x += dx;
+        self.y += dy;
+        self.open = true;
+        self.extend_bounds(self.x, self.y);
+        self.glyph.commands.push(PathCommand::MoveTo(self.x, self.y));
+    }
+
+    fn line_to(&mut self, dx: f32, dy: f32) {
+        if !self.open {
+            self.move_to(0.0, 0.0);
+        }
+        self.x += dx;
+        self.y += dy;
+        self.extend_bounds(self.x, self.y);
+        self.glyph.commands.push(PathCommand::LineTo(self.x, self.y));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn curve_to(&mut self, dx1: f32, dy1: f32, dx2: f32, dy2: f32, dx3: f32, dy3: f32) {
+        if !self.open {
+            self.move_to(0.0, 0.0);
+        }
+        let (x1, y1) = (self.x + dx1, self.y + dy1);
+        let (x2, y2) = (x1 + dx2, y1 + dy2);
+        self.x = x2 + dx3;
+        self.y = y2 + dy3;
+        self.extend_bounds(x1, y1);
+        self.extend_bounds(x2, y2);
+        self.extend_bounds(self.x, self.y);
+        self.glyph.commands.push(PathCommand::CurveTo(x1, y1, x2, y2, self.x, self.y));
+    }
+
+    /// Execute a charstring. Returns `Ok(true)` once `endchar` is reached.
+    fn run(&mut self, range: Range<usize>, depth: usize) -> Result<bool, String> {
+        if depth > MAX_SUBR_DEPTH {
+            return Err(String::from("charstring subroutines nested too deeply"));
+        }
+        let cff = self.cff;
+        let code = &self.data[range];
+        let mut i = 0;
+        while i < code.len() {
+            let b0 = code[i];
+            i += 1;
+            match b0 {
+                28 => {
+                    checked(code, i, 2)?;
+                    self.push(read_u16_at(code, i) as i16 as f32)?;
+                    i += 2;
+                }
+                32..=246 => self.push(b0 as f32 - 139.0)?,
+                247..=250 => {
+                    let b1 = *code.get(i).ok_or_else(|| String::from("truncated charstring"))?;
+                    self.push((b0 as f32 - 247.0) * 256.0 + b1 as f32 + 108.0)?;
+                    i += 1;
+                }
+                251..=254 => {
+                    let b1 = *code.get(i).ok_or_else(|| String::from("truncated charstring"))?;
+                    self.push(-(b0 as f32 - 251.0) * 256.0 - b1 as f32 - 108.0)?;
+                    i += 1;
+                }
+                255 => {
+                    checked(code, i, 4)?;
+                    self.push(read_u32_at(code, i) as i32 as f32 / 65536.0)?;
+                    i += 4;
+                }
+                // hstem, vstem, hstemhm, vstemhm
+                1 | 3 | 18 | 23 => {
+                    self.take_width(self.stack.len() % 2 == 1);
+                    self.stems += self.stack.len() / 2;
+                    self.stack.clear();
+                }
+                // hintmask, cntrmask
+                19 | 20 => {
+                    // Pending arguments are an implicit vstem
+                    self.take_width(self.stack.len() % 2 == 1);
+                    self.stems += self.stack.len() / 2;
+                    self.stack.clear();
+                    i += self.stems.div_ceil(8);
+                }
+                // rmoveto
+                21 => {
+                    self.take_width(self.stack.len() > 2);
+                    let (dx, dy) = (self.arg(0)?, self.arg(1)?);
+                    self.move_to(dx, dy);
+                    self.stack.clear();
+                }
+                // hmoveto
+                22 => {
+                    self.take_width(self.stack.len() > 1);
+                    let dx = self.arg(0)?;
+                    self.move_to(dx, 0.0);
+                    self.stack.clear();
+                }
+                // vmoveto
+                4 => {
+                    self.take_width(self.stack.len() > 1);
+                    let dy = self.arg(0)?;
+                    self.move_to(0.0, dy);
+                    self.stack.clear();
+                }
+                // rlineto
+                5 => {
+                    for pair in 0..self.stack.len() / 2 {
+                        let (dx, dy) = (self.stack[pair * 2], self.stack[pair * 2 + 1]);
+                        self.line_to(dx, dy);
+                    }
+                    self.stack.clear();
+                }
+                // hlineto, vlineto: alternating horizontal and vertical
+                6 | 7 => {
+                    let mut horizontal = b0 == 6;
+                    for k in 0..self.stack.len() {
+                        let d = self.stack[k];
+                        if horizontal { self.line_to(d, 0.0) } else { self.line_to(0.0, d) }
+                        horizontal = !horizontal;
+                    }
+                    self.stack.clear();
+                }
+                // rrcurveto
+                8 => {
+                    for c in 0..self.stack.len() / 6 {
+                        let s: [f32; 6] = core::array::from_fn(|k| self.stack[c * 6 + k]);
+                        self.curve_to(s[0], s[1], s[2], s[3], s[4], s[5]);
+                    }
+                    self.stack.clear();
+                }
+                // rcurveline: curves, then a line
+                24 => {
+                    let n = self.stack.len();
+                    if n < 8 {
+                        return Err(String::from("rcurveline stack underflow"));
+                    }
+                    for c in 0..(n - 2) / 6 {
+                        let s: [f32; 6] = core::array::from_fn(|k| self.stack[c * 6 + k]);
+                        self.curve_to(s[0], s[1], s[2], s[3], s[4], s[5]);
+                    }
+                    self.line_to(self.stack[n - 2], self.stack[n - 1]);
+                    self.stack.clear();
+                }
+                // rlinecurve: lines, then a curve
+                25 => {
+                    let n = self.stack.len();
+                    if n < 8 {
+                        return Err(String::from("rlinecurve stack underflow"));
+                    }
+                    for pair in 0..(n - 6) / 2 {
+                        let (dx, dy) = (self.stack[pair * 2], self.stack[pair * 2 + 1]);
+                        self.line_to(dx, dy);
+                    }
+                    let s: [f32; 6] = core::array::from_fn(|k| self.stack[n - 6 + k]);
+                    self.curve_to(s[0], s[1], s[2], s[3], s[4], s[5]);
+                    self.stack.clear();
+                }
+                // vvcurveto: optional leading dx1, then (dya dxb dyb dyc)+
+                26 => {
+                    let mut k = 0;
+                    let mut dx1 = 0.0;
+                    if self.stack.len() % 2 == 1 {
+                        dx1 = self.stack[0];
+                        k = 1;
+                    }
+                    while k + 4 <= self.stack.len() {
+                        let s: [f32; 4] = core::array::from_fn(|j| self.stack[k + j]);
+                        self.curve_to(dx1, s[0], s[1], s[2], 0.0, s[3]);
+                        dx1 = 0.0;
+                        k += 4;
+                    }
+                    self.stack.clear();
+                }
+                // hhcurveto: optional leading dy1, then (dxa dxb dyb dxc)+
+                27 => {
+                    let mut k = 0;
+                    let mut dy1 = 0.0;
+                    if self.stack.len() % 2 == 1 {
+                        dy1 = self.stack[0];
+                        k = 1;
+                    }
+                    while k + 4 <= self.stack.len() {
+                        let s: [f32; 4] = core::array::from_fn(|j| self.stack[k + j]);
+                        self.curve_to(s[0], dy1, s[1], s[2], s[3], 0.0);
+                        dy1 = 0.0;
+                        k += 4;
+                    }
+                    self.stack.clear();
+                }
+                // vhcurveto, hvcurveto: curves alternating vertical and
+                // horizontal tangents, with an optional final extra delta
+                30 | 31 => {
+                    let mut horizontal = b0 == 31;
+                    let mut k = 0;
+                    let n = self.stack.len();
+                    while k + 4 <= n {
+                        let s: [f32; 4] = core::array::from_fn(|j| self.stack[k + j]);
+                        let last = if n - k == 5 { self.stack[k + 4] } else { 0.0 };
+                        if horizontal {
+                            self.curve_to(s[0], 0.0, s[1], s[2], last, s[3]);
+                        } else {
+                            self.curve_to(0.0, s[0], s[1], s[2], s[3], last);
+                        }
+                        horizontal = !horizontal;
+                        k += 4;
+                    }
+                    self.stack.clear();
+                }
+                // callsubr, callgsubr
+                10 | 29 => {
+                    let subrs = if b0 == 10 { self.local_subrs } else { cff.global_subrs };
+                    let index = self.stack.pop().ok_or_else(|| String::from("callsubr stack underflow"))?;
+                    let index = index as i32 + subr_bias(subrs.count);
+                    let range = u32::try_from(index).ok()
+                        .and_then(|index| subrs.get(self.data, index))
+                        .ok_or_else(|| String::from("charstring subroutine out of range"))?;
+                    if self.run(range, depth + 1)? {
+                        return Ok(true);
+                    }
+                }
+                // return
+                11 => return Ok(false),
+                // endchar (four extra arguments would be a seac accent,
+                // which is not composed)
+                14 => {
+                    let n = self.stack.len();
+                    self.take_width(n == 1 || n == 5);
+                    self.stack.clear();
+                    return Ok(true);
+                }
+                // vsindex (CFF2)
+                15 => {
+                    self.vsindex = self.stack.pop().map_or(0, |v| v as u16);
+                    self.stack.clear();
+                }
+                // blend (CFF2): keep the n default values, drop the deltas
+                16 => {
+                    let n = self.stack.pop().ok_or_else(|| String::from("blend stack underflow"))? as usize;
+                    let regions = cff.region_counts.get(self.vsindex as usize).copied().unwrap_or(0) as usize;
+                    let total = n * (regions + 1);
+                    if total > self.stack.len() {
+                        return Err(String::from("blend stack underflow"));
+                    }
+                    let base = self.stack.len() - total;
+                    self.stack.truncate(base + n);
+                }
+                12 => {
+                    let b1 = *code.get(i).ok_or_else(|| String::from("truncated charstring"))?;
+                    i += 1;
+                    self.flex(b1)?;
+                    self.stack.clear();
+                }
+                _ => return Err(String::from("unsupported charstring operator")),
+            }
+        }
+        // CFF2 charstrings end without endchar
+        Ok(depth == 0 && cff.cff2)
+    }
+
+    /// The two-byte flex operators (all others are deprecated arithmetic).
+    fn flex(&mut self, op: u8) -> Result<(), String> {
+        let s = &self.stack;
+        let need = match op {
+            34 => 7,
+            35 => 13,
+            36 => 9,
+            37 => 11,
+            _ => return Err(String::from("unsupported charstring operator")),
+        };
+        if s.len() < need {
+            return Err(String::from("flex stack underflow"));
+        }
+        let s: [f32; 13] = core::array::from_fn(|k| s.get(k).copied().unwrap_or(0.0));
+        match op {
+            // hflex
+            34 => {
+                self.curve_to(s[0], 0.0, s[1], s[2], s[3], 0.0);
+                self.curve_to(s[4], 0.0, s[5], -s[2], s[6], 0.0);
+            }
+            // flex
+            35 => {
+                self.curve_to(s[0], s[1], s[2], s[3], s[4], s[5]);
+                self.curve_to(s[6], s[7], s[8], s[9], s[10], s[11]);
+            }
+            // hflex1
+            36 => {
+                self.curve_to(s[0], s[1], s[2], s[3], s[4], 0.0);
+                self.curve_to(s[5], 0.0, s[6], s[7], s[8], -(s[1] + s[3] + s[7]));
+            }
+            // flex1: the last delta is along the dominant axis
+            _ => {
+                let dx = s[0] + s[2] + s[4] + s[6] + s[8];
+                let dy = s[1] + s[3] + s[5] + s[7] + s[9];
+                let (dx6, dy6) = if dx.abs() > dy.abs() { (s[10], -dy) } else { (-dx, s[10]) };
+                self.curve_to(s[0], s[1], s[2], s[3], s[4], s[5]);
+                self.curve_to(s[6], s[7], s[8], s[9], dx6, dy6);
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, v: f32) -> Result<(), String> {
+        if self.stack.len() >= MAX_STACK {
+            return Err(String::from("charstring stack overflow"));
+        }
+        self.stack.push(v);
+        Ok(())
+    }
+
+    fn arg(&self, i: usize) -> Result<f32, String> {
+        self.stack.get(i).copied().ok_or_else(|| String::from("charstring stack underflow"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// Build an INDEX with 2-byte offsets.
+    fn index(items: &[&[u8]], cff2: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        let count = items.len() as u32;
+        if cff2 {
+            out.extend_from_slice(&count.to_be_bytes());
+        } else {
+            out.extend_from_slice(&(count as u16).to_be_bytes());
+        }
+        if items.is_empty() {
+            return out;
+        }
+        out.push(2);
+        let mut offset = 1u16;
+        out.extend_from_slice(&offset.to_be_bytes());
+        for item in items {
+            offset += item.len() as u16;
+            out.extend_from_slice(&offset.to_be_bytes());
+        }
+        for item in items {
+            out.extend_from_slice(item);
+        }
+        out
+    }
+
+    /// DICT operand as a fixed-size 5-byte integer.
+    fn int(v: i32) -> Vec<u8> {
+        let mut out = vec![29];
+        out.extend_from_slice(&v.to_be_bytes());
+        out
+    }
+
+    fn cat(parts: &[&[u8]]) -> Vec<u8> {
+        parts.concat()
+    }
+
+    /// Name-keyed CFF with a width, hints, a local and a global subroutine.
+    fn cff1_font() -> Vec<u8> {
+        // width 500, 10 20 hstemhm, 30 40 hintmask (implicit vstem) 0xC0,
+        // 100 100 rmoveto, 200 0 rlineto, callsubr 0, callgsubr 0, endchar
+        let glyph = [
+            248, 136, 149, 159, 18, 169, 179, 19, 0xC0, 239, 239, 21, 247, 92, 139, 5, 32, 10,
+            32, 29, 14,
+        ];
+        // 50 50 50 -50 vhcurveto, return
+        let local_subr = [189, 189, 189, 89, 30, 11];
+        // -200 hlineto, return
+        let global_subr = [251, 92, 6, 11];
+
+        let header = [1, 0, 4, 2];
+        let names = index(&[b"T"], false);
+        let top_len = index(&[&[0; 17]], false).len();
+        let strings = index(&[], false);
+        let gsubrs = index(&[&global_subr], false);
+        let char_strings_at = header.len() + names.len() + top_len + strings.len() + gsubrs.len();
+        let char_strings = index(&[&[14], &glyph], false);
+        let private_at = char_strings_at + char_strings.len();
+        let private = cat(&[&int(6), &[19]]);
+        let top = cat(&[
+            &int(char_strings_at as i32), &[17],
+            &int(private.len() as i32), &int(private_at as i32), &[18],
+        ]);
+        let local_subrs = index(&[&local_subr], false);
+        cat(&[
+            &header, &names, &index(&[&top], false), &strings, &gsubrs,
+            &char_strings, &private, &local_subrs,
+        ])
+    }
+
+    #[test]
+    fn parses_cff_charstrings() {
+        let data = cff1_font();
+        let cff = CffTable::parse(&data).unwrap();
+        assert_eq!(cff.num_glyphs(), 2);
+        assert!(cff.glyph(&data, 0).unwrap().commands.is_empty());
+
+        let glyph = cff.glyph(&data, 1).unwrap();
+        assert_eq!(glyph.commands, vec![
+            PathCommand::MoveTo(100.0, 100.0),
+            PathCommand::LineTo(300.0, 100.0),
+            PathCommand::CurveTo(300.0, 150.0, 350.0, 200.0, 300.0, 200.0),
+            PathCommand::LineTo(100.0, 200.0),
+        ]);
+        assert_eq!((glyph.x_min, glyph.y_min, glyph.x_max, glyph.y_max), (100.0, 100.0, 350.0, 200.0));
+    }
+
+    #[test]
+    fn parses_cff2_with_blend() {
+        // Top DICT: CharStrings, FDArray, vstore
+        let top_len = 6 + 7 + 6;
+        let header = [2, 0, 5, 0, top_len as u8];
+        let gsubrs = index(&[], true);
+        // 100 5 -5 1 blend 100 rmoveto, 200 0 rlineto (no endchar in CFF2)
+        let glyph = [239, 144, 134, 140, 16, 239, 21, 247, 92, 139, 5];
+        let char_strings_at = header.len() + top_len + gsubrs.len();
+        let char_strings = index(&[&glyph], true);
+        // VariationStore with one ItemVariationData of two regions
+        let vstore_at = char_strings_at + char_strings.len();
+        let vstore = [
+            0, 24, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 12,
+            0, 0, 0, 0, 0, 2, 0, 0, 0, 1,
+        ];
+        let fd_array_at = vstore_at + vstore.len();
+        let font_dict = cat(&[&int(0), &int(0), &[18]]);
+        let fd_array = index(&[&font_dict], true);
+        let top = cat(&[
+            &int(char_strings_at as i32), &[17],
+            &int(fd_array_at as i32), &[12, 36],
+            &int(vstore_at as i32), &[24],
+        ]);
+        assert_eq!(top.len(), top_len);
+        let data = cat(&[&header, &top, &gsubrs, &char_strings, &vstore, &fd_array]);
+
+        let glyph = CffTable::parse_cff2(&data).unwrap().glyph(&data, 0).unwrap();
+        assert_eq!(glyph.commands, vec![
+            PathCommand::MoveTo(100.0, 100.0),
+            PathCommand::LineTo(300.0, 100.0),
+        ]);
+    }
+
+    #[test]
+    fn rejects_bad_charstrings() {
+        let mut data = cff1_font();
+        // Point the glyph's callsubr past the local subroutines
+        let at = data.windows(2).position(|w| w == [32, 10]).unwrap();
+        data[at] = 33;
+        let cff = CffTable::parse(&data).unwrap();
+        assert!(cff.glyph(&data, 1).is_err());
+        assert!(cff.glyph(&data, 2).is_err());
+        assert!(CffTable::parse(&data[..20]).is_err());
+    }
+
+    /// Wrap `cff1_font` in an 'OTTO' sfnt mapping 'A' to glyph 1.
+    fn otf_font() -> Vec<u8> {
+        let mut head = vec![0u8; 54];
+        head[12..16].copy_from_slice(&0x5F0F3CF5u32.to_be_bytes());
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let mut hhea = vec![0u8; 36];
+        hhea[4..6].copy_from_slice(&800i16.to_be_bytes());
+        hhea[6..8].copy_from_slice(&(-200i16).to_be_bytes());
+        hhea[34..36].copy_from_slice(&2u16.to_be_bytes());
+        let maxp = [0, 0, 0x50, 0, 0, 2];
+        let hmtx = [1, 244, 0, 0, 2, 88, 0, 100];
+        let cmap = [
+            0, 0, 0, 1, 0, 3, 0, 10, 0, 0, 0, 12,
+            0, 12, 0, 0, 0, 0, 0, 28, 0, 0, 0, 0, 0, 0, 0, 1,
+            0, 0, 0, 0x41, 0, 0, 0, 0x41, 0, 0, 0, 1,
+        ];
+        let cff = cff1_font();
+        let tables: [(&[u8; 4], &[u8]); 6] = [
+            (b"CFF ", &cff), (b"cmap", &cmap), (b"head", &head),
+            (b"hhea", &hhea), (b"hmtx", &hmtx), (b"maxp", &maxp),
+        ];
+        let mut out = vec![0x4F, 0x54, 0x54, 0x4F, 0, tables.len() as u8, 0, 0, 0, 0, 0, 0];
+        let mut offset = 12 + tables.len() * 16;
+        for (tag, data) in &tables {
+            out.extend_from_slice(&tag[..]);
+            out.extend_from_slice(&[0; 4]);
+            out.extend_from_slice(&(offset as u32).to_be_bytes());
+            out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            offset += data.len();
+        }
+        for (_, data) in &tables {
+            out.extend_from_slice(data);
+        }
+        out
+    }
+
+    #[test]
+    fn loads_otf_font() {
+        let font = crate::Font::parse(&otf_font()).unwrap();
+        let glyph = font.glyph_index('A');
+        assert_eq!(glyph, 1);
+        assert_eq!(font.advance_width(glyph, 10.0), 6.0);
+
+        // 250 x 100 units at 1/100 scale, with the 2px safety margin
+        let bitmap = font.rasterize_glyph(glyph, 10.0).unwrap();
+        assert_eq!((bitmap.width, bitmap.height), (5, 3));
+        assert_eq!(bitmap.x_offset, 1);
+        assert!(bitmap.coverage.iter().any(|&c| c > 200));
+        assert_eq!(font.debug_glyph(glyph).num_contours, 1);
+
+        // .notdef is empty
+        assert_eq!(font.rasterize_glyph(0, 10.0).unwrap().height, 0);
+    }
+
+    #[test]
+    fn parses_real_numbers() {
+        // -2.25, 0.140541E-3
+        assert_eq!(parse_real(&[0xE2, 0xA2, 0x5F]).unwrap(), (-2.25, 3));
+        let (v, len) = parse_real(&[0x0A, 0x14, 0x05, 0x41, 0xC3, 0xFF]).unwrap();
+        assert!((v - 0.140541e-3).abs() < 1e-9);
+        assert_eq!(len, 6);
+    }
+}
@@ -46,6 +46,8 @@ const WE_HAVE_A_SCALE: u16 = 0x0008;
 const MORE_COMPONENTS: u16 = 0x0020;
 const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
 const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const SCALED_COMPONENT_OFFSET: u16 = 0x0800;
+const UNSCALED_COMPONENT_OFFSET: u16 = 0x1000;
 
 pub fn parse_glyph(glyf_data: &[u8], offset: u32) -> Result<Option<SimpleGlyph>, String> {
     let off = offset as usize;
@@ -241,13 +243,27 @@ fn parse_compound_glyph(
     })
 }
 
+/// Maximum nesting of compound glyphs inside compound glyphs.
+const MAX_COMPONENT_DEPTH: u32 = 8;
+
 /// Resolve a compound glyph by recursively looking up components.
-/// `lookup_fn` should return the parsed simple glyph for a given glyph index.
+/// `lookup_fn` should return the `glyf` offset of a glyph index, or `None`
+/// for an empty glyph. Nested compounds are flattened, component transforms
+/// and scaled offsets applied, and point-matched components aligned.
 pub fn resolve_compound(
     glyf_data: &[u8],
     offset: u32,
-    lookup_fn: &dyn Fn(u16) -> Option<SimpleGlyph>,
+    lookup_fn: &dyn Fn(u16) -> Option<u32>,
 ) -> Result<Option<SimpleGlyph>, String> {
+    resolve_at_depth(glyf_data, offset, lookup_fn, 0).map(Some)
+}
+
+fn resolve_at_depth(
+    glyf_data: &[u8],
+    offset: u32,
+    lookup_fn: &dyn Fn(u16) -> Option<u32>,
+    depth: u32,
+) -> Result<SimpleGlyph, String> {
     let off = offset as usize;
     if off + 10 > glyf_data.len() {
         return Err(String::from("compound glyph offset out of bounds"));
@@ -261,30 +277,23 @@ pub fn resolve_compound(
 
     if num_contours >= 0 {
         // Not compound — parse as simple
-        return parse_simple_glyph(&mut r, num_contours as u16, x_min, y_min, x_max, y_max)
-            .map(Some);
+        return parse_simple_glyph(&mut r, num_contours as u16, x_min, y_min, x_max, y_max);
+    }
+    if depth >= MAX_COMPONENT_DEPTH {
+        return Err(String::from("compound glyph nested too deeply"));
     }
 
-    let mut all_contours = Vec::new();
+    let mut all_contours: Vec<Vec<GlyphPoint>> = Vec::new();
 
     loop {
         let flags = r.read_u16()?;
         let glyph_index = r.read_u16()?;
 
-        let (x_offset, y_offset) = if flags & ARG_1_AND_2_ARE_WORDS != 0 {
-            if flags & ARGS_ARE_XY_VALUES != 0 {
-                (r.read_i16()?, r.read_i16()?)
-            } else {
-                let _ = r.read_u16()?;
-                let _ = r.read_u16()?;
-                (0i16, 0i16)
-            }
-        } else if flags & ARGS_ARE_XY_VALUES != 0 {
-            (r.read_i8()? as i16, r.read_i8()? as i16)
-        } else {
-            let _ = r.read_u8()?;
-            let _ = r.read_u8()?;
-            (0i16, 0i16)
+        let (arg1, arg2) = match (flags & ARG_1_AND_2_ARE_WORDS != 0, flags & ARGS_ARE_XY_VALUES != 0) {
+            (true, true) => (r.read_i16()? as i32, r.read_i16()? as i32),
+            (true, false) => (r.read_u16()? as i32, r.read_u16()? as i32),
+            (false, true) => (r.read_i8()? as i32, r.read_i8()? as i32),
+            (false, false) => (r.read_u8()? as i32, r.read_u8()? as i32),
         };
 
         let (scale_xx, scale_xy, scale_yx, scale_yy) = if flags & WE_HAVE_A_SCALE != 0 {
@@ -304,19 +313,46 @@ pub fn resolve_compound(
             (1.0, 0.0, 0.0, 1.0)
         };
 
-        if let Some(component_glyph) = lookup_fn(glyph_index) {
-            for contour in &component_glyph.contours {
-                let transformed: Vec<GlyphPoint> = contour.iter().map(|p| {
-                    let tx = (p.x as f32 * scale_xx + p.y as f32 * scale_yx) + x_offset as f32;
-                    let ty = (p.x as f32 * scale_xy + p.y as f32 * scale_yy) + y_offset as f32;
-                    GlyphPoint {
-                        x: tx as i16,
-                        y: ty as i16,
-                        on_curve: p.on_curve,
-                    }
-                }).collect();
-                all_contours.push(transformed);
+        let component = match lookup_fn(glyph_index) {
+            Some(comp_off) => resolve_at_depth(glyf_data, comp_off, lookup_fn, depth + 1)?.contours,
+            None => Vec::new(),
+        };
+        let transform = |p: &GlyphPoint| (
+            p.x as f32 * scale_xx + p.y as f32 * scale_yx,
+            p.x as f32 * scale_xy + p.y as f32 * scale_yy,
+        );
+
+        let (dx, dy) = if flags & ARGS_ARE_XY_VALUES != 0 {
+            if flags & SCALED_COMPONENT_OFFSET != 0 && flags & UNSCALED_COMPONENT_OFFSET == 0 {
+                (arg1 as f32 * scale_xx + arg2 as f32 * scale_yx,
+                 arg1 as f32 * scale_xy + arg2 as f32 * scale_yy)
+            } else {
+                (arg1 as f32, arg2 as f32)
             }
+        } else {
+            // Point matching: move the component so its point `arg2`
+            // lands on point `arg1` of the glyph assembled so far.
+            let parent = all_contours.iter().flatten().nth(arg1 as usize);
+            let child = component.iter().flatten().nth(arg2 as usize);
+            match (parent, child) {
+                (Some(p), Some(c)) => {
+                    let (cx, cy) = transform(c);
+                    (p.x as f32 - cx, p.y as f32 - cy)
+                }
+                _ => (0.0, 0.0),
+            }
+        };
+
+        for contour in &component {
+            let transformed: Vec<GlyphPoint> = contour.iter().map(|p| {
+                let (tx, ty) = transform(p);
+                GlyphPoint {
+                    x: round_to_i16(tx + dx),
+                    y: round_to_i16(ty + dy),
+                    on_curve: p.on_curve,
+                }
+            }).collect();
+            all_contours.push(transformed);
         }
 
         if flags & MORE_COMPONENTS == 0 {
@@ -324,8 +360,13 @@ pub fn resolve_compound(
         }
     }
 
-    Ok(Some(SimpleGlyph {
+    Ok(SimpleGlyph {
         x_min, y_min, x_max, y_max,
         contours: all_contours,
-    }))
+    })
+}
+
+fn round_to_i16(v: f32) -> i16 {
+    let r = if v < 0.0 { v - 0.5 } else { v + 0.5 };
+    r.clamp(i16::MIN as f32, i16::MAX as f32) as i16
 }
@@ -0,0 +1,187 @@
+//! `GPOS` table: glyph positioning.
+//!
+//! Applies single adjustments and pair adjustments (kerning). Mark
+//! attachment and contextual positioning are not supported.
+
+use crate::tables::layout::{coverage_index, glyph_class, i16_at, u16_at, Gdef, GlyphInfo, LayoutTable, Lookup};
+
+/// Features applied by default when shaping horizontal text.
+pub const DEFAULT_FEATURES: [&[u8; 4]; 1] = [b"kern"];
+
+/// GPOS extension lookup type.
+pub const EXTENSION: u16 = 9;
+
+/// Position adjustment of one glyph, in font units (y up).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Adjustment {
+    pub x_advance: i32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+}
+
+// ValueRecord format bits
+const X_PLACEMENT: u16 = 0x0001;
+const Y_PLACEMENT: u16 = 0x0002;
+const X_ADVANCE: u16 = 0x0004;
+
+/// Size in bytes of a ValueRecord: one u16 per set format bit.
+fn value_size(format: u16) -> usize {
+    (format & 0x00FF).count_ones() as usize * 2
+}
+
+/// Add the ValueRecord at `at` to `adj`. Device tables are ignored.
+fn add_value(data: &[u8], at: usize, format: u16, adj: &mut Adjustment) -> Option<()> {
+    let mut field = at;
+    let mut read = |bit: u16| -> Option<i32> {
+        if format & bit == 0 {
+            return Some(0);
+        }
+        let v = i16_at(data, field)? as i32;
+        field += 2;
+        Some(v)
+    };
+    adj.x_offset += read(X_PLACEMENT)?;
+    adj.y_offset += read(Y_PLACEMENT)?;
+    adj.x_advance += read(X_ADVANCE)?;
+    Some(())
+}
+
+/// A parsed `GPOS` table ready to apply to a glyph buffer.
+pub struct Gpos<'a> {
+    pub data: &'a [u8],
+    pub table: &'a LayoutTable,
+    pub gdef: Option<(&'a [u8], Gdef)>,
+}
+
+impl Gpos<'_> {
+    /// Whether any active lookup can adjust positions.
+    pub fn has_lookups(&self) -> bool {
+        self.table.active.iter().any(|&i| matches!(self.table.lookups[i as usize].kind, 1 | 2))
+    }
+
+    /// Apply every active lookup, accumulating into `adjustments` (one per
+    /// glyph in `buf`).
+    pub fn apply(&self, buf: &[GlyphInfo], adjustments: &mut [Adjustment]) {
+        for &index in &self.table.active {
+            let lookup = &self.table.lookups[index as usize];
+            let mut i = 0;
+            while i < buf.len() {
+                if lookup.may_apply(buf[i].glyph) && !self.ignores(lookup, buf[i].glyph) {
+                    if let Some(next) = self.apply_at(lookup, buf, adjustments, i) {
+                        i = next.max(i + 1);
+                        continue;
+                    }
+                }
+                i += 1;
+            }
+        }
+    }
+
+    /// Pair adjustment between two adjacent glyphs from the active pair
+    /// lookups, in font units: the change to the first glyph's advance.
+    pub fn pair_kern(&self, left: u16, right: u16) -> i32 {
+        let buf = [GlyphInfo { glyph: left, cluster: 0 }, GlyphInfo { glyph: right, cluster: 1 }];
+        let mut adj = [Adjustment::default(); 2];
+        for &index in &self.table.active {
+            let lookup = &self.table.lookups[index as usize];
+            if lookup.kind != 2 {
+                continue;
+            }
+            for &sub in &lookup.subtables {
+                if self.pair(sub, &buf, &mut adj, 0, 1).is_some() {
+                    break;
+                }
+            }
+        }
+        adj[0].x_advance
+    }
+
+    fn ignores(&self, lookup: &Lookup, glyph: u16) -> bool {
+        match &self.gdef {
+            Some((data, gdef)) => gdef.ignores(data, lookup, glyph),
+            None => false,
+        }
+    }
+
+    fn apply_at(&self, lookup: &Lookup, buf: &[GlyphInfo], adj: &mut [Adjustment], i: usize) -> Option<usize> {
+        for &sub in &lookup.subtables {
+            let applied = match lookup.kind {
+                1 => self.single(sub, buf, adj, i),
+                2 => {
+                    let j = (i + 1..buf.len()).find(|&j| !self.ignores(lookup, buf[j].glyph))?;
+                    self.pair(sub, buf, adj, i, j)
+                }
+                _ => None,
+            };
+            if applied.is_some() {
+                return applied;
+            }
+        }
+        None
+    }
+
+    fn single(&self, sub: usize, buf: &[GlyphInfo], adj: &mut [Adjustment], i: usize) -> Option<usize> {
+        let d = self.data;
+        let cov = coverage_index(d, sub + u16_at(d, sub + 2)? as usize, buf[i].glyph)?;
+        let format = u16_at(d, sub + 4)?;
+        let at = match u16_at(d, sub)? {
+            1 => sub + 6,
+            2 => sub + 8 + cov as usize * value_size(format),
+            _ => return None,
+        };
+        add_value(d, at, format, &mut adj[i])?;
+        Some(i + 1)
+    }
+
+    /// Pair adjustment of glyphs `i` and `j`. Returns the position to
+    /// continue from: the second glyph again, unless it was adjusted too.
+    fn pair(&self, sub: usize, buf: &[GlyphInfo], adj: &mut [Adjustment], i: usize, j: usize) -> Option<usize> {
+        let d = self.data;
+        let (first, second) = (buf[i].glyph, buf[j].glyph);
+        let cov = coverage_index(d, sub + u16_at(d, sub + 2)? as usize, first)?;
+        let format1 = u16_at(d, sub + 4)?;
+        let format2 = u16_at(d, sub + 6)?;
+        let (size1, size2) = (value_size(format1), value_size(format2));
+        let record = match u16_at(d, sub)? {
+            1 => {
+                // Pair sets of (second glyph, value1, value2), sorted by glyph
+                let set = sub + u16_at(d, sub + 10 + cov as usize * 2)? as usize;
+                let stride = 2 + size1 + size2;
+                let (mut lo, mut hi) = (0, u16_at(d, set)? as usize);
+                loop {
+                    if lo >= hi {
+                        return None;
+                    }
+                    let mid = (lo + hi) / 2;
+                    let rec = set + 2 + mid * stride;
+                    let glyph = u16_at(d, rec)?;
+                    if glyph == second {
+                        break rec + 2;
+                    } else if glyph < second {
+                        lo = mid + 1;
+                    } else {
+                        hi = mid;
+                    }
+                }
+            }
+            2 => {
+                // Class1 x Class2 matrix of (value1, value2)
+                let class1 = glyph_class(d, sub + u16_at(d, sub + 8)? as usize, first) as usize;
+                let class2 = glyph_class(d, sub + u16_at(d, sub + 10)? as usize, second) as usize;
+                let class1_count = u16_at(d, sub + 12)? as usize;
+                let class2_count = u16_at(d, sub + 14)? as usize;
+                if class1 >= class1_count || class2 >= class2_count {
+                    return None;
+                }
+                sub + 16 + (class1 * class2_count + class2) * (size1 + size2)
+            }
+            _ => return None,
+        };
+        add_value(d, record, format1, &mut adj[i])?;
+        if format2 != 0 {
+            add_value(d, record + size1, format2, &mut adj[j])?;
+            return Some(j + 1);
+        }
+        Some(j)
+    }
+}
@@ -0,0 +1,359 @@
+//! `GSUB` table: glyph substitution.
+//!
+//! Applies single, multiple, alternate and ligature substitutions, and
+//! contextual / chained contextual substitutions that invoke them (how
+//! programming fonts implement `calt` ligatures). Reverse chaining
+//! substitution (type 8) is not supported.
+
+use alloc::vec::Vec;
+use crate::tables::layout::{
+    coverage_index, glyph_class, i16_at, u16_at, Gdef, GlyphInfo, LayoutTable, Lookup,
+};
+
+/// Features applied by default when shaping horizontal text.
+pub const DEFAULT_FEATURES: [&[u8; 4]; 6] = [b"ccmp", b"locl", b"rlig", b"liga", b"clig", b"calt"];
+
+/// GSUB extension lookup type.
+pub const EXTENSION: u16 = 7;
+
+/// Maximum depth of lookups invoked from contextual lookups.
+const MAX_NESTING: usize = 6;
+
+/// Buffer size beyond which multiple substitution stops expanding glyphs.
+const MAX_GLYPHS: usize = 1 << 14;
+
+/// How the values of a context rule's glyph sequences are matched.
+#[derive(Clone, Copy)]
+enum Match {
+    /// Glyph IDs
+    Glyphs,
+    /// Classes in the ClassDef at this offset
+    Classes(usize),
+    /// Coverage offsets relative to this subtable
+    Coverages(usize),
+}
+
+/// A sequence of `count` u16 values at `at`, matched by `by`.
+#[derive(Clone, Copy)]
+struct Seq {
+    at: usize,
+    count: usize,
+    by: Match,
+}
+
+/// A parsed `GSUB` table ready to apply to a glyph buffer.
+pub struct Gsub<'a> {
+    pub data: &'a [u8],
+    pub table: &'a LayoutTable,
+    pub gdef: Option<(&'a [u8], Gdef)>,
+}
+
+impl Gsub<'_> {
+    /// Apply every active lookup to the buffer, in lookup list order.
+    pub fn apply(&self, buf: &mut Vec<GlyphInfo>) {
+        for &index in &self.table.active {
+            let lookup = &self.table.lookups[index as usize];
+            let mut i = 0;
+            while i < buf.len() {
+                if lookup.may_apply(buf[i].glyph) && !self.ignores(lookup, buf[i].glyph) {
+                    if let Some(next) = self.apply_at(index, buf, i, 0) {
+                        i = next.max(i + 1);
+                        continue;
+                    }
+                }
+                i += 1;
+            }
+        }
+    }
+
+    fn ignores(&self, lookup: &Lookup, glyph: u16) -> bool {
+        match &self.gdef {
+            Some((data, gdef)) => gdef.ignores(data, lookup, glyph),
+            None => false,
+        }
+    }
+
+    /// Next glyph after `i` the lookup doesn't skip.
+    fn next(&self, lookup: &Lookup, buf: &[GlyphInfo], i: usize) -> Option<usize> {
+        (i + 1..buf.len()).find(|&j| !self.ignores(lookup, buf[j].glyph))
+    }
+
+    /// Nearest glyph before `i` the lookup doesn't skip.
+    fn prev(&self, lookup: &Lookup, buf: &[GlyphInfo], i: usize) -> Option<usize> {
+        (0..i).rev().find(|&j| !self.ignores(lookup, buf[j].glyph))
+    }
+
+    /// Apply one lookup at position `i`. Returns the position after the
+    /// glyphs it consumed if any subtable applied.
+    fn apply_at(&self, index: u16, buf: &mut Vec<GlyphInfo>, i: usize, depth: usize) -> Option<usize> {
+        let lookup = self.table.lookups.get(index as usize)?;
+        for &sub in &lookup.subtables {
+            let applied = match lookup.kind {
+                1 => self.single(sub, buf, i),
+                2 => self.multiple(sub, buf, i),
+                3 => self.alternate(sub, buf, i),
+                4 => self.ligature(lookup, sub, buf, i),
+                5 => self.context(lookup, sub, buf, i, depth),
+                6 => self.chain_context(lookup, sub, buf, i, depth),
+                _ => None,
+            };
+            if applied.is_some() {
+                return applied;
+            }
+        }
+        None
+    }
+
+    fn single(&self, sub: usize, buf: &mut [GlyphInfo], i: usize) -> Option<usize> {
+        let d = self.data;
+        let cov = coverage_index(d, sub + u16_at(d, sub + 2)? as usize, buf[i].glyph)?;
+        buf[i].glyph = match u16_at(d, sub)? {
+            1 => (buf[i].glyph as i32 + i16_at(d, sub + 4)? as i32) as u16,
+            2 => u16_at(d, sub + 6 + cov as usize * 2)?,
+            _ => return None,
+        };
+        Some(i + 1)
+    }
+
+    fn multiple(&self, sub: usize, buf: &mut Vec<GlyphInfo>, i: usize) -> Option<usize> {
+        let d = self.data;
+        let cov = coverage_index(d, sub + u16_at(d, sub + 2)? as usize, buf[i].glyph)?;
+        let seq = sub + u16_at(d, sub + 6 + cov as usize * 2)? as usize;
+        let count = u16_at(d, seq)? as usize;
+        if count == 0 || buf.len() + count > MAX_GLYPHS {
+            return None;
+        }
+        let cluster = buf[i].cluster;
+        let glyphs = (0..count)
+            .map(|k| u16_at(d, seq + 2 + k * 2).map(|glyph| GlyphInfo { glyph, cluster }))
+            .collect::<Option<Vec<_>>>()?;
+        buf.splice(i..i + 1, glyphs);
+        Some(i + count)
+    }
+
+    /// Alternates are only reachable through features this shaper doesn't
+    /// enable by default, or from contextual lookups; take the first.
+    fn alternate(&self, sub: usize, buf: &mut [GlyphInfo], i: usize) -> Option<usize> {
+        let d = self.data;
+        let cov = coverage_index(d, sub + u16_at(d, sub + 2)? as usize, buf[i].glyph)?;
+        let set = sub + u16_at(d, sub + 6 + cov as usize * 2)? as usize;
+        if u16_at(d, set)? == 0 {
+            return None;
+        }
+        buf[i].glyph = u16_at(d, set + 2)?;
+        Some(i + 1)
+    }
+
+    fn ligature(&self, lookup: &Lookup, sub: usize, buf: &mut Vec<GlyphInfo>, i: usize) -> Option<usize> {
+        let d = self.data;
+        let cov = coverage_index(d, sub + u16_at(d, sub + 2)? as usize, buf[i].glyph)?;
+        let set = sub + u16_at(d, sub + 6 + cov as usize * 2)? as usize;
+        let count = u16_at(d, set)? as usize;
+        'ligatures: for l in 0..count {
+            let lig = set + u16_at(d, set + 2 + l * 2)? as usize;
+            let components = u16_at(d, lig + 2)? as usize;
+            let mut matched = Vec::with_capacity(components.saturating_sub(1));
+            let mut j = i;
+            for c in 1..components {
+                j = match self.next(lookup, buf, j) {
+                    Some(j) if Some(buf[j].glyph) == u16_at(d, lig + 2 + c * 2) => j,
+                    _ => continue 'ligatures,
+                };
+                matched.push(j);
+            }
+            buf[i].glyph = u16_at(d, lig)?;
+            for &j in matched.iter().rev() {
+                buf.remove(j);
+            }
+            return Some(i + 1);
+        }
+        None
+    }
+
+    fn matches(&self, by: Match, value: u16, glyph: u16) -> bool {
+        match by {
+            Match::Glyphs => value == glyph,
+            Match::Classes(class_def) => glyph_class(self.data, class_def, glyph) == value,
+            Match::Coverages(base) => coverage_index(self.data, base + value as usize, glyph).is_some(),
+        }
+    }
+
+    /// Match a rule around position `i`, whose first input glyph has already
+    /// been matched. `input` holds the rest of the input sequence. Returns
+    /// the buffer positions of the whole input sequence.
+    fn match_rule(
+        &self,
+        lookup: &Lookup,
+        buf: &[GlyphInfo],
+        i: usize,
+        backtrack: Seq,
+        input: Seq,
+        lookahead: Seq,
+    ) -> Option<Vec<usize>> {
+        let d = self.data;
+        let mut positions = Vec::with_capacity(input.count + 1);
+        positions.push(i);
+        let mut j = i;
+        for k in 0..input.count {
+            j = self.next(lookup, buf, j)?;
+            if !self.matches(input.by, u16_at(d, input.at + k * 2)?, buf[j].glyph) {
+                return None;
+            }
+            positions.push(j);
+        }
+        let mut back = i;
+        for k in 0..backtrack.count {
+            back = self.prev(lookup, buf, back)?;
+            if !self.matches(backtrack.by, u16_at(d, backtrack.at + k * 2)?, buf[back].glyph) {
+                return None;
+            }
+        }
+        for k in 0..lookahead.count {
+            j = self.next(lookup, buf, j)?;
+            if !self.matches(lookahead.by, u16_at(d, lookahead.at + k * 2)?, buf[j].glyph) {
+                return None;
+            }
+        }
+        Some(positions)
+    }
+
+    /// Run a matched rule's (sequence index, lookup index) records.
+    fn apply_records(
+        &self,
+        buf: &mut Vec<GlyphInfo>,
+        mut positions: Vec<usize>,
+        records: usize,
+        count: usize,
+        depth: usize,
+    ) -> Option<usize> {
+        if depth >= MAX_NESTING {
+            return None;
+        }
+        for r in 0..count {
+            let seq_index = u16_at(self.data, records + r * 4)? as usize;
+            let lookup_index = u16_at(self.data, records + r * 4 + 2)?;
+            let Some(&pos) = positions.get(seq_index) else { continue };
+            if pos >= buf.len() {
+                continue;
+            }
+            let before = buf.len();
+            self.apply_at(lookup_index, buf, pos, depth + 1);
+            // Keep later positions on their glyphs if the length changed
+            let delta = buf.len() as isize - before as isize;
+            if delta != 0 {
+                for p in &mut positions[seq_index + 1..] {
+                    *p = (*p as isize + delta).max(pos as isize) as usize;
+                }
+            }
+        }
+        positions.last().map(|&p| (p + 1).min(buf.len()))
+    }
+
+    fn context(&self, lookup: &Lookup, sub: usize, buf: &mut Vec<GlyphInfo>, i: usize, depth: usize) -> Option<usize> {
+        let d = self.data;
+        let none = Seq { at: 0, count: 0, by: Match::Glyphs };
+        match u16_at(d, sub)? {
+            1 | 2 => {
+                let format = u16_at(d, sub)?;
+                let cov = coverage_index(d, sub + u16_at(d, sub + 2)? as usize, buf[i].glyph)?;
+                let (by, set_index, sets) = if format == 1 {
+                    (Match::Glyphs, cov as usize, sub + 4)
+                } else {
+                    let class_def = sub + u16_at(d, sub + 4)? as usize;
+                    (Match::Classes(class_def), glyph_class(d, class_def, buf[i].glyph) as usize, sub + 6)
+                };
+                if set_index >= u16_at(d, sets)? as usize {
+                    return None;
+                }
+                let set = match u16_at(d, sets + 2 + set_index * 2)? {
+                    0 => return None,
+                    off => sub + off as usize,
+                };
+                for r in 0..u16_at(d, set)? as usize {
+                    let rule = set + u16_at(d, set + 2 + r * 2)? as usize;
+                    let glyph_count = u16_at(d, rule)? as usize;
+                    let record_count = u16_at(d, rule + 2)? as usize;
+                    let input = Seq { at: rule + 4, count: glyph_count.saturating_sub(1), by };
+                    if let Some(positions) = self.match_rule(lookup, buf, i, none, input, none) {
+                        let records = input.at + input.count * 2;
+                        return self.apply_records(buf, positions, records, record_count, depth);
+                    }
+                }
+                None
+            }
+            3 => {
+                let glyph_count = u16_at(d, sub + 2)? as usize;
+                let record_count = u16_at(d, sub + 4)? as usize;
+                if glyph_count == 0
+                    || coverage_index(d, sub + u16_at(d, sub + 6)? as usize, buf[i].glyph).is_none()
+                {
+                    return None;
+                }
+                let input = Seq { at: sub + 8, count: glyph_count - 1, by: Match::Coverages(sub) };
+                let positions = self.match_rule(lookup, buf, i, none, input, none)?;
+                let records = sub + 6 + glyph_count * 2;
+                self.apply_records(buf, positions, records, record_count, depth)
+            }
+            _ => None,
+        }
+    }
+
+    fn chain_context(&self, lookup: &Lookup, sub: usize, buf: &mut Vec<GlyphInfo>, i: usize, depth: usize) -> Option<usize> {
+        let d = self.data;
+        match u16_at(d, sub)? {
+            1 | 2 => {
+                let format = u16_at(d, sub)?;
+                let cov = coverage_index(d, sub + u16_at(d, sub + 2)? as usize, buf[i].glyph)?;
+                let (matchers, set_index, sets) = if format == 1 {
+                    ([Match::Glyphs; 3], cov as usize, sub + 4)
+                } else {
+                    let class_def = |at: usize| Some(Match::Classes(sub + u16_at(d, at)? as usize));
+                    let input_def = sub + u16_at(d, sub + 6)? as usize;
+                    let matchers = [class_def(sub + 4)?, Match::Classes(input_def), class_def(sub + 8)?];
+                    (matchers, glyph_class(d, input_def, buf[i].glyph) as usize, sub + 10)
+                };
+                if set_index >= u16_at(d, sets)? as usize {
+                    return None;
+                }
+                let set = match u16_at(d, sets + 2 + set_index * 2)? {
+                    0 => return None,
+                    off => sub + off as usize,
+                };
+                for r in 0..u16_at(d, set)? as usize {
+                    let rule = set + u16_at(d, set + 2 + r * 2)? as usize;
+                    let backtrack = Seq { at: rule + 2, count: u16_at(d, rule)? as usize, by: matchers[0] };
+                    let input_at = backtrack.at + backtrack.count * 2;
+                    let input_count = u16_at(d, input_at)? as usize;
+                    let input = Seq { at: input_at + 2, count: input_count.saturating_sub(1), by: matchers[1] };
+                    let lookahead_at = input.at + input.count * 2;
+                    let lookahead = Seq { at: lookahead_at + 2, count: u16_at(d, lookahead_at)? as usize, by: matchers[2] };
+                    let records_at = lookahead.at + lookahead.count * 2;
+                    if let Some(positions) = self.match_rule(lookup, buf, i, backtrack, input, lookahead) {
+                        let record_count = u16_at(d, records_at)? as usize;
+                        return self.apply_records(buf, positions, records_at + 2, record_count, depth);
+                    }
+                }
+                None
+            }
+            3 => {
+                let by = Match::Coverages(sub);
+                let backtrack = Seq { at: sub + 4, count: u16_at(d, sub + 2)? as usize, by };
+                let input_at = backtrack.at + backtrack.count * 2;
+                let input_count = u16_at(d, input_at)? as usize;
+                if input_count == 0
+                    || coverage_index(d, sub + u16_at(d, input_at + 2)? as usize, buf[i].glyph).is_none()
+                {
+                    return None;
+                }
+                let input = Seq { at: input_at + 4, count: input_count - 1, by };
+                let lookahead_at = input.at + input.count * 2;
+                let lookahead = Seq { at: lookahead_at + 2, count: u16_at(d, lookahead_at)? as usize, by };
+                let records_at = lookahead.at + lookahead.count * 2;
+                let positions = self.match_rule(lookup, buf, i, backtrack, input, lookahead)?;
+                let record_count = u16_at(d, records_at)? as usize;
+                self.apply_records(buf, positions, records_at + 2, record_count, depth)
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,397 @@
+//! OpenType layout common tables shared by `GSUB` and `GPOS`: script and
+//! feature selection, lookup lists, coverage and class definitions, and the
+//! `GDEF` glyph classes that lookup flags filter on.
+//!
+//! All offsets are bounds-checked; malformed data makes a lookup not apply
+//! rather than failing the whole font.
+
+use alloc::vec::Vec;
+
+#[inline]
+pub(crate) fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    let b = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([b[0], b[1]]))
+}
+
+#[inline]
+pub(crate) fn i16_at(data: &[u8], offset: usize) -> Option<i16> {
+    u16_at(data, offset).map(|v| v as i16)
+}
+
+#[inline]
+pub(crate) fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let b = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Index of `glyph` in the Coverage table at `offset`, if covered.
+pub(crate) fn coverage_index(data: &[u8], offset: usize, glyph: u16) -> Option<u16> {
+    let format = u16_at(data, offset)?;
+    let count = u16_at(data, offset + 2)? as usize;
+    match format {
+        1 => {
+            // Sorted glyph array
+            let (mut lo, mut hi) = (0, count);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let g = u16_at(data, offset + 4 + mid * 2)?;
+                if g == glyph {
+                    return Some(mid as u16);
+                } else if g < glyph {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+            None
+        }
+        2 => {
+            // Sorted ranges: start, end, start coverage index
+            let (mut lo, mut hi) = (0, count);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let rec = offset + 4 + mid * 6;
+                let start = u16_at(data, rec)?;
+                let end = u16_at(data, rec + 2)?;
+                if glyph < start {
+                    hi = mid;
+                } else if glyph > end {
+                    lo = mid + 1;
+                } else {
+                    return Some(u16_at(data, rec + 4)? + (glyph - start));
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Class of `glyph` in the ClassDef table at `offset` (0 if unlisted).
+pub(crate) fn glyph_class(data: &[u8], offset: usize, glyph: u16) -> u16 {
+    let lookup = || -> Option<u16> {
+        match u16_at(data, offset)? {
+            1 => {
+                let start = u16_at(data, offset + 2)?;
+                let count = u16_at(data, offset + 4)?;
+                if glyph < start || glyph - start >= count {
+                    return None;
+                }
+                u16_at(data, offset + 6 + (glyph - start) as usize * 2)
+            }
+            2 => {
+                let count = u16_at(data, offset + 2)? as usize;
+                let (mut lo, mut hi) = (0, count);
+                while lo < hi {
+                    let mid = (lo + hi) / 2;
+                    let rec = offset + 4 + mid * 6;
+                    let start = u16_at(data, rec)?;
+                    let end = u16_at(data, rec + 2)?;
+                    if glyph < start {
+                        hi = mid;
+                    } else if glyph > end {
+                        lo = mid + 1;
+                    } else {
+                        return u16_at(data, rec + 4);
+                    }
+                }
+                None
+            }
+            _ => None,
+        }
+    };
+    lookup().unwrap_or(0)
+}
+
+// Lookup flags
+const IGNORE_BASE_GLYPHS: u16 = 0x0002;
+const IGNORE_LIGATURES: u16 = 0x0004;
+const IGNORE_MARKS: u16 = 0x0008;
+const USE_MARK_FILTERING_SET: u16 = 0x0010;
+const MARK_ATTACHMENT_TYPE: u16 = 0xFF00;
+
+/// One lookup from a LookupList.
+#[derive(Debug, Clone)]
+pub struct Lookup {
+    /// Lookup type, with extension lookups already unwrapped.
+    pub kind: u16,
+    pub flag: u16,
+    pub mark_filtering_set: u16,
+    /// Subtable offsets from the start of the table
+    pub subtables: Vec<usize>,
+    /// Glyphs the lookup can start at, as a lossy bit set
+    digest: GlyphDigest,
+}
+
+impl Lookup {
+    /// False if no subtable can apply at `glyph`; true if one may.
+    #[inline]
+    pub fn may_apply(&self, glyph: u16) -> bool {
+        self.digest.may_contain(glyph)
+    }
+}
+
+/// Two 64-bit masks over bits 0-5 and 6-11 of glyph IDs. A glyph is maybe
+/// present if both of its bits are set, so most glyphs a lookup doesn't
+/// cover are rejected without a coverage table search.
+#[derive(Debug, Clone, Copy, Default)]
+struct GlyphDigest {
+    low: u64,
+    high: u64,
+}
+
+impl GlyphDigest {
+    const ALL: Self = Self { low: u64::MAX, high: u64::MAX };
+
+    fn add_range(&mut self, start: u16, end: u16) {
+        // Bits from..=to, wrapping around bit 63
+        let span = |from: u16, to: u16| -> u64 {
+            if to - from >= 63 {
+                return u64::MAX;
+            }
+            let (a, b) = (from & 63, to & 63);
+            if a <= b {
+                (u64::MAX >> (63 - b)) & (u64::MAX << a)
+            } else {
+                (u64::MAX << a) | (u64::MAX >> (63 - b))
+            }
+        };
+        self.low |= span(start, end);
+        self.high |= span(start >> 6, end >> 6);
+    }
+
+    fn may_contain(&self, glyph: u16) -> bool {
+        self.low & (1 << (glyph & 63)) != 0 && self.high & (1 << ((glyph >> 6) & 63)) != 0
+    }
+
+    /// Add every glyph of the Coverage table at `offset`.
+    fn add_coverage(&mut self, data: &[u8], offset: usize) -> Option<()> {
+        let count = u16_at(data, offset + 2)? as usize;
+        match u16_at(data, offset)? {
+            1 => {
+                for i in 0..count {
+                    let g = u16_at(data, offset + 4 + i * 2)?;
+                    self.add_range(g, g);
+                }
+            }
+            2 => {
+                for i in 0..count {
+                    let rec = offset + 4 + i * 6;
+                    let (start, end) = (u16_at(data, rec)?, u16_at(data, rec + 2)?);
+                    if start <= end {
+                        self.add_range(start, end);
+                    }
+                }
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+/// The parts of a `GSUB` or `GPOS` table the shaper uses: every lookup, and
+/// the lookups of the requested features in application order.
+#[derive(Debug, Clone)]
+pub struct LayoutTable {
+    pub lookups: Vec<Lookup>,
+    pub active: Vec<u16>,
+}
+
+impl LayoutTable {
+    /// Parse the lookup list and collect the lookups for `features` under
+    /// the 'latn' script (falling back to 'DFLT', then the first script).
+    /// `extension_type` is the table's extension lookup type (7 in GSUB,
+    /// 9 in GPOS). Returns `None` if the table header is malformed.
+    pub fn parse(data: &[u8], features: &[&[u8; 4]], extension_type: u16) -> Option<Self> {
+        let script_list = u16_at(data, 4)? as usize;
+        let feature_list = u16_at(data, 6)? as usize;
+        let lookup_list = u16_at(data, 8)? as usize;
+
+        let mut lookups = Vec::new();
+        let lookup_count = u16_at(data, lookup_list)? as usize;
+        for i in 0..lookup_count {
+            let table = lookup_list + u16_at(data, lookup_list + 2 + i * 2)? as usize;
+            lookups.push(parse_lookup(data, table, extension_type).unwrap_or(Lookup {
+                kind: 0,
+                flag: 0,
+                mark_filtering_set: 0,
+                subtables: Vec::new(),
+                digest: GlyphDigest::default(),
+            }));
+        }
+
+        let mut active: Vec<u16> = Vec::new();
+        if let Some(lang_sys) = default_lang_sys(data, script_list) {
+            let required = u16_at(data, lang_sys + 2)?;
+            let count = u16_at(data, lang_sys + 4)? as usize;
+            let feature_count = u16_at(data, feature_list)?;
+            let indices = (0..count)
+                .filter_map(|i| u16_at(data, lang_sys + 6 + i * 2))
+                .chain((required != 0xFFFF).then_some(required));
+            for index in indices {
+                if index >= feature_count {
+                    continue;
+                }
+                let rec = feature_list + 2 + index as usize * 6;
+                let tag = data.get(rec..rec + 4)?;
+                if index != required && !features.iter().any(|f| &f[..] == tag) {
+                    continue;
+                }
+                let feature = feature_list + u16_at(data, rec + 4)? as usize;
+                let n = u16_at(data, feature + 2)? as usize;
+                for k in 0..n {
+                    let lookup = u16_at(data, feature + 4 + k * 2)?;
+                    if (lookup as usize) < lookups.len() && !active.contains(&lookup) {
+                        active.push(lookup);
+                    }
+                }
+            }
+        }
+        // Features are applied together, in lookup list order
+        active.sort_unstable();
+
+        Some(Self { lookups, active })
+    }
+}
+
+fn parse_lookup(data: &[u8], table: usize, extension_type: u16) -> Option<Lookup> {
+    let mut kind = u16_at(data, table)?;
+    let is_extension = kind == extension_type;
+    let flag = u16_at(data, table + 2)?;
+    let count = u16_at(data, table + 4)? as usize;
+    let mut subtables = Vec::with_capacity(count);
+    for i in 0..count {
+        let mut sub = table + u16_at(data, table + 6 + i * 2)? as usize;
+        if is_extension {
+            // ExtensionFormat1: format, wrapped lookup type, 32-bit offset
+            kind = u16_at(data, sub + 2)?;
+            sub += u32_at(data, sub + 4)? as usize;
+        }
+        subtables.push(sub);
+    }
+    let mark_filtering_set = if flag & USE_MARK_FILTERING_SET != 0 {
+        u16_at(data, table + 6 + count * 2)?
+    } else {
+        0
+    };
+    // Contextual lookup types sit just below the extension type: 5 and 6
+    // in GSUB, 7 and 8 in GPOS
+    let (context, chain_context) = (extension_type - 2, extension_type - 1);
+    let mut digest = GlyphDigest::default();
+    for &sub in &subtables {
+        let coverage = match (kind, u16_at(data, sub)?) {
+            (k, 3) if k == context => u16_at(data, sub + 6),
+            (k, 3) if k == chain_context => {
+                let input = sub + 4 + u16_at(data, sub + 2)? as usize * 2;
+                u16_at(data, input + 2)
+            }
+            _ => u16_at(data, sub + 2),
+        };
+        match coverage {
+            Some(offset) if digest.add_coverage(data, sub + offset as usize).is_some() => {}
+            _ => digest = GlyphDigest::ALL,
+        }
+    }
+    Some(Lookup { kind, flag, mark_filtering_set, subtables, digest })
+}
+
+/// Offset of the default LangSys of the preferred script.
+fn default_lang_sys(data: &[u8], script_list: usize) -> Option<usize> {
+    let count = u16_at(data, script_list)? as usize;
+    let script_at = |i: usize| -> Option<(&[u8], usize)> {
+        let rec = script_list + 2 + i * 6;
+        Some((data.get(rec..rec + 4)?, script_list + u16_at(data, rec + 4)? as usize))
+    };
+    let script = [b"latn", b"DFLT"]
+        .iter()
+        .find_map(|want| (0..count).filter_map(script_at).find(|(tag, _)| tag == *want))
+        .or_else(|| script_at(0))?
+        .1;
+    match u16_at(data, script)? {
+        0 => None,
+        offset => Some(script + offset as usize),
+    }
+}
+
+/// `GDEF` glyph classes, used to skip glyphs according to lookup flags.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gdef {
+    glyph_class_def: usize,
+    mark_attach_class_def: usize,
+    mark_glyph_sets: usize,
+}
+
+/// Glyph class of marks in the GDEF glyph class definition.
+const CLASS_BASE: u16 = 1;
+const CLASS_LIGATURE: u16 = 2;
+const CLASS_MARK: u16 = 3;
+
+impl Gdef {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let minor = u16_at(data, 2)?;
+        Some(Self {
+            glyph_class_def: u16_at(data, 4)? as usize,
+            mark_attach_class_def: u16_at(data, 10)? as usize,
+            mark_glyph_sets: if minor >= 2 { u16_at(data, 12)? as usize } else { 0 },
+        })
+    }
+
+    /// Whether a lookup with `lookup`'s flags skips over `glyph`.
+    pub fn ignores(&self, data: &[u8], lookup: &Lookup, glyph: u16) -> bool {
+        if self.glyph_class_def == 0 || lookup.flag & 0xFF1E == 0 {
+            return false;
+        }
+        match glyph_class(data, self.glyph_class_def, glyph) {
+            CLASS_BASE => lookup.flag & IGNORE_BASE_GLYPHS != 0,
+            CLASS_LIGATURE => lookup.flag & IGNORE_LIGATURES != 0,
+            CLASS_MARK => {
+                if lookup.flag & IGNORE_MARKS != 0 {
+                    return true;
+                }
+                if lookup.flag & USE_MARK_FILTERING_SET != 0 {
+                    return !self.in_mark_set(data, lookup.mark_filtering_set, glyph);
+                }
+                let attach_type = lookup.flag & MARK_ATTACHMENT_TYPE;
+                attach_type != 0
+                    && self.mark_attach_class_def != 0
+                    && glyph_class(data, self.mark_attach_class_def, glyph) != attach_type >> 8
+            }
+            _ => false,
+        }
+    }
+
+    fn in_mark_set(&self, data: &[u8], set: u16, glyph: u16) -> bool {
+        if self.mark_glyph_sets == 0 {
+            return false;
+        }
+        let coverage = u32_at(data, self.mark_glyph_sets + 4 + set as usize * 4);
+        coverage.is_some_and(|c| coverage_index(data, self.mark_glyph_sets + c as usize, glyph).is_some())
+    }
+}
+
+/// A glyph in the shaping buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlyphInfo {
+    pub glyph: u16,
+    /// Index of the first char this glyph was produced from.
+    pub cluster: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_never_rejects_covered_glyphs() {
+        for (start, end) in [(0, 0), (5, 9), (60, 70), (63, 64), (120, 250), (4000, 4100), (65500, 65535)] {
+            let mut digest = GlyphDigest::default();
+            digest.add_range(start, end);
+            for g in start..=end {
+                assert!(digest.may_contain(g), "{g} in {start}..={end}");
+            }
+            if end - start < 32 {
+                assert!(!digest.may_contain(end.wrapping_add(40)));
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-//! TrueType table directory parser and tag-based table lookup.
+//! TrueType/OpenType table directory parser and tag-based table lookup.
 
 use alloc::string::String;
 use crate::reader::Reader;
@@ -11,6 +11,10 @@ pub mod cmap;
 pub mod loca;
 pub mod glyf;
 pub mod kern;
+pub mod cff;
+pub mod layout;
+pub mod gsub;
+pub mod gpos;
 
 #[derive(Debug, Clone, Copy)]
 pub struct TableRecord {
@@ -29,9 +33,10 @@ impl TableDirectory {
     pub fn parse(data: &[u8]) -> Result<Self, String> {
         let mut r = Reader::new(data);
         let sfversion = r.read_u32()?;
-        // Accept TrueType (0x00010000) and OpenType with TrueType outlines ('true')
-        if sfversion != 0x00010000 && sfversion != 0x74727565 {
-            return Err(String::from("not a TrueType font"));
+        // Accept TrueType (0x00010000), Apple TrueType ('true') and OpenType
+        // with CFF outlines ('OTTO')
+        if sfversion != 0x00010000 && sfversion != 0x74727565 && sfversion != 0x4F54544F {
+            return Err(String::from("not a TrueType or OpenType font"));
         }
         let num_tables = r.read_u16()?;
         r.skip(6)?; // searchRange, entrySelector, rangeShift
@@ -3,8 +3,12 @@
 //! Provides the same professional Noto Sans Mono font used in the kernel,
 //! with alpha-blended glyph rendering for smooth text output.
 
+use alloc::vec::Vec;
+
+use libunicode::bidi::{self, Direction};
+use libunicode::{cluster_width, graphemes, is_combining_mark};
 use noto_sans_mono_bitmap::{
-    get_raster, get_raster_width, FontWeight, RasterHeight,
+    get_raster, get_raster_width, FontWeight, RasterHeight, RasterizedChar,
 };
 
 use crate::color::Color;
@@ -50,6 +54,10 @@ pub fn draw_char(fb: &mut FrameBuf, ch: char, x0: usize, y0: usize, fg: Color) {
             None => return,
         },
     };
+    blit(fb, &rc, x0, y0, fg);
+}
+
+fn blit(fb: &mut FrameBuf, rc: &RasterizedChar, x0: usize, y0: usize, fg: Color) {
 
     let width = rc.width();
     for (y, row) in rc.raster().iter().enumerate() {
@@ -84,3 +92,53 @@ pub fn text_width(text: &[u8]) -> usize {
     let m = metrics();
     text.len() * m.char_width
 }
+
+/// Draw a UTF-8 string at (x, y), returning its width in pixels.
+///
+/// Each grapheme cluster takes one cell, or two for wide characters, and
+/// right-to-left text is drawn in visual order. Combining marks the font
+/// has are drawn over their base; characters it lacks show as '?'.
+pub fn draw_str(fb: &mut FrameBuf, text: &str, x: usize, y: usize, fg: Color) -> usize {
+    let m = metrics();
+    let mut cx = x;
+    for (cluster, rtl) in visual_clusters(text) {
+        let mut chars = cluster.chars();
+        let Some(base) = chars.next() else { continue };
+        let base = if rtl { bidi::mirror(base) } else { base };
+        draw_char(fb, base, cx, y, fg);
+        for mark in chars.filter(|&c| is_combining_mark(c)) {
+            if let Some(rc) = get_raster(mark, FontWeight::Regular, RasterHeight::Size16) {
+                blit(fb, &rc, cx, y, fg);
+            }
+        }
+        cx += cluster_width(cluster).max(1) * m.char_width;
+    }
+    cx - x
+}
+
+/// Measure the pixel width of a UTF-8 string as drawn by [`draw_str`].
+pub fn str_width(text: &str) -> usize {
+    graphemes(text).map(|cluster| cluster_width(cluster).max(1)).sum::<usize>() * metrics().char_width
+}
+
+/// The grapheme clusters of a line in display order, each with whether it
+/// sits in a right-to-left run.
+fn visual_clusters(text: &str) -> Vec<(&str, bool)> {
+    if text.is_ascii() {
+        return graphemes(text).map(|cluster| (cluster, false)).collect();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let levels = bidi::resolve_levels(&chars, Direction::Auto);
+    let mut clusters = Vec::new();
+    let mut cluster_levels = Vec::new();
+    let mut index = 0;
+    for cluster in graphemes(text) {
+        clusters.push(cluster);
+        cluster_levels.push(levels[index]);
+        index += cluster.chars().count();
+    }
+    bidi::visual_order(&cluster_levels)
+        .into_iter()
+        .map(|i| (clusters[i], bidi::is_rtl(cluster_levels[i])))
+        .collect()
+}
@@ -93,6 +93,41 @@ impl FrameBuf {
         self.expand_dirty(x as i32, y as i32, 1, 1);
     }
 
+    /// Alpha-blend a color over a single pixel (alpha 255 = opaque).
+    /// Expands the dirty rect.
+    #[inline]
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: Color, alpha: u8) {
+        if x >= self.width || y >= self.height || alpha == 0 {
+            return;
+        }
+        self.blend_in_bounds(x, y, color, alpha);
+        self.expand_dirty(x as i32, y as i32, 1, 1);
+    }
+
+    /// `blend_pixel` without the bounds check or dirty tracking, for
+    /// callers that clip and mark dirty regions themselves.
+    #[inline]
+    pub(crate) fn blend_in_bounds(&mut self, x: usize, y: usize, color: Color, alpha: u8) {
+        let off = y * self.stride + x * self.bpp;
+        let (c0, c1, c2) = if self.is_bgr {
+            (color.b, color.g, color.r)
+        } else {
+            (color.r, color.g, color.b)
+        };
+        let a = alpha as u16;
+        let inv = 255 - a;
+        let mix = |fg: u8, bg: u8| ((fg as u16 * a + bg as u16 * inv + 127) / 255) as u8;
+        unsafe {
+            let p = self.ptr.add(off);
+            *p = mix(c0, *p);
+            *p.add(1) = mix(c1, *p.add(1));
+            *p.add(2) = mix(c2, *p.add(2));
+            if self.bpp == 4 {
+                *p.add(3) = 0;
+            }
+        }
+    }
+
     /// Fill the entire buffer with a solid color. Marks the full buffer dirty.
     pub fn clear(&mut self, color: Color) {
         let buf = unsafe { core::slice::from_raw_parts_mut(self.ptr, self.stride * self.height) };
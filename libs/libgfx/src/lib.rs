@@ -2,13 +2,24 @@
 //!
 //! Pure drawing library operating on raw pixel buffers. No syscall dependencies —
 //! callers provide the framebuffer memory and handle flushing themselves.
+//!
+//! `shapes` draws aliased integer primitives; `path`, `stroke`, `paint` and
+//! `raster` provide anti-aliased vector paths with gradients and alpha.
+//! `text` lays out Unicode text (grapheme clusters, bidi, fallback fonts)
+//! for `ttf_font` to draw.
 
 #![no_std]
+extern crate alloc;
 
 pub mod bitmap_font;
 pub mod color;
 pub mod font;
 pub mod framebuf;
 pub mod math;
+pub mod paint;
+pub mod path;
+pub mod raster;
 pub mod shapes;
+pub mod stroke;
+pub mod text;
 pub mod ttf_font;
@@ -16,3 +16,87 @@ pub fn isqrt_i64(n: i64) -> i64 {
     }
     x
 }
+
+/// Square root of a non-negative `f32` (0.0 for negative input).
+///
+/// Bit-level initial guess refined with Newton's method; accurate to a few
+/// ULP, which is plenty for geometry.
+pub fn sqrt_f32(x: f32) -> f32 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x == f32::INFINITY {
+        return x;
+    }
+    let mut y = f32::from_bits((x.to_bits() >> 1) + 0x1FBD_1DF5);
+    for _ in 0..3 {
+        y = 0.5 * (y + x / y);
+    }
+    y
+}
+
+/// Largest integer <= x, as f32.
+#[inline]
+pub fn floor_f32(x: f32) -> f32 {
+    let i = x as i32 as f32;
+    if i > x {
+        i - 1.0
+    } else {
+        i
+    }
+}
+
+/// Smallest integer >= x, as f32.
+#[inline]
+pub fn ceil_f32(x: f32) -> f32 {
+    let i = x as i32 as f32;
+    if i < x {
+        i + 1.0
+    } else {
+        i
+    }
+}
+
+/// Sine and cosine of `angle` (radians).
+///
+/// Reduces to an octant around a multiple of pi/2 and evaluates Taylor
+/// polynomials there; absolute error is below 1e-6 for |angle| up to ~1e4.
+pub fn sin_cos(angle: f32) -> (f32, f32) {
+    use core::f32::consts::FRAC_PI_2;
+    let quadrant = floor_f32(angle / FRAC_PI_2 + 0.5);
+    // Two-step reduction keeps precision for larger angles
+    let r = (angle - quadrant * 1.570_796_4) + quadrant * 4.371_139e-8;
+    let r2 = r * r;
+    let s = r * (1.0 - r2 / 6.0 * (1.0 - r2 / 20.0 * (1.0 - r2 / 42.0 * (1.0 - r2 / 72.0))));
+    let c = 1.0 - r2 / 2.0 * (1.0 - r2 / 12.0 * (1.0 - r2 / 30.0 * (1.0 - r2 / 56.0)));
+    match (quadrant as i32) & 3 {
+        0 => (s, c),
+        1 => (c, -s),
+        2 => (-s, -c),
+        _ => (-c, s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    #[test]
+    fn float_helpers() {
+        for x in [0.0f32, 1e-6, 0.25, 2.0, 3.0, 1e6, 12345.678] {
+            let s = sqrt_f32(x);
+            assert!((s * s - x).abs() <= x * 1e-6, "sqrt({x}) = {s}");
+        }
+        for i in -2000..2000 {
+            let a = i as f32 * 0.0137;
+            let (s, c) = sin_cos(a);
+            let expected = (a as f64).sin_cos();
+            assert!((s as f64 - expected.0).abs() < 2e-6, "sin({a})");
+            assert!((c as f64 - expected.1).abs() < 2e-6, "cos({a})");
+        }
+        assert_eq!((floor_f32(-1.5), ceil_f32(-1.5)), (-2.0, -1.0));
+        assert_eq!((floor_f32(2.0), ceil_f32(2.0)), (2.0, 2.0));
+    }
+}
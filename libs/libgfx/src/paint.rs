@@ -0,0 +1,243 @@
+//! Paints for filled and stroked paths: solid colors and gradients, each
+//! with an alpha channel.
+
+use alloc::vec::Vec;
+
+use crate::color::Color;
+use crate::math::sqrt_f32;
+use crate::path::Point;
+
+/// A color at a position along a gradient (0.0 = start, 1.0 = end).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorStop {
+    pub offset: f32,
+    pub color: Color,
+    /// Opacity, 255 = opaque.
+    pub alpha: u8,
+}
+
+/// Gradient along the line from `start` to `end`; colors are constant
+/// perpendicular to it and padded beyond either end.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearGradient {
+    pub start: Point,
+    pub end: Point,
+    pub stops: Vec<ColorStop>,
+}
+
+impl LinearGradient {
+    pub fn new(x0: f32, y0: f32, x1: f32, y1: f32) -> Self {
+        Self {
+            start: Point::new(x0, y0),
+            end: Point::new(x1, y1),
+            stops: Vec::new(),
+        }
+    }
+
+    /// Add an opaque color stop.
+    pub fn stop(self, offset: f32, color: Color) -> Self {
+        self.stop_alpha(offset, color, 255)
+    }
+
+    /// Add a color stop with the given opacity.
+    pub fn stop_alpha(mut self, offset: f32, color: Color, alpha: u8) -> Self {
+        add_stop(
+            &mut self.stops,
+            ColorStop {
+                offset,
+                color,
+                alpha,
+            },
+        );
+        self
+    }
+}
+
+/// Gradient by distance from `center`, reaching the last stop at `radius`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RadialGradient {
+    pub center: Point,
+    pub radius: f32,
+    pub stops: Vec<ColorStop>,
+}
+
+impl RadialGradient {
+    pub fn new(cx: f32, cy: f32, radius: f32) -> Self {
+        Self {
+            center: Point::new(cx, cy),
+            radius,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Add an opaque color stop.
+    pub fn stop(self, offset: f32, color: Color) -> Self {
+        self.stop_alpha(offset, color, 255)
+    }
+
+    /// Add a color stop with the given opacity.
+    pub fn stop_alpha(mut self, offset: f32, color: Color, alpha: u8) -> Self {
+        add_stop(
+            &mut self.stops,
+            ColorStop {
+                offset,
+                color,
+                alpha,
+            },
+        );
+        self
+    }
+}
+
+/// Keep stops sorted by offset; equal offsets keep insertion order so a
+/// hard edge can be made with two stops at the same position.
+fn add_stop(stops: &mut Vec<ColorStop>, stop: ColorStop) {
+    let at = stops
+        .iter()
+        .position(|s| s.offset > stop.offset)
+        .unwrap_or(stops.len());
+    stops.insert(at, stop);
+}
+
+/// How a path's covered pixels are colored.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Paint {
+    Solid { color: Color, alpha: u8 },
+    Linear(LinearGradient),
+    Radial(RadialGradient),
+}
+
+impl Paint {
+    /// Opaque solid color.
+    pub const fn solid(color: Color) -> Self {
+        Paint::Solid { color, alpha: 255 }
+    }
+
+    /// Translucent solid color.
+    pub const fn with_alpha(color: Color, alpha: u8) -> Self {
+        Paint::Solid { color, alpha }
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint::solid(color)
+    }
+}
+
+impl From<LinearGradient> for Paint {
+    fn from(gradient: LinearGradient) -> Self {
+        Paint::Linear(gradient)
+    }
+}
+
+impl From<RadialGradient> for Paint {
+    fn from(gradient: RadialGradient) -> Self {
+        Paint::Radial(gradient)
+    }
+}
+
+/// Gradient lookup table entries
+const LUT_SIZE: usize = 256;
+
+/// A `Paint` prepared for per-pixel evaluation.
+pub(crate) enum Shader {
+    Solid(Color, u8),
+    Linear {
+        lut: Vec<(Color, u8)>,
+        origin: Point,
+        /// Gradient vector divided by its squared length, so that the dot
+        /// product with (p - origin) gives the offset
+        dir: Point,
+    },
+    Radial {
+        lut: Vec<(Color, u8)>,
+        center: Point,
+        inv_radius: f32,
+    },
+}
+
+impl Shader {
+    pub fn new(paint: &Paint) -> Self {
+        match paint {
+            Paint::Solid { color, alpha } => Shader::Solid(*color, *alpha),
+            Paint::Linear(g) => {
+                let (dx, dy) = (g.end.x - g.start.x, g.end.y - g.start.y);
+                let len2 = dx * dx + dy * dy;
+                let dir = if len2 > 0.0 {
+                    Point::new(dx / len2, dy / len2)
+                } else {
+                    Point::default()
+                };
+                Shader::Linear {
+                    lut: build_lut(&g.stops),
+                    origin: g.start,
+                    dir,
+                }
+            }
+            Paint::Radial(g) => Shader::Radial {
+                lut: build_lut(&g.stops),
+                center: g.center,
+                inv_radius: if g.radius > 0.0 { 1.0 / g.radius } else { 0.0 },
+            },
+        }
+    }
+
+    /// Color and opacity at the center of pixel (x, y).
+    #[inline]
+    pub fn eval(&self, x: usize, y: usize) -> (Color, u8) {
+        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+        match self {
+            Shader::Solid(color, alpha) => (*color, *alpha),
+            Shader::Linear { lut, origin, dir } => {
+                lut_at(lut, (px - origin.x) * dir.x + (py - origin.y) * dir.y)
+            }
+            Shader::Radial {
+                lut,
+                center,
+                inv_radius,
+            } => {
+                let (dx, dy) = (px - center.x, py - center.y);
+                lut_at(lut, sqrt_f32(dx * dx + dy * dy) * inv_radius)
+            }
+        }
+    }
+}
+
+#[inline]
+fn lut_at(lut: &[(Color, u8)], t: f32) -> (Color, u8) {
+    let i = (t.clamp(0.0, 1.0) * (LUT_SIZE - 1) as f32 + 0.5) as usize;
+    lut[i.min(LUT_SIZE - 1)]
+}
+
+/// Sample the gradient at `LUT_SIZE` evenly spaced offsets, interpolating
+/// color and alpha linearly between stops.
+fn build_lut(stops: &[ColorStop]) -> Vec<(Color, u8)> {
+    let mut lut = Vec::with_capacity(LUT_SIZE);
+    for i in 0..LUT_SIZE {
+        let t = i as f32 / (LUT_SIZE - 1) as f32;
+        let entry = match stops.iter().position(|s| s.offset > t) {
+            _ if stops.is_empty() => (Color::BLACK, 0),
+            Some(0) => (stops[0].color, stops[0].alpha),
+            None => {
+                let last = stops[stops.len() - 1];
+                (last.color, last.alpha)
+            }
+            Some(n) => {
+                let (a, b) = (stops[n - 1], stops[n]);
+                let f = (t - a.offset) / (b.offset - a.offset);
+                let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * f + 0.5) as u8;
+                (
+                    Color::rgb(
+                        mix(a.color.r, b.color.r),
+                        mix(a.color.g, b.color.g),
+                        mix(a.color.b, b.color.b),
+                    ),
+                    mix(a.alpha, b.alpha),
+                )
+            }
+        };
+        lut.push(entry);
+    }
+    lut
+}
@@ -0,0 +1,482 @@
+//! Vector paths: lines, quadratic and cubic Béziers, arcs, and common shapes.
+//!
+//! Coordinates are `f32` pixels with y pointing down. A `Path` is only
+//! geometry — fill or stroke it into a `FrameBuf` with the functions in
+//! [`crate::raster`].
+
+use alloc::vec::Vec;
+
+use crate::math::{ceil_f32, sin_cos, sqrt_f32};
+
+/// A point in pixel coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    #[inline]
+    pub(crate) fn lerp(self, other: Point, t: f32) -> Point {
+        Point::new(
+            self.x + (other.x - self.x) * t,
+            self.y + (other.y - self.y) * t,
+        )
+    }
+}
+
+/// How overlapping or self-intersecting parts of a path are filled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// Inside where the winding number is non-zero.
+    #[default]
+    NonZero,
+    /// Inside where an odd number of edges lie to the left.
+    EvenOdd,
+}
+
+/// One path command.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathEl {
+    MoveTo(Point),
+    LineTo(Point),
+    /// Quadratic Bézier: control point, end point.
+    QuadTo(Point, Point),
+    /// Cubic Bézier: two control points, end point.
+    CubicTo(Point, Point, Point),
+    Close,
+}
+
+/// A sequence of subpaths built from move/line/curve/arc commands.
+///
+/// ```ignore
+/// let mut path = Path::new();
+/// path.move_to(10.0, 10.0).line_to(50.0, 10.0).quad_to(60.0, 30.0, 30.0, 40.0).close();
+/// raster::fill_path(&mut fb, &path, FillRule::NonZero, &Paint::solid(Color::BLUE));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    elements: Vec<PathEl>,
+    /// Start of the current subpath
+    start: Option<Point>,
+    /// Current point
+    current: Option<Point>,
+}
+
+/// Magic constant for approximating a quarter circle with a cubic.
+const KAPPA: f32 = 0.552_284_8;
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The path's commands.
+    pub fn elements(&self) -> &[PathEl] {
+        &self.elements
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// The end point of the last command, if any.
+    pub fn current_point(&self) -> Option<Point> {
+        self.current
+    }
+
+    /// Start a new subpath at (x, y).
+    pub fn move_to(&mut self, x: f32, y: f32) -> &mut Self {
+        let p = Point::new(x, y);
+        self.elements.push(PathEl::MoveTo(p));
+        self.start = Some(p);
+        self.current = Some(p);
+        self
+    }
+
+    /// Straight line to (x, y). Starts a subpath there if there is none.
+    pub fn line_to(&mut self, x: f32, y: f32) -> &mut Self {
+        if self.current.is_none() {
+            return self.move_to(x, y);
+        }
+        let p = Point::new(x, y);
+        self.elements.push(PathEl::LineTo(p));
+        self.current = Some(p);
+        self
+    }
+
+    /// Quadratic Bézier through control point (cx, cy) to (x, y).
+    pub fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) -> &mut Self {
+        self.ensure_current(cx, cy);
+        let p = Point::new(x, y);
+        self.elements.push(PathEl::QuadTo(Point::new(cx, cy), p));
+        self.current = Some(p);
+        self
+    }
+
+    /// Cubic Bézier through control points (c1x, c1y) and (c2x, c2y) to (x, y).
+    pub fn cubic_to(
+        &mut self,
+        c1x: f32,
+        c1y: f32,
+        c2x: f32,
+        c2y: f32,
+        x: f32,
+        y: f32,
+    ) -> &mut Self {
+        self.ensure_current(c1x, c1y);
+        let p = Point::new(x, y);
+        self.elements.push(PathEl::CubicTo(
+            Point::new(c1x, c1y),
+            Point::new(c2x, c2y),
+            p,
+        ));
+        self.current = Some(p);
+        self
+    }
+
+    /// Circular arc centered on (cx, cy), from `start_angle` sweeping by
+    /// `sweep_angle` (radians; positive sweeps clockwise on screen).
+    ///
+    /// Connects to the arc's start with a line if a subpath is open,
+    /// otherwise starts a new subpath there.
+    pub fn arc(
+        &mut self,
+        cx: f32,
+        cy: f32,
+        radius: f32,
+        start_angle: f32,
+        sweep_angle: f32,
+    ) -> &mut Self {
+        self.elliptical_arc(cx, cy, radius, radius, start_angle, sweep_angle)
+    }
+
+    /// Axis-aligned elliptical arc; see [`Path::arc`].
+    pub fn elliptical_arc(
+        &mut self,
+        cx: f32,
+        cy: f32,
+        rx: f32,
+        ry: f32,
+        start_angle: f32,
+        sweep_angle: f32,
+    ) -> &mut Self {
+        let (sin, cos) = sin_cos(start_angle);
+        let (x, y) = (cx + rx * cos, cy + ry * sin);
+        if self.current.is_some() {
+            self.line_to(x, y);
+        } else {
+            self.move_to(x, y);
+        }
+
+        // Split into pieces of at most a quarter turn, each one cubic
+        let pieces = ceil_f32(sweep_angle.abs() / core::f32::consts::FRAC_PI_2).max(1.0);
+        let step = sweep_angle / pieces;
+        let (s, c) = sin_cos(step / 4.0);
+        let k = 4.0 / 3.0 * s / c;
+        let (mut sin0, mut cos0) = (sin, cos);
+        for i in 1..=pieces as usize {
+            let (sin1, cos1) = sin_cos(start_angle + step * i as f32);
+            self.cubic_to(
+                cx + rx * (cos0 - k * sin0),
+                cy + ry * (sin0 + k * cos0),
+                cx + rx * (cos1 + k * sin1),
+                cy + ry * (sin1 - k * cos1),
+                cx + rx * cos1,
+                cy + ry * sin1,
+            );
+            (sin0, cos0) = (sin1, cos1);
+        }
+        self
+    }
+
+    /// Close the current subpath with a line back to its start.
+    pub fn close(&mut self) -> &mut Self {
+        if self.current.is_some() {
+            self.elements.push(PathEl::Close);
+            self.current = self.start;
+        }
+        self
+    }
+
+    /// Append all of `other`'s subpaths.
+    pub fn extend(&mut self, other: &Path) -> &mut Self {
+        self.elements.extend_from_slice(&other.elements);
+        self.start = other.start;
+        self.current = other.current;
+        self
+    }
+
+    fn ensure_current(&mut self, x: f32, y: f32) {
+        if self.current.is_none() {
+            self.move_to(x, y);
+        }
+    }
+
+    /// Rectangle with its top-left corner at (x, y).
+    pub fn rect(x: f32, y: f32, w: f32, h: f32) -> Self {
+        let mut path = Self::new();
+        path.move_to(x, y)
+            .line_to(x + w, y)
+            .line_to(x + w, y + h)
+            .line_to(x, y + h)
+            .close();
+        path
+    }
+
+    /// Rectangle with circular corners of `radius` (clamped to half the
+    /// shorter side).
+    pub fn rounded_rect(x: f32, y: f32, w: f32, h: f32, radius: f32) -> Self {
+        let r = radius.min(w.abs() / 2.0).min(h.abs() / 2.0);
+        if r <= 0.0 {
+            return Self::rect(x, y, w, h);
+        }
+        let k = r * (1.0 - KAPPA);
+        let (x1, y1) = (x + w, y + h);
+        let mut path = Self::new();
+        path.move_to(x + r, y)
+            .line_to(x1 - r, y)
+            .cubic_to(x1 - k, y, x1, y + k, x1, y + r)
+            .line_to(x1, y1 - r)
+            .cubic_to(x1, y1 - k, x1 - k, y1, x1 - r, y1)
+            .line_to(x + r, y1)
+            .cubic_to(x + k, y1, x, y1 - k, x, y1 - r)
+            .line_to(x, y + r)
+            .cubic_to(x, y + k, x + k, y, x + r, y)
+            .close();
+        path
+    }
+
+    /// Circle centered on (cx, cy).
+    pub fn circle(cx: f32, cy: f32, radius: f32) -> Self {
+        Self::ellipse(cx, cy, radius, radius)
+    }
+
+    /// Axis-aligned ellipse centered on (cx, cy).
+    pub fn ellipse(cx: f32, cy: f32, rx: f32, ry: f32) -> Self {
+        let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+        let mut path = Self::new();
+        path.move_to(cx + rx, cy)
+            .cubic_to(cx + rx, cy + ky, cx + kx, cy + ry, cx, cy + ry)
+            .cubic_to(cx - kx, cy + ry, cx - rx, cy + ky, cx - rx, cy)
+            .cubic_to(cx - rx, cy - ky, cx - kx, cy - ry, cx, cy - ry)
+            .cubic_to(cx + kx, cy - ry, cx + rx, cy - ky, cx + rx, cy)
+            .close();
+        path
+    }
+
+    /// A copy of this path with every point mapped through `transform`.
+    pub fn transform(&self, transform: &Transform) -> Path {
+        let map = |p: Point| transform.apply(p);
+        let elements = self
+            .elements
+            .iter()
+            .map(|el| match *el {
+                PathEl::MoveTo(p) => PathEl::MoveTo(map(p)),
+                PathEl::LineTo(p) => PathEl::LineTo(map(p)),
+                PathEl::QuadTo(c, p) => PathEl::QuadTo(map(c), map(p)),
+                PathEl::CubicTo(c1, c2, p) => PathEl::CubicTo(map(c1), map(c2), map(p)),
+                PathEl::Close => PathEl::Close,
+            })
+            .collect();
+        Path {
+            elements,
+            start: self.start.map(map),
+            current: self.current.map(map),
+        }
+    }
+
+    /// Bounding box of all points, including control points, as
+    /// (min_x, min_y, max_x, max_y).
+    pub fn bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        let mut bounds: Option<(f32, f32, f32, f32)> = None;
+        let mut add = |p: Point| {
+            bounds = Some(match bounds {
+                Some((x0, y0, x1, y1)) => (x0.min(p.x), y0.min(p.y), x1.max(p.x), y1.max(p.y)),
+                None => (p.x, p.y, p.x, p.y),
+            });
+        };
+        for el in &self.elements {
+            match *el {
+                PathEl::MoveTo(p) | PathEl::LineTo(p) => add(p),
+                PathEl::QuadTo(c, p) => {
+                    add(c);
+                    add(p);
+                }
+                PathEl::CubicTo(c1, c2, p) => {
+                    add(c1);
+                    add(c2);
+                    add(p);
+                }
+                PathEl::Close => {}
+            }
+        }
+        bounds
+    }
+
+    /// Flatten curves into polylines whose distance from the true curve
+    /// stays within `tolerance` pixels.
+    pub(crate) fn flatten(&self, tolerance: f32) -> Vec<Polyline> {
+        let mut out = Vec::new();
+        let mut current: Option<Polyline> = None;
+        let mut last = Point::default();
+        for el in &self.elements {
+            match *el {
+                PathEl::MoveTo(p) => {
+                    out.extend(current.take());
+                    current = Some(Polyline {
+                        points: alloc::vec![p],
+                        closed: false,
+                    });
+                    last = p;
+                }
+                PathEl::LineTo(p) => {
+                    let line = current.get_or_insert_with(|| Polyline::starting_at(last));
+                    line.points.push(p);
+                    last = p;
+                }
+                PathEl::QuadTo(c, p) => {
+                    let line = current.get_or_insert_with(|| Polyline::starting_at(last));
+                    let dd = length(last.x - 2.0 * c.x + p.x, last.y - 2.0 * c.y + p.y);
+                    let n = subdivisions(dd / 4.0, tolerance);
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        line.points.push(last.lerp(c, t).lerp(c.lerp(p, t), t));
+                    }
+                    last = p;
+                }
+                PathEl::CubicTo(c1, c2, p) => {
+                    let line = current.get_or_insert_with(|| Polyline::starting_at(last));
+                    let dd = length(last.x - 2.0 * c1.x + c2.x, last.y - 2.0 * c1.y + c2.y)
+                        .max(length(c1.x - 2.0 * c2.x + p.x, c1.y - 2.0 * c2.y + p.y));
+                    let n = subdivisions(dd * 0.75, tolerance);
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let (a, b, c) = (last.lerp(c1, t), c1.lerp(c2, t), c2.lerp(p, t));
+                        let (d, e) = (a.lerp(b, t), b.lerp(c, t));
+                        line.points.push(d.lerp(e, t));
+                    }
+                    last = p;
+                }
+                PathEl::Close => {
+                    if let Some(mut line) = current.take() {
+                        line.closed = true;
+                        last = line.points[0];
+                        out.push(line);
+                    }
+                }
+            }
+        }
+        out.extend(current);
+        out
+    }
+}
+
+/// Number of uniform steps that keep a curve with second-difference
+/// magnitude `error` (the chord error of a single step) within `tolerance`
+fn subdivisions(error: f32, tolerance: f32) -> usize {
+    (ceil_f32(sqrt_f32(error / tolerance)) as usize).clamp(1, 256)
+}
+
+#[inline]
+pub(crate) fn length(dx: f32, dy: f32) -> f32 {
+    sqrt_f32(dx * dx + dy * dy)
+}
+
+/// A flattened subpath.
+#[derive(Clone, Debug)]
+pub(crate) struct Polyline {
+    pub points: Vec<Point>,
+    pub closed: bool,
+}
+
+impl Polyline {
+    fn starting_at(p: Point) -> Self {
+        Self {
+            points: alloc::vec![p],
+            closed: false,
+        }
+    }
+}
+
+/// 2D affine transform mapping (x, y) to
+/// (a·x + c·y + e, b·x + d·y + f).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    pub const fn translate(x: f32, y: f32) -> Self {
+        Self {
+            e: x,
+            f: y,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub const fn scale(sx: f32, sy: f32) -> Self {
+        Self {
+            a: sx,
+            d: sy,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Rotation by `angle` radians (clockwise on screen) about the origin.
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = sin_cos(angle);
+        Self {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            e: 0.0,
+            f: 0.0,
+        }
+    }
+
+    /// This transform followed by `next`.
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f,
+        }
+    }
+
+    #[inline]
+    pub fn apply(&self, p: Point) -> Point {
+        Point::new(
+            self.a * p.x + self.c * p.y + self.e,
+            self.b * p.x + self.d * p.y + self.f,
+        )
+    }
+}
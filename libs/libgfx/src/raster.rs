@@ -0,0 +1,460 @@
+//! Anti-aliased path rendering into a `FrameBuf`.
+//!
+//! Same approach as `libfont::rasterizer`: each pixel row is sampled at
+//! `SUB_SCANLINES` heights, spans inside the path (per the fill rule) add
+//! exact fractional horizontal coverage, and the averaged coverage scales
+//! the paint's alpha when compositing over the existing pixels. Only edges
+//! crossing the current row are examined, so large paths stay cheap.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::framebuf::FrameBuf;
+use crate::math::{ceil_f32, floor_f32};
+use crate::paint::{Paint, Shader};
+use crate::path::{FillRule, Path, Point};
+use crate::stroke::{stroke_polylines, Stroke};
+
+/// Maximum distance (pixels) between a curve and its flattened polyline.
+const TOLERANCE: f32 = 0.05;
+
+/// Vertical samples per pixel row.
+const SUB_SCANLINES: usize = 8;
+
+const INV_SUB: f32 = 1.0 / SUB_SCANLINES as f32;
+
+/// Fill `path` with `paint`. Open subpaths are closed implicitly.
+pub fn fill_path(fb: &mut FrameBuf, path: &Path, rule: FillRule, paint: &Paint) {
+    let polygons: Vec<Vec<Point>> = path
+        .flatten(TOLERANCE)
+        .into_iter()
+        .map(|l| l.points)
+        .collect();
+    fill_polygons(fb, &polygons, rule, paint);
+}
+
+/// Stroke `path` with `paint`.
+pub fn stroke_path(fb: &mut FrameBuf, path: &Path, stroke: &Stroke, paint: &Paint) {
+    let polygons = stroke_polylines(&path.flatten(TOLERANCE), stroke, TOLERANCE);
+    fill_polygons(fb, &polygons, FillRule::NonZero, paint);
+}
+
+/// Fill a rectangle with corners rounded to `radius`.
+pub fn fill_rounded_rect(
+    fb: &mut FrameBuf,
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+    radius: f32,
+    paint: &Paint,
+) {
+    fill_path(
+        fb,
+        &Path::rounded_rect(x, y, w, h, radius),
+        FillRule::NonZero,
+        paint,
+    );
+}
+
+/// Anti-aliased filled circle.
+pub fn fill_circle_aa(fb: &mut FrameBuf, cx: f32, cy: f32, radius: f32, paint: &Paint) {
+    fill_path(fb, &Path::circle(cx, cy, radius), FillRule::NonZero, paint);
+}
+
+/// Anti-aliased line with round caps.
+pub fn draw_line_aa(
+    fb: &mut FrameBuf,
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+    width: f32,
+    paint: &Paint,
+) {
+    let mut path = Path::new();
+    path.move_to(x0, y0).line_to(x1, y1);
+    let stroke = Stroke::new(width).with_cap(crate::stroke::LineCap::Round);
+    stroke_path(fb, &path, &stroke, paint);
+}
+
+/// A polygon edge, stored top to bottom
+struct Edge {
+    x0: f32,
+    y0: f32,
+    y1: f32,
+    /// dx/dy
+    slope: f32,
+    /// +1 if the original edge pointed down, -1 if up
+    dir: i32,
+}
+
+fn fill_polygons(fb: &mut FrameBuf, polygons: &[Vec<Point>], rule: FillRule, paint: &Paint) {
+    let mut edges = Vec::new();
+    let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+    let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+    for poly in polygons {
+        for (i, &a) in poly.iter().enumerate() {
+            let b = poly[(i + 1) % poly.len()];
+            if !(a.x.is_finite() && a.y.is_finite()) {
+                return;
+            }
+            min_x = min_x.min(a.x);
+            max_x = max_x.max(a.x);
+            min_y = min_y.min(a.y);
+            max_y = max_y.max(a.y);
+            if a.y == b.y {
+                continue;
+            }
+            let (top, bottom, dir) = if a.y < b.y { (a, b, 1) } else { (b, a, -1) };
+            edges.push(Edge {
+                x0: top.x,
+                y0: top.y,
+                y1: bottom.y,
+                slope: (bottom.x - top.x) / (bottom.y - top.y),
+                dir,
+            });
+        }
+    }
+    if edges.is_empty() {
+        return;
+    }
+
+    // Pixel bounds, clipped to the framebuffer
+    let x_start = floor_f32(min_x).max(0.0) as usize;
+    let x_end = (ceil_f32(max_x).max(0.0) as usize).min(fb.width);
+    let y_start = floor_f32(min_y).max(0.0) as usize;
+    let y_end = (ceil_f32(max_y).max(0.0) as usize).min(fb.height);
+    if x_start >= x_end || y_start >= y_end {
+        return;
+    }
+
+    edges.sort_unstable_by(|a, b| {
+        a.y0.partial_cmp(&b.y0)
+            .unwrap_or(core::cmp::Ordering::Equal)
+    });
+
+    let shader = Shader::new(paint);
+    let width = x_end - x_start;
+    // Fractional coverage per pixel, plus a difference array for whole
+    // pixels (summed across the row) so long spans cost O(1)
+    let mut partial = vec![0.0f32; width + 1];
+    let mut whole = vec![0i32; width + 1];
+    let mut active: Vec<usize> = Vec::new();
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    let mut next_edge = 0;
+    let (mut dirty_x0, mut dirty_x1) = (usize::MAX, 0);
+    let (mut dirty_y0, mut dirty_y1) = (usize::MAX, 0);
+
+    for row in y_start..y_end {
+        let (row_top, row_bottom) = (row as f32, (row + 1) as f32);
+        while next_edge < edges.len() && edges[next_edge].y0 < row_bottom {
+            active.push(next_edge);
+            next_edge += 1;
+        }
+        active.retain(|&i| edges[i].y1 > row_top);
+        if active.is_empty() {
+            continue;
+        }
+
+        let (mut lo, mut hi) = (width, 0);
+        for sub in 0..SUB_SCANLINES {
+            let y = row_top + (sub as f32 + 0.5) * INV_SUB;
+            crossings.clear();
+            for &i in &active {
+                let e = &edges[i];
+                if e.y0 <= y && y < e.y1 {
+                    crossings.push((e.x0 + (y - e.y0) * e.slope, e.dir));
+                }
+            }
+            if crossings.is_empty() {
+                continue;
+            }
+            crossings.sort_unstable_by(|a, b| {
+                a.0.partial_cmp(&b.0).unwrap_or(core::cmp::Ordering::Equal)
+            });
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                let inside = match rule {
+                    FillRule::NonZero => winding != 0,
+                    FillRule::EvenOdd => winding & 1 != 0,
+                };
+                if !inside {
+                    continue;
+                }
+                let left = (pair[0].0 - x_start as f32).max(0.0);
+                let right = (pair[1].0 - x_start as f32).min(width as f32);
+                if right > left {
+                    add_span(&mut partial, &mut whole, left, right);
+                    lo = lo.min(left as usize);
+                    hi = hi.max((ceil_f32(right) as usize).min(width));
+                }
+            }
+        }
+        if lo >= hi {
+            continue;
+        }
+
+        let mut full = 0;
+        for v in &mut whole[..lo] {
+            full += *v;
+            *v = 0;
+        }
+        for i in lo..hi {
+            full += whole[i];
+            whole[i] = 0;
+            let coverage = (full as f32 + partial[i]) * INV_SUB;
+            partial[i] = 0.0;
+            let coverage = (coverage.min(1.0) * 255.0 + 0.5) as u32;
+            if coverage == 0 {
+                continue;
+            }
+            let x = x_start + i;
+            let (color, alpha) = shader.eval(x, row);
+            let alpha = (coverage * alpha as u32 + 127) / 255;
+            if alpha > 0 {
+                fb.blend_in_bounds(x, row, color, alpha as u8);
+            }
+        }
+        for i in hi..=width {
+            whole[i] = 0;
+            partial[i] = 0.0;
+        }
+
+        dirty_x0 = dirty_x0.min(x_start + lo);
+        dirty_x1 = dirty_x1.max(x_start + hi);
+        dirty_y0 = dirty_y0.min(row);
+        dirty_y1 = row + 1;
+    }
+
+    if dirty_x0 < dirty_x1 {
+        fb.mark_dirty(
+            dirty_x0 as i32,
+            dirty_y0 as i32,
+            (dirty_x1 - dirty_x0) as i32,
+            (dirty_y1 - dirty_y0) as i32,
+        );
+    }
+}
+
+/// Add coverage for the span [left, right) of one sub-scanline
+#[inline]
+fn add_span(partial: &mut [f32], whole: &mut [i32], left: f32, right: f32) {
+    let first = left as usize;
+    let last = right as usize;
+    if first == last {
+        partial[first] += right - left;
+        return;
+    }
+    partial[first] += (first + 1) as f32 - left;
+    whole[first + 1] += 1;
+    whole[last] -= 1;
+    partial[last] += right - last as f32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::paint::LinearGradient;
+    use crate::stroke::{LineCap, LineJoin};
+
+    const W: usize = 32;
+    const H: usize = 24;
+
+    /// Black 4-byte RGB buffer; the red channel of each pixel ends up
+    /// holding the coverage of a white fill
+    fn canvas(buf: &mut Vec<u8>) -> FrameBuf {
+        buf.clear();
+        buf.resize(W * H * 4, 0);
+        unsafe { FrameBuf::from_raw(buf.as_mut_ptr(), W, H, W * 4, 4, false) }
+    }
+
+    fn value(buf: &[u8], x: usize, y: usize) -> u8 {
+        buf[(y * W + x) * 4]
+    }
+
+    fn total(buf: &[u8]) -> f32 {
+        buf.chunks_exact(4).map(|p| p[0] as f32 / 255.0).sum()
+    }
+
+    const WHITE: Paint = Paint::solid(Color::WHITE);
+
+    #[test]
+    fn fills_pixel_aligned_rect_exactly() {
+        let mut buf = Vec::new();
+        let mut fb = canvas(&mut buf);
+        fill_path(
+            &mut fb,
+            &Path::rect(2.0, 3.0, 5.0, 4.0),
+            FillRule::NonZero,
+            &WHITE,
+        );
+        let dirty = fb.take_dirty().unwrap();
+        assert_eq!((dirty.x, dirty.y, dirty.w, dirty.h), (2, 3, 5, 4));
+        for y in 0..H {
+            for x in 0..W {
+                let inside = (2..7).contains(&x) && (3..7).contains(&y);
+                assert_eq!(
+                    value(&buf, x, y),
+                    if inside { 255 } else { 0 },
+                    "({x}, {y})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn partial_pixels_get_partial_coverage() {
+        let mut buf = Vec::new();
+        let mut fb = canvas(&mut buf);
+        fill_path(
+            &mut fb,
+            &Path::rect(1.5, 1.0, 2.0, 1.0),
+            FillRule::NonZero,
+            &WHITE,
+        );
+        assert_eq!(
+            [value(&buf, 1, 1), value(&buf, 2, 1), value(&buf, 3, 1)],
+            [128, 255, 128]
+        );
+
+        // Translucent paint scales the coverage
+        let mut fb = canvas(&mut buf);
+        let paint = Paint::with_alpha(Color::WHITE, 128);
+        fill_path(
+            &mut fb,
+            &Path::rect(0.0, 0.0, 1.0, 1.0),
+            FillRule::NonZero,
+            &paint,
+        );
+        assert_eq!(value(&buf, 0, 0), 128);
+    }
+
+    #[test]
+    fn fill_rules_differ_on_nested_contours() {
+        let mut path = Path::rect(2.0, 2.0, 12.0, 12.0);
+        path.extend(&Path::rect(5.0, 5.0, 6.0, 6.0));
+        let mut buf = Vec::new();
+        let mut fb = canvas(&mut buf);
+        fill_path(&mut fb, &path, FillRule::NonZero, &WHITE);
+        assert_eq!(value(&buf, 8, 8), 255);
+        let mut fb = canvas(&mut buf);
+        fill_path(&mut fb, &path, FillRule::EvenOdd, &WHITE);
+        assert_eq!(value(&buf, 8, 8), 0);
+        assert_eq!(value(&buf, 3, 8), 255);
+    }
+
+    #[test]
+    fn circle_area_matches() {
+        let mut buf = Vec::new();
+        let mut fb = canvas(&mut buf);
+        fill_path(
+            &mut fb,
+            &Path::circle(12.3, 11.7, 9.0),
+            FillRule::NonZero,
+            &WHITE,
+        );
+        // Chords of the flattened curve sit up to TOLERANCE inside it
+        let expected = core::f32::consts::PI * 81.0;
+        assert!((total(&buf) - expected).abs() < 2.0, "{}", total(&buf));
+        // Arcs trace the same circle
+        let mut arc = Path::new();
+        arc.arc(12.3, 11.7, 9.0, 0.3, core::f32::consts::TAU)
+            .close();
+        let mut fb = canvas(&mut buf);
+        fill_path(&mut fb, &arc, FillRule::NonZero, &WHITE);
+        assert!((total(&buf) - expected).abs() < 2.0, "{}", total(&buf));
+    }
+
+    #[test]
+    fn strokes_cover_width_and_caps() {
+        let mut line = Path::new();
+        line.move_to(4.0, 5.0).line_to(12.0, 5.0);
+        let mut buf = Vec::new();
+        for (cap, expected) in [
+            (LineCap::Butt, 16.0),
+            (LineCap::Square, 20.0),
+            (LineCap::Round, 16.0 + core::f32::consts::PI),
+        ] {
+            let mut fb = canvas(&mut buf);
+            stroke_path(&mut fb, &line, &Stroke::new(2.0).with_cap(cap), &WHITE);
+            assert!(
+                (total(&buf) - expected).abs() < 0.2,
+                "{:?}: {}",
+                cap,
+                total(&buf)
+            );
+        }
+
+        // A right-angle corner: the segments cover 76 pixels between them,
+        // and the outer 2x2 corner is filled by a miter, halved by a bevel
+        // and rounded to a quarter circle by a round join
+        let mut corner = Path::new();
+        corner
+            .move_to(4.0, 10.0)
+            .line_to(14.0, 10.0)
+            .line_to(14.0, 20.0);
+        let mut areas = [0.0; 3];
+        for (i, join) in [LineJoin::Miter, LineJoin::Bevel, LineJoin::Round]
+            .into_iter()
+            .enumerate()
+        {
+            let mut fb = canvas(&mut buf);
+            stroke_path(&mut fb, &corner, &Stroke::new(4.0).with_join(join), &WHITE);
+            areas[i] = total(&buf);
+        }
+        assert!((areas[0] - 80.0).abs() < 0.2, "{areas:?}");
+        assert!((areas[1] - 78.0).abs() < 0.2, "{areas:?}");
+        assert!(
+            (areas[2] - (76.0 + core::f32::consts::PI)).abs() < 0.2,
+            "{areas:?}"
+        );
+    }
+
+    #[test]
+    fn gradients_interpolate() {
+        let mut buf = Vec::new();
+        let mut fb = canvas(&mut buf);
+        let gradient = LinearGradient::new(0.0, 0.0, W as f32, 0.0)
+            .stop(0.0, Color::BLACK)
+            .stop(1.0, Color::WHITE);
+        fill_path(
+            &mut fb,
+            &Path::rect(0.0, 0.0, W as f32, 1.0),
+            FillRule::NonZero,
+            &gradient.into(),
+        );
+        let row: Vec<u8> = (0..W).map(|x| value(&buf, x, 0)).collect();
+        assert!(row.windows(2).all(|w| w[0] < w[1]), "{row:?}");
+        assert!(row[0] < 8 && row[W - 1] > 247, "{row:?}");
+    }
+
+    #[test]
+    fn clips_to_the_framebuffer() {
+        let mut buf = Vec::new();
+        let mut fb = canvas(&mut buf);
+        fill_path(
+            &mut fb,
+            &Path::circle(0.0, 0.0, 100.0),
+            FillRule::NonZero,
+            &WHITE,
+        );
+        assert!(buf.chunks_exact(4).all(|p| p[0] == 255));
+        let dirty = fb.take_dirty().unwrap();
+        assert_eq!(
+            (dirty.x, dirty.y, dirty.w, dirty.h),
+            (0, 0, W as i32, H as i32)
+        );
+        let mut fb = canvas(&mut buf);
+        fill_path(
+            &mut fb,
+            &Path::rect(-10.0, 30.0, 5.0, 5.0),
+            FillRule::NonZero,
+            &WHITE,
+        );
+        assert!(fb.take_dirty().is_none());
+    }
+}
@@ -0,0 +1,240 @@
+//! Stroke styles and conversion of stroked paths to fillable outlines.
+//!
+//! Each segment, join and cap of a stroke becomes its own small polygon,
+//! all wound the same way, so filling them together with the non-zero
+//! rule gives their union without computing overlaps.
+
+use alloc::vec::Vec;
+
+use crate::math::{ceil_f32, sin_cos, sqrt_f32};
+use crate::path::{length, Point, Polyline};
+
+/// Shape at the open ends of a stroked subpath.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// Ends exactly at the end point.
+    #[default]
+    Butt,
+    /// Semicircle around the end point.
+    Round,
+    /// Square extending half the width past the end point.
+    Square,
+}
+
+/// Shape where two segments of a stroked subpath meet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Sharp corner, beveled once it would exceed the miter limit.
+    #[default]
+    Miter,
+    /// Circular corner.
+    Round,
+    /// Corner cut off straight across.
+    Bevel,
+}
+
+/// How to stroke a path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    /// Longest miter allowed, as a multiple of the width.
+    pub miter_limit: f32,
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl Stroke {
+    /// `width`-pixel stroke with butt caps and miter joins.
+    pub const fn new(width: f32) -> Self {
+        Self {
+            width,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+        }
+    }
+
+    pub const fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub const fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub const fn with_miter_limit(mut self, limit: f32) -> Self {
+        self.miter_limit = limit;
+        self
+    }
+}
+
+/// Outline polygons covering the stroke of `lines`, to be filled with the
+/// non-zero rule.
+pub(crate) fn stroke_polylines(
+    lines: &[Polyline],
+    stroke: &Stroke,
+    tolerance: f32,
+) -> Vec<Vec<Point>> {
+    let hw = stroke.width / 2.0;
+    let mut out = Vec::new();
+    if hw <= 0.0 {
+        return out;
+    }
+    for line in lines {
+        let mut pts: Vec<Point> = Vec::with_capacity(line.points.len());
+        for &p in &line.points {
+            if pts.last() != Some(&p) {
+                pts.push(p);
+            }
+        }
+        let closed = line.closed && pts.len() > 2;
+        if closed && pts.first() == pts.last() {
+            pts.pop();
+        }
+
+        if pts.len() == 1 {
+            // Zero-length subpath: only caps that extend past the point draw
+            let p = pts[0];
+            match stroke.cap {
+                LineCap::Butt => {}
+                LineCap::Round => out.push(circle(p, hw, tolerance)),
+                LineCap::Square => out.push(Vec::from([
+                    Point::new(p.x - hw, p.y - hw),
+                    Point::new(p.x + hw, p.y - hw),
+                    Point::new(p.x + hw, p.y + hw),
+                    Point::new(p.x - hw, p.y + hw),
+                ])),
+            }
+            continue;
+        }
+
+        let n = pts.len();
+        let segments = if closed { n } else { n - 1 };
+        for i in 0..segments {
+            let (a, b) = (pts[i], pts[(i + 1) % n]);
+            let (dx, dy) = unit(a, b);
+            let (nx, ny) = (-dy * hw, dx * hw);
+            out.push(Vec::from([
+                Point::new(a.x + nx, a.y + ny),
+                Point::new(b.x + nx, b.y + ny),
+                Point::new(b.x - nx, b.y - ny),
+                Point::new(a.x - nx, a.y - ny),
+            ]));
+        }
+
+        let joins = if closed { 0..n } else { 1..n - 1 };
+        for i in joins {
+            let prev = pts[(i + n - 1) % n];
+            let (p, next) = (pts[i], pts[(i + 1) % n]);
+            join(&mut out, prev, p, next, hw, stroke, tolerance);
+        }
+
+        if !closed {
+            cap(&mut out, pts[1], pts[0], hw, stroke.cap, tolerance);
+            cap(&mut out, pts[n - 2], pts[n - 1], hw, stroke.cap, tolerance);
+        }
+    }
+
+    for poly in &mut out {
+        if signed_area(poly) < 0.0 {
+            poly.reverse();
+        }
+    }
+    out
+}
+
+/// Unit direction from `a` to `b`
+fn unit(a: Point, b: Point) -> (f32, f32) {
+    let len = length(b.x - a.x, b.y - a.y);
+    ((b.x - a.x) / len, (b.y - a.y) / len)
+}
+
+/// Fill the outer corner at `p` between segments prev→p and p→next.
+fn join(
+    out: &mut Vec<Vec<Point>>,
+    prev: Point,
+    p: Point,
+    next: Point,
+    hw: f32,
+    stroke: &Stroke,
+    tolerance: f32,
+) {
+    let (d0x, d0y) = unit(prev, p);
+    let (d1x, d1y) = unit(p, next);
+    let cross = d0x * d1y - d0y * d1x;
+    let dot = d0x * d1x + d0y * d1y;
+    if cross.abs() < 1e-6 && dot > 0.0 {
+        return; // straight on
+    }
+    if stroke.join == LineJoin::Round {
+        out.push(circle(p, hw, tolerance));
+        return;
+    }
+
+    // The outer side is opposite the direction of the turn
+    let side = if cross > 0.0 { -hw } else { hw };
+    let a = Point::new(p.x - d0y * side, p.y + d0x * side);
+    let b = Point::new(p.x - d1y * side, p.y + d1x * side);
+
+    // Miter length over stroke width is 1 / cos(turn / 2)
+    let cos_half = sqrt_f32((1.0 + dot) / 2.0);
+    if stroke.join == LineJoin::Miter && cos_half * stroke.miter_limit > 1.0 {
+        let scale = side / (1.0 + dot);
+        let tip = Point::new(p.x - (d0y + d1y) * scale, p.y + (d0x + d1x) * scale);
+        out.push(Vec::from([p, a, tip, b]));
+    } else {
+        out.push(Vec::from([p, a, b]));
+    }
+}
+
+/// Cap the end of the segment from → end.
+fn cap(out: &mut Vec<Vec<Point>>, from: Point, end: Point, hw: f32, cap: LineCap, tolerance: f32) {
+    match cap {
+        LineCap::Butt => {}
+        LineCap::Round => out.push(circle(end, hw, tolerance)),
+        LineCap::Square => {
+            let (dx, dy) = unit(from, end);
+            let (nx, ny) = (-dy * hw, dx * hw);
+            let (ex, ey) = (end.x + dx * hw, end.y + dy * hw);
+            out.push(Vec::from([
+                Point::new(end.x + nx, end.y + ny),
+                Point::new(ex + nx, ey + ny),
+                Point::new(ex - nx, ey - ny),
+                Point::new(end.x - nx, end.y - ny),
+            ]));
+        }
+    }
+}
+
+/// Polygon approximating a circle within `tolerance`, with the vertices
+/// pushed out slightly so its area matches the circle's
+fn circle(c: Point, r: f32, tolerance: f32) -> Vec<Point> {
+    use core::f32::consts::{PI, TAU};
+    // Sagitta of a chord spanning angle a is r * (1 - cos(a / 2)) ≈ r a² / 8
+    let steps = (ceil_f32(PI * sqrt_f32(r / (2.0 * tolerance))) as usize).clamp(8, 256);
+    let (sin, _) = sin_cos(TAU / steps as f32);
+    let r = r * sqrt_f32(TAU / (steps as f32 * sin));
+    (0..steps)
+        .map(|i| {
+            let (sin, cos) = sin_cos(i as f32 * TAU / steps as f32);
+            Point::new(c.x + r * cos, c.y + r * sin)
+        })
+        .collect()
+}
+
+fn signed_area(poly: &[Point]) -> f32 {
+    let mut area = 0.0;
+    for (i, a) in poly.iter().enumerate() {
+        let b = poly[(i + 1) % poly.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+    area / 2.0
+}
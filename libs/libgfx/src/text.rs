@@ -0,0 +1,367 @@
+//! Unicode text layout over a chain of fonts.
+//!
+//! Text is split into grapheme clusters, each set in the first font of a
+//! [`FontChain`] with glyphs for the whole cluster, ordered with the Unicode
+//! bidirectional algorithm and shaped a run at a time. Combining marks stay
+//! on their base, right-to-left runs read right to left with mirrored
+//! brackets, and characters the primary font lacks come from a fallback
+//! font rather than '?'. `ttf_font::draw_text` is the single-font case.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use libfont::{CachedFont, ShapedGlyph};
+use libunicode::bidi::{self, Direction};
+use libunicode::{char_width, graphemes, is_combining_mark};
+
+use crate::color::Color;
+use crate::framebuf::FrameBuf;
+use crate::ttf_font;
+
+/// A primary font followed by fallbacks, tried in order for characters the
+/// fonts before them lack.
+pub struct FontChain {
+    fonts: Vec<CachedFont>,
+}
+
+impl FontChain {
+    pub fn new(primary: CachedFont) -> Self {
+        Self {
+            fonts: alloc::vec![primary],
+        }
+    }
+
+    /// Add a fallback after the fonts already in the chain.
+    pub fn push(&mut self, fallback: CachedFont) {
+        self.fonts.push(fallback);
+    }
+
+    pub fn primary(&self) -> &CachedFont {
+        &self.fonts[0]
+    }
+
+    pub fn primary_mut(&mut self) -> &mut CachedFont {
+        &mut self.fonts[0]
+    }
+
+    /// The font at `index`, as chosen by [`font_for`](Self::font_for) or
+    /// recorded in [`PlacedGlyph::font`].
+    pub fn font(&self, index: usize) -> &CachedFont {
+        &self.fonts[index]
+    }
+
+    pub fn font_mut(&mut self, index: usize) -> &mut CachedFont {
+        &mut self.fonts[index]
+    }
+
+    /// Split the chain back into its primary font and fallbacks.
+    pub fn into_parts(self) -> (CachedFont, Vec<CachedFont>) {
+        let mut fonts = self.fonts.into_iter();
+        let primary = fonts.next().unwrap();
+        (primary, fonts.collect())
+    }
+
+    /// Index of the font to set the grapheme cluster `cluster` in.
+    pub fn font_for(&self, cluster: &str) -> usize {
+        pick_font(&self.fonts, cluster)
+    }
+
+    pub fn clear_cache(&mut self) {
+        for font in self.fonts.iter_mut() {
+            font.clear_cache();
+        }
+    }
+
+    /// Lay `text` out at `size` pixels.
+    pub fn layout(&self, text: &str, size: f32) -> Layout {
+        layout(&self.fonts, text, size)
+    }
+
+    /// Draw a line of text with its baseline origin at (x, y), returning the
+    /// advance width in pixels.
+    pub fn draw_text(
+        &mut self,
+        fb: &mut FrameBuf,
+        text: &str,
+        x: i32,
+        y: i32,
+        size: f32,
+        fg: Color,
+    ) -> i32 {
+        let layout = self.layout(text, size);
+        for g in &layout.glyphs {
+            let (gx, gy) = g.origin(x, y);
+            ttf_font::draw_glyph(fb, &mut self.fonts[g.font], g.glyph_index, gx, gy, size, fg);
+        }
+        layout.advance(x)
+    }
+
+    /// Measure the pixel width of a line of text without drawing.
+    pub fn text_width(&self, text: &str, size: f32) -> i32 {
+        self.layout(text, size).advance(0)
+    }
+}
+
+/// A glyph placed by [`layout`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlacedGlyph {
+    /// Index of the font in the chain the glyph comes from.
+    pub font: usize,
+    pub glyph_index: u16,
+    /// Index (in chars) of the first char of the text this glyph came from.
+    pub cluster: usize,
+    /// Position from the line origin in pixels (y up).
+    pub x: f32,
+    pub y: f32,
+}
+
+impl PlacedGlyph {
+    /// Pixel position to draw the glyph at, for a line origin of (x, y).
+    #[inline]
+    pub fn origin(&self, x: i32, y: i32) -> (i32, i32) {
+        ((x as f32 + self.x + 0.5) as i32, y - (self.y + 0.5) as i32)
+    }
+}
+
+/// A line of text laid out in display order.
+#[derive(Debug, Clone, Default)]
+pub struct Layout {
+    /// Glyphs from left to right.
+    pub glyphs: Vec<PlacedGlyph>,
+    /// Total advance width in pixels.
+    pub width: f32,
+}
+
+impl Layout {
+    /// Advance width in whole pixels for a line starting at `x`, rounded
+    /// the same way as glyph positions.
+    pub fn advance(&self, x: i32) -> i32 {
+        (x as f32 + self.width + 0.5) as i32 - x
+    }
+}
+
+/// Whether a font must have a glyph for `ch` to set it. Controls, joiners
+/// and variation selectors draw nothing and are often missing from fonts.
+fn needs_glyph(ch: char) -> bool {
+    !matches!(ch, '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}')
+        && (char_width(ch) > 0 || is_combining_mark(ch))
+}
+
+/// First font with glyphs for all of `cluster`, else the first with its base
+/// character, else the primary font.
+fn pick_font<F: Borrow<CachedFont>>(fonts: &[F], cluster: &str) -> usize {
+    let mut chars = cluster.chars().filter(|&c| needs_glyph(c));
+    let Some(base) = chars.next() else {
+        return 0;
+    };
+    let rest: Vec<char> = chars.collect();
+    let mut with_base = None;
+    for (i, font) in fonts.iter().enumerate() {
+        let font = font.borrow();
+        if font.glyph_index(base) == 0 {
+            continue;
+        }
+        if rest.iter().all(|&c| font.glyph_index(c) != 0) {
+            return i;
+        }
+        with_base.get_or_insert(i);
+    }
+    with_base.unwrap_or(0)
+}
+
+/// Shape one run of text in one font and direction, ready to draw in
+/// logical order.
+///
+/// Glyphs the font lacks become '?' (or vanish, for characters that draw
+/// nothing). Combining marks lose their advance and, unless the font already
+/// put them over their base, are centered on it; the shaper has no mark
+/// attachment, and monospace fonts give marks a cell of their own.
+pub fn shape(font: &CachedFont, text: &str, size: f32) -> Vec<ShapedGlyph> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut glyphs = font.shape(text, size);
+    let char_at = |g: &ShapedGlyph| chars.get(g.cluster).copied().unwrap_or('\0');
+    if glyphs.iter().any(|g| g.glyph_index == 0) {
+        glyphs.retain(|g| g.glyph_index != 0 || needs_glyph(char_at(g)));
+        let fallback = font.glyph_index('?');
+        for g in glyphs
+            .iter_mut()
+            .filter(|g| g.glyph_index == 0 && fallback != 0)
+        {
+            g.glyph_index = fallback;
+            g.x_advance = font.advance_width(fallback, size);
+        }
+    }
+
+    let bounds = |glyph: u16| {
+        font.font()
+            .glyph_bounds(glyph, size)
+            .map(|(x0, _, x1, _)| (x0, x1))
+    };
+    // Last base glyph and the pen distance from it to the current glyph
+    let mut base: Option<usize> = None;
+    let mut from_base = 0.0;
+    for k in 0..glyphs.len() {
+        let Some(b) = base.filter(|_| is_combining_mark(char_at(&glyphs[k]))) else {
+            base = Some(k);
+            from_base = glyphs[k].x_advance;
+            continue;
+        };
+        glyphs[k].x_advance = 0.0;
+        if let (Some((b0, b1)), Some((m0, m1))) =
+            (bounds(glyphs[b].glyph_index), bounds(glyphs[k].glyph_index))
+        {
+            let (lo, hi) = (
+                b0 + glyphs[b].x_offset - from_base,
+                b1 + glyphs[b].x_offset - from_base,
+            );
+            let center = (m0 + m1) / 2.0 + glyphs[k].x_offset;
+            if center < lo || center > hi {
+                glyphs[k].x_offset += (lo + hi) / 2.0 - center;
+            }
+        }
+    }
+    glyphs
+}
+
+/// One run of clusters sharing a font and an embedding level, in chars.
+struct Run {
+    start: usize,
+    end: usize,
+    font: usize,
+    level: u8,
+}
+
+/// Lay out one line of `text` at `size` pixels, using the first of `fonts`
+/// as the primary font and the rest as fallbacks.
+pub fn layout<F: Borrow<CachedFont>>(fonts: &[F], text: &str, size: f32) -> Layout {
+    let chars: Vec<char> = text.chars().collect();
+    let levels = if text.is_ascii() {
+        alloc::vec![0; chars.len()]
+    } else {
+        bidi::resolve_levels(&chars, Direction::Auto)
+    };
+
+    // Split into runs of clusters, remembering where each cluster starts
+    let mut cluster_start = alloc::vec![0; chars.len()];
+    let mut runs: Vec<Run> = Vec::new();
+    let mut pos = 0;
+    for cluster in graphemes(text) {
+        let end = pos + cluster.chars().count();
+        cluster_start[pos..end].fill(pos);
+        let font = pick_font(fonts, cluster);
+        match runs.last_mut() {
+            Some(run) if run.font == font && run.level == levels[pos] => run.end = end,
+            _ => runs.push(Run {
+                start: pos,
+                end,
+                font,
+                level: levels[pos],
+            }),
+        }
+        pos = end;
+    }
+
+    let mut out = Layout::default();
+    let run_levels: Vec<u8> = runs.iter().map(|r| r.level).collect();
+    for r in bidi::visual_order(&run_levels) {
+        let run = &runs[r];
+        let rtl = bidi::is_rtl(run.level);
+        let run_text: String = chars[run.start..run.end]
+            .iter()
+            .map(|&c| if rtl { bidi::mirror(c) } else { c })
+            .collect();
+        let mut glyphs = shape(fonts[run.font].borrow(), &run_text, size);
+        for g in glyphs.iter_mut() {
+            g.cluster += run.start;
+        }
+        if rtl {
+            // Reverse whole clusters so marks keep following their base
+            let mut reordered = Vec::with_capacity(glyphs.len());
+            let mut end = glyphs.len();
+            while end > 0 {
+                let start_char = cluster_start[glyphs[end - 1].cluster];
+                let mut start = end - 1;
+                while start > 0 && cluster_start[glyphs[start - 1].cluster] == start_char {
+                    start -= 1;
+                }
+                reordered.extend_from_slice(&glyphs[start..end]);
+                end = start;
+            }
+            glyphs = reordered;
+        }
+        for g in glyphs {
+            out.glyphs.push(PlacedGlyph {
+                font: run.font,
+                glyph_index: g.glyph_index,
+                cluster: g.cluster,
+                x: out.width + g.x_offset,
+                y: g.y_offset,
+            });
+            out.width += g.x_advance;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libfont::Font;
+
+    static MONO: &[u8] = include_bytes!("../../../fonts/DejaVuSansMono.ttf");
+    static SANS: &[u8] = include_bytes!("../../../fonts/DejaVuSans.ttf");
+
+    fn chain() -> FontChain {
+        let mut chain = FontChain::new(CachedFont::new(Font::parse(MONO).unwrap(), 16));
+        chain.push(CachedFont::new(Font::parse(SANS).unwrap(), 16));
+        chain
+    }
+
+    #[test]
+    fn falls_back_for_missing_characters() {
+        let chain = chain();
+        // DejaVu Sans Mono has no Hebrew; DejaVu Sans does
+        assert_eq!(chain.font_for("a"), 0);
+        assert_eq!(chain.font_for("\u{5D0}"), 1);
+        // Han is in neither font: the primary draws '?'
+        let layout = chain.layout("\u{4E2D}", 16.0);
+        assert_eq!(layout.glyphs.len(), 1);
+        assert_eq!(
+            layout.glyphs[0].glyph_index,
+            chain.primary().glyph_index('?')
+        );
+    }
+
+    #[test]
+    fn right_to_left_runs_are_reversed() {
+        let chain = chain();
+        let layout = chain.layout("ab \u{5D0}\u{5D1}", 16.0);
+        let clusters: Vec<usize> = layout.glyphs.iter().map(|g| g.cluster).collect();
+        assert_eq!(clusters, [0, 1, 2, 4, 3]);
+        assert_eq!(layout.glyphs[3].font, 1);
+        assert!(layout.glyphs.windows(2).all(|w| w[0].x < w[1].x));
+    }
+
+    #[test]
+    fn combining_marks_sit_on_their_base() {
+        let chain = chain();
+        let font = chain.primary();
+        let layout = chain.layout("e\u{301}x", 16.0);
+        assert_eq!(layout.glyphs.len(), 3);
+        let e = font.glyph_index('e');
+        // The mark takes no room of its own in the monospace font...
+        assert_eq!(layout.glyphs[2].x, font.advance_width(e, 16.0));
+        // ...and is drawn over the 'e' rather than in the next cell
+        let (m0, _, m1, _) = font
+            .font()
+            .glyph_bounds(layout.glyphs[1].glyph_index, 16.0)
+            .unwrap();
+        let center = layout.glyphs[1].x + (m0 + m1) / 2.0;
+        let (e0, _, e1, _) = font.font().glyph_bounds(e, 16.0).unwrap();
+        assert!(
+            center > e0 && center < e1,
+            "mark centered at {center}, 'e' spans {e0}..{e1}"
+        );
+    }
+}
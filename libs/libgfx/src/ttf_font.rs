@@ -1,13 +1,17 @@
 //! TrueType font rendering to FrameBuf using libfont.
 //!
 //! Provides the same compositing pattern as `bitmap_font.rs` but with
-//! runtime-loaded TrueType fonts at any pixel size.
+//! runtime-loaded TrueType fonts at any pixel size. Strings are laid out
+//! with [`text`](crate::text) first, so ligatures, kerning, combining marks
+//! and right-to-left runs render; use a [`FontChain`](crate::text::FontChain)
+//! to fall back to other fonts for missing characters.
 
 use libfont::CachedFont;
 use libfont::rasterizer::GlyphBitmap;
 use libfont::SubpixelBitmap;
 use crate::color::Color;
 use crate::framebuf::FrameBuf;
+use crate::text;
 
 /// Draw a single character with anti-aliased alpha blending.
 ///
@@ -32,7 +36,10 @@ pub fn draw_char(
     draw_glyph(fb, font, glyph_index, x, y, size, fg)
 }
 
-fn draw_glyph(
+/// Draw a glyph by index, e.g. one produced by `CachedFont::shape`.
+///
+/// Returns the glyph's advance width in pixels.
+pub fn draw_glyph(
     fb: &mut FrameBuf,
     font: &mut CachedFont,
     glyph_index: u16,
@@ -102,24 +109,12 @@ pub fn draw_text(
     size: f32,
     fg: Color,
 ) -> i32 {
-    let mut cursor_x = x;
-    let mut prev_glyph: Option<u16> = None;
-
-    for ch in text.chars() {
-        let glyph_index = font.glyph_index(ch);
-
-        // Apply kerning
-        if let Some(prev) = prev_glyph {
-            let kern = font.kern(prev, glyph_index, size);
-            cursor_x += kern as i32;
-        }
-
-        let advance = draw_char(fb, font, ch, cursor_x, y, size, fg);
-        cursor_x += advance;
-        prev_glyph = Some(glyph_index);
+    let layout = text::layout(&[&*font], text, size);
+    for g in &layout.glyphs {
+        let (gx, gy) = g.origin(x, y);
+        draw_glyph(fb, font, g.glyph_index, gx, gy, size, fg);
     }
-
-    cursor_x - x
+    layout.advance(x)
 }
 
 /// Draw a single character with LCD subpixel rendering.
@@ -144,7 +139,10 @@ pub fn draw_char_subpixel(
     draw_glyph_subpixel(fb, font, glyph_index, x, y, size, fg)
 }
 
-fn draw_glyph_subpixel(
+/// Draw a glyph by index with LCD subpixel rendering.
+///
+/// Returns the glyph's advance width in pixels.
+pub fn draw_glyph_subpixel(
     fb: &mut FrameBuf,
     font: &mut CachedFont,
     glyph_index: u16,
@@ -226,40 +224,15 @@ pub fn draw_text_subpixel(
     size: f32,
     fg: Color,
 ) -> i32 {
-    let mut cursor_x = x;
-    let mut prev_glyph: Option<u16> = None;
-
-    for ch in text.chars() {
-        let glyph_index = font.glyph_index(ch);
-        if let Some(prev) = prev_glyph {
-            let kern = font.kern(prev, glyph_index, size);
-            cursor_x += kern as i32;
-        }
-        let advance = draw_char_subpixel(fb, font, ch, cursor_x, y, size, fg);
-        cursor_x += advance;
-        prev_glyph = Some(glyph_index);
+    let layout = text::layout(&[&*font], text, size);
+    for g in &layout.glyphs {
+        let (gx, gy) = g.origin(x, y);
+        draw_glyph_subpixel(fb, font, g.glyph_index, gx, gy, size, fg);
     }
-
-    cursor_x - x
+    layout.advance(x)
 }
 
 /// Measure the pixel width of a text string without drawing.
 pub fn text_width(font: &mut CachedFont, text: &str, size: f32) -> i32 {
-    let mut width = 0i32;
-    let mut prev_glyph: Option<u16> = None;
-
-    for ch in text.chars() {
-        let glyph_index = font.glyph_index(ch);
-
-        if let Some(prev) = prev_glyph {
-            let kern = font.kern(prev, glyph_index, size);
-            width += kern as i32;
-        }
-
-        let advance = font.advance_width(glyph_index, size);
-        width += advance as i32;
-        prev_glyph = Some(glyph_index);
-    }
-
-    width
+    text::layout(&[&*font], text, size).advance(0)
 }
@@ -0,0 +1,562 @@
+//! DEFLATE compression (RFC 1951) and the zlib wrapper (RFC 1950).
+//!
+//! Greedy LZ77 matching over hash chains, with each block sent as whichever
+//! of stored, fixed Huffman or dynamic Huffman coding is smallest.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::checksum::adler32;
+use crate::inflate::{reverse_bits, CLEN_ORDER, DIST_BASE, DIST_EXTRA, LENGTH_BASE, LENGTH_EXTRA};
+
+const WINDOW: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+/// Chain links followed looking for a match
+const MAX_CHAIN: usize = 128;
+/// A match this long is taken without looking further
+const NICE_MATCH: usize = 128;
+/// Symbols collected before a block is emitted
+const BLOCK_SYMBOLS: usize = 16384;
+/// Largest stored block
+const MAX_STORED: usize = 65535;
+
+/// LSB-first bit writer
+struct BitWriter {
+    out: Vec<u8>,
+    bits: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new(out: Vec<u8>) -> Self {
+        Self {
+            out,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    fn put(&mut self, value: u32, n: u32) {
+        self.bits |= (value as u64) << self.count;
+        self.count += n;
+        while self.count >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Write a Huffman code, which goes out MSB first
+    fn put_code(&mut self, code: u16, len: u8) {
+        self.put(reverse_bits(code as u32, len as u32), len as u32);
+    }
+
+    fn align(&mut self) {
+        if self.count > 0 {
+            self.put(0, 8 - self.count);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.align();
+        self.out
+    }
+}
+
+/// An LZ77 symbol: a literal byte, or a back reference
+#[derive(Clone, Copy)]
+enum Symbol {
+    Literal(u8),
+    Match { len: u16, dist: u16 },
+}
+
+fn length_code(len: usize) -> usize {
+    LENGTH_BASE
+        .iter()
+        .rposition(|&base| base as usize <= len)
+        .unwrap()
+}
+
+fn dist_code(dist: usize) -> usize {
+    DIST_BASE
+        .iter()
+        .rposition(|&base| base as usize <= dist)
+        .unwrap()
+}
+
+/// Greedy LZ77 matcher over hash chains
+struct Matcher {
+    head: Vec<i32>,
+    prev: Vec<i32>,
+}
+
+impl Matcher {
+    fn new() -> Self {
+        Self {
+            head: vec![-1; 1 << HASH_BITS],
+            prev: vec![-1; WINDOW],
+        }
+    }
+
+    fn hash(data: &[u8], pos: usize) -> usize {
+        let v = (data[pos] as u32) << 16 | (data[pos + 1] as u32) << 8 | data[pos + 2] as u32;
+        (v.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+    }
+
+    fn insert(&mut self, data: &[u8], pos: usize) {
+        if pos + MIN_MATCH <= data.len() {
+            let h = Self::hash(data, pos);
+            self.prev[pos % WINDOW] = self.head[h];
+            self.head[h] = pos as i32;
+        }
+    }
+
+    /// Longest match for `pos` as (length, distance)
+    fn find(&self, data: &[u8], pos: usize) -> Option<(usize, usize)> {
+        if pos + MIN_MATCH > data.len() {
+            return None;
+        }
+        let max_len = MAX_MATCH.min(data.len() - pos);
+        let mut best = (0, 0);
+        let mut candidate = self.head[Self::hash(data, pos)];
+        let mut chain = 0;
+        while candidate >= 0 && chain < MAX_CHAIN {
+            let start = candidate as usize;
+            if pos - start > WINDOW {
+                break;
+            }
+            if data[start + best.0] == data[pos + best.0] {
+                let len = data[start..start + max_len]
+                    .iter()
+                    .zip(&data[pos..pos + max_len])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best.0 {
+                    best = (len, pos - start);
+                    if len >= NICE_MATCH.min(max_len) {
+                        break;
+                    }
+                }
+            }
+            let next = self.prev[start % WINDOW];
+            if next >= candidate {
+                break;
+            }
+            candidate = next;
+            chain += 1;
+        }
+        (best.0 >= MIN_MATCH).then_some(best)
+    }
+}
+
+/// Code lengths for `freqs`, limited to `max_len` bits
+fn huffman_lengths(freqs: &[u32], max_len: u8) -> Vec<u8> {
+    let mut freqs: Vec<u32> = freqs.to_vec();
+    loop {
+        let lengths = unlimited_lengths(&freqs);
+        if lengths.iter().all(|&len| len <= max_len) {
+            return lengths;
+        }
+        // Flatten the distribution and retry; nonzero stays nonzero
+        for f in freqs.iter_mut().filter(|f| **f > 0) {
+            *f = (*f >> 1) | 1;
+        }
+    }
+}
+
+/// Huffman code lengths by repeatedly merging the two lightest nodes
+fn unlimited_lengths(freqs: &[u32]) -> Vec<u8> {
+    let mut lengths = vec![0u8; freqs.len()];
+    // (weight, node); leaves are 0..n, internal nodes follow
+    let mut nodes: Vec<(u64, usize)> = freqs
+        .iter()
+        .enumerate()
+        .filter(|(_, &f)| f > 0)
+        .map(|(i, &f)| (f as u64, i))
+        .collect();
+    match nodes.len() {
+        0 => return lengths,
+        1 => {
+            lengths[nodes[0].1] = 1;
+            return lengths;
+        }
+        _ => {}
+    }
+
+    let mut parent = vec![usize::MAX; freqs.len() + nodes.len()];
+    let mut next = freqs.len();
+    while nodes.len() > 1 {
+        nodes.sort_unstable_by(|a, b| b.cmp(a));
+        let (wa, a) = nodes.pop().unwrap();
+        let (wb, b) = nodes.pop().unwrap();
+        parent[a] = next;
+        parent[b] = next;
+        nodes.push((wa + wb, next));
+        next += 1;
+    }
+    for (symbol, &f) in freqs.iter().enumerate() {
+        if f > 0 {
+            let mut depth = 0;
+            let mut node = symbol;
+            while parent[node] != usize::MAX {
+                node = parent[node];
+                depth += 1;
+            }
+            lengths[symbol] = depth.min(u8::MAX as u32) as u8;
+        }
+    }
+    lengths
+}
+
+/// Canonical codes for `lengths`
+fn canonical_codes(lengths: &[u8]) -> Vec<u16> {
+    let mut counts = [0u16; 16];
+    for &len in lengths {
+        counts[len as usize] += 1;
+    }
+    counts[0] = 0;
+    let mut next = [0u16; 16];
+    let mut code = 0u16;
+    for len in 1..16 {
+        code = (code + counts[len - 1]) << 1;
+        next[len] = code;
+    }
+    lengths
+        .iter()
+        .map(|&len| {
+            if len == 0 {
+                0
+            } else {
+                let c = next[len as usize];
+                next[len as usize] += 1;
+                c
+            }
+        })
+        .collect()
+}
+
+/// Run-length encode code lengths with symbols 16/17/18 as
+/// (symbol, extra bits value)
+fn rle_lengths(lengths: &[u8]) -> Vec<(u8, u8)> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lengths.len() {
+        let len = lengths[i];
+        let run = lengths[i..].iter().take_while(|&&l| l == len).count();
+        if len == 0 && run >= 3 {
+            let n = run.min(138);
+            if n >= 11 {
+                out.push((18, (n - 11) as u8));
+            } else {
+                out.push((17, (n - 3) as u8));
+            }
+            i += n;
+        } else if len != 0 && run >= 4 {
+            out.push((len, 0));
+            let n = (run - 1).min(6);
+            out.push((16, (n - 3) as u8));
+            i += 1 + n;
+        } else {
+            out.push((len, 0));
+            i += 1;
+        }
+    }
+    out
+}
+
+fn fixed_lengths() -> (Vec<u8>, Vec<u8>) {
+    let mut lit = vec![8u8; 288];
+    lit[144..256].fill(9);
+    lit[256..280].fill(7);
+    (lit, vec![5u8; 30])
+}
+
+/// Bits needed to code `symbols` with the given lengths, excluding headers
+fn coded_bits(symbols: &[Symbol], lit: &[u8], dist: &[u8]) -> usize {
+    let mut bits = lit[256] as usize;
+    for symbol in symbols {
+        bits += match *symbol {
+            Symbol::Literal(b) => lit[b as usize] as usize,
+            Symbol::Match { len, dist: d } => {
+                let lc = length_code(len as usize);
+                let dc = dist_code(d as usize);
+                lit[257 + lc] as usize
+                    + LENGTH_EXTRA[lc] as usize
+                    + dist[dc] as usize
+                    + DIST_EXTRA[dc] as usize
+            }
+        };
+    }
+    bits
+}
+
+fn write_symbols(out: &mut BitWriter, symbols: &[Symbol], lit_len: &[u8], dist_len: &[u8]) {
+    let lit_codes = canonical_codes(lit_len);
+    let dist_codes = canonical_codes(dist_len);
+    for symbol in symbols {
+        match *symbol {
+            Symbol::Literal(b) => out.put_code(lit_codes[b as usize], lit_len[b as usize]),
+            Symbol::Match { len, dist } => {
+                let lc = length_code(len as usize);
+                out.put_code(lit_codes[257 + lc], lit_len[257 + lc]);
+                out.put(len as u32 - LENGTH_BASE[lc] as u32, LENGTH_EXTRA[lc] as u32);
+                let dc = dist_code(dist as usize);
+                out.put_code(dist_codes[dc], dist_len[dc]);
+                out.put(dist as u32 - DIST_BASE[dc] as u32, DIST_EXTRA[dc] as u32);
+            }
+        }
+    }
+    out.put_code(lit_codes[256], lit_len[256]);
+}
+
+/// Emit `symbols`, covering `raw` bytes of input, as one block
+fn write_block(out: &mut BitWriter, symbols: &[Symbol], raw: &[u8], last: bool) {
+    let mut lit_freq = [0u32; 286];
+    let mut dist_freq = [0u32; 30];
+    lit_freq[256] = 1;
+    for symbol in symbols {
+        match *symbol {
+            Symbol::Literal(b) => lit_freq[b as usize] += 1,
+            Symbol::Match { len, dist } => {
+                lit_freq[257 + length_code(len as usize)] += 1;
+                dist_freq[dist_code(dist as usize)] += 1;
+            }
+        }
+    }
+
+    let lit_len = huffman_lengths(&lit_freq, 15);
+    let mut dist_len = huffman_lengths(&dist_freq, 15);
+    // At least one distance code must be sent
+    if dist_len.iter().all(|&len| len == 0) {
+        dist_len[0] = 1;
+    }
+    let nlen = 257
+        + lit_len[257..]
+            .iter()
+            .rposition(|&l| l != 0)
+            .map_or(0, |i| i + 1);
+    let ndist = 1 + dist_len.iter().rposition(|&l| l != 0).unwrap_or(0);
+
+    let mut all = lit_len[..nlen].to_vec();
+    all.extend_from_slice(&dist_len[..ndist]);
+    let rle = rle_lengths(&all);
+    let mut clen_freq = [0u32; 19];
+    for &(symbol, _) in &rle {
+        clen_freq[symbol as usize] += 1;
+    }
+    let clen_len = huffman_lengths(&clen_freq, 7);
+    let ncode = 4.max(
+        1 + CLEN_ORDER
+            .iter()
+            .rposition(|&i| clen_len[i] != 0)
+            .unwrap_or(0),
+    );
+
+    let extra_bits = |symbol: u8| match symbol {
+        16 => 2,
+        17 => 3,
+        18 => 7,
+        _ => 0,
+    };
+    let header_bits = 5
+        + 5
+        + 4
+        + 3 * ncode
+        + rle
+            .iter()
+            .map(|&(s, _)| clen_len[s as usize] as usize + extra_bits(s) as usize)
+            .sum::<usize>();
+    let dynamic_bits = header_bits + coded_bits(symbols, &lit_len, &dist_len);
+    let (fixed_lit, fixed_dist) = fixed_lengths();
+    let fixed_bits = coded_bits(symbols, &fixed_lit, &fixed_dist);
+    let stored_bits = (raw.len() + 4) * 8 + 7;
+
+    if raw.len() <= MAX_STORED && stored_bits <= dynamic_bits.min(fixed_bits) {
+        out.put(last as u32, 1);
+        out.put(0, 2);
+        out.align();
+        out.put(raw.len() as u32, 16);
+        out.put(!raw.len() as u32 & 0xFFFF, 16);
+        out.out.extend_from_slice(raw);
+    } else if fixed_bits <= dynamic_bits {
+        out.put(last as u32, 1);
+        out.put(1, 2);
+        write_symbols(out, symbols, &fixed_lit, &fixed_dist);
+    } else {
+        out.put(last as u32, 1);
+        out.put(2, 2);
+        out.put((nlen - 257) as u32, 5);
+        out.put((ndist - 1) as u32, 5);
+        out.put((ncode - 4) as u32, 4);
+        for &i in &CLEN_ORDER[..ncode] {
+            out.put(clen_len[i] as u32, 3);
+        }
+        let clen_codes = canonical_codes(&clen_len);
+        for &(symbol, extra) in &rle {
+            out.put_code(clen_codes[symbol as usize], clen_len[symbol as usize]);
+            out.put(extra as u32, extra_bits(symbol));
+        }
+        write_symbols(out, symbols, &lit_len, &dist_len);
+    }
+}
+
+/// Compress `data` as a raw DEFLATE stream.
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    deflate_into(Vec::new(), data)
+}
+
+fn deflate_into(out: Vec<u8>, data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter::new(out);
+    if data.is_empty() {
+        write_block(&mut out, &[], &[], true);
+        return out.finish();
+    }
+
+    let mut matcher = Matcher::new();
+    let mut symbols = Vec::with_capacity(BLOCK_SYMBOLS);
+    let mut block_start = 0;
+    let mut pos = 0;
+    while pos < data.len() {
+        let step = match matcher.find(data, pos) {
+            Some((len, dist)) => {
+                symbols.push(Symbol::Match {
+                    len: len as u16,
+                    dist: dist as u16,
+                });
+                len
+            }
+            None => {
+                symbols.push(Symbol::Literal(data[pos]));
+                1
+            }
+        };
+        for p in pos..pos + step {
+            matcher.insert(data, p);
+        }
+        pos += step;
+
+        // Keep stored fallbacks within the 64K limit
+        if symbols.len() >= BLOCK_SYMBOLS || pos - block_start >= MAX_STORED - MAX_MATCH {
+            write_block(
+                &mut out,
+                &symbols,
+                &data[block_start..pos],
+                pos == data.len(),
+            );
+            symbols.clear();
+            block_start = pos;
+        }
+    }
+    if !symbols.is_empty() {
+        write_block(&mut out, &symbols, &data[block_start..], true);
+    }
+    out.finish()
+}
+
+/// Compress `data` as a zlib stream.
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    // CMF: deflate, 32K window; FLG: default level, check bits
+    let mut out = deflate_into(vec![0x78, 0x9C], data);
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inflate::zlib_decompress;
+
+    fn round_trip(data: &[u8]) -> Vec<u8> {
+        let compressed = zlib_compress(data);
+        let out = zlib_decompress(&compressed, data.len()).unwrap();
+        assert_eq!(out, data);
+        compressed
+    }
+
+    /// Deterministic pseudo-random bytes
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x1234_5678u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trip(b"");
+        round_trip(b"a");
+        round_trip(b"abcabcabcabcabcabcabcabc");
+        let text: Vec<u8> = (0..20000u32)
+            .flat_map(|i| (i % 251).to_le_bytes())
+            .collect();
+        round_trip(&text);
+        // Incompressible data goes out in stored blocks, a few bytes of
+        // framing each
+        let random = noise(200_000);
+        let size = round_trip(&random).len();
+        assert!(size < random.len() + random.len() / 1000, "{}", size);
+        // Long runs compress to almost nothing
+        assert!(round_trip(&[7u8; 100_000]).len() < 400);
+    }
+
+    #[test]
+    fn decodes_reference_streams() {
+        // zlib.compress(b"hello hello hello hello") from CPython: fixed Huffman
+        let fixed = [
+            0x78, 0x9C, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8, 0x40, 0x27, 0x01, 0x68, 0x03,
+            0x08, 0xB1,
+        ];
+        assert_eq!(
+            zlib_decompress(&fixed, 64).unwrap(),
+            b"hello hello hello hello"
+        );
+        assert!(zlib_decompress(&fixed, 10).is_err());
+
+        // zlib.compress(b"".join(b"%d," % (i * i % 97) for i in range(200)), 9):
+        // dynamic Huffman
+        let dynamic = [
+            0x78, 0xDA, 0xED, 0x90, 0xC9, 0x8D, 0x05, 0x21, 0x0C, 0x05, 0x13, 0xAA, 0x43, 0xDB,
+            0x06, 0x03, 0xF9, 0x27, 0x36, 0x05, 0x09, 0xFC, 0x04, 0x46, 0x42, 0x88, 0xE5, 0xAD,
+            0xFE, 0x08, 0x06, 0x87, 0x68, 0x72, 0x52, 0xCD, 0x38, 0xF4, 0x60, 0x07, 0x45, 0x0E,
+            0xC6, 0x62, 0x25, 0x49, 0x05, 0x9D, 0x1C, 0x11, 0xC5, 0xFA, 0x88, 0x64, 0x16, 0x47,
+            0xB4, 0x5C, 0x05, 0x3C, 0x0F, 0xE6, 0xC7, 0xA6, 0x27, 0xB9, 0xD8, 0x9B, 0x39, 0xC8,
+            0x64, 0xAB, 0xA6, 0xD4, 0x24, 0x82, 0xDD, 0xB4, 0x8C, 0x4D, 0x25, 0x21, 0x92, 0x53,
+            0xEC, 0xC9, 0x3A, 0x2C, 0xF7, 0x7A, 0xEB, 0x5D, 0x7D, 0xF4, 0xAB, 0x2F, 0x48, 0xA8,
+            0x04, 0x69, 0x92, 0xE3, 0x09, 0x29, 0xA7, 0xA8, 0xD2, 0x1A, 0x68, 0xA3, 0x99, 0x96,
+            0xFB, 0x9A, 0x1B, 0xE1, 0x06, 0x89, 0x17, 0xAA, 0x6F, 0x40, 0x63, 0x1A, 0xD6, 0xC8,
+            0x06, 0xEF, 0x57, 0x22, 0x6F, 0x1D, 0x4B, 0x59, 0xAD, 0x6E, 0x49, 0xAB, 0x5A, 0xB8,
+            0x5E, 0x79, 0x47, 0x70, 0x9C, 0x45, 0xF0, 0xFD, 0xCF, 0xE4, 0xC7, 0x4C, 0xFE, 0x00,
+            0x90, 0x13, 0x6D, 0xAD,
+        ];
+        let expected: Vec<u8> = (0..200u32)
+            .flat_map(|i| alloc::format!("{},", i * i % 97).into_bytes())
+            .collect();
+        assert_eq!(zlib_decompress(&dynamic, 1024).unwrap(), expected);
+
+        let mut corrupt = dynamic;
+        corrupt[40] ^= 0x10;
+        assert!(zlib_decompress(&corrupt, 1024).is_err());
+    }
+
+    #[test]
+    fn length_limited_codes() {
+        // Fibonacci frequencies force a very deep unrestricted tree
+        let mut freqs = [0u32; 30];
+        let (mut a, mut b) = (1u32, 1u32);
+        for f in freqs.iter_mut() {
+            *f = a;
+            (a, b) = (b, a + b);
+        }
+        let lengths = huffman_lengths(&freqs, 15);
+        assert!(lengths.iter().all(|&l| (1..=15).contains(&l)));
+        let kraft: u64 = lengths.iter().map(|&l| 1u64 << (15 - l)).sum();
+        assert!(kraft <= 1 << 15);
+    }
+}
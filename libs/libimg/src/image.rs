@@ -0,0 +1,162 @@
+//! Decoded images and conversion to framebuffer pixel layouts.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// An 8-bit RGBA image.
+///
+/// Rows top-to-bottom, pixels left-to-right, 4 bytes per pixel (R, G, B, A).
+/// Alpha is straight (not premultiplied).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl Image {
+    /// Create a fully transparent image.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            rgba: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Wrap an RGBA buffer. Returns `None` if it is not `width * height * 4` bytes.
+    pub fn from_rgba(width: u32, height: u32, rgba: Vec<u8>) -> Option<Self> {
+        if rgba.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    /// Build an opaque image from `width * height * 3` bytes of RGB.
+    pub fn from_rgb(width: u32, height: u32, rgb: &[u8]) -> Self {
+        let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+        for px in rgb.chunks_exact(3) {
+            rgba.extend_from_slice(&[px[0], px[1], px[2], 255]);
+        }
+        rgba.resize(width as usize * height as usize * 4, 255);
+        Self {
+            width,
+            height,
+            rgba,
+        }
+    }
+
+    /// The RGBA value of the pixel at (x, y). Panics if out of bounds.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.rgba[i],
+            self.rgba[i + 1],
+            self.rgba[i + 2],
+            self.rgba[i + 3],
+        ]
+    }
+
+    /// The RGBA bytes of row `y`.
+    pub fn row(&self, y: u32) -> &[u8] {
+        let stride = self.width as usize * 4;
+        &self.rgba[y as usize * stride..(y as usize + 1) * stride]
+    }
+
+    /// Whether every pixel has full alpha.
+    pub fn is_opaque(&self) -> bool {
+        self.rgba.chunks_exact(4).all(|px| px[3] == 255)
+    }
+
+    /// Drop alpha, giving `width * height * 3` bytes of RGB (e.g. for
+    /// [`crate::bmp::encode_bmp_24`]).
+    pub fn to_rgb(&self) -> Vec<u8> {
+        let mut rgb = Vec::with_capacity(self.width as usize * self.height as usize * 3);
+        for px in self.rgba.chunks_exact(4) {
+            rgb.extend_from_slice(&px[..3]);
+        }
+        rgb
+    }
+
+    /// Convert to rows laid out like a `FrameBuf` with `bpp` bytes per pixel
+    /// (3 or 4) and the given channel order, `width * bpp` bytes per row.
+    ///
+    /// Each row can be copied straight into the framebuffer:
+    ///
+    /// ```ignore
+    /// let pixels = image.to_pixels(fb.bpp, fb.is_bgr);
+    /// let row_bytes = image.width as usize * fb.bpp;
+    /// for (y, row) in pixels.chunks_exact(row_bytes).enumerate() {
+    ///     fb.copy_row_from(x, top + y, row, row_bytes);
+    /// }
+    /// fb.mark_dirty(x as i32, top as i32, image.width as i32, image.height as i32);
+    /// ```
+    ///
+    /// Alpha is ignored; with 4 bytes per pixel it is stored in the fourth
+    /// byte. Use [`Image::blend_row_into`] to composite over existing pixels.
+    pub fn to_pixels(&self, bpp: usize, is_bgr: bool) -> Vec<u8> {
+        let mut out = vec![0u8; self.width as usize * self.height as usize * bpp];
+        for (src, dst) in self.rgba.chunks_exact(4).zip(out.chunks_exact_mut(bpp)) {
+            store(dst, src, is_bgr);
+            if bpp == 4 {
+                dst[3] = src[3];
+            }
+        }
+        out
+    }
+
+    /// Alpha-blend row `y` over `dst`, a row in a `FrameBuf` layout with
+    /// `bpp` bytes per pixel. Pixels beyond the end of `dst` are clipped.
+    pub fn blend_row_into(&self, y: u32, dst: &mut [u8], bpp: usize, is_bgr: bool) {
+        for (src, out) in self.row(y).chunks_exact(4).zip(dst.chunks_exact_mut(bpp)) {
+            match src[3] {
+                0 => {}
+                255 => store(out, src, is_bgr),
+                alpha => {
+                    let (r, b) = if is_bgr { (2, 0) } else { (0, 2) };
+                    out[r] = blend(src[0], out[r], alpha);
+                    out[1] = blend(src[1], out[1], alpha);
+                    out[b] = blend(src[2], out[b], alpha);
+                }
+            }
+        }
+    }
+}
+
+fn store(dst: &mut [u8], rgba: &[u8], is_bgr: bool) {
+    if is_bgr {
+        dst[0] = rgba[2];
+        dst[1] = rgba[1];
+        dst[2] = rgba[0];
+    } else {
+        dst[..3].copy_from_slice(&rgba[..3]);
+    }
+}
+
+fn blend(fg: u8, bg: u8, alpha: u8) -> u8 {
+    let a = alpha as u32;
+    ((fg as u32 * a + bg as u32 * (255 - a) + 127) / 255) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_framebuffer_layouts() {
+        let image = Image::from_rgba(2, 1, vec![10, 20, 30, 255, 200, 100, 0, 128]).unwrap();
+        assert_eq!(image.to_pixels(3, false), [10, 20, 30, 200, 100, 0]);
+        assert_eq!(
+            image.to_pixels(4, true),
+            [30, 20, 10, 255, 0, 100, 200, 128]
+        );
+
+        let mut row = [0u8; 8];
+        image.blend_row_into(0, &mut row, 4, true);
+        assert_eq!(row, [30, 20, 10, 0, 0, 50, 100, 0]);
+    }
+}
@@ -0,0 +1,374 @@
+//! DEFLATE decompression (RFC 1951) and the zlib wrapper (RFC 1950).
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::checksum::adler32;
+
+/// Base lengths for length codes 257..=285
+pub(crate) const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+/// Extra bits for length codes 257..=285
+pub(crate) const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances for distance codes 0..=29
+pub(crate) const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+/// Extra bits for distance codes 0..=29
+pub(crate) const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which code length code lengths are stored
+pub(crate) const CLEN_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Bits resolved by a single table lookup
+const FAST_BITS: u32 = 9;
+
+/// Canonical Huffman decoding table.
+///
+/// Codes up to `FAST_BITS` long resolve with one lookup; longer codes fall
+/// back to walking the code lengths one bit at a time.
+struct Huffman {
+    /// Number of codes of each length
+    counts: [u16; 16],
+    /// Symbols ordered by code
+    symbols: [u16; 288],
+    /// `length << 9 | symbol` indexed by the next `FAST_BITS` input bits,
+    /// 0 for codes longer than that
+    fast: [u16; 1 << FAST_BITS],
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, String> {
+        let mut table = Huffman {
+            counts: [0; 16],
+            symbols: [0; 288],
+            fast: [0; 1 << FAST_BITS],
+        };
+        for &len in lengths {
+            table.counts[len as usize] += 1;
+        }
+        table.counts[0] = 0;
+
+        // Reject over-subscribed codes; incomplete ones are allowed (e.g. a
+        // single distance code)
+        let mut left: i32 = 1;
+        for len in 1..16 {
+            left = (left << 1) - table.counts[len] as i32;
+            if left < 0 {
+                return Err(String::from("over-subscribed Huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + table.counts[len];
+        }
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                table.symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        // Assign canonical codes and fill the fast table with the
+        // bit-reversed code, since the stream delivers codes LSB first
+        let mut code = 0u32;
+        let mut index = 0usize;
+        for len in 1..=FAST_BITS as usize {
+            for _ in 0..table.counts[len] {
+                let symbol = table.symbols[index];
+                let reversed = reverse_bits(code, len as u32);
+                let entry = (len as u16) << 9 | symbol;
+                let mut fill = reversed as usize;
+                while fill < 1 << FAST_BITS {
+                    table.fast[fill] = entry;
+                    fill += 1 << len;
+                }
+                code += 1;
+                index += 1;
+            }
+            code <<= 1;
+        }
+        Ok(table)
+    }
+}
+
+pub(crate) fn reverse_bits(code: u32, len: u32) -> u32 {
+    code.reverse_bits() >> (32 - len)
+}
+
+/// LSB-first bit reader over the compressed stream
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u64,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    fn refill(&mut self) {
+        while self.count <= 56 && self.pos < self.data.len() {
+            self.bits |= (self.data[self.pos] as u64) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+    }
+
+    fn bits(&mut self, n: u32) -> Result<u32, String> {
+        if n == 0 {
+            return Ok(0);
+        }
+        if self.count < n {
+            self.refill();
+            if self.count < n {
+                return Err(String::from("unexpected end of deflate stream"));
+            }
+        }
+        let value = (self.bits & ((1u64 << n) - 1)) as u32;
+        self.bits >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    fn decode(&mut self, table: &Huffman) -> Result<u16, String> {
+        if self.count < FAST_BITS {
+            self.refill();
+        }
+        if self.count >= FAST_BITS {
+            let entry = table.fast[(self.bits & ((1 << FAST_BITS) - 1)) as usize];
+            if entry != 0 {
+                let len = (entry >> 9) as u32;
+                self.bits >>= len;
+                self.count -= len;
+                return Ok(entry & 0x1FF);
+            }
+        }
+
+        // Walk the code one bit at a time
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..16 {
+            code |= self.bits(1)? as i32;
+            let count = table.counts[len] as i32;
+            if code - count < first {
+                return Ok(table.symbols[(index + (code - first)) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(String::from("invalid Huffman code"))
+    }
+
+    /// Discard bits up to the next byte boundary
+    fn align(&mut self) {
+        let drop = self.count % 8;
+        self.bits >>= drop;
+        self.count -= drop;
+    }
+
+    /// Bytes consumed so far, counting whole buffered bytes as unread
+    fn position(&self) -> usize {
+        self.pos - (self.count / 8) as usize
+    }
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    // Both tables are built from fixed, valid lengths
+    let lit = Huffman::new(&lengths).unwrap();
+    let dist = Huffman::new(&[5u8; 30]).unwrap();
+    (lit, dist)
+}
+
+fn dynamic_tables(input: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let nlen = input.bits(5)? as usize + 257;
+    let ndist = input.bits(5)? as usize + 1;
+    let ncode = input.bits(4)? as usize + 4;
+    if nlen > 286 || ndist > 30 {
+        return Err(String::from("bad dynamic block counts"));
+    }
+
+    let mut clen = [0u8; 19];
+    for &index in &CLEN_ORDER[..ncode] {
+        clen[index] = input.bits(3)? as u8;
+    }
+    let clen_table = Huffman::new(&clen)?;
+
+    let mut lengths = [0u8; 286 + 30];
+    let mut i = 0;
+    while i < nlen + ndist {
+        let symbol = input.decode(&clen_table)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                if i == 0 {
+                    return Err(String::from("repeat with no previous length"));
+                }
+                (lengths[i - 1], 3 + input.bits(2)? as usize)
+            }
+            17 => (0, 3 + input.bits(3)? as usize),
+            _ => (0, 11 + input.bits(7)? as usize),
+        };
+        if i + repeat > nlen + ndist {
+            return Err(String::from("too many code lengths"));
+        }
+        lengths[i..i + repeat].fill(value);
+        i += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err(String::from("no end-of-block code"));
+    }
+
+    let lit = Huffman::new(&lengths[..nlen])?;
+    let dist = Huffman::new(&lengths[nlen..nlen + ndist])?;
+    Ok((lit, dist))
+}
+
+fn inflate_block(
+    input: &mut BitReader,
+    out: &mut Vec<u8>,
+    lit: &Huffman,
+    dist: &Huffman,
+    limit: usize,
+) -> Result<(), String> {
+    loop {
+        let symbol = input.decode(lit)? as usize;
+        if symbol < 256 {
+            if out.len() >= limit {
+                return Err(String::from("decompressed data exceeds limit"));
+            }
+            out.push(symbol as u8);
+        } else if symbol == 256 {
+            return Ok(());
+        } else {
+            let index = symbol - 257;
+            if index >= 29 {
+                return Err(format!("invalid length code {symbol}"));
+            }
+            let len =
+                LENGTH_BASE[index] as usize + input.bits(LENGTH_EXTRA[index] as u32)? as usize;
+            let dsym = input.decode(dist)? as usize;
+            if dsym >= 30 {
+                return Err(format!("invalid distance code {dsym}"));
+            }
+            let distance = DIST_BASE[dsym] as usize + input.bits(DIST_EXTRA[dsym] as u32)? as usize;
+            if distance > out.len() {
+                return Err(String::from("distance too far back"));
+            }
+            if out.len() + len > limit {
+                return Err(String::from("decompressed data exceeds limit"));
+            }
+            let start = out.len() - distance;
+            if distance >= len {
+                out.extend_from_within(start..start + len);
+            } else {
+                // Overlapping copy repeats the last `distance` bytes
+                for i in 0..len {
+                    let byte = out[start + i];
+                    out.push(byte);
+                }
+            }
+        }
+    }
+}
+
+/// Decompress a raw DEFLATE stream, producing at most `limit` bytes.
+///
+/// Returns the data and the number of input bytes consumed.
+pub fn inflate(data: &[u8], limit: usize) -> Result<(Vec<u8>, usize), String> {
+    let mut input = BitReader::new(data);
+    let mut out = Vec::new();
+    loop {
+        let last = input.bits(1)? == 1;
+        match input.bits(2)? {
+            0 => {
+                input.align();
+                let len = input.bits(16)?;
+                let nlen = input.bits(16)?;
+                if len != !nlen & 0xFFFF {
+                    return Err(String::from("stored block length mismatch"));
+                }
+                let len = len as usize;
+                if out.len() + len > limit {
+                    return Err(String::from("decompressed data exceeds limit"));
+                }
+                // Whole bytes may still be buffered after the header
+                let mut remaining = len;
+                while remaining > 0 && input.count >= 8 {
+                    out.push(input.bits(8)? as u8);
+                    remaining -= 1;
+                }
+                let bytes = input
+                    .data
+                    .get(input.pos..input.pos + remaining)
+                    .ok_or_else(|| String::from("unexpected end of stored block"))?;
+                out.extend_from_slice(bytes);
+                input.pos += remaining;
+            }
+            1 => {
+                let (lit, dist) = fixed_tables();
+                inflate_block(&mut input, &mut out, &lit, &dist, limit)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_tables(&mut input)?;
+                inflate_block(&mut input, &mut out, &lit, &dist, limit)?;
+            }
+            _ => return Err(String::from("invalid block type")),
+        }
+        if last {
+            return Ok((out, input.position()));
+        }
+    }
+}
+
+/// Decompress a zlib stream, producing at most `limit` bytes.
+pub fn zlib_decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    if data.len() < 6 {
+        return Err(String::from("zlib stream too short"));
+    }
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0F != 8 || cmf >> 4 > 7 {
+        return Err(String::from("not a deflate zlib stream"));
+    }
+    if ((cmf as u16) << 8 | flg as u16) % 31 != 0 {
+        return Err(String::from("bad zlib header check"));
+    }
+    if flg & 0x20 != 0 {
+        return Err(String::from("zlib preset dictionary not supported"));
+    }
+    let (out, used) = inflate(&data[2..], limit)?;
+    let trailer = data
+        .get(2 + used..2 + used + 4)
+        .ok_or_else(|| String::from("missing zlib checksum"))?;
+    let expected = u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    if adler32(&out) != expected {
+        return Err(String::from("zlib checksum mismatch"));
+    }
+    Ok(out)
+}
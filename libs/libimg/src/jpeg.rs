@@ -0,0 +1,1248 @@
+//! Baseline and progressive JPEG decoding.
+//!
+//! Decodes Huffman-coded JPEG files (SOF0, SOF1 and SOF2 frames) with any
+//! chroma subsampling, restart intervals, and grayscale, YCbCr, RGB, CMYK
+//! or YCCK color. Arithmetic-coded, lossless, hierarchical and 12-bit files
+//! are rejected.
+//!
+//! Baseline scans are dequantized and transformed block by block as they
+//! are read. Progressive scans refine a coefficient buffer per component,
+//! which is transformed once every scan has arrived.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::image::Image;
+use crate::MAX_DIMENSION;
+
+/// Natural (row-major) index of each zigzag position
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// Bits resolved by a single table lookup
+const FAST_BITS: u32 = 9;
+
+/// Canonical Huffman decoding table from a DHT segment
+struct HuffTable {
+    /// `length << 8 | symbol` indexed by the next `FAST_BITS` input bits,
+    /// 0 for codes longer than that
+    fast: [u16; 1 << FAST_BITS],
+    /// First code of each length
+    first_code: [u32; 17],
+    /// Number of codes of each length
+    counts: [u16; 17],
+    /// Index in `symbols` of the first code of each length
+    first_index: [u16; 17],
+    symbols: Vec<u8>,
+}
+
+impl HuffTable {
+    fn new(counts: &[u8; 16], symbols: &[u8]) -> Result<Self, String> {
+        let mut table = HuffTable {
+            fast: [0; 1 << FAST_BITS],
+            first_code: [0; 17],
+            counts: [0; 17],
+            first_index: [0; 17],
+            symbols: symbols.to_vec(),
+        };
+        let mut code = 0u32;
+        let mut index = 0u16;
+        for len in 1..=16 {
+            let count = counts[len - 1] as u16;
+            table.first_code[len] = code;
+            table.counts[len] = count;
+            table.first_index[len] = index;
+            if code + count as u32 > 1 << len {
+                return Err(String::from("bad Huffman table"));
+            }
+            if len <= FAST_BITS as usize {
+                for i in 0..count {
+                    let c = (code + i as u32) as usize;
+                    let symbol = symbols[(index + i) as usize] as u16;
+                    let shift = FAST_BITS as usize - len;
+                    for fill in c << shift..(c + 1) << shift {
+                        table.fast[fill] = (len as u16) << 8 | symbol;
+                    }
+                }
+            }
+            code = (code + count as u32) << 1;
+            index += count;
+        }
+        Ok(table)
+    }
+}
+
+/// MSB-first reader over entropy-coded data.
+///
+/// Removes stuffed zero bytes and stops at the next marker, supplying zero
+/// bits from then on.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u32,
+    count: u32,
+    /// Marker reached, with `pos` left pointing at its 0xFF
+    marker: Option<u8>,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self {
+            data,
+            pos,
+            bits: 0,
+            count: 0,
+            marker: None,
+        }
+    }
+
+    fn fill(&mut self) {
+        while self.count <= 24 {
+            let mut byte = 0;
+            if self.marker.is_none() && self.pos < self.data.len() {
+                byte = self.data[self.pos];
+                if byte == 0xFF {
+                    match self.data.get(self.pos + 1) {
+                        Some(0) => self.pos += 2,
+                        Some(&next) => {
+                            self.marker = Some(next);
+                            byte = 0;
+                        }
+                        None => {
+                            self.pos += 1;
+                            byte = 0;
+                        }
+                    }
+                } else {
+                    self.pos += 1;
+                }
+            }
+            self.bits |= (byte as u32) << (24 - self.count);
+            self.count += 8;
+        }
+    }
+
+    fn bits(&mut self, n: u32) -> u32 {
+        if n == 0 {
+            return 0;
+        }
+        self.fill();
+        let value = self.bits >> (32 - n);
+        self.bits <<= n;
+        self.count -= n;
+        value
+    }
+
+    fn bit(&mut self) -> bool {
+        self.bits(1) == 1
+    }
+
+    /// Read an `n`-bit magnitude and sign-extend it
+    fn receive_extend(&mut self, n: u32) -> i32 {
+        if n == 0 {
+            return 0;
+        }
+        let value = self.bits(n) as i32;
+        if value < 1 << (n - 1) {
+            value - (1 << n) + 1
+        } else {
+            value
+        }
+    }
+
+    fn decode(&mut self, table: &HuffTable) -> Result<u8, String> {
+        self.fill();
+        let entry = table.fast[(self.bits >> (32 - FAST_BITS)) as usize];
+        if entry != 0 {
+            let len = (entry >> 8) as u32;
+            self.bits <<= len;
+            self.count -= len;
+            return Ok(entry as u8);
+        }
+        for len in FAST_BITS as usize + 1..=16 {
+            let code = self.bits >> (32 - len);
+            let offset = code.wrapping_sub(table.first_code[len]);
+            if offset < table.counts[len] as u32 {
+                self.bits <<= len;
+                self.count -= len as u32;
+                return Ok(table.symbols[table.first_index[len] as usize + offset as usize]);
+            }
+        }
+        Err(String::from("bad Huffman code"))
+    }
+
+    /// Position of the next marker in the data
+    fn marker_position(&mut self) -> usize {
+        if self.marker.is_none() {
+            while self.pos + 1 < self.data.len()
+                && (self.data[self.pos] != 0xFF || matches!(self.data[self.pos + 1], 0 | 0xFF))
+            {
+                self.pos += 1;
+            }
+        }
+        self.pos
+    }
+
+    /// Consume a restart marker and reset the bit buffer
+    fn restart(&mut self) -> Result<(), String> {
+        let pos = self.marker_position();
+        match self.data.get(pos + 1) {
+            Some(0xD0..=0xD7) => {
+                self.pos = pos + 2;
+                self.bits = 0;
+                self.count = 0;
+                self.marker = None;
+                Ok(())
+            }
+            _ => Err(String::from("missing restart marker")),
+        }
+    }
+}
+
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant: usize,
+    /// Blocks per row and column, padded to whole MCUs
+    blocks_w: usize,
+    blocks_h: usize,
+    /// Coefficients in natural order, for progressive frames
+    coefs: Vec<i16>,
+    /// Decoded samples, `blocks_w * 8` per row
+    plane: Vec<u8>,
+    dc_pred: i32,
+    dc_table: usize,
+    ac_table: usize,
+}
+
+struct Frame {
+    width: usize,
+    height: usize,
+    progressive: bool,
+    components: Vec<Component>,
+    hmax: usize,
+    vmax: usize,
+    mcus_x: usize,
+    mcus_y: usize,
+}
+
+/// Parameters of the scan being decoded
+struct Scan {
+    /// Indices into the frame's components
+    components: Vec<usize>,
+    /// Spectral selection start and end (zigzag positions)
+    start: usize,
+    end: usize,
+    /// Successive approximation bit positions, high and low
+    high: u32,
+    low: u32,
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Quantization tables in natural order
+    quant: [[u16; 64]; 4],
+    dc_tables: [Option<HuffTable>; 4],
+    ac_tables: [Option<HuffTable>; 4],
+    frame: Option<Frame>,
+    restart_interval: usize,
+    /// Color transform from an Adobe APP14 segment
+    adobe_transform: Option<u8>,
+    /// Remaining blocks in a progressive end-of-band run
+    eobrun: u32,
+}
+
+fn read_u16_be(buf: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([buf[offset], buf[offset + 1]])
+}
+
+impl<'a> Decoder<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            quant: [[0; 64]; 4],
+            dc_tables: [None, None, None, None],
+            ac_tables: [None, None, None, None],
+            frame: None,
+            restart_interval: 0,
+            adobe_transform: None,
+            eobrun: 0,
+        }
+    }
+
+    /// The next marker code, skipping anything before it. `None` at the end
+    /// of the data.
+    fn next_marker(&mut self) -> Option<u8> {
+        while self.pos + 1 < self.data.len() {
+            if self.data[self.pos] == 0xFF && !matches!(self.data[self.pos + 1], 0 | 0xFF) {
+                let marker = self.data[self.pos + 1];
+                self.pos += 2;
+                return Some(marker);
+            }
+            self.pos += 1;
+        }
+        None
+    }
+
+    /// The body of the segment at `pos`, advancing past it
+    fn segment(&mut self) -> Result<&'a [u8], String> {
+        let data = self.data;
+        if self.pos + 2 > data.len() {
+            return Err(String::from("truncated segment"));
+        }
+        let len = read_u16_be(data, self.pos) as usize;
+        if len < 2 || self.pos + len > data.len() {
+            return Err(String::from("truncated segment"));
+        }
+        let body = &data[self.pos + 2..self.pos + len];
+        self.pos += len;
+        Ok(body)
+    }
+
+    fn decode(mut self) -> Result<Image, String> {
+        if !self.data.starts_with(&[0xFF, 0xD8]) {
+            return Err(String::from("not a JPEG file"));
+        }
+        self.pos = 2;
+        let mut scans = 0;
+        loop {
+            let marker = match self.next_marker() {
+                Some(marker) => marker,
+                // Tolerate a missing EOI once image data has been seen
+                None if scans > 0 => break,
+                None => return Err(String::from("no image data")),
+            };
+            match marker {
+                0xC0 | 0xC1 => self.start_frame(false)?,
+                0xC2 => self.start_frame(true)?,
+                0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => {
+                    return Err(format!("unsupported JPEG frame type SOF{}", marker - 0xC0))
+                }
+                0xC4 => self.define_huffman()?,
+                0xDB => self.define_quant()?,
+                0xDD => {
+                    let body = self.segment()?;
+                    if body.len() < 2 {
+                        return Err(String::from("bad DRI segment"));
+                    }
+                    self.restart_interval = read_u16_be(body, 0) as usize;
+                }
+                0xDA => {
+                    self.scan()?;
+                    scans += 1;
+                }
+                0xD9 => break,
+                0xEE => {
+                    let body = self.segment()?;
+                    if body.len() >= 12 && body.starts_with(b"Adobe") {
+                        self.adobe_transform = Some(body[11]);
+                    }
+                }
+                // Stray restart markers and SOI carry no segment
+                0xD0..=0xD8 | 0x01 => {}
+                _ => {
+                    self.segment()?;
+                }
+            }
+        }
+        if scans == 0 {
+            return Err(String::from("no image data"));
+        }
+        self.finish()
+    }
+
+    fn define_quant(&mut self) -> Result<(), String> {
+        let body = self.segment()?;
+        let mut i = 0;
+        while i < body.len() {
+            let precision = body[i] >> 4;
+            let id = (body[i] & 0x0F) as usize;
+            if id > 3 || precision > 1 {
+                return Err(String::from("bad DQT segment"));
+            }
+            let size = if precision == 0 { 64 } else { 128 };
+            let values = body
+                .get(i + 1..i + 1 + size)
+                .ok_or_else(|| String::from("truncated DQT segment"))?;
+            for (k, &natural) in ZIGZAG.iter().enumerate() {
+                self.quant[id][natural] = if precision == 0 {
+                    values[k] as u16
+                } else {
+                    read_u16_be(values, k * 2)
+                };
+            }
+            i += 1 + size;
+        }
+        Ok(())
+    }
+
+    fn define_huffman(&mut self) -> Result<(), String> {
+        let body = self.segment()?;
+        let mut i = 0;
+        while i < body.len() {
+            let class = body[i] >> 4;
+            let id = (body[i] & 0x0F) as usize;
+            if class > 1 || id > 3 {
+                return Err(String::from("bad DHT segment"));
+            }
+            let counts: [u8; 16] = body
+                .get(i + 1..i + 17)
+                .ok_or_else(|| String::from("truncated DHT segment"))?
+                .try_into()
+                .unwrap();
+            let total: usize = counts.iter().map(|&c| c as usize).sum();
+            let symbols = body
+                .get(i + 17..i + 17 + total)
+                .ok_or_else(|| String::from("truncated DHT segment"))?;
+            let table = HuffTable::new(&counts, symbols)?;
+            if class == 0 {
+                self.dc_tables[id] = Some(table);
+            } else {
+                self.ac_tables[id] = Some(table);
+            }
+            i += 17 + total;
+        }
+        Ok(())
+    }
+
+    fn start_frame(&mut self, progressive: bool) -> Result<(), String> {
+        if self.frame.is_some() {
+            return Err(String::from("more than one frame"));
+        }
+        let body = self.segment()?;
+        if body.len() < 6 {
+            return Err(String::from("bad SOF segment"));
+        }
+        if body[0] != 8 {
+            return Err(format!("unsupported sample precision {}", body[0]));
+        }
+        let height = read_u16_be(body, 1) as usize;
+        let width = read_u16_be(body, 3) as usize;
+        if width == 0 || height == 0 {
+            return Err(String::from("missing image size"));
+        }
+        if width > MAX_DIMENSION as usize || height > MAX_DIMENSION as usize {
+            return Err(format!("unsupported image size {width}x{height}"));
+        }
+        let count = body[5] as usize;
+        if !matches!(count, 1 | 3 | 4) || body.len() < 6 + count * 3 {
+            return Err(String::from("bad SOF component count"));
+        }
+
+        let mut components = Vec::with_capacity(count);
+        for c in body[6..6 + count * 3].chunks_exact(3) {
+            let (h, v, quant) = ((c[1] >> 4) as usize, (c[1] & 0x0F) as usize, c[2] as usize);
+            if !(1..=4).contains(&h) || !(1..=4).contains(&v) || quant > 3 {
+                return Err(String::from("bad SOF component"));
+            }
+            components.push(Component {
+                id: c[0],
+                h,
+                v,
+                quant,
+                blocks_w: 0,
+                blocks_h: 0,
+                coefs: Vec::new(),
+                plane: Vec::new(),
+                dc_pred: 0,
+                dc_table: 0,
+                ac_table: 0,
+            });
+        }
+
+        let hmax = components.iter().map(|c| c.h).max().unwrap();
+        let vmax = components.iter().map(|c| c.v).max().unwrap();
+        if components
+            .iter()
+            .any(|c| hmax % c.h != 0 || vmax % c.v != 0)
+        {
+            return Err(String::from("unsupported sampling factors"));
+        }
+        let mcus_x = width.div_ceil(8 * hmax);
+        let mcus_y = height.div_ceil(8 * vmax);
+        for c in components.iter_mut() {
+            c.blocks_w = mcus_x * c.h;
+            c.blocks_h = mcus_y * c.v;
+            c.plane = vec![0; c.blocks_w * c.blocks_h * 64];
+            if progressive {
+                c.coefs = vec![0; c.blocks_w * c.blocks_h * 64];
+            }
+        }
+        self.frame = Some(Frame {
+            width,
+            height,
+            progressive,
+            components,
+            hmax,
+            vmax,
+            mcus_x,
+            mcus_y,
+        });
+        Ok(())
+    }
+
+    fn scan(&mut self) -> Result<(), String> {
+        let body = self.segment()?;
+        let frame = self
+            .frame
+            .as_mut()
+            .ok_or_else(|| String::from("scan before frame"))?;
+        let count = *body
+            .first()
+            .ok_or_else(|| String::from("bad SOS segment"))? as usize;
+        if !(1..=4).contains(&count) || body.len() < 4 + count * 2 {
+            return Err(String::from("bad SOS segment"));
+        }
+        let mut components = Vec::with_capacity(count);
+        for c in body[1..1 + count * 2].chunks_exact(2) {
+            let index = frame
+                .components
+                .iter()
+                .position(|comp| comp.id == c[0])
+                .ok_or_else(|| format!("scan names unknown component {}", c[0]))?;
+            let (dc, ac) = ((c[1] >> 4) as usize, (c[1] & 0x0F) as usize);
+            if dc > 3 || ac > 3 {
+                return Err(String::from("bad SOS table selector"));
+            }
+            frame.components[index].dc_table = dc;
+            frame.components[index].ac_table = ac;
+            components.push(index);
+        }
+        let tail = &body[1 + count * 2..];
+        let scan = if frame.progressive {
+            let scan = Scan {
+                components,
+                start: tail[0] as usize,
+                end: tail[1] as usize,
+                high: (tail[2] >> 4) as u32,
+                low: (tail[2] & 0x0F) as u32,
+            };
+            let dc_scan = scan.start == 0;
+            if scan.end > 63
+                || scan.start > scan.end
+                || (dc_scan && scan.end != 0)
+                || (!dc_scan && count != 1)
+                || scan.low > 13
+            {
+                return Err(String::from("bad progressive scan parameters"));
+            }
+            scan
+        } else {
+            Scan {
+                components,
+                start: 0,
+                end: 63,
+                high: 0,
+                low: 0,
+            }
+        };
+
+        let mut reader = BitReader::new(self.data, self.pos);
+        self.decode_scan(&scan, &mut reader)?;
+        self.pos = reader.marker_position();
+        Ok(())
+    }
+
+    fn decode_scan(&mut self, scan: &Scan, reader: &mut BitReader) -> Result<(), String> {
+        // Split the borrow: tables are read while the frame is written
+        let frame = self.frame.as_mut().unwrap();
+        let quant = &self.quant;
+        let dc_tables = &self.dc_tables;
+        let ac_tables = &self.ac_tables;
+        let eobrun = &mut self.eobrun;
+
+        for &index in &scan.components {
+            let c = &frame.components[index];
+            let needs_dc = scan.start == 0 && scan.high == 0;
+            if needs_dc && dc_tables[c.dc_table].is_none() {
+                return Err(String::from("scan uses an undefined DC table"));
+            }
+            if scan.end > 0 && ac_tables[c.ac_table].is_none() {
+                return Err(String::from("scan uses an undefined AC table"));
+            }
+        }
+        for c in frame.components.iter_mut() {
+            c.dc_pred = 0;
+        }
+        *eobrun = 0;
+
+        // A single-component scan covers just that component's blocks,
+        // without MCU padding; otherwise it is a sequence of MCUs
+        let (units_x, units_y) = if scan.components.len() == 1 {
+            let c = &frame.components[scan.components[0]];
+            let w = (frame.width * c.h).div_ceil(frame.hmax);
+            let h = (frame.height * c.v).div_ceil(frame.vmax);
+            (w.div_ceil(8), h.div_ceil(8))
+        } else {
+            (frame.mcus_x, frame.mcus_y)
+        };
+        let total = units_x * units_y;
+        let progressive = frame.progressive;
+
+        let decode_block =
+            |c: &mut Component, bx: usize, by: usize, reader: &mut BitReader, eobrun: &mut u32| {
+                let dc = dc_tables[c.dc_table].as_ref();
+                let ac = ac_tables[c.ac_table].as_ref();
+                if progressive {
+                    let offset = (by * c.blocks_w + bx) * 64;
+                    let coefs = &mut c.coefs[offset..offset + 64];
+                    if scan.start == 0 {
+                        decode_dc_progressive(reader, coefs, dc, &mut c.dc_pred, scan)
+                    } else if scan.high == 0 {
+                        decode_ac_first(reader, coefs, ac.unwrap(), eobrun, scan)
+                    } else {
+                        decode_ac_refine(reader, coefs, ac.unwrap(), eobrun, scan)
+                    }
+                } else {
+                    let mut block = [0i32; 64];
+                    decode_baseline(
+                        reader,
+                        &mut block,
+                        dc.unwrap(),
+                        ac.unwrap(),
+                        &mut c.dc_pred,
+                        &quant[c.quant],
+                    )?;
+                    let stride = c.blocks_w * 8;
+                    let offset = by * 8 * stride + bx * 8;
+                    idct_block(&block, &mut c.plane[offset..], stride);
+                    Ok(())
+                }
+            };
+
+        for unit in 0..total {
+            let (ux, uy) = (unit % units_x, unit / units_x);
+            if scan.components.len() == 1 {
+                let c = &mut frame.components[scan.components[0]];
+                decode_block(c, ux, uy, reader, eobrun)?;
+            } else {
+                for &index in &scan.components {
+                    let c = &mut frame.components[index];
+                    for v in 0..c.v {
+                        for h in 0..c.h {
+                            let (bx, by) = (ux * c.h + h, uy * c.v + v);
+                            decode_block(c, bx, by, reader, eobrun)?;
+                        }
+                    }
+                }
+            }
+
+            let done = unit + 1;
+            if self.restart_interval > 0 && done % self.restart_interval == 0 && done < total {
+                reader.restart()?;
+                for c in frame.components.iter_mut() {
+                    c.dc_pred = 0;
+                }
+                *eobrun = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Transform progressive coefficients, upsample chroma and convert to RGBA
+    fn finish(self) -> Result<Image, String> {
+        let mut frame = self.frame.ok_or_else(|| String::from("no frame"))?;
+        if frame.progressive {
+            for c in frame.components.iter_mut() {
+                let quant = &self.quant[c.quant];
+                let stride = c.blocks_w * 8;
+                for by in 0..c.blocks_h {
+                    for bx in 0..c.blocks_w {
+                        let offset = (by * c.blocks_w + bx) * 64;
+                        let mut block = [0i32; 64];
+                        for (i, value) in block.iter_mut().enumerate() {
+                            *value = c.coefs[offset + i] as i32 * quant[i] as i32;
+                        }
+                        idct_block(&block, &mut c.plane[by * 8 * stride + bx * 8..], stride);
+                    }
+                }
+            }
+        }
+
+        let (width, height) = (frame.width, frame.height);
+        let planes: Vec<Vec<u8>> = frame
+            .components
+            .iter()
+            .map(|c| upsample(c, &frame))
+            .collect();
+
+        let mut image = Image::new(width as u32, height as u32);
+        let ids: Vec<u8> = frame.components.iter().map(|c| c.id).collect();
+        for (i, px) in image.rgba.chunks_exact_mut(4).enumerate() {
+            let rgb = match planes.len() {
+                1 => [planes[0][i]; 3],
+                3 if self.adobe_transform == Some(0) || ids == b"RGB" => {
+                    [planes[0][i], planes[1][i], planes[2][i]]
+                }
+                3 => ycc_to_rgb(planes[0][i], planes[1][i], planes[2][i]),
+                _ => {
+                    let k = planes[3][i];
+                    let cmy = if self.adobe_transform == Some(2) {
+                        let [r, g, b] = ycc_to_rgb(planes[0][i], planes[1][i], planes[2][i]);
+                        [255 - r, 255 - g, 255 - b]
+                    } else {
+                        [planes[0][i], planes[1][i], planes[2][i]]
+                    };
+                    // Adobe stores CMYK inverted, so each channel times K
+                    // gives the RGB intensity
+                    cmy.map(|c| ((c as u32 * k as u32 + 127) / 255) as u8)
+                }
+            };
+            px.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+        }
+        Ok(image)
+    }
+}
+
+fn decode_baseline(
+    reader: &mut BitReader,
+    block: &mut [i32; 64],
+    dc: &HuffTable,
+    ac: &HuffTable,
+    dc_pred: &mut i32,
+    quant: &[u16; 64],
+) -> Result<(), String> {
+    let size = reader.decode(dc)? as u32;
+    if size > 11 {
+        return Err(String::from("bad DC coefficient"));
+    }
+    *dc_pred += reader.receive_extend(size);
+    block[0] = *dc_pred * quant[0] as i32;
+
+    let mut k = 1;
+    while k < 64 {
+        let rs = reader.decode(ac)?;
+        let (run, size) = ((rs >> 4) as usize, (rs & 0x0F) as u32);
+        if size == 0 {
+            if run != 15 {
+                break;
+            }
+            k += 16;
+            continue;
+        }
+        k += run;
+        if k > 63 {
+            return Err(String::from("bad AC coefficient run"));
+        }
+        let natural = ZIGZAG[k];
+        block[natural] = reader.receive_extend(size) * quant[natural] as i32;
+        k += 1;
+    }
+    Ok(())
+}
+
+fn decode_dc_progressive(
+    reader: &mut BitReader,
+    coefs: &mut [i16],
+    dc: Option<&HuffTable>,
+    dc_pred: &mut i32,
+    scan: &Scan,
+) -> Result<(), String> {
+    if scan.high == 0 {
+        // Presence of the table was checked before the scan
+        let size = reader.decode(dc.unwrap())? as u32;
+        if size > 11 {
+            return Err(String::from("bad DC coefficient"));
+        }
+        *dc_pred += reader.receive_extend(size);
+        coefs[0] = (*dc_pred << scan.low) as i16;
+    } else if reader.bit() {
+        coefs[0] |= 1 << scan.low;
+    }
+    Ok(())
+}
+
+fn decode_ac_first(
+    reader: &mut BitReader,
+    coefs: &mut [i16],
+    ac: &HuffTable,
+    eobrun: &mut u32,
+    scan: &Scan,
+) -> Result<(), String> {
+    if *eobrun > 0 {
+        *eobrun -= 1;
+        return Ok(());
+    }
+    let mut k = scan.start;
+    while k <= scan.end {
+        let rs = reader.decode(ac)?;
+        let (run, size) = ((rs >> 4) as u32, (rs & 0x0F) as u32);
+        if size == 0 {
+            if run < 15 {
+                *eobrun = (1 << run) - 1 + reader.bits(run);
+                break;
+            }
+            k += 16;
+            continue;
+        }
+        k += run as usize;
+        if k > 63 {
+            return Err(String::from("bad AC coefficient run"));
+        }
+        coefs[ZIGZAG[k]] = (reader.receive_extend(size) * (1 << scan.low)) as i16;
+        k += 1;
+    }
+    Ok(())
+}
+
+fn decode_ac_refine(
+    reader: &mut BitReader,
+    coefs: &mut [i16],
+    ac: &HuffTable,
+    eobrun: &mut u32,
+    scan: &Scan,
+) -> Result<(), String> {
+    let bit = 1i16 << scan.low;
+    // Nonzero coefficients get a correction bit in every refinement scan
+    let refine = |reader: &mut BitReader, coef: &mut i16| {
+        if reader.bit() && *coef & bit == 0 {
+            if *coef > 0 {
+                *coef += bit;
+            } else {
+                *coef -= bit;
+            }
+        }
+    };
+
+    let mut k = scan.start;
+    if *eobrun == 0 {
+        while k <= scan.end {
+            let rs = reader.decode(ac)?;
+            let (mut run, size) = ((rs >> 4) as i32, (rs & 0x0F) as u32);
+            let mut value = 0i16;
+            if size == 0 {
+                if run < 15 {
+                    *eobrun = (1 << run) + reader.bits(run as u32);
+                    break;
+                }
+                // ZRL: skip 16 zero coefficients
+            } else {
+                if size != 1 {
+                    return Err(String::from("bad refinement coefficient"));
+                }
+                value = if reader.bit() { bit } else { -bit };
+            }
+
+            // Skip `run` zero coefficients, refining nonzero ones on the
+            // way, then place the new coefficient
+            while k <= scan.end {
+                let coef = &mut coefs[ZIGZAG[k]];
+                k += 1;
+                if *coef != 0 {
+                    refine(reader, coef);
+                } else {
+                    if run == 0 {
+                        *coef = value;
+                        break;
+                    }
+                    run -= 1;
+                }
+            }
+        }
+    }
+
+    if *eobrun > 0 {
+        // Refine the rest of the band
+        while k <= scan.end {
+            let coef = &mut coefs[ZIGZAG[k]];
+            if *coef != 0 {
+                refine(reader, coef);
+            }
+            k += 1;
+        }
+        *eobrun -= 1;
+    }
+    Ok(())
+}
+
+/// Fixed-point multiply constants (scaled by 4096) for the inverse DCT
+mod idct {
+    pub const C0_541: i32 = 2217;
+    pub const C1_847: i32 = -7567;
+    pub const C0_765: i32 = 3135;
+    pub const C1_175: i32 = 4816;
+    pub const C0_298: i32 = 1223;
+    pub const C2_053: i32 = 8410;
+    pub const C3_072: i32 = 12586;
+    pub const C1_501: i32 = 6149;
+    pub const C0_899: i32 = -3685;
+    pub const C2_562: i32 = -10497;
+    pub const C1_961: i32 = -8034;
+    pub const C0_390: i32 = -1597;
+}
+
+/// One-dimensional inverse DCT of eight inputs, as the even part
+/// (x0..x3) and odd part (t0..t3) of the output butterflies
+fn idct_1d(s: [i32; 8]) -> ([i32; 4], [i32; 4]) {
+    use idct::*;
+    let p1 = (s[2] + s[6]) * C0_541;
+    let t2 = p1 + s[6] * C1_847;
+    let t3 = p1 + s[2] * C0_765;
+    let t0 = (s[0] + s[4]) * 4096;
+    let t1 = (s[0] - s[4]) * 4096;
+    let x = [t0 + t3, t1 + t2, t1 - t2, t0 - t3];
+
+    let (t0, t1, t2, t3) = (s[7], s[5], s[3], s[1]);
+    let p3 = t0 + t2;
+    let p4 = t1 + t3;
+    let p1 = t0 + t3;
+    let p2 = t1 + t2;
+    let p5 = (p3 + p4) * C1_175;
+    let p1 = p5 + p1 * C0_899;
+    let p2 = p5 + p2 * C2_562;
+    let p3 = p3 * C1_961;
+    let p4 = p4 * C0_390;
+    let t = [
+        t0 * C0_298 + p1 + p3,
+        t1 * C2_053 + p2 + p4,
+        t2 * C3_072 + p2 + p3,
+        t3 * C1_501 + p1 + p4,
+    ];
+    (x, t)
+}
+
+/// Inverse DCT of dequantized coefficients (natural order) into an 8x8
+/// block of samples at the start of `out`, `stride` bytes per row
+fn idct_block(block: &[i32; 64], out: &mut [u8], stride: usize) {
+    let mut tmp = [0i32; 64];
+    for col in 0..8 {
+        let s: [i32; 8] = core::array::from_fn(|row| block[row * 8 + col]);
+        if s[1..].iter().all(|&v| v == 0) {
+            // Flat column: every output is the DC term
+            for row in 0..8 {
+                tmp[row * 8 + col] = s[0] * 4;
+            }
+            continue;
+        }
+        let (x, t) = idct_1d(s);
+        // Scaled up by 4096; keep 2 extra bits for the second pass
+        let x = x.map(|v| v + 512);
+        for i in 0..4 {
+            tmp[i * 8 + col] = (x[i] + t[3 - i]) >> 10;
+            tmp[(7 - i) * 8 + col] = (x[i] - t[3 - i]) >> 10;
+        }
+    }
+    for row in 0..8 {
+        let s: [i32; 8] = tmp[row * 8..row * 8 + 8].try_into().unwrap();
+        let (x, t) = idct_1d(s);
+        // Remove the 4096 and 4 scales plus 8 from the two passes, round,
+        // and level-shift by 128
+        let x = x.map(|v| v + (1 << 16) + (128 << 17));
+        let line = &mut out[row * stride..row * stride + 8];
+        for i in 0..4 {
+            line[i] = ((x[i] + t[3 - i]) >> 17).clamp(0, 255) as u8;
+            line[7 - i] = ((x[i] - t[3 - i]) >> 17).clamp(0, 255) as u8;
+        }
+    }
+}
+
+/// Component samples at full image resolution, cropped to the image.
+///
+/// Subsampled components are interpolated linearly between sample centers.
+fn upsample(c: &Component, frame: &Frame) -> Vec<u8> {
+    let (width, height) = (frame.width, frame.height);
+    let stride = c.blocks_w * 8;
+    let (fx, fy) = (frame.hmax / c.h, frame.vmax / c.v);
+    if fx == 1 && fy == 1 {
+        let mut out = Vec::with_capacity(width * height);
+        for y in 0..height {
+            out.extend_from_slice(&c.plane[y * stride..y * stride + width]);
+        }
+        return out;
+    }
+
+    let src_w = (width * c.h).div_ceil(frame.hmax);
+    let src_h = (height * c.v).div_ceil(frame.vmax);
+    // Source sample and 8-bit fraction toward the next for each output
+    // coordinate: output x sits at (x + 0.5) / f - 0.5 in source samples
+    let taps = |out_len: usize, f: usize, src_len: usize| -> Vec<(usize, usize, u32)> {
+        (0..out_len)
+            .map(|x| {
+                let pos = ((2 * x + 1) as i32 - f as i32) * 128 / f as i32;
+                let pos = pos.max(0) as usize;
+                let i0 = (pos >> 8).min(src_len - 1);
+                let i1 = (i0 + 1).min(src_len - 1);
+                (i0, i1, (pos & 0xFF) as u32)
+            })
+            .collect()
+    };
+    let xs = taps(width, fx, src_w);
+    let ys = taps(height, fy, src_h);
+
+    let mut out = Vec::with_capacity(width * height);
+    for &(y0, y1, wy) in &ys {
+        let (row0, row1) = (&c.plane[y0 * stride..], &c.plane[y1 * stride..]);
+        for &(x0, x1, wx) in &xs {
+            let top = row0[x0] as u32 * (256 - wx) + row0[x1] as u32 * wx;
+            let bottom = row1[x0] as u32 * (256 - wx) + row1[x1] as u32 * wx;
+            out.push(((top * (256 - wy) + bottom * wy + (1 << 15)) >> 16) as u8);
+        }
+    }
+    out
+}
+
+/// JFIF YCbCr to RGB
+fn ycc_to_rgb(y: u8, cb: u8, cr: u8) -> [u8; 3] {
+    let y = (y as i32) << 16;
+    let cb = cb as i32 - 128;
+    let cr = cr as i32 - 128;
+    let round = 1 << 15;
+    let r = (y + 91881 * cr + round) >> 16;
+    let g = (y - 22554 * cb - 46802 * cr + round) >> 16;
+    let b = (y + 116130 * cb + round) >> 16;
+    [r, g, b].map(|v| v.clamp(0, 255) as u8)
+}
+
+/// Decode a JPEG file to RGBA (always opaque).
+pub fn decode_jpeg(data: &[u8]) -> Result<Image, String> {
+    Decoder::new(data).decode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Largest per-channel difference between `image` and the gradient
+    /// the reference files were encoded from, optionally as luma
+    fn gradient_error(image: &Image, gray: bool) -> i32 {
+        let (w, h) = (image.width, image.height);
+        let mut worst = 0;
+        for y in 0..h {
+            for x in 0..w {
+                let rgb = [
+                    x * 255 / (w - 1),
+                    y * 255 / (h - 1),
+                    (x + y) * 255 / (w + h - 2),
+                ];
+                let rgb = rgb.map(|v| v as i32);
+                let luma = (299 * rgb[0] + 587 * rgb[1] + 114 * rgb[2]) / 1000;
+                let pixel = image.pixel(x, y);
+                assert_eq!(pixel[3], 255);
+                for c in 0..3 {
+                    let expected = if gray { luma } else { rgb[c] };
+                    worst = worst.max((pixel[c] as i32 - expected).abs());
+                }
+            }
+        }
+        worst
+    }
+
+    #[test]
+    fn baseline_and_progressive_agree() {
+        let baseline = decode_jpeg(BASELINE).unwrap();
+        let progressive = decode_jpeg(PROGRESSIVE).unwrap();
+        assert_eq!((baseline.width, baseline.height), (27, 19));
+        assert_eq!((progressive.width, progressive.height), (27, 19));
+        // Same quantized coefficients, so the same pixels
+        assert!(baseline.rgba == progressive.rgba);
+        let error = gradient_error(&baseline, false);
+        assert!(error <= 12, "{}", error);
+    }
+
+    #[test]
+    fn decodes_grayscale() {
+        let image = decode_jpeg(GRAY).unwrap();
+        assert_eq!((image.width, image.height), (27, 19));
+        for p in image.rgba.chunks_exact(4) {
+            assert!(p[0] == p[1] && p[1] == p[2]);
+        }
+        let error = gradient_error(&image, true);
+        assert!(error <= 4, "{}", error);
+    }
+
+    #[test]
+    fn flat_blocks_transform_to_their_mean() {
+        let mut out = [0u8; 64];
+        for (dc, expected) in [(0, 128), (80, 138), (-1024, 0), (2000, 255)] {
+            let mut block = [0i32; 64];
+            block[0] = dc;
+            idct_block(&block, &mut out, 8);
+            assert!(out.iter().all(|&v| v == expected), "{dc} {out:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_corrupt_files() {
+        assert!(decode_jpeg(&BASELINE[..200]).is_err());
+        assert!(decode_jpeg(&BASELINE[2..]).is_err());
+        assert!(decode_jpeg(&[0xFF, 0xD8, 0xFF, 0xD9]).is_err());
+        // Lossless frame in place of the baseline one
+        let mut lossless = BASELINE.to_vec();
+        let sof = lossless.windows(2).position(|m| m == [0xFF, 0xC0]).unwrap();
+        lossless[sof + 1] = 0xC3;
+        assert!(decode_jpeg(&lossless).is_err());
+    }
+
+    // 27x19 gradient (red across, green down, blue diagonal) written by a
+    // small reference encoder: baseline 4:2:0 with the Annex K tables and a
+    // restart every MCU; the same coefficients as a progressive file with
+    // spectral selection, successive approximation and a restart every two
+    // MCUs; and its luma alone as a grayscale baseline file.
+    const BASELINE: &[u8] = &[
+        0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0x4A, 0x46, 0x49, 0x46, 0x00, 0x01, 0x01, 0x00, 0x00,
+        0x01, 0x00, 0x01, 0x00, 0x00, 0xFF, 0xDB, 0x00, 0x43, 0x00, 0x03, 0x02, 0x02, 0x03, 0x02,
+        0x02, 0x03, 0x03, 0x03, 0x03, 0x04, 0x03, 0x03, 0x04, 0x05, 0x08, 0x05, 0x05, 0x04, 0x04,
+        0x05, 0x0A, 0x07, 0x07, 0x06, 0x08, 0x0C, 0x0A, 0x0C, 0x0C, 0x0B, 0x0A, 0x0B, 0x0B, 0x0D,
+        0x0E, 0x12, 0x10, 0x0D, 0x0E, 0x11, 0x0E, 0x0B, 0x0B, 0x10, 0x16, 0x10, 0x11, 0x13, 0x14,
+        0x15, 0x15, 0x15, 0x0C, 0x0F, 0x17, 0x18, 0x16, 0x14, 0x18, 0x12, 0x14, 0x15, 0x14, 0xFF,
+        0xDB, 0x00, 0x43, 0x01, 0x03, 0x04, 0x04, 0x05, 0x04, 0x05, 0x09, 0x05, 0x05, 0x09, 0x14,
+        0x0D, 0x0B, 0x0D, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14,
+        0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14,
+        0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14,
+        0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x00, 0x13,
+        0x00, 0x1B, 0x03, 0x01, 0x22, 0x00, 0x02, 0x11, 0x01, 0x03, 0x11, 0x01, 0xFF, 0xC4, 0x00,
+        0x1F, 0x00, 0x00, 0x01, 0x05, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B,
+        0xFF, 0xC4, 0x00, 0xB5, 0x10, 0x00, 0x02, 0x01, 0x03, 0x03, 0x02, 0x04, 0x03, 0x05, 0x05,
+        0x04, 0x04, 0x00, 0x00, 0x01, 0x7D, 0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21,
+        0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07, 0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xA1, 0x08,
+        0x23, 0x42, 0xB1, 0xC1, 0x15, 0x52, 0xD1, 0xF0, 0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0A,
+        0x16, 0x17, 0x18, 0x19, 0x1A, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2A, 0x34, 0x35, 0x36, 0x37,
+        0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4A, 0x53, 0x54, 0x55, 0x56,
+        0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x73, 0x74, 0x75,
+        0x76, 0x77, 0x78, 0x79, 0x7A, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8A, 0x92, 0x93,
+        0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9,
+        0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6,
+        0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xE1, 0xE2,
+        0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7,
+        0xF8, 0xF9, 0xFA, 0xFF, 0xC4, 0x00, 0x1F, 0x01, 0x00, 0x03, 0x01, 0x01, 0x01, 0x01, 0x01,
+        0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05,
+        0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0xFF, 0xDD, 0x00, 0x04, 0x00, 0x01, 0xFF, 0xDA, 0x00,
+        0x0C, 0x03, 0x01, 0x00, 0x02, 0x10, 0x03, 0x10, 0x00, 0x3F, 0x00, 0xF8, 0x7B, 0xC3, 0xDF,
+        0x0B, 0x7E, 0xEF, 0xEE, 0x7F, 0x4A, 0xF4, 0xDF, 0x0F, 0x7C, 0x2D, 0xFB, 0xBF, 0xB9, 0xFD,
+        0x2B, 0xDC, 0xBC, 0x3D, 0xF0, 0xB7, 0xEE, 0xFE, 0xEB, 0xF4, 0xAF, 0x4D, 0xF0, 0xF7, 0xC2,
+        0xDF, 0xBB, 0xFB, 0xAF, 0xD2, 0xB9, 0x2D, 0x35, 0x0E, 0x9C, 0xD7, 0xCC, 0x78, 0x5F, 0xC7,
+        0x5F, 0x77, 0xE7, 0xAF, 0xFF, 0xD0, 0xF3, 0x7F, 0x0F, 0x7C, 0x2D, 0xFB, 0xBF, 0xB9, 0xFD,
+        0x2B, 0xBB, 0x83, 0xE1, 0x6F, 0xEE, 0x53, 0xF7, 0x3D, 0xBD, 0x2B, 0xE8, 0x4F, 0x0F, 0x7C,
+        0x2D, 0xFB, 0xBF, 0xBA, 0xFD, 0x2B, 0xBC, 0x83, 0xE1, 0x6F, 0xEE, 0x53, 0xF7, 0x5D, 0xBD,
+        0x2B, 0xE9, 0xED, 0xF5, 0x0F, 0x97, 0xAD, 0x7F, 0x45, 0xE8, 0x3E, 0x3A, 0xFD, 0xCF, 0xDF,
+        0xAF, 0xFF, 0xD1, 0xF7, 0x1F, 0x0F, 0x69, 0xB6, 0xBF, 0x2F, 0xEE, 0x56, 0xBD, 0x37, 0xC3,
+        0xDA, 0x6D, 0xAF, 0xCB, 0xFB, 0x95, 0xAF, 0x3C, 0xF0, 0xF7, 0xF0, 0xD7, 0xA6, 0x78, 0x7B,
+        0xF8, 0x6B, 0xE0, 0xAD, 0x09, 0xAF, 0xE2, 0x7F, 0x0B, 0xBB, 0xFC, 0xBC, 0xD7, 0xFF, 0xD2,
+        0xFD, 0x0A, 0xF0, 0xF6, 0x9B, 0x6B, 0xF2, 0xFE, 0xE5, 0x6B, 0xBC, 0x83, 0x4D, 0xB5, 0xF2,
+        0x53, 0xF7, 0x2B, 0xD2, 0xB8, 0xDF, 0x0F, 0x7F, 0x0D, 0x77, 0x70, 0x7F, 0xA9, 0x4F, 0xA5,
+        0x72, 0x5B, 0x93, 0xB6, 0xBE, 0x67, 0x41, 0x77, 0xF2, 0x7A, 0xD7, 0xFF, 0xD9,
+    ];
+
+    const PROGRESSIVE: &[u8] = &[
+        0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0x4A, 0x46, 0x49, 0x46, 0x00, 0x01, 0x01, 0x00, 0x00,
+        0x01, 0x00, 0x01, 0x00, 0x00, 0xFF, 0xDB, 0x00, 0x43, 0x00, 0x03, 0x02, 0x02, 0x03, 0x02,
+        0x02, 0x03, 0x03, 0x03, 0x03, 0x04, 0x03, 0x03, 0x04, 0x05, 0x08, 0x05, 0x05, 0x04, 0x04,
+        0x05, 0x0A, 0x07, 0x07, 0x06, 0x08, 0x0C, 0x0A, 0x0C, 0x0C, 0x0B, 0x0A, 0x0B, 0x0B, 0x0D,
+        0x0E, 0x12, 0x10, 0x0D, 0x0E, 0x11, 0x0E, 0x0B, 0x0B, 0x10, 0x16, 0x10, 0x11, 0x13, 0x14,
+        0x15, 0x15, 0x15, 0x0C, 0x0F, 0x17, 0x18, 0x16, 0x14, 0x18, 0x12, 0x14, 0x15, 0x14, 0xFF,
+        0xDB, 0x00, 0x43, 0x01, 0x03, 0x04, 0x04, 0x05, 0x04, 0x05, 0x09, 0x05, 0x05, 0x09, 0x14,
+        0x0D, 0x0B, 0x0D, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14,
+        0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14,
+        0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14,
+        0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0x14, 0xFF, 0xC2, 0x00, 0x11, 0x08, 0x00, 0x13,
+        0x00, 0x1B, 0x03, 0x01, 0x22, 0x00, 0x02, 0x11, 0x01, 0x03, 0x11, 0x01, 0xFF, 0xC4, 0x01,
+        0x13, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFE, 0x02, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B,
+        0x0C, 0x0D, 0x0E, 0x0F, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A,
+        0x1B, 0x1C, 0x1D, 0x1E, 0x1F, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29,
+        0x2A, 0x2B, 0x2C, 0x2D, 0x2E, 0x2F, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38,
+        0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E, 0x3F, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47,
+        0x48, 0x49, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E, 0x4F, 0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56,
+        0x57, 0x58, 0x59, 0x5A, 0x5B, 0x5C, 0x5D, 0x5E, 0x5F, 0x60, 0x61, 0x62, 0x63, 0x64, 0x65,
+        0x66, 0x67, 0x68, 0x69, 0x6A, 0x6B, 0x6C, 0x6D, 0x6E, 0x6F, 0x70, 0x71, 0x72, 0x73, 0x74,
+        0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x7B, 0x7C, 0x7D, 0x7E, 0x7F, 0x80, 0x81, 0x82, 0x83,
+        0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8A, 0x8B, 0x8C, 0x8D, 0x8E, 0x8F, 0x90, 0x91, 0x92,
+        0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0x9B, 0x9C, 0x9D, 0x9E, 0x9F, 0xA0, 0xA1,
+        0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xAB, 0xAC, 0xAD, 0xAE, 0xAF, 0xB0,
+        0xB1, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xBB, 0xBC, 0xBD, 0xBE, 0xBF,
+        0xC0, 0xC1, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xCB, 0xCC, 0xCD, 0xCE,
+        0xCF, 0xD0, 0xD1, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xDB, 0xDC, 0xDD,
+        0xDE, 0xDF, 0xE0, 0xE1, 0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xEB, 0xEC,
+        0xED, 0xEE, 0xEF, 0xF0, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8, 0xF9, 0xFA, 0xFB,
+        0xFC, 0xFD, 0xFE, 0xFF, 0xFF, 0xC4, 0x01, 0x13, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0xFE, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04,
+        0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F, 0x10, 0x11, 0x12, 0x13,
+        0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x1C, 0x1D, 0x1E, 0x1F, 0x20, 0x21, 0x22,
+        0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2A, 0x2B, 0x2C, 0x2D, 0x2E, 0x2F, 0x30, 0x31,
+        0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E, 0x3F, 0x40,
+        0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E, 0x4F,
+        0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x5B, 0x5C, 0x5D, 0x5E,
+        0x5F, 0x60, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x6B, 0x6C, 0x6D,
+        0x6E, 0x6F, 0x70, 0x71, 0x72, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x7B, 0x7C,
+        0x7D, 0x7E, 0x7F, 0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8A, 0x8B,
+        0x8C, 0x8D, 0x8E, 0x8F, 0x90, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A,
+        0x9B, 0x9C, 0x9D, 0x9E, 0x9F, 0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9,
+        0xAA, 0xAB, 0xAC, 0xAD, 0xAE, 0xAF, 0xB0, 0xB1, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8,
+        0xB9, 0xBA, 0xBB, 0xBC, 0xBD, 0xBE, 0xBF, 0xC0, 0xC1, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6, 0xC7,
+        0xC8, 0xC9, 0xCA, 0xCB, 0xCC, 0xCD, 0xCE, 0xCF, 0xD0, 0xD1, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6,
+        0xD7, 0xD8, 0xD9, 0xDA, 0xDB, 0xDC, 0xDD, 0xDE, 0xDF, 0xE0, 0xE1, 0xE2, 0xE3, 0xE4, 0xE5,
+        0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xEB, 0xEC, 0xED, 0xEE, 0xEF, 0xF0, 0xF1, 0xF2, 0xF3, 0xF4,
+        0xF5, 0xF6, 0xF7, 0xF8, 0xF9, 0xFA, 0xFB, 0xFC, 0xFD, 0xFE, 0xFF, 0xFF, 0xDD, 0x00, 0x04,
+        0x00, 0x02, 0xFF, 0xDA, 0x00, 0x0C, 0x03, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x00, 0x00,
+        0x01, 0x07, 0x1C, 0x0D, 0x38, 0x37, 0x20, 0xD3, 0x01, 0x90, 0x55, 0x83, 0x0C, 0x0B, 0xD0,
+        0x78, 0x40, 0xBE, 0x05, 0xC8, 0x3D, 0x9F, 0xFF, 0xD0, 0x06, 0xE0, 0x1A, 0x70, 0x67, 0x41,
+        0xA6, 0x05, 0x00, 0x39, 0x30, 0x68, 0x81, 0x7C, 0x0A, 0x60, 0x5E, 0x82, 0xE4, 0x1E, 0xCF,
+        0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x01, 0x05, 0x02, 0x04, 0x70, 0x55, 0x80, 0x02,
+        0x38, 0x2A, 0xC0, 0x3F, 0xFF, 0xD0, 0x04, 0x70, 0x55, 0x80, 0x00, 0x81, 0x56, 0x42, 0xFF,
+        0xD1, 0x04, 0x70, 0x55, 0x80, 0x02, 0x38, 0x2A, 0xC0, 0x3F, 0xFF, 0xD2, 0x04, 0x70, 0x55,
+        0x80, 0x00, 0x81, 0x56, 0x42, 0xFF, 0xD3, 0x04, 0x70, 0x36, 0x04, 0x80, 0x02, 0x38, 0x1B,
+        0x02, 0x40, 0x3F, 0xFF, 0xD4, 0x04, 0x70, 0x36, 0x04, 0x80, 0x00, 0x80, 0xD8, 0x12, 0x22,
+        0xFF, 0xDA, 0x00, 0x08, 0x01, 0x03, 0x00, 0x01, 0x3F, 0x01, 0x05, 0x58, 0x2F, 0x4C, 0x44,
+        0x30, 0x00, 0x40, 0x05, 0xE9, 0x10, 0x63, 0x00, 0xFF, 0xD0, 0x05, 0x58, 0x0C, 0x06, 0x42,
+        0x00, 0x04, 0x00, 0x18, 0x0C, 0x48, 0x00, 0xFF, 0xDA, 0x00, 0x08, 0x01, 0x02, 0x00, 0x01,
+        0x3F, 0x01, 0x03, 0x40, 0x90, 0x00, 0x06, 0xC1, 0x20, 0x84, 0x01, 0xFF, 0xD0, 0x03, 0x40,
+        0x00, 0x6C, 0xC4, 0x01, 0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x06, 0x3F, 0x02, 0x31,
+        0x00, 0x18, 0x80, 0x3F, 0xFF, 0xD0, 0x31, 0x00, 0x18, 0x80, 0x3F, 0xFF, 0xD1, 0x31, 0x00,
+        0x18, 0x80, 0x3F, 0xFF, 0xD2, 0x31, 0x00, 0x18, 0x80, 0x3F, 0xFF, 0xD3, 0x31, 0x00, 0x18,
+        0x80, 0x3F, 0xFF, 0xD4, 0x31, 0x00, 0x18, 0x80, 0x3F, 0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01,
+        0x00, 0x01, 0x3F, 0x21, 0x31, 0x20, 0x13, 0x12, 0x01, 0xFF, 0xD0, 0x31, 0x20, 0x12, 0x17,
+        0x00, 0xFF, 0x00, 0xFF, 0xD1, 0x31, 0x20, 0x03, 0x12, 0x00, 0xFF, 0xD2, 0x31, 0x20, 0x02,
+        0x17, 0x00, 0x7F, 0xFF, 0xD3, 0x21, 0x32, 0x14, 0x00, 0x84, 0xC8, 0x50, 0x0F, 0xFF, 0xD4,
+        0x21, 0x32, 0x14, 0x00, 0x45, 0xE4, 0x28, 0x07, 0xFF, 0xDA, 0x00, 0x0C, 0x03, 0x01, 0x00,
+        0x02, 0x00, 0x03, 0x00, 0x00, 0x00, 0x10, 0x9D, 0xFF, 0x00, 0xFF, 0xD0, 0xB8, 0x6F, 0xFF,
+        0xDA, 0x00, 0x08, 0x01, 0x03, 0x00, 0x01, 0x3F, 0x10, 0x10, 0x08, 0xFF, 0x00, 0xFF, 0xD0,
+        0x41, 0xB0, 0x02, 0x0D, 0x80, 0x3F, 0xFF, 0xDA, 0x00, 0x08, 0x01, 0x02, 0x00, 0x01, 0x3F,
+        0x10, 0x31, 0x44, 0x30, 0x05, 0x18, 0x00, 0xFF, 0xD0, 0x01, 0xC0, 0x60, 0x00, 0x30, 0x0C,
+        0x01, 0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x01, 0x3F, 0x10, 0xF1, 0x40, 0x07, 0x8A,
+        0x00, 0x3F, 0xFF, 0xD0, 0xF1, 0x40, 0x04, 0x0A, 0x4A, 0x20, 0x0F, 0xFF, 0xD1, 0xF1, 0x48,
+        0x07, 0x8A, 0x40, 0x3F, 0xFF, 0xD2, 0xF1, 0x48, 0x04, 0x0A, 0x6A, 0x20, 0x0F, 0xFF, 0xD3,
+        0x10, 0x41, 0x07, 0xFF, 0xD4, 0x00, 0x81, 0x85, 0x10, 0x03, 0xFF, 0xD9,
+    ];
+
+    const GRAY: &[u8] = &[
+        0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0x4A, 0x46, 0x49, 0x46, 0x00, 0x01, 0x01, 0x00, 0x00,
+        0x01, 0x00, 0x01, 0x00, 0x00, 0xFF, 0xDB, 0x00, 0x43, 0x00, 0x03, 0x02, 0x02, 0x03, 0x02,
+        0x02, 0x03, 0x03, 0x03, 0x03, 0x04, 0x03, 0x03, 0x04, 0x05, 0x08, 0x05, 0x05, 0x04, 0x04,
+        0x05, 0x0A, 0x07, 0x07, 0x06, 0x08, 0x0C, 0x0A, 0x0C, 0x0C, 0x0B, 0x0A, 0x0B, 0x0B, 0x0D,
+        0x0E, 0x12, 0x10, 0x0D, 0x0E, 0x11, 0x0E, 0x0B, 0x0B, 0x10, 0x16, 0x10, 0x11, 0x13, 0x14,
+        0x15, 0x15, 0x15, 0x0C, 0x0F, 0x17, 0x18, 0x16, 0x14, 0x18, 0x12, 0x14, 0x15, 0x14, 0xFF,
+        0xC0, 0x00, 0x0B, 0x08, 0x00, 0x13, 0x00, 0x1B, 0x01, 0x01, 0x11, 0x00, 0xFF, 0xC4, 0x00,
+        0x1F, 0x00, 0x00, 0x01, 0x05, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B,
+        0xFF, 0xC4, 0x00, 0xB5, 0x10, 0x00, 0x02, 0x01, 0x03, 0x03, 0x02, 0x04, 0x03, 0x05, 0x05,
+        0x04, 0x04, 0x00, 0x00, 0x01, 0x7D, 0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21,
+        0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07, 0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xA1, 0x08,
+        0x23, 0x42, 0xB1, 0xC1, 0x15, 0x52, 0xD1, 0xF0, 0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0A,
+        0x16, 0x17, 0x18, 0x19, 0x1A, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2A, 0x34, 0x35, 0x36, 0x37,
+        0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4A, 0x53, 0x54, 0x55, 0x56,
+        0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x73, 0x74, 0x75,
+        0x76, 0x77, 0x78, 0x79, 0x7A, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8A, 0x92, 0x93,
+        0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9,
+        0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6,
+        0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xE1, 0xE2,
+        0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7,
+        0xF8, 0xF9, 0xFA, 0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, 0xF8, 0x7B,
+        0xC3, 0xDF, 0x0B, 0x7E, 0xEF, 0xEE, 0x7F, 0x4A, 0xF4, 0xDF, 0x0F, 0x7C, 0x2D, 0xFB, 0xBF,
+        0xB9, 0xFD, 0x2B, 0xD3, 0x3C, 0x3D, 0xF0, 0xB7, 0xEE, 0xFE, 0xE7, 0xF4, 0xAE, 0xEE, 0x0F,
+        0x85, 0xBF, 0xB9, 0x4F, 0xDC, 0xF6, 0xF4, 0xAC, 0xAF, 0x0F, 0x7C, 0x2D, 0xFB, 0xBF, 0xBA,
+        0xFD, 0x2B, 0xD3, 0x7C, 0x3D, 0xF0, 0xB7, 0xEE, 0xFE, 0xEB, 0xF4, 0xAF, 0x4C, 0xF0, 0xF7,
+        0xC2, 0xDF, 0xBB, 0xFB, 0xAF, 0xD2, 0xBB, 0xC8, 0x3E, 0x16, 0xFE, 0xE5, 0x3F, 0x75, 0xDB,
+        0xD2, 0xBC, 0xF7, 0xC3, 0xDA, 0x6D, 0xAF, 0xCB, 0xFB, 0x95, 0xAF, 0x4D, 0xF0, 0xF6, 0x9B,
+        0x6B, 0xF2, 0xFE, 0xE5, 0x6B, 0xD3, 0x3C, 0x3D, 0xA6, 0xDA, 0xFC, 0xBF, 0xB9, 0x5A, 0xEF,
+        0x20, 0xD3, 0x6D, 0x7C, 0x94, 0xFD, 0xCA, 0xF4, 0xAF, 0xFF, 0xD9,
+    ];
+}
@@ -1 +1,43 @@
+//! Image decoding and encoding for Breenix userspace.
+//!
+//! `#![no_std]` + `extern crate alloc`. Zero external dependencies.
+//!
+//! - [`bmp`]: 24-bit uncompressed BMP encode/decode
+//! - [`png`]: PNG encode/decode (all color types and bit depths, Adam7)
+//! - [`jpeg`]: baseline and progressive JPEG decode
+//!
+//! PNG and JPEG decode to an [`Image`] (8-bit RGBA), which converts to the
+//! pixel layout of a `libgfx::framebuf::FrameBuf` for blitting.
+
+#![no_std]
+extern crate alloc;
+
 pub mod bmp;
+pub mod checksum;
+pub mod deflate;
+pub mod image;
+pub mod inflate;
+pub mod jpeg;
+pub mod png;
+
+use alloc::string::String;
+
+pub use crate::image::Image;
+
+/// Largest width or height accepted by the decoders.
+pub const MAX_DIMENSION: u32 = 16384;
+
+/// Decode a PNG, JPEG or 24-bit BMP file, detected from its signature.
+pub fn decode(data: &[u8]) -> Result<Image, String> {
+    if data.starts_with(&png::SIGNATURE) {
+        png::decode_png(data)
+    } else if data.starts_with(&[0xFF, 0xD8]) {
+        jpeg::decode_jpeg(data)
+    } else if data.starts_with(b"BM") {
+        let (width, height, rgb) =
+            bmp::decode_bmp_24(data).ok_or_else(|| String::from("unsupported BMP"))?;
+        Ok(Image::from_rgb(width, height, &rgb))
+    } else {
+        Err(String::from("unknown image format"))
+    }
+}
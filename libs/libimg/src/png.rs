@@ -0,0 +1,841 @@
+//! PNG decoding and encoding.
+//!
+//! Decodes every standard color type (grayscale, RGB, palette, gray+alpha,
+//! RGBA) at every bit depth it allows, with tRNS transparency and Adam7
+//! interlacing. 16-bit samples are reduced to 8 bits.
+//!
+//! Encodes an [`Image`] using the smallest color type that holds it
+//! losslessly: low bit depth grayscale, a palette of up to 256 colors,
+//! gray+alpha, RGB or RGBA.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::checksum::Crc32;
+use crate::deflate::zlib_compress;
+use crate::image::Image;
+use crate::inflate::zlib_decompress;
+use crate::MAX_DIMENSION;
+
+/// The 8 bytes every PNG file starts with
+pub const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Pixel layout of the image data (IHDR color type)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorType {
+    Gray = 0,
+    Rgb = 2,
+    Indexed = 3,
+    GrayAlpha = 4,
+    Rgba = 6,
+}
+
+impl ColorType {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Gray),
+            2 => Some(Self::Rgb),
+            3 => Some(Self::Indexed),
+            4 => Some(Self::GrayAlpha),
+            6 => Some(Self::Rgba),
+            _ => None,
+        }
+    }
+
+    /// Samples per pixel
+    pub fn channels(self) -> usize {
+        match self {
+            Self::Gray | Self::Indexed => 1,
+            Self::GrayAlpha => 2,
+            Self::Rgb => 3,
+            Self::Rgba => 4,
+        }
+    }
+
+    /// Whether `depth` bits per sample is allowed for this color type
+    fn allows_depth(self, depth: u8) -> bool {
+        match self {
+            Self::Gray => matches!(depth, 1 | 2 | 4 | 8 | 16),
+            Self::Indexed => matches!(depth, 1 | 2 | 4 | 8),
+            _ => matches!(depth, 8 | 16),
+        }
+    }
+}
+
+/// Pass origins and spacing (x0, y0, dx, dy) for Adam7 interlacing
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// Contents of the IHDR chunk
+#[derive(Clone, Copy, Debug)]
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    color: ColorType,
+    interlaced: bool,
+}
+
+impl Header {
+    /// Bytes in one unfiltered row of `width` pixels
+    fn row_bytes(&self, width: usize) -> usize {
+        (width * self.color.channels() * self.depth as usize).div_ceil(8)
+    }
+
+    /// Bytes per complete pixel, at least 1, as used by the filters
+    fn filter_bpp(&self) -> usize {
+        (self.color.channels() * self.depth as usize).div_ceil(8)
+    }
+
+    /// The passes as (x0, y0, dx, dy, width, height), skipping empty ones
+    fn passes(&self) -> Vec<(usize, usize, usize, usize, usize, usize)> {
+        let layout: &[_] = if self.interlaced {
+            &ADAM7
+        } else {
+            &[(0, 0, 1, 1)]
+        };
+        layout
+            .iter()
+            .map(|&(x0, y0, dx, dy)| {
+                let w = self.width.saturating_sub(x0).div_ceil(dx);
+                let h = self.height.saturating_sub(y0).div_ceil(dy);
+                (x0, y0, dx, dy, w, h)
+            })
+            .filter(|&(.., w, h)| w > 0 && h > 0)
+            .collect()
+    }
+}
+
+/// Transparency from the tRNS chunk
+#[derive(Default)]
+struct Transparency {
+    /// Gray sample value that is fully transparent
+    gray: Option<u16>,
+    /// RGB sample values that are fully transparent
+    rgb: Option<[u16; 3]>,
+    /// Alpha for each palette entry (missing entries are opaque)
+    palette: Vec<u8>,
+}
+
+fn read_u32_be(buf: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        buf[offset],
+        buf[offset + 1],
+        buf[offset + 2],
+        buf[offset + 3],
+    ])
+}
+
+fn read_u16_be(buf: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([buf[offset], buf[offset + 1]])
+}
+
+fn parse_header(data: &[u8]) -> Result<Header, String> {
+    if data.len() != 13 {
+        return Err(String::from("bad IHDR length"));
+    }
+    let width = read_u32_be(data, 0);
+    let height = read_u32_be(data, 4);
+    if width == 0 || height == 0 || width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return Err(format!("unsupported image size {width}x{height}"));
+    }
+    let depth = data[8];
+    let color = ColorType::from_u8(data[9]).ok_or_else(|| format!("bad color type {}", data[9]))?;
+    if !color.allows_depth(depth) {
+        return Err(format!("bad bit depth {depth} for {color:?}"));
+    }
+    if data[10] != 0 || data[11] != 0 {
+        return Err(String::from("unknown compression or filter method"));
+    }
+    let interlaced = match data[12] {
+        0 => false,
+        1 => true,
+        _ => return Err(String::from("unknown interlace method")),
+    };
+    Ok(Header {
+        width: width as usize,
+        height: height as usize,
+        depth,
+        color,
+        interlaced,
+    })
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Reverse the filter on `row` in place, given the previous unfiltered row
+/// (all zeros for the first row of a pass)
+fn unfilter(filter: u8, row: &mut [u8], prev: &[u8], bpp: usize) -> Result<(), String> {
+    match filter {
+        0 => {}
+        1 => {
+            for i in bpp..row.len() {
+                row[i] = row[i].wrapping_add(row[i - bpp]);
+            }
+        }
+        2 => {
+            for (x, &up) in row.iter_mut().zip(prev) {
+                *x = x.wrapping_add(up);
+            }
+        }
+        3 => {
+            for i in 0..row.len() {
+                let left = if i >= bpp { row[i - bpp] } else { 0 };
+                row[i] = row[i].wrapping_add(((left as u16 + prev[i] as u16) / 2) as u8);
+            }
+        }
+        4 => {
+            for i in 0..row.len() {
+                let (left, upper_left) = if i >= bpp {
+                    (row[i - bpp], prev[i - bpp])
+                } else {
+                    (0, 0)
+                };
+                row[i] = row[i].wrapping_add(paeth(left, prev[i], upper_left));
+            }
+        }
+        _ => return Err(format!("bad filter type {filter}")),
+    }
+    Ok(())
+}
+
+/// Sample `index` of a row packed at `depth` bits per sample
+fn sample(row: &[u8], index: usize, depth: u8) -> u16 {
+    match depth {
+        16 => read_u16_be(row, index * 2),
+        8 => row[index] as u16,
+        _ => {
+            let bit = index * depth as usize;
+            let shift = 8 - depth as usize - bit % 8;
+            (row[bit / 8] >> shift) as u16 & ((1 << depth) - 1)
+        }
+    }
+}
+
+/// Scale a sample of `depth` bits to 8 bits
+fn to_8bit(value: u16, depth: u8) -> u8 {
+    match depth {
+        16 => ((value as u32 * 255 + 32767) / 65535) as u8,
+        8 => value as u8,
+        _ => (value as u32 * 255 / ((1 << depth) - 1)) as u8,
+    }
+}
+
+/// Convert unfiltered row `row` of `width` pixels to RGBA in `out`
+fn expand_row(
+    header: &Header,
+    row: &[u8],
+    width: usize,
+    palette: &[[u8; 3]],
+    trns: &Transparency,
+    out: &mut [u8],
+) -> Result<(), String> {
+    let depth = header.depth;
+    for (x, px) in out.chunks_exact_mut(4).take(width).enumerate() {
+        let rgba = match header.color {
+            ColorType::Gray => {
+                let v = sample(row, x, depth);
+                let g = to_8bit(v, depth);
+                let a = if trns.gray == Some(v) { 0 } else { 255 };
+                [g, g, g, a]
+            }
+            ColorType::Rgb => {
+                let v = [
+                    sample(row, x * 3, depth),
+                    sample(row, x * 3 + 1, depth),
+                    sample(row, x * 3 + 2, depth),
+                ];
+                let a = if trns.rgb == Some(v) { 0 } else { 255 };
+                [
+                    to_8bit(v[0], depth),
+                    to_8bit(v[1], depth),
+                    to_8bit(v[2], depth),
+                    a,
+                ]
+            }
+            ColorType::Indexed => {
+                let index = sample(row, x, depth) as usize;
+                let [r, g, b] = *palette
+                    .get(index)
+                    .ok_or_else(|| format!("palette index {index} out of range"))?;
+                [r, g, b, trns.palette.get(index).copied().unwrap_or(255)]
+            }
+            ColorType::GrayAlpha => {
+                let g = to_8bit(sample(row, x * 2, depth), depth);
+                [g, g, g, to_8bit(sample(row, x * 2 + 1, depth), depth)]
+            }
+            ColorType::Rgba => [
+                to_8bit(sample(row, x * 4, depth), depth),
+                to_8bit(sample(row, x * 4 + 1, depth), depth),
+                to_8bit(sample(row, x * 4 + 2, depth), depth),
+                to_8bit(sample(row, x * 4 + 3, depth), depth),
+            ],
+        };
+        px.copy_from_slice(&rgba);
+    }
+    Ok(())
+}
+
+/// Decode a PNG file to RGBA.
+pub fn decode_png(data: &[u8]) -> Result<Image, String> {
+    if !data.starts_with(&SIGNATURE) {
+        return Err(String::from("not a PNG file"));
+    }
+
+    let mut header = None;
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut trns = Transparency::default();
+    let mut idat = Vec::new();
+    let mut pos = SIGNATURE.len();
+    loop {
+        if pos + 12 > data.len() {
+            return Err(String::from("truncated chunk"));
+        }
+        let len = read_u32_be(data, pos) as usize;
+        let kind: [u8; 4] = data[pos + 4..pos + 8].try_into().unwrap();
+        let body = data
+            .get(pos + 8..pos + 8 + len)
+            .ok_or_else(|| String::from("truncated chunk"))?;
+        let crc = data
+            .get(pos + 8 + len..pos + 12 + len)
+            .ok_or_else(|| String::from("truncated chunk"))?;
+        let mut expected = Crc32::new();
+        expected.update(&kind);
+        expected.update(body);
+        if expected.finish() != read_u32_be(crc, 0) {
+            return Err(format!(
+                "bad CRC in {} chunk",
+                String::from_utf8_lossy(&kind)
+            ));
+        }
+        pos += 12 + len;
+
+        if header.is_none() && &kind != b"IHDR" {
+            return Err(String::from("missing IHDR"));
+        }
+        match &kind {
+            b"IHDR" => header = Some(parse_header(body)?),
+            b"PLTE" => {
+                if len % 3 != 0 || len / 3 > 256 {
+                    return Err(String::from("bad PLTE length"));
+                }
+                palette = body.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+            }
+            b"tRNS" => match header.map(|h| h.color) {
+                Some(ColorType::Gray) if len >= 2 => trns.gray = Some(read_u16_be(body, 0)),
+                Some(ColorType::Rgb) if len >= 6 => {
+                    trns.rgb = Some([
+                        read_u16_be(body, 0),
+                        read_u16_be(body, 2),
+                        read_u16_be(body, 4),
+                    ])
+                }
+                Some(ColorType::Indexed) => trns.palette = body.to_vec(),
+                _ => {}
+            },
+            b"IDAT" => idat.extend_from_slice(body),
+            b"IEND" => break,
+            // Unknown critical chunks change how the image must be read
+            _ if kind[0] & 0x20 == 0 => {
+                return Err(format!(
+                    "unknown critical chunk {}",
+                    String::from_utf8_lossy(&kind)
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    // IEND is only reached after IHDR
+    let header = header.unwrap();
+    if header.color == ColorType::Indexed && palette.is_empty() {
+        return Err(String::from("missing PLTE"));
+    }
+
+    let passes = header.passes();
+    let raw_len: usize = passes
+        .iter()
+        .map(|&(.., w, h)| h * (1 + header.row_bytes(w)))
+        .sum();
+    let raw = zlib_decompress(&idat, raw_len)?;
+    if raw.len() < raw_len {
+        return Err(String::from("image data too short"));
+    }
+
+    let mut image = Image::new(header.width as u32, header.height as u32);
+    let bpp = header.filter_bpp();
+    let mut offset = 0;
+    let mut line = vec![0u8; header.width * 4];
+    for (x0, y0, dx, dy, w, h) in passes {
+        let stride = header.row_bytes(w);
+        let mut prev = vec![0u8; stride];
+        let mut row = vec![0u8; stride];
+        for py in 0..h {
+            let filter = raw[offset];
+            row.copy_from_slice(&raw[offset + 1..offset + 1 + stride]);
+            offset += 1 + stride;
+            unfilter(filter, &mut row, &prev, bpp)?;
+            expand_row(&header, &row, w, &palette, &trns, &mut line)?;
+
+            let y = y0 + py * dy;
+            let dst = &mut image.rgba[y * header.width * 4..(y + 1) * header.width * 4];
+            if dx == 1 {
+                dst.copy_from_slice(&line[..w * 4]);
+            } else {
+                for px in 0..w {
+                    let x = x0 + px * dx;
+                    dst[x * 4..x * 4 + 4].copy_from_slice(&line[px * 4..px * 4 + 4]);
+                }
+            }
+            core::mem::swap(&mut prev, &mut row);
+        }
+    }
+    Ok(image)
+}
+
+/// Encode `image` as a non-interlaced PNG.
+pub fn encode_png(image: &Image) -> Vec<u8> {
+    encode(image, false)
+}
+
+/// Encode `image` as an Adam7-interlaced PNG, which viewers can show
+/// progressively as it loads.
+pub fn encode_png_interlaced(image: &Image) -> Vec<u8> {
+    encode(image, true)
+}
+
+/// How the encoder stores each pixel
+struct Layout {
+    color: ColorType,
+    depth: u8,
+    /// Palette colors, sorted, as RGBA packed into a u32
+    palette: Vec<u32>,
+}
+
+fn pack(px: &[u8]) -> u32 {
+    u32::from_be_bytes([px[0], px[1], px[2], px[3]])
+}
+
+/// Pick the smallest lossless color type and bit depth for `image`
+fn choose_layout(image: &Image) -> Layout {
+    let pixels = image.rgba.chunks_exact(4);
+    let opaque = image.is_opaque();
+    let gray = pixels.clone().all(|px| px[0] == px[1] && px[1] == px[2]);
+
+    if gray && opaque {
+        // The lowest depth whose levels include every value
+        let depth = [1u8, 2, 4]
+            .into_iter()
+            .find(|&d| {
+                let step = 255 / ((1u16 << d) - 1) as u8;
+                pixels.clone().all(|px| px[0] % step == 0)
+            })
+            .unwrap_or(8);
+        return Layout {
+            color: ColorType::Gray,
+            depth,
+            palette: Vec::new(),
+        };
+    }
+
+    let mut colors: Vec<u32> = Vec::new();
+    for px in pixels {
+        let color = pack(px);
+        if let Err(at) = colors.binary_search(&color) {
+            if colors.len() == 256 {
+                colors.clear();
+                break;
+            }
+            colors.insert(at, color);
+        }
+    }
+    if !colors.is_empty() {
+        let depth = match colors.len() {
+            0..=2 => 1,
+            3..=4 => 2,
+            5..=16 => 4,
+            _ => 8,
+        };
+        return Layout {
+            color: ColorType::Indexed,
+            depth,
+            palette: colors,
+        };
+    }
+
+    let color = match (gray, opaque) {
+        (true, _) => ColorType::GrayAlpha,
+        (false, true) => ColorType::Rgb,
+        (false, false) => ColorType::Rgba,
+    };
+    Layout {
+        color,
+        depth: 8,
+        palette: Vec::new(),
+    }
+}
+
+/// Pack one row of RGBA pixels into the layout's sample format
+fn pack_row(layout: &Layout, rgba: &[u8], out: &mut Vec<u8>) {
+    match layout.color {
+        ColorType::Gray | ColorType::Indexed => {
+            let depth = layout.depth as usize;
+            let mut byte = 0u8;
+            let mut used = 0;
+            for px in rgba.chunks_exact(4) {
+                let value = if layout.color == ColorType::Gray {
+                    px[0] / (255 / ((1u16 << depth) - 1) as u8)
+                } else {
+                    // Every pixel's color is in the palette
+                    layout.palette.binary_search(&pack(px)).unwrap() as u8
+                };
+                byte |= value << (8 - depth - used);
+                used += depth;
+                if used == 8 {
+                    out.push(byte);
+                    byte = 0;
+                    used = 0;
+                }
+            }
+            if used > 0 {
+                out.push(byte);
+            }
+        }
+        ColorType::GrayAlpha => {
+            for px in rgba.chunks_exact(4) {
+                out.extend_from_slice(&[px[0], px[3]]);
+            }
+        }
+        ColorType::Rgb => {
+            for px in rgba.chunks_exact(4) {
+                out.extend_from_slice(&px[..3]);
+            }
+        }
+        ColorType::Rgba => out.extend_from_slice(rgba),
+    }
+}
+
+/// Filter `row` against `prev`, appending the filter type and the result.
+///
+/// Tries every filter and keeps the one with the smallest sum of absolute
+/// values, the usual heuristic for what will compress best.
+fn filter_row(row: &[u8], prev: &[u8], bpp: usize, out: &mut Vec<u8>) {
+    let mut best = Vec::new();
+    let mut best_score = u64::MAX;
+    let mut candidate = vec![0u8; row.len()];
+    for filter in 0..5u8 {
+        for i in 0..row.len() {
+            let left = if i >= bpp { row[i - bpp] } else { 0 };
+            let upper_left = if i >= bpp { prev[i - bpp] } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => prev[i],
+                3 => ((left as u16 + prev[i] as u16) / 2) as u8,
+                _ => paeth(left, prev[i], upper_left),
+            };
+            candidate[i] = row[i].wrapping_sub(predicted);
+        }
+        let score: u64 = candidate
+            .iter()
+            .map(|&b| (b as i8).unsigned_abs() as u64)
+            .sum();
+        if score < best_score {
+            best_score = score;
+            best.clear();
+            best.push(filter);
+            best.extend_from_slice(&candidate);
+        }
+    }
+    out.extend_from_slice(&best);
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(body);
+    let mut crc = Crc32::new();
+    crc.update(kind);
+    crc.update(body);
+    out.extend_from_slice(&crc.finish().to_be_bytes());
+}
+
+fn encode(image: &Image, interlaced: bool) -> Vec<u8> {
+    let layout = choose_layout(image);
+    let header = Header {
+        width: image.width as usize,
+        height: image.height as usize,
+        depth: layout.depth,
+        color: layout.color,
+        interlaced,
+    };
+    let bpp = header.filter_bpp();
+    // Filtering rarely helps palette or sub-byte images
+    let filtered = layout.depth == 8 && layout.color != ColorType::Indexed;
+
+    let mut raw = Vec::new();
+    let mut rgba = Vec::with_capacity(header.width * 4);
+    let mut row = Vec::with_capacity(header.row_bytes(header.width));
+    for (x0, y0, dx, dy, w, h) in header.passes() {
+        let mut prev = vec![0u8; header.row_bytes(w)];
+        for py in 0..h {
+            let y = y0 + py * dy;
+            let src = image.row(y as u32);
+            rgba.clear();
+            for px in 0..w {
+                let x = (x0 + px * dx) * 4;
+                rgba.extend_from_slice(&src[x..x + 4]);
+            }
+            row.clear();
+            pack_row(&layout, &rgba, &mut row);
+            if filtered {
+                filter_row(&row, &prev, bpp, &mut raw);
+            } else {
+                raw.push(0);
+                raw.extend_from_slice(&row);
+            }
+            prev.copy_from_slice(&row);
+        }
+    }
+
+    let mut out = SIGNATURE.to_vec();
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&image.width.to_be_bytes());
+    ihdr.extend_from_slice(&image.height.to_be_bytes());
+    ihdr.extend_from_slice(&[layout.depth, layout.color as u8, 0, 0, interlaced as u8]);
+    write_chunk(&mut out, b"IHDR", &ihdr);
+    if layout.color == ColorType::Indexed {
+        let plte: Vec<u8> = layout
+            .palette
+            .iter()
+            .flat_map(|c| c.to_be_bytes()[..3].to_vec())
+            .collect();
+        write_chunk(&mut out, b"PLTE", &plte);
+        if layout.palette.iter().any(|c| c & 0xFF != 0xFF) {
+            let trns: Vec<u8> = layout.palette.iter().map(|c| *c as u8).collect();
+            write_chunk(&mut out, b"tRNS", &trns);
+        }
+    }
+    write_chunk(&mut out, b"IDAT", &zlib_compress(&raw));
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image_from(width: u32, height: u32, f: impl Fn(u32, u32) -> [u8; 4]) -> Image {
+        let mut image = Image::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let i = ((y * width + x) * 4) as usize;
+                image.rgba[i..i + 4].copy_from_slice(&f(x, y));
+            }
+        }
+        image
+    }
+
+    /// Round-trip through both encodings, returning the IHDR color type and
+    /// bit depth the encoder chose
+    fn round_trip(image: &Image) -> (u8, u8) {
+        let png = encode_png(image);
+        assert_eq!(&decode_png(&png).unwrap(), image);
+        let interlaced = encode_png_interlaced(image);
+        assert_eq!(interlaced[28], 1);
+        assert_eq!(&decode_png(&interlaced).unwrap(), image);
+        (png[25], png[24])
+    }
+
+    #[test]
+    fn round_trips_every_color_type() {
+        let bilevel = image_from(13, 7, |x, y| {
+            let v = if (x + y) % 3 == 0 { 255 } else { 0 };
+            [v, v, v, 255]
+        });
+        assert_eq!(round_trip(&bilevel), (0, 1));
+
+        let gray = image_from(17, 9, |x, y| {
+            let v = (x * 13 + y * 7) as u8;
+            [v, v, v, 255]
+        });
+        assert_eq!(round_trip(&gray), (0, 8));
+
+        let indexed = image_from(11, 5, |x, y| [(x * 20) as u8, (y * 40) as u8, 7, 255]);
+        assert_eq!(round_trip(&indexed), (3, 8));
+
+        let few_colors = image_from(9, 9, |x, _| [(x % 3) as u8 * 100, 0, 50, 128]);
+        assert_eq!(round_trip(&few_colors), (3, 2));
+
+        let gray_alpha = image_from(20, 20, |x, y| {
+            let v = (x * 12) as u8;
+            [v, v, v, (y * 12) as u8]
+        });
+        assert_eq!(round_trip(&gray_alpha), (4, 8));
+
+        let rgb = image_from(23, 19, |x, y| {
+            [(x * 11) as u8, (y * 13) as u8, (x * y) as u8, 255]
+        });
+        assert_eq!(round_trip(&rgb), (2, 8));
+
+        let rgba = image_from(31, 12, |x, y| {
+            [(x * 8) as u8, (y * 20) as u8, (x + y) as u8, (x * 8) as u8]
+        });
+        assert_eq!(round_trip(&rgba), (6, 8));
+
+        // Fewer pixels than Adam7 passes
+        let tiny = image_from(1, 1, |_, _| [1, 2, 3, 4]);
+        round_trip(&tiny);
+    }
+
+    /// Build a PNG by hand from already-filtered scanlines
+    fn build_png(
+        width: u32,
+        height: u32,
+        depth: u8,
+        color: u8,
+        extra: &[(&[u8; 4], &[u8])],
+        raw: &[u8],
+    ) -> Vec<u8> {
+        let mut out = SIGNATURE.to_vec();
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&width.to_be_bytes());
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        ihdr.extend_from_slice(&[depth, color, 0, 0, 0]);
+        write_chunk(&mut out, b"IHDR", &ihdr);
+        for (kind, body) in extra {
+            write_chunk(&mut out, kind, body);
+        }
+        write_chunk(&mut out, b"IDAT", &zlib_compress(raw));
+        write_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    #[test]
+    fn decodes_sixteen_bit_and_transparency_keys() {
+        // 2x1 RGB at 16 bits, with the second pixel matching the tRNS key
+        let raw = [
+            0, 0xFF, 0xFF, 0x80, 0x00, 0x00, 0x01, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC,
+        ];
+        let trns = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+        let png = build_png(2, 1, 16, 2, &[(b"tRNS", &trns)], &raw);
+        let image = decode_png(&png).unwrap();
+        assert_eq!(image.rgba, [255, 128, 0, 255, 0x12, 0x56, 0x9A, 0]);
+
+        // 4x1 gray at 2 bits, with level 1 transparent
+        let png = build_png(4, 1, 2, 0, &[(b"tRNS", &[0, 1])], &[0, 0b00_01_10_11]);
+        let image = decode_png(&png).unwrap();
+        assert_eq!(
+            image.rgba,
+            [0, 0, 0, 255, 85, 85, 85, 0, 170, 170, 170, 255, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn reverses_every_filter() {
+        // 3x2 RGB: row 0 uses Sub, row 1 uses Up; Average and Paeth are
+        // covered by the encoder round trips
+        let raw = [
+            1, 10, 20, 30, 5, 5, 5, 5, 5, 5, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        ];
+        let image = decode_png(&build_png(3, 2, 8, 2, &[], &raw)).unwrap();
+        assert_eq!(
+            image.row(0),
+            [10, 20, 30, 255, 15, 25, 35, 255, 20, 30, 40, 255]
+        );
+        assert_eq!(
+            image.row(1),
+            [11, 21, 31, 255, 16, 26, 36, 255, 21, 31, 41, 255]
+        );
+    }
+
+    #[test]
+    fn rejects_corrupt_files() {
+        let image = image_from(8, 8, |x, y| [x as u8, y as u8, 0, 255]);
+        let mut png = encode_png(&image);
+        assert!(decode_png(&png[..png.len() - 20]).is_err());
+        png[40] ^= 1;
+        assert!(decode_png(&png).is_err());
+        assert!(decode_png(b"\x89PNG\r\n\x1a\n").is_err());
+    }
+
+    #[test]
+    fn decodes_reference_file() {
+        // favicon-32x32.png from the Rust documentation: 8-bit gray+alpha
+        let image = decode_png(FAVICON).unwrap();
+        assert_eq!((image.width, image.height), (32, 32));
+        assert_eq!(crate::checksum::crc32(&image.rgba), FAVICON_RGBA_CRC);
+    }
+
+    const FAVICON_RGBA_CRC: u32 = 0xB2F4_747C;
+
+    const FAVICON: &[u8] = &[
+        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x20, 0x08, 0x04, 0x00, 0x00, 0x00, 0xD9,
+        0x73, 0xB2, 0x7F, 0x00, 0x00, 0x00, 0x09, 0x70, 0x48, 0x59, 0x73, 0x00, 0x00, 0x04, 0x74,
+        0x00, 0x00, 0x04, 0x74, 0x01, 0xC2, 0xC3, 0x69, 0x4E, 0x00, 0x00, 0x02, 0x64, 0x49, 0x44,
+        0x41, 0x54, 0x78, 0xDA, 0xA5, 0xD5, 0x03, 0x8C, 0x2D, 0x66, 0x10, 0x05, 0xE0, 0x6F, 0x6B,
+        0x9B, 0x71, 0x6D, 0x5B, 0x8F, 0x71, 0x6A, 0xDB, 0xB6, 0x8D, 0xA0, 0x0A, 0xEA, 0x46, 0xB5,
+        0x7D, 0x1B, 0xD5, 0x6E, 0x1F, 0x82, 0xDA, 0xE6, 0xB3, 0xED, 0xBD, 0x77, 0x3B, 0x49, 0xFE,
+        0x4C, 0x72, 0xB9, 0x3A, 0x67, 0x75, 0x66, 0xE7, 0xCE, 0xAF, 0x81, 0xB6, 0xD8, 0xD3, 0x10,
+        0x5C, 0x11, 0x64, 0x48, 0xA8, 0x7E, 0x60, 0x57, 0x37, 0x59, 0xDE, 0x28, 0x8B, 0x5D, 0x6C,
+        0x69, 0xF0, 0x62, 0x8B, 0x8D, 0xB2, 0xBC, 0x9B, 0xEC, 0xAA, 0x57, 0x6C, 0x83, 0xEB, 0xD5,
+        0x8C, 0x57, 0xD5, 0x23, 0xA9, 0x6A, 0xBC, 0x9A, 0xEB, 0x8B, 0x47, 0x07, 0x74, 0xFB, 0xCF,
+        0xDF, 0xF2, 0x83, 0x4E, 0x0C, 0xA6, 0x32, 0xD1, 0x38, 0xDD, 0xDA, 0x60, 0x1D, 0x43, 0xAD,
+        0x66, 0x69, 0x3A, 0xCF, 0xF1, 0xA9, 0x65, 0x6E, 0x0E, 0x2E, 0xF3, 0xA9, 0x39, 0x69, 0x5F,
+        0x6A, 0x35, 0x43, 0xC3, 0xBB, 0x09, 0x43, 0x55, 0x73, 0xDB, 0x5F, 0x39, 0xD4, 0x4A, 0x18,
+        0x6E, 0x61, 0x70, 0x38, 0x56, 0x76, 0x98, 0xAF, 0xF3, 0x38, 0x55, 0x43, 0x35, 0x61, 0xB5,
+        0xF2, 0xF1, 0x45, 0xCE, 0xB2, 0x1C, 0x80, 0xED, 0x82, 0x80, 0xB0, 0x9E, 0x6B, 0x51, 0x09,
+        0xB1, 0x1A, 0xF5, 0x57, 0x57, 0x2B, 0x9B, 0x9F, 0x62, 0x1F, 0x9D, 0xB0, 0xAF, 0x29, 0xC2,
+        0xCF, 0x12, 0xB5, 0xFA, 0xEB, 0xFC, 0xAF, 0x44, 0xFE, 0xDE, 0xD8, 0x36, 0x7C, 0xD3, 0x6D,
+        0x56, 0xC5, 0x1E, 0x16, 0x0A, 0x5F, 0xFF, 0x49, 0xEC, 0xE2, 0x7A, 0x93, 0xF5, 0xE8, 0x9D,
+        0xA3, 0x6C, 0x80, 0x73, 0xC5, 0xDF, 0x26, 0xB9, 0xDE, 0x2E, 0xC0, 0x4D, 0x6A, 0xC2, 0x64,
+        0x99, 0xDE, 0x43, 0x3C, 0x8D, 0x15, 0xCA, 0x4D, 0xD4, 0xDC, 0x04, 0x2C, 0x6F, 0xBC, 0xE9,
+        0xAE, 0x36, 0xCF, 0x77, 0xDE, 0xF7, 0x43, 0x3A, 0xFF, 0x14, 0x2A, 0xE8, 0x13, 0xB9, 0x3F,
+        0x7F, 0x5B, 0xDE, 0xB1, 0xA1, 0xAF, 0x36, 0xDD, 0x38, 0xCB, 0xC3, 0x5E, 0x46, 0xA9, 0xFA,
+        0x48, 0x97, 0x77, 0x8D, 0xC1, 0xC3, 0xE9, 0xFC, 0x28, 0x79, 0xC8, 0xD9, 0xC5, 0x36, 0xC5,
+        0xEA, 0xE1, 0xF5, 0x8E, 0x2E, 0x1F, 0xAB, 0x1A, 0x65, 0x2F, 0x0E, 0x2A, 0x1B, 0x9A, 0x6A,
+        0x86, 0xD3, 0xEB, 0x02, 0x3C, 0x6B, 0x73, 0x41, 0xDB, 0xBA, 0x23, 0x13, 0xEC, 0x43, 0xC2,
+        0x6B, 0x86, 0xA9, 0x42, 0x59, 0x6C, 0x08, 0x5C, 0x22, 0x84, 0x4F, 0xAD, 0x45, 0x5D, 0x80,
+        0x85, 0xE6, 0x08, 0x5A, 0x22, 0x0F, 0x60, 0x7F, 0x08, 0xCF, 0xCF, 0x84, 0x76, 0x09, 0x5C,
+        0x6E, 0x69, 0x8A, 0x0C, 0xD0, 0x92, 0x53, 0x0D, 0x01, 0x70, 0x69, 0x49, 0xEB, 0xCB, 0x65,
+        0x80, 0x4B, 0x7B, 0x0B, 0x10, 0x7C, 0x4F, 0x17, 0x58, 0xC3, 0x0D, 0x32, 0x00, 0x17, 0x0B,
+        0xE1, 0xAE, 0xA6, 0x00, 0xAF, 0x19, 0x1E, 0x3C, 0xCC, 0x13, 0xF9, 0xC0, 0x55, 0x27, 0x82,
+        0xF7, 0xCA, 0xB1, 0x2E, 0x42, 0x5E, 0xE2, 0x6B, 0xD0, 0xE6, 0x15, 0x3E, 0x49, 0xDB, 0xEB,
+        0x58, 0xCD, 0xCF, 0x7A, 0xF2, 0x12, 0xCB, 0x33, 0x46, 0x1A, 0x79, 0xD5, 0x4B, 0xC1, 0x2F,
+        0xD2, 0xF9, 0x6B, 0xA1, 0x8D, 0xC4, 0x5D, 0x69, 0xEB, 0x36, 0xD6, 0xB7, 0xE5, 0xEF, 0xF2,
+        0x8C, 0x2C, 0x6F, 0x5C, 0x87, 0x73, 0x5F, 0x80, 0x43, 0x5A, 0xFE, 0x67, 0x9C, 0xE5, 0x33,
+        0x95, 0x7B, 0x09, 0xB0, 0x92, 0xDF, 0xEA, 0xAD, 0x99, 0xCA, 0x59, 0x4C, 0x13, 0x4B, 0x9E,
+        0xFD, 0xD6, 0xC4, 0x53, 0xC0, 0x39, 0xBE, 0x54, 0x2C, 0xA5, 0xA0, 0x67, 0xC9, 0x62, 0x92,
+        0xE5, 0xBC, 0xC8, 0x41, 0xB6, 0x77, 0x98, 0x4D, 0xB5, 0xC2, 0xA6, 0x0E, 0xB3, 0xBD, 0x21,
+        0x16, 0x67, 0x39, 0xD7, 0x35, 0x94, 0x25, 0x99, 0x7F, 0xDD, 0x2A, 0x5A, 0xA1, 0xA2, 0xBB,
+        0xF4, 0x82, 0x6C, 0x28, 0xAD, 0x5A, 0xDA, 0x56, 0x8E, 0xF3, 0xBB, 0x56, 0xF8, 0xDD, 0xB1,
+        0xB6, 0xCE, 0x96, 0xD6, 0xB6, 0xA9, 0xDE, 0xEB, 0x35, 0x35, 0x47, 0x5B, 0x19, 0x89, 0x50,
+        0xC7, 0x88, 0x5C, 0x71, 0x5F, 0x36, 0xD5, 0x5E, 0xDB, 0xFA, 0x5C, 0x15, 0x77, 0xBB, 0x34,
+        0x78, 0xB7, 0x4A, 0xA8, 0x5E, 0xDB, 0x7A, 0x0E, 0x96, 0x0E, 0x59, 0x31, 0x25, 0x07, 0x4B,
+        0xAF, 0xA3, 0x6D, 0x5C, 0xD3, 0x68, 0x1B, 0x97, 0xA3, 0xAD, 0x1F, 0xC3, 0xF5, 0x22, 0x4B,
+        0x83, 0x17, 0xF5, 0x3E, 0x5C, 0x07, 0x31, 0xDE, 0xFF, 0x07, 0x48, 0xCA, 0x0D, 0x0A, 0x2B,
+        0x27, 0x00, 0x2F, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
+    ];
+}
@@ -0,0 +1,740 @@
+//! Unicode Bidirectional Algorithm (UAX #9).
+//!
+//! [`resolve_levels`] gives every character an embedding level (even is
+//! left-to-right, odd is right-to-left) following rules P1–P3, X1–X10,
+//! W1–W7, N0–N2, I1–I2 and L1. [`visual_order`] applies rule L2 to a line's
+//! levels, and [`mirror`] gives the glyph rule L4 draws for a mirrored
+//! character in a right-to-left run.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::tables::{self, in_table, lookup};
+
+/// Bidi_Class property values.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BidiClass {
+    /// Strong left-to-right.
+    L,
+    /// Strong right-to-left (Hebrew and similar).
+    R,
+    /// Strong right-to-left Arabic letter.
+    AL,
+    /// European number.
+    EN,
+    /// European number separator.
+    ES,
+    /// European number terminator.
+    ET,
+    /// Arabic number.
+    AN,
+    /// Common number separator.
+    CS,
+    /// Nonspacing mark.
+    NSM,
+    /// Boundary neutral.
+    BN,
+    /// Paragraph separator.
+    B,
+    /// Segment separator.
+    S,
+    /// Whitespace.
+    WS,
+    /// Other neutral.
+    ON,
+    LRE,
+    LRO,
+    RLE,
+    RLO,
+    PDF,
+    LRI,
+    RLI,
+    FSI,
+    PDI,
+}
+
+use BidiClass::*;
+
+/// Base direction of a paragraph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the first strong character, left-to-right if there is none.
+    Auto,
+    Ltr,
+    Rtl,
+}
+
+/// Deepest explicit embedding level (BD2).
+const MAX_DEPTH: u8 = 125;
+
+/// Deepest bracket nesting rule N0 tracks (BD16).
+const MAX_BRACKETS: usize = 63;
+
+/// The Bidi_Class of `ch`.
+pub fn bidi_class(ch: char) -> BidiClass {
+    if (ch as u32) >= 0x80 && in_table(ch, tables::COMBINING_MARKS) {
+        return NSM;
+    }
+    lookup(ch, tables::BIDI_CLASSES).unwrap_or(L)
+}
+
+/// The mirrored counterpart of `ch` (e.g. `(` for `)`), or `ch` itself.
+pub fn mirror(ch: char) -> char {
+    let c = ch as u32;
+    match tables::MIRRORS.binary_search_by_key(&c, |&(from, _)| from) {
+        Ok(i) => char::from_u32(tables::MIRRORS[i].1).unwrap_or(ch),
+        Err(_) => ch,
+    }
+}
+
+/// Whether `level` runs right-to-left.
+#[inline]
+pub fn is_rtl(level: u8) -> bool {
+    level & 1 == 1
+}
+
+#[inline]
+fn is_isolate_initiator(class: BidiClass) -> bool {
+    matches!(class, LRI | RLI | FSI)
+}
+
+#[inline]
+fn is_removed(class: BidiClass) -> bool {
+    matches!(class, LRE | RLE | LRO | RLO | PDF | BN)
+}
+
+/// Level of the first strong character, skipping isolates (P2/P3).
+fn first_strong(classes: &[BidiClass]) -> Option<u8> {
+    let mut isolates = 0usize;
+    for &class in classes {
+        match class {
+            LRI | RLI | FSI => isolates += 1,
+            PDI if isolates > 0 => isolates -= 1,
+            L if isolates == 0 => return Some(0),
+            R | AL if isolates == 0 => return Some(1),
+            B => break,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Matching PDI of every isolate initiator (BD9), or None when unmatched.
+fn matching_pdis(classes: &[BidiClass]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; classes.len()];
+    let mut open: Vec<usize> = Vec::new();
+    for (i, &class) in classes.iter().enumerate() {
+        match class {
+            LRI | RLI | FSI => open.push(i),
+            PDI => {
+                if let Some(start) = open.pop() {
+                    matches[start] = Some(i);
+                }
+            }
+            B => open.clear(),
+            _ => {}
+        }
+    }
+    matches
+}
+
+/// Embedding level of the first paragraph of `chars`.
+pub fn paragraph_level(chars: &[char], direction: Direction) -> u8 {
+    match direction {
+        Direction::Ltr => 0,
+        Direction::Rtl => 1,
+        Direction::Auto => {
+            let classes: Vec<BidiClass> = chars.iter().map(|&c| bidi_class(c)).collect();
+            first_strong(&classes).unwrap_or(0)
+        }
+    }
+}
+
+/// Resolve the embedding level of every character in `chars`.
+///
+/// Paragraph separators split the text into paragraphs, each with its own
+/// base level when `direction` is [`Direction::Auto`]. Characters removed
+/// by rule X9 (embedding controls and boundary neutrals) take the level of
+/// the character before them so a line can be reordered as a whole.
+pub fn resolve_levels(chars: &[char], direction: Direction) -> Vec<u8> {
+    let classes: Vec<BidiClass> = chars.iter().map(|&c| bidi_class(c)).collect();
+    let mut levels = vec![0u8; chars.len()];
+    let mut start = 0;
+    while start < chars.len() {
+        let end = classes[start..]
+            .iter()
+            .position(|&c| c == B)
+            .map_or(chars.len(), |i| start + i + 1);
+        let para = match direction {
+            Direction::Ltr => 0,
+            Direction::Rtl => 1,
+            Direction::Auto => first_strong(&classes[start..end]).unwrap_or(0),
+        };
+        Paragraph::new(&chars[start..end], &classes[start..end], para)
+            .resolve(&mut levels[start..end]);
+        start = end;
+    }
+    levels
+}
+
+/// Display order of a line: the logical index of each character from left
+/// to right (rule L2).
+pub fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let Some(&max) = levels.iter().max() else {
+        return order;
+    };
+    let Some(min_odd) = levels.iter().copied().filter(|&l| is_rtl(l)).min() else {
+        return order;
+    };
+    let mut current: Vec<u8> = levels.to_vec();
+    for level in (min_odd..=max).rev() {
+        let mut i = 0;
+        while i < current.len() {
+            if current[i] < level {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < current.len() && current[i] >= level {
+                i += 1;
+            }
+            order[start..i].reverse();
+            current[start..i].reverse();
+        }
+    }
+    order
+}
+
+#[derive(Clone, Copy)]
+struct Status {
+    level: u8,
+    overridden: Option<BidiClass>,
+    isolate: bool,
+}
+
+/// One paragraph being resolved.
+struct Paragraph<'a> {
+    chars: &'a [char],
+    /// Original classes
+    classes: &'a [BidiClass],
+    /// Classes as rewritten by the X, W and N rules
+    types: Vec<BidiClass>,
+    levels: Vec<u8>,
+    matching_pdi: Vec<Option<usize>>,
+    level: u8,
+}
+
+impl<'a> Paragraph<'a> {
+    fn new(chars: &'a [char], classes: &'a [BidiClass], level: u8) -> Self {
+        Self {
+            chars,
+            classes,
+            types: classes.to_vec(),
+            levels: vec![level; chars.len()],
+            matching_pdi: matching_pdis(classes),
+            level,
+        }
+    }
+
+    fn resolve(mut self, out: &mut [u8]) {
+        self.explicit_levels();
+        for sequence in self.isolating_run_sequences() {
+            self.resolve_sequence(&sequence);
+        }
+        self.finish(out);
+    }
+
+    /// X1–X8: explicit embeddings, overrides and isolates.
+    fn explicit_levels(&mut self) {
+        let mut stack = vec![Status {
+            level: self.level,
+            overridden: None,
+            isolate: false,
+        }];
+        let mut overflow_isolates = 0usize;
+        let mut overflow_embeddings = 0usize;
+        let mut valid_isolates = 0usize;
+        for i in 0..self.classes.len() {
+            let class = self.classes[i];
+            let top = *stack.last().unwrap();
+            match class {
+                RLE | LRE | RLO | LRO => {
+                    let level = if matches!(class, RLE | RLO) {
+                        (top.level + 1) | 1
+                    } else {
+                        (top.level + 2) & !1
+                    };
+                    if level <= MAX_DEPTH && overflow_isolates == 0 && overflow_embeddings == 0 {
+                        let overridden = match class {
+                            RLO => Some(R),
+                            LRO => Some(L),
+                            _ => None,
+                        };
+                        stack.push(Status {
+                            level,
+                            overridden,
+                            isolate: false,
+                        });
+                    } else if overflow_isolates == 0 {
+                        overflow_embeddings += 1;
+                    }
+                    self.levels[i] = top.level;
+                }
+                RLI | LRI | FSI => {
+                    self.levels[i] = top.level;
+                    if let Some(class) = top.overridden {
+                        self.types[i] = class;
+                    }
+                    let rtl = match class {
+                        RLI => true,
+                        LRI => false,
+                        _ => {
+                            let end = self.matching_pdi[i].unwrap_or(self.classes.len());
+                            first_strong(&self.classes[i + 1..end]) == Some(1)
+                        }
+                    };
+                    let level = if rtl {
+                        (top.level + 1) | 1
+                    } else {
+                        (top.level + 2) & !1
+                    };
+                    if level <= MAX_DEPTH && overflow_isolates == 0 && overflow_embeddings == 0 {
+                        valid_isolates += 1;
+                        stack.push(Status {
+                            level,
+                            overridden: None,
+                            isolate: true,
+                        });
+                    } else {
+                        overflow_isolates += 1;
+                    }
+                }
+                PDI => {
+                    if overflow_isolates > 0 {
+                        overflow_isolates -= 1;
+                    } else if valid_isolates > 0 {
+                        overflow_embeddings = 0;
+                        while stack.last().is_some_and(|s| !s.isolate) {
+                            stack.pop();
+                        }
+                        stack.pop();
+                        valid_isolates -= 1;
+                    }
+                    let top = *stack.last().unwrap();
+                    self.levels[i] = top.level;
+                    if let Some(class) = top.overridden {
+                        self.types[i] = class;
+                    }
+                }
+                PDF => {
+                    if overflow_isolates > 0 {
+                    } else if overflow_embeddings > 0 {
+                        overflow_embeddings -= 1;
+                    } else if !top.isolate && stack.len() >= 2 {
+                        stack.pop();
+                    }
+                    self.levels[i] = stack.last().unwrap().level;
+                }
+                B => self.levels[i] = self.level,
+                BN => self.levels[i] = top.level,
+                _ => {
+                    self.levels[i] = top.level;
+                    if let Some(class) = top.overridden {
+                        self.types[i] = class;
+                    }
+                }
+            }
+        }
+    }
+
+    /// X9–X10: level runs of the characters that remain, chained across
+    /// matched isolates into isolating run sequences.
+    fn isolating_run_sequences(&self) -> Vec<Vec<usize>> {
+        let mut runs: Vec<Vec<usize>> = Vec::new();
+        let mut last_level = None;
+        for i in 0..self.classes.len() {
+            if is_removed(self.classes[i]) {
+                continue;
+            }
+            if last_level == Some(self.levels[i]) {
+                runs.last_mut().unwrap().push(i);
+            } else {
+                runs.push(vec![i]);
+                last_level = Some(self.levels[i]);
+            }
+        }
+
+        // Run starting at each character, to follow isolates to their PDI
+        let mut run_at = vec![usize::MAX; self.classes.len()];
+        for (r, run) in runs.iter().enumerate() {
+            run_at[run[0]] = r;
+        }
+        let matched_pdi: Vec<bool> = {
+            let mut matched = vec![false; self.classes.len()];
+            for pdi in self.matching_pdi.iter().flatten() {
+                matched[*pdi] = true;
+            }
+            matched
+        };
+
+        let mut sequences = Vec::new();
+        for run in &runs {
+            if self.classes[run[0]] == PDI && matched_pdi[run[0]] {
+                continue;
+            }
+            let mut sequence = run.clone();
+            loop {
+                let last = *sequence.last().unwrap();
+                if !is_isolate_initiator(self.classes[last]) {
+                    break;
+                }
+                let Some(pdi) = self.matching_pdi[last] else {
+                    break;
+                };
+                match run_at.get(pdi) {
+                    Some(&r) if r != usize::MAX => sequence.extend_from_slice(&runs[r]),
+                    _ => break,
+                }
+            }
+            sequences.push(sequence);
+        }
+        sequences
+    }
+
+    /// Level of the nearest unremoved character before `index`, if any.
+    fn level_before(&self, index: usize) -> Option<u8> {
+        (0..index)
+            .rev()
+            .find(|&i| !is_removed(self.classes[i]))
+            .map(|i| self.levels[i])
+    }
+
+    /// Level of the nearest unremoved character after `index`, if any.
+    fn level_after(&self, index: usize) -> Option<u8> {
+        (index + 1..self.classes.len())
+            .find(|&i| !is_removed(self.classes[i]))
+            .map(|i| self.levels[i])
+    }
+
+    /// W1–W7, N0–N2 and I1–I2 over one isolating run sequence.
+    fn resolve_sequence(&mut self, sequence: &[usize]) {
+        let first = sequence[0];
+        let last = *sequence.last().unwrap();
+        let level = self.levels[first];
+        let before = self.level_before(first).unwrap_or(self.level);
+        let after = if is_isolate_initiator(self.classes[last]) {
+            self.level
+        } else {
+            self.level_after(last).unwrap_or(self.level)
+        };
+        let direction = |l: u8| if is_rtl(l) { R } else { L };
+        let sos = direction(level.max(before));
+        let eos = direction(self.levels[last].max(after));
+        let embedding = direction(level);
+
+        let mut t: Vec<BidiClass> = sequence.iter().map(|&i| self.types[i]).collect();
+        let n = t.len();
+
+        // W1: nonspacing marks take the class before them
+        for k in 0..n {
+            if t[k] == NSM {
+                t[k] = match k.checked_sub(1).map(|p| t[p]) {
+                    None => sos,
+                    Some(LRI | RLI | FSI | PDI) => ON,
+                    Some(prev) => prev,
+                };
+            }
+        }
+        // W2: European numbers after Arabic letters are Arabic numbers
+        let mut strong = sos;
+        for class in t.iter_mut() {
+            match *class {
+                L | R | AL => strong = *class,
+                EN if strong == AL => *class = AN,
+                _ => {}
+            }
+        }
+        // W3
+        for class in t.iter_mut() {
+            if *class == AL {
+                *class = R;
+            }
+        }
+        // W4: single separators between numbers of the same kind
+        for k in 1..n.saturating_sub(1) {
+            match (t[k - 1], t[k], t[k + 1]) {
+                (EN, ES | CS, EN) => t[k] = EN,
+                (AN, CS, AN) => t[k] = AN,
+                _ => {}
+            }
+        }
+        // W5: terminators next to European numbers
+        let mut k = 0;
+        while k < n {
+            if t[k] != ET {
+                k += 1;
+                continue;
+            }
+            let start = k;
+            while k < n && t[k] == ET {
+                k += 1;
+            }
+            if (start > 0 && t[start - 1] == EN) || (k < n && t[k] == EN) {
+                t[start..k].fill(EN);
+            }
+        }
+        // W6
+        for class in t.iter_mut() {
+            if matches!(*class, ES | ET | CS) {
+                *class = ON;
+            }
+        }
+        // W7: European numbers in left-to-right context
+        let mut strong = sos;
+        for class in t.iter_mut() {
+            match *class {
+                L | R => strong = *class,
+                EN if strong == L => *class = L,
+                _ => {}
+            }
+        }
+
+        self.resolve_brackets(sequence, &mut t, sos, embedding);
+
+        // N1/N2: neutrals between strong characters of one direction take
+        // it, all others take the embedding direction
+        let strong_of = |class: BidiClass| match class {
+            L => Some(L),
+            R | EN | AN => Some(R),
+            _ => None,
+        };
+        let mut k = 0;
+        while k < n {
+            if strong_of(t[k]).is_some() {
+                k += 1;
+                continue;
+            }
+            let start = k;
+            while k < n && strong_of(t[k]).is_none() {
+                k += 1;
+            }
+            let leading = if start == 0 {
+                sos
+            } else {
+                strong_of(t[start - 1]).unwrap()
+            };
+            let trailing = if k == n {
+                eos
+            } else {
+                strong_of(t[k]).unwrap()
+            };
+            let resolved = if leading == trailing {
+                leading
+            } else {
+                embedding
+            };
+            t[start..k].fill(resolved);
+        }
+
+        // I1/I2
+        for (k, &i) in sequence.iter().enumerate() {
+            let level = self.levels[i];
+            self.levels[i] = match (is_rtl(level), t[k]) {
+                (false, R) => level + 1,
+                (false, AN | EN) => level + 2,
+                (true, L | EN | AN) => level + 1,
+                _ => level,
+            };
+        }
+    }
+
+    /// N0: paired brackets take the direction of what they enclose.
+    fn resolve_brackets(
+        &self,
+        sequence: &[usize],
+        t: &mut [BidiClass],
+        sos: BidiClass,
+        embedding: BidiClass,
+    ) {
+        let mut open: Vec<(u32, usize)> = Vec::new();
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        for (k, &i) in sequence.iter().enumerate() {
+            if t[k] != ON {
+                continue;
+            }
+            let c = self.chars[i] as u32;
+            if let Some(&(_, close)) = tables::BRACKETS.iter().find(|&&(o, _)| o == c) {
+                if open.len() == MAX_BRACKETS {
+                    break;
+                }
+                open.push((close, k));
+            } else if tables::BRACKETS.iter().any(|&(_, close)| close == c) {
+                if let Some(depth) = open.iter().rposition(|&(close, _)| close == c) {
+                    pairs.push((open[depth].1, k));
+                    open.truncate(depth);
+                }
+            }
+        }
+        pairs.sort_unstable();
+
+        let strong_of = |class: BidiClass| match class {
+            L => Some(L),
+            R | EN | AN => Some(R),
+            _ => None,
+        };
+        let opposite = if embedding == L { R } else { L };
+        for (o, c) in pairs {
+            let mut found_opposite = false;
+            let mut resolved = None;
+            for &class in &t[o + 1..c] {
+                match strong_of(class) {
+                    Some(dir) if dir == embedding => {
+                        resolved = Some(embedding);
+                        break;
+                    }
+                    Some(_) => found_opposite = true,
+                    None => {}
+                }
+            }
+            if resolved.is_none() && found_opposite {
+                let context = t[..o]
+                    .iter()
+                    .rev()
+                    .find_map(|&class| strong_of(class))
+                    .unwrap_or(sos);
+                resolved = Some(if context == opposite {
+                    opposite
+                } else {
+                    embedding
+                });
+            }
+            let Some(dir) = resolved else {
+                continue;
+            };
+            for bracket in [o, c] {
+                t[bracket] = dir;
+                // Marks on the bracket follow it
+                for k in bracket + 1..t.len() {
+                    if self.classes[sequence[k]] != NSM {
+                        break;
+                    }
+                    t[k] = dir;
+                }
+            }
+        }
+    }
+
+    /// Give removed characters a level and apply L1.
+    fn finish(mut self, out: &mut [u8]) {
+        let mut prev = self.level;
+        for i in 0..self.classes.len() {
+            if is_removed(self.classes[i]) {
+                self.levels[i] = prev;
+            }
+            prev = self.levels[i];
+        }
+        // L1: separators and the whitespace before them or the line end
+        // return to the paragraph level
+        let mut trailing = true;
+        for i in (0..self.classes.len()).rev() {
+            match self.classes[i] {
+                B | S => {
+                    self.levels[i] = self.level;
+                    trailing = true;
+                }
+                WS | LRI | RLI | FSI | PDI | LRE | RLE | LRO | RLO | PDF | BN if trailing => {
+                    self.levels[i] = self.level;
+                }
+                _ => trailing = false,
+            }
+        }
+        out.copy_from_slice(&self.levels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    /// Reorder `text` for display.
+    fn visual(text: &str, direction: Direction) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let levels = resolve_levels(&chars, direction);
+        visual_order(&levels)
+            .into_iter()
+            .map(|i| {
+                if is_rtl(levels[i]) {
+                    mirror(chars[i])
+                } else {
+                    chars[i]
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(bidi_class('a'), L);
+        assert_eq!(bidi_class('\u{5D0}'), R);
+        assert_eq!(bidi_class('\u{627}'), AL);
+        assert_eq!(bidi_class('\u{664}'), AN);
+        assert_eq!(bidi_class('7'), EN);
+        assert_eq!(bidi_class('\u{5B8}'), NSM);
+        assert_eq!(bidi_class(' '), WS);
+        assert_eq!(bidi_class('\u{4E2D}'), L);
+    }
+
+    #[test]
+    fn hebrew_inside_latin() {
+        // "abc ABC def" with ABC in Hebrew
+        let chars: Vec<char> = "abc \u{5D0}\u{5D1}\u{5D2} def".chars().collect();
+        assert_eq!(
+            resolve_levels(&chars, Direction::Auto),
+            [0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            visual("abc \u{5D0}\u{5D1}\u{5D2} def", Direction::Auto),
+            "abc \u{5D2}\u{5D1}\u{5D0} def"
+        );
+    }
+
+    #[test]
+    fn rtl_paragraph_with_numbers_and_brackets() {
+        assert_eq!(paragraph_level(&['\u{5D0}', 'a'], Direction::Auto), 1);
+        // Numbers keep their left-to-right order inside right-to-left text
+        assert_eq!(
+            visual("\u{5D0}\u{5D1} 123 \u{5D2}", Direction::Auto),
+            "\u{5D2} 123 \u{5D1}\u{5D0}"
+        );
+        // Brackets around right-to-left text are mirrored with it
+        assert_eq!(
+            visual("\u{5D0}(\u{5D1})", Direction::Auto),
+            "(\u{5D1})\u{5D0}"
+        );
+        // ...and around left-to-right text in a right-to-left paragraph
+        assert_eq!(
+            visual("\u{5D0} (ab) \u{5D1}", Direction::Auto),
+            "\u{5D1} (ab) \u{5D0}"
+        );
+        // Arabic digits after an Arabic letter
+        assert_eq!(visual("\u{627}12", Direction::Auto), "12\u{627}");
+    }
+
+    #[test]
+    fn explicit_isolates_and_trailing_whitespace() {
+        // An isolated Hebrew word does not pull the following number
+        let text = "a \u{2067}\u{5D0}\u{2069} 1";
+        let chars: Vec<char> = text.chars().collect();
+        let levels = resolve_levels(&chars, Direction::Ltr);
+        assert_eq!(levels, [0, 0, 0, 1, 0, 0, 0]);
+        // Trailing whitespace returns to the paragraph level
+        let chars: Vec<char> = "\u{5D0}\u{5D1}  ".chars().collect();
+        assert_eq!(resolve_levels(&chars, Direction::Ltr), [1, 1, 0, 0]);
+        // Each paragraph gets its own direction
+        let chars: Vec<char> = "\u{5D0}\nab".chars().collect();
+        assert_eq!(resolve_levels(&chars, Direction::Auto), [1, 1, 0, 0]);
+    }
+}
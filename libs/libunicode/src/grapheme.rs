@@ -0,0 +1,215 @@
+//! Extended grapheme clusters (UAX #29).
+//!
+//! A grapheme cluster is what a reader sees as one character: a base with
+//! its combining marks, a Hangul syllable spelled in jamo, a CR LF pair, an
+//! emoji ZWJ sequence or a regional-indicator flag. Layout, cursor movement
+//! and terminal cells all work in clusters rather than `char`s.
+//!
+//! Implements rules GB3–GB13 except the Indic conjunct rule GB9c.
+
+use crate::tables::{self, in_table};
+
+/// Grapheme_Cluster_Break property values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Break {
+    Cr,
+    Lf,
+    Control,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Prepend,
+    SpacingMark,
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    Pictographic,
+    Other,
+}
+
+fn break_property(ch: char) -> Break {
+    let c = ch as u32;
+    match c {
+        0x0D => return Break::Cr,
+        0x0A => return Break::Lf,
+        0x00..=0x1F | 0x7F..=0x9F => return Break::Control,
+        0x20..=0x7E => return Break::Other,
+        0x200C => return Break::Extend,
+        0x200D => return Break::Zwj,
+        0x1100..=0x115F | 0xA960..=0xA97C => return Break::L,
+        0x1160..=0x11A7 | 0xD7B0..=0xD7C6 => return Break::V,
+        0x11A8..=0x11FF | 0xD7CB..=0xD7FB => return Break::T,
+        0xAC00..=0xD7A3 if (c - 0xAC00) % 28 == 0 => return Break::Lv,
+        0xAC00..=0xD7A3 => return Break::Lvt,
+        0x1F1E6..=0x1F1FF => return Break::RegionalIndicator,
+        // Emoji skin-tone modifiers, halfwidth kana voicing marks and tags
+        0x1F3FB..=0x1F3FF | 0xFF9E..=0xFF9F | 0xE0020..=0xE007F => return Break::Extend,
+        _ => {}
+    }
+    if in_table(ch, tables::COMBINING_MARKS) {
+        Break::Extend
+    } else if in_table(ch, tables::SPACING_MARKS) {
+        Break::SpacingMark
+    } else if in_table(ch, tables::PREPEND) {
+        Break::Prepend
+    } else if in_table(ch, tables::CONTROL) {
+        Break::Control
+    } else if in_table(ch, tables::PICTOGRAPHIC) {
+        Break::Pictographic
+    } else {
+        Break::Other
+    }
+}
+
+/// Whether `ch` is Extended_Pictographic (an emoji or emoji-capable symbol).
+pub fn is_pictographic(ch: char) -> bool {
+    in_table(ch, tables::PICTOGRAPHIC)
+}
+
+/// Byte length of the grapheme cluster at the start of `text`.
+pub fn cluster_len(text: &str) -> usize {
+    let mut chars = text.char_indices();
+    let Some((_, first)) = chars.next() else {
+        return 0;
+    };
+    let mut prev = break_property(first);
+    // ExtPict Extend* ends at `prev` (GB11)
+    let mut pictographic = prev == Break::Pictographic;
+    // ...and is followed by the ZWJ at `prev`
+    let mut pictographic_zwj = false;
+    // An odd number of regional indicators ends at `prev` (GB12/13)
+    let mut odd_regional = prev == Break::RegionalIndicator;
+    for (offset, ch) in chars {
+        let next = break_property(ch);
+        let joined = match (prev, next) {
+            (Break::Cr, Break::Lf) => true,
+            (Break::Cr | Break::Lf | Break::Control, _) => false,
+            (_, Break::Cr | Break::Lf | Break::Control) => false,
+            (Break::L, Break::L | Break::V | Break::Lv | Break::Lvt) => true,
+            (Break::Lv | Break::V, Break::V | Break::T) => true,
+            (Break::Lvt | Break::T, Break::T) => true,
+            (_, Break::Extend | Break::Zwj | Break::SpacingMark) => true,
+            (Break::Prepend, _) => true,
+            (Break::Zwj, Break::Pictographic) => pictographic_zwj,
+            (Break::RegionalIndicator, Break::RegionalIndicator) => odd_regional,
+            _ => false,
+        };
+        if !joined {
+            return offset;
+        }
+        pictographic_zwj = next == Break::Zwj && pictographic;
+        pictographic = next == Break::Pictographic || (pictographic && next == Break::Extend);
+        odd_regional = next == Break::RegionalIndicator && !odd_regional;
+        prev = next;
+    }
+    text.len()
+}
+
+/// Iterator over the grapheme clusters of a string.
+#[derive(Debug, Clone)]
+pub struct Graphemes<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        self.next_indexed().map(|(_, cluster)| cluster)
+    }
+}
+
+impl<'a> Graphemes<'a> {
+    fn next_indexed(&mut self) -> Option<(usize, &'a str)> {
+        let rest = &self.text[self.offset..];
+        if rest.is_empty() {
+            return None;
+        }
+        let start = self.offset;
+        let len = cluster_len(rest);
+        self.offset += len;
+        Some((start, &rest[..len]))
+    }
+}
+
+/// Iterator over the grapheme clusters of a string and their byte offsets.
+#[derive(Debug, Clone)]
+pub struct GraphemeIndices<'a>(Graphemes<'a>);
+
+impl<'a> Iterator for GraphemeIndices<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        self.0.next_indexed()
+    }
+}
+
+/// Split `text` into grapheme clusters.
+pub fn graphemes(text: &str) -> Graphemes<'_> {
+    Graphemes { text, offset: 0 }
+}
+
+/// Split `text` into grapheme clusters, each with its byte offset.
+pub fn grapheme_indices(text: &str) -> GraphemeIndices<'_> {
+    GraphemeIndices(graphemes(text))
+}
+
+/// Whether `ch` continues the cluster of whatever precedes it, whatever
+/// that is: a combining or spacing mark, a joiner or a variation selector.
+///
+/// Terminals use this to attach characters to the previous cell.
+pub fn is_extend(ch: char) -> bool {
+    matches!(
+        break_property(ch),
+        Break::Extend | Break::Zwj | Break::SpacingMark
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn split(text: &str) -> Vec<&str> {
+        graphemes(text).collect()
+    }
+
+    #[test]
+    fn combining_marks_join_their_base() {
+        assert_eq!(split("e\u{301}x"), ["e\u{301}", "x"]);
+        assert_eq!(
+            split("\u{5E9}\u{5C1}\u{5B8}\u{5DC}"),
+            ["\u{5E9}\u{5C1}\u{5B8}", "\u{5DC}"]
+        );
+        assert_eq!(split("\u{915}\u{93F}"), ["\u{915}\u{93F}"]);
+        assert_eq!(split("\r\n\n"), ["\r\n", "\n"]);
+        assert_eq!(split("\u{301}a"), ["\u{301}", "a"]);
+    }
+
+    #[test]
+    fn hangul_emoji_and_flags() {
+        // Jamo spelling of 한 followed by a precomposed 글
+        assert_eq!(
+            split("\u{1112}\u{1161}\u{11AB}\u{AE00}"),
+            ["\u{1112}\u{1161}\u{11AB}", "\u{AE00}"]
+        );
+        // Family ZWJ sequence with a skin tone, then a thumbs up
+        let family = "\u{1F468}\u{1F3FD}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(
+            split(&alloc::format!("{family}\u{1F44D}")),
+            [family, "\u{1F44D}"]
+        );
+        // ZWJ after a letter does not join the next emoji
+        assert_eq!(split("a\u{200D}\u{1F467}"), ["a\u{200D}", "\u{1F467}"]);
+        // Three regional indicators: one flag and a lone indicator
+        assert_eq!(
+            split("\u{1F1EF}\u{1F1F5}\u{1F1FA}"),
+            ["\u{1F1EF}\u{1F1F5}", "\u{1F1FA}"]
+        );
+        let offsets: Vec<usize> = grapheme_indices("a\u{308}bc").map(|(i, _)| i).collect();
+        assert_eq!(offsets, [0, 3, 4]);
+    }
+}
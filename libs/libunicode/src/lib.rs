@@ -0,0 +1,33 @@
+//! Unicode text segmentation for Breenix.
+//!
+//! `#![no_std]` + `extern crate alloc`. Zero external dependencies.
+//!
+//! [`grapheme`] splits text into extended grapheme clusters (UAX #29),
+//! [`width`] gives the terminal column width of characters and clusters
+//! (combining marks take none, East Asian wide characters take two) and
+//! [`bidi`] resolves embedding levels and display order with the Unicode
+//! Bidirectional Algorithm (UAX #9). Font selection and drawing are left to
+//! the caller: libgfx lays text out with these, libvt sizes terminal cells.
+//!
+//! The property tables are compact range tables covering the scripts in
+//! common use rather than the full Unicode Character Database; characters
+//! they miss fall back to the most common property (a narrow, strong
+//! left-to-right character that forms a cluster of its own).
+
+#![no_std]
+extern crate alloc;
+
+pub mod bidi;
+pub mod grapheme;
+mod tables;
+pub mod width;
+
+pub use crate::bidi::{BidiClass, Direction};
+pub use crate::grapheme::{grapheme_indices, graphemes};
+pub use crate::width::{char_width, cluster_width, str_width};
+
+/// Whether `ch` is a nonspacing or enclosing combining mark (general
+/// category Mn or Me), drawn over the character before it.
+pub fn is_combining_mark(ch: char) -> bool {
+    tables::in_table(ch, tables::COMBINING_MARKS)
+}
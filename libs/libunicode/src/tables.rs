@@ -0,0 +1,363 @@
+//! Character property range tables.
+//!
+//! Every table is sorted by first code point with non-overlapping inclusive
+//! ranges, so lookups are a binary search.
+
+use crate::bidi::BidiClass;
+
+/// Whether `ch` falls in one of `table`'s ranges.
+pub(crate) fn in_table(ch: char, table: &[(u32, u32)]) -> bool {
+    let c = ch as u32;
+    table
+        .binary_search_by(|&(lo, hi)| {
+            if hi < c {
+                core::cmp::Ordering::Less
+            } else if lo > c {
+                core::cmp::Ordering::Greater
+            } else {
+                core::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// The value of the range containing `ch`, if any.
+pub(crate) fn lookup<T: Copy>(ch: char, table: &[(u32, u32, T)]) -> Option<T> {
+    let c = ch as u32;
+    table
+        .binary_search_by(|&(lo, hi, _)| {
+            if hi < c {
+                core::cmp::Ordering::Less
+            } else if lo > c {
+                core::cmp::Ordering::Greater
+            } else {
+                core::cmp::Ordering::Equal
+            }
+        })
+        .ok()
+        .map(|i| table[i].2)
+}
+
+/// Nonspacing and enclosing marks (general categories Mn and Me).
+#[rustfmt::skip]
+pub(crate) const COMBINING_MARKS: &[(u32, u32)] = &[
+    (0x0300, 0x036F), (0x0483, 0x0489), (0x0591, 0x05BD), (0x05BF, 0x05BF),
+    (0x05C1, 0x05C2), (0x05C4, 0x05C5), (0x05C7, 0x05C7), (0x0610, 0x061A),
+    (0x064B, 0x065F), (0x0670, 0x0670), (0x06D6, 0x06DC), (0x06DF, 0x06E4),
+    (0x06E7, 0x06E8), (0x06EA, 0x06ED), (0x0711, 0x0711), (0x0730, 0x074A),
+    (0x07A6, 0x07B0), (0x07EB, 0x07F3), (0x07FD, 0x07FD), (0x0816, 0x0819),
+    (0x081B, 0x0823), (0x0825, 0x0827), (0x0829, 0x082D), (0x0859, 0x085B),
+    (0x0898, 0x089F), (0x08CA, 0x08E1), (0x08E3, 0x0902), (0x093A, 0x093A),
+    (0x093C, 0x093C), (0x0941, 0x0948), (0x094D, 0x094D), (0x0951, 0x0957),
+    (0x0962, 0x0963), (0x0981, 0x0981), (0x09BC, 0x09BC), (0x09C1, 0x09C4),
+    (0x09CD, 0x09CD), (0x09E2, 0x09E3), (0x09FE, 0x09FE), (0x0A01, 0x0A02),
+    (0x0A3C, 0x0A3C), (0x0A41, 0x0A42), (0x0A47, 0x0A48), (0x0A4B, 0x0A4D),
+    (0x0A51, 0x0A51), (0x0A70, 0x0A71), (0x0A75, 0x0A75), (0x0A81, 0x0A82),
+    (0x0ABC, 0x0ABC), (0x0AC1, 0x0AC5), (0x0AC7, 0x0AC8), (0x0ACD, 0x0ACD),
+    (0x0AE2, 0x0AE3), (0x0AFA, 0x0AFF), (0x0B01, 0x0B01), (0x0B3C, 0x0B3C),
+    (0x0B3F, 0x0B3F), (0x0B41, 0x0B44), (0x0B4D, 0x0B4D), (0x0B55, 0x0B56),
+    (0x0B62, 0x0B63), (0x0B82, 0x0B82), (0x0BC0, 0x0BC0), (0x0BCD, 0x0BCD),
+    (0x0C00, 0x0C00), (0x0C04, 0x0C04), (0x0C3C, 0x0C3C), (0x0C3E, 0x0C40),
+    (0x0C46, 0x0C48), (0x0C4A, 0x0C4D), (0x0C55, 0x0C56), (0x0C62, 0x0C63),
+    (0x0C81, 0x0C81), (0x0CBC, 0x0CBC), (0x0CBF, 0x0CBF), (0x0CC6, 0x0CC6),
+    (0x0CCC, 0x0CCD), (0x0CE2, 0x0CE3), (0x0D00, 0x0D01), (0x0D3B, 0x0D3C),
+    (0x0D41, 0x0D44), (0x0D4D, 0x0D4D), (0x0D62, 0x0D63), (0x0D81, 0x0D81),
+    (0x0DCA, 0x0DCA), (0x0DD2, 0x0DD4), (0x0DD6, 0x0DD6), (0x0E31, 0x0E31),
+    (0x0E34, 0x0E3A), (0x0E47, 0x0E4E), (0x0EB1, 0x0EB1), (0x0EB4, 0x0EBC),
+    (0x0EC8, 0x0ECE), (0x0F18, 0x0F19), (0x0F35, 0x0F35), (0x0F37, 0x0F37),
+    (0x0F39, 0x0F39), (0x0F71, 0x0F7E), (0x0F80, 0x0F84), (0x0F86, 0x0F87),
+    (0x0F8D, 0x0F97), (0x0F99, 0x0FBC), (0x0FC6, 0x0FC6), (0x102D, 0x1030),
+    (0x1032, 0x1037), (0x1039, 0x103A), (0x103D, 0x103E), (0x1058, 0x1059),
+    (0x105E, 0x1060), (0x1071, 0x1074), (0x1082, 0x1082), (0x1085, 0x1086),
+    (0x108D, 0x108D), (0x109D, 0x109D), (0x135D, 0x135F), (0x1712, 0x1714),
+    (0x1732, 0x1733), (0x1752, 0x1753), (0x1772, 0x1773), (0x17B4, 0x17B5),
+    (0x17B7, 0x17BD), (0x17C6, 0x17C6), (0x17C9, 0x17D3), (0x17DD, 0x17DD),
+    (0x180B, 0x180D), (0x180F, 0x180F), (0x1885, 0x1886), (0x18A9, 0x18A9),
+    (0x1920, 0x1922), (0x1927, 0x1928), (0x1932, 0x1932), (0x1939, 0x193B),
+    (0x1A17, 0x1A18), (0x1A1B, 0x1A1B), (0x1A56, 0x1A56), (0x1A58, 0x1A5E),
+    (0x1A60, 0x1A60), (0x1A62, 0x1A62), (0x1A65, 0x1A6C), (0x1A73, 0x1A7C),
+    (0x1A7F, 0x1A7F), (0x1AB0, 0x1ACE), (0x1B00, 0x1B03), (0x1B34, 0x1B34),
+    (0x1B36, 0x1B3A), (0x1B3C, 0x1B3C), (0x1B42, 0x1B42), (0x1B6B, 0x1B73),
+    (0x1B80, 0x1B81), (0x1BA2, 0x1BA5), (0x1BA8, 0x1BA9), (0x1BAB, 0x1BAD),
+    (0x1BE6, 0x1BE6), (0x1BE8, 0x1BE9), (0x1BED, 0x1BED), (0x1BEF, 0x1BF1),
+    (0x1C2C, 0x1C33), (0x1C36, 0x1C37), (0x1CD0, 0x1CD2), (0x1CD4, 0x1CE0),
+    (0x1CE2, 0x1CE8), (0x1CED, 0x1CED), (0x1CF4, 0x1CF4), (0x1CF8, 0x1CF9),
+    (0x1DC0, 0x1DFF), (0x20D0, 0x20F0), (0x2CEF, 0x2CF1), (0x2D7F, 0x2D7F),
+    (0x2DE0, 0x2DFF), (0x302A, 0x302D), (0x3099, 0x309A), (0xA66F, 0xA672),
+    (0xA674, 0xA67D), (0xA69E, 0xA69F), (0xA6F0, 0xA6F1), (0xA802, 0xA802),
+    (0xA806, 0xA806), (0xA80B, 0xA80B), (0xA825, 0xA826), (0xA82C, 0xA82C),
+    (0xA8C4, 0xA8C5), (0xA8E0, 0xA8F1), (0xA8FF, 0xA8FF), (0xA926, 0xA92D),
+    (0xA947, 0xA951), (0xA980, 0xA982), (0xA9B3, 0xA9B3), (0xA9B6, 0xA9B9),
+    (0xA9BC, 0xA9BD), (0xA9E5, 0xA9E5), (0xAA29, 0xAA2E), (0xAA31, 0xAA32),
+    (0xAA35, 0xAA36), (0xAA43, 0xAA43), (0xAA4C, 0xAA4C), (0xAA7C, 0xAA7C),
+    (0xAAB0, 0xAAB0), (0xAAB2, 0xAAB4), (0xAAB7, 0xAAB8), (0xAABE, 0xAABF),
+    (0xAAC1, 0xAAC1), (0xAAEC, 0xAAED), (0xAAF6, 0xAAF6), (0xABE5, 0xABE5),
+    (0xABE8, 0xABE8), (0xABED, 0xABED), (0xFB1E, 0xFB1E), (0xFE00, 0xFE0F),
+    (0xFE20, 0xFE2F), (0x101FD, 0x101FD), (0x102E0, 0x102E0), (0x10376, 0x1037A),
+    (0x10A01, 0x10A03), (0x10A05, 0x10A06), (0x10A0C, 0x10A0F), (0x10A38, 0x10A3A),
+    (0x10A3F, 0x10A3F), (0x10AE5, 0x10AE6), (0x10D24, 0x10D27), (0x10EAB, 0x10EAC),
+    (0x10F46, 0x10F50), (0x11001, 0x11001), (0x11038, 0x11046), (0x1107F, 0x11081),
+    (0x110B3, 0x110B6), (0x110B9, 0x110BA), (0x11100, 0x11102), (0x11127, 0x1112B),
+    (0x1112D, 0x11134), (0x11173, 0x11173), (0x11180, 0x11181), (0x111B6, 0x111BE),
+    (0x16AF0, 0x16AF4), (0x16B30, 0x16B36), (0x16F8F, 0x16F92), (0x1BC9D, 0x1BC9E),
+    (0x1CF00, 0x1CF46), (0x1D167, 0x1D169), (0x1D17B, 0x1D182), (0x1D185, 0x1D18B),
+    (0x1D1AA, 0x1D1AD), (0x1D242, 0x1D244), (0x1DA00, 0x1DA36), (0x1E000, 0x1E02A),
+    (0x1E130, 0x1E136), (0x1E2EC, 0x1E2EF), (0x1E8D0, 0x1E8D6), (0x1E944, 0x1E94A),
+    (0xE0100, 0xE01EF),
+];
+
+/// Format characters that occupy no column: bidi controls, zero-width
+/// spaces and joiners, the BOM and tag characters.
+#[rustfmt::skip]
+pub(crate) const ZERO_WIDTH_FORMAT: &[(u32, u32)] = &[
+    (0x061C, 0x061C), (0x180E, 0x180E), (0x200B, 0x200F), (0x202A, 0x202E),
+    (0x2060, 0x2064), (0x2066, 0x206F), (0xFEFF, 0xFEFF), (0xE0000, 0xE007F),
+];
+
+/// East Asian Wide and Fullwidth characters, including emoji presentation.
+#[rustfmt::skip]
+pub(crate) const WIDE: &[(u32, u32)] = &[
+    (0x1100, 0x115F), (0x231A, 0x231B), (0x2329, 0x232A), (0x23E9, 0x23EC),
+    (0x23F0, 0x23F0), (0x23F3, 0x23F3), (0x25FD, 0x25FE), (0x2614, 0x2615),
+    (0x2648, 0x2653), (0x267F, 0x267F), (0x2693, 0x2693), (0x26A1, 0x26A1),
+    (0x26AA, 0x26AB), (0x26BD, 0x26BE), (0x26C4, 0x26C5), (0x26CE, 0x26CE),
+    (0x26D4, 0x26D4), (0x26EA, 0x26EA), (0x26F2, 0x26F3), (0x26F5, 0x26F5),
+    (0x26FA, 0x26FA), (0x26FD, 0x26FD), (0x2705, 0x2705), (0x270A, 0x270B),
+    (0x2728, 0x2728), (0x274C, 0x274C), (0x274E, 0x274E), (0x2753, 0x2755),
+    (0x2757, 0x2757), (0x2795, 0x2797), (0x27B0, 0x27B0), (0x27BF, 0x27BF),
+    (0x2B1B, 0x2B1C), (0x2B50, 0x2B50), (0x2B55, 0x2B55), (0x2E80, 0x303E),
+    (0x3041, 0x33FF), (0x3400, 0x4DBF), (0x4E00, 0x9FFF), (0xA000, 0xA4CF),
+    (0xA960, 0xA97F), (0xAC00, 0xD7A3), (0xF900, 0xFAFF), (0xFE10, 0xFE19),
+    (0xFE30, 0xFE6F), (0xFF00, 0xFF60), (0xFFE0, 0xFFE6), (0x16FE0, 0x16FE4),
+    (0x17000, 0x18CFF), (0x1AFF0, 0x1B2FF), (0x1F004, 0x1F004), (0x1F0CF, 0x1F0CF),
+    (0x1F18E, 0x1F18E), (0x1F191, 0x1F19A), (0x1F200, 0x1F202), (0x1F210, 0x1F23B),
+    (0x1F240, 0x1F248), (0x1F250, 0x1F251), (0x1F260, 0x1F265), (0x1F300, 0x1F320),
+    (0x1F32D, 0x1F335), (0x1F337, 0x1F37C), (0x1F37E, 0x1F393), (0x1F3A0, 0x1F3CA),
+    (0x1F3CF, 0x1F3D3), (0x1F3E0, 0x1F3F0), (0x1F3F4, 0x1F3F4), (0x1F3F8, 0x1F43E),
+    (0x1F440, 0x1F440), (0x1F442, 0x1F4FC), (0x1F4FF, 0x1F53D), (0x1F54B, 0x1F54E),
+    (0x1F550, 0x1F567), (0x1F57A, 0x1F57A), (0x1F595, 0x1F596), (0x1F5A4, 0x1F5A4),
+    (0x1F5FB, 0x1F64F), (0x1F680, 0x1F6C5), (0x1F6CC, 0x1F6CC), (0x1F6D0, 0x1F6D2),
+    (0x1F6D5, 0x1F6D7), (0x1F6DC, 0x1F6DF), (0x1F6EB, 0x1F6EC), (0x1F6F4, 0x1F6FC),
+    (0x1F7E0, 0x1F7EB), (0x1F7F0, 0x1F7F0), (0x1F90C, 0x1F93A), (0x1F93C, 0x1F945),
+    (0x1F947, 0x1F9FF), (0x1FA70, 0x1FAFF), (0x20000, 0x2FFFD), (0x30000, 0x3FFFD),
+];
+
+/// Spacing combining marks that extend a grapheme cluster
+/// (Grapheme_Cluster_Break=SpacingMark).
+#[rustfmt::skip]
+pub(crate) const SPACING_MARKS: &[(u32, u32)] = &[
+    (0x0903, 0x0903), (0x093B, 0x093B), (0x093E, 0x0940), (0x0949, 0x094C),
+    (0x094E, 0x094F), (0x0982, 0x0983), (0x09BF, 0x09C0), (0x09C7, 0x09C8),
+    (0x09CB, 0x09CC), (0x0A03, 0x0A03), (0x0A3E, 0x0A40), (0x0A83, 0x0A83),
+    (0x0ABE, 0x0AC0), (0x0AC9, 0x0AC9), (0x0ACB, 0x0ACC), (0x0B02, 0x0B03),
+    (0x0B40, 0x0B40), (0x0B47, 0x0B48), (0x0B4B, 0x0B4C), (0x0BBF, 0x0BBF),
+    (0x0BC1, 0x0BC2), (0x0BC6, 0x0BC8), (0x0BCA, 0x0BCC), (0x0C01, 0x0C03),
+    (0x0C41, 0x0C44), (0x0C82, 0x0C83), (0x0CBE, 0x0CBE), (0x0CC0, 0x0CC1),
+    (0x0CC3, 0x0CC4), (0x0CC7, 0x0CC8), (0x0CCA, 0x0CCB), (0x0D02, 0x0D03),
+    (0x0D3F, 0x0D40), (0x0D46, 0x0D48), (0x0D4A, 0x0D4C), (0x0D82, 0x0D83),
+    (0x0DD0, 0x0DD1), (0x0DD8, 0x0DDE), (0x0DF2, 0x0DF3), (0x0E33, 0x0E33),
+    (0x0EB3, 0x0EB3), (0x0F3E, 0x0F3F), (0x0F7F, 0x0F7F), (0x1031, 0x1031),
+    (0x103B, 0x103C), (0x1056, 0x1057), (0x1084, 0x1084), (0x17B6, 0x17B6),
+    (0x17BE, 0x17C5), (0x17C7, 0x17C8), (0x1923, 0x1926), (0x1929, 0x192B),
+    (0x1930, 0x1931), (0x1933, 0x1938), (0x1A19, 0x1A1A), (0x1A55, 0x1A55),
+    (0x1A57, 0x1A57), (0x1A6D, 0x1A72), (0x1B04, 0x1B04), (0x1B3B, 0x1B3B),
+    (0x1B3D, 0x1B41), (0x1B43, 0x1B44), (0x1B82, 0x1B82), (0x1BA1, 0x1BA1),
+    (0x1BA6, 0x1BA7), (0x1BAA, 0x1BAA), (0x1BE7, 0x1BE7), (0x1BEA, 0x1BEC),
+    (0x1BEE, 0x1BEE), (0x1BF2, 0x1BF3), (0x1C24, 0x1C2B), (0x1C34, 0x1C35),
+    (0x1CE1, 0x1CE1), (0x1CF7, 0x1CF7), (0xA823, 0xA824), (0xA827, 0xA827),
+    (0xA880, 0xA881), (0xA8B4, 0xA8C3), (0xA952, 0xA953), (0xA983, 0xA983),
+    (0xA9B4, 0xA9B5), (0xA9BA, 0xA9BB), (0xA9BE, 0xA9C0), (0xAA2F, 0xAA30),
+    (0xAA33, 0xAA34), (0xAA4D, 0xAA4D), (0xAAEB, 0xAAEB), (0xAAEE, 0xAAEF),
+    (0xAAF5, 0xAAF5), (0xABE3, 0xABE4), (0xABE6, 0xABE7), (0xABE9, 0xABEA),
+    (0xABEC, 0xABEC),
+];
+
+/// Prepended concatenation marks (Grapheme_Cluster_Break=Prepend).
+#[rustfmt::skip]
+pub(crate) const PREPEND: &[(u32, u32)] = &[
+    (0x0600, 0x0605), (0x06DD, 0x06DD), (0x070F, 0x070F), (0x0890, 0x0891),
+    (0x08E2, 0x08E2), (0x0D4E, 0x0D4E), (0x110BD, 0x110BD), (0x110CD, 0x110CD),
+    (0x111C2, 0x111C3),
+];
+
+/// Format and separator characters that break clusters on both sides
+/// (Grapheme_Cluster_Break=Control, beyond the C0 and C1 controls).
+#[rustfmt::skip]
+pub(crate) const CONTROL: &[(u32, u32)] = &[
+    (0x00AD, 0x00AD), (0x061C, 0x061C), (0x180E, 0x180E), (0x200B, 0x200B),
+    (0x200E, 0x200F), (0x2028, 0x202E), (0x2060, 0x206F), (0xFEFF, 0xFEFF),
+    (0xFFF0, 0xFFFB), (0x13430, 0x1343F), (0x1BCA0, 0x1BCA3), (0x1D173, 0x1D17A),
+    (0xE0000, 0xE001F), (0xE0080, 0xE00FF), (0xE01F0, 0xE0FFF),
+];
+
+/// Extended_Pictographic: emoji and the symbols that can start an emoji
+/// ZWJ sequence.
+#[rustfmt::skip]
+pub(crate) const PICTOGRAPHIC: &[(u32, u32)] = &[
+    (0x00A9, 0x00A9), (0x00AE, 0x00AE), (0x203C, 0x203C), (0x2049, 0x2049),
+    (0x2122, 0x2122), (0x2139, 0x2139), (0x2194, 0x2199), (0x21A9, 0x21AA),
+    (0x231A, 0x231B), (0x2328, 0x2328), (0x2388, 0x2388), (0x23CF, 0x23CF),
+    (0x23E9, 0x23F3), (0x23F8, 0x23FA), (0x24C2, 0x24C2), (0x25AA, 0x25AB),
+    (0x25B6, 0x25B6), (0x25C0, 0x25C0), (0x25FB, 0x25FE), (0x2600, 0x2605),
+    (0x2607, 0x2612), (0x2614, 0x2685), (0x2690, 0x2705), (0x2708, 0x2712),
+    (0x2714, 0x2714), (0x2716, 0x2716), (0x271D, 0x271D), (0x2721, 0x2721),
+    (0x2728, 0x2728), (0x2733, 0x2734), (0x2744, 0x2744), (0x2747, 0x2747),
+    (0x274C, 0x274C), (0x274E, 0x274E), (0x2753, 0x2755), (0x2757, 0x2757),
+    (0x2763, 0x2767), (0x2795, 0x2797), (0x27A1, 0x27A1), (0x27B0, 0x27B0),
+    (0x27BF, 0x27BF), (0x2934, 0x2935), (0x2B05, 0x2B07), (0x2B1B, 0x2B1C),
+    (0x2B50, 0x2B50), (0x2B55, 0x2B55), (0x3030, 0x3030), (0x303D, 0x303D),
+    (0x3297, 0x3297), (0x3299, 0x3299), (0x1F000, 0x1F0FF), (0x1F10D, 0x1F10F),
+    (0x1F12F, 0x1F12F), (0x1F16C, 0x1F171), (0x1F17E, 0x1F17F), (0x1F18E, 0x1F18E),
+    (0x1F191, 0x1F19A), (0x1F1AD, 0x1F1E5), (0x1F201, 0x1F20F), (0x1F21A, 0x1F21A),
+    (0x1F22F, 0x1F22F), (0x1F232, 0x1F23A), (0x1F23C, 0x1F23F), (0x1F249, 0x1F3FA),
+    (0x1F400, 0x1F53D), (0x1F546, 0x1F64F), (0x1F680, 0x1F6FF), (0x1F774, 0x1F77F),
+    (0x1F7D5, 0x1F7FF), (0x1F80C, 0x1F80F), (0x1F848, 0x1F84F), (0x1F85A, 0x1F85F),
+    (0x1F888, 0x1F88F), (0x1F8AE, 0x1F8FF), (0x1F90C, 0x1F93A), (0x1F93C, 0x1F945),
+    (0x1F947, 0x1FAFF), (0x1FC00, 0x1FFFD),
+];
+
+/// Bidi classes of everything that is not strong left-to-right. Combining
+/// marks (NSM) are found through [`COMBINING_MARKS`] first and so are not
+/// repeated here.
+#[rustfmt::skip]
+pub(crate) const BIDI_CLASSES: &[(u32, u32, BidiClass)] = {
+    use BidiClass::*;
+    &[
+        (0x0000, 0x0008, BN), (0x0009, 0x0009, S), (0x000A, 0x000A, B), (0x000B, 0x000B, S),
+        (0x000C, 0x000C, WS), (0x000D, 0x000D, B), (0x000E, 0x001B, BN), (0x001C, 0x001E, B),
+        (0x001F, 0x001F, S), (0x0020, 0x0020, WS), (0x0021, 0x0022, ON), (0x0023, 0x0025, ET),
+        (0x0026, 0x002A, ON), (0x002B, 0x002B, ES), (0x002C, 0x002C, CS), (0x002D, 0x002D, ES),
+        (0x002E, 0x002F, CS), (0x0030, 0x0039, EN), (0x003A, 0x003A, CS), (0x003B, 0x0040, ON),
+        (0x005B, 0x0060, ON), (0x007B, 0x007E, ON), (0x007F, 0x0084, BN), (0x0085, 0x0085, B),
+        (0x0086, 0x009F, BN), (0x00A0, 0x00A0, CS), (0x00A1, 0x00A1, ON), (0x00A2, 0x00A5, ET),
+        (0x00A6, 0x00A9, ON), (0x00AB, 0x00AC, ON), (0x00AD, 0x00AD, BN), (0x00AE, 0x00AF, ON),
+        (0x00B0, 0x00B1, ET), (0x00B2, 0x00B3, EN), (0x00B4, 0x00B4, ON), (0x00B6, 0x00B8, ON),
+        (0x00B9, 0x00B9, EN), (0x00BB, 0x00BF, ON), (0x00D7, 0x00D7, ON), (0x00F7, 0x00F7, ON),
+        (0x02B9, 0x02BA, ON), (0x02C2, 0x02CF, ON), (0x02D2, 0x02DF, ON), (0x02E5, 0x02ED, ON),
+        (0x02EF, 0x02FF, ON), (0x0374, 0x0375, ON), (0x037E, 0x037E, ON), (0x0384, 0x0385, ON),
+        (0x0387, 0x0387, ON), (0x03F6, 0x03F6, ON), (0x058A, 0x058A, ON), (0x058D, 0x058E, ON),
+        (0x058F, 0x058F, ET), (0x0590, 0x05FF, R), (0x0600, 0x0605, AN), (0x0606, 0x0607, ON),
+        (0x0608, 0x0608, AL), (0x0609, 0x060A, ET), (0x060B, 0x060B, AL), (0x060C, 0x060C, CS),
+        (0x060D, 0x060D, AL), (0x060E, 0x060F, ON), (0x0610, 0x065F, AL), (0x0660, 0x0669, AN),
+        (0x066A, 0x066A, ET), (0x066B, 0x066C, AN), (0x066D, 0x06DC, AL), (0x06DD, 0x06DD, AN),
+        (0x06DE, 0x06DE, ON), (0x06DF, 0x06E8, AL), (0x06E9, 0x06E9, ON), (0x06EA, 0x06EF, AL),
+        (0x06F0, 0x06F9, EN), (0x06FA, 0x07BF, AL), (0x07C0, 0x07F5, R), (0x07F6, 0x07F9, ON),
+        (0x07FA, 0x085F, R), (0x0860, 0x088F, AL), (0x0890, 0x0891, AN), (0x0892, 0x08E1, AL),
+        (0x08E2, 0x08E2, AN), (0x08E3, 0x08FF, AL), (0x0BF3, 0x0BF8, ON), (0x0BF9, 0x0BF9, ET),
+        (0x0BFA, 0x0BFA, ON), (0x0E3F, 0x0E3F, ET), (0x0F3A, 0x0F3D, ON), (0x1680, 0x1680, WS),
+        (0x169B, 0x169C, ON), (0x17DB, 0x17DB, ET), (0x1800, 0x180A, ON), (0x180E, 0x180E, BN),
+        (0x2000, 0x200A, WS), (0x200B, 0x200D, BN), (0x200F, 0x200F, R), (0x2010, 0x2027, ON),
+        (0x2028, 0x2028, WS), (0x2029, 0x2029, B), (0x202A, 0x202A, LRE), (0x202B, 0x202B, RLE),
+        (0x202C, 0x202C, PDF), (0x202D, 0x202D, LRO), (0x202E, 0x202E, RLO), (0x202F, 0x202F, CS),
+        (0x2030, 0x2034, ET), (0x2035, 0x2043, ON), (0x2044, 0x2044, CS), (0x2045, 0x205E, ON),
+        (0x205F, 0x205F, WS), (0x2060, 0x2064, BN), (0x2066, 0x2066, LRI), (0x2067, 0x2067, RLI),
+        (0x2068, 0x2068, FSI), (0x2069, 0x2069, PDI), (0x206A, 0x206F, BN), (0x2070, 0x2070, EN),
+        (0x2074, 0x2079, EN), (0x207A, 0x207B, ES), (0x207C, 0x207E, ON), (0x2080, 0x2089, EN),
+        (0x208A, 0x208B, ES), (0x208C, 0x208E, ON), (0x20A0, 0x20CF, ET), (0x2100, 0x2101, ON),
+        (0x2103, 0x2106, ON), (0x2108, 0x2109, ON), (0x2114, 0x2114, ON), (0x2116, 0x2118, ON),
+        (0x211E, 0x2123, ON), (0x2125, 0x2125, ON), (0x2127, 0x2127, ON), (0x2129, 0x2129, ON),
+        (0x212E, 0x212E, ET), (0x213A, 0x213B, ON), (0x2140, 0x2144, ON), (0x214A, 0x214D, ON),
+        (0x2150, 0x215F, ON), (0x2189, 0x218B, ON), (0x2190, 0x2211, ON), (0x2212, 0x2212, ES),
+        (0x2213, 0x2213, ET), (0x2214, 0x2335, ON), (0x237B, 0x2394, ON), (0x2396, 0x2426, ON),
+        (0x2440, 0x244A, ON), (0x2460, 0x2487, ON), (0x2488, 0x249B, EN), (0x24EA, 0x26AB, ON),
+        (0x26AD, 0x27FF, ON), (0x2900, 0x2B73, ON), (0x2B76, 0x2B95, ON), (0x2B97, 0x2BFF, ON),
+        (0x2CE5, 0x2CEA, ON), (0x2CF9, 0x2CFF, ON), (0x2E00, 0x2E5D, ON), (0x2E80, 0x2FFB, ON),
+        (0x3000, 0x3000, WS), (0x3001, 0x3004, ON), (0x3008, 0x3020, ON), (0x3030, 0x3030, ON),
+        (0x3036, 0x3037, ON), (0x303D, 0x303F, ON), (0x309B, 0x309C, ON), (0x30A0, 0x30A0, ON),
+        (0x30FB, 0x30FB, ON), (0x31C0, 0x31E3, ON), (0x321D, 0x321E, ON), (0x3250, 0x325F, ON),
+        (0x327C, 0x327E, ON), (0x32B1, 0x32BF, ON), (0x32CC, 0x32CF, ON), (0x3377, 0x337A, ON),
+        (0x33DE, 0x33DF, ON), (0x33FF, 0x33FF, ON), (0x4DC0, 0x4DFF, ON), (0xA490, 0xA4C6, ON),
+        (0xA60D, 0xA60F, ON), (0xA673, 0xA673, ON), (0xA67E, 0xA67F, ON), (0xA700, 0xA721, ON),
+        (0xA788, 0xA788, ON), (0xA828, 0xA82B, ON), (0xA838, 0xA839, ET), (0xA874, 0xA877, ON),
+        (0xFB1D, 0xFB28, R), (0xFB29, 0xFB29, ES), (0xFB2A, 0xFB4F, R), (0xFB50, 0xFD3D, AL),
+        (0xFD3E, 0xFD4F, ON), (0xFD50, 0xFDCF, AL), (0xFDF0, 0xFDFC, AL), (0xFDFD, 0xFDFF, ON),
+        (0xFE10, 0xFE19, ON), (0xFE30, 0xFE4F, ON), (0xFE50, 0xFE50, CS), (0xFE51, 0xFE51, ON),
+        (0xFE52, 0xFE52, CS), (0xFE54, 0xFE54, ON), (0xFE55, 0xFE55, CS), (0xFE56, 0xFE5E, ON),
+        (0xFE5F, 0xFE5F, ET), (0xFE60, 0xFE61, ON), (0xFE62, 0xFE63, ES), (0xFE64, 0xFE66, ON),
+        (0xFE68, 0xFE68, ON), (0xFE69, 0xFE6A, ET), (0xFE6B, 0xFE6B, ON), (0xFE70, 0xFEFE, AL),
+        (0xFEFF, 0xFEFF, BN), (0xFF01, 0xFF02, ON), (0xFF03, 0xFF05, ET), (0xFF06, 0xFF0A, ON),
+        (0xFF0B, 0xFF0B, ES), (0xFF0C, 0xFF0C, CS), (0xFF0D, 0xFF0D, ES), (0xFF0E, 0xFF0F, CS),
+        (0xFF10, 0xFF19, EN), (0xFF1A, 0xFF1A, CS), (0xFF1B, 0xFF20, ON), (0xFF3B, 0xFF40, ON),
+        (0xFF5B, 0xFF65, ON), (0xFFE0, 0xFFE1, ET), (0xFFE2, 0xFFE4, ON), (0xFFE5, 0xFFE6, ET),
+        (0xFFE8, 0xFFEE, ON), (0xFFF9, 0xFFFD, ON), (0x10800, 0x10CFF, R), (0x10D00, 0x10D2F, AL),
+        (0x10D30, 0x10D39, AN), (0x10D3A, 0x10E5F, R), (0x10E60, 0x10E7E, AN), (0x10E7F, 0x10F2F, R),
+        (0x10F30, 0x10F6F, AL), (0x10F70, 0x10FFF, R), (0x1D7CE, 0x1D7FF, EN), (0x1E800, 0x1EC6F, R),
+        (0x1EC70, 0x1ECBF, AL), (0x1ECC0, 0x1ECFF, R), (0x1ED00, 0x1ED4F, AL), (0x1ED50, 0x1EDFF, R),
+        (0x1EE00, 0x1EEFF, AL), (0x1EF00, 0x1EFFF, R), (0x1F000, 0x1F0FF, ON), (0x1F100, 0x1F10A, EN),
+        (0x1F10B, 0x1F10F, ON), (0x1F12F, 0x1F12F, ON), (0x1F16A, 0x1F16F, ON), (0x1F1AD, 0x1F1AD, ON),
+        (0x1F260, 0x1F265, ON), (0x1F300, 0x1FBEF, ON), (0x1FBF0, 0x1FBF9, EN), (0xE0001, 0xE0001, BN),
+        (0xE0020, 0xE007F, BN),
+    ]
+};
+
+/// Bidi_Mirroring_Glyph pairs, drawn swapped in right-to-left runs.
+#[rustfmt::skip]
+pub(crate) const MIRRORS: &[(u32, u32)] = &[
+    (0x0028, 0x0029), (0x0029, 0x0028), (0x003C, 0x003E), (0x003E, 0x003C),
+    (0x005B, 0x005D), (0x005D, 0x005B), (0x007B, 0x007D), (0x007D, 0x007B),
+    (0x00AB, 0x00BB), (0x00BB, 0x00AB), (0x2039, 0x203A), (0x203A, 0x2039),
+    (0x2045, 0x2046), (0x2046, 0x2045), (0x207D, 0x207E), (0x207E, 0x207D),
+    (0x208D, 0x208E), (0x208E, 0x208D), (0x2208, 0x220B), (0x2209, 0x220C),
+    (0x220A, 0x220D), (0x220B, 0x2208), (0x220C, 0x2209), (0x220D, 0x220A),
+    (0x2264, 0x2265), (0x2265, 0x2264), (0x226A, 0x226B), (0x226B, 0x226A),
+    (0x2282, 0x2283), (0x2283, 0x2282), (0x2286, 0x2287), (0x2287, 0x2286),
+    (0x2308, 0x2309), (0x2309, 0x2308), (0x230A, 0x230B), (0x230B, 0x230A),
+    (0x2329, 0x232A), (0x232A, 0x2329), (0x27E6, 0x27E7), (0x27E7, 0x27E6),
+    (0x27E8, 0x27E9), (0x27E9, 0x27E8), (0x3008, 0x3009), (0x3009, 0x3008),
+    (0x300A, 0x300B), (0x300B, 0x300A), (0x300C, 0x300D), (0x300D, 0x300C),
+    (0x300E, 0x300F), (0x300F, 0x300E), (0x3010, 0x3011), (0x3011, 0x3010),
+    (0xFF08, 0xFF09), (0xFF09, 0xFF08), (0xFF1C, 0xFF1E), (0xFF1E, 0xFF1C),
+    (0xFF3B, 0xFF3D), (0xFF3D, 0xFF3B), (0xFF5B, 0xFF5D), (0xFF5D, 0xFF5B),
+];
+
+/// Bidi_Paired_Bracket pairs as (opening, closing), for rule N0.
+#[rustfmt::skip]
+pub(crate) const BRACKETS: &[(u32, u32)] = &[
+    (0x0028, 0x0029), (0x005B, 0x005D), (0x007B, 0x007D), (0x0F3A, 0x0F3B),
+    (0x0F3C, 0x0F3D), (0x169B, 0x169C), (0x2045, 0x2046), (0x207D, 0x207E),
+    (0x208D, 0x208E), (0x2308, 0x2309), (0x230A, 0x230B), (0x2329, 0x232A),
+    (0x27E6, 0x27E7), (0x27E8, 0x27E9), (0x3008, 0x3009), (0x300A, 0x300B),
+    (0x300C, 0x300D), (0x300E, 0x300F), (0x3010, 0x3011), (0xFF08, 0xFF09),
+    (0xFF3B, 0xFF3D), (0xFF5B, 0xFF5D),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_sorted(name: &str, ranges: impl Iterator<Item = (u32, u32)>) {
+        let mut prev: Option<u32> = None;
+        for (lo, hi) in ranges {
+            assert!(lo <= hi, "{name}: inverted range {lo:#X}..{hi:#X}");
+            if let Some(end) = prev {
+                assert!(
+                    lo > end,
+                    "{name}: range {lo:#X} overlaps or is out of order"
+                );
+            }
+            prev = Some(hi);
+        }
+    }
+
+    #[test]
+    fn tables_are_sorted_and_disjoint() {
+        let plain: [(&str, &[(u32, u32)]); 7] = [
+            ("COMBINING_MARKS", COMBINING_MARKS),
+            ("ZERO_WIDTH_FORMAT", ZERO_WIDTH_FORMAT),
+            ("WIDE", WIDE),
+            ("SPACING_MARKS", SPACING_MARKS),
+            ("PREPEND", PREPEND),
+            ("CONTROL", CONTROL),
+            ("PICTOGRAPHIC", PICTOGRAPHIC),
+        ];
+        for (name, table) in plain {
+            assert_sorted(name, table.iter().copied());
+        }
+        assert_sorted(
+            "BIDI_CLASSES",
+            BIDI_CLASSES.iter().map(|&(lo, hi, _)| (lo, hi)),
+        );
+        assert_sorted("MIRRORS", MIRRORS.iter().map(|&(from, _)| (from, from)));
+        assert_sorted("BRACKETS", BRACKETS.iter().map(|&(open, _)| (open, open)));
+    }
+}
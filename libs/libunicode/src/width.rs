@@ -0,0 +1,76 @@
+//! Display width in terminal columns (`wcwidth`).
+//!
+//! Combining marks and zero-width format characters take no column, East
+//! Asian wide and fullwidth characters (CJK, Hangul, kana, emoji) take two
+//! and everything else takes one. A grapheme cluster is as wide as its
+//! widest character, so a base with marks stays one column and an emoji ZWJ
+//! sequence stays two.
+
+use crate::grapheme::{graphemes, is_pictographic};
+use crate::tables::{self, in_table};
+
+/// Emoji presentation selector: turns a text-style symbol into a wide emoji.
+const VS16: char = '\u{FE0F}';
+
+/// Columns occupied by `ch`: 0, 1 or 2. Control characters take none.
+pub fn char_width(ch: char) -> usize {
+    let c = ch as u32;
+    if (0x20..0x7F).contains(&c) {
+        return 1;
+    }
+    if c < 0x20 || (0x7F..0xA0).contains(&c) {
+        return 0;
+    }
+    // Hangul medial vowels and final consonants combine with the initial
+    if (0x1160..=0x11FF).contains(&c) || (0xD7B0..=0xD7FF).contains(&c) {
+        return 0;
+    }
+    if in_table(ch, tables::COMBINING_MARKS) || in_table(ch, tables::ZERO_WIDTH_FORMAT) {
+        return 0;
+    }
+    if in_table(ch, tables::WIDE) {
+        return 2;
+    }
+    1
+}
+
+/// Columns occupied by one grapheme cluster.
+pub fn cluster_width(cluster: &str) -> usize {
+    let mut chars = cluster.chars();
+    let Some(first) = chars.next() else {
+        return 0;
+    };
+    let mut width = char_width(first);
+    for ch in chars {
+        if ch == VS16 && is_pictographic(first) {
+            width = 2;
+        }
+        width = width.max(char_width(ch));
+    }
+    width
+}
+
+/// Columns occupied by a string.
+pub fn str_width(text: &str) -> usize {
+    graphemes(text).map(cluster_width).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths() {
+        assert_eq!(char_width('a'), 1);
+        assert_eq!(char_width('\u{301}'), 0);
+        assert_eq!(char_width('\u{200B}'), 0);
+        assert_eq!(char_width('\u{4E2D}'), 2);
+        assert_eq!(char_width('\u{FF21}'), 2);
+        assert_eq!(char_width('\u{1F600}'), 2);
+        assert_eq!(char_width('\u{3099}'), 0);
+        assert_eq!(char_width('\u{5D0}'), 1);
+        assert_eq!(str_width("e\u{301}\u{4E2D}\u{6587}"), 5);
+        assert_eq!(str_width("\u{1112}\u{1161}\u{11AB}"), 2);
+        assert_eq!(str_width("\u{2764}\u{FE0F}\u{2764}"), 3);
+    }
+}
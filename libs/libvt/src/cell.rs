@@ -0,0 +1,122 @@
+//! Character cells and their rendition attributes.
+
+use crate::color::{Color, Rgb};
+
+/// SGR rendition attributes of a cell, as a bit set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Attrs(u16);
+
+impl Attrs {
+    pub const BOLD: Attrs = Attrs(1 << 0);
+    pub const DIM: Attrs = Attrs(1 << 1);
+    pub const ITALIC: Attrs = Attrs(1 << 2);
+    pub const UNDERLINE: Attrs = Attrs(1 << 3);
+    pub const BLINK: Attrs = Attrs(1 << 4);
+    pub const INVERSE: Attrs = Attrs(1 << 5);
+    pub const HIDDEN: Attrs = Attrs(1 << 6);
+    pub const STRIKETHROUGH: Attrs = Attrs(1 << 7);
+    /// Not SGR: the cell holds a double-width character that also covers
+    /// the cell to its right.
+    pub const WIDE: Attrs = Attrs(1 << 8);
+    /// Not SGR: the right half of the double-width character to the left.
+    pub const WIDE_SPACER: Attrs = Attrs(1 << 9);
+
+    pub const fn empty() -> Self {
+        Attrs(0)
+    }
+
+    pub const fn contains(self, other: Attrs) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Attrs) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Attrs) {
+        self.0 &= !other.0;
+    }
+}
+
+/// Combining characters kept per cell; further ones are dropped.
+pub const MAX_COMBINING: usize = 2;
+
+/// One character position of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    /// Combining marks and joiners drawn with `ch`, '\0' when unused
+    pub combining: [char; MAX_COMBINING],
+    pub fg: Color,
+    pub bg: Color,
+    pub attrs: Attrs,
+}
+
+impl Cell {
+    pub const BLANK: Cell = Cell {
+        ch: ' ',
+        combining: ['\0'; MAX_COMBINING],
+        fg: Color::Default,
+        bg: Color::Default,
+        attrs: Attrs::empty(),
+    };
+
+    /// A blank cell carrying `pen`'s background, as left by erase operations.
+    pub const fn erased(pen: &Cell) -> Cell {
+        Cell {
+            ch: ' ',
+            combining: ['\0'; MAX_COMBINING],
+            fg: Color::Default,
+            bg: pen.bg,
+            attrs: Attrs::empty(),
+        }
+    }
+
+    /// The combining characters drawn with `ch`.
+    pub fn combining(&self) -> impl Iterator<Item = char> + '_ {
+        self.combining.iter().copied().take_while(|&c| c != '\0')
+    }
+
+    /// Attach a combining character, returning false if the cell is full.
+    pub fn push_combining(&mut self, c: char) -> bool {
+        match self.combining.iter_mut().find(|slot| **slot == '\0') {
+            Some(slot) => {
+                *slot = c;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The colors to paint this cell with, as (foreground, background).
+    ///
+    /// Bold brightens the eight normal ANSI colors, dim halves the
+    /// foreground, inverse swaps the pair and hidden paints the foreground
+    /// in the background color.
+    pub fn colors(&self, default_fg: Rgb, default_bg: Rgb) -> (Rgb, Rgb) {
+        let fg = match self.fg {
+            Color::Indexed(index @ 0..=7) if self.attrs.contains(Attrs::BOLD) => {
+                Color::Indexed(index + 8)
+            }
+            fg => fg,
+        };
+        let mut fg = fg.to_rgb(default_fg);
+        let mut bg = self.bg.to_rgb(default_bg);
+        if self.attrs.contains(Attrs::DIM) {
+            fg = Rgb::new(fg.r / 2, fg.g / 2, fg.b / 2);
+        }
+        if self.attrs.contains(Attrs::INVERSE) {
+            core::mem::swap(&mut fg, &mut bg);
+        }
+        if self.attrs.contains(Attrs::HIDDEN) {
+            fg = bg;
+        }
+        (fg, bg)
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell::BLANK
+    }
+}
@@ -0,0 +1,91 @@
+//! Cell colors and the xterm 256-color palette.
+
+/// An RGB triple.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// A foreground or background color as set by SGR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// The renderer's default foreground or background.
+    Default,
+    /// An entry of the 256-color palette (0-15 are the ANSI colors).
+    Indexed(u8),
+    /// A 24-bit color from `38;2;r;g;b` / `48;2;r;g;b`.
+    Rgb(Rgb),
+}
+
+impl Color {
+    /// Resolve to RGB, using `default` for [`Color::Default`].
+    pub fn to_rgb(self, default: Rgb) -> Rgb {
+        match self {
+            Color::Default => default,
+            Color::Indexed(index) => palette(index),
+            Color::Rgb(rgb) => rgb,
+        }
+    }
+}
+
+/// The 16 ANSI colors: normal 0-7, then bright 8-15.
+const ANSI: [Rgb; 16] = [
+    Rgb::new(0, 0, 0),
+    Rgb::new(205, 49, 49),
+    Rgb::new(13, 188, 121),
+    Rgb::new(229, 229, 16),
+    Rgb::new(36, 114, 200),
+    Rgb::new(188, 63, 188),
+    Rgb::new(17, 168, 205),
+    Rgb::new(229, 229, 229),
+    Rgb::new(102, 102, 102),
+    Rgb::new(241, 76, 76),
+    Rgb::new(35, 209, 139),
+    Rgb::new(245, 245, 67),
+    Rgb::new(59, 142, 234),
+    Rgb::new(214, 112, 214),
+    Rgb::new(41, 184, 219),
+    Rgb::new(255, 255, 255),
+];
+
+/// Look up an entry of the xterm 256-color palette.
+///
+/// 0-15 are the ANSI colors, 16-231 a 6x6x6 color cube and 232-255 a
+/// grayscale ramp from near-black to near-white.
+pub fn palette(index: u8) -> Rgb {
+    match index {
+        0..=15 => ANSI[index as usize],
+        16..=231 => {
+            let cube = index - 16;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            Rgb::new(level(cube / 36), level((cube / 6) % 6), level(cube % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            Rgb::new(gray, gray, gray)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_cube_and_ramp() {
+        assert_eq!(palette(1), Rgb::new(205, 49, 49));
+        assert_eq!(palette(16), Rgb::new(0, 0, 0));
+        assert_eq!(palette(196), Rgb::new(255, 0, 0));
+        assert_eq!(palette(231), Rgb::new(255, 255, 255));
+        assert_eq!(palette(232), Rgb::new(8, 8, 8));
+        assert_eq!(palette(255), Rgb::new(238, 238, 238));
+    }
+}
@@ -0,0 +1,144 @@
+//! Keyboard and mouse input encoding.
+//!
+//! The sequences a terminal sends to the program on its other end depend on
+//! modes the program set (application cursor keys, mouse tracking); the
+//! [`Terminal`](crate::Terminal) methods that produce them live alongside
+//! the types here.
+
+use alloc::vec::Vec;
+
+use crate::terminal::MouseMode;
+
+/// Keys whose sequence changes with DECCKM (application cursor keys).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorKey {
+    Up,
+    Down,
+    Right,
+    Left,
+    Home,
+    End,
+}
+
+impl CursorKey {
+    pub(crate) fn sequence(self, application: bool) -> &'static [u8] {
+        match (self, application) {
+            (CursorKey::Up, false) => b"\x1b[A",
+            (CursorKey::Down, false) => b"\x1b[B",
+            (CursorKey::Right, false) => b"\x1b[C",
+            (CursorKey::Left, false) => b"\x1b[D",
+            (CursorKey::Home, false) => b"\x1b[H",
+            (CursorKey::End, false) => b"\x1b[F",
+            (CursorKey::Up, true) => b"\x1bOA",
+            (CursorKey::Down, true) => b"\x1bOB",
+            (CursorKey::Right, true) => b"\x1bOC",
+            (CursorKey::Left, true) => b"\x1bOD",
+            (CursorKey::Home, true) => b"\x1bOH",
+            (CursorKey::End, true) => b"\x1bOF",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    WheelUp,
+    WheelDown,
+    /// Motion with no button held.
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseEventKind {
+    Press,
+    Release,
+    Move,
+}
+
+/// A mouse event in cell coordinates (0-based).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseEvent {
+    pub kind: MouseEventKind,
+    pub button: MouseButton,
+    pub col: usize,
+    pub row: usize,
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+}
+
+/// Encode `event` for the tracking mode in force, or `None` if the mode
+/// does not report it.
+pub(crate) fn encode_mouse(event: &MouseEvent, mode: MouseMode, sgr: bool) -> Option<Vec<u8>> {
+    let wheel = matches!(event.button, MouseButton::WheelUp | MouseButton::WheelDown);
+    let reported = match (mode, event.kind) {
+        (MouseMode::Off, _) => false,
+        (MouseMode::X10, kind) => kind == MouseEventKind::Press,
+        (_, MouseEventKind::Press) => true,
+        // Wheels have no release
+        (_, MouseEventKind::Release) => !wheel,
+        (MouseMode::Normal, MouseEventKind::Move) => false,
+        (MouseMode::ButtonMotion, MouseEventKind::Move) => event.button != MouseButton::None,
+        (MouseMode::AnyMotion, MouseEventKind::Move) => true,
+    };
+    if !reported {
+        return None;
+    }
+
+    let mut code: u32 = match event.button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+        MouseButton::None => 3,
+        MouseButton::WheelUp => 64,
+        MouseButton::WheelDown => 65,
+    };
+    // The legacy encoding cannot say which button was released
+    if event.kind == MouseEventKind::Release && !sgr {
+        code = 3;
+    }
+    if event.kind == MouseEventKind::Move {
+        code += 32;
+    }
+    if mode != MouseMode::X10 {
+        code += (event.shift as u32) * 4 + (event.alt as u32) * 8 + (event.ctrl as u32) * 16;
+    }
+
+    let col = event.col + 1;
+    let row = event.row + 1;
+    let mut out = Vec::new();
+    if sgr {
+        let fin = if event.kind == MouseEventKind::Release {
+            'm'
+        } else {
+            'M'
+        };
+        push_fmt(&mut out, format_args!("\x1b[<{code};{col};{row}{fin}"));
+    } else {
+        // Each value travels as one byte offset by 32
+        if col > 223 || row > 223 {
+            return None;
+        }
+        out.extend_from_slice(b"\x1b[M");
+        out.push(32 + code as u8);
+        out.push(32 + col as u8);
+        out.push(32 + row as u8);
+    }
+    Some(out)
+}
+
+/// Append formatted text to a byte buffer.
+pub(crate) fn push_fmt(out: &mut Vec<u8>, args: core::fmt::Arguments) {
+    struct Bytes<'a>(&'a mut Vec<u8>);
+
+    impl core::fmt::Write for Bytes<'_> {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.0.extend_from_slice(s.as_bytes());
+            Ok(())
+        }
+    }
+
+    let _ = core::fmt::Write::write_fmt(&mut Bytes(out), args);
+}
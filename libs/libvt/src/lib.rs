@@ -0,0 +1,27 @@
+//! VT/xterm terminal emulation.
+//!
+//! `#![no_std]` + `extern crate alloc`. Depends only on `libunicode`, for
+//! character widths and combining marks.
+//!
+//! [`Parser`] turns a byte stream into printable characters, control codes
+//! and escape sequences; [`Terminal`] applies them to a grid of [`Cell`]s
+//! with 256-color and 24-bit SGR, double-width characters and combining
+//! marks, the alternate screen buffer, scroll regions, scrollback, bracketed
+//! paste, xterm mouse reporting and OSC window titles. Rendering is left to
+//! the caller: it reads cells, resolves their colors against its own
+//! defaults and repaints what [`Terminal::damage`] reports.
+
+#![no_std]
+extern crate alloc;
+
+pub mod cell;
+pub mod color;
+pub mod input;
+pub mod parser;
+pub mod terminal;
+
+pub use crate::cell::{Attrs, Cell, MAX_COMBINING};
+pub use crate::color::{Color, Rgb};
+pub use crate::input::{CursorKey, MouseButton, MouseEvent, MouseEventKind};
+pub use crate::parser::{Params, Parser, Perform};
+pub use crate::terminal::{MouseMode, Terminal};
@@ -0,0 +1,427 @@
+//! Escape sequence parser.
+//!
+//! A byte-at-a-time state machine after Paul Williams' DEC ANSI parser, as
+//! used by xterm: it decodes UTF-8 text, splits out C0 controls and collects
+//! ESC, CSI and OSC sequences, handing each complete unit to a [`Perform`]
+//! implementation. DCS, SOS, PM and APC strings are consumed and dropped.
+
+use alloc::vec::Vec;
+
+/// Most parameters kept for one CSI sequence; later ones are dropped.
+pub const MAX_PARAMS: usize = 16;
+
+/// Most intermediate bytes of one sequence; longer sequences are ignored.
+const MAX_INTERMEDIATES: usize = 2;
+
+/// Longest OSC payload kept; the rest of a longer string is dropped.
+const MAX_OSC_LEN: usize = 1024;
+
+/// Receiver of the parser's output.
+pub trait Perform {
+    /// Draw a character at the cursor.
+    fn print(&mut self, c: char);
+
+    /// Run a C0 control (BEL, BS, HT, LF, VT, FF, CR, ...).
+    fn execute(&mut self, _byte: u8) {}
+
+    /// Run a CSI sequence. A private marker (`?`, `>`, `<`, `=`) comes first
+    /// in `intermediates`.
+    fn csi_dispatch(&mut self, _params: &Params, _intermediates: &[u8], _action: u8) {}
+
+    /// Run an ESC sequence.
+    fn esc_dispatch(&mut self, _intermediates: &[u8], _byte: u8) {}
+
+    /// Run an OSC string, with the terminator stripped.
+    fn osc_dispatch(&mut self, _data: &[u8]) {}
+}
+
+/// Numeric parameters of a CSI sequence.
+///
+/// Omitted parameters read as 0, which every command treats as its default.
+/// Parameters separated by `:` instead of `;` are marked as sub-parameters
+/// of the one before them (`38:2::255:0:0`).
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values: [u16; MAX_PARAMS],
+    len: usize,
+    /// Bit `i` is set when parameter `i` follows a `:`
+    subparams: u32,
+}
+
+impl Params {
+    /// Number of parameters, at least 1 once a sequence is dispatched.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Parameter `index`, or 0 if it was omitted.
+    pub fn get(&self, index: usize) -> u16 {
+        if index < self.len {
+            self.values[index]
+        } else {
+            0
+        }
+    }
+
+    /// Parameter `index`, or `default` if it was omitted or 0.
+    pub fn arg(&self, index: usize, default: u16) -> u16 {
+        match self.get(index) {
+            0 => default,
+            value => value,
+        }
+    }
+
+    /// Whether parameter `index` was joined to the previous one by `:`.
+    pub fn is_subparam(&self, index: usize) -> bool {
+        index < self.len && self.subparams & (1 << index) != 0
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+        self.subparams = 0;
+    }
+
+    fn push(&mut self, value: u16, subparam: bool) {
+        if self.len < MAX_PARAMS {
+            self.values[self.len] = value;
+            if subparam {
+                self.subparams |= 1 << self.len;
+            }
+            self.len += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    CsiEntry,
+    CsiParam,
+    CsiIntermediate,
+    CsiIgnore,
+    OscString,
+    /// DCS, SOS, PM and APC strings, skipped up to their terminator
+    IgnoreString,
+}
+
+/// The escape sequence state machine.
+pub struct Parser {
+    state: State,
+    params: Params,
+    /// Value of the parameter being read
+    param: u16,
+    /// Whether the parameter being read follows a `:`
+    param_is_sub: bool,
+    intermediates: [u8; MAX_INTERMEDIATES],
+    intermediate_len: usize,
+    /// Set when a sequence has too many intermediates to be understood
+    overflowed: bool,
+    osc: Vec<u8>,
+    /// Code point of the UTF-8 sequence being decoded
+    utf8_code: u32,
+    /// Continuation bytes still expected
+    utf8_needed: u8,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self {
+            state: State::Ground,
+            params: Params::default(),
+            param: 0,
+            param_is_sub: false,
+            intermediates: [0; MAX_INTERMEDIATES],
+            intermediate_len: 0,
+            overflowed: false,
+            osc: Vec::new(),
+            utf8_code: 0,
+            utf8_needed: 0,
+        }
+    }
+
+    /// Feed a run of bytes.
+    pub fn advance_all<P: Perform>(&mut self, performer: &mut P, bytes: &[u8]) {
+        for &byte in bytes {
+            self.advance(performer, byte);
+        }
+    }
+
+    /// Feed one byte.
+    pub fn advance<P: Perform>(&mut self, performer: &mut P, byte: u8) {
+        if self.state == State::Ground {
+            self.ground(performer, byte);
+            return;
+        }
+
+        // Controls that apply in every state but ground
+        match byte {
+            0x18 | 0x1A => {
+                // CAN and SUB abort the sequence
+                self.state = State::Ground;
+                performer.execute(byte);
+                return;
+            }
+            0x1B => {
+                if self.state == State::OscString {
+                    performer.osc_dispatch(&self.osc);
+                }
+                self.enter_escape();
+                return;
+            }
+            _ => {}
+        }
+
+        match self.state {
+            State::Ground => unreachable!(),
+            State::Escape => match byte {
+                0x00..=0x1F => performer.execute(byte),
+                0x20..=0x2F => {
+                    self.collect(byte);
+                    self.state = State::EscapeIntermediate;
+                }
+                b'[' => self.enter_csi(),
+                b']' => {
+                    self.osc.clear();
+                    self.state = State::OscString;
+                }
+                b'P' | b'X' | b'^' | b'_' => self.state = State::IgnoreString,
+                0x30..=0x7E => {
+                    performer.esc_dispatch(self.intermediates(), byte);
+                    self.state = State::Ground;
+                }
+                _ => {}
+            },
+            State::EscapeIntermediate => match byte {
+                0x00..=0x1F => performer.execute(byte),
+                0x20..=0x2F => self.collect(byte),
+                0x30..=0x7E => {
+                    if !self.overflowed {
+                        performer.esc_dispatch(self.intermediates(), byte);
+                    }
+                    self.state = State::Ground;
+                }
+                _ => {}
+            },
+            State::CsiEntry | State::CsiParam => match byte {
+                0x00..=0x1F => performer.execute(byte),
+                b'0'..=b'9' => {
+                    self.param = self
+                        .param
+                        .saturating_mul(10)
+                        .saturating_add((byte - b'0') as u16);
+                    self.state = State::CsiParam;
+                }
+                b';' | b':' => {
+                    self.params.push(self.param, self.param_is_sub);
+                    self.param = 0;
+                    self.param_is_sub = byte == b':';
+                    self.state = State::CsiParam;
+                }
+                b'<'..=b'?' => {
+                    if self.state == State::CsiEntry {
+                        self.collect(byte);
+                        self.state = State::CsiParam;
+                    } else {
+                        self.state = State::CsiIgnore;
+                    }
+                }
+                0x20..=0x2F => {
+                    self.collect(byte);
+                    self.state = State::CsiIntermediate;
+                }
+                0x40..=0x7E => self.dispatch_csi(performer, byte),
+                _ => {}
+            },
+            State::CsiIntermediate => match byte {
+                0x00..=0x1F => performer.execute(byte),
+                0x20..=0x2F => self.collect(byte),
+                0x30..=0x3F => self.state = State::CsiIgnore,
+                0x40..=0x7E => self.dispatch_csi(performer, byte),
+                _ => {}
+            },
+            State::CsiIgnore => match byte {
+                0x00..=0x1F => performer.execute(byte),
+                0x40..=0x7E => self.state = State::Ground,
+                _ => {}
+            },
+            State::OscString => match byte {
+                0x07 => {
+                    performer.osc_dispatch(&self.osc);
+                    self.state = State::Ground;
+                }
+                0x00..=0x1F => {}
+                _ => {
+                    if self.osc.len() < MAX_OSC_LEN {
+                        self.osc.push(byte);
+                    }
+                }
+            },
+            State::IgnoreString => {
+                if byte == 0x07 {
+                    self.state = State::Ground;
+                }
+            }
+        }
+    }
+
+    fn ground<P: Perform>(&mut self, performer: &mut P, byte: u8) {
+        if self.utf8_needed > 0 {
+            if byte & 0xC0 == 0x80 {
+                self.utf8_code = (self.utf8_code << 6) | (byte & 0x3F) as u32;
+                self.utf8_needed -= 1;
+                if self.utf8_needed == 0 {
+                    performer.print(char::from_u32(self.utf8_code).unwrap_or('\u{FFFD}'));
+                }
+                return;
+            }
+            // Truncated sequence: replace it, then handle this byte afresh
+            self.utf8_needed = 0;
+            performer.print('\u{FFFD}');
+        }
+
+        match byte {
+            0x1B => self.enter_escape(),
+            0x00..=0x1F => performer.execute(byte),
+            0x20..=0x7E => performer.print(byte as char),
+            0x7F => {}
+            0xC2..=0xDF => self.start_utf8(byte & 0x1F, 1),
+            0xE0..=0xEF => self.start_utf8(byte & 0x0F, 2),
+            0xF0..=0xF4 => self.start_utf8(byte & 0x07, 3),
+            _ => performer.print('\u{FFFD}'),
+        }
+    }
+
+    fn start_utf8(&mut self, bits: u8, needed: u8) {
+        self.utf8_code = bits as u32;
+        self.utf8_needed = needed;
+    }
+
+    fn enter_escape(&mut self) {
+        self.intermediate_len = 0;
+        self.overflowed = false;
+        self.state = State::Escape;
+    }
+
+    fn enter_csi(&mut self) {
+        self.params.clear();
+        self.param = 0;
+        self.param_is_sub = false;
+        self.intermediate_len = 0;
+        self.overflowed = false;
+        self.state = State::CsiEntry;
+    }
+
+    fn collect(&mut self, byte: u8) {
+        if self.intermediate_len < MAX_INTERMEDIATES {
+            self.intermediates[self.intermediate_len] = byte;
+            self.intermediate_len += 1;
+        } else {
+            self.overflowed = true;
+        }
+    }
+
+    fn intermediates(&self) -> &[u8] {
+        &self.intermediates[..self.intermediate_len]
+    }
+
+    fn dispatch_csi<P: Perform>(&mut self, performer: &mut P, action: u8) {
+        self.params.push(self.param, self.param_is_sub);
+        if !self.overflowed {
+            performer.csi_dispatch(&self.params, self.intermediates(), action);
+        }
+        self.state = State::Ground;
+    }
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec;
+
+    #[derive(Default)]
+    struct Log {
+        text: String,
+        controls: Vec<u8>,
+        csi: Vec<(Vec<u16>, Vec<u8>, u8)>,
+        osc: Vec<Vec<u8>>,
+    }
+
+    impl Perform for Log {
+        fn print(&mut self, c: char) {
+            self.text.push(c);
+        }
+
+        fn execute(&mut self, byte: u8) {
+            self.controls.push(byte);
+        }
+
+        fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], action: u8) {
+            let values = (0..params.len()).map(|i| params.get(i)).collect();
+            self.csi.push((values, intermediates.to_vec(), action));
+        }
+
+        fn osc_dispatch(&mut self, data: &[u8]) {
+            self.osc.push(data.to_vec());
+        }
+    }
+
+    fn parse(bytes: &[u8]) -> Log {
+        let mut log = Log::default();
+        Parser::new().advance_all(&mut log, bytes);
+        log
+    }
+
+    #[test]
+    fn decodes_utf8_and_replaces_bad_bytes() {
+        let log = parse("a\u{e9}\u{2500}\u{1F600}".as_bytes());
+        assert_eq!(log.text, "a\u{e9}\u{2500}\u{1F600}");
+        let log = parse(b"\xE2\x94x\xFF");
+        assert_eq!(log.text, "\u{FFFD}x\u{FFFD}");
+    }
+
+    #[test]
+    fn collects_csi_params_and_private_marker() {
+        let log = parse(b"\x1b[1;31m\x1b[?1049h\x1b[m");
+        assert_eq!(log.csi[0], (vec![1, 31], vec![], b'm'));
+        assert_eq!(log.csi[1], (vec![1049], vec![b'?'], b'h'));
+        assert_eq!(log.csi[2], (vec![0], vec![], b'm'));
+    }
+
+    #[test]
+    fn marks_colon_subparams() {
+        let mut log = Log::default();
+        let mut parser = Parser::new();
+        parser.advance_all(&mut log, b"\x1b[38:2::1:2:3m");
+        assert_eq!(log.csi[0].0, vec![38, 2, 0, 1, 2, 3]);
+        assert!(!parser.params.is_subparam(0));
+        assert!((1..6).all(|i| parser.params.is_subparam(i)));
+    }
+
+    #[test]
+    fn osc_ends_at_bel_or_st() {
+        let log = parse(b"\x1b]0;one\x07\x1b]2;two\x1b\\after");
+        assert_eq!(log.osc, vec![b"0;one".to_vec(), b"2;two".to_vec()]);
+        assert_eq!(log.text, "after");
+    }
+
+    #[test]
+    fn controls_execute_inside_sequences() {
+        let log = parse(b"\x1b[2\nJ\x1bP1$qm\x1b\\x");
+        assert_eq!(log.controls, vec![b'\n']);
+        assert_eq!(log.csi[0], (vec![2], vec![], b'J'));
+        assert_eq!(log.text, "x");
+    }
+}
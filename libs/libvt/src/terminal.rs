@@ -0,0 +1,1333 @@
+//! The terminal screen model.
+//!
+//! [`Terminal`] applies parser output to a grid of cells: cursor movement,
+//! erasing, insertion and deletion, SGR renditions, scroll regions, the
+//! alternate screen, tab stops and the DEC private modes programs rely on.
+//! Lines scrolled off the top of the primary screen go to a bounded
+//! scrollback buffer that the renderer can page through.
+//!
+//! Replies to queries (cursor position, device attributes) collect in a
+//! response buffer the caller writes back to the program.
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use libunicode::char_width;
+use libunicode::grapheme::is_extend;
+
+use crate::cell::{Attrs, Cell};
+use crate::color::{Color, Rgb};
+use crate::input::{encode_mouse, push_fmt, CursorKey, MouseEvent};
+use crate::parser::{Params, Parser, Perform};
+
+/// Scrollback lines kept unless the caller sets another limit.
+pub const DEFAULT_SCROLLBACK: usize = 1000;
+
+/// Longest window title kept from OSC 0/2, in characters.
+const MAX_TITLE_CHARS: usize = 256;
+
+/// Which mouse events are reported to the program (DECSET 9/1000/1002/1003).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseMode {
+    Off,
+    /// Button presses only
+    X10,
+    /// Presses and releases
+    Normal,
+    /// Presses, releases and motion while a button is held
+    ButtonMotion,
+    /// Presses, releases and all motion
+    AnyMotion,
+}
+
+/// Cursor state kept by DECSC / DECRC.
+#[derive(Debug, Clone, Copy)]
+struct SavedCursor {
+    x: usize,
+    y: usize,
+    pen: Cell,
+    origin: bool,
+    line_drawing: bool,
+}
+
+impl SavedCursor {
+    const HOME: SavedCursor = SavedCursor {
+        x: 0,
+        y: 0,
+        pen: Cell::BLANK,
+        origin: false,
+        line_drawing: false,
+    };
+}
+
+/// A VT/xterm terminal: screen contents, cursor, modes and scrollback.
+pub struct Terminal {
+    cols: usize,
+    rows: usize,
+    /// The screen being drawn on
+    lines: Vec<Vec<Cell>>,
+    /// The primary screen while the alternate one is shown
+    primary: Option<Vec<Vec<Cell>>>,
+    scrollback: VecDeque<Vec<Cell>>,
+    scrollback_limit: usize,
+    /// Lines of scrollback the view is scrolled back by (0 = live screen)
+    view_offset: usize,
+
+    x: usize,
+    y: usize,
+    /// Set after printing in the last column; the next print wraps first
+    pending_wrap: bool,
+    /// Rendition applied to printed characters
+    pen: Cell,
+    saved: SavedCursor,
+    /// First and last row of the scroll region
+    top: usize,
+    bottom: usize,
+    tab_stops: Vec<bool>,
+    /// Last printed character, for REP
+    last_char: char,
+
+    autowrap: bool,
+    origin: bool,
+    insert: bool,
+    newline: bool,
+    cursor_visible: bool,
+    app_cursor: bool,
+    bracketed_paste: bool,
+    mouse_mode: MouseMode,
+    mouse_sgr: bool,
+    /// G0 is the DEC special graphics (line drawing) set
+    line_drawing: bool,
+
+    title: String,
+    title_changed: bool,
+    bell: bool,
+    responses: Vec<u8>,
+
+    /// Damaged column range of each row since the last `clear_damage`
+    damage: Vec<Option<Range<usize>>>,
+    /// Lines the whole screen moved up since the last `clear_damage`
+    scrolled: usize,
+    damaged: bool,
+
+    parser: Parser,
+}
+
+impl Terminal {
+    pub fn new(cols: usize, rows: usize) -> Self {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        Self {
+            cols,
+            rows,
+            lines: vec![vec![Cell::BLANK; cols]; rows],
+            primary: None,
+            scrollback: VecDeque::new(),
+            scrollback_limit: DEFAULT_SCROLLBACK,
+            view_offset: 0,
+            x: 0,
+            y: 0,
+            pending_wrap: false,
+            pen: Cell::BLANK,
+            saved: SavedCursor::HOME,
+            top: 0,
+            bottom: rows - 1,
+            tab_stops: default_tab_stops(cols),
+            last_char: ' ',
+            autowrap: true,
+            origin: false,
+            insert: false,
+            newline: false,
+            cursor_visible: true,
+            app_cursor: false,
+            bracketed_paste: false,
+            mouse_mode: MouseMode::Off,
+            mouse_sgr: false,
+            line_drawing: false,
+            title: String::new(),
+            title_changed: false,
+            bell: false,
+            responses: Vec::new(),
+            damage: vec![Some(0..cols); rows],
+            scrolled: 0,
+            damaged: true,
+            parser: Parser::new(),
+        }
+    }
+
+    /// Process output from the program.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut parser = core::mem::take(&mut self.parser);
+        parser.advance_all(self, bytes);
+        self.parser = parser;
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Change the screen size, keeping the cursor's line on screen.
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        if cols == self.cols && rows == self.rows {
+            return;
+        }
+
+        // Shrinking past the cursor pushes lines off the top, as output would
+        let excess = (self.y + 1).saturating_sub(rows);
+        for _ in 0..excess {
+            let line = self.lines.remove(0);
+            if self.primary.is_none() {
+                self.push_scrollback(line);
+            }
+        }
+        self.y -= excess;
+        self.saved.y = self.saved.y.saturating_sub(excess);
+        resize_screen(&mut self.lines, cols, rows);
+        if let Some(primary) = self.primary.as_mut() {
+            resize_screen(primary, cols, rows);
+        }
+
+        self.cols = cols;
+        self.rows = rows;
+        self.x = self.x.min(cols - 1);
+        self.y = self.y.min(rows - 1);
+        self.saved.x = self.saved.x.min(cols - 1);
+        self.saved.y = self.saved.y.min(rows - 1);
+        self.pending_wrap = false;
+        self.top = 0;
+        self.bottom = rows - 1;
+        self.tab_stops = default_tab_stops(cols);
+        self.view_offset = 0;
+        self.damage = vec![None; rows];
+        self.damage_all();
+    }
+
+    /// Return to the power-on state (RIS), keeping the size and scrollback.
+    pub fn reset(&mut self) {
+        let mut fresh = Terminal::new(self.cols, self.rows);
+        fresh.scrollback = core::mem::take(&mut self.scrollback);
+        fresh.scrollback_limit = self.scrollback_limit;
+        fresh.title = core::mem::take(&mut self.title);
+        *self = fresh;
+    }
+
+    // ─── Screen contents ────────────────────────────────────────────────
+
+    /// A cell of the live screen.
+    pub fn cell(&self, x: usize, y: usize) -> &Cell {
+        &self.lines[y][x]
+    }
+
+    /// Row `row` of what is on display: the live screen, or scrollback when
+    /// the view is scrolled back. Scrollback lines keep the width they had
+    /// when they scrolled off, so may be shorter or longer than `cols`.
+    pub fn display_line(&self, row: usize) -> &[Cell] {
+        let history = self.scrollback.len();
+        let index = history - self.view_offset + row;
+        if index < history {
+            &self.scrollback[index]
+        } else {
+            &self.lines[index - history]
+        }
+    }
+
+    /// The cursor position, as (column, row).
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    /// Move the cursor, clamped to the screen.
+    pub fn set_cursor(&mut self, x: usize, y: usize) {
+        self.damage_cell(self.x, self.y);
+        self.x = x.min(self.cols - 1);
+        self.y = y.min(self.rows - 1);
+        self.pending_wrap = false;
+        self.damage_cell(self.x, self.y);
+    }
+
+    /// Whether the cursor should be drawn: DECTCEM is set and the view is on
+    /// the live screen.
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible && self.view_offset == 0
+    }
+
+    /// The window title last set by OSC 0 or 2.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Whether the title changed since the last call.
+    pub fn take_title_changed(&mut self) -> bool {
+        core::mem::take(&mut self.title_changed)
+    }
+
+    /// Whether BEL was received since the last call.
+    pub fn take_bell(&mut self) -> bool {
+        core::mem::take(&mut self.bell)
+    }
+
+    /// Replies to queries, to be written back to the program.
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        core::mem::take(&mut self.responses)
+    }
+
+    /// Whether the alternate screen is shown.
+    pub fn alternate_screen(&self) -> bool {
+        self.primary.is_some()
+    }
+
+    /// Make LF, VT and FF also return the carriage (LNM), for output that
+    /// has not been through a tty's newline translation.
+    pub fn set_newline_mode(&mut self, enabled: bool) {
+        self.newline = enabled;
+    }
+
+    // ─── Scrollback ─────────────────────────────────────────────────────
+
+    /// Number of lines in the scrollback buffer.
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback.len()
+    }
+
+    /// Cap the scrollback buffer, dropping the oldest lines beyond it.
+    pub fn set_scrollback_limit(&mut self, lines: usize) {
+        self.scrollback_limit = lines;
+        while self.scrollback.len() > lines {
+            self.scrollback.pop_front();
+        }
+        self.view_offset = self.view_offset.min(self.scrollback.len());
+    }
+
+    /// How many lines the view is scrolled back into history.
+    pub fn view_offset(&self) -> usize {
+        self.view_offset
+    }
+
+    /// Scroll the view by `lines`: positive into history, negative back
+    /// toward the live screen.
+    pub fn scroll_view(&mut self, lines: isize) {
+        let offset = (self.view_offset as isize + lines).clamp(0, self.scrollback.len() as isize);
+        if offset as usize != self.view_offset {
+            self.view_offset = offset as usize;
+            self.damage_all();
+        }
+    }
+
+    /// Return the view to the live screen.
+    pub fn reset_view(&mut self) {
+        self.scroll_view(-(self.view_offset as isize));
+    }
+
+    /// The displayed text from `start` through `end`, each a (column, row)
+    /// of the view, in either order: what copying a mouse selection gives.
+    /// Rows are joined with newlines and lose their trailing blanks.
+    pub fn display_text(&self, start: (usize, usize), end: (usize, usize)) -> String {
+        let (start, end) = if (start.1, start.0) <= (end.1, end.0) {
+            (start, end)
+        } else {
+            (end, start)
+        };
+        let mut text = String::new();
+        for row in start.1..=end.1.min(self.rows - 1) {
+            let line = self.display_line(row);
+            let first = if row == start.1 { start.0 } else { 0 };
+            let last = if row == end.1 { end.0 + 1 } else { line.len() };
+            let mut row_text = String::new();
+            for cell in line.iter().take(last).skip(first) {
+                if cell.attrs.contains(Attrs::WIDE_SPACER) {
+                    continue;
+                }
+                row_text.push(cell.ch);
+                row_text.extend(cell.combining());
+            }
+            if row > start.1 {
+                text.push('\n');
+            }
+            text.push_str(row_text.trim_end_matches(' '));
+        }
+        text
+    }
+
+    // ─── Input ──────────────────────────────────────────────────────────
+
+    /// The sequence a cursor key sends under the current DECCKM mode.
+    pub fn cursor_key(&self, key: CursorKey) -> &'static [u8] {
+        key.sequence(self.app_cursor)
+    }
+
+    /// Whether the program asked for bracketed paste (DECSET 2004).
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// The bytes to send for pasted text. Newlines become carriage returns,
+    /// as typed Enter would, and with bracketed paste on the text is wrapped
+    /// in `ESC [200~` ... `ESC [201~` (with any end marker inside removed).
+    pub fn paste(&self, text: &[u8]) -> Vec<u8> {
+        const END: &[u8] = b"\x1b[201~";
+        let mut out = Vec::with_capacity(text.len() + 12);
+        if self.bracketed_paste {
+            out.extend_from_slice(b"\x1b[200~");
+        }
+        let mut i = 0;
+        while i < text.len() {
+            if self.bracketed_paste && text[i..].starts_with(END) {
+                i += END.len();
+                continue;
+            }
+            match text[i] {
+                b'\r' if text.get(i + 1) == Some(&b'\n') => {}
+                b'\n' => out.push(b'\r'),
+                byte => out.push(byte),
+            }
+            i += 1;
+        }
+        if self.bracketed_paste {
+            out.extend_from_slice(END);
+        }
+        out
+    }
+
+    /// Which mouse events the program asked for.
+    pub fn mouse_mode(&self) -> MouseMode {
+        self.mouse_mode
+    }
+
+    /// The report to send for a mouse event, or `None` if the program did not
+    /// ask for this kind of event.
+    pub fn mouse_report(&self, event: &MouseEvent) -> Option<Vec<u8>> {
+        encode_mouse(event, self.mouse_mode, self.mouse_sgr)
+    }
+
+    // ─── Damage ─────────────────────────────────────────────────────────
+
+    /// Whether anything needs repainting.
+    pub fn is_damaged(&self) -> bool {
+        self.damaged
+    }
+
+    /// The damaged columns of display row `row`, if any.
+    pub fn damage(&self, row: usize) -> Option<Range<usize>> {
+        self.damage.get(row).cloned().flatten()
+    }
+
+    /// Lines the whole screen moved up since damage was last cleared.
+    ///
+    /// A renderer may move its previous image up this far and then repaint
+    /// only the damaged cells; damage is already given in the moved
+    /// coordinates, with the rows scrolled in marked damaged.
+    pub fn scrolled(&self) -> usize {
+        self.scrolled
+    }
+
+    /// Forget all damage, once the renderer has repainted it.
+    pub fn clear_damage(&mut self) {
+        for row in self.damage.iter_mut() {
+            *row = None;
+        }
+        self.scrolled = 0;
+        self.damaged = false;
+    }
+
+    /// Mark every cell for repainting.
+    pub fn damage_all(&mut self) {
+        for row in self.damage.iter_mut() {
+            *row = Some(0..self.cols);
+        }
+        self.damaged = true;
+    }
+
+    /// Mark one cell for repainting, e.g. where a cursor was drawn.
+    pub fn damage_cell(&mut self, x: usize, y: usize) {
+        self.damage_range(y, x, x + 1);
+    }
+
+    fn damage_range(&mut self, y: usize, start: usize, end: usize) {
+        let end = end.min(self.cols);
+        if y >= self.rows || start >= end {
+            return;
+        }
+        let row = &mut self.damage[y];
+        *row = Some(match row.take() {
+            Some(range) => range.start.min(start)..range.end.max(end),
+            None => start..end,
+        });
+        self.damaged = true;
+    }
+
+    fn damage_rows(&mut self, rows: Range<usize>) {
+        for y in rows {
+            self.damage_range(y, 0, self.cols);
+        }
+    }
+
+    // ─── Editing primitives ─────────────────────────────────────────────
+
+    fn blank(&self) -> Cell {
+        Cell::erased(&self.pen)
+    }
+
+    fn blank_line(&self) -> Vec<Cell> {
+        vec![self.blank(); self.cols]
+    }
+
+    fn push_scrollback(&mut self, line: Vec<Cell>) {
+        if self.scrollback_limit == 0 {
+            return;
+        }
+        if self.scrollback.len() == self.scrollback_limit {
+            self.scrollback.pop_front();
+        } else if self.view_offset > 0 {
+            // Keep a scrolled-back view on the same history
+            self.view_offset += 1;
+        }
+        self.scrollback.push_back(line);
+    }
+
+    /// Scroll the region up `count` lines, saving lines that leave the top of
+    /// the primary screen.
+    fn scroll_up(&mut self, count: usize) {
+        let count = count.min(self.bottom - self.top + 1);
+        for _ in 0..count {
+            let line = self.lines.remove(self.top);
+            if self.top == 0 && self.primary.is_none() {
+                self.push_scrollback(line);
+            }
+            let blank = self.blank_line();
+            self.lines.insert(self.bottom, blank);
+        }
+
+        if self.top == 0 && self.bottom == self.rows - 1 && self.view_offset == 0 {
+            self.scrolled += count;
+            self.damage.drain(..count);
+            self.damage.extend((0..count).map(|_| None));
+            self.damage_rows(self.rows - count..self.rows);
+        } else {
+            self.damage_rows(self.top..self.bottom + 1);
+        }
+        if self.view_offset > 0 {
+            self.damage_all();
+        }
+    }
+
+    /// Scroll the region down `count` lines.
+    fn scroll_down(&mut self, count: usize) {
+        let count = count.min(self.bottom - self.top + 1);
+        for _ in 0..count {
+            self.lines.remove(self.bottom);
+            let blank = self.blank_line();
+            self.lines.insert(self.top, blank);
+        }
+        self.damage_rows(self.top..self.bottom + 1);
+    }
+
+    fn linefeed(&mut self) {
+        self.pending_wrap = false;
+        if self.y == self.bottom {
+            self.scroll_up(1);
+        } else if self.y < self.rows - 1 {
+            self.move_to(self.x, self.y + 1);
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        self.pending_wrap = false;
+        if self.y == self.top {
+            self.scroll_down(1);
+        } else if self.y > 0 {
+            self.move_to(self.x, self.y - 1);
+        }
+    }
+
+    /// Move the cursor without origin-mode translation, clamped to the screen.
+    fn move_to(&mut self, x: usize, y: usize) {
+        self.damage_cell(self.x, self.y);
+        self.x = x.min(self.cols - 1);
+        self.y = y.min(self.rows - 1);
+        self.pending_wrap = false;
+        self.damage_cell(self.x, self.y);
+    }
+
+    /// Move to a row counted from the origin (the scroll region's top when
+    /// DECOM is set), staying inside the region in origin mode.
+    fn move_to_origin_row(&mut self, x: usize, row: usize) {
+        if self.origin {
+            self.move_to(x, (self.top + row).min(self.bottom));
+        } else {
+            self.move_to(x, row);
+        }
+    }
+
+    /// Rows the cursor may move between vertically: the scroll region when
+    /// it starts inside it, else the whole screen.
+    fn vertical_bounds(&self) -> (usize, usize) {
+        let top = if self.y >= self.top { self.top } else { 0 };
+        let bottom = if self.y <= self.bottom {
+            self.bottom
+        } else {
+            self.rows - 1
+        };
+        (top, bottom)
+    }
+
+    fn erase(&mut self, y: usize, columns: Range<usize>) {
+        let blank = self.blank();
+        let end = columns.end.min(self.cols);
+        for cell in &mut self.lines[y][columns.start.min(end)..end] {
+            *cell = blank;
+        }
+        self.damage_range(y, columns.start, end);
+    }
+
+    fn erase_display(&mut self, mode: u16) {
+        match mode {
+            0 => {
+                self.erase(self.y, self.x..self.cols);
+                for y in self.y + 1..self.rows {
+                    self.erase(y, 0..self.cols);
+                }
+            }
+            1 => {
+                for y in 0..self.y {
+                    self.erase(y, 0..self.cols);
+                }
+                self.erase(self.y, 0..self.x + 1);
+            }
+            2 => {
+                for y in 0..self.rows {
+                    self.erase(y, 0..self.cols);
+                }
+            }
+            3 => {
+                self.scrollback.clear();
+                self.view_offset = 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn erase_line(&mut self, mode: u16) {
+        match mode {
+            0 => self.erase(self.y, self.x..self.cols),
+            1 => self.erase(self.y, 0..self.x + 1),
+            2 => self.erase(self.y, 0..self.cols),
+            _ => {}
+        }
+    }
+
+    fn insert_blanks(&mut self, count: usize) {
+        let blank = self.blank();
+        let count = count.min(self.cols - self.x);
+        let line = &mut self.lines[self.y];
+        line.truncate(self.cols - count);
+        for _ in 0..count {
+            line.insert(self.x, blank);
+        }
+        self.damage_range(self.y, self.x, self.cols);
+    }
+
+    /// Before the cell at (x, y) is overwritten, blank the other half of
+    /// the double-width character it is part of.
+    fn split_wide(&mut self, x: usize, y: usize) {
+        let attrs = self.lines[y][x].attrs;
+        let other = if attrs.contains(Attrs::WIDE_SPACER) && x > 0 {
+            x - 1
+        } else if attrs.contains(Attrs::WIDE) && x + 1 < self.cols {
+            x + 1
+        } else {
+            return;
+        };
+        let line = &mut self.lines[y];
+        line[other] = Cell::erased(&line[other]);
+        self.damage_cell(other, y);
+    }
+
+    /// Attach a combining character to the character before the cursor.
+    fn combine(&mut self, c: char) {
+        let mut x = if self.pending_wrap {
+            self.x
+        } else if self.x > 0 {
+            self.x - 1
+        } else {
+            return;
+        };
+        if x > 0 && self.lines[self.y][x].attrs.contains(Attrs::WIDE_SPACER) {
+            x -= 1;
+        }
+        if self.lines[self.y][x].push_combining(c) {
+            self.damage_cell(x, self.y);
+        }
+    }
+
+    fn delete_chars(&mut self, count: usize) {
+        let blank = self.blank();
+        let count = count.min(self.cols - self.x);
+        let line = &mut self.lines[self.y];
+        line.drain(self.x..self.x + count);
+        line.resize(self.cols, blank);
+        self.damage_range(self.y, self.x, self.cols);
+    }
+
+    /// IL / DL: scroll the part of the region from the cursor down.
+    fn insert_or_delete_lines(&mut self, count: usize, insert: bool) {
+        if self.y < self.top || self.y > self.bottom {
+            return;
+        }
+        let top = self.top;
+        self.top = self.y;
+        if insert {
+            self.scroll_down(count);
+        } else {
+            // Deleted lines never go to scrollback
+            let limit = core::mem::replace(&mut self.scrollback_limit, 0);
+            self.scroll_up(count);
+            self.scrollback_limit = limit;
+        }
+        self.top = top;
+        self.move_to(0, self.y);
+    }
+
+    fn tab(&mut self, forward: bool, count: usize) {
+        let mut x = self.x;
+        for _ in 0..count {
+            if forward {
+                x = (x + 1..self.cols)
+                    .find(|&col| self.tab_stops[col])
+                    .unwrap_or(self.cols - 1);
+            } else {
+                x = (0..x).rev().find(|&col| self.tab_stops[col]).unwrap_or(0);
+            }
+        }
+        self.move_to(x, self.y);
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved = SavedCursor {
+            x: self.x,
+            y: self.y,
+            pen: self.pen,
+            origin: self.origin,
+            line_drawing: self.line_drawing,
+        };
+    }
+
+    fn restore_cursor(&mut self) {
+        let saved = self.saved;
+        self.pen = saved.pen;
+        self.origin = saved.origin;
+        self.line_drawing = saved.line_drawing;
+        self.move_to(saved.x, saved.y);
+    }
+
+    fn enter_alternate_screen(&mut self) {
+        if self.primary.is_none() {
+            let alternate = vec![vec![Cell::erased(&self.pen); self.cols]; self.rows];
+            self.primary = Some(core::mem::replace(&mut self.lines, alternate));
+            self.view_offset = 0;
+            self.damage_all();
+        }
+    }
+
+    fn leave_alternate_screen(&mut self) {
+        if let Some(primary) = self.primary.take() {
+            self.lines = primary;
+            self.damage_all();
+        }
+    }
+
+    fn set_scroll_region(&mut self, top: u16, bottom: u16) {
+        let top = top.max(1) as usize - 1;
+        let bottom = match bottom {
+            0 => self.rows,
+            bottom => (bottom as usize).min(self.rows),
+        } - 1;
+        if top < bottom {
+            self.top = top;
+            self.bottom = bottom;
+            self.move_to_origin_row(0, 0);
+        }
+    }
+
+    fn set_mode(&mut self, params: &Params, private: bool, enable: bool) {
+        for i in 0..params.len() {
+            match (private, params.get(i)) {
+                (false, 4) => self.insert = enable,
+                (false, 20) => self.newline = enable,
+                (true, 1) => self.app_cursor = enable,
+                (true, 6) => {
+                    self.origin = enable;
+                    self.move_to_origin_row(0, 0);
+                }
+                (true, 7) => self.autowrap = enable,
+                (true, 25) => {
+                    self.cursor_visible = enable;
+                    self.damage_cell(self.x, self.y);
+                }
+                (true, 9) => self.set_mouse_mode(MouseMode::X10, enable),
+                (true, 1000) => self.set_mouse_mode(MouseMode::Normal, enable),
+                (true, 1002) => self.set_mouse_mode(MouseMode::ButtonMotion, enable),
+                (true, 1003) => self.set_mouse_mode(MouseMode::AnyMotion, enable),
+                (true, 1006) => self.mouse_sgr = enable,
+                (true, 47) | (true, 1047) => {
+                    if enable {
+                        self.enter_alternate_screen();
+                    } else {
+                        self.leave_alternate_screen();
+                    }
+                }
+                (true, 1048) => {
+                    if enable {
+                        self.save_cursor();
+                    } else {
+                        self.restore_cursor();
+                    }
+                }
+                (true, 1049) => {
+                    if enable {
+                        self.save_cursor();
+                        self.enter_alternate_screen();
+                    } else {
+                        self.leave_alternate_screen();
+                        self.restore_cursor();
+                    }
+                }
+                (true, 2004) => self.bracketed_paste = enable,
+                _ => {}
+            }
+        }
+    }
+
+    fn set_mouse_mode(&mut self, mode: MouseMode, enable: bool) {
+        if enable {
+            self.mouse_mode = mode;
+        } else if self.mouse_mode == mode {
+            self.mouse_mode = MouseMode::Off;
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, params: &Params) {
+        let mut i = 0;
+        while i < params.len() {
+            let code = params.get(i);
+            i += 1;
+            match code {
+                0 => {
+                    self.pen.fg = Color::Default;
+                    self.pen.bg = Color::Default;
+                    self.pen.attrs = Attrs::empty();
+                }
+                1 => self.pen.attrs.insert(Attrs::BOLD),
+                2 => self.pen.attrs.insert(Attrs::DIM),
+                3 => self.pen.attrs.insert(Attrs::ITALIC),
+                4 => self.pen.attrs.insert(Attrs::UNDERLINE),
+                5 | 6 => self.pen.attrs.insert(Attrs::BLINK),
+                7 => self.pen.attrs.insert(Attrs::INVERSE),
+                8 => self.pen.attrs.insert(Attrs::HIDDEN),
+                9 => self.pen.attrs.insert(Attrs::STRIKETHROUGH),
+                21 => self.pen.attrs.insert(Attrs::UNDERLINE),
+                22 => {
+                    self.pen.attrs.remove(Attrs::BOLD);
+                    self.pen.attrs.remove(Attrs::DIM);
+                }
+                23 => self.pen.attrs.remove(Attrs::ITALIC),
+                24 => self.pen.attrs.remove(Attrs::UNDERLINE),
+                25 => self.pen.attrs.remove(Attrs::BLINK),
+                27 => self.pen.attrs.remove(Attrs::INVERSE),
+                28 => self.pen.attrs.remove(Attrs::HIDDEN),
+                29 => self.pen.attrs.remove(Attrs::STRIKETHROUGH),
+                30..=37 => self.pen.fg = Color::Indexed((code - 30) as u8),
+                39 => self.pen.fg = Color::Default,
+                40..=47 => self.pen.bg = Color::Indexed((code - 40) as u8),
+                49 => self.pen.bg = Color::Default,
+                90..=97 => self.pen.fg = Color::Indexed((code - 90 + 8) as u8),
+                100..=107 => self.pen.bg = Color::Indexed((code - 100 + 8) as u8),
+                38 | 48 => {
+                    let (color, used) = extended_color(params, i);
+                    i += used;
+                    if let Some(color) = color {
+                        if code == 38 {
+                            self.pen.fg = color;
+                        } else {
+                            self.pen.bg = color;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            // Unknown sub-parameters belong to the code before them
+            while params.is_subparam(i) {
+                i += 1;
+            }
+        }
+    }
+
+    fn report(&mut self, args: core::fmt::Arguments) {
+        push_fmt(&mut self.responses, args);
+    }
+}
+
+impl Perform for Terminal {
+    fn print(&mut self, c: char) {
+        let c = if self.line_drawing {
+            dec_special_graphics(c)
+        } else {
+            c
+        };
+        let width = char_width(c);
+        if width == 0 {
+            if is_extend(c) {
+                self.combine(c);
+            }
+            return;
+        }
+        let wide = width == 2 && self.cols >= 2;
+        if self.pending_wrap && self.autowrap {
+            self.move_to(0, self.y);
+            self.linefeed();
+        }
+        if wide && self.x + 1 == self.cols {
+            // No room for both halves: wrap early, or overwrite the last two
+            if self.autowrap {
+                self.split_wide(self.x, self.y);
+                self.lines[self.y][self.x] = self.blank();
+                self.damage_cell(self.x, self.y);
+                self.move_to(0, self.y);
+                self.linefeed();
+            } else {
+                self.x -= 1;
+            }
+        }
+        let width = if wide { 2 } else { 1 };
+        if self.insert {
+            self.insert_blanks(width);
+        }
+        let (x, y) = (self.x, self.y);
+        for i in 0..width {
+            self.split_wide(x + i, y);
+        }
+        let mut cell = Cell { ch: c, ..self.pen };
+        if wide {
+            let mut spacer = Cell {
+                ch: ' ',
+                ..self.pen
+            };
+            spacer.attrs.insert(Attrs::WIDE_SPACER);
+            self.lines[y][x + 1] = spacer;
+            cell.attrs.insert(Attrs::WIDE);
+        }
+        self.lines[y][x] = cell;
+        self.damage_range(y, x, x + width);
+        self.last_char = c;
+        if x + width < self.cols {
+            self.x = x + width;
+            self.damage_cell(self.x, self.y);
+        } else {
+            self.x = self.cols - 1;
+            self.pending_wrap = self.autowrap;
+        }
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            0x07 => self.bell = true,
+            0x08 => {
+                if self.x > 0 {
+                    self.move_to(self.x - 1, self.y);
+                } else {
+                    self.pending_wrap = false;
+                }
+            }
+            b'\t' => self.tab(true, 1),
+            b'\n' | 0x0B | 0x0C => {
+                if self.newline {
+                    self.move_to(0, self.y);
+                }
+                self.linefeed();
+            }
+            b'\r' => self.move_to(0, self.y),
+            _ => {}
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], action: u8) {
+        let n = params.arg(0, 1) as usize;
+        match (intermediates, action) {
+            ([], b'@') => self.insert_blanks(n),
+            ([], b'A') => {
+                let (top, _) = self.vertical_bounds();
+                self.move_to(self.x, self.y.saturating_sub(n).max(top));
+            }
+            ([], b'B') | ([], b'e') => {
+                let (_, bottom) = self.vertical_bounds();
+                self.move_to(self.x, (self.y + n).min(bottom));
+            }
+            ([], b'C') | ([], b'a') => self.move_to(self.x + n, self.y),
+            ([], b'D') => self.move_to(self.x.saturating_sub(n), self.y),
+            ([], b'E') => {
+                let (_, bottom) = self.vertical_bounds();
+                self.move_to(0, (self.y + n).min(bottom));
+            }
+            ([], b'F') => {
+                let (top, _) = self.vertical_bounds();
+                self.move_to(0, self.y.saturating_sub(n).max(top));
+            }
+            ([], b'G') | ([], b'`') => self.move_to(n - 1, self.y),
+            ([], b'H') | ([], b'f') => {
+                let col = params.arg(1, 1) as usize - 1;
+                self.move_to_origin_row(col, n - 1);
+            }
+            ([], b'I') => self.tab(true, n),
+            ([], b'J') => self.erase_display(params.get(0)),
+            ([], b'K') => self.erase_line(params.get(0)),
+            ([], b'L') => self.insert_or_delete_lines(n, true),
+            ([], b'M') => self.insert_or_delete_lines(n, false),
+            ([], b'P') => self.delete_chars(n),
+            ([], b'S') => {
+                // Explicit scrolls never add to scrollback
+                let limit = core::mem::replace(&mut self.scrollback_limit, 0);
+                self.scroll_up(n);
+                self.scrollback_limit = limit;
+            }
+            ([], b'T') => self.scroll_down(n),
+            ([], b'X') => {
+                let end = self.x + n;
+                self.erase(self.y, self.x..end);
+            }
+            ([], b'Z') => self.tab(false, n),
+            ([], b'b') => {
+                for _ in 0..n.min(self.cols * self.rows) {
+                    self.print(self.last_char);
+                }
+            }
+            ([], b'c') => self.responses.extend_from_slice(b"\x1b[?62;22c"),
+            ([b'>'], b'c') => self.responses.extend_from_slice(b"\x1b[>0;10;1c"),
+            ([], b'd') => self.move_to_origin_row(self.x, n - 1),
+            ([], b'g') => match params.get(0) {
+                0 => self.tab_stops[self.x] = false,
+                3 => self.tab_stops.iter_mut().for_each(|stop| *stop = false),
+                _ => {}
+            },
+            ([], b'h') => self.set_mode(params, false, true),
+            ([], b'l') => self.set_mode(params, false, false),
+            ([b'?'], b'h') => self.set_mode(params, true, true),
+            ([b'?'], b'l') => self.set_mode(params, true, false),
+            ([], b'm') => self.select_graphic_rendition(params),
+            ([], b'n') => match params.get(0) {
+                5 => self.responses.extend_from_slice(b"\x1b[0n"),
+                6 => {
+                    let row = if self.origin {
+                        self.y - self.top
+                    } else {
+                        self.y
+                    };
+                    let col = self.x;
+                    self.report(format_args!("\x1b[{};{}R", row + 1, col + 1));
+                }
+                _ => {}
+            },
+            ([], b'r') => self.set_scroll_region(params.get(0), params.get(1)),
+            ([], b's') => self.save_cursor(),
+            ([], b'u') => self.restore_cursor(),
+            ([b'!'], b'p') => {
+                // DECSTR soft reset: modes and rendition, not the screen
+                self.pen = Cell::BLANK;
+                self.insert = false;
+                self.origin = false;
+                self.autowrap = true;
+                self.cursor_visible = true;
+                self.app_cursor = false;
+                self.line_drawing = false;
+                self.top = 0;
+                self.bottom = self.rows - 1;
+                self.saved = SavedCursor::HOME;
+            }
+            _ => {}
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], byte: u8) {
+        match (intermediates, byte) {
+            ([], b'7') => self.save_cursor(),
+            ([], b'8') => self.restore_cursor(),
+            ([], b'D') => self.linefeed(),
+            ([], b'E') => {
+                self.move_to(0, self.y);
+                self.linefeed();
+            }
+            ([], b'H') => self.tab_stops[self.x] = true,
+            ([], b'M') => self.reverse_index(),
+            ([], b'c') => self.reset(),
+            ([b'('], b'0') => self.line_drawing = true,
+            ([b'('], _) => self.line_drawing = false,
+            ([b'#'], b'8') => {
+                // DECALN screen alignment pattern
+                for line in self.lines.iter_mut() {
+                    line.fill(Cell {
+                        ch: 'E',
+                        ..Cell::BLANK
+                    });
+                }
+                self.damage_all();
+            }
+            _ => {}
+        }
+    }
+
+    fn osc_dispatch(&mut self, data: &[u8]) {
+        let split = data.iter().position(|&b| b == b';').unwrap_or(data.len());
+        let (code, text) = (&data[..split], data.get(split + 1..).unwrap_or(&[]));
+        if code == b"0" || code == b"2" {
+            self.title = String::from_utf8_lossy(text)
+                .chars()
+                .filter(|c| !c.is_control())
+                .take(MAX_TITLE_CHARS)
+                .collect();
+            self.title_changed = true;
+        }
+    }
+}
+
+/// Tab stops every eight columns.
+fn default_tab_stops(cols: usize) -> Vec<bool> {
+    (0..cols).map(|col| col % 8 == 0 && col > 0).collect()
+}
+
+/// Pad or cut a screen to `cols` x `rows`.
+fn resize_screen(lines: &mut Vec<Vec<Cell>>, cols: usize, rows: usize) {
+    lines.resize_with(rows, Vec::new);
+    for line in lines.iter_mut() {
+        line.resize(cols, Cell::BLANK);
+    }
+}
+
+/// Parse the color after SGR 38 or 48, starting at parameter `i`.
+///
+/// Accepts `5;n` and `2;r;g;b`, and their `:` forms, which may carry a color
+/// space id before the components (`2::r:g:b`). Returns the color and the
+/// number of parameters used.
+fn extended_color(params: &Params, i: usize) -> (Option<Color>, usize) {
+    if params.is_subparam(i) {
+        let mut count = 0;
+        while params.is_subparam(i + count) {
+            count += 1;
+        }
+        let color = match (params.get(i), count) {
+            (5, 2) => Some(Color::Indexed(params.get(i + 1) as u8)),
+            (2, 4) => Some(rgb_at(params, i + 1)),
+            (2, 5..) => Some(rgb_at(params, i + 2)),
+            _ => None,
+        };
+        return (color, count);
+    }
+    match params.get(i) {
+        5 if i + 1 < params.len() => (Some(Color::Indexed(params.get(i + 1) as u8)), 2),
+        2 if i + 3 < params.len() => (Some(rgb_at(params, i + 1)), 4),
+        _ => (None, params.len() - i),
+    }
+}
+
+fn rgb_at(params: &Params, i: usize) -> Color {
+    let component = |j: usize| params.get(j).min(255) as u8;
+    Color::Rgb(Rgb::new(component(i), component(i + 1), component(i + 2)))
+}
+
+/// Map a character through the DEC special graphics set, which programs
+/// select with `ESC ( 0` to draw boxes.
+fn dec_special_graphics(c: char) -> char {
+    match c {
+        '`' => '\u{25C6}',
+        'a' => '\u{2592}',
+        'f' => '\u{00B0}',
+        'g' => '\u{00B1}',
+        'j' => '\u{2518}',
+        'k' => '\u{2510}',
+        'l' => '\u{250C}',
+        'm' => '\u{2514}',
+        'n' => '\u{253C}',
+        'q' => '\u{2500}',
+        't' => '\u{251C}',
+        'u' => '\u{2524}',
+        'v' => '\u{2534}',
+        'w' => '\u{252C}',
+        'x' => '\u{2502}',
+        'y' => '\u{2264}',
+        'z' => '\u{2265}',
+        '~' => '\u{00B7}',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{MouseButton, MouseEventKind};
+    use alloc::string::String;
+
+    fn line_text(term: &Terminal, row: usize) -> String {
+        let mut text = String::new();
+        for cell in term.display_line(row) {
+            text.push(cell.ch);
+            text.extend(cell.combining());
+        }
+        String::from(text.trim_end())
+    }
+
+    #[test]
+    fn prints_wraps_and_scrolls_into_scrollback() {
+        let mut term = Terminal::new(4, 2);
+        term.feed(b"abcdef\r\nxy");
+        assert_eq!(line_text(&term, 0), "ef");
+        assert_eq!(line_text(&term, 1), "xy");
+        assert_eq!(term.scrollback_len(), 1);
+        term.scroll_view(1);
+        assert_eq!(line_text(&term, 0), "abcd");
+        assert!(!term.cursor_visible());
+    }
+
+    #[test]
+    fn sgr_256_and_truecolor() {
+        let mut term = Terminal::new(8, 1);
+        term.feed(b"\x1b[1;3;4;38;5;196;48;2;1;2;3mA\x1b[38:2::9:8:7;0mB");
+        let a = term.cell(0, 0);
+        assert_eq!(a.fg, Color::Indexed(196));
+        assert_eq!(a.bg, Color::Rgb(Rgb::new(1, 2, 3)));
+        assert!(a.attrs.contains(Attrs::BOLD));
+        assert!(a.attrs.contains(Attrs::ITALIC));
+        assert!(a.attrs.contains(Attrs::UNDERLINE));
+        assert_eq!(
+            *term.cell(1, 0),
+            Cell {
+                ch: 'B',
+                ..Cell::BLANK
+            }
+        );
+    }
+
+    #[test]
+    fn alternate_screen_restores_primary_and_cursor() {
+        let mut term = Terminal::new(10, 3);
+        term.feed(b"shell$ ");
+        term.feed(b"\x1b[?1049h\x1b[2J\x1b[Heditor");
+        assert!(term.alternate_screen());
+        assert_eq!(line_text(&term, 0), "editor");
+        term.feed(b"\x1b[?1049l");
+        assert_eq!(line_text(&term, 0), "shell$");
+        assert_eq!(term.cursor(), (7, 0));
+    }
+
+    #[test]
+    fn scroll_region_keeps_lines_outside() {
+        let mut term = Terminal::new(5, 4);
+        term.feed(b"top\r\n1\r\n2\r\nbot");
+        term.feed(b"\x1b[2;3r\x1b[3;1H\nnew");
+        assert_eq!(line_text(&term, 0), "top");
+        assert_eq!(line_text(&term, 1), "2");
+        assert_eq!(line_text(&term, 2), "new");
+        assert_eq!(line_text(&term, 3), "bot");
+        assert_eq!(term.scrollback_len(), 0);
+    }
+
+    #[test]
+    fn full_screen_scroll_shifts_damage() {
+        let mut term = Terminal::new(5, 3);
+        term.clear_damage();
+        term.feed(b"\x1b[3;1Hx\n");
+        assert_eq!(term.scrolled(), 1);
+        assert_eq!(term.damage(1), Some(0..2));
+        assert_eq!(term.damage(2), Some(0..5));
+        assert_eq!(term.damage(0), None);
+    }
+
+    #[test]
+    fn reports_cursor_position_and_title() {
+        let mut term = Terminal::new(20, 5);
+        term.feed(b"\x1b[3;7H\x1b[6n\x1b]2;bless - notes\x07");
+        assert_eq!(term.take_responses(), b"\x1b[3;7R");
+        assert!(term.take_title_changed());
+        assert_eq!(term.title(), "bless - notes");
+    }
+
+    #[test]
+    fn bracketed_paste_and_mouse_reports() {
+        let mut term = Terminal::new(80, 24);
+        assert_eq!(term.paste(b"a\nb"), b"a\rb");
+        term.feed(b"\x1b[?2004h\x1b[?1002h\x1b[?1006h");
+        assert_eq!(term.paste(b"x\x1b[201~y"), b"\x1b[200~xy\x1b[201~");
+
+        let mut event = MouseEvent {
+            kind: MouseEventKind::Press,
+            button: MouseButton::Left,
+            col: 4,
+            row: 2,
+            shift: false,
+            alt: false,
+            ctrl: false,
+        };
+        assert_eq!(term.mouse_report(&event).unwrap(), b"\x1b[<0;5;3M");
+        event.kind = MouseEventKind::Release;
+        assert_eq!(term.mouse_report(&event).unwrap(), b"\x1b[<0;5;3m");
+        event.kind = MouseEventKind::Move;
+        event.button = MouseButton::None;
+        assert!(term.mouse_report(&event).is_none());
+
+        term.feed(b"\x1b[?1006l");
+        event.kind = MouseEventKind::Press;
+        event.button = MouseButton::WheelUp;
+        assert_eq!(term.mouse_report(&event).unwrap(), b"\x1b[M\x60\x25\x23");
+    }
+
+    #[test]
+    fn display_text_of_a_selection() {
+        let mut term = Terminal::new(10, 3);
+        term.feed("ls -l\r\n\u{4F60}\u{597D} e\u{301}\r\nend".as_bytes());
+        assert_eq!(
+            term.display_text((3, 0), (1, 2)),
+            "-l\n\u{4F60}\u{597D} e\u{301}\nen"
+        );
+        // Backwards selections read the same; a wide character's spacer
+        // adds nothing
+        assert_eq!(term.display_text((3, 1), (0, 1)), "\u{4F60}\u{597D}");
+        term.feed(b"\r\nmore\r\n");
+        term.scroll_view(2);
+        assert_eq!(term.display_text((0, 0), (4, 0)), "ls -l");
+    }
+
+    #[test]
+    fn line_drawing_charset() {
+        let mut term = Terminal::new(4, 1);
+        term.feed(b"\x1b(0lqk\x1b(Bq");
+        assert_eq!(line_text(&term, 0), "\u{250C}\u{2500}\u{2510}q");
+    }
+
+    #[test]
+    fn wide_and_combining_characters() {
+        let mut term = Terminal::new(5, 2);
+        term.feed("e\u{301}\u{4E2D}x\u{6587}".as_bytes());
+        let e = term.cell(0, 0);
+        assert_eq!(
+            (e.ch, e.combining().collect::<String>()),
+            ('e', String::from("\u{301}"))
+        );
+        assert!(term.cell(1, 0).attrs.contains(Attrs::WIDE));
+        assert!(term.cell(2, 0).attrs.contains(Attrs::WIDE_SPACER));
+        assert_eq!(term.cell(3, 0).ch, 'x');
+        // No room for 文 in the last column: it wraps and leaves a blank
+        assert_eq!(term.cell(4, 0).ch, ' ');
+        assert_eq!(term.cell(0, 1).ch, '\u{6587}');
+        assert_eq!(term.cursor(), (2, 1));
+
+        // Overwriting either half of a wide character blanks the other
+        term.feed(b"\x1b[1;3Hy");
+        assert_eq!(line_text(&term, 0), "e\u{301} yx");
+        assert!(!term.cell(1, 0).attrs.contains(Attrs::WIDE));
+    }
+}
@@ -183,6 +183,15 @@ if [[ "$(uname)" == "Darwin" ]]; then
                 echo "  Installed native Breenix ls as /bin/ls"
             fi
 
+            # Replace the BusyBox df applet with the native statfs-based bdf.
+            # rm first: /bin/df is a hardlink to busybox, so cp would clobber it.
+            if [ -f /mnt/ext2/bin/bdf ]; then
+                rm -f /mnt/ext2/bin/df
+                cp /mnt/ext2/bin/bdf /mnt/ext2/bin/df
+                chmod 755 /mnt/ext2/bin/df
+                echo "  Installed native Breenix df as /bin/df"
+            fi
+
             # Create /etc with passwd and group for musl getpwuid/getgrgid
             mkdir -p /mnt/ext2/etc
             cat > /mnt/ext2/etc/passwd << PASSWD
@@ -290,6 +299,8 @@ mono.font=/usr/share/fonts/DejaVuSansMono.ttf
 mono.size=10
 display.font=/usr/share/fonts/DejaVuSans.ttf
 display.size=14
+# Tried in order for characters the fonts above lack (Hebrew, Arabic, Indic)
+fallback.fonts=/usr/share/fonts/DejaVuSans.ttf,/usr/share/fonts/NotoSans-Regular.ttf
 FONTSCONF
             echo "  Created /etc/fonts.conf"
 
@@ -298,7 +309,8 @@ FONTSCONF
 # Breenix hotkey configuration
 # Format: modifier+key = action
 # Modifiers: Super (Cmd), Alt, Ctrl, Shift
-# Actions: exec <path>, close, focus_next, focus_prev
+# Actions: exec <path>, close, focus_next, focus_prev, maximize, minimize,
+#          snap_left, snap_right, lower
 # Double-tap: Super+Super = exec /bin/blauncher
 
 Super+Super  = exec /bin/blauncher
@@ -306,6 +318,11 @@ Super+Return = exec /bin/bterm
 Alt+q        = close
 Alt+Tab      = focus_next
 Alt+Shift+Tab = focus_prev
+Alt+Escape   = lower
+Super+Up     = maximize
+Super+Down   = minimize
+Super+Left   = snap_left
+Super+Right  = snap_right
 HOTKEYS
             echo "  Created /etc/hotkeys.conf"
 
@@ -463,6 +480,15 @@ else
         echo "  Installed native Breenix ls as /bin/ls"
     fi
 
+    # Replace the BusyBox df applet with the native statfs-based bdf.
+    # rm first: /bin/df is a hardlink to busybox, so cp would clobber it.
+    if [ -f "$MOUNT_DIR/bin/bdf" ]; then
+        rm -f "$MOUNT_DIR/bin/df"
+        cp "$MOUNT_DIR/bin/bdf" "$MOUNT_DIR/bin/df"
+        chmod 755 "$MOUNT_DIR/bin/df"
+        echo "  Installed native Breenix df as /bin/df"
+    fi
+
     # Create /etc with passwd and group for musl getpwuid/getgrgid
     mkdir -p "$MOUNT_DIR/etc"
     cat > "$MOUNT_DIR/etc/passwd" << PASSWD
@@ -568,6 +594,8 @@ mono.font=/usr/share/fonts/DejaVuSansMono.ttf
 mono.size=10
 display.font=/usr/share/fonts/DejaVuSans.ttf
 display.size=14
+# Tried in order for characters the fonts above lack (Hebrew, Arabic, Indic)
+fallback.fonts=/usr/share/fonts/DejaVuSans.ttf,/usr/share/fonts/NotoSans-Regular.ttf
 FONTSCONF
     echo "  Created /etc/fonts.conf"
 
@@ -576,7 +604,8 @@ FONTSCONF
 # Breenix hotkey configuration
 # Format: modifier+key = action
 # Modifiers: Super (Cmd), Alt, Ctrl, Shift
-# Actions: exec <path>, close, focus_next, focus_prev
+# Actions: exec <path>, close, focus_next, focus_prev, maximize, minimize,
+#          snap_left, snap_right, lower
 # Double-tap: Super+Super = exec /bin/blauncher
 
 Super+Super  = exec /bin/blauncher
@@ -584,6 +613,11 @@ Super+Return = exec /bin/bterm
 Alt+q        = close
 Alt+Tab      = focus_next
 Alt+Shift+Tab = focus_prev
+Alt+Escape   = lower
+Super+Up     = maximize
+Super+Down   = minimize
+Super+Left   = snap_left
+Super+Right  = snap_right
 HOTKEYS
     echo "  Created /etc/hotkeys.conf"
 
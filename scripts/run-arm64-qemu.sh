@@ -5,6 +5,7 @@
 #
 # Environment variables:
 #   BREENIX_GRAPHICS=1      - Enable headed display with VirtIO GPU (default: headless)
+#   BREENIX_OUTPUTS=N       - Number of VirtIO GPU display outputs (default: 1)
 #   BOOT_TESTS=1            - Enable parallel boot test framework with progress bars
 #   BREENIX_NET_DEBUG=1     - Enable network packet capture
 #   BREENIX_VIRTIO_TRACE=1  - Enable VirtIO tracing
@@ -79,7 +80,9 @@ fi
 # VirtIO GPU and keyboard are always added on ARM64 so the kernel's
 # VirtIO MMIO enumeration finds them.  The -display flag controls
 # whether a host window is created, not whether the devices exist.
-VIRTIO_DISPLAY_OPTS="-device virtio-gpu-device -device virtio-keyboard-device"
+# BREENIX_OUTPUTS=2 gives the GPU a second display output (one host window
+# per output); BWM spans the desktop across them.
+VIRTIO_DISPLAY_OPTS="-device virtio-gpu-device,max_outputs=${BREENIX_OUTPUTS:-1} -device virtio-keyboard-device"
 
 if [ "${BREENIX_GRAPHICS:-0}" = "1" ]; then
     echo "Graphics mode enabled - VirtIO GPU with native window"
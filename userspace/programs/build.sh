@@ -129,6 +129,10 @@ STD_BINARIES=(
     "fs_write_test"
     "fs_rename_test"
     "fs_large_file_test"
+    "fs_truncate_test"
+    "fs_statfs_test"
+    "flock_test"
+    "splice_test"
     "fs_directory_test"
     "fs_link_test"
     "access_test"
@@ -148,6 +152,16 @@ STD_BINARIES=(
     "signal_return_test"
     "signal_regs_test"
     "sigaltstack_test"
+    "siginfo_test"
+    "rt_signal_test"
+    "coredump_test"
+    "ptrace_test"
+    "rusage_test"
+    "priority_test"
+    "affinity_test"
+    "proc_pid_test"
+    "dsp_mixer_test"
+    "dsp_capture_test"
     "sigsuspend_test"
     "pause_test"
     "tty_test"
@@ -282,6 +296,9 @@ STD_BINARIES=(
     # Log viewer
     "bless"
 
+    # Filesystem tools
+    "bdf"
+
     # Self-check test runner
     "bcheck"
 
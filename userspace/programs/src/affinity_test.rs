@@ -0,0 +1,189 @@
+//! CPU affinity tests
+//!
+//! Tests that sched_getaffinity() reports the online CPUs, that
+//! sched_setaffinity() pins the caller to each CPU in turn (checked with
+//! sched_getcpu() and /proc/<pid>/status), that the pin is inherited across
+//! fork(), and that masks without an online CPU are rejected.
+//! Must emit "AFFINITY_TEST_PASSED" on success.
+
+use libbreenix::error::Error;
+use libbreenix::process::{self, ForkResult};
+use libbreenix::Errno;
+
+/// Passes over each pinned CPU that must all land on it
+const PIN_CHECKS: usize = 20;
+
+fn is_errno<T>(result: &Result<T, Error>, errno: Errno) -> bool {
+    matches!(result, Err(Error::Os(e)) if *e == errno)
+}
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+/// Yield and spin a little so the scheduler gets chances to move us, then
+/// check we are still on `cpu`
+fn stays_on(cpu: u32) -> bool {
+    (0..PIN_CHECKS).all(|i| {
+        let _ = process::yield_now();
+        let mut x = 0u64;
+        for j in 0..10_000u64 {
+            x = std::hint::black_box(x.wrapping_add(i as u64 ^ j));
+        }
+        matches!(process::sched_getcpu(), Ok(now) if now == cpu)
+    })
+}
+
+/// The value of a `/proc/<pid>/status` field
+fn status_field(pid: i32, field: &str) -> Option<String> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(field)?.strip_prefix(":\t"))
+        .map(String::from)
+}
+
+/// Exit with the inherited mask, or 255 if it stays off the pinned CPU
+fn report_affinity() -> i32 {
+    let mask = process::sched_getaffinity(0).unwrap_or(0);
+    let on_mask = process::sched_getcpu().is_ok_and(|cpu| mask & (1 << cpu) != 0);
+    if on_mask {
+        mask as i32 & 0xff
+    } else {
+        255
+    }
+}
+
+fn main() {
+    println!("=== CPU Affinity Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+    let pid = process::getpid().map(|p| p.raw() as i32).unwrap_or(0);
+
+    println!("\nTest 1: default affinity");
+    let online = process::sched_getaffinity(0).unwrap_or(0);
+    let cpus: Vec<u32> = (0..64).filter(|&cpu| online & (1 << cpu) != 0).collect();
+    println!("  online mask {:#x}, {} CPU(s)", online, cpus.len());
+    report(
+        "all online CPUs are allowed",
+        online & 1 != 0 && online == (1u64 << cpus.len()) - 1,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "sched_getcpu is in the mask",
+        matches!(process::sched_getcpu(), Ok(cpu) if online & (1 << cpu) != 0),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "getaffinity by pid matches",
+        matches!(process::sched_getaffinity(pid), Ok(mask) if mask == online),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 2: pin to each CPU");
+    let mut pinned_all = true;
+    let mut proc_ok = true;
+    for &cpu in &cpus {
+        let set = process::sched_setaffinity(0, 1 << cpu);
+        let mask = process::sched_getaffinity(0);
+        let stays = stays_on(cpu);
+        let listed = status_field(pid, "Cpus_allowed_list");
+        let hex = status_field(pid, "Cpus_allowed");
+        println!(
+            "  cpu {}: set {:?}, stays {}, Cpus_allowed_list {:?}",
+            cpu,
+            set.is_ok(),
+            stays,
+            listed
+        );
+        pinned_all &= set.is_ok() && matches!(mask, Ok(m) if m == 1 << cpu) && stays;
+        proc_ok &= listed == Some(cpu.to_string()) && hex == Some(format!("{:x}", 1u64 << cpu));
+    }
+    report(
+        "pinned thread runs only on its CPU",
+        pinned_all,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "/proc/<pid>/status shows Cpus_allowed",
+        proc_ok,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 3: affinity is inherited by fork");
+    let last = *cpus.last().unwrap_or(&0);
+    let _ = process::sched_setaffinity(0, 1 << last);
+    let inherited = match process::fork() {
+        Ok(ForkResult::Child) => process::exit(report_affinity()),
+        Ok(ForkResult::Parent(child)) => {
+            let mut status = 0;
+            process::waitpid(child.raw() as i32, &mut status, 0)
+                .ok()
+                .filter(|_| process::wifexited(status))
+                .map(|_| process::wexitstatus(status))
+        }
+        Err(_) => None,
+    };
+    report(
+        "child starts pinned to the parent's CPU",
+        inherited == Some(1 << last),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 4: invalid masks");
+    report(
+        "empty mask is EINVAL",
+        is_errno(&process::sched_setaffinity(0, 0), Errno::EINVAL),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "mask of offline CPUs only is EINVAL",
+        is_errno(&process::sched_setaffinity(0, !online), Errno::EINVAL),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "missing pid is ESRCH",
+        is_errno(&process::sched_setaffinity(0x7fff, online), Errno::ESRCH)
+            && is_errno(&process::sched_getaffinity(0x7fff), Errno::ESRCH),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "a rejected mask leaves the pin in place",
+        matches!(process::sched_getaffinity(0), Ok(mask) if mask == 1 << last),
+        &mut passed,
+        &mut failed,
+    );
+
+    let restored = process::sched_setaffinity(0, u64::MAX);
+    report(
+        "all CPUs can be allowed again",
+        restored.is_ok() && matches!(process::sched_getaffinity(0), Ok(mask) if mask == online),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("AFFINITY_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("AFFINITY_TEST_FAILED");
+        process::exit(1);
+    }
+}
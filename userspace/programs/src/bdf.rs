@@ -0,0 +1,162 @@
+//! bdf - report filesystem disk space usage
+//!
+//! Usage: bdf [-h] [-i] [path...]
+//!
+//! With no paths, reports every mounted filesystem (/, /home, /proc, /dev,
+//! /dev/pts). Otherwise reports the filesystem containing each path.
+//!
+//! Options:
+//! - `-h` - Print sizes in human-readable units (K, M, G)
+//! - `-i` - Report inode usage instead of block usage
+//!
+//! Installed as /bin/df in place of the BusyBox applet.
+
+use libbreenix::fs::{
+    self, StatFs, DEVFS_SUPER_MAGIC, DEVPTS_SUPER_MAGIC, EXT2_SUPER_MAGIC, PROC_SUPER_MAGIC,
+};
+
+/// Mount points reported when no paths are given
+const MOUNT_POINTS: &[&str] = &["/", "/home", "/proc", "/dev", "/dev/pts"];
+
+/// Filesystem type name for a statfs magic number
+fn fs_type_name(magic: i64) -> &'static str {
+    match magic {
+        EXT2_SUPER_MAGIC => "ext2",
+        PROC_SUPER_MAGIC => "proc",
+        DEVFS_SUPER_MAGIC => "devfs",
+        DEVPTS_SUPER_MAGIC => "devpts",
+        _ => "unknown",
+    }
+}
+
+/// Format a byte count with a K/M/G suffix, like `df -h`
+fn human_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "K", "M", "G", "T"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 || value >= 10.0 {
+        format!("{:.0}{}", value, UNITS[unit])
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
+
+/// Percentage of `used` out of `used + avail`, rounded up as df does
+fn use_percent(used: u64, avail: u64) -> String {
+    let total = used + avail;
+    if total == 0 {
+        return "-".to_string();
+    }
+    format!("{}%", (used * 100).div_ceil(total))
+}
+
+fn print_header(human: bool, inodes: bool) {
+    let size_col = match (inodes, human) {
+        (true, _) => "Inodes",
+        (false, true) => "Size",
+        (false, false) => "1K-blocks",
+    };
+    let (used_col, avail_col, pct_col) = if inodes {
+        ("IUsed", "IFree", "IUse%")
+    } else {
+        ("Used", "Available", "Use%")
+    };
+    println!(
+        "{:<8} {:>10} {:>10} {:>10} {:>5} Mounted on",
+        "Type", size_col, used_col, avail_col, pct_col
+    );
+}
+
+fn print_row(mount: &str, st: &StatFs, human: bool, inodes: bool) {
+    let (size, used, avail, pct) = if inodes {
+        let used = st.f_files.saturating_sub(st.f_ffree);
+        (
+            st.f_files.to_string(),
+            used.to_string(),
+            st.f_ffree.to_string(),
+            use_percent(used, st.f_ffree),
+        )
+    } else {
+        let bsize = st.f_frsize.max(st.f_bsize) as u64;
+        let total = st.f_blocks * bsize;
+        let used = st.f_blocks.saturating_sub(st.f_bfree) * bsize;
+        let avail = st.f_bavail * bsize;
+        let pct = use_percent(used, avail);
+        if human {
+            (human_size(total), human_size(used), human_size(avail), pct)
+        } else {
+            (
+                (total / 1024).to_string(),
+                (used / 1024).to_string(),
+                (avail / 1024).to_string(),
+                pct,
+            )
+        }
+    };
+    println!(
+        "{:<8} {:>10} {:>10} {:>10} {:>5} {}",
+        fs_type_name(st.f_type),
+        size,
+        used,
+        avail,
+        pct,
+        mount
+    );
+}
+
+fn main() {
+    let mut human = false;
+    let mut inodes = false;
+    let mut paths: Vec<String> = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-h" => human = true,
+            "-i" => inodes = true,
+            "-hi" | "-ih" => {
+                human = true;
+                inodes = true;
+            }
+            s if s.starts_with('-') => {
+                eprintln!("bdf: unknown option: {}", s);
+                eprintln!("Usage: bdf [-h] [-i] [path...]");
+                std::process::exit(1);
+            }
+            s => paths.push(s.to_string()),
+        }
+    }
+
+    print_header(human, inodes);
+
+    let mut status = 0;
+    if paths.is_empty() {
+        // Report each mount once; /home falls back to the root fs when unmounted
+        let mut seen: Vec<(i64, [i32; 2])> = Vec::new();
+        for mount in MOUNT_POINTS {
+            if let Ok(st) = fs::statfs(mount) {
+                let key = (st.f_type, st.f_fsid);
+                if st.f_type == EXT2_SUPER_MAGIC && seen.contains(&key) {
+                    continue;
+                }
+                seen.push(key);
+                print_row(mount, &st, human, inodes);
+            }
+        }
+    } else {
+        for path in &paths {
+            match fs::statfs(path) {
+                Ok(st) => print_row(path, &st, human, inodes),
+                Err(e) => {
+                    eprintln!("bdf: {}: {}", path, e);
+                    status = 1;
+                }
+            }
+        }
+    }
+
+    std::process::exit(status);
+}
@@ -107,17 +107,13 @@ fn scan_fonts() -> Vec<FontEntry> {
             if name_bytes.len() >= 4 {
                 let ext_start = name_bytes.len() - 4;
                 let ext = &name_bytes[ext_start..];
-                if ext[0] == b'.'
-                    && (ext[1] == b't' || ext[1] == b'T')
-                    && (ext[2] == b't' || ext[2] == b'T')
-                    && (ext[3] == b'f' || ext[3] == b'F')
-                {
+                if ext.eq_ignore_ascii_case(b".ttf") || ext.eq_ignore_ascii_case(b".otf") {
                     let name_str = core::str::from_utf8(name_bytes).unwrap_or("?");
                     let path = format!("{}/{}", FONT_DIR, name_str);
 
                     let display_name = name_str
-                        .trim_end_matches(".ttf")
-                        .trim_end_matches(".TTF")
+                        .get(..name_str.len().saturating_sub(4))
+                        .unwrap_or(name_str)
                         .replace('-', " ")
                         .replace('_', " ");
 
@@ -235,9 +231,11 @@ fn write_full_config(config: &FontConfig) {
          mono.font={}\n\
          mono.size={}\n\
          display.font={}\n\
-         display.size={}\n",
+         display.size={}\n\
+         fallback.fonts={}\n",
         config.mono_path, format_size(config.mono_size),
         config.display_path, format_size(config.display_size),
+        config.fallback_paths.join(","),
     );
     let _ = std::fs::write(CONFIG_PATH, content);
 }
@@ -432,6 +432,9 @@ fn native_exec(
             let dfl = libbreenix::signal::Sigaction::default_action();
             let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGINT, Some(&dfl), None);
             let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGQUIT, Some(&dfl), None);
+            let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGTSTP, Some(&dfl), None);
+            let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGTTIN, Some(&dfl), None);
+            let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGTTOU, Some(&dfl), None);
 
             // Child: redirect stdout/stderr to pipes, close read ends
             let _ = libbreenix::io::close(stdout_r);
@@ -756,6 +759,9 @@ fn native_pipe(
                 let dfl = libbreenix::signal::Sigaction::default_action();
                 let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGINT, Some(&dfl), None);
                 let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGQUIT, Some(&dfl), None);
+                let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGTSTP, Some(&dfl), None);
+                let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGTTIN, Some(&dfl), None);
+                let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGTTOU, Some(&dfl), None);
 
                 // Set up stdin: first child keeps original stdin,
                 // others read from previous pipe
@@ -1183,6 +1189,9 @@ fn command_executor_fn(
             let dfl = libbreenix::signal::Sigaction::default_action();
             let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGINT, Some(&dfl), None);
             let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGQUIT, Some(&dfl), None);
+            let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGTSTP, Some(&dfl), None);
+            let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGTTIN, Some(&dfl), None);
+            let _ = libbreenix::signal::sigaction(libbreenix::signal::SIGTTOU, Some(&dfl), None);
 
             // Build argv pointer array from stack-buffered data (no heap access)
             let mut argv_ptrs = [core::ptr::null::<u8>(); MAX_ARGS];
@@ -2237,40 +2246,24 @@ fn run_repl() {
             continue;
         }
 
-        // Handle shell builtins (cd, pwd, exit, which, help) in shell syntax
-        if let Some(code) = builtin_wrap(line) {
-            match ctx.eval(&code) {
-                Ok(result) => {
-                    if !result.is_undefined() {
-                        let formatted = ctx.format_value(result);
-                        let msg = format!("{}\n", formatted);
-                        let _ = io::stdout().write_all(msg.as_bytes());
-                        let _ = io::stdout().flush();
-                    }
-                }
-                Err(e) => {
-                    let msg = format!("{}\n", e);
-                    let _ = io::stderr().write_all(msg.as_bytes());
-                }
-            }
+        // Handle `time <command>` as a shell builtin
+        if let Some(command) = parse_time_command(line) {
+            time_line(&mut ctx, command);
             continue;
         }
 
-        // Handle bare command shorthand: if it looks like a command (starts with
-        // a letter, no JS operators), wrap it in exec() automatically
-        let is_auto_exec = should_auto_exec(line);
-        let code = if is_auto_exec {
-            auto_exec_wrap(line)
-        } else {
-            line.to_string()
-        };
+        eval_line(&mut ctx, line);
+    }
+}
 
+/// Run one line typed at the prompt: a shell builtin, a bare command or
+/// JavaScript.
+fn eval_line(ctx: &mut Context, line: &str) {
+    // Handle shell builtins (cd, pwd, exit, which, help) in shell syntax
+    if let Some(code) = builtin_wrap(line) {
         match ctx.eval(&code) {
             Ok(result) => {
-                // Auto-print non-undefined expression results (like Node REPL),
-                // but only for JS expressions -- auto-exec'd commands handle
-                // their own output via auto_exec_wrap().
-                if !is_auto_exec && !result.is_undefined() {
+                if !result.is_undefined() {
                     let formatted = ctx.format_value(result);
                     let msg = format!("{}\n", formatted);
                     let _ = io::stdout().write_all(msg.as_bytes());
@@ -2282,9 +2275,82 @@ fn run_repl() {
                 let _ = io::stderr().write_all(msg.as_bytes());
             }
         }
+        return;
+    }
+
+    // Handle bare command shorthand: if it looks like a command (starts with
+    // a letter, no JS operators), wrap it in exec() automatically
+    let is_auto_exec = should_auto_exec(line);
+    let code = if is_auto_exec {
+        auto_exec_wrap(line)
+    } else {
+        line.to_string()
+    };
+
+    match ctx.eval(&code) {
+        Ok(result) => {
+            // Auto-print non-undefined expression results (like Node REPL),
+            // but only for JS expressions -- auto-exec'd commands handle
+            // their own output via auto_exec_wrap().
+            if !is_auto_exec && !result.is_undefined() {
+                let formatted = ctx.format_value(result);
+                let msg = format!("{}\n", formatted);
+                let _ = io::stdout().write_all(msg.as_bytes());
+                let _ = io::stdout().flush();
+            }
+        }
+        Err(e) => {
+            let msg = format!("{}\n", e);
+            let _ = io::stderr().write_all(msg.as_bytes());
+        }
     }
 }
 
+/// Parse `time <command>`, returning the command.
+fn parse_time_command(line: &str) -> Option<&str> {
+    let command = line.strip_prefix("time ")?.trim();
+    (!command.is_empty()).then_some(command)
+}
+
+/// Format a duration in nanoseconds as `0m1.234s`.
+fn format_duration(ns: u64) -> String {
+    let millis = ns / 1_000_000;
+    let secs = millis / 1000;
+    format!("{}m{}.{:03}s", secs / 60, secs % 60, millis % 1000)
+}
+
+/// The `time` builtin: run `command` like a typed line, then report the real
+/// time it took and the user and system time of the children it ran.
+fn time_line(ctx: &mut Context, command: &str) {
+    use libbreenix::process::{getrusage, Rusage, RUSAGE_CHILDREN};
+
+    let monotonic_ns = || {
+        libbreenix::time::now_monotonic()
+            .map(|ts| ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64)
+            .unwrap_or(0)
+    };
+    let cpu_ns = |usage: &Rusage| {
+        let ns = |tv: &libbreenix::signal::Timeval| {
+            tv.tv_sec as u64 * 1_000_000_000 + tv.tv_usec as u64 * 1000
+        };
+        (ns(&usage.ru_utime), ns(&usage.ru_stime))
+    };
+
+    let start = monotonic_ns();
+    let (user_before, sys_before) = cpu_ns(&getrusage(RUSAGE_CHILDREN).unwrap_or_default());
+    eval_line(ctx, command);
+    let real = monotonic_ns().saturating_sub(start);
+    let (user_after, sys_after) = cpu_ns(&getrusage(RUSAGE_CHILDREN).unwrap_or_default());
+
+    let msg = format!(
+        "\nreal\t{}\nuser\t{}\nsys\t{}\n",
+        format_duration(real),
+        format_duration(user_after.saturating_sub(user_before)),
+        format_duration(sys_after.saturating_sub(sys_before)),
+    );
+    let _ = io::stderr().write_all(msg.as_bytes());
+}
+
 /// Convert shell-style builtin commands to JS function calls.
 ///
 /// Returns `Some(js_code)` if the line is a recognized builtin, `None` otherwise.
@@ -2330,7 +2396,7 @@ fn builtin_wrap(line: &str) -> Option<String> {
             Some(String::from("undefined"))
         }
         "help" => {
-            Some(String::from(r#"print("bsh -- Breenish ECMAScript Shell\n\nShell builtins:\n  cd <dir>       Change directory\n  pwd            Print working directory\n  exit [code]    Exit the shell\n  which <cmd>    Find command in PATH\n  source <file>  Execute a script file\n  fart [count]   Play fart sound(s)\n  help           Show this help\n\nProcess execution:\n  exec(cmd, ...args)    Run a command, returns {exitCode, stdout, stderr}\n  pipe(cmd1, cmd2, ...) Pipeline commands\n  ls /bin               Bare commands are auto-wrapped in exec()\n\nFile operations:\n  readFile(path)          Read file contents\n  writeFile(path, data)   Write to file\n  glob(pattern)           Wildcard expansion (*.rs)\n\nEnvironment:\n  env()              All environment variables\n  env(name)          Get variable\n  env(name, value)   Set variable\n\nJavaScript:\n  Full ECMAScript: let/const, functions, arrows, closures,\n  if/else, for/while, try/catch, async/await, template literals,\n  destructuring, spread, Map, Set, JSON, Math, Promise\n\nUse Tab for auto-completion. Up/Down for history.\nSee: docs/user-guide/bsh-shell-guide.md for full documentation.")"#))
+            Some(String::from(r#"print("bsh -- Breenish ECMAScript Shell\n\nShell builtins:\n  cd <dir>       Change directory\n  pwd            Print working directory\n  exit [code]    Exit the shell\n  which <cmd>    Find command in PATH\n  source <file>  Execute a script file\n  time <cmd>     Run a command and report its run time\n  fart [count]   Play fart sound(s)\n  help           Show this help\n\nProcess execution:\n  exec(cmd, ...args)    Run a command, returns {exitCode, stdout, stderr}\n  pipe(cmd1, cmd2, ...) Pipeline commands\n  ls /bin               Bare commands are auto-wrapped in exec()\n\nFile operations:\n  readFile(path)          Read file contents\n  writeFile(path, data)   Write to file\n  glob(pattern)           Wildcard expansion (*.rs)\n\nEnvironment:\n  env()              All environment variables\n  env(name)          Get variable\n  env(name, value)   Set variable\n\nJavaScript:\n  Full ECMAScript: let/const, functions, arrows, closures,\n  if/else, for/while, try/catch, async/await, template literals,\n  destructuring, spread, Map, Set, JSON, Math, Promise\n\nUse Tab for auto-completion. Up/Down for history.\nSee: docs/user-guide/bsh-shell-guide.md for full documentation.")"#))
         }
         _ => None,
     }
@@ -2562,8 +2628,22 @@ fn run_file(path: &str) {
     }
 }
 
+/// Keep the shell running when it hands the terminal to a job and takes it
+/// back from the background; children restore the defaults after fork
+fn ignore_job_control_signals() {
+    let ign = libbreenix::signal::Sigaction::ignore();
+    for sig in [
+        libbreenix::signal::SIGTSTP,
+        libbreenix::signal::SIGTTIN,
+        libbreenix::signal::SIGTTOU,
+    ] {
+        let _ = libbreenix::signal::sigaction(sig, Some(&ign), None);
+    }
+}
+
 fn main() {
     let args: Vec<String> = std::env::args().collect();
+    ignore_job_control_signals();
 
     if args.len() == 1 {
         // No arguments: interactive REPL
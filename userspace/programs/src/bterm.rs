@@ -1,18 +1,23 @@
 //! bterm — Standalone terminal emulator using Breengel windowing.
 //!
 //! Each tab spawns its own PTY + shell (bsh). Keyboard input flows to the
-//! active tab's PTY master; PTY output is fed through a VT100/ANSI parser
-//! and rendered into the Breengel window's framebuffer.
+//! active tab's PTY master; PTY output is fed through the libvt terminal
+//! emulator and rendered into the Breengel window's framebuffer.
 //!
 //! Keyboard shortcuts:
 //!   Ctrl+T       — open a new tab
 //!   Ctrl+W       — close the active tab (exits if last tab)
 //!   Ctrl+Plus/=  — increase font size
 //!   Ctrl+Minus   — decrease font size
+//!   Shift+PgUp/PgDn, mouse wheel — page through scrollback
+//!   Ctrl+Shift+C — copy the text selected with the mouse
+//!   Ctrl+Shift+V — paste the clipboard
+//!
+//! Files dropped on the window are typed in as (quoted) paths.
 
 use std::process;
 
-use breengel::{Window, Event, CachedFont, TabBar, Rect, Theme, Color, FrameBuf};
+use breengel::{Window, Event, CachedFont, ClipboardData, FontChain, TabBar, Rect, Theme, Color, FrameBuf, cursor_shape, mime};
 use libbreenix::io;
 use libbreenix::fs;
 use libbreenix::process::{fork, exec, setsid, ForkResult};
@@ -20,7 +25,10 @@ use libbreenix::pty;
 use libbreenix::types::Fd;
 use libbreenix::time;
 
+use libvt::{Attrs, Cell, CursorKey, MouseButton, MouseEvent, MouseEventKind, MouseMode, Rgb, Terminal};
+
 use libgfx::bitmap_font;
+use libgfx::text;
 use libgfx::ttf_font;
 
 use libbui::{InputState, WidgetEvent};
@@ -32,6 +40,9 @@ const MIN_FONT_SIZE: f32 = 8.0;
 const MAX_FONT_SIZE: f32 = 32.0;
 const FONT_SIZE_STEP: f32 = 1.0;
 
+/// Lines the mouse wheel scrolls per notch.
+const WHEEL_LINES: usize = 3;
+
 /// Bitmap font cell dimensions (fallback when TTF is unavailable).
 const BITMAP_CELL_W: usize = 7;
 const BITMAP_CELL_H: usize = 18;
@@ -43,288 +54,140 @@ const TAB_BAR_HEIGHT: i32 = 24;
 const BG_COLOR: Color = Color::rgb(30, 30, 40);
 const FG_COLOR: Color = Color::rgb(204, 204, 204);
 
-// ANSI color palette (standard 8 colors)
-const ANSI_COLORS: [Color; 8] = [
-    Color::rgb(0, 0, 0),       // 0: black
-    Color::rgb(205, 49, 49),   // 1: red
-    Color::rgb(13, 188, 121),  // 2: green
-    Color::rgb(229, 229, 16),  // 3: yellow
-    Color::rgb(36, 114, 200),  // 4: blue
-    Color::rgb(188, 63, 188),  // 5: magenta
-    Color::rgb(17, 168, 205),  // 6: cyan
-    Color::rgb(229, 229, 229), // 7: white
-];
-
 // ─── Window dimensions ──────────────────────────────────────────────────────
 
 const WIN_WIDTH: u32 = 750;
 const WIN_HEIGHT: u32 = 550;
 
-// ─── Character Cell ─────────────────────────────────────────────────────────
+// ─── Rendering ─────────────────────────────────────────────────────────────
 
-#[derive(Clone, Copy)]
-struct Cell {
-    ch: u8,
-    fg: Color,
-    bg: Color,
-    bold: bool,
-}
+fn to_color(rgb: Rgb) -> Color { Color::rgb(rgb.r, rgb.g, rgb.b) }
 
-impl Cell {
-    const fn blank() -> Self {
-        Self { ch: b' ', fg: FG_COLOR, bg: BG_COLOR, bold: false }
-    }
-}
-
-// ─── ANSI Parser State Machine ──────────────────────────────────────────────
+const DEFAULT_FG: Rgb = Rgb::new(FG_COLOR.r, FG_COLOR.g, FG_COLOR.b);
+const DEFAULT_BG: Rgb = Rgb::new(BG_COLOR.r, BG_COLOR.g, BG_COLOR.b);
 
+/// A mouse selection: the cell the drag began at and the cell it is at
+/// now, as (column, row) of the view.
 #[derive(Clone, Copy, PartialEq)]
-enum AnsiState {
-    Normal,
-    Escape,
-    Csi,
-    CsiParam,
-    OscString,
-}
-
-// ─── Terminal Emulator ──────────────────────────────────────────────────────
-
-struct TermEmu {
-    cols: usize,
-    rows: usize,
-    cells: Vec<Cell>,
-    cursor_x: usize,
-    cursor_y: usize,
-    fg: Color,
-    bg: Color,
-    bold: bool,
-    ansi_state: AnsiState,
-    ansi_params: [u16; 8],
-    ansi_param_idx: usize,
-    dirty: bool,
+struct Selection {
+    anchor: (usize, usize),
+    head: (usize, usize),
 }
 
-impl TermEmu {
-    fn new(cols: usize, rows: usize) -> Self {
-        Self {
-            cols, rows,
-            cells: vec![Cell::blank(); cols * rows],
-            cursor_x: 0, cursor_y: 0,
-            fg: FG_COLOR, bg: BG_COLOR, bold: false,
-            ansi_state: AnsiState::Normal,
-            ansi_params: [0; 8],
-            ansi_param_idx: 0,
-            dirty: true,
-        }
+impl Selection {
+    fn contains(&self, col: usize, row: usize) -> bool {
+        let (a, h) = ((self.anchor.1, self.anchor.0), (self.head.1, self.head.0));
+        let (start, end) = if a <= h { (a, h) } else { (h, a) };
+        (start..=end).contains(&(row, col))
     }
+}
 
-    fn cell(&self, x: usize, y: usize) -> &Cell { &self.cells[y * self.cols + x] }
-    fn cell_mut(&mut self, x: usize, y: usize) -> &mut Cell { &mut self.cells[y * self.cols + x] }
-
-    fn resize(&mut self, new_cols: usize, new_rows: usize) {
-        if new_cols == self.cols && new_rows == self.rows {
-            return;
-        }
-        let mut new_cells = vec![Cell::blank(); new_cols * new_rows];
-        let copy_cols = self.cols.min(new_cols);
-        let copy_rows = self.rows.min(new_rows);
-        for y in 0..copy_rows {
-            for x in 0..copy_cols {
-                new_cells[y * new_cols + x] = self.cells[y * self.cols + x];
+/// Paint the rows of `term` that changed since the last render.
+fn render_terminal(term: &mut Terminal, fb: &mut FrameBuf, x_off: usize, y_off: usize,
+                   clip_w: usize, clip_h: usize, cell_w: usize, cell_h: usize,
+                   font_size: f32, mut ttf: Option<&mut FontChain>, selection: Option<Selection>) {
+    if !term.is_damaged() { return; }
+    // FrameBuf has no blit, so a scroll repaints every row
+    let scrolled = term.scrolled() > 0;
+    let max_x = (x_off + clip_w).min(fb.width);
+    let max_y = (y_off + clip_h).min(fb.height);
+    let (cursor_x, cursor_y) = term.cursor();
+    for row in 0..term.rows() {
+        let py = y_off + row * cell_h;
+        if py + cell_h > max_y { break; }
+        if !scrolled && term.damage(row).is_none() { continue; }
+        let line = term.display_line(row);
+        let cols = term.cols().min((max_x.saturating_sub(x_off)) / cell_w.max(1));
+        let cells: Vec<(Cell, Color)> = (0..cols).map(|col| {
+            let cell = line.get(col).copied().unwrap_or(Cell::BLANK);
+            let (fg, bg) = cell.colors(DEFAULT_FG, DEFAULT_BG);
+            let (mut fg, mut bg) = (to_color(fg), to_color(bg));
+            // No bold face: brighten text in the default color instead
+            if cell.attrs.contains(Attrs::BOLD) && cell.fg == libvt::Color::Default {
+                fg = Color::rgb(fg.r.saturating_add(40), fg.g.saturating_add(40), fg.b.saturating_add(40));
             }
-        }
-        self.cols = new_cols;
-        self.rows = new_rows;
-        self.cells = new_cells;
-        self.cursor_x = self.cursor_x.min(new_cols.saturating_sub(1));
-        self.cursor_y = self.cursor_y.min(new_rows.saturating_sub(1));
-        self.dirty = true;
-    }
-
-    fn scroll_up(&mut self) {
-        let cols = self.cols;
-        for y in 1..self.rows {
-            for x in 0..cols {
-                self.cells[(y - 1) * cols + x] = self.cells[y * cols + x];
+            if selection.map_or(false, |s| s.contains(col, row)) {
+                (fg, bg) = (bg, fg);
             }
-        }
-        let last_row = self.rows - 1;
-        for x in 0..cols { self.cells[last_row * cols + x] = Cell::blank(); }
-        self.dirty = true;
-    }
-
-    fn put_char(&mut self, ch: u8) {
-        if self.cursor_x >= self.cols { self.cursor_x = 0; self.cursor_y += 1; }
-        if self.cursor_y >= self.rows { self.scroll_up(); self.cursor_y = self.rows - 1; }
-        let fg = self.fg; let bg = self.bg; let bold = self.bold;
-        let idx = self.cursor_y * self.cols + self.cursor_x;
-        self.cells[idx] = Cell { ch, fg, bg, bold };
-        self.cursor_x += 1;
-        self.dirty = true;
-    }
-
-    fn feed(&mut self, byte: u8) {
-        match self.ansi_state {
-            AnsiState::Normal => match byte {
-                0x1b => self.ansi_state = AnsiState::Escape,
-                b'\n' => {
-                    self.cursor_x = 0; self.cursor_y += 1;
-                    if self.cursor_y >= self.rows { self.scroll_up(); self.cursor_y = self.rows - 1; }
-                    self.dirty = true;
-                }
-                b'\r' => { self.cursor_x = 0; self.dirty = true; }
-                b'\t' => {
-                    let next_tab = (self.cursor_x + 8) & !7;
-                    while self.cursor_x < next_tab && self.cursor_x < self.cols { self.put_char(b' '); }
-                }
-                0x08 => { if self.cursor_x > 0 { self.cursor_x -= 1; self.dirty = true; } }
-                0x07 => {}
-                ch if ch >= 0x20 => self.put_char(ch),
-                _ => {}
-            },
-            AnsiState::Escape => match byte {
-                b'[' => { self.ansi_state = AnsiState::Csi; self.ansi_params = [0; 8]; self.ansi_param_idx = 0; }
-                b']' => { self.ansi_state = AnsiState::OscString; }
-                _ => { self.ansi_state = AnsiState::Normal; }
-            },
-            AnsiState::Csi | AnsiState::CsiParam => {
-                if byte >= b'0' && byte <= b'9' {
-                    self.ansi_state = AnsiState::CsiParam;
-                    let idx = self.ansi_param_idx;
-                    if idx < 8 {
-                        self.ansi_params[idx] = self.ansi_params[idx].saturating_mul(10).saturating_add((byte - b'0') as u16);
-                    }
-                } else if byte == b';' {
-                    if self.ansi_param_idx < 7 { self.ansi_param_idx += 1; }
-                } else {
-                    let nparams = self.ansi_param_idx + 1;
-                    self.execute_csi(byte, nparams);
-                    self.ansi_state = AnsiState::Normal;
+            let px = x_off + col * cell_w;
+            for dy in 0..cell_h { for dx in 0..cell_w { fb.put_pixel(px + dx, py + dy, bg); } }
+            (cell, fg)
+        }).collect();
+        // Backgrounds go down first so ligature glyphs can overhang the
+        // neighbouring cells of their run
+        let visible = |c: &(Cell, Color)| c.0.ch != ' ' && !c.0.attrs.contains(Attrs::HIDDEN);
+        let cluster = |cell: &Cell| -> String { std::iter::once(cell.ch).chain(cell.combining()).collect() };
+        let mut col = 0;
+        while col < cols {
+            if !visible(&cells[col]) { col += 1; continue; }
+            let px = x_off + col * cell_w;
+            let Some(ref mut chain) = ttf else {
+                bitmap_font::draw_char(fb, cells[col].0.ch, px, py, cells[col].1);
+                col += 1;
+                continue;
+            };
+            // Shape each run of same-colored text in one font together, then
+            // pin every glyph to its cluster's cell to keep the grid
+            let fg = cells[col].1;
+            let font = chain.font_for(&cluster(&cells[col].0));
+            let end = (col..cols).find(|&c| {
+                !visible(&cells[c]) || cells[c].1 != fg || chain.font_for(&cluster(&cells[c].0)) != font
+            }).unwrap_or(cols);
+            // The run's text with the column of every char
+            let mut run = String::new();
+            let mut columns = Vec::new();
+            for c in col..end {
+                for ch in cluster(&cells[c].0).chars() {
+                    run.push(ch);
+                    columns.push(c);
                 }
             }
-            AnsiState::OscString => {
-                if byte == 0x07 || byte == b'\\' { self.ansi_state = AnsiState::Normal; }
+            let glyphs = text::shape(chain.font(font), &run, font_size);
+            // Pen position within the current cell: marks follow their base
+            let (mut pen_col, mut pen) = (usize::MAX, 0.0);
+            for glyph in glyphs {
+                let c = columns[glyph.cluster];
+                if c != pen_col {
+                    // Fallback fonts are not monospaced to our grid and wide
+                    // characters span two cells: center them
+                    let span = if cells[c].0.attrs.contains(Attrs::WIDE) { 2 } else { 1 };
+                    pen = if font != 0 || span == 2 { ((span * cell_w) as f32 - glyph.x_advance) / 2.0 } else { 0.0 };
+                    pen_col = c;
+                }
+                let gx = (x_off + c * cell_w) as f32 + pen + glyph.x_offset;
+                ttf_font::draw_glyph(fb, chain.font_mut(font), glyph.glyph_index, gx as i32,
+                                     py as i32 - glyph.y_offset as i32, font_size, fg);
+                pen += glyph.x_advance;
             }
+            col = end;
         }
-    }
-
-    fn execute_csi(&mut self, cmd: u8, nparams: usize) {
-        let p0 = self.ansi_params[0] as usize;
-        let p1 = if nparams > 1 { self.ansi_params[1] as usize } else { 0 };
-        match cmd {
-            b'H' | b'f' => {
-                let row = if p0 > 0 { p0 - 1 } else { 0 };
-                let col = if p1 > 0 { p1 - 1 } else { 0 };
-                self.cursor_y = row.min(self.rows - 1);
-                self.cursor_x = col.min(self.cols - 1);
-                self.dirty = true;
+        for (col, (cell, fg)) in cells.iter().enumerate() {
+            let px = x_off + col * cell_w;
+            if cell.attrs.contains(Attrs::UNDERLINE) {
+                for dx in 0..cell_w { fb.put_pixel(px + dx, py + cell_h - 1, *fg); }
             }
-            b'A' => { let n = if p0 > 0 { p0 } else { 1 }; self.cursor_y = self.cursor_y.saturating_sub(n); self.dirty = true; }
-            b'B' => { let n = if p0 > 0 { p0 } else { 1 }; self.cursor_y = (self.cursor_y + n).min(self.rows - 1); self.dirty = true; }
-            b'C' => { let n = if p0 > 0 { p0 } else { 1 }; self.cursor_x = (self.cursor_x + n).min(self.cols - 1); self.dirty = true; }
-            b'D' => { let n = if p0 > 0 { p0 } else { 1 }; self.cursor_x = self.cursor_x.saturating_sub(n); self.dirty = true; }
-            b'G' => { let col = if p0 > 0 { p0 - 1 } else { 0 }; self.cursor_x = col.min(self.cols - 1); self.dirty = true; }
-            b'J' => {
-                match p0 {
-                    0 => {
-                        for x in self.cursor_x..self.cols { *self.cell_mut(x, self.cursor_y) = Cell::blank(); }
-                        for y in (self.cursor_y + 1)..self.rows { for x in 0..self.cols { *self.cell_mut(x, y) = Cell::blank(); } }
-                    }
-                    1 => {
-                        for y in 0..self.cursor_y { for x in 0..self.cols { *self.cell_mut(x, y) = Cell::blank(); } }
-                        for x in 0..=self.cursor_x.min(self.cols - 1) { *self.cell_mut(x, self.cursor_y) = Cell::blank(); }
-                    }
-                    2 | 3 => {
-                        for y in 0..self.rows { for x in 0..self.cols { *self.cell_mut(x, y) = Cell::blank(); } }
-                        self.cursor_x = 0; self.cursor_y = 0;
-                    }
-                    _ => {}
-                }
-                self.dirty = true;
+            if cell.attrs.contains(Attrs::STRIKETHROUGH) {
+                for dx in 0..cell_w { fb.put_pixel(px + dx, py + cell_h / 2, *fg); }
             }
-            b'K' => {
-                match p0 {
-                    0 => { for x in self.cursor_x..self.cols { *self.cell_mut(x, self.cursor_y) = Cell::blank(); } }
-                    1 => { for x in 0..=self.cursor_x.min(self.cols - 1) { *self.cell_mut(x, self.cursor_y) = Cell::blank(); } }
-                    2 => { for x in 0..self.cols { *self.cell_mut(x, self.cursor_y) = Cell::blank(); } }
-                    _ => {}
-                }
-                self.dirty = true;
-            }
-            b'd' => { let row = if p0 > 0 { p0 - 1 } else { 0 }; self.cursor_y = row.min(self.rows - 1); self.dirty = true; }
-            b'r' => { self.cursor_x = 0; self.cursor_y = 0; self.dirty = true; }
-            b'm' => {
-                if nparams == 1 && p0 == 0 { self.fg = FG_COLOR; self.bg = BG_COLOR; self.bold = false; }
-                else {
-                    for i in 0..nparams {
-                        let code = self.ansi_params[i];
-                        match code {
-                            0 => { self.fg = FG_COLOR; self.bg = BG_COLOR; self.bold = false; }
-                            1 => { self.bold = true; }
-                            22 => { self.bold = false; }
-                            30..=37 => { self.fg = ANSI_COLORS[(code - 30) as usize]; }
-                            39 => { self.fg = FG_COLOR; }
-                            40..=47 => { self.bg = ANSI_COLORS[(code - 40) as usize]; }
-                            49 => { self.bg = BG_COLOR; }
-                            90..=97 => {
-                                let mut c = ANSI_COLORS[(code - 90) as usize];
-                                c.r = c.r.saturating_add(60); c.g = c.g.saturating_add(60); c.b = c.b.saturating_add(60);
-                                self.fg = c;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
-            b'l' | b'h' => {}
-            _ => {}
         }
-    }
-
-    fn render(&mut self, fb: &mut FrameBuf, x_off: usize, y_off: usize,
-              clip_w: usize, clip_h: usize, cell_w: usize, cell_h: usize,
-              font_size: f32, mut ttf: Option<&mut CachedFont>) {
-        if !self.dirty { return; }
-        self.dirty = false;
-        let max_x = (x_off + clip_w).min(fb.width);
-        let max_y = (y_off + clip_h).min(fb.height);
-        for row in 0..self.rows {
-            let py = y_off + row * cell_h;
-            if py + cell_h > max_y { break; }
-            for col in 0..self.cols {
-                let cell = self.cell(col, row);
-                let px = x_off + col * cell_w;
-                if px + cell_w > max_x { break; }
-                for dy in 0..cell_h { for dx in 0..cell_w { fb.put_pixel(px + dx, py + dy, cell.bg); } }
-                if cell.ch != b' ' {
-                    let fg = if cell.bold {
-                        Color::rgb(cell.fg.r.saturating_add(40), cell.fg.g.saturating_add(40), cell.fg.b.saturating_add(40))
-                    } else { cell.fg };
-                    if let Some(ref mut font) = ttf {
-                        ttf_font::draw_char(fb, *font, cell.ch as char, px as i32, py as i32, font_size, fg);
-                    } else {
-                        bitmap_font::draw_char(fb, cell.ch as char, px, py, fg);
-                    }
+        // Cursor underline
+        if term.cursor_visible() && row == cursor_y && cursor_x < term.cols() {
+            let cx = x_off + cursor_x * cell_w;
+            let cw = cell_w;
+            for dy in 0..2usize { for dx in 0..cw {
+                if cx + dx < max_x && py + cell_h - 2 + dy < max_y {
+                    fb.put_pixel(cx + dx, py + cell_h - 2 + dy, Color::WHITE);
                 }
-            }
-            // Cursor underline
-            if row == self.cursor_y && self.cursor_x < self.cols {
-                let cx = x_off + self.cursor_x * cell_w;
-                let cw = cell_w;
-                for dy in 0..2usize { for dx in 0..cw {
-                    if cx + dx < max_x && py + cell_h - 2 + dy < max_y {
-                        fb.put_pixel(cx + dx, py + cell_h - 2 + dy, Color::WHITE);
-                    }
-                }}
-            }
+            }}
         }
     }
+    term.clear_damage();
 }
 
 // ─── Tab ────────────────────────────────────────────────────────────────────
 
 struct Tab {
-    emu: TermEmu,
+    term: Terminal,
     master_fd: Fd,
     #[allow(dead_code)] // used for future kill/waitpid on tab close
     child_pid: i64,
@@ -402,7 +265,7 @@ fn spawn_tab_cmd(cols: usize, rows: usize, cmd: &[u8]) -> Tab {
     let (master_fd, child_pid) = spawn_child(cmd);
     let _ = io::fcntl_setfl(master_fd, io::status_flags::O_NONBLOCK);
     Tab {
-        emu: TermEmu::new(cols, rows),
+        term: Terminal::new(cols, rows),
         master_fd,
         child_pid,
     }
@@ -413,6 +276,73 @@ fn make_static_label(s: &str) -> &'static [u8] {
     Box::leak(boxed)
 }
 
+/// Label for a tab titled `title`, reusing an earlier label with the same text.
+fn title_label(labels: &mut Vec<&'static [u8]>, title: &str) -> &'static [u8] {
+    if let Some(label) = labels.iter().find(|label| **label == title.as_bytes()) {
+        return label;
+    }
+    let label = make_static_label(title);
+    labels.push(label);
+    label
+}
+
+/// Report a mouse event at window position (`x`, `y`) to the program in
+/// `tab`. Returns false if the program has not enabled mouse reporting.
+fn send_mouse(tab: &Tab, kind: MouseEventKind, button: MouseButton, x: i32, y: i32,
+              cell_w: usize, cell_h: usize) -> bool {
+    if tab.term.mouse_mode() == MouseMode::Off {
+        return false;
+    }
+    if y >= TAB_BAR_HEIGHT && x >= 0 {
+        let event = MouseEvent {
+            kind,
+            button,
+            col: (x as usize / cell_w).min(tab.term.cols() - 1),
+            row: ((y - TAB_BAR_HEIGHT) as usize / cell_h).min(tab.term.rows() - 1),
+            shift: false,
+            alt: false,
+            ctrl: false,
+        };
+        if let Some(report) = tab.term.mouse_report(&event) {
+            let _ = io::write(tab.master_fd, &report);
+        }
+    }
+    true
+}
+
+/// The cell under window position (`x`, `y`), clamped to the grid.
+fn cell_at(term: &Terminal, x: i32, y: i32, cell_w: usize, cell_h: usize) -> (usize, usize) {
+    let col = x.max(0) as usize / cell_w;
+    let row = (y - TAB_BAR_HEIGHT).max(0) as usize / cell_h;
+    (col.min(term.cols() - 1), row.min(term.rows() - 1))
+}
+
+/// Whether a key pressed with Ctrl held is `letter` (lowercase ASCII).
+fn is_ctrl_letter(ascii: u8, keycode: u16, letter: u8) -> bool {
+    ascii == letter - b'a' + 1
+        || keycode == letter as u16
+        || keycode == letter.to_ascii_uppercase() as u16
+}
+
+/// Quote `path` for the shell if it has characters the shell would split
+/// or expand.
+fn shell_quote(path: &str) -> String {
+    if !path.is_empty() && path.bytes().all(|b| b.is_ascii_alphanumeric() || b"/._-+,:@".contains(&b)) {
+        return String::from(path);
+    }
+    format!("'{}'", path.replace('\'', "'\\''"))
+}
+
+/// What pasting or dropping `data` types in: text as is, files as paths.
+fn paste_text(data: &ClipboardData) -> String {
+    if data.mime == mime::URI_LIST {
+        let paths: Vec<String> = data.file_paths().iter().map(|p| shell_quote(p)).collect();
+        paths.join(" ")
+    } else {
+        String::from(data.text().unwrap_or(""))
+    }
+}
+
 /// Compute cell dimensions from TTF font metrics.
 fn ttf_cell_dims(font: &mut CachedFont, size: f32) -> (usize, usize) {
     let metrics = font.metrics(size);
@@ -444,13 +374,13 @@ fn main() {
     let mut font_size = if win.mono_size() >= 6.0 { win.mono_size() } else { 14.0 };
     print!("[bterm] config: mono_size={} font_size={}\n", win.mono_size(), font_size);
 
-    // Take TrueType font for local use (needed alongside &mut FrameBuf)
-    let mut ttf_font: Option<CachedFont> = win.take_mono_font();
+    // Take TrueType fonts for local use (needed alongside &mut FrameBuf)
+    let mut ttf_font: Option<FontChain> = win.take_mono_chain();
 
     // Compute cell dimensions from font metrics (or fall back to bitmap constants)
-    let (mut cell_w, mut cell_h) = if let Some(ref mut font) = ttf_font {
+    let (mut cell_w, mut cell_h) = if let Some(ref mut chain) = ttf_font {
         print!("[bterm] font_size={} ", font_size);
-        let dims = ttf_cell_dims(font, font_size);
+        let dims = ttf_cell_dims(chain.primary_mut(), font_size);
         print!("cell={}x{}\n", dims.0, dims.1);
         dims
     } else {
@@ -481,6 +411,17 @@ fn main() {
 
     // Mouse state for InputState edge detection
     let mut prev_buttons: u32 = 0;
+    // Last pointer position and held button, for mouse reporting
+    let mut pointer: (i32, i32) = (0, 0);
+    let mut held_button = MouseButton::None;
+    // Tab labels made from window titles, kept to reuse rather than leak again
+    let mut title_labels: Vec<&'static [u8]> = Vec::new();
+    // Mouse selection in the active tab, and whether the button is still
+    // extending it
+    let mut selection: Option<Selection> = None;
+    let mut selecting = false;
+    // Text to type into the active tab from a paste or drop
+    let mut pasted: Option<String> = None;
 
     // Read buffer for PTY output
     let mut pty_buf = [0u8; 4096];
@@ -510,17 +451,33 @@ fn main() {
                         print!("[bterm] key: ascii={} keycode=0x{:02X} ctrl={}\n",
                                *ascii, *keycode, modifiers.ctrl);
                     }
+                    if modifiers.ctrl && modifiers.shift {
+                        // Ctrl+Shift+C: copy the selection
+                        if is_ctrl_letter(*ascii, *keycode, b'c') {
+                            if let (Some(sel), Some(tab)) = (selection, tabs.get(tab_bar.selected())) {
+                                let text = tab.term.display_text(sel.anchor, sel.head);
+                                let _ = win.set_clipboard(&[(mime::TEXT, text.as_bytes())]);
+                            }
+                            continue;
+                        }
+                        // Ctrl+Shift+V: paste
+                        if is_ctrl_letter(*ascii, *keycode, b'v') {
+                            pasted = win.request_clipboard(&[mime::TEXT, mime::URI_LIST]).map(|d| paste_text(&d));
+                            continue;
+                        }
+                    }
                     if modifiers.ctrl {
                         // Ctrl+T: new tab
                         if *ascii == b't' - b'a' + 1 || *ascii == b'T' - b'A' + 1
                            || *keycode == b't' as u16 || *keycode == b'T' as u16
                         {
+                            selection = None;
                             let label = make_tab_label();
                             let idx = tab_bar.add_tab(label);
                             tabs.push(spawn_tab(cols, rows));
                             tab_bar.set_selected(idx);
                             if let Some(tab) = tabs.get_mut(idx) {
-                                tab.emu.dirty = true;
+                                tab.term.damage_all();
                             }
                             continue;
                         }
@@ -532,12 +489,13 @@ fn main() {
                             if tabs.len() <= 1 {
                                 process::exit(0);
                             }
+                            selection = None;
                             let _ = io::close(tabs[sel].master_fd);
                             tabs.remove(sel);
                             tab_bar.remove_tab(sel);
                             let new_sel = tab_bar.selected();
                             if let Some(tab) = tabs.get_mut(new_sel) {
-                                tab.emu.dirty = true;
+                                tab.term.damage_all();
                             }
                             continue;
                         }
@@ -575,29 +533,65 @@ fn main() {
                         }
                     }
 
-                    // Arrow keys (USB HID keycodes)
+                    // Cursor and editing keys (USB HID keycodes)
                     let sel = tab_bar.selected();
-                    if let Some(tab) = tabs.get(sel) {
-                        match *keycode {
-                            79 => { let _ = io::write(tab.master_fd, b"\x1b[C"); } // Right
-                            80 => { let _ = io::write(tab.master_fd, b"\x1b[D"); } // Left
-                            81 => { let _ = io::write(tab.master_fd, b"\x1b[B"); } // Down
-                            82 => { let _ = io::write(tab.master_fd, b"\x1b[A"); } // Up
-                            _ => {
-                                if *ascii > 0 {
-                                    let _ = io::write(tab.master_fd, &[*ascii]);
-                                }
-                            }
+                    if let Some(tab) = tabs.get_mut(sel) {
+                        // The selection is of what is on screen, which this
+                        // key is about to change
+                        if selection.take().is_some() {
+                            selecting = false;
+                            tab.term.damage_all();
+                        }
+                        // Shift+PgUp/PgDn page through scrollback
+                        if modifiers.shift && (*keycode == 75 || *keycode == 78) {
+                            let page = tab.term.rows() as isize;
+                            tab.term.scroll_view(if *keycode == 75 { page } else { -page });
+                            continue;
+                        }
+                        tab.term.reset_view();
+                        let seq: &[u8] = match *keycode {
+                            79 => tab.term.cursor_key(CursorKey::Right),
+                            80 => tab.term.cursor_key(CursorKey::Left),
+                            81 => tab.term.cursor_key(CursorKey::Down),
+                            82 => tab.term.cursor_key(CursorKey::Up),
+                            74 => tab.term.cursor_key(CursorKey::Home),
+                            77 => tab.term.cursor_key(CursorKey::End),
+                            73 => b"\x1b[2~", // Insert
+                            76 => b"\x1b[3~", // Delete
+                            75 => b"\x1b[5~", // Page Up
+                            78 => b"\x1b[6~", // Page Down
+                            _ => if *ascii > 0 { core::slice::from_ref(ascii) } else { &[] },
+                        };
+                        if !seq.is_empty() {
+                            let _ = io::write(tab.master_fd, seq);
                         }
                     }
                 }
                 Event::MouseMove { x, y } => {
                     mouse_x = *x;
                     mouse_y = *y;
+                    pointer = (*x, *y);
+                    // I-beam over the text, unless the program takes the mouse
+                    let over_text = *y >= TAB_BAR_HEIGHT && tabs.get(tab_bar.selected())
+                        .map_or(false, |tab| tab.term.mouse_mode() == MouseMode::Off);
+                    let _ = win.set_cursor(if over_text { cursor_shape::TEXT } else { cursor_shape::ARROW });
+                    if let Some(tab) = tabs.get_mut(tab_bar.selected()) {
+                        send_mouse(tab, MouseEventKind::Move, held_button, *x, *y, cell_w, cell_h);
+                        if selecting {
+                            if let Some(ref mut sel) = selection {
+                                let head = cell_at(&tab.term, *x, *y, cell_w, cell_h);
+                                if head != sel.head {
+                                    sel.head = head;
+                                    tab.term.damage_all();
+                                }
+                            }
+                        }
+                    }
                 }
                 Event::MouseButton { button, pressed, x, y } => {
                     mouse_x = *x;
                     mouse_y = *y;
+                    pointer = (*x, *y);
                     if *button == 0 || *button == 1 {
                         if *pressed {
                             buttons |= 1;
@@ -605,11 +599,62 @@ fn main() {
                             buttons &= !1;
                         }
                     }
+                    let button = match *button {
+                        2 => MouseButton::Right,
+                        3 | 4 => MouseButton::Middle,
+                        _ => MouseButton::Left,
+                    };
+                    let kind = if *pressed { MouseEventKind::Press } else { MouseEventKind::Release };
+                    held_button = if *pressed { button } else { MouseButton::None };
+                    if let Some(tab) = tabs.get_mut(tab_bar.selected()) {
+                        // Programs with mouse reporting get the mouse; for the
+                        // rest, the left button selects text
+                        let reported = send_mouse(tab, kind, button, *x, *y, cell_w, cell_h);
+                        if !reported && button == MouseButton::Left {
+                            if *pressed && *y >= TAB_BAR_HEIGHT {
+                                let cell = cell_at(&tab.term, *x, *y, cell_w, cell_h);
+                                selection = Some(Selection { anchor: cell, head: cell });
+                                selecting = true;
+                                tab.term.damage_all();
+                            } else if !*pressed && selecting {
+                                selecting = false;
+                                // A click without a drag selects nothing
+                                if selection.map_or(false, |s| s.anchor == s.head) {
+                                    selection = None;
+                                    tab.term.damage_all();
+                                }
+                            }
+                        }
+                    }
+                }
+                Event::Drop { .. } => {
+                    pasted = win.request_drop(&[mime::URI_LIST, mime::TEXT]).map(|d| paste_text(&d));
+                }
+                Event::Scroll { delta_y } => {
+                    let sel = tab_bar.selected();
+                    if let Some(tab) = tabs.get_mut(sel) {
+                        let wheel = if *delta_y > 0 { MouseButton::WheelUp } else { MouseButton::WheelDown };
+                        if send_mouse(tab, MouseEventKind::Press, wheel, pointer.0, pointer.1, cell_w, cell_h) {
+                            continue;
+                        }
+                        if tab.term.alternate_screen() {
+                            // Full-screen programs without mouse support get arrow keys
+                            let key = if *delta_y > 0 { CursorKey::Up } else { CursorKey::Down };
+                            for _ in 0..WHEEL_LINES {
+                                let _ = io::write(tab.master_fd, tab.term.cursor_key(key));
+                            }
+                        } else {
+                            selection = None;
+                            selecting = false;
+                            tab.term.scroll_view(*delta_y as isize * WHEEL_LINES as isize);
+                            tab.term.damage_all();
+                        }
+                    }
                 }
                 Event::FontChanged => {
                     // Window loaded the new font internally — swap it in
-                    win.put_mono_font(ttf_font.take());
-                    ttf_font = win.take_mono_font();
+                    win.put_mono_chain(ttf_font.take());
+                    ttf_font = win.take_mono_chain();
                     font_size = win.mono_size();
                     print!("[bterm] font changed: {} size={}\n", win.mono_path(), font_size);
                     font_changed = true;
@@ -623,12 +668,12 @@ fn main() {
                         cols = new_cols;
                         rows = new_rows;
                         for tab in tabs.iter_mut() {
-                            tab.emu.resize(cols, rows);
+                            tab.term.resize(cols, rows);
                         }
                     }
                     tab_bar.set_rect(Rect::new(0, 0, *width as i32, TAB_BAR_HEIGHT));
                     for tab in tabs.iter_mut() {
-                        tab.emu.dirty = true;
+                        tab.term.damage_all();
                     }
                 }
                 _ => {}
@@ -637,9 +682,9 @@ fn main() {
 
         // Handle font size change: recompute grid, resize all tabs
         if font_changed {
-            if let Some(ref mut font) = ttf_font {
-                font.clear_cache();
-                let (new_cw, new_ch) = ttf_cell_dims(font, font_size);
+            if let Some(ref mut chain) = ttf_font {
+                chain.clear_cache();
+                let (new_cw, new_ch) = ttf_cell_dims(chain.primary_mut(), font_size);
                 cell_w = new_cw;
                 cell_h = new_ch;
                 let new_cols = (content_w as usize / cell_w).max(1);
@@ -648,12 +693,12 @@ fn main() {
                     cols = new_cols;
                     rows = new_rows;
                     for tab in tabs.iter_mut() {
-                        tab.emu.resize(cols, rows);
+                        tab.term.resize(cols, rows);
                     }
                 } else {
                     // Same grid size but different font — force redraw
                     for tab in tabs.iter_mut() {
-                        tab.emu.dirty = true;
+                        tab.term.damage_all();
                     }
                 }
             }
@@ -662,32 +707,51 @@ fn main() {
         // Pass mouse state to TabBar for tab switching
         let input = InputState::from_raw(mouse_x, mouse_y, buttons, prev_buttons);
         if let WidgetEvent::ValueChanged(_) = tab_bar.update(&input) {
+            selection = None;
+            selecting = false;
             let sel = tab_bar.selected();
             if let Some(tab) = tabs.get_mut(sel) {
-                tab.emu.dirty = true;
+                tab.term.damage_all();
             }
         }
         prev_buttons = buttons;
 
+        // Type pasted or dropped text into the active tab
+        if let Some(text) = pasted.take() {
+            if let Some(tab) = tabs.get_mut(tab_bar.selected()) {
+                tab.term.reset_view();
+                let _ = io::write(tab.master_fd, &tab.term.paste(text.as_bytes()));
+            }
+        }
+
         // ── 2. Read PTY output for ALL tabs (non-blocking) ──────────
-        for tab in tabs.iter_mut() {
+        let mut labels_changed = false;
+        for (index, tab) in tabs.iter_mut().enumerate() {
             loop {
                 match io::read(tab.master_fd, &mut pty_buf) {
                     Ok(0) => break,
                     Ok(n) => {
-                        for i in 0..n {
-                            tab.emu.feed(pty_buf[i]);
+                        tab.term.feed(&pty_buf[..n]);
+                        // Answer queries such as cursor position reports
+                        let responses = tab.term.take_responses();
+                        if !responses.is_empty() {
+                            let _ = io::write(tab.master_fd, &responses);
                         }
                     }
                     Err(_) => break, // EAGAIN or error
                 }
             }
+            // Programs name their tab with OSC 0/2
+            if tab.term.take_title_changed() && !tab.term.title().is_empty() {
+                tab_bar.set_label(index, title_label(&mut title_labels, tab.term.title()));
+                labels_changed = true;
+            }
         }
 
         // ── 3. Render ───────────────────────────────────────────────
         let sel = tab_bar.selected();
-        let any_dirty = tabs.get(sel).map_or(false, |t| t.emu.dirty);
-        let need_redraw = any_dirty || !events.is_empty() || font_changed || first_frame;
+        let any_dirty = tabs.get(sel).map_or(false, |t| t.term.is_damaged());
+        let need_redraw = any_dirty || labels_changed || !events.is_empty() || font_changed || first_frame;
         first_frame = false;
 
         if need_redraw {
@@ -698,7 +762,8 @@ fn main() {
 
             // Render active tab's terminal emulator into content area
             if let Some(tab) = tabs.get_mut(sel) {
-                tab.emu.render(
+                render_terminal(
+                    &mut tab.term,
                     fb,
                     0,
                     TAB_BAR_HEIGHT as usize,
@@ -708,6 +773,7 @@ fn main() {
                     cell_h,
                     font_size,
                     ttf_font.as_mut(),
+                    selection,
                 );
             }
 
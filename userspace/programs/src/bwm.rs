@@ -25,6 +25,8 @@ use libgfx::bitmap_font;
 use libgfx::ttf_font;
 use libgfx::color::Color;
 use libgfx::framebuf::FrameBuf;
+use libbui::wm::{self, Display, Placement, Switcher, APPBAR_HEIGHT, TASKBAR_HEIGHT};
+use libbui::wm::{focus_after_minimize, next_visible_window, snap_placement};
 
 // ─── Constants ───────────────────────────────────────────────────────────────
 
@@ -42,20 +44,14 @@ const CELL_H: usize = 18;
 const TTF_FONT_SIZE: f32 = 14.0;
 
 
-/// Top taskbar height
-const TASKBAR_HEIGHT: usize = 28;
-
-/// Bottom app bar height
-const APPBAR_HEIGHT: usize = 36;
-
-/// Chrome button size (close/minimize)
+/// Chrome button size (close/maximize/minimize)
 const CHROME_BTN_SIZE: usize = 20;
 
 /// Padding between chrome buttons
 const CHROME_BTN_PAD: usize = 4;
 
 /// Space reserved in title bar for chrome buttons
-const CHROME_RESERVED: usize = 52;
+const CHROME_RESERVED: usize = 3 * CHROME_BTN_SIZE + 4 * CHROME_BTN_PAD;
 
 /// Resize grab zone width in pixels (edges and corners)
 const RESIZE_GRAB: usize = 8;
@@ -69,6 +65,9 @@ const MIN_WIN_HEIGHT: usize = 80 + TITLE_BAR_HEIGHT + BORDER_WIDTH * 2;
 /// Maximum remembered window positions/sizes
 const MAX_DEFAULTS: usize = 16;
 
+/// Two title bar clicks within this many ms toggle maximize
+const DOUBLE_CLICK_MS: u64 = 400;
+
 // Colors
 const TITLE_FOCUSED_BG: Color = Color::rgb(40, 100, 220);
 const TITLE_UNFOCUSED_BG: Color = Color::rgb(45, 50, 65);
@@ -92,6 +91,8 @@ const CLOSE_BTN_BG: Color = Color::rgb(200, 50, 50);
 const CLOSE_BTN_TEXT: Color = Color::rgb(255, 255, 255);
 const MINIMIZE_BTN_BG: Color = Color::rgb(80, 85, 100);
 const MINIMIZE_BTN_TEXT: Color = Color::rgb(255, 255, 255);
+const MAXIMIZE_BTN_BG: Color = Color::rgb(80, 85, 100);
+const MAXIMIZE_BTN_TEXT: Color = Color::rgb(255, 255, 255);
 
 // ─── Input Parser ────────────────────────────────────────────────────────────
 // Parses stdin bytes (keyboard input) into InputEvents that BWM can either
@@ -209,6 +210,26 @@ mod modifier {
     pub const SUPER: u8 = 8;
 }
 
+/// Hotkey codes for keys with no ASCII byte, above the ASCII range
+mod hotkey_key {
+    pub const UP: u8 = 0x80;
+    pub const DOWN: u8 = 0x81;
+    pub const RIGHT: u8 = 0x82;
+    pub const LEFT: u8 = 0x83;
+
+    /// The hotkey code for a key event from the input parser.
+    pub fn from_key(ascii: u8, keycode: u16) -> Option<u8> {
+        match (ascii, keycode) {
+            (0, 0x52) => Some(UP),
+            (0, 0x51) => Some(DOWN),
+            (0, 0x4F) => Some(RIGHT),
+            (0, 0x50) => Some(LEFT),
+            (0, _) => None,
+            _ => Some(ascii),
+        }
+    }
+}
+
 /// What action a hotkey triggers
 #[derive(Clone)]
 enum HotkeyAction {
@@ -220,6 +241,16 @@ enum HotkeyAction {
     FocusNext,
     /// Cycle focus to the previous window
     FocusPrev,
+    /// Maximize the focused window, or restore it if maximized
+    Maximize,
+    /// Minimize the focused window to the app bar
+    Minimize,
+    /// Tile the focused window to the left half of the screen
+    SnapLeft,
+    /// Tile the focused window to the right half of the screen
+    SnapRight,
+    /// Send the focused window to the bottom of the stacking order
+    Lower,
 }
 
 /// A single hotkey binding
@@ -331,6 +362,26 @@ impl HotkeyManager {
             modifiers: modifier::ALT | modifier::SHIFT, key: b'\t', taps: 1,
             action: HotkeyAction::FocusPrev,
         });
+        self.bindings.push(Hotkey {
+            modifiers: modifier::SUPER, key: hotkey_key::UP, taps: 1,
+            action: HotkeyAction::Maximize,
+        });
+        self.bindings.push(Hotkey {
+            modifiers: modifier::SUPER, key: hotkey_key::DOWN, taps: 1,
+            action: HotkeyAction::Minimize,
+        });
+        self.bindings.push(Hotkey {
+            modifiers: modifier::SUPER, key: hotkey_key::LEFT, taps: 1,
+            action: HotkeyAction::SnapLeft,
+        });
+        self.bindings.push(Hotkey {
+            modifiers: modifier::SUPER, key: hotkey_key::RIGHT, taps: 1,
+            action: HotkeyAction::SnapRight,
+        });
+        self.bindings.push(Hotkey {
+            modifiers: modifier::ALT, key: 0x1b, taps: 1,
+            action: HotkeyAction::Lower,
+        });
     }
 
     #[cfg(not(target_arch = "aarch64"))]
@@ -366,6 +417,10 @@ impl HotkeyManager {
                 b"space" => { key = b' '; }
                 b"backspace" => { key = 0x08; }
                 b"escape" | b"esc" => { key = 0x1b; }
+                b"up" => { key = hotkey_key::UP; }
+                b"down" => { key = hotkey_key::DOWN; }
+                b"left" => { key = hotkey_key::LEFT; }
+                b"right" => { key = hotkey_key::RIGHT; }
                 k if k.len() == 1 => { key = k[0].to_ascii_lowercase(); }
                 _ => {} // Unknown token, skip
             }
@@ -386,6 +441,11 @@ impl HotkeyManager {
                 b"close" => HotkeyAction::Close,
                 b"focus_next" => HotkeyAction::FocusNext,
                 b"focus_prev" => HotkeyAction::FocusPrev,
+                b"maximize" => HotkeyAction::Maximize,
+                b"minimize" => HotkeyAction::Minimize,
+                b"snap_left" => HotkeyAction::SnapLeft,
+                b"snap_right" => HotkeyAction::SnapRight,
+                b"lower" => HotkeyAction::Lower,
                 _ => return None,
             }
         };
@@ -393,9 +453,9 @@ impl HotkeyManager {
         Some(Hotkey { modifiers, key, taps, action })
     }
 
-    /// Called every frame with the current modifier bitmask and whether a
-    /// non-modifier key was pressed this frame. Returns an action if a
-    /// hotkey matched.
+    /// Called every frame with the current modifier bitmask, and again for
+    /// each key press with its hotkey code in `key_pressed`. Returns an
+    /// action if a hotkey matched; the key press is then not passed on.
     ///
     /// `super_taps` is the count of Super press-edges latched in the kernel HID
     /// path since the previous frame (op=31, read-and-clear). Because the latch
@@ -423,18 +483,20 @@ impl HotkeyManager {
         let prev = self.prev_modifiers;
         self.prev_modifiers = current_mods;
 
-        // Check modifier+key bindings first (single-tap, key != 0)
+        // Check modifier+key bindings first (single-tap, key != 0). Each key
+        // press arrives once, so no cooldown: Alt+Tab can be pressed in quick
+        // succession. The binding needing the most held modifiers wins, so
+        // Alt+Shift+Tab is not taken for Alt+Tab.
         if let Some(ascii) = key_pressed {
-            if self.cooldown == 0 {
-                for binding in &self.bindings {
-                    if binding.key != 0 && binding.taps == 1
-                        && binding.key == ascii.to_ascii_lowercase()
-                        && (current_mods & binding.modifiers) == binding.modifiers
-                    {
-                        self.cooldown = 30;
-                        return Some(binding.action.clone());
-                    }
-                }
+            let best = self.bindings.iter()
+                .filter(|b| {
+                    b.key != 0 && b.taps == 1
+                        && b.key == ascii.to_ascii_lowercase()
+                        && (current_mods & b.modifiers) == b.modifiers
+                })
+                .max_by_key(|b| b.modifiers.count_ones());
+            if let Some(binding) = best {
+                return Some(binding.action.clone());
             }
         }
 
@@ -631,12 +693,13 @@ fn save_window_defaults(win: &Window, defaults: &mut [WindowDefaults; MAX_DEFAUL
         }
     }
     if let Some(i) = slot {
+        let (x, y, width, height) = win.floating_rect();
         defaults[i].title = win.title;
         defaults[i].title_len = win.title_len;
-        defaults[i].x = win.x;
-        defaults[i].y = win.y;
-        defaults[i].width = win.width;
-        defaults[i].height = win.height;
+        defaults[i].x = x;
+        defaults[i].y = y;
+        defaults[i].width = width;
+        defaults[i].height = height;
         defaults[i].valid = true;
     }
 }
@@ -669,6 +732,10 @@ struct Window {
     /// Chromeless windows have no title bar, border, or chrome buttons.
     /// Detected by title prefix \x01.
     chromeless: bool,
+    placement: Placement,
+    /// Floating frame (x, y, width, height) to go back to when a maximized
+    /// or tiled window is restored
+    restore_rect: (i32, i32, usize, usize),
 }
 
 impl Window {
@@ -687,6 +754,15 @@ impl Window {
 
     fn total_height(&self) -> usize { self.height }
 
+    /// The frame this window has, or returns to, when floating.
+    fn floating_rect(&self) -> (i32, i32, usize, usize) {
+        if self.placement == Placement::Floating {
+            (self.x, self.y, self.width, self.height)
+        } else {
+            self.restore_rect
+        }
+    }
+
     fn hit_title(&self, mx: i32, my: i32) -> bool {
         if self.chromeless { return false; }
         mx >= self.x && mx < self.x + self.width as i32
@@ -720,16 +796,26 @@ impl Window {
         (bx, by, CHROME_BTN_SIZE, CHROME_BTN_SIZE)
     }
 
-    fn minimize_btn_rect(&self) -> (i32, i32, usize, usize) {
+    fn maximize_btn_rect(&self) -> (i32, i32, usize, usize) {
         let (cx, cy, _, _) = self.close_btn_rect();
         (cx - CHROME_BTN_PAD as i32 - CHROME_BTN_SIZE as i32, cy, CHROME_BTN_SIZE, CHROME_BTN_SIZE)
     }
 
+    fn minimize_btn_rect(&self) -> (i32, i32, usize, usize) {
+        let (mx, my, _, _) = self.maximize_btn_rect();
+        (mx - CHROME_BTN_PAD as i32 - CHROME_BTN_SIZE as i32, my, CHROME_BTN_SIZE, CHROME_BTN_SIZE)
+    }
+
     fn hit_close_button(&self, mx: i32, my: i32) -> bool {
         let (bx, by, bw, bh) = self.close_btn_rect();
         mx >= bx && mx < bx + bw as i32 && my >= by && my < by + bh as i32
     }
 
+    fn hit_maximize_button(&self, mx: i32, my: i32) -> bool {
+        let (bx, by, bw, bh) = self.maximize_btn_rect();
+        mx >= bx && mx < bx + bw as i32 && my >= by && my < by + bh as i32
+    }
+
     fn hit_minimize_button(&self, mx: i32, my: i32) -> bool {
         let (bx, by, bw, bh) = self.minimize_btn_rect();
         mx >= bx && mx < bx + bw as i32 && my >= by && my < by + bh as i32
@@ -768,6 +854,13 @@ impl Window {
     }
 }
 
+impl wm::ManagedWindow for Window {
+    fn window_id(&self) -> u32 { self.window_id }
+    fn is_minimized(&self) -> bool { self.minimized }
+    fn set_minimized(&mut self, minimized: bool) { self.minimized = minimized; }
+    fn is_chromeless(&self) -> bool { self.chromeless }
+}
+
 // ─── Drawing Helpers ─────────────────────────────────────────────────────────
 
 fn fill_rect(fb: &mut FrameBuf, x: i32, y: i32, w: usize, h: usize, color: Color) {
@@ -820,6 +913,19 @@ fn draw_window_frame(fb: &mut FrameBuf, win: &Window, focused: bool,
     let cy = cby + (cbh as i32 - CELL_H as i32) / 2;
     draw_text_at(fb, b"x", cx, cy, CLOSE_BTN_TEXT, ui_font.as_mut());
 
+    // Maximize shows a window outline; restore (when maximized or tiled)
+    // shows two overlapping ones
+    let (xbx, xby, xbw, xbh) = win.maximize_btn_rect();
+    fill_rect(fb, xbx, xby, xbw, xbh, MAXIMIZE_BTN_BG);
+    let size = xbw as i32 - 10;
+    if win.placement == Placement::Floating {
+        libgfx::shapes::draw_rect(fb, xbx + 5, xby + 5, size, size, MAXIMIZE_BTN_TEXT);
+    } else {
+        libgfx::shapes::draw_rect(fb, xbx + 7, xby + 4, size - 2, size - 2, MAXIMIZE_BTN_TEXT);
+        fill_rect(fb, xbx + 4, xby + 7, (size - 2) as usize, (size - 2) as usize, MAXIMIZE_BTN_BG);
+        libgfx::shapes::draw_rect(fb, xbx + 4, xby + 7, size - 2, size - 2, MAXIMIZE_BTN_TEXT);
+    }
+
     let (mbx, mby, mbw, mbh) = win.minimize_btn_rect();
     fill_rect(fb, mbx, mby, mbw, mbh, MINIMIZE_BTN_BG);
     let mx = mbx + (mbw as i32 - CELL_W as i32) / 2;
@@ -1028,15 +1134,6 @@ fn appbar_hit_test(windows: &[Window], screen_w: usize, screen_h: usize, mx: i32
     None
 }
 
-fn next_visible_window(windows: &[Window], current: usize) -> usize {
-    for i in (0..windows.len()).rev() {
-        if !windows[i].minimized {
-            return i;
-        }
-    }
-    current
-}
-
 // ─── Input Routing ──────────────────────────────────────────────────────────
 
 fn route_keyboard_to_focused(windows: &[Window], focused_win: usize, event: &WindowInputEvent) {
@@ -1104,12 +1201,180 @@ fn route_mouse_scroll_to_focused(
     }
 }
 
+// ─── Drag and Drop ──────────────────────────────────────────────────────────
+
+/// A drag between windows, started by a client with `Window::start_drag`.
+/// The data sits in the kernel's drag selection; BWM only tells windows
+/// where the drag is.
+struct DragSession {
+    /// Window the pointer is over, which has been sent DRAG_ENTER
+    over: Option<u32>,
+}
+
+/// Cursor shown while hovering or dragging a resize edge.
+fn resize_cursor(edge: ResizeEdge) -> u32 {
+    match edge {
+        ResizeEdge::Top | ResizeEdge::Bottom => graphics::cursor_shape::RESIZE_NS,
+        ResizeEdge::Left | ResizeEdge::Right => graphics::cursor_shape::RESIZE_EW,
+        ResizeEdge::TopLeft | ResizeEdge::BottomRight => graphics::cursor_shape::RESIZE_NWSE,
+        ResizeEdge::TopRight | ResizeEdge::BottomLeft => graphics::cursor_shape::RESIZE_NESW,
+    }
+}
+
+/// Cursor for the pointer at (`mx`, `my`): resize arrows on a border, the
+/// window's own cursor (`Window::set_cursor`) over its content, else the arrow.
+fn hover_cursor(windows: &[Window], mx: i32, my: i32) -> u32 {
+    for i in (0..windows.len()).rev() {
+        if windows[i].minimized { continue; }
+        if let Some(edge) = windows[i].hit_resize_edge(mx, my) {
+            return resize_cursor(edge);
+        }
+        if windows[i].hit_any(mx, my) {
+            if windows[i].window_id != 0 && windows[i].hit_content(mx, my) {
+                return graphics::window_cursor(windows[i].window_id)
+                    .unwrap_or(graphics::cursor_shape::ARROW);
+            }
+            break; // Title bar or chrome
+        }
+    }
+    graphics::cursor_shape::ARROW
+}
+
+/// Cursor image shown while dragging data between windows: an arrow with a
+/// small page at its tail. Hotspot at the arrow's tip.
+const DRAG_CURSOR_SIZE: usize = 24;
+fn drag_cursor_image() -> [u32; DRAG_CURSOR_SIZE * DRAG_CURSOR_SIZE] {
+    const WHITE: u32 = 0xFFFF_FFFF;
+    const BLACK: u32 = 0xFF00_0000;
+    let mut img = [0u32; DRAG_CURSOR_SIZE * DRAG_CURSOR_SIZE];
+    for y in 0..14 {
+        let edge = y * 2 / 3;
+        for x in 0..=edge {
+            let outline = x == 0 || x == edge || y == 13;
+            img[y * DRAG_CURSOR_SIZE + x] = if outline { BLACK } else { WHITE };
+        }
+    }
+    for y in 12..22 {
+        for x in 12..21 {
+            let outline = y == 12 || y == 21 || x == 12 || x == 20;
+            img[y * DRAG_CURSOR_SIZE + x] = if outline { BLACK } else { WHITE };
+        }
+    }
+    img
+}
+
+/// The topmost window under (`mx`, `my`), if the point is in its content.
+fn content_window_at(windows: &[Window], mx: i32, my: i32) -> Option<usize> {
+    let idx = (0..windows.len()).rev()
+        .find(|&i| !windows[i].minimized && windows[i].hit_any(mx, my))?;
+    if windows[idx].window_id != 0 && windows[idx].hit_content(mx, my) { Some(idx) } else { None }
+}
+
+fn send_drag_event(windows: &[Window], win_idx: usize, event_type: u16, mx: i32, my: i32) {
+    let event = WindowInputEvent {
+        event_type,
+        keycode: 0,
+        mouse_x: (mx - windows[win_idx].content_x()) as i16,
+        mouse_y: (my - windows[win_idx].content_y()) as i16,
+        modifiers: 0, scroll_y: 0,
+    };
+    let _ = graphics::write_window_input(windows[win_idx].window_id, &event);
+}
+
+fn send_drag_leave(window_id: u32) {
+    let event = WindowInputEvent {
+        event_type: input_event_type::DRAG_LEAVE,
+        keycode: 0, mouse_x: 0, mouse_y: 0, modifiers: 0, scroll_y: 0,
+    };
+    let _ = graphics::write_window_input(window_id, &event);
+}
+
+/// Follow the pointer: DRAG_LEAVE and DRAG_ENTER as it crosses windows,
+/// DRAG_MOTION within one.
+fn drag_motion(windows: &[Window], session: &mut DragSession, mx: i32, my: i32) {
+    let target = content_window_at(windows, mx, my);
+    let target_id = target.map(|i| windows[i].window_id);
+    if target_id != session.over {
+        if let Some(id) = session.over { send_drag_leave(id); }
+        if let Some(i) = target { send_drag_event(windows, i, input_event_type::DRAG_ENTER, mx, my); }
+        session.over = target_id;
+    } else if let Some(i) = target {
+        send_drag_event(windows, i, input_event_type::DRAG_MOTION, mx, my);
+    }
+}
+
+/// The button went up: drop on the window under the pointer, if any, and
+/// end the drag.
+fn drag_drop(windows: &[Window], session: DragSession, mx: i32, my: i32) {
+    let target = content_window_at(windows, mx, my);
+    let target_id = target.map(|i| windows[i].window_id);
+    if let Some(id) = session.over.filter(|&id| Some(id) != target_id) {
+        send_drag_leave(id);
+    }
+    if let Some(i) = target {
+        send_drag_event(windows, i, input_event_type::DROP, mx, my);
+    }
+    let _ = graphics::end_drag();
+}
+
+// ─── Displays ───────────────────────────────────────────────────────────────
+
+/// The outputs that are on, in output order, or the whole screen if the GPU
+/// reports none.
+fn read_displays(screen_w: usize, screen_h: usize) -> Vec<Display> {
+    let mut infos = [graphics::DisplayInfo::default(); 16];
+    let count = graphics::list_displays(&mut infos).unwrap_or(0).min(infos.len());
+    let mut displays: Vec<Display> = Vec::new();
+    for d in infos[..count].iter().filter(|d| d.active()) {
+        let display = Display { x: d.x as i32, y: d.y as i32, w: d.width as usize, h: d.height as usize };
+        // A mirrored output shows the same rectangle as another
+        if !displays.contains(&display) {
+            displays.push(display);
+        }
+    }
+    if displays.is_empty() {
+        displays.push(Display { x: 0, y: 0, w: screen_w, h: screen_h });
+    }
+    displays
+}
+
+/// The display showing point (`x`, `y`), or the first one.
+fn display_at(displays: &[Display], x: i32, y: i32) -> Display {
+    displays.iter().find(|d| d.contains(x, y)).copied().unwrap_or(displays[0])
+}
+
+/// The display showing the middle of window `win`'s title bar.
+fn window_display(displays: &[Display], win: &Window) -> Display {
+    display_at(displays, win.x + win.width as i32 / 2, win.y + TITLE_BAR_HEIGHT as i32 / 2)
+}
+
+/// After the outputs changed, re-place maximized and tiled windows on their
+/// (possibly new) display, and bring back windows left off every display.
+fn relayout_for_displays(windows: &mut [Window], displays: &[Display], screen_h: usize) {
+    for idx in 0..windows.len() {
+        if windows[idx].chromeless { continue; }
+        let win = &windows[idx];
+        let on_screen = displays.iter().any(|d| d.contains(win.x + win.width as i32 / 2, win.y + TITLE_BAR_HEIGHT as i32 / 2));
+        if win.placement != Placement::Floating {
+            let display = window_display(displays, win);
+            let placement = win.placement;
+            place_window(windows, idx, placement, display, screen_h);
+        } else if !on_screen {
+            let d = displays[0];
+            let (w, h) = (win.width.min(d.w), win.height.min(d.h));
+            set_window_geometry(windows, idx, d.x + 30, d.y + TASKBAR_HEIGHT as i32 + 10, w, h);
+        }
+    }
+}
+
 // ─── Window Discovery ───────────────────────────────────────────────────────
 
+/// New windows open on `primary`, the first display.
 fn discover_windows(
-    windows: &mut Vec<Window>, screen_w: usize, screen_h: usize,
+    windows: &mut Vec<Window>, primary: Display,
     next_order: &mut u32, win_defaults: &mut [WindowDefaults; MAX_DEFAULTS],
 ) -> bool {
+    let (screen_w, screen_h) = (primary.w, primary.h);
     let mut win_infos = [graphics::WindowInfo {
         buffer_id: 0, owner_pid: 0, width: 0, height: 0,
         x: 0, y: 0, title_len: 0, title: [0; 64],
@@ -1140,8 +1405,8 @@ fn discover_windows(
 
         let n = windows.len();
         let usable_h = screen_h.saturating_sub(TASKBAR_HEIGHT + APPBAR_HEIGHT);
-        let cascade_x = 30 + (n as i32 * 50) % ((screen_w as i32 - 500).max(100));
-        let cascade_y = TASKBAR_HEIGHT as i32 + 10
+        let cascade_x = primary.x + 30 + (n as i32 * 50) % ((screen_w as i32 - 500).max(100));
+        let cascade_y = primary.y + TASKBAR_HEIGHT as i32 + 10
             + (n as i32 * 50) % ((usable_h as i32 - 500).max(100));
 
         let mut title = [0u8; 32];
@@ -1169,12 +1434,12 @@ fn discover_windows(
             Some((dx, dy, dw, dh)) => (dx, dy, dw, dh),
             None => {
                 if chromeless && total_w >= screen_w && total_h >= screen_h {
-                    (0i32, 0i32, total_w, total_h)
+                    (primary.x, primary.y, total_w, total_h)
                 } else if chromeless {
                     // Center small chromeless windows on screen
                     let cx = (screen_w as i32 - total_w as i32) / 2;
                     let cy = screen_h as i32 / 5; // Spotlight-style: 1/5 from top
-                    (cx.max(0), cy.max(0), total_w, total_h)
+                    (primary.x + cx.max(0), primary.y + cy.max(0), total_w, total_h)
                 } else {
                     (cascade_x, cascade_y, total_w, total_h)
                 }
@@ -1200,15 +1465,7 @@ fn discover_windows(
             let default_cw = win_w.saturating_sub(BORDER_WIDTH * 2);
             let default_ch = win_h.saturating_sub(TITLE_BAR_HEIGHT + BORDER_WIDTH * 2);
             if default_cw != info.width as usize || default_ch != info.height as usize {
-                let resize_event = WindowInputEvent {
-                    event_type: input_event_type::WINDOW_RESIZED,
-                    keycode: default_cw as u16,
-                    mouse_x: default_ch as i16,
-                    mouse_y: 0,
-                    modifiers: 0,
-                    scroll_y: 0,
-                };
-                let _ = graphics::write_window_input(info.buffer_id, &resize_event);
+                send_resize_event(info.buffer_id, default_cw, default_ch);
             }
         }
 
@@ -1221,6 +1478,8 @@ fn discover_windows(
             minimized: false,
             creation_order: order,
             chromeless,
+            placement: Placement::Floating,
+            restore_rect: (win_x, win_y, win_w, win_h),
         });
         added = true;
     }
@@ -1239,6 +1498,102 @@ fn update_kernel_z_order(windows: &[Window]) {
     }
 }
 
+/// Tell a client its content area is now `width` x `height`. Breengel
+/// answers by resizing its window buffer (`resize_window_buffer`).
+fn send_resize_event(window_id: u32, width: usize, height: usize) {
+    let resize_event = WindowInputEvent {
+        event_type: input_event_type::WINDOW_RESIZED,
+        keycode: width as u16,
+        mouse_x: height as i16,
+        mouse_y: 0,
+        modifiers: 0,
+        scroll_y: 0,
+    };
+    let _ = graphics::write_window_input(window_id, &resize_event);
+}
+
+/// Move and resize the frame of window `idx`, updating the kernel's content
+/// position and notifying the client if its content size changed.
+fn set_window_geometry(windows: &mut [Window], idx: usize, x: i32, y: i32, width: usize, height: usize) {
+    let win = &mut windows[idx];
+    let old_size = (win.content_width(), win.content_height());
+    win.x = x;
+    win.y = y;
+    win.width = width;
+    win.height = height;
+    if win.window_id == 0 { return; }
+    let _ = graphics::set_window_position(win.window_id, win.content_x(), win.content_y(), idx as u32);
+    if (win.content_width(), win.content_height()) != old_size {
+        send_resize_event(win.window_id, win.content_width(), win.content_height());
+    }
+}
+
+/// Maximize, tile or restore window `idx` on `display`.
+fn place_window(windows: &mut [Window], idx: usize, placement: Placement, display: Display, screen_h: usize) {
+    let win = &mut windows[idx];
+    if win.chromeless { return; }
+    let ((x, y, w, h), restore_rect) =
+        wm::place(win.placement, (win.x, win.y, win.width, win.height), win.restore_rect, placement, display, screen_h);
+    win.restore_rect = restore_rect;
+    win.placement = placement;
+    win.minimized = false;
+    set_window_geometry(windows, idx, x, y, w, h);
+}
+
+/// Apply `placement` to window `idx`, or restore it if already placed so.
+fn toggle_placement(windows: &mut [Window], idx: usize, placement: Placement, displays: &[Display], screen_h: usize) {
+    let placement = wm::toggled(windows[idx].placement, placement);
+    let display = window_display(displays, &windows[idx]);
+    place_window(windows, idx, placement, display, screen_h);
+}
+
+/// Move window `idx` to the top of the stacking order, returning its new index.
+fn raise_window(windows: &mut Vec<Window>, idx: usize) -> usize {
+    if idx < windows.len() - 1 {
+        let win = windows.remove(idx);
+        windows.push(win);
+        update_kernel_z_order(windows);
+    }
+    windows.len() - 1
+}
+
+/// Move window `idx` to the bottom of the stacking order, focusing the
+/// topmost window left.
+fn lower_window(windows: &mut Vec<Window>, idx: usize, focused_win: &mut usize) {
+    if windows.len() < 2 { return; }
+    send_focus_event(windows, *focused_win, input_event_type::FOCUS_LOST);
+    let win = windows.remove(idx);
+    windows.insert(0, win);
+    update_kernel_z_order(windows);
+    *focused_win = next_visible_window(windows, 0);
+    send_focus_event(windows, *focused_win, input_event_type::FOCUS_GAINED);
+}
+
+/// Minimize window `idx` to the app bar, focusing the topmost window left.
+fn minimize_window(windows: &mut [Window], idx: usize, focused_win: &mut usize) {
+    windows[idx].minimized = true;
+    if *focused_win == idx {
+        send_focus_event(windows, *focused_win, input_event_type::FOCUS_LOST);
+        *focused_win = focus_after_minimize(windows, idx, *focused_win);
+        send_focus_event(windows, *focused_win, input_event_type::FOCUS_GAINED);
+    }
+}
+
+/// Take one Alt+Tab (or Alt+Shift+Tab) step: restore the stacking order the
+/// switch began with, then raise, restore and focus the next window down.
+/// Releasing Alt ends the switch with the chosen window on top and the rest
+/// in their old order, so a single Alt+Tab flips between two windows.
+fn switch_window(windows: &mut Vec<Window>, switcher: &mut Option<Switcher>, focused_win: &mut usize, forward: bool) {
+    let sw = switcher.get_or_insert_with(|| Switcher::new(windows));
+    let Some(target) = sw.step(forward) else { return; };
+
+    send_focus_event(windows, *focused_win, input_event_type::FOCUS_LOST);
+    sw.restack(windows, target);
+    update_kernel_z_order(windows);
+    *focused_win = next_visible_window(windows, 0);
+    send_focus_event(windows, *focused_win, input_event_type::FOCUS_GAINED);
+}
+
 /// Redraw all windows in z-order (index 0 = bottom), plus taskbar and app bar.
 /// Window frames and decorations go into the compositor buffer; GPU compositing
 /// handles client content via per-window textured quads.
@@ -1401,10 +1756,11 @@ fn execute_hotkey_action(action: &HotkeyAction, windows: &mut Vec<Window>, focus
                 let _ = graphics::write_window_input(windows[focused_win].window_id, &event);
             }
         }
-        HotkeyAction::FocusNext | HotkeyAction::FocusPrev => {
-            // Focus cycling is handled in the main loop since it needs
-            // mutable access to focused_win and redraw state.
-            // The main loop checks for these after calling update().
+        HotkeyAction::FocusNext | HotkeyAction::FocusPrev
+        | HotkeyAction::Maximize | HotkeyAction::Minimize
+        | HotkeyAction::SnapLeft | HotkeyAction::SnapRight | HotkeyAction::Lower => {
+            // Focus cycling and window placement are handled in the main loop
+            // since they need mutable access to focused_win and redraw state.
         }
     }
 }
@@ -1464,6 +1820,10 @@ fn main() {
     }
 
     print!("[bwm] GPU compositing mode (VirGL), display: {}x{}\n", screen_w, screen_h);
+    let mut displays = read_displays(screen_w, screen_h);
+    for d in &displays {
+        print!("[bwm] Output: {}x{} at {},{}\n", d.w, d.h, d.x, d.y);
+    }
 
     // Try to map COMPOSITE_TEX directly into our address space.
     // If successful, all pixel writes go straight to GPU texture backing (zero-copy).
@@ -1544,10 +1904,18 @@ fn main() {
         .unwrap_or((0, 0));
     #[cfg(target_arch = "aarch64")]
     let mut active_cursor_shape = graphics::cursor_shape::ARROW;
+    // With a hardware cursor plane the kernel moves and reshapes the pointer
+    // itself, so pointer-only changes need no composite
+    #[cfg(target_arch = "aarch64")]
+    let hw_cursor = graphics::hardware_cursor();
     let mut prev_buttons: u32 = 0;
     let mut dragging: Option<(usize, i32, i32)> = None;
     // Active resize: (win_idx, edge, anchor_x, anchor_y, orig_x, orig_y, orig_w, orig_h)
     let mut resizing: Option<(usize, ResizeEdge, i32, i32, i32, i32, usize, usize)> = None;
+    // Last title bar click (window id, monotonic ms), for double-click maximize
+    let mut last_title_click: Option<(u32, u64)> = None;
+    let mut switcher: Option<Switcher> = None;
+    let mut dnd: Option<DragSession> = None;
     let mut full_redraw = true;
     let mut content_dirty = false;
     let mut windows_dirty = false;
@@ -1610,7 +1978,7 @@ fn main() {
     let mut registry_gen: u32 = 0;
 
     // Initial window discovery (before entering event loop)
-    if discover_windows(&mut windows, screen_w, screen_h, &mut next_creation_order, &mut win_defaults) {
+    if discover_windows(&mut windows, displays[0], &mut next_creation_order, &mut win_defaults) {
         focused_win = next_visible_window(&windows, 0);
         compose_full_redraw(composite_buf, &mut fb, &mut shadow_fb, &bg_cache, &windows, focused_win, &clock_text, &mut ui_font);
         full_redraw = true;
@@ -1638,34 +2006,50 @@ fn main() {
         // keyboard-ready latch can't busy-loop compositor_wait.
         let super_taps = take_super_tap_count();
         let current_mods = graphics::poll_modifier_state() as u8;
+        // Actions run after keyboard input is read (section 3b), which adds
+        // the modifier+key hotkeys
+        let mut hotkey_actions: Vec<HotkeyAction> = Vec::new();
         if let Some(action) = hotkey_mgr.update(current_mods, None, super_taps) {
-            match &action {
-                HotkeyAction::FocusNext => {
-                    if !windows.is_empty() {
-                        send_focus_event(&windows, focused_win, input_event_type::FOCUS_LOST);
-                        focused_win = next_visible_window(&windows, (focused_win + 1) % windows.len());
-                        send_focus_event(&windows, focused_win, input_event_type::FOCUS_GAINED);
-                        compose_full_redraw(composite_buf, &mut fb, &mut shadow_fb, &bg_cache, &windows, focused_win, &clock_text, &mut ui_font);
-                        full_redraw = true;
-                    }
-                }
-                HotkeyAction::FocusPrev => {
-                    if !windows.is_empty() {
-                        send_focus_event(&windows, focused_win, input_event_type::FOCUS_LOST);
-                        focused_win = if focused_win == 0 { windows.len() - 1 } else { focused_win - 1 };
-                        focused_win = next_visible_window(&windows, focused_win);
-                        send_focus_event(&windows, focused_win, input_event_type::FOCUS_GAINED);
-                        compose_full_redraw(composite_buf, &mut fb, &mut shadow_fb, &bg_cache, &windows, focused_win, &clock_text, &mut ui_font);
-                        full_redraw = true;
-                    }
+            hotkey_actions.push(action);
+        }
+        // Releasing Alt ends an Alt+Tab switch
+        if current_mods & modifier::ALT == 0 {
+            switcher = None;
+        }
+
+        // A client started a drag. One started after its button went up (the
+        // client saw the motion late) is over already.
+        if ready & graphics::COMPOSITOR_READY_DRAG != 0 && graphics::drag_source().is_some() {
+            if prev_buttons & 1 != 0 {
+                let mut session = DragSession { over: None };
+                drag_motion(&windows, &mut session, mouse_x, mouse_y);
+                dnd = Some(session);
+                let img = drag_cursor_image();
+                let size = DRAG_CURSOR_SIZE as u32;
+                let _ = graphics::set_cursor_image(&img, size, size, 0, 0);
+                #[cfg(target_arch = "aarch64")]
+                {
+                    active_cursor_shape = graphics::cursor_shape::CUSTOM;
                 }
-                _ => execute_hotkey_action(&action, &mut windows, focused_win),
+            } else {
+                let _ = graphics::end_drag();
+            }
+        }
+
+        // Outputs were hotplugged or changed mode
+        if ready & graphics::COMPOSITOR_READY_DISPLAYS != 0 {
+            let new_displays = read_displays(screen_w, screen_h);
+            if new_displays != displays {
+                displays = new_displays;
+                relayout_for_displays(&mut windows, &displays, screen_h);
+                compose_full_redraw(composite_buf, &mut fb, &mut shadow_fb, &bg_cache, &windows, focused_win, &clock_text, &mut ui_font);
+                full_redraw = true;
             }
         }
 
         // ── 1. Discover new/removed client windows (only when registry changed) ──
         if ready & graphics::COMPOSITOR_READY_REGISTRY != 0 {
-            if discover_windows(&mut windows, screen_w, screen_h, &mut next_creation_order, &mut win_defaults) {
+            if discover_windows(&mut windows, displays[0], &mut next_creation_order, &mut win_defaults) {
                 // Chromeless windows always float to top of z-order
                 let chromeless_idx = windows.iter().position(|w| w.chromeless);
                 if let Some(idx) = chromeless_idx {
@@ -1703,7 +2087,10 @@ fn main() {
                                 }
                             }
                             InputEvent::Key { ascii, keycode, modifiers } => {
-                                if !windows.is_empty() {
+                                let key = hotkey_key::from_key(ascii, keycode);
+                                if let Some(action) = key.and_then(|k| hotkey_mgr.update(current_mods, Some(k), 0)) {
+                                    hotkey_actions.push(action);
+                                } else if !windows.is_empty() {
                                     let win_event = WindowInputEvent {
                                         event_type: input_event_type::KEY_PRESS,
                                         keycode,
@@ -1722,6 +2109,41 @@ fn main() {
             }
         }
 
+        // ── 3b. Run hotkey actions ──
+        for action in hotkey_actions {
+            let has_focus = focused_win < windows.len() && !windows[focused_win].minimized;
+            match action {
+                HotkeyAction::FocusNext | HotkeyAction::FocusPrev => {
+                    let forward = matches!(action, HotkeyAction::FocusNext);
+                    switch_window(&mut windows, &mut switcher, &mut focused_win, forward);
+                }
+                HotkeyAction::Maximize if has_focus => {
+                    toggle_placement(&mut windows, focused_win, Placement::Maximized, &displays, screen_h);
+                }
+                HotkeyAction::SnapLeft if has_focus => {
+                    toggle_placement(&mut windows, focused_win, Placement::LeftHalf, &displays, screen_h);
+                }
+                HotkeyAction::SnapRight if has_focus => {
+                    toggle_placement(&mut windows, focused_win, Placement::RightHalf, &displays, screen_h);
+                }
+                HotkeyAction::Minimize if has_focus => {
+                    let idx = focused_win;
+                    minimize_window(&mut windows, idx, &mut focused_win);
+                }
+                HotkeyAction::Lower if has_focus => {
+                    let idx = focused_win;
+                    lower_window(&mut windows, idx, &mut focused_win);
+                }
+                HotkeyAction::Exec(_) | HotkeyAction::Close => {
+                    execute_hotkey_action(&action, &mut windows, focused_win);
+                    continue;
+                }
+                _ => continue,
+            }
+            compose_full_redraw(composite_buf, &mut fb, &mut shadow_fb, &bg_cache, &windows, focused_win, &clock_text, &mut ui_font);
+            full_redraw = true;
+        }
+
         // Dirty rect tracking — initialized before mouse processing so drag
         // can expand the dirty region. Used by section 5 (client blit) and 6 (composite).
         let mut dirty_x0 = i32::MAX;
@@ -1731,6 +2153,19 @@ fn main() {
         #[cfg(target_arch = "aarch64")]
         let mut cursor_dirty = false;
 
+        // A window changed the cursor it wants; the pointer may be over it
+        if ready & graphics::COMPOSITOR_READY_CURSOR != 0
+            && dragging.is_none() && resizing.is_none() && dnd.is_none()
+        {
+            let shape = hover_cursor(&windows, mouse_x, mouse_y);
+            let _ = graphics::set_cursor_shape(shape);
+            #[cfg(target_arch = "aarch64")]
+            {
+                cursor_dirty |= active_cursor_shape != shape;
+                active_cursor_shape = shape;
+            }
+        }
+
         // ── 4. Process mouse input (only when mouse changed) ──
         let mut mouse_moved_this_frame = false;
         if ready & graphics::COMPOSITOR_READY_MOUSE != 0 {
@@ -1744,21 +2179,29 @@ fn main() {
                     mouse_x = new_mx;
                     mouse_y = new_my;
 
-                    if let Some((win_idx, off_x, off_y)) = dragging {
+                    if let Some(ref mut session) = dnd {
+                        drag_motion(&windows, session, mouse_x, mouse_y);
+                    } else if let Some((win_idx, mut off_x, off_y)) = dragging {
+                        let (mut new_w, mut new_h) = (windows[win_idx].width, windows[win_idx].height);
+                        if windows[win_idx].placement != Placement::Floating {
+                            // Dragging a maximized or tiled window floats it at its
+                            // old size, keeping the grab point in proportion
+                            let (_, _, w, h) = windows[win_idx].restore_rect;
+                            off_x = off_x * w as i32 / new_w.max(1) as i32;
+                            (new_w, new_h) = (w, h);
+                            windows[win_idx].placement = Placement::Floating;
+                            dragging = Some((win_idx, off_x, off_y));
+                        }
                         let new_x = mouse_x - off_x;
                         // Clamp drag to stay below taskbar
                         let new_y = (mouse_y - off_y).max(TASKBAR_HEIGHT as i32);
-                        if new_x != windows[win_idx].x || new_y != windows[win_idx].y {
+                        if new_x != windows[win_idx].x || new_y != windows[win_idx].y
+                            || new_w != windows[win_idx].width || new_h != windows[win_idx].height
+                        {
                             // Capture old bounds before moving
                             let (ox0, oy0, ox1, oy1) = windows[win_idx].bounds();
-                            windows[win_idx].x = new_x;
-                            windows[win_idx].y = new_y;
                             // Update kernel window position for GPU compositing
-                            if windows[win_idx].window_id != 0 {
-                                let cx = windows[win_idx].content_x();
-                                let cy = windows[win_idx].content_y();
-                                let _ = graphics::set_window_position(windows[win_idx].window_id, cx, cy, win_idx as u32);
-                            }
+                            set_window_geometry(&mut windows, win_idx, new_x, new_y, new_w, new_h);
                             // Dirty region = union of old and new bounds
                             let (nx0, ny0, nx1, ny1) = windows[win_idx].bounds();
                             let dr_x0 = ox0.min(nx0).max(0) as usize;
@@ -1810,37 +2253,9 @@ fn main() {
 
                         let (ox0, oy0, ox1, oy1) = windows[win_idx].bounds();
 
-                        // Update frame dimensions live (content stays at old size until release)
-                        windows[win_idx].x = new_x;
-                        windows[win_idx].y = new_y;
-                        windows[win_idx].width = new_w as usize;
-                        windows[win_idx].height = new_h as usize;
-
-                        // Update kernel window position
-                        if windows[win_idx].window_id != 0 {
-                            let cx = windows[win_idx].content_x();
-                            let cy = windows[win_idx].content_y();
-                            let _ = graphics::set_window_position(
-                                windows[win_idx].window_id, cx, cy, win_idx as u32,
-                            );
-                        }
-
-                        // Send live resize event to client
-                        let new_cw = windows[win_idx].content_width();
-                        let new_ch = windows[win_idx].content_height();
-                        if windows[win_idx].window_id != 0 {
-                            let resize_event = WindowInputEvent {
-                                event_type: input_event_type::WINDOW_RESIZED,
-                                keycode: new_cw as u16,
-                                mouse_x: new_ch as i16,
-                                mouse_y: 0,
-                                modifiers: 0,
-                                scroll_y: 0,
-                            };
-                            let _ = graphics::write_window_input(
-                                windows[win_idx].window_id, &resize_event,
-                            );
-                        }
+                        // Update the frame, kernel window position and client
+                        // content size live
+                        set_window_geometry(&mut windows, win_idx, new_x, new_y, new_w as usize, new_h as usize);
 
                         // Dirty region = union of old and new bounds
                         let (nx0, ny0, nx1, ny1) = windows[win_idx].bounds();
@@ -1864,24 +2279,9 @@ fn main() {
                         route_mouse_move_to_focused(&windows, focused_win, local_x, local_y);
                     }
 
-                    // Update cursor shape based on resize edge hover
-                    if dragging.is_none() && resizing.is_none() {
-                        let mut hover_shape = graphics::cursor_shape::ARROW;
-                        for i in (0..windows.len()).rev() {
-                            if windows[i].minimized { continue; }
-                            if let Some(edge) = windows[i].hit_resize_edge(mouse_x, mouse_y) {
-                                hover_shape = match edge {
-                                    ResizeEdge::Top | ResizeEdge::Bottom => graphics::cursor_shape::RESIZE_NS,
-                                    ResizeEdge::Left | ResizeEdge::Right => graphics::cursor_shape::RESIZE_EW,
-                                    ResizeEdge::TopLeft | ResizeEdge::BottomRight => graphics::cursor_shape::RESIZE_NWSE,
-                                    ResizeEdge::TopRight | ResizeEdge::BottomLeft => graphics::cursor_shape::RESIZE_NESW,
-                                };
-                                break;
-                            }
-                            if windows[i].hit_any(mouse_x, mouse_y) {
-                                break; // Inside window but not on edge
-                            }
-                        }
+                    // Update cursor shape: resize edges, else the window's own cursor
+                    if dragging.is_none() && resizing.is_none() && dnd.is_none() {
+                        let hover_shape = hover_cursor(&windows, mouse_x, mouse_y);
                         let _ = graphics::set_cursor_shape(hover_shape);
                         #[cfg(target_arch = "aarch64")]
                         {
@@ -1895,7 +2295,22 @@ fn main() {
                 // Per-endpoint button tracking in the kernel prevents dual USB HID
                 // endpoints from racing (one endpoint can't cancel the other's press).
                 if (buttons & 1) == 0 && (prev_buttons & 1) != 0 {
-                    if let Some((win_idx, _, _, _, _, _, orig_w, orig_h)) = resizing.take() {
+                    if let Some(session) = dnd.take() {
+                        drag_drop(&windows, session, mouse_x, mouse_y);
+                        let shape = hover_cursor(&windows, mouse_x, mouse_y);
+                        let _ = graphics::set_cursor_shape(shape);
+                        #[cfg(target_arch = "aarch64")]
+                        {
+                            cursor_dirty = true;
+                            active_cursor_shape = shape;
+                        }
+                        // The drag source still sees its button go up
+                        if focused_win < windows.len() {
+                            let local_x = (mouse_x - windows[focused_win].content_x()) as i16;
+                            let local_y = (mouse_y - windows[focused_win].content_y()) as i16;
+                            route_mouse_button_to_focused(&windows, focused_win, 1, false, local_x, local_y);
+                        }
+                    } else if let Some((win_idx, _, _, _, _, _, orig_w, orig_h)) = resizing.take() {
                         // Reset cursor shape back to arrow
                         let _ = graphics::set_cursor_shape(graphics::cursor_shape::ARROW);
                         #[cfg(target_arch = "aarch64")]
@@ -1914,22 +2329,18 @@ fn main() {
                         if (new_cw != old_cw || new_ch != old_ch)
                             && windows[win_idx].window_id != 0
                         {
-                            let resize_event = WindowInputEvent {
-                                event_type: input_event_type::WINDOW_RESIZED,
-                                keycode: new_cw as u16,
-                                mouse_x: new_ch as i16,
-                                mouse_y: 0,
-                                modifiers: 0,
-                                scroll_y: 0,
-                            };
-                            let _ = graphics::write_window_input(
-                                windows[win_idx].window_id, &resize_event,
-                            );
+                            send_resize_event(windows[win_idx].window_id, new_cw, new_ch);
                         }
                         save_window_defaults(&windows[win_idx], &mut win_defaults);
-                    } else if dragging.is_some() {
-                        let drag_idx = dragging.unwrap().0;
-                        dragging = None;
+                    } else if let Some((drag_idx, _, _)) = dragging.take() {
+                        // Dropping on the top edge of a display maximizes, on a
+                        // side edge tiles
+                        let display = display_at(&displays, mouse_x, mouse_y);
+                        if let Some(placement) = snap_placement(display, mouse_x, mouse_y) {
+                            place_window(&mut windows, drag_idx, placement, display, screen_h);
+                            compose_full_redraw(composite_buf, &mut fb, &mut shadow_fb, &bg_cache, &windows, focused_win, &clock_text, &mut ui_font);
+                            full_redraw = true;
+                        }
                         save_window_defaults(&windows[drag_idx], &mut win_defaults);
                     } else if !windows.is_empty() && focused_win < windows.len()
                         && !windows[focused_win].minimized
@@ -1967,20 +2378,12 @@ fn main() {
                         if let Some(idx) = appbar_hit_test(&windows, screen_w, screen_h, mouse_x, mouse_y) {
                             if idx == focused_win && !windows[idx].minimized {
                                 // Click focused window button → minimize
-                                windows[idx].minimized = true;
-                                send_focus_event(&windows, focused_win, input_event_type::FOCUS_LOST);
-                                focused_win = next_visible_window(&windows, focused_win);
-                                send_focus_event(&windows, focused_win, input_event_type::FOCUS_GAINED);
+                                minimize_window(&mut windows, idx, &mut focused_win);
                             } else {
                                 // Click unfocused/minimized → restore and focus
                                 windows[idx].minimized = false;
                                 // Bring to top of z-order
-                                if idx < windows.len() - 1 {
-                                    let win = windows.remove(idx);
-                                    windows.push(win);
-                                    update_kernel_z_order(&windows);
-                                }
-                                let top = windows.len() - 1;
+                                let top = raise_window(&mut windows, idx);
                                 if top != focused_win {
                                     send_focus_event(&windows, focused_win, input_event_type::FOCUS_LOST);
                                     focused_win = top;
@@ -2016,12 +2419,7 @@ fn main() {
                         }
                         if let Some(ci) = clicked_idx {
                             let z_changed = ci < windows.len() - 1;
-                            if z_changed {
-                                let win = windows.remove(ci);
-                                windows.push(win);
-                                update_kernel_z_order(&windows);
-                            }
-                            let top = windows.len() - 1;
+                            let top = raise_window(&mut windows, ci);
                             let focus_changed = top != focused_win;
 
                             if focus_changed {
@@ -2034,18 +2432,15 @@ fn main() {
 
                             if let Some(edge) = clicked_resize {
                                 // Start resize — set cursor shape for the edge
-                                let shape = match edge {
-                                    ResizeEdge::Top | ResizeEdge::Bottom => graphics::cursor_shape::RESIZE_NS,
-                                    ResizeEdge::Left | ResizeEdge::Right => graphics::cursor_shape::RESIZE_EW,
-                                    ResizeEdge::TopLeft | ResizeEdge::BottomRight => graphics::cursor_shape::RESIZE_NWSE,
-                                    ResizeEdge::TopRight | ResizeEdge::BottomLeft => graphics::cursor_shape::RESIZE_NESW,
-                                };
+                                let shape = resize_cursor(edge);
                                 let _ = graphics::set_cursor_shape(shape);
                                 #[cfg(target_arch = "aarch64")]
                                 {
                                     cursor_dirty |= active_cursor_shape != shape;
                                     active_cursor_shape = shape;
                                 }
+                                // A resized window floats where the user leaves it
+                                windows[top].placement = Placement::Floating;
                                 resizing = Some((
                                     top, edge,
                                     mouse_x, mouse_y,
@@ -2063,17 +2458,33 @@ fn main() {
                                     if windows[top].owner_pid > 0 {
                                         let _ = signal::kill(windows[top].owner_pid as i32, signal::SIGTERM);
                                     }
+                                } else if windows[top].hit_maximize_button(mouse_x, mouse_y) {
+                                    // Restores a tiled window too
+                                    let placement = if windows[top].placement == Placement::Floating {
+                                        Placement::Maximized
+                                    } else {
+                                        Placement::Floating
+                                    };
+                                    let display = window_display(&displays, &windows[top]);
+                                    place_window(&mut windows, top, placement, display, screen_h);
+                                    compose_full_redraw(composite_buf, &mut fb, &mut shadow_fb, &bg_cache, &windows, focused_win, &clock_text, &mut ui_font);
+                                    full_redraw = true;
                                 } else if windows[top].hit_minimize_button(mouse_x, mouse_y) {
-                                    windows[top].minimized = true;
-                                    if focused_win == top {
-                                        send_focus_event(&windows, focused_win, input_event_type::FOCUS_LOST);
-                                        focused_win = next_visible_window(&windows, focused_win);
-                                        send_focus_event(&windows, focused_win, input_event_type::FOCUS_GAINED);
-                                    }
+                                    minimize_window(&mut windows, top, &mut focused_win);
                                     compose_full_redraw(composite_buf, &mut fb, &mut shadow_fb, &bg_cache, &windows, focused_win, &clock_text, &mut ui_font);
                                     full_redraw = true;
                                 } else {
-                                    dragging = Some((top, mouse_x - windows[top].x, mouse_y - windows[top].y));
+                                    let now = monotonic_ms().unwrap_or(0);
+                                    let id = windows[top].window_id;
+                                    if matches!(last_title_click, Some((last_id, at)) if last_id == id && now.saturating_sub(at) < DOUBLE_CLICK_MS) {
+                                        last_title_click = None;
+                                        toggle_placement(&mut windows, top, Placement::Maximized, &displays, screen_h);
+                                        compose_full_redraw(composite_buf, &mut fb, &mut shadow_fb, &bg_cache, &windows, focused_win, &clock_text, &mut ui_font);
+                                        full_redraw = true;
+                                    } else {
+                                        last_title_click = Some((id, now));
+                                        dragging = Some((top, mouse_x - windows[top].x, mouse_y - windows[top].y));
+                                    }
                                 }
                             } else if windows[top].hit_content(mouse_x, mouse_y) {
                                 let local_x = (mouse_x - windows[top].content_x()) as i16;
@@ -2139,7 +2550,8 @@ fn main() {
         // ── 6. Composite to GPU (only when something changed) ──
         #[cfg(target_arch = "aarch64")]
         {
-            if full_redraw || content_dirty || windows_dirty || mouse_moved_this_frame || cursor_dirty {
+            let pointer_dirty = !hw_cursor && (mouse_moved_this_frame || cursor_dirty);
+            if full_redraw || content_dirty || windows_dirty || pointer_dirty {
                 let (cbuf, cw, ch): (&[u32], u32, u32) = if direct_mapped {
                     (&[], 0, 0)
                 } else {
@@ -2164,7 +2576,7 @@ fn main() {
                     let dw = (dirty_x1.min(sw) - dirty_x0.max(0)).max(0) as u32;
                     let dh = (dirty_y1.min(sh) - dirty_y0.max(0)).max(0) as u32;
                     let _ = graphics::virgl_composite_windows_rect(cbuf, cw, ch, 2, dx, dy, dw, dh);
-                } else if windows_dirty || mouse_moved_this_frame || cursor_dirty {
+                } else if windows_dirty || pointer_dirty {
                     let _ = graphics::virgl_composite_windows_rect(cbuf, cw, ch, 0, 0, 0, 0, 0);
                 }
                 full_redraw = false;
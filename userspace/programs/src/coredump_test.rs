@@ -0,0 +1,250 @@
+//! Core dump tests
+//!
+//! Tests that prlimit() reads and updates RLIMIT_CORE, that a child killed
+//! by a real SIGSEGV fault writes an ELF core file named core.<pid> in its
+//! working directory (ET_CORE, an NT_PRSTATUS note for the faulting thread and
+//! PT_LOAD segments holding its memory) with WCOREDUMP set, and that with
+//! RLIMIT_CORE at zero a SIGABRT writes no file and leaves WCOREDUMP clear.
+//! Must emit "COREDUMP_TEST_PASSED" on success.
+
+use libbreenix::error::Error;
+use libbreenix::process::{self, ForkResult, Rlimit, RLIMIT_CORE, RLIM_INFINITY};
+use libbreenix::signal::{kill, SIGABRT, SIGSEGV};
+use libbreenix::Errno;
+
+/// Bytes the faulting child keeps in its image, searched for in the dump
+static MARKER: [u8; 16] = *b"BREENIX-CORE-MRK";
+
+/// Unmapped address the child writes to
+const FAULT_ADDR: u64 = 0x10;
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const NT_PRSTATUS: u32 = 1;
+
+/// Offset of pr_pid in struct elf_prstatus
+const PRSTATUS_PID_OFFSET: usize = 32;
+
+fn is_errno<T>(result: &Result<T, Error>, errno: Errno) -> bool {
+    matches!(result, Err(Error::Os(e)) if *e == errno)
+}
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+fn u16_at(buf: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([buf[at], buf[at + 1]])
+}
+
+fn u32_at(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(buf[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(buf: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(buf[at..at + 8].try_into().unwrap())
+}
+
+/// Fork a child that runs `die`, and return its pid and wait status
+fn run_child(die: fn() -> !) -> Option<(i32, i32)> {
+    match process::fork() {
+        Ok(ForkResult::Child) => die(),
+        Ok(ForkResult::Parent(pid)) => {
+            let pid = pid.raw() as i32;
+            let mut status = 0;
+            process::waitpid(pid, &mut status, 0).ok()?;
+            Some((pid, status))
+        }
+        Err(_) => None,
+    }
+}
+
+fn fault() -> ! {
+    std::hint::black_box(&MARKER);
+    unsafe { core::ptr::write_volatile(FAULT_ADDR as *mut u64, 1) };
+    process::exit(0);
+}
+
+fn abort_self() -> ! {
+    let pid = process::getpid().map(|p| p.raw() as i32).unwrap_or(0);
+    let _ = kill(pid, SIGABRT);
+    process::exit(0);
+}
+
+/// Wait for the core file, which is written after the child is reaped
+fn read_core(path: &str) -> Option<Vec<u8>> {
+    for _ in 0..200 {
+        if let Ok(data) = std::fs::read(path) {
+            return Some(data);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    None
+}
+
+/// Check the ELF header, find NT_PRSTATUS for `pid`, count PT_LOAD segments
+/// and look for MARKER in the dumped memory
+fn check_core(core: &[u8], pid: i32) -> (bool, bool, usize, bool) {
+    let header_ok = core.len() >= 64
+        && core[..4] == *b"\x7fELF"
+        && core[4] == 2 // ELFCLASS64
+        && u16_at(core, 16) == 4; // ET_CORE
+    if !header_ok {
+        return (false, false, 0, false);
+    }
+    let phoff = u64_at(core, 32) as usize;
+    let phentsize = u16_at(core, 54) as usize;
+    let phnum = u16_at(core, 56) as usize;
+
+    let mut prstatus = false;
+    let mut loads = 0;
+    let mut marker = false;
+    for i in 0..phnum {
+        let ph = phoff + i * phentsize;
+        if ph + 56 > core.len() {
+            return (false, false, 0, false);
+        }
+        let offset = u64_at(core, ph + 8) as usize;
+        let filesz = u64_at(core, ph + 32) as usize;
+        let Some(data) = core.get(offset..offset + filesz) else {
+            return (false, false, 0, false);
+        };
+        match u32_at(core, ph) {
+            PT_NOTE => {
+                let mut at = 0;
+                while at + 12 <= data.len() {
+                    let namesz = u32_at(data, at) as usize;
+                    let descsz = u32_at(data, at + 4) as usize;
+                    let kind = u32_at(data, at + 8);
+                    let desc = at + 12 + ((namesz + 3) & !3);
+                    if kind == NT_PRSTATUS
+                        && desc + PRSTATUS_PID_OFFSET + 4 <= data.len()
+                        && u32_at(data, desc + PRSTATUS_PID_OFFSET) == pid as u32
+                    {
+                        prstatus = true;
+                    }
+                    at = desc + ((descsz + 3) & !3);
+                }
+            }
+            PT_LOAD => {
+                loads += 1;
+                marker |= data.windows(MARKER.len()).any(|w| w == MARKER);
+            }
+            _ => {}
+        }
+    }
+    (true, prstatus, loads, marker)
+}
+
+fn main() {
+    println!("=== Core Dump Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    if process::chdir(b"/tmp\0").is_err() {
+        println!("FAIL: chdir /tmp failed");
+        println!("COREDUMP_TEST_FAILED");
+        process::exit(1);
+    }
+
+    println!("\nTest 1: RLIMIT_CORE get and set");
+    let initial = process::getrlimit(RLIMIT_CORE);
+    let unlimited = Rlimit {
+        rlim_cur: RLIM_INFINITY,
+        rlim_max: RLIM_INFINITY,
+    };
+    let set = process::setrlimit(RLIMIT_CORE, &unlimited).is_ok();
+    let inverted = Rlimit {
+        rlim_cur: 2,
+        rlim_max: 1,
+    };
+    let rejected = is_errno(&process::setrlimit(RLIMIT_CORE, &inverted), Errno::EINVAL);
+    report(
+        "starts at 0, raised to unlimited, soft > hard refused",
+        matches!(initial, Ok(r) if r.rlim_cur == 0)
+            && set
+            && process::getrlimit(RLIMIT_CORE).ok() == Some(unlimited)
+            && rejected,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 2: SIGSEGV fault writes core.<pid>");
+    match run_child(fault) {
+        Some((pid, status)) => {
+            let path = format!("/tmp/core.{}", pid);
+            report(
+                "killed by SIGSEGV with WCOREDUMP",
+                process::wifsignaled(status)
+                    && process::wtermsig(status) == SIGSEGV
+                    && process::wcoredump(status),
+                &mut passed,
+                &mut failed,
+            );
+            let core = read_core(&path);
+            let (header, prstatus, loads, marker) = core
+                .as_deref()
+                .map_or((false, false, 0, false), |core| check_core(core, pid));
+            report(
+                "ELF ET_CORE file in the cwd",
+                header,
+                &mut passed,
+                &mut failed,
+            );
+            report(
+                "NT_PRSTATUS for the faulting thread",
+                prstatus,
+                &mut passed,
+                &mut failed,
+            );
+            report(
+                "PT_LOAD segments hold the process memory",
+                loads > 0 && marker,
+                &mut passed,
+                &mut failed,
+            );
+            let _ = std::fs::remove_file(&path);
+        }
+        None => report("fork and wait", false, &mut passed, &mut failed),
+    }
+
+    println!("\nTest 3: RLIMIT_CORE of zero writes nothing");
+    let zero = Rlimit {
+        rlim_cur: 0,
+        rlim_max: RLIM_INFINITY,
+    };
+    let lowered = process::setrlimit(RLIMIT_CORE, &zero).is_ok();
+    match run_child(abort_self) {
+        Some((pid, status)) => {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            let absent = std::fs::metadata(format!("/tmp/core.{}", pid)).is_err();
+            report(
+                "SIGABRT without WCOREDUMP or a core file",
+                lowered
+                    && process::wifsignaled(status)
+                    && process::wtermsig(status) == SIGABRT
+                    && !process::wcoredump(status)
+                    && absent,
+                &mut passed,
+                &mut failed,
+            );
+        }
+        None => report("fork and wait", false, &mut passed, &mut failed),
+    }
+
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("COREDUMP_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("COREDUMP_TEST_FAILED");
+        process::exit(1);
+    }
+}
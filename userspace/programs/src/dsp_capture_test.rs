@@ -0,0 +1,185 @@
+//! /dev/dsp capture tests
+//!
+//! Tests that a stream opened for reading records: reads return whole frames
+//! in the stream's own format, SNDCTL_DSP_GETISPACE and poll report captured
+//! audio, RESET discards it so a nonblocking read reports EAGAIN, every
+//! recording stream gets its own copy, and write-only streams never poll
+//! readable. Works with or without a capture device: without one the kernel
+//! supplies silence at the device rate.
+//! Must emit "DSP_CAPTURE_TEST_PASSED" on success.
+
+use libbreenix::audio::{Dsp, AFMT_S16_LE, AFMT_U8};
+use libbreenix::errno::Errno;
+use libbreenix::error::Error;
+use libbreenix::fs;
+use libbreenix::io::{self, poll_events, PollFd};
+use libbreenix::process;
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+/// Read until `buf` is full or a read fails
+fn read_full(dsp: &Dsp, buf: &mut [u8]) -> usize {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match dsp.read(&mut buf[filled..]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => filled += n,
+        }
+    }
+    filled
+}
+
+fn main() {
+    println!("=== /dev/dsp Capture Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    println!("\nTest 1: open a capture stream");
+    let mono = match Dsp::open_with_flags(fs::O_RDONLY) {
+        Ok(dsp) => dsp,
+        Err(e) => {
+            println!("  open failed: {:?}", e);
+            println!("DSP_CAPTURE_TEST_FAILED");
+            process::exit(1);
+        }
+    };
+    report(
+        "capture stream takes U8 mono at 22050 Hz",
+        mono.set_format(AFMT_U8).ok() == Some(AFMT_U8)
+            && mono.set_channels(1).ok() == Some(1)
+            && mono.set_rate(22050).ok() == Some(22050),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 2: blocking reads");
+    let mut buf = vec![0u8; 4096];
+    let got = read_full(&mono, &mut buf);
+    println!("  read {} bytes", got);
+    report(
+        "blocking reads fill the buffer",
+        got == buf.len(),
+        &mut passed,
+        &mut failed,
+    );
+    let mut odd = [0i16; 3];
+    report(
+        "S16 reads return whole frames",
+        mono.set_format(AFMT_S16_LE).ok() == Some(AFMT_S16_LE)
+            && mono.set_channels(2).ok() == Some(2)
+            && mono.read_samples(&mut odd).is_ok_and(|n| n == 4),
+        &mut passed,
+        &mut failed,
+    );
+    let _ = mono.set_format(AFMT_U8);
+    let _ = mono.set_channels(1);
+
+    println!("\nTest 3: captured audio is reported");
+    let _ = read_full(&mono, &mut buf);
+    let info = mono.input_space().unwrap_or_default();
+    println!(
+        "  {} fragments, {} bytes of {}-byte fragments",
+        info.fragments, info.bytes, info.fragsize
+    );
+    let mut fds = [PollFd::new(mono.fd(), poll_events::POLLIN)];
+    let readable =
+        io::poll(&mut fds, 1000).is_ok_and(|n| n == 1) && fds[0].revents & poll_events::POLLIN != 0;
+    let info = mono.input_space().unwrap_or_default();
+    report(
+        "GETISPACE and poll see captured audio",
+        readable && info.fragsize > 0 && info.bytes >= info.fragsize,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 4: RESET and nonblocking reads");
+    let nonblocking = match Dsp::open_with_flags(fs::O_RDONLY | fs::O_NONBLOCK) {
+        Ok(dsp) => {
+            let result = dsp.reset().and_then(|()| dsp.read(&mut buf));
+            println!("  read after reset {:?}", result);
+            matches!(result, Err(Error::Os(Errno::EAGAIN))) || result.is_ok_and(|n| n < buf.len())
+        }
+        Err(e) => {
+            println!("  open failed: {:?}", e);
+            false
+        }
+    };
+    report(
+        "a drained nonblocking stream does not wait",
+        nonblocking,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 5: every recording stream gets a copy");
+    let stereo = match Dsp::open_capture() {
+        Ok(dsp) => dsp,
+        Err(e) => {
+            println!("  open failed: {:?}", e);
+            println!("DSP_CAPTURE_TEST_FAILED");
+            process::exit(1);
+        }
+    };
+    let mut samples = vec![0i16; 2048];
+    let stereo_got = stereo.read_samples(&mut samples).unwrap_or(0);
+    let mono_got = read_full(&mono, &mut buf);
+    println!("  read {} and {} bytes", stereo_got, mono_got);
+    report(
+        "two streams record at once",
+        stereo_got > 0 && stereo_got % 4 == 0 && mono_got == buf.len(),
+        &mut passed,
+        &mut failed,
+    );
+    // Silence from the null source is the U8 midpoint; a real device may
+    // capture anything, so only check when every sample is the same
+    if buf.iter().all(|&b| b == buf[0]) {
+        report(
+            "silence reads back as the U8 midpoint",
+            buf[0] == 128,
+            &mut passed,
+            &mut failed,
+        );
+    } else {
+        println!("  captured real audio, silence check skipped");
+    }
+
+    println!("\nTest 6: playback-only streams");
+    let write_only = match Dsp::open() {
+        Ok(dsp) => {
+            let mut fds = [PollFd::new(dsp.fd(), poll_events::POLLIN)];
+            io::poll(&mut fds, 100).is_ok_and(|n| n == 0)
+                && dsp.input_space().is_ok_and(|info| info.bytes == 0)
+        }
+        Err(e) => {
+            println!("  open failed: {:?}", e);
+            false
+        }
+    };
+    report(
+        "a write-only stream never polls readable",
+        write_only,
+        &mut passed,
+        &mut failed,
+    );
+
+    drop(stereo);
+    drop(mono);
+
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("DSP_CAPTURE_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("DSP_CAPTURE_TEST_FAILED");
+        process::exit(1);
+    }
+}
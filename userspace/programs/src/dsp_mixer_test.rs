@@ -0,0 +1,233 @@
+//! /dev/dsp mixer tests
+//!
+//! Tests that every open of /dev/dsp is a separate stream: two streams
+//! negotiate different rates, formats, channel counts and volumes through
+//! the OSS ioctls without affecting each other, both accept audio at once,
+//! a full nonblocking stream reports EAGAIN and polls not-writable, and
+//! SNDCTL_DSP_SYNC waits until the mixer has consumed everything.
+//! Works with or without a sound device: without one the mixer paces its
+//! output by the timer.
+//! Must emit "DSP_MIXER_TEST_PASSED" on success.
+
+use libbreenix::audio::{self, Dsp, AFMT_S16_LE, AFMT_U8, MAX_VOLUME};
+use libbreenix::errno::Errno;
+use libbreenix::error::Error;
+use libbreenix::fs;
+use libbreenix::io::{self, poll_events, PollFd};
+use libbreenix::process;
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+/// A second of a 440 Hz square wave as mono U8 at `rate`
+fn square_u8(rate: u32) -> Vec<u8> {
+    let half_period = (rate / 880).max(1) as usize;
+    (0..rate as usize)
+        .map(|i| if (i / half_period) & 1 == 0 { 96 } else { 160 })
+        .collect()
+}
+
+fn main() {
+    println!("=== /dev/dsp Mixer Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    println!("\nTest 1: open two streams");
+    let (first, second) = match (Dsp::open(), Dsp::open()) {
+        (Ok(first), Ok(second)) => (first, second),
+        (first, second) => {
+            println!("  open failed: {:?} {:?}", first.err(), second.err());
+            println!("DSP_MIXER_TEST_FAILED");
+            process::exit(1);
+        }
+    };
+    report(
+        "each open gets its own descriptor",
+        first.fd().raw() != second.fd().raw(),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "/proc/self/fd names /dev/dsp",
+        std::fs::read_link(format!("/proc/self/fd/{}", first.fd().raw()))
+            .is_ok_and(|target| target.to_str() == Some("/dev/dsp")),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "new streams default to S16_LE at full volume",
+        first.format().ok() == Some(AFMT_S16_LE)
+            && first.volume().ok() == Some((MAX_VOLUME, MAX_VOLUME)),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 2: format negotiation");
+    report(
+        "GETFMTS offers U8 and S16_LE",
+        first
+            .formats()
+            .is_ok_and(|mask| mask & (AFMT_U8 | AFMT_S16_LE) == (AFMT_U8 | AFMT_S16_LE)),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "first stream takes U8 mono at 22050 Hz",
+        first.set_format(AFMT_U8).ok() == Some(AFMT_U8)
+            && first.set_channels(1).ok() == Some(1)
+            && first.set_rate(22050).ok() == Some(22050),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "second stream keeps S16_LE stereo and takes 48000 Hz",
+        second.format().ok() == Some(AFMT_S16_LE)
+            && second.set_channels(2).ok() == Some(2)
+            && second.set_rate(48000).ok() == Some(48000),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "unsupported values read back what is in effect",
+        first.set_format(0x0000_0200).ok() == Some(AFMT_U8)
+            && second.set_channels(6).ok() == Some(2)
+            && second
+                .set_rate(1_000_000)
+                .is_ok_and(|rate| rate < 1_000_000),
+        &mut passed,
+        &mut failed,
+    );
+    let _ = second.set_rate(48000);
+    let first_block = first.block_size().unwrap_or(0);
+    let second_block = second.block_size().unwrap_or(0);
+    println!("  block sizes {} / {}", first_block, second_block);
+    report(
+        "block size follows each stream's format",
+        first_block > 0 && second_block > 4 * first_block,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 3: volume");
+    report(
+        "volume is per stream and clamped",
+        first.set_volume(50, 25).ok() == Some((50, 25))
+            && second.set_volume(200, 80).ok() == Some((MAX_VOLUME, 80))
+            && first.volume().ok() == Some((50, 25)),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 4: concurrent playback");
+    let tone = square_u8(22050);
+    let first_written = first.write(&tone[..4096]).unwrap_or(0);
+    let silence = vec![0i16; 4096];
+    let second_written = second.write_samples(&silence).unwrap_or(0);
+    println!("  wrote {} and {} bytes", first_written, second_written);
+    report(
+        "both streams accept audio",
+        first_written == 4096 && second_written == silence.len() * 2,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "queued audio shows up as delay",
+        first.delay().is_ok_and(|delay| delay > 0),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "SYNC waits until the stream is mixed",
+        first.drain().is_ok()
+            && second.drain().is_ok()
+            && first.delay().ok() == Some(0)
+            && second.delay().ok() == Some(0),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 5: buffer space and poll");
+    let space = first.space().unwrap_or_default();
+    println!(
+        "  {} of {} fragments free, {} bytes",
+        space.fragments, space.fragstotal, space.bytes
+    );
+    report(
+        "an empty stream reports the whole buffer free",
+        space.fragstotal > 0
+            && space.fragments == space.fragstotal
+            && space.bytes == space.fragstotal * space.fragsize,
+        &mut passed,
+        &mut failed,
+    );
+    let mut fds = [PollFd::new(first.fd(), poll_events::POLLOUT)];
+    report(
+        "an empty stream polls writable",
+        io::poll(&mut fds, 0).is_ok_and(|n| n == 1) && fds[0].revents & poll_events::POLLOUT != 0,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 6: nonblocking writes");
+    let full = match Dsp::open_with_flags(fs::O_WRONLY | fs::O_NONBLOCK) {
+        Ok(stream) => {
+            let mut result = Ok(0);
+            for _ in 0..64 {
+                result = stream.write(&tone);
+                if result.is_err() {
+                    break;
+                }
+            }
+            let mut fds = [PollFd::new(stream.fd(), poll_events::POLLOUT)];
+            let not_writable = io::poll(&mut fds, 0).is_ok_and(|n| n == 0);
+            let reset = stream.reset().is_ok()
+                && stream.delay().ok() == Some(0)
+                && io::poll(&mut fds, 0).is_ok_and(|n| n == 1);
+            println!("  last write {:?}", result);
+            matches!(result, Err(Error::Os(Errno::EAGAIN))) && not_writable && reset
+        }
+        Err(e) => {
+            println!("  open failed: {:?}", e);
+            false
+        }
+    };
+    report(
+        "a full stream returns EAGAIN, polls busy, and RESET empties it",
+        full,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 7: legacy AudioWrite");
+    // Only meaningful with a sound device; AudioInit reports EIO without one
+    if audio::init().is_ok() {
+        report(
+            "write_pcm mixes alongside /dev/dsp",
+            audio::write_samples(&silence).is_ok_and(|n| n == silence.len() * 2),
+            &mut passed,
+            &mut failed,
+        );
+    } else {
+        println!("  no sound device, skipped");
+    }
+
+    drop(first);
+    drop(second);
+
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("DSP_MIXER_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("DSP_MIXER_TEST_FAILED");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,271 @@
+//! Advisory file locking tests
+//!
+//! Tests flock() and fcntl record locks across fork(): flock sharing through
+//! an inherited descriptor, EAGAIN with LOCK_NB, a blocked flock waking on
+//! unlock, F_GETLK/F_SETLK byte ranges, POSIX locks dropped by closing any
+//! descriptor for the file, EDEADLK from F_SETLKW, and OFD locks.
+//! Must emit "FLOCK_TEST_PASSED" on success.
+
+use libbreenix::error::Error;
+use libbreenix::fs::{
+    self, Flock, F_RDLCK, F_UNLCK, F_WRLCK, LOCK_EX, LOCK_NB, LOCK_SH, LOCK_UN, O_CREAT, O_RDWR,
+    O_TRUNC,
+};
+use libbreenix::io::fcntl_cmd::{F_GETLK, F_OFD_GETLK, F_OFD_SETLK, F_SETLK, F_SETLKW};
+use libbreenix::io::{self, close};
+use libbreenix::process::{self, wexitstatus, wifexited, ForkResult};
+use libbreenix::time::sleep_ms;
+use libbreenix::types::Fd;
+use libbreenix::Errno;
+
+const PATH: &str = "/tmp/flock_test.dat";
+
+/// Time given to a child to reach a blocking lock call
+const SETTLE_MS: u64 = 100;
+
+fn open_file() -> Fd {
+    match fs::open_with_mode(PATH, O_RDWR | O_CREAT, 0o644) {
+        Ok(fd) => fd,
+        Err(e) => {
+            println!("  open {} failed: {:?}", PATH, e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Fork a child running `f`; its return value becomes the exit status
+fn spawn_child(f: impl FnOnce() -> i32) -> i32 {
+    match process::fork() {
+        Ok(ForkResult::Child) => process::exit(f()),
+        Ok(ForkResult::Parent(pid)) => pid.raw() as i32,
+        Err(e) => {
+            println!("  fork failed: {:?}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Wait for a child and return its exit status (-1 if it did not exit)
+fn wait_child(pid: i32) -> i32 {
+    let mut status = 0;
+    match process::waitpid(pid, &mut status, 0) {
+        Ok(_) if wifexited(status) => wexitstatus(status),
+        _ => -1,
+    }
+}
+
+fn is_errno(result: &Result<(), Error>, errno: Errno) -> bool {
+    matches!(result, Err(Error::Os(e)) if *e == errno)
+}
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+fn main() {
+    println!("=== Advisory File Locking Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    let _ = fs::unlink(PATH);
+    match fs::open_with_mode(PATH, O_RDWR | O_CREAT | O_TRUNC, 0o644) {
+        Ok(fd) => {
+            let _ = io::write(fd, &[0u8; 256]);
+            let _ = close(fd);
+        }
+        Err(e) => {
+            println!("FAIL: cannot create {}: {:?}", PATH, e);
+            println!("FLOCK_TEST_FAILED");
+            process::exit(1);
+        }
+    }
+
+    // Test 1: an exclusive flock excludes other open file descriptions but is
+    // shared with the child through the inherited descriptor
+    println!("\nTest 1: flock sharing and LOCK_NB");
+    let fd = open_file();
+    let locked = fs::flock(fd, LOCK_EX).is_ok();
+    let child = spawn_child(|| {
+        let own = open_file();
+        if !is_errno(&fs::flock(own, LOCK_SH | LOCK_NB), Errno::EAGAIN) {
+            return 1;
+        }
+        if fs::flock(fd, LOCK_EX | LOCK_NB).is_err() {
+            return 2;
+        }
+        0
+    });
+    let status = wait_child(child);
+    if status != 0 {
+        println!("  child status {}", status);
+    }
+    report(
+        "EAGAIN on a new description, granted on the inherited one",
+        locked && status == 0,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 2: a blocked flock is woken when the holder unlocks
+    println!("\nTest 2: Blocking flock wakes on LOCK_UN");
+    let (ready_r, ready_w) = io::pipe().expect("pipe");
+    let child = spawn_child(|| {
+        let own = open_file();
+        let _ = io::write(ready_w, b"r");
+        if fs::flock(own, LOCK_EX).is_err() {
+            return 1;
+        }
+        let _ = io::write(ready_w, b"g");
+        0
+    });
+    let mut byte = [0u8; 1];
+    let _ = io::read(ready_r, &mut byte);
+    let _ = sleep_ms(SETTLE_MS);
+    let _ = io::fcntl_setfl(ready_r, io::status_flags::O_NONBLOCK);
+    let still_blocked = is_errno(&io::read(ready_r, &mut byte).map(|_| ()), Errno::EAGAIN);
+    let _ = fs::flock(fd, LOCK_UN);
+    let status = wait_child(child);
+    let _ = close(ready_r);
+    let _ = close(ready_w);
+    report(
+        "child waited for the lock, then acquired it",
+        still_blocked && status == 0,
+        &mut passed,
+        &mut failed,
+    );
+    let _ = close(fd);
+
+    // Test 3: POSIX record locks conflict only on overlapping ranges, and
+    // F_GETLK reports the holder
+    println!("\nTest 3: F_SETLK / F_GETLK byte ranges");
+    let fd = open_file();
+    let parent_pid = process::getpid().map(|p| p.raw() as i32).unwrap_or(0);
+    let locked = fs::fcntl_setlk(fd, F_SETLK, &Flock::range(F_WRLCK, 0, 100)).is_ok();
+    let child = spawn_child(|| {
+        let mut query = Flock::range(F_RDLCK, 50, 10);
+        if fs::fcntl_getlk(fd, F_GETLK, &mut query).is_err() {
+            return 1;
+        }
+        if query.l_type != F_WRLCK
+            || query.l_start != 0
+            || query.l_len != 100
+            || query.l_pid != parent_pid
+        {
+            return 2;
+        }
+        if !is_errno(
+            &fs::fcntl_setlk(fd, F_SETLK, &Flock::range(F_RDLCK, 90, 20)),
+            Errno::EAGAIN,
+        ) {
+            return 3;
+        }
+        if fs::fcntl_setlk(fd, F_SETLK, &Flock::range(F_WRLCK, 100, 100)).is_err() {
+            return 4;
+        }
+        0
+    });
+    let status = wait_child(child);
+    if status != 0 {
+        println!("  child status {}", status);
+    }
+    report(
+        "overlap refused, disjoint range granted, holder reported",
+        locked && status == 0,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 4: closing any descriptor for the file drops the process's POSIX
+    // locks on it
+    println!("\nTest 4: close() releases POSIX locks");
+    let other = open_file();
+    let _ = close(other);
+    let child = spawn_child(|| {
+        let mut query = Flock::range(F_WRLCK, 0, 0);
+        if fs::fcntl_getlk(fd, F_GETLK, &mut query).is_err() || query.l_type != F_UNLCK {
+            return 1;
+        }
+        0
+    });
+    report(
+        "lock gone after closing a second descriptor",
+        wait_child(child) == 0,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 5: waiting on a lock held by a process that waits on us is EDEADLK
+    println!("\nTest 5: F_SETLKW deadlock detection");
+    let (ready_r, ready_w) = io::pipe().expect("pipe");
+    let locked = fs::fcntl_setlk(fd, F_SETLK, &Flock::range(F_WRLCK, 0, 10)).is_ok();
+    let child = spawn_child(|| {
+        if fs::fcntl_setlk(fd, F_SETLK, &Flock::range(F_WRLCK, 10, 10)).is_err() {
+            return 1;
+        }
+        let _ = io::write(ready_w, b"r");
+        if fs::fcntl_setlk(fd, F_SETLKW, &Flock::range(F_WRLCK, 0, 10)).is_err() {
+            return 2;
+        }
+        0
+    });
+    let mut byte = [0u8; 1];
+    let _ = io::read(ready_r, &mut byte);
+    let _ = sleep_ms(SETTLE_MS);
+    let deadlock = is_errno(
+        &fs::fcntl_setlk(fd, F_SETLKW, &Flock::range(F_WRLCK, 10, 10)),
+        Errno::EDEADLK,
+    );
+    let _ = fs::fcntl_setlk(fd, F_SETLK, &Flock::range(F_UNLCK, 0, 0));
+    let status = wait_child(child);
+    let _ = close(ready_r);
+    let _ = close(ready_w);
+    report(
+        "EDEADLK returned, child acquired the lock after unlock",
+        locked && deadlock && status == 0,
+        &mut passed,
+        &mut failed,
+    );
+    let _ = close(fd);
+
+    // Test 6: OFD locks conflict between descriptions in the same process
+    println!("\nTest 6: F_OFD_SETLK between two descriptions");
+    let first = open_file();
+    let second = open_file();
+    let locked = fs::fcntl_setlk(first, F_OFD_SETLK, &Flock::range(F_WRLCK, 0, 10)).is_ok();
+    let refused = is_errno(
+        &fs::fcntl_setlk(second, F_OFD_SETLK, &Flock::range(F_WRLCK, 5, 10)),
+        Errno::EAGAIN,
+    );
+    let mut query = Flock::range(F_WRLCK, 0, 1);
+    let reported = fs::fcntl_getlk(second, F_OFD_GETLK, &mut query).is_ok()
+        && query.l_type == F_WRLCK
+        && query.l_pid == -1;
+    let _ = close(first);
+    let released = fs::fcntl_setlk(second, F_OFD_SETLK, &Flock::range(F_WRLCK, 5, 10)).is_ok();
+    let _ = close(second);
+    report(
+        "conflict reported with l_pid -1, released on close",
+        locked && refused && reported && released,
+        &mut passed,
+        &mut failed,
+    );
+
+    let _ = fs::unlink(PATH);
+
+    // Summary
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("FLOCK_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("FLOCK_TEST_FAILED");
+        process::exit(1);
+    }
+}
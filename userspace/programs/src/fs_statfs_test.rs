@@ -0,0 +1,179 @@
+//! Filesystem statistics syscall tests
+//!
+//! Tests statfs/fstatfs: ext2 capacity and free counts (including that a
+//! write consumes free blocks), the type magic of every virtual filesystem,
+//! fstatfs on files and pipes, and ENOENT for a missing path.
+//! Must emit "FS_STATFS_TEST_PASSED" on success.
+
+use libbreenix::error::Error;
+use libbreenix::fs::{
+    self, File, DEVFS_SUPER_MAGIC, DEVPTS_SUPER_MAGIC, EXT2_SUPER_MAGIC, O_CREAT, O_RDWR, O_TRUNC,
+    PROC_SUPER_MAGIC,
+};
+use libbreenix::io::{close, pipe};
+use libbreenix::Errno;
+
+const PATH: &str = "/tmp/statfs_test.dat";
+
+/// Linux PIPEFS_MAGIC, reported by fstatfs on a pipe
+const PIPEFS_MAGIC: i64 = 0x5049_5045;
+
+fn main() {
+    println!("=== Filesystem Statistics Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    // Test 1: statfs on the root filesystem reports sane ext2 numbers
+    println!("\nTest 1: statfs(\"/\")");
+    let root = fs::statfs("/");
+    match &root {
+        Ok(st)
+            if st.f_type == EXT2_SUPER_MAGIC
+                && st.f_bsize >= 1024
+                && st.f_blocks > 0
+                && st.f_bfree <= st.f_blocks
+                && st.f_bavail <= st.f_bfree
+                && st.f_files > 0
+                && st.f_ffree <= st.f_files =>
+        {
+            println!(
+                "  PASS: bsize={} blocks={} bfree={} files={} ffree={}",
+                st.f_bsize, st.f_blocks, st.f_bfree, st.f_files, st.f_ffree
+            );
+            passed += 1;
+        }
+        Ok(st) => {
+            println!(
+                "  FAIL: type={:#x} bsize={} blocks={} bfree={} bavail={} files={} ffree={}",
+                st.f_type, st.f_bsize, st.f_blocks, st.f_bfree, st.f_bavail, st.f_files, st.f_ffree
+            );
+            failed += 1;
+        }
+        Err(e) => {
+            println!("  FAIL: {:?}", e);
+            failed += 1;
+        }
+    }
+
+    // Test 2: writing a file consumes free blocks and an inode
+    println!("\nTest 2: Writing 64KB lowers f_bfree");
+    let _ = fs::unlink(PATH);
+    match (root, File::open(PATH, O_RDWR | O_CREAT | O_TRUNC)) {
+        (Ok(before), Ok(file)) => {
+            let chunk = [0x5Au8; 4096];
+            for _ in 0..16 {
+                let _ = file.write(&chunk);
+            }
+            match fs::fstatfs(file.fd()) {
+                Ok(after) if after.f_bfree < before.f_bfree && after.f_ffree < before.f_ffree => {
+                    println!(
+                        "  PASS: bfree {} -> {}, ffree {} -> {}",
+                        before.f_bfree, after.f_bfree, before.f_ffree, after.f_ffree
+                    );
+                    passed += 1;
+                }
+                Ok(after) => {
+                    println!(
+                        "  FAIL: bfree {} -> {}, ffree {} -> {}",
+                        before.f_bfree, after.f_bfree, before.f_ffree, after.f_ffree
+                    );
+                    failed += 1;
+                }
+                Err(e) => {
+                    println!("  FAIL: fstatfs error {:?}", e);
+                    failed += 1;
+                }
+            }
+        }
+        _ => {
+            println!("  FAIL: Cannot create {}", PATH);
+            failed += 1;
+        }
+    }
+    let _ = fs::unlink(PATH);
+
+    // Test 3: virtual filesystems report their type magic
+    println!("\nTest 3: Virtual filesystem magic");
+    let virtual_fs = [
+        ("/proc", PROC_SUPER_MAGIC),
+        ("/proc/uptime", PROC_SUPER_MAGIC),
+        ("/dev", DEVFS_SUPER_MAGIC),
+        ("/dev/null", DEVFS_SUPER_MAGIC),
+        ("/dev/pts", DEVPTS_SUPER_MAGIC),
+    ];
+    let mut magic_ok = true;
+    for (path, magic) in virtual_fs {
+        match fs::statfs(path) {
+            Ok(st) if st.f_type == magic && st.f_blocks == 0 => {}
+            Ok(st) => {
+                println!("  {}: type={:#x} blocks={}", path, st.f_type, st.f_blocks);
+                magic_ok = false;
+            }
+            Err(e) => {
+                println!("  {}: {:?}", path, e);
+                magic_ok = false;
+            }
+        }
+    }
+    if magic_ok {
+        println!("  PASS: proc, devfs, and devpts identified");
+        passed += 1;
+    } else {
+        println!("  FAIL: wrong magic or error");
+        failed += 1;
+    }
+
+    // Test 4: fstatfs on a pipe reports pipefs
+    println!("\nTest 4: fstatfs on a pipe");
+    match pipe() {
+        Ok((read_fd, write_fd)) => {
+            match fs::fstatfs(read_fd) {
+                Ok(st) if st.f_type == PIPEFS_MAGIC => {
+                    println!("  PASS: pipefs");
+                    passed += 1;
+                }
+                Ok(st) => {
+                    println!("  FAIL: type={:#x}", st.f_type);
+                    failed += 1;
+                }
+                Err(e) => {
+                    println!("  FAIL: {:?}", e);
+                    failed += 1;
+                }
+            }
+            let _ = close(read_fd);
+            let _ = close(write_fd);
+        }
+        Err(_) => {
+            println!("  FAIL: pipe() failed");
+            failed += 1;
+        }
+    }
+
+    // Test 5: a missing path is ENOENT
+    println!("\nTest 5: statfs on a missing path");
+    match fs::statfs("/no/such/path") {
+        Err(Error::Os(Errno::ENOENT)) => {
+            println!("  PASS: ENOENT");
+            passed += 1;
+        }
+        other => {
+            println!(
+                "  FAIL: expected ENOENT, got {:?}",
+                other.map(|st| st.f_type)
+            );
+            failed += 1;
+        }
+    }
+
+    // Summary
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("FS_STATFS_TEST_PASSED");
+        std::process::exit(0);
+    } else {
+        println!("FS_STATFS_TEST_FAILED");
+        std::process::exit(1);
+    }
+}
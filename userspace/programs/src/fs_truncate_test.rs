@@ -0,0 +1,237 @@
+//! File size and durability syscall tests
+//!
+//! Tests ftruncate/truncate (shrink and grow), fallocate, and fsync/fdatasync
+//! on ext2. The file is large enough to use indirect blocks so that shrinking
+//! has to walk the indirect tree.
+//! Must emit "FS_TRUNCATE_TEST_PASSED" on success.
+
+use libbreenix::error::Error;
+use libbreenix::fs::{self, File, FALLOC_FL_KEEP_SIZE, O_CREAT, O_RDONLY, O_RDWR, O_TRUNC};
+use libbreenix::io::close;
+use libbreenix::Errno;
+
+const PATH: &str = "/tmp/truncate_test.dat\0";
+const FILE_SIZE: usize = 65536;
+const SHRUNK_SIZE: u64 = 20000;
+const GROWN_SIZE: u64 = 30000;
+
+/// Fill `buf` from the start of the file, returning the bytes read
+fn read_prefix(file: &File, buf: &mut [u8]) -> Result<usize, Error> {
+    file.seek(0, fs::SEEK_SET)?;
+    let mut total = 0;
+    while total < buf.len() {
+        let end = core::cmp::min(total + 4096, buf.len());
+        match file.read(&mut buf[total..end])? {
+            0 => break,
+            n => total += n,
+        }
+    }
+    Ok(total)
+}
+
+fn main() {
+    println!("=== File Truncate/Sync Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    let file = match File::open(PATH, O_RDWR | O_CREAT | O_TRUNC) {
+        Ok(f) => f,
+        Err(_) => {
+            println!("FAIL: Cannot create {}", PATH.trim_end_matches('\0'));
+            println!("FS_TRUNCATE_TEST_FAILED");
+            std::process::exit(1);
+        }
+    };
+
+    // Fill a 64KB file with a pattern (needs indirect blocks)
+    let mut pattern = [0u8; FILE_SIZE];
+    for (i, b) in pattern.iter_mut().enumerate() {
+        *b = (i % 251) as u8 + 1;
+    }
+    let mut written = 0;
+    while written < FILE_SIZE {
+        let end = core::cmp::min(written + 4096, FILE_SIZE);
+        match file.write(&pattern[written..end]) {
+            Ok(n) => written += n,
+            Err(_) => break,
+        }
+    }
+    let blocks_full = fs::fstat(file.fd()).map(|s| s.st_blocks).unwrap_or(0);
+
+    // Test 1: ftruncate shrinks the file and frees blocks
+    println!("\nTest 1: ftruncate shrink 64KB -> {}", SHRUNK_SIZE);
+    match file.set_len(SHRUNK_SIZE) {
+        Ok(()) => match fs::fstat(file.fd()) {
+            Ok(stat) if stat.st_size as u64 == SHRUNK_SIZE && stat.st_blocks < blocks_full => {
+                println!(
+                    "  PASS: size={} st_blocks {} -> {}",
+                    stat.st_size, blocks_full, stat.st_blocks
+                );
+                passed += 1;
+            }
+            Ok(stat) => {
+                println!(
+                    "  FAIL: size={} st_blocks {} -> {}",
+                    stat.st_size, blocks_full, stat.st_blocks
+                );
+                failed += 1;
+            }
+            Err(_) => {
+                println!("  FAIL: fstat error");
+                failed += 1;
+            }
+        },
+        Err(e) => {
+            println!("  FAIL: ftruncate error {:?}", e);
+            failed += 1;
+        }
+    }
+
+    // Test 2: surviving data is intact
+    println!("\nTest 2: Data before the cut is preserved");
+    let mut buf = [0u8; GROWN_SIZE as usize];
+    match read_prefix(&file, &mut buf[..SHRUNK_SIZE as usize]) {
+        Ok(n) if n == SHRUNK_SIZE as usize && buf[..n] == pattern[..n] => {
+            println!("  PASS: {} bytes match", n);
+            passed += 1;
+        }
+        Ok(n) => {
+            println!("  FAIL: read {} bytes or content mismatch", n);
+            failed += 1;
+        }
+        Err(_) => {
+            println!("  FAIL: read error");
+            failed += 1;
+        }
+    }
+
+    // Test 3: growing exposes zeros, not the old pattern
+    println!("\nTest 3: ftruncate grow {} -> {}", SHRUNK_SIZE, GROWN_SIZE);
+    buf.fill(0xAA);
+    match file
+        .set_len(GROWN_SIZE)
+        .and_then(|_| read_prefix(&file, &mut buf))
+    {
+        Ok(n) if n == GROWN_SIZE as usize => {
+            let head_ok = buf[..SHRUNK_SIZE as usize] == pattern[..SHRUNK_SIZE as usize];
+            let tail_ok = buf[SHRUNK_SIZE as usize..].iter().all(|&b| b == 0);
+            if head_ok && tail_ok {
+                println!("  PASS: extension reads back as zeros");
+                passed += 1;
+            } else {
+                println!("  FAIL: head_ok={} tail_ok={}", head_ok, tail_ok);
+                failed += 1;
+            }
+        }
+        Ok(n) => {
+            println!("  FAIL: read {} bytes, expected {}", n, GROWN_SIZE);
+            failed += 1;
+        }
+        Err(e) => {
+            println!("  FAIL: {:?}", e);
+            failed += 1;
+        }
+    }
+
+    // Test 4: truncate by path to zero releases every block
+    println!("\nTest 4: truncate(path, 0)");
+    match fs::truncate(PATH, 0).and_then(|_| fs::fstat(file.fd())) {
+        Ok(stat) if stat.st_size == 0 && stat.st_blocks == 0 => {
+            println!("  PASS: size=0 st_blocks=0");
+            passed += 1;
+        }
+        Ok(stat) => {
+            println!("  FAIL: size={} st_blocks={}", stat.st_size, stat.st_blocks);
+            failed += 1;
+        }
+        Err(e) => {
+            println!("  FAIL: {:?}", e);
+            failed += 1;
+        }
+    }
+
+    // Test 5: fallocate backs the range and extends the size
+    println!("\nTest 5: fallocate 16KB");
+    match fs::fallocate(file.fd(), 0, 0, 16384).and_then(|_| fs::fstat(file.fd())) {
+        Ok(stat) if stat.st_size == 16384 && stat.st_blocks >= 32 => {
+            println!("  PASS: size={} st_blocks={}", stat.st_size, stat.st_blocks);
+            passed += 1;
+        }
+        Ok(stat) => {
+            println!("  FAIL: size={} st_blocks={}", stat.st_size, stat.st_blocks);
+            failed += 1;
+        }
+        Err(e) => {
+            println!("  FAIL: {:?}", e);
+            failed += 1;
+        }
+    }
+
+    // Test 6: FALLOC_FL_KEEP_SIZE allocates without growing
+    println!("\nTest 6: fallocate FALLOC_FL_KEEP_SIZE");
+    match fs::fallocate(file.fd(), FALLOC_FL_KEEP_SIZE, 16384, 8192)
+        .and_then(|_| fs::fstat(file.fd()))
+    {
+        Ok(stat) if stat.st_size == 16384 && stat.st_blocks >= 48 => {
+            println!("  PASS: size unchanged, st_blocks={}", stat.st_blocks);
+            passed += 1;
+        }
+        Ok(stat) => {
+            println!("  FAIL: size={} st_blocks={}", stat.st_size, stat.st_blocks);
+            failed += 1;
+        }
+        Err(e) => {
+            println!("  FAIL: {:?}", e);
+            failed += 1;
+        }
+    }
+
+    // Test 7: fsync and fdatasync succeed on a regular file
+    println!("\nTest 7: fsync/fdatasync");
+    match file.sync_all().and_then(|_| file.sync_data()) {
+        Ok(()) => {
+            println!("  PASS: both returned 0");
+            passed += 1;
+        }
+        Err(e) => {
+            println!("  FAIL: {:?}", e);
+            failed += 1;
+        }
+    }
+
+    // Test 8: ftruncate on a read-only fd is rejected
+    println!("\nTest 8: ftruncate on O_RDONLY fd");
+    match fs::open(PATH, O_RDONLY) {
+        Ok(fd) => {
+            match fs::ftruncate(fd, 0) {
+                Err(Error::Os(Errno::EINVAL)) => {
+                    println!("  PASS: EINVAL");
+                    passed += 1;
+                }
+                other => {
+                    println!("  FAIL: expected EINVAL, got {:?}", other);
+                    failed += 1;
+                }
+            }
+            let _ = close(fd);
+        }
+        Err(_) => {
+            println!("  FAIL: Cannot reopen file");
+            failed += 1;
+        }
+    }
+
+    drop(file);
+    let _ = fs::unlink(PATH);
+
+    // Summary
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("FS_TRUNCATE_TEST_PASSED");
+        std::process::exit(0);
+    } else {
+        println!("FS_TRUNCATE_TEST_FAILED");
+        std::process::exit(1);
+    }
+}
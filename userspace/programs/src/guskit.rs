@@ -15,7 +15,7 @@
 
 use std::process;
 
-use breengel::{CachedFont, Event, Window};
+use breengel::{CachedFont, Event, Window, mime};
 use libbreenix::fs::{self, File};
 use libbreenix::io::{self, PollFd};
 use libbreenix::socket::SockAddrIn;
@@ -1057,6 +1057,8 @@ struct GuskitState {
     file_picker: Option<FilePicker>,
     file_picker_dir: Vec<u8>,
     picker_theme: Theme,
+    /// Path of a picker entry dragged out, for main to start the drag with
+    pending_drag: Option<Vec<u8>>,
 
     // Window dimensions
     width: usize,
@@ -1140,6 +1142,7 @@ impl GuskitState {
             collab_poll_fds: [PollFd::default(); 20],
             file_picker: None,
             file_picker_dir: Vec::from(b"/home" as &[u8]),
+            pending_drag: None,
             picker_theme: Theme::dark(),
             width,
             height,
@@ -1538,6 +1541,39 @@ impl GuskitState {
         }
     }
 
+    /// Load the BMP at `full_path` onto the canvas, replacing the drawing.
+    fn open_bmp(&mut self, full_path: &[u8]) {
+        let path_str = core::str::from_utf8(full_path).unwrap_or("");
+        if let Ok(file) = File::open(path_str, fs::O_RDONLY) {
+            let mut file_data = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                match file.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(n) => file_data.extend_from_slice(&chunk[..n]),
+                    Err(_) => break,
+                }
+            }
+            if let Some((bw, bh, rgb)) = bmp::decode_bmp_24(&file_data) {
+                let copy_w = (bw as usize).min(self.canvas_w);
+                let copy_h = (bh as usize).min(self.canvas_h);
+                for b in self.canvas.iter_mut() {
+                    *b = 255;
+                }
+                for y in 0..copy_h {
+                    for x in 0..copy_w {
+                        let si = (y * bw as usize + x) * 3;
+                        let di = (y * self.canvas_w + x) * 3;
+                        self.canvas[di] = rgb[si];
+                        self.canvas[di + 1] = rgb[si + 1];
+                        self.canvas[di + 2] = rgb[si + 2];
+                    }
+                }
+                println!("Opened {}", path_str);
+            }
+        }
+    }
+
     /// Process all mouse input for this frame. Handles file picker modal intercept,
     /// then press/drag/release state machine for normal drawing.
     /// `left_down` and `was_down` must already be updated from Breengel events
@@ -1569,6 +1605,7 @@ impl GuskitState {
             None,
             OpenFile(Vec<u8>),   // full path bytes to load
             Navigate(Vec<u8>),   // dir entry name to navigate into
+            Drag(Vec<u8>),       // full path bytes to drag out
             Close,
         }
         let picker_action = if let Some(ref mut picker) = self.file_picker {
@@ -1587,6 +1624,14 @@ impl GuskitState {
                         None => PickerAction::None,
                     }
                 }
+                FilePickerResult::DragStarted(_idx) => {
+                    match picker.selected_entry() {
+                        Some(entry) if entry.name != b".." => {
+                            PickerAction::Drag(join_path(&self.file_picker_dir, &entry.name))
+                        }
+                        _ => PickerAction::None,
+                    }
+                }
                 FilePickerResult::Cancelled => PickerAction::Close,
                 FilePickerResult::Active => PickerAction::None,
             }
@@ -1597,37 +1642,12 @@ impl GuskitState {
         // Phase 2: act on result now that the picker borrow is released.
         match picker_action {
             PickerAction::OpenFile(full_path) => {
-                let path_str = core::str::from_utf8(&full_path).unwrap_or("");
-                if let Ok(file) = File::open(path_str, fs::O_RDONLY) {
-                    let mut file_data = Vec::new();
-                    let mut chunk = [0u8; 4096];
-                    loop {
-                        match file.read(&mut chunk) {
-                            Ok(0) => break,
-                            Ok(n) => file_data.extend_from_slice(&chunk[..n]),
-                            Err(_) => break,
-                        }
-                    }
-                    if let Some((bw, bh, rgb)) = bmp::decode_bmp_24(&file_data) {
-                        let copy_w = (bw as usize).min(self.canvas_w);
-                        let copy_h = (bh as usize).min(self.canvas_h);
-                        for b in self.canvas.iter_mut() {
-                            *b = 255;
-                        }
-                        for y in 0..copy_h {
-                            for x in 0..copy_w {
-                                let si = (y * bw as usize + x) * 3;
-                                let di = (y * self.canvas_w + x) * 3;
-                                self.canvas[di] = rgb[si];
-                                self.canvas[di + 1] = rgb[si + 1];
-                                self.canvas[di + 2] = rgb[si + 2];
-                            }
-                        }
-                        println!("Opened {}", path_str);
-                    }
-                }
+                self.open_bmp(&full_path);
                 self.file_picker = None;
             }
+            PickerAction::Drag(full_path) => {
+                self.pending_drag = Some(full_path);
+            }
             PickerAction::Navigate(name) => {
                 if name == b".." {
                     if let Some(pos) = self.file_picker_dir.iter().rposition(|&b| b == b'/') {
@@ -1969,6 +1989,15 @@ fn main() {
                 Event::FocusLost => {
                     menu_bar.close();
                 }
+                Event::Drop { .. } => {
+                    // Open the first BMP of the files dropped on the window
+                    if let Some(data) = win.request_drop(&[mime::URI_LIST]) {
+                        if let Some(path) = data.file_paths().iter().find(|p| p.ends_with(".bmp")) {
+                            state.open_bmp(path.as_bytes());
+                            state.file_picker = None;
+                        }
+                    }
+                }
                 Event::Resized { width: w, height: h } => {
                     state.handle_resize(w as usize, h as usize);
                     menu_bar.set_rect(BuiRect::new(0, 0, w as i32, MENU_BAR_HEIGHT));
@@ -1989,6 +2018,11 @@ fn main() {
         if !menu_bar.is_open() {
             state.process_input();
         }
+        if let Some(path) = state.pending_drag.take() {
+            let path_str = core::str::from_utf8(&path).unwrap_or("");
+            let uri_list = breengel::clipboard::file_uri_list(&[path_str]);
+            let _ = win.start_drag(&[(mime::URI_LIST, uri_list.as_bytes()), (mime::TEXT, path.as_slice())]);
+        }
 
         // 4. Render into window framebuffer
         state.render(win.framebuf(), &mut mono_font, font_size);
@@ -5,6 +5,9 @@
 //! - Process group queries with getpgrp()/getpgid()
 //! - SIGCONT signal delivery
 //! - waitpid with WUNTRACED flag
+//! - Stopping and continuing a child with SIGSTOP/SIGCONT (WUNTRACED,
+//!   WCONTINUED, CLD_STOPPED/CLD_CONTINUED)
+//! - SIGTTIN stopping a background reader of a controlling terminal
 //! - Terminal process group control with tcgetpgrp()/tcsetpgrp()
 //!
 //! These tests verify that the building blocks for shell job control work
@@ -13,9 +16,10 @@
 
 use libbreenix::error::Error;
 use libbreenix::process::{self, ForkResult};
+use libbreenix::pty;
 use libbreenix::signal;
 use libbreenix::termios;
-use libbreenix::types::Fd;
+use libbreenix::types::{Fd, Timespec};
 
 /// Extract the raw errno code from a libbreenix Error
 fn errno_code(e: &Error) -> i64 {
@@ -185,6 +189,167 @@ fn test_getpgid_specific() {
     pass("getpgid(our_pid) matches getpgrp()");
 }
 
+/// Wait up to a second for the SIGCHLD of `child` and return its si_code
+fn sigchld_code(child: i32) -> i32 {
+    let set = signal::sigmask(signal::SIGCHLD);
+    let timeout = Timespec {
+        tv_sec: 1,
+        tv_nsec: 0,
+    };
+    let mut info = signal::Siginfo::default();
+    match signal::sigtimedwait(&set, Some(&mut info), Some(&timeout)) {
+        Ok(_) if info.si_pid() == child => info.si_code,
+        Ok(_) => fail("SIGCHLD came from the wrong process"),
+        Err(_) => fail("no SIGCHLD within a second"),
+    }
+}
+
+/// Test 6: Stopping and continuing a child
+fn test_stop_and_continue() {
+    println!("Test 6: Stopping and continuing a child");
+
+    let chld = signal::sigmask(signal::SIGCHLD);
+    let mut old_mask: u64 = 0;
+    if signal::sigprocmask(signal::SIG_BLOCK, Some(&chld), Some(&mut old_mask)).is_err() {
+        fail("sigprocmask(SIG_BLOCK, SIGCHLD) failed");
+    }
+
+    let child = match process::fork() {
+        Ok(ForkResult::Child) => loop {
+            let _ = process::yield_now();
+        },
+        Ok(ForkResult::Parent(child_pid)) => child_pid.raw() as i32,
+        Err(_) => fail("fork() failed"),
+    };
+    println!("  Child PID: {}", child);
+
+    let mut status: i32 = 0;
+    if signal::kill(child, signal::SIGSTOP).is_err() {
+        fail("kill(child, SIGSTOP) failed");
+    }
+    match process::waitpid(child, &mut status, process::WUNTRACED) {
+        Ok(pid) if pid.raw() as i32 == child => {}
+        _ => fail("waitpid(WUNTRACED) did not report the child"),
+    }
+    if !process::wifstopped(status) || process::wstopsig(status) != signal::SIGSTOP {
+        println!("  status = {:#x}", status);
+        fail("status is not WIFSTOPPED with SIGSTOP");
+    }
+    if sigchld_code(child) != signal::CLD_STOPPED {
+        fail("SIGCHLD si_code is not CLD_STOPPED");
+    }
+    pass("SIGSTOP stopped the child: WIFSTOPPED and CLD_STOPPED");
+
+    // The stop is reported once
+    match process::waitpid(child, &mut status, process::WUNTRACED | process::WNOHANG) {
+        Ok(pid) if pid.raw() == 0 => {}
+        _ => fail("the stop was reported twice"),
+    }
+
+    if signal::kill(child, signal::SIGCONT).is_err() {
+        fail("kill(child, SIGCONT) failed");
+    }
+    match process::waitpid(child, &mut status, process::WCONTINUED) {
+        Ok(pid) if pid.raw() as i32 == child => {}
+        _ => fail("waitpid(WCONTINUED) did not report the child"),
+    }
+    if !process::wifcontinued(status) {
+        println!("  status = {:#x}", status);
+        fail("status is not WIFCONTINUED");
+    }
+    if sigchld_code(child) != signal::CLD_CONTINUED {
+        fail("SIGCHLD si_code is not CLD_CONTINUED");
+    }
+    pass("SIGCONT continued the child: WIFCONTINUED and CLD_CONTINUED");
+
+    let _ = signal::kill(child, signal::SIGKILL);
+    match process::waitpid(child, &mut status, 0) {
+        Ok(_) if process::wifsignaled(status) && process::wtermsig(status) == signal::SIGKILL => {}
+        _ => fail("continued child was not killed by SIGKILL"),
+    }
+    let _ = signal::sigprocmask(signal::SIG_SETMASK, Some(&old_mask), None);
+    pass("continued child killed with SIGKILL");
+}
+
+/// Session leader side of test 7: exits 0 once SIGTTIN stopped its
+/// background child
+fn ttin_session_leader(slave_path: &str) -> ! {
+    if process::setsid().is_err() {
+        std::process::exit(10);
+    }
+    let slave = match libbreenix::fs::open(slave_path, libbreenix::fs::O_RDWR) {
+        Ok(fd) => fd,
+        Err(_) => std::process::exit(11),
+    };
+    if termios::set_controlling_terminal(slave).is_err() {
+        std::process::exit(12);
+    }
+
+    let reader = match process::fork() {
+        Ok(ForkResult::Child) => {
+            // Background group of the terminal's session: reading must stop us
+            let _ = process::setpgid(0, 0);
+            let mut buf = [0u8; 8];
+            loop {
+                match libbreenix::io::read(slave, &mut buf) {
+                    Err(ref e) if errno_code(e) == 4 => continue, // EINTR
+                    _ => std::process::exit(2),
+                }
+            }
+        }
+        Ok(ForkResult::Parent(pid)) => pid.raw() as i32,
+        Err(_) => std::process::exit(13),
+    };
+
+    let mut status: i32 = 0;
+    let mut stopped = false;
+    for _ in 0..200 {
+        match process::waitpid(reader, &mut status, process::WUNTRACED | process::WNOHANG) {
+            Ok(pid) if pid.raw() as i32 == reader => {
+                stopped =
+                    process::wifstopped(status) && process::wstopsig(status) == signal::SIGTTIN;
+                break;
+            }
+            Ok(_) => {
+                let _ = libbreenix::time::sleep_ms(10);
+            }
+            Err(_) => break,
+        }
+    }
+    let _ = signal::kill(reader, signal::SIGKILL);
+    let _ = process::waitpid(reader, &mut status, 0);
+    std::process::exit(if stopped { 0 } else { 14 });
+}
+
+/// Test 7: SIGTTIN stops a background reader of the controlling terminal
+fn test_background_read_sigttin() {
+    println!("Test 7: SIGTTIN stops a background reader of the controlling terminal");
+
+    let (master, path) = pty::openpty().unwrap_or_else(|_| fail("openpty failed"));
+    let path_len = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+    let slave_path = match core::str::from_utf8(&path[..path_len + 1]) {
+        Ok(p) => p,
+        Err(_) => fail("slave path is not UTF-8"),
+    };
+
+    let leader = match process::fork() {
+        Ok(ForkResult::Child) => ttin_session_leader(slave_path),
+        Ok(ForkResult::Parent(pid)) => pid.raw() as i32,
+        Err(_) => fail("fork() failed"),
+    };
+
+    let mut status: i32 = 0;
+    if process::waitpid(leader, &mut status, 0).is_err() {
+        fail("waitpid(session leader) failed");
+    }
+    let _ = libbreenix::io::close(master);
+    if !process::wifexited(status) || process::wexitstatus(status) != 0 {
+        println!("  session leader status = {:#x}", status);
+        fail("background read was not stopped by SIGTTIN");
+    }
+    pass("background read of the controlling terminal stopped with SIGTTIN");
+}
+
 fn main() {
     println!("=== Job Control Infrastructure Tests ===\n");
 
@@ -203,6 +368,12 @@ fn main() {
     test_getpgid_specific();
     println!();
 
+    test_stop_and_continue();
+    println!();
+
+    test_background_read_sigttin();
+    println!();
+
     println!("=== All job control infrastructure tests passed ===");
     println!("JOB_CONTROL_TEST_PASSED");
 
@@ -0,0 +1,301 @@
+//! Scheduling priority tests
+//!
+//! Tests that getpriority()/setpriority() read and clamp nice levels and are
+//! inherited across fork(), that sched_setscheduler() and friends validate
+//! and report SCHED_OTHER, SCHED_FIFO and SCHED_RR with their priorities, and
+//! that the scheduler honours them: competing with nice 19 CPU hogs, a nice
+//! 0 thread gets most of its CPU, and a SCHED_FIFO thread nearly all of it.
+//! Must emit "PRIORITY_TEST_PASSED" on success.
+
+use libbreenix::error::Error;
+use libbreenix::process::{
+    self, ForkResult, SchedParam, PRIO_PGRP, PRIO_PROCESS, RUSAGE_THREAD, SCHED_FIFO, SCHED_OTHER,
+    SCHED_RR,
+};
+use libbreenix::signal::{kill, SIGKILL};
+use libbreenix::Errno;
+
+/// CPU hogs competing with the measured thread; more than there are CPUs
+const HOGS: usize = 8;
+
+/// How long each CPU share is measured for
+const MEASURE_MS: u64 = 300;
+
+fn is_errno<T>(result: &Result<T, Error>, errno: Errno) -> bool {
+    matches!(result, Err(Error::Os(e)) if *e == errno)
+}
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+fn monotonic_ms() -> u64 {
+    libbreenix::time::now_monotonic()
+        .map(|ts| ts.tv_sec as u64 * 1000 + ts.tv_nsec as u64 / 1_000_000)
+        .unwrap_or(0)
+}
+
+/// CPU time of the calling thread in milliseconds
+fn thread_cpu_ms() -> u64 {
+    process::getrusage(RUSAGE_THREAD)
+        .map(|usage| {
+            let micros = |tv: &libbreenix::signal::Timeval| {
+                tv.tv_sec as u64 * 1_000_000 + tv.tv_usec as u64
+            };
+            (micros(&usage.ru_utime) + micros(&usage.ru_stime)) / 1000
+        })
+        .unwrap_or(0)
+}
+
+/// Spin for `MEASURE_MS` of wall time and return the percentage of it this
+/// thread spent on a CPU
+fn measure_share() -> u64 {
+    let start_cpu = thread_cpu_ms();
+    let start = monotonic_ms();
+    let mut x = 0u64;
+    while monotonic_ms() < start + MEASURE_MS {
+        for i in 0..1000u64 {
+            x = std::hint::black_box(x.wrapping_mul(31).wrapping_add(i));
+        }
+    }
+    let wall = monotonic_ms() - start;
+    (thread_cpu_ms() - start_cpu) * 100 / wall.max(1)
+}
+
+/// Fork a child that runs `body` and exits with its result
+fn spawn(body: fn() -> i32) -> Option<i32> {
+    match process::fork() {
+        Ok(ForkResult::Child) => process::exit(body()),
+        Ok(ForkResult::Parent(pid)) => Some(pid.raw() as i32),
+        Err(_) => None,
+    }
+}
+
+/// Spin at nice 19 until killed
+fn hog() -> i32 {
+    let _ = process::setpriority(PRIO_PROCESS, 0, 19);
+    let mut x = 0u64;
+    loop {
+        x = std::hint::black_box(x.wrapping_add(1));
+    }
+}
+
+/// Exit with the inherited nice level
+fn report_nice() -> i32 {
+    process::getpriority(PRIO_PROCESS, 0).unwrap_or(-100)
+}
+
+/// Exit with the inherited policy and priority (policy * 100 + priority)
+fn report_policy() -> i32 {
+    match (process::sched_getscheduler(0), process::sched_getparam(0)) {
+        (Ok(policy), Ok(param)) => policy * 100 + param.sched_priority,
+        _ => -1,
+    }
+}
+
+/// Wait for `pid` and return its exit status
+fn exit_status(pid: i32) -> Option<i32> {
+    let mut status = 0;
+    process::waitpid(pid, &mut status, 0).ok()?;
+    process::wifexited(status).then(|| process::wexitstatus(status))
+}
+
+fn set_policy(policy: i32, priority: i32) -> Result<(), Error> {
+    process::sched_setscheduler(
+        0,
+        policy,
+        &SchedParam {
+            sched_priority: priority,
+        },
+    )
+}
+
+fn main() {
+    println!("=== Scheduling Priority Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    println!("\nTest 1: getpriority and setpriority");
+    let initial = process::getpriority(PRIO_PROCESS, 0);
+    let set = process::setpriority(PRIO_PROCESS, 0, 5);
+    let raised = process::getpriority(PRIO_PROCESS, 0);
+    let _ = process::setpriority(PRIO_PROCESS, 0, 100);
+    let clamped = process::getpriority(PRIO_PROCESS, 0);
+    let lowered = process::setpriority(PRIO_PROCESS, 0, 0).and_then(|_| process::nice(-3));
+    report(
+        "default nice is 0",
+        matches!(initial, Ok(0)),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "setpriority changes nice",
+        set.is_ok() && matches!(raised, Ok(5)),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "nice is clamped to 19",
+        matches!(clamped, Ok(19)),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "root may lower nice",
+        matches!(lowered, Ok(-3)),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "process group is selectable",
+        matches!(process::getpriority(PRIO_PGRP, 0), Ok(n) if n <= -3),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "bad which is EINVAL, missing pid ESRCH",
+        is_errno(&process::getpriority(7, 0), Errno::EINVAL)
+            && is_errno(&process::setpriority(PRIO_PROCESS, 0x7fff, 0), Errno::ESRCH),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 2: nice is inherited by fork");
+    let _ = process::setpriority(PRIO_PROCESS, 0, 7);
+    let inherited = spawn(report_nice).and_then(exit_status);
+    let _ = process::setpriority(PRIO_PROCESS, 0, 0);
+    report(
+        "child starts at the parent's nice",
+        inherited == Some(7),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 3: policy ranges");
+    report(
+        "real-time priorities are 1..99",
+        matches!(process::sched_get_priority_min(SCHED_FIFO), Ok(1))
+            && matches!(process::sched_get_priority_max(SCHED_RR), Ok(99)),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "SCHED_OTHER has priority 0 only",
+        matches!(process::sched_get_priority_max(SCHED_OTHER), Ok(0)),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "unknown policy is EINVAL",
+        is_errno(&process::sched_get_priority_max(9), Errno::EINVAL),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 4: sched_setscheduler and sched_setparam");
+    report(
+        "starts as SCHED_OTHER",
+        matches!(process::sched_getscheduler(0), Ok(SCHED_OTHER)),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "out-of-range priorities are EINVAL",
+        is_errno(&set_policy(SCHED_FIFO, 0), Errno::EINVAL)
+            && is_errno(&set_policy(SCHED_RR, 100), Errno::EINVAL)
+            && is_errno(&set_policy(SCHED_OTHER, 5), Errno::EINVAL),
+        &mut passed,
+        &mut failed,
+    );
+    let rr = set_policy(SCHED_RR, 20);
+    let rr_policy = process::sched_getscheduler(0);
+    let setparam = process::sched_setparam(0, &SchedParam { sched_priority: 30 });
+    let rr_param = process::sched_getparam(0);
+    report(
+        "SCHED_RR with priority 20",
+        rr.is_ok() && matches!(rr_policy, Ok(SCHED_RR)),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "sched_setparam keeps the policy",
+        setparam.is_ok()
+            && matches!(rr_param, Ok(p) if p.sched_priority == 30)
+            && matches!(process::sched_getscheduler(0), Ok(SCHED_RR)),
+        &mut passed,
+        &mut failed,
+    );
+    let inherited = spawn(report_policy).and_then(exit_status);
+    report(
+        "child inherits the real-time policy",
+        inherited == Some(SCHED_RR * 100 + 30),
+        &mut passed,
+        &mut failed,
+    );
+    let back = set_policy(SCHED_OTHER, 0);
+    report(
+        "back to SCHED_OTHER",
+        back.is_ok()
+            && matches!(process::sched_getparam(0), Ok(p) if p.sched_priority == 0)
+            && matches!(process::sched_getscheduler(0), Ok(SCHED_OTHER)),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 5: CPU share against nice 19 hogs");
+    let hogs: Vec<i32> = (0..HOGS).filter_map(|_| spawn(hog)).collect();
+    // Let the hogs settle at nice 19 and build up run time
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let normal_share = measure_share();
+    let _ = set_policy(SCHED_FIFO, 10);
+    let fifo_share = measure_share();
+    let _ = set_policy(SCHED_OTHER, 0);
+    let _ = process::setpriority(PRIO_PROCESS, 0, 19);
+    let equal_share = measure_share();
+    let _ = process::setpriority(PRIO_PROCESS, 0, 0);
+    for &pid in &hogs {
+        let _ = kill(pid, SIGKILL);
+    }
+    for &pid in &hogs {
+        let mut status = 0;
+        let _ = process::waitpid(pid, &mut status, 0);
+    }
+    println!(
+        "  shares: nice 0 {}%, SCHED_FIFO {}%, nice 19 {}%",
+        normal_share, fifo_share, equal_share
+    );
+    report(
+        "all hogs started",
+        hogs.len() == HOGS,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "nice 0 gets most of the CPU",
+        normal_share >= 70 && normal_share > equal_share,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "SCHED_FIFO gets nearly all of the CPU",
+        fifo_share >= 90,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("PRIORITY_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("PRIORITY_TEST_FAILED");
+        process::exit(1);
+    }
+}
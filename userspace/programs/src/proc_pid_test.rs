@@ -0,0 +1,333 @@
+//! /proc/<pid>/ hierarchy tests
+//!
+//! Tests that /proc/self links to the caller's /proc/<pid>, that cmdline,
+//! environ, stat, statm and maps describe this process, that the exe, cwd
+//! and fd/<n> links name the program, the working directory and each open
+//! file, that task/ lists the thread group, and that a forked child shows
+//! its parent in /proc/<child>/stat.
+//! Must emit "PROC_PID_TEST_PASSED" on success.
+
+use libbreenix::io;
+use libbreenix::memory::{self, MAP_ANONYMOUS, MAP_PRIVATE, PROT_READ, PROT_WRITE};
+use libbreenix::process::{self, ForkResult};
+use std::fs;
+
+const MAP_SIZE: usize = 3 * 4096;
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+fn link(path: &str) -> Option<String> {
+    fs::read_link(path)
+        .ok()
+        .map(|target| target.to_string_lossy().into_owned())
+}
+
+fn dir_names(path: &str) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// The fields of /proc/<pid>/stat after `(comm)`, which may contain spaces
+fn stat_fields(pid: i32) -> Option<(String, Vec<String>)> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let comm = stat[open + 1..close].to_string();
+    let rest = stat[close + 1..]
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    Some((comm, rest))
+}
+
+/// Exit 0 if /proc/<pid>/stat of this child names `parent` as its parent
+fn check_child_stat(parent: i32) -> i32 {
+    let pid = process::getpid().map(|p| p.raw() as i32).unwrap_or(0);
+    match stat_fields(pid) {
+        Some((_, fields)) if fields.get(1) == Some(&parent.to_string()) => 0,
+        _ => 1,
+    }
+}
+
+fn main() {
+    println!("=== /proc/<pid> Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+    let pid = process::getpid().map(|p| p.raw() as i32).unwrap_or(0);
+
+    println!("\nTest 1: /proc/self");
+    report(
+        "/proc/self links to the caller's pid",
+        link("/proc/self") == Some(pid.to_string()),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "/proc lists self",
+        dir_names("/proc").contains(&String::from("self")),
+        &mut passed,
+        &mut failed,
+    );
+    let status = fs::read_to_string("/proc/self/status").unwrap_or_default();
+    report(
+        "/proc/self/status is our status",
+        status.lines().any(|line| line == format!("Pid:\t{}", pid)),
+        &mut passed,
+        &mut failed,
+    );
+    let entries = dir_names(&format!("/proc/{}", pid));
+    println!("  /proc/{}: {:?}", pid, entries);
+    report(
+        "/proc/<pid> lists the per-process entries",
+        [
+            "cmdline", "cwd", "environ", "exe", "fd", "maps", "stat", "statm", "status", "task",
+        ]
+        .iter()
+        .all(|name| entries.iter().any(|entry| entry == name)),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 2: cmdline and environ");
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).unwrap_or_default();
+    let expected: Vec<u8> = std::env::args()
+        .flat_map(|arg| arg.into_bytes().into_iter().chain([0]))
+        .collect();
+    println!("  cmdline {:?}", String::from_utf8_lossy(&cmdline));
+    report(
+        "cmdline is argv, NUL-terminated",
+        !cmdline.is_empty() && cmdline == expected,
+        &mut passed,
+        &mut failed,
+    );
+    let environ = fs::read("/proc/self/environ").unwrap_or_default();
+    report(
+        "environ holds PATH",
+        environ
+            .split(|&b| b == 0)
+            .any(|var| var.starts_with(b"PATH=")),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 3: stat and statm");
+    let ppid = status
+        .lines()
+        .find_map(|line| line.strip_prefix("PPid:\t"))
+        .unwrap_or("")
+        .to_string();
+    match stat_fields(pid) {
+        Some((comm, fields)) => {
+            println!("  comm {:?}, {} fields after it", comm, fields.len());
+            report(
+                "stat has 52 fields",
+                fields.len() == 50,
+                &mut passed,
+                &mut failed,
+            );
+            report(
+                "stat names the program, state and parent",
+                comm == "proc_pid_test"
+                    && fields.first().map(String::as_str) == Some("R")
+                    && fields.get(1) == Some(&ppid),
+                &mut passed,
+                &mut failed,
+            );
+            report(
+                "stat counts one thread at nice 0",
+                fields.get(15).map(String::as_str) == Some("20")
+                    && fields.get(16).map(String::as_str) == Some("0")
+                    && fields.get(17).map(String::as_str) == Some("1"),
+                &mut passed,
+                &mut failed,
+            );
+        }
+        None => report("stat is readable", false, &mut passed, &mut failed),
+    }
+    let statm: Vec<u64> = fs::read_to_string("/proc/self/statm")
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter_map(|field| field.parse().ok())
+        .collect();
+    println!("  statm {:?}", statm);
+    report(
+        "statm has 7 sizes, resident no larger than total",
+        statm.len() == 7 && statm[0] > 0 && statm[1] <= statm[0],
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 4: maps");
+    let region = memory::mmap(
+        std::ptr::null_mut(),
+        MAP_SIZE,
+        PROT_READ | PROT_WRITE,
+        MAP_PRIVATE | MAP_ANONYMOUS,
+        -1,
+        0,
+    );
+    let maps = fs::read_to_string("/proc/self/maps").unwrap_or_default();
+    for line in maps.lines() {
+        println!("  {}", line);
+    }
+    let mapped = region.as_ref().is_ok_and(|&ptr| {
+        let start = ptr as usize;
+        let range = format!("{:08x}-{:08x} rw-p", start, start + MAP_SIZE);
+        maps.lines().any(|line| line.starts_with(&range))
+    });
+    report(
+        "anonymous mmap appears as rw-p",
+        mapped,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "stack and program image are named",
+        maps.lines().any(|line| line.ends_with(" [stack]"))
+            && maps.lines().any(|line| line.ends_with("proc_pid_test")),
+        &mut passed,
+        &mut failed,
+    );
+    if let Ok(ptr) = region {
+        let _ = memory::munmap(ptr, MAP_SIZE);
+    }
+
+    println!("\nTest 5: exe, cwd and fd links");
+    let exe = link("/proc/self/exe");
+    let cwd = std::env::current_dir()
+        .ok()
+        .map(|dir| dir.to_string_lossy().into_owned());
+    println!("  exe {:?}, cwd {:?}", exe, link("/proc/self/cwd"));
+    report(
+        "exe names the program",
+        exe.is_some_and(|exe| exe.starts_with('/') && exe.ends_with("proc_pid_test")),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "cwd names the working directory",
+        cwd.is_some() && link(&format!("/proc/{}/cwd", pid)) == cwd,
+        &mut passed,
+        &mut failed,
+    );
+    let file = fs::File::open("/proc/version");
+    let pipe = io::pipe();
+    let fds = dir_names("/proc/self/fd");
+    println!("  fds {:?}", fds);
+    let file_ok = file.is_ok() && {
+        use std::os::fd::AsRawFd;
+        let fd = file.as_ref().map(|f| f.as_raw_fd()).unwrap_or(-1);
+        fds.contains(&fd.to_string())
+            && link(&format!("/proc/self/fd/{}", fd)).as_deref() == Some("/proc/version")
+    };
+    report(
+        "fd link names an open file",
+        file_ok,
+        &mut passed,
+        &mut failed,
+    );
+    let pipe_ok = pipe.as_ref().is_ok_and(|(read_end, write_end)| {
+        let read_link = link(&format!("/proc/self/fd/{}", read_end.raw()));
+        let write_link = link(&format!("/proc/self/fd/{}", write_end.raw()));
+        read_link
+            .as_deref()
+            .is_some_and(|t| t.starts_with("pipe:["))
+            && read_link == write_link
+    });
+    report(
+        "both pipe ends link to the same pipe",
+        pipe_ok,
+        &mut passed,
+        &mut failed,
+    );
+    if let Ok((read_end, write_end)) = pipe {
+        let _ = io::close(read_end);
+        let _ = io::close(write_end);
+    }
+    drop(file);
+    report(
+        "fd lists stdio",
+        ["0", "1", "2"]
+            .iter()
+            .all(|fd| fds.iter().any(|entry| entry == fd)),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "exe opens the executable through the link",
+        fs::metadata("/proc/self/exe").is_ok_and(|meta| meta.len() > 0),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 6: task");
+    let tasks = dir_names(&format!("/proc/{}/task", pid));
+    println!("  tasks {:?}", tasks);
+    report(
+        "task lists the thread",
+        tasks == vec![pid.to_string()],
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "task/<tid>/status is the thread's status",
+        fs::read_to_string(format!("/proc/{}/task/{}/status", pid, pid))
+            .is_ok_and(|status| status.lines().any(|line| line == format!("Pid:\t{}", pid))),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 7: forked child");
+    let child_ok = match process::fork() {
+        Ok(ForkResult::Child) => process::exit(check_child_stat(pid)),
+        Ok(ForkResult::Parent(child)) => {
+            let child = child.raw() as i32;
+            let cmdline_ok = fs::read(format!("/proc/{}/cmdline", child))
+                .is_ok_and(|child_cmdline| child_cmdline == cmdline);
+            let mut status = 0;
+            let exited = process::waitpid(child, &mut status, 0).is_ok()
+                && process::wifexited(status)
+                && process::wexitstatus(status) == 0;
+            cmdline_ok && exited
+        }
+        Err(_) => false,
+    };
+    report(
+        "child inherits cmdline and shows its parent",
+        child_ok,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "a missing pid has no directory",
+        fs::read_to_string("/proc/32767/stat").is_err(),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("PROC_PID_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("PROC_PID_TEST_FAILED");
+        process::exit(1);
+    }
+}
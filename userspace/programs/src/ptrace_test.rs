@@ -0,0 +1,245 @@
+//! ptrace tests
+//!
+//! Tests that a child calling PTRACE_TRACEME stops for its parent on a
+//! signal and that waitpid reports the stop, that PEEKDATA/POKEDATA read and
+//! write the tracee's memory (read-only text included), that GETREGS/SETREGS
+//! round-trip its registers and refuse a kernel program counter, that
+//! SINGLESTEP stops it with SIGTRAP after one instruction, that
+//! PTRACE_SYSCALL stops it at syscall entry and exit with the syscall number
+//! and return value visible, and that PTRACE_ATTACH stops a running process
+//! which PTRACE_DETACH lets go again.
+//! Must emit "PTRACE_TEST_PASSED" on success.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use libbreenix::error::Error;
+use libbreenix::process::{self, ForkResult};
+use libbreenix::ptrace::{self, PTRACE_O_TRACESYSGOOD};
+use libbreenix::signal::{kill, SIGKILL, SIGSTOP, SIGTRAP};
+use libbreenix::syscall::nr;
+use libbreenix::Errno;
+
+/// Word the tracer rewrites; the tracee exits with its low byte
+static TARGET: AtomicU64 = AtomicU64::new(0x1111);
+
+/// Value the tracer pokes into TARGET
+const POKED: u64 = 42;
+
+/// Kernel address no tracee may be resumed at
+const KERNEL_PC: u64 = 0xffff_8000_0000_0000;
+
+fn is_errno<T>(result: &Result<T, Error>, errno: Errno) -> bool {
+    matches!(result, Err(Error::Os(e)) if *e == errno)
+}
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+/// Wait for `pid` and return its status
+fn wait(pid: i32) -> Option<i32> {
+    let mut status = 0;
+    process::waitpid(pid, &mut status, 0).ok()?;
+    Some(status)
+}
+
+/// Whether `status` is a stop with signal `sig`
+fn stopped_by(status: Option<i32>, sig: i32) -> bool {
+    status.is_some_and(|s| process::wifstopped(s) && process::wstopsig(s) == sig)
+}
+
+/// Traced child: stop, make one syscall, exit with TARGET's low byte
+fn traced_child() -> ! {
+    if ptrace::traceme().is_err() {
+        process::exit(100);
+    }
+    let pid = process::getpid().map(|p| p.raw() as i32).unwrap_or(0);
+    let _ = kill(pid, SIGSTOP);
+    let _ = process::getpid();
+    process::exit((TARGET.load(Ordering::SeqCst) & 0xff) as i32);
+}
+
+/// Untraced child: spin in syscalls until killed
+fn busy_child() -> ! {
+    loop {
+        let _ = process::yield_now();
+    }
+}
+
+fn fork_child(body: fn() -> !) -> Option<i32> {
+    match process::fork() {
+        Ok(ForkResult::Child) => body(),
+        Ok(ForkResult::Parent(pid)) => Some(pid.raw() as i32),
+        Err(_) => None,
+    }
+}
+
+fn main() {
+    println!("=== ptrace Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    let Some(pid) = fork_child(traced_child) else {
+        println!("FAIL: fork failed");
+        println!("PTRACE_TEST_FAILED");
+        process::exit(1);
+    };
+
+    println!("\nTest 1: PTRACE_TRACEME child stops on SIGSTOP");
+    report(
+        "waitpid reports a SIGSTOP stop",
+        stopped_by(wait(pid), SIGSTOP),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 2: PEEKDATA and POKEDATA");
+    let target = &TARGET as *const AtomicU64 as u64;
+    let before = ptrace::peek_data(pid, target);
+    let poked = ptrace::poke_data(pid, target, POKED).is_ok();
+    let after = ptrace::peek_data(pid, target);
+    report(
+        "reads 0x1111, writes 42, reads 42 back",
+        before.ok() == Some(0x1111) && poked && after.ok() == Some(POKED),
+        &mut passed,
+        &mut failed,
+    );
+    let text = traced_child as *const () as u64;
+    let text_ok = match ptrace::peek_data(pid, text) {
+        Ok(word) => ptrace::poke_data(pid, text, word).is_ok(),
+        Err(_) => false,
+    };
+    report(
+        "read-only text is readable and writable",
+        text_ok,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "unmapped address gives EIO",
+        is_errno(&ptrace::peek_data(pid, 0x10), Errno::EIO),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 3: GETREGS and SETREGS");
+    let regs = ptrace::get_regs(pid).ok();
+    let round_trip = regs.is_some_and(|regs| {
+        regs.pc() != 0
+            && ptrace::set_regs(pid, &regs).is_ok()
+            && ptrace::get_regs(pid).is_ok_and(|again| again.pc() == regs.pc())
+    });
+    report("registers round-trip", round_trip, &mut passed, &mut failed);
+    let refused = regs.is_some_and(|mut bad| {
+        #[cfg(target_arch = "x86_64")]
+        {
+            bad.rip = KERNEL_PC;
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            bad.pc = KERNEL_PC;
+        }
+        is_errno(&ptrace::set_regs(pid, &bad), Errno::EIO)
+    });
+    report(
+        "kernel program counter refused with EIO",
+        refused,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 4: SINGLESTEP");
+    let pc_before = regs.map(|r| r.pc()).unwrap_or(0);
+    let stepped = ptrace::single_step(pid, 0).is_ok() && stopped_by(wait(pid), SIGTRAP);
+    let pc_after = ptrace::get_regs(pid).map(|r| r.pc()).unwrap_or(0);
+    report(
+        "one instruction then a SIGTRAP stop",
+        stepped && pc_after != 0 && pc_after != pc_before,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 5: PTRACE_SYSCALL");
+    let options = ptrace::set_options(pid, PTRACE_O_TRACESYSGOOD).is_ok();
+    let entry = ptrace::syscall(pid, 0).is_ok() && stopped_by(wait(pid), SIGTRAP | 0x80);
+    let entry_nr = ptrace::get_regs(pid).map(|r| r.syscall_number());
+    report(
+        "stops at getpid entry",
+        options && entry && entry_nr.ok() == Some(nr::GETPID),
+        &mut passed,
+        &mut failed,
+    );
+    let exit = ptrace::syscall(pid, 0).is_ok() && stopped_by(wait(pid), SIGTRAP | 0x80);
+    let ret = ptrace::get_regs(pid).map(|r| r.return_value());
+    report(
+        "stops at getpid exit with the pid as return value",
+        exit && ret.ok() == Some(pid as u64),
+        &mut passed,
+        &mut failed,
+    );
+    let _ = ptrace::set_options(pid, 0);
+
+    println!("\nTest 6: PTRACE_CONT to exit");
+    let exited = ptrace::cont(pid, 0).is_ok()
+        && wait(pid)
+            .is_some_and(|s| process::wifexited(s) && process::wexitstatus(s) == POKED as i32);
+    report(
+        "exits with the poked value",
+        exited,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "not traced any more",
+        is_errno(&ptrace::cont(pid, 0), Errno::ESRCH),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 7: PTRACE_ATTACH and PTRACE_DETACH");
+    match fork_child(busy_child) {
+        Some(pid) => {
+            let attached = ptrace::attach(pid).is_ok();
+            let stopped = attached && stopped_by(wait(pid), SIGSTOP);
+            report(
+                "attach stops a running process",
+                stopped,
+                &mut passed,
+                &mut failed,
+            );
+            report(
+                "second attach refused with EPERM",
+                is_errno(&ptrace::attach(pid), Errno::EPERM),
+                &mut passed,
+                &mut failed,
+            );
+            report(
+                "detach lets it run untraced",
+                ptrace::detach(pid, 0).is_ok() && is_errno(&ptrace::get_regs(pid), Errno::ESRCH),
+                &mut passed,
+                &mut failed,
+            );
+            let _ = kill(pid, SIGKILL);
+            let killed = wait(pid)
+                .is_some_and(|s| process::wifsignaled(s) && process::wtermsig(s) == SIGKILL);
+            report("killed after detaching", killed, &mut passed, &mut failed);
+        }
+        None => report("fork", false, &mut passed, &mut failed),
+    }
+
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("PTRACE_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("PTRACE_TEST_FAILED");
+        process::exit(1);
+    }
+}
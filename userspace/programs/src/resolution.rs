@@ -1,6 +1,8 @@
 //! resolution - display framebuffer resolution
 //!
 //! Usage: resolution
+//!        resolution <display> <width>x<height>
+//!        resolution <display> off
 //!
 //! Queries the kernel framebuffer and displays:
 //! - Resolution (width x height)
@@ -8,10 +10,76 @@
 //! - Bytes per pixel
 //! - Pixel format (RGB/BGR)
 //! - Framebuffer size in KB
+//! - Each display output, its mode and where it sits in the framebuffer
+//!
+//! With a display number, sets that output's mode (or turns it off). The
+//! framebuffer is the desktop all outputs show part of; its size is fixed at
+//! boot, so a mode must fit inside it.
+
+use libbreenix::graphics::{self, DisplayInfo};
 
-use libbreenix::graphics;
+fn print_displays() {
+    let mut displays = [DisplayInfo::default(); 16];
+    let count = match graphics::list_displays(&mut displays) {
+        Ok(n) => n.min(displays.len()),
+        // No GPU display outputs (e.g. a firmware framebuffer)
+        Err(_) => return,
+    };
+    for d in &displays[..count] {
+        if !d.connected() {
+            println!("Display {}: disconnected", d.id);
+        } else if !d.active() {
+            println!(
+                "Display {}: off (display is {}x{})",
+                d.id, d.preferred_width, d.preferred_height
+            );
+        } else {
+            println!(
+                "Display {}: {}x{} at {},{} (display is {}x{})",
+                d.id, d.width, d.height, d.x, d.y, d.preferred_width, d.preferred_height
+            );
+        }
+    }
+}
+
+fn parse_mode(mode: &str) -> Option<(u32, u32)> {
+    if mode == "off" {
+        return Some((0, 0));
+    }
+    let (w, h) = mode.split_once('x')?;
+    let (w, h) = (w.parse::<u32>().ok()?, h.parse::<u32>().ok()?);
+    if w == 0 || h == 0 {
+        return None;
+    }
+    Some((w, h))
+}
+
+fn set_mode(display: &str, mode: &str) -> ! {
+    let (id, (w, h)) = match (display.parse::<u32>(), parse_mode(mode)) {
+        (Ok(id), Some(mode)) => (id, mode),
+        _ => {
+            eprintln!("usage: resolution [<display> <width>x<height> | <display> off]");
+            std::process::exit(2);
+        }
+    };
+    match graphics::set_display_mode(id, w, h) {
+        Ok(()) => {
+            print_displays();
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("resolution: cannot set display {} to {}: {}", id, mode, e);
+            std::process::exit(1);
+        }
+    }
+}
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() == 3 {
+        set_mode(&args[1], &args[2]);
+    }
+
     match graphics::fbinfo() {
         Ok(info) => {
             println!("Resolution: {}x{}", info.width, info.height);
@@ -29,6 +97,8 @@ fn main() {
             let fb_size = info.stride * info.height * info.bytes_per_pixel;
             println!("Framebuffer size: {} KB", fb_size / 1024);
 
+            print_displays();
+
             std::process::exit(0);
         }
         Err(e) => {
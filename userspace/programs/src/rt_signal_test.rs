@@ -0,0 +1,267 @@
+//! Real-time signal queueing tests
+//!
+//! Tests that repeated real-time signals queue instead of collapsing, each
+//! carrying its sigqueue() value, that sigtimedwait() accepts them in signal
+//! number then arrival order and times out with EAGAIN, that a full queue
+//! refuses more with EAGAIN, that queued instances reach SA_SIGINFO handlers
+//! one at a time, and that pthread_kill() delivers to the named thread only.
+//! Must emit "RT_SIGNAL_TEST_PASSED" on success.
+
+use std::sync::atomic::{AtomicI32, AtomicU32, AtomicU64, Ordering};
+
+use libbreenix::error::Error;
+use libbreenix::process;
+use libbreenix::signal::{
+    sigmask, sigpending, sigprocmask, Siginfo, Ucontext, SIGRTMIN, SIGUSR1, SIG_BLOCK, SIG_UNBLOCK,
+    SI_QUEUE, SI_TKILL,
+};
+use libbreenix::time::now_monotonic;
+use libbreenix::types::Timespec;
+use libbreenix::{sigaction, sigqueue, sigtimedwait, Errno, Sigaction};
+
+extern "C" {
+    fn pthread_create(
+        thread: *mut usize,
+        attr: *const u8,
+        start_routine: extern "C" fn(*mut u8) -> *mut u8,
+        arg: *mut u8,
+    ) -> i32;
+    fn pthread_join(thread: usize, retval: *mut *mut u8) -> i32;
+    fn pthread_kill(thread: usize, sig: i32) -> i32;
+}
+
+/// Per-process real-time queue limit (POSIX SIGQUEUE_MAX)
+const SIGQUEUE_MAX: u64 = 32;
+
+/// Signal directed at the helper thread in test 6
+const THREAD_SIG: i32 = SIGRTMIN + 3;
+
+/// Values seen by the SA_SIGINFO handler, in delivery order
+static HANDLED: AtomicU32 = AtomicU32::new(0);
+static HANDLED_VALUES: [AtomicU64; 4] = [const { AtomicU64::new(u64::MAX) }; 4];
+
+/// What the helper thread accepted
+static THREAD_TID: AtomicI32 = AtomicI32::new(0);
+static THREAD_SIGNO: AtomicI32 = AtomicI32::new(0);
+static THREAD_CODE: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn value_handler(_sig: i32, info: *mut Siginfo, _ucontext: *mut Ucontext) {
+    let Some(info) = (unsafe { info.as_ref() }) else {
+        return;
+    };
+    let slot = HANDLED.fetch_add(1, Ordering::SeqCst) as usize;
+    if let Some(value) = HANDLED_VALUES.get(slot) {
+        value.store(info.si_value(), Ordering::SeqCst);
+    }
+}
+
+/// Helper thread: block THREAD_SIG, publish its tid, and wait for the signal
+extern "C" fn waiting_thread(_arg: *mut u8) -> *mut u8 {
+    let set = sigmask(THREAD_SIG);
+    let _ = sigprocmask(SIG_BLOCK, Some(&set), None);
+    let tid = process::gettid().map(|t| t.raw() as i32).unwrap_or(-1);
+    THREAD_TID.store(tid, Ordering::SeqCst);
+
+    let mut info = Siginfo::default();
+    let timeout = Timespec {
+        tv_sec: 2,
+        tv_nsec: 0,
+    };
+    if let Ok(sig) = sigtimedwait(&set, Some(&mut info), Some(&timeout)) {
+        THREAD_CODE.store(info.si_code, Ordering::SeqCst);
+        THREAD_SIGNO.store(sig, Ordering::SeqCst);
+    } else {
+        THREAD_SIGNO.store(-1, Ordering::SeqCst);
+    }
+    core::ptr::null_mut()
+}
+
+/// Accept one signal from `set` without waiting
+fn poll(set: u64) -> Result<(i32, Siginfo), Error> {
+    let mut info = Siginfo::default();
+    let zero = Timespec::new();
+    sigtimedwait(&set, Some(&mut info), Some(&zero)).map(|sig| (sig, info))
+}
+
+fn is_errno<T>(result: &Result<T, Error>, errno: Errno) -> bool {
+    matches!(result, Err(Error::Os(e)) if *e == errno)
+}
+
+fn elapsed_ms(start: &Timespec) -> i128 {
+    now_monotonic()
+        .map(|now| (now.as_nanos() - start.as_nanos()) / 1_000_000)
+        .unwrap_or(0)
+}
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+fn main() {
+    println!("=== Real-Time Signal Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    let my_pid = process::getpid().map(|p| p.raw() as i32).unwrap_or(0);
+    let rt1 = sigmask(SIGRTMIN);
+    let rt2 = sigmask(SIGRTMIN + 1) | sigmask(SIGRTMIN + 2);
+    let blocked = rt1 | rt2 | sigmask(SIGUSR1);
+    if sigprocmask(SIG_BLOCK, Some(&blocked), None).is_err() {
+        println!("FAIL: sigprocmask failed");
+        println!("RT_SIGNAL_TEST_FAILED");
+        process::exit(1);
+    }
+
+    // Test 1: three sigqueue() calls leave three instances, accepted in order
+    // with their values and the sender's pid
+    println!("\nTest 1: Repeated SIGRTMIN queues every instance");
+    let sent = (0..3u64).all(|value| sigqueue(my_pid, SIGRTMIN, 100 + value).is_ok());
+    let mut pending = 0u64;
+    let _ = sigpending(&mut pending);
+    let mut in_order = true;
+    for value in 0..3u64 {
+        match poll(rt1) {
+            Ok((sig, info)) => {
+                in_order &= sig == SIGRTMIN
+                    && info.si_code == SI_QUEUE
+                    && info.si_pid() == my_pid
+                    && info.si_value() == 100 + value;
+            }
+            Err(_) => in_order = false,
+        }
+    }
+    let drained = is_errno(&poll(rt1), Errno::EAGAIN);
+    report(
+        "three instances with values 100, 101, 102, then EAGAIN",
+        sent && pending & rt1 != 0 && in_order && drained,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 2: lowest signal number first; standard signals still collapse
+    println!("\nTest 2: Acceptance order across signals");
+    let _ = sigqueue(my_pid, SIGRTMIN + 2, 2);
+    let _ = sigqueue(my_pid, SIGRTMIN + 1, 1);
+    let _ = sigqueue(my_pid, SIGUSR1, 0);
+    let _ = sigqueue(my_pid, SIGUSR1, 0);
+    let order: Vec<i32> = (0..4)
+        .filter_map(|_| poll(blocked).ok())
+        .map(|(sig, _)| sig)
+        .collect();
+    report(
+        "SIGUSR1 once, then SIGRTMIN+1, then SIGRTMIN+2",
+        order == [SIGUSR1, SIGRTMIN + 1, SIGRTMIN + 2],
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 3: a timed wait with nothing pending times out with EAGAIN
+    println!("\nTest 3: sigtimedwait timeout");
+    let start = now_monotonic().unwrap_or_default();
+    let timeout = Timespec {
+        tv_sec: 0,
+        tv_nsec: 50_000_000,
+    };
+    let result = sigtimedwait(&rt1, None, Some(&timeout));
+    let waited = elapsed_ms(&start);
+    report(
+        "EAGAIN after roughly 50ms",
+        is_errno(&result, Errno::EAGAIN) && waited >= 40,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 4: the queue is bounded; the overflowing sigqueue() fails with EAGAIN
+    println!("\nTest 4: Queue limit");
+    let filled = (0..SIGQUEUE_MAX).all(|value| sigqueue(my_pid, SIGRTMIN, value).is_ok());
+    let overflow = is_errno(&sigqueue(my_pid, SIGRTMIN, 0), Errno::EAGAIN);
+    let mut count = 0;
+    while poll(rt1).is_ok() {
+        count += 1;
+    }
+    report(
+        "SIGQUEUE_MAX instances accepted, the next refused",
+        filled && overflow && count == SIGQUEUE_MAX,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 5: unblocking runs the handler once per queued instance
+    println!("\nTest 5: Queued instances reach the handler");
+    let handler_set = sigaction(
+        SIGRTMIN,
+        Some(&Sigaction::with_siginfo(value_handler)),
+        None,
+    )
+    .is_ok();
+    let _ = sigqueue(my_pid, SIGRTMIN, 7);
+    let _ = sigqueue(my_pid, SIGRTMIN, 8);
+    let _ = sigprocmask(SIG_UNBLOCK, Some(&rt1), None);
+    for _ in 0..100 {
+        if HANDLED.load(Ordering::SeqCst) >= 2 {
+            break;
+        }
+        let _ = process::yield_now();
+    }
+    report(
+        "handler saw values 7 then 8",
+        handler_set
+            && HANDLED.load(Ordering::SeqCst) == 2
+            && HANDLED_VALUES[0].load(Ordering::SeqCst) == 7
+            && HANDLED_VALUES[1].load(Ordering::SeqCst) == 8,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 6: pthread_kill() targets one thread, which accepts the signal as
+    // SI_TKILL; the main thread never sees it pending
+    println!("\nTest 6: pthread_kill to a waiting thread");
+    let mut thread = 0usize;
+    let created = unsafe {
+        pthread_create(
+            &mut thread,
+            core::ptr::null(),
+            waiting_thread,
+            core::ptr::null_mut(),
+        )
+    } == 0;
+    for _ in 0..1000 {
+        if !created || THREAD_TID.load(Ordering::SeqCst) != 0 {
+            break;
+        }
+        let _ = process::yield_now();
+    }
+    let killed = created && unsafe { pthread_kill(thread, THREAD_SIG) } == 0;
+    let joined = created && unsafe { pthread_join(thread, core::ptr::null_mut()) } == 0;
+    let mut pending = 0u64;
+    let _ = sigpending(&mut pending);
+    let gone = unsafe { pthread_kill(thread, THREAD_SIG) } != 0;
+    report(
+        "thread accepted SI_TKILL, not pending here, ESRCH after exit",
+        killed
+            && joined
+            && THREAD_SIGNO.load(Ordering::SeqCst) == THREAD_SIG
+            && THREAD_CODE.load(Ordering::SeqCst) == SI_TKILL
+            && pending & sigmask(THREAD_SIG) == 0
+            && gone,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Summary
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("RT_SIGNAL_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("RT_SIGNAL_TEST_FAILED");
+        process::exit(1);
+    }
+}
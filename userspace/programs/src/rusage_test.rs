@@ -0,0 +1,288 @@
+//! Resource usage and waitid tests
+//!
+//! Tests that getrusage() reports user and system time, max RSS and minor
+//! faults for the caller (RUSAGE_SELF, RUSAGE_THREAD) and its reaped
+//! children (RUSAGE_CHILDREN), that wait4() returns the usage of the child it
+//! reaps, that times() counts clock ticks, and that waitid() selects children
+//! by P_PID, P_PGID and P_ALL, reports exits, stops and kills as SIGCHLD
+//! siginfo, and leaves a child waitable under WNOWAIT.
+//! Must emit "RUSAGE_TEST_PASSED" on success.
+
+use libbreenix::error::Error;
+use libbreenix::process::{
+    self, ForkResult, Rusage, P_ALL, P_PGID, P_PID, RUSAGE_CHILDREN, RUSAGE_SELF, RUSAGE_THREAD,
+    WEXITED, WNOHANG, WNOWAIT, WSTOPPED,
+};
+use libbreenix::signal::{
+    kill, Siginfo, CLD_EXITED, CLD_KILLED, CLD_STOPPED, SIGCHLD, SIGKILL, SIGSTOP,
+};
+use libbreenix::Errno;
+
+/// Memory a forked child writes to, taking copy-on-write faults
+static mut SCRATCH: [u8; 16384] = [0; 16384];
+
+fn is_errno<T>(result: &Result<T, Error>, errno: Errno) -> bool {
+    matches!(result, Err(Error::Os(e)) if *e == errno)
+}
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+fn monotonic_ms() -> u64 {
+    libbreenix::time::now_monotonic()
+        .map(|ts| ts.tv_sec as u64 * 1000 + ts.tv_nsec as u64 / 1_000_000)
+        .unwrap_or(0)
+}
+
+fn micros(tv: &libbreenix::signal::Timeval) -> u64 {
+    tv.tv_sec as u64 * 1_000_000 + tv.tv_usec as u64
+}
+
+/// Spin in userspace for `ms` milliseconds
+fn burn_user(ms: u64) {
+    let end = monotonic_ms() + ms;
+    let mut x = 0u64;
+    while monotonic_ms() < end {
+        for i in 0..10_000u64 {
+            x = std::hint::black_box(x.wrapping_mul(31).wrapping_add(i));
+        }
+    }
+}
+
+/// Make `count` cheap syscalls
+fn burn_system(count: u32) {
+    for _ in 0..count {
+        let _ = std::hint::black_box(process::getpid());
+    }
+}
+
+/// Fork a child that runs `body` and exits with its result
+fn spawn(body: fn() -> i32) -> Option<i32> {
+    match process::fork() {
+        Ok(ForkResult::Child) => process::exit(body()),
+        Ok(ForkResult::Parent(pid)) => Some(pid.raw() as i32),
+        Err(_) => None,
+    }
+}
+
+/// Burns CPU, dirties copied pages and sleeps, then exits 0 if its own
+/// usage showed all of it
+fn busy_child() -> i32 {
+    burn_user(50);
+    burn_system(2000);
+    let scratch = core::ptr::addr_of_mut!(SCRATCH);
+    for page in 0..4 {
+        unsafe { (*scratch)[page * 4096] = 1 };
+    }
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    match process::getrusage(RUSAGE_SELF) {
+        Ok(usage)
+            if micros(&usage.ru_utime) > 0
+                && micros(&usage.ru_stime) > 0
+                && usage.ru_minflt > 0
+                && usage.ru_nvcsw > 0 =>
+        {
+            0
+        }
+        _ => 1,
+    }
+}
+
+fn exit_seven() -> i32 {
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    7
+}
+
+fn own_group_exit_three() -> i32 {
+    let _ = process::setpgid(0, 0);
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    3
+}
+
+fn sleep_forever() -> i32 {
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+}
+
+fn waitid(idtype: i32, id: i32, options: i32) -> Result<Siginfo, Error> {
+    let mut info = Siginfo::default();
+    process::waitid(idtype, id, &mut info, options, None).map(|_| info)
+}
+
+fn is_child_event(info: &Siginfo, pid: i32, code: i32, status: i32) -> bool {
+    info.si_signo == SIGCHLD
+        && info.si_pid() == pid
+        && info.si_code == code
+        && info.si_status() == status
+}
+
+fn main() {
+    println!("=== Resource Usage Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    println!("\nTest 1: RUSAGE_SELF and RUSAGE_THREAD");
+    burn_user(30);
+    burn_system(2000);
+    let own = process::getrusage(RUSAGE_SELF);
+    let thread = process::getrusage(RUSAGE_THREAD);
+    report(
+        "user and system time, max RSS",
+        matches!(&own, Ok(u) if micros(&u.ru_utime) > 0 && micros(&u.ru_stime) > 0 && u.ru_maxrss > 0),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "thread time within process time",
+        matches!((&own, &thread), (Ok(o), Ok(t))
+            if micros(&t.ru_utime) > 0 && micros(&t.ru_utime) <= micros(&o.ru_utime) + 1000),
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "unknown who is EINVAL",
+        is_errno(&process::getrusage(5), Errno::EINVAL),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 2: wait4 rusage and RUSAGE_CHILDREN");
+    let before = process::getrusage(RUSAGE_CHILDREN).unwrap_or_default();
+    let mut child_usage = Rusage::default();
+    let mut status = 0;
+    let waited = spawn(busy_child)
+        .map(|pid| process::wait4(pid, &mut status, 0, Some(&mut child_usage)).is_ok());
+    let after = process::getrusage(RUSAGE_CHILDREN).unwrap_or_default();
+    report(
+        "child saw its own time, faults and switches",
+        waited == Some(true) && process::wifexited(status) && process::wexitstatus(status) == 0,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "wait4 returned the child's usage",
+        micros(&child_usage.ru_utime) >= 40_000
+            && micros(&child_usage.ru_stime) > 0
+            && child_usage.ru_maxrss > 0
+            && child_usage.ru_minflt > 0,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "RUSAGE_CHILDREN grew by the child's time",
+        micros(&after.ru_utime) >= micros(&before.ru_utime) + micros(&child_usage.ru_utime)
+            && after.ru_nvcsw > before.ru_nvcsw,
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 3: times");
+    let first = process::times();
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    let second = process::times();
+    report(
+        "clock ticks advance and include reaped children",
+        matches!((first, second), (Ok((_, t1)), Ok((tms, t2)))
+            if t2 >= t1 + 3 && tms.tms_cutime >= 4 && tms.tms_utime > 0),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 4: waitid P_PID with WNOWAIT");
+    match spawn(exit_seven) {
+        Some(pid) => {
+            let pending = waitid(P_PID, pid, WEXITED | WNOHANG);
+            let peeked = waitid(P_PID, pid, WEXITED | WNOWAIT);
+            let reaped = waitid(P_PID, pid, WEXITED);
+            let gone = waitid(P_PID, pid, WEXITED);
+            report(
+                "WNOHANG before exit reports no child",
+                matches!(&pending, Ok(info) if info.si_pid() == 0),
+                &mut passed,
+                &mut failed,
+            );
+            report(
+                "WNOWAIT reports the exit and leaves the child",
+                matches!(&peeked, Ok(info) if is_child_event(info, pid, CLD_EXITED, 7)),
+                &mut passed,
+                &mut failed,
+            );
+            report(
+                "second wait reaps it",
+                matches!(&reaped, Ok(info) if is_child_event(info, pid, CLD_EXITED, 7))
+                    && is_errno(&gone, Errno::ECHILD),
+                &mut passed,
+                &mut failed,
+            );
+        }
+        None => report("fork", false, &mut passed, &mut failed),
+    }
+
+    println!("\nTest 5: waitid P_PGID");
+    match spawn(own_group_exit_three) {
+        Some(pid) => {
+            let _ = process::setpgid(pid, pid);
+            let info = waitid(P_PGID, pid, WEXITED);
+            report(
+                "child found by its process group",
+                matches!(&info, Ok(info) if is_child_event(info, pid, CLD_EXITED, 3)),
+                &mut passed,
+                &mut failed,
+            );
+        }
+        None => report("fork", false, &mut passed, &mut failed),
+    }
+    report(
+        "empty process group is ECHILD",
+        is_errno(&waitid(P_PGID, 0x7fff, WEXITED), Errno::ECHILD),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\nTest 6: waitid P_ALL with WSTOPPED");
+    match spawn(sleep_forever) {
+        Some(pid) => {
+            let _ = kill(pid, SIGSTOP);
+            let stopped = waitid(P_ALL, 0, WSTOPPED);
+            let _ = kill(pid, SIGKILL);
+            let killed = waitid(P_ALL, 0, WEXITED);
+            report(
+                "stop reported as CLD_STOPPED",
+                matches!(&stopped, Ok(info) if is_child_event(info, pid, CLD_STOPPED, SIGSTOP)),
+                &mut passed,
+                &mut failed,
+            );
+            report(
+                "kill reported as CLD_KILLED",
+                matches!(&killed, Ok(info) if is_child_event(info, pid, CLD_KILLED, SIGKILL)),
+                &mut passed,
+                &mut failed,
+            );
+        }
+        None => report("fork", false, &mut passed, &mut failed),
+    }
+    report(
+        "no state to wait for is EINVAL",
+        is_errno(&waitid(P_ALL, 0, WNOHANG), Errno::EINVAL),
+        &mut passed,
+        &mut failed,
+    );
+
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("RUSAGE_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("RUSAGE_TEST_FAILED");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,237 @@
+//! SA_SIGINFO delivery tests
+//!
+//! Tests that SA_SIGINFO handlers receive a populated siginfo_t and ucontext_t:
+//! si_pid/si_uid/SI_USER for kill() from this process and from a child,
+//! CLD_EXITED and si_status for SIGCHLD, and si_addr/SEGV_MAPERR for a fault
+//! whose handler resumes past the faulting load by editing uc_mcontext.
+//! Must emit "SIGINFO_TEST_PASSED" on success.
+
+use std::sync::atomic::{AtomicI32, AtomicU32, AtomicU64, Ordering};
+
+use libbreenix::process::{self, wexitstatus, wifexited, ForkResult};
+use libbreenix::signal::{
+    Siginfo, Ucontext, CLD_EXITED, SEGV_MAPERR, SIGCHLD, SIGSEGV, SIGUSR1, SIGUSR2, SI_USER,
+};
+use libbreenix::{kill, sigaction, Sigaction};
+
+/// Unmapped address the fault test loads from
+const FAULT_ADDR: u64 = 0x1000;
+
+/// Value left in the load's destination register when the load is skipped
+const SENTINEL: u64 = 0x5157_1f0f;
+
+/// Length of the faulting load instruction in `faulting_load`
+#[cfg(target_arch = "x86_64")]
+const FAULT_INSN_LEN: u64 = 3;
+#[cfg(target_arch = "aarch64")]
+const FAULT_INSN_LEN: u64 = 4;
+
+/// What the handler saw for one signal
+struct Record {
+    count: AtomicU32,
+    code: AtomicI32,
+    pid: AtomicI32,
+    uid: AtomicU32,
+    status: AtomicI32,
+    addr: AtomicU64,
+}
+
+impl Record {
+    const fn new() -> Self {
+        Record {
+            count: AtomicU32::new(0),
+            code: AtomicI32::new(-1),
+            pid: AtomicI32::new(-1),
+            uid: AtomicU32::new(u32::MAX),
+            status: AtomicI32::new(-1),
+            addr: AtomicU64::new(0),
+        }
+    }
+}
+
+static RECORDS: [Record; 32] = [const { Record::new() }; 32];
+
+extern "C" fn record_handler(sig: i32, info: *mut Siginfo, ucontext: *mut Ucontext) {
+    let (Some(info), Some(ucontext)) = (unsafe { info.as_ref() }, unsafe { ucontext.as_mut() })
+    else {
+        return;
+    };
+    let Some(record) = RECORDS.get(sig as usize) else {
+        return;
+    };
+    if info.si_signo != sig {
+        return;
+    }
+    record.code.store(info.si_code, Ordering::SeqCst);
+    if sig == SIGSEGV {
+        record.addr.store(info.si_addr(), Ordering::SeqCst);
+        // Resume after the faulting load instead of re-executing it
+        let pc = ucontext.uc_mcontext.pc();
+        ucontext.uc_mcontext.set_pc(pc + FAULT_INSN_LEN);
+    } else {
+        record.pid.store(info.si_pid(), Ordering::SeqCst);
+        record.uid.store(info.si_uid(), Ordering::SeqCst);
+        record.status.store(info.si_status(), Ordering::SeqCst);
+    }
+    record.count.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Load from `addr` with a fixed-length instruction
+#[cfg(target_arch = "x86_64")]
+fn faulting_load(addr: u64) -> u64 {
+    let value: u64;
+    unsafe {
+        // mov rax, [rdi] (48 8b 07)
+        core::arch::asm!(
+            "mov rax, qword ptr [rdi]",
+            in("rdi") addr,
+            inout("rax") SENTINEL => value,
+            options(nostack),
+        );
+    }
+    value
+}
+
+/// Load from `addr` with a fixed-length instruction
+#[cfg(target_arch = "aarch64")]
+fn faulting_load(addr: u64) -> u64 {
+    let value: u64;
+    unsafe {
+        core::arch::asm!(
+            "ldr x0, [x1]",
+            in("x1") addr,
+            inout("x0") SENTINEL => value,
+            options(nostack),
+        );
+    }
+    value
+}
+
+fn record(sig: i32) -> &'static Record {
+    &RECORDS[sig as usize]
+}
+
+/// Yield until the handler for `sig` has run `count` times
+fn wait_for(sig: i32, count: u32) -> bool {
+    for _ in 0..100 {
+        if record(sig).count.load(Ordering::SeqCst) >= count {
+            return true;
+        }
+        let _ = process::yield_now();
+    }
+    false
+}
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+fn main() {
+    println!("=== SA_SIGINFO Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    let my_pid = process::getpid().map(|p| p.raw() as i32).unwrap_or(0);
+    let my_uid = process::getuid();
+    for sig in [SIGUSR1, SIGUSR2, SIGCHLD, SIGSEGV] {
+        if sigaction(sig, Some(&Sigaction::with_siginfo(record_handler)), None).is_err() {
+            println!("FAIL: sigaction({}) failed", sig);
+            println!("SIGINFO_TEST_FAILED");
+            process::exit(1);
+        }
+    }
+
+    // Test 1: kill() to self reports SI_USER with our pid and uid
+    println!("\nTest 1: kill() to self");
+    let sent = kill(my_pid, SIGUSR1).is_ok();
+    let delivered = wait_for(SIGUSR1, 1);
+    let r = record(SIGUSR1);
+    report(
+        "si_code SI_USER, si_pid and si_uid of the sender",
+        sent && delivered
+            && r.code.load(Ordering::SeqCst) == SI_USER
+            && r.pid.load(Ordering::SeqCst) == my_pid
+            && r.uid.load(Ordering::SeqCst) == my_uid,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 2: a child's kill() names the child; its exit raises SIGCHLD with
+    // CLD_EXITED and the exit status
+    println!("\nTest 2: kill() from a child, then SIGCHLD");
+    let child = match process::fork() {
+        Ok(ForkResult::Child) => {
+            let _ = kill(my_pid, SIGUSR2);
+            process::exit(7);
+        }
+        Ok(ForkResult::Parent(pid)) => pid.raw() as i32,
+        Err(e) => {
+            println!("FAIL: fork failed: {:?}", e);
+            println!("SIGINFO_TEST_FAILED");
+            process::exit(1);
+        }
+    };
+    let mut status = 0;
+    let mut reaped = false;
+    for _ in 0..100 {
+        // Signals from the child may interrupt the wait with EINTR
+        if process::waitpid(child, &mut status, 0).is_ok() {
+            reaped = true;
+            break;
+        }
+    }
+    let delivered = wait_for(SIGUSR2, 1) && wait_for(SIGCHLD, 1);
+    let usr2 = record(SIGUSR2);
+    let chld = record(SIGCHLD);
+    report(
+        "SIGUSR2 si_pid is the child",
+        delivered
+            && usr2.code.load(Ordering::SeqCst) == SI_USER
+            && usr2.pid.load(Ordering::SeqCst) == child,
+        &mut passed,
+        &mut failed,
+    );
+    report(
+        "SIGCHLD CLD_EXITED with si_pid and si_status 7",
+        reaped
+            && wifexited(status)
+            && wexitstatus(status) == 7
+            && chld.code.load(Ordering::SeqCst) == CLD_EXITED
+            && chld.pid.load(Ordering::SeqCst) == child
+            && chld.status.load(Ordering::SeqCst) == 7,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 3: a fault reports si_addr, and the handler's uc_mcontext edit
+    // resumes execution after the faulting load
+    println!("\nTest 3: SIGSEGV si_addr and ucontext resume");
+    let value = faulting_load(FAULT_ADDR);
+    let segv = record(SIGSEGV);
+    report(
+        "SEGV_MAPERR at the faulting address, load skipped",
+        segv.count.load(Ordering::SeqCst) == 1
+            && segv.code.load(Ordering::SeqCst) == SEGV_MAPERR
+            && segv.addr.load(Ordering::SeqCst) == FAULT_ADDR
+            && value == SENTINEL,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Summary
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("SIGINFO_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("SIGINFO_TEST_FAILED");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,192 @@
+//! In-kernel data transfer tests
+//!
+//! Tests sendfile() from a file into a pipe with and without an offset
+//! pointer, splice() between files and pipes, tee() leaving the source pipe
+//! intact, and the EINVAL/ESPIPE error cases.
+//! Must emit "SPLICE_TEST_PASSED" on success.
+
+use libbreenix::error::Error;
+use libbreenix::fs::{self, O_CREAT, O_RDONLY, O_RDWR, O_TRUNC, SEEK_CUR, SEEK_SET};
+use libbreenix::io::splice_flags::SPLICE_F_NONBLOCK;
+use libbreenix::io::{self, close};
+use libbreenix::process;
+use libbreenix::types::Fd;
+use libbreenix::Errno;
+
+const SRC_PATH: &str = "/tmp/splice_src.dat";
+const DST_PATH: &str = "/tmp/splice_dst.dat";
+
+/// Source file contents: 4 KiB of a repeating pattern
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+fn create_source(data: &[u8]) -> bool {
+    match fs::open_with_mode(SRC_PATH, O_RDWR | O_CREAT | O_TRUNC, 0o644) {
+        Ok(fd) => {
+            let ok = io::write(fd, data)
+                .map(|n| n == data.len())
+                .unwrap_or(false);
+            let _ = close(fd);
+            ok
+        }
+        Err(_) => false,
+    }
+}
+
+/// Drain up to `len` bytes from a pipe's read end
+fn read_exact(fd: Fd, len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len);
+    let mut buf = [0u8; 512];
+    while out.len() < len {
+        let want = (len - out.len()).min(buf.len());
+        match io::read(fd, &mut buf[..want]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => out.extend_from_slice(&buf[..n]),
+        }
+    }
+    out
+}
+
+fn is_errno<T>(result: &Result<T, Error>, errno: Errno) -> bool {
+    matches!(result, Err(Error::Os(e)) if *e == errno)
+}
+
+fn report(name: &str, ok: bool, passed: &mut u32, failed: &mut u32) {
+    if ok {
+        println!("  PASS: {}", name);
+        *passed += 1;
+    } else {
+        println!("  FAIL: {}", name);
+        *failed += 1;
+    }
+}
+
+fn main() {
+    println!("=== sendfile/splice/tee Test ===");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    let data = pattern(4096);
+    if !create_source(&data) {
+        println!("FAIL: cannot create {}", SRC_PATH);
+        println!("SPLICE_TEST_FAILED");
+        process::exit(1);
+    }
+    let src = match fs::open(SRC_PATH, O_RDONLY) {
+        Ok(fd) => fd,
+        Err(e) => {
+            println!("FAIL: cannot open {}: {:?}", SRC_PATH, e);
+            println!("SPLICE_TEST_FAILED");
+            process::exit(1);
+        }
+    };
+    let (pipe_r, pipe_w) = io::pipe().expect("pipe");
+
+    // Test 1: sendfile with an offset pointer updates the offset and leaves
+    // the file position alone
+    println!("\nTest 1: sendfile with offset");
+    let mut offset: i64 = 100;
+    let sent = io::sendfile(pipe_w, src, Some(&mut offset), 1000);
+    let received = read_exact(pipe_r, 1000);
+    let position = fs::lseek(src, 0, SEEK_CUR).unwrap_or(u64::MAX);
+    report(
+        "1000 bytes from offset 100, offset advanced, position unchanged",
+        matches!(sent, Ok(1000)) && received == data[100..1100] && offset == 1100 && position == 0,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 2: sendfile without an offset reads from and advances the file
+    // position, stopping at end of file
+    println!("\nTest 2: sendfile at file position");
+    let _ = fs::lseek(src, 4000, SEEK_SET);
+    let sent = io::sendfile(pipe_w, src, None, 1000);
+    let received = read_exact(pipe_r, 96);
+    let position = fs::lseek(src, 0, SEEK_CUR).unwrap_or(0);
+    let at_eof = matches!(io::sendfile(pipe_w, src, None, 1000), Ok(0));
+    report(
+        "short copy at EOF, position advanced, then 0",
+        matches!(sent, Ok(96)) && received == data[4000..] && position == 4096 && at_eof,
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 3: splice file -> pipe -> file round trip
+    println!("\nTest 3: splice file to pipe to file");
+    let _ = fs::unlink(DST_PATH);
+    let dst = fs::open_with_mode(DST_PATH, O_RDWR | O_CREAT | O_TRUNC, 0o644).expect("open dst");
+    let mut in_off: i64 = 0;
+    let into_pipe = io::splice(src, Some(&mut in_off), pipe_w, None, 2048, 0);
+    let mut out_off: i64 = 0;
+    let out_of_pipe = io::splice(pipe_r, None, dst, Some(&mut out_off), 2048, 0);
+    let mut copied = [0u8; 2048];
+    let _ = fs::lseek(dst, 0, SEEK_SET);
+    let copied_len = io::read(dst, &mut copied).unwrap_or(0);
+    report(
+        "2048 bytes moved through a pipe, offsets updated",
+        matches!(into_pipe, Ok(2048))
+            && matches!(out_of_pipe, Ok(2048))
+            && in_off == 2048
+            && out_off == 2048
+            && copied_len == 2048
+            && copied[..] == data[..2048],
+        &mut passed,
+        &mut failed,
+    );
+    let _ = close(dst);
+    let _ = fs::unlink(DST_PATH);
+
+    // Test 4: tee duplicates into a second pipe and leaves the source intact
+    println!("\nTest 4: tee");
+    let (tee_r, tee_w) = io::pipe().expect("pipe");
+    let _ = io::write(pipe_w, b"hello, tee");
+    let teed = io::tee(pipe_r, tee_w, 64, 0);
+    let copy = read_exact(tee_r, 10);
+    let original = read_exact(pipe_r, 10);
+    report(
+        "both pipes hold the data",
+        matches!(teed, Ok(10)) && copy == b"hello, tee" && original == b"hello, tee",
+        &mut passed,
+        &mut failed,
+    );
+
+    // Test 5: error cases
+    println!("\nTest 5: Error cases");
+    let mut off: i64 = 0;
+    let espipe = is_errno(
+        &io::splice(pipe_r, Some(&mut off), tee_w, None, 16, 0),
+        Errno::ESPIPE,
+    );
+    let no_pipe = is_errno(&io::splice(src, None, src, None, 16, 0), Errno::EINVAL);
+    let same_pipe = is_errno(&io::tee(pipe_r, pipe_w, 16, 0), Errno::EINVAL);
+    let from_pipe = is_errno(&io::sendfile(tee_w, pipe_r, None, 16), Errno::EINVAL);
+    let empty = is_errno(
+        &io::splice(pipe_r, None, tee_w, None, 16, SPLICE_F_NONBLOCK),
+        Errno::EAGAIN,
+    );
+    report(
+        "ESPIPE, EINVAL (no pipe, same pipe, pipe source), EAGAIN",
+        espipe && no_pipe && same_pipe && from_pipe && empty,
+        &mut passed,
+        &mut failed,
+    );
+
+    let _ = close(tee_r);
+    let _ = close(tee_w);
+    let _ = close(pipe_r);
+    let _ = close(pipe_w);
+    let _ = close(src);
+    let _ = fs::unlink(SRC_PATH);
+
+    // Summary
+    println!("\n=== Summary: {} passed, {} failed ===", passed, failed);
+    if failed == 0 {
+        println!("SPLICE_TEST_PASSED");
+        process::exit(0);
+    } else {
+        println!("SPLICE_TEST_FAILED");
+        process::exit(1);
+    }
+}
@@ -634,6 +634,66 @@ fn shared_userspace_stages() -> Vec<BootStage> {
             failure_meaning: "sigaltstack() syscall failed - alternate signal stacks not working",
             check_hint: "Check syscall/signal.rs:sys_sigaltstack() and SA_ONSTACK support",
         },
+        BootStage {
+            name: "SA_SIGINFO siginfo and ucontext verified",
+            marker: "SIGINFO_TEST_PASSED",
+            failure_meaning: "SA_SIGINFO handlers got a wrong siginfo_t, or ucontext edits were not applied on sigreturn",
+            check_hint: "Check siginfo_test.rs, FrameLayout in signal/delivery.rs, and SigInfo/UContext in signal/types.rs",
+        },
+        BootStage {
+            name: "Queued real-time signals verified",
+            marker: "RT_SIGNAL_TEST_PASSED",
+            failure_meaning: "real-time signals collapsed or lost their values, sigtimedwait() misbehaved, or pthread_kill() missed its thread",
+            check_hint: "Check rt_signal_test.rs, SignalState::queue_signal() in signal/types.rs, and sys_rt_sigtimedwait()/sys_tgkill() in syscall/signal.rs",
+        },
+        BootStage {
+            name: "Core dumps verified",
+            marker: "COREDUMP_TEST_PASSED",
+            failure_meaning: "a core-dumping signal wrote no ELF core file, a malformed one, or one despite RLIMIT_CORE being zero",
+            check_hint: "Check coredump_test.rs, capture()/CoreDump::write() in signal/coredump.rs, and sys_prlimit64() in syscall/handlers.rs",
+        },
+        BootStage {
+            name: "ptrace verified",
+            marker: "PTRACE_TEST_PASSED",
+            failure_meaning: "a tracee did not stop for its tracer, waitpid missed a stop, or peek/poke, registers, single-step or syscall stops misbehaved",
+            check_hint: "Check ptrace_test.rs, process/ptrace.rs, syscall/ptrace.rs, and the debug exception handlers for single-step",
+        },
+        BootStage {
+            name: "resource usage and waitid verified",
+            marker: "RUSAGE_TEST_PASSED",
+            failure_meaning: "getrusage, wait4 or times reported missing CPU time, faults or switches, or waitid selected, reported or reaped the wrong child",
+            check_hint: "Check rusage_test.rs, task/accounting.rs, process/rusage.rs, syscall/rusage.rs and syscall/waitid.rs",
+        },
+        BootStage {
+            name: "scheduling priorities verified",
+            marker: "PRIORITY_TEST_PASSED",
+            failure_meaning: "nice levels or SCHED_FIFO/SCHED_RR were rejected, not inherited, or a higher-priority thread did not get its share of the CPU",
+            check_hint: "Check priority_test.rs, task/priority.rs, syscall/sched.rs and the queue ordering in task/scheduler.rs",
+        },
+        BootStage {
+            name: "CPU affinity verified",
+            marker: "AFFINITY_TEST_PASSED",
+            failure_meaning: "sched_setaffinity/sched_getaffinity were rejected or not inherited, or a pinned thread ran on another CPU",
+            check_hint: "Check affinity_test.rs, syscall/sched.rs and the cpu_allowed checks in task/scheduler.rs enqueue and work-stealing",
+        },
+        BootStage {
+            name: "/proc/<pid> hierarchy verified",
+            marker: "PROC_PID_TEST_PASSED",
+            failure_meaning: "/proc/self or a /proc/<pid> entry (maps, fd, cmdline, environ, stat, statm, exe, cwd, task) was missing or did not describe the process",
+            check_hint: "Check proc_pid_test.rs, fs/procfs/pid.rs, lookup_pid_path in fs/procfs/mod.rs and the procfs link handling in sys_open/sys_readlink",
+        },
+        BootStage {
+            name: "/dev/dsp mixer verified",
+            marker: "DSP_MIXER_TEST_PASSED",
+            failure_meaning: "/dev/dsp streams did not negotiate formats independently, or writes, EAGAIN, poll readiness or SNDCTL_DSP_SYNC misbehaved",
+            check_hint: "Check dsp_mixer_test.rs, audio/stream.rs, audio/ioctl.rs and the kaudiod mixer loop in audio/mod.rs",
+        },
+        BootStage {
+            name: "/dev/dsp capture verified",
+            marker: "DSP_CAPTURE_TEST_PASSED",
+            failure_meaning: "/dev/dsp reads did not return captured audio in the stream's format, or GETISPACE, POLLIN, RESET or nonblocking reads misbehaved",
+            check_hint: "Check dsp_capture_test.rs, AudioStream::read/push_captured in audio/stream.rs, the kaudiocapd loop in audio/mod.rs and read_pcm in drivers/virtio/sound*.rs",
+        },
 
         // UDP Socket tests
         BootStage {
@@ -1319,6 +1379,30 @@ fn shared_userspace_stages() -> Vec<BootStage> {
             failure_meaning: "Block allocation regression test failed",
             check_hint: "Check fs_block_alloc_test.rs and fs/ext2/block_group.rs",
         },
+        BootStage {
+            name: "Truncate/fallocate/fsync test passed",
+            marker: "FS_TRUNCATE_TEST_PASSED",
+            failure_meaning: "ftruncate, truncate, fallocate, or fsync misbehaved",
+            check_hint: "Check fs_truncate_test.rs, Ext2Fs::truncate_file, and syscall/fs.rs",
+        },
+        BootStage {
+            name: "statfs/fstatfs test passed",
+            marker: "FS_STATFS_TEST_PASSED",
+            failure_meaning: "statfs or fstatfs reported wrong capacity or filesystem type",
+            check_hint: "Check fs_statfs_test.rs, Ext2Fs::usage, and sys_statfs in syscall/fs.rs",
+        },
+        BootStage {
+            name: "flock/fcntl locking test passed",
+            marker: "FLOCK_TEST_PASSED",
+            failure_meaning: "flock or fcntl record locks did not conflict, wake, or release correctly",
+            check_hint: "Check flock_test.rs, fs/lock.rs, and sys_flock/sys_fcntl_lock in syscall/fs.rs",
+        },
+        BootStage {
+            name: "sendfile/splice/tee test passed",
+            marker: "SPLICE_TEST_PASSED",
+            failure_meaning: "sendfile, splice, or tee moved the wrong data or returned the wrong error",
+            check_hint: "Check splice_test.rs, syscall/splice.rs, and PipeBuffer::peek/consume in ipc/pipe.rs",
+        },
 
         // Coreutil tests (BusyBox applets)
         BootStage {
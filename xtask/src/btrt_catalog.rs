@@ -145,6 +145,20 @@ pub const UTEST_PIPE_FORK: u16 = 373;
 pub const UTEST_PIPE_CONCURRENT: u16 = 374;
 pub const UTEST_JOB_CONTROL: u16 = 375;
 pub const UTEST_SIGKILL_TEARDOWN: u16 = 376;
+pub const UTEST_FS_TRUNCATE: u16 = 377;
+pub const UTEST_FS_STATFS: u16 = 378;
+pub const UTEST_FLOCK: u16 = 379;
+pub const UTEST_SPLICE: u16 = 380;
+pub const UTEST_SIGINFO: u16 = 381;
+pub const UTEST_RT_SIGNAL: u16 = 382;
+pub const UTEST_COREDUMP: u16 = 383;
+pub const UTEST_PTRACE: u16 = 384;
+pub const UTEST_RUSAGE: u16 = 385;
+pub const UTEST_PRIORITY: u16 = 386;
+pub const UTEST_AFFINITY: u16 = 387;
+pub const UTEST_PROC_PID: u16 = 388;
+pub const UTEST_DSP_MIXER: u16 = 389;
+pub const UTEST_DSP_CAPTURE: u16 = 390;
 
 /// Complete catalog (mirrors kernel-side).
 pub static CATALOG: &[BootTestDef] = &[
@@ -699,6 +713,76 @@ pub static CATALOG: &[BootTestDef] = &[
         name: "utest_sigkill_teardown",
         category: BootTestCategory::UserspaceResult,
     },
+    BootTestDef {
+        id: UTEST_FS_TRUNCATE,
+        name: "utest_fs_truncate",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_FS_STATFS,
+        name: "utest_fs_statfs",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_FLOCK,
+        name: "utest_flock",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_SPLICE,
+        name: "utest_splice",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_SIGINFO,
+        name: "utest_siginfo",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_RT_SIGNAL,
+        name: "utest_rt_signal",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_COREDUMP,
+        name: "utest_coredump",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_PTRACE,
+        name: "utest_ptrace",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_RUSAGE,
+        name: "utest_rusage",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_PRIORITY,
+        name: "utest_priority",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_AFFINITY,
+        name: "utest_affinity",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_PROC_PID,
+        name: "utest_proc_pid",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_DSP_MIXER,
+        name: "utest_dsp_mixer",
+        category: BootTestCategory::UserspaceResult,
+    },
+    BootTestDef {
+        id: UTEST_DSP_CAPTURE,
+        name: "utest_dsp_capture",
+        category: BootTestCategory::UserspaceResult,
+    },
 ];
 
 /// Look up a test name by ID.